@@ -2,7 +2,50 @@
 //!
 //! Validates NFR-001: <50ms menu appearance, <10ms action execution
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+use juhradiald::accessibility::AccessibilitySettings;
+use juhradiald::battery::BatteryState;
+use juhradiald::content_hash::ContentHash;
+use juhradiald::hidpp::ConnectionType;
+use juhradiald::menu_payload::{DeviceStatus, PayloadEncoder, PayloadOptions, SlotHints};
+use juhradiald::theme::Theme;
+
+fn benchmark_menu_payload(c: &mut Criterion) {
+    let theme = Theme::catppuccin_mocha();
+    let theme_hash = ContentHash::of(&theme);
+    let accessibility = AccessibilitySettings::default();
+    let options = PayloadOptions {
+        success_flash: Some(false),
+        ..Default::default()
+    };
+    let battery = BatteryState {
+        percentage: 64,
+        available: true,
+        ..Default::default()
+    };
+
+    // Every ShowMenu after the first: static prefix reused, status appended
+    c.bench_function("menu_payload_encode_cached", |b| {
+        let mut encoder = PayloadEncoder::new();
+        b.iter(|| {
+            let status = DeviceStatus::build(&battery, Some(ConnectionType::Bolt), &theme);
+            let json = encoder.encode(&theme, theme_hash, &accessibility, &options, None, &SlotHints::default(), status.as_ref());
+            black_box(json.map(str::len))
+        })
+    });
+
+    // First open, or the first after a theme/accessibility/config change
+    c.bench_function("menu_payload_encode_cold", |b| {
+        b.iter(|| {
+            let mut encoder = PayloadEncoder::new();
+            let status = DeviceStatus::build(&battery, Some(ConnectionType::Bolt), &theme);
+            let json = encoder.encode(&theme, theme_hash, &accessibility, &options, None, &SlotHints::default(), status.as_ref());
+            black_box(json.map(str::len))
+        })
+    });
+}
 
 fn benchmark_event_processing(c: &mut Criterion) {
     c.bench_function("process_gesture_event", |b| {
@@ -35,6 +78,7 @@ fn benchmark_action_execution(c: &mut Criterion) {
 
 criterion_group!(
     benches,
+    benchmark_menu_payload,
     benchmark_event_processing,
     benchmark_profile_lookup,
     benchmark_action_execution
@@ -0,0 +1,392 @@
+//! Action schema for radial menu slices and bindings
+//!
+//! The JSON shape stored in profiles.json (`{"type": "shortcut", "value":
+//! "ctrl+c"}`). Executing actions is the daemon's job; this module only
+//! describes them.
+
+use serde::{Deserialize, Serialize};
+
+use crate::locale::LocalizedText;
+
+/// Action types supported by radial menu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+#[non_exhaustive]
+pub enum ActionType {
+    /// Keyboard shortcut (e.g., "Ctrl+C")
+    #[serde(rename = "shortcut")]
+    Shortcut(String),
+
+    /// Shell command (e.g., "dolphin ~")
+    #[serde(rename = "command")]
+    Command(String),
+
+    /// D-Bus method call
+    #[serde(rename = "dbus")]
+    DBus(DBusCall),
+
+    /// KWin script action
+    #[serde(rename = "kwin")]
+    KWin(String),
+
+    /// Paste the clipboard without formatting
+    #[serde(rename = "paste_plain")]
+    PastePlain,
+
+    /// Switch to a profile by name, or `next` / `previous` through the
+    /// profiles sorted by name; applied by the daemon, not spawned
+    #[serde(rename = "profile_switch")]
+    ProfileSwitch(String),
+
+    /// Held-slice wheel control: while the gesture button is held on this
+    /// slice, each vertical wheel detent runs `up` or `down` (a shortcut or
+    /// command, see [`ActionType::is_wheel_axis_step`]); the release runs
+    /// nothing
+    #[serde(rename = "wheel_axis")]
+    WheelAxis { up: Box<Action>, down: Box<Action> },
+
+    /// Media player control over MPRIS (`"play_pause"`, `"next"`,
+    /// `"previous"`); run by the daemon, no external binary
+    #[serde(rename = "media")]
+    Media(MediaControl),
+
+    /// Default output volume (`{"up": 5}`, `{"down": 5}` in percent, or
+    /// `"mute"` to toggle); run by the daemon, no external binary
+    #[serde(rename = "volume")]
+    Volume(VolumeControl),
+
+    /// No action (empty slice)
+    #[serde(rename = "none")]
+    None,
+}
+
+impl ActionType {
+    /// Type name as written in profiles.json
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionType::Shortcut(_) => "shortcut",
+            ActionType::Command(_) => "command",
+            ActionType::DBus(_) => "dbus",
+            ActionType::KWin(_) => "kwin",
+            ActionType::PastePlain => "paste_plain",
+            ActionType::ProfileSwitch(_) => "profile_switch",
+            ActionType::WheelAxis { .. } => "wheel_axis",
+            ActionType::Media(_) => "media",
+            ActionType::Volume(_) => "volume",
+            ActionType::None => "none",
+        }
+    }
+
+    /// Whether this may be bound to a `wheel_axis` direction
+    ///
+    /// Only shortcuts, commands and volume steps: they are cheap to repeat
+    /// per detent and the daemon runs them itself.
+    pub fn is_wheel_axis_step(&self) -> bool {
+        matches!(self, ActionType::Shortcut(_) | ActionType::Command(_) | ActionType::Volume(_))
+    }
+}
+
+/// Player command for a `media` action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaControl {
+    PlayPause,
+    Next,
+    Previous,
+}
+
+impl MediaControl {
+    /// MPRIS `org.mpris.MediaPlayer2.Player` method
+    pub fn mpris_method(self) -> &'static str {
+        match self {
+            MediaControl::PlayPause => "PlayPause",
+            MediaControl::Next => "Next",
+            MediaControl::Previous => "Previous",
+        }
+    }
+}
+
+/// Volume change for a `volume` action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeControl {
+    /// Raise by this many percent, up to 100%
+    Up(u8),
+    /// Lower by this many percent
+    Down(u8),
+    /// Toggle mute
+    Mute,
+}
+
+/// D-Bus method call specification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DBusCall {
+    /// D-Bus service name
+    pub service: String,
+    /// Object path
+    pub path: String,
+    /// Interface name
+    pub interface: String,
+    /// Method name
+    pub method: String,
+    /// Method arguments (as JSON)
+    #[serde(default)]
+    pub args: Vec<serde_json::Value>,
+}
+
+/// A complete action with icon and label
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Action {
+    /// Action type and parameters
+    #[serde(flatten)]
+    pub action_type: ActionType,
+
+    /// Display label, one string or one per language tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<LocalizedText>,
+
+    /// Icon (emoji, path, or system icon name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+
+    /// Longer help text the menu shows after hovering the slice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Run only after a second press and release on the slice
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_confirmation: bool,
+
+    /// After it runs, refuse to run the slice again for this many
+    /// milliseconds (0 = no cooldown)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cooldown_ms: u64,
+}
+
+fn is_zero(ms: &u64) -> bool {
+    *ms == 0
+}
+
+/// Default actions for the 8 slices (Story 2.6)
+/// N=0, NE=1, E=2, SE=3, S=4, SW=5, W=6, NW=7
+pub fn get_default_actions() -> [Action; 8] {
+    [
+        // N (0): Copy
+        Action {
+            action_type: ActionType::Shortcut("ctrl+c".to_string()),
+            label: Some("Copy".into()),
+            icon: Some("📋".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+        // NE (1): Paste
+        Action {
+            action_type: ActionType::Shortcut("ctrl+v".to_string()),
+            label: Some("Paste".into()),
+            icon: Some("📄".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+        // E (2): Undo
+        Action {
+            action_type: ActionType::Shortcut("ctrl+z".to_string()),
+            label: Some("Undo".into()),
+            icon: Some("↩️".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+        // SE (3): Redo
+        Action {
+            action_type: ActionType::Shortcut("ctrl+shift+z".to_string()),
+            label: Some("Redo".into()),
+            icon: Some("↪️".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+        // S (4): Select All
+        Action {
+            action_type: ActionType::Shortcut("ctrl+a".to_string()),
+            label: Some("Select All".into()),
+            icon: Some("🔲".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+        // SW (5): Cut
+        Action {
+            action_type: ActionType::Shortcut("ctrl+x".to_string()),
+            label: Some("Cut".into()),
+            icon: Some("✂️".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+        // W (6): Save
+        Action {
+            action_type: ActionType::Shortcut("ctrl+s".to_string()),
+            label: Some("Save".into()),
+            icon: Some("💾".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+        // NW (7): Close Tab
+        Action {
+            action_type: ActionType::Shortcut("ctrl+w".to_string()),
+            label: Some("Close".into()),
+            icon: Some("❌".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_serialization() {
+        let action = Action {
+            action_type: ActionType::Shortcut("Ctrl+C".to_string()),
+            label: Some("Copy".into()),
+            icon: Some("📋".to_string()),
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        };
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(json.contains("shortcut"));
+        assert!(json.contains("Ctrl+C"));
+    }
+
+    #[test]
+    fn test_default_actions_have_clean_labels_and_icons() {
+        for action in get_default_actions() {
+            let label = action.label.unwrap().current().to_string();
+            let icon = action.icon.unwrap();
+            assert_eq!(crate::label::text_issue(&label), None, "{label}");
+            assert_eq!(crate::label::icon_issue(&icon), None, "{icon}");
+            assert!(crate::label::display_width(&label) <= crate::label::LABEL_BUDGET_CELLS, "{label}");
+        }
+    }
+
+    #[test]
+    fn test_action_deserialization() {
+        let json = r#"{"type":"shortcut","value":"ctrl+c","label":"Copy"}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+
+        match action.action_type {
+            ActionType::Shortcut(keys) => assert_eq!(keys, "ctrl+c"),
+            _ => panic!("Expected Shortcut action"),
+        }
+        assert_eq!(action.label, Some("Copy".into()));
+
+        let json = r#"{"type":"shortcut","value":"ctrl+c","label":{"en":"Copy","de":"Kopieren"}}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        let label = action.label.as_ref().unwrap();
+        assert_eq!(label.resolve(&crate::Locale::parse("de_DE.UTF-8").unwrap()), "Kopieren");
+        assert_eq!(serde_json::to_value(&action).unwrap()["label"], serde_json::json!({"en": "Copy", "de": "Kopieren"}));
+    }
+
+    #[test]
+    fn test_command_action() {
+        let action = Action {
+            action_type: ActionType::Command("konsole".to_string()),
+            label: Some("Terminal".into()),
+            icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        };
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(json.contains("command"));
+        assert!(json.contains("konsole"));
+    }
+
+    #[test]
+    fn test_none_action() {
+        let action = Action {
+            action_type: ActionType::None,
+            label: None,
+            icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        };
+
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(json.contains("none"));
+    }
+
+    #[test]
+    fn test_paste_plain_action_round_trip() {
+        let action: Action = serde_json::from_str(r#"{"type": "paste_plain"}"#).unwrap();
+        assert!(matches!(action.action_type, ActionType::PastePlain));
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(json.contains("paste_plain"));
+    }
+
+    #[test]
+    fn test_wheel_axis_round_trip() {
+        let json = r#"{"type": "wheel_axis", "value": {
+            "up": {"type": "shortcut", "value": "XF86AudioRaiseVolume"},
+            "down": {"type": "command", "value": "pactl set-sink-volume @DEFAULT_SINK@ -5%"}
+        }, "label": "Volume"}"#;
+        let action: Action = serde_json::from_str(json).unwrap();
+        let ActionType::WheelAxis { ref up, ref down } = action.action_type else {
+            panic!("Expected WheelAxis");
+        };
+        assert!(up.action_type.is_wheel_axis_step());
+        assert_eq!(down.action_type.as_str(), "command");
+        assert_eq!(action.action_type.as_str(), "wheel_axis");
+        assert!(!action.action_type.is_wheel_axis_step());
+
+        let again: Action = serde_json::from_str(&serde_json::to_string(&action).unwrap()).unwrap();
+        assert!(matches!(again.action_type, ActionType::WheelAxis { .. }));
+    }
+
+    #[test]
+    fn test_media_and_volume_forms() {
+        let action: Action = serde_json::from_str(r#"{"type": "media", "value": "play_pause"}"#).unwrap();
+        assert!(matches!(action.action_type, ActionType::Media(MediaControl::PlayPause)));
+        assert_eq!(action.action_type.as_str(), "media");
+
+        let up: Action = serde_json::from_str(r#"{"type": "volume", "value": {"up": 5}}"#).unwrap();
+        assert!(matches!(up.action_type, ActionType::Volume(VolumeControl::Up(5))));
+        assert!(up.action_type.is_wheel_axis_step());
+        let mute: Action = serde_json::from_str(r#"{"type": "volume", "value": "mute"}"#).unwrap();
+        assert_eq!(serde_json::to_string(&mute).unwrap(), r#"{"type":"volume","value":"mute"}"#);
+        assert_eq!(
+            serde_json::to_string(&ActionType::Volume(VolumeControl::Down(10))).unwrap(),
+            r#"{"type":"volume","value":{"down":10}}"#
+        );
+        assert!(serde_json::from_str::<Action>(r#"{"type": "volume", "value": {"up": 300}}"#).is_err());
+    }
+
+    #[test]
+    fn test_default_actions() {
+        let actions = get_default_actions();
+
+        assert_eq!(actions.len(), 8);
+
+        // Verify N=Copy
+        match &actions[0].action_type {
+            ActionType::Shortcut(keys) => assert_eq!(keys, "ctrl+c"),
+            _ => panic!("Expected Shortcut"),
+        }
+
+        // Verify S=Select All
+        match &actions[4].action_type {
+            ActionType::Shortcut(keys) => assert_eq!(keys, "ctrl+a"),
+            _ => panic!("Expected Shortcut"),
+        }
+    }
+}
@@ -3,6 +3,7 @@
 //! Provides built-in themes that are compiled into the binary.
 //! These themes are always available, regardless of filesystem state.
 
+use crate::locale::{best_match, Locale};
 use crate::theme::Theme;
 
 /// Catppuccin Mocha theme JSON (default)
@@ -24,30 +25,61 @@ pub struct BundledThemeInfo {
     pub name: &'static str,
     /// Display name (human readable)
     pub display_name: &'static str,
-    /// Short description
+    /// Short description, in English
     pub description: &'static str,
+    /// Translations of `description` by language tag
+    pub descriptions: &'static [(&'static str, &'static str)],
     /// Whether this is the default theme
     pub is_default: bool,
 }
 
+impl BundledThemeInfo {
+    /// The description in `locale`, falling back to English
+    pub fn description_in(&self, locale: &Locale) -> &'static str {
+        let entries = self.descriptions.iter().copied().chain([("en", self.description)]);
+        best_match(entries, locale).unwrap_or(self.description)
+    }
+}
+
 /// List of all bundled themes with metadata
 pub const BUNDLED_THEME_INFO: &[BundledThemeInfo] = &[
     BundledThemeInfo {
         name: "catppuccin-mocha",
         display_name: "Catppuccin Mocha",
         description: "Warm pastel dark theme with lavender accent",
+        descriptions: &[
+            ("de", "Warmes dunkles Pastell-Theme mit Lavendel-Akzent"),
+            ("es", "Tema oscuro pastel y cálido con acento lavanda"),
+            ("fr", "Thème sombre pastel et chaleureux avec accent lavande"),
+            ("pt-BR", "Tema escuro pastel e quente com destaque lavanda"),
+            ("sv", "Varmt mörkt pasteltema med lavendelaccent"),
+        ],
         is_default: true,
     },
     BundledThemeInfo {
         name: "vaporwave",
         display_name: "Vaporwave",
         description: "80s neon aesthetic with magenta and cyan",
+        descriptions: &[
+            ("de", "80er-Neon-Ästhetik in Magenta und Cyan"),
+            ("es", "Estética neón de los 80 en magenta y cian"),
+            ("fr", "Esthétique néon des années 80 en magenta et cyan"),
+            ("pt-BR", "Estética neon dos anos 80 em magenta e ciano"),
+            ("sv", "80-talsneon i magenta och cyan"),
+        ],
         is_default: false,
     },
     BundledThemeInfo {
         name: "matrix-rain",
         display_name: "Matrix Rain",
         description: "Monochrome green hacker aesthetic",
+        descriptions: &[
+            ("de", "Einfarbig grüne Hacker-Ästhetik"),
+            ("es", "Estética hacker monocroma en verde"),
+            ("fr", "Esthétique hacker monochrome verte"),
+            ("pt-BR", "Estética hacker monocromática verde"),
+            ("sv", "Enfärgad grön hackerestetik"),
+        ],
         is_default: false,
     },
 ];
@@ -155,6 +187,17 @@ mod tests {
         assert!((theme.animation.glow_intensity - 1.5).abs() < 0.01);
     }
 
+    #[test]
+    fn test_vaporwave_texture_ships_in_assets() {
+        let mut theme = get_bundled_theme("vaporwave").unwrap();
+        assert_eq!(theme.glassmorphism.background_texture.as_deref(), Some("grid.svg"));
+        // The repository's assets/ is installed as <data dir>/assets
+        let assets = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../assets/themes/vaporwave");
+        assert!(theme.resolve_background_texture(&assets).is_none());
+        let texture = theme.glassmorphism.texture.unwrap();
+        assert_eq!(texture.format, crate::theme::TextureFormat::Svg);
+    }
+
     #[test]
     fn test_matrix_rain_parses() {
         let theme = get_bundled_theme("matrix-rain");
@@ -231,6 +274,19 @@ mod tests {
         assert!(vaporwave_info.description.contains("80s"));
     }
 
+    #[test]
+    fn test_bundled_descriptions_follow_the_locale() {
+        let info = get_bundled_theme_info("vaporwave").unwrap();
+        let locale = |value| Locale::parse(value).unwrap();
+        assert_eq!(info.description_in(&locale("de_AT.UTF-8")), "80er-Neon-Ästhetik in Magenta und Cyan");
+        assert_eq!(info.description_in(&locale("pt_PT")), "Estética neon dos anos 80 em magenta e ciano");
+        assert_eq!(info.description_in(&locale("ja_JP")), info.description);
+        assert_eq!(info.description_in(&Locale::default()), info.description);
+        for info in BUNDLED_THEME_INFO {
+            assert!(info.descriptions.iter().all(|(tag, _)| Locale::parse(tag).is_some()), "{}", info.name);
+        }
+    }
+
     #[test]
     fn test_catppuccin_mocha_ux_spec_values() {
         // Verify values match UX spec Section 4.3
@@ -0,0 +1,89 @@
+//! Button action and thumb-wheel mode identifiers
+//!
+//! These are the snake_case ids the Settings UI writes to config.json and
+//! profiles.json.
+
+use serde::{Deserialize, Serialize};
+
+/// Actions that can be assigned to mouse buttons.
+/// These match the action IDs written by the Python Settings UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonAction {
+    RadialMenu,
+    VirtualDesktops,
+    MiddleClick,
+    Back,
+    Forward,
+    Copy,
+    Paste,
+    Undo,
+    Redo,
+    Screenshot,
+    Smartshift,
+    ScrollLeftRight,
+    VolumeUp,
+    VolumeDown,
+    PlayPause,
+    Mute,
+    ZoomIn,
+    ZoomOut,
+    ShowDesktop,
+    SwitchDesktopLeft,
+    SwitchDesktopRight,
+    TaskSwitcher,
+    CloseWindow,
+    LockScreen,
+    Calculator,
+    None,
+    Custom,
+}
+
+impl std::fmt::Display for ButtonAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ButtonAction::RadialMenu => write!(f, "radial_menu"),
+            ButtonAction::VirtualDesktops => write!(f, "virtual_desktops"),
+            ButtonAction::MiddleClick => write!(f, "middle_click"),
+            ButtonAction::Back => write!(f, "back"),
+            ButtonAction::Forward => write!(f, "forward"),
+            ButtonAction::Copy => write!(f, "copy"),
+            ButtonAction::Paste => write!(f, "paste"),
+            ButtonAction::Undo => write!(f, "undo"),
+            ButtonAction::Redo => write!(f, "redo"),
+            ButtonAction::Screenshot => write!(f, "screenshot"),
+            ButtonAction::Smartshift => write!(f, "smartshift"),
+            ButtonAction::ScrollLeftRight => write!(f, "scroll_left_right"),
+            ButtonAction::VolumeUp => write!(f, "volume_up"),
+            ButtonAction::VolumeDown => write!(f, "volume_down"),
+            ButtonAction::PlayPause => write!(f, "play_pause"),
+            ButtonAction::Mute => write!(f, "mute"),
+            ButtonAction::ZoomIn => write!(f, "zoom_in"),
+            ButtonAction::ZoomOut => write!(f, "zoom_out"),
+            ButtonAction::ShowDesktop => write!(f, "show_desktop"),
+            ButtonAction::SwitchDesktopLeft => write!(f, "switch_desktop_left"),
+            ButtonAction::SwitchDesktopRight => write!(f, "switch_desktop_right"),
+            ButtonAction::TaskSwitcher => write!(f, "task_switcher"),
+            ButtonAction::CloseWindow => write!(f, "close_window"),
+            ButtonAction::LockScreen => write!(f, "lock_screen"),
+            ButtonAction::Calculator => write!(f, "calculator"),
+            ButtonAction::None => write!(f, "none"),
+            ButtonAction::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+/// What a thumb-wheel rotation should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbwheelMode {
+    /// Thumb wheel keeps its native behaviour (not diverted).
+    #[default]
+    Off,
+    /// Rotation adjusts system volume.
+    Volume,
+    /// Rotation scrolls horizontally.
+    Scroll,
+    /// Rotation zooms in/out (Ctrl +/-).
+    Zoom,
+}
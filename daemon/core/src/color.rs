@@ -0,0 +1,206 @@
+//! Hex color parsing and HSL shade helpers
+//!
+//! Used by the theme engine to resolve derived colors
+//! (`{"derive": "accent", "lighten": 0.15}`) into concrete `#rrggbb` values.
+//! Amounts are fractions in `0.0..=1.0`: `lighten`/`darken` move the HSL
+//! lightness by that much, `mix` blends that far towards the other color.
+//! [`contrast_ratio`] is the WCAG 2 ratio, for picking readable shades.
+
+/// An sRGB color with an alpha channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// A color in HSL; hue in degrees `0.0..360.0`, the rest `0.0..=1.0`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hsl {
+    pub h: f32,
+    pub s: f32,
+    pub l: f32,
+}
+
+impl Rgba {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Parse `#rgb`, `#rrggbb` or `#rrggbbaa`
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix('#')?;
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let byte = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+        match digits.len() {
+            3 => {
+                let nibble = |i: usize| u8::from_str_radix(&digits[i..i + 1], 16).ok().map(|n| n * 17);
+                Some(Self::rgb(nibble(0)?, nibble(1)?, nibble(2)?))
+            }
+            6 => Some(Self::rgb(byte(0)?, byte(2)?, byte(4)?)),
+            8 => Some(Self { r: byte(0)?, g: byte(2)?, b: byte(4)?, a: byte(6)? }),
+            _ => None,
+        }
+    }
+
+    /// `#rrggbb`, dropping alpha
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// `#rrggbbaa`
+    pub fn to_hex_alpha(self) -> String {
+        format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+
+    pub fn to_hsl(self) -> Hsl {
+        let r = f32::from(self.r) / 255.0;
+        let g = f32::from(self.g) / 255.0;
+        let b = f32::from(self.b) / 255.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let delta = max - min;
+        if delta == 0.0 {
+            return Hsl { h: 0.0, s: 0.0, l };
+        }
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+        Hsl { h, s, l }
+    }
+
+    /// From HSL, keeping `alpha`
+    pub fn from_hsl(hsl: Hsl, alpha: u8) -> Self {
+        let l = hsl.l.clamp(0.0, 1.0);
+        let s = hsl.s.clamp(0.0, 1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let h = hsl.h.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+        let channel = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Self { r: channel(r), g: channel(g), b: channel(b), a: alpha }
+    }
+}
+
+/// Raise the HSL lightness by `amount`
+pub fn lighten(color: Rgba, amount: f32) -> Rgba {
+    let mut hsl = color.to_hsl();
+    hsl.l = (hsl.l + amount).clamp(0.0, 1.0);
+    Rgba::from_hsl(hsl, color.a)
+}
+
+/// Lower the HSL lightness by `amount`
+pub fn darken(color: Rgba, amount: f32) -> Rgba {
+    lighten(color, -amount)
+}
+
+/// Blend `amount` of the way from `color` to `other` (0.0 keeps `color`)
+pub fn mix(color: Rgba, other: Rgba, amount: f32) -> Rgba {
+    let amount = amount.clamp(0.0, 1.0);
+    let blend = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * amount).round() as u8;
+    Rgba {
+        r: blend(color.r, other.r),
+        g: blend(color.g, other.g),
+        b: blend(color.b, other.b),
+        a: blend(color.a, other.a),
+    }
+}
+
+/// `color` with its alpha set to `alpha` (0.0 transparent, 1.0 opaque)
+pub fn with_alpha(color: Rgba, alpha: f32) -> Rgba {
+    Rgba { a: (alpha.clamp(0.0, 1.0) * 255.0).round() as u8, ..color }
+}
+
+/// Relative luminance (WCAG 2), `0.0` black to `1.0` white; alpha ignored
+pub fn relative_luminance(color: Rgba) -> f32 {
+    let [r, g, b] = linear_rgb(color);
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// WCAG contrast ratio between two colors, `1.0` to `21.0`
+pub fn contrast_ratio(a: Rgba, b: Rgba) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// The color's channels as linear-light fractions
+fn linear_rgb(color: Rgba) -> [f32; 3] {
+    let linear = |c: u8| {
+        let c = f32::from(c) / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    [linear(color.r), linear(color.g), linear(color.b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Rgba {
+        Rgba::from_hex(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(hex("#fff"), Rgba::rgb(255, 255, 255));
+        assert_eq!(hex("#B4BEFE").to_hex(), "#b4befe");
+        assert_eq!(hex("#11223344").to_hex_alpha(), "#11223344");
+        for bad in ["b4befe", "#b4bef", "#gggggg", "#", "#ééé"] {
+            assert_eq!(Rgba::from_hex(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        for s in ["#000000", "#ffffff", "#1e1e2e", "#b4befe", "#ff6b9d", "#00f5d4", "#808080", "#ff0000"] {
+            let color = hex(s);
+            assert_eq!(Rgba::from_hsl(color.to_hsl(), 255), color, "{s}");
+        }
+        let red = hex("#ff0000").to_hsl();
+        assert_eq!((red.h, red.s, red.l), (0.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn test_shades() {
+        assert_eq!(lighten(hex("#333333"), 0.2).to_hex(), "#666666");
+        assert_eq!(darken(hex("#999999"), 0.2).to_hex(), "#666666");
+        assert_eq!(lighten(hex("#ff0000"), 1.0).to_hex(), "#ffffff");
+        assert_eq!(darken(hex("#ff0000"), 1.0).to_hex(), "#000000");
+        assert_eq!(mix(hex("#000000"), hex("#ffffff"), 0.5).to_hex(), "#808080");
+        assert_eq!(mix(hex("#123456"), hex("#ffffff"), 0.0), hex("#123456"));
+        assert_eq!(with_alpha(hex("#123456"), 0.5).to_hex_alpha(), "#12345680");
+        // Alpha survives a lightness change
+        assert_eq!(lighten(hex("#12345680"), 0.2).a, 0x80);
+    }
+
+    #[test]
+    fn test_contrast() {
+        assert_eq!(relative_luminance(hex("#000000")), 0.0);
+        assert!((relative_luminance(hex("#ffffff")) - 1.0).abs() < 1e-6);
+        assert!((contrast_ratio(hex("#000000"), hex("#ffffff")) - 21.0).abs() < 1e-3);
+        assert_eq!(contrast_ratio(hex("#1e1e2e"), hex("#b4befe")), contrast_ratio(hex("#b4befe"), hex("#1e1e2e")));
+        // #767676 is the classic 4.5:1 grey on white
+        assert!((contrast_ratio(hex("#767676"), hex("#ffffff")) - 4.54).abs() < 0.01);
+    }
+}
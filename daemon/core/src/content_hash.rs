@@ -0,0 +1,81 @@
+//! Content hashes for change detection
+//!
+//! Themes and profiles are hashed over their canonical JSON: the loaded value
+//! is serialized back through `serde_json::Value`, whose objects keep their
+//! keys sorted, so a file that was saved again unchanged, reformatted or
+//! reordered hashes the same. The hash is FNV-1a 64, stable across builds
+//! and machines, and is shown as 16 hex digits.
+
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+/// FNV-1a 64-bit hash, stable across builds (unlike `DefaultHasher`)
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Hash of a value's canonical JSON; serializes as its hex form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContentHash(pub u64);
+
+impl ContentHash {
+    /// Hash `value` as canonical JSON
+    ///
+    /// A value that cannot be represented as JSON hashes as `null`.
+    pub fn of<T: Serialize + ?Sized>(value: &T) -> Self {
+        let canonical = serde_json::to_value(value)
+            .and_then(|v| serde_json::to_vec(&v))
+            .unwrap_or_else(|_| b"null".to_vec());
+        Self(content_hash(&canonical))
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl Serialize for ContentHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme::Theme;
+
+    #[test]
+    fn test_hash_ignores_formatting_and_key_order() {
+        let compact: Theme = serde_json::from_str(
+            r##"{"name":"t","colors":{"base":"#000000","surface":"#111111","text":"#ffffff","accent":"#ff00ff","border":"#222222"},"glassmorphism":{},"animation":{}}"##,
+        )
+        .unwrap();
+        let reordered: Theme = serde_json::from_str(
+            r##"{
+                "animation": {},
+                "glassmorphism": {},
+                "colors": {"border": "#222222", "accent": "#ff00ff", "text": "#ffffff", "surface": "#111111", "base": "#000000"},
+                "name": "t"
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(ContentHash::of(&compact), ContentHash::of(&reordered));
+
+        let mut edited = compact.clone();
+        edited.colors.accent = "#00ff00".to_string();
+        assert_ne!(ContentHash::of(&compact), ContentHash::of(&edited));
+    }
+
+    #[test]
+    fn test_hash_prints_as_hex() {
+        assert_eq!(ContentHash(0xaf63_dc4c_8601_ec8c).to_string(), "af63dc4c8601ec8c");
+        assert_eq!(serde_json::to_string(&ContentHash(1)).unwrap(), r#""0000000000000001""#);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
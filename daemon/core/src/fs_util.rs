@@ -0,0 +1,256 @@
+//! Private files
+//!
+//! Everything the daemon writes belongs to the user alone: profiles and
+//! config, selection state, stats, HID++ traces and the scripts it hands to
+//! KWin. None of it may follow a symlink planted by someone else, and a file
+//! that another program loads (a KWin script) must be checked to still be the
+//! user's before it is used.
+//!
+//! - [`write_private`] replaces a file atomically through a uniquely named
+//!   `0600` temp file in the same directory. A symlink at the target (a
+//!   config kept in a dotfiles repo) is kept and the file it points to is
+//!   replaced, only when that file is the user's.
+//! - [`open_private_append`] opens a log for appending with `O_NOFOLLOW`.
+//! - [`private_dir`] creates a `0700` directory and refuses one that is a
+//!   symlink or owned by another user.
+//! - [`check_owned`] verifies a file before it is handed to another process.
+
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+/// Directory name under `$XDG_RUNTIME_DIR`
+pub const RUNTIME_DIR_NAME: &str = "juhradial";
+
+/// `$XDG_RUNTIME_DIR/juhradial`, for files that only live as long as the session
+pub fn runtime_dir() -> Option<PathBuf> {
+    crate::paths::paths().runtime_dir.clone()
+}
+
+/// Effective user id of this process
+pub fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
+/// Create `dir` (and its parents) and make sure only the user can use it
+///
+/// An existing directory must be owned by the user; group and other
+/// permissions are removed from it. A symlink is refused.
+pub fn private_dir(dir: &Path) -> io::Result<()> {
+    DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    let meta = fs::symlink_metadata(dir)?;
+    if !meta.is_dir() {
+        return Err(refused(dir, "is not a directory"));
+    }
+    if meta.uid() != current_uid() {
+        return Err(refused(dir, "is owned by another user"));
+    }
+    if meta.mode() & 0o077 != 0 {
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Atomically replace `path` with `contents`, readable by the user only
+///
+/// Creates the parent directory if needed. The contents go to a new temp
+/// file next to `path` that is then renamed over it, so readers see the old
+/// or the new file, never half of one. When `path` is a symlink, the temp
+/// file goes next to the file it resolves to and replaces that, leaving the
+/// link in place; a link to a missing file or to someone else's is refused.
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let target = write_target(path)?;
+    let path = target.as_path();
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!(".{name}."))
+        .suffix(".tmp")
+        .permissions(fs::Permissions::from_mode(0o600))
+        .tempfile_in(dir)?;
+    file.write_all(contents.as_ref())?;
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// The file [`write_private`] replaces for `path`: `path` itself, or the
+/// regular file of the user's a symlink there resolves to
+fn write_target(path: &Path) -> io::Result<PathBuf> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => {
+            let owned = fs::canonicalize(path).and_then(|real| {
+                let meta = fs::metadata(&real)?;
+                Ok((meta.is_file() && meta.uid() == current_uid()).then_some(real))
+            });
+            match owned {
+                Ok(Some(real)) => Ok(real),
+                Ok(None) => {
+                    tracing::warn!(path = %path.display(), "Not writing through a symlink to a file that is not the user's");
+                    Err(refused(path, "links to a file that is not the user's"))
+                }
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "Not writing through an unresolvable symlink");
+                    Err(e)
+                }
+            }
+        }
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Create a uniquely named private file in `dir` holding `contents`
+///
+/// The name is `<prefix><random><suffix>`. The file is removed when the
+/// returned path is dropped.
+pub fn create_private_temp(dir: &Path, prefix: &str, suffix: &str, contents: &[u8]) -> io::Result<tempfile::TempPath> {
+    let mut file = tempfile::Builder::new()
+        .prefix(prefix)
+        .suffix(suffix)
+        .permissions(fs::Permissions::from_mode(0o600))
+        .tempfile_in(dir)?;
+    file.write_all(contents)?;
+    file.as_file().sync_all()?;
+    Ok(file.into_temp_path())
+}
+
+/// Open `path` for appending, creating it `0600`; a symlink is refused
+pub fn open_private_append(path: &Path) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .custom_flags(libc::O_NOFOLLOW | libc::O_CLOEXEC)
+        .open(path)?;
+    if file.metadata()?.uid() != current_uid() {
+        return Err(refused(path, "is owned by another user"));
+    }
+    Ok(file)
+}
+
+/// Check that `path` is a regular file of the user's that nobody else can
+/// change, before another program is asked to load it
+pub fn check_owned(path: &Path) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.file_type().is_file() {
+        return Err(refused(path, "is not a regular file"));
+    }
+    if meta.uid() != current_uid() {
+        return Err(refused(path, "is owned by another user"));
+    }
+    if meta.mode() & 0o022 != 0 {
+        return Err(refused(path, "is writable by other users"));
+    }
+    Ok(())
+}
+
+fn refused(path: &Path, why: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, format!("{} {}", path.display(), why))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn mode(path: &Path) -> u32 {
+        fs::symlink_metadata(path).unwrap().mode() & 0o777
+    }
+
+    #[test]
+    fn test_write_private_keeps_a_symlinked_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dotfiles = tmp.path().join("dotfiles");
+        fs::create_dir(&dotfiles).unwrap();
+        let real = dotfiles.join("config.json");
+        fs::write(&real, "{}").unwrap();
+        let path = tmp.path().join("config.json");
+        symlink(&real, &path).unwrap();
+
+        write_private(&path, "{\"theme\": \"nord\"}").unwrap();
+        assert!(fs::symlink_metadata(&path).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "{\"theme\": \"nord\"}");
+        assert_eq!(mode(&real), 0o600);
+        // No temp file left behind in either directory
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 2);
+        assert_eq!(fs::read_dir(&dotfiles).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_private_refuses_a_dangling_symlink() {
+        let tmp = tempfile::tempdir().unwrap();
+        let missing = tmp.path().join("missing");
+        let path = tmp.path().join("state.json");
+        symlink(&missing, &path).unwrap();
+
+        assert!(write_private(&path, "{}").is_err());
+        assert!(fs::symlink_metadata(&path).unwrap().file_type().is_symlink());
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_write_private_creates_the_parent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("a").join("b.json");
+        write_private(&path, "1").unwrap();
+        write_private(&path, "2").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2");
+    }
+
+    #[test]
+    fn test_append_refuses_a_symlink() {
+        let tmp = tempfile::tempdir().unwrap();
+        let victim = tmp.path().join("victim");
+        fs::write(&victim, "").unwrap();
+        let path = tmp.path().join("stats.jsonl");
+        symlink(&victim, &path).unwrap();
+        assert!(open_private_append(&path).is_err());
+        assert_eq!(fs::read_to_string(&victim).unwrap(), "");
+
+        fs::remove_file(&path).unwrap();
+        writeln!(open_private_append(&path).unwrap(), "one").unwrap();
+        writeln!(open_private_append(&path).unwrap(), "two").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+        assert_eq!(mode(&path), 0o600);
+    }
+
+    #[test]
+    fn test_private_dir_tightens_and_refuses_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("run").join("juhradial");
+        private_dir(&dir).unwrap();
+        assert_eq!(mode(&dir), 0o700);
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        private_dir(&dir).unwrap();
+        assert_eq!(mode(&dir), 0o700);
+
+        let link = tmp.path().join("link");
+        symlink(&dir, &link).unwrap();
+        assert_eq!(private_dir(&link).unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn test_check_owned() {
+        let tmp = tempfile::tempdir().unwrap();
+        let script = create_private_temp(tmp.path(), "cursor-", ".js", b"x").unwrap();
+        let name = script.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("cursor-") && name.ends_with(".js"));
+        check_owned(&script).unwrap();
+
+        let link = tmp.path().join("link.js");
+        symlink(&script, &link).unwrap();
+        assert!(check_owned(&link).is_err());
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o666)).unwrap();
+        assert!(check_owned(&script).is_err());
+
+        let path = script.to_path_buf();
+        drop(script);
+        assert!(!path.exists());
+    }
+}
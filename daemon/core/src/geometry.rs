@@ -0,0 +1,535 @@
+//! Radial ring geometry
+//!
+//! Pointer offset → slice index, mirroring the overlay's hit testing
+//! (`overlay/juhradial-overlay.py`). Offsets are in logical pixels relative to
+//! the menu centre, with +y pointing down as on screen.
+//!
+//! The plain functions assume the default eight-slice ring; the `_in`
+//! variants take the ring's slice count (a profile's `slice_count`).
+//!
+//! [`MenuSize`] is the ring size a user asks for (`menu.size`, a profile's
+//! `menu_size`): pixels or a percentage of the shorter edge of the
+//! [`Monitor`] the menu opens on. Hit testing stays in the base geometry
+//! below; the overlay scales offsets by `diameter / MENU_DIAMETER`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Number of slices in the default ring
+pub const SLICE_COUNT: u8 = 8;
+
+/// Slice counts a profile may use; 12 (the clock layout) is experimental
+pub const SUPPORTED_SLICE_COUNTS: [u8; 4] = [4, 6, 8, 12];
+
+/// Largest supported slice count
+pub const MAX_SLICE_COUNT: u8 = 12;
+
+/// Angular width of one slice in degrees
+pub const SLICE_ANGLE: f64 = 360.0 / SLICE_COUNT as f64;
+
+/// Outer ring radius in pixels (overlay `MENU_RADIUS`)
+pub const MENU_RADIUS: f64 = 150.0;
+
+/// Radius of the centre dead zone in pixels (overlay `CENTER_ZONE_RADIUS`)
+pub const CENTER_ZONE_RADIUS: f64 = 45.0;
+
+/// Diameter of the base ring in logical pixels (`2 * MENU_RADIUS`)
+pub const MENU_DIAMETER: u32 = 300;
+
+/// Smallest diameter a [`MenuSize`] resolves to
+pub const MIN_MENU_DIAMETER: u32 = 150;
+
+/// Largest diameter a [`MenuSize`] resolves to
+pub const MAX_MENU_DIAMETER: u32 = 1600;
+
+/// Largest share of the monitor's shorter edge the ring may take, in percent
+pub const MAX_MENU_PERCENT: f64 = 60.0;
+
+/// A monitor in the compositor's logical coordinate space (the space cursor
+/// positions are reported in)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Monitor {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+    /// Device pixels per logical pixel: the compositor's output scale
+    /// (KWin `devicePixelRatio`, Hyprland `scale`), or the DPI over 96 on X11
+    pub scale: f64,
+    /// Whether the compositor already applies `scale` to logical pixels
+    /// (Wayland); on X11 nothing does, so pixel sizes are scaled here
+    pub compositor_scaled: bool,
+}
+
+impl Monitor {
+    /// Whether the logical point `(x, y)` lies on this monitor
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+
+    /// Length of the shorter edge in logical pixels
+    pub fn shorter_edge(&self) -> u32 {
+        u32::try_from(self.width.min(self.height)).unwrap_or(0)
+    }
+
+    /// Factor a size given in pixels at scale 1 is multiplied by to become
+    /// logical pixels on this monitor
+    pub fn pixel_density(&self) -> f64 {
+        if self.compositor_scaled || !self.scale.is_finite() || self.scale <= 0.0 {
+            1.0
+        } else {
+            self.scale
+        }
+    }
+}
+
+/// The monitor containing `(x, y)`, or the nearest one when the point falls
+/// in a gap between monitors
+pub fn monitor_at(monitors: &[Monitor], x: i32, y: i32) -> Option<&Monitor> {
+    monitors.iter().find(|m| m.contains(x, y)).or_else(|| {
+        monitors.iter().min_by_key(|m| {
+            let dx = (m.x - x).max(x - (m.x + m.width - 1)).max(0);
+            let dy = (m.y - y).max(y - (m.y + m.height - 1)).max(0);
+            i64::from(dx) * i64::from(dx) + i64::from(dy) * i64::from(dy)
+        })
+    })
+}
+
+/// Requested ring size (`menu.size`, a profile's `menu_size`)
+///
+/// Written in JSON as `"auto"`, a number of pixels (`360`, `"360px"`) or a
+/// percentage of the monitor's shorter edge (`"22%"`).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum MenuSize {
+    /// The overlay sizes the ring from the monitor height (1440p reference)
+    #[default]
+    Auto,
+    /// Diameter in pixels at scale 1
+    Pixels(u32),
+    /// Diameter as a percentage of the monitor's shorter edge
+    Percent(f64),
+}
+
+impl MenuSize {
+    /// Ring diameter in logical pixels on `monitor`, clamped to
+    /// [`MIN_MENU_DIAMETER`]..=[`MAX_MENU_DIAMETER`] and to
+    /// [`MAX_MENU_PERCENT`] of the shorter edge
+    ///
+    /// `None` leaves the size to the overlay: always for `Auto`, and for a
+    /// percentage when the monitor is unknown.
+    pub fn diameter(&self, monitor: Option<&Monitor>) -> Option<u32> {
+        let wanted = match *self {
+            MenuSize::Auto => return None,
+            MenuSize::Pixels(px) => f64::from(px) * monitor.map_or(1.0, Monitor::pixel_density),
+            MenuSize::Percent(percent) => f64::from(monitor?.shorter_edge()) * percent / 100.0,
+        };
+        let ceiling = monitor
+            .map(|m| (f64::from(m.shorter_edge()) * MAX_MENU_PERCENT / 100.0) as u32)
+            .map_or(MAX_MENU_DIAMETER, |fit| fit.clamp(MIN_MENU_DIAMETER, MAX_MENU_DIAMETER));
+        Some((wanted.round() as u32).clamp(MIN_MENU_DIAMETER, ceiling))
+    }
+}
+
+/// Why a `menu.size` value was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MenuSizeError {
+    #[error("'{0}' is not a size (use \"auto\", pixels like 360 or \"360px\", or a percentage like \"22%\")")]
+    Unrecognized(String),
+    #[error("percentage {0} is outside 1-{max}%", max = MAX_MENU_PERCENT)]
+    PercentOutOfRange(String),
+    #[error("{0}px is outside {min}-{max}px", min = MIN_MENU_DIAMETER, max = MAX_MENU_DIAMETER)]
+    PixelsOutOfRange(u32),
+}
+
+impl FromStr for MenuSize {
+    type Err = MenuSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if text.eq_ignore_ascii_case("auto") {
+            return Ok(MenuSize::Auto);
+        }
+        if let Some(percent) = text.strip_suffix('%') {
+            let value: f64 = percent.trim().parse().map_err(|_| MenuSizeError::Unrecognized(s.to_string()))?;
+            if !(1.0..=MAX_MENU_PERCENT).contains(&value) {
+                return Err(MenuSizeError::PercentOutOfRange(percent.trim().to_string()));
+            }
+            return Ok(MenuSize::Percent(value));
+        }
+        let pixels = text.strip_suffix("px").unwrap_or(text).trim();
+        let value: u32 = pixels.parse().map_err(|_| MenuSizeError::Unrecognized(s.to_string()))?;
+        MenuSize::pixels(value)
+    }
+}
+
+impl MenuSize {
+    /// A pixel size, checked against the accepted range
+    pub fn pixels(value: u32) -> Result<Self, MenuSizeError> {
+        if (MIN_MENU_DIAMETER..=MAX_MENU_DIAMETER).contains(&value) {
+            Ok(MenuSize::Pixels(value))
+        } else {
+            Err(MenuSizeError::PixelsOutOfRange(value))
+        }
+    }
+}
+
+impl fmt::Display for MenuSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuSize::Auto => write!(f, "auto"),
+            MenuSize::Pixels(px) => write!(f, "{px}px"),
+            MenuSize::Percent(percent) => write!(f, "{percent}%"),
+        }
+    }
+}
+
+impl Serialize for MenuSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            MenuSize::Pixels(px) => serializer.serialize_u32(*px),
+            other => serializer.collect_str(other),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MenuSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Pixels(u32),
+            Text(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Pixels(px) => MenuSize::pixels(px),
+            Repr::Text(text) => text.parse(),
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Clockwise angle of an offset from straight up, in degrees `[0, 360)`
+pub fn angle_of(dx: f64, dy: f64) -> f64 {
+    let angle = dx.atan2(-dy).to_degrees();
+    if angle < 0.0 {
+        angle + 360.0
+    } else {
+        angle
+    }
+}
+
+/// Whether a profile may use `count` slices
+pub fn is_supported_slice_count(count: u8) -> bool {
+    SUPPORTED_SLICE_COUNTS.contains(&count)
+}
+
+/// Angular width of one slice of a `count`-slice ring in degrees
+pub fn slice_angle(count: u8) -> f64 {
+    360.0 / f64::from(count.max(1))
+}
+
+/// Slice index (0 = N, clockwise) for an angle in degrees
+pub fn slice_for_angle(angle: f64) -> u8 {
+    slice_for_angle_in(angle, SLICE_COUNT)
+}
+
+/// [`slice_for_angle`] on a `count`-slice ring
+pub fn slice_for_angle_in(angle: f64, count: u8) -> u8 {
+    let count = count.max(1);
+    let width = slice_angle(count);
+    (((angle + width / 2.0) / width) as u32 % u32::from(count)) as u8
+}
+
+/// Slice under an offset, or `None` in the centre zone or outside the ring
+pub fn slice_at(dx: f64, dy: f64) -> Option<u8> {
+    slice_at_with_radii(dx, dy, CENTER_ZONE_RADIUS, MENU_RADIUS)
+}
+
+/// [`slice_at`] with a theme-specific centre radius and outer radius
+pub fn slice_at_with_radii(dx: f64, dy: f64, center_radius: f64, outer_radius: f64) -> Option<u8> {
+    let distance = dx.hypot(dy);
+    if distance < center_radius || distance > outer_radius {
+        return None;
+    }
+    Some(slice_for_angle(angle_of(dx, dy)))
+}
+
+/// Centre angle of a slice in degrees, clockwise from straight up
+pub fn slice_center_angle(slice: u8) -> f64 {
+    slice_center_angle_in(slice, SLICE_COUNT)
+}
+
+/// [`slice_center_angle`] on a `count`-slice ring
+pub fn slice_center_angle_in(slice: u8, count: u8) -> f64 {
+    f64::from(slice % count.max(1)) * slice_angle(count)
+}
+
+/// Mirror a slice across the vertical axis (NE ↔ NW, E ↔ W; N and S stay)
+pub fn mirror_slice(slice: u8) -> u8 {
+    mirror_slice_in(slice, SLICE_COUNT)
+}
+
+/// [`mirror_slice`] on a `count`-slice ring; with an even count N and S stay
+pub fn mirror_slice_in(slice: u8, count: u8) -> u8 {
+    let count = count.max(1);
+    (count - slice % count) % count
+}
+
+/// The slot `delta` steps clockwise from `slot` (negative = counter-clockwise),
+/// wrapping around a `count`-slice ring; keyboard and wheel navigation
+pub fn step_slot(slot: u8, delta: i32, count: u8) -> u8 {
+    let count = i32::from(count.max(1));
+    (i32::from(slot) + delta).rem_euclid(count) as u8
+}
+
+/// Profile slot shown at a ring position (`input.left_handed` mirrors)
+///
+/// Mirroring is its own inverse, so this also gives the position a slot is
+/// drawn at.
+pub fn slot_for_position(position: u8, left_handed: bool) -> u8 {
+    slot_for_position_in(position, left_handed, SLICE_COUNT)
+}
+
+/// [`slot_for_position`] on a `count`-slice ring
+pub fn slot_for_position_in(position: u8, left_handed: bool, count: u8) -> u8 {
+    if left_handed {
+        mirror_slice_in(position, count)
+    } else {
+        position % count.max(1)
+    }
+}
+
+/// [`slice_at_with_radii`] mapped to the profile slot for the handedness
+pub fn slot_at_with_radii(dx: f64, dy: f64, center_radius: f64, outer_radius: f64, left_handed: bool) -> Option<u8> {
+    slot_at_with_radii_in(dx, dy, center_radius, outer_radius, left_handed, SLICE_COUNT)
+}
+
+/// [`slot_at_with_radii`] on a `count`-slice ring
+pub fn slot_at_with_radii_in(
+    dx: f64,
+    dy: f64,
+    center_radius: f64,
+    outer_radius: f64,
+    left_handed: bool,
+    count: u8,
+) -> Option<u8> {
+    let distance = dx.hypot(dy);
+    if distance < center_radius || distance > outer_radius {
+        return None;
+    }
+    let position = slice_for_angle_in(angle_of(dx, dy), count);
+    Some(slot_for_position_in(position, left_handed, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(width: i32, height: i32, scale: f64, compositor_scaled: bool) -> Monitor {
+        Monitor { x: 0, y: 0, width, height, scale, compositor_scaled }
+    }
+
+    #[test]
+    fn test_percent_size_follows_the_shorter_edge() {
+        let size = MenuSize::Percent(22.0);
+        // 1366x768 laptop, 1080p, 1440p, 4K at 100% and a portrait 1440x2560
+        let cases = [
+            (monitor(1366, 768, 1.0, true), 169),
+            (monitor(1920, 1080, 1.0, true), 238),
+            (monitor(2560, 1440, 1.0, true), 317),
+            (monitor(3840, 2160, 1.0, true), 475),
+            (monitor(1440, 2560, 1.0, true), 317),
+        ];
+        for (m, expected) in cases {
+            assert_eq!(size.diameter(Some(&m)), Some(expected), "{}x{}", m.width, m.height);
+        }
+        // 4K at 200% is 1920x1080 logical: the same ring as 1080p, drawn
+        // with twice the device pixels
+        assert_eq!(size.diameter(Some(&monitor(1920, 1080, 2.0, true))), Some(238));
+        // Unknown monitor: left to the overlay
+        assert_eq!(size.diameter(None), None);
+        assert_eq!(MenuSize::Auto.diameter(Some(&monitor(1920, 1080, 1.0, true))), None);
+    }
+
+    #[test]
+    fn test_size_clamps_to_the_monitor_and_the_range() {
+        // 60% of a 768px edge caps a large request
+        assert_eq!(MenuSize::Pixels(600).diameter(Some(&monitor(1366, 768, 1.0, true))), Some(460));
+        assert_eq!(MenuSize::Percent(60.0).diameter(Some(&monitor(3840, 2160, 1.0, true))), Some(1296));
+        // Tiny percentages still give a usable ring
+        assert_eq!(MenuSize::Percent(1.0).diameter(Some(&monitor(1920, 1080, 1.0, true))), Some(MIN_MENU_DIAMETER));
+        // A monitor too small for even the minimum keeps the minimum
+        assert_eq!(MenuSize::Pixels(300).diameter(Some(&monitor(320, 200, 1.0, true))), Some(MIN_MENU_DIAMETER));
+        assert_eq!(MenuSize::Pixels(300).diameter(None), Some(300));
+    }
+
+    #[test]
+    fn test_pixel_size_scales_only_where_the_compositor_does_not() {
+        // Wayland: logical pixels are already scaled by the compositor
+        assert_eq!(MenuSize::Pixels(300).diameter(Some(&monitor(1920, 1080, 2.0, true))), Some(300));
+        // X11 at 144 DPI: nothing scales, so the ring grows by 1.5
+        assert_eq!(MenuSize::Pixels(300).diameter(Some(&monitor(2560, 1440, 1.5, false))), Some(450));
+        assert_eq!(monitor(1920, 1080, 0.0, false).pixel_density(), 1.0);
+    }
+
+    #[test]
+    fn test_menu_size_parses_and_round_trips() {
+        assert_eq!("auto".parse(), Ok(MenuSize::Auto));
+        assert_eq!("22%".parse(), Ok(MenuSize::Percent(22.0)));
+        assert_eq!(" 22.5 % ".parse(), Ok(MenuSize::Percent(22.5)));
+        assert_eq!("360px".parse(), Ok(MenuSize::Pixels(360)));
+        assert_eq!("360".parse(), Ok(MenuSize::Pixels(360)));
+        assert!(matches!("big".parse::<MenuSize>(), Err(MenuSizeError::Unrecognized(_))));
+        assert!(matches!("80%".parse::<MenuSize>(), Err(MenuSizeError::PercentOutOfRange(_))));
+        assert_eq!("20px".parse::<MenuSize>(), Err(MenuSizeError::PixelsOutOfRange(20)));
+
+        for (json, size) in [("\"auto\"", MenuSize::Auto), ("360", MenuSize::Pixels(360)), ("\"22%\"", MenuSize::Percent(22.0))] {
+            let parsed: MenuSize = serde_json::from_str(json).unwrap();
+            assert_eq!(parsed, size);
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        }
+        assert_eq!(serde_json::from_str::<MenuSize>("\"360px\"").unwrap(), MenuSize::Pixels(360));
+        let err = serde_json::from_str::<MenuSize>("5000").unwrap_err().to_string();
+        assert!(err.contains("5000px is outside"), "{err}");
+    }
+
+    #[test]
+    fn test_monitor_at_picks_the_containing_or_nearest_monitor() {
+        let monitors = [
+            Monitor { x: 0, y: 0, width: 1920, height: 1080, scale: 1.0, compositor_scaled: true },
+            Monitor { x: 1920, y: 0, width: 1280, height: 720, scale: 2.0, compositor_scaled: true },
+        ];
+        assert_eq!(monitor_at(&monitors, 100, 100), Some(&monitors[0]));
+        assert_eq!(monitor_at(&monitors, 1920, 10), Some(&monitors[1]));
+        // Below the shorter right-hand monitor: nearest is still that one
+        assert_eq!(monitor_at(&monitors, 2500, 900), Some(&monitors[1]));
+        assert_eq!(monitor_at(&[], 0, 0), None);
+    }
+
+    #[test]
+    fn test_cardinal_directions() {
+        assert_eq!(slice_at(0.0, -100.0), Some(0)); // N
+        assert_eq!(slice_at(100.0, 0.0), Some(2)); // E
+        assert_eq!(slice_at(0.0, 100.0), Some(4)); // S
+        assert_eq!(slice_at(-100.0, 0.0), Some(6)); // W
+        assert_eq!(slice_at(70.0, -70.0), Some(1)); // NE
+        assert_eq!(slice_at(-70.0, -70.0), Some(7)); // NW
+    }
+
+    #[test]
+    fn test_dead_zone_and_outside() {
+        assert_eq!(slice_at(0.0, 0.0), None);
+        assert_eq!(slice_at(10.0, -30.0), None);
+        assert_eq!(slice_at(0.0, -151.0), None);
+        assert_eq!(slice_at(0.0, -150.0), Some(0));
+        assert_eq!(slice_at_with_radii(0.0, -30.0, 20.0, 150.0), Some(0));
+    }
+
+    #[test]
+    fn test_slice_boundaries_wrap() {
+        assert_eq!(slice_for_angle(0.0), 0);
+        assert_eq!(slice_for_angle(22.4), 0);
+        assert_eq!(slice_for_angle(22.5), 1);
+        assert_eq!(slice_for_angle(337.4), 7);
+        assert_eq!(slice_for_angle(337.5), 0);
+        assert_eq!(slice_for_angle(359.9), 0);
+    }
+
+    #[test]
+    fn test_mirror_is_an_involution() {
+        for slice in 0..SLICE_COUNT {
+            assert_eq!(mirror_slice(mirror_slice(slice)), slice);
+            assert_eq!(slot_for_position(slot_for_position(slice, true), true), slice);
+            assert_eq!(slot_for_position(slice, false), slice);
+        }
+        assert_eq!(mirror_slice(0), 0); // N
+        assert_eq!(mirror_slice(4), 4); // S
+        assert_eq!(mirror_slice(1), 7); // NE -> NW
+        assert_eq!(mirror_slice(2), 6); // E -> W
+        assert_eq!(mirror_slice(3), 5); // SE -> SW
+    }
+
+    #[test]
+    fn test_left_handed_hit_testing() {
+        let (center, outer) = (CENTER_ZONE_RADIUS, MENU_RADIUS);
+        // The NE position selects the profile's NW slot
+        assert_eq!(slot_at_with_radii(70.0, -70.0, center, outer, true), Some(7));
+        assert_eq!(slot_at_with_radii(70.0, -70.0, center, outer, false), Some(1));
+        assert_eq!(slot_at_with_radii(-100.0, 0.0, center, outer, true), Some(2));
+        assert_eq!(slot_at_with_radii(0.0, 0.0, center, outer, true), None);
+
+        // Boundaries move with the mirror: N keeps its wedge, neighbours swap
+        assert_eq!(slot_for_position(slice_for_angle(22.4), true), 0);
+        assert_eq!(slot_for_position(slice_for_angle(22.5), true), 7);
+        assert_eq!(slot_for_position(slice_for_angle(337.4), true), 1);
+        assert_eq!(slot_for_position(slice_for_angle(337.5), true), 0);
+        assert_eq!(slot_for_position(slice_for_angle(157.5), true), 4);
+    }
+
+    #[test]
+    fn test_slice_center_round_trip() {
+        for slice in 0..SLICE_COUNT {
+            let rad = slice_center_angle(slice).to_radians();
+            let (dx, dy) = (100.0 * rad.sin(), -100.0 * rad.cos());
+            assert_eq!(slice_at(dx, dy), Some(slice));
+        }
+    }
+
+    #[test]
+    fn test_every_supported_count_round_trips_and_mirrors() {
+        let (center, outer) = (CENTER_ZONE_RADIUS, MENU_RADIUS);
+        for count in SUPPORTED_SLICE_COUNTS {
+            assert!(is_supported_slice_count(count));
+            let width = slice_angle(count);
+            for slot in 0..count {
+                let angle = slice_center_angle_in(slot, count);
+                let rad = angle.to_radians();
+                let (dx, dy) = (100.0 * rad.sin(), -100.0 * rad.cos());
+                assert_eq!(slot_at_with_radii_in(dx, dy, center, outer, false, count), Some(slot), "{count} slices");
+                // Arc edges: the last degree before the boundary still belongs to the slot
+                assert_eq!(slice_for_angle_in((angle + width / 2.0 - 0.01).rem_euclid(360.0), count), slot);
+                assert_eq!(slice_for_angle_in(angle + width / 2.0, count), (slot + 1) % count);
+
+                let mirrored = mirror_slice_in(slot, count);
+                assert_eq!(mirror_slice_in(mirrored, count), slot);
+                assert_eq!((slice_center_angle_in(slot, count) + slice_center_angle_in(mirrored, count)) % 360.0, 0.0);
+                let (mx, my) = (-dx, dy);
+                assert_eq!(slot_at_with_radii_in(mx, my, center, outer, true, count), Some(slot), "{count} slices mirrored");
+            }
+            // N and S stay put; the hub is no slot
+            assert_eq!(mirror_slice_in(0, count), 0);
+            assert_eq!(mirror_slice_in(count / 2, count), count / 2);
+            assert_eq!(slot_at_with_radii_in(0.0, 0.0, center, outer, false, count), None);
+        }
+        assert!(!is_supported_slice_count(10));
+        // The clock layout: 30° arcs, 3 o'clock is slot 3
+        assert_eq!(slice_angle(12), 30.0);
+        assert_eq!(slot_at_with_radii_in(100.0, 0.0, center, outer, false, 12), Some(3));
+        assert_eq!(slot_at_with_radii_in(100.0, 0.0, center, outer, true, 12), Some(9));
+        assert_eq!(slot_at_with_radii_in(100.0, 0.0, center, outer, false, 4), Some(1));
+        // E is a boundary on the six-slice ring; like every edge it goes clockwise
+        assert_eq!(slot_at_with_radii_in(100.0, 0.0, center, outer, false, 6), Some(2));
+    }
+
+    #[test]
+    fn test_step_slot_wraps_around_the_ring() {
+        for count in SUPPORTED_SLICE_COUNTS {
+            let last = count - 1;
+            assert_eq!(step_slot(last, 1, count), 0);
+            assert_eq!(step_slot(0, -1, count), last);
+            assert_eq!(step_slot(1, i32::from(count), count), 1);
+            assert_eq!(step_slot(0, -i32::from(count) - 2, count), count - 2);
+            // A full lap of single steps visits every slot once
+            let mut slot = 0;
+            let mut seen = Vec::new();
+            for _ in 0..count {
+                seen.push(slot);
+                slot = step_slot(slot, 1, count);
+            }
+            assert_eq!(seen, (0..count).collect::<Vec<_>>());
+            assert_eq!(slot, 0);
+        }
+    }
+}
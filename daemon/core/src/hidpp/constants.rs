@@ -33,6 +33,9 @@ pub mod features {
     pub const I_ROOT: u16 = 0x0000;
     /// IFeatureSet - Enumerate device features (READ-ONLY)
     pub const I_FEATURE_SET: u16 = 0x0001;
+    /// Device information: unit ID and firmware versions (READ-ONLY)
+    /// Functions: [0] getDeviceInfo, [1] getFwInfo(entity)
+    pub const DEVICE_INFORMATION: u16 = 0x0003;
     /// Device name and type (READ-ONLY)
     pub const DEVICE_NAME: u16 = 0x0005;
     /// Battery status (READ-ONLY) - older devices
@@ -165,6 +168,7 @@ pub mod allowed_features {
     pub const SAFELIST: &[u16] = &[
         features::I_ROOT,
         features::I_FEATURE_SET,
+        features::DEVICE_INFORMATION,
         features::DEVICE_NAME,
         features::BATTERY_STATUS,
         features::LED_CONTROL,
@@ -182,3 +186,34 @@ pub mod allowed_features {
         SAFELIST.contains(&feature_id)
     }
 }
+
+/// Audited read-only functions of features that also have write functions
+///
+/// # Audit (Easy-Switch host state)
+///
+/// - CHANGE_HOST 0x1814 `[0] getHostInfo` returns `numHosts, currentHost`
+///   and changes nothing. `[1] setCurrentHost` is a volatile switch used only
+///   by the explicit Easy-Switch D-Bus method, so it is not listed here.
+/// - HOSTS_INFO 0x1815 stays blocklisted as a feature: its setters (friendly
+///   name, descriptor, move/delete host) rewrite the pairing table. Only the
+///   getters `[0] getHostInfo`, `[1] getHostDescriptor` and
+///   `[3] getHostFriendlyName` are read, for host names.
+///
+/// The active slot comes from 0x1814 `[0]`, so detecting a switch to another
+/// host never needs more of 0x1815 than the name getters above.
+pub mod read_only_functions {
+    use super::features;
+
+    /// `(feature id, function ids)` pairs that only read device state
+    pub const READ_ONLY: &[(u16, &[u8])] = &[
+        (features::CHANGE_HOST, &[0x00]),
+        (features::HOSTS_INFO, &[0x00, 0x01, 0x03]),
+    ];
+
+    /// Whether `function` of `feature_id` is an audited getter
+    pub fn is_read_only(feature_id: u16, function: u8) -> bool {
+        READ_ONLY
+            .iter()
+            .any(|(id, functions)| *id == feature_id && functions.contains(&function))
+    }
+}
@@ -150,13 +150,20 @@ pub enum ConnectionType {
     Unifying,
 }
 
-impl fmt::Display for ConnectionType {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl ConnectionType {
+    /// Display name ("USB", "Bolt", "Bluetooth" or "Unifying")
+    pub fn as_str(&self) -> &'static str {
         match self {
-            ConnectionType::Usb => write!(f, "USB"),
-            ConnectionType::Bolt => write!(f, "Bolt"),
-            ConnectionType::Bluetooth => write!(f, "Bluetooth"),
-            ConnectionType::Unifying => write!(f, "Unifying"),
+            ConnectionType::Usb => "USB",
+            ConnectionType::Bolt => "Bolt",
+            ConnectionType::Bluetooth => "Bluetooth",
+            ConnectionType::Unifying => "Unifying",
         }
     }
 }
+
+impl fmt::Display for ConnectionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
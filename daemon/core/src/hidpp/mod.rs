@@ -0,0 +1,135 @@
+//! HID++ 2.0 wire-format types
+//!
+//! Message framing, report types and the feature id tables, with no device
+//! I/O. The daemon's `hidpp` module re-exports these alongside its hidraw
+//! device and haptic manager.
+
+pub mod constants;
+pub mod messages;
+
+pub use constants::{
+    allowed_features, blocklisted_features, features, product_ids, read_only_functions, report_type,
+    LOGITECH_VENDOR_ID,
+};
+pub use messages::{ConnectionType, HidppLongMessage, HidppShortMessage};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_message_construction() {
+        let msg = HidppShortMessage::new(0xFF, 0x00, 0x01, 0x05).with_params([0xAA, 0xBB, 0xCC]);
+
+        let bytes = msg.to_bytes();
+        assert_eq!(bytes[0], 0x10); // Short report type
+        assert_eq!(bytes[1], 0xFF); // Device index
+        assert_eq!(bytes[2], 0x00); // Feature index
+        assert_eq!(bytes[3], 0x15); // Function 1, SW ID 5
+        assert_eq!(bytes[4], 0xAA);
+        assert_eq!(bytes[5], 0xBB);
+        assert_eq!(bytes[6], 0xCC);
+    }
+
+    #[test]
+    fn test_short_message_parsing() {
+        let bytes = [0x10, 0xFF, 0x00, 0x15, 0xAA, 0xBB, 0xCC];
+        let msg = HidppShortMessage::from_bytes(&bytes).unwrap();
+
+        assert_eq!(msg.device_index, 0xFF);
+        assert_eq!(msg.feature_index, 0x00);
+        assert_eq!(msg.function_id(), 0x01);
+        assert_eq!(msg.sw_id(), 0x05);
+        assert_eq!(msg.params, [0xAA, 0xBB, 0xCC]);
+    }
+
+    #[test]
+    fn test_long_message_construction() {
+        let msg = HidppLongMessage::new(0x01, 0x05, 0x02, 0x0A).with_params(&[1, 2, 3, 4, 5]);
+
+        let bytes = msg.to_bytes();
+        assert_eq!(bytes[0], 0x11); // Long report type
+        assert_eq!(bytes[1], 0x01); // Device index
+        assert_eq!(bytes[2], 0x05); // Feature index
+        assert_eq!(bytes[3], 0x2A); // Function 2, SW ID 10
+        assert_eq!(bytes[4], 1);
+        assert_eq!(bytes[5], 2);
+        assert_eq!(bytes[6], 3);
+    }
+
+    #[test]
+    fn test_connection_type_display() {
+        assert_eq!(format!("{}", ConnectionType::Usb), "USB");
+        assert_eq!(format!("{}", ConnectionType::Bolt), "Bolt");
+        assert_eq!(format!("{}", ConnectionType::Bluetooth), "Bluetooth");
+        assert_eq!(format!("{}", ConnectionType::Unifying), "Unifying");
+    }
+
+    #[test]
+    fn test_blocklisted_features_detection() {
+        assert!(blocklisted_features::is_blocklisted(
+            blocklisted_features::REPORT_RATE
+        ));
+        assert!(blocklisted_features::is_blocklisted(
+            blocklisted_features::ONBOARD_PROFILES
+        ));
+        assert!(blocklisted_features::is_blocklisted(
+            blocklisted_features::MODE_STATUS
+        ));
+        assert!(blocklisted_features::is_blocklisted(
+            blocklisted_features::MOUSE_BUTTON_SPY
+        ));
+        assert!(blocklisted_features::is_blocklisted(
+            blocklisted_features::PERSISTENT_REMAPPABLE_ACTION
+        ));
+        assert!(blocklisted_features::is_blocklisted(
+            blocklisted_features::HOST_INFO
+        ));
+
+        // REPROG_CONTROLS_V4 (0x1B04) was moved to allowed
+        assert!(!blocklisted_features::is_blocklisted(
+            features::REPROG_CONTROLS_V4
+        ));
+    }
+
+    #[test]
+    fn test_read_only_functions_exclude_setters() {
+        assert!(read_only_functions::is_read_only(features::CHANGE_HOST, 0x00));
+        // setCurrentHost switches the device away
+        assert!(!read_only_functions::is_read_only(features::CHANGE_HOST, 0x01));
+        for function in [0x00, 0x01, 0x03] {
+            assert!(read_only_functions::is_read_only(features::HOSTS_INFO, function));
+        }
+        for function in [0x02, 0x04, 0x05, 0x06] {
+            assert!(!read_only_functions::is_read_only(features::HOSTS_INFO, function));
+        }
+        // Still blocklisted as a feature
+        assert!(blocklisted_features::is_blocklisted(features::HOSTS_INFO));
+    }
+
+    #[test]
+    fn test_allowed_features_not_blocklisted() {
+        assert!(!blocklisted_features::is_blocklisted(features::I_ROOT));
+        assert!(!blocklisted_features::is_blocklisted(
+            features::I_FEATURE_SET
+        ));
+        assert!(!blocklisted_features::is_blocklisted(features::DEVICE_NAME));
+        assert!(!blocklisted_features::is_blocklisted(
+            features::BATTERY_STATUS
+        ));
+        assert!(!blocklisted_features::is_blocklisted(features::LED_CONTROL));
+        assert!(!blocklisted_features::is_blocklisted(
+            features::FORCE_FEEDBACK
+        ));
+    }
+
+    #[test]
+    fn test_allowed_features_in_safelist() {
+        assert!(allowed_features::is_allowed(features::I_ROOT));
+        assert!(allowed_features::is_allowed(features::I_FEATURE_SET));
+        assert!(allowed_features::is_allowed(features::DEVICE_NAME));
+        assert!(allowed_features::is_allowed(features::BATTERY_STATUS));
+        assert!(allowed_features::is_allowed(features::LED_CONTROL));
+        assert!(allowed_features::is_allowed(features::FORCE_FEEDBACK));
+    }
+}
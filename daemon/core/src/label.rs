@@ -0,0 +1,174 @@
+//! Slice label width and text checks
+//!
+//! The overlay draws a label inside its slice arc, which fits about
+//! [`LABEL_BUDGET_CELLS`] terminal-style cells at the default ring size. A
+//! wider label gets a shortened form cut at a grapheme boundary with `…`, so
+//! the overlay never has to truncate an emoji or a combining sequence itself.
+//!
+//! Labels and icons pasted from another document sometimes arrive broken:
+//! UTF-8 that was decoded as Windows-1252 and encoded again (`ðŸ“‹` for 📋),
+//! replacement characters from a lossy conversion, or stray control
+//! characters. [`text_issue`] spots those so loading can warn, and
+//! [`repair_mojibake`] recovers the intended text where it can.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Default label budget in cells (`menu.label_max_cells`)
+pub const LABEL_BUDGET_CELLS: usize = 14;
+
+/// Smallest budget `menu.label_max_cells` accepts
+pub const MIN_LABEL_BUDGET_CELLS: usize = 4;
+
+/// Appended to a shortened label; one cell wide
+pub const ELLIPSIS: char = '…';
+
+/// Cells `text` takes when drawn (wide emoji and CJK count as two)
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// `label` cut to fit `budget` cells with a trailing `…`, or `None` when it
+/// already fits
+///
+/// The cut falls between grapheme clusters and drops whitespace before the
+/// ellipsis. A budget under two cells keeps just the ellipsis.
+pub fn ellipsize(label: &str, budget: usize) -> Option<String> {
+    if display_width(label) <= budget {
+        return None;
+    }
+    let room = budget.saturating_sub(1);
+    let mut short = String::new();
+    let mut width = 0;
+    for grapheme in label.graphemes(true) {
+        let w = grapheme.width();
+        if width + w > room {
+            break;
+        }
+        short.push_str(grapheme);
+        width += w;
+    }
+    let mut short = short.trim_end().to_string();
+    short.push(ELLIPSIS);
+    Some(short)
+}
+
+/// Why a label or icon is likely not what its author meant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextIssue {
+    /// Contains U+FFFD from a lossy conversion
+    Replacement,
+    /// Contains a control character
+    Control,
+    /// UTF-8 that was decoded as Windows-1252 and encoded again; holds the
+    /// repaired text
+    Mojibake(String),
+    /// An emoji icon made of more than one grapheme cluster
+    NotOneGrapheme(usize),
+}
+
+impl std::fmt::Display for TextIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextIssue::Replacement => write!(f, "contains U+FFFD replacement characters"),
+            TextIssue::Control => write!(f, "contains control characters"),
+            TextIssue::Mojibake(repaired) => write!(f, "looks double-encoded; probably meant '{}'", repaired),
+            TextIssue::NotOneGrapheme(n) => write!(f, "is {} characters, not a single emoji", n),
+        }
+    }
+}
+
+/// Broken text in a label or description
+pub fn text_issue(text: &str) -> Option<TextIssue> {
+    if text.contains('\u{FFFD}') {
+        return Some(TextIssue::Replacement);
+    }
+    if let Some(repaired) = repair_mojibake(text) {
+        return Some(TextIssue::Mojibake(repaired));
+    }
+    text.chars().any(char::is_control).then_some(TextIssue::Control)
+}
+
+/// Broken text in an icon, which must be one emoji unless it is a path or
+/// an icon name (plain ASCII)
+pub fn icon_issue(icon: &str) -> Option<TextIssue> {
+    if let Some(issue) = text_issue(icon) {
+        return Some(issue);
+    }
+    if icon.is_ascii() || icon.contains('/') {
+        return None;
+    }
+    let count = icon.graphemes(true).count();
+    (count != 1).then_some(TextIssue::NotOneGrapheme(count))
+}
+
+/// The text `text` was before its UTF-8 got decoded as Windows-1252 and
+/// encoded again, or `None` when it does not look double-encoded
+///
+/// Only text whose every character maps back to a single byte, and whose
+/// bytes then form valid UTF-8 with at least one multi-byte sequence,
+/// counts. Plain accented words (`Café`) fail the UTF-8 step and are left
+/// alone.
+pub fn repair_mojibake(text: &str) -> Option<String> {
+    if text.is_ascii() {
+        return None;
+    }
+    let bytes: Vec<u8> = text.chars().map(windows_1252_byte).collect::<Option<_>>()?;
+    let repaired = String::from_utf8(bytes).ok()?;
+    (!repaired.is_ascii()).then_some(repaired)
+}
+
+/// The Windows-1252 byte that decodes to `c`
+fn windows_1252_byte(c: char) -> Option<u8> {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž', '\u{8F}',
+        '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}', 'ž', 'Ÿ',
+    ];
+    match u32::from(c) {
+        0..=0x7F | 0xA0..=0xFF => u8::try_from(u32::from(c)).ok(),
+        _ => HIGH.iter().position(|&h| h == c).map(|i| 0x80 + i as u8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_width_counts_wide_graphemes_twice() {
+        assert_eq!(display_width("Copy"), 4);
+        assert_eq!(display_width("📋 Copy"), 7);
+        assert_eq!(display_width("コピー"), 6);
+    }
+
+    #[test]
+    fn test_ellipsize_cuts_between_graphemes() {
+        assert_eq!(ellipsize("Copy", 14), None);
+        assert_eq!(ellipsize("Toggle Always On Top Plus Extra", 14).as_deref(), Some("Toggle Always…"));
+        // Never splits the family emoji or the flag
+        assert_eq!(ellipsize("👨‍👩‍👧 Family chat", 4).as_deref(), Some("👨‍👩‍👧…"));
+        assert_eq!(ellipsize("ab🇳🇴cd", 4).as_deref(), Some("ab…"));
+        let short = ellipsize("Sehr langer Menüeintrag", 10).unwrap();
+        assert!(display_width(&short) <= 10, "{short}");
+        assert_eq!(ellipsize("Anything", 0).as_deref(), Some("…"));
+    }
+
+    #[test]
+    fn test_broken_text_is_spotted() {
+        assert_eq!(text_issue("Café"), None);
+        assert_eq!(text_issue("📋 Copy"), None);
+        assert_eq!(text_issue("ðŸ“‹"), Some(TextIssue::Mojibake("📋".to_string())));
+        assert_eq!(text_issue("CafÃ©"), Some(TextIssue::Mojibake("Café".to_string())));
+        assert_eq!(text_issue("Sa\u{FFFD}e"), Some(TextIssue::Replacement));
+        assert_eq!(text_issue("Save\n"), Some(TextIssue::Control));
+    }
+
+    #[test]
+    fn test_icon_is_one_emoji_a_name_or_a_path() {
+        for good in ["📋", "↩️", "✂️", "👨‍👩‍👧", "edit-copy", "/usr/share/icons/a.svg"] {
+            assert_eq!(icon_issue(good), None, "{good}");
+        }
+        assert_eq!(icon_issue("📋📄"), Some(TextIssue::NotOneGrapheme(2)));
+        assert!(matches!(icon_issue("â†©ï¸\u{8F}"), Some(TextIssue::Mojibake(ref s)) if s == "↩️"));
+    }
+}
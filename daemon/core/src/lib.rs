@@ -0,0 +1,56 @@
+//! JuhRadial MX core library
+//!
+//! The device- and desktop-independent parts of the daemon, usable by third
+//! party tools without D-Bus, evdev or HID access:
+//!
+//! - [`theme`] / [`bundled_themes`]: theme schema, validation and loading
+//! - [`profiles`]: per-app radial profiles and hardware profiles (profiles.json)
+//! - [`action`] / [`buttons`]: the slice action schema and button bindings
+//! - [`color`]: hex/HSL parsing and the shade helpers behind derived theme colors
+//! - [`content_hash`]: canonical-JSON hashes of loaded themes and profiles
+//! - [`fs_util`]: private, symlink-safe file writes
+//! - [`geometry`]: pointer offset → slice hit testing for the ring
+//! - [`hidpp`]: HID++ message framing and feature constants
+//! - [`label`]: slice label width, shortening and broken-text checks
+//! - [`locale`]: per-language labels and theme names, resolved for the process locale
+//! - [`migration`]: profiles.json schema migrations
+//! - [`paths`]: install data directory resolution (packaged / prefix installs)
+//! - [`shortcut_lint`]: advisory warnings for destructive shortcuts in prime slices
+//! - [`slice_tint`]: per-slice fill colors, from the theme or generated from its accent
+//! - [`validation`]: structured findings shared by theme, profile and config validation
+//!
+//! # Stability
+//!
+//! The crate follows semver. Within a `0.x` release line only additive
+//! changes land in patch releases; anything that breaks a public signature
+//! or a serialized format bumps the minor version. Enums that grow with the
+//! daemon ([`action::ActionType`], the error types) are `#[non_exhaustive]`,
+//! so downstream matches need a wildcard arm. The on-disk JSON formats stay
+//! backward compatible regardless of the crate version.
+
+pub mod action;
+pub mod buttons;
+pub mod bundled_themes;
+pub mod color;
+pub mod content_hash;
+pub mod fs_util;
+pub mod geometry;
+pub mod hidpp;
+pub mod label;
+pub mod locale;
+pub mod migration;
+pub mod paths;
+pub mod profiles;
+pub mod shortcut_lint;
+pub mod slice_tint;
+#[cfg(test)]
+mod test_support;
+pub mod theme;
+pub mod validation;
+
+pub use action::{Action, ActionType};
+pub use bundled_themes::DEFAULT_THEME_NAME;
+pub use content_hash::ContentHash;
+pub use locale::{Locale, LocalizedText};
+pub use profiles::{HardwareProfile, Profile, ProfileManager};
+pub use theme::{Theme, ThemeManager};
@@ -0,0 +1,304 @@
+//! Localized text for slice labels and theme metadata
+//!
+//! A slice `label` or a theme's `display_name` is either a plain string (the
+//! original format) or a map of BCP-47 language tags to strings:
+//!
+//! ```json
+//! "label": {"en": "Copy", "de": "Kopieren", "pt-BR": "Copiar"}
+//! ```
+//!
+//! [`LocalizedText`] reads and writes both forms unchanged, and
+//! [`LocalizedText::resolve`] picks the string for a [`Locale`]: the exact
+//! tag, then the tag with its subtags dropped one at a time (`de-AT`, `de`),
+//! then any entry of the same language (`de-CH`), then `en`, then the first
+//! entry by tag. The process locale comes from `LC_ALL`, `LC_MESSAGES` or
+//! `LANG`, in that order, the same precedence the C library uses.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Language every localized text falls back to before "any entry"
+pub const FALLBACK_LANGUAGE: &str = "en";
+
+/// Preferred languages, most specific first, as lowercase tags (`de-at`, `de`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Locale {
+    tags: Vec<String>,
+}
+
+impl Locale {
+    /// Parse a POSIX locale (`de_AT.UTF-8@euro`) or a BCP-47 tag (`de-AT`)
+    ///
+    /// `C`, `POSIX` and empty values name no language and give `None`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let base = value.split(['.', '@']).next().unwrap_or_default().trim();
+        if base.is_empty() || base == "C" || base == "POSIX" {
+            return None;
+        }
+        let full = normalize_tag(base);
+        if !full.split('-').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric())) {
+            return None;
+        }
+        let mut tags = vec![full.clone()];
+        let mut tag = full.as_str();
+        while let Some((shorter, _)) = tag.rsplit_once('-') {
+            tags.push(shorter.to_string());
+            tag = shorter;
+        }
+        Some(Self { tags })
+    }
+
+    /// The first of `LC_ALL`, `LC_MESSAGES` and `LANG` that is set and non-empty
+    ///
+    /// No usable value (or `C`) leaves the locale empty, so texts resolve to
+    /// their `en` entry.
+    pub fn from_vars(lc_all: Option<&str>, lc_messages: Option<&str>, lang: Option<&str>) -> Self {
+        [lc_all, lc_messages, lang]
+            .into_iter()
+            .flatten()
+            .find(|v| !v.is_empty())
+            .and_then(Self::parse)
+            .unwrap_or_default()
+    }
+
+    /// The locale of this process's environment
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok();
+        Self::from_vars(var("LC_ALL").as_deref(), var("LC_MESSAGES").as_deref(), var("LANG").as_deref())
+    }
+
+    /// Preferred tags, most specific first
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// The language subtag (`de` for `de-AT`)
+    pub fn language(&self) -> Option<&str> {
+        self.tags.last().map(String::as_str)
+    }
+}
+
+/// Lowercase with `-` separators, so `pt_BR`, `pt-br` and `PT-BR` compare equal
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+fn language_of(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// Best entry of `(tag, text)` pairs for `locale`, by the fallback chain in
+/// the module docs; `None` only when there are no entries
+pub fn best_match<'a, I>(entries: I, locale: &Locale) -> Option<&'a str>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+    I::IntoIter: Clone,
+{
+    let entries = entries.into_iter();
+    let find = |wanted: &dyn Fn(&str) -> bool| entries.clone().find(|(tag, _)| wanted(tag)).map(|(_, text)| text);
+    for tag in locale.tags() {
+        if let Some(text) = find(&|candidate| normalize_tag(candidate) == *tag) {
+            return Some(text);
+        }
+    }
+    if let Some(language) = locale.language() {
+        if let Some(text) = find(&|candidate| language_of(candidate).eq_ignore_ascii_case(language)) {
+            return Some(text);
+        }
+    }
+    find(&|candidate| language_of(candidate).eq_ignore_ascii_case(FALLBACK_LANGUAGE))
+        .or_else(|| entries.clone().map(|(_, text)| text).next())
+}
+
+/// A string, or one string per language tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalizedText {
+    /// The same text in every locale (the original format)
+    Plain(String),
+    /// Text per BCP-47 tag, never empty
+    Localized(BTreeMap<String, String>),
+}
+
+impl LocalizedText {
+    /// The text to show in `locale`
+    pub fn resolve(&self, locale: &Locale) -> &str {
+        match self {
+            Self::Plain(text) => text,
+            Self::Localized(map) => best_match(map.iter().map(|(k, v)| (k.as_str(), v.as_str())), locale).unwrap_or_default(),
+        }
+    }
+
+    /// The text in the process locale, read from the environment per call
+    pub fn current(&self) -> &str {
+        self.resolve(&Locale::from_env())
+    }
+
+    /// Every stored text, for checks that look at all translations
+    pub fn values(&self) -> impl Iterator<Item = &str> {
+        let (plain, map) = match self {
+            Self::Plain(text) => (Some(text.as_str()), None),
+            Self::Localized(map) => (None, Some(map.values().map(String::as_str))),
+        };
+        plain.into_iter().chain(map.into_iter().flatten())
+    }
+
+    /// Whether this is the per-language form
+    pub fn is_localized(&self) -> bool {
+        matches!(self, Self::Localized(_))
+    }
+
+    /// Whether every stored text is empty
+    pub fn is_empty(&self) -> bool {
+        self.values().all(str::is_empty)
+    }
+}
+
+impl Default for LocalizedText {
+    fn default() -> Self {
+        Self::Plain(String::new())
+    }
+}
+
+impl From<&str> for LocalizedText {
+    fn from(text: &str) -> Self {
+        Self::Plain(text.to_string())
+    }
+}
+
+impl From<String> for LocalizedText {
+    fn from(text: String) -> Self {
+        Self::Plain(text)
+    }
+}
+
+impl PartialEq<str> for LocalizedText {
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, Self::Plain(text) if text == other)
+    }
+}
+
+impl PartialEq<&str> for LocalizedText {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Serialize for LocalizedText {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Plain(text) => serializer.serialize_str(text),
+            Self::Localized(map) => map.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LocalizedText {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TextVisitor;
+
+        impl<'de> Visitor<'de> for TextVisitor {
+            type Value = LocalizedText;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string or a map of language tags to strings")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+                Ok(LocalizedText::Plain(text.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, text: String) -> Result<Self::Value, E> {
+                Ok(LocalizedText::Plain(text))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut map = BTreeMap::new();
+                while let Some((tag, text)) = access.next_entry::<String, String>()? {
+                    if Locale::parse(&tag).is_none() {
+                        return Err(de::Error::custom(format!("'{}' is not a language tag", tag)));
+                    }
+                    map.insert(tag, text);
+                }
+                if map.is_empty() {
+                    return Err(de::Error::custom("a localized text needs at least one language"));
+                }
+                Ok(LocalizedText::Localized(map))
+            }
+        }
+
+        deserializer.deserialize_any(TextVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn copy() -> LocalizedText {
+        serde_json::from_str(r#"{"en": "Copy", "de": "Kopieren", "pt-BR": "Copiar", "zh-Hans": "复制"}"#).unwrap()
+    }
+
+    fn locale(value: &str) -> Locale {
+        Locale::parse(value).unwrap()
+    }
+
+    #[test]
+    fn test_locale_parsing_and_precedence() {
+        assert_eq!(locale("de_AT.UTF-8@euro").tags(), ["de-at", "de"]);
+        assert_eq!(locale("zh-Hans-CN").tags(), ["zh-hans-cn", "zh-hans", "zh"]);
+        assert_eq!(Locale::parse("C"), None);
+        assert_eq!(Locale::parse("POSIX"), None);
+        assert_eq!(Locale::parse("C.UTF-8"), None);
+        assert_eq!(Locale::parse(""), None);
+
+        // LC_ALL beats LC_MESSAGES beats LANG; empty values are skipped
+        assert_eq!(Locale::from_vars(Some("fr_FR.UTF-8"), Some("de_DE"), Some("en_US")).language(), Some("fr"));
+        assert_eq!(Locale::from_vars(Some(""), Some("de_DE"), Some("en_US")).language(), Some("de"));
+        assert_eq!(Locale::from_vars(None, None, Some("sv_SE.UTF-8")).language(), Some("sv"));
+        assert_eq!(Locale::from_vars(None, Some("C"), Some("sv_SE")), Locale::default());
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let text = copy();
+        assert_eq!(text.resolve(&locale("de_DE.UTF-8")), "Kopieren");
+        assert_eq!(text.resolve(&locale("pt_BR")), "Copiar");
+        // Same language, other region
+        assert_eq!(text.resolve(&locale("pt_PT")), "Copiar");
+        assert_eq!(text.resolve(&locale("zh_Hans_CN")), "复制");
+        // Unknown language and no locale: English
+        assert_eq!(text.resolve(&locale("ja_JP")), "Copy");
+        assert_eq!(text.resolve(&Locale::default()), "Copy");
+        // No English either: the first entry by tag
+        let no_en: LocalizedText = serde_json::from_str(r#"{"sv": "Kopiera", "de": "Kopieren"}"#).unwrap();
+        assert_eq!(no_en.resolve(&locale("ja_JP")), "Kopieren");
+        // Plain text is the same everywhere
+        assert_eq!(LocalizedText::from("Copy").resolve(&locale("de_DE")), "Copy");
+        // Tags in the file are matched without regard to case or separator
+        let odd: LocalizedText = serde_json::from_str(r#"{"EN": "Copy", "pt_br": "Copiar"}"#).unwrap();
+        assert_eq!(odd.resolve(&locale("pt-BR")), "Copiar");
+        assert_eq!(odd.resolve(&locale("fi_FI")), "Copy");
+    }
+
+    #[test]
+    fn test_both_forms_round_trip() {
+        let plain: LocalizedText = serde_json::from_str(r#""Copy""#).unwrap();
+        assert_eq!(plain, "Copy");
+        assert_eq!(serde_json::to_string(&plain).unwrap(), r#""Copy""#);
+
+        let text = copy();
+        assert!(text.is_localized());
+        let json = serde_json::to_value(&text).unwrap();
+        assert_eq!(json, serde_json::json!({"en": "Copy", "de": "Kopieren", "pt-BR": "Copiar", "zh-Hans": "复制"}));
+        assert_eq!(serde_json::from_value::<LocalizedText>(json).unwrap(), text);
+        assert_eq!(text.values().count(), 4);
+
+        assert!(serde_json::from_str::<LocalizedText>("{}").is_err());
+        assert!(serde_json::from_str::<LocalizedText>(r#"{"en": 1}"#).is_err());
+        assert!(serde_json::from_str::<LocalizedText>(r#"{"not a tag": "x"}"#).is_err());
+        assert!(serde_json::from_str::<LocalizedText>("12").is_err());
+    }
+}
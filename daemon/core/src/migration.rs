@@ -0,0 +1,152 @@
+//! profiles.json schema migrations
+//!
+//! Migrations run on the raw JSON document before it is deserialized, so a
+//! step can rename, move or fill in fields that the typed schema of the
+//! current version would reject or misread. `STEPS[i]` takes a document from
+//! version `MIN_SCHEMA_VERSION + i` to the next one; `migrate_document` runs
+//! every step from the file's version up to [`SCHEMA_VERSION`].
+//!
+//! Bumping `SCHEMA_VERSION` needs a new step at the end of `STEPS`.
+
+use serde_json::{Map, Value};
+
+use crate::profiles::SCHEMA_VERSION;
+
+/// Oldest schema version that can be migrated
+pub const MIN_SCHEMA_VERSION: u32 = 1;
+
+/// Slice slots per profile since v2
+const SLICE_COUNT: usize = 8;
+
+/// Rewrites a top-level document from one version to the next
+type Step = fn(&mut Map<String, Value>);
+
+/// The migration ladder, oldest first
+const STEPS: [Step; (SCHEMA_VERSION - MIN_SCHEMA_VERSION) as usize] = [migrate_v1_to_v2];
+
+/// What `migrate_document` did with a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationOutcome {
+    /// Already at `SCHEMA_VERSION` (or not a versioned document); untouched
+    Current,
+    /// Rewritten in place from this version to `SCHEMA_VERSION`
+    Migrated { from: u32 },
+    /// Written by a newer daemon; untouched
+    TooNew { found: u32 },
+    /// Older than `MIN_SCHEMA_VERSION`; untouched
+    TooOld { found: u32 },
+}
+
+/// Schema version a document declares
+///
+/// A document without `version` is the flat shape the settings UI writes and
+/// counts as current. `None` when `version` is not a `u32`; the typed loader
+/// reports that.
+pub fn document_version(doc: &Value) -> Option<u32> {
+    match doc.get("version") {
+        None => Some(SCHEMA_VERSION),
+        Some(version) => version.as_u64().and_then(|v| u32::try_from(v).ok()),
+    }
+}
+
+/// Bring a document up to `SCHEMA_VERSION` in place
+pub fn migrate_document(doc: &mut Value) -> MigrationOutcome {
+    let Some(found) = document_version(doc) else {
+        return MigrationOutcome::Current;
+    };
+    if found > SCHEMA_VERSION {
+        return MigrationOutcome::TooNew { found };
+    }
+    if found < MIN_SCHEMA_VERSION {
+        return MigrationOutcome::TooOld { found };
+    }
+    let Some(map) = doc.as_object_mut() else {
+        return MigrationOutcome::Current;
+    };
+    if found == SCHEMA_VERSION {
+        return MigrationOutcome::Current;
+    }
+
+    for (version, step) in (found..SCHEMA_VERSION).zip(&STEPS[(found - MIN_SCHEMA_VERSION) as usize..]) {
+        tracing::debug!(from = version, to = version + 1, "Migrating profiles.json");
+        step(map);
+    }
+    map.insert("version".to_string(), Value::from(SCHEMA_VERSION));
+    MigrationOutcome::Migrated { from: found }
+}
+
+/// v1 -> v2: the per-app `hardware` map and fixed eight-slot slice lists
+///
+/// v2 reads `slices` as one entry per slot (eight without a `slice_count`),
+/// so v1 lists of any other length are padded with empty slots or truncated.
+fn migrate_v1_to_v2(doc: &mut Map<String, Value>) {
+    if doc.get("hardware").is_none_or(Value::is_null) {
+        doc.insert("hardware".to_string(), Value::Object(Map::new()));
+    }
+    if let Some(profiles) = doc.get_mut("profiles").and_then(Value::as_array_mut) {
+        for slices in profiles
+            .iter_mut()
+            .filter_map(|p| p.get_mut("slices"))
+            .filter_map(Value::as_array_mut)
+        {
+            slices.resize(SLICE_COUNT, Value::Null);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::ProfilesConfig;
+
+    /// A v1 file: no `hardware` map and a short slice list
+    const V1_FIXTURE: &str = r#"{
+        "version": 1,
+        "profiles": [
+            {
+                "name": "default",
+                "slices": [
+                    {"type": "shortcut", "value": "ctrl+c", "label": "Copy"},
+                    {"type": "shortcut", "value": "ctrl+v", "label": "Paste"},
+                    null, null, null, null
+                ]
+            },
+            {"name": "gimp", "window_class": "gimp", "slices": []}
+        ]
+    }"#;
+
+    #[test]
+    fn test_v1_fixture_migrates_to_current() {
+        let mut doc: Value = serde_json::from_str(V1_FIXTURE).unwrap();
+        // Read as-is, the typed schema would keep the short slice list
+        let unmigrated: ProfilesConfig = serde_json::from_value(doc.clone()).unwrap();
+        assert_eq!(unmigrated.profiles[0].slices.len(), 6);
+
+        assert_eq!(migrate_document(&mut doc), MigrationOutcome::Migrated { from: 1 });
+        assert_eq!(doc["version"], SCHEMA_VERSION);
+        assert!(doc["hardware"].as_object().unwrap().is_empty());
+        let config: ProfilesConfig = serde_json::from_value(doc.clone()).unwrap();
+        assert_eq!(config.profiles[0].slices[1].as_ref().unwrap().label, Some("Paste".into()));
+        assert!(config.profiles[0].slices[7].is_none());
+        assert_eq!(config.profiles[1].window_class.as_deref(), Some("gimp"));
+
+        // Migrating again is a no-op
+        assert_eq!(migrate_document(&mut doc), MigrationOutcome::Current);
+    }
+
+    #[test]
+    fn test_version_outcomes() {
+        let mut newer = serde_json::json!({"version": SCHEMA_VERSION + 1, "profiles": []});
+        assert_eq!(migrate_document(&mut newer), MigrationOutcome::TooNew { found: SCHEMA_VERSION + 1 });
+        assert_eq!(newer["version"], SCHEMA_VERSION + 1);
+        let mut ancient = serde_json::json!({"version": 0});
+        assert_eq!(migrate_document(&mut ancient), MigrationOutcome::TooOld { found: 0 });
+        // The flat UI shape has no version and is current
+        let mut flat = serde_json::json!({"hardware": {}});
+        assert_eq!(migrate_document(&mut flat), MigrationOutcome::Current);
+        // A malformed version is left for the typed loader to report
+        let mut bogus = serde_json::json!({"version": "two"});
+        assert_eq!(migrate_document(&mut bogus), MigrationOutcome::Current);
+        assert_eq!(bogus["version"], "two");
+    }
+}
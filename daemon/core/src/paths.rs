@@ -0,0 +1,355 @@
+//! Where the daemon's files live
+//!
+//! [`Paths`] reads the XDG base directory variables once per process; every
+//! other module asks it instead of parsing the environment itself. Tests
+//! build their own with [`Paths::resolve`] from a [`PathsEnv`] instead of
+//! setting process-wide variables.
+//!
+//! Per the base directory spec, a relative or empty `$XDG_*_HOME` is ignored
+//! and the `$HOME` default used:
+//!
+//! - config: `$XDG_CONFIG_HOME/juhradial` (config.json, profiles.json, user
+//!   themes, macros)
+//! - user data: `$XDG_DATA_HOME/juhradial` (usage stats, HID++ traces)
+//! - cache: `$XDG_CACHE_HOME/juhradial`
+//! - runtime: `$XDG_RUNTIME_DIR/juhradial`, none without `$XDG_RUNTIME_DIR`
+//!
+//! Snap, flatpak, `/usr/local` and prefix installs do not put their data in
+//! `/usr/share/juhradial`. The install data directory (themes, assets,
+//! overlay) is resolved once, first match wins:
+//!
+//! 1. `$JUHRADIAL_DATA_DIR`
+//! 2. `paths.data_dir` in config.json
+//! 3. the first existing `<dir>/juhradial` in `$XDG_DATA_DIRS`
+//! 4. the compiled-in `/usr/share/juhradial`
+
+use serde::Serialize;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directory name under each XDG base directory
+pub const APP_DIR_NAME: &str = "juhradial";
+
+/// Environment override for the data directory
+pub const DATA_DIR_ENV: &str = "JUHRADIAL_DATA_DIR";
+
+/// Compiled-in data directory
+pub const DEFAULT_DATA_DIR: &str = "/usr/share/juhradial";
+
+/// `$XDG_DATA_DIRS` when unset, per the base directory spec
+const DEFAULT_XDG_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+/// Where the data directory came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDirSource {
+    /// `$JUHRADIAL_DATA_DIR`
+    Env,
+    /// `paths.data_dir` in config.json
+    Config,
+    /// An entry of `$XDG_DATA_DIRS`
+    XdgDataDirs,
+    /// [`DEFAULT_DATA_DIR`]
+    Default,
+}
+
+/// The resolved data directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataDir {
+    pub path: PathBuf,
+    pub source: DataDirSource,
+}
+
+impl DataDir {
+    /// System-wide themes
+    pub fn themes_dir(&self) -> PathBuf {
+        self.path.join("themes")
+    }
+
+    /// Icons and wheel images
+    pub fn assets_dir(&self) -> PathBuf {
+        self.path.join("assets")
+    }
+
+    /// `Status()` / startup log form
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "data_dir": self.path,
+            "source": self.source,
+            "exists": self.path.is_dir(),
+            "themes_dir": self.themes_dir(),
+            "assets_dir": self.assets_dir(),
+        })
+    }
+}
+
+/// Resolve the data directory from explicit inputs
+///
+/// Explicit overrides are taken as given (a missing directory is reported, not
+/// skipped); `$XDG_DATA_DIRS` entries only count when the directory exists.
+pub fn resolve_data_dir(env: Option<&OsStr>, configured: Option<&Path>, xdg_data_dirs: Option<&OsStr>) -> DataDir {
+    if let Some(dir) = env.filter(|d| !d.is_empty()) {
+        return DataDir {
+            path: PathBuf::from(dir),
+            source: DataDirSource::Env,
+        };
+    }
+    if let Some(dir) = configured.filter(|d| !d.as_os_str().is_empty()) {
+        return DataDir {
+            path: dir.to_path_buf(),
+            source: DataDirSource::Config,
+        };
+    }
+    let xdg = xdg_data_dirs
+        .filter(|d| !d.is_empty())
+        .unwrap_or(OsStr::new(DEFAULT_XDG_DATA_DIRS));
+    // Relative entries are invalid per the spec and ignored
+    let found = std::env::split_paths(xdg)
+        .filter(|base| base.is_absolute())
+        .map(|base| base.join("juhradial"))
+        .find(|dir| dir.is_dir());
+    match found {
+        Some(path) => DataDir {
+            path,
+            source: DataDirSource::XdgDataDirs,
+        },
+        None => DataDir {
+            path: PathBuf::from(DEFAULT_DATA_DIR),
+            source: DataDirSource::Default,
+        },
+    }
+}
+
+/// The environment variables [`Paths`] is resolved from
+#[derive(Debug, Clone, Default)]
+pub struct PathsEnv {
+    pub home: Option<OsString>,
+    pub xdg_config_home: Option<OsString>,
+    pub xdg_data_home: Option<OsString>,
+    pub xdg_cache_home: Option<OsString>,
+    pub xdg_runtime_dir: Option<OsString>,
+    pub xdg_data_dirs: Option<OsString>,
+    /// `$JUHRADIAL_DATA_DIR`
+    pub data_dir: Option<OsString>,
+}
+
+impl PathsEnv {
+    /// This process's environment
+    pub fn from_process() -> Self {
+        let var = std::env::var_os;
+        Self {
+            home: var("HOME"),
+            xdg_config_home: var("XDG_CONFIG_HOME"),
+            xdg_data_home: var("XDG_DATA_HOME"),
+            xdg_cache_home: var("XDG_CACHE_HOME"),
+            xdg_runtime_dir: var("XDG_RUNTIME_DIR"),
+            xdg_data_dirs: var("XDG_DATA_DIRS"),
+            data_dir: var(DATA_DIR_ENV),
+        }
+    }
+}
+
+/// The daemon's directories, resolved from a [`PathsEnv`]
+#[derive(Debug, Clone)]
+pub struct Paths {
+    /// `$XDG_CONFIG_HOME/juhradial`
+    pub config_dir: PathBuf,
+    /// `$XDG_DATA_HOME/juhradial`
+    pub user_data_dir: PathBuf,
+    /// `$XDG_CACHE_HOME/juhradial`
+    pub cache_dir: PathBuf,
+    /// `$XDG_RUNTIME_DIR/juhradial`
+    pub runtime_dir: Option<PathBuf>,
+    /// `$JUHRADIAL_DATA_DIR` and `$XDG_DATA_DIRS`, for [`Paths::init_data_dir`]
+    data_dir_env: Option<OsString>,
+    xdg_data_dirs: Option<OsString>,
+    data_dir: OnceLock<DataDir>,
+}
+
+impl Paths {
+    /// Resolve every directory from `env`
+    ///
+    /// Without `$HOME` the `$HOME` defaults are relative to the working
+    /// directory (`.config/juhradial`, ...).
+    pub fn resolve(env: &PathsEnv) -> Self {
+        let base = |xdg: &Option<OsString>, default: &str| {
+            absolute(xdg)
+                .unwrap_or_else(|| env.home.as_deref().map(PathBuf::from).unwrap_or_default().join(default))
+                .join(APP_DIR_NAME)
+        };
+        Self {
+            config_dir: base(&env.xdg_config_home, ".config"),
+            user_data_dir: base(&env.xdg_data_home, ".local/share"),
+            cache_dir: base(&env.xdg_cache_home, ".cache"),
+            runtime_dir: absolute(&env.xdg_runtime_dir).map(|dir| dir.join(APP_DIR_NAME)),
+            data_dir_env: env.data_dir.clone(),
+            xdg_data_dirs: env.xdg_data_dirs.clone(),
+            data_dir: OnceLock::new(),
+        }
+    }
+
+    /// config.json
+    pub fn config_path(&self) -> PathBuf {
+        self.config_dir.join("config.json")
+    }
+
+    /// profiles.json
+    pub fn profiles_path(&self) -> PathBuf {
+        self.config_dir.join("profiles.json")
+    }
+
+    /// `$XDG_RUNTIME_DIR` itself, where compositors keep their sockets
+    pub fn session_runtime_dir(&self) -> Option<&Path> {
+        self.runtime_dir.as_deref().and_then(Path::parent)
+    }
+
+    /// User themes (`$XDG_CONFIG_HOME/juhradial/themes`)
+    pub fn user_themes_dir(&self) -> PathBuf {
+        self.config_dir.join("themes")
+    }
+
+    /// Theme directories in load order, later overriding earlier: system,
+    /// then user
+    pub fn themes_dirs(&self) -> [PathBuf; 2] {
+        [self.data_dir().themes_dir(), self.user_themes_dir()]
+    }
+
+    /// Resolve the data directory with the config override
+    ///
+    /// Call once at startup before anything reads [`Paths::data_dir`]; later
+    /// calls return the first result.
+    pub fn init_data_dir(&self, configured: Option<&Path>) -> &DataDir {
+        self.data_dir
+            .get_or_init(|| resolve_data_dir(self.data_dir_env.as_deref(), configured, self.xdg_data_dirs.as_deref()))
+    }
+
+    /// The data directory (environment only if never initialised)
+    pub fn data_dir(&self) -> &DataDir {
+        self.init_data_dir(None)
+    }
+}
+
+/// An XDG variable's path, `None` when unset, empty or relative
+fn absolute(var: &Option<OsString>) -> Option<PathBuf> {
+    var.as_deref().map(PathBuf::from).filter(|path| path.is_absolute())
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// The process-wide paths, resolved from the environment on first use
+pub fn paths() -> &'static Paths {
+    PATHS.get_or_init(|| Paths::resolve(&PathsEnv::from_process()))
+}
+
+/// Resolve the process-wide data directory with the config override
+///
+/// See [`Paths::init_data_dir`].
+pub fn init_data_dir(configured: Option<&Path>) -> &'static DataDir {
+    paths().init_data_dir(configured)
+}
+
+/// The process-wide data directory (environment only if never initialised)
+pub fn data_dir() -> &'static DataDir {
+    paths().data_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolution_order() {
+        let root = TempDir::new().unwrap();
+        let xdg = root.path().join("share");
+        std::fs::create_dir_all(xdg.join("juhradial")).unwrap();
+        let xdg_dirs = std::env::join_paths([root.path().join("missing"), xdg.clone()]).unwrap();
+
+        let env = OsStr::new("/snap/juhradial/current/share/juhradial");
+        let configured = Path::new("/opt/prefix/share/juhradial");
+
+        let dir = resolve_data_dir(Some(env), Some(configured), Some(&xdg_dirs));
+        assert_eq!(dir.source, DataDirSource::Env);
+        assert_eq!(dir.path, PathBuf::from(env));
+
+        let dir = resolve_data_dir(Some(OsStr::new("")), Some(configured), Some(&xdg_dirs));
+        assert_eq!(dir.source, DataDirSource::Config);
+        assert_eq!(dir.themes_dir(), configured.join("themes"));
+
+        let dir = resolve_data_dir(None, None, Some(&xdg_dirs));
+        assert_eq!(dir.source, DataDirSource::XdgDataDirs);
+        assert_eq!(dir.path, xdg.join("juhradial"));
+    }
+
+    #[test]
+    fn test_xdg_homes_and_home_defaults() {
+        let env = PathsEnv {
+            home: Some("/home/u".into()),
+            xdg_config_home: Some("/custom/config".into()),
+            xdg_data_home: Some("relative/data".into()),
+            xdg_cache_home: Some("".into()),
+            ..Default::default()
+        };
+        let paths = Paths::resolve(&env);
+        assert_eq!(paths.config_dir, PathBuf::from("/custom/config/juhradial"));
+        assert_eq!(paths.profiles_path(), PathBuf::from("/custom/config/juhradial/profiles.json"));
+        assert_eq!(paths.user_themes_dir(), PathBuf::from("/custom/config/juhradial/themes"));
+        // Relative and empty values are ignored
+        assert_eq!(paths.user_data_dir, PathBuf::from("/home/u/.local/share/juhradial"));
+        assert_eq!(paths.cache_dir, PathBuf::from("/home/u/.cache/juhradial"));
+        assert_eq!(paths.runtime_dir, None);
+        assert_eq!(paths.session_runtime_dir(), None);
+
+        let env = PathsEnv {
+            xdg_runtime_dir: Some("/run/user/1000".into()),
+            ..Default::default()
+        };
+        let paths = Paths::resolve(&env);
+        assert_eq!(paths.config_path(), PathBuf::from(".config/juhradial/config.json"));
+        assert_eq!(paths.runtime_dir, Some(PathBuf::from("/run/user/1000/juhradial")));
+        assert_eq!(paths.session_runtime_dir(), Some(Path::new("/run/user/1000")));
+    }
+
+    #[test]
+    fn test_data_dir_resolves_once_from_env() {
+        let env = PathsEnv {
+            home: Some("/home/u".into()),
+            data_dir: Some("/snap/juhradial/current/share/juhradial".into()),
+            ..Default::default()
+        };
+        let paths = Paths::resolve(&env);
+        let dir = paths.init_data_dir(Some(Path::new("/opt/prefix/share/juhradial")));
+        assert_eq!(dir.source, DataDirSource::Env);
+        assert_eq!(
+            paths.themes_dirs(),
+            [
+                PathBuf::from("/snap/juhradial/current/share/juhradial/themes"),
+                PathBuf::from("/home/u/.config/juhradial/themes"),
+            ]
+        );
+
+        let env = PathsEnv {
+            xdg_data_dirs: Some(TempDir::new().unwrap().path().as_os_str().to_owned()),
+            ..Default::default()
+        };
+        let paths = Paths::resolve(&env);
+        assert_eq!(paths.init_data_dir(Some(Path::new("/opt/prefix/share/juhradial"))).source, DataDirSource::Config);
+        // Later calls keep the first result
+        assert_eq!(paths.init_data_dir(None).source, DataDirSource::Config);
+        assert_eq!(paths.data_dir().path, PathBuf::from("/opt/prefix/share/juhradial"));
+    }
+
+    #[test]
+    fn test_falls_back_to_compiled_default() {
+        let root = TempDir::new().unwrap();
+        let xdg_dirs = std::env::join_paths([root.path().join("missing"), PathBuf::from("relative/share")]).unwrap();
+        let dir = resolve_data_dir(None, None, Some(&xdg_dirs));
+        assert_eq!(dir.source, DataDirSource::Default);
+        assert_eq!(dir.path, PathBuf::from(DEFAULT_DATA_DIR));
+
+        let json = dir.to_json();
+        assert_eq!(json["source"], "default");
+        assert_eq!(json["themes_dir"], "/usr/share/juhradial/themes");
+    }
+}
@@ -0,0 +1,2143 @@
+//! Profile management for per-application radial menu configurations
+//!
+//! Story 3.1: Profile Configuration Schema
+//!
+//! Configuration is stored at `~/.config/juhradial/profiles.json`
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::action::{get_default_actions, Action, ActionType};
+use crate::buttons::{ButtonAction, ThumbwheelMode};
+use crate::content_hash::ContentHash;
+use crate::fs_util;
+use crate::geometry::{is_supported_slice_count, MenuSize, SLICE_COUNT, SUPPORTED_SLICE_COUNTS};
+use crate::migration::{self, MigrationOutcome, MIN_SCHEMA_VERSION};
+use crate::paths::Paths;
+use crate::shortcut_lint::ShortcutLintRules;
+
+/// Current schema version for profiles.json
+///
+/// v2 added the optional `hardware` map (per-app HardwareProfile) and reads
+/// one slice slot per `slice_count` (eight unless set). Older files are migrated on load (see
+/// [`crate::migration`]); newer ones are refused.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Largest profiles.json that is read; bigger files fail to load
+pub const MAX_PROFILES_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Shared, hot-reloadable per-app hardware profile map keyed by (lowercased)
+/// window resource class. Written by `ReloadConfig`, read by the focus-change
+/// consumer that applies a `HardwareProfile` when the active window changes.
+pub type SharedHardwareProfiles = Arc<RwLock<HashMap<String, HardwareProfile>>>;
+
+/// What two profiles in one file both claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionKind {
+    Name,
+    WindowClass,
+    /// Same virtual desktop, with or without the same window class
+    VirtualDesktop,
+}
+
+/// Two profiles sharing a name or match; the first declared is used
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileCollision {
+    pub kind: CollisionKind,
+    /// The shared name, window class or desktop ("code on desktop 2")
+    pub key: String,
+    /// Index in `profiles` of the entry that is used, and its name
+    pub winner: usize,
+    pub winner_name: String,
+    /// Index in `profiles` of the entry that is ignored, and its name
+    pub ignored: usize,
+    pub ignored_name: String,
+}
+
+impl std::fmt::Display for ProfileCollision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            CollisionKind::Name => write!(
+                f,
+                "profiles[{}] reuses the name '{}' of profiles[{}]; it is ignored",
+                self.ignored, self.key, self.winner
+            ),
+            CollisionKind::WindowClass => write!(
+                f,
+                "profiles[{}] '{}' claims window_class '{}' already used by profiles[{}] '{}'; '{}' is used for it",
+                self.ignored, self.ignored_name, self.key, self.winner, self.winner_name, self.winner_name
+            ),
+            CollisionKind::VirtualDesktop => write!(
+                f,
+                "profiles[{}] '{}' claims {} already used by profiles[{}] '{}'; '{}' is used for it",
+                self.ignored, self.ignored_name, self.key, self.winner, self.winner_name, self.winner_name
+            ),
+        }
+    }
+}
+
+/// Duplicate names and matches, in declaration order
+///
+/// The first profile declaring a name, or a window class / virtual desktop
+/// combination, wins. A profile ignored for its name is not checked for its
+/// match.
+pub fn find_collisions(profiles: &[Profile]) -> Vec<ProfileCollision> {
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    let mut matches: HashMap<(Option<&str>, Option<u32>), usize> = HashMap::new();
+    let mut collisions = Vec::new();
+    for (i, profile) in profiles.iter().enumerate() {
+        if let Some(&first) = names.get(profile.name.as_str()) {
+            collisions.push(ProfileCollision {
+                kind: CollisionKind::Name,
+                key: profile.name.clone(),
+                winner: first,
+                winner_name: profile.name.clone(),
+                ignored: i,
+                ignored_name: profile.name.clone(),
+            });
+            continue;
+        }
+        names.insert(&profile.name, i);
+        let (kind, key) = match (profile.window_class.as_deref(), profile.virtual_desktop) {
+            (None, None) => continue,
+            (Some(class), None) => (CollisionKind::WindowClass, class.to_string()),
+            (None, Some(desktop)) => (CollisionKind::VirtualDesktop, format!("desktop {desktop}")),
+            (Some(class), Some(desktop)) => (CollisionKind::VirtualDesktop, format!("{class} on desktop {desktop}")),
+        };
+        match matches.get(&(profile.window_class.as_deref(), profile.virtual_desktop)) {
+            Some(&first) => collisions.push(ProfileCollision {
+                kind,
+                key,
+                winner: first,
+                winner_name: profiles[first].name.clone(),
+                ignored: i,
+                ignored_name: profile.name.clone(),
+            }),
+            None => {
+                matches.insert((profile.window_class.as_deref(), profile.virtual_desktop), i);
+            }
+        }
+    }
+    collisions
+}
+
+/// `profile_switch` target selecting the next profile by name
+pub const SWITCH_NEXT: &str = "next";
+
+/// `profile_switch` target selecting the previous profile by name
+pub const SWITCH_PREVIOUS: &str = "previous";
+
+/// A `profile_switch` action naming a profile that does not exist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownSwitchTarget {
+    /// Index in `profiles` of the profile holding the action
+    pub profile: usize,
+    /// Field holding the action (`slices`, `center`, `thumbwheel_left`, ...)
+    pub field: &'static str,
+    /// Slice index when `field` is `slices`
+    pub slice: Option<usize>,
+    pub target: String,
+}
+
+impl std::fmt::Display for UnknownSwitchTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "profile_switch target '{}' is not a profile name, '{}' or '{}'",
+            self.target, SWITCH_NEXT, SWITCH_PREVIOUS
+        )
+    }
+}
+
+/// `profile_switch` actions whose target names no profile in `profiles`
+///
+/// `default` always resolves, since the loader adds it when missing.
+pub fn unknown_switch_targets(profiles: &[Profile]) -> Vec<UnknownSwitchTarget> {
+    let mut names: HashSet<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+    names.extend(["default", SWITCH_NEXT, SWITCH_PREVIOUS]);
+    let mut unknown = Vec::new();
+    for (i, profile) in profiles.iter().enumerate() {
+        let slices = profile.slices.iter().enumerate().map(|(j, a)| ("slices", Some(j), a));
+        let others = [
+            ("center", None, &profile.center),
+            ("thumbwheel_left", None, &profile.thumbwheel_left),
+            ("thumbwheel_right", None, &profile.thumbwheel_right),
+        ];
+        for (field, slice, action) in slices.chain(others) {
+            if let Some(Action { action_type: ActionType::ProfileSwitch(ref target), .. }) = action {
+                if !names.contains(target.as_str()) {
+                    unknown.push(UnknownSwitchTarget { profile: i, field, slice, target: target.clone() });
+                }
+            }
+        }
+    }
+    unknown
+}
+
+/// A profile whose `extends` chain could not be followed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InheritanceIssue {
+    /// `extends` names no profile; the profile keeps only its own slots
+    MissingParent {
+        /// Index in `profiles` of the profile naming the parent
+        profile: usize,
+        parent: String,
+    },
+    /// The chain comes back to a profile it already passed; the profile
+    /// inherits nothing
+    Cycle {
+        profile: usize,
+        /// Names along the chain, ending with the repeated one
+        chain: Vec<String>,
+    },
+}
+
+impl InheritanceIssue {
+    /// Index in `profiles` of the affected profile
+    pub fn profile(&self) -> usize {
+        match *self {
+            Self::MissingParent { profile, .. } | Self::Cycle { profile, .. } => profile,
+        }
+    }
+}
+
+impl std::fmt::Display for InheritanceIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingParent { parent, .. } => {
+                write!(f, "extends '{}', which is not a profile; its unset slots stay empty", parent)
+            }
+            Self::Cycle { chain, .. } => {
+                write!(f, "extends chain loops ({}); nothing is inherited", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+/// Profiles with their `extends` chains applied, in declaration order
+///
+/// A profile that extends another starts from its parent's (already
+/// resolved) slots, `center`, thumb wheel actions, `icon` and `menu_size`,
+/// and its own non-null entries replace them: a `null` or missing slot
+/// inherits, `{"type": "none"}` leaves the slot empty. `name`, the match
+/// (`window_class`, `virtual_desktop`), `description` and `slice_count` are
+/// never inherited. Parents are looked up by name, first declared wins.
+pub fn resolve_inheritance(profiles: &[Profile]) -> (Vec<Profile>, Vec<InheritanceIssue>) {
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    for (i, profile) in profiles.iter().enumerate() {
+        names.entry(profile.name.as_str()).or_insert(i);
+    }
+    let mut issues = Vec::new();
+    let resolved = profiles
+        .iter()
+        .enumerate()
+        .map(|(i, profile)| {
+            // Walk up to the root: a profile without `extends` or a missing parent
+            let mut chain = vec![i];
+            let mut cycle = false;
+            while let Some(parent) = profiles[*chain.last().unwrap_or(&i)].extends.as_deref() {
+                let Some(&index) = names.get(parent) else {
+                    if chain.len() == 1 {
+                        issues.push(InheritanceIssue::MissingParent { profile: i, parent: parent.to_string() });
+                    }
+                    break;
+                };
+                cycle = chain.contains(&index);
+                chain.push(index);
+                if cycle {
+                    break;
+                }
+            }
+            if cycle {
+                let chain = chain.iter().map(|&c| profiles[c].name.clone()).collect();
+                issues.push(InheritanceIssue::Cycle { profile: i, chain });
+                return profile.clone();
+            }
+            let mut merged = profiles[chain.pop().unwrap_or(i)].clone();
+            while let Some(child) = chain.pop() {
+                merged = profiles[child].inherit(&merged);
+            }
+            merged
+        })
+        .collect();
+    (resolved, issues)
+}
+
+/// serde default for `ProfilesConfig::version` when a file omits it.
+///
+/// The GTK settings UI writes profiles.json in a FLAT shape (per-app keys plus a
+/// `hardware` map) with no top-level `version`/`profiles`. Defaulting both lets
+/// that file load without a parse error so the `hardware` map is still read.
+fn default_schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+/// Top-level profiles configuration (Story 3.1: Task 1.1, 1.3)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfilesConfig {
+    /// Schema version for future migrations. Defaulted so a UI-written file that
+    /// omits it still loads (see `default_schema_version`).
+    #[serde(default = "default_schema_version")]
+    pub version: u32,
+
+    /// All profiles (default + application-specific). Defaulted to empty so a
+    /// flat UI-written file (which has no `profiles` array) still loads; the
+    /// loader then inserts the built-in default profile.
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+
+    /// Per-application hardware profiles, keyed by window resource class.
+    /// Added in schema v2; absent in v1 files (defaults to empty).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub hardware: HashMap<String, HardwareProfile>,
+}
+
+impl Default for ProfilesConfig {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            profiles: vec![create_default_profile()],
+            hardware: HashMap::new(),
+        }
+    }
+}
+
+/// SmartShift setting for a hardware profile.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SmartshiftSetting {
+    pub enabled: bool,
+    pub threshold: u8,
+}
+
+/// Per-application HARDWARE state to apply on focus (Story 3.x).
+///
+/// Every field is optional: only the ones present are applied, and each maps to
+/// a VOLATILE HID++ setter (no onboard-memory writes). Missing fields leave the
+/// current device state untouched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HardwareProfile {
+    /// Pointer DPI (ADJUSTABLE_DPI 0x2201).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u16>,
+
+    /// SmartShift auto-disengage (HiResScroll 0x2111).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smartshift: Option<SmartshiftSetting>,
+
+    /// HiRes/ratchet scroll mode (true = hi-res on).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hires: Option<bool>,
+
+    /// Thumb-wheel mode (divert is derived: any non-Off mode diverts).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbwheel: Option<ThumbwheelMode>,
+
+    /// Per-button action overrides keyed by button name (gesture/thumb/middle/
+    /// back/forward/shift_wheel). Recorded in the schema; applied via config, not
+    /// by the daemon's `apply_hardware_profile` (volatile device state only).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub buttons: HashMap<String, ButtonAction>,
+}
+
+impl ProfilesConfig {
+    /// Create a new ProfilesConfig with default profile
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create ProfilesConfig with default profile using get_default_actions()
+    /// (Story 3.1: Task 4.1, 4.2)
+    pub fn with_default_actions() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            profiles: vec![create_default_profile(), create_default_left_profile()],
+            hardware: HashMap::new(),
+        }
+    }
+
+    /// Stamp the current version on an already deserialized older config
+    ///
+    /// Files are migrated before deserialization by `load_from_path` (see
+    /// [`crate::migration`]); this covers configs built from JSON that could
+    /// already be read, where serde defaulted the `hardware` map.
+    pub fn migrate(&mut self) {
+        if self.version < SCHEMA_VERSION {
+            tracing::info!(
+                from = self.version,
+                to = SCHEMA_VERSION,
+                "Migrating profiles config (defaulting new hardware fields)"
+            );
+            // `hardware` is already populated (empty) by serde default; nothing
+            // else to backfill for v1 -> v2.
+            self.version = SCHEMA_VERSION;
+        }
+    }
+}
+
+/// A radial menu profile (Story 3.1: Task 1.2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// Profile name
+    pub name: String,
+
+    /// Profile whose slots this one starts from (usually "default"); only
+    /// the non-null entries below override it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
+    /// Window class to match (None for default profile)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_class: Option<String>,
+
+    /// Virtual desktop to match, numbered from 1 (None matches any desktop)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_desktop: Option<u32>,
+
+    /// Slices in the ring: 4, 6, 8 or 12 (the experimental clock layout)
+    #[serde(default = "default_slice_count", skip_serializing_if = "is_default_slice_count")]
+    pub slice_count: u8,
+
+    /// Ring size for this profile, overriding `menu.size` (None follows it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_size: Option<MenuSize>,
+
+    /// One action per slot, clockwise from N (N, NE, E, SE, S, SW, W, NW
+    /// with 8 slices); the loader pads or truncates to `slice_count`. With
+    /// `extends`, `null` (or a missing entry) inherits the parent's slot
+    #[serde(default)]
+    pub slices: Vec<Option<Action>>,
+
+    /// Center tap action
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub center: Option<Action>,
+
+    /// Action for the horizontal thumb wheel turned left (replaces scrolling)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbwheel_left: Option<Action>,
+
+    /// Action for the horizontal thumb wheel turned right (replaces scrolling)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbwheel_right: Option<Action>,
+
+    /// Profile icon (emoji or path)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+
+    /// Profile description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+fn default_slice_count() -> u8 {
+    SLICE_COUNT
+}
+
+fn is_default_slice_count(count: &u8) -> bool {
+    *count == SLICE_COUNT
+}
+
+impl Profile {
+    /// Resize the ring, keeping the slots that still fit
+    ///
+    /// Growing pads with empty slots, so an 8-slice profile switched to 12
+    /// keeps its eight actions in slots 0-7 and gains four empty ones.
+    pub fn set_slice_count(&mut self, count: u8) -> Result<(), ProfileError> {
+        if !is_supported_slice_count(count) {
+            return Err(ProfileError::ValidationError(format!(
+                "slice_count {count} is not supported (use one of {SUPPORTED_SLICE_COUNTS:?})"
+            )));
+        }
+        self.slice_count = count;
+        self.slices.resize(count.into(), None);
+        Ok(())
+    }
+
+    /// This profile on top of its resolved `parent` (see [`resolve_inheritance`])
+    fn inherit(&self, parent: &Profile) -> Profile {
+        let slots = self.slices.len().max(self.slice_count.into());
+        Profile {
+            slices: (0..slots)
+                .map(|slot| {
+                    self.slices
+                        .get(slot)
+                        .cloned()
+                        .flatten()
+                        .or_else(|| parent.slices.get(slot).cloned().flatten())
+                })
+                .collect(),
+            center: self.center.clone().or_else(|| parent.center.clone()),
+            thumbwheel_left: self.thumbwheel_left.clone().or_else(|| parent.thumbwheel_left.clone()),
+            thumbwheel_right: self.thumbwheel_right.clone().or_else(|| parent.thumbwheel_right.clone()),
+            icon: self.icon.clone().or_else(|| parent.icon.clone()),
+            menu_size: self.menu_size.or(parent.menu_size),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            extends: None,
+            window_class: None,
+            virtual_desktop: None,
+            slice_count: SLICE_COUNT,
+            menu_size: None,
+            slices: vec![None; SLICE_COUNT.into()],
+            center: None,
+            thumbwheel_left: None,
+            thumbwheel_right: None,
+            icon: None,
+            description: Some("Default profile".to_string()),
+        }
+    }
+}
+
+/// Create the default profile with common actions (Story 3.1: Task 4.1, 4.2)
+pub fn create_default_profile() -> Profile {
+    let default_actions = get_default_actions();
+
+    Profile {
+        name: "default".to_string(),
+        extends: None,
+        window_class: None,
+        virtual_desktop: None,
+        slice_count: SLICE_COUNT,
+        menu_size: None,
+        slices: vec![
+            Some(default_actions[0].clone()), // N: Copy
+            Some(default_actions[1].clone()), // NE: Paste
+            Some(default_actions[2].clone()), // E: Undo
+            Some(default_actions[3].clone()), // SE: Redo
+            Some(default_actions[4].clone()), // S: Select All
+            Some(default_actions[5].clone()), // SW: Cut
+            Some(default_actions[6].clone()), // W: Save
+            Some(default_actions[7].clone()), // NW: Close
+        ],
+        center: None,
+        thumbwheel_left: None,
+        thumbwheel_right: None,
+        icon: Some("🎯".to_string()),
+        description: Some("Default profile with common shortcuts".to_string()),
+    }
+}
+
+/// Name of the bundled left-handed profile
+pub const DEFAULT_LEFT_PROFILE_NAME: &str = "default-left";
+
+/// Bundled example of the default profile mirrored for left-handed use
+///
+/// Same actions as [`create_default_profile`] with NE ↔ NW, E ↔ W and
+/// SE ↔ SW swapped, for users who prefer editing a mirrored layout over the
+/// global `input.left_handed` flag.
+pub fn create_default_left_profile() -> Profile {
+    let default = create_default_profile();
+    Profile {
+        name: DEFAULT_LEFT_PROFILE_NAME.to_string(),
+        slices: (0..default.slice_count)
+            .map(|i| default.slices[crate::geometry::mirror_slice_in(i, default.slice_count) as usize].clone())
+            .collect(),
+        icon: Some("🫲".to_string()),
+        description: Some("Default shortcuts mirrored for left-handed use".to_string()),
+        ..default
+    }
+}
+
+/// Validate an icon reference (Story 3.5)
+///
+/// Accepts:
+/// - Unicode emoji (single character or emoji sequence)
+/// - File path (ends with .png, .svg, .ico)
+/// - System icon name (alphanumeric with hyphens)
+///
+/// Returns true if the icon reference appears valid.
+pub fn validate_icon_reference(icon: &str) -> bool {
+    if icon.is_empty() {
+        return false;
+    }
+
+    // Check if it's an emoji or symbol character (common Unicode emoji ranges)
+    let first_char = icon.chars().next().unwrap();
+    let cp = first_char as u32;
+    if cp > 0x1F000                        // Supplementary emoji (Mahjong+)
+        || (0x2190..=0x21FF).contains(&cp) // Arrows (↩, ↪)
+        || (0x2300..=0x23FF).contains(&cp) // Misc Technical (⏏, ⌨)
+        || (0x2500..=0x27BF).contains(&cp) // Box Drawing through Dingbats (✂, ❌)
+        // Supplemental Arrows, Misc Symbols (⭐)
+        || (0x2900..=0x2BFF).contains(&cp)
+    {
+        return true;
+    }
+
+    // Check if it's a file path
+    let lower = icon.to_lowercase();
+    if lower.ends_with(".png") || lower.ends_with(".svg") || lower.ends_with(".ico") {
+        // It's a file path - we don't validate existence here (done at render)
+        return true;
+    }
+
+    // Check if it's a system icon name (letters, numbers, hyphens)
+    if icon
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return true;
+    }
+
+    // Unknown format
+    false
+}
+
+/// Direction indices for slices
+pub mod direction {
+    pub const NORTH: usize = 0;
+    pub const NORTH_EAST: usize = 1;
+    pub const EAST: usize = 2;
+    pub const SOUTH_EAST: usize = 3;
+    pub const SOUTH: usize = 4;
+    pub const SOUTH_WEST: usize = 5;
+    pub const WEST: usize = 6;
+    pub const NORTH_WEST: usize = 7;
+}
+
+/// Get the config directory path (~/.config/juhradial/) (Story 3.1: Task 2.1, 2.3)
+///
+/// Respects XDG_CONFIG_HOME if set, see [`crate::paths`]
+pub fn get_config_dir() -> PathBuf {
+    crate::paths::paths().config_dir.clone()
+}
+
+/// Get the profiles.json file path (Story 3.1: Task 2.2)
+pub fn get_profiles_path() -> PathBuf {
+    crate::paths::paths().profiles_path()
+}
+
+/// Ensure config directory exists (Story 3.1: Task 2.4)
+pub fn ensure_config_dir() -> Result<PathBuf, ProfileError> {
+    ensure_config_dir_at(get_config_dir())
+}
+
+fn ensure_config_dir_at(config_dir: PathBuf) -> Result<PathBuf, ProfileError> {
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir).map_err(ProfileError::IoError)?;
+        tracing::info!("Created config directory: {:?}", config_dir);
+    }
+
+    Ok(config_dir)
+}
+
+/// Profile names by what they match: a window class, a virtual desktop or both
+///
+/// The first profile to claim a match keeps it (see [`find_collisions`]).
+#[derive(Debug, Clone, Default)]
+pub struct ProfileMatcher {
+    by_class_and_desktop: HashMap<(String, u32), String>,
+    by_class: HashMap<String, String>,
+    by_desktop: HashMap<u32, String>,
+}
+
+impl ProfileMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the match of profile `name`; a profile matching neither is skipped
+    pub fn insert(&mut self, window_class: Option<&str>, desktop: Option<u32>, name: &str) {
+        let name = name.to_string();
+        match (window_class, desktop) {
+            (Some(class), Some(desktop)) => self.by_class_and_desktop.entry((class.to_string(), desktop)).or_insert(name),
+            (Some(class), None) => self.by_class.entry(class.to_string()).or_insert(name),
+            (None, Some(desktop)) => self.by_desktop.entry(desktop).or_insert(name),
+            (None, None) => return,
+        };
+    }
+
+    /// Best match for a window on a desktop (`None` when unknown)
+    ///
+    /// Class and desktop together beat the class alone, which beats the
+    /// desktop alone; `None` means the default profile applies.
+    pub fn find(&self, window_class: &str, desktop: Option<u32>) -> Option<&str> {
+        let on_desktop = desktop.and_then(|d| self.by_class_and_desktop.get(&(window_class.to_string(), d)));
+        on_desktop
+            .or_else(|| self.by_class.get(window_class))
+            .or_else(|| desktop.and_then(|d| self.by_desktop.get(&d)))
+            .map(String::as_str)
+    }
+}
+
+/// Profile manager for loading and switching profiles
+#[derive(Debug)]
+pub struct ProfileManager {
+    /// All loaded profiles
+    profiles: HashMap<String, Profile>,
+
+    /// Current active profile name
+    current_profile: String,
+
+    /// Window class / virtual desktop to profile mapping (Story 3.1: Task 3.4)
+    matcher: ProfileMatcher,
+
+    /// Per-application hardware profiles keyed by window resource class (v2)
+    hardware: HashMap<String, HardwareProfile>,
+
+    /// Duplicate names / window classes found at load
+    collisions: Vec<ProfileCollision>,
+
+    /// Content hash of each loaded profile, keyed by name
+    hashes: HashMap<String, ContentHash>,
+
+    /// Content hash of all profiles and hardware profiles together
+    hash: ContentHash,
+
+    /// Config file path (used for future save functionality)
+    #[allow(dead_code)]
+    config_path: PathBuf,
+}
+
+impl ProfileManager {
+    /// Create a new profile manager with default profile
+    pub fn new() -> Self {
+        let mut profiles = HashMap::new();
+        let default_profile = create_default_profile();
+        profiles.insert("default".to_string(), default_profile);
+        let hardware = HashMap::new();
+        let (hashes, hash) = hash_profiles(&profiles, &hardware);
+
+        Self {
+            profiles,
+            current_profile: "default".to_string(),
+            matcher: ProfileMatcher::new(),
+            hardware,
+            collisions: Vec::new(),
+            hashes,
+            hash,
+            config_path: get_profiles_path(),
+        }
+    }
+
+    /// Load profiles from JSON file or create default (Story 3.1: Task 3, 5)
+    ///
+    /// If profiles.json doesn't exist, creates it with default profile.
+    pub fn load_or_create() -> Result<Self, ProfileError> {
+        Self::load_or_create_in(crate::paths::paths())
+    }
+
+    /// [`load_or_create`](Self::load_or_create) with the profiles.json of `paths`
+    pub fn load_or_create_in(paths: &Paths) -> Result<Self, ProfileError> {
+        let config_path = paths.profiles_path();
+
+        // Check if file exists (Task 5.1)
+        if !config_path.exists() {
+            tracing::info!("profiles.json not found, creating default...");
+            // Create default profiles.json (Task 5.2)
+            let manager = Self::create_default_file(paths)?;
+            return Ok(manager);
+        }
+
+        // Load existing file (Task 3.1)
+        Self::load_from_path(&config_path)
+    }
+
+    /// Load profiles from a specific path (Story 3.1: Task 3.1-3.5)
+    ///
+    /// Files over [`MAX_PROFILES_FILE_BYTES`] are refused without being read.
+    pub fn load_from_path(path: &Path) -> Result<Self, ProfileError> {
+        // Task 3.1: Read file
+        let mut content = String::new();
+        crate::theme::open_limited(path, MAX_PROFILES_FILE_BYTES)
+            .and_then(|mut reader| reader.read_to_string(&mut content))
+            .map_err(ProfileError::IoError)?;
+
+        // Migrate older schema versions on the raw document, then deserialize.
+        // Current files are parsed from the text so errors keep their position.
+        let mut doc: serde_json::Value = serde_json::from_str(&content).map_err(ProfileError::ParseError)?;
+        let config: ProfilesConfig = match migration::migrate_document(&mut doc) {
+            MigrationOutcome::Current => serde_json::from_str(&content).map_err(ProfileError::ParseError)?,
+            MigrationOutcome::Migrated { from } => {
+                let config = serde_json::from_value(doc.clone()).map_err(ProfileError::ParseError)?;
+                save_migrated(path, from, &doc);
+                config
+            }
+            MigrationOutcome::TooNew { found } | MigrationOutcome::TooOld { found } => {
+                let backup = match backup_profiles(path, found) {
+                    Ok(backup) => Some(backup),
+                    Err(e) => {
+                        tracing::warn!(path = %path.display(), error = %e, "Failed to back up profiles.json");
+                        None
+                    }
+                };
+                return Err(ProfileError::UnsupportedVersion { found, backup });
+            }
+        };
+
+        // Capture per-app hardware profiles before consuming `config.profiles`.
+        // Keys are normalized to lowercase so lookups are case-insensitive: the
+        // KWin/Hyprland/X11 window-class sources differ in case, and the D-Bus
+        // `ReportActiveWindow` path lowercases the incoming class.
+        let hardware: HashMap<String, HardwareProfile> = config
+            .hardware
+            .iter()
+            .map(|(k, v)| (k.to_lowercase(), v.clone()))
+            .collect();
+
+        // First declared wins for both names and window classes, so the result
+        // never depends on map insertion order.
+        let collisions = find_collisions(&config.profiles);
+        for collision in &collisions {
+            tracing::warn!(
+                kind = ?collision.kind,
+                key = %collision.key,
+                used = %format!("profiles[{}] '{}'", collision.winner, collision.winner_name),
+                ignored = %format!("profiles[{}] '{}'", collision.ignored, collision.ignored_name),
+                "Profile collision in profiles.json - the first declared entry wins"
+            );
+        }
+        let ignored: HashSet<usize> = collisions
+            .iter()
+            .filter(|c| c.kind == CollisionKind::Name)
+            .map(|c| c.ignored)
+            .collect();
+        // Advisory: an unknown target only fails when the action is used
+        for unknown in unknown_switch_targets(&config.profiles) {
+            tracing::warn!(
+                profile = %config.profiles[unknown.profile].name,
+                field = unknown.field,
+                slice = ?unknown.slice,
+                target = %unknown.target,
+                "profile_switch action names an unknown profile"
+            );
+        }
+
+        // Apply `extends` before anything looks at the slots, so the checks
+        // below and the runtime see each profile whole; the file keeps the
+        // sparse form
+        let (resolved, issues) = resolve_inheritance(&config.profiles);
+        for issue in &issues {
+            tracing::warn!(profile = %config.profiles[issue.profile()].name, "Profile {}", issue);
+        }
+
+        // Task 3.3, 3.4: Build profile map and window mappings
+        let mut profiles = HashMap::new();
+        let mut matcher = ProfileMatcher::new();
+        let mut command_count = 0usize;
+
+        for (index, mut profile) in resolved.into_iter().enumerate() {
+            if ignored.contains(&index) {
+                continue;
+            }
+            // Story 3.6: Validate and fix slice count
+            // If profile has wrong number of slices, pad or truncate to slice_count
+            if !is_supported_slice_count(profile.slice_count) {
+                tracing::warn!(
+                    profile = %profile.name,
+                    slice_count = profile.slice_count,
+                    "Unsupported slice_count - using 8 (supported: 4, 6, 8, 12)"
+                );
+                profile.slice_count = SLICE_COUNT;
+            }
+            let found = profile.slices.len();
+            if found != usize::from(profile.slice_count) {
+                tracing::warn!(
+                    profile = %profile.name,
+                    found,
+                    expected = profile.slice_count,
+                    "Profile has incorrect slice count - padding/truncating to slice_count"
+                );
+                profile.slices.resize(profile.slice_count.into(), None);
+            }
+
+            // Story 3.5: Validate icons (warn on invalid, don't fail)
+            for (i, slice) in profile.slices.iter().enumerate() {
+                if let Some(action) = slice {
+                    // Surface shell commands on import so a shared profile can't hide one
+                    if let ActionType::Command(ref cmd) = action.action_type {
+                        command_count += 1;
+                        tracing::warn!(
+                            profile = %profile.name,
+                            slice = i,
+                            command = %cmd,
+                            "Profile contains shell command action"
+                        );
+                    }
+                    if let ActionType::WheelAxis { ref up, ref down } = action.action_type {
+                        for (direction, step) in [("up", up), ("down", down)] {
+                            match step.action_type {
+                                ActionType::Command(ref cmd) => {
+                                    command_count += 1;
+                                    tracing::warn!(
+                                        profile = %profile.name,
+                                        slice = i,
+                                        wheel = direction,
+                                        command = %cmd,
+                                        "Profile contains shell command action"
+                                    );
+                                }
+                                ref other if !other.is_wheel_axis_step() => tracing::warn!(
+                                    profile = %profile.name,
+                                    slice = i,
+                                    wheel = direction,
+                                    kind = other.as_str(),
+                                    "Wheel axis steps must be shortcuts, commands or volume steps - slice will do nothing"
+                                ),
+                                _ => {}
+                            }
+                        }
+                    }
+                    if let Some(ref icon) = action.icon {
+                        if let Some(issue) = crate::label::icon_issue(icon) {
+                            tracing::warn!(
+                                profile = %profile.name,
+                                slice = i,
+                                icon = %icon,
+                                "Slice icon {} - check for a bad copy-paste",
+                                issue
+                            );
+                        } else if !validate_icon_reference(icon) {
+                            tracing::warn!(
+                                profile = %profile.name,
+                                slice = i,
+                                icon = %icon,
+                                "Icon may not be valid - will fall back to default at render time"
+                            );
+                        }
+                    }
+                    if let Some(issue) = action.label.iter().flat_map(|l| l.values()).find_map(crate::label::text_issue) {
+                        tracing::warn!(
+                            profile = %profile.name,
+                            slice = i,
+                            label = ?action.label,
+                            "Slice label {} - check for a bad copy-paste",
+                            issue
+                        );
+                    }
+                }
+            }
+            for (direction, action) in [("left", &profile.thumbwheel_left), ("right", &profile.thumbwheel_right)] {
+                if let Some(Action { action_type: ActionType::Command(ref cmd), .. }) = action {
+                    command_count += 1;
+                    tracing::warn!(
+                        profile = %profile.name,
+                        thumbwheel = direction,
+                        command = %cmd,
+                        "Profile contains shell command action"
+                    );
+                }
+            }
+
+            // Story 3.3: Build window class / desktop mapping for profile matching
+            matcher.insert(profile.window_class.as_deref(), profile.virtual_desktop, &profile.name);
+
+            profiles.insert(profile.name.clone(), profile);
+        }
+
+        // Ensure default profile exists
+        if !profiles.contains_key("default") {
+            profiles.insert("default".to_string(), create_default_profile());
+            tracing::warn!("Default profile missing from config, using built-in default");
+        }
+
+        if command_count > 0 {
+            tracing::warn!(
+                command_count,
+                path = %path.display(),
+                "Imported profiles contain shell command actions - review them or set an execution policy"
+            );
+        }
+
+        tracing::info!(
+            profile_count = profiles.len(),
+            "Loaded profiles from {:?}",
+            path
+        );
+
+        let (hashes, hash) = hash_profiles(&profiles, &hardware);
+        tracing::debug!(hash = %hash, "profiles.json content hash");
+
+        Ok(Self {
+            profiles,
+            current_profile: "default".to_string(),
+            matcher,
+            hardware,
+            collisions,
+            hashes,
+            hash,
+            config_path: path.to_path_buf(),
+        })
+    }
+
+    /// Create default profiles.json file (Story 3.1: Task 4.3, 4.4)
+    fn create_default_file(paths: &Paths) -> Result<Self, ProfileError> {
+        // Ensure directory exists (Task 2.4)
+        ensure_config_dir_at(paths.config_dir.clone())?;
+
+        let config_path = paths.profiles_path();
+        let config = ProfilesConfig::with_default_actions();
+
+        // Write JSON file (Task 4.3)
+        let json = serde_json::to_string_pretty(&config).map_err(ProfileError::ParseError)?;
+
+        fs_util::write_private(&config_path, json).map_err(ProfileError::IoError)?;
+
+        // Log creation (Task 4.4)
+        tracing::info!("Created default profiles.json at {:?}", config_path);
+
+        // Load the newly created config
+        Self::load_from_path(&config_path)
+    }
+
+    /// Duplicate names / window classes found at load (empty when clean)
+    pub fn collisions(&self) -> &[ProfileCollision] {
+        &self.collisions
+    }
+
+    /// Content hash of a loaded profile
+    pub fn profile_hash(&self, name: &str) -> Option<ContentHash> {
+        self.hashes.get(name).copied()
+    }
+
+    /// Content hash of every loaded profile, keyed by name
+    pub fn profile_hashes(&self) -> &HashMap<String, ContentHash> {
+        &self.hashes
+    }
+
+    /// Content hash of all loaded profiles and hardware profiles; equal for
+    /// two loads exactly when nothing they apply differs
+    pub fn content_hash(&self) -> ContentHash {
+        self.hash
+    }
+
+    /// Get profile for a window class (falls back to default)
+    pub fn get_profile_for_window(&self, window_class: &str) -> &Profile {
+        self.get_profile_for_context(window_class, None)
+    }
+
+    /// Get profile for a window class on a virtual desktop (`None` when unknown)
+    ///
+    /// Precedence: class and desktop, then class, then desktop, then default.
+    pub fn get_profile_for_context(&self, window_class: &str, desktop: Option<u32>) -> &Profile {
+        if let Some(profile_name) = self.matcher.find(window_class, desktop) {
+            if let Some(profile) = self.profiles.get(profile_name) {
+                return profile;
+            }
+        }
+        self.profiles
+            .get("default")
+            .expect("Default profile must exist")
+    }
+
+    /// Get the hardware profile for a window class, if one is configured.
+    ///
+    /// Lookup is case-insensitive: the map keys are lowercased at load time, so
+    /// the query is lowercased to match.
+    pub fn get_hardware_profile_for_window(&self, window_class: &str) -> Option<&HardwareProfile> {
+        self.hardware.get(&window_class.to_lowercase())
+    }
+
+    /// Whether any per-app hardware profiles are configured.
+    pub fn has_hardware_profiles(&self) -> bool {
+        !self.hardware.is_empty()
+    }
+
+    /// Clone the (lowercased-key) per-app hardware profile map for sharing with
+    /// the focus-change consumer behind a `SharedHardwareProfiles`.
+    pub fn hardware_profiles(&self) -> HashMap<String, HardwareProfile> {
+        self.hardware.clone()
+    }
+
+    /// Get current active profile
+    pub fn current(&self) -> &Profile {
+        self.profiles
+            .get(&self.current_profile)
+            .expect("Current profile must exist")
+    }
+
+    /// Set current profile by name
+    pub fn set_current(&mut self, name: &str) -> Result<(), ProfileError> {
+        if self.profiles.contains_key(name) {
+            self.current_profile = name.to_string();
+            Ok(())
+        } else {
+            Err(ProfileError::NotFound(name.to_string()))
+        }
+    }
+
+    /// Iterate over all loaded profiles
+    pub fn profiles(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.values()
+    }
+
+    /// Log the risky shortcuts of the loaded profiles under `rules` (the
+    /// `profile_lint` override in config.json)
+    ///
+    /// Advisory only: a risky shortcut in an easy slot never blocks loading.
+    pub fn lint_shortcuts(&self, rules: &ShortcutLintRules) {
+        let mut loaded: Vec<&Profile> = self.profiles.values().collect();
+        loaded.sort_by(|a, b| a.name.cmp(&b.name));
+        for profile in loaded {
+            // Smaller targets: a miss on the clock layout lands on a neighbour
+            for risk in rules.check_crowding(profile) {
+                tracing::warn!(
+                    profile = %risk.profile,
+                    slice = risk.slice,
+                    neighbours = ?risk.neighbours,
+                    shortcut = %risk.keys,
+                    "Risky shortcut ({}) next to other actions on a {}-slice ring; its narrow slices make a near miss likely",
+                    risk.reason,
+                    profile.slice_count
+                );
+            }
+            for risk in rules.check_profile(profile) {
+                tracing::warn!(
+                    profile = %risk.profile,
+                    slice = risk.slice,
+                    keys = %risk.keys,
+                    reason = %risk.reason,
+                    "High-risk shortcut in a prime slice - easy to trigger by accident"
+                );
+            }
+        }
+    }
+
+    /// Get profile count
+    pub fn profile_count(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// Get list of profile names
+    pub fn profile_names(&self) -> Vec<&String> {
+        self.profiles.keys().collect()
+    }
+
+    /// Profile names sorted, the order `next` / `previous` cycle through
+    pub fn sorted_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Profile a `profile_switch` target selects when `from` is in effect
+    ///
+    /// `next` / `previous` wrap around the sorted names; from a profile that
+    /// is not loaded they start at the first / last. Any other target must
+    /// name a loaded profile.
+    pub fn switch_target(&self, from: &str, target: &str) -> Result<&Profile, ProfileError> {
+        let names = self.sorted_names();
+        let position = names.iter().position(|&name| name == from);
+        let name = match target {
+            SWITCH_NEXT => match position {
+                Some(i) => names[(i + 1) % names.len()],
+                None => names[0],
+            },
+            SWITCH_PREVIOUS => match position {
+                Some(i) => names[(i + names.len() - 1) % names.len()],
+                None => names[names.len() - 1],
+            },
+            name => name,
+        };
+        self.profiles
+            .get(name)
+            .ok_or_else(|| ProfileError::NotFound(name.to_string()))
+    }
+}
+
+impl Default for ProfileManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-profile hashes and the hash of the whole set
+fn hash_profiles(
+    profiles: &HashMap<String, Profile>,
+    hardware: &HashMap<String, HardwareProfile>,
+) -> (HashMap<String, ContentHash>, ContentHash) {
+    let hashes = profiles
+        .iter()
+        .map(|(name, profile)| (name.clone(), ContentHash::of(profile)))
+        .collect();
+    (hashes, ContentHash::of(&(profiles, hardware)))
+}
+
+/// Re-read profiles.json and return its (lowercased-key) hardware profile map.
+///
+/// Used by the `ReloadConfig` D-Bus path to refresh the shared hardware map
+/// after the settings UI saves. A read/parse failure yields an empty map rather
+/// than an error, so a transient bad file never breaks the running daemon.
+pub fn load_hardware_profiles() -> HashMap<String, HardwareProfile> {
+    match ProfileManager::load_or_create() {
+        Ok(manager) => manager.hardware_profiles(),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to reload hardware profiles; keeping empty map");
+            HashMap::new()
+        }
+    }
+}
+
+/// Copy profiles.json to `profiles.json.bak-<version>` next to it
+fn backup_profiles(path: &Path, version: u32) -> std::io::Result<PathBuf> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak-{}", version));
+    let backup = path.with_file_name(name);
+    fs_util::write_private(&backup, fs::read(path)?)?;
+    Ok(backup)
+}
+
+/// Back up a file migrated from `from` and write the migrated document over it
+///
+/// The migrated config is used either way; a failure only means the file is
+/// migrated again on the next load.
+fn save_migrated(path: &Path, from: u32, doc: &serde_json::Value) {
+    let saved = backup_profiles(path, from).and_then(|backup| {
+        let json = serde_json::to_string_pretty(doc)?;
+        fs_util::write_private(path, json)?;
+        Ok(backup)
+    });
+    match saved {
+        Ok(backup) => tracing::warn!(
+            path = %path.display(),
+            backup = %backup.display(),
+            from,
+            to = SCHEMA_VERSION,
+            "Migrated profiles.json to the current schema; the original was backed up"
+        ),
+        Err(e) => tracing::warn!(
+            path = %path.display(),
+            from,
+            to = SCHEMA_VERSION,
+            error = %e,
+            "Migrated profiles.json in memory but could not rewrite it"
+        ),
+    }
+}
+
+/// Profile error type
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ProfileError {
+    /// Profile not found
+    #[error("Profile not found: {0}")]
+    NotFound(String),
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(std::io::Error),
+    /// JSON parse error
+    #[error("JSON parse error: {0}")]
+    ParseError(serde_json::Error),
+    /// Validation error
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+    /// profiles.json declares a schema version this build cannot read
+    #[error("{}", unsupported_version_message(*.found, .backup.as_deref()))]
+    UnsupportedVersion {
+        /// Version the file declares
+        found: u32,
+        /// Where the file was copied before giving up, if the copy worked
+        backup: Option<PathBuf>,
+    },
+}
+
+/// Display text for `ProfileError::UnsupportedVersion`
+fn unsupported_version_message(found: u32, backup: Option<&Path>) -> String {
+    let mut message = format!(
+        "profiles.json schema version {} is not supported (this build reads {} to {})",
+        found, MIN_SCHEMA_VERSION, SCHEMA_VERSION
+    );
+    if found > SCHEMA_VERSION {
+        message.push_str("; it was written by a newer JuhRadial MX");
+    }
+    if let Some(backup) = backup {
+        message.push_str(&format!("; the file was backed up to {}", backup.display()));
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::PathsEnv;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_v1_config_migrates_and_defaults_hardware() {
+        // A v1 file has no `hardware` map; it must load, default to empty, and
+        // be stamped to the current schema version.
+        let v1 = r#"{
+            "version": 1,
+            "profiles": [ {
+                "name": "default",
+                "slices": [null, null, null, null, null, null, null, null]
+            } ]
+        }"#;
+        let mut config: ProfilesConfig = serde_json::from_str(v1).unwrap();
+        assert!(config.hardware.is_empty());
+        config.migrate();
+        assert_eq!(config.version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_load_v1_file_backs_up_and_rewrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let v1 = r#"{
+            "version": 1,
+            "profiles": [
+                {"name": "default", "slices": [null, null, null]},
+                {"name": "code", "window_class": "code", "slices": [null, null, null, null, null, null, null, null, null]}
+            ]
+        }"#;
+        fs::write(&config_path, v1).unwrap();
+
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        assert_eq!(manager.get_profile_for_window("code").name, "code");
+        assert_eq!(fs::read_to_string(temp_dir.path().join("profiles.json.bak-1")).unwrap(), v1);
+        let rewritten: ProfilesConfig = serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(rewritten.version, SCHEMA_VERSION);
+        assert_eq!(rewritten.profiles.len(), 2);
+    }
+
+    #[test]
+    fn test_oversized_profiles_file_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        fs::File::create(&config_path)
+            .unwrap()
+            .set_len(MAX_PROFILES_FILE_BYTES + 1)
+            .unwrap();
+
+        let err = ProfileManager::load_from_path(&config_path).unwrap_err();
+        assert!(matches!(err, ProfileError::IoError(ref e) if e.kind() == std::io::ErrorKind::FileTooLarge));
+    }
+
+    #[test]
+    fn test_load_newer_version_is_refused_with_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let newer = format!(
+            r#"{{"version": {}, "profiles": [{{"name": "default", "layers": []}}]}}"#,
+            SCHEMA_VERSION + 1
+        );
+        fs::write(&config_path, &newer).unwrap();
+
+        let err = ProfileManager::load_from_path(&config_path).unwrap_err();
+        let backup = temp_dir.path().join(format!("profiles.json.bak-{}", SCHEMA_VERSION + 1));
+        assert!(matches!(
+            err,
+            ProfileError::UnsupportedVersion { found, backup: Some(ref b) } if found == SCHEMA_VERSION + 1 && *b == backup
+        ));
+        assert!(err.to_string().contains("newer JuhRadial MX"));
+        // The original is left alone for the newer build
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), newer);
+        assert_eq!(fs::read_to_string(&backup).unwrap(), newer);
+    }
+
+    #[test]
+    fn test_load_flat_ui_written_profiles_json() {
+        // The GTK settings UI writes a FLAT shape with NO top-level
+        // `version`/`profiles`: per-app keys plus a `hardware` map. It must load
+        // without a parse error, default to the built-in default profile, and
+        // still expose the hardware map (with case-insensitive keys).
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let flat = r#"{
+            "default": { "name": "default", "app_class": "default", "slices": [] },
+            "Firefox": { "name": "Firefox", "app_class": "Firefox", "slices": [] },
+            "hardware": {
+                "Firefox": { "dpi": 1200, "thumbwheel": "scroll" }
+            }
+        }"#;
+        fs::write(&config_path, flat).unwrap();
+
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        // Built-in default profile is always present.
+        assert_eq!(manager.current().name, "default");
+        // Hardware map loaded; lookup is case-insensitive (queried lowercase).
+        let hw = manager
+            .get_hardware_profile_for_window("firefox")
+            .expect("hardware profile for firefox must load from flat UI file");
+        assert_eq!(hw.dpi, Some(1200));
+        assert_eq!(hw.thumbwheel, Some(ThumbwheelMode::Scroll));
+        // Original-case query also resolves.
+        assert!(manager.get_hardware_profile_for_window("Firefox").is_some());
+    }
+
+    #[test]
+    fn test_hardware_profile_roundtrip() {
+        let mut config = ProfilesConfig::with_default_actions();
+        config.hardware.insert(
+            "blender".to_string(),
+            HardwareProfile {
+                dpi: Some(1600),
+                smartshift: Some(SmartshiftSetting { enabled: true, threshold: 40 }),
+                hires: Some(true),
+                thumbwheel: Some(ThumbwheelMode::Zoom),
+                buttons: HashMap::new(),
+            },
+        );
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: ProfilesConfig = serde_json::from_str(&json).unwrap();
+        let hw = parsed.hardware.get("blender").unwrap();
+        assert_eq!(hw.dpi, Some(1600));
+        assert_eq!(hw.thumbwheel, Some(ThumbwheelMode::Zoom));
+        assert_eq!(hw.smartshift, Some(SmartshiftSetting { enabled: true, threshold: 40 }));
+    }
+
+    // Task 6.1: Test ProfilesConfig serialization/deserialization
+    #[test]
+    fn test_profiles_config_serialization() {
+        let config = ProfilesConfig::with_default_actions();
+
+        // Serialize
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        assert!(json.contains(&format!("\"version\": {}", SCHEMA_VERSION)));
+        assert!(json.contains("\"name\": \"default\""));
+        assert!(json.contains("\"slices\""));
+
+        // Deserialize
+        let parsed: ProfilesConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.version, SCHEMA_VERSION);
+        assert_eq!(parsed.profiles.len(), 2);
+        assert_eq!(parsed.profiles[0].name, "default");
+        assert_eq!(parsed.profiles[1].name, "default-left");
+    }
+
+    #[test]
+    fn test_profile_serialization() {
+        let profile = create_default_profile();
+        let json = serde_json::to_string(&profile).unwrap();
+
+        // Verify required fields
+        assert!(json.contains("\"name\":\"default\""));
+        assert!(json.contains("\"slices\""));
+
+        // Deserialize back
+        let parsed: Profile = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.name, "default");
+        assert_eq!(parsed.slices.len(), 8);
+    }
+
+    // Task 6.2: Test default profile creation
+    #[test]
+    fn test_create_default_profile() {
+        let profile = create_default_profile();
+
+        assert_eq!(profile.name, "default");
+        assert!(profile.window_class.is_none());
+        assert_eq!(profile.slices.len(), 8);
+        assert_eq!(profile.icon, Some("🎯".to_string()));
+
+        // All slices should have actions
+        for (i, slice) in profile.slices.iter().enumerate() {
+            assert!(slice.is_some(), "Slice {} should have an action", i);
+        }
+
+        // Verify first slice is Copy (ctrl+c)
+        let first_action = profile.slices[0].as_ref().unwrap();
+        assert_eq!(first_action.label, Some("Copy".into()));
+    }
+
+    #[test]
+    fn test_default_left_profile_mirrors_default() {
+        let default = create_default_profile();
+        let left = create_default_left_profile();
+        assert_eq!(left.name, "default-left");
+        assert!(left.window_class.is_none());
+        let label = |p: &Profile, i: usize| p.slices[i].as_ref().unwrap().label.clone();
+        assert_eq!(label(&left, direction::NORTH), label(&default, direction::NORTH));
+        assert_eq!(label(&left, direction::SOUTH), label(&default, direction::SOUTH));
+        assert_eq!(label(&left, direction::NORTH_WEST), label(&default, direction::NORTH_EAST));
+        assert_eq!(label(&left, direction::EAST), label(&default, direction::WEST));
+        assert_eq!(label(&left, direction::SOUTH_WEST), label(&default, direction::SOUTH_EAST));
+    }
+
+    // Task 6.3: Test load from valid JSON file
+    #[test]
+    fn test_load_from_valid_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        // Write a valid config
+        let config = ProfilesConfig::with_default_actions();
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        fs::write(&config_path, json).unwrap();
+
+        // Load it
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        // default + the bundled default-left example
+        assert_eq!(manager.profile_count(), 2);
+        assert_eq!(manager.current().name, "default");
+    }
+
+    #[test]
+    fn test_load_with_multiple_profiles() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        // Create config with multiple profiles
+        let mut config = ProfilesConfig::with_default_actions();
+        let mut firefox_profile = create_default_profile();
+        firefox_profile.name = "firefox".to_string();
+        firefox_profile.window_class = Some("firefox".to_string());
+        firefox_profile.icon = Some("🦊".to_string());
+        config.profiles.push(firefox_profile);
+
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        fs::write(&config_path, json).unwrap();
+
+        // Load and verify
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        assert_eq!(manager.profile_count(), 3);
+
+        // Test window class lookup
+        let firefox = manager.get_profile_for_window("firefox");
+        assert_eq!(firefox.name, "firefox");
+
+        // Unknown window class should fall back to default
+        let unknown = manager.get_profile_for_window("unknown-app");
+        assert_eq!(unknown.name, "default");
+    }
+
+    #[test]
+    fn test_load_resolves_name_and_window_class_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let slices = "[null, null, null, null, null, null, null, null]";
+        fs::write(
+            &config_path,
+            format!(
+                r#"{{"profiles": [
+                    {{"name": "default", "slices": {s}}},
+                    {{"name": "firefox", "window_class": "firefox", "icon": "first", "slices": {s}}},
+                    {{"name": "firefox", "window_class": "firefox-dev", "icon": "second", "slices": {s}}},
+                    {{"name": "browser", "window_class": "firefox", "slices": {s}}}
+                ]}}"#,
+                s = slices
+            ),
+        )
+        .unwrap();
+
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        let collisions = manager.collisions();
+        assert_eq!(collisions.len(), 2);
+        assert_eq!(collisions[0].kind, CollisionKind::Name);
+        assert_eq!((collisions[0].winner, collisions[0].ignored), (1, 2));
+        assert_eq!(collisions[1].kind, CollisionKind::WindowClass);
+        assert_eq!(collisions[1].key, "firefox");
+        assert_eq!(collisions[1].winner_name, "firefox");
+        assert_eq!(collisions[1].ignored_name, "browser");
+
+        // First declared wins both ways; the ignored duplicate's class is unmapped
+        let firefox = manager.get_profile_for_window("firefox");
+        assert_eq!(firefox.icon.as_deref(), Some("first"));
+        assert_eq!(manager.get_profile_for_window("firefox-dev").name, "default");
+        assert_eq!(manager.profile_count(), 3);
+    }
+
+    #[test]
+    fn test_no_collisions_for_distinct_profiles() {
+        let mut vscode = create_default_profile();
+        vscode.name = "vscode".to_string();
+        vscode.window_class = Some("code".to_string());
+        let profiles = [create_default_profile(), create_default_left_profile(), vscode];
+        assert!(find_collisions(&profiles).is_empty());
+    }
+
+    fn desktop_manager(temp_dir: &TempDir) -> ProfileManager {
+        let config_path = temp_dir.path().join("profiles.json");
+        let slices = "[null, null, null, null, null, null, null, null]";
+        fs::write(
+            &config_path,
+            format!(
+                r#"{{"profiles": [
+                    {{"name": "default", "slices": {s}}},
+                    {{"name": "comms", "virtual_desktop": 1, "slices": {s}}},
+                    {{"name": "code", "virtual_desktop": 2, "slices": {s}}},
+                    {{"name": "firefox", "window_class": "firefox", "slices": {s}}},
+                    {{"name": "firefox-code", "window_class": "firefox", "virtual_desktop": 2, "slices": {s}}},
+                    {{"name": "konsole-comms", "window_class": "konsole", "virtual_desktop": 1, "slices": {s}}}
+                ]}}"#,
+                s = slices
+            ),
+        )
+        .unwrap();
+        ProfileManager::load_from_path(&config_path).unwrap()
+    }
+
+    #[test]
+    fn test_context_precedence_class_and_desktop_then_class_then_desktop() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = desktop_manager(&temp_dir);
+        let name = |class: &str, desktop: Option<u32>| manager.get_profile_for_context(class, desktop).name.clone();
+
+        // Class and desktop together beat the class alone
+        assert_eq!(name("firefox", Some(2)), "firefox-code");
+        // The class alone beats the desktop alone
+        assert_eq!(name("firefox", Some(1)), "firefox");
+        assert_eq!(name("firefox", Some(3)), "firefox");
+        assert_eq!(name("firefox", None), "firefox");
+        // The desktop alone beats the default
+        assert_eq!(name("dolphin", Some(1)), "comms");
+        assert_eq!(name("dolphin", Some(2)), "code");
+        assert_eq!(name("konsole", Some(1)), "konsole-comms");
+        // A class+desktop profile never matches on its class alone
+        assert_eq!(name("konsole", Some(2)), "code");
+        assert_eq!(name("konsole", None), "default");
+        // Nothing matches: default
+        assert_eq!(name("dolphin", Some(3)), "default");
+        assert_eq!(name("dolphin", None), "default");
+        assert_eq!(manager.get_profile_for_window("firefox").name, "firefox");
+    }
+
+    #[test]
+    fn test_profile_matcher_keeps_the_first_claim() {
+        let mut matcher = ProfileMatcher::new();
+        matcher.insert(None, Some(2), "code");
+        matcher.insert(None, Some(2), "also-code");
+        matcher.insert(Some("gimp"), Some(2), "gimp-code");
+        matcher.insert(Some("gimp"), Some(2), "gimp-other");
+        matcher.insert(None, None, "default");
+        assert_eq!(matcher.find("gimp", Some(2)), Some("gimp-code"));
+        assert_eq!(matcher.find("krita", Some(2)), Some("code"));
+        assert_eq!(matcher.find("gimp", None), None);
+
+        let mut code = create_default_profile();
+        code.name = "code".to_string();
+        code.virtual_desktop = Some(2);
+        let mut gimp = code.clone();
+        gimp.name = "gimp".to_string();
+        gimp.window_class = Some("gimp".to_string());
+        let mut also_code = code.clone();
+        also_code.name = "also-code".to_string();
+        let mut gimp_anywhere = gimp.clone();
+        gimp_anywhere.name = "gimp-anywhere".to_string();
+        gimp_anywhere.virtual_desktop = None;
+        let collisions = find_collisions(&[code, gimp, also_code, gimp_anywhere]);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].kind, CollisionKind::VirtualDesktop);
+        assert_eq!(collisions[0].key, "desktop 2");
+        assert_eq!((collisions[0].winner, collisions[0].ignored), (0, 2));
+    }
+
+    #[test]
+    fn test_virtual_desktop_is_optional_in_json() {
+        let profile: Profile =
+            serde_json::from_str(r#"{"name": "code", "window_class": "code", "slices": [null, null, null, null, null, null, null, null]}"#)
+                .unwrap();
+        assert_eq!(profile.virtual_desktop, None);
+        let json = serde_json::to_value(&profile).unwrap();
+        assert!(json.get("virtual_desktop").is_none());
+
+        let mut on_desktop = profile;
+        on_desktop.virtual_desktop = Some(3);
+        let json = serde_json::to_string(&on_desktop).unwrap();
+        let back: Profile = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.virtual_desktop, Some(3));
+    }
+
+    #[test]
+    fn test_content_hash_ignores_formatting_and_tracks_edits() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("profiles.json");
+        let mut config = ProfilesConfig::with_default_actions();
+        fs::write(&path, serde_json::to_string_pretty(&config).unwrap()).unwrap();
+        let pretty = ProfileManager::load_from_path(&path).unwrap();
+        fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+        let compact = ProfileManager::load_from_path(&path).unwrap();
+        assert_eq!(pretty.content_hash(), compact.content_hash());
+        assert_eq!(pretty.profile_hashes(), compact.profile_hashes());
+
+        config.profiles[0].slices[0] = None;
+        config.hardware.insert("Firefox".to_string(), HardwareProfile { dpi: Some(1200), ..Default::default() });
+        fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+        let edited = ProfileManager::load_from_path(&path).unwrap();
+        assert_ne!(edited.content_hash(), compact.content_hash());
+        assert_ne!(edited.profile_hash("default"), compact.profile_hash("default"));
+        assert_eq!(edited.profile_hash(DEFAULT_LEFT_PROFILE_NAME), compact.profile_hash(DEFAULT_LEFT_PROFILE_NAME));
+        assert_eq!(edited.profile_hash("missing"), None);
+    }
+
+    fn named<'a>(manager: &'a ProfileManager, name: &str) -> &'a Profile {
+        manager.profiles().find(|p| p.name == name).unwrap()
+    }
+
+    fn shortcut(action: &Option<Action>) -> Option<&str> {
+        match action {
+            Some(Action { action_type: ActionType::Shortcut(ref keys), .. }) => Some(keys),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_extends_inherits_unset_slots_and_follows_parent_edits() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("profiles.json");
+        let write = |parent_n: &str| {
+            let json = format!(
+                r#"{{"profiles": [
+                    {{"name": "default", "slices": [
+                        {{"type": "shortcut", "value": "{parent_n}"}}, {{"type": "shortcut", "value": "ctrl+c"}},
+                        {{"type": "shortcut", "value": "ctrl+v"}}, null, null, null, null, null
+                    ], "center": {{"type": "shortcut", "value": "super"}}, "icon": "🏠"}},
+                    {{"name": "code", "window_class": "code", "extends": "default",
+                      "slices": [null, {{"type": "shortcut", "value": "ctrl+shift+p"}}, {{"type": "none"}}]}},
+                    {{"name": "code-debug", "window_class": "code-debug", "extends": "code",
+                      "slices": [null, null, null, {{"type": "shortcut", "value": "f5"}}], "icon": "🐞"}}
+                ]}}"#
+            );
+            fs::write(&path, json).unwrap();
+        };
+        write("ctrl+z");
+        let manager = ProfileManager::load_from_path(&path).unwrap();
+        let code = named(&manager, "code");
+        assert_eq!(code.slices.len(), 8);
+        assert_eq!(shortcut(&code.slices[0]), Some("ctrl+z"));
+        assert_eq!(shortcut(&code.slices[1]), Some("ctrl+shift+p"));
+        // {"type": "none"} empties the slot instead of inheriting ctrl+v
+        assert!(matches!(code.slices[2], Some(Action { action_type: ActionType::None, .. })));
+        assert!(code.center.is_some());
+        assert_eq!(code.icon.as_deref(), Some("🏠"));
+        assert_eq!(code.window_class.as_deref(), Some("code"));
+        // Chains resolve through the middle profile
+        let debug = named(&manager, "code-debug");
+        assert_eq!(shortcut(&debug.slices[0]), Some("ctrl+z"));
+        assert_eq!(shortcut(&debug.slices[1]), Some("ctrl+shift+p"));
+        assert_eq!(shortcut(&debug.slices[3]), Some("f5"));
+        assert_eq!(debug.icon.as_deref(), Some("🐞"));
+        let before = manager.profile_hash("code-debug");
+
+        // Editing the parent and reloading updates every child
+        write("ctrl+y");
+        let manager = ProfileManager::load_from_path(&path).unwrap();
+        assert_eq!(shortcut(&named(&manager, "code").slices[0]), Some("ctrl+y"));
+        assert_eq!(shortcut(&named(&manager, "code-debug").slices[0]), Some("ctrl+y"));
+        assert_ne!(manager.profile_hash("code-debug"), before);
+    }
+
+    #[test]
+    fn test_extends_cycles_and_missing_parents() {
+        let profile = |name: &str, extends: Option<&str>| Profile {
+            name: name.to_string(),
+            extends: extends.map(str::to_string),
+            slices: vec![
+                Some(Action {
+                    action_type: ActionType::Shortcut(format!("{name}+x")),
+                    label: None,
+                    icon: None,
+                    description: None,
+                    require_confirmation: false,
+                    cooldown_ms: 0,
+                }),
+                None,
+            ],
+            ..Profile::default()
+        };
+        let profiles = [
+            profile("a", Some("b")),
+            profile("b", Some("a")),
+            profile("c", Some("a")),
+            profile("lost", Some("gone")),
+            profile("d", Some("lost")),
+            profile("self", Some("self")),
+        ];
+        let (resolved, issues) = resolve_inheritance(&profiles);
+        assert_eq!(
+            issues,
+            [
+                InheritanceIssue::Cycle { profile: 0, chain: vec!["a".into(), "b".into(), "a".into()] },
+                InheritanceIssue::Cycle { profile: 1, chain: vec!["b".into(), "a".into(), "b".into()] },
+                InheritanceIssue::Cycle { profile: 2, chain: vec!["c".into(), "a".into(), "b".into(), "a".into()] },
+                InheritanceIssue::MissingParent { profile: 3, parent: "gone".into() },
+                InheritanceIssue::Cycle { profile: 5, chain: vec!["self".into(), "self".into()] },
+            ]
+        );
+        assert!(issues[3].to_string().contains("'gone'"));
+        // Profiles in or behind a loop keep only their own slots
+        assert_eq!(shortcut(&resolved[2].slices[0]), Some("c+x"));
+        assert!(resolved[2].slices[1].is_none());
+        // A missing grandparent still lets the parent's slots through
+        assert_eq!(shortcut(&resolved[4].slices[0]), Some("d+x"));
+        assert_eq!(resolved[4].slices.len(), 8);
+    }
+
+    #[test]
+    fn test_extends_keeps_the_sparse_form_on_disk() {
+        let json = r#"{"version": 2, "profiles": [
+            {"name": "default", "slices": [{"type": "shortcut", "value": "ctrl+c"}, null, null, null, null, null, null, null]},
+            {"name": "gimp", "window_class": "gimp", "extends": "default", "slices": [null, {"type": "none"}]}
+        ]}"#;
+        let config: ProfilesConfig = serde_json::from_str(json).unwrap();
+        let saved: serde_json::Value = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["profiles"][1]["extends"], "default");
+        assert_eq!(saved["profiles"][1]["slices"], serde_json::json!([null, {"type": "none"}]));
+        assert!(saved["profiles"][0].get("extends").is_none());
+    }
+
+    #[test]
+    fn test_switch_target_cycles_sorted_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let mut config = ProfilesConfig::new();
+        config.profiles = vec![create_default_profile()];
+        for name in ["zed", "blender"] {
+            let mut profile = create_default_profile();
+            profile.name = name.to_string();
+            config.profiles.push(profile);
+        }
+        fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+
+        assert_eq!(manager.sorted_names(), ["blender", "default", "zed"]);
+        assert_eq!(manager.switch_target("blender", SWITCH_NEXT).unwrap().name, "default");
+        assert_eq!(manager.switch_target("zed", SWITCH_NEXT).unwrap().name, "blender");
+        assert_eq!(manager.switch_target("blender", SWITCH_PREVIOUS).unwrap().name, "zed");
+        assert_eq!(manager.switch_target("gone", SWITCH_NEXT).unwrap().name, "blender");
+        assert_eq!(manager.switch_target("gone", SWITCH_PREVIOUS).unwrap().name, "zed");
+        assert_eq!(manager.switch_target("default", "zed").unwrap().name, "zed");
+        assert!(matches!(
+            manager.switch_target("default", "gimp"),
+            Err(ProfileError::NotFound(name)) if name == "gimp"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_switch_targets() {
+        let mut profile = create_default_profile();
+        profile.slices[2] = Some(Action {
+            action_type: ActionType::ProfileSwitch("gimp".to_string()),
+            label: None,
+            icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        });
+        profile.thumbwheel_right = Some(Action {
+            action_type: ActionType::ProfileSwitch(SWITCH_NEXT.to_string()),
+            label: None,
+            icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        });
+        let unknown = unknown_switch_targets(&[profile]);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!((unknown[0].field, unknown[0].slice), ("slices", Some(2)));
+        assert_eq!(unknown[0].target, "gimp");
+    }
+
+    // Task 6.4: Test load failure on malformed JSON
+    #[test]
+    fn test_load_malformed_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        // Write invalid JSON
+        fs::write(&config_path, "{ invalid json }").unwrap();
+
+        // Load should fail
+        let result = ProfileManager::load_from_path(&config_path);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ProfileError::ParseError(_)));
+    }
+
+    // Story 3.6: Test that wrong slice count is padded, not rejected
+    #[test]
+    fn test_load_wrong_slice_count_pads() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        let config = ProfilesConfig::with_default_actions();
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        fs::write(&config_path, json).unwrap();
+
+        let result = ProfileManager::load_from_path(&config_path);
+        assert!(result.is_ok());
+        let manager = result.unwrap();
+        assert_eq!(manager.current().slices.len(), 8);
+
+        // Short and long lists, and an unsupported slice_count
+        fs::write(
+            &config_path,
+            r#"{"version": 2, "profiles": [
+                {"name": "default", "slices": [{"type": "shortcut", "value": "ctrl+c"}, null, null]},
+                {"name": "long", "window_class": "long", "slices": [null, null, null, null, null, null, null, null, null]},
+                {"name": "ten", "window_class": "ten", "slice_count": 10, "slices": []}
+            ]}"#,
+        )
+        .unwrap();
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        assert_eq!(manager.current().slices.len(), 8);
+        assert!(manager.current().slices[0].is_some());
+        assert_eq!(manager.get_profile_for_window("long").slices.len(), 8);
+        let ten = manager.get_profile_for_window("ten");
+        assert_eq!((ten.slice_count, ten.slices.len()), (8, 8));
+    }
+
+    #[test]
+    fn test_switching_to_the_clock_layout_keeps_the_eight_slots() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+        let mut config = ProfilesConfig::with_default_actions();
+        let eight = config.profiles[0].clone();
+
+        // Edited by hand: slice_count raised, the eight slices left as they were
+        let mut doc = serde_json::to_value(&config).unwrap();
+        doc["profiles"][0]["slice_count"] = 12.into();
+        fs::write(&config_path, doc.to_string()).unwrap();
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+        let clock = manager.current();
+        assert_eq!((clock.slice_count, clock.slices.len()), (12, 12));
+        assert_eq!(serde_json::to_value(&clock.slices[..8]).unwrap(), serde_json::to_value(&eight.slices).unwrap());
+        assert!(clock.slices[8..].iter().all(Option::is_none));
+
+        // The same through the API, and back down
+        let profile = &mut config.profiles[0];
+        profile.set_slice_count(12).unwrap();
+        assert_eq!(profile.slices.len(), 12);
+        assert!(profile.set_slice_count(7).is_err());
+        assert_eq!(profile.slice_count, 12);
+        profile.set_slice_count(4).unwrap();
+        assert_eq!(serde_json::to_value(&profile.slices).unwrap(), serde_json::to_value(&eight.slices[..4]).unwrap());
+
+        // Eight-slice profiles serialize as before, without slice_count
+        assert!(serde_json::to_value(&eight).unwrap().get("slice_count").is_none());
+        assert_eq!(serde_json::to_value(&*profile).unwrap()["slice_count"], 4);
+    }
+
+    // Story 3.5: Test icon validation
+    #[test]
+    fn test_validate_icon_reference() {
+        // Valid emoji (supplementary range)
+        assert!(validate_icon_reference("📋"));
+        assert!(validate_icon_reference("🎯"));
+
+        // Valid emoji (arrows, dingbats - previously failing)
+        assert!(validate_icon_reference("↩️"));
+        assert!(validate_icon_reference("↪️"));
+        assert!(validate_icon_reference("✂️"));
+        assert!(validate_icon_reference("❌"));
+
+        // Valid file paths
+        assert!(validate_icon_reference("/path/to/icon.png"));
+        assert!(validate_icon_reference("icons/copy.svg"));
+        assert!(validate_icon_reference("C:\\icons\\paste.ico"));
+
+        // Valid system icon names
+        assert!(validate_icon_reference("edit-copy"));
+        assert!(validate_icon_reference("document_save"));
+        assert!(validate_icon_reference("icon123"));
+
+        // Invalid
+        assert!(!validate_icon_reference(""));
+        assert!(!validate_icon_reference("has space.txt"));
+    }
+
+    // Story 3.3: Test window class to profile matching
+    #[test]
+    fn test_window_class_to_profile_matching() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        // Create config with app-specific profile
+        let mut config = ProfilesConfig::with_default_actions();
+        let mut vscode = create_default_profile();
+        vscode.name = "vscode".to_string();
+        vscode.window_class = Some("code".to_string());
+        config.profiles.push(vscode);
+
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        fs::write(&config_path, json).unwrap();
+
+        let manager = ProfileManager::load_from_path(&config_path).unwrap();
+
+        // Match known window class
+        let profile = manager.get_profile_for_window("code");
+        assert_eq!(profile.name, "vscode");
+
+        // Fallback to default for unknown
+        let profile = manager.get_profile_for_window("unknown-app");
+        assert_eq!(profile.name, "default");
+    }
+
+    // Story 3.4: Test default profile fallback
+    #[test]
+    fn test_default_profile_fallback() {
+        // ProfileManager::new() should always have default profile
+        let manager = ProfileManager::new();
+        let default = manager.get_profile_for_window("any-unknown-app");
+        assert_eq!(default.name, "default");
+        assert_eq!(default.slices.len(), 8);
+        // All slices should have actions
+        for slice in &default.slices {
+            assert!(slice.is_some());
+        }
+    }
+
+    // Task 6.5: Test config directory creation
+    #[test]
+    fn test_config_dir_functions() {
+        // Test get_config_dir returns valid path
+        let config_dir = get_config_dir();
+        assert!(config_dir.to_string_lossy().contains("juhradial"));
+
+        // Test get_profiles_path
+        let profiles_path = get_profiles_path();
+        assert!(profiles_path.to_string_lossy().ends_with("profiles.json"));
+    }
+
+    #[test]
+    fn test_ensure_config_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_dir = temp_dir.path().join(crate::paths::APP_DIR_NAME);
+
+        let result = ensure_config_dir_at(config_dir.clone());
+        assert!(result.is_ok(), "ensure_config_dir should succeed");
+
+        let returned_path = result.unwrap();
+        assert!(
+            returned_path.exists(),
+            "Config directory should exist after ensure_config_dir"
+        );
+        assert_eq!(returned_path, config_dir);
+    }
+
+    #[test]
+    fn test_default_profile_manager() {
+        let manager = ProfileManager::new();
+        assert_eq!(manager.profile_count(), 1);
+        assert_eq!(manager.current().name, "default");
+    }
+
+    #[test]
+    fn test_set_current_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("profiles.json");
+
+        // Create config with two profiles
+        let mut config = ProfilesConfig::with_default_actions();
+        let mut second = create_default_profile();
+        second.name = "second".to_string();
+        config.profiles.push(second);
+
+        let json = serde_json::to_string_pretty(&config).unwrap();
+        fs::write(&config_path, json).unwrap();
+
+        let mut manager = ProfileManager::load_from_path(&config_path).unwrap();
+
+        // Switch to second profile
+        assert!(manager.set_current("second").is_ok());
+        assert_eq!(manager.current().name, "second");
+
+        // Try to switch to non-existent profile
+        assert!(manager.set_current("nonexistent").is_err());
+    }
+
+    #[test]
+    fn test_direction_constants() {
+        assert_eq!(direction::NORTH, 0);
+        assert_eq!(direction::NORTH_EAST, 1);
+        assert_eq!(direction::EAST, 2);
+        assert_eq!(direction::SOUTH_EAST, 3);
+        assert_eq!(direction::SOUTH, 4);
+        assert_eq!(direction::SOUTH_WEST, 5);
+        assert_eq!(direction::WEST, 6);
+        assert_eq!(direction::NORTH_WEST, 7);
+    }
+
+    #[test]
+    fn test_profile_error_display() {
+        let err = ProfileError::NotFound("test".to_string());
+        assert!(format!("{}", err).contains("test"));
+
+        let err = ProfileError::ValidationError("invalid".to_string());
+        assert!(format!("{}", err).contains("invalid"));
+    }
+
+    #[test]
+    fn test_xdg_config_home() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = Paths::resolve(&PathsEnv {
+            home: Some("/nonexistent-home".into()),
+            xdg_config_home: Some(temp_dir.path().into()),
+            ..Default::default()
+        });
+        assert!(paths.config_dir.starts_with(temp_dir.path()));
+
+        // The default profiles.json is created under XDG_CONFIG_HOME
+        let manager = ProfileManager::load_or_create_in(&paths).unwrap();
+        assert_eq!(manager.config_path, temp_dir.path().join("juhradial/profiles.json"));
+        assert!(manager.config_path.is_file());
+        assert!(ProfileManager::load_or_create_in(&paths).is_ok());
+    }
+
+    // ------------------------------------------------------------------------
+    // Property tests: hand-edited profiles.json must never panic the daemon
+    // ------------------------------------------------------------------------
+
+    use crate::test_support::{arb_json, set_path};
+    use proptest::prelude::*;
+
+    /// A slice entry: null, a well-formed action, or arbitrary JSON
+    fn arb_slice() -> impl Strategy<Value = serde_json::Value> {
+        prop_oneof![
+            Just(serde_json::Value::Null),
+            (
+                prop_oneof![Just("shortcut"), Just("command"), Just("kwin"), Just("none"), Just("bogus")],
+                arb_json(),
+                arb_json(),
+            )
+                .prop_map(|(kind, value, icon)| serde_json::json!({
+                    "type": kind,
+                    "value": value,
+                    "icon": icon,
+                })),
+            arb_json(),
+        ]
+    }
+
+    /// A profiles.json-shaped document with arbitrary values in every slot
+    fn arb_profiles_doc() -> impl Strategy<Value = serde_json::Value> {
+        (
+            arb_json(),
+            prop::collection::vec(arb_slice(), 0..12),
+            arb_json(),
+        )
+            .prop_map(|(version, slices, hardware)| {
+                let mut doc = serde_json::json!({
+                    "profiles": [{"name": "default", "slices": slices}],
+                });
+                set_path(&mut doc, &["version"], version);
+                set_path(&mut doc, &["hardware", "firefox"], hardware);
+                doc
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_profiles_config_from_arbitrary_json(doc in prop_oneof![arb_json(), arb_profiles_doc()]) {
+            if let Ok(mut config) = serde_json::from_value::<ProfilesConfig>(doc) {
+                config.migrate();
+                prop_assert!(config.version >= SCHEMA_VERSION);
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_load_from_path_never_panics(doc in prop_oneof![arb_json(), arb_profiles_doc()]) {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path().join("profiles.json");
+            fs::write(&path, doc.to_string()).unwrap();
+            if let Ok(manager) = ProfileManager::load_from_path(&path) {
+                prop_assert_eq!(&manager.get_profile_for_window("default").name, "default");
+                prop_assert!(manager.current().slices.len() == 8);
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! Advisory lint for destructive shortcuts in easy-to-hit slices
+//!
+//! A close/quit/delete shortcut in one of the ergonomic "prime" slices (N, NE
+//! and E by default, mirrored for left-handed use) is easy to fire by
+//! accident. Profiles are never rejected for it: the daemon
+//! (`ProfileManager::lint_shortcuts` after each load) and `validate-profiles`
+//! only warn, naming the profile and slice, so the placement is a conscious
+//! choice. The risk table and the prime slices are data
+//! ([`ShortcutLintRules`]); callers build them from `profile_lint` in
+//! config.json and pass them in.
+//!
+//! On the 12-slice clock layout every slot is a near neighbour's narrow
+//! target, so a risky shortcut next to another action is flagged wherever it
+//! sits ([`ShortcutLintRules::check_crowding`]).
+
+use serde::{Deserialize, Serialize};
+
+use crate::action::ActionType;
+use crate::geometry::{slot_for_position_in, step_slot};
+use crate::profiles::{Profile, DEFAULT_LEFT_PROFILE_NAME};
+
+/// Ring positions checked by default (N, NE, E)
+pub const DEFAULT_PRIME_SLICES: [u8; 3] = [0, 1, 2];
+
+/// Slice count from which risky shortcuts are checked against their neighbours
+pub const CROWDED_SLICE_COUNT: u8 = 12;
+
+/// Built-in risk table: normalized keys and what they do
+const BUILTIN_RISKY_SHORTCUTS: &[(&str, &str)] = &[
+    ("ctrl+w", "closes the tab or document"),
+    ("ctrl+f4", "closes the tab or document"),
+    ("ctrl+shift+w", "closes the window"),
+    ("alt+f4", "closes the window"),
+    ("ctrl+q", "quits the application"),
+    ("ctrl+shift+q", "quits the application"),
+    ("delete", "deletes the selection"),
+    ("shift+delete", "permanently deletes the selection"),
+    ("ctrl+alt+delete", "opens the logout dialog"),
+];
+
+/// One entry of the risk table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskyShortcut {
+    /// Key sequence, e.g. `Ctrl+W` (case and modifier order do not matter)
+    pub keys: String,
+    /// What the shortcut does, shown in the warning
+    pub reason: String,
+}
+
+/// Which shortcuts are risky and which slots count as prime
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutLintRules {
+    /// Run the lint at all
+    pub enabled: bool,
+    /// Ring positions (0 = N, clockwise, as a right-handed layout draws
+    /// them) where risky shortcuts are flagged
+    pub prime_slices: Vec<u8>,
+    /// Replaces the built-in risk table when set
+    pub risky_shortcuts: Vec<RiskyShortcut>,
+    /// Mirror the prime positions (set from `input.left_handed`)
+    #[serde(skip)]
+    pub left_handed: bool,
+}
+
+impl Default for ShortcutLintRules {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            prime_slices: DEFAULT_PRIME_SLICES.to_vec(),
+            risky_shortcuts: BUILTIN_RISKY_SHORTCUTS
+                .iter()
+                .map(|(keys, reason)| RiskyShortcut {
+                    keys: keys.to_string(),
+                    reason: reason.to_string(),
+                })
+                .collect(),
+            left_handed: false,
+        }
+    }
+}
+
+/// A risky shortcut found in a prime slice
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShortcutRisk {
+    pub profile: String,
+    pub slice: u8,
+    pub keys: String,
+    pub reason: String,
+}
+
+/// A risky shortcut beside other actions on a crowded ring
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrowdedRisk {
+    pub profile: String,
+    pub slice: u8,
+    /// Adjacent slots holding an action, counter-clockwise one first
+    pub neighbours: Vec<u8>,
+    pub keys: String,
+    pub reason: String,
+}
+
+impl ShortcutLintRules {
+    /// Why `keys` is risky, if it is in the table
+    pub fn risk_for(&self, keys: &str) -> Option<&str> {
+        let keys = normalize_keys(keys);
+        self.risky_shortcuts
+            .iter()
+            .find(|r| normalize_keys(&r.keys) == keys)
+            .map(|r| r.reason.as_str())
+    }
+
+    /// Risky shortcuts in `profile`'s prime slices (empty when disabled)
+    ///
+    /// The bundled left-handed profile is already mirrored, so its prime
+    /// slots are too.
+    pub fn check_profile(&self, profile: &Profile) -> Vec<ShortcutRisk> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mirrored = self.left_handed != (profile.name == DEFAULT_LEFT_PROFILE_NAME);
+        let count = profile.slice_count;
+        let mut risks = Vec::new();
+        for &position in self.prime_slices.iter().filter(|&&p| p < count) {
+            let slice = slot_for_position_in(position, mirrored, count);
+            let Some(Some(action)) = profile.slices.get(slice as usize) else {
+                continue;
+            };
+            let ActionType::Shortcut(ref keys) = action.action_type else {
+                continue;
+            };
+            if let Some(reason) = self.risk_for(keys) {
+                risks.push(ShortcutRisk {
+                    profile: profile.name.clone(),
+                    slice,
+                    keys: keys.clone(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+        risks
+    }
+
+    /// Risky shortcuts with an assigned neighbour on a ring of
+    /// [`CROWDED_SLICE_COUNT`] or more slices (empty when disabled)
+    ///
+    /// Actions marked `require_confirmation` are not flagged: a near miss
+    /// only opens the prompt.
+    pub fn check_crowding(&self, profile: &Profile) -> Vec<CrowdedRisk> {
+        let count = profile.slice_count;
+        if !self.enabled || count < CROWDED_SLICE_COUNT {
+            return Vec::new();
+        }
+        let assigned = |slot: u8| profile.slices.get(usize::from(slot)).is_some_and(Option::is_some);
+        let mut risks = Vec::new();
+        for (slice, action) in (0u8..).zip(&profile.slices) {
+            let Some(action) = action.as_ref().filter(|a| !a.require_confirmation) else {
+                continue;
+            };
+            let ActionType::Shortcut(ref keys) = action.action_type else {
+                continue;
+            };
+            let Some(reason) = self.risk_for(keys) else {
+                continue;
+            };
+            let neighbours: Vec<u8> = [step_slot(slice, -1, count), step_slot(slice, 1, count)]
+                .into_iter()
+                .filter(|&n| assigned(n))
+                .collect();
+            if !neighbours.is_empty() {
+                risks.push(CrowdedRisk {
+                    profile: profile.name.clone(),
+                    slice,
+                    neighbours,
+                    keys: keys.clone(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+        risks
+    }
+}
+
+/// Canonical form of a key sequence: lowercase, modifiers first in a fixed
+/// order, common aliases folded (`Control` → `ctrl`, `Super` → `meta`, `Del`
+/// → `delete`)
+pub fn normalize_keys(keys: &str) -> String {
+    const MODIFIERS: [&str; 4] = ["ctrl", "alt", "shift", "meta"];
+    let mut modifiers = [false; 4];
+    let mut rest = Vec::new();
+    for part in keys.split('+') {
+        let part = part.trim().to_ascii_lowercase();
+        let part = match part.as_str() {
+            "control" => "ctrl".to_string(),
+            "super" | "win" => "meta".to_string(),
+            "del" => "delete".to_string(),
+            _ => part,
+        };
+        match MODIFIERS.iter().position(|m| *m == part) {
+            Some(i) => modifiers[i] = true,
+            None if !part.is_empty() => rest.push(part),
+            None => {}
+        }
+    }
+    MODIFIERS
+        .iter()
+        .zip(modifiers)
+        .filter(|&(_, set)| set)
+        .map(|(name, _)| name.to_string())
+        .chain(rest)
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+
+    fn shortcut(keys: &str) -> Option<Action> {
+        Some(Action {
+            action_type: ActionType::Shortcut(keys.to_string()),
+            label: None,
+            icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        })
+    }
+
+    #[test]
+    fn test_normalize_keys() {
+        assert_eq!(normalize_keys("Ctrl+W"), "ctrl+w");
+        assert_eq!(normalize_keys("shift + Control + w"), "ctrl+shift+w");
+        assert_eq!(normalize_keys("Super+Del"), "meta+delete");
+    }
+
+    #[test]
+    fn test_flags_risky_shortcut_in_prime_slice_only() {
+        let mut profile = crate::profiles::create_default_profile();
+        profile.name = "browser".to_string();
+        profile.slices[1] = shortcut("CTRL+w");
+        profile.slices[4] = shortcut("alt+F4");
+
+        let risks = ShortcutLintRules::default().check_profile(&profile);
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].profile, "browser");
+        assert_eq!(risks[0].slice, 1);
+        assert_eq!(risks[0].reason, "closes the tab or document");
+    }
+
+    #[test]
+    fn test_rules_override_from_json() {
+        let mut profile = crate::profiles::create_default_profile();
+        profile.slices[4] = shortcut("Ctrl+P");
+
+        let rules: ShortcutLintRules = serde_json::from_str(
+            r#"{"prime_slices": [4], "risky_shortcuts": [{"keys": "ctrl+p", "reason": "prints"}]}"#,
+        )
+        .unwrap();
+        assert!(rules.enabled);
+        assert_eq!(rules.check_profile(&profile)[0].reason, "prints");
+
+        let disabled = ShortcutLintRules {
+            enabled: false,
+            ..rules
+        };
+        assert!(disabled.check_profile(&profile).is_empty());
+    }
+
+    #[test]
+    fn test_prime_slices_mirror_for_left_hand() {
+        let mut profile = crate::profiles::create_default_profile();
+        profile.slices[7] = shortcut("ctrl+q");
+        let rules = ShortcutLintRules::default();
+        assert!(rules.check_profile(&profile).is_empty());
+
+        let left = ShortcutLintRules {
+            left_handed: true,
+            ..rules.clone()
+        };
+        assert_eq!(left.check_profile(&profile)[0].slice, 7);
+
+        // The bundled default profiles keep Close out of the prime slices
+        assert!(rules.check_profile(&crate::profiles::create_default_profile()).is_empty());
+        assert!(rules.check_profile(&crate::profiles::create_default_left_profile()).is_empty());
+    }
+
+    #[test]
+    fn test_crowding_only_on_the_clock_layout() {
+        let rules = ShortcutLintRules::default();
+        // Default profile: Close (ctrl+w) in slot 7 beside Save and Copy
+        let mut profile = crate::profiles::create_default_profile();
+        assert!(rules.check_crowding(&profile).is_empty());
+
+        profile.set_slice_count(12).unwrap();
+        // Slot 8 is empty after the switch, so only Save (6) neighbours it
+        let risks = rules.check_crowding(&profile);
+        assert_eq!(risks.len(), 1);
+        assert_eq!((risks[0].slice, risks[0].neighbours.clone()), (7, vec![6]));
+
+        // Confirmation takes the risk out of a near miss
+        profile.slices[7].as_mut().unwrap().require_confirmation = true;
+        assert!(rules.check_crowding(&profile).is_empty());
+
+        // A lone risky shortcut with empty neighbours is left alone
+        let mut lone = crate::profiles::Profile::default();
+        lone.set_slice_count(12).unwrap();
+        lone.slices[11] = shortcut("alt+f4");
+        assert!(rules.check_crowding(&lone).is_empty());
+        lone.slices[0] = shortcut("ctrl+c");
+        assert_eq!(rules.check_crowding(&lone)[0].neighbours, vec![0]);
+
+        let disabled = ShortcutLintRules { enabled: false, ..rules };
+        assert!(disabled.check_crowding(&lone).is_empty());
+    }
+}
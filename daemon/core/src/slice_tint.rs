@@ -0,0 +1,237 @@
+//! Per-slice fill colors
+//!
+//! `accessibility.slice_tinting` lets the ring tell slices apart by color as
+//! well as by position and label:
+//!
+//! - `off`: every slice is the theme's surface color
+//! - `theme`: the theme's `overrides.sliceColors`, repeated around the ring;
+//!   surface when the theme has none
+//! - `auto`: tints generated from the accent ([`auto_tints`])
+//!
+//! The resolved list, one color per slice, goes into the menu payload in
+//! every mode, so the overlay always paints from it.
+//!
+//! Auto tints keep the accent's saturation and share one lightness, picked
+//! so each has [`MIN_CONTRAST`] against the surface. Their hues are evenly
+//! spaced from the accent's but not laid out in hue order: neighbouring
+//! slices sit at least 90° apart where the slice count allows it, and a red
+//! is never put next to a green, the pair deuteranopes confuse most (unless
+//! the count is so small that every slice neighbours every other).
+
+use serde::{Deserialize, Serialize};
+
+use crate::color::{contrast_ratio, relative_luminance, Hsl, Rgba};
+use crate::theme::Theme;
+
+/// Minimum contrast of an auto tint against the theme surface (WCAG 2 ratio
+/// for graphical objects)
+pub const MIN_CONTRAST: f32 = 3.0;
+
+/// Hue separations tried for neighbouring auto tints, widest first
+const SEPARATIONS: [f32; 5] = [90.0, 60.0, 45.0, 30.0, 0.0];
+
+/// Where the per-slice colors come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SliceTinting {
+    /// Every slice in the surface color
+    Off,
+    /// The theme's `overrides.sliceColors`, surface when it has none
+    #[default]
+    Theme,
+    /// Tints generated from the accent
+    Auto,
+}
+
+/// One `#rrggbb` (or `#rrggbbaa`) color per slice for `count` slices
+pub fn slice_colors(theme: &Theme, mode: SliceTinting, count: usize) -> Vec<String> {
+    let surface = theme.colors.surface.clone();
+    let themed: Vec<String> = match mode {
+        SliceTinting::Off => Vec::new(),
+        SliceTinting::Theme => theme
+            .overrides
+            .as_ref()
+            .and_then(|o| o.slice_colors.as_deref())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|c| Rgba::from_hex(c))
+            .map(|c| if c.a == 255 { c.to_hex() } else { c.to_hex_alpha() })
+            .collect(),
+        SliceTinting::Auto => {
+            match (Rgba::from_hex(&theme.colors.accent), Rgba::from_hex(&surface)) {
+                (Some(accent), Some(surface)) => {
+                    auto_tints(accent, surface, count).into_iter().map(Rgba::to_hex).collect()
+                }
+                _ => Vec::new(),
+            }
+        }
+    };
+    if themed.is_empty() {
+        return vec![surface; count];
+    }
+    themed.iter().cycle().take(count).cloned().collect()
+}
+
+/// `count` tints around the accent's hue, in ring order (slice 0 first,
+/// in the accent's own hue)
+pub fn auto_tints(accent: Rgba, surface: Rgba, count: usize) -> Vec<Rgba> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let base = accent.to_hsl();
+    let step = 360.0 / count as f32;
+    let hues: Vec<f32> = (0..count).map(|i| (base.h + i as f32 * step).rem_euclid(360.0)).collect();
+    let order = ring_order(&hues);
+    let saturation = base.s.clamp(0.55, 0.85);
+
+    // Light tints on a dark surface, dark ones on a light surface
+    let dark_surface = relative_luminance(surface) < 0.18;
+    let (mut lightness, delta) = if dark_surface { (0.62, 0.02) } else { (0.42, -0.02) };
+    loop {
+        let tints: Vec<Rgba> = order
+            .iter()
+            .map(|&i| Rgba::from_hsl(Hsl { h: hues[i], s: saturation, l: lightness }, 255))
+            .collect();
+        let readable = tints.iter().all(|&t| contrast_ratio(t, surface) >= MIN_CONTRAST);
+        let next = lightness + delta;
+        if readable || !(0.0..=1.0).contains(&next) {
+            return tints;
+        }
+        lightness = next;
+    }
+}
+
+/// Circular distance between two hues in degrees
+fn hue_distance(a: f32, b: f32) -> f32 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+fn is_red(hue: f32) -> bool {
+    hue_distance(hue, 0.0) <= 20.0
+}
+
+fn is_green(hue: f32) -> bool {
+    (75.0..=165.0).contains(&hue)
+}
+
+/// Indices into `hues` in ring order, starting at 0: the widest neighbour
+/// separation that keeps reds away from greens, else the widest at all
+fn ring_order(hues: &[f32]) -> Vec<usize> {
+    for avoid_red_green in [true, false] {
+        for min_separation in SEPARATIONS {
+            let neighbours = |a: f32, b: f32| {
+                hue_distance(a, b) >= min_separation - 0.01
+                    && !(avoid_red_green && ((is_red(a) && is_green(b)) || (is_green(a) && is_red(b))))
+            };
+            let mut path = vec![0];
+            let mut used = vec![false; hues.len()];
+            used[0] = true;
+            if extend_ring(hues, &neighbours, &mut path, &mut used) {
+                return path;
+            }
+        }
+    }
+    (0..hues.len()).collect()
+}
+
+/// Depth-first search for a closed ring, trying the farthest hue first
+fn extend_ring(hues: &[f32], neighbours: &dyn Fn(f32, f32) -> bool, path: &mut Vec<usize>, used: &mut [bool]) -> bool {
+    let last = hues[path[path.len() - 1]];
+    if path.len() == hues.len() {
+        return path.len() < 3 || neighbours(last, hues[0]);
+    }
+    let mut next: Vec<usize> = (0..hues.len()).filter(|&j| !used[j] && neighbours(last, hues[j])).collect();
+    next.sort_by(|&a, &b| hue_distance(last, hues[b]).total_cmp(&hue_distance(last, hues[a])));
+    for j in next {
+        used[j] = true;
+        path.push(j);
+        if extend_ring(hues, neighbours, path, used) {
+            return true;
+        }
+        path.pop();
+        used[j] = false;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundled_themes;
+    use crate::geometry::SUPPORTED_SLICE_COUNTS;
+
+    fn hex(s: &str) -> Rgba {
+        Rgba::from_hex(s).unwrap()
+    }
+
+    fn neighbours(tints: &[Rgba]) -> impl Iterator<Item = (f32, f32)> + '_ {
+        (0..tints.len()).map(|i| (tints[i].to_hsl().h, tints[(i + 1) % tints.len()].to_hsl().h))
+    }
+
+    #[test]
+    fn test_eight_tints_are_spread_around_the_ring() {
+        for accent_hue in (0..360).step_by(5) {
+            let accent = Rgba::from_hsl(Hsl { h: accent_hue as f32, s: 0.8, l: 0.7 }, 255);
+            let tints = auto_tints(accent, hex("#313244"), 8);
+            assert_eq!(tints.len(), 8);
+            // Rounding to 8-bit channels moves hues by a degree or so
+            for (a, b) in neighbours(&tints) {
+                assert!(hue_distance(a, b) >= 88.0, "accent {accent_hue}: {a} next to {b}");
+            }
+            assert!(hue_distance(tints[0].to_hsl().h, accent_hue as f32) < 2.0);
+        }
+    }
+
+    #[test]
+    fn test_no_red_next_to_green() {
+        for count in [6, 8, 12] {
+            for accent_hue in (0..360).step_by(5) {
+                let accent = Rgba::from_hsl(Hsl { h: accent_hue as f32, s: 0.7, l: 0.6 }, 255);
+                let tints = auto_tints(accent, hex("#1e1e2e"), count);
+                // Two degrees of slack for 8-bit rounding at the class edges
+                let red = |h: f32| hue_distance(h, 0.0) <= 18.0;
+                let green = |h: f32| (77.0..=163.0).contains(&h);
+                for (a, b) in neighbours(&tints) {
+                    let confusable = (red(a) && green(b)) || (green(a) && red(b));
+                    assert!(!confusable, "{count} slices, accent {accent_hue}: {a} next to {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tints_contrast_with_the_surface() {
+        for surface in ["#1e1e2e", "#313244", "#eff1f5", "#ffffff", "#000000"] {
+            for count in SUPPORTED_SLICE_COUNTS {
+                let tints = auto_tints(hex("#b4befe"), hex(surface), count as usize);
+                for tint in tints {
+                    let ratio = contrast_ratio(tint, hex(surface));
+                    assert!(ratio >= MIN_CONTRAST, "{} on {surface}: {ratio}", tint.to_hex());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_modes() {
+        let mut theme = bundled_themes::get_bundled_theme("catppuccin-mocha").unwrap();
+        let surface = theme.colors.surface.clone();
+        assert_eq!(slice_colors(&theme, SliceTinting::Off, 4), vec![surface.clone(); 4]);
+        assert_eq!(slice_colors(&theme, SliceTinting::Theme, 4), vec![surface.clone(); 4]);
+
+        let auto = slice_colors(&theme, SliceTinting::Auto, 12);
+        assert_eq!(auto.len(), 12);
+        assert!(auto.iter().all(|c| c.len() == 7 && *c != surface));
+
+        theme.overrides = Some(crate::theme::ThemeOverrides {
+            slice_colors: Some(vec!["#F38BA8".into(), "not a color".into(), "#a6e3a180".into()]),
+            custom_font: None,
+        });
+        assert_eq!(slice_colors(&theme, SliceTinting::Theme, 5), ["#f38ba8", "#a6e3a180", "#f38ba8", "#a6e3a180", "#f38ba8"]);
+        assert_eq!(slice_colors(&theme, SliceTinting::Off, 2), vec![surface; 2]);
+
+        let json = serde_json::to_string(&SliceTinting::Auto).unwrap();
+        assert_eq!(json, "\"auto\"");
+    }
+}
@@ -0,0 +1,39 @@
+//! Shared helpers for property tests
+
+use proptest::prelude::*;
+use serde_json::Value;
+
+/// Arbitrary JSON documents, nested a few levels deep
+pub(crate) fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_filter("JSON numbers are finite", |f| f.is_finite())
+            .prop_map(Value::from),
+        ".{0,12}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+            prop::collection::hash_map("[a-z_]{1,12}", inner, 0..8)
+                .prop_map(|m| Value::Object(m.into_iter().collect())),
+        ]
+    })
+}
+
+/// Replace the value at `path` (object keys) in `doc`, creating objects as needed
+pub(crate) fn set_path(doc: &mut Value, path: &[&str], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut node = doc;
+    for key in parents {
+        if !node.get(*key).is_some_and(Value::is_object) {
+            node[*key] = Value::Object(Default::default());
+        }
+        node = &mut node[*key];
+    }
+    node[*last] = value;
+}
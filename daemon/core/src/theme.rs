@@ -0,0 +1,3307 @@
+//! Theme engine for radial menu visual customization
+//!
+//! Story 4.1: Theme JSON Schema & Parser
+//!
+//! Supports JSON themes with validation and directory scanning.
+//! Themes are loaded from:
+//! - System: `<data dir>/themes/` (`/usr/share/juhradial/themes/` unless
+//!   overridden, see [`crate::paths`])
+//! - User: `~/.config/juhradial/themes/` (XDG compliant)
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::color::{self, Rgba};
+use crate::content_hash::{content_hash, ContentHash};
+use crate::locale::LocalizedText;
+use crate::paths::{DataDir, Paths};
+pub use crate::validation::{IssueCode, ValidationIssue, ValidationResult};
+
+/// Theme configuration filename
+const THEME_FILENAME: &str = "theme.json";
+
+/// Largest theme file that is parsed; bigger files are skipped
+pub const MAX_THEME_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Largest slice background texture that is loaded; bigger ones are dropped
+pub const MAX_TEXTURE_BYTES: u64 = 1024 * 1024;
+
+/// Most system and user themes loaded at startup; bundled themes don't count
+pub const MAX_LOADED_THEMES: usize = 200;
+
+/// Theme configuration (Story 4.1: Task 2.3 - matches UX Spec Section 4.2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Theme {
+    /// Theme identifier (directory name or file stem, inferred from path if not set)
+    #[serde(default)]
+    pub name: String,
+
+    /// Theme display name, one string or one per language tag
+    #[serde(default)]
+    pub display_name: LocalizedText,
+
+    /// Theme version
+    #[serde(default = "default_version")]
+    pub version: String,
+
+    /// Theme author
+    #[serde(default)]
+    pub author: String,
+
+    /// Color palette (11 colors from UX spec)
+    pub colors: ThemeColors,
+
+    /// Glassmorphism effects
+    #[serde(alias = "effects")]
+    pub glassmorphism: GlassmorphismSettings,
+
+    /// Animation settings
+    pub animation: AnimationSettings,
+
+    /// Optional overrides
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<ThemeOverrides>,
+}
+
+fn default_version() -> String {
+    "1.0".to_string()
+}
+
+/// Theme color palette (UX Spec Section 4.2 - 11 colors)
+///
+/// In theme.json each color is a hex string or a derivation from another
+/// color (see [`ColorDerivation`]); derivations are resolved while parsing,
+/// so every field here holds a concrete hex value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", from = "ThemeColorsSpec")]
+pub struct ThemeColors {
+    /// Background color (hex) - Catppuccin: Base
+    pub base: String,
+
+    /// Surface color for slices - Catppuccin: Surface 0
+    pub surface: String,
+
+    /// Primary text color
+    pub text: String,
+
+    /// Secondary text color
+    #[serde(default = "default_text_secondary")]
+    pub text_secondary: String,
+
+    /// Primary accent color
+    pub accent: String,
+
+    /// Secondary accent color
+    #[serde(default = "default_accent_secondary")]
+    pub accent_secondary: String,
+
+    /// Border color
+    pub border: String,
+
+    /// Shadow color
+    #[serde(default = "default_shadow")]
+    pub shadow: String,
+
+    /// Success state color
+    #[serde(default = "default_success")]
+    pub success: String,
+
+    /// Warning state color
+    #[serde(default = "default_warning")]
+    pub warning: String,
+
+    /// Error state color
+    #[serde(default = "default_error")]
+    pub error: String,
+
+    /// Derivations that could not be resolved, reported by `validate_and_clamp`
+    #[serde(skip)]
+    derivation_errors: Vec<ValidationIssue>,
+
+    /// The palette as written, kept so a replaced accent re-runs the
+    /// derivations; `None` for palettes built in code
+    #[serde(skip)]
+    spec: Option<Box<[ColorValue; 11]>>,
+}
+
+fn default_text_secondary() -> String {
+    "#bac2de".to_string()
+}
+fn default_accent_secondary() -> String {
+    "#89b4fa".to_string()
+}
+fn default_shadow() -> String {
+    "#11111b".to_string()
+}
+fn default_success() -> String {
+    "#a6e3a1".to_string()
+}
+fn default_warning() -> String {
+    "#fab387".to_string()
+}
+fn default_error() -> String {
+    "#f38ba8".to_string()
+}
+
+/// Index of `accent` in [`COLOR_KEYS`]
+const ACCENT_INDEX: usize = 4;
+
+/// theme.json keys of the palette, in [`ThemeColors`] field order
+pub const COLOR_KEYS: [&str; 11] = [
+    "base",
+    "surface",
+    "text",
+    "textSecondary",
+    "accent",
+    "accentSecondary",
+    "border",
+    "shadow",
+    "success",
+    "warning",
+    "error",
+];
+
+/// A color computed from another palette color
+///
+/// `{"derive": "accent", "lighten": 0.15}`. `derive` and `mix.color` name a
+/// palette key or give a hex color. The steps run in the order mix, lighten,
+/// darken; amounts must be within `0.0..=1.0`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ColorDerivation {
+    /// Palette key (or hex color) the color starts from
+    pub derive: String,
+    /// Raise the HSL lightness by this much
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lighten: Option<f32>,
+    /// Lower the HSL lightness by this much
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub darken: Option<f32>,
+    /// Blend towards another color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mix: Option<ColorMix>,
+}
+
+/// The `mix` step of a [`ColorDerivation`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColorMix {
+    /// Palette key or hex color to blend towards
+    pub color: String,
+    /// 0.0 keeps the starting color, 1.0 gives `color`
+    pub amount: f32,
+}
+
+/// A palette entry as written in theme.json
+#[derive(Debug, Clone)]
+enum ColorValue {
+    Hex(String),
+    Derived(ColorDerivation),
+}
+
+impl<'de> Deserialize<'de> for ColorValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::String(hex) => Ok(ColorValue::Hex(hex)),
+            value @ serde_json::Value::Object(_) => serde_json::from_value(value)
+                .map(ColorValue::Derived)
+                .map_err(D::Error::custom),
+            other => Err(D::Error::custom(format!(
+                "expected a hex color string or a derivation object, found {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// [`ThemeColors`] before derivations are resolved
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThemeColorsSpec {
+    base: ColorValue,
+    surface: ColorValue,
+    text: ColorValue,
+    #[serde(default)]
+    text_secondary: Option<ColorValue>,
+    accent: ColorValue,
+    #[serde(default)]
+    accent_secondary: Option<ColorValue>,
+    border: ColorValue,
+    #[serde(default)]
+    shadow: Option<ColorValue>,
+    #[serde(default)]
+    success: Option<ColorValue>,
+    #[serde(default)]
+    warning: Option<ColorValue>,
+    #[serde(default)]
+    error: Option<ColorValue>,
+}
+
+impl From<ThemeColorsSpec> for ThemeColors {
+    fn from(spec: ThemeColorsSpec) -> Self {
+        let or = |value: Option<ColorValue>, default: fn() -> String| {
+            value.unwrap_or_else(|| ColorValue::Hex(default()))
+        };
+        let values = [
+            spec.base,
+            spec.surface,
+            spec.text,
+            or(spec.text_secondary, default_text_secondary),
+            spec.accent,
+            or(spec.accent_secondary, default_accent_secondary),
+            spec.border,
+            or(spec.shadow, default_shadow),
+            or(spec.success, default_success),
+            or(spec.warning, default_warning),
+            or(spec.error, default_error),
+        ];
+        ThemeColors::resolved(values)
+    }
+}
+
+impl ThemeColors {
+    /// Resolve a written palette, keeping it for [`ThemeColors::with_accent`]
+    fn resolved(values: [ColorValue; 11]) -> Self {
+        let (resolved, derivation_errors) = resolve_colors(&values);
+        let [base, surface, text, text_secondary, accent, accent_secondary, border, shadow, success, warning, error] =
+            resolved;
+        ThemeColors {
+            base,
+            surface,
+            text,
+            text_secondary,
+            accent,
+            accent_secondary,
+            border,
+            shadow,
+            success,
+            warning,
+            error,
+            derivation_errors,
+            spec: Some(Box::new(values)),
+        }
+    }
+
+    /// The palette with `accent` (a hex color) in place of the theme's own
+    ///
+    /// Colors derived from the accent are derived again from the new one;
+    /// a palette built in code has no derivations and only swaps the accent.
+    pub fn with_accent(&self, accent: &str) -> Self {
+        let mut values = match &self.spec {
+            Some(spec) => (**spec).clone(),
+            None => [
+                &self.base,
+                &self.surface,
+                &self.text,
+                &self.text_secondary,
+                &self.accent,
+                &self.accent_secondary,
+                &self.border,
+                &self.shadow,
+                &self.success,
+                &self.warning,
+                &self.error,
+            ]
+            .map(|hex| ColorValue::Hex(hex.clone())),
+        };
+        values[ACCENT_INDEX] = ColorValue::Hex(accent.to_string());
+        Self::resolved(values)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Resolution {
+    Pending,
+    Visiting,
+    Done(Option<Rgba>),
+}
+
+/// Resolve every palette entry to a hex string
+///
+/// Hex entries are kept as written (`validate_and_clamp` checks them).
+/// Derived entries that fail resolve to an empty string with an issue.
+fn resolve_colors(values: &[ColorValue; 11]) -> ([String; 11], Vec<ValidationIssue>) {
+    let mut state = [Resolution::Pending; 11];
+    let mut issues = Vec::new();
+    for index in 0..values.len() {
+        resolve_color(values, index, &mut state, &mut Vec::new(), &mut issues);
+    }
+    let resolved = std::array::from_fn(|i| match (&values[i], state[i]) {
+        (ColorValue::Hex(hex), _) => hex.clone(),
+        (ColorValue::Derived(_), Resolution::Done(Some(rgba))) => rgba.to_hex(),
+        (ColorValue::Derived(_), _) => String::new(),
+    });
+    (resolved, issues)
+}
+
+fn resolve_color(
+    values: &[ColorValue; 11],
+    index: usize,
+    state: &mut [Resolution; 11],
+    chain: &mut Vec<usize>,
+    issues: &mut Vec<ValidationIssue>,
+) -> Option<Rgba> {
+    match state[index] {
+        Resolution::Done(rgba) => return rgba,
+        Resolution::Visiting => {
+            let start = chain.iter().position(|&i| i == index).unwrap_or(0);
+            let cycle: Vec<&str> = chain[start..]
+                .iter()
+                .chain(std::iter::once(&index))
+                .map(|&i| COLOR_KEYS[i])
+                .collect();
+            for &i in &chain[start..] {
+                issues.push(derivation_issue(i, format!("circular derivation {}", cycle.join(" -> "))));
+                state[i] = Resolution::Done(None);
+            }
+            return None;
+        }
+        Resolution::Pending => {}
+    }
+    let derivation = match &values[index] {
+        ColorValue::Hex(hex) => {
+            let rgba = parse_palette_hex(hex);
+            state[index] = Resolution::Done(rgba);
+            return rgba;
+        }
+        ColorValue::Derived(derivation) => derivation,
+    };
+
+    state[index] = Resolution::Visiting;
+    chain.push(index);
+    let mut reference = |name: &str, issues: &mut Vec<ValidationIssue>| -> Result<Option<Rgba>, String> {
+        if name.starts_with('#') {
+            return parse_palette_hex(name)
+                .map(Some)
+                .ok_or_else(|| format!("'{}' is not a #RRGGBB color", name));
+        }
+        let Some(other) = COLOR_KEYS.iter().position(|k| *k == name) else {
+            return Err(format!("unknown color '{}' (expected one of {})", name, COLOR_KEYS.join(", ")));
+        };
+        Ok(resolve_color(values, other, state, chain, issues))
+    };
+
+    let base = reference(&derivation.derive, issues);
+    let mix = derivation
+        .mix
+        .as_ref()
+        .map(|mix| reference(&mix.color, issues).map(|color| (color, mix.amount)));
+    chain.pop();
+    if let Resolution::Done(rgba) = state[index] {
+        // Part of a cycle, already reported
+        return rgba;
+    }
+
+    let result = (|| -> Result<Option<Rgba>, String> {
+        for (step, amount) in [
+            ("lighten", derivation.lighten),
+            ("darken", derivation.darken),
+            ("mix.amount", derivation.mix.as_ref().map(|m| m.amount)),
+        ] {
+            if let Some(amount) = amount.filter(|a| !(0.0..=1.0).contains(a)) {
+                return Err(format!("{} {} is outside 0.0..=1.0", step, amount));
+            }
+        }
+        let Some(mut rgba) = base? else {
+            return Err(format!("derives from '{}', which has no valid color", derivation.derive));
+        };
+        if let Some(mix) = mix {
+            let (Some(other), amount) = mix? else {
+                return Err("mix color has no valid color".to_string());
+            };
+            rgba = color::mix(rgba, other, amount);
+        }
+        if let Some(amount) = derivation.lighten {
+            rgba = color::lighten(rgba, amount);
+        }
+        if let Some(amount) = derivation.darken {
+            rgba = color::darken(rgba, amount);
+        }
+        Ok(Some(rgba))
+    })();
+    let rgba = result.unwrap_or_else(|message| {
+        issues.push(derivation_issue(index, message));
+        None
+    });
+    state[index] = Resolution::Done(rgba);
+    rgba
+}
+
+/// An opaque palette color (`#RGB` or `#RRGGBB`)
+fn parse_palette_hex(hex: &str) -> Option<Rgba> {
+    is_valid_hex_color(hex).then(|| Rgba::from_hex(hex)).flatten()
+}
+
+fn derivation_issue(index: usize, message: String) -> ValidationIssue {
+    let key = COLOR_KEYS[index];
+    ValidationIssue::error(
+        format!("colors.{}", key),
+        IssueCode::InvalidDerivation,
+        format!("Cannot derive {}: {}", key, message),
+    )
+}
+
+/// Glassmorphism effect settings (UX Spec Section 4.2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlassmorphismSettings {
+    /// Blur radius in pixels (8-48, default 24)
+    #[serde(default = "default_blur_radius")]
+    pub blur_radius: u8,
+
+    /// Background opacity (0.5-0.95, default 0.75)
+    #[serde(default = "default_background_opacity")]
+    pub background_opacity: f32,
+
+    /// Saturation multiplier (1.0-2.5, default 1.8)
+    #[serde(default = "default_saturation")]
+    pub saturation: f32,
+
+    /// Border opacity (0.0-0.5, default 0.15)
+    #[serde(default = "default_border_opacity")]
+    pub border_opacity: f32,
+
+    /// Noise texture opacity (0.0-0.1, default 0.04)
+    #[serde(default = "default_noise_opacity")]
+    pub noise_opacity: f32,
+
+    /// Texture drawn behind the slices: a PNG or SVG file, relative to the
+    /// directory holding the theme file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_texture: Option<String>,
+
+    /// `background_texture` once it was found and checked; `None` without
+    /// one or when it was rejected (see [`Theme::resolve_background_texture`])
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<ThemeTexture>,
+
+    /// Why `background_texture` was rejected, reported by `validate_and_clamp`
+    #[serde(skip)]
+    texture_error: Option<ValidationIssue>,
+}
+
+fn default_blur_radius() -> u8 {
+    24
+}
+fn default_background_opacity() -> f32 {
+    0.75
+}
+fn default_saturation() -> f32 {
+    1.8
+}
+fn default_border_opacity() -> f32 {
+    0.15
+}
+fn default_noise_opacity() -> f32 {
+    0.04
+}
+
+/// Slice background texture file format, sniffed from its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureFormat {
+    Png,
+    Svg,
+}
+
+impl TextureFormat {
+    /// Format of a file starting with `bytes`: the PNG signature, or an SVG
+    /// root element within the first 4KB of markup
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Some(Self::Png);
+        }
+        let text = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+        let start = text.iter().position(|b| !b.is_ascii_whitespace())?;
+        let head = &text[start..text.len().min(start + 4096)];
+        (head.starts_with(b"<") && head.windows(4).any(|w| w == b"<svg")).then_some(Self::Svg)
+    }
+}
+
+/// Why a theme's background texture cannot be used
+#[derive(Debug, thiserror::Error)]
+pub enum TextureError {
+    #[error("must be a relative path inside the theme directory")]
+    NotRelative,
+    #[error("resolves to {}, outside the theme directory", .0.display())]
+    OutsideThemeDir(PathBuf),
+    #[error("cannot read: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a regular file")]
+    NotAFile,
+    #[error("larger than {} bytes", MAX_TEXTURE_BYTES)]
+    TooLarge,
+    #[error("not a PNG or SVG image")]
+    UnknownFormat,
+}
+
+/// A checked slice background texture, sent to the overlay in `MenuPayload`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ThemeTexture {
+    /// Absolute path with symlinks resolved
+    pub path: PathBuf,
+    pub format: TextureFormat,
+    /// Hash of the file's bytes; the overlay caches the loaded image under it
+    pub hash: ContentHash,
+}
+
+impl ThemeTexture {
+    /// Find `relative` under `theme_dir` and check the file
+    ///
+    /// `..` and absolute paths are refused, and so is a symlink leading out
+    /// of the theme directory. At most [`MAX_TEXTURE_BYTES`] are read.
+    pub fn load(theme_dir: &Path, relative: &str) -> Result<Self, TextureError> {
+        let path = Path::new(relative);
+        if relative.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return Err(TextureError::NotRelative);
+        }
+        let dir = theme_dir.canonicalize()?;
+        let path = dir.join(path).canonicalize()?;
+        if !path.starts_with(&dir) {
+            return Err(TextureError::OutsideThemeDir(path));
+        }
+        let file = fs::File::open(&path)?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() {
+            return Err(TextureError::NotAFile);
+        }
+        if metadata.len() > MAX_TEXTURE_BYTES {
+            return Err(TextureError::TooLarge);
+        }
+        // The cap holds even if the file grows after the size check
+        let mut bytes = Vec::new();
+        file.take(MAX_TEXTURE_BYTES + 1).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > MAX_TEXTURE_BYTES {
+            return Err(TextureError::TooLarge);
+        }
+        let format = TextureFormat::sniff(&bytes).ok_or(TextureError::UnknownFormat)?;
+        Ok(Self {
+            path,
+            format,
+            hash: ContentHash(content_hash(&bytes)),
+        })
+    }
+}
+
+/// Animation settings (UX Spec Section 4.2)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationSettings {
+    /// Glow effect intensity multiplier (0.0-2.0, default 1.0)
+    #[serde(default = "default_glow_intensity")]
+    pub glow_intensity: f32,
+
+    /// Enable particle effects
+    #[serde(default)]
+    pub enable_particles: bool,
+
+    /// Idle effect type: "none", "matrix-rain", "particles"
+    #[serde(default = "default_idle_effect")]
+    pub idle_effect: String,
+
+    /// How much of the menu the idle effect covers (0.0-1.0, default 0.5)
+    #[serde(default = "default_idle_density")]
+    pub idle_density: f32,
+
+    /// Idle effect speed multiplier (0.1-4.0, default 1.0)
+    #[serde(default = "default_idle_speed")]
+    pub idle_speed: f32,
+
+    /// Frame rate the idle effect may draw at (1-60, default 30)
+    #[serde(default = "default_idle_max_fps")]
+    pub idle_max_fps: u8,
+}
+
+fn default_glow_intensity() -> f32 {
+    1.0
+}
+fn default_idle_effect() -> String {
+    "none".to_string()
+}
+fn default_idle_density() -> f32 {
+    0.5
+}
+fn default_idle_speed() -> f32 {
+    1.0
+}
+fn default_idle_max_fps() -> u8 {
+    30
+}
+
+/// Idle effects a theme can name in `animation.idleEffect`
+pub const IDLE_EFFECTS: [&str; 3] = ["none", "matrix-rain", "particles"];
+
+/// The idle effect as the overlay runs it
+///
+/// Resolved from the theme's `animation` section, then bounded by the
+/// user's [`IdleEffectLimits`]. `type` is `"none"` whenever the effect is
+/// off, for reduced motion included.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct IdleEffectSettings {
+    /// One of [`IDLE_EFFECTS`]
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Share of the menu the effect covers, 0.0-1.0
+    pub density: f32,
+    /// Speed multiplier
+    pub speed: f32,
+    /// Most frames per second the effect may draw
+    pub max_fps: u8,
+}
+
+impl IdleEffectSettings {
+    /// No idle effect
+    pub const OFF: Self = Self {
+        kind: "none",
+        density: 0.0,
+        speed: 0.0,
+        max_fps: 0,
+    };
+
+    /// Whether an effect runs
+    pub fn is_active(&self) -> bool {
+        self.kind != "none"
+    }
+
+    /// Bounded by `limits`; off when they turn idle effects off
+    pub fn limited(self, limits: &IdleEffectLimits) -> Self {
+        if !self.is_active() || !limits.enabled || limits.max_fps == 0 || limits.max_density <= 0.0 {
+            return Self::OFF;
+        }
+        Self {
+            density: self.density.min(limits.max_density),
+            max_fps: self.max_fps.min(limits.max_fps),
+            ..self
+        }
+    }
+}
+
+/// User caps on idle effects, whatever the theme asks for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleEffectLimits {
+    /// Idle effects allowed at all
+    pub enabled: bool,
+    /// Frame rate cap
+    pub max_fps: u8,
+    /// Density cap
+    pub max_density: f32,
+}
+
+impl Default for IdleEffectLimits {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_fps: 60,
+            max_density: 1.0,
+        }
+    }
+}
+
+/// Theme overrides for custom configurations
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ThemeOverrides {
+    /// Custom per-slice colors (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slice_colors: Option<Vec<String>>,
+
+    /// Custom font family (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_font: Option<String>,
+}
+
+/// High contrast mode settings (Story 4.5: Task 1.1)
+#[derive(Debug, Clone)]
+pub struct HighContrastSettings {
+    /// Override text color (pure white for max visibility)
+    pub text_color: String,
+    /// Override border opacity (60% for visibility)
+    pub border_opacity: f32,
+    /// Selection border color (solid white)
+    pub selection_border_color: String,
+    /// Selection border width in pixels
+    pub selection_border_width: u8,
+    /// Background opacity (95% for solid appearance)
+    pub background_opacity: f32,
+    /// Blur radius (0 = disabled for performance/clarity)
+    pub blur_radius: u8,
+}
+
+impl Default for HighContrastSettings {
+    fn default() -> Self {
+        Self {
+            text_color: "#ffffff".to_string(),
+            border_opacity: 0.60,
+            selection_border_color: "#ffffff".to_string(),
+            selection_border_width: 3,
+            background_opacity: HIGH_CONTRAST_BACKGROUND_OPACITY,
+            blur_radius: 0,
+        }
+    }
+}
+
+/// Background opacity used in high contrast mode
+pub const HIGH_CONTRAST_BACKGROUND_OPACITY: f32 = 0.95;
+
+/// Effective colors after applying accessibility adjustments (Story 4.5: Task 1.2)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveColors {
+    pub base: String,
+    pub surface: String,
+    pub text: String,
+    pub text_secondary: String,
+    pub accent: String,
+    pub accent_secondary: String,
+    pub border: String,
+    pub shadow: String,
+    pub success: String,
+    pub warning: String,
+    pub error: String,
+}
+
+/// Effective glassmorphism settings after applying accessibility adjustments (Story 4.5: Task 2.1)
+#[derive(Debug, Clone)]
+pub struct EffectiveGlassmorphism {
+    pub blur_radius: u8,
+    pub background_opacity: f32,
+    pub saturation: f32,
+    pub border_opacity: f32,
+    pub noise_opacity: f32,
+}
+
+/// Number of values in a serialized [`PackedThemeColors`]
+pub const PACKED_THEME_COLORS_LEN: usize = COLOR_KEYS.len() + 5;
+
+/// Effective colors and glassmorphism as plain numbers, so the overlay can
+/// hand them to its renderer without parsing hex strings every frame
+///
+/// Serialized as one flat JSON array of [`PACKED_THEME_COLORS_LEN`] numbers:
+/// the 11 colors in [`COLOR_KEYS`] order, each a premultiplied `0xRRGGBBAA`
+/// ([`pack_premultiplied`]), then `blur_radius`, `background_opacity`,
+/// `saturation`, `border_opacity` and `noise_opacity`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PackedThemeColors {
+    pub colors: [u32; COLOR_KEYS.len()],
+    pub glassmorphism: [f32; 5],
+}
+
+impl Serialize for PackedThemeColors {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+        let mut tuple = serializer.serialize_tuple(PACKED_THEME_COLORS_LEN)?;
+        for color in &self.colors {
+            tuple.serialize_element(color)?;
+        }
+        for value in &self.glassmorphism {
+            tuple.serialize_element(value)?;
+        }
+        tuple.end()
+    }
+}
+
+/// `color` as `0xRRGGBBAA` with red, green and blue multiplied by alpha
+/// (rounded to nearest), the layout premultiplied-alpha blending expects
+pub fn pack_premultiplied(color: Rgba) -> u32 {
+    let a = u32::from(color.a);
+    let channel = |c: u8| (u32::from(c) * a + 127) / 255;
+    channel(color.r) << 24 | channel(color.g) << 16 | channel(color.b) << 8 | a
+}
+
+impl EffectiveColors {
+    /// The colors in [`COLOR_KEYS`] order, packed with [`pack_premultiplied`];
+    /// a value that is not a hex color packs as transparent (0)
+    pub fn packed(&self) -> [u32; COLOR_KEYS.len()] {
+        [
+            &self.base,
+            &self.surface,
+            &self.text,
+            &self.text_secondary,
+            &self.accent,
+            &self.accent_secondary,
+            &self.border,
+            &self.shadow,
+            &self.success,
+            &self.warning,
+            &self.error,
+        ]
+        .map(|hex| Rgba::from_hex(hex).map_or(0, pack_premultiplied))
+    }
+}
+
+impl EffectiveGlassmorphism {
+    /// The settings in [`PackedThemeColors`] order, `blur_radius` first
+    pub fn packed(&self) -> [f32; 5] {
+        [
+            f32::from(self.blur_radius),
+            self.background_opacity,
+            self.saturation,
+            self.border_opacity,
+            self.noise_opacity,
+        ]
+    }
+}
+
+/// Animation timings with reduced motion support (Task 3.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EffectiveAnimationTimings {
+    /// Menu appear duration (ms)
+    pub appear_ms: u16,
+    /// Menu dismiss duration (ms)
+    pub dismiss_ms: u16,
+    /// Slice highlight in duration (ms)
+    pub highlight_in_ms: u16,
+    /// Slice highlight out duration (ms)
+    pub highlight_out_ms: u16,
+    /// Icon scale animation enabled
+    pub icon_scale_enabled: bool,
+    /// Idle effects enabled (matrix rain, particles)
+    pub idle_effects_enabled: bool,
+}
+
+impl EffectiveAnimationTimings {
+    /// Create timings for reduced motion mode (all 0ms, effects disabled)
+    pub fn reduced_motion() -> Self {
+        Self {
+            appear_ms: 0,
+            dismiss_ms: 0,
+            highlight_in_ms: 0,
+            highlight_out_ms: 0,
+            icon_scale_enabled: false,
+            idle_effects_enabled: false,
+        }
+    }
+
+    /// These timings with `idle_effects_enabled` matching `idle`
+    pub fn with_idle_effect(self, idle: &IdleEffectSettings) -> Self {
+        Self {
+            idle_effects_enabled: idle.is_active(),
+            ..self
+        }
+    }
+
+    /// Create default timings from UX spec
+    pub fn default_timings() -> Self {
+        Self {
+            appear_ms: 30,
+            dismiss_ms: 50,
+            highlight_in_ms: 80,
+            highlight_out_ms: 60,
+            icon_scale_enabled: true,
+            idle_effects_enabled: true,
+        }
+    }
+}
+
+impl Default for EffectiveAnimationTimings {
+    fn default() -> Self {
+        Self::default_timings()
+    }
+}
+
+impl Default for Theme {
+    /// Default Catppuccin Mocha theme (UX Spec Section 3.2)
+    fn default() -> Self {
+        Self::catppuccin_mocha()
+    }
+}
+
+impl Theme {
+    /// Create the bundled Catppuccin Mocha theme (Story 4.1: Task 5.2)
+    pub fn catppuccin_mocha() -> Self {
+        Self {
+            name: "catppuccin-mocha".to_string(),
+            display_name: "Catppuccin Mocha".into(),
+            version: "1.0".to_string(),
+            author: "JuhRadial Team".to_string(),
+            colors: ThemeColors {
+                base: "#1e1e2e".to_string(),
+                surface: "#313244".to_string(),
+                text: "#cdd6f4".to_string(),
+                text_secondary: "#bac2de".to_string(),
+                accent: "#b4befe".to_string(),
+                accent_secondary: "#89b4fa".to_string(),
+                border: "#585b70".to_string(),
+                shadow: "#11111b".to_string(),
+                success: "#a6e3a1".to_string(),
+                warning: "#fab387".to_string(),
+                error: "#f38ba8".to_string(),
+                derivation_errors: Vec::new(),
+                spec: None,
+            },
+            glassmorphism: GlassmorphismSettings {
+                blur_radius: 24,
+                background_opacity: 0.75,
+                saturation: 1.8,
+                border_opacity: 0.15,
+                noise_opacity: 0.04,
+                background_texture: None,
+                texture: None,
+                texture_error: None,
+            },
+            animation: AnimationSettings {
+                glow_intensity: 1.0,
+                enable_particles: false,
+                idle_effect: "none".to_string(),
+                idle_density: default_idle_density(),
+                idle_speed: default_idle_speed(),
+                idle_max_fps: default_idle_max_fps(),
+            },
+            overrides: None,
+        }
+    }
+
+    /// Get effective animation timings based on accessibility settings (Story 4.6: Task 3.1)
+    ///
+    /// Returns 0ms for all timings when reduced motion is active,
+    /// otherwise returns the theme's configured timings.
+    pub fn get_effective_animation_timings(
+        &self,
+        reduce_motion: bool,
+    ) -> EffectiveAnimationTimings {
+        if reduce_motion {
+            // Story 4.6: Task 3.2 - Return 0ms for all timings
+            EffectiveAnimationTimings::reduced_motion()
+        } else {
+            // Use theme's animation settings
+            EffectiveAnimationTimings {
+                appear_ms: 30, // UX spec default
+                dismiss_ms: 50,
+                highlight_in_ms: 80,
+                highlight_out_ms: 60,
+                icon_scale_enabled: true,
+                // Task 3.3: Idle effects from theme
+                idle_effects_enabled: self.get_effective_idle_effect(false).is_active(),
+            }
+        }
+    }
+
+    /// The theme's idle effect; off under reduced motion
+    ///
+    /// `enableParticles` with `idleEffect` "none" means particles. An
+    /// unknown effect name is off (`validate_and_clamp` warns about it).
+    pub fn get_effective_idle_effect(&self, reduce_motion: bool) -> IdleEffectSettings {
+        let name = match self.animation.idle_effect.as_str() {
+            "none" if self.animation.enable_particles => "particles",
+            name => name,
+        };
+        let kind = IDLE_EFFECTS.iter().copied().find(|k| *k == name).unwrap_or("none");
+        if reduce_motion || kind == "none" {
+            return IdleEffectSettings::OFF;
+        }
+        IdleEffectSettings {
+            kind,
+            density: self.animation.idle_density,
+            speed: self.animation.idle_speed,
+            max_fps: self.animation.idle_max_fps,
+        }
+    }
+
+    /// This theme with `accent` (a hex color) in place of its own accent,
+    /// colors derived from the accent following it
+    pub fn with_accent(&self, accent: &str) -> Theme {
+        Theme {
+            colors: self.colors.with_accent(accent),
+            ..self.clone()
+        }
+    }
+
+    /// Get effective colors with high contrast adjustments (Story 4.5: Task 1.2, 1.3)
+    ///
+    /// When high contrast is active, text colors are overridden to pure white
+    /// for maximum visibility.
+    pub fn get_effective_colors(&self, high_contrast: bool) -> EffectiveColors {
+        if high_contrast {
+            let hc = HighContrastSettings::default();
+            EffectiveColors {
+                base: self.colors.base.clone(),
+                surface: self.colors.surface.clone(),
+                text: hc.text_color.clone(),         // Override to white
+                text_secondary: hc.text_color.clone(), // Override to white
+                accent: self.colors.accent.clone(),
+                accent_secondary: self.colors.accent_secondary.clone(),
+                border: self.colors.border.clone(),
+                shadow: self.colors.shadow.clone(),
+                success: self.colors.success.clone(),
+                warning: self.colors.warning.clone(),
+                error: self.colors.error.clone(),
+            }
+        } else {
+            EffectiveColors {
+                base: self.colors.base.clone(),
+                surface: self.colors.surface.clone(),
+                text: self.colors.text.clone(),
+                text_secondary: self.colors.text_secondary.clone(),
+                accent: self.colors.accent.clone(),
+                accent_secondary: self.colors.accent_secondary.clone(),
+                border: self.colors.border.clone(),
+                shadow: self.colors.shadow.clone(),
+                success: self.colors.success.clone(),
+                warning: self.colors.warning.clone(),
+                error: self.colors.error.clone(),
+            }
+        }
+    }
+
+    /// Get effective glassmorphism settings with high contrast adjustments (Story 4.5: Task 2.1-2.4)
+    ///
+    /// When high contrast is active:
+    /// - background_opacity is set to 0.95 (nearly opaque)
+    /// - blur_radius is set to 0 (disabled)
+    /// - border_opacity is set to 0.60 (more visible)
+    pub fn get_effective_glassmorphism(&self, high_contrast: bool) -> EffectiveGlassmorphism {
+        if high_contrast {
+            let hc = HighContrastSettings::default();
+            EffectiveGlassmorphism {
+                blur_radius: hc.blur_radius,           // 0 - disabled
+                background_opacity: hc.background_opacity, // 0.95
+                saturation: 1.0,                       // Normal saturation
+                border_opacity: hc.border_opacity,     // 0.60
+                noise_opacity: 0.0,                    // Disabled for clarity
+            }
+        } else {
+            EffectiveGlassmorphism {
+                blur_radius: self.glassmorphism.blur_radius,
+                background_opacity: self.glassmorphism.background_opacity,
+                saturation: self.glassmorphism.saturation,
+                border_opacity: self.glassmorphism.border_opacity,
+                noise_opacity: self.glassmorphism.noise_opacity,
+            }
+        }
+    }
+
+    /// [`get_effective_colors`](Self::get_effective_colors) and
+    /// [`get_effective_glassmorphism`](Self::get_effective_glassmorphism), packed
+    pub fn get_packed_colors(&self, high_contrast: bool) -> PackedThemeColors {
+        PackedThemeColors {
+            colors: self.get_effective_colors(high_contrast).packed(),
+            glassmorphism: self.get_effective_glassmorphism(high_contrast).packed(),
+        }
+    }
+
+    /// Background opacity from [`get_effective_glassmorphism`](Self::get_effective_glassmorphism)
+    /// without building the rest
+    pub fn effective_background_opacity(&self, high_contrast: bool) -> f32 {
+        if high_contrast {
+            HIGH_CONTRAST_BACKGROUND_OPACITY
+        } else {
+            self.glassmorphism.background_opacity
+        }
+    }
+
+    /// Texture to draw behind the slices; none in high contrast, which
+    /// paints solid slices
+    pub fn effective_background_texture(&self, high_contrast: bool) -> Option<&ThemeTexture> {
+        self.glassmorphism.texture.as_ref().filter(|_| !high_contrast)
+    }
+
+    /// Get high contrast settings for selection styling
+    pub fn get_high_contrast_settings() -> HighContrastSettings {
+        HighContrastSettings::default()
+    }
+
+    /// Parse theme from JSON string (Story 4.2: Task 2.2)
+    ///
+    /// Used by bundled_themes to parse embedded JSON.
+    pub fn from_json(json: &str) -> Result<Self, ThemeError> {
+        let mut theme = parse_theme(json.as_bytes())?;
+
+        // Set display_name from name if not provided
+        if theme.display_name.is_empty() {
+            theme.display_name = theme.name.as_str().into();
+        }
+
+        Ok(theme)
+    }
+
+    /// Load theme from a JSON file (Story 4.1: Task 2.1, 2.2)
+    ///
+    /// Files over [`MAX_THEME_FILE_BYTES`] are rejected without being read.
+    pub fn load_from_path(path: &Path) -> Result<Self, ThemeError> {
+        let mut json = Vec::new();
+        open_limited(path, MAX_THEME_FILE_BYTES)
+            .and_then(|mut reader| reader.read_to_end(&mut json))
+            .map_err(ThemeError::IoError)?;
+        let mut theme = parse_theme(&json)?;
+
+        // Extract theme name from the directory (themes/{name}/theme.json)
+        // or the file stem (themes/{name}.json) if not set
+        if theme.name.is_empty() {
+            let inferred = if is_flat_theme_file(path) {
+                path.file_stem()
+            } else {
+                path.parent().and_then(|p| p.file_name())
+            };
+            if let Some(name) = inferred {
+                theme.name = name.to_string_lossy().to_string();
+            }
+        }
+
+        // Set display_name from name if not provided
+        if theme.display_name.is_empty() {
+            theme.display_name = theme.name.as_str().into();
+        }
+
+        if let Some(dir) = path.parent() {
+            theme.resolve_background_texture(dir);
+        }
+
+        Ok(theme)
+    }
+
+    /// Check `glassmorphism.backgroundTexture` against `theme_dir`, the
+    /// directory holding the theme file
+    ///
+    /// A texture that cannot be used is dropped, so the slices stay flat, and
+    /// comes back here and from [`Theme::validate_and_clamp`] as a warning
+    /// rather than failing the theme.
+    pub fn resolve_background_texture(&mut self, theme_dir: &Path) -> Option<&ValidationIssue> {
+        let glass = &mut self.glassmorphism;
+        glass.texture = None;
+        glass.texture_error = None;
+        let relative = glass.background_texture.as_deref()?;
+        match ThemeTexture::load(theme_dir, relative) {
+            Ok(texture) => glass.texture = Some(texture),
+            Err(e) => {
+                let message = format!("background texture {} not used, drawing flat slices: {}", relative, e);
+                glass.texture_error = Some(
+                    ValidationIssue::warning("glassmorphism.backgroundTexture", IssueCode::InvalidTexture, message)
+                        .got(relative),
+                );
+            }
+        }
+        glass.texture_error.as_ref()
+    }
+
+    /// Validate and clamp theme values to valid ranges (Story 4.1: Task 3)
+    pub fn validate_and_clamp(&mut self) -> ValidationResult {
+        let mut result = ValidationResult::new();
+
+        // Validate blur_radius: 8-48 (Task 3.2)
+        if self.glassmorphism.blur_radius < 8 {
+            result.push(ValidationIssue::clamped("glassmorphism.blurRadius", self.glassmorphism.blur_radius, "8-48", format!(
+                "blur_radius {} below minimum 8, clamping",
+                self.glassmorphism.blur_radius
+            )));
+            self.glassmorphism.blur_radius = 8;
+        } else if self.glassmorphism.blur_radius > 48 {
+            result.push(ValidationIssue::clamped("glassmorphism.blurRadius", self.glassmorphism.blur_radius, "8-48", format!(
+                "blur_radius {} above maximum 48, clamping",
+                self.glassmorphism.blur_radius
+            )));
+            self.glassmorphism.blur_radius = 48;
+        }
+
+        // Validate background_opacity: 0.5-0.95 (Task 3.3)
+        if self.glassmorphism.background_opacity < 0.5 {
+            result.push(ValidationIssue::clamped("glassmorphism.backgroundOpacity", self.glassmorphism.background_opacity, "0.5-0.95", format!(
+                "background_opacity {} below minimum 0.5, clamping",
+                self.glassmorphism.background_opacity
+            )));
+            self.glassmorphism.background_opacity = 0.5;
+        } else if self.glassmorphism.background_opacity > 0.95 {
+            result.push(ValidationIssue::clamped("glassmorphism.backgroundOpacity", self.glassmorphism.background_opacity, "0.5-0.95", format!(
+                "background_opacity {} above maximum 0.95, clamping",
+                self.glassmorphism.background_opacity
+            )));
+            self.glassmorphism.background_opacity = 0.95;
+        }
+
+        // Validate saturation: 1.0-2.5 (Task 3.4)
+        if self.glassmorphism.saturation < 1.0 {
+            result.push(ValidationIssue::clamped("glassmorphism.saturation", self.glassmorphism.saturation, "1.0-2.5", format!(
+                "saturation {} below minimum 1.0, clamping",
+                self.glassmorphism.saturation
+            )));
+            self.glassmorphism.saturation = 1.0;
+        } else if self.glassmorphism.saturation > 2.5 {
+            result.push(ValidationIssue::clamped("glassmorphism.saturation", self.glassmorphism.saturation, "1.0-2.5", format!(
+                "saturation {} above maximum 2.5, clamping",
+                self.glassmorphism.saturation
+            )));
+            self.glassmorphism.saturation = 2.5;
+        }
+
+        // Validate border_opacity: 0.0-0.5 (Task 3.5)
+        if self.glassmorphism.border_opacity < 0.0 {
+            result.push(ValidationIssue::clamped("glassmorphism.borderOpacity", self.glassmorphism.border_opacity, "0.0-0.5", format!(
+                "border_opacity {} below minimum 0.0, clamping",
+                self.glassmorphism.border_opacity
+            )));
+            self.glassmorphism.border_opacity = 0.0;
+        } else if self.glassmorphism.border_opacity > 0.5 {
+            result.push(ValidationIssue::clamped("glassmorphism.borderOpacity", self.glassmorphism.border_opacity, "0.0-0.5", format!(
+                "border_opacity {} above maximum 0.5, clamping",
+                self.glassmorphism.border_opacity
+            )));
+            self.glassmorphism.border_opacity = 0.5;
+        }
+
+        // Validate noise_opacity: 0.0-0.1 (Task 3.6)
+        if self.glassmorphism.noise_opacity < 0.0 {
+            result.push(ValidationIssue::clamped("glassmorphism.noiseOpacity", self.glassmorphism.noise_opacity, "0.0-0.1", format!(
+                "noise_opacity {} below minimum 0.0, clamping",
+                self.glassmorphism.noise_opacity
+            )));
+            self.glassmorphism.noise_opacity = 0.0;
+        } else if self.glassmorphism.noise_opacity > 0.1 {
+            result.push(ValidationIssue::clamped("glassmorphism.noiseOpacity", self.glassmorphism.noise_opacity, "0.0-0.1", format!(
+                "noise_opacity {} above maximum 0.1, clamping",
+                self.glassmorphism.noise_opacity
+            )));
+            self.glassmorphism.noise_opacity = 0.1;
+        }
+
+        // Validate glow_intensity: 0.0-2.0
+        if self.animation.glow_intensity < 0.0 {
+            result.push(ValidationIssue::clamped("animation.glowIntensity", self.animation.glow_intensity, "0.0-2.0", format!(
+                "glow_intensity {} below minimum 0.0, clamping",
+                self.animation.glow_intensity
+            )));
+            self.animation.glow_intensity = 0.0;
+        } else if self.animation.glow_intensity > 2.0 {
+            result.push(ValidationIssue::clamped("animation.glowIntensity", self.animation.glow_intensity, "0.0-2.0", format!(
+                "glow_intensity {} above maximum 2.0, clamping",
+                self.animation.glow_intensity
+            )));
+            self.animation.glow_intensity = 2.0;
+        }
+
+        // Validate the idle effect: a known name, density 0.0-1.0, speed
+        // 0.1-4.0, frame rate 1-60
+        if !IDLE_EFFECTS.contains(&self.animation.idle_effect.as_str()) {
+            result.push(
+                ValidationIssue::warning("animation.idleEffect", IssueCode::NotAllowed, format!(
+                    "idle_effect '{}' is not one of {}, using none",
+                    self.animation.idle_effect,
+                    IDLE_EFFECTS.join(", ")
+                ))
+                .got(self.animation.idle_effect.as_str())
+                .expected(IDLE_EFFECTS.join(", ")),
+            );
+            self.animation.idle_effect = "none".to_string();
+        }
+        for (field, value, min, max, default) in [
+            ("idleDensity", &mut self.animation.idle_density, 0.0, 1.0, default_idle_density()),
+            ("idleSpeed", &mut self.animation.idle_speed, 0.1, 4.0, default_idle_speed()),
+        ] {
+            // NaN fails both comparisons; it takes the default
+            let clamped = if value.is_nan() { default } else { value.clamp(min, max) };
+            if clamped != *value {
+                // NaN is not a JSON number; it is reported as null
+                result.push(ValidationIssue::clamped(format!("animation.{}", field), *value, format!("{}-{}", min, max), format!(
+                    "{} {} outside {}-{}, clamping to {}",
+                    field, value, min, max, clamped
+                )));
+                *value = clamped;
+            }
+        }
+        if !(1..=60).contains(&self.animation.idle_max_fps) {
+            let clamped = self.animation.idle_max_fps.clamp(1, 60);
+            result.push(ValidationIssue::clamped("animation.idleMaxFps", self.animation.idle_max_fps, "1-60", format!(
+                "idle_max_fps {} outside 1-60, clamping to {}",
+                self.animation.idle_max_fps, clamped
+            )));
+            self.animation.idle_max_fps = clamped;
+        }
+
+        // Validate color hex formats (Task 3.7)
+        let color_fields = [
+            ("base", &self.colors.base),
+            ("surface", &self.colors.surface),
+            ("text", &self.colors.text),
+            ("text_secondary", &self.colors.text_secondary),
+            ("accent", &self.colors.accent),
+            ("accent_secondary", &self.colors.accent_secondary),
+            ("border", &self.colors.border),
+            ("shadow", &self.colors.shadow),
+            ("success", &self.colors.success),
+            ("warning", &self.colors.warning),
+            ("error", &self.colors.error),
+        ];
+
+        for issue in &self.colors.derivation_errors {
+            result.errors.push(issue.clone());
+        }
+        if let Some(issue) = &self.glassmorphism.texture_error {
+            result.push(issue.clone());
+        }
+        for (name, value) in color_fields {
+            let field = format!("colors.{}", snake_to_camel(name));
+            if self.colors.derivation_errors.iter().any(|issue| issue.field == field) {
+                continue;
+            }
+            if !is_valid_hex_color(value) {
+                result.push(
+                    ValidationIssue::error(&field, IssueCode::InvalidColor, format!(
+                        "Invalid hex color for {}: '{}' (expected #RRGGBB)",
+                        name, value
+                    ))
+                    .got(value.as_str())
+                    .expected("#RRGGBB"),
+                );
+            }
+        }
+
+        result
+    }
+}
+
+/// Parse theme.json; a file that is valid JSON but not a valid theme comes
+/// back as [`ThemeError::Invalid`] listing every problem found
+///
+/// The typed parse runs first; the field-by-field walk only happens once it
+/// has failed.
+fn parse_theme(json: &[u8]) -> Result<Theme, ThemeError> {
+    serde_json::from_slice(json).map_err(|e| {
+        let Ok(doc) = serde_json::from_slice::<serde_json::Value>(json) else {
+            return ThemeError::ParseError(e);
+        };
+        let result = diagnose(&doc);
+        if result.has_errors() {
+            ThemeError::Invalid(result)
+        } else {
+            // Something the walk does not check (e.g. a number out of f32 range)
+            ThemeError::ParseError(e)
+        }
+    })
+}
+
+/// Palette keys a theme must set; the rest have defaults
+const REQUIRED_COLORS: [&str; 5] = ["base", "surface", "text", "accent", "border"];
+
+/// What a theme.json field must hold
+#[derive(Debug, Clone, Copy)]
+enum Expect {
+    String,
+    Number,
+    Byte,
+    Bool,
+    Object,
+    Strings,
+    /// A string or a map of language tags to strings
+    Text,
+}
+
+impl Expect {
+    fn matches(self, value: &serde_json::Value) -> bool {
+        match self {
+            Expect::String => value.is_string(),
+            Expect::Number => value.is_number(),
+            Expect::Byte => value.as_u64().is_some_and(|n| n <= u64::from(u8::MAX)),
+            Expect::Bool => value.is_boolean(),
+            Expect::Object => value.is_object(),
+            Expect::Strings => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(serde_json::Value::is_string)),
+            Expect::Text => value.is_string() || value
+                .as_object()
+                .is_some_and(|map| !map.is_empty() && map.values().all(serde_json::Value::is_string)),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Expect::String => "a string",
+            Expect::Number => "a number",
+            Expect::Byte => "a whole number from 0 to 255",
+            Expect::Bool => "true or false",
+            Expect::Object => "an object",
+            Expect::Strings => "a list of strings",
+            Expect::Text => "a string or an object of language tags to strings",
+        }
+    }
+}
+
+/// JSON type name of `value` for messages
+fn json_kind(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+/// Field-level problems of a theme.json document, all in one pass
+///
+/// Covers what makes the typed parse fail: missing sections and palette
+/// colors, and values of the wrong JSON type. Range and hex checks stay with
+/// [`Theme::validate_and_clamp`].
+fn diagnose(doc: &serde_json::Value) -> ValidationResult {
+    use serde_json::Value;
+
+    let mut result = ValidationResult::new();
+    let Some(root) = doc.as_object() else {
+        result.push(ValidationIssue::wrong_type("", json_kind(doc), "an object", format!(
+            "theme should be an object, found {}",
+            json_kind(doc)
+        )));
+        return result;
+    };
+
+    let check = |result: &mut ValidationResult, field: String, value: &Value, expect: Expect, optional: bool| {
+        if !(expect.matches(value) || optional && value.is_null()) {
+            let message = format!("{} should be {}, found {}", field, expect.describe(), json_kind(value));
+            result.push(ValidationIssue::wrong_type(field, json_kind(value), expect.describe(), message));
+        }
+    };
+    let section = |result: &mut ValidationResult, key: &str, fields: &[(&str, Expect)], optional: bool| {
+        let Some(value) = root.get(key) else {
+            if !optional {
+                result.push(ValidationIssue::missing(key, format!("missing required section {}", key)));
+            }
+            return;
+        };
+        check(result, key.to_string(), value, Expect::Object, optional);
+        for (name, expect) in fields {
+            if let Some(value) = value.get(name) {
+                check(result, format!("{}.{}", key, name), value, *expect, optional);
+            }
+        }
+    };
+
+    for (key, expect) in [("name", Expect::String), ("display_name", Expect::Text), ("version", Expect::String), ("author", Expect::String)] {
+        if let Some(value) = root.get(key) {
+            check(&mut result, key.to_string(), value, expect, false);
+        }
+    }
+    let effects = if root.contains_key("glassmorphism") || !root.contains_key("effects") {
+        "glassmorphism"
+    } else {
+        "effects"
+    };
+    section(&mut result, effects, &[
+        ("blurRadius", Expect::Byte),
+        ("backgroundOpacity", Expect::Number),
+        ("saturation", Expect::Number),
+        ("borderOpacity", Expect::Number),
+        ("noiseOpacity", Expect::Number),
+        ("backgroundTexture", Expect::String),
+    ], false);
+    section(&mut result, "animation", &[
+        ("glowIntensity", Expect::Number),
+        ("enableParticles", Expect::Bool),
+        ("idleEffect", Expect::String),
+        ("idleDensity", Expect::Number),
+        ("idleSpeed", Expect::Number),
+        ("idleMaxFps", Expect::Byte),
+    ], false);
+    section(&mut result, "overrides", &[
+        ("sliceColors", Expect::Strings),
+        ("customFont", Expect::String),
+    ], true);
+
+    match root.get("colors") {
+        None => result.push(ValidationIssue::missing("colors", "missing required section colors")),
+        Some(Value::Object(colors)) => {
+            for key in COLOR_KEYS {
+                let field = format!("colors.{}", key);
+                match colors.get(key) {
+                    None if REQUIRED_COLORS.contains(&key) => {
+                        result.push(ValidationIssue::missing(&field, format!("{} is required", field)));
+                    }
+                    None | Some(Value::String(_)) => {}
+                    Some(value @ Value::Object(_)) => {
+                        if let Err(e) = serde_json::from_value::<ColorDerivation>(value.clone()) {
+                            let message = format!("{} is not a valid derivation: {}", field, e);
+                            result.push(ValidationIssue::error(field, IssueCode::InvalidDerivation, message));
+                        }
+                    }
+                    Some(value) => {
+                        let message = format!(
+                            "{} should be a string like \"#89b4fa\" or a derivation object, found {}",
+                            field, json_kind(value)
+                        );
+                        result.push(ValidationIssue::wrong_type(field, json_kind(value), "a color", message));
+                    }
+                }
+            }
+        }
+        Some(value) => check(&mut result, "colors".to_string(), value, Expect::Object, false),
+    }
+
+    result
+}
+
+/// Check if a string is a valid hex color (#RRGGBB or #RGB)
+fn is_valid_hex_color(color: &str) -> bool {
+    if !color.starts_with('#') {
+        return false;
+    }
+    let hex = &color[1..];
+    // Accept #RGB (3 chars) or #RRGGBB (6 chars)
+    (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// theme.json key for a snake_case field name (`text_secondary` -> `textSecondary`)
+fn snake_to_camel(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.push(c.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Where a loaded theme came from (Story 4.2: Task 3.3)
+///
+/// Ordered by priority: a theme from a later source shadows one with the
+/// same name from an earlier source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeSource {
+    /// Compiled into the daemon binary
+    Bundled,
+    /// System themes directory (`<data dir>/themes/`)
+    System,
+    /// User themes directory (~/.config/juhradial/themes/)
+    User,
+}
+
+impl ThemeSource {
+    /// Lowercase name used in logs and D-Bus output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThemeSource::Bundled => "bundled",
+            ThemeSource::System => "system",
+            ThemeSource::User => "user",
+        }
+    }
+}
+
+impl std::fmt::Display for ThemeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Provenance of a loaded theme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThemeOrigin {
+    /// Source the theme was loaded from
+    pub source: ThemeSource,
+    /// theme.json path (None for bundled themes)
+    pub path: Option<PathBuf>,
+}
+
+impl ThemeOrigin {
+    /// Origin of a theme compiled into the binary
+    pub fn bundled() -> Self {
+        Self {
+            source: ThemeSource::Bundled,
+            path: None,
+        }
+    }
+
+    /// Path for log output, `<bundled>` when there is no file
+    fn display_path(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "<bundled>".to_string(),
+        }
+    }
+}
+
+/// Where a bundled theme's background texture is installed:
+/// `<data dir>/assets/themes/{name}/`
+pub fn bundled_texture_dir(data_dir: &DataDir, name: &str) -> PathBuf {
+    data_dir.assets_dir().join("themes").join(name)
+}
+
+/// Theme manager shared between the D-Bus service and background watchers
+pub type SharedThemeManager = Arc<RwLock<ThemeManager>>;
+
+/// Theme manager for loading and switching themes (Story 4.1: Task 1.1)
+pub struct ThemeManager {
+    /// All loaded themes by name
+    themes: HashMap<String, Theme>,
+
+    /// Where each loaded theme came from, keyed by theme name
+    origins: HashMap<String, ThemeOrigin>,
+
+    /// Content hash of each loaded theme, keyed by theme name
+    hashes: HashMap<String, ContentHash>,
+
+    /// Current active theme name
+    current_theme: String,
+
+    /// Accent that replaces the current theme's own (the desktop accent)
+    accent_override: Option<String>,
+
+    /// The current theme with `accent_override` applied, and its hash
+    accented: Option<(Theme, ContentHash)>,
+}
+
+impl ThemeManager {
+    /// Create a new theme manager with all bundled themes (Story 4.2: Task 3)
+    pub fn new() -> Self {
+        let mut manager = Self::empty();
+        manager.load_bundled(crate::paths::data_dir());
+        manager.ensure_fallback();
+        manager.current_theme = "catppuccin-mocha".to_string();
+        manager
+    }
+
+    /// Load all themes from bundled, system, and user directories (Story 4.2: Task 3)
+    ///
+    /// Loading order (later overrides earlier):
+    /// 1. Bundled themes (always available)
+    /// 2. System themes (`<data dir>/themes/`)
+    /// 3. User themes (~/.config/juhradial/themes/)
+    ///
+    /// Without the `themes` feature only the bundled themes are loaded.
+    pub fn load_all() -> Result<Self, ThemeError> {
+        Self::load_all_in(crate::paths::paths())
+    }
+
+    /// [`load_all`](Self::load_all) from the theme directories of `paths`
+    pub fn load_all_in(paths: &Paths) -> Result<Self, ThemeError> {
+        let mut manager = Self::empty();
+
+        // Step 1: Load bundled themes first (Story 4.2: Task 3.1, 3.2)
+        manager.load_bundled(paths.data_dir());
+
+        if cfg!(feature = "themes") {
+            let [system_dir, user_dir] = paths.themes_dirs();
+            // Step 2: Load system themes (override bundled with same name)
+            manager.load_from_dir(&system_dir, ThemeSource::System);
+
+            // Step 3: Load user themes (override system and bundled)
+            manager.load_from_dir(&user_dir, ThemeSource::User);
+        }
+
+        // Should always have bundled themes, but fallback just in case
+        manager.ensure_fallback();
+
+        // Determine initial theme (prefer catppuccin-mocha if available)
+        manager.current_theme = if manager.themes.contains_key("catppuccin-mocha") {
+            "catppuccin-mocha".to_string()
+        } else {
+            manager.themes.keys().next().cloned().unwrap_or_default()
+        };
+
+        tracing::info!(
+            theme_count = manager.themes.len(),
+            current = %manager.current_theme,
+            bundled = crate::bundled_themes::list_bundled_themes().len(),
+            "Theme manager initialized"
+        );
+
+        Ok(manager)
+    }
+
+    /// Manager with no themes loaded yet
+    fn empty() -> Self {
+        Self {
+            themes: HashMap::new(),
+            origins: HashMap::new(),
+            hashes: HashMap::new(),
+            current_theme: String::new(),
+            accent_override: None,
+            accented: None,
+        }
+    }
+
+    /// Insert all bundled themes (Story 4.2: Task 3.1, 3.2)
+    fn load_bundled(&mut self, data_dir: &DataDir) {
+        for theme_name in crate::bundled_themes::list_bundled_themes() {
+            if let Some(mut theme) = crate::bundled_themes::get_bundled_theme(theme_name) {
+                if let Some(issue) = theme.resolve_background_texture(&bundled_texture_dir(data_dir, theme_name)) {
+                    tracing::warn!(theme = %theme_name, warning = %issue, "Theme validation warning");
+                }
+                tracing::debug!(theme = %theme.name, "Loaded bundled theme");
+                self.insert_with_origin(theme, ThemeOrigin::bundled());
+            }
+        }
+    }
+
+    /// Fallback to hardcoded default if bundled themes fail (shouldn't happen)
+    fn ensure_fallback(&mut self) {
+        if self.themes.is_empty() {
+            tracing::warn!("No themes loaded, using fallback Catppuccin Mocha");
+            self.insert_with_origin(Theme::catppuccin_mocha(), ThemeOrigin::bundled());
+        }
+    }
+
+    /// Load every valid theme found in `dir`, recording `source` as its origin
+    ///
+    /// Invalid or unreadable themes are logged and skipped. Returns the number
+    /// of themes loaded.
+    ///
+    /// Stops once [`MAX_LOADED_THEMES`] system and user themes are loaded and
+    /// logs the files it did not read.
+    pub fn load_from_dir(&mut self, dir: &Path, source: ThemeSource) -> usize {
+        let mut loaded = 0;
+
+        let paths = scan_themes_directory(dir);
+        for (index, theme_path) in paths.iter().cloned().enumerate() {
+            if self.external_count() >= MAX_LOADED_THEMES {
+                let skipped = &paths[index..];
+                tracing::warn!(
+                    source = %source,
+                    dir = %dir.display(),
+                    limit = MAX_LOADED_THEMES,
+                    skipped = skipped.len(),
+                    "Theme limit reached, skipping remaining theme files"
+                );
+                for path in skipped {
+                    tracing::debug!(path = %path.display(), "Theme skipped by limit");
+                }
+                break;
+            }
+            match Theme::load_from_path(&theme_path) {
+                Ok(mut theme) => {
+                    if self.shadowed_by_subdirectory(&theme.name, &theme_path) {
+                        tracing::warn!(
+                            theme = %theme.name,
+                            path = %theme_path.display(),
+                            "Flat theme file has the same name as a theme subdirectory, skipping"
+                        );
+                        continue;
+                    }
+
+                    let validation = theme.validate_and_clamp();
+
+                    for warning in &validation.warnings {
+                        tracing::warn!(
+                            theme = %theme.name,
+                            warning = %warning,
+                            "Theme validation warning"
+                        );
+                    }
+
+                    if validation.has_errors() {
+                        for error in &validation.errors {
+                            tracing::warn!(
+                                theme = %theme.name,
+                                source = %source,
+                                error = %error,
+                                path = %theme_path.display(),
+                                "Skipping invalid theme"
+                            );
+                        }
+                        continue;
+                    }
+
+                    tracing::info!(
+                        theme = %theme.name,
+                        source = %source,
+                        path = %theme_path.display(),
+                        "Loaded theme"
+                    );
+                    self.insert_with_origin(
+                        theme,
+                        ThemeOrigin {
+                            source,
+                            path: Some(theme_path),
+                        },
+                    );
+                    loaded += 1;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        source = %source,
+                        path = %theme_path.display(),
+                        error = %e,
+                        "Failed to load theme, skipping"
+                    );
+                }
+            }
+        }
+
+        loaded
+    }
+
+    /// Loaded themes that came from the system or user directories
+    fn external_count(&self) -> usize {
+        self.origins
+            .values()
+            .filter(|o| o.source != ThemeSource::Bundled)
+            .count()
+    }
+
+    /// Insert a theme, logging when it shadows one from a lower-priority source
+    fn insert_with_origin(&mut self, theme: Theme, origin: ThemeOrigin) {
+        if let Some(previous) = self.origins.get(&theme.name) {
+            if previous.source < origin.source {
+                tracing::info!(
+                    theme = %theme.name,
+                    source = %origin.source,
+                    path = %origin.display_path(),
+                    shadowed_source = %previous.source,
+                    shadowed_path = %previous.display_path(),
+                    "Theme shadows lower-priority theme"
+                );
+            }
+        }
+
+        self.origins.insert(theme.name.clone(), origin);
+        self.hashes.insert(theme.name.clone(), ContentHash::of(&theme));
+        let is_current = theme.name == self.current_theme;
+        self.themes.insert(theme.name.clone(), theme);
+        if is_current {
+            self.refresh_accented();
+        }
+    }
+
+    /// Get the current active theme
+    ///
+    /// With an accent override this is the theme with that accent.
+    pub fn current(&self) -> &Theme {
+        if let Some((theme, _)) = &self.accented {
+            return theme;
+        }
+        self.themes
+            .get(&self.current_theme)
+            .expect("Current theme must exist")
+    }
+
+    /// Draw the current theme, and any theme switched to later, with
+    /// `accent` instead of its own; `None` restores the theme's accent
+    ///
+    /// Returns whether the override changed.
+    pub fn set_accent_override(&mut self, accent: Option<&str>) -> bool {
+        if self.accent_override.as_deref() == accent {
+            return false;
+        }
+        self.accent_override = accent.map(str::to_string);
+        self.refresh_accented();
+        true
+    }
+
+    /// The accent drawn instead of the theme's own, if any
+    pub fn accent_override(&self) -> Option<&str> {
+        self.accent_override.as_deref()
+    }
+
+    /// Rebuild the accented current theme after it or the override changed
+    fn refresh_accented(&mut self) {
+        self.accented = match (&self.accent_override, self.themes.get(&self.current_theme)) {
+            (Some(accent), Some(theme)) => {
+                let theme = theme.with_accent(accent);
+                let hash = ContentHash::of(&theme);
+                Some((theme, hash))
+            }
+            _ => None,
+        };
+    }
+
+    /// Set current theme by name
+    pub fn set_current(&mut self, name: &str) -> Result<(), ThemeError> {
+        if self.themes.contains_key(name) {
+            self.current_theme = name.to_string();
+            self.refresh_accented();
+            tracing::info!(theme = %name, "Switched to theme");
+            Ok(())
+        } else {
+            Err(ThemeError::NotFound(name.to_string()))
+        }
+    }
+
+    /// Get a theme by name
+    pub fn get(&self, name: &str) -> Option<&Theme> {
+        self.themes.get(name)
+    }
+
+    /// Get where a theme was loaded from
+    pub fn origin(&self, name: &str) -> Option<&ThemeOrigin> {
+        self.origins.get(name)
+    }
+
+    /// Content hash of a loaded theme
+    pub fn hash(&self, name: &str) -> Option<ContentHash> {
+        self.hashes.get(name).copied()
+    }
+
+    /// Content hash of the current theme, accent override included
+    pub fn current_hash(&self) -> ContentHash {
+        if let Some((_, hash)) = &self.accented {
+            return *hash;
+        }
+        self.hash(&self.current_theme).expect("Current theme must exist")
+    }
+
+    /// Whether `theme` is the loaded theme of that name, unchanged, from `path`
+    ///
+    /// Lets a file watcher skip a save that did not change the theme.
+    pub fn is_unchanged(&self, theme: &Theme, path: &Path) -> bool {
+        self.origins.get(&theme.name).and_then(|o| o.path.as_deref()) == Some(path)
+            && self.hash(&theme.name) == Some(ContentHash::of(theme))
+    }
+
+    /// Whether a flat theme file at `path` loses to a subdirectory theme of the
+    /// same name in the same themes directory
+    pub fn shadowed_by_subdirectory(&self, name: &str, path: &Path) -> bool {
+        if !is_flat_theme_file(path) {
+            return false;
+        }
+        let dir = path.parent();
+        self.origins
+            .get(name)
+            .and_then(|origin| origin.path.as_deref())
+            .is_some_and(|existing| {
+                !is_flat_theme_file(existing) && existing.parent().and_then(Path::parent) == dir
+            })
+    }
+
+    /// Get all themes with their origin, sorted by name
+    pub fn themes_with_sources(&self) -> Vec<(&Theme, &ThemeOrigin)> {
+        let mut entries: Vec<(&Theme, &ThemeOrigin)> = self
+            .themes
+            .iter()
+            .filter_map(|(name, theme)| self.origins.get(name).map(|origin| (theme, origin)))
+            .collect();
+        entries.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+        entries
+    }
+
+    /// Get all theme names
+    pub fn theme_names(&self) -> Vec<&String> {
+        self.themes.keys().collect()
+    }
+
+    /// Get theme count
+    pub fn theme_count(&self) -> usize {
+        self.themes.len()
+    }
+
+    /// Check if a theme exists
+    pub fn has_theme(&self, name: &str) -> bool {
+        self.themes.contains_key(name)
+    }
+
+    /// Add a new theme or update an existing one (Story 4.3: hot-reload support)
+    ///
+    /// This method is used by the hot-reloader to update themes without restarting.
+    /// An existing theme keeps its origin; a new theme is recorded as a user theme.
+    pub fn add_or_update_theme(&mut self, theme: Theme) {
+        let origin = self.origins.get(&theme.name).cloned().unwrap_or(ThemeOrigin {
+            source: ThemeSource::User,
+            path: None,
+        });
+        self.add_or_update_theme_from(theme, origin);
+    }
+
+    /// Add or update a theme loaded from a known origin
+    ///
+    /// A reload from a lower-priority source does not replace a theme that
+    /// shadows it.
+    pub fn add_or_update_theme_from(&mut self, theme: Theme, origin: ThemeOrigin) {
+        let name = theme.name.clone();
+
+        if let Some(existing) = self.origins.get(&name) {
+            if existing.source > origin.source {
+                tracing::debug!(
+                    theme = %name,
+                    path = %origin.display_path(),
+                    shadowed_by = %existing.display_path(),
+                    "Reloaded theme is shadowed, keeping higher-priority version"
+                );
+                return;
+            }
+        }
+
+        let is_update = self.themes.contains_key(&name);
+        self.insert_with_origin(theme, origin);
+
+        if is_update {
+            tracing::debug!(theme = %name, "Updated existing theme");
+        } else {
+            tracing::debug!(theme = %name, "Added new theme");
+        }
+    }
+
+    /// Remove a theme by name
+    ///
+    /// Returns the removed theme if it existed.
+    /// Cannot remove the current theme or bundled themes.
+    pub fn remove_theme(&mut self, name: &str) -> Option<Theme> {
+        // Cannot remove current theme
+        if name == self.current_theme {
+            tracing::warn!(theme = %name, "Cannot remove currently active theme");
+            return None;
+        }
+
+        // Cannot remove bundled themes
+        if crate::bundled_themes::is_bundled_theme(name) {
+            tracing::warn!(theme = %name, "Cannot remove bundled theme");
+            return None;
+        }
+
+        self.origins.remove(name);
+        self.hashes.remove(name);
+        self.themes.remove(name)
+    }
+}
+
+impl Default for ThemeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Load all themes and select `current`, falling back to the default theme
+pub fn load_theme_manager(current: &str) -> ThemeManager {
+    let mut manager = ThemeManager::load_all().unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Failed to load themes, using bundled themes");
+        ThemeManager::new()
+    });
+    if let Err(e) = manager.set_current(current) {
+        tracing::warn!(error = %e, "Configured theme unavailable, keeping default");
+    }
+    manager
+}
+
+/// Shared variant of [`load_theme_manager`]
+pub fn load_shared_theme_manager(current: &str) -> SharedThemeManager {
+    Arc::new(RwLock::new(load_theme_manager(current)))
+}
+
+/// Get system themes directory path (Story 4.1: Task 1.2)
+///
+/// `themes/` under the resolved data directory ([`crate::paths::data_dir`]).
+pub fn get_system_themes_dir() -> PathBuf {
+    crate::paths::data_dir().themes_dir()
+}
+
+/// Get user themes directory path (XDG compliant) (Story 4.1: Task 1.3)
+///
+/// `themes/` under the config directory, see [`crate::paths`].
+pub fn get_user_themes_dir() -> PathBuf {
+    crate::paths::paths().user_themes_dir()
+}
+
+/// Scan a directory for theme files (Story 4.1: Task 1.4)
+///
+/// Two layouts are accepted: `themes/{theme-name}/theme.json` and a flat
+/// `themes/{theme-name}.json`. Subdirectory themes come first, so they win a
+/// name collision when loaded in order. A top-level `theme.json` names no
+/// theme and is skipped.
+pub fn scan_themes_directory(dir: &Path) -> Vec<PathBuf> {
+    let mut subdir_files = Vec::new();
+    let mut flat_files = Vec::new();
+
+    if !dir.is_dir() {
+        return subdir_files;
+    }
+
+    // Read directory entries
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+
+            if path.is_dir() {
+                // Subdirectory containing theme.json
+                let theme_json = path.join(THEME_FILENAME);
+                if theme_json.is_file() {
+                    subdir_files.push(theme_json);
+                }
+            } else if path.file_name().is_some_and(|n| n == THEME_FILENAME) {
+                tracing::warn!(
+                    path = %path.display(),
+                    "Top-level theme.json is ambiguous, skipping; rename it to <name>.json or move it into <name>/"
+                );
+            } else if is_flat_theme_file(&path) && path.is_file() {
+                flat_files.push(path);
+            }
+        }
+    }
+
+    subdir_files.sort();
+    flat_files.sort();
+    subdir_files.extend(flat_files);
+    subdir_files
+}
+
+/// Open a JSON file for parsing, refusing files larger than `limit` bytes
+///
+/// Oversized files fail with [`io::ErrorKind::FileTooLarge`] before any of
+/// them is read. The reader stops at `limit` too, so a file that grows after
+/// the size check fails to parse instead of being read in full.
+pub fn open_limited(path: &Path, limit: u64) -> io::Result<impl Read> {
+    let file = fs::File::open(path)?;
+    let bytes = file.metadata()?.len();
+    if bytes > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::FileTooLarge,
+            format!("{} bytes, over the {} byte limit", bytes, limit),
+        ));
+    }
+    Ok(BufReader::new(file.take(limit)))
+}
+
+/// Whether `path` names a flat theme file (`{name}.json`, not `theme.json`)
+///
+/// Hidden files (editor swap and backup files) are not themes.
+pub fn is_flat_theme_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    name != THEME_FILENAME
+        && !name.starts_with('.')
+        && path.extension().is_some_and(|e| e == "json")
+}
+
+/// Theme error type
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ThemeError {
+    /// Theme not found
+    #[error("Theme not found: {0}")]
+    NotFound(String),
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(std::io::Error),
+    /// JSON parse error
+    #[error("JSON parse error: {0}")]
+    ParseError(serde_json::Error),
+    /// Validation error
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+    /// Valid JSON that does not describe a theme, with every problem found
+    #[error("Invalid theme: {}", issue_list(.0))]
+    Invalid(ValidationResult),
+}
+
+/// The error messages of `result`, joined for a single log line
+fn issue_list(result: &ValidationResult) -> String {
+    result.errors.iter().map(|issue| issue.message.as_str()).collect::<Vec<_>>().join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Task 4.1: Test animation timing override
+    #[test]
+    fn test_reduced_motion_timings() {
+        let timings = EffectiveAnimationTimings::reduced_motion();
+
+        assert_eq!(timings.appear_ms, 0);
+        assert_eq!(timings.dismiss_ms, 0);
+        assert_eq!(timings.highlight_in_ms, 0);
+        assert_eq!(timings.highlight_out_ms, 0);
+        assert!(!timings.icon_scale_enabled);
+        assert!(!timings.idle_effects_enabled);
+    }
+
+    #[test]
+    fn test_default_timings() {
+        let timings = EffectiveAnimationTimings::default_timings();
+
+        assert_eq!(timings.appear_ms, 30);
+        assert_eq!(timings.dismiss_ms, 50);
+        assert_eq!(timings.highlight_in_ms, 80);
+        assert_eq!(timings.highlight_out_ms, 60);
+        assert!(timings.icon_scale_enabled);
+        assert!(timings.idle_effects_enabled);
+    }
+    use tempfile::TempDir;
+
+    // Task 6.1: Test valid theme parsing with all fields
+    #[test]
+    fn test_valid_theme_parsing() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("test-theme");
+        fs::create_dir(&theme_dir).unwrap();
+
+        let theme_json = "{
+            \"name\": \"test-theme\",
+            \"display_name\": \"Test Theme\",
+            \"version\": \"1.0\",
+            \"author\": \"Test Author\",
+            \"colors\": {
+                \"base\": \"#1e1e2e\",
+                \"surface\": \"#313244\",
+                \"text\": \"#cdd6f4\",
+                \"textSecondary\": \"#bac2de\",
+                \"accent\": \"#b4befe\",
+                \"accentSecondary\": \"#89b4fa\",
+                \"border\": \"#585b70\",
+                \"shadow\": \"#11111b\",
+                \"success\": \"#a6e3a1\",
+                \"warning\": \"#fab387\",
+                \"error\": \"#f38ba8\"
+            },
+            \"glassmorphism\": {
+                \"blurRadius\": 24,
+                \"backgroundOpacity\": 0.75,
+                \"saturation\": 1.8,
+                \"borderOpacity\": 0.15,
+                \"noiseOpacity\": 0.04
+            },
+            \"animation\": {
+                \"glowIntensity\": 1.0,
+                \"enableParticles\": false,
+                \"idleEffect\": \"none\"
+            }
+        }";
+
+        let theme_path = theme_dir.join("theme.json");
+        fs::write(&theme_path, theme_json).unwrap();
+
+        let theme = Theme::load_from_path(&theme_path).unwrap();
+        assert_eq!(theme.name, "test-theme");
+        assert_eq!(theme.display_name, "Test Theme");
+        assert_eq!(theme.version, "1.0");
+        assert_eq!(theme.author, "Test Author");
+        assert_eq!(theme.colors.base, "#1e1e2e");
+        assert_eq!(theme.glassmorphism.blur_radius, 24);
+        assert_eq!(theme.animation.glow_intensity, 1.0);
+    }
+
+    // Task 6.2: Test validation of out-of-range values (clamp + warning)
+    #[test]
+    fn test_validation_clamps_out_of_range() {
+        let mut theme = Theme::catppuccin_mocha();
+
+        // Set out-of-range values
+        theme.glassmorphism.blur_radius = 100; // Max is 48
+        theme.glassmorphism.background_opacity = 0.1; // Min is 0.5
+        theme.glassmorphism.saturation = 5.0; // Max is 2.5
+        theme.glassmorphism.border_opacity = 1.0; // Max is 0.5
+        theme.glassmorphism.noise_opacity = 0.5; // Max is 0.1
+        theme.animation.glow_intensity = 10.0; // Max is 2.0
+
+        let result = theme.validate_and_clamp();
+
+        // Should have warnings but no errors
+        assert!(!result.warnings.is_empty());
+        assert!(result.is_valid());
+
+        // Values should be clamped
+        assert_eq!(theme.glassmorphism.blur_radius, 48);
+        assert_eq!(theme.glassmorphism.background_opacity, 0.5);
+        assert_eq!(theme.glassmorphism.saturation, 2.5);
+        assert_eq!(theme.glassmorphism.border_opacity, 0.5);
+        assert_eq!(theme.glassmorphism.noise_opacity, 0.1);
+        assert_eq!(theme.animation.glow_intensity, 2.0);
+    }
+
+    #[test]
+    fn test_validation_clamps_below_minimum() {
+        let mut theme = Theme::catppuccin_mocha();
+
+        theme.glassmorphism.blur_radius = 2; // Min is 8
+        theme.glassmorphism.saturation = 0.5; // Min is 1.0
+
+        let result = theme.validate_and_clamp();
+
+        assert!(!result.warnings.is_empty());
+        assert!(result.is_valid());
+        assert_eq!(theme.glassmorphism.blur_radius, 8);
+        assert_eq!(theme.glassmorphism.saturation, 1.0);
+    }
+
+    // Task 6.3: Test malformed JSON handling
+    #[test]
+    fn test_malformed_json_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("bad-theme");
+        fs::create_dir(&theme_dir).unwrap();
+
+        let theme_path = theme_dir.join("theme.json");
+        fs::write(&theme_path, "{ invalid json }").unwrap();
+
+        let result = Theme::load_from_path(&theme_path);
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), ThemeError::ParseError(_)));
+    }
+
+    #[test]
+    fn test_missing_required_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("incomplete-theme");
+        fs::create_dir(&theme_dir).unwrap();
+
+        // Missing colors field
+        let theme_json = r#"{
+            "name": "incomplete",
+            "glassmorphism": { "blurRadius": 24 }
+        }"#;
+
+        let theme_path = theme_dir.join("theme.json");
+        fs::write(&theme_path, theme_json).unwrap();
+
+        let result = Theme::load_from_path(&theme_path);
+        assert!(result.is_err());
+    }
+
+    // Task 6.4: Test directory scanning with mock filesystem
+    #[test]
+    fn test_directory_scanning() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Create theme directories
+        let theme1_dir = temp_dir.path().join("theme-one");
+        let theme2_dir = temp_dir.path().join("theme-two");
+        let not_a_theme = temp_dir.path().join("not-a-theme");
+
+        fs::create_dir(&theme1_dir).unwrap();
+        fs::create_dir(&theme2_dir).unwrap();
+        fs::create_dir(&not_a_theme).unwrap();
+
+        // Create theme.json files
+        fs::write(theme1_dir.join("theme.json"), "{}").unwrap();
+        fs::write(theme2_dir.join("theme.json"), "{}").unwrap();
+        // not_a_theme has no theme.json
+
+        let found = scan_themes_directory(temp_dir.path());
+        assert_eq!(found.len(), 2);
+    }
+
+    #[test]
+    fn test_scan_flat_and_subdirectory_layouts() {
+        let temp_dir = TempDir::new().unwrap();
+        let subdir = write_test_theme(temp_dir.path(), "nord", "nord");
+        fs::write(temp_dir.path().join("my-theme.json"), "{}").unwrap();
+        // Ambiguous, hidden, and non-JSON files are not themes
+        fs::write(temp_dir.path().join(THEME_FILENAME), "{}").unwrap();
+        fs::write(temp_dir.path().join(".my-theme.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+
+        let found = scan_themes_directory(temp_dir.path());
+        assert_eq!(found, vec![subdir, temp_dir.path().join("my-theme.json")]);
+    }
+
+    #[test]
+    fn test_scan_nonexistent_directory() {
+        let found = scan_themes_directory(Path::new("/nonexistent/path"));
+        assert!(found.is_empty());
+    }
+
+    // Task 6.5: Test user theme overrides system theme
+    #[test]
+    fn test_theme_manager_with_themes() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("valid-theme");
+        fs::create_dir(&theme_dir).unwrap();
+
+        let theme_json = "{
+            \"name\": \"valid-theme\",
+            \"colors\": {
+                \"base\": \"#1e1e2e\",
+                \"surface\": \"#313244\",
+                \"text\": \"#cdd6f4\",
+                \"accent\": \"#b4befe\",
+                \"border\": \"#585b70\"
+            },
+            \"glassmorphism\": {},
+            \"animation\": {}
+        }";
+
+        fs::write(theme_dir.join("theme.json"), theme_json).unwrap();
+
+        let themes = scan_themes_directory(temp_dir.path());
+        assert_eq!(themes.len(), 1);
+
+        let theme = Theme::load_from_path(&themes[0]).unwrap();
+        assert_eq!(theme.name, "valid-theme");
+    }
+
+    // Story 4.2: Test ThemeManager loads all bundled themes
+    #[test]
+    fn test_theme_manager_loads_bundled_themes() {
+        let manager = ThemeManager::new();
+
+        // Should have all 3 bundled themes
+        assert_eq!(manager.theme_count(), 3);
+        assert!(manager.has_theme("catppuccin-mocha"));
+        assert!(manager.has_theme("vaporwave"));
+        assert!(manager.has_theme("matrix-rain"));
+
+        // Default should be catppuccin-mocha
+        assert_eq!(manager.current().name, "catppuccin-mocha");
+    }
+
+    fn write_test_theme(root: &Path, dir_name: &str, name: &str) -> PathBuf {
+        let theme_dir = root.join(dir_name);
+        fs::create_dir_all(&theme_dir).unwrap();
+        let theme_json = format!(
+            r##"{{
+                "name": "{}",
+                "colors": {{
+                    "base": "#1e1e2e",
+                    "surface": "#313244",
+                    "text": "#cdd6f4",
+                    "accent": "#b4befe",
+                    "border": "#585b70"
+                }},
+                "glassmorphism": {{}},
+                "animation": {{}}
+            }}"##,
+            name
+        );
+        let path = theme_dir.join(THEME_FILENAME);
+        fs::write(&path, theme_json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_flat_theme_name_from_stem_and_subdirectory_precedence() {
+        let temp_dir = TempDir::new().unwrap();
+        let flat = temp_dir.path().join("ocean.json");
+        let json = |name: &str| {
+            format!(
+                r##"{{"name": "{}", "colors": {{"base": "#1e1e2e", "surface": "#313244",
+                "text": "#cdd6f4", "accent": "#89b4fa", "border": "#585b70"}},
+                "glassmorphism": {{}}, "animation": {{}}}}"##,
+                name
+            )
+        };
+        fs::write(&flat, json("")).unwrap();
+        assert_eq!(Theme::load_from_path(&flat).unwrap().name, "ocean");
+
+        // A subdirectory theme with the same name wins over the flat file
+        let subdir = write_test_theme(temp_dir.path(), "ocean", "ocean");
+        fs::write(temp_dir.path().join("forest.json"), json("forest")).unwrap();
+        let mut manager = ThemeManager::new();
+        assert_eq!(manager.load_from_dir(temp_dir.path(), ThemeSource::User), 2);
+        assert_eq!(manager.origin("ocean").unwrap().path.as_deref(), Some(subdir.as_path()));
+        assert_eq!(manager.get("forest").unwrap().colors.accent, "#89b4fa");
+        assert!(manager.shadowed_by_subdirectory("ocean", &flat));
+        assert!(!manager.shadowed_by_subdirectory("forest", &temp_dir.path().join("forest.json")));
+    }
+
+    #[test]
+    fn test_load_from_dir_records_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_test_theme(temp_dir.path(), "custom", "custom");
+        fs::create_dir(temp_dir.path().join("broken")).unwrap();
+        fs::write(temp_dir.path().join("broken").join(THEME_FILENAME), "{ nope").unwrap();
+
+        let mut manager = ThemeManager::new();
+        assert_eq!(manager.load_from_dir(temp_dir.path(), ThemeSource::System), 1);
+
+        let origin = manager.origin("custom").unwrap();
+        assert_eq!(origin.source, ThemeSource::System);
+        assert_eq!(origin.path.as_deref(), Some(path.as_path()));
+        assert_eq!(
+            manager.origin("vaporwave").unwrap(),
+            &ThemeOrigin::bundled()
+        );
+    }
+
+    #[test]
+    fn test_oversized_theme_is_skipped_unread() {
+        let temp_dir = TempDir::new().unwrap();
+        write_test_theme(temp_dir.path(), "small", "small");
+        let huge = temp_dir.path().join("huge.json");
+        fs::File::create(&huge).unwrap().set_len(MAX_THEME_FILE_BYTES + 1).unwrap();
+
+        let err = Theme::load_from_path(&huge).unwrap_err();
+        assert!(matches!(err, ThemeError::IoError(ref e) if e.kind() == io::ErrorKind::FileTooLarge));
+
+        let mut manager = ThemeManager::new();
+        assert_eq!(manager.load_from_dir(temp_dir.path(), ThemeSource::User), 1);
+        assert!(manager.get("huge").is_none());
+    }
+
+    const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR";
+
+    #[test]
+    fn test_texture_checks() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("grainy");
+        fs::create_dir_all(theme_dir.join("textures")).unwrap();
+        fs::write(theme_dir.join("textures/grain.png"), PNG_HEADER).unwrap();
+        fs::write(theme_dir.join("grid.svg"), "\u{feff}\n<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>").unwrap();
+        // Named .png, but not one
+        fs::write(theme_dir.join("fake.png"), "<html><body/></html>").unwrap();
+        fs::File::create(theme_dir.join("huge.png")).unwrap().set_len(MAX_TEXTURE_BYTES + 1).unwrap();
+        fs::write(temp_dir.path().join("outside.png"), PNG_HEADER).unwrap();
+        std::os::unix::fs::symlink(temp_dir.path().join("outside.png"), theme_dir.join("escape.png")).unwrap();
+
+        let png = ThemeTexture::load(&theme_dir, "./textures/grain.png").unwrap();
+        assert_eq!(png.format, TextureFormat::Png);
+        assert_eq!(png.path, theme_dir.canonicalize().unwrap().join("textures/grain.png"));
+        assert_eq!(png.hash, ContentHash(content_hash(PNG_HEADER)));
+        assert_eq!(ThemeTexture::load(&theme_dir, "grid.svg").unwrap().format, TextureFormat::Svg);
+
+        let err = |relative: &str| ThemeTexture::load(&theme_dir, relative).unwrap_err();
+        assert!(matches!(err("../outside.png"), TextureError::NotRelative));
+        assert!(matches!(err("textures/../../outside.png"), TextureError::NotRelative));
+        assert!(matches!(err(temp_dir.path().join("outside.png").to_str().unwrap()), TextureError::NotRelative));
+        assert!(matches!(err(""), TextureError::NotRelative));
+        assert!(matches!(err("escape.png"), TextureError::OutsideThemeDir(_)));
+        assert!(matches!(err("missing.png"), TextureError::Io(_)));
+        assert!(matches!(err("textures"), TextureError::NotAFile));
+        assert!(matches!(err("huge.png"), TextureError::TooLarge));
+        assert!(matches!(err("fake.png"), TextureError::UnknownFormat));
+    }
+
+    #[test]
+    fn test_bad_texture_falls_back_to_flat_slices() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = write_test_theme(temp_dir.path(), "grainy", "grainy");
+        let json = fs::read_to_string(&path).unwrap();
+        fs::write(&path, json.replace(r#""glassmorphism": {}"#, r#""glassmorphism": {"backgroundTexture": "grain.png"}"#)).unwrap();
+
+        // Missing: the theme still loads, with a warning and no texture
+        let mut manager = ThemeManager::new();
+        assert_eq!(manager.load_from_dir(temp_dir.path(), ThemeSource::User), 1);
+        let mut theme = manager.get("grainy").unwrap().clone();
+        assert!(theme.glassmorphism.texture.is_none());
+        let validation = theme.validate_and_clamp();
+        assert!(!validation.has_errors());
+        assert_eq!(validation.warnings[0].code, IssueCode::InvalidTexture);
+        assert_eq!(validation.warnings[0].field, "glassmorphism.backgroundTexture");
+        let flat_hash = ContentHash::of(&theme);
+
+        // Once the file is there it is used, and a new texture changes the hash
+        fs::write(temp_dir.path().join("grainy/grain.png"), PNG_HEADER).unwrap();
+        let theme = Theme::load_from_path(&path).unwrap();
+        assert!(theme.glassmorphism.texture.is_some());
+        assert!(!manager.is_unchanged(&theme, &path));
+        assert_ne!(ContentHash::of(&theme), flat_hash);
+        fs::write(temp_dir.path().join("grainy/grain.png"), [PNG_HEADER, b"more"].concat()).unwrap();
+        assert_ne!(ContentHash::of(&Theme::load_from_path(&path).unwrap()), ContentHash::of(&theme));
+    }
+
+    #[test]
+    fn test_theme_count_is_capped() {
+        let system_dir = TempDir::new().unwrap();
+        let user_dir = TempDir::new().unwrap();
+        for i in 0..MAX_LOADED_THEMES - 10 {
+            let name = format!("sys-{:03}", i);
+            write_test_theme(system_dir.path(), &name, &name);
+        }
+        for i in 0..50 {
+            let name = format!("user-{:03}", i);
+            write_test_theme(user_dir.path(), &name, &name);
+        }
+
+        let mut manager = ThemeManager::new();
+        let bundled = manager.theme_count();
+        assert_eq!(manager.load_from_dir(system_dir.path(), ThemeSource::System), MAX_LOADED_THEMES - 10);
+        // The cap spans directories: only 10 user themes fit
+        assert_eq!(manager.load_from_dir(user_dir.path(), ThemeSource::User), 10);
+        assert!(manager.get("user-009").is_some());
+        assert!(manager.get("user-010").is_none());
+        assert_eq!(manager.theme_count(), bundled + MAX_LOADED_THEMES);
+        assert_eq!(manager.load_from_dir(user_dir.path(), ThemeSource::User), 0);
+    }
+
+    #[test]
+    fn test_higher_priority_source_shadows() {
+        let system_dir = TempDir::new().unwrap();
+        let user_dir = TempDir::new().unwrap();
+        write_test_theme(system_dir.path(), "vaporwave", "vaporwave");
+        let user_path = write_test_theme(user_dir.path(), "vaporwave", "vaporwave");
+
+        let mut manager = ThemeManager::new();
+        manager.load_from_dir(system_dir.path(), ThemeSource::System);
+        manager.load_from_dir(user_dir.path(), ThemeSource::User);
+
+        let origin = manager.origin("vaporwave").unwrap();
+        assert_eq!(origin.source, ThemeSource::User);
+        assert_eq!(origin.path.as_deref(), Some(user_path.as_path()));
+        assert_eq!(manager.theme_count(), 3);
+    }
+
+    #[test]
+    fn test_lower_priority_reload_keeps_shadowing_theme() {
+        let user_dir = TempDir::new().unwrap();
+        let user_path = write_test_theme(user_dir.path(), "vaporwave", "vaporwave");
+
+        let mut manager = ThemeManager::new();
+        manager.load_from_dir(user_dir.path(), ThemeSource::User);
+        manager.add_or_update_theme_from(
+            Theme::catppuccin_mocha(),
+            ThemeOrigin {
+                source: ThemeSource::System,
+                path: Some(PathBuf::from("/usr/share/juhradial/themes/x/theme.json")),
+            },
+        );
+        assert_eq!(
+            manager.origin("catppuccin-mocha").unwrap().source,
+            ThemeSource::System
+        );
+
+        let mut system_vaporwave = Theme::catppuccin_mocha();
+        system_vaporwave.name = "vaporwave".to_string();
+        manager.add_or_update_theme_from(
+            system_vaporwave,
+            ThemeOrigin {
+                source: ThemeSource::System,
+                path: None,
+            },
+        );
+        assert_eq!(
+            manager.origin("vaporwave").unwrap().path.as_deref(),
+            Some(user_path.as_path())
+        );
+    }
+
+    #[test]
+    fn test_unchanged_save_is_detected_by_hash() {
+        let user_dir = TempDir::new().unwrap();
+        let path = write_test_theme(user_dir.path(), "vaporwave", "vaporwave");
+        let mut manager = ThemeManager::new();
+        let bundled_hash = manager.hash("vaporwave").unwrap();
+        manager.load_from_dir(user_dir.path(), ThemeSource::User);
+        let loaded = manager.get("vaporwave").unwrap().clone();
+        assert_ne!(manager.hash("vaporwave"), Some(bundled_hash));
+        assert_eq!(manager.hash("vaporwave"), Some(ContentHash::of(&loaded)));
+
+        // Same content from the same file: unchanged
+        assert!(manager.is_unchanged(&loaded, &path));
+        // Same content from another file, or edited content: changed
+        assert!(!manager.is_unchanged(&loaded, &user_dir.path().join("vaporwave.json")));
+        let mut edited = loaded;
+        edited.colors.accent = "#00ff00".to_string();
+        assert!(!manager.is_unchanged(&edited, &path));
+        manager.add_or_update_theme(edited.clone());
+        assert_eq!(manager.hash("vaporwave"), Some(ContentHash::of(&edited)));
+        assert_eq!(manager.current_hash(), manager.hash("catppuccin-mocha").unwrap());
+    }
+
+    #[test]
+    fn test_themes_with_sources_sorted() {
+        let manager = ThemeManager::new();
+        let names: Vec<&str> = manager
+            .themes_with_sources()
+            .iter()
+            .map(|(theme, _)| theme.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["catppuccin-mocha", "matrix-rain", "vaporwave"]);
+        assert!(manager
+            .themes_with_sources()
+            .iter()
+            .all(|(_, origin)| origin.source == ThemeSource::Bundled));
+    }
+
+    #[test]
+    fn test_theme_source_serialization() {
+        assert_eq!(serde_json::to_string(&ThemeSource::User).unwrap(), "\"user\"");
+        assert_eq!(ThemeSource::Bundled.to_string(), "bundled");
+        assert!(ThemeSource::Bundled < ThemeSource::System);
+        assert!(ThemeSource::System < ThemeSource::User);
+    }
+
+    #[test]
+    fn test_theme_manager_set_current() {
+        let mut manager = ThemeManager::new();
+
+        // Try to set non-existent theme
+        assert!(manager.set_current("nonexistent").is_err());
+
+        // Set to vaporwave should work
+        assert!(manager.set_current("vaporwave").is_ok());
+        assert_eq!(manager.current().name, "vaporwave");
+
+        // Set to matrix-rain should work
+        assert!(manager.set_current("matrix-rain").is_ok());
+        assert_eq!(manager.current().name, "matrix-rain");
+
+        // Set back to default
+        assert!(manager.set_current("catppuccin-mocha").is_ok());
+        assert_eq!(manager.current().name, "catppuccin-mocha");
+    }
+
+    #[test]
+    fn test_default_theme_values() {
+        let theme = Theme::catppuccin_mocha();
+
+        // Verify all UX spec values
+        assert_eq!(theme.glassmorphism.blur_radius, 24);
+        assert_eq!(theme.glassmorphism.background_opacity, 0.75);
+        assert_eq!(theme.glassmorphism.saturation, 1.8);
+        assert_eq!(theme.glassmorphism.border_opacity, 0.15);
+        assert_eq!(theme.glassmorphism.noise_opacity, 0.04);
+        assert_eq!(theme.animation.glow_intensity, 1.0);
+        assert!(!theme.animation.enable_particles);
+        assert_eq!(theme.animation.idle_effect, "none");
+    }
+
+    #[test]
+    fn test_hex_color_validation() {
+        // Valid colors
+        assert!(is_valid_hex_color("#1e1e2e"));
+        assert!(is_valid_hex_color("#FFF"));
+        assert!(is_valid_hex_color("#abc123"));
+
+        // Invalid colors
+        assert!(!is_valid_hex_color("1e1e2e")); // No #
+        assert!(!is_valid_hex_color("#1e1e2")); // Wrong length
+        assert!(!is_valid_hex_color("#GGGGGG")); // Invalid chars
+        assert!(!is_valid_hex_color("")); // Empty
+    }
+
+    #[test]
+    fn test_invalid_color_produces_error() {
+        let mut theme = Theme::catppuccin_mocha();
+        theme.colors.base = "invalid".to_string();
+
+        let result = theme.validate_and_clamp();
+        assert!(result.has_errors());
+        assert!(!result.is_valid());
+    }
+
+    #[test]
+    fn test_theme_error_display() {
+        let err = ThemeError::NotFound("test".to_string());
+        assert!(format!("{}", err).contains("test"));
+
+        let err = ThemeError::ValidationError("invalid".to_string());
+        assert!(format!("{}", err).contains("invalid"));
+    }
+
+    #[test]
+    fn test_get_user_themes_dir() {
+        let dir = get_user_themes_dir();
+        assert!(dir.to_string_lossy().contains("juhradial"));
+        assert!(dir.to_string_lossy().contains("themes"));
+    }
+
+    #[test]
+    fn test_load_all_in_reads_the_given_directories() {
+        let config_home = TempDir::new().unwrap();
+        let data_dir = TempDir::new().unwrap();
+        write_test_theme(&config_home.path().join("juhradial/themes"), "ocean", "ocean");
+        write_test_theme(&data_dir.path().join("themes"), "dusk", "dusk");
+        let paths = crate::paths::Paths::resolve(&crate::paths::PathsEnv {
+            xdg_config_home: Some(config_home.path().into()),
+            data_dir: Some(data_dir.path().into()),
+            ..Default::default()
+        });
+
+        let manager = ThemeManager::load_all_in(&paths).unwrap();
+        assert!(manager.has_theme("catppuccin-mocha"));
+        assert_eq!(manager.has_theme("ocean"), cfg!(feature = "themes"));
+        if cfg!(feature = "themes") {
+            assert_eq!(manager.origin("ocean").unwrap().source, ThemeSource::User);
+            assert_eq!(manager.origin("dusk").unwrap().source, ThemeSource::System);
+        }
+    }
+
+    #[test]
+    fn test_theme_with_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("minimal-theme");
+        fs::create_dir(&theme_dir).unwrap();
+
+        // Minimal theme - only required fields
+        let theme_json = "{
+            \"name\": \"minimal\",
+            \"colors\": {
+                \"base\": \"#000000\",
+                \"surface\": \"#111111\",
+                \"text\": \"#ffffff\",
+                \"accent\": \"#ff0000\",
+                \"border\": \"#333333\"
+            },
+            \"glassmorphism\": {},
+            \"animation\": {}
+        }";
+
+        let theme_path = theme_dir.join("theme.json");
+        fs::write(&theme_path, theme_json).unwrap();
+
+        let theme = Theme::load_from_path(&theme_path).unwrap();
+
+        // Check defaults were applied
+        assert_eq!(theme.version, "1.0");
+        assert_eq!(theme.glassmorphism.blur_radius, 24);
+        assert_eq!(theme.animation.glow_intensity, 1.0);
+    }
+
+    #[test]
+    fn test_theme_name_from_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("my-custom-theme");
+        fs::create_dir(&theme_dir).unwrap();
+
+        // Theme without name field
+        let theme_json = "{
+            \"colors\": {
+                \"base\": \"#000000\",
+                \"surface\": \"#111111\",
+                \"text\": \"#ffffff\",
+                \"accent\": \"#ff0000\",
+                \"border\": \"#333333\"
+            },
+            \"glassmorphism\": {},
+            \"animation\": {}
+        }";
+
+        let theme_path = theme_dir.join("theme.json");
+        fs::write(&theme_path, theme_json).unwrap();
+
+        let theme = Theme::load_from_path(&theme_path).unwrap();
+
+        // Name should be inferred from directory
+        assert_eq!(theme.name, "my-custom-theme");
+    }
+
+    #[test]
+    fn test_effects_alias() {
+        let temp_dir = TempDir::new().unwrap();
+        let theme_dir = temp_dir.path().join("alias-theme");
+        fs::create_dir(&theme_dir).unwrap();
+
+        // Use "effects" instead of "glassmorphism" (alias)
+        let theme_json = "{
+            \"name\": \"alias-test\",
+            \"colors\": {
+                \"base\": \"#000000\",
+                \"surface\": \"#111111\",
+                \"text\": \"#ffffff\",
+                \"accent\": \"#ff0000\",
+                \"border\": \"#333333\"
+            },
+            \"effects\": {
+                \"blurRadius\": 32
+            },
+            \"animation\": {}
+        }";
+
+        let theme_path = theme_dir.join("theme.json");
+        fs::write(&theme_path, theme_json).unwrap();
+
+        let theme = Theme::load_from_path(&theme_path).unwrap();
+        assert_eq!(theme.glassmorphism.blur_radius, 32);
+    }
+
+    // Story 4.6: Reduced motion tests
+    #[test]
+    fn test_effective_timings_with_reduced_motion() {
+        let theme = Theme::catppuccin_mocha();
+
+        // With reduced motion ON
+        let timings = theme.get_effective_animation_timings(true);
+        assert_eq!(timings.appear_ms, 0);
+        assert_eq!(timings.dismiss_ms, 0);
+        assert_eq!(timings.highlight_in_ms, 0);
+        assert_eq!(timings.highlight_out_ms, 0);
+        assert!(!timings.icon_scale_enabled);
+        assert!(!timings.idle_effects_enabled);
+    }
+
+    #[test]
+    fn test_effective_timings_without_reduced_motion() {
+        let theme = Theme::catppuccin_mocha();
+
+        // With reduced motion OFF
+        let timings = theme.get_effective_animation_timings(false);
+        assert_eq!(timings.appear_ms, 30);
+        assert_eq!(timings.dismiss_ms, 50);
+        assert_eq!(timings.highlight_in_ms, 80);
+        assert_eq!(timings.highlight_out_ms, 60);
+        assert!(timings.icon_scale_enabled);
+    }
+
+    #[test]
+    fn test_idle_effects_from_theme() {
+        let mut theme = Theme::catppuccin_mocha();
+
+        // Default theme has no idle effects
+        let timings = theme.get_effective_animation_timings(false);
+        assert!(!timings.idle_effects_enabled);
+
+        // Enable particles
+        theme.animation.enable_particles = true;
+        let timings = theme.get_effective_animation_timings(false);
+        assert!(timings.idle_effects_enabled);
+
+        // Enable matrix rain
+        theme.animation.enable_particles = false;
+        theme.animation.idle_effect = "matrix-rain".to_string();
+        let timings = theme.get_effective_animation_timings(false);
+        assert!(timings.idle_effects_enabled);
+    }
+
+    #[test]
+    fn test_effective_idle_effect() {
+        let mut theme = Theme::catppuccin_mocha();
+        assert_eq!(theme.get_effective_idle_effect(false), IdleEffectSettings::OFF);
+
+        // enableParticles alone means particles
+        theme.animation.enable_particles = true;
+        assert_eq!(theme.get_effective_idle_effect(false).kind, "particles");
+
+        theme.animation.idle_effect = "matrix-rain".to_string();
+        theme.animation.idle_density = 0.8;
+        theme.animation.idle_max_fps = 60;
+        let idle = theme.get_effective_idle_effect(false);
+        assert_eq!(idle.kind, "matrix-rain");
+        assert_eq!((idle.density, idle.speed, idle.max_fps), (0.8, 1.0, 60));
+
+        // Reduced motion turns it off, in the timings too
+        assert_eq!(theme.get_effective_idle_effect(true), IdleEffectSettings::OFF);
+        assert!(!theme.get_effective_animation_timings(true).idle_effects_enabled);
+
+        // The user's caps
+        let limits = IdleEffectLimits { enabled: true, max_fps: 24, max_density: 0.5 };
+        let limited = idle.limited(&limits);
+        assert_eq!((limited.kind, limited.density, limited.max_fps), ("matrix-rain", 0.5, 24));
+        let off = IdleEffectLimits { enabled: false, ..limits };
+        assert_eq!(idle.limited(&off), IdleEffectSettings::OFF);
+        let timings = theme.get_effective_animation_timings(false).with_idle_effect(&idle.limited(&off));
+        assert!(!timings.idle_effects_enabled);
+    }
+
+    #[test]
+    fn test_validation_clamps_idle_effect() {
+        let mut theme = Theme::catppuccin_mocha();
+        theme.animation.idle_effect = "fireworks".to_string();
+        theme.animation.idle_density = 3.0;
+        theme.animation.idle_speed = f32::NAN;
+        theme.animation.idle_max_fps = 0;
+
+        let result = theme.validate_and_clamp();
+        let fields: Vec<&str> = result.warnings.iter().map(|w| w.field.as_str()).collect();
+        assert_eq!(fields, [
+            "animation.idleEffect",
+            "animation.idleDensity",
+            "animation.idleSpeed",
+            "animation.idleMaxFps",
+        ]);
+        assert_eq!(theme.animation.idle_effect, "none");
+        assert_eq!(theme.animation.idle_density, 1.0);
+        assert_eq!(theme.animation.idle_speed, 1.0);
+        assert_eq!(theme.animation.idle_max_fps, 1);
+
+        // The bundled matrix-rain theme is already in range
+        let mut matrix = Theme::from_json(include_str!("themes/matrix-rain.json")).unwrap();
+        assert!(matrix.validate_and_clamp().warnings.is_empty());
+        assert_eq!(matrix.get_effective_idle_effect(false).kind, "matrix-rain");
+    }
+
+    // Story 4.5: High contrast mode tests
+    /// Catppuccin Mocha with `colors` replaced by `colors`
+    fn theme_with_colors(colors: serde_json::Value) -> Theme {
+        let mut doc = serde_json::to_value(Theme::catppuccin_mocha()).unwrap();
+        doc["colors"] = colors;
+        Theme::from_json(&doc.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_derived_colors_resolve_at_load() {
+        let mut theme = theme_with_colors(serde_json::json!({
+            "base": "#000000",
+            "surface": {"derive": "base", "lighten": 0.2},
+            "text": "#999999",
+            "textSecondary": {"derive": "text", "darken": 0.2},
+            "accent": {"derive": "textSecondary", "mix": {"color": "#ffffff", "amount": 0.5}},
+            "border": {"derive": "#333333", "mix": {"color": "base", "amount": 1.0}, "lighten": 0.4},
+        }));
+        assert!(theme.validate_and_clamp().is_valid());
+        assert_eq!(theme.colors.surface, "#333333");
+        assert_eq!(theme.colors.text_secondary, "#666666");
+        assert_eq!(theme.colors.accent, "#b3b3b3");
+        assert_eq!(theme.colors.border, "#666666");
+        assert_eq!(theme.get_effective_colors(false).accent, "#b3b3b3");
+
+        // Saved themes hold the resolved values
+        let saved = serde_json::to_value(&theme).unwrap();
+        assert_eq!(saved["colors"]["surface"], "#333333");
+
+        let matrix = crate::bundled_themes::get_bundled_theme("matrix-rain").unwrap();
+        assert_eq!(matrix.colors.text_secondary, "#009900");
+        assert_eq!(matrix.colors.accent_secondary, "#33ff33");
+    }
+
+    #[test]
+    fn test_derivation_errors() {
+        let mut theme = theme_with_colors(serde_json::json!({
+            "base": "#000000",
+            "surface": {"derive": "border", "lighten": 0.1},
+            "text": {"derive": "surface"},
+            "accent": {"derive": "base", "lighten": 1.5},
+            "accentSecondary": {"derive": "highlight"},
+            "border": {"derive": "text", "darken": 0.1},
+            "success": {"derive": "accent", "mix": {"color": "base", "amount": -0.1}},
+            "warning": {"derive": "surface", "darken": 0.1},
+        }));
+        let result = theme.validate_and_clamp();
+        let message = |field: &str| {
+            result
+                .errors
+                .iter()
+                .find(|issue| issue.field == field)
+                .map(|issue| issue.message.clone())
+                .unwrap_or_default()
+        };
+        for field in ["colors.surface", "colors.text", "colors.border"] {
+            assert!(message(field).contains("circular derivation"), "{field}: {}", message(field));
+        }
+        assert!(message("colors.accent").contains("lighten 1.5 is outside"));
+        assert!(message("colors.accentSecondary").contains("unknown color 'highlight'"));
+        assert!(message("colors.success").contains("mix.amount -0.1 is outside"));
+        assert!(message("colors.warning").contains("derives from 'surface'"));
+        // One issue per field, no follow-up hex errors
+        assert_eq!(result.errors.len(), 7);
+        assert!(result.errors.iter().all(|issue| issue.code == IssueCode::InvalidDerivation));
+
+        let err = Theme::from_json(&serde_json::json!({
+            "colors": {"base": "#000000", "surface": {"derive": "base", "lightn": 0.1}},
+            "glassmorphism": {}, "animation": {}
+        }).to_string());
+        assert!(err.unwrap_err().to_string().contains("lightn"));
+    }
+
+    #[test]
+    fn test_accent_override_rederives_dependent_colors() {
+        let theme = theme_with_colors(serde_json::json!({
+            "base": "#000000",
+            "surface": "#111111",
+            "text": "#ffffff",
+            "accent": "#ff0000",
+            "accentSecondary": {"derive": "accent", "mix": {"color": "base", "amount": 0.5}},
+            "border": "#222222",
+        }));
+        assert_eq!(theme.colors.accent_secondary, "#800000");
+        let accented = theme.with_accent("#00ff00");
+        assert_eq!(accented.colors.accent, "#00ff00");
+        assert_eq!(accented.colors.accent_secondary, "#008000");
+        assert_eq!(accented.colors.surface, "#111111");
+        // Built in code: the accent alone changes
+        let mocha = Theme::catppuccin_mocha().with_accent("#3daee9");
+        assert_eq!(mocha.colors.accent, "#3daee9");
+        assert_eq!(mocha.colors.accent_secondary, Theme::catppuccin_mocha().colors.accent_secondary);
+
+        let mut manager = ThemeManager::new();
+        let plain_hash = manager.current_hash();
+        assert!(manager.set_accent_override(Some("#3daee9")));
+        assert!(!manager.set_accent_override(Some("#3daee9")));
+        assert_eq!(manager.current().colors.accent, "#3daee9");
+        assert_ne!(manager.current_hash(), plain_hash);
+        // Follows a theme switch, and leaves the stored theme alone
+        manager.set_current("vaporwave").unwrap();
+        assert_eq!(manager.current().colors.accent, "#3daee9");
+        assert_ne!(manager.get("vaporwave").unwrap().colors.accent, "#3daee9");
+        assert!(manager.set_accent_override(None));
+        assert_eq!(manager.current().colors.accent, manager.get("vaporwave").unwrap().colors.accent);
+        assert_eq!(manager.current_hash(), manager.hash("vaporwave").unwrap());
+        manager.set_current("catppuccin-mocha").unwrap();
+        assert_eq!(manager.current_hash(), plain_hash);
+    }
+
+    #[test]
+    fn test_localized_display_name() {
+        let mut doc = serde_json::to_value(Theme::catppuccin_mocha()).unwrap();
+        doc["display_name"] = serde_json::json!({"en": "Catppuccin Mocha", "de": "Catppuccin Mokka"});
+        let theme = Theme::from_json(&doc.to_string()).unwrap();
+        let locale = |value| crate::Locale::parse(value).unwrap();
+        assert_eq!(theme.display_name.resolve(&locale("de_DE.UTF-8")), "Catppuccin Mokka");
+        assert_eq!(theme.display_name.resolve(&locale("fr_FR")), "Catppuccin Mocha");
+        assert_eq!(serde_json::to_value(&theme).unwrap()["display_name"], doc["display_name"]);
+
+        doc["display_name"] = serde_json::json!({"de": 3});
+        let Err(ThemeError::Invalid(result)) = Theme::from_json(&doc.to_string()) else {
+            panic!("expected a display_name diagnostic");
+        };
+        assert_eq!(result.errors[0].field, "display_name");
+    }
+
+    #[test]
+    fn test_invalid_theme_lists_every_field_problem() {
+        let err = Theme::from_json(&serde_json::json!({
+            "name": 7,
+            "colors": {"base": "#000000", "surface": "#111111", "text": "#ffffff", "border": "#222222",
+                       "accentSecondary": 42, "shadow": {"derive": "base", "lightn": 0.1}},
+            "effects": {"blurRadius": 24.5, "saturation": "high"},
+            "overrides": {"sliceColors": null, "customFont": false}
+        }).to_string())
+        .unwrap_err();
+        let ThemeError::Invalid(result) = err else {
+            panic!("expected field-level diagnostics, got {err}");
+        };
+        let fields: Vec<&str> = result.errors.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(fields, [
+            "name",
+            "effects.blurRadius",
+            "effects.saturation",
+            "animation",
+            "overrides.customFont",
+            "colors.accent",
+            "colors.accentSecondary",
+            "colors.shadow",
+        ]);
+        assert_eq!(
+            result.errors[6].message,
+            "colors.accentSecondary should be a string like \"#89b4fa\" or a derivation object, found number"
+        );
+        assert!(result.errors[7].message.contains("lightn"));
+        assert!(ThemeError::Invalid(result).to_string().contains("missing required section animation"));
+
+        // Not JSON at all: still serde's position
+        assert!(matches!(Theme::from_json("{\"colors\": ,}"), Err(ThemeError::ParseError(_))));
+        assert!(matches!(Theme::from_json("[]"), Err(ThemeError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_high_contrast_colors() {
+        let theme = Theme::catppuccin_mocha();
+
+        // Without high contrast
+        let colors = theme.get_effective_colors(false);
+        assert_eq!(colors.text, "#cdd6f4"); // Theme's original color
+
+        // With high contrast
+        let hc_colors = theme.get_effective_colors(true);
+        assert_eq!(hc_colors.text, "#ffffff"); // Overridden to white
+        assert_eq!(hc_colors.text_secondary, "#ffffff"); // Also white
+    }
+
+    #[test]
+    fn test_high_contrast_glassmorphism() {
+        let theme = Theme::catppuccin_mocha();
+
+        // Without high contrast
+        let glass = theme.get_effective_glassmorphism(false);
+        assert_eq!(glass.blur_radius, 24);
+        assert_eq!(glass.background_opacity, 0.75);
+        assert_eq!(glass.border_opacity, 0.15);
+
+        // With high contrast
+        let hc_glass = theme.get_effective_glassmorphism(true);
+        assert_eq!(hc_glass.blur_radius, 0); // Disabled
+        assert_eq!(hc_glass.background_opacity, 0.95); // Nearly opaque
+        assert_eq!(hc_glass.border_opacity, 0.60); // More visible
+        assert_eq!(hc_glass.noise_opacity, 0.0); // Disabled
+    }
+
+    #[test]
+    fn test_pack_premultiplied_every_channel_and_alpha() {
+        for a in 0..=255u8 {
+            for c in 0..=255u8 {
+                let expected = (f64::from(c) * f64::from(a) / 255.0).round() as u32;
+                let packed = pack_premultiplied(Rgba { r: c, g: c, b: c, a });
+                assert_eq!(packed >> 24, expected, "c={c} a={a}");
+                assert_eq!(packed & 0xff, u32::from(a));
+            }
+        }
+        assert_eq!(pack_premultiplied(Rgba::rgb(0x1e, 0x1e, 0x2e)), 0x1e1e2eff);
+        assert_eq!(pack_premultiplied(Rgba::from_hex("#ff800080").unwrap()), 0x80400080);
+        assert_eq!(pack_premultiplied(Rgba::from_hex("#ffffff00").unwrap()), 0);
+        // Channels stay in their own bytes
+        assert_eq!(pack_premultiplied(Rgba { r: 0xff, g: 0, b: 0x40, a: 0xcc }), 0xcc0033cc);
+    }
+
+    #[test]
+    fn test_packed_colors_layout() {
+        let theme = Theme::catppuccin_mocha();
+        let packed = theme.get_packed_colors(false);
+        let colors = theme.get_effective_colors(false);
+        assert_eq!(packed.colors[0], pack_premultiplied(Rgba::from_hex(&colors.base).unwrap()));
+        assert_eq!(packed.colors[ACCENT_INDEX], pack_premultiplied(Rgba::from_hex(&colors.accent).unwrap()));
+        assert_eq!(packed.colors[10], pack_premultiplied(Rgba::from_hex(&colors.error).unwrap()));
+        assert_eq!(packed.glassmorphism, [24.0, 0.75, 1.8, 0.15, 0.04]);
+
+        let hc = theme.get_packed_colors(true);
+        assert_eq!(hc.colors[2], 0xffffffff);
+        assert_eq!(hc.colors[3], 0xffffffff);
+        assert_eq!(hc.glassmorphism, [0.0, 0.95, 1.0, 0.60, 0.0]);
+
+        let json = serde_json::to_value(packed).unwrap();
+        let values = json.as_array().unwrap();
+        assert_eq!(values.len(), PACKED_THEME_COLORS_LEN);
+        assert_eq!(values[0], 0x1e1e2eff_u32);
+        assert_eq!(values[11], 24.0);
+        assert_eq!(values[12], 0.75);
+
+        // Anything that is not a hex color packs as transparent
+        let mut broken = colors.clone();
+        broken.shadow = "rgba(0, 0, 0, 0.5)".to_string();
+        assert_eq!(broken.packed()[7], 0);
+    }
+
+    #[test]
+    fn test_high_contrast_settings_defaults() {
+        let hc = HighContrastSettings::default();
+
+        assert_eq!(hc.text_color, "#ffffff");
+        assert_eq!(hc.border_opacity, 0.60);
+        assert_eq!(hc.selection_border_color, "#ffffff");
+        assert_eq!(hc.selection_border_width, 3);
+        assert_eq!(hc.background_opacity, 0.95);
+        assert_eq!(hc.blur_radius, 0);
+    }
+
+    #[test]
+    fn test_get_high_contrast_settings() {
+        let hc = Theme::get_high_contrast_settings();
+        assert_eq!(hc.text_color, "#ffffff");
+        assert_eq!(hc.selection_border_width, 3);
+    }
+
+    // ------------------------------------------------------------------------
+    // Property tests: hand-edited theme.json must never panic the daemon
+    // ------------------------------------------------------------------------
+
+    use crate::test_support::{arb_json, set_path};
+    use proptest::prelude::*;
+
+    /// Any JSON number, including ones far outside f32/u8 range
+    fn arb_number() -> impl Strategy<Value = serde_json::Value> {
+        prop_oneof![
+            any::<i64>().prop_map(serde_json::Value::from),
+            (-1.0e40f64..1.0e40).prop_map(serde_json::Value::from),
+            (-5.0f64..5.0).prop_map(serde_json::Value::from),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_theme_from_arbitrary_json(doc in arb_json()) {
+            if let Ok(mut theme) = Theme::from_json(&doc.to_string()) {
+                theme.validate_and_clamp();
+            }
+        }
+
+        #[test]
+        fn prop_validate_and_clamp_keeps_ranges(
+            values in prop::collection::vec(arb_number(), 9),
+            color in ".{0,9}",
+        ) {
+            let mut doc = serde_json::to_value(Theme::catppuccin_mocha()).unwrap();
+            let paths: [&[&str]; 9] = [
+                &["glassmorphism", "blurRadius"],
+                &["glassmorphism", "backgroundOpacity"],
+                &["glassmorphism", "saturation"],
+                &["glassmorphism", "borderOpacity"],
+                &["glassmorphism", "noiseOpacity"],
+                &["animation", "glowIntensity"],
+                &["animation", "idleDensity"],
+                &["animation", "idleSpeed"],
+                &["animation", "idleMaxFps"],
+            ];
+            for (path, value) in paths.iter().zip(values) {
+                set_path(&mut doc, path, value);
+            }
+            set_path(&mut doc, &["colors", "accent"], serde_json::Value::String(color));
+
+            // Out-of-range integers for a u8 field are a parse error, not a panic
+            let Ok(mut theme) = Theme::from_json(&doc.to_string()) else {
+                return Ok(());
+            };
+            theme.validate_and_clamp();
+            let g = &theme.glassmorphism;
+            prop_assert!((8..=48).contains(&g.blur_radius));
+            prop_assert!((0.5..=0.95).contains(&g.background_opacity));
+            prop_assert!((1.0..=2.5).contains(&g.saturation));
+            prop_assert!((0.0..=0.5).contains(&g.border_opacity));
+            prop_assert!((0.0..=0.1).contains(&g.noise_opacity));
+            prop_assert!((0.0..=2.0).contains(&theme.animation.glow_intensity));
+            prop_assert!((0.0..=1.0).contains(&theme.animation.idle_density));
+            prop_assert!((0.1..=4.0).contains(&theme.animation.idle_speed));
+            prop_assert!((1..=60).contains(&theme.animation.idle_max_fps));
+        }
+    }
+}
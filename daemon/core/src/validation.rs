@@ -0,0 +1,288 @@
+//! Structured validation findings
+//!
+//! Theme, profile and config validation report [`ValidationIssue`]s: the
+//! dotted field path, a [`Severity`], a machine-readable [`IssueCode`], the
+//! human message, and where known the value found and what was expected.
+//! They serialize to JSON as-is, so `validate-theme --json`, `DumpState` and
+//! the `ThemeReloadFailed` signal carry the same entries, and `Display` is
+//! the message alone, as logs have always shown it.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The file (or the entry) is rejected
+    Error,
+    /// It loads, but something is corrected or ignored
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// What kind of problem a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IssueCode {
+    /// The file could not be read or written
+    Io,
+    /// Not JSON, or JSON the loader cannot read
+    ParseError,
+    /// A number outside its range (clamped when a warning)
+    OutOfRange,
+    /// Not one of the allowed values
+    NotAllowed,
+    /// A value of the wrong JSON type
+    WrongType,
+    /// A required key or section is absent
+    Missing,
+    /// A key nothing reads
+    UnknownKey,
+    /// Not a `#RGB` or `#RRGGBB` color
+    InvalidColor,
+    /// A derived theme color that cannot be computed
+    InvalidDerivation,
+    /// A theme background texture that cannot be used
+    InvalidTexture,
+    /// A profiles.json schema version this build does not read as-is
+    SchemaVersion,
+    /// A slice count other than 4, 6, 8 or 12, or a slice list of the wrong length
+    SliceCount,
+    /// An icon that may not resolve
+    InvalidIcon,
+    /// A shortcut with an empty or unmappable key
+    InvalidShortcut,
+    /// A destructive shortcut where it is easy to hit
+    RiskyShortcut,
+    /// An action that runs a shell command
+    ShellCommand,
+    /// A label that looks broken or will be shortened
+    Label,
+    /// An action the daemon cannot run
+    InvalidAction,
+    /// Two profiles with the same name or match
+    Collision,
+    /// A reference to a profile that is not there, or an `extends` loop
+    UnknownProfile,
+    /// Anything else
+    Invalid,
+}
+
+impl IssueCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Io => "io",
+            Self::ParseError => "parse_error",
+            Self::OutOfRange => "out_of_range",
+            Self::NotAllowed => "not_allowed",
+            Self::WrongType => "wrong_type",
+            Self::Missing => "missing",
+            Self::UnknownKey => "unknown_key",
+            Self::InvalidColor => "invalid_color",
+            Self::InvalidDerivation => "invalid_derivation",
+            Self::InvalidTexture => "invalid_texture",
+            Self::SchemaVersion => "schema_version",
+            Self::SliceCount => "slice_count",
+            Self::InvalidIcon => "invalid_icon",
+            Self::InvalidShortcut => "invalid_shortcut",
+            Self::RiskyShortcut => "risky_shortcut",
+            Self::ShellCommand => "shell_command",
+            Self::Label => "label",
+            Self::InvalidAction => "invalid_action",
+            Self::Collision => "collision",
+            Self::UnknownProfile => "unknown_profile",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+impl fmt::Display for IssueCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single validation finding and the field it concerns
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Dotted path, e.g. `glassmorphism.blurRadius` or `profiles[1].slices[3]`;
+    /// empty for the whole document
+    pub field: String,
+    pub code: IssueCode,
+    pub message: String,
+    /// The value found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub got: Option<Value>,
+    /// What would have been accepted, e.g. `8-48` or `#RRGGBB`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+}
+
+impl ValidationIssue {
+    pub fn new(severity: Severity, field: impl Into<String>, code: IssueCode, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            field: field.into(),
+            code,
+            message: message.into(),
+            got: None,
+            expected: None,
+        }
+    }
+
+    pub fn error(field: impl Into<String>, code: IssueCode, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, field, code, message)
+    }
+
+    pub fn warning(field: impl Into<String>, code: IssueCode, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, field, code, message)
+    }
+
+    /// Record the value found
+    pub fn got(mut self, got: impl Into<Value>) -> Self {
+        self.got = Some(got.into());
+        self
+    }
+
+    /// Record what would have been accepted
+    pub fn expected(mut self, expected: impl Into<String>) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+
+    /// A number outside `expected` that was clamped into it
+    pub fn clamped(field: impl Into<String>, got: impl Into<Value>, expected: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::warning(field, IssueCode::OutOfRange, message).got(got).expected(expected)
+    }
+
+    /// A value of the wrong JSON type
+    pub fn wrong_type(field: impl Into<String>, found: &str, expected: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::error(field, IssueCode::WrongType, message).got(found).expected(expected)
+    }
+
+    /// A required key or section that is absent
+    pub fn missing(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::error(field, IssueCode::Missing, message)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Validation result with warnings and errors (Story 4.1: Task 3.8)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationResult {
+    /// Non-fatal warnings (values were clamped)
+    pub warnings: Vec<ValidationIssue>,
+    /// Fatal errors (the document cannot be used)
+    pub errors: Vec<ValidationIssue>,
+}
+
+impl ValidationResult {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// File `issue` under its severity
+    pub fn push(&mut self, issue: ValidationIssue) {
+        match issue.severity {
+            Severity::Error => self.errors.push(issue),
+            Severity::Warning => self.warnings.push(issue),
+        }
+    }
+
+    /// A warning with no more specific code
+    pub fn add_warning(&mut self, field: &str, message: String) {
+        self.push(ValidationIssue::warning(field, IssueCode::Invalid, message));
+    }
+
+    /// An error with no more specific code
+    pub fn add_error(&mut self, field: &str, message: String) {
+        self.push(ValidationIssue::error(field, IssueCode::Invalid, message));
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Errors, then warnings
+    pub fn issues(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.errors.iter().chain(&self.warnings)
+    }
+}
+
+impl Extend<ValidationIssue> for ValidationResult {
+    fn extend<I: IntoIterator<Item = ValidationIssue>>(&mut self, issues: I) {
+        for issue in issues {
+            self.push(issue);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_issue_serializes_structured_and_displays_its_message() {
+        let issue = ValidationIssue::clamped(
+            "glassmorphism.blurRadius",
+            60,
+            "8-48",
+            "blur_radius 60 above maximum 48, clamping",
+        );
+        assert_eq!(issue.to_string(), "blur_radius 60 above maximum 48, clamping");
+        assert_eq!(
+            serde_json::to_value(&issue).unwrap(),
+            json!({
+                "severity": "warning",
+                "field": "glassmorphism.blurRadius",
+                "code": "out_of_range",
+                "message": "blur_radius 60 above maximum 48, clamping",
+                "got": 60,
+                "expected": "8-48",
+            })
+        );
+        let missing = serde_json::to_value(ValidationIssue::missing("colors", "missing required section colors")).unwrap();
+        assert_eq!(missing["code"], "missing");
+        assert!(missing.get("got").is_none());
+    }
+
+    #[test]
+    fn test_result_files_issues_by_severity() {
+        let mut result = ValidationResult::new();
+        result.add_warning("a", "clamped".into());
+        result.extend([ValidationIssue::error("b", IssueCode::InvalidColor, "bad")]);
+        assert!(result.has_errors());
+        assert_eq!(result.errors[0].code, IssueCode::InvalidColor);
+        assert_eq!(result.warnings[0].code, IssueCode::Invalid);
+        let fields: Vec<&str> = result.issues().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["b", "a"]);
+        assert_eq!(IssueCode::SliceCount.as_str(), serde_json::to_value(IssueCode::SliceCount).unwrap());
+    }
+}
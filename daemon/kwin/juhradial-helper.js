@@ -0,0 +1,52 @@
+// JuhRadial MX resident KWin helper
+//
+// Loaded by juhradiald under the plugin name "juhradialmx-helper" and
+// unloaded when the daemon stops. Reports the cursor position (compositor
+// logical pixels, the space the overlay positions itself in), the active
+// window's resource class and the screen layout with each screen's scale to
+// the daemon, so none of them needs a one-shot script per query.
+
+var DAEMON = ["org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon", "org.kde.juhradialmx.Daemon"];
+
+function reportCursor() {
+    var pos = workspace.cursorPos;
+    callDBus(DAEMON[0], DAEMON[1], DAEMON[2], "ReportCursorPosition",
+             Math.round(pos.x), Math.round(pos.y));
+}
+
+function reportActive(w) {
+    if (w && w.resourceClass) {
+        callDBus(DAEMON[0], DAEMON[1], DAEMON[2], "ReportActiveWindow",
+                 String(w.resourceClass));
+    }
+}
+
+if (typeof workspace.cursorPosChanged !== "undefined") {
+    workspace.cursorPosChanged.connect(reportCursor);
+    reportCursor();
+}
+
+function reportScreens() {
+    var screens = workspace.screens.map(function (s) {
+        var g = s.geometry;
+        return {name: String(s.name), x: g.x, y: g.y, width: g.width, height: g.height,
+                scale: s.devicePixelRatio};
+    });
+    callDBus(DAEMON[0], DAEMON[1], DAEMON[2], "ReportScreens", JSON.stringify(screens));
+}
+
+// Plasma 6 only; without it the daemon queries the layout itself
+if (typeof workspace.screens !== "undefined" && typeof workspace.screensChanged !== "undefined") {
+    workspace.screensChanged.connect(reportScreens);
+    reportScreens();
+}
+
+if (typeof workspace.windowActivated !== "undefined") {
+    // Plasma 6
+    workspace.windowActivated.connect(reportActive);
+    reportActive(workspace.activeWindow);
+} else if (typeof workspace.clientActivated !== "undefined") {
+    // Plasma 5
+    workspace.clientActivated.connect(reportActive);
+    reportActive(workspace.activeClient);
+}
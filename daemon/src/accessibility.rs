@@ -4,9 +4,78 @@
 //!
 //! Detects system accessibility preferences including:
 //! - Reduced motion / animation preferences
-//! - High contrast mode (for Story 4.5)
+//! - High contrast mode (Story 4.5)
+//!
+//! Startup detection reads environment variables; the XDG desktop portal
+//! watcher keeps both in sync with the desktop settings at runtime, along
+//! with the desktop accent color (see [`crate::system_accent`]).
 
 use std::env;
+use std::sync::{Arc, RwLock};
+
+use tokio_stream::StreamExt;
+use zbus::zvariant::Value;
+
+use crate::config::AccessibilityConfig;
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+use crate::idle_effect::IdleEffectHandle;
+use crate::system_accent::{accent_from_setting, SystemAccentHandle, ACCENT_SETTINGS};
+use crate::theme::SharedThemeManager;
+use crate::theme_transition::ResolvedTheme;
+
+pub use juhradial_core::theme::EffectiveAnimationTimings;
+
+/// XDG desktop portal bus name
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+
+/// XDG desktop portal object path
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// XDG desktop portal settings interface
+const PORTAL_SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+
+/// GNOME animation switch (bool, false = reduced motion)
+const GNOME_INTERFACE_NAMESPACE: &str = "org.gnome.desktop.interface";
+const GNOME_ENABLE_ANIMATIONS_KEY: &str = "enable-animations";
+
+/// KDE animation speed slider (double, 0 = animations disabled)
+const KDE_GLOBALS_NAMESPACE: &str = "org.kde.kdeglobals.KDE";
+const KDE_ANIMATION_FACTOR_KEY: &str = "AnimationDurationFactor";
+
+/// Portal settings that carry the desktop reduce-motion preference
+const REDUCED_MOTION_SETTINGS: &[(&str, &str)] = &[
+    (GNOME_INTERFACE_NAMESPACE, GNOME_ENABLE_ANIMATIONS_KEY),
+    (KDE_GLOBALS_NAMESPACE, KDE_ANIMATION_FACTOR_KEY),
+];
+
+/// Cross-desktop contrast preference (u32, 1 = higher contrast)
+pub(crate) const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const APPEARANCE_CONTRAST_KEY: &str = "contrast";
+
+/// GNOME high contrast switch (bool)
+const GNOME_A11Y_INTERFACE_NAMESPACE: &str = "org.gnome.desktop.a11y.interface";
+const GNOME_HIGH_CONTRAST_KEY: &str = "high-contrast";
+
+/// KDE color scheme name (string); high contrast schemes say so in the name
+pub(crate) const KDE_GENERAL_NAMESPACE: &str = "org.kde.kdeglobals.General";
+const KDE_COLOR_SCHEME_KEY: &str = "ColorScheme";
+
+/// Portal settings that carry the desktop high contrast preference
+const HIGH_CONTRAST_SETTINGS: &[(&str, &str)] = &[
+    (APPEARANCE_NAMESPACE, APPEARANCE_CONTRAST_KEY),
+    (GNOME_A11Y_INTERFACE_NAMESPACE, GNOME_HIGH_CONTRAST_KEY),
+    (KDE_GENERAL_NAMESPACE, KDE_COLOR_SCHEME_KEY),
+];
+
+/// Accessibility settings shared between the D-Bus service and the portal watcher
+pub type SharedAccessibility = Arc<RwLock<AccessibilitySettings>>;
+
+/// Create shared accessibility settings with system detection and config overrides
+pub fn new_shared_accessibility(config: &AccessibilityConfig) -> SharedAccessibility {
+    let mut settings = AccessibilitySettings::new();
+    settings.apply_config(config);
+    Arc::new(RwLock::new(settings))
+}
 
 /// Accessibility settings for the application
 #[derive(Debug, Clone, Default)]
@@ -56,8 +125,16 @@ impl AccessibilitySettings {
             }
         }
 
-        // Note: D-Bus detection (Task 1.2, 1.4) requires async runtime
-        // This is handled in detect_system_preferences_async()
+        // GTK themes HighContrast / HighContrastInverse
+        if let Ok(theme) = env::var("GTK_THEME") {
+            if is_high_contrast_scheme(&theme) {
+                self.system_prefers_high_contrast = true;
+                tracing::info!(theme = %theme, "Detected high contrast from GTK_THEME");
+            }
+        }
+
+        // Desktop settings arrive through the portal watcher
+        // (run_accessibility_watcher)
 
         tracing::debug!(
             reduced_motion = self.system_prefers_reduced_motion,
@@ -88,6 +165,22 @@ impl AccessibilitySettings {
         }
     }
 
+    /// Overrides, system preferences and what is in effect, for `DumpState()`
+    pub fn status(&self) -> serde_json::Value {
+        serde_json::json!({
+            "reduced_motion": {
+                "override": self.reduced_motion_override,
+                "system": self.system_prefers_reduced_motion,
+                "effective": self.should_reduce_motion(),
+            },
+            "high_contrast": {
+                "override": self.high_contrast_override,
+                "system": self.system_prefers_high_contrast,
+                "effective": self.should_use_high_contrast(),
+            },
+        })
+    }
+
     /// Set user override for reduced motion (Task 2.1, 2.2)
     pub fn set_reduced_motion(&mut self, value: Option<bool>) {
         self.reduced_motion_override = value;
@@ -108,6 +201,12 @@ impl AccessibilitySettings {
         );
     }
 
+    /// Apply the user overrides from config.json
+    pub fn apply_config(&mut self, config: &AccessibilityConfig) {
+        self.reduced_motion_override = config.reduced_motion.as_override();
+        self.high_contrast_override = config.high_contrast.as_override();
+    }
+
     /// Get the system's detected reduced motion preference
     pub fn system_prefers_reduced_motion(&self) -> bool {
         self.system_prefers_reduced_motion
@@ -129,83 +228,253 @@ impl AccessibilitySettings {
     }
 }
 
-/// Animation timings with reduced motion support (Task 3.2)
-#[derive(Debug, Clone, Copy)]
-pub struct EffectiveAnimationTimings {
-    /// Menu appear duration (ms)
-    pub appear_ms: u16,
-    /// Menu dismiss duration (ms)
-    pub dismiss_ms: u16,
-    /// Slice highlight in duration (ms)
-    pub highlight_in_ms: u16,
-    /// Slice highlight out duration (ms)
-    pub highlight_out_ms: u16,
-    /// Icon scale animation enabled
-    pub icon_scale_enabled: bool,
-    /// Idle effects enabled (matrix rain, particles)
-    pub idle_effects_enabled: bool,
-}
+/// Interpret a portal setting as a reduced-motion preference
+///
+/// Returns `None` for settings that do not describe animations or carry an
+/// unexpected type.
+pub fn reduced_motion_from_setting(namespace: &str, key: &str, value: &Value<'_>) -> Option<bool> {
+    // Portal `Read` (v1) wraps the value in an extra variant
+    if let Value::Value(inner) = value {
+        return reduced_motion_from_setting(namespace, key, inner);
+    }
 
-impl EffectiveAnimationTimings {
-    /// Create timings for reduced motion mode (all 0ms, effects disabled)
-    pub fn reduced_motion() -> Self {
-        Self {
-            appear_ms: 0,
-            dismiss_ms: 0,
-            highlight_in_ms: 0,
-            highlight_out_ms: 0,
-            icon_scale_enabled: false,
-            idle_effects_enabled: false,
+    match (namespace, key, value) {
+        (GNOME_INTERFACE_NAMESPACE, GNOME_ENABLE_ANIMATIONS_KEY, Value::Bool(enabled)) => {
+            Some(!enabled)
         }
+        (KDE_GLOBALS_NAMESPACE, KDE_ANIMATION_FACTOR_KEY, Value::F64(factor)) => {
+            Some(*factor <= 0.0)
+        }
+        _ => None,
     }
+}
 
-    /// Create default timings from UX spec
-    pub fn default_timings() -> Self {
-        Self {
-            appear_ms: 30,
-            dismiss_ms: 50,
-            highlight_in_ms: 80,
-            highlight_out_ms: 60,
-            icon_scale_enabled: true,
-            idle_effects_enabled: true,
+/// Interpret a portal setting as a high contrast preference
+///
+/// Returns `None` for settings that do not describe contrast or carry an
+/// unexpected type.
+pub fn high_contrast_from_setting(namespace: &str, key: &str, value: &Value<'_>) -> Option<bool> {
+    if let Value::Value(inner) = value {
+        return high_contrast_from_setting(namespace, key, inner);
+    }
+
+    match (namespace, key, value) {
+        (APPEARANCE_NAMESPACE, APPEARANCE_CONTRAST_KEY, Value::U32(contrast)) => Some(*contrast == 1),
+        (GNOME_A11Y_INTERFACE_NAMESPACE, GNOME_HIGH_CONTRAST_KEY, Value::Bool(enabled)) => Some(*enabled),
+        (KDE_GENERAL_NAMESPACE, KDE_COLOR_SCHEME_KEY, Value::Str(scheme)) => {
+            Some(is_high_contrast_scheme(scheme))
         }
+        _ => None,
     }
 }
 
-impl Default for EffectiveAnimationTimings {
-    fn default() -> Self {
-        Self::default_timings()
+/// Whether a theme or color scheme name is a high contrast one
+/// ("HighContrast", "BreezeHighContrast", "high-contrast-inverse", ...)
+fn is_high_contrast_scheme(name: &str) -> bool {
+    let compact: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    compact.contains("highcontrast")
+}
+
+/// Effective animation timings for the current theme and accessibility state,
+/// with the idle effect limited as in the menu payload
+pub fn current_animation_timings(
+    themes: &SharedThemeManager,
+    accessibility: &SharedAccessibility,
+    idle_effect: &IdleEffectHandle,
+) -> EffectiveAnimationTimings {
+    let reduce_motion = accessibility
+        .read()
+        .map(|a| a.should_reduce_motion())
+        .unwrap_or(false);
+
+    match themes.read() {
+        Ok(themes) => {
+            let theme = themes.current();
+            let idle = theme.get_effective_idle_effect(reduce_motion).limited(&idle_effect.limits());
+            theme.get_effective_animation_timings(reduce_motion).with_idle_effect(&idle)
+        }
+        Err(_) if reduce_motion => EffectiveAnimationTimings::reduced_motion(),
+        Err(_) => EffectiveAnimationTimings::default_timings(),
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Broadcast `AnimationTimingsChanged` with the current effective timings
+pub async fn emit_animation_timings_changed(
+    connection: &zbus::Connection,
+    themes: &SharedThemeManager,
+    accessibility: &SharedAccessibility,
+    idle_effect: &IdleEffectHandle,
+) -> zbus::Result<()> {
+    let timings = current_animation_timings(themes, accessibility, idle_effect);
+    let json = serde_json::to_string(&timings).unwrap_or_default();
+    connection
+        .emit_signal(None::<&str>, DBUS_PATH, DBUS_INTERFACE, "AnimationTimingsChanged", &(json,))
+        .await
+}
 
-    // Task 4.1: Test animation timing override
-    #[test]
-    fn test_reduced_motion_timings() {
-        let timings = EffectiveAnimationTimings::reduced_motion();
-
-        assert_eq!(timings.appear_ms, 0);
-        assert_eq!(timings.dismiss_ms, 0);
-        assert_eq!(timings.highlight_in_ms, 0);
-        assert_eq!(timings.highlight_out_ms, 0);
-        assert!(!timings.icon_scale_enabled);
-        assert!(!timings.idle_effects_enabled);
+/// Announce the theme change from `before` and emit a fresh `MenuPayload`,
+/// so the overlay restyles before the next open
+async fn refresh_menu_payload(connection: &zbus::Connection, before: Option<ResolvedTheme>) -> zbus::Result<()> {
+    let iface = connection
+        .object_server()
+        .interface::<_, crate::dbus::JuhRadialService>(DBUS_PATH)
+        .await?;
+    let service = iface.get().await;
+    service.emit_theme_changed(iface.signal_emitter(), before).await?;
+    // An open menu keeps the hover help it was opened with
+    service.emit_menu_payload(iface.signal_emitter()).await.map(drop)
+}
+
+/// Follow the desktop reduce-motion, high contrast and accent color settings
+/// through the XDG settings portal
+///
+/// Reads the GNOME, KDE and cross-desktop settings once, then applies every
+/// `SettingChanged` for them. When an effective preference or the accent
+/// changes, the new timings or a refreshed menu payload are broadcast so an
+/// overlay between menu invocations picks them up. Best-effort: without a portal the startup
+/// environment detection stays in effect.
+pub async fn run_accessibility_watcher(
+    connection: zbus::Connection,
+    accessibility: SharedAccessibility,
+    themes: SharedThemeManager,
+    system_accent: SystemAccentHandle,
+    idle_effect: IdleEffectHandle,
+) {
+    let proxy = match zbus::Proxy::new(
+        &connection,
+        PORTAL_BUS_NAME,
+        PORTAL_PATH,
+        PORTAL_SETTINGS_INTERFACE,
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::warn!(error = %e, "Accessibility watcher: settings portal unavailable");
+            return;
+        }
+    };
+
+    let settings = REDUCED_MOTION_SETTINGS.iter().chain(HIGH_CONTRAST_SETTINGS).chain(ACCENT_SETTINGS);
+    for (namespace, key) in settings {
+        let value: zbus::zvariant::OwnedValue = match proxy.call("ReadOne", &(namespace, key)).await {
+            Ok(v) => v,
+            Err(_) => match proxy.call("Read", &(namespace, key)).await {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::debug!(namespace, key, error = %e, "Portal setting not available");
+                    continue;
+                }
+            },
+        };
+        if let Some(reduced) = reduced_motion_from_setting(namespace, key, &value) {
+            apply_system_reduced_motion(&accessibility, reduced);
+        } else if let Some(high_contrast) = high_contrast_from_setting(namespace, key, &value) {
+            apply_system_high_contrast(&accessibility, high_contrast);
+        } else if let Some(Some(accent)) = accent_from_setting(namespace, key, &value) {
+            // Either key may be unset while the other carries the accent
+            apply_system_accent(&system_accent, &themes, Some(accent));
+        }
     }
 
-    #[test]
-    fn test_default_timings() {
-        let timings = EffectiveAnimationTimings::default_timings();
-
-        assert_eq!(timings.appear_ms, 30);
-        assert_eq!(timings.dismiss_ms, 50);
-        assert_eq!(timings.highlight_in_ms, 80);
-        assert_eq!(timings.highlight_out_ms, 60);
-        assert!(timings.icon_scale_enabled);
-        assert!(timings.idle_effects_enabled);
+    let mut stream = match proxy.receive_signal("SettingChanged").await {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!(error = %e, "Accessibility watcher: could not watch SettingChanged");
+            return;
+        }
+    };
+
+    while let Some(msg) = stream.next().await {
+        let Ok((namespace, key, value)) = msg
+            .body()
+            .deserialize::<(String, String, zbus::zvariant::OwnedValue)>()
+        else {
+            continue;
+        };
+        if let Some(reduced) = reduced_motion_from_setting(&namespace, &key, &value) {
+            if apply_system_reduced_motion(&accessibility, reduced) {
+                if let Err(e) = emit_animation_timings_changed(&connection, &themes, &accessibility, &idle_effect).await {
+                    tracing::warn!(error = %e, "Failed to emit AnimationTimingsChanged");
+                }
+            }
+        } else if let Some(high_contrast) = high_contrast_from_setting(&namespace, &key, &value) {
+            let before = ResolvedTheme::current(&themes, &accessibility);
+            if apply_system_high_contrast(&accessibility, high_contrast) {
+                if let Err(e) = refresh_menu_payload(&connection, before).await {
+                    tracing::warn!(error = %e, "Failed to emit refreshed MenuPayload");
+                }
+            }
+        } else if let Some(accent) = accent_from_setting(&namespace, &key, &value) {
+            let before = ResolvedTheme::current(&themes, &accessibility);
+            if apply_system_accent(&system_accent, &themes, accent) {
+                if let Err(e) = refresh_menu_payload(&connection, before).await {
+                    tracing::warn!(error = %e, "Failed to emit refreshed MenuPayload");
+                }
+            }
+        }
     }
+}
+
+/// Record the desktop accent color; true when the drawn accent changed
+fn apply_system_accent(system_accent: &SystemAccentHandle, themes: &SharedThemeManager, accent: Option<String>) -> bool {
+    let followed = system_accent.update(|s| {
+        s.set_system(accent.clone());
+        s.effective().is_some()
+    });
+    let Ok(mut themes) = themes.write() else {
+        return false;
+    };
+    let changed = system_accent.apply(&mut themes);
+    tracing::info!(
+        system = accent.as_deref().unwrap_or("none"),
+        followed = followed.unwrap_or(false),
+        "System accent color updated"
+    );
+    changed
+}
+
+/// Record the system reduce-motion preference; true when the effective value changed
+fn apply_system_reduced_motion(accessibility: &SharedAccessibility, reduced: bool) -> bool {
+    let Ok(mut settings) = accessibility.write() else {
+        return false;
+    };
+    let before = settings.should_reduce_motion();
+    settings.set_system_reduced_motion(reduced);
+    let after = settings.should_reduce_motion();
+
+    tracing::info!(
+        system = reduced,
+        effective = after,
+        "System reduced motion preference updated"
+    );
+    before != after
+}
+
+/// Record the system high contrast preference; true when the effective value changed
+fn apply_system_high_contrast(accessibility: &SharedAccessibility, high_contrast: bool) -> bool {
+    let Ok(mut settings) = accessibility.write() else {
+        return false;
+    };
+    let before = settings.should_use_high_contrast();
+    settings.set_system_high_contrast(high_contrast);
+    let after = settings.should_use_high_contrast();
+
+    tracing::info!(
+        system = high_contrast,
+        effective = after,
+        "System high contrast preference updated"
+    );
+    before != after
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     // Task 4.2: Test user setting overrides system
     #[test]
@@ -269,4 +538,124 @@ mod tests {
         assert!(!settings.system_prefers_reduced_motion);
         assert!(!settings.system_prefers_high_contrast);
     }
+
+    #[test]
+    fn test_reduced_motion_from_gnome_setting() {
+        assert_eq!(
+            reduced_motion_from_setting(GNOME_INTERFACE_NAMESPACE, GNOME_ENABLE_ANIMATIONS_KEY, &Value::Bool(false)),
+            Some(true)
+        );
+        assert_eq!(
+            reduced_motion_from_setting(GNOME_INTERFACE_NAMESPACE, GNOME_ENABLE_ANIMATIONS_KEY, &Value::Bool(true)),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_reduced_motion_from_kde_setting() {
+        assert_eq!(
+            reduced_motion_from_setting(KDE_GLOBALS_NAMESPACE, KDE_ANIMATION_FACTOR_KEY, &Value::F64(0.0)),
+            Some(true)
+        );
+        assert_eq!(
+            reduced_motion_from_setting(KDE_GLOBALS_NAMESPACE, KDE_ANIMATION_FACTOR_KEY, &Value::F64(1.0)),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_reduced_motion_from_wrapped_and_unrelated_settings() {
+        let wrapped = Value::Value(Box::new(Value::Bool(false)));
+        assert_eq!(
+            reduced_motion_from_setting(GNOME_INTERFACE_NAMESPACE, GNOME_ENABLE_ANIMATIONS_KEY, &wrapped),
+            Some(true)
+        );
+        assert_eq!(
+            reduced_motion_from_setting(GNOME_INTERFACE_NAMESPACE, "color-scheme", &Value::Bool(false)),
+            None
+        );
+        assert_eq!(
+            reduced_motion_from_setting(KDE_GLOBALS_NAMESPACE, KDE_ANIMATION_FACTOR_KEY, &Value::Bool(true)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_high_contrast_from_settings() {
+        assert_eq!(
+            high_contrast_from_setting(APPEARANCE_NAMESPACE, APPEARANCE_CONTRAST_KEY, &Value::U32(1)),
+            Some(true)
+        );
+        assert_eq!(
+            high_contrast_from_setting(APPEARANCE_NAMESPACE, APPEARANCE_CONTRAST_KEY, &Value::U32(0)),
+            Some(false)
+        );
+        let wrapped = Value::Value(Box::new(Value::Bool(true)));
+        assert_eq!(
+            high_contrast_from_setting(GNOME_A11Y_INTERFACE_NAMESPACE, GNOME_HIGH_CONTRAST_KEY, &wrapped),
+            Some(true)
+        );
+        assert_eq!(
+            high_contrast_from_setting(KDE_GENERAL_NAMESPACE, KDE_COLOR_SCHEME_KEY, &Value::from("BreezeHighContrast")),
+            Some(true)
+        );
+        assert_eq!(
+            high_contrast_from_setting(KDE_GENERAL_NAMESPACE, KDE_COLOR_SCHEME_KEY, &Value::from("BreezeDark")),
+            Some(false)
+        );
+        assert_eq!(
+            high_contrast_from_setting(APPEARANCE_NAMESPACE, APPEARANCE_CONTRAST_KEY, &Value::Bool(true)),
+            None
+        );
+        assert!(is_high_contrast_scheme("high-contrast-inverse"));
+    }
+
+    #[test]
+    fn test_high_contrast_config_override() {
+        let config = AccessibilityConfig {
+            high_contrast: crate::config::AccessibilityPreference::Off,
+            ..Default::default()
+        };
+        let shared = new_shared_accessibility(&config);
+
+        assert!(!apply_system_high_contrast(&shared, true));
+        assert!(!shared.read().unwrap().should_use_high_contrast());
+
+        shared.write().unwrap().apply_config(&AccessibilityConfig::default());
+        assert!(shared.read().unwrap().should_use_high_contrast());
+        assert!(apply_system_high_contrast(&shared, false));
+    }
+
+    #[test]
+    fn test_config_override_beats_system() {
+        let config = AccessibilityConfig {
+            reduced_motion: crate::config::AccessibilityPreference::Off,
+            ..Default::default()
+        };
+        let shared = new_shared_accessibility(&config);
+
+        // System flips to reduced motion, but the config forces it off
+        assert!(!apply_system_reduced_motion(&shared, true));
+        assert!(!shared.read().unwrap().should_reduce_motion());
+
+        shared.write().unwrap().apply_config(&AccessibilityConfig::default());
+        assert!(shared.read().unwrap().should_reduce_motion());
+    }
+
+    #[test]
+    fn test_current_timings_follow_reduced_motion() {
+        let themes: SharedThemeManager = Arc::new(RwLock::new(crate::theme::ThemeManager::new()));
+        let shared: SharedAccessibility = Arc::new(RwLock::new(AccessibilitySettings::default()));
+
+        assert_eq!(
+            current_animation_timings(&themes, &shared, &IdleEffectHandle::default()).appear_ms,
+            EffectiveAnimationTimings::default_timings().appear_ms
+        );
+
+        assert!(apply_system_reduced_motion(&shared, true));
+        assert_eq!(
+            current_animation_timings(&themes, &shared, &IdleEffectHandle::default()),
+            EffectiveAnimationTimings::reduced_motion()
+        );
+    }
 }
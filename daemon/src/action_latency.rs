@@ -0,0 +1,447 @@
+//! Action execution latency histograms (NFR-001)
+//!
+//! Actions must start within 10ms. `ActionExecutor` records every execution
+//! here, keyed by action type, plus a phase breakdown for shortcuts (setup vs
+//! the ydotool / xdotool spawn) so a slow fork can be told apart from the rest.
+//! The menu-open path (ShowMenu entry until the payload signal is sent) has
+//! its own histogram against a 5ms budget, and so does a haptic event until
+//! its pulse is written (2ms, including any wait for the device lock).
+//! Executions that used a pre-warmed plan (`crate::prewarm`) also record the
+//! preparation time they skipped. Buckets are fixed, so percentiles are
+//! reported as the upper bound of the bucket they fall in (capped at the
+//! largest sample seen).
+//!
+//! The overlay reports the actions it spawns itself (the Settings ring) with
+//! `ReportActionResult`, and those land in the same histograms. Counters live
+//! in a [`LatencyHandle`] for the daemon's lifetime and are exposed in
+//! `Status()`; `ResetActionLatency()` clears them.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+/// NFR-001 start latency target
+pub const LATENCY_TARGET: Duration = Duration::from_millis(10);
+
+/// Budget for ShowMenu until the menu payload is on the bus
+pub const MENU_OPEN_TARGET: Duration = Duration::from_millis(5);
+
+/// Budget for a haptic event until its pulse is written, lock wait included
+pub const HAPTIC_SEND_TARGET: Duration = Duration::from_millis(2);
+
+/// Bucket upper bounds in microseconds; larger samples land in an overflow bucket
+const BUCKET_BOUNDS_US: [u64; 13] = [
+    50, 100, 250, 500, 1_000, 2_000, 5_000, 10_000, 20_000, 50_000, 100_000, 250_000, 1_000_000,
+];
+
+/// Fixed-bucket latency histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKET_BOUNDS_US.len() + 1],
+    count: u64,
+    sum_us: u64,
+    over_target: u64,
+    max_us: u64,
+    target: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    /// Histogram against `LATENCY_TARGET`
+    pub const fn new() -> Self {
+        Self::with_target(LATENCY_TARGET)
+    }
+
+    pub const fn with_target(target: Duration) -> Self {
+        Self {
+            buckets: [0; BUCKET_BOUNDS_US.len() + 1],
+            count: 0,
+            sum_us: 0,
+            over_target: 0,
+            max_us: 0,
+            target,
+        }
+    }
+
+    pub fn record(&mut self, elapsed: Duration) {
+        let us = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let bucket = BUCKET_BOUNDS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(BUCKET_BOUNDS_US.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum_us = self.sum_us.saturating_add(us);
+        self.max_us = self.max_us.max(us);
+        if elapsed > self.target {
+            self.over_target += 1;
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Total of all samples in microseconds
+    pub fn sum_us(&self) -> u64 {
+        self.sum_us
+    }
+
+    /// Cumulative sample count at or below each bucket bound, in
+    /// microseconds; the last entry (`None`) is every sample
+    pub fn cumulative_buckets(&self) -> impl Iterator<Item = (Option<u64>, u64)> + '_ {
+        let bounds = BUCKET_BOUNDS_US.iter().copied().map(Some).chain([None]);
+        bounds.zip(self.buckets.iter().scan(0, |seen, n| {
+            *seen += n;
+            Some(*seen)
+        }))
+    }
+
+    /// Samples above the histogram's target
+    pub fn over_target(&self) -> u64 {
+        self.over_target
+    }
+
+    /// Percentile (`q` in 0.0..=1.0) in microseconds, `None` when empty
+    pub fn percentile_us(&self, q: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                let bound = BUCKET_BOUNDS_US.get(i).copied().unwrap_or(self.max_us);
+                return Some(bound.min(self.max_us));
+            }
+        }
+        Some(self.max_us)
+    }
+
+    fn to_json(self) -> Value {
+        let ms = |us: Option<u64>| us.map(|us| us as f64 / 1000.0);
+        json!({
+            "count": self.count,
+            "p50_ms": ms(self.percentile_us(0.50)),
+            "p95_ms": ms(self.percentile_us(0.95)),
+            "p99_ms": ms(self.percentile_us(0.99)),
+            "max_ms": ms((self.count > 0).then_some(self.max_us)),
+            "over_target": self.over_target,
+        })
+    }
+}
+
+/// Action types with their own histogram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionKind {
+    Shortcut,
+    Command,
+    DBus,
+    KWin,
+    PastePlain,
+    /// `media` and `volume`: the dispatch to the media worker, not the bus call
+    Media,
+}
+
+impl ActionKind {
+    pub const ALL: [ActionKind; 6] = [
+        Self::Shortcut,
+        Self::Command,
+        Self::DBus,
+        Self::KWin,
+        Self::PastePlain,
+        Self::Media,
+    ];
+
+    /// Kind named `name` (as in [`ActionKind::as_str`])
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == name)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Shortcut => "shortcut",
+            Self::Command => "command",
+            Self::DBus => "dbus",
+            Self::KWin => "kwin",
+            Self::PastePlain => "paste_plain",
+            Self::Media => "media",
+        }
+    }
+}
+
+/// Where a shortcut's time went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutPhase {
+    /// Session detection and key-code mapping before any process is spawned
+    Setup,
+    /// Spawning ydotool (Wayland uinput path)
+    YdotoolSpawn,
+    /// Spawning xdotool (X11, or the Wayland fallback)
+    XdotoolSpawn,
+}
+
+impl ShortcutPhase {
+    pub const ALL: [ShortcutPhase; 3] = [Self::Setup, Self::YdotoolSpawn, Self::XdotoolSpawn];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Setup => "setup",
+            Self::YdotoolSpawn => "ydotool_spawn",
+            Self::XdotoolSpawn => "xdotool_spawn",
+        }
+    }
+}
+
+/// All latency counters since startup or the last reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyStats {
+    actions: [LatencyHistogram; ActionKind::ALL.len()],
+    shortcut_phases: [LatencyHistogram; ShortcutPhase::ALL.len()],
+    menu_open: LatencyHistogram,
+    prewarm_saved: LatencyHistogram,
+    haptic_send: LatencyHistogram,
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyStats {
+    pub const fn new() -> Self {
+        Self {
+            actions: [LatencyHistogram::new(); ActionKind::ALL.len()],
+            shortcut_phases: [LatencyHistogram::new(); ShortcutPhase::ALL.len()],
+            menu_open: LatencyHistogram::with_target(MENU_OPEN_TARGET),
+            prewarm_saved: LatencyHistogram::new(),
+            haptic_send: LatencyHistogram::with_target(HAPTIC_SEND_TARGET),
+        }
+    }
+
+    pub fn record(&mut self, kind: ActionKind, elapsed: Duration) {
+        self.actions[kind as usize].record(elapsed);
+    }
+
+    pub fn record_phase(&mut self, phase: ShortcutPhase, elapsed: Duration) {
+        self.shortcut_phases[phase as usize].record(elapsed);
+    }
+
+    pub fn record_menu_open(&mut self, elapsed: Duration) {
+        self.menu_open.record(elapsed);
+    }
+
+    pub fn record_prewarm_saved(&mut self, saved: Duration) {
+        self.prewarm_saved.record(saved);
+    }
+
+    pub fn record_haptic_send(&mut self, elapsed: Duration) {
+        self.haptic_send.record(elapsed);
+    }
+
+    pub fn histogram(&self, kind: ActionKind) -> &LatencyHistogram {
+        &self.actions[kind as usize]
+    }
+
+    pub fn phase(&self, phase: ShortcutPhase) -> &LatencyHistogram {
+        &self.shortcut_phases[phase as usize]
+    }
+
+    pub fn menu_open(&self) -> &LatencyHistogram {
+        &self.menu_open
+    }
+
+    pub fn prewarm_saved(&self) -> &LatencyHistogram {
+        &self.prewarm_saved
+    }
+
+    pub fn haptic_send(&self) -> &LatencyHistogram {
+        &self.haptic_send
+    }
+
+    /// JSON for `Status()`
+    pub fn to_json(&self) -> Value {
+        let actions: serde_json::Map<String, Value> = ActionKind::ALL
+            .iter()
+            .map(|&k| (k.as_str().to_string(), self.histogram(k).to_json()))
+            .collect();
+        let phases: serde_json::Map<String, Value> = ShortcutPhase::ALL
+            .iter()
+            .map(|&p| (p.as_str().to_string(), self.shortcut_phases[p as usize].to_json()))
+            .collect();
+        let over_target: u64 = self.actions.iter().map(|h| h.over_target()).sum();
+        let mut menu_open = self.menu_open.to_json();
+        menu_open["target_ms"] = json!(MENU_OPEN_TARGET.as_millis() as u64);
+        let mut haptic_send = self.haptic_send.to_json();
+        haptic_send["target_ms"] = json!(HAPTIC_SEND_TARGET.as_millis() as u64);
+        json!({
+            "target_ms": LATENCY_TARGET.as_millis() as u64,
+            "over_target": over_target,
+            "actions": actions,
+            "shortcut_phases": phases,
+            "menu_open": menu_open,
+            "prewarm_saved": self.prewarm_saved.to_json(),
+            "haptic_send": haptic_send,
+        })
+    }
+}
+
+/// Shared handle to the daemon's latency counters
+///
+/// Held by the D-Bus service (`Status()`, `ResetActionLatency()`, overlay
+/// reports), the executor's callers through `DaemonHandles`, the haptic
+/// manager and the metrics endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHandle {
+    stats: Arc<Mutex<LatencyStats>>,
+}
+
+impl LatencyHandle {
+    fn with(&self, f: impl FnOnce(&mut LatencyStats)) {
+        if let Ok(mut stats) = self.stats.lock() {
+            f(&mut stats);
+        }
+    }
+
+    /// Record one action execution
+    pub fn record_action(&self, kind: ActionKind, elapsed: Duration) {
+        self.with(|s| s.record(kind, elapsed));
+    }
+
+    /// Record one phase of a shortcut execution
+    pub fn record_shortcut_phase(&self, phase: ShortcutPhase, elapsed: Duration) {
+        self.with(|s| s.record_phase(phase, elapsed));
+    }
+
+    /// Record one ShowMenu, entry to payload sent
+    pub fn record_menu_open(&self, elapsed: Duration) {
+        self.with(|s| s.record_menu_open(elapsed));
+    }
+
+    /// Record the setup time a pre-warmed execution skipped
+    pub fn record_prewarm_saved(&self, saved: Duration) {
+        self.with(|s| s.record_prewarm_saved(saved));
+    }
+
+    /// Record one haptic event, `emit_shared` entry to pulse written
+    pub fn record_haptic_send(&self, elapsed: Duration) {
+        self.with(|s| s.record_haptic_send(elapsed));
+    }
+
+    /// Copy of the current counters
+    pub fn snapshot(&self) -> LatencyStats {
+        self.stats.lock().map(|s| *s).unwrap_or_default()
+    }
+
+    /// Clear all counters
+    pub fn reset(&self) {
+        self.with(|s| *s = LatencyStats::new());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_percentiles_use_bucket_bounds() {
+        let mut h = LatencyHistogram::new();
+        assert_eq!(h.percentile_us(0.5), None);
+        // 90 fast samples (~0.3ms), 9 at 3ms, 1 at 40ms
+        for _ in 0..90 {
+            h.record(Duration::from_micros(300));
+        }
+        for _ in 0..9 {
+            h.record(ms(3));
+        }
+        h.record(ms(40));
+        assert_eq!(h.count(), 100);
+        assert_eq!(h.percentile_us(0.50), Some(500));
+        assert_eq!(h.percentile_us(0.95), Some(5_000));
+        // Capped at the largest sample rather than the 50ms bucket bound
+        assert_eq!(h.percentile_us(0.999), Some(40_000));
+        assert_eq!(h.over_target(), 1);
+    }
+
+    #[test]
+    fn test_overflow_bucket_reports_max() {
+        let mut h = LatencyHistogram::new();
+        h.record(Duration::from_secs(3));
+        assert_eq!(h.percentile_us(0.5), Some(3_000_000));
+    }
+
+    #[test]
+    fn test_stats_json_and_reset() {
+        let mut stats = LatencyStats::new();
+        stats.record(ActionKind::Shortcut, ms(12));
+        stats.record(ActionKind::Command, ms(1));
+        stats.record_phase(ShortcutPhase::XdotoolSpawn, ms(11));
+        let json = stats.to_json();
+        assert_eq!(json["over_target"], 1);
+        assert_eq!(json["actions"]["shortcut"]["count"], 1);
+        assert_eq!(json["actions"]["shortcut"]["p99_ms"], 12.0);
+        assert_eq!(json["actions"]["kwin"]["p50_ms"], Value::Null);
+        assert_eq!(json["shortcut_phases"]["xdotool_spawn"]["over_target"], 1);
+
+        stats = LatencyStats::new();
+        assert_eq!(stats.to_json()["over_target"], 0);
+    }
+
+    #[test]
+    fn test_menu_open_uses_its_own_budget() {
+        let mut stats = LatencyStats::new();
+        stats.record_menu_open(ms(7));
+        stats.record_menu_open(ms(2));
+        assert_eq!(stats.menu_open().over_target(), 1);
+        let json = stats.to_json();
+        assert_eq!(json["menu_open"]["target_ms"], 5);
+        assert_eq!(json["menu_open"]["count"], 2);
+        // Not counted against the action budget
+        assert_eq!(json["over_target"], 0);
+    }
+
+    #[test]
+    fn test_prewarm_saved_is_reported_separately() {
+        let mut stats = LatencyStats::new();
+        stats.record_prewarm_saved(Duration::from_micros(400));
+        let json = stats.to_json();
+        assert_eq!(json["prewarm_saved"]["count"], 1);
+        assert_eq!(json["prewarm_saved"]["max_ms"], 0.4);
+        assert_eq!(json["over_target"], 0);
+    }
+
+    #[test]
+    fn test_haptic_send_has_its_own_budget() {
+        let mut stats = LatencyStats::new();
+        stats.record_haptic_send(Duration::from_micros(80));
+        stats.record_haptic_send(ms(100));
+        let json = stats.to_json();
+        assert_eq!(json["haptic_send"]["target_ms"], 2);
+        assert_eq!(json["haptic_send"]["count"], 2);
+        assert_eq!(json["haptic_send"]["over_target"], 1);
+        assert_eq!(json["over_target"], 0);
+    }
+
+    #[test]
+    fn test_handle_clones_share_counters() {
+        let handle = LatencyHandle::default();
+        let reporter = handle.clone();
+        reporter.record_action(ActionKind::from_name("command").unwrap(), ms(3));
+        assert_eq!(handle.snapshot().histogram(ActionKind::Command).count(), 1);
+        assert_eq!(ActionKind::from_name("exec"), None);
+        handle.reset();
+        assert_eq!(reporter.snapshot(), LatencyStats::new());
+    }
+}
@@ -7,67 +7,41 @@
 //!
 //! ## Shell Commands (Story 2.8)
 //! Executes commands via sh -c for shell interpretation, non-blocking.
+//!
+//! ## Invocation Context
+//! Commands see the menu invocation (`crate::invocation`) as `JUH_*`
+//! environment variables; D-Bus string arguments have them substituted.
+//!
+//! ## Flatpak
+//! Inside a Flatpak sandbox, commands and key/clipboard helpers run on the
+//! host through `crate::sandbox`.
+//!
+//! ## Pre-warming
+//! A shortcut or command whose slice has been highlighted for a while may
+//! already be resolved (`PreparedAction`, see `crate::prewarm`); executing it
+//! then skips straight to the spawn.
+//!
+//! ## Media and Volume
+//! Queued to `crate::media_control`, which talks MPRIS and PulseAudio over
+//! D-Bus; executing returns once the action is queued.
+//!
+//! ## Paste as Plain Text
+//! Reads the clipboard through `crate::clipboard` and either types short text
+//! or swaps it to `text/plain`, sends ctrl+v and restores the original.
 
-use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::Instant;
-
-/// Action types supported by radial menu
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", content = "value")]
-pub enum ActionType {
-    /// Keyboard shortcut (e.g., "Ctrl+C")
-    #[serde(rename = "shortcut")]
-    Shortcut(String),
-
-    /// Shell command (e.g., "dolphin ~")
-    #[serde(rename = "command")]
-    Command(String),
-
-    /// D-Bus method call
-    #[serde(rename = "dbus")]
-    DBus(DBusCall),
-
-    /// KWin script action
-    #[serde(rename = "kwin")]
-    KWin(String),
-
-    /// No action (empty slice)
-    #[serde(rename = "none")]
-    None,
-}
-
-/// D-Bus method call specification
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DBusCall {
-    /// D-Bus service name
-    pub service: String,
-    /// Object path
-    pub path: String,
-    /// Interface name
-    pub interface: String,
-    /// Method name
-    pub method: String,
-    /// Method arguments (as JSON)
-    #[serde(default)]
-    pub args: Vec<serde_json::Value>,
-}
+use std::time::{Duration, Instant};
 
-/// A complete action with icon and label
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Action {
-    /// Action type and parameters
-    #[serde(flatten)]
-    pub action_type: ActionType,
+use crate::action_latency::{ActionKind, ShortcutPhase};
+use crate::clipboard::{self, CommandClipboard, PastePlainError, PastePlan};
+use crate::execution_policy::{CommandLine, ExecutionPolicy};
+use crate::handles::DaemonHandles;
+use crate::invocation::InvocationContext;
+use crate::media_control::MediaRequest;
+use crate::sandbox::{ExecutionStrategy, ResolvedBinaries};
 
-    /// Display label
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub label: Option<String>,
-
-    /// Icon (emoji, path, or system icon name)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub icon: Option<String>,
-}
+pub use juhradial_core::action::{get_default_actions, Action, ActionType, DBusCall};
 
 /// Action executor
 pub struct ActionExecutor;
@@ -76,22 +50,73 @@ impl ActionExecutor {
     /// Execute an action
     ///
     /// Returns within 10ms for keyboard shortcuts (NFR-001)
-    pub async fn execute(action: &Action) -> Result<(), ActionError> {
-        match &action.action_type {
+    pub async fn execute(action: &Action, handles: &DaemonHandles) -> Result<(), ActionError> {
+        Self::execute_with_policy(action, &ExecutionPolicy::unrestricted(), &InvocationContext::now(), handles).await
+    }
+
+    /// Execute an action loaded from profiles.json under an execution policy
+    ///
+    /// Command and KWin actions are checked against the policy first.
+    /// Every execution is recorded in the latency histograms of `handles`;
+    /// actions the policy refuses are not. `context` is the invocation the
+    /// action was picked in, handed to commands and D-Bus calls as `JUH_*`
+    /// variables.
+    pub async fn execute_with_policy(
+        action: &Action,
+        policy: &ExecutionPolicy,
+        context: &InvocationContext,
+        handles: &DaemonHandles,
+    ) -> Result<(), ActionError> {
+        let start = Instant::now();
+        let prepared = handles.prewarm.with(|p| p.take(&action.action_type)).flatten();
+        let saved = prepared.as_ref().map(PreparedAction::cost);
+        let (kind, result) = match &action.action_type {
             ActionType::Shortcut(keys) => {
-                Self::execute_shortcut(keys).await
+                let plan = prepared.and_then(PreparedAction::into_shortcut);
+                (ActionKind::Shortcut, Self::execute_shortcut(keys, plan, handles).await)
             }
             ActionType::Command(cmd) => {
-                Self::execute_command(cmd).await
+                let plan = prepared.and_then(PreparedAction::into_command);
+                (ActionKind::Command, Self::execute_command(cmd, policy, plan, context, &handles.binaries).await)
             }
             ActionType::DBus(call) => {
-                Self::execute_dbus(call).await
+                (ActionKind::DBus, Self::execute_dbus(call, context).await)
             }
             ActionType::KWin(script) => {
-                Self::execute_kwin(script).await
+                (ActionKind::KWin, Self::execute_kwin(script, policy).await)
+            }
+            ActionType::PastePlain => {
+                (ActionKind::PastePlain, Self::execute_paste_plain(handles).await)
+            }
+            ActionType::Media(control) => {
+                let request = MediaRequest::Media(*control);
+                (ActionKind::Media, handles.media.dispatch(request).map_err(ActionError::from))
+            }
+            ActionType::Volume(control) => {
+                let request = MediaRequest::Volume(*control);
+                (ActionKind::Media, handles.media.dispatch(request).map_err(ActionError::from))
+            }
+            ActionType::None => return Ok(()),
+            // Applied by the daemon (`profile_selection`), never executed here
+            ActionType::ProfileSwitch(_) => return Err(ActionError::InvalidAction),
+            // Its steps run per wheel detent (`wheel_axis`); the slice itself does nothing
+            ActionType::WheelAxis { .. } => return Err(ActionError::InvalidAction),
+            // `ActionType` is `#[non_exhaustive]` in juhradial-core
+            _ => return Err(ActionError::InvalidAction),
+        };
+        if !matches!(
+            result,
+            Err(ActionError::CommandNotAllowed(_)
+                | ActionError::ProfileNotApproved
+                | ActionError::RateLimited(_)
+                | ActionError::HostSpawnDenied(_))
+        ) {
+            handles.latency.record_action(kind, start.elapsed());
+            if let Some(saved) = saved {
+                handles.latency.record_prewarm_saved(saved);
             }
-            ActionType::None => Ok(()),
         }
+        result
     }
 
     /// Execute keyboard shortcut via xdotool (Story 2.6)
@@ -100,24 +125,30 @@ impl ActionExecutor {
     /// Format: "ctrl+c", "ctrl+shift+z", "super+e"
     ///
     /// AC1: Execution within 10ms
-    async fn execute_shortcut(keys: &str) -> Result<(), ActionError> {
+    async fn execute_shortcut(
+        keys: &str,
+        prepared: Option<ShortcutPlan>,
+        handles: &DaemonHandles,
+    ) -> Result<(), ActionError> {
+        let strategy = handles.binaries.strategy();
+        host_spawn_ready(strategy)?;
         let start = Instant::now();
 
-        tracing::info!(keys, "Executing keyboard shortcut");
+        tracing::info!(keys, prewarmed = prepared.is_some(), "Executing keyboard shortcut");
 
-        let is_wayland = std::env::var_os("WAYLAND_DISPLAY").is_some()
-            || std::env::var("XDG_SESSION_TYPE")
-                .map(|s| s.eq_ignore_ascii_case("wayland"))
-                .unwrap_or(false);
+        // A pre-warmed plan has no setup phase left to record
+        let plan = prepared.unwrap_or_else(|| {
+            let plan = ShortcutPlan::resolve(keys, &handles.binaries);
+            handles.latency.record_shortcut_phase(ShortcutPhase::Setup, start.elapsed());
+            plan
+        });
 
-        // On Wayland, X11 input synthesis (xdotool) does not reach native
-        // Wayland windows. Inject through the kernel uinput device via ydotool,
-        // which needs evdev key CODES (not keysym names) and is the reliable
-        // path on KDE Plasma Wayland. Unmapped chords fall through to xdotool.
         let mut injected = false;
-        if is_wayland {
-            if let Some(codes) = Self::shortcut_to_evdev_codes(keys) {
-                injected = Self::inject_via_ydotool(&codes);
+        if plan.wayland {
+            if let Some(args) = &plan.ydotool_args {
+                let spawn_start = Instant::now();
+                injected = Self::inject_via_ydotool(strategy, &plan.ydotool, args);
+                handles.latency.record_shortcut_phase(ShortcutPhase::YdotoolSpawn, spawn_start.elapsed());
                 if !injected {
                     tracing::warn!(keys, "ydotool injection failed; trying xdotool");
                 }
@@ -129,7 +160,10 @@ impl ActionExecutor {
         // X11 (or Wayland fallback): keysyms are case-sensitive (e.g.
         // XF86AudioRaiseVolume), so pass the ORIGINAL case to xdotool.
         if !injected {
-            match Command::new("xdotool").args(["key", keys]).spawn() {
+            let spawn_start = Instant::now();
+            let spawned = strategy.command(&plan.xdotool).args(["key", keys]).spawn();
+            handles.latency.record_shortcut_phase(ShortcutPhase::XdotoolSpawn, spawn_start.elapsed());
+            match spawned {
                 Ok(mut child) => match child.try_wait() {
                     Ok(Some(status)) if !status.success() => {
                         tracing::warn!("xdotool exited with error status");
@@ -140,7 +174,7 @@ impl ActionExecutor {
                 Err(e) => {
                     tracing::debug!("xdotool unavailable: {}, trying ydotool codes", e);
                     let ok = Self::shortcut_to_evdev_codes(keys)
-                        .map(|c| Self::inject_via_ydotool(&c))
+                        .map(|c| Self::inject_via_ydotool(strategy, &plan.ydotool, &ydotool_key_args(&c)))
                         .unwrap_or(false);
                     if !ok {
                         return Err(ActionError::ExecutionFailed(format!(
@@ -173,7 +207,7 @@ impl ActionExecutor {
     /// to evdev key codes (modifiers first, main key last) for uinput injection.
     /// Returns None for any token we do not map, so the caller can fall back to
     /// xdotool. Codes are from linux/input-event-codes.h.
-    fn shortcut_to_evdev_codes(keys: &str) -> Option<Vec<u16>> {
+    pub(crate) fn shortcut_to_evdev_codes(keys: &str) -> Option<Vec<u16>> {
         let mut codes = Vec::new();
         for tok in keys.split('+') {
             let code: u16 = match tok.trim().to_ascii_lowercase().as_str() {
@@ -213,34 +247,117 @@ impl ActionExecutor {
         }
     }
 
-    /// Inject a key chord through the kernel uinput device via ydotool: press
-    /// every code in order, then release in reverse. ydotool uses uinput, so it
-    /// drives both X11 and Wayland (incl. KDE Plasma). Returns true if started.
-    fn inject_via_ydotool(codes: &[u16]) -> bool {
-        let mut args: Vec<String> = vec!["key".to_string()];
-        args.extend(codes.iter().map(|c| format!("{}:1", c)));
-        args.extend(codes.iter().rev().map(|c| format!("{}:0", c)));
-        match Command::new("ydotool").args(&args).spawn() {
+    /// Inject a key chord through the kernel uinput device via ydotool
+    /// (`args` from `ydotool_key_args`). ydotool uses uinput, so it drives
+    /// both X11 and Wayland (incl. KDE Plasma). Returns true if started.
+    fn inject_via_ydotool(strategy: &ExecutionStrategy, ydotool: &Path, args: &[String]) -> bool {
+        match strategy.command(ydotool).args(args).spawn() {
             Ok(mut child) => !matches!(child.try_wait(), Ok(Some(status)) if !status.success()),
             Err(_) => false,
         }
     }
 
+    /// Type literal text: ydotool on Wayland, xdotool on X11 or as fallback
+    fn type_text(strategy: &ExecutionStrategy, text: &str) -> Result<(), ActionError> {
+        if is_wayland_session() {
+            match strategy.command("ydotool").args(["type", "--", text]).spawn() {
+                Ok(_) => return Ok(()),
+                Err(e) => tracing::debug!("ydotool unavailable: {}, trying xdotool", e),
+            }
+        }
+        strategy.command("xdotool")
+            .args(["type", "--clearmodifiers", "--", text])
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| ActionError::ExecutionFailed(format!("Text synthesis failed: {}", e)))
+    }
+
+    /// Paste the clipboard as plain text
+    ///
+    /// An empty clipboard is `InvalidAction`; non-text content (images, files)
+    /// is left alone with a warning.
+    async fn execute_paste_plain(handles: &DaemonHandles) -> Result<(), ActionError> {
+        let strategy = handles.binaries.strategy();
+        host_spawn_ready(strategy)?;
+        let mut clipboard = CommandClipboard::detect(strategy.clone());
+        tracing::info!(backend = ?clipboard.backend(), "Pasting clipboard as plain text");
+        let mut reader = clipboard.clone();
+        let plan = tokio::task::spawn_blocking(move || {
+            clipboard::prepare_paste_plain(&mut reader)
+        })
+        .await
+        .map_err(|e| ActionError::ExecutionFailed(format!("Clipboard task failed: {}", e)))?;
+
+        match plan {
+            Ok(PastePlan::Type(text)) => {
+                tracing::debug!(chars = text.chars().count(), "Typing clipboard text");
+                Self::type_text(strategy, &text)
+            }
+            Ok(PastePlan::Paste { restore }) => {
+                Self::execute_shortcut("ctrl+v", None, handles).await?;
+                if let Some(saved) = restore {
+                    // The target app reads the clipboard asynchronously after
+                    // the keypress, so give it time before swapping back.
+                    std::thread::spawn(move || {
+                        std::thread::sleep(clipboard::RESTORE_DELAY);
+                        if let Err(e) = clipboard::restore(&mut clipboard, &saved) {
+                            tracing::warn!(error = %e, mime = %saved.mime, "Failed to restore clipboard");
+                        }
+                    });
+                }
+                Ok(())
+            }
+            Err(PastePlainError::Empty) => {
+                tracing::info!("Clipboard is empty; nothing to paste");
+                Err(ActionError::InvalidAction)
+            }
+            Err(e @ PastePlainError::NotText(_)) => {
+                tracing::warn!(error = %e, "Paste as plain text skipped");
+                Ok(())
+            }
+            Err(PastePlainError::Clipboard(e)) => {
+                tracing::error!(error = %e, "Clipboard access failed");
+                Err(ActionError::ExecutionFailed(e.to_string()))
+            }
+        }
+    }
+
     /// Execute shell command (Story 2.8)
     ///
-    /// Runs command via sh -c for shell interpretation.
+    /// Runs command via sh -c for shell interpretation; in allowlist mode
+    /// the checked argv runs without a shell.
     /// Non-blocking: spawns subprocess and returns immediately.
     ///
     /// AC1: Execution begins within 10ms
-    async fn execute_command(cmd: &str) -> Result<(), ActionError> {
+    async fn execute_command(
+        cmd: &str,
+        policy: &ExecutionPolicy,
+        prepared: Option<CommandPlan>,
+        context: &InvocationContext,
+        binaries: &ResolvedBinaries,
+    ) -> Result<(), ActionError> {
+        let line = match policy.check_command(cmd) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(cmd, error = %e, "Shell command refused by execution policy");
+                return Err(e);
+            }
+        };
+
+        host_spawn_ready(binaries.strategy())?;
         let start = Instant::now();
 
-        tracing::info!(cmd, "Executing shell command");
+        tracing::info!(
+            cmd,
+            strategy = binaries.strategy().as_str(),
+            prewarmed = prepared.is_some(),
+            "Executing shell command"
+        );
 
-        // Use sh -c for shell interpretation (handles pipes, redirects, etc.)
-        let result = Command::new("sh")
-            .args(["-c", cmd])
-            .spawn();
+        // sh -c for shell interpretation (pipes, redirects, etc.), unless the
+        // allowlist handed back the argv to run without a shell
+        let plan = prepared.unwrap_or_else(|| CommandPlan::resolve(cmd, binaries));
+        let result = plan.command(binaries.strategy(), &line, context).spawn();
 
         match result {
             Ok(_child) => {
@@ -273,7 +390,7 @@ impl ActionExecutor {
         Ok(())
     }
 
-    async fn execute_dbus(call: &DBusCall) -> Result<(), ActionError> {
+    async fn execute_dbus(call: &DBusCall, context: &InvocationContext) -> Result<(), ActionError> {
         tracing::info!(
             service = %call.service,
             path = %call.path,
@@ -282,33 +399,8 @@ impl ActionExecutor {
             "Executing D-Bus call"
         );
 
-        // Build dbus-send arguments
-        let mut args = vec![
-            "--session".to_string(),
-            "--print-reply".to_string(),
-            format!("--dest={}", call.service),
-            call.path.clone(),
-            format!("{}.{}", call.interface, call.method),
-        ];
-
-        // Append typed arguments
-        for arg in &call.args {
-            match arg {
-                serde_json::Value::String(s) => args.push(format!("string:{}", s)),
-                serde_json::Value::Bool(b) => args.push(format!("boolean:{}", b)),
-                serde_json::Value::Number(n) => {
-                    if let Some(i) = n.as_i64() {
-                        args.push(format!("int32:{}", i));
-                    } else if let Some(f) = n.as_f64() {
-                        args.push(format!("double:{}", f));
-                    }
-                }
-                _ => {}
-            }
-        }
-
         let result = Command::new("dbus-send")
-            .args(&args)
+            .args(dbus_send_args(call, context))
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
@@ -327,7 +419,12 @@ impl ActionExecutor {
         }
     }
 
-    async fn execute_kwin(script: &str) -> Result<(), ActionError> {
+    async fn execute_kwin(script: &str, policy: &ExecutionPolicy) -> Result<(), ActionError> {
+        if let Err(e) = policy.check_kwin() {
+            tracing::warn!(script, error = %e, "KWin action refused by execution policy");
+            return Err(e);
+        }
+
         tracing::info!(script, "Executing KWin script");
 
         // Use dbus-send to invoke kglobalaccel shortcut
@@ -360,87 +457,233 @@ impl ActionExecutor {
     }
 }
 
+/// A shortcut or command resolved ahead of its execution (see `crate::prewarm`)
+///
+/// Holds everything up to the spawn: for shortcuts the session type, key
+/// codes, backend paths and argv; for commands only the resolved shell.
+/// Preparing never starts a process.
+#[derive(Debug, Clone)]
+pub struct PreparedAction {
+    plan: Plan,
+    cost: Duration,
+}
+
+#[derive(Debug, Clone)]
+enum Plan {
+    Shortcut(ShortcutPlan),
+    Command(CommandPlan),
+}
+
+impl PreparedAction {
+    /// Resolve `action`, looking its binaries up in `binaries`; `None` for
+    /// types that are not pre-warmed
+    pub fn prepare(action: &ActionType, binaries: &ResolvedBinaries) -> Option<Self> {
+        let start = Instant::now();
+        let plan = match action {
+            ActionType::Shortcut(keys) => Plan::Shortcut(ShortcutPlan::resolve(keys, binaries)),
+            ActionType::Command(cmd) => Plan::Command(CommandPlan::resolve(cmd, binaries)),
+            _ => return None,
+        };
+        Some(Self { plan, cost: start.elapsed() })
+    }
+
+    /// Whether this was prepared from `action`
+    pub fn matches(&self, action: &ActionType) -> bool {
+        match (&self.plan, action) {
+            (Plan::Shortcut(plan), ActionType::Shortcut(keys)) => plan.keys == *keys,
+            (Plan::Command(plan), ActionType::Command(cmd)) => plan.cmd == *cmd,
+            _ => false,
+        }
+    }
+
+    /// Time preparing took, which the execution no longer spends
+    pub fn cost(&self) -> Duration {
+        self.cost
+    }
+
+    fn into_shortcut(self) -> Option<ShortcutPlan> {
+        match self.plan {
+            Plan::Shortcut(plan) => Some(plan),
+            Plan::Command(_) => None,
+        }
+    }
+
+    fn into_command(self) -> Option<CommandPlan> {
+        match self.plan {
+            Plan::Command(plan) => Some(plan),
+            Plan::Shortcut(_) => None,
+        }
+    }
+}
+
+/// A shortcut's setup phase, done
+#[derive(Debug, Clone)]
+struct ShortcutPlan {
+    keys: String,
+    wayland: bool,
+    /// ydotool `key` arguments; `None` off Wayland or for unmapped chords
+    ydotool_args: Option<Vec<String>>,
+    ydotool: PathBuf,
+    xdotool: PathBuf,
+}
+
+impl ShortcutPlan {
+    /// On Wayland, X11 input synthesis (xdotool) does not reach native
+    /// Wayland windows. Inject through the kernel uinput device via ydotool,
+    /// which needs evdev key CODES (not keysym names) and is the reliable
+    /// path on KDE Plasma Wayland. Unmapped chords fall through to xdotool.
+    fn resolve(keys: &str, binaries: &ResolvedBinaries) -> Self {
+        let wayland = is_wayland_session();
+        let ydotool_args = if wayland {
+            ActionExecutor::shortcut_to_evdev_codes(keys).map(|codes| ydotool_key_args(&codes))
+        } else {
+            None
+        };
+        Self {
+            keys: keys.to_string(),
+            wayland,
+            ydotool_args,
+            ydotool: binaries.resolve("ydotool"),
+            xdotool: binaries.resolve("xdotool"),
+        }
+    }
+}
+
+/// A command resolved as far as it may be before it runs
+#[derive(Debug, Clone)]
+struct CommandPlan {
+    cmd: String,
+    shell: PathBuf,
+}
+
+impl CommandPlan {
+    fn resolve(cmd: &str, binaries: &ResolvedBinaries) -> Self {
+        Self {
+            cmd: cmd.to_string(),
+            shell: binaries.resolve("sh"),
+        }
+    }
+
+    /// `sh -c cmd`, or the allowlisted argv run directly, with the
+    /// invocation in its environment. The shell command text is passed as
+    /// written; in an argv, each word has its `$JUH_*` variables substituted
+    fn command(&self, strategy: &ExecutionStrategy, line: &CommandLine, context: &InvocationContext) -> Command {
+        match line {
+            CommandLine::Argv(argv) if !argv.is_empty() => {
+                let argv: Vec<String> = argv.iter().map(|word| context.substitute(word)).collect();
+                let mut command = strategy.command_with_env(&argv[0], context.variables());
+                command.args(&argv[1..]);
+                command
+            }
+            _ => {
+                let mut command = strategy.command_with_env(&self.shell, context.variables());
+                command.args(["-c", &self.cmd]);
+                command
+            }
+        }
+    }
+}
+
+/// `dbus-send` arguments for `call`, with `$JUH_*` substituted in string
+/// arguments
+fn dbus_send_args(call: &DBusCall, context: &InvocationContext) -> Vec<String> {
+    let mut args = vec![
+        "--session".to_string(),
+        "--print-reply".to_string(),
+        format!("--dest={}", call.service),
+        call.path.clone(),
+        format!("{}.{}", call.interface, call.method),
+    ];
+
+    // Append typed arguments
+    for arg in &call.args {
+        match arg {
+            serde_json::Value::String(s) => args.push(format!("string:{}", context.substitute(s))),
+            serde_json::Value::Bool(b) => args.push(format!("boolean:{}", b)),
+            serde_json::Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    args.push(format!("int32:{}", i));
+                } else if let Some(f) = n.as_f64() {
+                    args.push(format!("double:{}", f));
+                }
+            }
+            _ => {}
+        }
+    }
+    args
+}
+
+/// ydotool `key` arguments: press every code in order, then release in reverse
+fn ydotool_key_args(codes: &[u16]) -> Vec<String> {
+    let mut args: Vec<String> = vec!["key".to_string()];
+    args.extend(codes.iter().map(|c| format!("{}:1", c)));
+    args.extend(codes.iter().rev().map(|c| format!("{}:0", c)));
+    args
+}
+
+/// Whether this is a Wayland session (selects ydotool and wl-clipboard)
+pub fn is_wayland_session() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+        || std::env::var("XDG_SESSION_TYPE")
+            .map(|s| s.eq_ignore_ascii_case("wayland"))
+            .unwrap_or(false)
+}
+
+/// Which tool synthesizes shortcut key presses in this session, for
+/// `DumpState()`
+///
+/// ydotool on Wayland (xdotool for chords it cannot map), xdotool on X11.
+pub fn key_synthesis_backend(strategy: &ExecutionStrategy) -> serde_json::Value {
+    let wayland = is_wayland_session();
+    serde_json::json!({
+        "session": if wayland { "wayland" } else { "x11" },
+        "primary": if wayland { "ydotool" } else { "xdotool" },
+        "ydotool": strategy.find("ydotool").unwrap_or_else(|| "ydotool".into()),
+        "xdotool": strategy.find("xdotool").unwrap_or_else(|| "xdotool".into()),
+    })
+}
+
 /// Action error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum ActionError {
     /// Action execution failed with reason
+    #[error("Execution failed: {0}")]
     ExecutionFailed(String),
     /// Action timed out
+    #[error("Action timed out")]
     Timeout,
     /// Invalid action configuration
+    #[error("Invalid action configuration")]
     InvalidAction,
     /// Shell command execution failed
+    #[error("Shell execution failed: {0}")]
     ShellExecution(String),
+    /// Command rejected by the allowlist execution policy
+    #[error("Command not allowed: {0}")]
+    CommandNotAllowed(String),
+    /// profiles.json changed since it was last approved (confirm policy)
+    #[error("Profiles changed since last approval; call ApproveProfile")]
+    ProfileNotApproved,
+    /// `execution.max_commands_per_minute` reached; the next start is
+    /// allowed after this many milliseconds
+    #[error("Command rate limit reached; try again in {0}ms")]
+    RateLimited(u64),
+    /// Sandboxed and `flatpak-spawn --host` is not permitted
+    #[error(
+        "Cannot run programs outside the Flatpak sandbox ({0}); grant the app {permission}",
+        permission = crate::sandbox::HOST_PERMISSION
+    )]
+    HostSpawnDenied(String),
 }
 
-impl std::fmt::Display for ActionError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ActionError::ExecutionFailed(msg) => write!(f, "Execution failed: {}", msg),
-            ActionError::Timeout => write!(f, "Action timed out"),
-            ActionError::InvalidAction => write!(f, "Invalid action configuration"),
-            ActionError::ShellExecution(msg) => write!(f, "Shell execution failed: {}", msg),
-        }
+/// `HostSpawnDenied` when programs would go through a denied `flatpak-spawn`
+fn host_spawn_ready(strategy: &ExecutionStrategy) -> Result<(), ActionError> {
+    match strategy.denied() {
+        Some(reason) => Err(ActionError::HostSpawnDenied(reason.to_string())),
+        None => Ok(()),
     }
 }
 
-impl std::error::Error for ActionError {}
-
-/// Default actions for the 8 slices (Story 2.6)
-/// N=0, NE=1, E=2, SE=3, S=4, SW=5, W=6, NW=7
-pub fn get_default_actions() -> [Action; 8] {
-    [
-        // N (0): Copy
-        Action {
-            action_type: ActionType::Shortcut("ctrl+c".to_string()),
-            label: Some("Copy".to_string()),
-            icon: Some("📋".to_string()),
-        },
-        // NE (1): Paste
-        Action {
-            action_type: ActionType::Shortcut("ctrl+v".to_string()),
-            label: Some("Paste".to_string()),
-            icon: Some("📄".to_string()),
-        },
-        // E (2): Undo
-        Action {
-            action_type: ActionType::Shortcut("ctrl+z".to_string()),
-            label: Some("Undo".to_string()),
-            icon: Some("↩️".to_string()),
-        },
-        // SE (3): Redo
-        Action {
-            action_type: ActionType::Shortcut("ctrl+shift+z".to_string()),
-            label: Some("Redo".to_string()),
-            icon: Some("↪️".to_string()),
-        },
-        // S (4): Select All
-        Action {
-            action_type: ActionType::Shortcut("ctrl+a".to_string()),
-            label: Some("Select All".to_string()),
-            icon: Some("🔲".to_string()),
-        },
-        // SW (5): Cut
-        Action {
-            action_type: ActionType::Shortcut("ctrl+x".to_string()),
-            label: Some("Cut".to_string()),
-            icon: Some("✂️".to_string()),
-        },
-        // W (6): Save
-        Action {
-            action_type: ActionType::Shortcut("ctrl+s".to_string()),
-            label: Some("Save".to_string()),
-            icon: Some("💾".to_string()),
-        },
-        // NW (7): Close Tab
-        Action {
-            action_type: ActionType::Shortcut("ctrl+w".to_string()),
-            label: Some("Close".to_string()),
-            icon: Some("❌".to_string()),
-        },
-    ]
-}
-
 // ============================================================================
 // Button Action Dispatch
 // ============================================================================
@@ -485,14 +728,14 @@ fn zoom_shortcut(zoom_in: bool) -> &'static str {
 /// Execute a button action directly.
 /// Returns Ok(true) if the action was handled, Ok(false) if it should use the
 /// radial menu flow (caller handles ShowMenu/HideMenu).
-pub async fn execute_button_action(action: ButtonAction) -> Result<bool, ActionError> {
+pub async fn execute_button_action(action: ButtonAction, handles: &DaemonHandles) -> Result<bool, ActionError> {
     match action {
         ButtonAction::RadialMenu => {
             // Caller handles the radial menu show/hide flow
             Ok(false)
         }
         ButtonAction::VirtualDesktops => {
-            execute_virtual_desktops().await?;
+            execute_virtual_desktops(handles).await?;
             Ok(true)
         }
         ButtonAction::None => Ok(true),
@@ -513,19 +756,22 @@ pub async fn execute_button_action(action: ButtonAction) -> Result<bool, ActionE
         | ButtonAction::LockScreen
         | ButtonAction::Calculator => {
             if let Some(preset) = crate::presets::Preset::from_button_action(action) {
-                crate::presets::execute_preset(preset).await?;
+                crate::presets::execute_preset(preset, handles).await?;
             }
             Ok(true)
         }
         // Zoom uses layout-independent numpad Ctrl+/- (see zoom_shortcut).
         ButtonAction::ZoomIn | ButtonAction::ZoomOut => {
             let keys = zoom_shortcut(matches!(action, ButtonAction::ZoomIn));
-            ActionExecutor::execute(&Action {
+            let act = Action {
                 action_type: ActionType::Shortcut(keys.to_string()),
                 label: None,
                 icon: None,
-            })
-            .await?;
+                description: None,
+                require_confirmation: false,
+                cooldown_ms: 0,
+            };
+            ActionExecutor::execute(&act, handles).await?;
             Ok(true)
         }
         // All other actions map to keyboard shortcuts
@@ -536,8 +782,11 @@ pub async fn execute_button_action(action: ButtonAction) -> Result<bool, ActionE
                     action_type: ActionType::Shortcut(keys.to_string()),
                     label: None,
                     icon: None,
+                    description: None,
+                    require_confirmation: false,
+                    cooldown_ms: 0,
                 };
-                ActionExecutor::execute(&act).await?;
+                ActionExecutor::execute(&act, handles).await?;
             }
             Ok(true)
         }
@@ -550,23 +799,25 @@ pub async fn execute_button_action(action: ButtonAction) -> Result<bool, ActionE
 /// X11 is mouse buttons 6 (left) and 7 (right); xdotool synthesizes these
 /// directly, with ydotool as a Wayland fallback (consistent with the keyboard
 /// shortcut path). Non-blocking: each click is spawned, not awaited.
-pub async fn execute_horizontal_scroll(clicks: i32) -> Result<(), ActionError> {
+pub async fn execute_horizontal_scroll(clicks: i32, handles: &DaemonHandles) -> Result<(), ActionError> {
     if clicks == 0 {
         return Ok(());
     }
     // Button 6 = scroll left, 7 = scroll right.
     let button = if clicks > 0 { "7" } else { "6" };
     let count = clicks.unsigned_abs().min(16);
+    let strategy = handles.binaries.strategy();
+    host_spawn_ready(strategy)?;
 
     for _ in 0..count {
-        let spawned = Command::new("xdotool")
+        let spawned = strategy.command("xdotool")
             .args(["click", button])
             .spawn();
         if let Err(e) = spawned {
             tracing::debug!("xdotool horizontal scroll failed: {}, trying ydotool", e);
             // ydotool click button codes: 0x06 = left scroll, 0x07 = right scroll.
             let yd_button = if clicks > 0 { "0x07" } else { "0x06" };
-            if let Err(e2) = Command::new("ydotool").args(["click", yd_button]).spawn() {
+            if let Err(e2) = strategy.command("ydotool").args(["click", yd_button]).spawn() {
                 tracing::error!("Both xdotool and ydotool horizontal scroll failed: {}", e2);
                 return Err(ActionError::ExecutionFailed(format!(
                     "Horizontal scroll failed: {}",
@@ -579,7 +830,7 @@ pub async fn execute_horizontal_scroll(clicks: i32) -> Result<(), ActionError> {
 }
 
 /// Execute virtual desktops overview toggle (desktop-specific)
-async fn execute_virtual_desktops() -> Result<(), ActionError> {
+async fn execute_virtual_desktops(handles: &DaemonHandles) -> Result<(), ActionError> {
     let desktop = detect_desktop();
     tracing::info!(desktop, "Triggering virtual desktops overview");
 
@@ -654,7 +905,7 @@ async fn execute_virtual_desktops() -> Result<(), ActionError> {
         }
         "kde" => {
             // Toggle KDE Overview via kglobalaccel shortcut invocation
-            ActionExecutor::execute_kwin("Overview").await
+            ActionExecutor::execute_kwin("Overview", &ExecutionPolicy::unrestricted()).await
         }
         "hyprland" => {
             // Try Hyprspace overview plugin first, fall back to workspace switch
@@ -673,8 +924,11 @@ async fn execute_virtual_desktops() -> Result<(), ActionError> {
                         action_type: ActionType::Shortcut("super".to_string()),
                         label: None,
                         icon: None,
+                        description: None,
+                        require_confirmation: false,
+                        cooldown_ms: 0,
                     };
-                    ActionExecutor::execute(&act).await
+                    ActionExecutor::execute(&act, handles).await
                 }
             }
         }
@@ -684,8 +938,11 @@ async fn execute_virtual_desktops() -> Result<(), ActionError> {
                 action_type: ActionType::Shortcut("super".to_string()),
                 label: None,
                 icon: None,
+                description: None,
+                require_confirmation: false,
+                cooldown_ms: 0,
             };
-            ActionExecutor::execute(&act).await
+            ActionExecutor::execute(&act, handles).await
         }
         _ => {
             tracing::warn!(desktop, "Virtual desktops not supported on this desktop environment");
@@ -721,95 +978,119 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_action_serialization() {
-        let action = Action {
-            action_type: ActionType::Shortcut("Ctrl+C".to_string()),
-            label: Some("Copy".to_string()),
-            icon: Some("📋".to_string()),
-        };
+    fn test_action_error_display() {
+        let err = ActionError::ExecutionFailed("test error".to_string());
+        assert!(format!("{}", err).contains("test error"));
 
-        let json = serde_json::to_string(&action).unwrap();
-        assert!(json.contains("shortcut"));
-        assert!(json.contains("Ctrl+C"));
-    }
+        let err = ActionError::Timeout;
+        assert!(format!("{}", err).contains("timed out"));
 
-    #[test]
-    fn test_action_deserialization() {
-        let json = r#"{"type":"shortcut","value":"ctrl+c","label":"Copy"}"#;
-        let action: Action = serde_json::from_str(json).unwrap();
+        let err = ActionError::ShellExecution("command not found".to_string());
+        assert!(format!("{}", err).contains("Shell execution"));
 
-        match action.action_type {
-            ActionType::Shortcut(keys) => assert_eq!(keys, "ctrl+c"),
-            _ => panic!("Expected Shortcut action"),
-        }
-        assert_eq!(action.label, Some("Copy".to_string()));
-    }
+        let err = ActionError::CommandNotAllowed("'rm' is not allowlisted".to_string());
+        assert!(format!("{}", err).contains("not allowed"));
 
-    #[test]
-    fn test_command_action() {
-        let action = Action {
-            action_type: ActionType::Command("konsole".to_string()),
-            label: Some("Terminal".to_string()),
-            icon: None,
-        };
+        let err = ActionError::ProfileNotApproved;
+        assert!(format!("{}", err).contains("ApproveProfile"));
 
-        let json = serde_json::to_string(&action).unwrap();
-        assert!(json.contains("command"));
-        assert!(json.contains("konsole"));
+        let err = ActionError::RateLimited(1500);
+        assert!(format!("{}", err).contains("1500ms"));
+
+        let err = ActionError::HostSpawnDenied("AccessDenied".to_string());
+        assert!(format!("{}", err).contains("--talk-name=org.freedesktop.Flatpak"));
     }
 
-    #[test]
-    fn test_none_action() {
+    #[tokio::test]
+    async fn test_execute_none_action() {
         let action = Action {
             action_type: ActionType::None,
             label: None,
             icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
         };
 
-        let json = serde_json::to_string(&action).unwrap();
-        assert!(json.contains("none"));
+        let result = ActionExecutor::execute(&action, &DaemonHandles::default()).await;
+        assert!(result.is_ok());
     }
 
-    #[test]
-    fn test_default_actions() {
-        let actions = get_default_actions();
-
-        assert_eq!(actions.len(), 8);
-
-        // Verify N=Copy
-        match &actions[0].action_type {
-            ActionType::Shortcut(keys) => assert_eq!(keys, "ctrl+c"),
-            _ => panic!("Expected Shortcut"),
-        }
-
-        // Verify S=Select All
-        match &actions[4].action_type {
-            ActionType::Shortcut(keys) => assert_eq!(keys, "ctrl+a"),
-            _ => panic!("Expected Shortcut"),
+    fn hostile_context() -> InvocationContext {
+        InvocationContext {
+            window_class: Some("x\"; touch \"$MARKER\"; echo \"$(touch $MARKER)`touch $MARKER`".to_string()),
+            profile: Some("* ~ $HOME".to_string()),
+            ..InvocationContext::now().at((12, -3)).with_slice(4)
         }
     }
 
     #[test]
-    fn test_action_error_display() {
-        let err = ActionError::ExecutionFailed("test error".to_string());
-        assert!(format!("{}", err).contains("test error"));
+    fn test_command_sees_invocation_as_environment() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let marker = dir.path().join("injected");
+        let cmd = r#"printf '%s|%s|%s|%s' "$JUH_CURSOR_X" "$JUH_SLICE" "$JUH_WINDOW_CLASS" "$JUH_PROFILE""#;
+        let output = CommandPlan::resolve(cmd, &ResolvedBinaries::default())
+            .command(&ExecutionStrategy::Direct, &CommandLine::Shell, &hostile_context())
+            .env("MARKER", &marker)
+            .output()
+            .unwrap();
+        let context = hostile_context();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!("12|4|{}|* ~ $HOME", context.window_class.unwrap())
+        );
+        assert!(!marker.exists(), "a window class ran as shell code");
+
+        // Unquoted, the value is split into words but still never evaluated
+        let output = CommandPlan::resolve("echo $JUH_WINDOW_CLASS", &ResolvedBinaries::default())
+            .command(&ExecutionStrategy::Direct, &CommandLine::Shell, &hostile_context())
+            .env("MARKER", &marker)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(!marker.exists(), "an unquoted window class ran as shell code");
+    }
 
-        let err = ActionError::Timeout;
-        assert!(format!("{}", err).contains("timed out"));
+    #[test]
+    fn test_allowlisted_argv_runs_without_shell() {
+        let line = CommandLine::Argv(vec!["printf".into(), "%s|%s".into(), "a  b".into(), "*".into()]);
+        let output = CommandPlan::resolve("printf '%s|%s' 'a  b' '*'", &ResolvedBinaries::default())
+            .command(&ExecutionStrategy::Direct, &line, &hostile_context())
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "a  b|*");
+    }
 
-        let err = ActionError::ShellExecution("command not found".to_string());
-        assert!(format!("{}", err).contains("Shell execution"));
+    #[test]
+    fn test_allowlisted_argv_substitutes_invocation() {
+        let line = CommandLine::Argv(vec!["printf".into(), "%s|%s".into(), "$JUH_PROFILE".into(), "${JUH_SLICE}".into()]);
+        let output = CommandPlan::resolve("printf '%s|%s' $JUH_PROFILE ${JUH_SLICE}", &ResolvedBinaries::default())
+            .command(&ExecutionStrategy::Direct, &line, &hostile_context())
+            .output()
+            .unwrap();
+        // One word each, never globbed or expanded again
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "* ~ $HOME|4");
     }
 
-    #[tokio::test]
-    async fn test_execute_none_action() {
-        let action = Action {
-            action_type: ActionType::None,
-            label: None,
-            icon: None,
+    #[test]
+    fn test_dbus_string_args_are_substituted_literally() {
+        let call = DBusCall {
+            service: "org.example.Shot".to_string(),
+            path: "/Shot".to_string(),
+            interface: "org.example.Shot".to_string(),
+            method: "Region".to_string(),
+            args: vec![
+                serde_json::json!("$JUH_CURSOR_X,${JUH_CURSOR_Y}"),
+                serde_json::json!("$JUH_WINDOW_CLASS"),
+                serde_json::json!(7),
+            ],
         };
-
-        let result = ActionExecutor::execute(&action).await;
-        assert!(result.is_ok());
+        let context = hostile_context();
+        let args = dbus_send_args(&call, &context);
+        assert_eq!(args[5], "string:12,-3");
+        // One argument, exactly the window class
+        assert_eq!(args[6], format!("string:{}", context.window_class.unwrap()));
+        assert_eq!(args[7], "int32:7");
+        assert_eq!(args.len(), 8);
     }
 }
@@ -0,0 +1,295 @@
+//! One-off radial menus for other tools (`ShowAdHocMenu`)
+//!
+//! A D-Bus caller passes a menu definition as JSON:
+//!
+//! ```json
+//! {"slices": [{"type": "command", "value": "notify-send hi", "label": "Hi", "icon": "dialog-information"}, null]}
+//! ```
+//!
+//! Slots are profile slots (0 = N, clockwise; at most eight, missing ones are
+//! empty) and only `command`, `dbus` and `none` actions are accepted. The
+//! daemon shows the menu at the cursor with the current theme, sending the
+//! slices in the `adhoc` block of the menu payload, and answers the call with
+//! the slot the user picked once the overlay reports the close
+//! (`ReportMenuClosed`), or -1 when it was cancelled. Only one ad-hoc menu is
+//! open at a time; one still open after [`ADHOC_TIMEOUT`] is dismissed, so a
+//! caller that went away cannot leave it on screen.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::sync::oneshot;
+
+use crate::actions::{Action, ActionType};
+use crate::menu_payload::OverlaySlice;
+use crate::profiles::validate_icon_reference;
+
+/// Slots in the ring
+pub const MAX_SLICES: usize = 8;
+
+/// Largest accepted definition, in bytes of JSON
+pub const MAX_DEFINITION_BYTES: usize = 16 * 1024;
+
+/// An ad-hoc menu nobody picked from is dismissed after this long
+pub const ADHOC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reply for a cancelled, dismissed or empty-slot close
+pub const CANCELLED: i32 = -1;
+
+/// Why a definition was refused or could not be shown
+#[derive(Debug, thiserror::Error)]
+pub enum AdHocError {
+    /// Definition larger than [`MAX_DEFINITION_BYTES`]
+    #[error("Menu definition is {0} bytes; the limit is {limit}", limit = MAX_DEFINITION_BYTES)]
+    TooLarge(usize),
+    /// Not valid JSON for the schema
+    #[error("Invalid menu definition: {0}")]
+    Parse(serde_json::Error),
+    /// More than [`MAX_SLICES`] slices
+    #[error("Menu definition has {0} slices; the ring has {slots}", slots = MAX_SLICES)]
+    TooManySlices(usize),
+    /// Every slot is empty
+    #[error("Menu definition has no actions")]
+    Empty,
+    /// A slice uses an action type ad-hoc menus cannot run
+    #[error("slices[{slice}]: '{kind}' actions are not allowed in ad-hoc menus (use command, dbus or none)")]
+    ActionNotAllowed { slice: usize, kind: &'static str },
+    /// A slice's action or icon failed validation
+    #[error("slices[{slice}]: {reason}")]
+    InvalidSlice { slice: usize, reason: String },
+    /// The menu payload would exceed its size cap
+    #[error("Menu payload exceeds the size cap")]
+    PayloadTooLarge,
+    /// Another ad-hoc menu is open
+    #[error("Another menu is already open")]
+    Busy,
+}
+
+/// A validated ad-hoc menu definition
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdHocMenu {
+    slices: Vec<Option<Action>>,
+}
+
+impl AdHocMenu {
+    /// Parse and validate a definition
+    pub fn parse(json: &str) -> Result<Self, AdHocError> {
+        if json.len() > MAX_DEFINITION_BYTES {
+            return Err(AdHocError::TooLarge(json.len()));
+        }
+        let mut menu: AdHocMenu = serde_json::from_str(json).map_err(AdHocError::Parse)?;
+        if menu.slices.len() > MAX_SLICES {
+            return Err(AdHocError::TooManySlices(menu.slices.len()));
+        }
+        for (slice, action) in menu.slices.iter().enumerate() {
+            if let Some(action) = action {
+                check_slice(slice, action)?;
+            }
+        }
+        if !menu.slices.iter().flatten().any(|a| !matches!(a.action_type, ActionType::None)) {
+            return Err(AdHocError::Empty);
+        }
+        menu.slices.resize(MAX_SLICES, None);
+        Ok(menu)
+    }
+
+    /// Action in a profile slot; `None` for empty slots and `CANCELLED`
+    pub fn action(&self, slot: i32) -> Option<&Action> {
+        let action = self.slices.get(usize::try_from(slot).ok()?)?.as_ref()?;
+        (!matches!(action.action_type, ActionType::None)).then_some(action)
+    }
+
+    /// What the overlay draws, one entry per slot; labels wider than
+    /// `label_budget` cells also get a shortened form
+    pub fn overlay_slices(&self, label_budget: Option<usize>) -> Vec<Option<OverlaySlice>> {
+        self.slices
+            .iter()
+            .map(|action| {
+                action.as_ref().map(|a| {
+                    let label = a.label.as_ref().map(|l| l.current().to_string()).unwrap_or_default();
+                    OverlaySlice {
+                        short_label: label_budget.and_then(|budget| crate::label::ellipsize(&label, budget)),
+                        label,
+                        icon: a.icon.clone(),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Slice descriptions for hover help, one per slot
+    pub fn descriptions(&self) -> Vec<Option<String>> {
+        self.slices
+            .iter()
+            .map(|action| action.as_ref().and_then(|a| a.description.clone()))
+            .collect()
+    }
+
+    /// Actions by slot, for pre-warming
+    pub fn actions(&self) -> Vec<Option<Action>> {
+        self.slices.clone()
+    }
+}
+
+/// Same checks profile loading applies to a slice, plus the action allowlist
+fn check_slice(slice: usize, action: &Action) -> Result<(), AdHocError> {
+    let kind = match &action.action_type {
+        ActionType::Command(cmd) if cmd.trim().is_empty() => {
+            return Err(AdHocError::InvalidSlice { slice, reason: "empty command".to_string() });
+        }
+        ActionType::Command(_) | ActionType::DBus(_) | ActionType::None => None,
+        ActionType::Shortcut(_) => Some("shortcut"),
+        ActionType::KWin(_) => Some("kwin"),
+        ActionType::PastePlain => Some("paste_plain"),
+        ActionType::ProfileSwitch(_) => Some("profile_switch"),
+        ActionType::WheelAxis { .. } => Some("wheel_axis"),
+        ActionType::Media(_) => Some("media"),
+        ActionType::Volume(_) => Some("volume"),
+        _ => Some("unknown"),
+    };
+    if let Some(kind) = kind {
+        return Err(AdHocError::ActionNotAllowed { slice, kind });
+    }
+    if let Some(ref icon) = action.icon {
+        if !validate_icon_reference(icon) {
+            return Err(AdHocError::InvalidSlice { slice, reason: format!("invalid icon '{}'", icon) });
+        }
+    }
+    Ok(())
+}
+
+/// How an ad-hoc menu ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdHocOutcome {
+    /// The overlay reported the close with this slot (-1 = cancelled)
+    Closed(i32),
+    /// Nobody picked within [`ADHOC_TIMEOUT`]
+    TimedOut,
+}
+
+#[derive(Debug)]
+struct Pending {
+    id: u64,
+    tx: oneshot::Sender<AdHocOutcome>,
+}
+
+/// The open ad-hoc menu, if any
+///
+/// Held by the D-Bus service: `ShowAdHocMenu` begins a menu and waits for
+/// its outcome, `ReportMenuClosed` completes it.
+#[derive(Debug, Default)]
+pub struct AdHocGate {
+    pending: Arc<Mutex<Option<Pending>>>,
+    next_id: AtomicU64,
+}
+
+impl AdHocGate {
+    /// Claim the gate; `Busy` while another ad-hoc menu is open
+    ///
+    /// The returned receiver resolves with the outcome. After `timeout` it
+    /// resolves with `TimedOut` (a timer thread, since D-Bus methods do not
+    /// run on the tokio runtime).
+    pub fn begin(&self, timeout: Duration) -> Result<oneshot::Receiver<AdHocOutcome>, AdHocError> {
+        let mut pending = self.pending.lock().map_err(|_| AdHocError::Busy)?;
+        if pending.as_ref().is_some_and(|p| !p.tx.is_closed()) {
+            return Err(AdHocError::Busy);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        *pending = Some(Pending { id, tx });
+
+        let expiring = Arc::clone(&self.pending);
+        std::thread::spawn(move || {
+            std::thread::sleep(timeout);
+            if let Ok(mut pending) = expiring.lock() {
+                if pending.as_ref().is_some_and(|p| p.id == id) {
+                    if let Some(p) = pending.take() {
+                        let _ = p.tx.send(AdHocOutcome::TimedOut);
+                    }
+                }
+            }
+        });
+        Ok(rx)
+    }
+
+    /// The overlay closed the menu; `true` when an ad-hoc menu was open
+    pub fn complete(&self, slot: i32) -> bool {
+        let Some(pending) = self.pending.lock().ok().and_then(|mut p| p.take()) else {
+            return false;
+        };
+        pending.tx.send(AdHocOutcome::Closed(slot)).is_ok()
+    }
+
+    /// Whether an ad-hoc menu is open
+    pub fn is_open(&self) -> bool {
+        self.pending
+            .lock()
+            .map(|p| p.as_ref().is_some_and(|p| !p.tx.is_closed()))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_validates_definition() {
+        let menu = AdHocMenu::parse(
+            r#"{"slices": [
+                {"type": "command", "value": "notify-send hi", "label": "Hi", "icon": "dialog-information"},
+                null,
+                {"type": "dbus", "value": {"service": "org.example", "path": "/", "interface": "org.example", "method": "Go"}}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(menu.action(0).is_some());
+        assert!(menu.action(1).is_none());
+        assert!(menu.action(2).is_some());
+        assert!(menu.action(7).is_none());
+        assert!(menu.action(CANCELLED).is_none());
+        let slices = menu.overlay_slices(Some(crate::label::LABEL_BUDGET_CELLS));
+        assert_eq!(slices.len(), MAX_SLICES);
+        assert_eq!(
+            slices[0],
+            Some(OverlaySlice { label: "Hi".to_string(), short_label: None, icon: Some("dialog-information".to_string()) })
+        );
+        assert_eq!(menu.overlay_slices(Some(1))[0].as_ref().unwrap().short_label.as_deref(), Some("…"));
+
+        let shortcut = r#"{"slices": [{"type": "shortcut", "value": "ctrl+q"}]}"#;
+        assert!(matches!(
+            AdHocMenu::parse(shortcut),
+            Err(AdHocError::ActionNotAllowed { slice: 0, kind: "shortcut" })
+        ));
+        let nine = format!(r#"{{"slices": [{}]}}"#, [r#"{"type": "command", "value": "true"}"#; 9].join(","));
+        assert!(matches!(AdHocMenu::parse(&nine), Err(AdHocError::TooManySlices(9))));
+        assert!(matches!(AdHocMenu::parse(r#"{"slices": [null, {"type": "none"}]}"#), Err(AdHocError::Empty)));
+        let bad_icon = r#"{"slices": [{"type": "command", "value": "true", "icon": "../../x y"}]}"#;
+        assert!(matches!(AdHocMenu::parse(bad_icon), Err(AdHocError::InvalidSlice { slice: 0, .. })));
+        let huge = format!(r#"{{"slices": [], "pad": "{}"}}"#, "x".repeat(MAX_DEFINITION_BYTES));
+        assert!(matches!(AdHocMenu::parse(&huge), Err(AdHocError::TooLarge(_))));
+    }
+
+    #[tokio::test]
+    async fn test_gate_allows_one_menu_and_times_out() {
+        let gate = AdHocGate::default();
+        assert!(!gate.complete(0));
+        let rx = gate.begin(ADHOC_TIMEOUT).unwrap();
+        assert!(gate.is_open());
+        assert!(matches!(gate.begin(ADHOC_TIMEOUT), Err(AdHocError::Busy)));
+        assert!(gate.complete(3));
+        assert_eq!(rx.await.unwrap(), AdHocOutcome::Closed(3));
+        assert!(!gate.is_open());
+
+        let rx = gate.begin(Duration::from_millis(20)).unwrap();
+        assert_eq!(rx.await.unwrap(), AdHocOutcome::TimedOut);
+        // A late close after the timeout completes nothing
+        assert!(!gate.complete(1));
+        // A caller that went away frees the gate
+        drop(gate.begin(ADHOC_TIMEOUT).unwrap());
+        assert!(gate.begin(ADHOC_TIMEOUT).is_ok());
+    }
+}
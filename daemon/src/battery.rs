@@ -4,23 +4,32 @@
 //!
 //! SPDX-License-Identifier: GPL-3.0
 
-use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::RwLock;
 
+use crate::conflicts::{Conflict, ConflictTool, ConflictsHandle};
+use crate::hidpp::request::{self, ErrorCode, Request, RequestError, SwIdCounter, Transport, REQUEST_TIMEOUT};
+use crate::hidpp::trace::HidppTrace;
+use crate::hidpp::HapticError;
+
 /// HID++ feature IDs
 const FEATURE_BATTERY_STATUS: u16 = 0x1000;
 const FEATURE_UNIFIED_BATTERY: u16 = 0x1004;
 
-/// HID++ report types
-const HIDPP_SHORT: u8 = 0x10;
-const HIDPP_LONG: u8 = 0x11;
+/// How long samples stay in the in-memory history
+pub const HISTORY_RETENTION_SECS: u64 = 24 * 60 * 60;
+
+/// Cap on kept samples, so bursts of live notifications cannot grow it
+/// unbounded (steady polling adds ~1440 a day)
+const HISTORY_MAX_SAMPLES: usize = 2048;
 
-/// Software ID for our requests
-const SOFTWARE_ID: u8 = 0x01;
+/// Default `battery.unavailable_after_timeouts`
+pub const UNAVAILABLE_AFTER_TIMEOUTS: u32 = 3;
 
 /// Battery state shared across threads
 #[derive(Debug, Clone, Default)]
@@ -29,10 +38,347 @@ pub struct BatteryState {
     pub percentage: u8,
     /// Whether the device is charging
     pub charging: bool,
+    /// Whether charging has finished (still on the charger)
+    pub charge_complete: bool,
     /// Whether battery info is available
     pub available: bool,
-    /// Last error message if any
-    pub error: Option<String>,
+    /// Why the last query failed, cleared by the next reading
+    pub error: Option<BatteryFailure>,
+    /// Samples from the last 24 hours (`GetBatteryHistory`)
+    pub history: BatteryHistory,
+    /// Timeouts in a row since the last reading (see [`BatteryState::fail`])
+    pub timeouts: u32,
+    /// Programs found competing for the device when a query last failed
+    pub conflicts: Vec<Conflict>,
+}
+
+impl BatteryState {
+    /// Whether logid was among the competing programs (the old flag)
+    pub fn logid_active(&self) -> bool {
+        self.conflicts.iter().any(|c| c.tool == ConflictTool::Logid)
+    }
+
+    /// Record a successful battery query or live notification
+    pub fn apply(&mut self, reading: BatteryReading) {
+        self.percentage = reading.percentage;
+        self.charging = reading.charging;
+        self.charge_complete = reading.charge_complete;
+        self.available = true;
+        self.error = None;
+        self.timeouts = 0;
+        self.history.record(BatterySample {
+            timestamp: unix_now(),
+            percentage: reading.percentage,
+            charging: reading.charging,
+        });
+    }
+
+    /// Record a failed query
+    ///
+    /// A timeout only marks the battery unavailable once `unavailable_after`
+    /// have happened in a row: a napping mouse misses a poll or two and the
+    /// widget should not flicker. Any other failure counts at once.
+    pub fn fail(&mut self, kind: BatteryErrorKind, message: impl Into<String>, unavailable_after: u32) {
+        self.timeouts = match kind {
+            BatteryErrorKind::Timeout => self.timeouts.saturating_add(1),
+            _ => 0,
+        };
+        if kind != BatteryErrorKind::Timeout || self.timeouts >= unavailable_after.max(1) {
+            self.available = false;
+        }
+        self.error = Some(BatteryFailure {
+            kind,
+            message: message.into(),
+        });
+    }
+}
+
+/// What the last battery query ran into
+///
+/// Lets the settings app pick a hint: a udev rule for `PermissionDenied`,
+/// "move the mouse" for `Timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryErrorKind {
+    /// The hidraw node is not accessible to this user
+    PermissionDenied,
+    /// No device to ask: unplugged, on another host, or without a battery feature
+    DeviceNotFound,
+    /// No reply, usually because the mouse is asleep
+    Timeout,
+    /// logid, Solaar, ratbagd or another program is competing for the device
+    Conflict,
+    /// The device answered with an error or an unreadable reply
+    Protocol,
+}
+
+impl BatteryErrorKind {
+    /// Name used on D-Bus (`GetBatteryError`)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PermissionDenied => "permission_denied",
+            Self::DeviceNotFound => "device_not_found",
+            Self::Timeout => "timeout",
+            Self::Conflict => "conflict",
+            Self::Protocol => "protocol",
+        }
+    }
+
+    /// Blame another program for failures its competition causes, if
+    /// `conflict_found`
+    ///
+    /// The check only runs for timeouts and protocol errors.
+    pub fn or_conflict(self, conflict_found: impl FnOnce() -> bool) -> Self {
+        match self {
+            Self::Timeout | Self::Protocol => {
+                if conflict_found() {
+                    Self::Conflict
+                } else {
+                    self
+                }
+            }
+            kind => kind,
+        }
+    }
+
+    fn of_io(e: &std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            _ => Self::DeviceNotFound,
+        }
+    }
+}
+
+impl From<&BatteryError> for BatteryErrorKind {
+    fn from(e: &BatteryError) -> Self {
+        match e {
+            BatteryError::DeviceNotFound | BatteryError::FeatureNotSupported => Self::DeviceNotFound,
+            BatteryError::PermissionDenied => Self::PermissionDenied,
+            BatteryError::IoError(e) => Self::of_io(e),
+            BatteryError::ProtocolError(_) => Self::Protocol,
+            BatteryError::Timeout => Self::Timeout,
+        }
+    }
+}
+
+/// The shared poller queries through the haptic manager
+impl From<&HapticError> for BatteryErrorKind {
+    fn from(e: &HapticError) -> Self {
+        match e {
+            HapticError::DeviceNotFound
+            | HapticError::HostSwitchedAway
+            | HapticError::NotSupported
+            | HapticError::UnsupportedDevice => Self::DeviceNotFound,
+            HapticError::PermissionDenied => Self::PermissionDenied,
+            HapticError::IoError(e) => Self::of_io(e),
+            HapticError::CommunicationError => Self::Timeout,
+            HapticError::ProtocolError(_) | HapticError::SafetyViolation { .. } => Self::Protocol,
+        }
+    }
+}
+
+/// The last battery failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatteryFailure {
+    pub kind: BatteryErrorKind,
+    /// The underlying error's message, for logs
+    pub message: String,
+}
+
+/// Record `error` in the shared state, classified and checked against
+/// other programs competing for the device
+async fn record_failure<E>(
+    state: &SharedBatteryState,
+    conflicts: &ConflictsHandle,
+    error: &E,
+    unavailable_after: u32,
+) -> BatteryErrorKind
+where
+    for<'a> BatteryErrorKind: From<&'a E>,
+    E: std::fmt::Display,
+{
+    let mut report = None;
+    let kind = BatteryErrorKind::from(error).or_conflict(|| {
+        let found = conflicts.refresh_system();
+        let any = !found.is_empty();
+        report = Some(found);
+        any
+    });
+    let message = match (&report, kind) {
+        (Some(report), BatteryErrorKind::Conflict) => format!("{} ({})", error, report.summary()),
+        _ => error.to_string(),
+    };
+    let mut s = state.write().await;
+    s.fail(kind, message, unavailable_after);
+    if let Some(report) = report {
+        s.conflicts = report.conflicts;
+    }
+    kind
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// One point of the discharge curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatterySample {
+    /// Unix time in seconds
+    pub timestamp: u64,
+    pub percentage: u8,
+    pub charging: bool,
+}
+
+/// Ring buffer of recent battery samples, kept in memory only
+#[derive(Debug, Clone, Default)]
+pub struct BatteryHistory {
+    samples: VecDeque<BatterySample>,
+}
+
+impl BatteryHistory {
+    /// Append a sample, dropping those older than the retention window
+    pub fn record(&mut self, sample: BatterySample) {
+        let cutoff = sample.timestamp.saturating_sub(HISTORY_RETENTION_SECS);
+        while self.samples.front().is_some_and(|s| s.timestamp < cutoff) {
+            self.samples.pop_front();
+        }
+        if self.samples.len() >= HISTORY_MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Samples, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = &BatterySample> {
+        self.samples.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+/// Fields of one periodic battery summary line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SummaryLine {
+    pub percentage: u8,
+    pub charging: bool,
+    pub available: bool,
+    /// Change since the previous summary; `None` for the first one or when
+    /// either end had no reading
+    pub delta: Option<i16>,
+    /// Failed polls since the previous summary
+    pub poll_errors: u32,
+}
+
+/// Periodic battery summary, so journald keeps a history without per-poll lines
+#[derive(Debug)]
+pub struct BatterySummary {
+    interval: Duration,
+    last_at: Instant,
+    last_percentage: Option<u8>,
+    poll_errors: u32,
+}
+
+impl BatterySummary {
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Self {
+            interval,
+            last_at: now,
+            last_percentage: None,
+            poll_errors: 0,
+        }
+    }
+
+    /// Count a failed poll toward the next summary
+    pub fn record_error(&mut self) {
+        self.poll_errors = self.poll_errors.saturating_add(1);
+    }
+
+    /// The summary for `state` once `interval` has elapsed, resetting the counters
+    pub fn take_if_due(&mut self, now: Instant, state: &BatteryState) -> Option<SummaryLine> {
+        if now.duration_since(self.last_at) < self.interval {
+            return None;
+        }
+        let current = state.available.then_some(state.percentage);
+        let line = SummaryLine {
+            percentage: state.percentage,
+            charging: state.charging,
+            available: state.available,
+            delta: current
+                .zip(self.last_percentage)
+                .map(|(now, before)| now as i16 - before as i16),
+            poll_errors: self.poll_errors,
+        };
+        self.last_at = now;
+        self.last_percentage = current;
+        self.poll_errors = 0;
+        Some(line)
+    }
+}
+
+/// Log a summary line at info level
+fn log_summary(line: SummaryLine) {
+    tracing::info!(
+        percentage = line.percentage,
+        charging = line.charging,
+        available = line.available,
+        delta = ?line.delta,
+        poll_errors = line.poll_errors,
+        "Battery summary"
+    );
+}
+
+/// One decoded battery query result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryReading {
+    pub percentage: u8,
+    pub charging: bool,
+    pub charge_complete: bool,
+}
+
+impl BatteryReading {
+    /// UNIFIED_BATTERY (0x1004) charging_status: 0=discharging, 1=charging,
+    /// 2=charging_slow, 3=charging_complete, 5=invalid
+    pub fn from_unified(percentage: u8, charging_status: u8) -> Self {
+        Self {
+            percentage,
+            charging: (1..=3).contains(&charging_status),
+            charge_complete: charging_status == 3,
+        }
+    }
+
+    /// BATTERY_STATUS (0x1000) status: 0=discharging, 1=recharging,
+    /// 2=almost full, 3=charged, 4=slow recharge
+    pub fn from_battery_status(percentage: u8, status: u8) -> Self {
+        Self {
+            percentage,
+            charging: (1..=4).contains(&status),
+            charge_complete: status == 3,
+        }
+    }
+}
+
+/// Decode a battery query response (full HID++ report, header included)
+///
+/// UNIFIED_BATTERY (0x1004): [4] state_of_charge, [5] level, [6] flags,
+/// [7] charging_status. BATTERY_STATUS (0x1000): [4] level, [5] next_level,
+/// [6] status. Returns `None` for a report too short for its feature, so a
+/// truncated hidraw read is an error instead of a misread.
+pub fn parse_battery_response(response: &[u8], is_unified: bool) -> Option<BatteryReading> {
+    if is_unified {
+        let (percentage, charging_status) = (*response.get(4)?, *response.get(7)?);
+        Some(BatteryReading::from_unified(percentage, charging_status))
+    } else {
+        let (percentage, status) = (*response.get(4)?, *response.get(6)?);
+        Some(BatteryReading::from_battery_status(percentage, status))
+    }
 }
 
 /// Shared battery state type
@@ -47,16 +393,24 @@ pub fn new_shared_state() -> SharedBatteryState {
 pub struct BatteryHandler {
     /// Path to the hidraw device
     device_path: Option<PathBuf>,
-    /// Device file handle
-    device: Option<File>,
+    /// Device file handle (non-blocking hidraw fd)
+    device: Option<Box<dyn Transport>>,
     /// Device index (for Bolt receiver)
     device_index: u8,
+    /// Rotating software ID for request/reply correlation
+    sw_ids: SwIdCounter,
     /// Cached feature index for battery
     battery_feature_index: Option<u8>,
     /// Whether using UNIFIED_BATTERY (true) or BATTERY_STATUS (false)
     is_unified_battery: bool,
+    /// The device answered the status request Unsupported; it is not asked again
+    battery_refused: bool,
     /// Shared battery state
     state: SharedBatteryState,
+    /// HID++ trace the exchanges are recorded to
+    trace: HidppTrace,
+    /// Failures are checked against programs competing for the device
+    conflicts: ConflictsHandle,
 }
 
 impl BatteryHandler {
@@ -66,9 +420,13 @@ impl BatteryHandler {
             device_path: None,
             device: None,
             device_index: 0x02, // Default for Bolt receiver
+            sw_ids: SwIdCounter::new(),
             battery_feature_index: None,
             is_unified_battery: false,
+            battery_refused: false,
             state,
+            trace: HidppTrace::default(),
+            conflicts: ConflictsHandle::default(),
         }
     }
 
@@ -158,7 +516,7 @@ impl BatteryHandler {
             })?;
 
         self.device_path = Some(path.clone());
-        self.device = Some(file);
+        self.device = Some(Box::new(file));
         Ok(())
     }
 
@@ -176,116 +534,41 @@ impl BatteryHandler {
                 continue;
             }
 
-            // Try a simple HID++ ping to validate the device has a mouse connected
-            // IRoot function 0x01 (ping) with test data
-            let ping_params = [0, 0, 0xAA];
-            match self.hidpp_request(0x00, 0x01, &ping_params) {
-                Ok(resp) if resp.len() >= 7 && resp[6] == 0xAA => {
-                    tracing::info!(path = %path.display(), "Found Logitech HID++ device (validated)");
-                    return Ok(());
-                }
-                _ => {
-                    // This device didn't respond correctly, try next
-                    tracing::debug!(path = %path.display(), "HID++ device did not validate, trying next");
-                    self.device = None;
-                    self.device_path = None;
-                    continue;
-                }
+            if self.ping() {
+                tracing::info!(path = %path.display(), "Found Logitech HID++ device (validated)");
+                return Ok(());
             }
+            // This device didn't respond correctly, try next
+            tracing::debug!(path = %path.display(), "HID++ device did not validate, trying next");
+            self.device = None;
+            self.device_path = None;
         }
 
         Err(BatteryError::DeviceNotFound)
     }
 
-    /// Send a HID++ request and read the response
+    /// Whether a mouse answers on the open device
+    ///
+    /// IRoot function 0x01 (ping) echoes its test byte.
+    fn ping(&mut self) -> bool {
+        let ping_params = [0, 0, 0xAA];
+        matches!(self.hidpp_request(0x00, 0x01, &ping_params), Ok(resp) if resp.len() >= 7 && resp[6] == 0xAA)
+    }
+
+    /// Send a HID++ read and wait for the reply correlated to it
+    ///
+    /// Every request here is an idempotent read, so timeouts are retried
+    /// with a fresh software ID.
     fn hidpp_request(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Result<Vec<u8>, BatteryError> {
         let device = self.device.as_mut().ok_or(BatteryError::DeviceNotFound)?;
-
-        // Drain any pending data first to avoid stale responses
-        let mut drain_buf = [0u8; 64];
-        loop {
-            match device.read(&mut drain_buf) {
-                Ok(_) => continue, // Discard stale data
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
-                Err(_) => break,
-            }
-        }
-
-        // Build HID++ short report (7 bytes)
-        let mut request = [0u8; 7];
-        request[0] = HIDPP_SHORT;
-        request[1] = self.device_index;
-        request[2] = feature_index;
-        request[3] = (function << 4) | SOFTWARE_ID;
-
-        // Copy params (up to 3 bytes for short report)
-        let param_len = params.len().min(3);
-        request[4..4 + param_len].copy_from_slice(&params[..param_len]);
-
-        tracing::debug!(
+        let request = Request {
+            long: false,
+            device_index: self.device_index,
             feature_index,
             function,
-            "Sending HID++ request: {:02X?}",
-            &request
-        );
-
-        // Send request
-        device.write_all(&request).map_err(BatteryError::IoError)?;
-
-        // Read response with timeout (non-blocking, so we poll)
-        let mut response = [0u8; 20];
-        let mut attempts = 0;
-
-        loop {
-            match device.read(&mut response) {
-                Ok(len) if len >= 7 => {
-                    let resp_function = (response[3] >> 4) & 0x0F;
-                    let resp_sw_id = response[3] & 0x0F;
-
-                    tracing::debug!(
-                        "HID++ response: {:02X?} (feat={}, fn={}, sw={})",
-                        &response[..len],
-                        response[2],
-                        resp_function,
-                        resp_sw_id
-                    );
-
-                    // Check if this is a response to our request
-                    if response[0] == HIDPP_SHORT || response[0] == HIDPP_LONG {
-                        // Must match: device index, feature index, function, AND software ID
-                        if response[1] == self.device_index
-                            && response[2] == feature_index
-                            && resp_function == function
-                            && resp_sw_id == SOFTWARE_ID
-                        {
-                            return Ok(response[..len].to_vec());
-                        }
-                        // Check for error response (0x8F = error report)
-                        if response[2] == 0x8F || (response[2] == feature_index && response[4] == 0x05) {
-                            tracing::debug!("HID++ error response: {:02X?}", &response[..len]);
-                            return Err(BatteryError::ProtocolError("Device returned error".into()));
-                        }
-                        // Ignore unrelated notifications (button events, etc)
-                    }
-                }
-                Ok(_) => {
-                    // Short read, continue
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // No data yet
-                }
-                Err(e) => {
-                    return Err(BatteryError::IoError(e));
-                }
-            }
-
-            attempts += 1;
-            if attempts > 100 {
-                return Err(BatteryError::Timeout);
-            }
-
-            std::thread::sleep(std::time::Duration::from_millis(10));
-        }
+            params,
+        };
+        Ok(request::exchange_read(device, &self.trace, &request, &mut self.sw_ids, REQUEST_TIMEOUT)?)
     }
 
     /// Get the feature index for a given feature ID using IRoot
@@ -307,12 +590,16 @@ impl BatteryHandler {
     }
 
     /// Query battery status from the device
-    pub fn query_battery(&mut self) -> Result<(u8, bool), BatteryError> {
+    pub fn query_battery(&mut self) -> Result<BatteryReading, BatteryError> {
         // Open device if not already open
         if self.device.is_none() {
             self.open()?;
         }
 
+        if self.battery_refused {
+            return Err(BatteryError::FeatureNotSupported);
+        }
+
         // Get battery feature index if not cached
         if self.battery_feature_index.is_none() {
             // Try UNIFIED_BATTERY first (newer devices), then BATTERY_STATUS
@@ -343,104 +630,81 @@ impl BatteryHandler {
         // UNIFIED_BATTERY (0x1004): function 1 = get_status
         // BATTERY_STATUS (0x1000): function 0 = GetBatteryLevelStatus
         let function = if self.is_unified_battery { 0x01 } else { 0x00 };
-        let response = self.hidpp_request(feature_index, function, &[])?;
+        let response = match self.hidpp_request(feature_index, function, &[]) {
+            Err(BatteryError::FeatureNotSupported) => {
+                tracing::warn!(feature_index, "Device reports the battery feature unsupported; no longer polling it");
+                self.battery_refused = true;
+                return Err(BatteryError::FeatureNotSupported);
+            }
+            response => response?,
+        };
 
         // Log raw response for debugging
-        tracing::info!(
+        tracing::trace!(
             response_len = response.len(),
             is_unified = self.is_unified_battery,
             "Battery response: {:02X?}",
             &response[..response.len().min(12)]
         );
 
-        // HID++ UNIFIED_BATTERY (0x1004) response format:
-        // [0] report_type, [1] device_index, [2] feature_index, [3] function_id
-        // [4] state_of_charge (percentage), [5] level (0-4), [6] flags, [7] charging_status
-        //
-        // HID++ BATTERY_STATUS (0x1000) response format:
-        // [4] level, [5] next_level, [6] status
-        if response.len() >= 8 && self.is_unified_battery {
-            let percentage = response[4];
-            let charging_status = response[7]; // Charging status is at byte 7 for UNIFIED_BATTERY
-
-            // UNIFIED_BATTERY charging_status: 0=discharging, 1=charging, 2=charging_slow, 3=charging_complete, 5=invalid
-            let charging = (1..=3).contains(&charging_status);
-
-            tracing::debug!(
-                percentage,
-                charging_status,
-                charging,
-                "Battery query result (UNIFIED_BATTERY)"
-            );
-
-            Ok((percentage, charging))
-        } else if response.len() >= 7 {
-            let percentage = response[4];
-            let charging_status = response[6];
-
-            // BATTERY_STATUS status: 0=discharging, 1-4=various charging states
-            let charging = (1..=4).contains(&charging_status);
-
-            tracing::debug!(
-                percentage,
-                charging_status,
-                charging,
-                "Battery query result (BATTERY_STATUS)"
-            );
-
-            Ok((percentage, charging))
-        } else {
-            Err(BatteryError::ProtocolError("Invalid battery response".into()))
+        match parse_battery_response(&response, self.is_unified_battery) {
+            Some(reading) => {
+                tracing::trace!(
+                    percentage = reading.percentage,
+                    charging = reading.charging,
+                    is_unified = self.is_unified_battery,
+                    "Battery query result"
+                );
+                Ok(reading)
+            }
+            None => Err(BatteryError::ProtocolError("Invalid battery response".into())),
         }
     }
 
     /// Update the shared battery state
     pub async fn update_state(&mut self) {
         match self.query_battery() {
-            Ok((percentage, charging)) => {
+            Ok(reading) => {
                 let mut state = self.state.write().await;
-                state.percentage = percentage;
-                state.charging = charging;
-                state.available = true;
-                state.error = None;
-                tracing::debug!(percentage, charging, "Battery state updated");
+                state.apply(reading);
+                tracing::trace!(percentage = reading.percentage, charging = reading.charging, "Battery state updated");
             }
             Err(e) => {
-                let mut state = self.state.write().await;
-                state.available = false;
-                state.error = Some(e.to_string());
-                tracing::warn!(error = %e, "Failed to query battery");
+                let kind = record_failure(&self.state, &self.conflicts, &e, UNAVAILABLE_AFTER_TIMEOUTS).await;
+                tracing::warn!(error = %e, kind = kind.as_str(), "Failed to query battery");
             }
         }
     }
 }
 
 /// Battery error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum BatteryError {
+    #[error("Device not found")]
     DeviceNotFound,
+    #[error("Permission denied")]
     PermissionDenied,
+    #[error("I/O error: {0}")]
     IoError(std::io::Error),
+    #[error("Protocol error: {0}")]
     ProtocolError(String),
+    #[error("Battery feature not supported")]
     FeatureNotSupported,
+    #[error("Request timeout")]
     Timeout,
 }
 
-impl std::fmt::Display for BatteryError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            BatteryError::DeviceNotFound => write!(f, "Device not found"),
-            BatteryError::PermissionDenied => write!(f, "Permission denied"),
-            BatteryError::IoError(e) => write!(f, "I/O error: {}", e),
-            BatteryError::ProtocolError(msg) => write!(f, "Protocol error: {}", msg),
-            BatteryError::FeatureNotSupported => write!(f, "Battery feature not supported"),
-            BatteryError::Timeout => write!(f, "Request timeout"),
+impl From<RequestError> for BatteryError {
+    fn from(e: RequestError) -> Self {
+        match e {
+            RequestError::Timeout => BatteryError::Timeout,
+            RequestError::Io(e) => BatteryError::IoError(e),
+            RequestError::ErrorReply(e) if e.code() == ErrorCode::Unsupported => BatteryError::FeatureNotSupported,
+            e => BatteryError::ProtocolError(e.to_string()),
         }
     }
 }
 
-impl std::error::Error for BatteryError {}
-
 
 /// Start a periodic battery update task (legacy - uses its own hidraw handle)
 #[deprecated(note = "Use start_battery_updater_shared instead to share hidraw with haptic")]
@@ -458,24 +722,19 @@ pub async fn start_battery_updater(state: SharedBatteryState) {
         interval.tick().await;
 
         match handler.query_battery() {
-            Ok((percentage, charging)) => {
+            Ok(reading) => {
                 consecutive_errors = 0;
                 let mut s = state.write().await;
-                s.percentage = percentage;
-                s.charging = charging;
-                s.available = true;
-                s.error = None;
-                tracing::debug!(percentage, charging, "Battery state updated");
+                s.apply(reading);
+                tracing::trace!(percentage = reading.percentage, charging = reading.charging, "Battery state updated");
             }
             Err(e) => {
                 consecutive_errors += 1;
-                let mut s = state.write().await;
-                s.available = false;
-                s.error = Some(e.to_string());
+                let kind = record_failure(&state, &handler.conflicts, &e, UNAVAILABLE_AFTER_TIMEOUTS).await;
 
                 // Only log warning for first few errors, then go quiet
                 if consecutive_errors <= 3 {
-                    tracing::warn!(error = %e, "Failed to query battery");
+                    tracing::warn!(error = %e, kind = kind.as_str(), "Failed to query battery");
                 } else if consecutive_errors == 4 {
                     tracing::info!("Battery queries failing repeatedly - suppressing further warnings");
                 }
@@ -488,23 +747,36 @@ pub async fn start_battery_updater(state: SharedBatteryState) {
 ///
 /// This version shares the HidppDevice with haptic feedback to avoid
 /// conflicts when both need to access the same hidraw device.
+/// Individual polls log at trace level; `summary_interval` (from
+/// `battery.summary_interval_minutes`, `None` = off) sets how often one
+/// info-level summary is written instead. Timeouts only mark the battery
+/// unavailable after `unavailable_after` in a row
+/// (`battery.unavailable_after_timeouts`). A new hidraw node in `devices`
+/// (the receiver or mouse plugged back in) polls at once.
 pub async fn start_battery_updater_shared(
     state: SharedBatteryState,
     haptic_manager: crate::hidpp::SharedHapticManager,
+    summary_interval: Option<Duration>,
+    unavailable_after: u32,
+    mut devices: crate::device_watcher::DeviceSubscription,
+    conflicts: ConflictsHandle,
 ) {
+    if !cfg!(feature = "battery") {
+        // Logged at startup (`features`); idle rather than return, since the
+        // daemon treats the updater exiting as a failure
+        return std::future::pending().await;
+    }
     let mut consecutive_errors = 0u32;
+    let mut summary = summary_interval.map(|interval| BatterySummary::new(interval, Instant::now()));
 
-    // The HID++ battery query polls hidraw with std::thread::sleep(10ms) up to
-    // 100 times (~1s worst case). Holding a std::sync::Mutex across that
-    // blocking I/O while running on a tokio worker is the canonical recipe for
-    // task starvation. Run every query on the blocking thread pool instead.
+    // The HID++ battery query polls hidraw with std::thread::sleep(10ms) for
+    // up to a second per attempt. Run it on the blocking thread pool so it
+    // cannot starve tokio workers; `query_battery_shared` also keeps the
+    // haptic manager unlocked during the wait so haptics are not delayed.
     async fn run_query(
         haptic_manager: crate::hidpp::SharedHapticManager,
-    ) -> Result<(u8, bool), crate::hidpp::HapticError> {
-        tokio::task::spawn_blocking(move || {
-            let mut manager = haptic_manager.lock().unwrap();
-            manager.query_battery()
-        })
+    ) -> Result<BatteryReading, crate::hidpp::HapticError> {
+        tokio::task::spawn_blocking(move || crate::hidpp::query_battery_shared(&haptic_manager))
         .await
         .expect("battery query task panicked")
     }
@@ -525,19 +797,14 @@ pub async fn start_battery_updater_shared(
     let initial_result = run_query(haptic_manager.clone()).await;
 
     match initial_result {
-        Ok((percentage, charging)) => {
+        Ok(reading) => {
             let mut s = state.write().await;
-            s.percentage = percentage;
-            s.charging = charging;
-            s.available = true;
-            s.error = None;
-            tracing::info!(percentage, charging, "Initial battery state");
+            s.apply(reading);
+            tracing::info!(percentage = reading.percentage, charging = reading.charging, "Initial battery state");
         }
         Err(e) => {
-            let mut s = state.write().await;
-            s.available = false;
-            s.error = Some(format!("{}", e));
-            tracing::warn!(error = %e, "Failed initial battery query");
+            let kind = record_failure(&state, &conflicts, &e, unavailable_after).await;
+            tracing::warn!(error = %e, kind = kind.as_str(), "Failed initial battery query");
         }
     }
 
@@ -547,40 +814,65 @@ pub async fn start_battery_updater_shared(
         } else {
             POLL_INTERVAL_SECS
         };
-        tokio::time::sleep(tokio::time::Duration::from_secs(cadence)).await;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(cadence);
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => break,
+                change = devices.changed() => {
+                    // Lagged counts as a change; removals alone wait for the poll
+                    if change.is_none_or(|c| !c.added.is_empty()) {
+                        tracing::debug!("hidraw device added; polling battery now");
+                        break;
+                    }
+                }
+            }
+        }
 
         let result = run_query(haptic_manager.clone()).await;
 
         match result {
-            Ok((percentage, charging)) => {
+            Ok(reading) => {
                 consecutive_errors = 0;
                 let mut s = state.write().await;
-                s.percentage = percentage;
-                s.charging = charging;
-                s.available = true;
-                s.error = None;
-                tracing::debug!(percentage, charging, "Battery state updated (shared)");
+                s.apply(reading);
+                tracing::trace!(percentage = reading.percentage, charging = reading.charging, "Battery state updated (shared)");
+            }
+            Err(e @ crate::hidpp::HapticError::HostSwitchedAway) => {
+                // Skipped without device I/O; polling resumes once it is back
+                record_failure(&state, &conflicts, &e, unavailable_after).await;
+                tracing::trace!("Battery poll skipped: device is on another host");
             }
             Err(e) => {
                 consecutive_errors += 1;
-                let mut s = state.write().await;
-                s.available = false;
-                s.error = Some(format!("{}", e));
+                if let Some(summary) = summary.as_mut() {
+                    summary.record_error();
+                }
+                let kind = record_failure(&state, &conflicts, &e, unavailable_after).await;
 
                 // Only log warning for first few errors, then go quiet
                 if consecutive_errors <= 3 {
-                    tracing::warn!(error = %e, "Failed to query battery (shared)");
+                    tracing::warn!(error = %e, kind = kind.as_str(), "Failed to query battery (shared)");
                 } else if consecutive_errors == 4 {
                     tracing::info!("Battery queries failing repeatedly - suppressing further warnings");
                 }
             }
         }
+
+        // Checked once per poll, so a summary can trail its interval by one cadence
+        if let Some(summary) = summary.as_mut() {
+            let s = state.read().await;
+            if let Some(line) = summary.take_if_due(Instant::now(), &s) {
+                log_summary(line);
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hidpp::constants::report_type;
+    use crate::hidpp::request::mock::MockTransport;
 
     #[test]
     fn test_battery_state_default() {
@@ -588,5 +880,184 @@ mod tests {
         assert_eq!(state.percentage, 0);
         assert!(!state.charging);
         assert!(!state.available);
+        assert!(state.history.is_empty());
+    }
+
+    fn sample(timestamp: u64, percentage: u8) -> BatterySample {
+        BatterySample { timestamp, percentage, charging: false }
+    }
+
+    #[test]
+    fn test_history_drops_samples_older_than_a_day() {
+        let mut history = BatteryHistory::default();
+        history.record(sample(1_000, 90));
+        history.record(sample(2_000, 89));
+        history.record(sample(1_500 + HISTORY_RETENTION_SECS, 70));
+        let kept: Vec<u64> = history.samples().map(|s| s.timestamp).collect();
+        assert_eq!(kept, vec![2_000, 1_500 + HISTORY_RETENTION_SECS]);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = BatteryHistory::default();
+        for i in 0..(HISTORY_MAX_SAMPLES as u64 + 10) {
+            history.record(sample(i, 50));
+        }
+        assert_eq!(history.len(), HISTORY_MAX_SAMPLES);
+        assert_eq!(history.samples().next().unwrap().timestamp, 10);
+    }
+
+    #[test]
+    fn test_apply_records_history() {
+        let mut state = BatteryState::default();
+        state.apply(BatteryReading::from_unified(64, 1));
+        let last = *state.history.samples().last().unwrap();
+        assert_eq!((last.percentage, last.charging), (64, true));
+    }
+
+    #[test]
+    fn test_summary_reports_delta_and_errors() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(900);
+        let mut summary = BatterySummary::new(interval, start);
+        let mut state = BatteryState::default();
+        state.apply(BatteryReading::from_unified(80, 0));
+
+        assert!(summary.take_if_due(start + Duration::from_secs(60), &state).is_none());
+        let first = summary.take_if_due(start + interval, &state).unwrap();
+        assert_eq!(first.delta, None);
+        assert_eq!(first.percentage, 80);
+
+        state.apply(BatteryReading::from_unified(77, 0));
+        summary.record_error();
+        summary.record_error();
+        let second = summary.take_if_due(start + interval * 2, &state).unwrap();
+        assert_eq!(second.delta, Some(-3));
+        assert_eq!(second.poll_errors, 2);
+        assert!(!second.charging);
+
+        // Counters reset after each summary
+        let third = summary.take_if_due(start + interval * 3, &state).unwrap();
+        assert_eq!((third.delta, third.poll_errors), (Some(0), 0));
+    }
+
+    const STATUS: Request = Request { long: false, device_index: 0x02, feature_index: 0x06, function: 0x01, params: &[] };
+
+    /// One battery poll against `io`, as `BatteryHandler::hidpp_request` maps it
+    fn poll(io: &mut MockTransport) -> BatteryError {
+        let result = request::exchange_read(io, &HidppTrace::default(), &STATUS, &mut SwIdCounter::new(), Duration::from_millis(2));
+        BatteryError::from(result.unwrap_err())
+    }
+
+    #[test]
+    fn test_sleeping_mouse_stays_available_until_threshold() {
+        let mut state = BatteryState::default();
+        state.apply(BatteryReading::from_unified(80, 0));
+
+        // The mouse naps: nothing ever answers
+        let mut io = MockTransport::default();
+        for poll_number in 1..=UNAVAILABLE_AFTER_TIMEOUTS {
+            let e = poll(&mut io);
+            let kind = BatteryErrorKind::from(&e).or_conflict(|| false);
+            assert_eq!(kind, BatteryErrorKind::Timeout);
+            state.fail(kind, e.to_string(), UNAVAILABLE_AFTER_TIMEOUTS);
+            assert_eq!(state.available, poll_number < UNAVAILABLE_AFTER_TIMEOUTS, "poll {}", poll_number);
+            assert_eq!(state.error.as_ref().unwrap().message, "Request timeout");
+        }
+        assert_eq!(state.percentage, 80);
+
+        // Waking up starts the count over
+        state.apply(BatteryReading::from_unified(79, 0));
+        assert!(state.error.is_none());
+        for _ in 1..UNAVAILABLE_AFTER_TIMEOUTS {
+            state.fail(BatteryErrorKind::Timeout, "Request timeout", UNAVAILABLE_AFTER_TIMEOUTS);
+        }
+        assert!(state.available);
+    }
+
+    #[test]
+    fn test_other_failures_are_unavailable_at_once() {
+        let mut state = BatteryState::default();
+        state.apply(BatteryReading::from_unified(80, 0));
+
+        // HID++ 2.0 error report for the status request
+        let mut io = MockTransport::default();
+        io.batches
+            .push_back(vec![vec![report_type::SHORT, 0x02, 0xFF, 0x06, 0x11, 0x05, 0x00]]);
+        let e = poll(&mut io);
+        let kind = BatteryErrorKind::from(&e);
+        assert_eq!(kind, BatteryErrorKind::Protocol);
+        state.fail(kind, e.to_string(), UNAVAILABLE_AFTER_TIMEOUTS);
+        assert!(!state.available);
+
+        // A timeout after another failure starts its own count
+        state.apply(BatteryReading::from_unified(80, 0));
+        state.fail(BatteryErrorKind::Timeout, "Request timeout", 1);
+        assert!(!state.available);
+
+        let denied = BatteryError::IoError(std::io::ErrorKind::PermissionDenied.into());
+        assert_eq!(BatteryErrorKind::from(&denied), BatteryErrorKind::PermissionDenied);
+        let away = HapticError::HostSwitchedAway;
+        assert_eq!(BatteryErrorKind::from(&away), BatteryErrorKind::DeviceNotFound);
+        assert_eq!(BatteryErrorKind::from(&HapticError::CommunicationError), BatteryErrorKind::Timeout);
+    }
+
+    /// Ping, UNIFIED_BATTERY lookup and two status polls, recorded from a
+    /// Bolt receiver by a daemon that had already used other software ids
+    const BATTERY_TRACE: &str = include_str!("../tests/fixtures/hidpp-battery.trace");
+
+    #[test]
+    fn test_battery_query_replays_recorded_trace() {
+        let mut handler = BatteryHandler::new(new_shared_state());
+        handler.device = Some(Box::new(MockTransport::replay(BATTERY_TRACE)));
+        assert!(handler.ping());
+
+        let reading = handler.query_battery().unwrap();
+        assert_eq!(handler.battery_feature_index, Some(0x08));
+        assert!(handler.is_unified_battery);
+        assert_eq!((reading.percentage, reading.charging), (75, false));
+
+        // The next poll reuses the cached feature index
+        let reading = handler.query_battery().unwrap();
+        assert_eq!((reading.percentage, reading.charging), (76, true));
+    }
+
+    #[test]
+    fn test_unsupported_status_stops_polling() {
+        let mut io = MockTransport::default();
+        io.batches.push_back(vec![vec![report_type::SHORT, 0x02, 0xFF, 0x06, 0x11, 0x09, 0x00]]);
+        let mut handler = BatteryHandler::new(new_shared_state());
+        handler.device = Some(Box::new(io));
+        handler.battery_feature_index = Some(0x06);
+        handler.is_unified_battery = true;
+        assert!(matches!(handler.query_battery(), Err(BatteryError::FeatureNotSupported)));
+        // Not asked again: an empty transport would time out instead
+        assert!(matches!(handler.query_battery(), Err(BatteryError::FeatureNotSupported)));
+    }
+
+    #[test]
+    fn test_conflict_takes_the_blame_for_silence() {
+        let e = poll(&mut MockTransport::default());
+        let kind = BatteryErrorKind::from(&e).or_conflict(|| true);
+        assert_eq!(kind, BatteryErrorKind::Conflict);
+        assert_eq!(kind.as_str(), "conflict");
+
+        let mut state = BatteryState::default();
+        state.apply(BatteryReading::from_unified(80, 0));
+        state.fail(kind, e.to_string(), UNAVAILABLE_AFTER_TIMEOUTS);
+        assert!(!state.available);
+        assert!(!state.logid_active());
+        state.conflicts.push(Conflict {
+            tool: ConflictTool::Logid,
+            pid: 812,
+            name: "logid".to_string(),
+            nodes: Vec::new(),
+            guidance: ConflictTool::Logid.guidance(),
+        });
+        assert!(state.logid_active());
+
+        // Permission problems are not another program's doing
+        let denied = BatteryErrorKind::PermissionDenied.or_conflict(|| panic!("not checked"));
+        assert_eq!(denied, BatteryErrorKind::PermissionDenied);
     }
 }
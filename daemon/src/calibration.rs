@@ -0,0 +1,420 @@
+//! First-run calibration (`StartCalibration`)
+//!
+//! The settings UI shows the menu and asks for a flick in each of the eight
+//! directions, twice round. Each press and release of the gesture button is
+//! a sample: how long the button was held and how far, and which way, the
+//! pointer travelled from the press point. A sample that went the wrong way,
+//! barely moved or was held for seconds is rejected and the same direction
+//! asked for again. Once every direction has its samples the session
+//! derives [`Recommendation`]s from percentiles of them:
+//!
+//! - `hold_threshold_ms`: a little over the 90th percentile hold, so the
+//!   user's natural flick fits inside `menu.flick_window_ms`
+//! - `flick_distance_px`: under the 10th percentile travel, so their
+//!   shortest deliberate flick still counts (`menu.flick_distance_px`)
+//! - `dead_zone_px`: a fraction of that travel, the radius below which
+//!   motion is noise; advisory, the overlay's centre zone is fixed
+//!
+//! While a session captures, the gesture loop feeds it and the input
+//! pipeline runs capture-only: releases dismiss the menu instead of running
+//! the slice, flicks select nothing and dwell never confirms. Progress goes
+//! out as the `CalibrationEvent` signal (JSON); `AcceptCalibration` applies
+//! the recommendation to `menu` and saves the config, `CancelCalibration`
+//! drops it. Same split as `slice_confirmation`: [`Calibration`] is the pure
+//! state machine behind a [`CalibrationHandle`] on `DaemonHandles`.
+
+
+use serde::Serialize;
+
+use crate::geometry::angle_of;
+use crate::handles::StateHandle;
+
+/// Times each direction is asked for
+pub const ROUNDS: usize = 2;
+
+/// Least pointer travel for a sample; less is a tap, not a flick
+pub const MIN_TRAVEL_PX: f64 = 15.0;
+
+/// Longest hold for a sample; more means the user was not flicking
+pub const MAX_HOLD_MS: u64 = 2000;
+
+/// Furthest a sample may point from the asked direction
+pub const MAX_ANGLE_ERROR_DEG: f64 = 45.0;
+
+/// Range of `hold_threshold_ms` recommendations
+const HOLD_THRESHOLD_MS: (u64, u64) = (120, 600);
+
+/// Range of `flick_distance_px` recommendations
+const FLICK_DISTANCE_PX: (u32, u32) = (30, 200);
+
+/// Range of `dead_zone_px` recommendations
+const DEAD_ZONE_PX: (u32, u32) = (15, 60);
+
+/// A direction the user is asked to flick in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// Clockwise from straight up, the ring's slot order
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// Clockwise angle from straight up, in degrees
+    pub fn angle(self) -> f64 {
+        Self::ALL.iter().position(|&d| d == self).unwrap_or(0) as f64 * 45.0
+    }
+}
+
+/// One accepted flick
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Sample {
+    pub direction: Direction,
+    pub duration_ms: u64,
+    /// Distance of the farthest point reached from the press point
+    pub travel_px: f64,
+    /// Clockwise angle of that point from straight up
+    pub angle_deg: f64,
+}
+
+/// Why a release was not taken as a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectReason {
+    /// Under [`MIN_TRAVEL_PX`]
+    TooShort,
+    /// Held longer than [`MAX_HOLD_MS`]
+    TooSlow,
+    /// More than [`MAX_ANGLE_ERROR_DEG`] off the asked direction
+    WrongDirection,
+}
+
+/// Percentiles the recommendation came from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Measured {
+    pub samples: usize,
+    pub duration_ms_p50: u64,
+    pub duration_ms_p90: u64,
+    pub travel_px_p10: f64,
+    pub travel_px_p50: f64,
+}
+
+/// Settings derived from a finished session
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Recommendation {
+    /// For `menu.flick_window_ms`
+    pub hold_threshold_ms: u64,
+    /// For `menu.flick_distance_px`
+    pub flick_distance_px: u32,
+    /// Advisory: no setting takes it
+    pub dead_zone_px: u32,
+    pub measured: Measured,
+}
+
+/// Progress of a session, sent as the `CalibrationEvent` signal
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CalibrationEvent {
+    /// Ask for a flick; `step` counts from 0 to `total - 1`
+    Prompt { step: usize, total: usize, direction: Direction },
+    /// The release was taken
+    Sample { step: usize, sample: Sample },
+    /// The release was not taken; the same prompt follows
+    Rejected { step: usize, direction: Direction, reason: RejectReason },
+    /// Every prompt has its sample
+    Complete { recommendation: Recommendation },
+}
+
+#[derive(Debug)]
+struct Session {
+    samples: Vec<Sample>,
+    /// Farthest pointer offset of the press in progress
+    press: Option<(i32, i32)>,
+    result: Option<Recommendation>,
+}
+
+impl Session {
+    fn total() -> usize {
+        Direction::ALL.len() * ROUNDS
+    }
+
+    fn prompt(&self) -> CalibrationEvent {
+        let step = self.samples.len();
+        CalibrationEvent::Prompt { step, total: Self::total(), direction: Direction::ALL[step % Direction::ALL.len()] }
+    }
+}
+
+/// The calibration state machine
+#[derive(Debug, Default)]
+pub struct Calibration {
+    session: Option<Session>,
+}
+
+impl Calibration {
+    /// Start a session, dropping any earlier one; the first prompt
+    pub fn start(&mut self) -> CalibrationEvent {
+        let session = Session { samples: Vec::new(), press: None, result: None };
+        let prompt = session.prompt();
+        self.session = Some(session);
+        prompt
+    }
+
+    /// Whether input is being captured (a session that is not finished)
+    pub fn is_capturing(&self) -> bool {
+        self.session.as_ref().is_some_and(|s| s.result.is_none())
+    }
+
+    /// The gesture button went down
+    pub fn pressed(&mut self) {
+        if let Some(session) = self.session.as_mut().filter(|s| s.result.is_none()) {
+            session.press = Some((0, 0));
+        }
+    }
+
+    /// Pointer offset from the press point
+    pub fn moved(&mut self, x: i32, y: i32) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        if let Some(farthest) = session.press.as_mut() {
+            if f64::from(x).hypot(f64::from(y)) > f64::from(farthest.0).hypot(f64::from(farthest.1)) {
+                *farthest = (x, y);
+            }
+        }
+    }
+
+    /// The gesture button came up after `duration_ms`; the events for the
+    /// caller, none when nothing is captured
+    pub fn released(&mut self, duration_ms: u64) -> Vec<CalibrationEvent> {
+        let Some(session) = self.session.as_mut().filter(|s| s.result.is_none()) else {
+            return Vec::new();
+        };
+        // A release whose press came before the session
+        let Some((dx, dy)) = session.press.take() else {
+            return Vec::new();
+        };
+        let step = session.samples.len();
+        let direction = Direction::ALL[step % Direction::ALL.len()];
+        let travel_px = f64::from(dx).hypot(f64::from(dy));
+        let angle_deg = angle_of(f64::from(dx), f64::from(dy));
+        let error = (angle_deg - direction.angle()).rem_euclid(360.0);
+        let reason = if travel_px < MIN_TRAVEL_PX {
+            Some(RejectReason::TooShort)
+        } else if duration_ms > MAX_HOLD_MS {
+            Some(RejectReason::TooSlow)
+        } else if error.min(360.0 - error) > MAX_ANGLE_ERROR_DEG {
+            Some(RejectReason::WrongDirection)
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return vec![CalibrationEvent::Rejected { step, direction, reason }, session.prompt()];
+        }
+
+        let sample = Sample { direction, duration_ms, travel_px, angle_deg };
+        session.samples.push(sample);
+        let mut events = vec![CalibrationEvent::Sample { step, sample }];
+        if session.samples.len() < Session::total() {
+            events.push(session.prompt());
+        } else if let Some(recommendation) = recommend(&session.samples) {
+            session.result = Some(recommendation);
+            events.push(CalibrationEvent::Complete { recommendation });
+        }
+        events
+    }
+
+    /// The finished session's recommendation, ending it; `None` while it
+    /// still captures or without a session
+    pub fn accept(&mut self) -> Option<Recommendation> {
+        let recommendation = self.session.as_ref()?.result?;
+        self.session = None;
+        Some(recommendation)
+    }
+
+    /// End the session without a result; `false` when there was none
+    pub fn cancel(&mut self) -> bool {
+        self.session.take().is_some()
+    }
+}
+
+/// Nearest-rank percentile `p` (0-100) of sorted `values`
+fn percentile<T: Copy>(sorted: &[T], p: usize) -> Option<T> {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+/// Settings for the user who produced `samples`; `None` without samples
+pub fn recommend(samples: &[Sample]) -> Option<Recommendation> {
+    let mut durations: Vec<u64> = samples.iter().map(|s| s.duration_ms).collect();
+    durations.sort_unstable();
+    let mut travels: Vec<f64> = samples.iter().map(|s| s.travel_px).collect();
+    travels.sort_by(f64::total_cmp);
+    let measured = Measured {
+        samples: samples.len(),
+        duration_ms_p50: percentile(&durations, 50)?,
+        duration_ms_p90: percentile(&durations, 90)?,
+        travel_px_p10: percentile(&travels, 10)?,
+        travel_px_p50: percentile(&travels, 50)?,
+    };
+
+    let round_to = |value: f64, step: f64| (value / step).round() * step;
+    let hold = round_to(measured.duration_ms_p90 as f64 * 1.25, 10.0) as u64;
+    let distance = round_to(measured.travel_px_p10 * 0.7, 5.0) as u32;
+    let dead_zone = round_to(measured.travel_px_p10 * 0.35, 5.0) as u32;
+    Some(Recommendation {
+        hold_threshold_ms: hold.clamp(HOLD_THRESHOLD_MS.0, HOLD_THRESHOLD_MS.1),
+        flick_distance_px: distance.clamp(FLICK_DISTANCE_PX.0, FLICK_DISTANCE_PX.1),
+        dead_zone_px: dead_zone.clamp(DEAD_ZONE_PX.0, DEAD_ZONE_PX.1),
+        measured,
+    })
+}
+
+/// The daemon's calibration session
+pub type CalibrationHandle = StateHandle<Calibration>;
+
+impl CalibrationHandle {
+    /// Whether the input pipeline should only capture
+    pub fn is_capturing(&self) -> bool {
+        self.update(|c| c.is_capturing()).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Offset of a flick of `distance` pixels towards `direction`
+    fn offset(direction: Direction, distance: f64) -> (i32, i32) {
+        let radians = direction.angle().to_radians();
+        ((radians.sin() * distance).round() as i32, (-radians.cos() * distance).round() as i32)
+    }
+
+    /// One press, a few motion reports out along the direction, release
+    fn flick(c: &mut Calibration, direction: Direction, distance: f64, duration_ms: u64) -> Vec<CalibrationEvent> {
+        c.pressed();
+        for fraction in [0.25, 0.6, 1.0, 0.9] {
+            let (x, y) = offset(direction, distance * fraction);
+            c.moved(x, y);
+        }
+        c.released(duration_ms)
+    }
+
+    /// Run a whole session, the n-th flick taking `shape(n)`
+    fn run(shape: impl Fn(usize) -> (f64, u64)) -> Recommendation {
+        let mut c = Calibration::default();
+        c.start();
+        let mut last = Vec::new();
+        for n in 0..Session::total() {
+            let (distance, duration) = shape(n);
+            last = flick(&mut c, Direction::ALL[n % 8], distance, duration);
+        }
+        match last.last() {
+            Some(CalibrationEvent::Complete { recommendation }) => *recommendation,
+            other => panic!("session did not complete: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_quick_short_flicks() {
+        // 90-130ms, 60-100px
+        let r = run(|n| (60.0 + (n % 5) as f64 * 10.0, 90 + (n % 5) as u64 * 10));
+        assert_eq!(r.measured.samples, 16);
+        assert!((140..=200).contains(&r.hold_threshold_ms), "{r:?}");
+        assert!((35..=50).contains(&r.flick_distance_px), "{r:?}");
+        assert!((15..=25).contains(&r.dead_zone_px), "{r:?}");
+    }
+
+    #[test]
+    fn test_slow_long_flicks() {
+        // 250-450ms, 150-300px
+        let r = run(|n| (150.0 + (n % 4) as f64 * 50.0, 250 + (n % 5) as u64 * 50));
+        assert!((500..=600).contains(&r.hold_threshold_ms), "{r:?}");
+        assert!((100..=110).contains(&r.flick_distance_px), "{r:?}");
+        assert!((50..=55).contains(&r.dead_zone_px), "{r:?}");
+        assert!(r.flick_distance_px as f64 <= r.measured.travel_px_p10);
+    }
+
+    #[test]
+    fn test_recommendations_are_clamped() {
+        let tiny = run(|_| (16.0, 20));
+        assert_eq!((tiny.hold_threshold_ms, tiny.flick_distance_px, tiny.dead_zone_px), (120, 30, 15));
+        let huge = run(|_| (900.0, 1900));
+        assert_eq!((huge.hold_threshold_ms, huge.flick_distance_px, huge.dead_zone_px), (600, 200, 60));
+    }
+
+    #[test]
+    fn test_bad_samples_are_rejected_and_reprompted() {
+        let mut c = Calibration::default();
+        assert_eq!(c.start(), CalibrationEvent::Prompt { step: 0, total: 16, direction: Direction::Up });
+
+        let rejected = |events: Vec<CalibrationEvent>| match events.as_slice() {
+            [CalibrationEvent::Rejected { step: 0, reason, .. }, CalibrationEvent::Prompt { step: 0, direction: Direction::Up, .. }] => *reason,
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(rejected(flick(&mut c, Direction::Up, 8.0, 100)), RejectReason::TooShort);
+        assert_eq!(rejected(flick(&mut c, Direction::Up, 100.0, 2500)), RejectReason::TooSlow);
+        assert_eq!(rejected(flick(&mut c, Direction::Right, 100.0, 100)), RejectReason::WrongDirection);
+
+        // Up and a little right is still up
+        c.pressed();
+        c.moved(30, -90);
+        let events = c.released(120);
+        assert!(matches!(events[0], CalibrationEvent::Sample { step: 0, .. }));
+        assert_eq!(events[1], CalibrationEvent::Prompt { step: 1, total: 16, direction: Direction::UpRight });
+    }
+
+    #[test]
+    fn test_capture_lifecycle() {
+        let mut c = Calibration::default();
+        assert!(!c.is_capturing());
+        // No session: the pipeline runs as usual
+        assert!(flick(&mut c, Direction::Up, 100.0, 100).is_empty());
+        assert_eq!(c.accept(), None);
+        assert!(!c.cancel());
+
+        c.start();
+        assert!(c.is_capturing());
+        // A release without a press in the session is ignored
+        assert!(c.released(100).is_empty());
+        assert_eq!(c.accept(), None, "nothing to accept while capturing");
+        assert!(c.cancel());
+        assert!(!c.is_capturing());
+
+        c.start();
+        for n in 0..Session::total() {
+            flick(&mut c, Direction::ALL[n % 8], 100.0, 150);
+        }
+        assert!(!c.is_capturing(), "a finished session no longer captures");
+        assert!(flick(&mut c, Direction::Up, 100.0, 100).is_empty());
+        let accepted = c.accept().unwrap();
+        assert_eq!(accepted.flick_distance_px, 70);
+        assert_eq!(c.accept(), None);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<u64> = (1..=10).collect();
+        assert_eq!(percentile(&values, 10), Some(1));
+        assert_eq!(percentile(&values, 50), Some(5));
+        assert_eq!(percentile(&values, 90), Some(9));
+        assert_eq!(percentile::<u64>(&[], 50), None);
+        assert_eq!(recommend(&[]), None);
+    }
+}
@@ -0,0 +1,405 @@
+//! Clipboard access for the "Paste as plain text" action
+//!
+//! Talks to the session clipboard through the usual command-line helpers:
+//! wl-clipboard (`wl-paste` / `wl-copy`) on Wayland, xclip or xsel on X11. The
+//! session check is the same one the shortcut path uses to pick ydotool over
+//! xdotool, so clipboard and key synthesis always target the same display server.
+//!
+//! `prepare_paste_plain` decides how to paste: short ASCII text is typed
+//! directly (the clipboard is never touched), anything longer is swapped onto
+//! the clipboard as `text/plain` for a ctrl+v, and the original contents are
+//! put back afterwards with `restore`. Only the primary format of the original
+//! is restored, since the helpers set one MIME type per invocation.
+
+use std::io::Write;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::sandbox::ExecutionStrategy;
+
+/// Longest clipboard text typed directly instead of swapped and pasted
+///
+/// Typing is per-key through uinput/XTest; past this it is slower than a
+/// paste and more exposed to focus changes mid-way.
+pub const TYPE_MAX_CHARS: usize = 200;
+
+/// How long the focused app gets to read the swapped clipboard before restore
+pub const RESTORE_DELAY: Duration = Duration::from_millis(500);
+
+/// X11 selection targets that describe the selection rather than hold content
+const META_TARGETS: [&str; 5] = ["TARGETS", "TIMESTAMP", "MULTIPLE", "SAVE_TARGETS", "DELETE"];
+
+/// Text types in order of preference for reading
+const TEXT_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "UTF8_STRING",
+    "text/plain",
+    "STRING",
+    "TEXT",
+];
+
+/// Clipboard helper failure
+#[derive(Debug, thiserror::Error)]
+pub enum ClipboardError {
+    /// The helper binary could not be started
+    #[error("{tool} unavailable: {error}")]
+    Spawn { tool: &'static str, error: std::io::Error },
+    /// The helper ran and failed
+    #[error("{tool} failed ({status})")]
+    Failed { tool: &'static str, status: std::process::ExitStatus },
+}
+
+/// Why a plain-text paste did nothing
+#[derive(Debug, thiserror::Error)]
+pub enum PastePlainError {
+    /// Nothing on the clipboard, or empty text
+    #[error("Clipboard is empty")]
+    Empty,
+    /// Only non-text content (images, files); carries the offered types
+    #[error("Clipboard holds no text (offers {})", .0.join(", "))]
+    NotText(Vec<String>),
+    /// A clipboard helper failed
+    #[error(transparent)]
+    Clipboard(#[from] ClipboardError),
+}
+
+
+/// Read/write access to the session clipboard
+pub trait Clipboard {
+    /// Types currently offered, empty when nothing is copied
+    fn types(&mut self) -> Result<Vec<String>, ClipboardError>;
+    /// Contents in one of the offered types
+    fn read(&mut self, mime: &str) -> Result<Vec<u8>, ClipboardError>;
+    /// Replace the clipboard with `data` offered as `mime`
+    fn write(&mut self, mime: &str, data: &[u8]) -> Result<(), ClipboardError>;
+    /// Replace the clipboard with plain text
+    fn write_text(&mut self, text: &str) -> Result<(), ClipboardError>;
+}
+
+/// Clipboard contents to put back after a swap-and-paste
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedClipboard {
+    pub mime: String,
+    pub data: Vec<u8>,
+}
+
+/// How to deliver the text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PastePlan {
+    /// Short text: type it with the key-synthesis backend
+    Type(String),
+    /// Plain text is on the clipboard: send ctrl+v, then restore `restore`
+    /// (`None` when the original was already plain text)
+    Paste { restore: Option<SavedClipboard> },
+}
+
+fn is_text_type(mime: &str) -> bool {
+    TEXT_TYPES.iter().any(|t| t.eq_ignore_ascii_case(mime)) || mime.starts_with("text/plain")
+}
+
+/// Inspect the clipboard and, for long text, swap it to plain text
+pub fn prepare_paste_plain(clipboard: &mut impl Clipboard) -> Result<PastePlan, PastePlainError> {
+    let types: Vec<String> = clipboard
+        .types()?
+        .into_iter()
+        .filter(|t| !t.is_empty() && !META_TARGETS.contains(&t.as_str()))
+        .collect();
+    if types.is_empty() {
+        return Err(PastePlainError::Empty);
+    }
+    let Some(text_type) = TEXT_TYPES
+        .iter()
+        .find_map(|want| types.iter().find(|t| t.eq_ignore_ascii_case(want)))
+        .or_else(|| types.iter().find(|t| is_text_type(t)))
+        .cloned()
+    else {
+        return Err(PastePlainError::NotText(types));
+    };
+
+    let text = String::from_utf8_lossy(&clipboard.read(&text_type)?).into_owned();
+    if text.is_empty() {
+        return Err(PastePlainError::Empty);
+    }
+    // ydotool types through a US keymap, so only ASCII is safe to type
+    let typeable = text.chars().count() <= TYPE_MAX_CHARS
+        && text.chars().all(|c| c.is_ascii_graphic() || matches!(c, ' ' | '\n' | '\t'));
+    if typeable {
+        return Ok(PastePlan::Type(text));
+    }
+
+    // The first offered type is the source's primary (richest) format
+    let primary = &types[0];
+    let restore = if is_text_type(primary) {
+        None
+    } else {
+        Some(SavedClipboard {
+            mime: primary.clone(),
+            data: clipboard.read(primary)?,
+        })
+    };
+    clipboard.write_text(&text)?;
+    Ok(PastePlan::Paste { restore })
+}
+
+/// Put saved contents back on the clipboard
+pub fn restore(clipboard: &mut impl Clipboard, saved: &SavedClipboard) -> Result<(), ClipboardError> {
+    clipboard.write(&saved.mime, &saved.data)
+}
+
+// ============================================================================
+// Command-line helpers
+// ============================================================================
+
+/// Clipboard helper family
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardBackend {
+    /// wl-clipboard (Wayland)
+    WlClipboard,
+    /// xclip (X11)
+    Xclip,
+    /// xsel (X11, text only)
+    Xsel,
+}
+
+impl ClipboardBackend {
+    /// Pick the helper for the current session
+    pub fn detect(strategy: &ExecutionStrategy) -> Self {
+        if crate::actions::is_wayland_session() {
+            Self::WlClipboard
+        } else if strategy.on_path("xclip") {
+            Self::Xclip
+        } else {
+            Self::Xsel
+        }
+    }
+}
+
+/// `Clipboard` backed by wl-clipboard, xclip or xsel
+#[derive(Debug, Clone)]
+pub struct CommandClipboard {
+    backend: ClipboardBackend,
+    /// Where the helpers run
+    strategy: Arc<ExecutionStrategy>,
+}
+
+impl CommandClipboard {
+    pub fn new(backend: ClipboardBackend, strategy: Arc<ExecutionStrategy>) -> Self {
+        Self { backend, strategy }
+    }
+
+    /// Helper for the current session
+    pub fn detect(strategy: Arc<ExecutionStrategy>) -> Self {
+        Self::new(ClipboardBackend::detect(&strategy), strategy)
+    }
+
+    pub fn backend(&self) -> ClipboardBackend {
+        self.backend
+    }
+
+    /// Run a reading helper; `Ok(None)` when it exits non-zero (nothing copied)
+    fn output(&self, tool: &'static str, args: &[&str]) -> Result<Option<Vec<u8>>, ClipboardError> {
+        let output = self
+            .strategy
+            .command(tool)
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|error| ClipboardError::Spawn { tool, error })?;
+        Ok(output.status.success().then_some(output.stdout))
+    }
+
+    /// Run a writing helper with `data` on stdin
+    ///
+    /// The helpers fork a background process that serves the selection; the
+    /// foreground one exits once stdin is consumed.
+    fn input(&self, tool: &'static str, args: &[&str], data: &[u8]) -> Result<(), ClipboardError> {
+        let mut child = self
+            .strategy
+            .command(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| ClipboardError::Spawn { tool, error })?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(data)
+                .map_err(|error| ClipboardError::Spawn { tool, error })?;
+        }
+        let status = child
+            .wait()
+            .map_err(|error| ClipboardError::Spawn { tool, error })?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(ClipboardError::Failed { tool, status })
+        }
+    }
+}
+
+impl Clipboard for CommandClipboard {
+    fn types(&mut self) -> Result<Vec<String>, ClipboardError> {
+        let listing = match self.backend {
+            ClipboardBackend::WlClipboard => self.output("wl-paste", &["--list-types"])?,
+            ClipboardBackend::Xclip => {
+                self.output("xclip", &["-selection", "clipboard", "-t", "TARGETS", "-o"])?
+            }
+            // xsel cannot list targets; non-empty output means text
+            ClipboardBackend::Xsel => {
+                return Ok(match self.output("xsel", &["--clipboard", "--output"])? {
+                    Some(text) if !text.is_empty() => vec!["text/plain".to_string()],
+                    _ => Vec::new(),
+                });
+            }
+        };
+        Ok(listing
+            .map(|out| {
+                String::from_utf8_lossy(&out)
+                    .lines()
+                    .map(|l| l.trim().to_string())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn read(&mut self, mime: &str) -> Result<Vec<u8>, ClipboardError> {
+        let out = match self.backend {
+            ClipboardBackend::WlClipboard => {
+                self.output("wl-paste", &["--no-newline", "--type", mime])?
+            }
+            ClipboardBackend::Xclip => {
+                self.output("xclip", &["-selection", "clipboard", "-t", mime, "-o"])?
+            }
+            ClipboardBackend::Xsel => self.output("xsel", &["--clipboard", "--output"])?,
+        };
+        Ok(out.unwrap_or_default())
+    }
+
+    fn write(&mut self, mime: &str, data: &[u8]) -> Result<(), ClipboardError> {
+        match self.backend {
+            ClipboardBackend::WlClipboard => self.input("wl-copy", &["--type", mime], data),
+            ClipboardBackend::Xclip => {
+                self.input("xclip", &["-selection", "clipboard", "-t", mime, "-i"], data)
+            }
+            ClipboardBackend::Xsel => self.input("xsel", &["--clipboard", "--input"], data),
+        }
+    }
+
+    fn write_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+        let mime = match self.backend {
+            ClipboardBackend::WlClipboard => "text/plain;charset=utf-8",
+            ClipboardBackend::Xclip | ClipboardBackend::Xsel => "UTF8_STRING",
+        };
+        self.write(mime, text.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// In-memory clipboard holding one item in several formats
+    #[derive(Default)]
+    struct FakeClipboard {
+        offers: Vec<(String, Vec<u8>)>,
+        writes: Vec<(String, Vec<u8>)>,
+    }
+
+    impl FakeClipboard {
+        fn with(offers: &[(&str, &[u8])]) -> Self {
+            Self {
+                offers: offers.iter().map(|(m, d)| (m.to_string(), d.to_vec())).collect(),
+                writes: Vec::new(),
+            }
+        }
+    }
+
+    impl Clipboard for FakeClipboard {
+        fn types(&mut self) -> Result<Vec<String>, ClipboardError> {
+            Ok(self.offers.iter().map(|(m, _)| m.clone()).collect())
+        }
+
+        fn read(&mut self, mime: &str) -> Result<Vec<u8>, ClipboardError> {
+            Ok(self
+                .offers
+                .iter()
+                .find(|(m, _)| m == mime)
+                .map(|(_, d)| d.clone())
+                .unwrap_or_default())
+        }
+
+        fn write(&mut self, mime: &str, data: &[u8]) -> Result<(), ClipboardError> {
+            self.writes.push((mime.to_string(), data.to_vec()));
+            self.offers = vec![(mime.to_string(), data.to_vec())];
+            Ok(())
+        }
+
+        fn write_text(&mut self, text: &str) -> Result<(), ClipboardError> {
+            self.write("text/plain", text.as_bytes())
+        }
+    }
+
+    #[test]
+    fn test_empty_clipboard() {
+        let mut clip = FakeClipboard::default();
+        assert!(matches!(prepare_paste_plain(&mut clip), Err(PastePlainError::Empty)));
+        // X11 reports targets even for an empty text selection
+        let mut clip = FakeClipboard::with(&[("TARGETS", b""), ("UTF8_STRING", b"")]);
+        assert!(matches!(prepare_paste_plain(&mut clip), Err(PastePlainError::Empty)));
+        assert!(clip.writes.is_empty());
+    }
+
+    #[test]
+    fn test_image_is_not_pasted() {
+        let mut clip = FakeClipboard::with(&[("image/png", b"\x89PNG")]);
+        match prepare_paste_plain(&mut clip) {
+            Err(PastePlainError::NotText(types)) => assert_eq!(types, vec!["image/png"]),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(clip.writes.is_empty());
+    }
+
+    #[test]
+    fn test_short_text_is_typed_without_touching_clipboard() {
+        let mut clip = FakeClipboard::with(&[
+            ("text/html", b"<b>hello</b> world"),
+            ("text/plain;charset=utf-8", b"hello world"),
+        ]);
+        assert_eq!(
+            prepare_paste_plain(&mut clip).unwrap(),
+            PastePlan::Type("hello world".to_string())
+        );
+        assert!(clip.writes.is_empty());
+    }
+
+    #[test]
+    fn test_long_rich_text_is_swapped_and_restored() {
+        let plain = "x".repeat(TYPE_MAX_CHARS + 1);
+        let html = format!("<i>{}</i>", plain);
+        let mut clip = FakeClipboard::with(&[
+            ("text/html", html.as_bytes()),
+            ("text/plain", plain.as_bytes()),
+        ]);
+        let PastePlan::Paste { restore: Some(saved) } = prepare_paste_plain(&mut clip).unwrap() else {
+            panic!("expected a swap with restore");
+        };
+        assert_eq!(clip.writes, vec![("text/plain".to_string(), plain.clone().into_bytes())]);
+        assert_eq!(saved.mime, "text/html");
+
+        restore(&mut clip, &saved).unwrap();
+        assert_eq!(clip.offers, vec![("text/html".to_string(), html.into_bytes())]);
+    }
+
+    #[test]
+    fn test_non_ascii_is_pasted_and_plain_original_not_restored() {
+        let mut clip = FakeClipboard::with(&[("UTF8_STRING", "grüße".as_bytes())]);
+        assert_eq!(
+            prepare_paste_plain(&mut clip).unwrap(),
+            PastePlan::Paste { restore: None }
+        );
+        assert_eq!(clip.writes.len(), 1);
+    }
+}
@@ -9,31 +9,81 @@
 //! restarted from the graphical session (issue #32). The bus name is present
 //! whenever KWin is running, and the watcher updates the flag live so a KWin
 //! restart is reflected without restarting the daemon.
+//!
+//! The same watcher asks KWin whether its blur effect is loaded, at startup and
+//! after every change of KWin's bus name, so the menu payload can tell the
+//! overlay to paint a more opaque background when nothing blurs behind it.
+//!
+//! It is the one place that follows KWin restarts. A new owner of the name
+//! ([`KWinLifecycle`]) re-runs everything a KWin instance loses: the blur
+//! query, the resident helper script (`KWinHelper::reattach`), the standalone
+//! active-window script when the window tracker uses KWin
+//! (`window_tracker::attach_kwin`), and the desktop tracker's proxies, which
+//! re-subscribe when [`KWinSession::subscribe`] reports a restart. Screen
+//! bounds are queried per press, so they need nothing. Both scripts report the
+//! active window as they load, which refreshes the focused profile.
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
 
+use crate::kwin_script::KWinHelper;
 use tokio_stream::StreamExt;
 
 /// The well-known bus name KWin owns while it is running.
-const KWIN_BUS_NAME: &str = "org.kde.KWin";
+pub(crate) const KWIN_BUS_NAME: &str = "org.kde.KWin";
+
+const KWIN_EFFECTS_PATH: &str = "/Effects";
+const KWIN_EFFECTS_INTERFACE: &str = "org.kde.kwin.Effects";
+
+/// A restarted KWin can own its name before `/Effects` is exported
+const BLUR_QUERY_ATTEMPTS: u32 = 3;
+const BLUR_QUERY_RETRY: Duration = Duration::from_secs(1);
 
-/// Shared, live "is KWin available?" flag. Cheap to clone (an `Arc`) and
-/// lock-free to read on the input hot path.
-#[derive(Clone, Default)]
-pub struct KWinAvailability(Arc<AtomicBool>);
+/// Whether KWin's blur effect is loaded (false without KWin), shared
+/// between the KWin watcher and the payload builders
+#[derive(Debug, Clone, Default)]
+pub struct BlurAvailability(Arc<AtomicBool>);
+
+impl BlurAvailability {
+    /// Whether the compositor blurs behind the menu, as of the last check
+    pub fn is_available(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    /// Record the latest check, returning the previous value
+    fn replace(&self, loaded: bool) -> bool {
+        self.0.swap(loaded, Ordering::AcqRel)
+    }
+}
+
+/// Shared, live "is KWin available?" flag, with the resident helper loaded
+/// into it. Cheap to clone (`Arc`s) and lock-free to read on the input hot
+/// path.
+#[derive(Debug, Clone, Default)]
+pub struct KWinAvailability {
+    owned: Arc<AtomicBool>,
+    helper: KWinHelper,
+}
 
 impl KWinAvailability {
     pub fn new() -> Self {
-        Self(Arc::new(AtomicBool::new(false)))
+        Self::default()
     }
 
     pub fn set_owned(&self, owned: bool) {
-        self.0.store(owned, Ordering::Release);
+        self.owned.store(owned, Ordering::Release);
     }
 
     pub fn is_owned(&self) -> bool {
-        self.0.load(Ordering::Acquire)
+        self.owned.load(Ordering::Acquire)
+    }
+
+    /// The resident helper script, while this KWin runs it
+    pub fn helper(&self) -> &KWinHelper {
+        &self.helper
     }
 }
 
@@ -42,25 +92,127 @@ impl KWinAvailability {
 pub enum CursorBackend {
     /// Query KWin (accurate multi-monitor Wayland cursor) via its D-Bus script.
     KWin,
-    /// Direct cursor-query cascade (GNOME/Hyprland/Sway/COSMIC/X11/fallback).
+    /// Direct cursor-query cascade (the KWin helper's last report, then
+    /// GNOME/Hyprland/Sway/COSMIC/X11/fallback).
     Fallback,
 }
 
 /// Pure routing decision, kept out of the D-Bus code so it is unit-testable.
-pub fn cursor_backend(kwin_owned: bool) -> CursorBackend {
-    if kwin_owned {
+///
+/// A resident KWin helper that has reported the cursor makes the one-shot
+/// script unnecessary (`KWinHelper::cursor`).
+pub fn cursor_backend(kwin_owned: bool, helper_reporting: bool) -> CursorBackend {
+    if kwin_owned && !helper_reporting {
         CursorBackend::KWin
     } else {
         CursorBackend::Fallback
     }
 }
 
-/// Keep `availability` in sync with `org.kde.KWin` ownership for the life of the
-/// connection. Initializes from `NameHasOwner`, then follows `NameOwnerChanged`
-/// (filtered to KWin) and re-queries on each change so a KWin restart is
-/// reflected. Best-effort: on a D-Bus error it logs and keeps the last known
-/// state rather than forcing the flag to false.
-pub async fn run_kwin_watcher(connection: zbus::Connection, availability: KWinAvailability) {
+/// What a change of `org.kde.KWin`'s owner means for the daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerChange {
+    /// KWin was running when the daemon started
+    Attached,
+    /// A new KWin took the name after startup (a restart, or a first start
+    /// after the daemon's)
+    Reattached,
+    /// KWin went away
+    Lost,
+    /// Nothing the daemon acts on
+    Unchanged,
+}
+
+/// Tracks the owner of `org.kde.KWin` across restarts
+#[derive(Debug, Default)]
+pub struct KWinLifecycle {
+    /// Unique name of the current owner
+    owner: Option<String>,
+    /// The startup check has been seen
+    started: bool,
+}
+
+impl KWinLifecycle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the current owner (`None` = unowned); the first call is the
+    /// startup check
+    pub fn observe(&mut self, owner: Option<&str>) -> OwnerChange {
+        let first = !std::mem::replace(&mut self.started, true);
+        if owner == self.owner.as_deref() {
+            return OwnerChange::Unchanged;
+        }
+        self.owner = owner.map(str::to_string);
+        match (owner, first) {
+            (None, _) => OwnerChange::Lost,
+            (Some(_), true) => OwnerChange::Attached,
+            (Some(_), false) => OwnerChange::Reattached,
+        }
+    }
+}
+
+/// Everything the KWin watcher keeps in step with KWin
+#[derive(Clone)]
+pub struct KWinSession {
+    availability: KWinAvailability,
+    blur: BlurAvailability,
+    /// Bumped on every reattach; the desktop tracker re-creates its proxies
+    restarts: Arc<watch::Sender<u64>>,
+    /// The window tracker relies on KWin scripts for the active window
+    tracks_windows: bool,
+}
+
+impl KWinSession {
+    pub fn new(availability: KWinAvailability, blur: BlurAvailability, tracks_windows: bool) -> Self {
+        Self {
+            availability,
+            blur,
+            restarts: Arc::new(watch::Sender::new(0)),
+            tracks_windows,
+        }
+    }
+
+    /// Changes each time a new KWin is attached after startup
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.restarts.subscribe()
+    }
+
+    /// Bring the daemon's KWin state in line with `change`
+    async fn apply(&self, connection: &zbus::Connection, change: OwnerChange) {
+        let owned = match change {
+            OwnerChange::Unchanged => return,
+            OwnerChange::Lost => false,
+            OwnerChange::Attached | OwnerChange::Reattached => true,
+        };
+        self.availability.set_owned(owned);
+        refresh_blur(connection, &self.blur, owned).await;
+        self.availability.helper().reattach(connection, owned).await;
+        if owned && self.tracks_windows {
+            crate::window_tracker::attach_kwin(connection, self.availability.helper()).await;
+        }
+        match change {
+            OwnerChange::Reattached => {
+                self.restarts.send_modify(|n| *n += 1);
+                tracing::info!(
+                    helper = self.availability.helper().is_active(),
+                    blur_available = self.blur.is_available(),
+                    "KWin restarted, reattached"
+                );
+            }
+            OwnerChange::Lost => tracing::info!("KWin went away; using the non-KWin fallbacks until it returns"),
+            _ => {}
+        }
+    }
+}
+
+/// Keep the daemon attached to `org.kde.KWin` for the life of the connection
+///
+/// Reads the owner at startup, then follows `NameOwnerChanged` (filtered to
+/// KWin) and re-attaches on every new owner. Best-effort: on a D-Bus error it
+/// logs and keeps the last known state rather than forcing the flag to false.
+pub async fn run_kwin_watcher(connection: zbus::Connection, session: KWinSession) {
     let proxy = match zbus::fdo::DBusProxy::new(&connection).await {
         Ok(p) => p,
         Err(e) => {
@@ -77,38 +229,72 @@ pub async fn run_kwin_watcher(connection: zbus::Connection, availability: KWinAv
         }
     };
 
-    match proxy.name_has_owner(kwin.clone()).await {
-        Ok(owned) => {
-            availability.set_owned(owned);
-            tracing::info!(
-                kwin_owned = owned,
-                "Initial KWin availability (D-Bus capability)"
-            );
+    // Server-side filter to NameOwnerChanged where arg0 (the name) is KWin;
+    // subscribed before the first check so a KWin start in between is seen
+    let stream = proxy
+        .receive_name_owner_changed_with_args(&[(0u8, KWIN_BUS_NAME)])
+        .await;
+
+    let mut lifecycle = KWinLifecycle::new();
+    let owner = match proxy.get_name_owner(kwin).await {
+        Ok(owner) => Some(owner.to_string()),
+        Err(zbus::fdo::Error::NameHasNoOwner(_)) => None,
+        Err(e) => {
+            tracing::warn!(error = %e, "KWin watcher: initial GetNameOwner failed");
+            None
         }
-        Err(e) => tracing::warn!(error = %e, "KWin watcher: initial NameHasOwner failed"),
-    }
+    };
+    tracing::info!(kwin_owned = owner.is_some(), "Initial KWin availability (D-Bus capability)");
+    session.apply(&connection, lifecycle.observe(owner.as_deref())).await;
 
-    // Server-side filter to NameOwnerChanged where arg0 (the name) is KWin.
-    let mut stream = match proxy
-        .receive_name_owner_changed_with_args(&[(0u8, KWIN_BUS_NAME)])
-        .await
-    {
+    let mut stream = match stream {
         Ok(s) => s,
         Err(e) => {
             tracing::warn!(error = %e, "KWin watcher: could not watch NameOwnerChanged; KWin state is point-in-time only");
             return;
         }
     };
+    while let Some(signal) = stream.next().await {
+        let owner = match signal.args() {
+            Ok(args) => args.new_owner().as_ref().map(|o| o.to_string()),
+            Err(e) => {
+                tracing::warn!(error = %e, "KWin watcher: unreadable NameOwnerChanged");
+                continue;
+            }
+        };
+        tracing::debug!(kwin_owned = owner.is_some(), "KWin name owner changed");
+        session.apply(&connection, lifecycle.observe(owner.as_deref())).await;
+    }
+}
 
-    while stream.next().await.is_some() {
-        match proxy.name_has_owner(kwin.clone()).await {
-            Ok(owned) => {
-                availability.set_owned(owned);
-                tracing::info!(kwin_owned = owned, "KWin availability changed");
+/// Re-check the blur effect after KWin (re)appears; no KWin means no blur
+async fn refresh_blur(connection: &zbus::Connection, blur: &BlurAvailability, kwin_owned: bool) {
+    let mut loaded = false;
+    if kwin_owned {
+        for attempt in 1..=BLUR_QUERY_ATTEMPTS {
+            match query_blur_loaded(connection).await {
+                Ok(l) => {
+                    loaded = l;
+                    break;
+                }
+                Err(e) if attempt < BLUR_QUERY_ATTEMPTS => {
+                    tracing::debug!(error = %e, attempt, "KWin blur query failed, retrying");
+                    tokio::time::sleep(BLUR_QUERY_RETRY).await;
+                }
+                Err(e) => tracing::warn!(error = %e, "KWin blur query failed; assuming no blur"),
             }
-            Err(e) => tracing::warn!(error = %e, "KWin watcher: NameHasOwner re-query failed"),
         }
     }
+    let previous = blur.replace(loaded);
+    if previous != loaded {
+        tracing::info!(blur_available = loaded, "Compositor blur availability changed");
+    }
+}
+
+/// `org.kde.kwin.Effects.isEffectLoaded("blur")`
+async fn query_blur_loaded(connection: &zbus::Connection) -> zbus::Result<bool> {
+    let proxy = zbus::Proxy::new(connection, KWIN_BUS_NAME, KWIN_EFFECTS_PATH, KWIN_EFFECTS_INTERFACE).await?;
+    proxy.call("isEffectLoaded", &("blur",)).await
 }
 
 #[cfg(test)]
@@ -117,12 +303,17 @@ mod tests {
 
     #[test]
     fn kwin_owner_selects_kwin_backend() {
-        assert_eq!(cursor_backend(true), CursorBackend::KWin);
+        assert_eq!(cursor_backend(true, false), CursorBackend::KWin);
+    }
+
+    #[test]
+    fn reporting_helper_skips_the_one_shot_script() {
+        assert_eq!(cursor_backend(true, true), CursorBackend::Fallback);
     }
 
     #[test]
     fn no_owner_uses_fallback() {
-        assert_eq!(cursor_backend(false), CursorBackend::Fallback);
+        assert_eq!(cursor_backend(false, false), CursorBackend::Fallback);
     }
 
     #[test]
@@ -148,4 +339,24 @@ mod tests {
         a.set_owned(true);
         assert!(b.is_owned());
     }
+
+    #[test]
+    fn startup_owner_attaches_and_a_new_owner_reattaches() {
+        let mut kwin = KWinLifecycle::new();
+        assert_eq!(kwin.observe(Some(":1.20")), OwnerChange::Attached);
+        assert_eq!(kwin.observe(Some(":1.20")), OwnerChange::Unchanged);
+        // KWin crashes, then its replacement takes the name
+        assert_eq!(kwin.observe(None), OwnerChange::Lost);
+        assert_eq!(kwin.observe(None), OwnerChange::Unchanged);
+        assert_eq!(kwin.observe(Some(":1.87")), OwnerChange::Reattached);
+        // `kwin_wayland --replace` hands the name over without a gap
+        assert_eq!(kwin.observe(Some(":1.90")), OwnerChange::Reattached);
+    }
+
+    #[test]
+    fn kwin_starting_after_the_daemon_reattaches() {
+        let mut kwin = KWinLifecycle::new();
+        assert_eq!(kwin.observe(None), OwnerChange::Unchanged);
+        assert_eq!(kwin.observe(Some(":1.5")), OwnerChange::Reattached);
+    }
 }
@@ -4,16 +4,18 @@
 //! Configuration is stored at `~/.config/juhradial/config.json`.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::config_schema;
+use crate::fs_util;
+use crate::geometry::MenuSize;
+
 // ============================================================================
 // Constants
 // ============================================================================
 
-/// Default config directory name
-const CONFIG_DIR: &str = "juhradial";
-
 /// Default config file name
 const CONFIG_FILE: &str = "config.json";
 
@@ -40,6 +42,22 @@ pub struct HapticEventConfig {
     /// Pattern for invalid/blocked actions (default: angry_alert)
     #[serde(default = "default_invalid")]
     pub invalid: String,
+
+    /// Play the menu appear haptic (default: true)
+    #[serde(default = "default_true")]
+    pub menu_appear_enabled: bool,
+
+    /// Play slice change haptics (default: true)
+    #[serde(default = "default_true")]
+    pub slice_change_enabled: bool,
+
+    /// Play the selection confirm haptic (default: true)
+    #[serde(default = "default_true")]
+    pub confirm_enabled: bool,
+
+    /// Play the invalid action haptic (default: true)
+    #[serde(default = "default_true")]
+    pub invalid_enabled: bool,
 }
 
 fn default_menu_appear() -> String { "damp_state_change".to_string() }
@@ -54,6 +72,10 @@ impl Default for HapticEventConfig {
             slice_change: default_slice_change(),
             confirm: default_confirm(),
             invalid: default_invalid(),
+            menu_appear_enabled: true,
+            slice_change_enabled: true,
+            confirm_enabled: true,
+            invalid_enabled: true,
         }
     }
 }
@@ -93,13 +115,44 @@ pub struct HapticConfig {
     /// Prevents duplicate haptic when cursor re-enters the same slice quickly
     #[serde(default = "default_reentry_debounce")]
     pub reentry_debounce_ms: u64,
+
+    /// Named custom patterns; `per_event` entries may refer to them by name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub patterns: HashMap<String, Vec<HapticStepConfig>>,
+
+    /// Precede the first pulse after this long without device traffic with a
+    /// wake ping (reply not awaited); 0 = never
+    #[serde(default = "default_wake_ping_idle")]
+    pub wake_ping_idle_ms: u64,
 }
 
+/// One step of a custom haptic pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HapticStepConfig {
+    /// Multiplier on the base pulse intensity (legacy haptic devices)
+    #[serde(default = "default_intensity_scale")]
+    pub intensity_scale: f32,
+
+    /// Pulse length in milliseconds
+    pub duration_ms: u16,
+
+    /// Pause before the next step in milliseconds
+    #[serde(default)]
+    pub gap_after_ms: u16,
+
+    /// MX Master 4 waveform for this step (default: the event's built-in waveform)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waveform: Option<String>,
+}
+
+fn default_intensity_scale() -> f32 { 1.0 }
+
 fn default_true() -> bool { true }
 fn default_pattern() -> String { "subtle_collision".to_string() }
 fn default_debounce() -> u64 { 20 }
 fn default_slice_debounce() -> u64 { 20 }
 fn default_reentry_debounce() -> u64 { 50 }
+fn default_wake_ping_idle() -> u64 { 30_000 }
 
 impl Default for HapticConfig {
     fn default() -> Self {
@@ -110,6 +163,8 @@ impl Default for HapticConfig {
             debounce_ms: 20,
             slice_debounce_ms: 20,
             reentry_debounce_ms: 50,
+            patterns: HashMap::new(),
+            wake_ping_idle_ms: default_wake_ping_idle(),
         }
     }
 }
@@ -124,79 +179,18 @@ impl HapticConfig {
     pub fn is_disabled(&self) -> bool {
         !self.enabled
     }
+
+    /// Idle time before a wake ping, `None` when off
+    pub fn wake_ping_idle(&self) -> Option<std::time::Duration> {
+        (self.wake_ping_idle_ms > 0).then(|| std::time::Duration::from_millis(self.wake_ping_idle_ms))
+    }
 }
 
 // ============================================================================
 // Button Action Configuration
 // ============================================================================
 
-/// Actions that can be assigned to mouse buttons.
-/// These match the action IDs written by the Python Settings UI.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ButtonAction {
-    RadialMenu,
-    VirtualDesktops,
-    MiddleClick,
-    Back,
-    Forward,
-    Copy,
-    Paste,
-    Undo,
-    Redo,
-    Screenshot,
-    Smartshift,
-    ScrollLeftRight,
-    VolumeUp,
-    VolumeDown,
-    PlayPause,
-    Mute,
-    ZoomIn,
-    ZoomOut,
-    ShowDesktop,
-    SwitchDesktopLeft,
-    SwitchDesktopRight,
-    TaskSwitcher,
-    CloseWindow,
-    LockScreen,
-    Calculator,
-    None,
-    Custom,
-}
-
-impl std::fmt::Display for ButtonAction {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ButtonAction::RadialMenu => write!(f, "radial_menu"),
-            ButtonAction::VirtualDesktops => write!(f, "virtual_desktops"),
-            ButtonAction::MiddleClick => write!(f, "middle_click"),
-            ButtonAction::Back => write!(f, "back"),
-            ButtonAction::Forward => write!(f, "forward"),
-            ButtonAction::Copy => write!(f, "copy"),
-            ButtonAction::Paste => write!(f, "paste"),
-            ButtonAction::Undo => write!(f, "undo"),
-            ButtonAction::Redo => write!(f, "redo"),
-            ButtonAction::Screenshot => write!(f, "screenshot"),
-            ButtonAction::Smartshift => write!(f, "smartshift"),
-            ButtonAction::ScrollLeftRight => write!(f, "scroll_left_right"),
-            ButtonAction::VolumeUp => write!(f, "volume_up"),
-            ButtonAction::VolumeDown => write!(f, "volume_down"),
-            ButtonAction::PlayPause => write!(f, "play_pause"),
-            ButtonAction::Mute => write!(f, "mute"),
-            ButtonAction::ZoomIn => write!(f, "zoom_in"),
-            ButtonAction::ZoomOut => write!(f, "zoom_out"),
-            ButtonAction::ShowDesktop => write!(f, "show_desktop"),
-            ButtonAction::SwitchDesktopLeft => write!(f, "switch_desktop_left"),
-            ButtonAction::SwitchDesktopRight => write!(f, "switch_desktop_right"),
-            ButtonAction::TaskSwitcher => write!(f, "task_switcher"),
-            ButtonAction::CloseWindow => write!(f, "close_window"),
-            ButtonAction::LockScreen => write!(f, "lock_screen"),
-            ButtonAction::Calculator => write!(f, "calculator"),
-            ButtonAction::None => write!(f, "none"),
-            ButtonAction::Custom => write!(f, "custom"),
-        }
-    }
-}
+pub use juhradial_core::buttons::{ButtonAction, ThumbwheelMode};
 
 fn default_gesture_action() -> ButtonAction { ButtonAction::VirtualDesktops }
 fn default_thumb_action() -> ButtonAction { ButtonAction::RadialMenu }
@@ -250,21 +244,6 @@ impl Default for ButtonsConfig {
 // Thumb-Wheel Configuration
 // ============================================================================
 
-/// What a thumb-wheel rotation should do.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
-#[serde(rename_all = "snake_case")]
-pub enum ThumbwheelMode {
-    /// Thumb wheel keeps its native behaviour (not diverted).
-    #[default]
-    Off,
-    /// Rotation adjusts system volume.
-    Volume,
-    /// Rotation scrolls horizontally.
-    Scroll,
-    /// Rotation zooms in/out (Ctrl +/-).
-    Zoom,
-}
-
 /// Resolved output of a thumb-wheel rotation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ThumbwheelOutput {
@@ -288,16 +267,29 @@ pub struct ThumbwheelConfig {
     /// Repeats per rotation notification (1..=8). Higher = faster response.
     #[serde(default = "default_thumbwheel_speed")]
     pub speed: u8,
+
+    /// Honor per-profile `thumbwheel_left`/`thumbwheel_right` bindings.
+    /// Global kill-switch: `false` always passes the wheel through.
+    #[serde(default = "default_true")]
+    pub profile_mappings: bool,
+
+    /// Wheel detents accumulated per bound action (1..=16).
+    #[serde(default = "default_detents_per_action")]
+    pub detents_per_action: u8,
 }
 
 fn default_thumbwheel_speed() -> u8 { 1 }
 
+fn default_detents_per_action() -> u8 { 1 }
+
 impl Default for ThumbwheelConfig {
     fn default() -> Self {
         Self {
             mode: ThumbwheelMode::default(),
             invert: false,
             speed: default_thumbwheel_speed(),
+            profile_mappings: true,
+            detents_per_action: default_detents_per_action(),
         }
     }
 }
@@ -318,6 +310,11 @@ impl ThumbwheelConfig {
         self.speed.clamp(1, 8)
     }
 
+    /// Detents needed per profile-bound thumb-wheel action.
+    pub fn detents(&self) -> u8 {
+        self.detents_per_action.clamp(1, 16)
+    }
+
     /// Resolve a raw signed thumb-wheel delta into a directional output.
     ///
     /// Returns `None` when the wheel is off or the delta is zero. `invert` is
@@ -340,6 +337,575 @@ impl ThumbwheelConfig {
     }
 }
 
+// ============================================================================
+// Accessibility
+// ============================================================================
+
+/// Tri-state accessibility preference: follow the system or force it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessibilityPreference {
+    /// Follow the detected system setting.
+    #[default]
+    Auto,
+    /// Always on, regardless of the system setting.
+    On,
+    /// Always off, regardless of the system setting.
+    Off,
+}
+
+impl AccessibilityPreference {
+    /// User override for `AccessibilitySettings` (None = follow system).
+    pub fn as_override(self) -> Option<bool> {
+        match self {
+            AccessibilityPreference::Auto => None,
+            AccessibilityPreference::On => Some(true),
+            AccessibilityPreference::Off => Some(false),
+        }
+    }
+}
+
+/// Accessibility overrides (Story 4.6).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessibilityConfig {
+    /// Reduced motion: "auto" follows the desktop animation setting.
+    #[serde(default)]
+    pub reduced_motion: AccessibilityPreference,
+    /// High contrast: "auto" follows the desktop contrast preference.
+    #[serde(default)]
+    pub high_contrast: AccessibilityPreference,
+    /// Hover to select: a slice highlighted this long runs without the
+    /// release (0 = off).
+    #[serde(default)]
+    pub dwell_confirm_ms: u64,
+    /// Per-slice fill colors: "off", "theme" (the theme's `sliceColors`)
+    /// or "auto" (tints generated from the accent).
+    #[serde(default)]
+    pub slice_tinting: crate::slice_tint::SliceTinting,
+}
+
+impl AccessibilityConfig {
+    /// Dwell before a highlighted slice confirms, `None` when off
+    ///
+    /// Raised to [`MIN_DWELL_CONFIRM`](crate::dwell_confirm::MIN_DWELL_CONFIRM).
+    pub fn dwell_confirm(&self) -> Option<std::time::Duration> {
+        (self.dwell_confirm_ms > 0).then(|| {
+            std::time::Duration::from_millis(self.dwell_confirm_ms).max(crate::dwell_confirm::MIN_DWELL_CONFIRM)
+        })
+    }
+}
+
+// ============================================================================
+// Action Execution Policy
+// ============================================================================
+
+/// Policy for Command and KWin actions loaded from profiles.json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandPolicy {
+    /// Run every command (historical behavior).
+    #[default]
+    Unrestricted,
+    /// Refuse Command/KWin actions until the current profiles.json is approved
+    /// via the `ApproveProfile` D-Bus method.
+    Confirm,
+    /// Only commands whose argv is allowlisted, run without a shell.
+    Allowlist,
+}
+
+/// Execution policy for profile actions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionConfig {
+    /// Which policy applies to Command/KWin actions.
+    #[serde(default)]
+    pub policy: CommandPolicy,
+
+    /// Absolute binary paths allowed in `allowlist` mode, with any arguments.
+    #[serde(default)]
+    pub allowed_binaries: Vec<PathBuf>,
+
+    /// Exact argv lists allowed in `allowlist` mode.
+    #[serde(default)]
+    pub allowed_commands: Vec<Vec<String>>,
+
+    /// Most Command actions started in any minute (0 = no limit).
+    #[serde(default)]
+    pub max_commands_per_minute: u32,
+
+    /// Inside a Flatpak sandbox, run commands and helpers on the host with
+    /// `flatpak-spawn --host` (read at startup).
+    #[serde(default = "default_true")]
+    pub host_spawn: bool,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            policy: CommandPolicy::default(),
+            allowed_binaries: Vec::new(),
+            allowed_commands: Vec::new(),
+            max_commands_per_minute: 0,
+            host_spawn: true,
+        }
+    }
+}
+
+// ============================================================================
+// Overlay Supervision
+// ============================================================================
+
+/// Overlay process supervision settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayConfig {
+    /// Spawn (or adopt) the overlay and restart it after a crash.
+    #[serde(default = "default_true")]
+    pub supervise: bool,
+
+    /// Overlay entry point. Auto-detected from the install locations when unset.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            supervise: true,
+            path: None,
+        }
+    }
+}
+
+// ============================================================================
+// Install Paths
+// ============================================================================
+
+/// Install location overrides for packaged builds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathsConfig {
+    /// Data directory (themes, assets). `$JUHRADIAL_DATA_DIR` takes precedence;
+    /// when neither is set, `$XDG_DATA_DIRS` and then `/usr/share/juhradial`.
+    /// Read once at startup.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+}
+
+// ============================================================================
+// Debugging
+// ============================================================================
+
+/// Diagnostics for bug reports.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DebugConfig {
+    /// Log every HID++ report to ~/.local/share/juhradial/hidpp-trace.log.
+    /// SIGUSR2 toggles it at runtime.
+    #[serde(default)]
+    pub hidpp_trace: bool,
+}
+
+// ============================================================================
+// Menu Behaviour
+// ============================================================================
+
+/// Radial menu behaviour handled by the daemon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuConfig {
+    /// Cancel an open menu after this many milliseconds without activity
+    /// (0 = never; values below 1000 are raised to 1000).
+    #[serde(default)]
+    pub auto_dismiss_ms: u64,
+
+    /// Flash the slice in the theme's error color when its action fails to
+    /// start, holding the menu open briefly after release.
+    #[serde(default = "default_true")]
+    pub action_feedback: bool,
+
+    /// Also flash the success color when an action starts (needs `action_feedback`).
+    #[serde(default)]
+    pub success_flash: bool,
+
+    /// Minimum menu background opacity when the compositor does not blur
+    /// behind the menu (0.0-1.0).
+    #[serde(default = "default_no_blur_min_opacity")]
+    pub no_blur_min_opacity: f32,
+
+    /// Show a slice's description after its highlight has rested this many
+    /// milliseconds (0 = never).
+    #[serde(default = "default_hover_help_ms")]
+    pub hover_help_ms: u64,
+
+    /// Resolve a slice's action once its highlight has rested this many
+    /// milliseconds, so the release only spawns (0 = never).
+    #[serde(default = "default_prewarm_ms")]
+    pub prewarm_ms: u64,
+
+    /// Most wheel steps a held `wheel_axis` slice runs per second; faster
+    /// detents are dropped (0 is read as 1).
+    #[serde(default = "default_wheel_axis_per_second")]
+    pub wheel_axis_per_second: u32,
+
+    /// Crossfade an open menu to a newly selected theme over this many
+    /// milliseconds (0 = switch at once; always 0 under reduced motion).
+    #[serde(default = "default_theme_transition_ms")]
+    pub theme_transition_ms: u32,
+
+    /// Time allowed for the second press on a `require_confirmation` slice
+    /// before the menu closes without running it.
+    #[serde(default = "default_confirm_window_ms")]
+    pub confirm_window_ms: u64,
+
+    /// Swap an open menu to the profile of a focus report that arrives this
+    /// many milliseconds after the press at most (0 = never).
+    #[serde(default = "default_late_focus_ms")]
+    pub late_focus_ms: u64,
+
+    /// A newly focused window switches the profile only once focus has
+    /// stayed on it this many milliseconds (0 = at once); a menu press always
+    /// uses the window focused at that moment.
+    #[serde(default = "default_focus_settle_ms")]
+    pub focus_settle_ms: u64,
+
+    /// Slice labels wider than this many cells get a shortened form in the
+    /// payload (0 = never shorten; below 4 is read as 4).
+    #[serde(default = "default_label_max_cells")]
+    pub label_max_cells: u32,
+
+    /// Ring size: "auto" (the overlay scales with the monitor height),
+    /// pixels at scale 1, or a percentage of the monitor's shorter edge
+    /// ("22%"). A profile's `menu_size` overrides it.
+    #[serde(default)]
+    pub size: MenuSize,
+
+    /// Flick mode: a fast flick of the held trigger runs the slice in that
+    /// direction without drawing the menu (see `crate::flick`).
+    #[serde(default)]
+    pub flick: bool,
+
+    /// Pointer travel from the press point that makes a flick, in pixels.
+    #[serde(default = "default_flick_distance_px")]
+    pub flick_distance_px: u32,
+
+    /// A flick must be released within this many milliseconds of the press;
+    /// the menu opens once it passes.
+    #[serde(default = "default_flick_window_ms")]
+    pub flick_window_ms: u64,
+}
+
+fn default_no_blur_min_opacity() -> f32 {
+    crate::menu_payload::NO_BLUR_MIN_OPACITY
+}
+
+fn default_hover_help_ms() -> u64 {
+    crate::hover_help::HOVER_HELP_MS
+}
+
+fn default_prewarm_ms() -> u64 {
+    crate::prewarm::PREWARM_MS
+}
+
+fn default_wheel_axis_per_second() -> u32 {
+    crate::wheel_axis::MAX_PER_SECOND
+}
+
+fn default_theme_transition_ms() -> u32 {
+    crate::theme_transition::TRANSITION_MS
+}
+
+fn default_confirm_window_ms() -> u64 {
+    crate::slice_confirmation::CONFIRM_WINDOW_MS
+}
+
+fn default_late_focus_ms() -> u64 {
+    crate::late_focus::LATE_FOCUS_MS
+}
+
+fn default_focus_settle_ms() -> u64 {
+    crate::window_tracker::settle::FOCUS_SETTLE_MS
+}
+
+fn default_label_max_cells() -> u32 {
+    crate::label::LABEL_BUDGET_CELLS as u32
+}
+
+fn default_flick_distance_px() -> u32 {
+    crate::flick::FLICK_DISTANCE_PX
+}
+
+fn default_flick_window_ms() -> u64 {
+    crate::flick::FLICK_WINDOW_MS
+}
+
+impl Default for MenuConfig {
+    fn default() -> Self {
+        Self {
+            auto_dismiss_ms: 0,
+            action_feedback: true,
+            success_flash: false,
+            no_blur_min_opacity: default_no_blur_min_opacity(),
+            hover_help_ms: default_hover_help_ms(),
+            prewarm_ms: default_prewarm_ms(),
+            wheel_axis_per_second: default_wheel_axis_per_second(),
+            theme_transition_ms: default_theme_transition_ms(),
+            confirm_window_ms: default_confirm_window_ms(),
+            late_focus_ms: default_late_focus_ms(),
+            focus_settle_ms: default_focus_settle_ms(),
+            label_max_cells: default_label_max_cells(),
+            size: MenuSize::Auto,
+            flick: false,
+            flick_distance_px: default_flick_distance_px(),
+            flick_window_ms: default_flick_window_ms(),
+        }
+    }
+}
+
+impl MenuConfig {
+    /// Auto-dismiss timeout, `None` when disabled
+    pub fn auto_dismiss(&self) -> Option<std::time::Duration> {
+        (self.auto_dismiss_ms > 0).then(|| std::time::Duration::from_millis(self.auto_dismiss_ms))
+    }
+
+    /// Hover delay before a slice description shows, `None` when disabled
+    pub fn hover_help(&self) -> Option<std::time::Duration> {
+        (self.hover_help_ms > 0).then(|| std::time::Duration::from_millis(self.hover_help_ms))
+    }
+
+    /// Highlight dwell before its action is pre-warmed, `None` when disabled
+    pub fn prewarm(&self) -> Option<std::time::Duration> {
+        (self.prewarm_ms > 0).then(|| std::time::Duration::from_millis(self.prewarm_ms))
+    }
+
+    /// Time allowed for a confirming second press
+    pub fn confirm_window(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.confirm_window_ms)
+    }
+
+    /// How long after the press a focus report may still swap the profile
+    pub fn late_focus(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.late_focus_ms)
+    }
+
+    /// How long focus must stay on a window before its profile applies
+    pub fn focus_settle(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.focus_settle_ms)
+    }
+
+    /// Flick detection settings, `None` when flick mode is off or either
+    /// threshold is 0
+    pub fn flick(&self) -> Option<crate::flick::FlickSettings> {
+        (self.flick && self.flick_distance_px > 0 && self.flick_window_ms > 0).then(|| crate::flick::FlickSettings {
+            distance: f64::from(self.flick_distance_px),
+            window: std::time::Duration::from_millis(self.flick_window_ms),
+        })
+    }
+
+    /// Label width budget in cells, `None` when shortening is off
+    pub fn label_budget(&self) -> Option<usize> {
+        (self.label_max_cells > 0).then(|| (self.label_max_cells as usize).max(crate::label::MIN_LABEL_BUDGET_CELLS))
+    }
+}
+
+// ============================================================================
+// Input
+// ============================================================================
+
+/// Pointer ergonomics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputConfig {
+    /// Mirror the ring across the vertical axis (NE ↔ NW, E ↔ W) without
+    /// editing profiles; see `juhradial_core::geometry::slot_for_position`.
+    #[serde(default)]
+    pub left_handed: bool,
+    /// Scanning for non-MX mice (read at startup)
+    #[serde(default)]
+    pub generic_mouse: GenericMouseMode,
+}
+
+/// Whether the daemon looks for a generic (non-MX) mouse.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GenericMouseMode {
+    /// Scan, backing off from 2s to 30s while none is found; hotplug still
+    /// re-scans at once.
+    #[default]
+    Auto,
+    /// Never scan for or open a generic mouse (MX-only setups).
+    Off,
+    /// Scan every 2s while none is found.
+    Require,
+}
+
+// ============================================================================
+// Menu Triggers
+// ============================================================================
+
+/// One way to open the radial menu. Every trigger drives the same menu with
+/// the same hold/confirm semantics; see `crate::menu_trigger`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "kebab-case")]
+pub enum MenuTrigger {
+    /// The MX buttons assigned `radial_menu` in `buttons` (HID++ divert, or
+    /// the evdev fallback when diversion is unavailable)
+    MxGestureButton,
+    /// A button code (e.g. 0x113 = BTN_SIDE) on any pointer device
+    EvdevButton { code: u16 },
+    /// A KDE global shortcut registered with KGlobalAccel, e.g. `Meta+Space`
+    Shortcut { keys: String },
+}
+
+fn default_triggers() -> Vec<MenuTrigger> {
+    vec![MenuTrigger::MxGestureButton]
+}
+
+// ============================================================================
+// Profile Lint
+// ============================================================================
+
+/// Risky-shortcut lint for profiles (`profile_lint`); see
+/// `juhradial_core::shortcut_lint`.
+pub use juhradial_core::shortcut_lint::{RiskyShortcut, ShortcutLintRules};
+
+// ============================================================================
+// Battery
+// ============================================================================
+
+/// Battery poller logging.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatteryConfig {
+    /// Log one info-level battery summary this often (0 = never). Individual
+    /// polls only log at trace level. Read once at startup.
+    #[serde(default = "default_summary_interval_minutes")]
+    pub summary_interval_minutes: u64,
+    /// Unanswered polls in a row before the battery shows as unavailable. A
+    /// sleeping mouse misses a poll or two; other failures count at once.
+    #[serde(default = "default_unavailable_after_timeouts")]
+    pub unavailable_after_timeouts: u32,
+}
+
+fn default_summary_interval_minutes() -> u64 { 15 }
+
+fn default_unavailable_after_timeouts() -> u32 { crate::battery::UNAVAILABLE_AFTER_TIMEOUTS }
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            summary_interval_minutes: default_summary_interval_minutes(),
+            unavailable_after_timeouts: default_unavailable_after_timeouts(),
+        }
+    }
+}
+
+impl BatteryConfig {
+    /// Summary cadence, `None` when disabled
+    pub fn summary_interval(&self) -> Option<std::time::Duration> {
+        (self.summary_interval_minutes > 0)
+            .then(|| std::time::Duration::from_secs(self.summary_interval_minutes * 60))
+    }
+
+    /// Timeout threshold; 0 behaves as 1
+    pub fn unavailable_after(&self) -> u32 {
+        self.unavailable_after_timeouts.max(1)
+    }
+}
+
+// ============================================================================
+// Colors
+// ============================================================================
+
+/// Theme color overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorsConfig {
+    /// Draw the menu in the desktop accent color instead of the theme's;
+    /// colors the theme derives from its accent follow. See `system_accent`.
+    #[serde(default = "default_true")]
+    pub accent_follow_system: bool,
+}
+
+impl Default for ColorsConfig {
+    fn default() -> Self {
+        Self { accent_follow_system: true }
+    }
+}
+
+// ============================================================================
+// Idle Effects
+// ============================================================================
+
+/// Limits on the theme's idle animation (matrix rain, particles)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleEffectsConfig {
+    /// Allow idle effects at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Frame rate cap for the effect (1-60)
+    #[serde(default = "default_idle_max_fps")]
+    pub max_fps: u8,
+
+    /// Density cap for the effect (0.0-1.0)
+    #[serde(default = "default_idle_max_density")]
+    pub max_density: f32,
+
+    /// Turn idle effects off while the machine runs on battery (UPower)
+    #[serde(default = "default_true")]
+    pub disable_on_battery: bool,
+}
+
+fn default_idle_max_fps() -> u8 {
+    30
+}
+
+fn default_idle_max_density() -> f32 {
+    1.0
+}
+
+impl Default for IdleEffectsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_fps: default_idle_max_fps(),
+            max_density: default_idle_max_density(),
+            disable_on_battery: true,
+        }
+    }
+}
+
+impl IdleEffectsConfig {
+    /// The caps, clamped to their ranges; off when `on_battery` and
+    /// `disable_on_battery`
+    pub fn limits(&self, on_battery: bool) -> crate::theme::IdleEffectLimits {
+        let max_density = if self.max_density.is_nan() { default_idle_max_density() } else { self.max_density.clamp(0.0, 1.0) };
+        crate::theme::IdleEffectLimits {
+            enabled: self.enabled && !(on_battery && self.disable_on_battery),
+            max_fps: self.max_fps.clamp(1, 60),
+            max_density,
+        }
+    }
+}
+
+/// Times when haptics and notifications stay silent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuietHoursConfig {
+    /// Windows of local time; see `quiet_hours`
+    #[serde(default)]
+    pub schedule: Vec<crate::quiet_hours::QuietWindow>,
+}
+
+// ============================================================================
+// Local Usage Statistics
+// ============================================================================
+
+/// Opt-in local usage statistics (never leaves the machine).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// Record menu invocations to ~/.local/share/juhradial/stats.ndjson.
+    #[serde(default)]
+    pub local_stats: bool,
+
+    /// Serve Prometheus metrics on 127.0.0.1 at this port (0 = off; needs a
+    /// build with the `metrics` feature). Read at startup.
+    #[serde(default)]
+    pub metrics_port: u16,
+}
+
 // ============================================================================
 // Main Configuration
 // ============================================================================
@@ -359,6 +925,14 @@ pub struct Config {
     #[serde(default = "default_true")]
     pub blur_enabled: bool,
 
+    /// Theme color overrides (desktop accent)
+    #[serde(default)]
+    pub colors: ColorsConfig,
+
+    /// Idle animation limits
+    #[serde(default)]
+    pub idle_effects: IdleEffectsConfig,
+
     /// Button action assignments
     #[serde(default)]
     pub buttons: ButtonsConfig,
@@ -367,6 +941,54 @@ pub struct Config {
     #[serde(default)]
     pub thumbwheel: ThumbwheelConfig,
 
+    /// Accessibility overrides
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+
+    /// Execution policy for profile Command/KWin actions
+    #[serde(default)]
+    pub execution: ExecutionConfig,
+
+    /// Overlay process supervision
+    #[serde(default)]
+    pub overlay: OverlayConfig,
+
+    /// Opt-in local usage statistics
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+
+    /// Radial menu behaviour (auto-dismiss)
+    #[serde(default)]
+    pub menu: MenuConfig,
+
+    /// Pointer ergonomics (handedness)
+    #[serde(default)]
+    pub input: InputConfig,
+
+    /// Sources that open the radial menu
+    #[serde(default = "default_triggers")]
+    pub triggers: Vec<MenuTrigger>,
+
+    /// Warnings for destructive shortcuts in prime slices
+    #[serde(default)]
+    pub profile_lint: ShortcutLintRules,
+
+    /// Battery summary logging
+    #[serde(default)]
+    pub battery: BatteryConfig,
+
+    /// Quiet hours schedule
+    #[serde(default)]
+    pub quiet_hours: QuietHoursConfig,
+
+    /// Install path overrides
+    #[serde(default)]
+    pub paths: PathsConfig,
+
+    /// Diagnostics (HID++ trace)
+    #[serde(default)]
+    pub debug: DebugConfig,
+
     /// Configuration file path (not serialized)
     #[serde(skip)]
     pub config_path: Option<PathBuf>,
@@ -382,8 +1004,22 @@ impl Default for Config {
             haptics: HapticConfig::default(),
             theme: default_theme(),
             blur_enabled: true,
+            colors: ColorsConfig::default(),
+            idle_effects: IdleEffectsConfig::default(),
             buttons: ButtonsConfig::default(),
             thumbwheel: ThumbwheelConfig::default(),
+            accessibility: AccessibilityConfig::default(),
+            execution: ExecutionConfig::default(),
+            overlay: OverlayConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            menu: MenuConfig::default(),
+            input: InputConfig::default(),
+            triggers: default_triggers(),
+            profile_lint: ShortcutLintRules::default(),
+            battery: BatteryConfig::default(),
+            quiet_hours: QuietHoursConfig::default(),
+            paths: PathsConfig::default(),
+            debug: DebugConfig::default(),
             config_path: None,
         }
     }
@@ -392,7 +1028,7 @@ impl Default for Config {
 impl Config {
     /// Get the default config directory path
     pub fn default_config_dir() -> Option<PathBuf> {
-        dirs::config_dir().map(|p| p.join(CONFIG_DIR))
+        Some(crate::paths::paths().config_dir.clone())
     }
 
     /// Get the default config file path
@@ -429,6 +1065,9 @@ impl Config {
         let contents = fs::read_to_string(path).map_err(ConfigError::IoError)?;
         let mut config: Config =
             serde_json::from_str(&contents).map_err(ConfigError::ParseError)?;
+        if let Ok(raw) = serde_json::from_str(&contents) {
+            config_schema::log_issues(path, &config_schema::validate(&raw));
+        }
 
         // Validate and clamp values
         config.haptics.validate();
@@ -455,32 +1094,63 @@ impl Config {
                 .ok_or_else(|| ConfigError::ValidationError("No config path".to_string()))?,
         };
 
-        // Ensure directory exists
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(ConfigError::IoError)?;
-        }
-
         // Serialize and write
         let contents = serde_json::to_string_pretty(self).map_err(ConfigError::ParseError)?;
-        fs::write(&path, contents).map_err(ConfigError::IoError)?;
+        fs_util::write_private(&path, contents).map_err(ConfigError::IoError)?;
 
         tracing::info!(path = %path.display(), "Configuration saved");
         Ok(())
     }
 
+    /// Queue the configuration for the write-behind task, for writes from a
+    /// running daemon; [`Self::save`] stays for startup and the CLI
+    pub fn save_deferred(&self, queue: &crate::persistence::PersistQueue) -> Result<(), ConfigError> {
+        let path = match &self.config_path {
+            Some(p) => p.clone(),
+            None => Self::default_config_path()
+                .ok_or_else(|| ConfigError::ValidationError("No config path".to_string()))?,
+        };
+        let contents = serde_json::to_string_pretty(self).map_err(ConfigError::ParseError)?;
+        queue.submit(crate::persistence::PersistJob::WriteConfig {
+            path,
+            contents: contents.into_bytes(),
+        });
+        Ok(())
+    }
+
     /// Create default config file if it doesn't exist
     pub fn create_default_if_missing() -> Result<Self, ConfigError> {
-        let config = Self::load_default()?;
+        if let Some(path) = Self::default_config_path() {
+            Self::bootstrap(&path)?;
+        }
+        Self::load_default()
+    }
 
-        // Save defaults if file didn't exist
-        if let Some(path) = &config.config_path {
-            if !path.exists() {
-                config.save()?;
-                tracing::info!(path = %path.display(), "Created default configuration file");
-            }
+    /// Write every key with its default to `path` if it is missing
+    ///
+    /// Also writes `config.schema.json` next to it when missing or from
+    /// another version, and points the new config.json at it through
+    /// `$schema`. Returns whether config.json was created.
+    pub fn bootstrap(path: &Path) -> Result<bool, ConfigError> {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir).map_err(ConfigError::IoError)?;
+
+        let schema_path = dir.join(config_schema::SCHEMA_FILE);
+        if fs::read_to_string(&schema_path).ok().as_deref() != Some(config_schema::SCHEMA) {
+            fs_util::write_private(&schema_path, config_schema::SCHEMA).map_err(ConfigError::IoError)?;
+            tracing::debug!(path = %schema_path.display(), "Wrote configuration schema");
+        }
+        if path.exists() {
+            return Ok(false);
         }
 
-        Ok(config)
+        let defaults = serde_json::to_string_pretty(&Self::default()).map_err(ConfigError::ParseError)?;
+        // `$schema` first, ahead of the fields in struct order
+        let body = defaults.strip_prefix('{').unwrap_or(&defaults);
+        let contents = format!("{{\n  \"$schema\": \"./{}\",{body}\n", config_schema::SCHEMA_FILE);
+        fs_util::write_private(path, contents).map_err(ConfigError::IoError)?;
+        tracing::info!(path = %path.display(), "Created default configuration file");
+        Ok(true)
     }
 
     /// Check if haptics are enabled
@@ -529,6 +1199,44 @@ impl Config {
         cids
     }
 
+    /// Whether the MX gesture button (and buttons assigned `radial_menu`) may
+    /// open the menu
+    pub fn mx_gesture_trigger(&self) -> bool {
+        self.triggers.contains(&MenuTrigger::MxGestureButton)
+    }
+
+    /// Button codes configured as `evdev-button` triggers, in config order
+    pub fn evdev_trigger_codes(&self) -> Vec<u16> {
+        let mut codes = Vec::new();
+        for trigger in &self.triggers {
+            if let MenuTrigger::EvdevButton { code } = trigger {
+                if !codes.contains(code) {
+                    codes.push(*code);
+                }
+            }
+        }
+        codes
+    }
+
+    /// Key sequences of the `shortcut` triggers, in config order
+    pub fn shortcut_triggers(&self) -> Vec<String> {
+        self.triggers
+            .iter()
+            .filter_map(|t| match t {
+                MenuTrigger::Shortcut { keys } => Some(keys.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Profile lint rules with the prime slices mirrored for `input.left_handed`
+    pub fn shortcut_lint_rules(&self) -> ShortcutLintRules {
+        ShortcutLintRules {
+            left_handed: self.input.left_handed,
+            ..self.profile_lint.clone()
+        }
+    }
+
     /// The full set of non-gesture button CIDs the daemon may divert. Used on
     /// config reload to clear the divert for any button returned to its native
     /// default so its hardware behaviour comes back without a reconnect.
@@ -559,6 +1267,11 @@ pub fn new_shared_config() -> SharedConfig {
 
 /// Create a new shared config from file (or defaults if file doesn't exist)
 pub fn load_shared_config() -> Result<SharedConfig, ConfigError> {
+    if let Some(path) = Config::default_config_path() {
+        if let Err(e) = Config::bootstrap(&path) {
+            tracing::warn!(path = %path.display(), error = %e, "Cannot write default configuration");
+        }
+    }
     let config = Config::load_default()?;
     Ok(Arc::new(RwLock::new(config)))
 }
@@ -568,36 +1281,19 @@ pub fn load_shared_config() -> Result<SharedConfig, ConfigError> {
 // ============================================================================
 
 /// Configuration error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum ConfigError {
     /// I/O error reading/writing file
-    IoError(std::io::Error),
+    #[error("I/O error: {0}")]
+    IoError(#[source] std::io::Error),
     /// JSON parsing error
-    ParseError(serde_json::Error),
+    #[error("Parse error: {0}")]
+    ParseError(#[source] serde_json::Error),
     /// Validation error
+    #[error("Validation error: {0}")]
     ValidationError(String),
 }
 
-impl std::fmt::Display for ConfigError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ConfigError::IoError(e) => write!(f, "I/O error: {}", e),
-            ConfigError::ParseError(e) => write!(f, "Parse error: {}", e),
-            ConfigError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
-        }
-    }
-}
-
-impl std::error::Error for ConfigError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            ConfigError::IoError(e) => Some(e),
-            ConfigError::ParseError(e) => Some(e),
-            ConfigError::ValidationError(_) => None,
-        }
-    }
-}
-
 // ============================================================================
 // Tests
 // ============================================================================
@@ -614,6 +1310,32 @@ mod tests {
         assert_eq!(config.theme, "catppuccin-mocha");
     }
 
+    #[test]
+    fn test_bootstrap_writes_defaults_and_schema_into_a_fresh_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("juhradial").join(CONFIG_FILE);
+        assert!(Config::bootstrap(&path).unwrap());
+
+        let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        // Through text, as the file went: f32 defaults widen differently in to_value
+        let mut expected: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&Config::default()).unwrap()).unwrap();
+        expected["$schema"] = "./config.schema.json".into();
+        assert_eq!(written, expected);
+        assert_eq!(written["menu"]["theme_transition_ms"], 150);
+        let schema_path = dir.path().join("juhradial").join(config_schema::SCHEMA_FILE);
+        assert_eq!(fs::read_to_string(&schema_path).unwrap(), config_schema::SCHEMA);
+        assert_eq!(config_schema::validate(&written), []);
+        assert_eq!(Config::load(&path).unwrap().theme, "catppuccin-mocha");
+
+        // An existing config is left alone; a stale schema is replaced
+        fs::write(&path, r#"{"theme": "vaporwave"}"#).unwrap();
+        fs::write(&schema_path, "{}").unwrap();
+        assert!(!Config::bootstrap(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"theme": "vaporwave"}"#);
+        assert_eq!(fs::read_to_string(&schema_path).unwrap(), config_schema::SCHEMA);
+    }
+
     #[test]
     fn test_haptic_config_defaults() {
         let haptic = HapticConfig::default();
@@ -664,6 +1386,21 @@ mod tests {
         assert_eq!(config.theme, "vaporwave");
     }
 
+    #[test]
+    fn test_per_event_enabled_flags() {
+        // Configs written before the flags existed keep every event on
+        let config: Config = serde_json::from_str(r#"{"haptics": {"per_event": {"confirm": "completed"}}}"#).unwrap();
+        let per_event = &config.haptics.per_event;
+        assert!(per_event.menu_appear_enabled && per_event.slice_change_enabled);
+        assert!(per_event.confirm_enabled && per_event.invalid_enabled);
+
+        let json = r#"{"haptics": {"per_event": {"slice_change_enabled": false}}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert!(!config.haptics.per_event.slice_change_enabled);
+        assert!(config.haptics.per_event.confirm_enabled);
+        assert_eq!(config.haptics.per_event.slice_change, "subtle_collision");
+    }
+
     #[test]
     fn test_config_json_minimal() {
         // Minimal config should use all defaults
@@ -860,7 +1597,7 @@ mod tests {
 
     #[test]
     fn test_thumbwheel_invert_flips_direction() {
-        let tw = ThumbwheelConfig { mode: ThumbwheelMode::Volume, invert: true, speed: 1 };
+        let tw = ThumbwheelConfig { mode: ThumbwheelMode::Volume, invert: true, speed: 1, ..Default::default() };
         assert_eq!(tw.resolve(5), Some(ThumbwheelOutput::Button(ButtonAction::VolumeDown)));
         assert_eq!(tw.resolve(-5), Some(ThumbwheelOutput::Button(ButtonAction::VolumeUp)));
     }
@@ -883,6 +1620,15 @@ mod tests {
         assert_eq!(ThumbwheelConfig { speed: 99, ..Default::default() }.repeats(), 8);
     }
 
+    #[test]
+    fn test_thumbwheel_profile_mapping_defaults() {
+        let config: Config = serde_json::from_str(r#"{"thumbwheel": {"mode": "scroll"}}"#).unwrap();
+        assert!(config.thumbwheel.profile_mappings);
+        assert_eq!(config.thumbwheel.detents(), 1);
+        assert_eq!(ThumbwheelConfig { detents_per_action: 0, ..Default::default() }.detents(), 1);
+        assert_eq!(ThumbwheelConfig { detents_per_action: 40, ..Default::default() }.detents(), 16);
+    }
+
     #[test]
     fn test_config_thumbwheel_json_roundtrip() {
         let json = r#"{"thumbwheel": {"mode": "volume", "invert": true, "speed": 4}}"#;
@@ -899,6 +1645,166 @@ mod tests {
         assert_eq!(config.thumbwheel.mode, ThumbwheelMode::Off);
     }
 
+    #[test]
+    fn test_accessibility_reduced_motion_preference() {
+        let json = r#"{"accessibility": {"reduced_motion": "on"}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.accessibility.reduced_motion, AccessibilityPreference::On);
+        assert_eq!(config.accessibility.reduced_motion.as_override(), Some(true));
+
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.accessibility.reduced_motion.as_override(), None);
+        assert_eq!(config.accessibility.high_contrast, AccessibilityPreference::Auto);
+
+        let json = r#"{"accessibility": {"high_contrast": "off"}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.accessibility.high_contrast.as_override(), Some(false));
+        assert_eq!(AccessibilityPreference::Off.as_override(), Some(false));
+    }
+
+    #[test]
+    fn test_accessibility_dwell_confirm() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.accessibility.dwell_confirm(), None);
+
+        let json = r#"{"accessibility": {"dwell_confirm_ms": 900}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.accessibility.dwell_confirm(), Some(std::time::Duration::from_millis(900)));
+
+        let json = r#"{"accessibility": {"dwell_confirm_ms": 50}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.accessibility.dwell_confirm(), Some(crate::dwell_confirm::MIN_DWELL_CONFIRM));
+    }
+
+    #[test]
+    fn test_execution_policy_config() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.execution.policy, CommandPolicy::Unrestricted);
+
+        let json = r#"{"execution": {"policy": "allowlist", "allowed_binaries": ["/usr/bin/konsole"]}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.execution.policy, CommandPolicy::Allowlist);
+        assert_eq!(config.execution.allowed_binaries, vec![PathBuf::from("/usr/bin/konsole")]);
+        assert!(config.execution.allowed_commands.is_empty());
+        assert_eq!(config.execution.max_commands_per_minute, 0);
+
+        let json = r#"{"execution": {"allowed_commands": [["/usr/bin/systemctl", "suspend"]], "max_commands_per_minute": 20}}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.execution.allowed_commands, vec![vec!["/usr/bin/systemctl".to_string(), "suspend".to_string()]]);
+        assert_eq!(config.execution.max_commands_per_minute, 20);
+    }
+
+    #[test]
+    fn test_telemetry_is_opt_in() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert!(!config.telemetry.local_stats);
+
+        let config: Config = serde_json::from_str(r#"{"telemetry": {"local_stats": true}}"#).unwrap();
+        assert!(config.telemetry.local_stats);
+        assert_eq!(config.telemetry.metrics_port, 0);
+        assert!(!config.debug.hidpp_trace);
+    }
+
+    #[test]
+    fn test_paths_data_dir_override() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert!(config.paths.data_dir.is_none());
+
+        let config: Config = serde_json::from_str(r#"{"paths": {"data_dir": "/opt/juhradial/share"}}"#).unwrap();
+        assert_eq!(config.paths.data_dir, Some(PathBuf::from("/opt/juhradial/share")));
+    }
+
+    #[test]
+    fn test_input_left_handed_flag() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert!(!config.input.left_handed);
+        let config: Config = serde_json::from_str(r#"{"input": {"left_handed": true}}"#).unwrap();
+        assert!(config.input.left_handed);
+    }
+
+    #[test]
+    fn test_input_generic_mouse_mode() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.input.generic_mouse, GenericMouseMode::Auto);
+        let config: Config = serde_json::from_str(r#"{"input": {"generic_mouse": "off"}}"#).unwrap();
+        assert_eq!(config.input.generic_mouse, GenericMouseMode::Off);
+        let config: Config = serde_json::from_str(r#"{"input": {"generic_mouse": "require"}}"#).unwrap();
+        assert_eq!(config.input.generic_mouse, GenericMouseMode::Require);
+    }
+
+    #[test]
+    fn test_action_feedback_defaults() {
+        let config: Config = serde_json::from_str(r#"{"menu": {"auto_dismiss_ms": 5000}}"#).unwrap();
+        assert!(config.menu.action_feedback);
+        assert!(!config.menu.success_flash);
+    }
+
+    #[test]
+    fn test_menu_size_forms() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.menu.size, MenuSize::Auto);
+        let config: Config = serde_json::from_str(r#"{"menu": {"size": "22%"}}"#).unwrap();
+        assert_eq!(config.menu.size, MenuSize::Percent(22.0));
+        let config: Config = serde_json::from_str(r#"{"menu": {"size": 360}}"#).unwrap();
+        assert_eq!(config.menu.size, MenuSize::Pixels(360));
+        assert!(serde_json::from_str::<Config>(r#"{"menu": {"size": "huge"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_flick_settings() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert!(config.menu.flick().is_none());
+        let config: Config = serde_json::from_str(r#"{"menu": {"flick": true}}"#).unwrap();
+        let flick = config.menu.flick().unwrap();
+        assert_eq!(flick.distance, 80.0);
+        assert_eq!(flick.window, std::time::Duration::from_millis(200));
+        let config: Config = serde_json::from_str(r#"{"menu": {"flick": true, "flick_window_ms": 0}}"#).unwrap();
+        assert!(config.menu.flick().is_none());
+    }
+
+    #[test]
+    fn test_battery_summary_interval() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.battery.summary_interval(), Some(std::time::Duration::from_secs(15 * 60)));
+        let config: Config =
+            serde_json::from_str(r#"{"battery": {"summary_interval_minutes": 0}}"#).unwrap();
+        assert!(config.battery.summary_interval().is_none());
+    }
+
+    #[test]
+    fn test_idle_effect_limits() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        let limits = config.idle_effects.limits(false);
+        assert!(limits.enabled);
+        assert_eq!((limits.max_fps, limits.max_density), (30, 1.0));
+        assert!(!config.idle_effects.limits(true).enabled);
+
+        let config: Config = serde_json::from_str(
+            r#"{"idle_effects": {"max_fps": 200, "max_density": 4.0, "disable_on_battery": false}}"#,
+        )
+        .unwrap();
+        let limits = config.idle_effects.limits(true);
+        assert!(limits.enabled, "kept on battery when asked to");
+        assert_eq!((limits.max_fps, limits.max_density), (60, 1.0));
+    }
+
+    #[test]
+    fn test_menu_triggers() {
+        let config = Config::default();
+        assert!(config.mx_gesture_trigger());
+        assert!(config.evdev_trigger_codes().is_empty());
+
+        let json = r#"{"triggers": [
+            {"source": "evdev-button", "code": 275},
+            {"source": "shortcut", "keys": "Meta+Space"},
+            {"source": "evdev-button", "code": 275}
+        ]}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert!(!config.mx_gesture_trigger());
+        assert_eq!(config.evdev_trigger_codes(), vec![0x113]);
+        assert_eq!(config.shortcut_triggers(), vec!["Meta+Space".to_string()]);
+    }
+
     #[test]
     fn test_action_for_cid() {
         let config = Config::default();
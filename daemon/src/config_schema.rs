@@ -0,0 +1,339 @@
+//! config.json schema
+//!
+//! `config.schema.json` is a hand-maintained draft-07 JSON Schema covering
+//! every key the daemon reads plus the sections the Settings UI keeps in the
+//! same file. The daemon writes it next to config.json so editors can validate
+//! and complete the file (a bootstrapped config.json points at it through
+//! `$schema`), and checks each loaded config against it, logging unknown keys
+//! with a "did you mean" suggestion.
+//!
+//! The checker understands the draft-07 subset the schema uses: `type`,
+//! `properties`, `additionalProperties`, `required`, `items`, `enum`, `const`,
+//! `oneOf`, `minimum`, `maximum` and local `$ref`s. Serde still decides what
+//! loads; findings are only logged.
+
+use std::fmt;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::validation::{IssueCode, ValidationIssue};
+
+/// The bundled schema
+pub const SCHEMA: &str = include_str!("config.schema.json");
+
+/// File name of the schema, next to config.json
+pub const SCHEMA_FILE: &str = "config.schema.json";
+
+/// Where a config value departs from the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaIssue {
+    /// Dotted key path, e.g. `menu.theme_transition_ms` or `triggers[1]`
+    pub path: String,
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    /// A key no section defines, with the closest known key
+    UnknownKey { suggestion: Option<String> },
+    /// Value of the wrong JSON type
+    WrongType { expected: String },
+    /// Not one of the allowed values or forms
+    NotAllowed,
+    /// Number outside `minimum`/`maximum`
+    OutOfRange,
+    /// A required key is absent
+    Missing,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey { suggestion: Some(s) } => write!(f, "unknown key, did you mean `{s}`?"),
+            Self::UnknownKey { suggestion: None } => write!(f, "unknown key"),
+            Self::WrongType { expected } => write!(f, "expected {expected}"),
+            Self::NotAllowed => write!(f, "not an allowed value"),
+            Self::OutOfRange => write!(f, "out of range"),
+            Self::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+impl IssueKind {
+    pub fn code(&self) -> IssueCode {
+        match self {
+            Self::UnknownKey { .. } => IssueCode::UnknownKey,
+            Self::WrongType { .. } => IssueCode::WrongType,
+            Self::NotAllowed => IssueCode::NotAllowed,
+            Self::OutOfRange => IssueCode::OutOfRange,
+            Self::Missing => IssueCode::Missing,
+        }
+    }
+}
+
+impl SchemaIssue {
+    /// As a validation finding: a warning, since serde decides what loads
+    pub fn to_issue(&self) -> ValidationIssue {
+        let issue = ValidationIssue::warning(&self.path, self.kind.code(), self.kind.to_string());
+        match &self.kind {
+            IssueKind::WrongType { expected } => issue.expected(expected.as_str()),
+            IssueKind::UnknownKey { suggestion: Some(suggestion) } => issue.expected(suggestion.as_str()),
+            _ => issue,
+        }
+    }
+}
+
+/// The bundled schema, parsed
+///
+/// Parsed per call: configs are only checked on load and reload.
+pub fn schema() -> Value {
+    serde_json::from_str(SCHEMA).unwrap_or_default()
+}
+
+/// Check a parsed config.json against the bundled schema
+pub fn validate(config: &Value) -> Vec<SchemaIssue> {
+    let schema = schema();
+    let mut checker = Checker::new(&schema);
+    checker.check(&schema, config, "");
+    checker.issues
+}
+
+/// Findings for the config.json at `path`; none when there is no file
+pub fn check_file(path: &Path) -> Vec<ValidationIssue> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    match serde_json::from_str(&contents) {
+        Ok(raw) => validate(&raw).iter().map(SchemaIssue::to_issue).collect(),
+        Err(e) => vec![ValidationIssue::error("", IssueCode::ParseError, format!("JSON parse error: {}", e))],
+    }
+}
+
+/// Log each issue found in the config at `path`
+pub fn log_issues(path: &Path, issues: &[SchemaIssue]) {
+    for issue in issues {
+        tracing::warn!(
+            path = %path.display(),
+            key = %issue.path,
+            code = %issue.kind.code(),
+            "Config key {}: {}",
+            issue.path,
+            issue.kind
+        );
+    }
+}
+
+/// Closest of `known` within a few edits of `key`
+pub fn suggest<'k>(key: &str, known: impl IntoIterator<Item = &'k str>) -> Option<&'k str> {
+    let limit = (key.chars().count() / 3).max(2);
+    known
+        .into_iter()
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+struct Checker<'s> {
+    /// Schema root, for `$ref`s
+    root: &'s Value,
+    issues: Vec<SchemaIssue>,
+}
+
+impl<'s> Checker<'s> {
+    fn new(root: &'s Value) -> Self {
+        Self { root, issues: Vec::new() }
+    }
+
+    fn push(&mut self, path: &str, kind: IssueKind) {
+        let path = if path.is_empty() { "(root)" } else { path };
+        self.issues.push(SchemaIssue { path: path.to_string(), kind });
+    }
+
+    fn resolve(&self, schema: &'s Value) -> &'s Value {
+        schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|pointer| self.root.pointer(pointer))
+            .unwrap_or(schema)
+    }
+
+    /// Whether `value` matches `schema` without recording anything
+    fn matches(&self, schema: &'s Value, value: &Value) -> bool {
+        let mut checker = Checker::new(self.root);
+        checker.check(schema, value, "");
+        checker.issues.is_empty()
+    }
+
+    fn check(&mut self, schema: &'s Value, value: &Value, path: &str) {
+        let schema = self.resolve(schema);
+        if let Some(expected) = schema.get("type") {
+            if !type_matches(expected, value) {
+                let expected = match expected {
+                    Value::Array(names) => names.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" or "),
+                    other => other.as_str().unwrap_or_default().to_string(),
+                };
+                self.push(path, IssueKind::WrongType { expected });
+                return;
+            }
+        }
+        let allowed = schema.get("enum").and_then(Value::as_array).is_none_or(|values| values.contains(value));
+        let constant = schema.get("const").is_none_or(|c| c == value);
+        if !allowed || !constant {
+            self.push(path, IssueKind::NotAllowed);
+        }
+        if let Some(n) = value.as_f64() {
+            let below = schema.get("minimum").and_then(Value::as_f64).is_some_and(|min| n < min);
+            let above = schema.get("maximum").and_then(Value::as_f64).is_some_and(|max| n > max);
+            if below || above {
+                self.push(path, IssueKind::OutOfRange);
+            }
+        }
+        if let Some(forms) = schema.get("oneOf").and_then(Value::as_array) {
+            if forms.iter().filter(|form| self.matches(form, value)).count() != 1 {
+                self.push(path, IssueKind::NotAllowed);
+            }
+        }
+        match value {
+            Value::Object(map) => self.check_object(schema, map, path),
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items") {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check(item_schema, item, &format!("{path}[{i}]"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_object(&mut self, schema: &'s Value, map: &Map<String, Value>, path: &str) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let required = schema.get("required").and_then(Value::as_array);
+        for key in required.into_iter().flatten().filter_map(Value::as_str) {
+            if !map.contains_key(key) {
+                self.push(&child_path(path, key), IssueKind::Missing);
+            }
+        }
+        for (key, value) in map {
+            let child = child_path(path, key);
+            match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+                (Some(property), _) => self.check(property, value, &child),
+                (None, Some(Value::Bool(false))) => {
+                    let known = properties.into_iter().flat_map(|p| p.keys().map(String::as_str));
+                    let suggestion = suggest(key, known).map(str::to_string);
+                    self.push(&child, IssueKind::UnknownKey { suggestion });
+                }
+                (None, Some(extra @ Value::Object(_))) => self.check(extra, value, &child),
+                (None, _) => {}
+            }
+        }
+    }
+}
+
+fn type_matches(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::Array(names) => names.iter().any(|name| type_matches(name, value)),
+        Value::String(name) => match name.as_str() {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_stays_in_sync_with_the_config_structs() {
+        assert_eq!(schema()["$schema"], "http://json-schema.org/draft-07/schema#");
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        assert_eq!(validate(&defaults), []);
+
+        // Fields the defaults leave out or null, and every trigger form
+        let mut config: Config = serde_json::from_value(json!({
+            "haptics": { "patterns": { "tick": [{ "duration_ms": 12, "waveform": "sharp_collision" }] } },
+            "overlay": { "path": "/opt/juhradial/overlay.py" },
+            "paths": { "data_dir": "/opt/juhradial/share" },
+            "triggers": [
+                { "source": "mx-gesture-button" },
+                { "source": "evdev-button", "code": 275 },
+                { "source": "shortcut", "keys": "Meta+Space" }
+            ]
+        }))
+        .unwrap();
+        config.execution.allowed_binaries.push("/usr/bin/playerctl".into());
+        config.execution.allowed_commands.push(vec!["/usr/bin/systemctl".into(), "suspend".into()]);
+        assert_eq!(validate(&serde_json::to_value(&config).unwrap()), []);
+    }
+
+    #[test]
+    fn test_unknown_keys_suggest_the_closest_known_key() {
+        let config = json!({
+            "$schema": "./config.schema.json",
+            "haptic": { "enabled": false },
+            "menu": { "theme_transtion_ms": 100, "hover_help_ms": -1 },
+            "thumbwheel": { "mode": "spin" },
+            "triggers": [{ "source": "evdev-button" }],
+            "frobnicate": true,
+            "scroll": { "natural": true, "smartshift_threshold": 50 }
+        });
+        let issues = validate(&config);
+        let unknown = |path: &str, suggestion: Option<&str>| SchemaIssue {
+            path: path.to_string(),
+            kind: IssueKind::UnknownKey { suggestion: suggestion.map(str::to_string) },
+        };
+        assert!(issues.contains(&unknown("haptic", Some("haptics"))));
+        assert!(issues.contains(&unknown("menu.theme_transtion_ms", Some("theme_transition_ms"))));
+        assert!(issues.contains(&unknown("frobnicate", None)));
+        let kind_at = |path: &str| issues.iter().find(|i| i.path == path).map(|i| i.kind.clone());
+        assert_eq!(kind_at("menu.hover_help_ms"), Some(IssueKind::OutOfRange));
+        assert_eq!(kind_at("thumbwheel.mode"), Some(IssueKind::NotAllowed));
+        assert_eq!(kind_at("triggers[0]"), Some(IssueKind::NotAllowed));
+        // Settings UI sections are free-form
+        assert_eq!(issues.len(), 6);
+
+        assert_eq!(IssueKind::UnknownKey { suggestion: Some("haptics".into()) }.to_string(), "unknown key, did you mean `haptics`?");
+        let structured = unknown("haptic", Some("haptics")).to_issue();
+        assert_eq!((structured.code, structured.expected.as_deref()), (IssueCode::UnknownKey, Some("haptics")));
+        assert_eq!(structured.message, "unknown key, did you mean `haptics`?");
+        assert!(!structured.is_error());
+        assert_eq!(suggest("blur", ["blur_enabled", "theme"]), None);
+    }
+}
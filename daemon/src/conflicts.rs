@@ -0,0 +1,323 @@
+//! Other programs driving the same Logitech device
+//!
+//! logid, Solaar and ratbagd all talk HID++ to the mouse. While one of them
+//! runs, our requests race theirs: battery queries time out, diverts get
+//! undone and haptics go quiet, with nothing in the log pointing at the
+//! cause. The detector looks for those tools by process name and, where
+//! `/proc/<pid>/fd` is readable (same user, or root), for any other process
+//! holding one of the Logitech hidraw nodes open.
+//!
+//! It runs with the startup self-test, on `Diagnostics()` and whenever the
+//! HID++ device reconnects. The last report is shared ([`ConflictsHandle`] on
+//! `DaemonHandles`): `Status()`
+//! and the self-test show it with per-tool guidance, battery failures are
+//! blamed on it, and the reconnect and hidraw polls slow down by
+//! [`BACKOFF_FACTOR`] while it lists anything, so we stop fighting a tool the
+//! user may want to keep.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// How much longer reconnect cooldowns and device polls wait while another
+/// program holds the device
+pub const BACKOFF_FACTOR: u32 = 6;
+
+/// A program known to drive Logitech devices, or any other holder of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictTool {
+    Logid,
+    Solaar,
+    Ratbagd,
+    /// Some other process with a Logitech hidraw node open
+    Other,
+}
+
+impl ConflictTool {
+    /// Self-test check id for this tool
+    pub fn check(self) -> &'static str {
+        match self {
+            Self::Logid => "logid",
+            Self::Solaar => "solaar",
+            Self::Ratbagd => "ratbagd",
+            Self::Other => "device_holder",
+        }
+    }
+
+    /// What the user can do about it, suitable for showing verbatim
+    pub fn guidance(self) -> &'static str {
+        match self {
+            Self::Logid => "Stop logid so it does not divert the same buttons: sudo systemctl disable --now logid",
+            Self::Solaar => {
+                "Quit Solaar and remove it from autostart, or at least turn off its rules and \
+                 button diversion for this mouse; it polls the same HID++ features."
+            }
+            Self::Ratbagd => {
+                "Stop ratbagd (used by Piper) while JuhRadial runs: sudo systemctl disable --now ratbagd"
+            }
+            Self::Other => {
+                "Another program has the mouse's hidraw node open. Close it if it is a mouse \
+                 configuration tool, then replug the receiver."
+            }
+        }
+    }
+
+    /// The known tool a process is, from its `comm` and command line
+    ///
+    /// Solaar is a Python script, so besides its own `comm` it can show up
+    /// as `python3 /usr/bin/solaar`.
+    fn of_process(comm: &str, cmdline: &[String]) -> Option<Self> {
+        match comm {
+            "logid" => return Some(Self::Logid),
+            "ratbagd" => return Some(Self::Ratbagd),
+            "solaar" => return Some(Self::Solaar),
+            _ => {}
+        }
+        let is_solaar = |arg: &String| Path::new(arg).file_name().is_some_and(|name| name == "solaar");
+        (comm.starts_with("python") && cmdline.iter().skip(1).take(2).any(is_solaar)).then_some(Self::Solaar)
+    }
+}
+
+/// One conflicting process
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    pub tool: ConflictTool,
+    pub pid: u32,
+    /// Process name (`/proc/<pid>/comm`)
+    pub name: String,
+    /// Logitech hidraw nodes it holds open; empty when its fds are not
+    /// readable to us or it only talks to the device now and then
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<PathBuf>,
+    pub guidance: &'static str,
+}
+
+impl Conflict {
+    /// One-line description for logs and the self-test
+    pub fn message(&self) -> String {
+        let holding = match self.nodes.as_slice() {
+            [] => String::new(),
+            nodes => {
+                let nodes: Vec<String> = nodes.iter().map(|n| n.display().to_string()).collect();
+                format!(" and holds {} open", nodes.join(", "))
+            }
+        };
+        format!("{} (pid {}) is running{}; it competes for the HID++ device", self.name, self.pid, holding)
+    }
+}
+
+/// What one scan found
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConflictReport {
+    pub conflicts: Vec<Conflict>,
+}
+
+impl ConflictReport {
+    pub fn is_empty(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Whether logid is among them (the old `logid_active` flag)
+    pub fn logid_active(&self) -> bool {
+        self.conflicts.iter().any(|c| c.tool == ConflictTool::Logid)
+    }
+
+    /// The conflicts' messages joined for a battery failure
+    pub fn summary(&self) -> String {
+        self.conflicts.iter().map(Conflict::message).collect::<Vec<_>>().join("; ")
+    }
+}
+
+/// Scans a `/proc` tree, overridable for tests
+pub struct ConflictDetector {
+    proc: PathBuf,
+    /// Our own pid, never a conflict
+    own_pid: u32,
+}
+
+impl ConflictDetector {
+    pub fn system() -> Self {
+        Self::new(PathBuf::from("/proc"), std::process::id())
+    }
+
+    pub fn new(proc: PathBuf, own_pid: u32) -> Self {
+        Self { proc, own_pid }
+    }
+
+    /// Known tools, plus any process holding one of `nodes` open
+    pub fn scan(&self, nodes: &[PathBuf]) -> ConflictReport {
+        let Ok(entries) = fs::read_dir(&self.proc) else {
+            return ConflictReport::default();
+        };
+        let mut conflicts: Vec<Conflict> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+                (pid != self.own_pid).then(|| self.check_process(pid, &entry.path(), nodes)).flatten()
+            })
+            .collect();
+        conflicts.sort_by_key(|c| c.pid);
+        ConflictReport { conflicts }
+    }
+
+    fn check_process(&self, pid: u32, dir: &Path, nodes: &[PathBuf]) -> Option<Conflict> {
+        let name = fs::read_to_string(dir.join("comm")).ok()?.trim().to_string();
+        let cmdline: Vec<String> = fs::read(dir.join("cmdline"))
+            .map(|raw| {
+                raw.split(|&b| b == 0)
+                    .filter(|arg| !arg.is_empty())
+                    .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let held = held_nodes(&dir.join("fd"), nodes);
+        let tool = ConflictTool::of_process(&name, &cmdline)
+            .or_else(|| (!held.is_empty()).then_some(ConflictTool::Other))?;
+        Some(Conflict {
+            tool,
+            pid,
+            name,
+            nodes: held,
+            guidance: tool.guidance(),
+        })
+    }
+}
+
+/// Which of `nodes` the fds in `fd_dir` point at; nothing when unreadable
+fn held_nodes(fd_dir: &Path, nodes: &[PathBuf]) -> Vec<PathBuf> {
+    if nodes.is_empty() {
+        return Vec::new();
+    }
+    let Ok(fds) = fs::read_dir(fd_dir) else {
+        return Vec::new();
+    };
+    let mut held: Vec<PathBuf> = fds
+        .flatten()
+        .filter_map(|fd| fs::read_link(fd.path()).ok())
+        .filter(|target| nodes.contains(target))
+        .collect();
+    held.sort();
+    held.dedup();
+    held
+}
+
+/// The last report, shared through `DaemonHandles`
+///
+/// Clones share the report.
+#[derive(Debug, Clone, Default)]
+pub struct ConflictsHandle {
+    last: Arc<RwLock<ConflictReport>>,
+}
+
+impl ConflictsHandle {
+    /// Scan the live system for programs competing for `nodes`, keep the
+    /// report for [`last`](Self::last) and log what appeared or went away
+    pub fn refresh(&self, nodes: &[PathBuf]) -> ConflictReport {
+        let report = ConflictDetector::system().scan(nodes);
+        if let Ok(mut last) = self.last.write() {
+            for conflict in report.conflicts.iter().filter(|c| !last.conflicts.iter().any(|l| l.pid == c.pid)) {
+                tracing::warn!(tool = ?conflict.tool, guidance = conflict.guidance, "{}", conflict.message());
+            }
+            if report.is_empty() && !last.is_empty() {
+                tracing::info!("No other program is competing for the device any more");
+            }
+            *last = report.clone();
+        }
+        report
+    }
+
+    /// [`refresh`](Self::refresh) against the Logitech hidraw nodes present
+    /// now (device reconnect)
+    pub fn refresh_system(&self) -> ConflictReport {
+        self.refresh(&crate::self_test::logitech_hidraw_nodes())
+    }
+
+    /// The report from the last scan
+    pub fn last(&self) -> ConflictReport {
+        self.last.read().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Whether the last scan found anything
+    pub fn active(&self) -> bool {
+        self.last.read().is_ok_and(|r| !r.is_empty())
+    }
+
+    /// `base`, stretched by [`BACKOFF_FACTOR`] while another program holds the
+    /// device
+    pub fn backoff(&self, base: Duration) -> Duration {
+        if self.active() {
+            base * BACKOFF_FACTOR
+        } else {
+            base
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn process(proc: &Path, pid: u32, comm: &str, cmdline: &[&str]) -> PathBuf {
+        let dir = proc.join(pid.to_string());
+        fs::create_dir_all(dir.join("fd")).unwrap();
+        fs::write(dir.join("comm"), format!("{comm}\n")).unwrap();
+        fs::write(dir.join("cmdline"), cmdline.iter().map(|a| format!("{a}\0")).collect::<String>()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scan_finds_tools_and_node_holders() {
+        let dir = TempDir::new().unwrap();
+        let proc = dir.path().join("proc");
+        let node = dir.path().join("dev/hidraw3");
+        fs::create_dir_all(node.parent().unwrap()).unwrap();
+        fs::write(&node, "").unwrap();
+
+        process(&proc, 812, "logid", &["/usr/bin/logid"]);
+        process(&proc, 900, "python3", &["/usr/bin/python3", "/usr/bin/solaar", "--window=hide"]);
+        process(&proc, 901, "python3", &["/usr/bin/python3", "-m", "http.server"]);
+        let holder = process(&proc, 950, "chrome", &["/opt/chrome/chrome"]);
+        std::os::unix::fs::symlink(&node, holder.join("fd/42")).unwrap();
+        std::os::unix::fs::symlink("/dev/null", holder.join("fd/0")).unwrap();
+        process(&proc, 960, "ratbagd", &["/usr/libexec/ratbagd"]);
+        // Ourselves, with the node open, is never a conflict
+        let own = process(&proc, 1000, "juhradiald", &["juhradiald"]);
+        std::os::unix::fs::symlink(&node, own.join("fd/7")).unwrap();
+        fs::create_dir_all(proc.join("self")).unwrap();
+
+        let report = ConflictDetector::new(proc, 1000).scan(std::slice::from_ref(&node));
+        let found: Vec<(ConflictTool, u32)> = report.conflicts.iter().map(|c| (c.tool, c.pid)).collect();
+        assert_eq!(found, [
+            (ConflictTool::Logid, 812),
+            (ConflictTool::Solaar, 900),
+            (ConflictTool::Other, 950),
+            (ConflictTool::Ratbagd, 960),
+        ]);
+        assert!(report.logid_active());
+        assert_eq!(report.conflicts[2].nodes, std::slice::from_ref(&node));
+        assert!(report.conflicts[2].message().contains("holds"));
+        assert!(report.conflicts[1].guidance.contains("Solaar"));
+        assert!(report.summary().starts_with("logid (pid 812) is running;"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["conflicts"][0]["tool"], "logid");
+        assert!(json["conflicts"][0].get("nodes").is_none());
+        assert_eq!(json["conflicts"][2]["nodes"][0], node.display().to_string());
+    }
+
+    #[test]
+    fn test_nothing_to_report() {
+        let dir = TempDir::new().unwrap();
+        let proc = dir.path().join("proc");
+        process(&proc, 10, "bash", &["bash"]);
+        let report = ConflictDetector::new(proc, 1).scan(&[PathBuf::from("/dev/hidraw0")]);
+        assert!(report.is_empty());
+        assert!(!report.logid_active());
+        assert!(ConflictDetector::new(dir.path().join("missing"), 1).scan(&[]).is_empty());
+    }
+}
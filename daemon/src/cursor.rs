@@ -5,6 +5,8 @@
 
 use std::process::Command;
 
+use crate::geometry::Monitor;
+
 /// KWin JavaScript used by KDE Wayland input paths to show the menu at the
 /// cursor in the coordinate space expected by the XWayland/Qt overlay.
 ///
@@ -25,15 +27,22 @@ callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
          Math.round(pos.y));
 "#;
 
-/// Menu diameter in pixels (matches overlay MENU_RADIUS * 2)
-pub const MENU_DIAMETER: i32 = 300;
+/// Base menu diameter in pixels (matches overlay MENU_RADIUS * 2); `menu.size`
+/// may resolve to another diameter per monitor
+pub const MENU_DIAMETER: i32 = crate::geometry::MENU_DIAMETER as i32;
 
-/// Minimum margin from screen edges in pixels
+/// Minimum margin from screen edges in pixels, for the base diameter
 pub const EDGE_MARGIN: i32 = 20;
 
-/// Menu radius (half of diameter)
+/// Base menu radius (half of diameter)
 pub const MENU_RADIUS: i32 = MENU_DIAMETER / 2;
 
+/// Edge margin for a ring of `diameter` pixels, scaled with the ring
+pub fn edge_margin(diameter: u32) -> i32 {
+    let diameter = i32::try_from(diameter).unwrap_or(i32::MAX);
+    (i64::from(EDGE_MARGIN) * i64::from(diameter) / i64::from(MENU_DIAMETER)) as i32
+}
+
 /// Screen dimensions for edge clamping
 #[derive(Debug, Clone, Copy)]
 pub struct ScreenBounds {
@@ -71,18 +80,26 @@ impl CursorPosition {
     ///
     /// # Arguments
     /// * `bounds` - Screen dimensions to clamp within
+    /// * `diameter` - Ring diameter in pixels ([`MENU_DIAMETER`] unless
+    ///   `menu.size` resolved another); the margin scales with it
     ///
     /// # Returns
-    /// New CursorPosition with clamped coordinates
-    pub fn clamp_to_screen(&self, bounds: &ScreenBounds) -> Self {
-        let min_x = EDGE_MARGIN + MENU_RADIUS;
-        let max_x = bounds.width - EDGE_MARGIN - MENU_RADIUS;
-        let min_y = EDGE_MARGIN + MENU_RADIUS;
-        let max_y = bounds.height - EDGE_MARGIN - MENU_RADIUS;
+    /// New CursorPosition with clamped coordinates. A screen too small for
+    /// the ring centres it.
+    pub fn clamp_to_screen(&self, bounds: &ScreenBounds, diameter: u32) -> Self {
+        let inset = edge_margin(diameter) + i32::try_from(diameter / 2).unwrap_or(i32::MAX);
+        let clamp = |value: i32, extent: i32| {
+            let (min, max) = (inset, extent - inset);
+            if min > max {
+                extent / 2
+            } else {
+                value.clamp(min, max)
+            }
+        };
 
         Self {
-            x: self.x.clamp(min_x, max_x),
-            y: self.y.clamp(min_y, max_y),
+            x: clamp(self.x, bounds.width),
+            y: clamp(self.y, bounds.height),
         }
     }
 }
@@ -90,14 +107,29 @@ impl CursorPosition {
 /// Get current cursor position
 ///
 /// Attempts to query cursor position using available methods:
-/// 1. Hyprland (wlroots-based Wayland) - via hyprctl
-/// 2. KWin scripting (Wayland) - most accurate for Plasma 6 Wayland multi-monitor
-/// 3. KWin D-Bus API (older Plasma versions)
-/// 4. GNOME Shell extension D-Bus (GNOME Wayland)
-/// 5. XWayland XQueryPointer (any Wayland compositor with XWayland)
-/// 6. xdotool fallback (X11)
-/// 7. Screen center fallback (ensures menu is always visible)
-pub fn get_cursor_position() -> CursorPosition {
+/// 1. The resident KWin helper's last report (`kwin_script`), when loaded;
+///    on an X11 session then `XQueryPointer` (see `session`)
+/// 2. Hyprland (wlroots-based Wayland) - via hyprctl
+/// 3. KWin scripting (Wayland) - most accurate for Plasma 6 Wayland multi-monitor
+/// 4. KWin D-Bus API (older Plasma versions)
+/// 5. GNOME Shell extension D-Bus (GNOME Wayland)
+/// 6. XWayland XQueryPointer (any Wayland compositor with XWayland)
+/// 7. xdotool fallback (X11)
+/// 8. Screen center fallback (ensures menu is always visible)
+pub fn get_cursor_position(helper: &crate::kwin_script::KWinHelper) -> CursorPosition {
+    // The resident KWin helper's last report, when it is loaded
+    if let Some(pos) = helper.cursor() {
+        tracing::debug!(x = pos.x, y = pos.y, "Got cursor position from the KWin helper");
+        return pos;
+    }
+
+    // An X11 server knows the pointer everywhere; no compositor to ask
+    if !crate::session::SessionKind::detect().is_wayland() {
+        if let Some(pos) = get_cursor_via_x11() {
+            return pos;
+        }
+    }
+
     // Try Hyprland first (wlroots-based Wayland compositor)
     if let Some(pos) = get_cursor_via_hyprland() {
         return pos;
@@ -177,9 +209,11 @@ fn get_cursor_via_hyprland_socket(sig: &str) -> Option<CursorPosition> {
     use std::os::unix::net::UnixStream;
     use std::time::Duration;
 
-    let xdg_runtime =
-        std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/run/user/1000".to_string());
-    let socket_path = format!("{}/hypr/{}/.socket.sock", xdg_runtime, sig);
+    let socket_path = crate::paths::paths()
+        .session_runtime_dir()?
+        .join("hypr")
+        .join(sig)
+        .join(".socket.sock");
 
     let mut stream = UnixStream::connect(&socket_path).ok()?;
     stream
@@ -385,6 +419,25 @@ fn get_cursor_via_xwayland() -> Option<CursorPosition> {
     }
 }
 
+/// Query cursor position via XQueryPointer on the root window over x11rb
+#[cfg(feature = "window-tracking")]
+fn get_cursor_via_x11() -> Option<CursorPosition> {
+    use x11rb::connection::Connection as _;
+    use x11rb::protocol::xproto::ConnectionExt as _;
+
+    let (conn, screen) = x11rb::connect(None).ok()?;
+    let root = conn.setup().roots.get(screen)?.root;
+    let reply = conn.query_pointer(root).ok()?.reply().ok()?;
+    tracing::debug!(x = reply.root_x, y = reply.root_y, "Got cursor position via x11rb XQueryPointer");
+    Some(CursorPosition::new(reply.root_x.into(), reply.root_y.into()))
+}
+
+/// Without x11rb, the libX11 query
+#[cfg(not(feature = "window-tracking"))]
+fn get_cursor_via_x11() -> Option<CursorPosition> {
+    get_cursor_via_xwayland()
+}
+
 /// Query cursor position via xdotool
 fn get_cursor_via_xdotool() -> Option<CursorPosition> {
     let output = Command::new("xdotool")
@@ -418,6 +471,14 @@ fn get_cursor_via_xdotool() -> Option<CursorPosition> {
 ///
 /// Queries total screen dimensions across all monitors for edge clamping.
 pub fn get_screen_bounds() -> ScreenBounds {
+    // RandR on an X11 session, without spawning xrandr
+    if !crate::session::SessionKind::detect().is_wayland() {
+        if let Some(bounds) = bounding_box(&get_monitors_via_randr()) {
+            tracing::debug!(width = bounds.width, height = bounds.height, "Got screen bounds via RandR");
+            return bounds;
+        }
+    }
+
     // Try Hyprland first (wlroots-based Wayland compositor)
     if let Some(bounds) = get_screen_via_hyprland() {
         return bounds;
@@ -457,57 +518,159 @@ fn get_screen_via_hyprland() -> Option<ScreenBounds> {
         return None;
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-
-    // Parse JSON output to get monitor dimensions
     // Calculate bounding box of all monitors
-    let monitors: Vec<serde_json::Value> = serde_json::from_str(&stdout).ok()?;
+    let bounds = bounding_box(&parse_hyprland_monitors(&String::from_utf8_lossy(&output.stdout)))?;
+    tracing::debug!(
+        width = bounds.width,
+        height = bounds.height,
+        "Got screen bounds via Hyprland"
+    );
+    Some(bounds)
+}
 
-    let mut max_x = 0i32;
-    let mut max_y = 0i32;
+/// Bounding box of all monitors, from the origin
+fn bounding_box(monitors: &[Monitor]) -> Option<ScreenBounds> {
+    let width = monitors.iter().map(|m| m.x + m.width).max().unwrap_or(0);
+    let height = monitors.iter().map(|m| m.y + m.height).max().unwrap_or(0);
+    (width > 0 && height > 0).then_some(ScreenBounds { width, height })
+}
 
-    for monitor in &monitors {
-        // Use continue (not ?) so one bad monitor doesn't kill the whole query
-        let x = match monitor.get("x").and_then(|v| v.as_i64()) {
-            Some(v) => v as i32,
-            None => continue,
-        };
-        let y = match monitor.get("y").and_then(|v| v.as_i64()) {
-            Some(v) => v as i32,
-            None => continue,
-        };
-        let width = match monitor.get("width").and_then(|v| v.as_i64()) {
-            Some(v) => v as i32,
-            None => continue,
-        };
-        let height = match monitor.get("height").and_then(|v| v.as_i64()) {
-            Some(v) => v as i32,
-            None => continue,
-        };
-        let scale = monitor.get("scale").and_then(|v| v.as_f64()).unwrap_or(1.0);
+/// Monitors from `hyprctl monitors -j`
+///
+/// Hyprland reports x/y in logical coords but width/height in physical
+/// pixels; dividing by the scale gives logical dimensions that match the
+/// cursor coordinate space. A monitor missing a field is skipped so one bad
+/// entry doesn't kill the whole query.
+fn parse_hyprland_monitors(json: &str) -> Vec<Monitor> {
+    let Ok(monitors) = serde_json::from_str::<Vec<serde_json::Value>>(json) else {
+        return Vec::new();
+    };
+    monitors
+        .iter()
+        .filter_map(|monitor| {
+            let field = |name: &str| monitor.get(name).and_then(|v| v.as_i64()).map(|v| v as i32);
+            let scale = monitor.get("scale").and_then(|v| v.as_f64()).filter(|s| *s > 0.0).unwrap_or(1.0);
+            Some(Monitor {
+                x: field("x")?,
+                y: field("y")?,
+                width: (f64::from(field("width")?) / scale) as i32,
+                height: (f64::from(field("height")?) / scale) as i32,
+                scale,
+                compositor_scaled: true,
+            })
+        })
+        .collect()
+}
 
-        // Hyprland reports x/y in logical coords but width/height in physical pixels.
-        // Divide by scale to get logical dimensions that match the cursor coordinate space.
-        let logical_w = (width as f64 / scale) as i32;
-        let logical_h = (height as f64 / scale) as i32;
+/// Monitors from plain `xrandr` output
+///
+/// Reads each connected output's `WxH+X+Y` and its physical size. The scale
+/// is the DPI over 96 in quarter steps, between 1 and 4; an output without a
+/// physical size (projectors, some virtual displays) gets 1.
+fn parse_xrandr_monitors(text: &str) -> Vec<Monitor> {
+    text.lines()
+        .filter(|line| line.contains(" connected"))
+        .filter_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let geometry = words.iter().find(|w| w.contains('x') && w.contains('+'))?;
+            let (size, position) = geometry.split_once('+')?;
+            let (width, height) = size.split_once('x')?;
+            let (x, y) = position.split_once('+')?;
+            let (width, height): (i32, i32) = (width.parse().ok()?, height.parse().ok()?);
+            let millimetres = |w: &&str| w.strip_suffix("mm").and_then(|n| n.parse::<f64>().ok());
+            let physical: Vec<f64> = words.iter().filter_map(millimetres).collect();
+            let scale = match physical[..] {
+                [w_mm, h_mm] => dpi_scale(width, height, w_mm, h_mm),
+                _ => 1.0,
+            };
+            Some(Monitor {
+                x: x.parse().ok()?,
+                y: y.parse().ok()?,
+                width,
+                height,
+                scale,
+                compositor_scaled: false,
+            })
+        })
+        .collect()
+}
 
-        max_x = max_x.max(x + logical_w);
-        max_y = max_y.max(y + logical_h);
+/// Scale of a `width`x`height` output of the given physical size: the DPI
+/// over 96 in quarter steps, between 1 and 4; 1 without a physical size
+fn dpi_scale(width: i32, height: i32, w_mm: f64, h_mm: f64) -> f64 {
+    if w_mm <= 0.0 || h_mm <= 0.0 {
+        return 1.0;
     }
+    let dpi = f64::from(width).hypot(f64::from(height)) / (w_mm.hypot(h_mm) / 25.4);
+    ((dpi / 96.0 * 4.0).round() / 4.0).clamp(1.0, 4.0)
+}
 
-    if max_x > 0 && max_y > 0 {
-        tracing::debug!(
-            width = max_x,
-            height = max_y,
-            "Got screen bounds via Hyprland"
-        );
-        return Some(ScreenBounds {
-            width: max_x,
-            height: max_y,
-        });
-    }
+/// Monitors from RandR 1.5 `GetMonitors` over x11rb
+///
+/// Empty when the server has no RandR 1.5 (or there is no X server).
+#[cfg(feature = "window-tracking")]
+fn get_monitors_via_randr() -> Vec<Monitor> {
+    use x11rb::connection::Connection as _;
+    use x11rb::protocol::randr::ConnectionExt as _;
+
+    let query = || -> Option<Vec<Monitor>> {
+        std::env::var_os("DISPLAY")?;
+        let (conn, screen) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen)?.root;
+        let version = conn.randr_query_version(1, 5).ok()?.reply().ok()?;
+        if (version.major_version, version.minor_version) < (1, 5) {
+            return None;
+        }
+        let reply = conn.randr_get_monitors(root, true).ok()?.reply().ok()?;
+        let monitors = reply
+            .monitors
+            .iter()
+            .map(|m| {
+                let (width, height) = (i32::from(m.width), i32::from(m.height));
+                Monitor {
+                    x: m.x.into(),
+                    y: m.y.into(),
+                    width,
+                    height,
+                    scale: dpi_scale(width, height, f64::from(m.width_in_millimeters), f64::from(m.height_in_millimeters)),
+                    compositor_scaled: false,
+                }
+            })
+            .collect();
+        Some(monitors)
+    };
+    query().unwrap_or_default()
+}
 
-    None
+/// Without x11rb there is no in-process RandR query
+#[cfg(not(feature = "window-tracking"))]
+fn get_monitors_via_randr() -> Vec<Monitor> {
+    Vec::new()
+}
+
+/// Query the monitor layout (RandR on X11, then Hyprland, then xrandr)
+///
+/// May spawn a process, so it belongs off the menu-open path; KWin reports
+/// its layout through the resident helper instead (see `monitors`).
+pub fn get_monitors() -> Vec<Monitor> {
+    if !crate::session::SessionKind::detect().is_wayland() {
+        let monitors = get_monitors_via_randr();
+        if !monitors.is_empty() {
+            return monitors;
+        }
+    }
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok() {
+        if let Ok(output) = Command::new("hyprctl").args(["monitors", "-j"]).output() {
+            let monitors = parse_hyprland_monitors(&String::from_utf8_lossy(&output.stdout));
+            if output.status.success() && !monitors.is_empty() {
+                return monitors;
+            }
+        }
+    }
+    match Command::new("xrandr").output() {
+        Ok(output) if output.status.success() => parse_xrandr_monitors(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
 }
 
 /// Query screen bounds via xrandr (for multi-monitor support)
@@ -586,7 +749,7 @@ mod tests {
             height: 1080,
         };
         let pos = CursorPosition::new(960, 540);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
 
         assert_eq!(clamped.x, 960);
         assert_eq!(clamped.y, 540);
@@ -600,7 +763,7 @@ mod tests {
             height: 1080,
         };
         let pos = CursorPosition::new(0, 0);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
 
         // min_x = EDGE_MARGIN + MENU_RADIUS = 20 + 150 = 170
         assert_eq!(clamped.x, EDGE_MARGIN + MENU_RADIUS);
@@ -615,7 +778,7 @@ mod tests {
             height: 1080,
         };
         let pos = CursorPosition::new(1920, 1080);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
 
         // max_x = width - EDGE_MARGIN - MENU_RADIUS = 1920 - 20 - 150 = 1750
         // max_y = height - EDGE_MARGIN - MENU_RADIUS = 1080 - 20 - 150 = 910
@@ -631,7 +794,7 @@ mod tests {
             height: 1080,
         };
         let pos = CursorPosition::new(10, 540);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
 
         assert_eq!(clamped.x, EDGE_MARGIN + MENU_RADIUS); // 170
         assert_eq!(clamped.y, 540); // Y unchanged
@@ -642,6 +805,64 @@ mod tests {
         assert_eq!(MENU_DIAMETER, 300);
         assert_eq!(EDGE_MARGIN, 20);
         assert_eq!(MENU_RADIUS, 150);
+        assert_eq!(edge_margin(MENU_DIAMETER as u32), EDGE_MARGIN);
+    }
+
+    #[test]
+    fn test_edge_clamping_follows_the_diameter() {
+        let bounds = ScreenBounds { width: 1366, height: 768 };
+        // A 600px ring (margin 40) on a 768px-high screen
+        let clamped = CursorPosition::new(0, 0).clamp_to_screen(&bounds, 600);
+        assert_eq!((clamped.x, clamped.y), (340, 340));
+        let clamped = CursorPosition::new(1366, 768).clamp_to_screen(&bounds, 600);
+        assert_eq!((clamped.x, clamped.y), (1366 - 340, 768 - 340));
+        // A 180px ring may sit closer to the edge
+        let clamped = CursorPosition::new(0, 0).clamp_to_screen(&bounds, 180);
+        assert_eq!((clamped.x, clamped.y), (102, 102));
+        // Too big for the screen: centred rather than clamped past the edge
+        let clamped = CursorPosition::new(0, 0).clamp_to_screen(&ScreenBounds { width: 500, height: 400 }, 600);
+        assert_eq!((clamped.x, clamped.y), (250, 200));
+    }
+
+    #[test]
+    fn test_parse_hyprland_monitors_converts_to_logical() {
+        let json = r#"[
+            {"name": "DP-1", "x": 0, "y": 0, "width": 3840, "height": 2160, "scale": 2.0},
+            {"name": "HDMI-A-1", "x": 1920, "y": 0, "width": 1920, "height": 1080, "scale": 1.0},
+            {"name": "broken", "x": 0}
+        ]"#;
+        let monitors = parse_hyprland_monitors(json);
+        assert_eq!(monitors.len(), 2);
+        assert_eq!((monitors[0].width, monitors[0].height, monitors[0].scale), (1920, 1080, 2.0));
+        assert!(monitors[0].compositor_scaled);
+        assert_eq!((monitors[1].x, monitors[1].width), (1920, 1920));
+        assert!(parse_hyprland_monitors("not json").is_empty());
+    }
+
+    #[test]
+    fn test_parse_xrandr_monitors_reads_geometry_and_dpi() {
+        let text = "\
+Screen 0: minimum 320 x 200, current 6400 x 2160, maximum 16384 x 16384
+DP-1 connected primary 3840x2160+0+0 (normal left inverted right x axis y axis) 597mm x 336mm
+   3840x2160     60.00*+
+HDMI-1 connected 2560x1440+3840+0 left (normal left inverted right x axis y axis) 597mm x 336mm
+DP-2 disconnected (normal left inverted right x axis y axis)
+VIRTUAL-1 connected 1920x1080+0+2160 (normal left inverted right x axis y axis) 0mm x 0mm
+";
+        let monitors = parse_xrandr_monitors(text);
+        assert_eq!(monitors.len(), 3);
+        // 27" 4K is ~163 DPI: 1.75 in quarter steps
+        assert_eq!((monitors[0].width, monitors[0].height, monitors[0].scale), (3840, 2160, 1.75));
+        assert!(!monitors[0].compositor_scaled);
+        // 27" 1440p is ~109 DPI
+        assert_eq!((monitors[1].x, monitors[1].scale), (3840, 1.25));
+        // No physical size: unscaled
+        assert_eq!((monitors[2].y, monitors[2].scale), (2160, 1.0));
+
+        // The RandR layout gives the bounds
+        let bounds = bounding_box(&monitors).unwrap();
+        assert_eq!((bounds.width, bounds.height), (6400, 3240));
+        assert!(bounding_box(&[]).is_none());
     }
 
     #[test]
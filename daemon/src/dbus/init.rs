@@ -2,12 +2,21 @@
 
 use std::sync::{Arc, Mutex};
 
+use crate::accessibility::SharedAccessibility;
 use crate::battery::SharedBatteryState;
 use crate::config::SharedConfig;
 use crate::gaming::SharedGamingMode;
+use crate::handles::DaemonHandles;
 use crate::hidpp::SharedHapticManager;
+use crate::hover_help::HoverHelpHandle;
 use crate::macros::{MacroEngine, MacroRecorder, SharedTriggerMap, TriggerMap};
+use crate::menu_dismissal::MenuDismissalHandle;
+use crate::overlay::OverlayHandle;
+use crate::profile_selection::SharedProfileSelection;
 use crate::profiles::SharedHardwareProfiles;
+use crate::self_test::{new_shared_report, SharedStartupReport, StartupReport};
+use crate::theme::SharedThemeManager;
+use crate::thumbwheel_mapping::{SharedThumbwheelMapper, ThumbwheelMapper};
 
 use super::service::JuhRadialService;
 use super::DBUS_PATH;
@@ -53,6 +62,14 @@ pub async fn init_dbus_service(
     let trigger_map = Arc::new(std::sync::RwLock::new(TriggerMap::default()));
     let (active_window_tx, _aw_rx) = tokio::sync::mpsc::unbounded_channel();
     let hardware_profiles = Arc::new(std::sync::RwLock::new(std::collections::HashMap::new()));
+    let (themes, accessibility, thumbwheel) = {
+        let c = config.read().map_err(|e| zbus::Error::Failure(format!("Config lock error: {}", e)))?;
+        (
+            crate::theme::load_shared_theme_manager(&c.theme),
+            crate::accessibility::new_shared_accessibility(&c.accessibility),
+            Arc::new(std::sync::RwLock::new(ThumbwheelMapper::new(&c.thumbwheel))),
+        )
+    };
     init_dbus_service_with_device(
         connection,
         battery_state,
@@ -66,6 +83,16 @@ pub async fn init_dbus_service(
         trigger_map,
         active_window_tx,
         hardware_profiles,
+        OverlayHandle::new(),
+        themes,
+        accessibility,
+        None,
+        thumbwheel,
+        new_shared_report(StartupReport::default()),
+        MenuDismissalHandle::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
     )
     .await
 }
@@ -85,6 +112,16 @@ pub async fn init_dbus_service_with_device(
     trigger_map: SharedTriggerMap,
     active_window_tx: tokio::sync::mpsc::UnboundedSender<String>,
     hardware_profiles: SharedHardwareProfiles,
+    overlay: OverlayHandle,
+    themes: SharedThemeManager,
+    accessibility: SharedAccessibility,
+    window_backend: Option<&'static str>,
+    thumbwheel: SharedThumbwheelMapper,
+    startup_report: SharedStartupReport,
+    menu_dismissal: MenuDismissalHandle,
+    hover_help: HoverHelpHandle,
+    profile_selection: SharedProfileSelection,
+    handles: DaemonHandles,
 ) -> zbus::Result<()> {
     let service = JuhRadialService::new_with_device(
         battery_state,
@@ -98,6 +135,16 @@ pub async fn init_dbus_service_with_device(
         trigger_map,
         active_window_tx,
         hardware_profiles,
+        overlay,
+        themes,
+        accessibility,
+        window_backend,
+        thumbwheel,
+        startup_report,
+        menu_dismissal,
+        hover_help,
+        profile_selection,
+        handles,
     );
 
     connection.object_server().at(DBUS_PATH, service).await?;
@@ -4,9 +4,22 @@
 //! This must be a single `#[interface]` impl block per zbus requirements.
 
 use zbus::{interface, object_server::SignalEmitter, fdo};
+use crate::action_latency::ActionKind;
+use crate::accessibility::current_animation_timings;
+use crate::actions::{Action, ActionError, ActionExecutor, ActionType};
+use crate::adhoc_menu::{AdHocError, AdHocMenu, AdHocOutcome, ADHOC_TIMEOUT, CANCELLED};
 use crate::config::Config;
+use crate::execution_policy::ExecutionPolicy;
+use crate::geometry::MAX_SLICE_COUNT;
 use crate::hidpp::{HapticEvent, Mx4HapticPattern};
+use crate::invocation::InvocationContext;
 use crate::macros::events_to_actions;
+use crate::menu_anchor::MenuAnchor;
+use crate::menu_payload::{ActionFeedback, DeviceStatus, MenuPayload, PayloadOptions, SliceConfirm, SliceHelp, SlotHints};
+use crate::menu_simulation::simulate_menu;
+use crate::profile_menu::ProfileMenu;
+use crate::stats::InputMethod;
+use crate::theme_transition::ResolvedTheme;
 use super::service::JuhRadialService;
 
 #[interface(name = "org.kde.juhradialmx.Daemon")]
@@ -22,14 +35,64 @@ impl JuhRadialService {
         x: i32,
         y: i32,
     ) -> fdo::Result<()> {
+        if !self.handles.panic_switch.is_armed() {
+            tracing::debug!(x, y, "ShowMenu suppressed - input interception disarmed");
+            return Ok(());
+        }
         if let Ok(gm) = self.gaming_mode.read() {
             if gm.should_suppress_overlay() {
                 tracing::debug!(x, y, "ShowMenu suppressed - gaming mode active");
                 return Ok(());
             }
         }
+        if let Some(pointer) = self.handles.slice_confirmation.update(|c| c.pressed()).flatten() {
+            // Second press on a require_confirmation slice: the menu is still up
+            tracing::debug!(x, y, ?pointer, "ShowMenu continues a pending confirmation");
+            self.handles.anchor.set(MenuAnchor::resumed(pointer));
+            return Ok(());
+        }
+        if self.flick_defers(x, y) {
+            tracing::debug!(x, y, "ShowMenu held back for a possible flick");
+            return Ok(());
+        }
 
+        let started = std::time::Instant::now();
         tracing::info!(x, y, "ShowMenu called - emitting MenuRequested signal");
+        if let Ok(mut thumbwheel) = self.thumbwheel.write() {
+            thumbwheel.set_menu_open(true);
+        }
+        self.menu_dismissal.update(|m, now| m.opened(now));
+        self.handles.monitors.pressed(x, y, self.handles.kwin.helper());
+        if self.local_stats_enabled() {
+            if let Ok(mut stats) = self.stats.lock() {
+                stats.menu_opened();
+            }
+        }
+        // Open with the window focused now, even before focus settled
+        if let Some(class) = self.handles.focus_settle.update(|s| s.flush(started)).flatten() {
+            crate::profile_selection::focus_window(&self.profile_selection, &self.thumbwheel, &class, &self.handles.persistence);
+            // Wakes the focus consumer for the rest (hardware profile)
+            let _ = self.active_window_tx.send(class);
+        }
+        self.open_profile(&emitter).await?;
+        let context = self
+            .thumbwheel
+            .read()
+            .map(|m| InvocationContext::now().in_window(m.active_class(), Some(m.menu_profile())))
+            .unwrap_or_else(|_| InvocationContext::now());
+        self.handles.menu_log.update(|l| l.opened((x, y), context.timestamp_ms, false, started));
+        self.handles.invocation.opened(context.at((x, y)));
+        // Opened with the last reported window; a report racing the press
+        // may still swap the profile (`late_focus`)
+        let profile = self.thumbwheel.read().ok().and_then(|m| m.profile_hash());
+        let late_window = self
+            .config
+            .read()
+            .map(|c| c.menu.late_focus())
+            .unwrap_or(std::time::Duration::from_millis(crate::late_focus::LATE_FOCUS_MS));
+        self.handles.late_focus.update(|l| l.opened(started, profile, late_window));
+        self.handles.anchor.reset();
+        self.handles.latency.record_menu_open(started.elapsed());
         Self::menu_requested(&emitter, x, y).await?;
         Ok(())
     }
@@ -44,6 +107,120 @@ impl JuhRadialService {
         Ok(())
     }
 
+    /// Overlay reports how the menu closed (`slice` -1 = cancelled)
+    ///
+    /// Resumes thumb-wheel bindings, runs the picked slot of a profile menu,
+    /// starts its cooldown and stops the auto-dismiss countdown; the
+    /// invocation is only recorded when `telemetry.local_stats` is enabled.
+    async fn report_menu_closed(&self, slice: i32, input: &str) -> fdo::Result<()> {
+        self.handles.late_focus.update(|l| l.closed());
+        if let Ok(mut thumbwheel) = self.thumbwheel.write() {
+            thumbwheel.set_menu_open(false);
+        }
+        self.menu_dismissal.update(|m, _| m.closed());
+        self.hover_help.update(|h, _| h.closed());
+        self.handles.prewarm.update(|p, _| p.closed());
+        self.handles.wheel_axis.update(|w| w.closed());
+        self.handles.slice_confirmation.update(|c| c.closed());
+        self.handles.dwell_confirm.update(|d, _| d.closed());
+        let now = std::time::Instant::now();
+        self.handles.menu_log.update(|l| l.closed(u8::try_from(slice).ok(), input, now));
+        // The overlay runs only the Settings ring; a profile menu's pick runs
+        // here, and only that run starts the slot's cooldown
+        let profile_menu = self.profile_menu.lock().ok().and_then(|mut m| m.take());
+        if let Some(action) = profile_menu.as_ref().and_then(|m| m.action(slice)) {
+            let slot = slice as u8;
+            if self.handles.slice_cooldown.update(|c| c.is_cooling(slot, now)).unwrap_or(false) {
+                tracing::debug!(slot, "Picked a slice still cooling - not running it");
+                crate::hidpp::emit_shared(&self.haptic_manager, HapticEvent::InvalidAction);
+            } else {
+                self.handles.slice_cooldown.update(|c| c.selected(slot, now));
+                self.run_profile_slice(slot, action.clone());
+            }
+        }
+        self.handles.slice_cooldown.update(|c| c.closed());
+        // Ad-hoc menus answer their caller and are not profile invocations
+        if self.adhoc.complete(slice) || !self.local_stats_enabled() {
+            return Ok(());
+        }
+        let input = InputMethod::from_name(input)
+            .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown input method: {}", input)))?;
+        let slice = u8::try_from(slice).ok().filter(|s| *s < MAX_SLICE_COUNT);
+        let mut stats = self
+            .stats
+            .lock()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        if let Some(event) = stats.menu_closed(&self.profile_name(), slice, input, &self.handles.persistence) {
+            tracing::debug!(slice = ?event.slice, open_ms = event.open_ms, "Menu invocation recorded");
+        }
+        Ok(())
+    }
+
+    /// Overlay reports whether the picked slot's action started (`error`
+    /// empty) or not; a failure logs the menu session at debug level
+    ///
+    /// `kind` names the action type it ran (as in the `action_latency`
+    /// histograms) and `elapsed_us` how long it took to start; executions
+    /// of a known kind are recorded with the daemon's own. An empty `kind`
+    /// records nothing.
+    async fn report_action_result(&self, slice: i32, error: &str, kind: &str, elapsed_us: u64) -> fdo::Result<()> {
+        let error = Some(error).filter(|e| !e.is_empty());
+        self.handles.menu_log.action_result(u8::try_from(slice).ok(), error);
+        if let Some(kind) = ActionKind::from_name(kind) {
+            self.handles.latency.record_action(kind, std::time::Duration::from_micros(elapsed_us));
+        } else if !kind.is_empty() {
+            tracing::debug!(kind, "ReportActionResult with an unknown action kind - latency not recorded");
+        }
+        Ok(())
+    }
+
+    /// Overlay asks whether it may spawn `argv`, an `exec` slice of the
+    /// Settings ring, and counts it against the command rate limit
+    ///
+    /// Checked under `execution.policy` like an ad-hoc menu's commands: the
+    /// allowlist and `max_commands_per_minute` apply, `confirm` does not.
+    /// Refusals are `AccessDenied` errors carrying the reason.
+    async fn check_command(&self, argv: Vec<String>) -> fdo::Result<()> {
+        let policy = self
+            .config
+            .read()
+            .map(|c| ExecutionPolicy::for_adhoc(&c.execution, &self.handles.command_rate))
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        policy.check_argv(&argv).map_err(|e| {
+            tracing::warn!(?argv, error = %e, "Settings ring command refused by execution policy");
+            fdo::Error::AccessDenied(e.to_string())
+        })
+    }
+
+    /// The `JUH_*` variables of the last menu invocation with `slice`
+    /// picked (-1 = none), for a command the overlay spawns
+    ///
+    /// The overlay sets them in the environment of the Settings ring's
+    /// `exec` slices, as the executor does for the commands it runs.
+    async fn get_invocation(&self, slice: i32) -> std::collections::HashMap<String, String> {
+        self.handles.invocation.variables(u8::try_from(slice).ok())
+    }
+
+    /// The last `n` menu sessions, newest first, as JSON (see `menu_log`)
+    async fn get_last_sessions(&self, n: u32) -> fdo::Result<String> {
+        let json = self.handles.menu_log.update(|l| l.to_json(n as usize)).unwrap_or_default();
+        serde_json::to_string(&json).map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    /// Overlay reports where the menu for `MenuRequested(press)` was drawn
+    ///
+    /// `CursorMoved` offsets are rebased onto `center`, so a ring clamped away
+    /// from a screen edge is hit-tested from its visible centre.
+    async fn report_menu_placement(&self, press_x: i32, press_y: i32, center_x: i32, center_y: i32) -> fdo::Result<()> {
+        let anchor = MenuAnchor::placed((press_x, press_y), (center_x, center_y));
+        if anchor.is_clamped() {
+            tracing::debug!(press_x, press_y, center_x, center_y, "Menu clamped away from the press point");
+        }
+        self.handles.anchor.set(anchor);
+        self.handles.invocation.placed((center_x, center_y));
+        Ok(())
+    }
+
     /// Execute an action by its identifier
     async fn execute_action(
         &self,
@@ -66,6 +243,7 @@ impl JuhRadialService {
         let preset = crate::presets::Preset::from_name(&name)
             .ok_or_else(|| fdo::Error::InvalidArgs(format!("Unknown preset: {}", name)))?;
 
+        let handles = self.handles.clone();
         std::thread::spawn(move || {
             let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
                 Ok(rt) => rt,
@@ -75,7 +253,7 @@ impl JuhRadialService {
                 }
             };
             rt.block_on(async move {
-                if let Err(e) = crate::presets::execute_preset(preset).await {
+                if let Err(e) = crate::presets::execute_preset(preset, &handles).await {
                     tracing::warn!(error = %e, preset = preset.as_str(), "Preset execution failed");
                 }
             });
@@ -84,6 +262,211 @@ impl JuhRadialService {
         Ok(())
     }
 
+    /// Paste the clipboard as plain text into the focused window
+    ///
+    /// Runs on its own thread like `ExecutePreset` (the clipboard helpers
+    /// block). An empty clipboard plays the invalid-action haptic.
+    async fn paste_plain(&self) -> fdo::Result<()> {
+        tracing::info!("PastePlain called");
+        let haptic_manager = self.haptic_manager.clone();
+        let handles = self.handles.clone();
+        std::thread::spawn(move || {
+            let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt,
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to build runtime for plain-text paste");
+                    return;
+                }
+            };
+            let action = Action {
+                action_type: ActionType::PastePlain,
+                label: None,
+                icon: None,
+                description: None,
+                require_confirmation: false,
+                cooldown_ms: 0,
+            };
+            match rt.block_on(ActionExecutor::execute(&action, &handles)) {
+                Ok(()) => {}
+                Err(ActionError::InvalidAction) => {
+                    crate::hidpp::emit_shared(&haptic_manager, HapticEvent::InvalidAction);
+                }
+                Err(e) => tracing::warn!(error = %e, "Paste as plain text failed"),
+            }
+        });
+        Ok(())
+    }
+
+    /// Run the menu pipeline for a window class without hardware (dry run)
+    ///
+    /// Returns the resolved profile, payload and slices as JSON. A slot of the
+    /// profile (0-7 on the default ring) also executes that slice's action
+    /// under the execution policy and adds the outcome; -1 only inspects. Stats, haptics and the overlay are not
+    /// touched.
+    async fn simulate_menu(&self, window_class: String, slice: i32) -> fdo::Result<String> {
+        tracing::info!(window_class = %window_class, slice, "SimulateMenu called");
+        let profiles = crate::profiles::ProfileManager::load_or_create()
+            .map_err(|e| fdo::Error::Failed(format!("Failed to load profiles: {}", e)))?;
+        let theme = self
+            .themes
+            .read()
+            .map(|t| t.current().clone())
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        let accessibility = self
+            .accessibility
+            .read()
+            .map(|a| a.clone())
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        let (policy, left_handed, no_blur_min_opacity, slice_tinting) = self
+            .config
+            .read()
+            .map(|c| {
+                (
+                    ExecutionPolicy::from_config(&c.execution, &self.handles.command_rate),
+                    c.input.left_handed,
+                    c.menu.no_blur_min_opacity,
+                    c.accessibility.slice_tinting,
+                )
+            })
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+
+        // Executors may block (KWin/D-Bus helpers), so run off the zbus
+        // executor like ExecutePreset and wait for the report.
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tint_theme = theme.clone();
+        let handles = self.handles.clone();
+        std::thread::spawn(move || {
+            let result = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(rt) => rt.block_on(simulate_menu(
+                    &profiles,
+                    &window_class,
+                    slice,
+                    &theme,
+                    &accessibility,
+                    left_handed,
+                    &policy,
+                    &handles,
+                )),
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to build runtime for menu simulation");
+                    return;
+                }
+            };
+            let _ = tx.send(result);
+        });
+        match rx.await {
+            Ok(Ok(mut report)) => {
+                report.payload = report
+                    .payload
+                    .with_blur(self.handles.blur.is_available(), no_blur_min_opacity)
+                    .with_slice_tinting(&tint_theme, slice_tinting);
+                Ok(report.to_json())
+            }
+            Ok(Err(e)) => Err(fdo::Error::InvalidArgs(e.to_string())),
+            Err(_) => Err(fdo::Error::Failed("Menu simulation did not complete".to_string())),
+        }
+    }
+
+    /// Show a one-off menu for another tool at the cursor (see `adhoc_menu`)
+    ///
+    /// Returns the picked slot once the menu closes, or -1 when it was
+    /// cancelled, dismissed or left alone for 10s. The picked action runs in
+    /// the daemon; `execution.policy = "allowlist"` applies to commands.
+    async fn show_adhoc_menu(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        definition: String,
+    ) -> fdo::Result<i32> {
+        if !self.handles.panic_switch.is_armed() {
+            return Err(fdo::Error::Failed("Menu disabled - input interception disarmed".to_string()));
+        }
+        if self.gaming_mode.read().is_ok_and(|gm| gm.should_suppress_overlay()) {
+            return Err(fdo::Error::Failed("Menu suppressed - gaming mode active".to_string()));
+        }
+        let menu = AdHocMenu::parse(&definition).map_err(|e| fdo::Error::InvalidArgs(e.to_string()))?;
+        let descriptions = menu.descriptions();
+        let help = SliceHelp::build(self.hover_help_delay(), Some(&descriptions));
+        let payload = self
+            .adhoc_payload(&menu, help.clone())
+            .ok_or_else(|| fdo::Error::InvalidArgs(AdHocError::PayloadTooLarge.to_string()))?;
+        if self.menu_dismissal.update(|m, _| m.is_open()).unwrap_or(false) {
+            return Err(fdo::Error::Failed(AdHocError::Busy.to_string()));
+        }
+        let outcome = self
+            .adhoc
+            .begin(ADHOC_TIMEOUT)
+            .map_err(|e| fdo::Error::Failed(e.to_string()))?;
+
+        // The cursor query shells out; keep it off the zbus executor
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let helper = self.handles.kwin.helper().clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(crate::cursor::get_cursor_position(&helper));
+        });
+        let pos = rx
+            .await
+            .map_err(|_| fdo::Error::Failed("Cursor position query did not complete".to_string()))?;
+
+        tracing::info!(x = pos.x, y = pos.y, "ShowAdHocMenu called - showing ad-hoc menu");
+        if let Ok(mut thumbwheel) = self.thumbwheel.write() {
+            thumbwheel.set_menu_open(true);
+        }
+        self.menu_dismissal.update(|m, now| m.opened(now));
+        self.hover_help.update(|h, _| h.opened(help.as_ref().map(SliceHelp::delay)));
+        let prewarm = self.config.read().ok().and_then(|c| c.menu.prewarm());
+        self.handles.prewarm.update(|p, _| p.opened(prewarm, menu.actions()));
+        // Ad-hoc slices never hold a wheel axis nor need confirming, and
+        // are click-to-select already
+        if let Ok(mut profile_menu) = self.profile_menu.lock() {
+            *profile_menu = None;
+        }
+        self.handles.wheel_axis.update(|w| w.closed());
+        self.handles.slice_confirmation.update(|c| c.closed());
+        self.handles.slice_cooldown.update(|c| c.closed());
+        self.handles.dwell_confirm.update(|d, _| d.opened(None, Vec::new()));
+        self.handles.anchor.reset();
+        let context = InvocationContext::now().at((pos.x, pos.y));
+        self.handles.menu_log.update(|l| l.opened((pos.x, pos.y), context.timestamp_ms, true, std::time::Instant::now()));
+        self.handles.invocation.opened(context);
+        Self::menu_payload(&emitter, &payload).await?;
+        Self::menu_requested(&emitter, pos.x, pos.y).await?;
+
+        let slot = match outcome.await {
+            Ok(AdHocOutcome::Closed(slot)) => slot,
+            Ok(AdHocOutcome::TimedOut) | Err(_) => {
+                tracing::info!(timeout = ?ADHOC_TIMEOUT, "Ad-hoc menu not answered - dismissing");
+                // The overlay reports the dismissal too; reset here in case it
+                // is gone
+                if let Ok(mut thumbwheel) = self.thumbwheel.write() {
+                    thumbwheel.set_menu_open(false);
+                }
+                self.menu_dismissal.update(|m, _| m.closed());
+                self.hover_help.update(|h, _| h.closed());
+                self.handles.prewarm.update(|p, _| p.closed());
+                self.handles.dwell_confirm.update(|d, _| d.closed());
+                Self::dismiss_menu_signal(&emitter).await?;
+                return Ok(CANCELLED);
+            }
+        };
+        let Some(action) = menu.action(slot).cloned() else {
+            tracing::info!(slot, "Ad-hoc menu closed without a selection");
+            return Ok(CANCELLED);
+        };
+        let policy = self
+            .config
+            .read()
+            .map(|c| ExecutionPolicy::for_adhoc(&c.execution, &self.handles.command_rate))
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+
+        let context = self.handles.invocation.current()
+            .unwrap_or_else(InvocationContext::now)
+            .with_slice(slot as u8);
+
+        crate::profile_menu::spawn_slice_action(action, slot as u8, policy, context, self.handles.clone());
+        tracing::info!(slot, "Ad-hoc menu selection executed");
+        Ok(slot)
+    }
+
     // =========================================================================
     // MENU SIGNALS
     // =========================================================================
@@ -91,12 +474,61 @@ impl JuhRadialService {
     #[zbus(signal)]
     async fn menu_requested(emitter: &SignalEmitter<'_>, x: i32, y: i32) -> zbus::Result<()>;
 
+    /// Render state (JSON) for the menu about to open; precedes MenuRequested
+    #[zbus(signal)]
+    async fn menu_payload(emitter: &SignalEmitter<'_>, payload: &str) -> zbus::Result<()>;
+
+    /// Effective animation timings (JSON) changed outside a menu invocation
+    #[zbus(signal)]
+    async fn animation_timings_changed(emitter: &SignalEmitter<'_>, timings: String) -> zbus::Result<()>;
+
+    /// The resolved theme changed (JSON: `from`, `to`, `transition_ms`)
+    #[zbus(signal)]
+    async fn theme_changed(emitter: &SignalEmitter<'_>, change: String) -> zbus::Result<()>;
+
+    /// An edited theme file was rejected and the previous version kept
+    /// (JSON: `theme`, `path`, `errors`; emitted by the theme hot-reload
+    /// task, declared here for introspection)
+    #[zbus(signal)]
+    async fn theme_reload_failed(emitter: &SignalEmitter<'_>, failure: String) -> zbus::Result<()>;
+
+    /// A SIGHUP reload swapped in config, themes and profiles (JSON summary)
+    #[zbus(signal)]
+    async fn reload_completed(emitter: &SignalEmitter<'_>, summary: String) -> zbus::Result<()>;
+
+    /// A SIGHUP reload was rolled back (JSON: `stage`, `errors`, `issues`)
+    #[zbus(signal)]
+    async fn reload_failed(emitter: &SignalEmitter<'_>, failure: String) -> zbus::Result<()>;
+
     #[zbus(signal, name = "HideMenu")]
     async fn hide_menu_signal(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
 
+    /// Auto-dismiss timeout elapsed: close without executing (emitted by the
+    /// gesture loop, declared here for introspection)
+    #[zbus(signal, name = "DismissMenu")]
+    async fn dismiss_menu_signal(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    /// A flick picked `slot` of a `slots`-slice ring: run it without showing
+    /// the menu (emitted by the gesture loop, declared here for
+    /// introspection)
+    #[zbus(signal)]
+    async fn flick_selected(emitter: &SignalEmitter<'_>, slot: u8, slots: u8) -> zbus::Result<()>;
+
     #[zbus(signal)]
     async fn slice_selected(emitter: &SignalEmitter<'_>, index: u8) -> zbus::Result<()>;
 
+    /// The highlight rested on `slot` for `menu.hover_help_ms`: show its
+    /// description (emitted by the hover-help task, declared here for
+    /// introspection)
+    #[zbus(signal)]
+    async fn highlight_stable(emitter: &SignalEmitter<'_>, slot: u8) -> zbus::Result<()>;
+
+    /// Released on `require_confirmation` slot `slot`: the menu stays open
+    /// for `timeout_ms` waiting for a second press (emitted by the gesture
+    /// loop, declared here for introspection)
+    #[zbus(signal)]
+    async fn awaiting_confirmation(emitter: &SignalEmitter<'_>, slot: u8, timeout_ms: u32) -> zbus::Result<()>;
+
     #[zbus(signal)]
     async fn action_executed(emitter: &SignalEmitter<'_>, action_id: String) -> zbus::Result<()>;
 
@@ -128,16 +560,47 @@ impl JuhRadialService {
     // =========================================================================
 
     /// Notify that a slice is being hovered
+    ///
+    /// Starts the hover-help and pre-warm countdowns for that slice.
     async fn notify_slice_hover(
         &self,
         #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
         index: u8,
     ) -> fdo::Result<()> {
         tracing::debug!(index, "Slice hover notification");
+        self.handles.menu_log.update(|l| l.highlighted(Some(index), std::time::Instant::now()));
+        self.menu_dismissal.update(|m, now| m.activity(now));
+        self.hover_help.update(|h, now| h.highlighted(Some(index), now));
+        self.handles.prewarm.update(|p, now| p.highlighted(Some(index), now));
+        self.handles.wheel_axis.update(|w| w.highlighted(Some(index)));
+        self.handles.slice_confirmation.update(|c| c.highlighted(Some(index)));
+        self.handles.slice_cooldown.update(|c| c.highlighted(Some(index)));
+        self.handles.dwell_confirm.update(|d, now| d.highlighted(Some(index), now));
         Self::slice_selected(&emitter, index).await?;
         Ok(())
     }
 
+    /// Overlay saw wheel or keyboard navigation, or the pointer left the
+    /// slices ("pointer"); resets the auto-dismiss countdown
+    async fn notify_menu_activity(&self, input: &str) -> fdo::Result<()> {
+        let now = std::time::Instant::now();
+        self.handles.menu_log.update(|l| match input {
+            "pointer" => l.highlighted(None, now),
+            _ => l.navigated(input, now),
+        });
+        if input == "pointer" {
+            self.hover_help.update(|h, now| h.highlighted(None, now));
+            self.handles.prewarm.update(|p, now| p.highlighted(None, now));
+            self.handles.wheel_axis.update(|w| w.highlighted(None));
+            self.handles.slice_confirmation.update(|c| c.highlighted(None));
+            self.handles.slice_cooldown.update(|c| c.highlighted(None));
+            self.handles.dwell_confirm.update(|d, now| d.highlighted(None, now));
+        }
+        let reset = self.menu_dismissal.update(|m, now| m.activity(now)).unwrap_or(false);
+        tracing::trace!(input, reset, "Menu activity");
+        Ok(())
+    }
+
     /// Trigger haptic feedback for a specific event
     async fn trigger_haptic(&self, event: &str) -> fdo::Result<()> {
         tracing::info!(event, "TriggerHaptic D-Bus method called");
@@ -152,20 +615,7 @@ impl JuhRadialService {
             }
         };
 
-        tracing::debug!("Attempting to lock haptic_manager");
-        match self.haptic_manager.lock() {
-            Ok(mut manager) => {
-                tracing::debug!("Lock acquired, calling emit()");
-                match manager.emit(haptic_event) {
-                    Ok(()) => tracing::info!("Haptic emit succeeded"),
-                    Err(e) => tracing::warn!(error = %e, "Haptic emit failed"),
-                }
-            }
-            Err(e) => {
-                tracing::error!(error = %e, "Failed to lock haptic manager");
-            }
-        }
-
+        crate::hidpp::emit_shared(&self.haptic_manager, haptic_event);
         Ok(())
     }
 
@@ -188,97 +638,56 @@ impl JuhRadialService {
         Ok(())
     }
 
-    /// Set the active profile
+    /// Select a profile by name, or `next` / `previous` in name order
+    ///
+    /// The selection wins over window-class matching until the focused window
+    /// class changes, and is kept across restarts. `profile_switch` slices
+    /// call this; the menu-appear haptic confirms the switch.
     async fn set_profile(&self, name: &str) -> fdo::Result<()> {
         tracing::info!(name, "SetProfile called");
-        Ok(())
+        match crate::profile_selection::apply_switch(&self.profile_selection, &self.thumbwheel, name, &self.handles.persistence) {
+            Ok(_) => {
+                crate::hidpp::emit_shared(&self.haptic_manager, HapticEvent::MenuAppear);
+                Ok(())
+            }
+            Err(crate::profiles::ProfileError::NotFound(name)) => {
+                Err(fdo::Error::InvalidArgs(format!("Unknown profile: {}", name)))
+            }
+            Err(e) => Err(fdo::Error::Failed(format!("Profile switch failed: {}", e))),
+        }
+    }
+
+    /// Approve the current profiles.json for the `confirm` execution policy
+    ///
+    /// Returns the approved content hash. Any later edit revokes the approval.
+    async fn approve_profile(&self) -> fdo::Result<String> {
+        let profiles_path = crate::profiles::get_profiles_path();
+        tracing::info!(path = %profiles_path.display(), "ApproveProfile called");
+        crate::execution_policy::approve_profiles(
+            &profiles_path,
+            &crate::execution_policy::get_approval_path(),
+        )
+        .map(|hash| format!("{:016x}", hash))
+        .map_err(|e| fdo::Error::Failed(format!("Failed to approve profiles: {}", e)))
     }
 
     /// Reload configuration from disk
-    async fn reload_config(&self) -> fdo::Result<()> {
+    ///
+    /// Also re-reads the theme directories and profiles.json. A missing
+    /// theme falls back to the default here; SIGHUP reloads the same
+    /// sources but refuses references that do not resolve (see
+    /// `crate::reload`).
+    async fn reload_config(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
         tracing::info!("ReloadConfig called - reloading configuration from disk");
 
         match Config::load_default() {
             Ok(new_config) => {
-                let haptic_config = new_config.haptics.clone();
-                let thumbwheel_config = new_config.thumbwheel.clone();
-                let remapped_cids = new_config.remapped_button_cids();
-
-                match self.config.write() {
-                    Ok(mut config) => {
-                        *config = new_config;
-                        tracing::info!(
-                            haptics_enabled = config.haptics.enabled,
-                            default_pattern = %config.haptics.default_pattern,
-                            theme = %config.theme,
-                            "Configuration reloaded successfully"
-                        );
-                    }
-                    Err(e) => {
-                        tracing::error!(error = %e, "Failed to acquire config write lock");
-                        return Err(fdo::Error::Failed(format!("Lock error: {}", e)));
-                    }
-                }
-
-                match self.haptic_manager.lock() {
-                    Ok(mut manager) => {
-                        manager.update_from_config(&haptic_config);
-                        tracing::info!(
-                            default_pattern = %haptic_config.default_pattern,
-                            menu_appear = %haptic_config.per_event.menu_appear,
-                            slice_change = %haptic_config.per_event.slice_change,
-                            confirm = %haptic_config.per_event.confirm,
-                            invalid = %haptic_config.per_event.invalid,
-                            "Haptic manager updated with new patterns"
-                        );
-
-                        // Re-apply volatile thumb-wheel divert from the new
-                        // config. Inversion is software-side (hidraw reader), so
-                        // only the divert state is pushed to the device here.
-                        if manager.thumbwheel_supported() {
-                            match manager.set_thumbwheel_reporting(thumbwheel_config.is_diverted(), false) {
-                                Ok(()) => tracing::info!(
-                                    diverted = thumbwheel_config.is_diverted(),
-                                    "Thumb-wheel reporting re-applied on reload"
-                                ),
-                                Err(e) => tracing::warn!(error = %e, "Failed to re-apply thumb-wheel reporting"),
-                            }
-                        }
-
-                        // Re-apply non-gesture button diverts so a newly
-                        // reassigned button takes effect immediately, and a
-                        // button returned to its native default has its divert
-                        // cleared, without a reconnect. Done under the manager
-                        // lock on the zbus executor (no Tokio runtime here, so
-                        // spawn_blocking is unavailable). The HID++ calls are
-                        // quick when connected and return immediately when not.
-                        let remapped: std::collections::HashSet<u16> =
-                            remapped_cids.into_iter().collect();
-                        for cid in Config::managed_button_cids() {
-                            let _ = manager.set_button_divert(cid, remapped.contains(&cid));
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!(error = %e, "Failed to lock haptic manager for update");
-                        return Err(fdo::Error::Failed(format!("Haptic manager lock error: {}", e)));
-                    }
-                }
-
-                // Refresh the shared per-app hardware profile map from
-                // profiles.json so a UI save takes effect without a daemon
-                // restart. The focus-change consumer reads this map directly.
-                let hardware = crate::profiles::load_hardware_profiles();
-                match self.hardware_profiles.write() {
-                    Ok(mut map) => {
-                        tracing::info!(count = hardware.len(), "Per-app hardware profiles reloaded");
-                        *map = hardware;
-                    }
-                    Err(e) => {
-                        tracing::error!(error = %e, "Failed to lock hardware profiles for reload");
-                    }
-                }
-
-                Ok(())
+                let themes = crate::theme::load_theme_manager(&new_config.theme);
+                let profiles = crate::profiles::ProfileManager::load_or_create();
+                self.apply_reload(&emitter, new_config, themes, profiles).await
             }
             Err(e) => {
                 tracing::error!(error = %e, "Failed to reload configuration");
@@ -299,6 +708,23 @@ impl JuhRadialService {
         Ok(())
     }
 
+    /// Called by the resident KWin helper on every cursor move
+    ///
+    /// Only stored: menu triggers read it instead of running a one-shot
+    /// script per press.
+    async fn report_cursor_position(&self, x: i32, y: i32) -> fdo::Result<()> {
+        self.handles.kwin.helper().report_cursor(x, y);
+        Ok(())
+    }
+
+    /// Called by the resident KWin helper when it loads and when screens
+    /// change, with a JSON array of `{name, x, y, width, height, scale}`
+    /// (logical pixels); sizes the menu per monitor
+    async fn report_screens(&self, screens: String) -> fdo::Result<()> {
+        self.handles.monitors.report_kwin(&screens);
+        Ok(())
+    }
+
     /// Called by KWin script to report cursor position and show menu
     async fn show_menu_at_cursor(
         &self,
@@ -306,7 +732,34 @@ impl JuhRadialService {
         x: i32,
         y: i32,
     ) -> fdo::Result<()> {
+        if !self.handles.panic_switch.is_armed() {
+            tracing::debug!(x, y, "ShowMenuAtCursor suppressed - input interception disarmed");
+            return Ok(());
+        }
+        if let Some(pointer) = self.handles.slice_confirmation.update(|c| c.pressed()).flatten() {
+            tracing::debug!(x, y, ?pointer, "ShowMenuAtCursor continues a pending confirmation");
+            self.handles.anchor.set(MenuAnchor::resumed(pointer));
+            return Ok(());
+        }
+        if self.flick_defers(x, y) {
+            tracing::debug!(x, y, "ShowMenuAtCursor held back for a possible flick");
+            return Ok(());
+        }
         tracing::info!(x, y, "ShowMenuAtCursor called from KWin script");
+        self.menu_dismissal.update(|m, now| m.opened(now));
+        // No payload on this path, so no descriptions to show
+        self.hover_help.update(|h, _| h.opened(None));
+        self.handles.prewarm.update(|p, _| p.opened(None, Vec::new()));
+        // Nor wheel or confirmation hints, so no routing for either, and no
+        // dwell the overlay could draw
+        if let Ok(mut profile_menu) = self.profile_menu.lock() {
+            *profile_menu = None;
+        }
+        self.handles.wheel_axis.update(|w| w.closed());
+        self.handles.slice_confirmation.update(|c| c.closed());
+        self.handles.slice_cooldown.update(|c| c.closed());
+        self.handles.dwell_confirm.update(|d, _| d.opened(None, Vec::new()));
+        self.handles.anchor.reset();
         Self::menu_requested(&emitter, x, y).await?;
         Ok(())
     }
@@ -324,6 +777,33 @@ impl JuhRadialService {
         }
     }
 
+    /// Why the last battery query failed, as (kind, message)
+    ///
+    /// `kind` is `permission_denied`, `device_not_found`, `timeout`,
+    /// `conflict` (another program competes for the device; the message
+    /// names it) or `protocol`; both are empty after a good reading.
+    async fn get_battery_error(&self) -> fdo::Result<(String, String)> {
+        let state = self.battery_state.read().await;
+        Ok(state
+            .error
+            .as_ref()
+            .map(|e| (e.kind.as_str().to_string(), e.message.clone()))
+            .unwrap_or_default())
+    }
+
+    /// Battery samples from the last 24 hours, oldest first
+    ///
+    /// Each entry is (unix seconds, percentage, charging). Kept in memory
+    /// only, so the curve starts over when the daemon restarts.
+    async fn get_battery_history(&self) -> fdo::Result<Vec<(u64, u8, bool)>> {
+        let state = self.battery_state.read().await;
+        Ok(state
+            .history
+            .samples()
+            .map(|s| (s.timestamp, s.percentage, s.charging))
+            .collect())
+    }
+
     // =========================================================================
     // DPI METHODS
     // =========================================================================
@@ -801,6 +1281,290 @@ impl JuhRadialService {
     #[zbus(signal)]
     async fn gaming_mode_changed(emitter: &SignalEmitter<'_>, enabled: bool) -> zbus::Result<()>;
 
+    // =========================================================================
+    // THEME METHODS
+    // =========================================================================
+
+    /// List available themes as JSON with their source and theme.json path
+    async fn list_themes(&self) -> fdo::Result<String> {
+        let manager = self
+            .themes
+            .read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+
+        let list: Vec<serde_json::Value> = manager
+            .themes_with_sources()
+            .into_iter()
+            .map(|(theme, origin)| {
+                let description = crate::bundled_themes::get_bundled_theme_info(&theme.name)
+                    .filter(|_| origin.source == crate::theme::ThemeSource::Bundled)
+                    .map(|info| info.description_in(&crate::locale::Locale::from_env()));
+                serde_json::json!({
+                    "name": theme.name,
+                    "display_name": theme.display_name.current(),
+                    "description": description,
+                    "source": origin.source,
+                    "path": origin.path,
+                })
+            })
+            .collect();
+
+        serde_json::to_string(&list)
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    // =========================================================================
+    // LOCAL STATISTICS
+    // =========================================================================
+
+    /// Aggregate counts from the local stats file as JSON
+    async fn get_stats_summary(&self) -> fdo::Result<String> {
+        let summary = self
+            .stats
+            .lock()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?
+            .summary();
+        let json = serde_json::json!({
+            "enabled": self.local_stats_enabled(),
+            "summary": summary,
+        });
+        serde_json::to_string(&json)
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    // =========================================================================
+    // STATUS / OVERLAY SUPERVISION
+    // =========================================================================
+
+    /// Daemon status as JSON (version, device, overlay supervision state)
+    async fn status(&self) -> fdo::Result<String> {
+        let connection = self.connection_status().await;
+        let status = serde_json::json!({
+            "version": self.version,
+            "features": crate::features::enabled(),
+            "device_mode": self.device_mode,
+            "device_name": self.device_name,
+            "connection": connection,
+            "hid_backend": crate::hidpp::HID_BACKEND,
+            "device_watcher": self.handles.device_watcher.status(),
+            "overlay": self.overlay.snapshot(),
+            "window_tracker": self.window_backend,
+            "session": crate::session::Environment::detect(self.window_backend),
+            "thumbwheel": self.thumbwheel.read().ok().map(|m| m.status()),
+            "startup_report": self.startup_report.read().ok().map(|r| r.to_json()),
+            "conflicts": self.handles.conflicts.last().conflicts,
+            "action_latency": self.handles.latency.snapshot().to_json(),
+            "late_focus": self.handles.late_focus.stats(),
+            "focus_settle": self.handles.focus_settle.stats(),
+            "profile_collisions": self.thumbwheel.read().ok().map(|m| m.profile_collisions().to_vec()),
+            "content_hashes": self.content_hashes(),
+            "paths": crate::paths::data_dir().to_json(),
+            "input_armed": self.handles.panic_switch.is_armed(),
+            "execution": self.handles.binaries.strategy().to_json(),
+            "metrics": self.handles.metrics.status(),
+            "quiet_hours": self.handles.quiet_hours.status(),
+            "idle_effect": self.handles.idle_effect.status(),
+            "persistence": self.handles.persistence.status(),
+        });
+        serde_json::to_string(&status)
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    /// Re-run the device access self-test and return the fresh report
+    ///
+    /// Lets the settings UI confirm a fix (udev rules, group membership)
+    /// without restarting the daemon.
+    async fn diagnostics(&self) -> fdo::Result<String> {
+        let report = crate::self_test::run_self_test(&self.handles.conflicts);
+        let mut json = report.to_json();
+        json["connect_timing"] = self
+            .connection_status()
+            .await
+            .map(|c| c["connect_timing"].clone())
+            .unwrap_or_default();
+        #[cfg(feature = "themes")]
+        {
+            json["theme_watcher"] = serde_json::json!(self.handles.theme_watcher.stats());
+        }
+        if let Ok(mut current) = self.startup_report.write() {
+            *current = report;
+        }
+        serde_json::to_string(&json)
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    /// The `juhradiald doctor` checks as JSON: pass/fail per required
+    /// access, with the udev rule or command that fixes each failure
+    async fn doctor_report(&self) -> fdo::Result<String> {
+        serde_json::to_string(&crate::doctor::run_doctor())
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    /// Config, profiles, themes, backends, device and accessibility state
+    /// and recent warnings as one JSON document for bug reports
+    ///
+    /// Actions are reduced to their type unless `include_sensitive`; see
+    /// `crate::state_dump`.
+    async fn dump_state(&self, include_sensitive: bool) -> fdo::Result<String> {
+        tracing::info!(include_sensitive, "DumpState called");
+        let connection = self.connection_status().await;
+        let battery = {
+            let state = self.battery_state.read().await;
+            serde_json::json!({
+                "available": state.available,
+                "percentage": state.percentage,
+                "charging": state.charging,
+                "charge_complete": state.charge_complete,
+                "error": state.error.as_ref().map(|e| serde_json::json!({
+                    "kind": e.kind.as_str(),
+                    "message": e.message,
+                })),
+            })
+        };
+        let daemon = serde_json::json!({
+            "running": true,
+            "device": {
+                "mode": self.device_mode,
+                "name": self.device_name,
+                "connection": connection,
+                "battery": battery,
+                "watcher": self.handles.device_watcher.status(),
+            },
+            "accessibility": self.accessibility.read().ok().map(|a| a.status()),
+            "window_tracker": self.window_backend,
+            "overlay": self.overlay.snapshot(),
+            "input_armed": self.handles.panic_switch.is_armed(),
+            "recent_warnings": crate::state_dump::recent_warnings(&self.handles.recent_warnings, include_sensitive),
+        });
+        let config = self
+            .config
+            .read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        let themes = self
+            .themes
+            .read()
+            .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+        let dump = crate::state_dump::document(
+            &config,
+            &crate::profiles::get_profiles_path(),
+            &themes,
+            self.handles.binaries.strategy(),
+            daemon,
+            include_sensitive,
+        );
+        serde_json::to_string(&dump)
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    /// Clear the action latency histograms reported in `Status()`
+    async fn reset_action_latency(&self) -> fdo::Result<()> {
+        tracing::info!("ResetActionLatency called");
+        self.handles.latency.reset();
+        Ok(())
+    }
+
+    /// Re-enable input interception after the panic switch disarmed it
+    ///
+    /// Returns `false` when it was not disarmed.
+    async fn rearm(&self) -> fdo::Result<bool> {
+        tracing::info!("Rearm called");
+        Ok(self.handles.panic_switch.rearm())
+    }
+
+    /// Silence haptics and notifications for `minutes` on top of the quiet
+    /// hours schedule; 0 ends the override
+    async fn quiet_now(&self, minutes: u32) -> fdo::Result<()> {
+        tracing::info!(minutes, "QuietNow called");
+        self.handles.quiet_hours.quiet_now(minutes);
+        Ok(())
+    }
+
+    // =========================================================================
+    // CALIBRATION
+    // =========================================================================
+
+    /// Start a calibration session (see `crate::calibration`)
+    ///
+    /// Returns the first prompt as JSON; the rest arrive as
+    /// `CalibrationEvent`. Until the session completes or is cancelled, the
+    /// gesture button only records samples and runs no slice.
+    async fn start_calibration(&self) -> fdo::Result<String> {
+        tracing::info!("StartCalibration called");
+        let prompt = self.handles.calibration.update(|c| c.start())
+            .ok_or_else(|| fdo::Error::Failed("Calibration state lock poisoned".into()))?;
+        serde_json::to_string(&prompt)
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    /// End the calibration session without applying it; `false` when none ran
+    async fn cancel_calibration(&self) -> fdo::Result<bool> {
+        tracing::info!("CancelCalibration called");
+        Ok(self.handles.calibration.update(|c| c.cancel()).unwrap_or(false))
+    }
+
+    /// Apply a completed session's recommendation to `menu` and save the
+    /// config; returns the recommendation as JSON
+    ///
+    /// Sets `menu.flick_window_ms` and `menu.flick_distance_px`; flick mode
+    /// itself is left as it was, and the dead-zone radius is advisory.
+    async fn accept_calibration(&self) -> fdo::Result<String> {
+        tracing::info!("AcceptCalibration called");
+        let recommendation = self.handles.calibration.update(|c| c.accept())
+            .flatten()
+            .ok_or_else(|| fdo::Error::Failed("No completed calibration to accept".into()))?;
+        let flick = {
+            let mut config = self
+                .config
+                .write()
+                .map_err(|e| fdo::Error::Failed(format!("Lock error: {}", e)))?;
+            config.menu.flick_window_ms = recommendation.hold_threshold_ms;
+            config.menu.flick_distance_px = recommendation.flick_distance_px;
+            config
+                .save_deferred(&self.handles.persistence)
+                .map_err(|e| fdo::Error::Failed(format!("Failed to save config: {}", e)))?;
+            config.menu.flick()
+        };
+        self.handles.flick.update(|f, _| f.set_settings(flick));
+        tracing::info!(
+            flick_window_ms = recommendation.hold_threshold_ms,
+            flick_distance_px = recommendation.flick_distance_px,
+            "Calibration applied"
+        );
+        serde_json::to_string(&recommendation)
+            .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))
+    }
+
+    /// Calibration progress (JSON: `type` prompt, sample, rejected or
+    /// complete; emitted by the gesture loop, declared here for
+    /// introspection)
+    #[zbus(signal)]
+    async fn calibration_event(emitter: &SignalEmitter<'_>, event: String) -> zbus::Result<()>;
+
+    /// Shut the daemon down the way SIGTERM does (`juhradiald --replace`)
+    ///
+    /// Replies first; the shutdown runs once the main loop picks it up.
+    async fn quit(&self) -> fdo::Result<()> {
+        tracing::info!("Quit called");
+        self.handles.quit.request();
+        Ok(())
+    }
+
+    /// Restart the overlay now, clearing a `failed` supervision state
+    async fn restart_overlay(&self) -> fdo::Result<()> {
+        tracing::info!("RestartOverlay called");
+        self.overlay.request_restart();
+        Ok(())
+    }
+
+    /// The supervisor restarted the overlay; carries the state to re-apply
+    #[zbus(signal)]
+    async fn overlay_restarted(
+        emitter: &SignalEmitter<'_>,
+        pid: u32,
+        theme: String,
+        profile: String,
+    ) -> zbus::Result<()>;
+
     // =========================================================================
     // DEVICE MODE METHODS
     // =========================================================================
@@ -818,8 +1582,8 @@ impl JuhRadialService {
     // =========================================================================
 
     #[zbus(property)]
-    async fn current_profile(&self) -> &str {
-        &self.current_profile
+    async fn current_profile(&self) -> String {
+        self.profile_name()
     }
 
     #[zbus(property)]
@@ -853,3 +1617,498 @@ impl JuhRadialService {
             .unwrap_or(false)
     }
 }
+
+impl JuhRadialService {
+    /// Payload inputs from the current config and compositor
+    fn payload_options(&self) -> PayloadOptions {
+        self.config
+            .read()
+            .map(|c| PayloadOptions {
+                left_handed: c.input.left_handed,
+                success_flash: c.menu.action_feedback.then_some(c.menu.success_flash),
+                blur_available: self.handles.blur.is_available(),
+                no_blur_min_opacity: c.menu.no_blur_min_opacity,
+                theme_transition_ms: c.menu.theme_transition_ms,
+                dwell_confirm_ms: c
+                    .accessibility
+                    .dwell_confirm()
+                    .map_or(0, |d| u32::try_from(d.as_millis()).unwrap_or(u32::MAX)),
+                slice_tinting: c.accessibility.slice_tinting,
+                accent: self
+                    .themes
+                    .read()
+                    .ok()
+                    .and_then(|t| t.accent_override().and_then(crate::color::Rgba::from_hex)),
+                idle_limits: self.handles.idle_effect.limits(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Serialized payload for an ad-hoc menu; `None` over the size cap
+    ///
+    /// Built without the encoder: the slices differ with every call, and the
+    /// device badges do not belong on another tool's menu.
+    fn adhoc_payload(&self, menu: &AdHocMenu, help: Option<SliceHelp<'_>>) -> Option<String> {
+        let options = self.payload_options();
+        let label_budget = self.config.read().ok().and_then(|c| c.menu.label_budget());
+        let themes = self.themes.read().ok()?;
+        let accessibility = self.accessibility.read().ok()?;
+        let theme = themes.current();
+        MenuPayload::build(theme, &accessibility)
+            .with_left_handed(options.left_handed)
+            .with_feedback(options.success_flash.map(|flash| ActionFeedback::build(theme, flash)))
+            .with_blur(options.blur_available, options.no_blur_min_opacity)
+            .with_theme_transition(options.theme_transition_ms)
+            .with_accent(options.accent)
+            .with_idle_limits(&options.idle_limits)
+            .with_adhoc(Some(menu.overlay_slices(label_budget)))
+            .with_slice_tinting(theme, options.slice_tinting)
+            .with_help(help)
+            .to_signal_json()
+    }
+
+    /// Run the slot picked on a profile menu (see `profile_menu`)
+    fn run_profile_slice(&self, slot: u8, action: Action) {
+        if let ActionType::ProfileSwitch(ref target) = action.action_type {
+            match crate::profile_selection::apply_switch(&self.profile_selection, &self.thumbwheel, target, &self.handles.persistence) {
+                Ok(_) => {
+                    crate::hidpp::emit_shared(&self.haptic_manager, HapticEvent::MenuAppear);
+                    self.handles.menu_log.action_result(Some(slot), None);
+                }
+                Err(e) => {
+                    tracing::warn!(slot, target = %target, error = %e, "Menu profile switch failed");
+                    crate::hidpp::emit_shared(&self.haptic_manager, HapticEvent::InvalidAction);
+                    self.handles.menu_log.action_result(Some(slot), Some(&e.to_string()));
+                }
+            }
+            return;
+        }
+        let policy = self
+            .config
+            .read()
+            .map(|c| ExecutionPolicy::from_config(&c.execution, &self.handles.command_rate))
+            .unwrap_or_else(|_| ExecutionPolicy::unrestricted());
+        let context = self.handles.invocation.current()
+            .unwrap_or_else(InvocationContext::now)
+            .with_slice(slot);
+        crate::profile_menu::spawn_slice_action(action, slot, policy, context, self.handles.clone());
+    }
+
+    /// `menu.hover_help_ms` as a delay, `None` when hover help is off
+    fn hover_help_delay(&self) -> Option<std::time::Duration> {
+        self.config.read().ok().and_then(|c| c.menu.hover_help())
+    }
+
+    /// The HID++ connection for `Status()` and `DumpState()`
+    async fn connection_status(&self) -> Option<serde_json::Value> {
+        // The manager mutex can be held across a HID++ round trip; wait on
+        // a std thread (no Tokio runtime on the zbus executor)
+        let haptic_manager = self.haptic_manager.clone();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(haptic_manager.lock().ok().map(|m| m.connection_status()));
+        });
+        rx.await.ok().flatten()
+    }
+
+    /// Hold a press's menu back while it may still be a flick; a menu that
+    /// is already up (toggle mode) is never deferred
+    fn flick_defers(&self, x: i32, y: i32) -> bool {
+        let menu_open = self.menu_dismissal.update(|m, _| m.is_open()).unwrap_or(false);
+        self.handles.flick
+            .update(|f, _| f.menu_requested(x, y, !menu_open))
+            .unwrap_or(false)
+    }
+
+    /// Content hashes of the theme and profiles in use, for `Status()`
+    fn content_hashes(&self) -> serde_json::Value {
+        let theme = self.themes.read().ok().map(|themes| {
+            let current = themes.current();
+            serde_json::json!({
+                "name": current.name,
+                "hash": themes.current_hash(),
+                "path": themes.origin(&current.name).and_then(|o| o.path.as_deref()),
+                "accent_override": themes.accent_override(),
+            })
+        });
+        let profiles = self.thumbwheel.read().ok().map(|mapper| {
+            serde_json::json!({
+                "hash": mapper.profiles_hash(),
+                "by_name": mapper.profile_hashes(),
+            })
+        });
+        serde_json::json!({ "theme": theme, "profiles": profiles })
+    }
+
+    /// `menu.confirm_window_ms` as a duration
+    fn confirm_window(&self) -> std::time::Duration {
+        self.config
+            .read()
+            .map(|c| c.menu.confirm_window())
+            .unwrap_or(std::time::Duration::from_millis(crate::slice_confirmation::CONFIRM_WINDOW_MS))
+    }
+
+    /// Whether high contrast is in effect (system or config override)
+    fn high_contrast(&self) -> bool {
+        self.accessibility
+            .read()
+            .map(|a| a.should_use_high_contrast())
+            .unwrap_or(false)
+    }
+
+    /// Send the open menu the profile in effect
+    ///
+    /// Emits `MenuPayload` and resets the open menu's per-profile state: the
+    /// actions its slots run, hover help, `wheel_axis` slices, slices that
+    /// need confirming and the slices that may dwell-confirm. Runs on
+    /// `ShowMenu` and again when a late focus report swaps the profile.
+    pub(crate) async fn open_profile(&self, emitter: &SignalEmitter<'_>) -> zbus::Result<()> {
+        let help = self.emit_menu_payload(emitter).await?;
+        let profile_menu = self
+            .thumbwheel
+            .read()
+            .ok()
+            .and_then(|m| m.menu_actions().map(|actions| ProfileMenu::new(m.menu_profile(), actions)));
+        // Only the slices the daemon runs are worth preparing; the Settings
+        // ring of the default profile is the overlay's
+        let prewarm = profile_menu
+            .as_ref()
+            .and_then(|_| self.config.read().ok().and_then(|c| c.menu.prewarm()));
+        let prewarm_actions = profile_menu.as_ref().map(ProfileMenu::actions).unwrap_or_default();
+        self.handles.prewarm.update(|p, _| p.opened(prewarm, prewarm_actions));
+        if let Ok(mut open) = self.profile_menu.lock() {
+            *open = profile_menu;
+        }
+        self.hover_help.update(|h, _| h.opened(help));
+        let axes = self
+            .thumbwheel
+            .read()
+            .ok()
+            .and_then(|m| m.slice_axes().map(<[_]>::to_vec))
+            .unwrap_or_default();
+        let per_second = self
+            .config
+            .read()
+            .map(|c| c.menu.wheel_axis_per_second)
+            .unwrap_or(crate::wheel_axis::MAX_PER_SECOND);
+        let confirm_slots = self
+            .thumbwheel
+            .read()
+            .ok()
+            .and_then(|m| m.confirm_slots().map(<[_]>::to_vec))
+            .unwrap_or_default();
+        // Neither kind runs on a plain release, so neither dwells
+        let mut no_dwell = crate::wheel_axis::slots(&axes);
+        no_dwell.extend_from_slice(&confirm_slots);
+        self.handles.wheel_axis.update(|w| w.opened(axes, per_second));
+        let window = self.confirm_window();
+        self.handles.slice_confirmation.update(|c| c.opened(confirm_slots, window));
+        if let Ok(mapper) = self.thumbwheel.read() {
+            self.handles.slice_cooldown.update(|c| c.opened(mapper.menu_profile(), mapper.cooldown_slots().to_vec()));
+        }
+        let dwell = self.config.read().ok().and_then(|c| c.accessibility.dwell_confirm());
+        self.handles.dwell_confirm.update(|d, _| d.opened(dwell, no_dwell));
+        Ok(())
+    }
+
+    /// Swap a freshly read config, theme set and profiles into the daemon
+    ///
+    /// Shared by `ReloadConfig` and the SIGHUP reload. Pushes the config to
+    /// the haptic manager, thumb-wheel and timers, replaces the themes and
+    /// the per-app hardware map, and tells the overlay what changed. A
+    /// profiles.json that failed to load keeps the thumb-wheel bindings and
+    /// empties the hardware map.
+    pub(crate) async fn apply_reload(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        new_config: Config,
+        reloaded_themes: crate::theme::ThemeManager,
+        profiles: Result<crate::profiles::ProfileManager, crate::profiles::ProfileError>,
+    ) -> fdo::Result<()> {
+        let haptic_config = new_config.haptics.clone();
+        let accessibility_config = new_config.accessibility.clone();
+        let idle_effects_config = new_config.idle_effects.clone();
+        let thumbwheel_config = new_config.thumbwheel.clone();
+        let auto_dismiss = new_config.menu.auto_dismiss();
+        let label_budget = new_config.menu.label_budget();
+        let remapped_cids = new_config.remapped_button_cids();
+        let flick = new_config.menu.flick();
+        self.handles.quiet_hours.evaluate(&new_config.quiet_hours.schedule);
+        self.handles.flick.update(|f, _| f.set_settings(flick));
+        let focus_settle = new_config.menu.focus_settle();
+        self.handles.focus_settle.update(|s| s.set_settle(focus_settle));
+        self.handles.system_accent.update(|a| a.set_follow(new_config.colors.accent_follow_system));
+
+        match self.config.write() {
+            Ok(mut config) => {
+                // A trace started by SIGUSR2 survives unrelated saves
+                if new_config.debug.hidpp_trace != config.debug.hidpp_trace {
+                    self.handles.hidpp_trace.set_enabled(new_config.debug.hidpp_trace);
+                }
+                *config = new_config;
+                tracing::info!(
+                    haptics_enabled = config.haptics.enabled,
+                    default_pattern = %config.haptics.default_pattern,
+                    theme = %config.theme,
+                    "Configuration reloaded successfully"
+                );
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to acquire config write lock");
+                return Err(fdo::Error::Failed(format!("Lock error: {}", e)));
+            }
+        }
+
+        match self.haptic_manager.lock() {
+            Ok(mut manager) => {
+                manager.update_from_config(&haptic_config);
+                tracing::info!(
+                    default_pattern = %haptic_config.default_pattern,
+                    menu_appear = %haptic_config.per_event.menu_appear,
+                    slice_change = %haptic_config.per_event.slice_change,
+                    confirm = %haptic_config.per_event.confirm,
+                    invalid = %haptic_config.per_event.invalid,
+                    "Haptic manager updated with new patterns"
+                );
+
+                // Re-apply volatile thumb-wheel divert from the new
+                // config. Inversion is software-side (hidraw reader), so
+                // only the divert state is pushed to the device here.
+                if manager.thumbwheel_supported() {
+                    match manager.set_thumbwheel_reporting(thumbwheel_config.is_diverted(), false) {
+                        Ok(()) => tracing::info!(
+                            diverted = thumbwheel_config.is_diverted(),
+                            "Thumb-wheel reporting re-applied on reload"
+                        ),
+                        Err(e) => tracing::warn!(error = %e, "Failed to re-apply thumb-wheel reporting"),
+                    }
+                }
+
+                // Re-apply non-gesture button diverts so a newly
+                // reassigned button takes effect immediately, and a
+                // button returned to its native default has its divert
+                // cleared, without a reconnect. Done under the manager
+                // lock on the zbus executor (no Tokio runtime here, so
+                // spawn_blocking is unavailable). The HID++ calls are
+                // quick when connected and return immediately when not.
+                let remapped: std::collections::HashSet<u16> =
+                    remapped_cids.into_iter().collect();
+                for cid in Config::managed_button_cids() {
+                    let _ = manager.set_button_divert(cid, remapped.contains(&cid));
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to lock haptic manager for update");
+                return Err(fdo::Error::Failed(format!("Haptic manager lock error: {}", e)));
+            }
+        }
+
+        // Refresh the shared per-app hardware profile map from
+        // profiles.json so a UI save takes effect without a daemon
+        // restart. The focus-change consumer reads this map directly.
+        let hardware = match profiles {
+            Ok(ref manager) => manager.hardware_profiles(),
+            Err(ref e) => {
+                tracing::warn!(error = %e, "Failed to reload hardware profiles; keeping empty map");
+                Default::default()
+            }
+        };
+        match self.hardware_profiles.write() {
+            Ok(mut map) => {
+                tracing::info!(count = hardware.len(), "Per-app hardware profiles reloaded");
+                *map = hardware;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to lock hardware profiles for reload");
+            }
+        }
+
+        // Thumb-wheel bindings come from the same profiles.json, and
+        // the kill-switch / detent count from the reloaded config. A
+        // save that left every profile as it was keeps the bindings.
+        match self.thumbwheel.write() {
+            Ok(mut mapper) => {
+                mapper.set_config(&thumbwheel_config);
+                mapper.set_label_budget(label_budget);
+                match profiles {
+                    Ok(ref manager) if mapper.profiles_hash() == Some(manager.content_hash()) => {
+                        tracing::debug!(hash = %manager.content_hash(), "profiles.json unchanged, keeping thumb-wheel bindings");
+                    }
+                    Ok(ref manager) => mapper.set_profiles(manager),
+                    Err(e) => tracing::warn!(error = %e, "Failed to reload thumb-wheel bindings"),
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to lock thumb-wheel bindings for reload");
+            }
+        }
+
+        self.menu_dismissal.update(|m, now| m.set_timeout(auto_dismiss, now));
+
+        // Swap in the theme files and re-apply accessibility overrides; a
+        // changed reduce-motion or high contrast decision is pushed to
+        // the overlay now rather than waiting for the next menu invocation.
+        let timings_before = current_animation_timings(&self.themes, &self.accessibility, &self.handles.idle_effect);
+        let high_contrast_before = self.high_contrast();
+        let theme_before = ResolvedTheme::current(&self.themes, &self.accessibility);
+        let idle_limits_before = self.handles.idle_effect.limits();
+        self.handles.idle_effect.update(|s| s.set_config(idle_effects_config));
+        if let Ok(mut themes) = self.themes.write() {
+            *themes = reloaded_themes;
+            self.handles.system_accent.apply(&mut themes);
+        }
+        if let Ok(mut accessibility) = self.accessibility.write() {
+            accessibility.apply_config(&accessibility_config);
+        }
+        let timings = current_animation_timings(&self.themes, &self.accessibility, &self.handles.idle_effect);
+        if timings != timings_before {
+            let json = serde_json::to_string(&timings)
+                .map_err(|e| fdo::Error::Failed(format!("JSON error: {}", e)))?;
+            Self::animation_timings_changed(emitter, json).await?;
+        }
+        let theme_changed = self.emit_theme_changed(emitter, theme_before).await?;
+        if theme_changed
+            || self.high_contrast() != high_contrast_before
+            || self.handles.idle_effect.limits() != idle_limits_before
+        {
+            self.emit_menu_payload(emitter).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reload config.json, the themes and profiles.json as one transaction
+    ///
+    /// Stages and cross-checks all three before anything live changes (see
+    /// `crate::reload`); emits `ReloadCompleted` with a summary, or
+    /// `ReloadFailed` with the stage and findings after a rollback.
+    pub(crate) async fn reload_all(&self, emitter: &SignalEmitter<'_>) -> zbus::Result<()> {
+        let sources = crate::reload::ReloadSources::default_paths();
+        let staged = match crate::reload::stage_logged(&sources) {
+            Ok(staged) => staged,
+            Err(failure) => {
+                let json = serde_json::to_string(&failure).unwrap_or_default();
+                return Self::reload_failed(emitter, json).await;
+            }
+        };
+        let summary = staged.summary();
+        let crate::reload::Staged { config, themes, profiles } = staged;
+        if let Err(e) = self.apply_reload(emitter, config, themes, Ok(profiles)).await {
+            tracing::error!(error = %e, "Reload staged but not fully applied");
+        }
+        tracing::info!(
+            theme = %summary.theme,
+            themes = summary.themes,
+            profiles = summary.profiles,
+            profiles_hash = %summary.profiles_hash,
+            "Reload complete: config, themes and profiles swapped in together"
+        );
+        Self::reload_completed(emitter, serde_json::to_string(&summary).unwrap_or_default()).await
+    }
+
+    /// Emit `ThemeChanged` if the resolved theme moved away from `before`
+    ///
+    /// The crossfade is `menu.theme_transition_ms`, or 0 under reduced
+    /// motion. Returns whether a change was announced.
+    pub(crate) async fn emit_theme_changed(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        before: Option<ResolvedTheme>,
+    ) -> zbus::Result<bool> {
+        let (Some(before), Some(after)) = (before, ResolvedTheme::current(&self.themes, &self.accessibility)) else {
+            return Ok(false);
+        };
+        let reduced_motion = self
+            .accessibility
+            .read()
+            .map(|a| a.should_reduce_motion())
+            .unwrap_or(false);
+        let transition_ms = self
+            .config
+            .read()
+            .map(|c| crate::theme_transition::effective_ms(c.menu.theme_transition_ms, reduced_motion))
+            .unwrap_or(0);
+        let change = self.handles.theme_transition.update(|t| t.switched(before, after, transition_ms, std::time::Instant::now())).flatten();
+        let Some(change) = change else {
+            return Ok(false);
+        };
+        tracing::info!(
+            from = %change.from.name,
+            to = %change.to.name,
+            high_contrast = change.to.high_contrast,
+            transition_ms = change.transition_ms,
+            "Theme changed"
+        );
+        Self::theme_changed(emitter, change.to_json()).await?;
+        Ok(true)
+    }
+
+    /// Emit `MenuPayload` for the current theme, accessibility state and config
+    ///
+    /// Runs on the menu-open path and when the desktop high contrast setting
+    /// flips, so an overlay between invocations restyles at once. Returns the
+    /// hover delay when the payload carried slice descriptions.
+    pub(crate) async fn emit_menu_payload(
+        &self,
+        emitter: &SignalEmitter<'_>,
+    ) -> zbus::Result<Option<std::time::Duration>> {
+        // Badges come from cached state only: never wait on the battery poller
+        // or a HID++ round-trip while the menu is opening.
+        let battery = self.battery_state.try_read().ok();
+        let connection = self
+            .haptic_manager
+            .try_lock()
+            .ok()
+            .and_then(|m| m.connection_type());
+        let options = self.payload_options();
+        let delay = self.hover_help_delay();
+        let confirm_window = self.confirm_window();
+        let menu_size = self.config.read().map(|c| c.menu.size).unwrap_or_default();
+        let monitor = self.handles.monitors.current();
+        // Encoded into a reused buffer; the static part is only re-serialized
+        // when the theme, accessibility state or config changed.
+        let mut encoder = self.payload_encoder.lock().await;
+        let (sent, help_delay) = {
+            let payload = match (self.themes.read(), self.accessibility.read(), self.thumbwheel.read()) {
+                (Ok(themes), Ok(accessibility), Ok(mapper)) => {
+                    let theme = themes.current();
+                    let status = battery
+                        .as_deref()
+                        .and_then(|b| DeviceStatus::build(b, connection, theme));
+                    let help = SliceHelp::build(delay, mapper.slice_descriptions());
+                    let wheel_axis = mapper.slice_axes().map(crate::wheel_axis::slots);
+                    let menu_diameter = mapper.menu_size().unwrap_or(menu_size).diameter(monitor.as_ref());
+                    let icon_box = crate::icon_resolver::raster_box(menu_diameter, mapper.slice_count(), monitor.as_ref());
+                    let icons: Option<Vec<_>> = mapper
+                        .slice_icons()
+                        .map(|icons| icons.iter().map(|icon| icon.as_ref().map(|i| i.hint(icon_box))).collect());
+                    let cooling = self.handles.slice_cooldown.update(|c| c.cooling(mapper.menu_profile(), std::time::Instant::now()))
+                        .unwrap_or_default();
+                    let slices = mapper.menu_slices();
+                    let hints = SlotHints {
+                        slices: slices.as_deref(),
+                        wheel_axis: wheel_axis.as_deref(),
+                        confirm: SliceConfirm::build(confirm_window, mapper.confirm_slots()),
+                        cooldown: (!cooling.is_empty()).then_some(cooling.as_slice()),
+                        profile_hash: mapper.profile_hash(),
+                        slice_count: Some(mapper.slice_count()),
+                        labels: mapper.localized_labels(),
+                        short_labels: mapper.short_labels(),
+                        menu_diameter,
+                        icons: icons.as_deref(),
+                    };
+                    encoder
+                        .encode(theme, themes.current_hash(), &accessibility, &options, help.as_ref(), &hints, status.as_ref())
+                        .map(|json| (json, help.as_ref().map(SliceHelp::delay)))
+                }
+                _ => None,
+            };
+            drop(battery);
+            payload.unzip()
+        };
+        if let Some(payload) = sent {
+            Self::menu_payload(emitter, payload).await?;
+        }
+        Ok(help_delay.flatten())
+    }
+}
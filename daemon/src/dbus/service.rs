@@ -2,19 +2,34 @@
 
 use std::sync::{Arc, Mutex};
 
+use crate::accessibility::SharedAccessibility;
+use crate::adhoc_menu::AdHocGate;
 use crate::battery::SharedBatteryState;
 use crate::config::SharedConfig;
 use crate::gaming::SharedGamingMode;
+use crate::handles::DaemonHandles;
 use crate::hidpp::SharedHapticManager;
+use crate::hover_help::HoverHelpHandle;
 use crate::macros::{MacroEngine, MacroRecorder, SharedTriggerMap, TriggerMap};
+use crate::menu_dismissal::MenuDismissalHandle;
+use crate::menu_payload::PayloadEncoder;
+use crate::overlay::OverlayHandle;
+use crate::profile_menu::ProfileMenu;
+use crate::profile_selection::SharedProfileSelection;
 use crate::profiles::SharedHardwareProfiles;
+use crate::self_test::{new_shared_report, SharedStartupReport, StartupReport};
+use crate::stats::{InputMethod, StatsCollector};
+use crate::theme::SharedThemeManager;
+use crate::thumbwheel_mapping::{SharedThumbwheelMapper, ThumbwheelMapper};
 
 /// JuhRadial MX D-Bus service
 ///
 /// Implements the D-Bus interface for IPC between daemon, KWin overlay, and Plasma widget.
 pub struct JuhRadialService {
-    /// Current profile name
+    /// Profile name used until a profile is selected
     pub(crate) current_profile: String,
+    /// Manually selected profile (`SetProfile`, `profile_switch` actions)
+    pub(crate) profile_selection: SharedProfileSelection,
     /// Daemon version
     pub(crate) version: String,
     /// Shared battery state
@@ -42,6 +57,33 @@ pub struct JuhRadialService {
     /// profiles.json so UI saves take effect without a daemon restart; the
     /// focus-change consumer reads it on each active-window change.
     pub(crate) hardware_profiles: SharedHardwareProfiles,
+    /// Overlay supervisor handle (`Status()` and `RestartOverlay()`)
+    pub(crate) overlay: OverlayHandle,
+    /// Loaded themes; the current one drives the menu payload
+    pub(crate) themes: SharedThemeManager,
+    /// Reduced motion / high contrast state (config overrides + portal watcher)
+    pub(crate) accessibility: SharedAccessibility,
+    /// Opt-in local usage statistics (`telemetry.local_stats`)
+    pub(crate) stats: Mutex<StatsCollector>,
+    /// Cached menu payload serialization; held across the signal emit
+    pub(crate) payload_encoder: tokio::sync::Mutex<PayloadEncoder>,
+    /// Active-window backend selected at startup (`Status()`), if any
+    pub(crate) window_backend: Option<&'static str>,
+    /// Per-profile thumb-wheel bindings (menu state, `Status()`, reload)
+    pub(crate) thumbwheel: SharedThumbwheelMapper,
+    /// Device access self-test results (`Status()`, refreshed by `Diagnostics()`)
+    pub(crate) startup_report: SharedStartupReport,
+    /// Auto-dismiss countdown for the open menu (timer runs in the gesture loop)
+    pub(crate) menu_dismissal: MenuDismissalHandle,
+    /// Dwell tracking for slice descriptions (timer runs in a daemon task)
+    pub(crate) hover_help: HoverHelpHandle,
+    /// The open `ShowAdHocMenu` menu, completed by `ReportMenuClosed`
+    pub(crate) adhoc: AdHocGate,
+    /// The open profile menu whose picked slot the daemon runs; `None` for
+    /// the Settings ring and ad-hoc menus
+    pub(crate) profile_menu: Mutex<Option<ProfileMenu>>,
+    /// Daemon state shared with the input loops and background tasks
+    pub(crate) handles: DaemonHandles,
 }
 
 impl JuhRadialService {
@@ -55,8 +97,21 @@ impl JuhRadialService {
         // No window-profile consumer on this simple path: drop the receiver so
         // ReportActiveWindow becomes a no-op.
         let (active_window_tx, _aw_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (themes, accessibility, thumbwheel) = match config.read() {
+            Ok(c) => (
+                crate::theme::load_shared_theme_manager(&c.theme),
+                crate::accessibility::new_shared_accessibility(&c.accessibility),
+                ThumbwheelMapper::new(&c.thumbwheel),
+            ),
+            Err(_) => (
+                crate::theme::load_shared_theme_manager(crate::DEFAULT_THEME_NAME),
+                crate::accessibility::new_shared_accessibility(&Default::default()),
+                ThumbwheelMapper::new(&Default::default()),
+            ),
+        };
         Self {
             current_profile: "default".to_string(),
+            profile_selection: Default::default(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             battery_state,
             config,
@@ -69,6 +124,19 @@ impl JuhRadialService {
             trigger_map: Arc::new(std::sync::RwLock::new(TriggerMap::default())),
             active_window_tx,
             hardware_profiles: Arc::new(std::sync::RwLock::new(std::collections::HashMap::new())),
+            overlay: OverlayHandle::new(),
+            themes,
+            accessibility,
+            stats: Mutex::new(StatsCollector::new()),
+            payload_encoder: tokio::sync::Mutex::new(PayloadEncoder::new()),
+            window_backend: None,
+            thumbwheel: Arc::new(std::sync::RwLock::new(thumbwheel)),
+            startup_report: new_shared_report(StartupReport::default()),
+            menu_dismissal: MenuDismissalHandle::default(),
+            hover_help: HoverHelpHandle::default(),
+            adhoc: AdHocGate::default(),
+            profile_menu: Mutex::new(None),
+            handles: DaemonHandles::default(),
         }
     }
 
@@ -86,9 +154,20 @@ impl JuhRadialService {
         trigger_map: SharedTriggerMap,
         active_window_tx: tokio::sync::mpsc::UnboundedSender<String>,
         hardware_profiles: SharedHardwareProfiles,
+        overlay: OverlayHandle,
+        themes: SharedThemeManager,
+        accessibility: SharedAccessibility,
+        window_backend: Option<&'static str>,
+        thumbwheel: SharedThumbwheelMapper,
+        startup_report: SharedStartupReport,
+        menu_dismissal: MenuDismissalHandle,
+        hover_help: HoverHelpHandle,
+        profile_selection: SharedProfileSelection,
+        handles: DaemonHandles,
     ) -> Self {
         Self {
             current_profile: "default".to_string(),
+            profile_selection,
             version: env!("CARGO_PKG_VERSION").to_string(),
             battery_state,
             config,
@@ -101,6 +180,49 @@ impl JuhRadialService {
             trigger_map,
             active_window_tx,
             hardware_profiles,
+            overlay,
+            themes,
+            accessibility,
+            stats: Mutex::new(StatsCollector::new()),
+            payload_encoder: tokio::sync::Mutex::new(PayloadEncoder::new()),
+            window_backend,
+            thumbwheel,
+            startup_report,
+            menu_dismissal,
+            hover_help,
+            adhoc: AdHocGate::default(),
+            profile_menu: Mutex::new(None),
+            handles,
+        }
+    }
+
+    /// Whether `telemetry.local_stats` is enabled in the current config
+    pub(crate) fn local_stats_enabled(&self) -> bool {
+        self.config
+            .read()
+            .map(|c| c.telemetry.local_stats)
+            .unwrap_or(false)
+    }
+
+    /// Selected profile name, or the fallback when none is selected
+    pub(crate) fn profile_name(&self) -> String {
+        self.profile_selection
+            .read()
+            .ok()
+            .and_then(|s| s.selected().map(str::to_string))
+            .unwrap_or_else(|| self.current_profile.clone())
+    }
+
+    /// Daemon is stopping: close the menu state and record a still-open menu
+    pub fn shutdown(&self) {
+        self.menu_dismissal.update(|m, _| m.closed());
+        self.hover_help.update(|h, _| h.closed());
+        self.handles.prewarm.update(|p, _| p.closed());
+        if !self.local_stats_enabled() {
+            return;
+        }
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.menu_closed(&self.profile_name(), None, InputMethod::Shutdown, &self.handles.persistence);
         }
     }
 }
@@ -152,8 +274,20 @@ mod tests {
             trigger_map,
             active_window_tx,
             hardware_profiles,
+            OverlayHandle::new(),
+            crate::theme::load_shared_theme_manager("vaporwave"),
+            crate::accessibility::new_shared_accessibility(&Default::default()),
+            Some("x11"),
+            Arc::new(std::sync::RwLock::new(ThumbwheelMapper::new(&Default::default()))),
+            new_shared_report(StartupReport::default()),
+            MenuDismissalHandle::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
         );
         assert_eq!(service.device_mode, "generic");
+        assert_eq!(service.window_backend, Some("x11"));
+        assert_eq!(service.themes.read().unwrap().current().name, "vaporwave");
         assert_eq!(service.device_name, "SteelSeries Rival 3");
     }
 }
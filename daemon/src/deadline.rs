@@ -0,0 +1,163 @@
+//! Shared handle and timer loop for the menu's deadline state machines
+//!
+//! Auto-dismiss, hover help, pre-warm, dwell confirm and flick detection all
+//! follow one split: a pure state machine that is told the time and reports
+//! when it next wants waking ([`Deadline`]), a [`DeadlineHandle`] that the
+//! D-Bus service and input loops share to feed it, and a tokio task
+//! ([`run_timer`]) that sleeps until that deadline. Every `update` wakes the
+//! task to re-read the deadline, which drops the old sleep, so a timer outrun
+//! by a reset or close finds a later (or no) deadline and fires nothing.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::Notify;
+
+/// A state machine driven by one timer
+pub trait Deadline {
+    /// When the timer should next wake; `None` while nothing is armed
+    fn wake_at(&self) -> Option<Instant>;
+}
+
+/// Shared handle between the feeders of a state machine and its timer task
+#[derive(Debug)]
+pub struct DeadlineHandle<S> {
+    state: Arc<Mutex<S>>,
+    changed: Arc<Notify>,
+}
+
+impl<S> Clone for DeadlineHandle<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+            changed: Arc::clone(&self.changed),
+        }
+    }
+}
+
+impl<S: Deadline + Default> Default for DeadlineHandle<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: Deadline> DeadlineHandle<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            changed: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Run `f` on the state machine and wake the timer to re-read its deadline
+    pub fn update<R>(&self, f: impl FnOnce(&mut S, Instant) -> R) -> Option<R> {
+        let result = self.state.lock().ok().map(|mut s| f(&mut s, Instant::now()));
+        self.changed.notify_one();
+        result
+    }
+
+    /// Run `f` on the state machine without waking the timer; for changes
+    /// that never move the deadline
+    pub fn with<R>(&self, f: impl FnOnce(&mut S) -> R) -> Option<R> {
+        self.state.lock().ok().map(|mut s| f(&mut s))
+    }
+
+    /// When the timer should next wake, if anything is armed
+    pub fn wake_at(&self) -> Option<Instant> {
+        self.state.lock().ok().and_then(|s| s.wake_at())
+    }
+
+    /// Resolves after the next `update`
+    pub async fn changed(&self) {
+        self.changed.notified().await;
+    }
+}
+
+/// Sleep until `deadline`, or forever when none is armed
+pub async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Own the timer of `handle` for the life of the daemon
+///
+/// When the deadline passes, `fire` runs on the state machine (under its
+/// lock) and whatever it returns is handed to `on_fire`, which may await
+/// (emitting a signal, say). `fire` returning `None` means the timer was
+/// outrun and there is nothing to do.
+pub async fn run_timer<S, T, F, Fut>(
+    handle: DeadlineHandle<S>,
+    mut fire: impl FnMut(&mut S, Instant) -> Option<T>,
+    mut on_fire: F,
+) where
+    S: Deadline,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    loop {
+        let wake_at = handle.wake_at();
+        tokio::select! {
+            _ = handle.changed() => continue,
+            _ = sleep_until(wake_at) => {
+                if let Some(fired) = handle.update(|s, now| fire(s, now)).flatten() {
+                    on_fire(fired).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct Once {
+        due: Option<Instant>,
+    }
+
+    impl Deadline for Once {
+        fn wake_at(&self) -> Option<Instant> {
+            self.due
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_wakes_timer_on_update() {
+        let handle = DeadlineHandle::<Once>::default();
+        let waiter = handle.clone();
+        let task = tokio::spawn(async move { waiter.changed().await });
+        tokio::task::yield_now().await;
+        handle.update(|s, now| s.due = Some(now));
+        task.await.unwrap();
+        assert!(handle.wake_at().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_timer_fires_the_rearmed_deadline_once() {
+        let handle = DeadlineHandle::<Once>::default();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let timer = tokio::spawn(run_timer(
+            handle.clone(),
+            |s: &mut Once, now| s.due.filter(|due| now >= *due).map(|_| s.due.take()),
+            move |fired| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send(fired);
+                }
+            },
+        ));
+        // Armed far out, then pulled in: only the new deadline fires
+        handle.update(|s, now| s.due = Some(now + Duration::from_secs(3600)));
+        handle.update(|s, now| s.due = Some(now + Duration::from_millis(5)));
+        let fired = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap();
+        assert!(fired.is_some());
+        assert_eq!(handle.wake_at(), None);
+        assert!(rx.try_recv().is_err());
+        timer.abort();
+    }
+}
@@ -0,0 +1,680 @@
+//! Device hotplug: udev events, a device inventory, and change notifications
+//!
+//! The input loops, the HID++ transport and the battery updater all need to
+//! know when a mouse or receiver comes or goes. They used to share an
+//! inotify watch on `/dev/input/` that woke them at the start of every event
+//! burst and again once it settled, so one replug (a receiver brings up
+//! several nodes at once) cost each loop two or three full rescans.
+//!
+//! [`DeviceWatcher`] owns that now. It listens for udev `input` and
+//! `hidraw` events on the kernel's uevent netlink socket (the udev
+//! multicast group, so a node is announced once udev has set it up and
+//! applied its permissions), waits until a burst has been quiet for
+//! [`DEBOUNCE`], and then takes the device inventory: the
+//! `/dev/input/event*` and `/dev/hidraw*` nodes with their inode numbers, so
+//! a node removed and re-created under the same name on resume still counts
+//! as changed. Subscribers hear about the burst only when the inventory
+//! differs from the last one, and only when it touches the kinds of device
+//! they asked for.
+//!
+//! The socket is only a trigger: messages are not authenticated, and a forged
+//! one costs a directory listing. Without udev (no `/run/udev/control`, a
+//! container) or without the socket, the watcher lists the directories every
+//! [`FALLBACK_POLL_INTERVAL`] instead. Listing does not open the nodes, so
+//! the polling does not disturb a mouse that is streaming events.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::fs::DirEntryExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::io::unix::AsyncFd;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::Instant;
+
+/// Quiet time after the last event of a burst before the inventory is taken
+pub const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Longest a continuous stream of events can hold the inventory back
+pub const MAX_SETTLE: Duration = Duration::from_secs(1);
+
+/// Inventory cadence when udev events are unavailable
+pub const FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// udev's control socket; present while `systemd-udevd` runs
+const UDEV_CONTROL: &str = "/run/udev/control";
+
+/// Netlink multicast group udev re-broadcasts processed events on (1 is
+/// the kernel's own, sent before udev has created the node)
+const UDEV_MONITOR_GROUP: u32 = 2;
+
+/// Prefix and magic of a udev monitor message
+const UDEV_PREFIX: &[u8] = b"libudev\0";
+const UDEV_MAGIC: u32 = 0xfeed_cafe;
+
+/// Inventory changes kept for a subscriber that has not caught up
+const CHANNEL_CAPACITY: usize = 16;
+
+/// Subsystems whose events start a rescan
+const WATCHED_SUBSYSTEMS: &[&str] = &["input", "hidraw"];
+
+/// What a device node is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    /// `/dev/input/event*` (evdev)
+    Input,
+    /// `/dev/hidraw*` (HID++)
+    Hidraw,
+}
+
+/// The device nodes present, with the inode each was created as
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Inventory {
+    nodes: BTreeMap<PathBuf, (DeviceKind, u64)>,
+}
+
+impl Inventory {
+    /// List `/dev/input/event*` and `/dev/hidraw*`
+    pub fn scan() -> Self {
+        Self::scan_in(Path::new("/dev/input"), Path::new("/dev"))
+    }
+
+    /// [`scan`](Self::scan) with other directories (tests)
+    pub fn scan_in(input_dir: &Path, dev_dir: &Path) -> Self {
+        let mut inventory = Self::default();
+        inventory.add_dir(input_dir, "event", DeviceKind::Input);
+        inventory.add_dir(dev_dir, "hidraw", DeviceKind::Hidraw);
+        inventory
+    }
+
+    fn add_dir(&mut self, dir: &Path, prefix: &str, kind: DeviceKind) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::debug!(dir = %dir.display(), error = %e, "Device directory unreadable");
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_str().is_some_and(|n| n.starts_with(prefix)) {
+                self.nodes.insert(entry.path(), (kind, entry.ino()));
+            }
+        }
+    }
+
+    /// Record a node (tests)
+    pub fn insert(&mut self, path: impl Into<PathBuf>, kind: DeviceKind, ino: u64) {
+        self.nodes.insert(path.into(), (kind, ino));
+    }
+
+    /// Nodes in the inventory
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// What changed from `self` to `next`; `None` when nothing did
+    pub fn diff(&self, next: &Inventory) -> Option<InventoryChange> {
+        let gone = |from: &Inventory, to: &Inventory| -> Vec<DeviceNode> {
+            from.nodes
+                .iter()
+                .filter(|(path, node)| to.nodes.get(*path) != Some(node))
+                .map(|(path, &(kind, _))| DeviceNode { path: path.clone(), kind })
+                .collect()
+        };
+        let change = InventoryChange { added: gone(next, self), removed: gone(self, next) };
+        (!change.added.is_empty() || !change.removed.is_empty()).then_some(change)
+    }
+}
+
+/// One device node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceNode {
+    pub path: PathBuf,
+    pub kind: DeviceKind,
+}
+
+/// Nodes that appeared and went between two inventories; a node re-created
+/// under the same name is in both lists
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InventoryChange {
+    pub added: Vec<DeviceNode>,
+    pub removed: Vec<DeviceNode>,
+}
+
+impl InventoryChange {
+    /// Whether a node of one of `kinds` came or went
+    pub fn touches(&self, kinds: &[DeviceKind]) -> bool {
+        self.added.iter().chain(&self.removed).any(|n| kinds.contains(&n.kind))
+    }
+}
+
+/// How the watcher learns about hotplug
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchSource {
+    /// udev events over netlink
+    Udev,
+    /// [`FALLBACK_POLL_INTERVAL`] listings
+    Poll,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct WatcherStats {
+    source: Option<WatchSource>,
+    /// Nodes in the last inventory
+    devices: usize,
+    /// Inventories taken after the first
+    rescans: u64,
+    /// Rescans that found a change
+    changes: u64,
+}
+
+/// The shared device watcher; clones share one channel and its counters
+///
+/// The daemon keeps one on `DaemonHandles`.
+#[derive(Debug, Clone)]
+pub struct DeviceWatcher {
+    tx: broadcast::Sender<Arc<InventoryChange>>,
+    stats: Arc<Mutex<WatcherStats>>,
+}
+
+impl Default for DeviceWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceWatcher {
+    pub fn new() -> Self {
+        Self { tx: broadcast::channel(CHANNEL_CAPACITY).0, stats: Arc::default() }
+    }
+
+    /// The `device_watcher` block of `Status()`
+    pub fn status(&self) -> serde_json::Value {
+        self.stats
+            .lock()
+            .ok()
+            .and_then(|stats| serde_json::to_value(&*stats).ok())
+            .unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Hear about inventory changes that touch `kinds`
+    pub fn subscribe(&self, kinds: &'static [DeviceKind]) -> DeviceSubscription {
+        DeviceSubscription { rx: self.tx.subscribe(), kinds }
+    }
+
+    /// Watch until the task is cancelled: udev events when udev runs, the
+    /// fallback poll otherwise
+    pub async fn run(self) {
+        let (trigger_tx, trigger_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let monitor = if Path::new(UDEV_CONTROL).exists() {
+            UdevMonitor::open()
+                .inspect_err(|e| tracing::warn!(error = %e, "udev monitor unavailable; polling for devices"))
+                .ok()
+        } else {
+            tracing::info!("udev is not running; polling for devices every {}s", FALLBACK_POLL_INTERVAL.as_secs());
+            None
+        };
+        match monitor {
+            Some(monitor) => {
+                tracing::info!("Device hotplug watcher listening for udev input and hidraw events");
+                // A failed monitor closes the channel, which ends the watch
+                tokio::join!(monitor.forward(trigger_tx), self.watch(trigger_rx, None, Inventory::scan));
+            }
+            None => self.watch(trigger_rx, Some(FALLBACK_POLL_INTERVAL), Inventory::scan).await,
+        }
+    }
+
+    /// The watch loop: rescan once per debounced burst of `triggers` (and
+    /// every `poll` when set), and broadcast the changes
+    pub async fn watch(
+        &self,
+        mut triggers: mpsc::Receiver<()>,
+        poll: Option<Duration>,
+        mut scan: impl FnMut() -> Inventory,
+    ) {
+        let mut inventory = scan();
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.source = Some(if poll.is_some() { WatchSource::Poll } else { WatchSource::Udev });
+            stats.devices = inventory.len();
+        }
+        let mut burst = Debounce::default();
+        let mut next_poll = poll.map(|interval| Instant::now() + interval);
+        let mut triggers_open = true;
+
+        loop {
+            let wake_at = [burst.deadline(), next_poll].into_iter().flatten().min();
+            tokio::select! {
+                trigger = triggers.recv(), if triggers_open => match trigger {
+                    Some(()) => {
+                        burst.event(Instant::now());
+                        continue;
+                    }
+                    None => {
+                        triggers_open = false;
+                        if poll.is_none() {
+                            return;
+                        }
+                        continue;
+                    }
+                },
+                _ = sleep_until(wake_at) => {}
+            }
+            burst.clear();
+            if let (Some(at), Some(interval)) = (next_poll.as_mut(), poll) {
+                *at = Instant::now() + interval;
+            }
+
+            let next = scan();
+            let change = inventory.diff(&next);
+            inventory = next;
+            if let Ok(mut stats) = self.stats.lock() {
+                stats.devices = inventory.len();
+                stats.rescans += 1;
+                stats.changes += u64::from(change.is_some());
+            }
+            let Some(change) = change else {
+                tracing::trace!("Device inventory unchanged");
+                continue;
+            };
+            tracing::info!(
+                added = ?change.added.iter().map(|n| n.path.display().to_string()).collect::<Vec<_>>(),
+                removed = ?change.removed.iter().map(|n| n.path.display().to_string()).collect::<Vec<_>>(),
+                "Device inventory changed"
+            );
+            // No subscribers is not an error
+            let _ = self.tx.send(Arc::new(change));
+        }
+    }
+}
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Trailing debounce over one burst of events
+#[derive(Debug, Default, Clone, Copy)]
+struct Debounce {
+    /// First and last event of the pending burst
+    burst: Option<(Instant, Instant)>,
+}
+
+impl Debounce {
+    fn event(&mut self, now: Instant) {
+        let first = self.burst.map_or(now, |(first, _)| first);
+        self.burst = Some((first, now));
+    }
+
+    /// When to rescan: [`DEBOUNCE`] after the last event, at most
+    /// [`MAX_SETTLE`] after the first
+    fn deadline(&self) -> Option<Instant> {
+        self.burst.map(|(first, last)| (last + DEBOUNCE).min(first + MAX_SETTLE))
+    }
+
+    fn clear(&mut self) {
+        self.burst = None;
+    }
+}
+
+/// A subscriber's view of [`DeviceWatcher`] changes
+#[derive(Debug)]
+pub struct DeviceSubscription {
+    rx: broadcast::Receiver<Arc<InventoryChange>>,
+    kinds: &'static [DeviceKind],
+}
+
+impl DeviceSubscription {
+    /// Forget changes received so far; call before a scan, so only changes
+    /// the scan cannot have seen wake [`changed`](Self::changed)
+    pub fn clear(&mut self) {
+        while !matches!(self.rx.try_recv(), Err(broadcast::error::TryRecvError::Empty | broadcast::error::TryRecvError::Closed)) {}
+    }
+
+    /// The next change that touches this subscriber's kinds
+    ///
+    /// Missed changes (a subscriber that fell behind) count as one. Never
+    /// resolves once the watcher is gone, leaving callers to their timers.
+    pub async fn changed(&mut self) -> Option<Arc<InventoryChange>> {
+        loop {
+            match self.rx.recv().await {
+                Ok(change) if change.touches(self.kinds) => return Some(change),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(_)) => return None,
+                Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+            }
+        }
+    }
+}
+
+/// A udev (or kernel) uevent, reduced to what the watcher filters on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UEvent {
+    pub action: String,
+    pub subsystem: String,
+    pub devname: Option<String>,
+}
+
+impl UEvent {
+    /// Parse a udev monitor message (`libudev` header, then `KEY=value`
+    /// properties) or a kernel one (`action@devpath`, then properties)
+    pub fn parse(message: &[u8]) -> Option<Self> {
+        let properties = if message.starts_with(UDEV_PREFIX) {
+            let field = |at: usize, be: bool| -> Option<u32> {
+                let bytes: [u8; 4] = message.get(at..at + 4)?.try_into().ok()?;
+                Some(if be { u32::from_be_bytes(bytes) } else { u32::from_ne_bytes(bytes) })
+            };
+            if field(8, true)? != UDEV_MAGIC {
+                return None;
+            }
+            let offset = field(16, false)? as usize;
+            let len = field(20, false)? as usize;
+            message.get(offset..offset.checked_add(len)?)?
+        } else {
+            let end = message.iter().position(|&b| b == 0)?;
+            if !message[..end].contains(&b'@') {
+                return None;
+            }
+            &message[end + 1..]
+        };
+
+        let mut event = UEvent { action: String::new(), subsystem: String::new(), devname: None };
+        for property in properties.split(|&b| b == 0) {
+            let Ok(property) = std::str::from_utf8(property) else {
+                continue;
+            };
+            match property.split_once('=') {
+                Some(("ACTION", value)) => event.action = value.to_string(),
+                Some(("SUBSYSTEM", value)) => event.subsystem = value.to_string(),
+                Some(("DEVNAME", value)) => event.devname = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        (!event.action.is_empty() && !event.subsystem.is_empty()).then_some(event)
+    }
+
+    /// Whether this event can change the inventory
+    pub fn is_relevant(&self) -> bool {
+        WATCHED_SUBSYSTEMS.contains(&self.subsystem.as_str())
+            && matches!(self.action.as_str(), "add" | "remove" | "change" | "bind" | "unbind")
+    }
+}
+
+/// `NETLINK_KOBJECT_UEVENT` socket on udev's multicast group
+struct UdevMonitor {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl UdevMonitor {
+    fn open() -> io::Result<Self> {
+        // SAFETY: plain socket(2); the descriptor is owned right away
+        let raw = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+                libc::NETLINK_KOBJECT_UEVENT,
+            )
+        };
+        if raw < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `raw` is a fresh descriptor nothing else owns
+        let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+        // SAFETY: sockaddr_nl is plain data; all-zero is a valid value
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = UDEV_MONITOR_GROUP;
+        // SAFETY: `addr` outlives the call and the length matches its type
+        let bound = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                (&addr as *const libc::sockaddr_nl).cast(),
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if bound != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    /// Send a trigger for every relevant event until `triggers` closes
+    async fn forward(self, triggers: mpsc::Sender<()>) {
+        let mut buffer = vec![0u8; 8192];
+        loop {
+            let received = match self.fd.readable().await {
+                Ok(mut guard) => match guard.try_io(|fd| {
+                    // SAFETY: recv(2) into a buffer we own, bounded by its length
+                    let n = unsafe { libc::recv(fd.as_raw_fd(), buffer.as_mut_ptr().cast(), buffer.len(), 0) };
+                    if n < 0 {
+                        Err(io::Error::last_os_error())
+                    } else {
+                        Ok(n as usize)
+                    }
+                }) {
+                    Ok(result) => result,
+                    Err(_would_block) => continue,
+                },
+                Err(e) => Err(e),
+            };
+            let len = match received {
+                Ok(len) => len,
+                // The kernel dropped messages for us (ENOBUFS): rescan anyway
+                Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => {
+                    tracing::debug!("udev monitor overrun; rescanning");
+                    if triggers.send(()).await.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "udev monitor failed; device hotplug now relies on the loops' polls");
+                    return;
+                }
+            };
+            let Some(event) = UEvent::parse(&buffer[..len]) else {
+                continue;
+            };
+            if !event.is_relevant() {
+                continue;
+            }
+            tracing::debug!(action = %event.action, subsystem = %event.subsystem, devname = ?event.devname, "udev event");
+            if triggers.send(()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const INPUT: &[DeviceKind] = &[DeviceKind::Input];
+    const HIDRAW: &[DeviceKind] = &[DeviceKind::Hidraw];
+
+    fn inventory(nodes: &[(&str, DeviceKind, u64)]) -> Inventory {
+        let mut inventory = Inventory::default();
+        for &(path, kind, ino) in nodes {
+            inventory.insert(path, kind, ino);
+        }
+        inventory
+    }
+
+    /// Run the watch loop over `states`, one per scan (the last repeats);
+    /// returns the trigger sender and the scan counter
+    fn start(watcher: &DeviceWatcher, states: Vec<Inventory>) -> (mpsc::Sender<()>, Arc<AtomicUsize>) {
+        let (tx, rx) = mpsc::channel(64);
+        let scans = Arc::new(AtomicUsize::new(0));
+        let counter = scans.clone();
+        let watcher = watcher.clone();
+        tokio::spawn(async move {
+            watcher
+                .watch(rx, None, move || {
+                    let n = counter.fetch_add(1, Ordering::SeqCst);
+                    states[n.min(states.len() - 1)].clone()
+                })
+                .await
+        });
+        (tx, scans)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_is_one_rescan_and_one_notification() {
+        let watcher = DeviceWatcher::new();
+        let before = inventory(&[("/dev/input/event3", DeviceKind::Input, 10)]);
+        let after = inventory(&[
+            ("/dev/input/event3", DeviceKind::Input, 10),
+            ("/dev/input/event7", DeviceKind::Input, 11),
+            ("/dev/hidraw4", DeviceKind::Hidraw, 12),
+        ]);
+        let mut input = watcher.subscribe(INPUT);
+        let mut hidraw = watcher.subscribe(HIDRAW);
+        let (triggers, scans) = start(&watcher, vec![before, after]);
+        tokio::task::yield_now().await;
+        assert_eq!(scans.load(Ordering::SeqCst), 1, "initial inventory");
+
+        // A receiver replug: seven events within 120ms
+        for _ in 0..7 {
+            triggers.send(()).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let change = tokio::time::timeout(Duration::from_secs(1), input.changed()).await.unwrap().unwrap();
+        assert_eq!(scans.load(Ordering::SeqCst), 2, "one rescan for the burst");
+        assert_eq!(change.added.len(), 2);
+        assert!(change.removed.is_empty());
+        assert!(hidraw.changed().await.is_some());
+
+        // Events that change nothing rescan once and notify no one
+        for _ in 0..3 {
+            triggers.send(()).await.unwrap();
+        }
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        assert_eq!(scans.load(Ordering::SeqCst), 3);
+        assert!(tokio::time::timeout(Duration::from_secs(5), input.changed()).await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_endless_burst_still_rescans() {
+        let watcher = DeviceWatcher::new();
+        let (triggers, scans) = start(&watcher, vec![Inventory::default()]);
+        tokio::task::yield_now().await;
+        // An event every 100ms never leaves DEBOUNCE of quiet
+        let started = Instant::now();
+        while scans.load(Ordering::SeqCst) < 2 {
+            assert!(started.elapsed() <= MAX_SETTLE + DEBOUNCE, "no rescan during a continuous burst");
+            triggers.send(()).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_subscribers_hear_only_their_kinds() {
+        let watcher = DeviceWatcher::new();
+        let mut input = watcher.subscribe(INPUT);
+        let mut hidraw = watcher.subscribe(HIDRAW);
+        let states = vec![
+            Inventory::default(),
+            inventory(&[("/dev/hidraw1", DeviceKind::Hidraw, 5)]),
+            inventory(&[("/dev/hidraw1", DeviceKind::Hidraw, 5), ("/dev/input/event2", DeviceKind::Input, 6)]),
+        ];
+        let (triggers, _) = start(&watcher, states);
+        tokio::task::yield_now().await;
+
+        triggers.send(()).await.unwrap();
+        let change = hidraw.changed().await.unwrap();
+        assert_eq!(change.added[0].path, Path::new("/dev/hidraw1"));
+        triggers.send(()).await.unwrap();
+        // The hidraw-only change was skipped
+        let change = input.changed().await.unwrap();
+        assert_eq!(change.added[0].path, Path::new("/dev/input/event2"));
+    }
+
+    #[tokio::test]
+    async fn test_clear_drops_changes_already_seen() {
+        let watcher = DeviceWatcher::new();
+        let mut input = watcher.subscribe(INPUT);
+        let change = inventory(&[]).diff(&inventory(&[("/dev/input/event1", DeviceKind::Input, 1)])).unwrap();
+        watcher.tx.send(Arc::new(change.clone())).unwrap();
+        input.clear();
+        assert!(tokio::time::timeout(Duration::from_millis(50), input.changed()).await.is_err());
+        watcher.tx.send(Arc::new(change)).unwrap();
+        assert!(input.changed().await.is_some());
+    }
+
+    #[test]
+    fn test_recreated_node_is_a_change() {
+        let before = inventory(&[("/dev/input/event5", DeviceKind::Input, 100)]);
+        let after = inventory(&[("/dev/input/event5", DeviceKind::Input, 101)]);
+        let change = before.diff(&after).unwrap();
+        assert_eq!(change.added.len(), 1);
+        assert_eq!(change.removed.len(), 1);
+        assert!(change.touches(INPUT));
+        assert!(!change.touches(HIDRAW));
+        assert_eq!(before.diff(&before.clone()), None);
+    }
+
+    #[test]
+    fn test_scan_lists_event_and_hidraw_nodes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("input");
+        std::fs::create_dir(&input).unwrap();
+        for name in ["event0", "event12", "mouse0", "by-id"] {
+            std::fs::write(input.join(name), "").unwrap();
+        }
+        for name in ["hidraw0", "hidraw3", "tty0"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let inventory = Inventory::scan_in(&input, dir.path());
+        assert_eq!(inventory.len(), 4);
+        assert_eq!(Inventory::scan_in(&dir.path().join("missing"), &dir.path().join("missing")), Inventory::default());
+    }
+
+    #[test]
+    fn test_parse_udev_and_kernel_messages() {
+        let properties = b"ACTION=add\0DEVPATH=/devices/virtual/input/input30/event7\0SUBSYSTEM=input\0DEVNAME=/dev/input/event7\0SEQNUM=4512\0";
+        let mut udev = Vec::new();
+        udev.extend_from_slice(UDEV_PREFIX);
+        udev.extend_from_slice(&UDEV_MAGIC.to_be_bytes());
+        let header_size = 40u32;
+        udev.extend_from_slice(&header_size.to_ne_bytes());
+        udev.extend_from_slice(&header_size.to_ne_bytes());
+        udev.extend_from_slice(&(properties.len() as u32).to_ne_bytes());
+        udev.resize(header_size as usize, 0);
+        udev.extend_from_slice(properties);
+        let event = UEvent::parse(&udev).unwrap();
+        assert_eq!(event.action, "add");
+        assert_eq!(event.subsystem, "input");
+        assert_eq!(event.devname.as_deref(), Some("/dev/input/event7"));
+        assert!(event.is_relevant());
+
+        let mut kernel = b"remove@/devices/pci0000:00/hidraw/hidraw2\0".to_vec();
+        kernel.extend_from_slice(b"ACTION=remove\0SUBSYSTEM=hidraw\0DEVNAME=hidraw2\0");
+        let event = UEvent::parse(&kernel).unwrap();
+        assert_eq!((event.action.as_str(), event.subsystem.as_str()), ("remove", "hidraw"));
+        assert!(event.is_relevant());
+
+        let mut other = b"add@/devices/virtual/net/veth0\0".to_vec();
+        other.extend_from_slice(b"ACTION=add\0SUBSYSTEM=net\0");
+        assert!(!UEvent::parse(&other).unwrap().is_relevant());
+
+        // Wrong magic, truncated header, no properties
+        let mut forged = udev.clone();
+        forged[8] ^= 0xff;
+        assert_eq!(UEvent::parse(&forged), None);
+        assert_eq!(UEvent::parse(&udev[..12]), None);
+        assert_eq!(UEvent::parse(b"add@/devices/x\0"), None);
+    }
+}
@@ -0,0 +1,530 @@
+//! `juhradiald doctor`: device access checks that print their own fix
+//!
+//! The startup self-test ([`crate::self_test`]) explains why the mouse could
+//! not be opened; the doctor says exactly what to change. Each required
+//! access is one check: every Logitech hidraw node (HID++), /dev/uinput when
+//! keys are synthesized through ydotool, and membership of the `input` group
+//! the rules grant access to. A failing hidraw check carries a udev rule for
+//! the bus, vendor and product read from sysfs rather than a generic
+//! template; `--write-rules` installs the collected rules at [`RULES_PATH`].
+//! `DoctorReport()` runs the same checks for the settings UI.
+//!
+//! Under sudo, opening a node proves nothing, so a node counts as set up
+//! when udev gave it to the `input` group read-write, and the group check is
+//! about the user who ran sudo.
+
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::self_test::{Groups, SelfTest, INPUT_GROUP};
+
+/// Where `--write-rules` installs the generated rules
+///
+/// Numbered below 73 so `TAG+="uaccess"` still reaches systemd-logind's
+/// seat rules.
+pub const RULES_PATH: &str = "/etc/udev/rules.d/70-juhradialmx.rules";
+
+/// Rule giving the `input` group /dev/uinput, created at boot
+pub const UINPUT_RULE: &str =
+    r#"KERNEL=="uinput", SUBSYSTEM=="misc", MODE="0660", GROUP="input", TAG+="uaccess", OPTIONS+="static_node=uinput""#;
+
+/// Outcome of one doctor check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DoctorStatus {
+    Pass,
+    Fail,
+    /// Not needed in this session
+    Skip,
+}
+
+/// One checked access, with the fix when it fails
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DoctorCheck {
+    /// `hidraw_access`, `uinput_access` or `input_group`
+    pub check: &'static str,
+    pub status: DoctorStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<PathBuf>,
+    /// udev rule that grants the access
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Shell command that fixes it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl DoctorCheck {
+    fn new(check: &'static str, status: DoctorStatus, message: String) -> Self {
+        Self { check, status, message, node: None, rule: None, command: None }
+    }
+
+    fn node(mut self, node: &Path) -> Self {
+        self.node = Some(node.to_path_buf());
+        self
+    }
+}
+
+/// Everything one doctor run found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DoctorReport {
+    /// No check failed
+    pub passed: bool,
+    pub checks: Vec<DoctorCheck>,
+    /// Rules file for the failing checks; empty when none needs a rule
+    pub rules: String,
+}
+
+impl DoctorReport {
+    fn new(checks: Vec<DoctorCheck>) -> Self {
+        let mut rules: Vec<&str> = Vec::new();
+        for rule in checks.iter().filter_map(|c| c.rule.as_deref()) {
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
+        let rules = if rules.is_empty() {
+            String::new()
+        } else {
+            let mut file = String::from("# JuhRadial MX - generated by `juhradiald doctor` for the devices found\n");
+            for rule in rules {
+                file.push_str(rule);
+                file.push('\n');
+            }
+            file
+        };
+        Self {
+            passed: checks.iter().all(|c| c.status != DoctorStatus::Fail),
+            checks,
+            rules,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// One line per check, each failure followed by its rule and command
+    pub fn to_human(&self) -> String {
+        let mut out = String::new();
+        for c in &self.checks {
+            let status = match c.status {
+                DoctorStatus::Pass => "pass",
+                DoctorStatus::Fail => "FAIL",
+                DoctorStatus::Skip => "skip",
+            };
+            let _ = writeln!(out, "[{status}] {}: {}", c.check, c.message);
+            if let Some(rule) = &c.rule {
+                let _ = writeln!(out, "       rule: {rule}");
+            }
+            if let Some(command) = &c.command {
+                let _ = writeln!(out, "       run:  {command}");
+            }
+        }
+        if !self.rules.is_empty() {
+            let _ = writeln!(
+                out,
+                "\nInstall the rules with: sudo juhradiald doctor --write-rules\n\
+                 (writes {RULES_PATH}, then run: sudo udevadm control --reload && sudo udevadm trigger)"
+            );
+        }
+        out
+    }
+}
+
+/// Bus, vendor and product of a HID device (`HID_ID` in its uevent)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HidId {
+    pub bus: u16,
+    pub vendor: u16,
+    pub product: u16,
+}
+
+impl HidId {
+    /// From `HID_ID=0003:0000046D:0000C548`
+    pub fn from_uevent(uevent: &str) -> Option<Self> {
+        let id = uevent.lines().find_map(|l| l.strip_prefix("HID_ID="))?;
+        let mut parts = id.trim().split(':').map(|p| u32::from_str_radix(p, 16).ok().and_then(|v| u16::try_from(v).ok()));
+        let (bus, vendor, product) = (parts.next()??, parts.next()??, parts.next()??);
+        Some(Self { bus, vendor, product })
+    }
+
+    /// Rule giving the `input` group this device's hidraw nodes
+    ///
+    /// Matches the HID device's kernel name (`0005:046D:B042.0003`), which
+    /// Bluetooth devices have even though no parent carries `idVendor`.
+    pub fn hidraw_rule(&self) -> String {
+        format!(
+            r#"SUBSYSTEM=="hidraw", KERNELS=="{:04X}:{:04X}:{:04X}.*", MODE="0660", GROUP="input", TAG+="uaccess""#,
+            self.bus, self.vendor, self.product
+        )
+    }
+}
+
+/// What the node's ownership and an open attempt say about access
+struct NodeAccess {
+    /// Group name and permission bits; `None` when the node is missing
+    owner: Option<(String, u32)>,
+    opens: bool,
+}
+
+impl NodeAccess {
+    fn read(node: &Path, groups: &Groups) -> Self {
+        let owner = fs::metadata(node).ok().map(|m| (groups.name(m.gid()), m.mode() & 0o7777));
+        let opens = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(node)
+            .is_ok();
+        Self { owner, opens }
+    }
+
+    /// Udev handed the node to the `input` group read-write
+    fn input_group_rw(&self) -> bool {
+        self.owner.as_ref().is_some_and(|(group, mode)| group == INPUT_GROUP && mode & 0o060 == 0o060)
+    }
+
+    fn describe(&self) -> String {
+        match &self.owner {
+            Some((group, mode)) => format!("group '{group}', mode {mode:04o}"),
+            None => "missing".to_string(),
+        }
+    }
+}
+
+/// The doctor's view of the system, overridable for tests
+pub struct Doctor {
+    roots: SelfTest,
+    /// Keys are synthesized through ydotool, which writes to /dev/uinput
+    synthesis: bool,
+    /// Running as root, where every open succeeds
+    elevated: bool,
+    /// The user sudo was run by
+    sudo_user: Option<String>,
+}
+
+impl Doctor {
+    /// This system and session
+    ///
+    /// ydotool is used in Wayland sessions; sudo hides the session, so under
+    /// it an installed ydotool counts as used.
+    pub fn system() -> Self {
+        let elevated = crate::fs_util::current_uid() == 0;
+        let wayland = crate::session::SessionKind::detect().is_wayland();
+        Self {
+            roots: SelfTest::system(),
+            synthesis: wayland || (elevated && crate::sandbox::ExecutionStrategy::detect(true).on_path("ydotool")),
+            elevated,
+            sudo_user: std::env::var("SUDO_USER").ok().filter(|u| !u.is_empty() && u != "root"),
+        }
+    }
+
+    pub fn run(&self) -> DoctorReport {
+        let groups = match (&self.sudo_user, self.elevated) {
+            (Some(user), true) => self.roots.groups_of(user),
+            _ => self.roots.groups(),
+        };
+        let mut checks = self.check_hidraw(&groups);
+        checks.push(self.check_uinput(&groups));
+        checks.push(self.check_input_group(&groups));
+        DoctorReport::new(checks)
+    }
+
+    fn granted(&self, access: &NodeAccess) -> bool {
+        access.input_group_rw() || (access.opens && !self.elevated)
+    }
+
+    fn check_hidraw(&self, groups: &Groups) -> Vec<DoctorCheck> {
+        let nodes = self.roots.logitech_hidraw_nodes();
+        if nodes.is_empty() {
+            return vec![DoctorCheck::new(
+                "hidraw_access",
+                DoctorStatus::Fail,
+                "No Logitech hidraw device found; connect the mouse (or its receiver) and run the doctor again"
+                    .to_string(),
+            )];
+        }
+        nodes
+            .iter()
+            .map(|node| {
+                let name = node.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                let uevent = fs::read_to_string(self.roots.sys.join("class/hidraw").join(&name).join("device/uevent"))
+                    .unwrap_or_default();
+                let id = HidId::from_uevent(&uevent);
+                let device = uevent
+                    .lines()
+                    .find_map(|l| l.strip_prefix("HID_NAME="))
+                    .map(str::to_string)
+                    .or_else(|| id.map(|id| format!("{:04x}:{:04x}", id.vendor, id.product)))
+                    .unwrap_or_else(|| "Logitech device".to_string());
+                let access = NodeAccess::read(node, groups);
+                if self.granted(&access) {
+                    return DoctorCheck::new("hidraw_access", DoctorStatus::Pass, format!("{device}: {} accessible", node.display()))
+                        .node(node);
+                }
+                let mut check = if access.owner.is_none() {
+                    let mut check = DoctorCheck::new(
+                        "hidraw_access",
+                        DoctorStatus::Fail,
+                        format!("{device}: {} is in sysfs but missing from /dev; udev did not create it", node.display()),
+                    );
+                    check.command = Some("systemctl status systemd-udevd".to_string());
+                    check
+                } else {
+                    DoctorCheck::new(
+                        "hidraw_access",
+                        DoctorStatus::Fail,
+                        format!("{device}: {} not accessible ({})", node.display(), access.describe()),
+                    )
+                };
+                check.rule = id.map(|id| id.hidraw_rule());
+                check.node(node)
+            })
+            .collect()
+    }
+
+    fn check_uinput(&self, groups: &Groups) -> DoctorCheck {
+        if !self.synthesis {
+            return DoctorCheck::new(
+                "uinput_access",
+                DoctorStatus::Skip,
+                "Keys are synthesized with xdotool in this session; /dev/uinput is not needed".to_string(),
+            );
+        }
+        let node = self.roots.dev.join("uinput");
+        let access = NodeAccess::read(&node, groups);
+        if self.granted(&access) {
+            return DoctorCheck::new("uinput_access", DoctorStatus::Pass, format!("{} accessible for ydotool", node.display()))
+                .node(&node);
+        }
+        let mut check = DoctorCheck::new(
+            "uinput_access",
+            DoctorStatus::Fail,
+            format!("{} not accessible for ydotool ({})", node.display(), access.describe()),
+        );
+        check.rule = Some(UINPUT_RULE.to_string());
+        if access.owner.is_none() {
+            check.command = Some("sudo modprobe uinput".to_string());
+        }
+        check.node(&node)
+    }
+
+    fn check_input_group(&self, groups: &Groups) -> DoctorCheck {
+        let user = groups.user.as_str();
+        let Some(gid) = groups.table.iter().find(|(_, (name, _))| name == INPUT_GROUP).map(|(gid, _)| *gid) else {
+            let mut check = DoctorCheck::new("input_group", DoctorStatus::Fail, "There is no 'input' group".to_string());
+            check.command = Some(format!("sudo groupadd --system input && sudo usermod -aG input {user}"));
+            return check;
+        };
+        let listed = groups.user_listed_in(gid);
+        if groups.process_has(gid) || (self.elevated && listed) {
+            return DoctorCheck::new("input_group", DoctorStatus::Pass, format!("'{user}' is in the 'input' group"));
+        }
+        if listed {
+            return DoctorCheck::new(
+                "input_group",
+                DoctorStatus::Fail,
+                format!("'{user}' is in the 'input' group, but this session started before; log out and back in"),
+            );
+        }
+        let mut check = DoctorCheck::new("input_group", DoctorStatus::Fail, format!("'{user}' is not in the 'input' group"));
+        check.command = Some(format!("sudo usermod -aG input {user}"));
+        check
+    }
+}
+
+/// Run the doctor against this system
+pub fn run_doctor() -> DoctorReport {
+    Doctor::system().run()
+}
+
+/// Why `--write-rules` wrote nothing
+#[derive(Debug, thiserror::Error)]
+pub enum WriteRulesError {
+    #[error("writing {0} needs root; run: sudo juhradiald doctor --write-rules")]
+    NotRoot(PathBuf),
+    #[error("could not write {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Install `report.rules` at `path`, refusing unless running as root
+pub fn write_rules(report: &DoctorReport, path: &Path) -> Result<(), WriteRulesError> {
+    if crate::fs_util::current_uid() != 0 {
+        return Err(WriteRulesError::NotRoot(path.to_path_buf()));
+    }
+    fs::write(path, &report.rules).map_err(|source| WriteRulesError::Io { path: path.to_path_buf(), source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    const RECEIVER: &str = "HID_ID=0003:0000046D:0000C548\nHID_NAME=Logitech USB Receiver\n";
+    const BLUETOOTH: &str = "HID_ID=0005:0000046D:0000B042\nHID_NAME=MX Master 4\n";
+
+    /// A fake root: `/etc/group` names this process's own gid as `group`
+    /// so files it creates can pass as udev-owned
+    struct Layout {
+        dir: TempDir,
+        own_gid: u32,
+    }
+
+    impl Layout {
+        fn new(group: &str, members: &str) -> Self {
+            let dir = TempDir::new().unwrap();
+            let own_gid = fs::metadata(dir.path()).unwrap().gid();
+            let etc = dir.path().join("etc");
+            fs::create_dir_all(&etc).unwrap();
+            fs::create_dir_all(dir.path().join("dev")).unwrap();
+            fs::write(etc.join("group"), format!("{group}:x:{own_gid}:{members}\n")).unwrap();
+            fs::write(etc.join("passwd"), "alice:x:1000:1000::/home/alice:/bin/bash\n").unwrap();
+            Self { dir, own_gid }
+        }
+
+        fn hidraw(&self, name: &str, uevent: &str, mode: Option<u32>) {
+            let device = self.dir.path().join("sys/class/hidraw").join(name).join("device");
+            fs::create_dir_all(&device).unwrap();
+            fs::write(device.join("uevent"), uevent).unwrap();
+            if let Some(mode) = mode {
+                self.node(name, mode);
+            }
+        }
+
+        fn node(&self, name: &str, mode: u32) {
+            let node = self.dir.path().join("dev").join(name);
+            fs::write(&node, "").unwrap();
+            fs::set_permissions(&node, fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        /// Run as if under `sudo` by alice, where opens prove nothing
+        fn doctor(&self, synthesis: bool) -> Doctor {
+            let root = self.dir.path();
+            Doctor {
+                roots: SelfTest {
+                    sys: root.join("sys"),
+                    dev: root.join("dev"),
+                    proc: root.join("proc"),
+                    etc: root.join("etc"),
+                },
+                synthesis,
+                elevated: true,
+                sudo_user: Some("alice".to_string()),
+            }
+        }
+    }
+
+    fn check<'a>(report: &'a DoctorReport, name: &str) -> Vec<&'a DoctorCheck> {
+        report.checks.iter().filter(|c| c.check == name).collect()
+    }
+
+    #[test]
+    fn test_hid_id_and_rule_from_uevent() {
+        let id = HidId::from_uevent(BLUETOOTH).unwrap();
+        assert_eq!(id, HidId { bus: 5, vendor: 0x046d, product: 0xb042 });
+        assert_eq!(
+            id.hidraw_rule(),
+            r#"SUBSYSTEM=="hidraw", KERNELS=="0005:046D:B042.*", MODE="0660", GROUP="input", TAG+="uaccess""#
+        );
+        assert_eq!(HidId::from_uevent("HID_NAME=x\n"), None);
+        assert_eq!(HidId::from_uevent("HID_ID=0003:zz:1\n"), None);
+    }
+
+    #[test]
+    fn test_rules_missing_for_detected_devices() {
+        // Nodes udev left root-only: one rule per device, not per node
+        let layout = Layout::new("input", "alice");
+        layout.hidraw("hidraw1", RECEIVER, Some(0o600));
+        layout.hidraw("hidraw2", RECEIVER, Some(0o600));
+        layout.hidraw("hidraw4", BLUETOOTH, Some(0o600));
+        let report = layout.doctor(false).run();
+
+        assert!(!report.passed);
+        let hidraw = check(&report, "hidraw_access");
+        assert_eq!(hidraw.len(), 3);
+        assert!(hidraw.iter().all(|c| c.status == DoctorStatus::Fail));
+        assert!(hidraw[0].message.starts_with("Logitech USB Receiver:"));
+        assert!(hidraw[0].message.contains("mode 0600"));
+        assert_eq!(hidraw[2].rule.as_deref(), Some(HidId::from_uevent(BLUETOOTH).unwrap().hidraw_rule().as_str()));
+        let rules: Vec<&str> = report.rules.lines().skip(1).collect();
+        assert_eq!(
+            rules,
+            [
+                r#"SUBSYSTEM=="hidraw", KERNELS=="0003:046D:C548.*", MODE="0660", GROUP="input", TAG+="uaccess""#,
+                r#"SUBSYSTEM=="hidraw", KERNELS=="0005:046D:B042.*", MODE="0660", GROUP="input", TAG+="uaccess""#,
+            ]
+        );
+        assert!(report.to_human().contains("--write-rules"));
+
+        // With the rules applied every check passes and nothing is generated
+        let layout = Layout::new("input", "alice");
+        layout.hidraw("hidraw1", RECEIVER, Some(0o660));
+        let report = layout.doctor(false).run();
+        assert!(report.passed, "{}", report.to_human());
+        assert!(report.rules.is_empty());
+        assert_eq!(check(&report, "uinput_access")[0].status, DoctorStatus::Skip);
+    }
+
+    #[test]
+    fn test_missing_nodes() {
+        // Nothing plugged in: no rule can be generated
+        let layout = Layout::new("input", "alice");
+        let report = layout.doctor(false).run();
+        let hidraw = check(&report, "hidraw_access");
+        assert_eq!((hidraw.len(), hidraw[0].status), (1, DoctorStatus::Fail));
+        assert!(hidraw[0].rule.is_none());
+
+        // In sysfs but not in /dev, and uinput not loaded
+        layout.hidraw("hidraw3", RECEIVER, None);
+        let report = layout.doctor(true).run();
+        let hidraw = check(&report, "hidraw_access");
+        assert!(hidraw[0].message.contains("missing from /dev"));
+        let uinput = check(&report, "uinput_access");
+        assert_eq!(uinput[0].status, DoctorStatus::Fail);
+        assert_eq!(uinput[0].command.as_deref(), Some("sudo modprobe uinput"));
+        assert!(report.rules.contains(UINPUT_RULE));
+
+        // uinput present and given to the input group
+        layout.node("uinput", 0o660);
+        let report = layout.doctor(true).run();
+        assert_eq!(check(&report, "uinput_access")[0].status, DoctorStatus::Pass);
+    }
+
+    #[test]
+    fn test_group_membership() {
+        // Not a member: the command names the user sudo ran for
+        let layout = Layout::new("input", "");
+        layout.hidraw("hidraw1", RECEIVER, Some(0o660));
+        let report = layout.doctor(false).run();
+        let group = check(&report, "input_group")[0];
+        assert_eq!(group.status, DoctorStatus::Fail);
+        assert_eq!(group.command.as_deref(), Some("sudo usermod -aG input alice"));
+        assert!(report.rules.is_empty(), "group membership needs no rule");
+
+        // No input group at all; the node's group has another name
+        let layout = Layout::new("plugdev", "alice");
+        layout.hidraw("hidraw1", RECEIVER, Some(0o660));
+        let report = layout.doctor(false).run();
+        assert!(check(&report, "hidraw_access")[0].message.contains("group 'plugdev'"));
+        assert!(check(&report, "input_group")[0].command.as_deref().unwrap().starts_with("sudo groupadd"));
+
+        // Without sudo the process's own groups decide: listed but not held
+        let layout = Layout::new("input", "alice");
+        let mut doctor = layout.doctor(false);
+        doctor.elevated = false;
+        let mut groups = doctor.roots.groups_of("alice");
+        groups.process_gids = vec![1000];
+        let check = doctor.check_input_group(&groups);
+        assert!(check.message.contains("log out and back in"));
+        groups.process_gids.push(layout.own_gid);
+        assert_eq!(doctor.check_input_group(&groups).status, DoctorStatus::Pass);
+    }
+}
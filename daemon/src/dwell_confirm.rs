@@ -0,0 +1,234 @@
+//! Hover to select (`accessibility.dwell_confirm_ms`)
+//!
+//! For users who cannot comfortably hold the gesture button and release it
+//! on target: once the highlight has rested on one slice for the dwell time,
+//! the daemon emits `HideMenu` as if the button came up there, so the overlay
+//! runs the slice with its usual flash and `confirm` haptic. Any highlight
+//! change restarts the countdown and a close cancels it. The hub and the area
+//! outside the ring never dwell.
+//!
+//! Slices that do not run on a plain release never dwell either:
+//! `require_confirmation` slices keep their explicit second press, and
+//! `wheel_axis` slices close without running. Once a confirmation is pending,
+//! dwell is off for the rest of that menu. The button release that follows a
+//! dwell is swallowed so it cannot confirm a second time. Only profile menus
+//! arm it; ad-hoc menus are click-to-select already.
+//!
+//! `DwellConfirm` is a [`Deadline`] state machine; the D-Bus service and the
+//! gesture loop feed it through the [`DwellConfirmHandle`] on `DaemonHandles`,
+//! and a daemon task runs its timer.
+
+use std::time::{Duration, Instant};
+
+use crate::deadline::{Deadline, DeadlineHandle};
+
+/// Shortest accepted dwell; above the overlay's 250ms tap threshold, so the
+/// `HideMenu` a dwell sends is never taken for a tap
+pub const MIN_DWELL_CONFIRM: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// No menu, no highlight, or nothing to dwell on
+    Idle,
+    /// Highlight on `slot` since `due - dwell`
+    Dwelling { slot: u8, due: Instant },
+    /// The dwell confirmed; the next release belongs to it
+    Fired,
+}
+
+/// Dwell tracking for the highlighted slice of the open menu
+#[derive(Debug)]
+pub struct DwellConfirm {
+    /// Dwell for the open menu; `None` while closed or off
+    dwell: Option<Duration>,
+    /// Slots of the open menu that never dwell
+    excluded: Vec<u8>,
+    state: State,
+}
+
+impl DwellConfirm {
+    pub fn new() -> Self {
+        Self {
+            dwell: None,
+            excluded: Vec::new(),
+            state: State::Idle,
+        }
+    }
+
+    /// Menu shown; `dwell` is `None` when off, `excluded` never dwell
+    pub fn opened(&mut self, dwell: Option<Duration>, excluded: Vec<u8>) {
+        self.dwell = dwell;
+        self.excluded = excluded;
+        self.state = State::Idle;
+    }
+
+    /// Highlight moved to `slot` (`None` = hub or outside the ring)
+    pub fn highlighted(&mut self, slot: Option<u8>, now: Instant) {
+        self.state = match (self.state, slot, self.dwell) {
+            (State::Fired, ..) => return,
+            (State::Dwelling { slot: current, .. }, Some(slot), _) if current == slot => return,
+            (_, Some(slot), Some(dwell)) if !self.excluded.contains(&slot) => State::Dwelling { slot, due: now + dwell },
+            _ => State::Idle,
+        };
+    }
+
+    /// A confirmation is pending; no dwell for the rest of this menu
+    pub fn suspend(&mut self) {
+        self.dwell = None;
+        if let State::Dwelling { .. } = self.state {
+            self.state = State::Idle;
+        }
+    }
+
+    /// Menu closed (selection, cancel, dismissal or shutdown)
+    ///
+    /// A fired dwell is kept until the release or the next press.
+    pub fn closed(&mut self) {
+        self.dwell = None;
+        self.excluded.clear();
+        if let State::Dwelling { .. } = self.state {
+            self.state = State::Idle;
+        }
+    }
+
+    /// The gesture button went down; an earlier dwell no longer owns a release
+    pub fn pressed(&mut self) {
+        if self.state == State::Fired {
+            self.state = State::Idle;
+        }
+    }
+
+    /// The gesture button came up; returns whether a dwell already confirmed
+    /// this menu, in which case the release does nothing
+    pub fn released(&mut self) -> bool {
+        let fired = self.state == State::Fired;
+        if fired {
+            self.state = State::Idle;
+        }
+        fired
+    }
+
+    /// Timer woke: the slot to confirm once its highlight has rested long enough
+    ///
+    /// A timer outrun by a highlight change or close finds a later (or no)
+    /// deadline and confirms nothing.
+    pub fn fire(&mut self, now: Instant) -> Option<u8> {
+        match self.state {
+            State::Dwelling { slot, due } if now >= due => {
+                self.state = State::Fired;
+                Some(slot)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for DwellConfirm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deadline for DwellConfirm {
+    /// When the timer should next wake
+    fn wake_at(&self) -> Option<Instant> {
+        match self.state {
+            State::Dwelling { due, .. } => Some(due),
+            _ => None,
+        }
+    }
+}
+
+/// Shared handle between the D-Bus service, the gesture loop and the timer task
+pub type DwellConfirmHandle = DeadlineHandle<DwellConfirm>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DWELL: Duration = Duration::from_millis(900);
+
+    fn open() -> (DwellConfirm, Instant) {
+        let mut dwell = DwellConfirm::new();
+        // Slot 2 needs confirming, slot 5 is a wheel_axis slice
+        dwell.opened(Some(DWELL), vec![2, 5]);
+        (dwell, Instant::now())
+    }
+
+    #[test]
+    fn test_rested_highlight_confirms_once_and_swallows_the_release() {
+        let (mut dwell, t0) = open();
+        dwell.highlighted(Some(1), t0);
+        assert_eq!(dwell.wake_at(), Some(t0 + DWELL));
+        assert_eq!(dwell.fire(t0 + DWELL - Duration::from_millis(1)), None);
+        // Re-reporting the same slot keeps the countdown
+        dwell.highlighted(Some(1), t0 + Duration::from_millis(500));
+        assert_eq!(dwell.fire(t0 + DWELL), Some(1));
+        assert_eq!(dwell.fire(t0 + DWELL * 2), None);
+        // The overlay closes the menu, then the held button comes up
+        dwell.closed();
+        assert!(dwell.released());
+        assert!(!dwell.released());
+    }
+
+    #[test]
+    fn test_highlight_change_restarts_and_dead_zone_cancels() {
+        let (mut dwell, t0) = open();
+        dwell.highlighted(Some(1), t0);
+        let t1 = t0 + Duration::from_millis(600);
+        dwell.highlighted(Some(3), t1);
+        // The timer armed for slot 1 wakes and finds a later deadline
+        assert_eq!(dwell.fire(t0 + DWELL), None);
+        assert_eq!(dwell.wake_at(), Some(t1 + DWELL));
+
+        dwell.highlighted(None, t1);
+        assert_eq!(dwell.wake_at(), None);
+        assert_eq!(dwell.fire(t1 + DWELL), None);
+    }
+
+    #[test]
+    fn test_excluded_slots_never_dwell() {
+        let (mut dwell, t0) = open();
+        dwell.highlighted(Some(2), t0);
+        assert_eq!(dwell.wake_at(), None);
+        dwell.highlighted(Some(5), t0);
+        assert_eq!(dwell.fire(t0 + DWELL), None);
+        assert!(!dwell.released());
+    }
+
+    #[test]
+    fn test_pending_confirmation_suspends_dwell() {
+        let (mut dwell, t0) = open();
+        dwell.highlighted(Some(1), t0);
+        dwell.suspend();
+        assert_eq!(dwell.fire(t0 + DWELL), None);
+        dwell.highlighted(Some(3), t0);
+        assert_eq!(dwell.wake_at(), None);
+        // The next menu dwells again
+        dwell.opened(Some(DWELL), Vec::new());
+        dwell.highlighted(Some(3), t0);
+        assert!(dwell.wake_at().is_some());
+    }
+
+    #[test]
+    fn test_off_close_and_press_reset() {
+        let t0 = Instant::now();
+        let mut dwell = DwellConfirm::new();
+        dwell.opened(None, Vec::new());
+        dwell.highlighted(Some(0), t0);
+        assert_eq!(dwell.wake_at(), None);
+
+        let (mut dwell, t0) = open();
+        dwell.highlighted(Some(0), t0);
+        dwell.closed();
+        assert_eq!(dwell.fire(t0 + DWELL), None);
+
+        // A dwell in toggle mode has no release; the next press clears it so
+        // that press's own release still works
+        dwell.opened(Some(DWELL), Vec::new());
+        dwell.highlighted(Some(0), t0);
+        assert_eq!(dwell.fire(t0 + DWELL), Some(0));
+        dwell.pressed();
+        assert!(!dwell.released());
+    }
+}
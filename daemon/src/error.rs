@@ -0,0 +1,113 @@
+//! Daemon-level error
+//!
+//! Module APIs keep their own error types; `JuhError` is what the daemon's
+//! entry points return, so a startup failure ends in one message naming what
+//! went wrong instead of a panic. Every module error converts with `?`.
+
+use crate::actions::ActionError;
+use crate::adhoc_menu::AdHocError;
+use crate::battery::BatteryError;
+use crate::clipboard::{ClipboardError, PastePlainError};
+use crate::config::ConfigError;
+use crate::evdev::EvdevError;
+use crate::hidpp::request::RequestError;
+use crate::hidpp::HapticError;
+use crate::hidraw::HidrawError;
+use crate::instance::InstanceError;
+use crate::kwin_script::KWinScriptError;
+use crate::macros::dpi::DpiError;
+use crate::macros::recorder::RecorderError;
+use crate::macros::storage::StorageError;
+use crate::menu_simulation::SimulationError;
+use crate::profiles::ProfileError;
+use crate::theme::ThemeError;
+#[cfg(feature = "themes")]
+use crate::theme_watcher::ThemeWatcherError;
+
+/// Any daemon failure
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum JuhError {
+    /// The session bus could not be reached
+    #[error("Cannot connect to the session D-Bus: {0}")]
+    SessionBus(#[source] zbus::Error),
+    /// Another D-Bus failure (name claim, service export)
+    #[error("D-Bus error: {0}")]
+    DBus(#[from] zbus::Error),
+    /// Logging could not be set up
+    #[error("Cannot set up logging: {0}")]
+    Logging(#[from] tracing_subscriber::util::TryInitError),
+    /// Signal handlers and other OS resources
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A shared state lock was poisoned by a panicking thread
+    #[error("{0} lock poisoned")]
+    LockPoisoned(&'static str),
+    #[error(transparent)]
+    Instance(#[from] InstanceError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Theme(#[from] ThemeError),
+    #[cfg(feature = "themes")]
+    #[error(transparent)]
+    ThemeWatcher(#[from] ThemeWatcherError),
+    #[error(transparent)]
+    Profile(#[from] ProfileError),
+    #[error(transparent)]
+    Action(#[from] ActionError),
+    #[error(transparent)]
+    AdHoc(#[from] AdHocError),
+    #[error(transparent)]
+    Simulation(#[from] SimulationError),
+    #[error(transparent)]
+    Clipboard(#[from] ClipboardError),
+    #[error(transparent)]
+    PastePlain(#[from] PastePlainError),
+    #[error(transparent)]
+    Haptic(#[from] HapticError),
+    #[error(transparent)]
+    Request(#[from] RequestError),
+    #[error(transparent)]
+    Battery(#[from] BatteryError),
+    #[error(transparent)]
+    Evdev(#[from] EvdevError),
+    #[error(transparent)]
+    Hidraw(#[from] HidrawError),
+    #[error(transparent)]
+    KWinScript(#[from] KWinScriptError),
+    #[error(transparent)]
+    Dpi(#[from] DpiError),
+    #[error(transparent)]
+    Recorder(#[from] RecorderError),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fails_with_config() -> Result<(), JuhError> {
+        Err(ConfigError::ValidationError("bad haptics".to_string()))?
+    }
+
+    #[test]
+    fn test_module_errors_convert_and_keep_their_message() {
+        let err = fails_with_config().unwrap_err();
+        assert!(matches!(err, JuhError::Config(_)));
+        assert_eq!(err.to_string(), "Validation error: bad haptics");
+
+        let err = JuhError::from(HapticError::HostSwitchedAway);
+        assert_eq!(err.to_string(), "Device switched to another Easy-Switch host");
+        let err = JuhError::from(ProfileError::UnsupportedVersion { found: 9, backup: None });
+        assert!(err.to_string().contains("written by a newer JuhRadial MX"));
+    }
+
+    #[test]
+    fn test_startup_errors_name_the_failure() {
+        let err = JuhError::SessionBus(zbus::Error::Failure("no bus".to_string()));
+        assert!(err.to_string().starts_with("Cannot connect to the session D-Bus"));
+        assert_eq!(JuhError::LockPoisoned("config").to_string(), "config lock poisoned");
+    }
+}
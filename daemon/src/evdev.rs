@@ -12,12 +12,28 @@
 //! ## Event Handling
 //! Listens for EV_KEY events on the gesture button and emits
 //! `GestureEvent::Pressed` and `GestureEvent::Released` accordingly.
+//!
+//! ## Reconnection
+//! A read error or EOF from the device node (unplug, suspend/resume) ends the
+//! loop with `EvdevError::Disconnected`. The next `start()` re-scans and
+//! prefers the same physical device by vendor/product/uniq, since the kernel
+//! may hand it a different `/dev/input/eventN` after resume.
+//!
+//! ## Panic switch
+//! Gesture button presses also feed the panic switch straight from the read
+//! loop. Once it is disarmed the loop ends with `EvdevError::Disarmed`, which
+//! closes the node and so drops any grab, and `start()` waits for a re-arm
+//! before opening the device again.
 
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use std::path::PathBuf;
 use std::time::Instant;
 use tokio::sync::mpsc;
 
+use crate::menu_trigger::{SharedTriggerArbiter, TriggerId};
+use crate::handles::DaemonHandles;
+use crate::panic_switch::PanicSwitch;
+
 /// MX Master 4 vendor ID (Logitech)
 pub const LOGITECH_VENDOR_ID: u16 = 0x046D;
 
@@ -47,8 +63,9 @@ const PRIMARY_BUTTONS: &[u16] = &[0x110, 0x111, 0x112];
 pub enum GestureEvent {
     /// Gesture button pressed, includes cursor position
     Pressed { x: i32, y: i32 },
-    /// Gesture button released, includes hold duration
-    Released { duration_ms: u64 },
+    /// Gesture button released, includes hold duration and when it happened
+    /// (races the menu auto-dismiss timer)
+    Released { duration_ms: u64, released_at: Instant },
     /// Cursor moved while button is held (for hover detection on Wayland)
     CursorMoved { x: i32, y: i32 },
     /// A non-gesture button was pressed/released (for macro trigger detection)
@@ -60,6 +77,14 @@ pub enum GestureEvent {
     },
     /// Horizontal scroll from the diverted thumb wheel (sign = direction).
     ThumbwheelScroll { clicks: i32 },
+    /// Thumb wheel turned far enough to fire the focused profile's binding.
+    ThumbwheelBound {
+        direction: crate::thumbwheel_mapping::WheelDirection,
+    },
+    /// Vertical wheel detent over a held `wheel_axis` slice, past the rate limit.
+    WheelAxisStep {
+        direction: crate::wheel_axis::AxisDirection,
+    },
     /// A device-originated HID++ notification (live hardware state change).
     Hardware(crate::hidpp::notifications::HardwareNotification),
 }
@@ -79,6 +104,122 @@ pub struct DeviceInfo {
     pub is_mx_master_4: bool,
     /// Whether this is a generic (non-Logitech) mouse detected as fallback
     pub is_generic_mouse: bool,
+    /// Kernel unique id (serial or Bluetooth address), when the driver sets one
+    pub uniq: Option<String>,
+}
+
+/// A physical device independent of its `/dev/input/eventN` node
+///
+/// The node number can change across suspend/resume or a receiver re-pair;
+/// vendor, product and `uniq` do not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceIdentity {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub uniq: Option<String>,
+}
+
+impl DeviceIdentity {
+    /// Identity of a scanned device
+    pub fn of(info: &DeviceInfo) -> Self {
+        Self {
+            vendor_id: info.vendor_id,
+            product_id: info.product_id,
+            uniq: info.uniq.clone(),
+        }
+    }
+
+    /// Same vendor/product, and the same `uniq` when both sides report one
+    pub fn matches(&self, info: &DeviceInfo) -> bool {
+        self.vendor_id == info.vendor_id
+            && self.product_id == info.product_id
+            && match (&self.uniq, &info.uniq) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+/// Pick the device to attach to from scan candidates (in node order)
+///
+/// Prefers the previously attached device wherever its node is now: an exact
+/// identity first, then a vendor/product match. Otherwise the first candidate.
+pub fn select_device(candidates: Vec<DeviceInfo>, previous: Option<&DeviceIdentity>) -> Option<DeviceInfo> {
+    let index = previous
+        .and_then(|id| {
+            candidates
+                .iter()
+                .position(|c| DeviceIdentity::of(c) == *id)
+                .or_else(|| candidates.iter().position(|c| id.matches(c)))
+        })
+        .unwrap_or(0);
+    candidates.into_iter().nth(index)
+}
+
+/// Whether a read error means the device node is gone (unplug, suspend)
+pub fn is_disconnect_error(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::UnexpectedEof
+        || matches!(e.raw_os_error(), Some(libc::ENODEV) | Some(libc::ENXIO) | Some(libc::EBADF))
+}
+
+/// Source of input events for the read loop
+///
+/// The device node in production; tests feed scripted events and errors
+/// (e.g. `ENODEV` to simulate a resume) through the same loop.
+#[cfg(target_os = "linux")]
+pub(crate) trait EventSource {
+    async fn next_event(&mut self) -> std::io::Result<evdev::InputEvent>;
+}
+
+/// Non-blocking reader over an opened device
+///
+/// Unlike `evdev::EventStream`, which retries forever when the node reads
+/// EOF after a resume, an empty read on a readable fd is reported as
+/// `UnexpectedEof`.
+#[cfg(target_os = "linux")]
+struct DeviceEvents {
+    device: tokio::io::unix::AsyncFd<evdev::Device>,
+    pending: VecDeque<evdev::InputEvent>,
+}
+
+#[cfg(target_os = "linux")]
+impl DeviceEvents {
+    fn new(device: evdev::Device) -> std::io::Result<Self> {
+        device.set_nonblocking(true)?;
+        Ok(Self {
+            device: tokio::io::unix::AsyncFd::new(device)?,
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl EventSource for DeviceEvents {
+    async fn next_event(&mut self) -> std::io::Result<evdev::InputEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+            let mut guard = self.device.readable_mut().await?;
+            let read = guard.try_io(|device| {
+                device
+                    .get_mut()
+                    .fetch_events()
+                    .map(|events| events.collect::<Vec<_>>())
+            });
+            match read {
+                Ok(Ok(events)) if events.is_empty() => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "device node returned EOF",
+                    ));
+                }
+                Ok(Ok(events)) => self.pending.extend(events),
+                Ok(Err(e)) => return Err(e),
+                Err(_would_block) => continue,
+            }
+        }
+    }
 }
 
 /// evdev handler for MX Master 4 and generic mice
@@ -113,7 +254,17 @@ pub struct EvdevHandler {
     active_button_action: Option<crate::config::ButtonAction>,
     /// Live KWin availability (D-Bus name ownership), used to pick the cursor
     /// backend on KDE instead of the XDG_CURRENT_DESKTOP env var (issue #32).
-    kwin_available: Option<crate::compositor::KWinAvailability>,
+    kwin_available: crate::compositor::KWinAvailability,
+    /// Per-profile thumb-wheel bindings (MX only; needs the grabbed device)
+    thumbwheel: Option<crate::thumbwheel_mapping::SharedThumbwheelMapper>,
+    /// Last attached device, re-found by identity after a disconnect
+    last_device: Option<DeviceIdentity>,
+    /// Decides which configured trigger owns the menu
+    arbiter: SharedTriggerArbiter,
+    /// Trigger whose press this handler acted on (its release is the one we honour)
+    held_trigger: Option<TriggerId>,
+    /// Daemon state shared with the service (panic switch, wheel axis)
+    handles: DaemonHandles,
 }
 
 impl EvdevHandler {
@@ -133,7 +284,12 @@ impl EvdevHandler {
             suppressed_keys: HashSet::new(),
             shared_config: None,
             active_button_action: None,
-            kwin_available: None,
+            kwin_available: crate::compositor::KWinAvailability::new(),
+            thumbwheel: None,
+            last_device: None,
+            arbiter: crate::menu_trigger::new_shared_arbiter(),
+            held_trigger: None,
+            handles: DaemonHandles::default(),
         }
     }
 
@@ -153,10 +309,20 @@ impl EvdevHandler {
             suppressed_keys: HashSet::new(),
             shared_config: None,
             active_button_action: None,
-            kwin_available: None,
+            kwin_available: crate::compositor::KWinAvailability::new(),
+            thumbwheel: None,
+            last_device: None,
+            arbiter: crate::menu_trigger::new_shared_arbiter(),
+            held_trigger: None,
+            handles: DaemonHandles::default(),
         }
     }
 
+    /// Intercept REL_HWHEEL for per-profile thumb-wheel bindings
+    pub fn set_thumbwheel_mapper(&mut self, mapper: crate::thumbwheel_mapping::SharedThumbwheelMapper) {
+        self.thumbwheel = Some(mapper);
+    }
+
     /// Set the shared configuration for button action lookup
     pub fn set_shared_config(&mut self, config: crate::config::SharedConfig) {
         self.shared_config = Some(config);
@@ -165,7 +331,7 @@ impl EvdevHandler {
     /// Share the live KWin availability flag so the gesture handler can pick the
     /// cursor backend by D-Bus capability rather than an environment string.
     pub fn set_kwin_availability(&mut self, kwin: crate::compositor::KWinAvailability) {
-        self.kwin_available = Some(kwin);
+        self.kwin_available = kwin;
     }
 
     /// Set which key codes should be suppressed (eaten) from the OS.
@@ -175,11 +341,75 @@ impl EvdevHandler {
         self.suppressed_keys = keys;
     }
 
+    /// Share the trigger arbiter with the other menu trigger sources
+    pub fn set_trigger_arbiter(&mut self, arbiter: SharedTriggerArbiter) {
+        self.arbiter = arbiter;
+    }
+
+    /// Share the daemon's state with the service and the other input loops
+    pub fn set_handles(&mut self, handles: DaemonHandles) {
+        self.handles = handles;
+    }
+
+    /// Use another panic switch than the one in the shared handles
+    pub fn set_panic_switch(&mut self, panic_switch: PanicSwitch) {
+        self.handles.panic_switch = panic_switch;
+    }
+
+    /// Whether a key code counts towards the panic sequence
+    ///
+    /// Deliberately ignores config and arbiter state: the MX gesture button,
+    /// or the legacy trigger button on a generic mouse.
+    fn is_panic_key(&self, key_code: u16) -> bool {
+        if self.generic_mode {
+            key_code == self.trigger_button
+        } else {
+            GESTURE_BUTTON_CODES.contains(&key_code)
+        }
+    }
+
     /// Update the trigger button (e.g. after config reload)
     pub fn set_trigger_button(&mut self, code: u16) {
         self.trigger_button = code;
     }
 
+    /// `evdev-button` codes from the `triggers` config list
+    pub fn configured_trigger_codes(&self) -> Vec<u16> {
+        self.shared_config
+            .as_ref()
+            .and_then(|c| c.read().ok().map(|c| c.evdev_trigger_codes()))
+            .unwrap_or_default()
+    }
+
+    /// Whether `triggers` includes the MX gesture button (default when unset)
+    fn mx_gesture_enabled(&self) -> bool {
+        self.shared_config
+            .as_ref()
+            .and_then(|c| c.read().ok().map(|c| c.mx_gesture_trigger()))
+            .unwrap_or(true)
+    }
+
+    /// Which menu trigger a key code is, if any
+    ///
+    /// Generic mode listens for the configured `evdev-button` codes, falling
+    /// back to the single `generic_trigger_button` when none are configured.
+    /// MX mode listens for the gesture button plus the configured codes.
+    fn trigger_for_key(&mut self, key_code: u16) -> Option<TriggerId> {
+        let configured = self.configured_trigger_codes();
+        if self.generic_mode && configured.is_empty() {
+            // Re-read trigger from config on each key event
+            // so rebinds in settings take effect immediately
+            self.reload_trigger_from_config();
+            return (key_code == self.trigger_button).then_some(TriggerId::EvdevButton(key_code));
+        }
+        if !self.generic_mode && GESTURE_BUTTON_CODES.contains(&key_code) {
+            return Some(TriggerId::MxGesture);
+        }
+        configured
+            .contains(&key_code)
+            .then_some(TriggerId::EvdevButton(key_code))
+    }
+
     /// Re-read trigger button from config file if it changed (throttled to every 2s)
     fn reload_trigger_from_config(&mut self) {
         if self.last_config_check.elapsed().as_secs() < 2 {
@@ -187,12 +417,7 @@ impl EvdevHandler {
         }
         self.last_config_check = Instant::now();
 
-        let home = match std::env::var("HOME") {
-            Ok(h) => h,
-            Err(_) => return,
-        };
-        let path = std::path::PathBuf::from(home).join(".config/juhradial/config.json");
-        let data = match std::fs::read_to_string(&path) {
+        let data = match std::fs::read_to_string(crate::paths::paths().config_path()) {
             Ok(d) => d,
             Err(_) => return,
         };
@@ -217,22 +442,28 @@ impl EvdevHandler {
     ///
     /// Returns the first matching device found.
     pub fn find_device() -> Result<DeviceInfo, EvdevError> {
+        Self::find_device_matching(None)
+    }
+
+    /// Scan for an MX Master 4, preferring `previous` wherever its node is now
+    pub fn find_device_matching(previous: Option<&DeviceIdentity>) -> Result<DeviceInfo, EvdevError> {
         // On non-Linux systems, return an error
         #[cfg(not(target_os = "linux"))]
         {
+            let _ = previous;
             tracing::warn!("evdev is only available on Linux");
             return Err(EvdevError::DeviceNotFound);
         }
 
         #[cfg(target_os = "linux")]
         {
-            Self::scan_linux_devices()
+            Self::scan_linux_devices(previous)
         }
     }
 
     /// Scan all input devices on Linux
     #[cfg(target_os = "linux")]
-    fn scan_linux_devices() -> Result<DeviceInfo, EvdevError> {
+    fn scan_linux_devices(previous: Option<&DeviceIdentity>) -> Result<DeviceInfo, EvdevError> {
         use std::fs;
 
         let input_dir = PathBuf::from("/dev/input");
@@ -258,6 +489,7 @@ impl EvdevHandler {
                 .unwrap_or(u32::MAX)
         });
 
+        let mut candidates = Vec::new();
         for entry in sorted_entries {
             let path = entry.path();
             let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
@@ -278,8 +510,7 @@ impl EvdevHandler {
                     );
 
                     if info.is_mx_master_4 {
-                        tracing::info!("MX Master 4 detected at {:?}", path);
-                        return Ok(info);
+                        candidates.push(info);
                     }
                 }
                 Ok(None) => continue,
@@ -290,6 +521,11 @@ impl EvdevHandler {
             }
         }
 
+        if let Some(info) = select_device(candidates, previous) {
+            tracing::info!("MX Master 4 detected at {:?}", info.path);
+            return Ok(info);
+        }
+
         tracing::warn!("MX Master 4 not found. Waiting for connection...");
         Err(EvdevError::DeviceNotFound)
     }
@@ -299,22 +535,41 @@ impl EvdevHandler {
     /// Looks for devices with EV_REL + REL_X + REL_Y capabilities (i.e., a mouse).
     /// Returns the first matching device found.
     pub fn find_any_mouse() -> Result<DeviceInfo, EvdevError> {
+        Self::find_any_mouse_matching(None)
+    }
+
+    /// Scan for any mouse, preferring `previous` wherever its node is now
+    pub fn find_any_mouse_matching(previous: Option<&DeviceIdentity>) -> Result<DeviceInfo, EvdevError> {
+        Self::find_any_mouse_with_buttons(previous, &[])
+    }
+
+    /// Scan for any mouse, preferring ones that report one of `trigger_codes`
+    /// (then `previous` among them) so the configured button is actually on
+    /// the attached device
+    pub fn find_any_mouse_with_buttons(
+        previous: Option<&DeviceIdentity>,
+        trigger_codes: &[u16],
+    ) -> Result<DeviceInfo, EvdevError> {
         #[cfg(not(target_os = "linux"))]
         {
+            let _ = (previous, trigger_codes);
             tracing::warn!("Generic mouse detection is only available on Linux");
             return Err(EvdevError::DeviceNotFound);
         }
 
         #[cfg(target_os = "linux")]
         {
-            Self::scan_generic_mouse()
+            Self::scan_generic_mouse(previous, trigger_codes)
         }
     }
 
     /// Scan all input devices for any mouse on Linux
     #[cfg(target_os = "linux")]
-    fn scan_generic_mouse() -> Result<DeviceInfo, EvdevError> {
-        use evdev::{Device, EventType, RelativeAxisCode};
+    fn scan_generic_mouse(
+        previous: Option<&DeviceIdentity>,
+        trigger_codes: &[u16],
+    ) -> Result<DeviceInfo, EvdevError> {
+        use evdev::{Device, EventType, KeyCode, RelativeAxisCode};
         use std::fs;
 
         let input_dir = PathBuf::from("/dev/input");
@@ -339,6 +594,8 @@ impl EvdevHandler {
                 .unwrap_or(u32::MAX)
         });
 
+        let mut candidates = Vec::new();
+        let mut has_trigger = Vec::new();
         for entry in sorted_entries {
             let path = entry.path();
             let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
@@ -399,25 +656,45 @@ impl EvdevHandler {
                 continue;
             }
 
+            let reports_trigger = device
+                .supported_keys()
+                .is_some_and(|keys| trigger_codes.iter().any(|&c| keys.contains(KeyCode(c))));
+
             tracing::info!(
                 path = %path.display(),
                 name = %name,
                 vendor = format!("0x{:04X}", vendor_id),
                 product = format!("0x{:04X}", product_id),
                 phys = %phys,
+                reports_trigger,
                 "Found generic mouse"
             );
 
-            return Ok(DeviceInfo {
+            has_trigger.push(reports_trigger);
+            candidates.push(DeviceInfo {
                 path: path.clone(),
                 name,
                 vendor_id,
                 product_id,
                 is_mx_master_4: false,
                 is_generic_mouse: true,
+                uniq: device.unique_name().filter(|u| !u.is_empty()).map(str::to_string),
             });
         }
 
+        // Mice without any configured trigger button only as a last resort
+        if has_trigger.contains(&true) {
+            candidates = candidates
+                .into_iter()
+                .zip(has_trigger)
+                .filter_map(|(info, reports)| reports.then_some(info))
+                .collect();
+        }
+
+        if let Some(info) = select_device(candidates, previous) {
+            return Ok(info);
+        }
+
         tracing::warn!("No generic mouse found");
         Err(EvdevError::DeviceNotFound)
     }
@@ -474,6 +751,7 @@ impl EvdevHandler {
             product_id,
             is_mx_master_4,
             is_generic_mouse: false,
+            uniq: device.unique_name().filter(|u| !u.is_empty()).map(str::to_string),
         }))
     }
 
@@ -525,15 +803,21 @@ impl EvdevHandler {
     /// Run the event loop on Linux
     #[cfg(target_os = "linux")]
     async fn run_event_loop(&mut self) -> Result<(), EvdevError> {
-        use evdev::{uinput::VirtualDevice as UinputDevice, Device, EventType, RelativeAxisCode};
+        use evdev::{uinput::VirtualDevice as UinputDevice, Device};
+
+        // Disarmed: leave the device alone until `Rearm`
+        if !self.handles.panic_switch.is_armed() {
+            tracing::info!("Input interception disarmed; waiting for re-arm before opening the device");
+            self.handles.panic_switch.armed().await;
+        }
 
-        // Find the device based on mode
+        // Find the device based on mode, preferring the one we last attached to
+        let previous = self.last_device.clone();
         let device_info = if self.generic_mode {
-            Self::find_any_mouse()?
+            Self::find_any_mouse_with_buttons(previous.as_ref(), &self.scan_trigger_codes())?
         } else {
-            Self::find_device()?
+            Self::find_device_matching(previous.as_ref())?
         };
-        self.device_path = Some(device_info.path.clone());
 
         // Open the device for reading
         let mut device = Device::open(&device_info.path).map_err(|e| {
@@ -556,6 +840,11 @@ impl EvdevHandler {
             device_info.path,
             mode_label
         );
+        if previous.as_ref().is_some_and(|id| id.matches(&device_info)) {
+            tracing::info!(path = %device_info.path.display(), "Reattached to previous device");
+        }
+        self.device_path = Some(device_info.path.clone());
+        self.last_device = Some(DeviceIdentity::of(&device_info));
 
         // If we have macro-bound buttons to suppress, grab the device
         // exclusively and forward non-suppressed events via a virtual device.
@@ -595,8 +884,46 @@ impl EvdevHandler {
             }
         }
 
-        // Create async event stream using into_event_stream()
-        let mut events = device.into_event_stream().map_err(EvdevError::IoError)?;
+        let mut events = DeviceEvents::new(device).map_err(EvdevError::IoError)?;
+        let result = self.pump_events(&mut events, virtual_device.as_mut()).await;
+        self.device_path = None;
+        if matches!(result, Err(EvdevError::Disconnected)) {
+            tracing::warn!(path = %device_info.path.display(), "Device node went away; re-scanning");
+        }
+        result
+    }
+
+    /// Button codes a generic mouse should report to be preferred by the scan
+    pub fn scan_trigger_codes(&self) -> Vec<u16> {
+        let configured = self.configured_trigger_codes();
+        if configured.is_empty() {
+            vec![self.trigger_button]
+        } else {
+            configured
+        }
+    }
+
+    /// Forget per-connection input state (a held button's release died with the node)
+    #[cfg(target_os = "linux")]
+    fn reset_input_state(&mut self) {
+        self.press_time = None;
+        self.menu_active = false;
+        self.active_button_action = None;
+        // Let other triggers open the menu again
+        if let Some(trigger) = self.held_trigger.take() {
+            crate::menu_trigger::release(&self.arbiter, trigger);
+        }
+    }
+
+    /// Read events until the source fails, forwarding through `virtual_device`
+    /// when the device is grabbed
+    #[cfg(target_os = "linux")]
+    async fn pump_events<S: EventSource>(
+        &mut self,
+        events: &mut S,
+        mut virtual_device: Option<&mut evdev::uinput::VirtualDevice>,
+    ) -> Result<(), EvdevError> {
+        use evdev::{EventType, RelativeAxisCode};
 
         // Buffer for batching events between SYN_REPORT frames.
         // Physical mice group REL_X + REL_Y + SYN_REPORT into one report.
@@ -606,41 +933,84 @@ impl EvdevHandler {
         let mut event_batch: Vec<evdev::InputEvent> = Vec::with_capacity(8);
 
         loop {
-            match events.next_event().await {
+            // Reads are cancel-safe: fetched events wait in the source's queue
+            let read = tokio::select! {
+                read = events.next_event() => read,
+                _ = self.handles.panic_switch.disarmed() => {
+                    self.reset_input_state();
+                    return Err(EvdevError::Disarmed);
+                }
+            };
+            match read {
                 Ok(event) => {
+                    // Panic sequence first, before any menu or grab handling
+                    if event.event_type() == EventType::KEY
+                        && event.value() == 1
+                        && self.is_panic_key(event.code())
+                        && self.handles.panic_switch.record_press(Instant::now())
+                    {
+                        self.reset_input_state();
+                        return Err(EvdevError::Disarmed);
+                    }
+
                     // Determine if this event should be suppressed from the OS.
                     // Only suppress KEY press/release (value 0 or 1) for macro-bound buttons.
                     let is_suppressed_key = event.event_type() == EventType::KEY
                         && self.suppressed_keys.contains(&event.code())
                         && (event.value() == 0 || event.value() == 1);
 
+                    // Horizontal wheel bound by the focused profile: keep it
+                    // from the OS. Only possible while events are re-emitted
+                    // through the virtual device.
+                    let is_bound_wheel = virtual_device.is_some()
+                        && event.event_type() == EventType::RELATIVE
+                        && matches!(
+                            RelativeAxisCode(event.code()),
+                            RelativeAxisCode::REL_HWHEEL | RelativeAxisCode::REL_HWHEEL_HI_RES
+                        )
+                        && self.thumbwheel_intercepts(event.value());
+
+                    // Vertical wheel over a held `wheel_axis` slice: it steps
+                    // the slice's action instead of scrolling
+                    let is_axis_wheel = virtual_device.is_some()
+                        && event.event_type() == EventType::RELATIVE
+                        && matches!(
+                            RelativeAxisCode(event.code()),
+                            RelativeAxisCode::REL_WHEEL | RelativeAxisCode::REL_WHEEL_HI_RES
+                        )
+                        && self.handles.wheel_axis.is_armed();
+
                     // Batch events for the virtual device.
                     // When SYN_REPORT arrives, emit the entire batch at once
                     // (emit() auto-appends SYN_REPORT, preserving original timing).
-                    if let Some(ref mut vdev) = virtual_device {
+                    if let Some(vdev) = virtual_device.as_deref_mut() {
                         if event.event_type() == EventType::SYNCHRONIZATION {
                             if !event_batch.is_empty() {
                                 let _ = vdev.emit(&event_batch);
                                 event_batch.clear();
                             }
-                        } else if !is_suppressed_key {
+                        } else if !is_suppressed_key && !is_bound_wheel && !is_axis_wheel {
                             event_batch.push(event);
                         }
                     }
 
+                    // Flick mode counts travel whichever loop saw the press
+                    if event.event_type() == EventType::RELATIVE {
+                        let delta = match RelativeAxisCode(event.code()) {
+                            RelativeAxisCode::REL_X => Some((event.value(), 0)),
+                            RelativeAxisCode::REL_Y => Some((0, event.value())),
+                            _ => None,
+                        };
+                        if let Some((dx, dy)) = delta {
+                            self.handles.flick.with(|f| f.pointer_delta(dx, dy));
+                        }
+                    }
+
                     match event.event_type() {
                         EventType::KEY => {
                             let key_code = event.code();
-                            let is_trigger = if self.generic_mode {
-                                // Re-read trigger from config on each key event
-                                // so rebinds in settings take effect immediately
-                                self.reload_trigger_from_config();
-                                key_code == self.trigger_button
-                            } else {
-                                GESTURE_BUTTON_CODES.contains(&key_code)
-                            };
-                            if is_trigger {
-                                self.handle_gesture_event(event.value()).await;
+                            if let Some(trigger) = self.trigger_for_key(key_code) {
+                                self.handle_gesture_event(trigger, event.value()).await;
                             } else if !PRIMARY_BUTTONS.contains(&key_code) {
                                 // Forward non-primary, non-gesture buttons for macro trigger detection
                                 let value = event.value();
@@ -655,6 +1025,22 @@ impl EvdevHandler {
                                 }
                             }
                         }
+                        // Hi-res events are swallowed alongside; detents count once
+                        EventType::RELATIVE
+                            if is_bound_wheel
+                                && RelativeAxisCode(event.code()) == RelativeAxisCode::REL_HWHEEL =>
+                        {
+                            self.handle_bound_thumbwheel(event.value()).await;
+                        }
+                        EventType::RELATIVE
+                            if is_axis_wheel
+                                && RelativeAxisCode(event.code()) == RelativeAxisCode::REL_WHEEL =>
+                        {
+                            let step = self.handles.wheel_axis.update(|w| w.detent(event.value(), Instant::now()));
+                            if let Some(direction) = step.flatten() {
+                                let _ = self.event_tx.send(GestureEvent::WheelAxisStep { direction }).await;
+                            }
+                        }
                         // Track mouse movement while menu is active
                         EventType::RELATIVE if self.menu_active => {
                             let code = RelativeAxisCode(event.code());
@@ -692,6 +1078,11 @@ impl EvdevHandler {
                         // No events available, continue waiting
                         continue;
                     }
+                    if is_disconnect_error(&e) {
+                        tracing::debug!("Read failed with {}; treating as disconnect", e);
+                        self.reset_input_state();
+                        return Err(EvdevError::Disconnected);
+                    }
                     tracing::error!("Error reading event: {:?}", e);
                     return Err(EvdevError::IoError(e));
                 }
@@ -699,6 +1090,28 @@ impl EvdevHandler {
         }
     }
 
+    /// Whether the focused profile binds this horizontal wheel direction
+    fn thumbwheel_intercepts(&self, value: i32) -> bool {
+        self.thumbwheel
+            .as_ref()
+            .and_then(|m| m.read().ok().map(|m| m.intercepts(value)))
+            .unwrap_or(false)
+    }
+
+    /// Accumulate bound REL_HWHEEL detents and fire the binding when due
+    async fn handle_bound_thumbwheel(&self, detents: i32) {
+        let direction = match self.thumbwheel.as_ref().and_then(|m| m.write().ok()) {
+            Some(mut mapper) => mapper.feed(detents),
+            None => return,
+        };
+        if let Some(direction) = direction {
+            let _ = self
+                .event_tx
+                .send(GestureEvent::ThumbwheelBound { direction })
+                .await;
+        }
+    }
+
     /// Get the configured action for the evdev trigger button.
     ///
     /// On MX Master 4, the radial thumb button normally arrives through HID++
@@ -719,39 +1132,51 @@ impl EvdevHandler {
         crate::config::ButtonAction::RadialMenu
     }
 
-    /// Handle a gesture button event
-    async fn handle_gesture_event(&mut self, value: i32) {
+    /// Handle a press/release of one menu trigger
+    async fn handle_gesture_event(&mut self, trigger: TriggerId, value: i32) {
         match value {
             1 => {
-                // Button pressed - check configured action
-                let action = self.get_evdev_button_action();
+                // Button pressed - check configured action (explicit
+                // `evdev-button` triggers always open the menu)
+                let action = match trigger {
+                    TriggerId::MxGesture => self.get_evdev_button_action(),
+                    _ => crate::config::ButtonAction::RadialMenu,
+                };
+                if action == crate::config::ButtonAction::RadialMenu {
+                    if trigger == TriggerId::MxGesture && !self.mx_gesture_enabled() {
+                        tracing::debug!("mx-gesture-button is not a configured trigger; ignoring press");
+                        return;
+                    }
+                    if !crate::menu_trigger::claim(&self.arbiter, trigger) {
+                        tracing::debug!(%trigger, "Menu held by another trigger; ignoring press");
+                        return;
+                    }
+                }
+                self.held_trigger = Some(trigger);
                 self.active_button_action = Some(action);
                 self.press_time = Some(Instant::now());
 
                 if action == crate::config::ButtonAction::RadialMenu {
                     // Radial menu flow: need cursor position
                     self.menu_active = true;
+                    self.handles.flick.update(|f, now| f.pressed(now));
                     self.cursor_x = 0;
                     self.cursor_y = 0;
 
                     // Pick the cursor backend by whether KWin owns its D-Bus
                     // name, not by XDG_CURRENT_DESKTOP, which is empty when
                     // systemd starts the daemon at cold boot (issue #32).
-                    let kwin_owned = self
-                        .kwin_available
-                        .as_ref()
-                        .map(|k| k.is_owned())
-                        .unwrap_or(false);
+                    let kwin_owned = self.kwin_available.is_owned();
 
-                    if crate::compositor::cursor_backend(kwin_owned)
+                    if crate::compositor::cursor_backend(kwin_owned, self.kwin_available.helper().cursor().is_some())
                         == crate::compositor::CursorBackend::KWin
                     {
                         tracing::info!(
                             kwin_owned,
                             "Gesture button pressed (radial_menu) - triggering KWin cursor query"
                         );
-                        if !Self::trigger_kwin_cursor_script() {
-                            let pos = crate::cursor::get_cursor_position();
+                        if !Self::trigger_kwin_cursor_script(self.kwin_available.helper()) {
+                            let pos = crate::cursor::get_cursor_position(self.kwin_available.helper());
                             tracing::warn!(
                                 x = pos.x,
                                 y = pos.y,
@@ -763,7 +1188,7 @@ impl EvdevHandler {
                                 .await;
                         }
                     } else {
-                        let pos = crate::cursor::get_cursor_position();
+                        let pos = crate::cursor::get_cursor_position(self.kwin_available.helper());
                         tracing::info!(
                             x = pos.x,
                             y = pos.y,
@@ -788,7 +1213,11 @@ impl EvdevHandler {
                 }
             }
             0 => {
-                // Button released
+                // Only the release of the press we acted on counts
+                if self.held_trigger != Some(trigger) {
+                    return;
+                }
+                self.held_trigger = None;
                 self.menu_active = false;
                 let duration_ms = self
                     .press_time
@@ -799,10 +1228,14 @@ impl EvdevHandler {
                 let active_action = self.active_button_action.take();
                 match active_action {
                     Some(crate::config::ButtonAction::RadialMenu) | None => {
-                        tracing::info!(duration_ms, "Gesture button released (radial_menu)");
+                        if !crate::menu_trigger::release(&self.arbiter, trigger) {
+                            tracing::debug!(%trigger, "Menu taken over by another trigger; ignoring release");
+                            return;
+                        }
+                        tracing::info!(duration_ms, %trigger, "Gesture button released (radial_menu)");
                         let _ = self
                             .event_tx
-                            .send(GestureEvent::Released { duration_ms })
+                            .send(GestureEvent::Released { duration_ms, released_at: Instant::now() })
                             .await;
                     }
                     Some(action) => {
@@ -826,85 +1259,14 @@ impl EvdevHandler {
     /// Trigger KWin script to get cursor position and call ShowMenuAtCursor
     ///
     /// This works correctly on Plasma 6 Wayland with multiple monitors.
-    fn trigger_kwin_cursor_script() -> bool {
-        use std::io::Write;
-        use std::process::Command;
-        use tempfile::Builder;
-
-        let script = crate::cursor::KWIN_CURSOR_SCRIPT;
-
-        // Create a temporary file with .js suffix securely
-        let mut temp_file = match Builder::new().suffix(".js").tempfile() {
-            Ok(file) => file,
-            Err(e) => {
-                tracing::warn!("Failed to create temp file for KWin script: {}", e);
-                return false;
-            }
-        };
-
-        // Write script to temp file
-        if let Err(e) = write!(temp_file, "{}", script) {
-            tracing::warn!("Failed to write KWin script: {}", e);
-            return false;
-        }
-
-        // Get the path as a string
-        let script_path = temp_file.path().to_string_lossy();
-
-        // Load script via D-Bus
-        let load_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                "/Scripting",
-                "org.kde.kwin.Scripting.loadScript",
-                &format!("string:{}", script_path),
-            ])
-            .output();
-
-        let load_output = match load_result {
-            Ok(output) if output.status.success() => output,
-            _ => {
-                tracing::warn!("Failed to load KWin script");
-                return false;
-            }
-        };
-
-        // Parse script ID from output (looks like "int32 5")
-        let stdout = String::from_utf8_lossy(&load_output.stdout);
-        let script_id: Option<i32> = stdout
-            .lines()
-            .find(|line| line.contains("int32"))
-            .and_then(|line| line.split_whitespace().last())
-            .and_then(|s| s.parse().ok());
-
-        let script_id = match script_id {
-            Some(id) => id,
-            None => {
-                tracing::warn!("Failed to parse KWin script ID");
-                return false;
-            }
-        };
-
-        // Run the script
-        let run_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                &format!("/Scripting/Script{}", script_id),
-                "org.kde.kwin.Script.run",
-            ])
-            .output();
-
-        match run_result {
-            Ok(output) if output.status.success() => {
+    pub(crate) fn trigger_kwin_cursor_script(helper: &crate::kwin_script::KWinHelper) -> bool {
+        match helper.run_script_blocking(crate::cursor::KWIN_CURSOR_SCRIPT, crate::kwin_script::CURSOR_PLUGIN) {
+            Ok(script_id) => {
                 tracing::debug!(script_id, "KWin cursor script triggered successfully");
                 true
             }
-            _ => {
-                tracing::warn!("Failed to run KWin script");
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to run KWin cursor script");
                 false
             }
         }
@@ -948,31 +1310,25 @@ impl EvdevHandler {
 }
 
 /// evdev error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum EvdevError {
     /// MX Master 4 device not found
+    #[error("MX Master 4 not found")]
     DeviceNotFound,
     /// Permission denied accessing device
+    #[error("Permission denied. Ensure udev rules are installed and user is in 'input' group.")]
     PermissionDenied,
+    /// The open device node failed or hit EOF (unplug, suspend/resume)
+    #[error("Device disconnected")]
+    Disconnected,
+    /// The panic switch disarmed input interception
+    #[error("Input interception disarmed")]
+    Disarmed,
     /// I/O error
+    #[error("I/O error: {0}")]
     IoError(std::io::Error),
 }
 
-impl std::fmt::Display for EvdevError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            EvdevError::DeviceNotFound => write!(f, "MX Master 4 not found"),
-            EvdevError::PermissionDenied => write!(
-                f,
-                "Permission denied. Ensure udev rules are installed and user is in 'input' group."
-            ),
-            EvdevError::IoError(e) => write!(f, "I/O error: {}", e),
-        }
-    }
-}
-
-impl std::error::Error for EvdevError {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1005,7 +1361,7 @@ mod tests {
     fn test_gesture_event_equality() {
         let e1 = GestureEvent::Pressed { x: 100, y: 200 };
         let e2 = GestureEvent::Pressed { x: 100, y: 200 };
-        let e3 = GestureEvent::Released { duration_ms: 500 };
+        let e3 = GestureEvent::Released { duration_ms: 500, released_at: Instant::now() };
 
         assert_eq!(e1, e2);
         assert_ne!(e1, e3);
@@ -1051,6 +1407,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_configured_evdev_triggers() {
+        let config = crate::config::Config {
+            triggers: vec![
+                crate::config::MenuTrigger::MxGestureButton,
+                crate::config::MenuTrigger::EvdevButton { code: 0x114 },
+            ],
+            ..Default::default()
+        };
+        let shared = std::sync::Arc::new(std::sync::RwLock::new(config));
+
+        let (tx, _rx) = mpsc::channel(1);
+        let mut mx = EvdevHandler::new(tx.clone());
+        mx.set_shared_config(shared.clone());
+        assert_eq!(mx.trigger_for_key(0x116), Some(TriggerId::MxGesture));
+        assert_eq!(mx.trigger_for_key(0x114), Some(TriggerId::EvdevButton(0x114)));
+        assert_eq!(mx.trigger_for_key(0x113), None);
+
+        // Configured codes replace the legacy generic_trigger_button
+        let mut generic = EvdevHandler::new_generic(tx, Some(0x113));
+        generic.set_shared_config(shared);
+        assert_eq!(generic.trigger_for_key(0x113), None);
+        assert_eq!(generic.trigger_for_key(0x116), None);
+        assert_eq!(generic.trigger_for_key(0x114), Some(TriggerId::EvdevButton(0x114)));
+        assert_eq!(generic.scan_trigger_codes(), vec![0x114]);
+    }
+
     #[test]
     fn test_evdev_error_display() {
         let err = EvdevError::DeviceNotFound;
@@ -1058,5 +1441,157 @@ mod tests {
 
         let err = EvdevError::PermissionDenied;
         assert!(format!("{}", err).contains("Permission denied"));
+
+        assert_eq!(EvdevError::Disconnected.to_string(), "Device disconnected");
+    }
+
+    fn mx_info(node: u32, uniq: Option<&str>) -> DeviceInfo {
+        DeviceInfo {
+            path: PathBuf::from(format!("/dev/input/event{}", node)),
+            name: "MX Master 4".to_string(),
+            vendor_id: LOGITECH_VENDOR_ID,
+            product_id: 0xB034,
+            is_mx_master_4: true,
+            is_generic_mouse: false,
+            uniq: uniq.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_select_device_follows_renumbered_node() {
+        let before = mx_info(5, Some("d2:7a:11:00:00:01"));
+        let previous = DeviceIdentity::of(&before);
+
+        // After resume the mouse is event9; a second MX took a lower number
+        let candidates = vec![mx_info(4, Some("d2:7a:11:00:00:02")), mx_info(9, Some("d2:7a:11:00:00:01"))];
+        let picked = select_device(candidates, Some(&previous)).unwrap();
+        assert_eq!(picked.path, PathBuf::from("/dev/input/event9"));
+
+        // No uniq from the driver: fall back to vendor/product
+        let candidates = vec![mx_info(9, None)];
+        assert_eq!(select_device(candidates, Some(&previous)).unwrap().path, PathBuf::from("/dev/input/event9"));
+
+        // Previous device gone: first candidate in node order
+        let mut other = mx_info(3, None);
+        other.product_id = 0xB035;
+        assert_eq!(select_device(vec![other], Some(&previous)).unwrap().product_id, 0xB035);
+        assert!(select_device(Vec::new(), Some(&previous)).is_none());
+    }
+
+    #[test]
+    fn test_disconnect_errors() {
+        assert!(is_disconnect_error(&std::io::Error::from_raw_os_error(libc::ENODEV)));
+        assert!(is_disconnect_error(&std::io::Error::from(std::io::ErrorKind::UnexpectedEof)));
+        assert!(!is_disconnect_error(&std::io::Error::from_raw_os_error(libc::EINVAL)));
+    }
+
+    /// Scripted reads: events in order, then the given error (e.g. a resume)
+    #[cfg(target_os = "linux")]
+    struct ScriptedEvents(VecDeque<std::io::Result<evdev::InputEvent>>);
+
+    #[cfg(target_os = "linux")]
+    impl EventSource for ScriptedEvents {
+        async fn next_event(&mut self) -> std::io::Result<evdev::InputEvent> {
+            self.0
+                .pop_front()
+                .unwrap_or_else(|| Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof)))
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_enodev_on_read_ends_loop_as_disconnect() {
+        use evdev::{EventType, InputEvent};
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let mut handler = EvdevHandler::new(tx);
+        handler.set_suppressed_keys([GESTURE_BUTTON_CODES[0]].into_iter().collect());
+        handler.menu_active = true;
+        handler.press_time = Some(Instant::now());
+
+        let mut events = ScriptedEvents(VecDeque::from([
+            Ok(InputEvent::new(EventType::KEY.0, 0x113, 1)),
+            Ok(InputEvent::new(EventType::SYNCHRONIZATION.0, 0, 0)),
+            Err(std::io::Error::from_raw_os_error(libc::ENODEV)),
+        ]));
+        let result = handler.pump_events(&mut events, None).await;
+        assert!(matches!(result, Err(EvdevError::Disconnected)));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            GestureEvent::MacroTriggered { key_code: 0x113, pressed: true }
+        );
+
+        // Held-button state is dropped; the grab config survives for the re-grab
+        assert!(!handler.menu_active);
+        assert!(handler.press_time.is_none());
+        assert!(handler.suppressed_keys.contains(&GESTURE_BUTTON_CODES[0]));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_other_read_errors_stay_io_errors() {
+        let (tx, _rx) = mpsc::channel(1);
+        let mut handler = EvdevHandler::new(tx);
+        let mut events = ScriptedEvents(VecDeque::from([Err(std::io::Error::from_raw_os_error(libc::EINVAL))]));
+        let result = handler.pump_events(&mut events, None).await;
+        assert!(matches!(result, Err(EvdevError::IoError(_))));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_panic_sequence_disarms_stuck_menu() {
+        use evdev::{EventType, InputEvent};
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let mut handler = EvdevHandler::new(tx);
+        let panic_switch = crate::panic_switch::PanicSwitch::new();
+        handler.set_panic_switch(panic_switch.clone());
+        // Wedged: the menu thinks it is held and another trigger owns the arbiter
+        let arbiter = crate::menu_trigger::new_shared_arbiter();
+        assert!(crate::menu_trigger::claim(&arbiter, TriggerId::Shortcut(0)));
+        handler.set_trigger_arbiter(arbiter);
+        handler.menu_active = true;
+        handler.press_time = Some(Instant::now());
+
+        let mut script = VecDeque::new();
+        for _ in 0..crate::panic_switch::PANIC_PRESSES {
+            for value in [1, 0] {
+                script.push_back(Ok(InputEvent::new(EventType::KEY.0, GESTURE_BUTTON_CODES[0], value)));
+                script.push_back(Ok(InputEvent::new(EventType::SYNCHRONIZATION.0, 0, 0)));
+            }
+        }
+        // Never reached: the loop stops at the fifth press
+        script.push_back(Ok(InputEvent::new(EventType::KEY.0, 0x113, 1)));
+        let result = handler.pump_events(&mut ScriptedEvents(script), None).await;
+
+        assert!(matches!(result, Err(EvdevError::Disarmed)));
+        assert!(!panic_switch.is_armed());
+        assert!(!handler.menu_active);
+        assert!(handler.press_time.is_none());
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// A device that never produces an event
+    #[cfg(target_os = "linux")]
+    struct IdleEvents;
+
+    #[cfg(target_os = "linux")]
+    impl EventSource for IdleEvents {
+        async fn next_event(&mut self) -> std::io::Result<evdev::InputEvent> {
+            std::future::pending().await
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_disarm_ends_idle_loop() {
+        let (tx, _rx) = mpsc::channel(1);
+        let mut handler = EvdevHandler::new(tx);
+        let panic_switch = crate::panic_switch::PanicSwitch::new();
+        handler.set_panic_switch(panic_switch.clone());
+        panic_switch.disarm(crate::panic_switch::DisarmReason::Signal);
+
+        let result = handler.pump_events(&mut IdleEvents, None).await;
+        assert!(matches!(result, Err(EvdevError::Disarmed)));
     }
 }
@@ -0,0 +1,440 @@
+//! Execution policy for profile Command and KWin actions
+//!
+//! profiles.json is plain JSON in the user's home directory, so a shared or
+//! naive profile can carry `"command": "rm -rf ~"`. The policy from
+//! config.json decides whether such actions run:
+//!
+//! - `unrestricted`: run everything (default, historical behavior)
+//! - `confirm`: refuse Command/KWin actions until the current profiles.json
+//!   content has been approved through `ApproveProfile`
+//! - `allowlist`: only commands whose argv is allowlisted: the first word a
+//!   listed absolute path (`allowed_binaries`), or the whole argv one of
+//!   `allowed_commands`. The command is split into words like a shell would
+//!   and run without one, so nothing can be chained behind it; shell
+//!   metacharacters are rejected rather than passed on as literal arguments.
+//!   `$JUH_*` variables in the words are substituted when the command runs
+//!
+//! In every mode `max_commands_per_minute` caps how many Command actions
+//! start in any minute. The count is kept in a [`CommandRateLimit`] shared by
+//! every policy the daemon builds.
+//!
+//! Menus of app profiles run here in the daemon. The overlay's Settings ring
+//! (config.json) checks each `exec` slice with `CheckCommand`, under the same
+//! allowlist and rate limit; `confirm` does not apply to it, as for ad-hoc
+//! menus, since it is not profiles.json content.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::actions::ActionError;
+use crate::config::{CommandPolicy, ExecutionConfig};
+use crate::fs_util;
+
+pub use juhradial_core::content_hash::content_hash;
+
+/// File (in the config dir) holding the approved profiles.json content hash
+const APPROVAL_FILENAME: &str = "approved_profiles";
+
+/// Characters a shell would act on; refused in allowlist mode, where no
+/// shell runs and they would only become literal arguments. `$` is allowed:
+/// `$JUH_*` variables are substituted into the argv, other names stay literal
+const SHELL_METACHARACTERS: &[char] = &[';', '|', '&', '`', '<', '>', '(', ')', '\n', '\\'];
+
+/// How long the rate limit looks back
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Starts of recent Command actions, for `max_commands_per_minute`
+#[derive(Debug, Default)]
+pub struct CommandRate {
+    starts: VecDeque<Instant>,
+}
+
+impl CommandRate {
+    /// Record a start at `at` unless `limit` commands already started in the
+    /// minute before it; returns how long until the next one may start when
+    /// refused. A `limit` of 0 never refuses.
+    pub fn try_start(&mut self, limit: u32, at: Instant) -> Result<(), Duration> {
+        if limit == 0 {
+            return Ok(());
+        }
+        while self.starts.front().is_some_and(|start| at.saturating_duration_since(*start) >= RATE_WINDOW) {
+            self.starts.pop_front();
+        }
+        let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+        if self.starts.len() >= limit {
+            let oldest = self.starts[self.starts.len() - limit];
+            return Err((oldest + RATE_WINDOW).saturating_duration_since(at));
+        }
+        self.starts.push_back(at);
+        Ok(())
+    }
+}
+
+/// Shared handle to the daemon's [`CommandRate`]
+#[derive(Debug, Clone, Default)]
+pub struct CommandRateLimit {
+    rate: Arc<Mutex<CommandRate>>,
+}
+
+impl CommandRateLimit {
+    /// [`CommandRate::try_start`] now; a poisoned lock never refuses
+    fn try_start(&self, limit: u32) -> Result<(), ActionError> {
+        let Ok(mut rate) = self.rate.lock() else {
+            return Ok(());
+        };
+        rate.try_start(limit, Instant::now())
+            .map_err(|wait| ActionError::RateLimited(u64::try_from(wait.as_millis()).unwrap_or(u64::MAX)))
+    }
+}
+
+/// How an allowed command runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandLine {
+    /// Through `sh -c`, as written
+    Shell,
+    /// This argv, without a shell (allowlist mode)
+    Argv(Vec<String>),
+}
+
+/// Resolved policy for one round of action execution
+#[derive(Debug, Clone)]
+pub struct ExecutionPolicy {
+    policy: CommandPolicy,
+    allowed_binaries: Vec<PathBuf>,
+    allowed_commands: Vec<Vec<String>>,
+    profiles_approved: bool,
+    max_per_minute: u32,
+    rate: CommandRateLimit,
+}
+
+impl ExecutionPolicy {
+    /// Policy that runs every action (built-in presets, default config)
+    pub fn unrestricted() -> Self {
+        Self {
+            policy: CommandPolicy::Unrestricted,
+            allowed_binaries: Vec::new(),
+            allowed_commands: Vec::new(),
+            profiles_approved: true,
+            max_per_minute: 0,
+            rate: CommandRateLimit::default(),
+        }
+    }
+
+    /// Resolve the policy for the default profiles.json and approval file
+    pub fn from_config(config: &ExecutionConfig, rate: &CommandRateLimit) -> Self {
+        Self::for_paths(config, rate, &crate::profiles::get_profiles_path(), &get_approval_path())
+    }
+
+    /// Resolve the policy for explicit profiles/approval file paths
+    pub fn for_paths(
+        config: &ExecutionConfig,
+        rate: &CommandRateLimit,
+        profiles_path: &Path,
+        approval_path: &Path,
+    ) -> Self {
+        let profiles_approved = config.policy != CommandPolicy::Confirm
+            || profiles_approved(profiles_path, approval_path);
+        Self {
+            policy: config.policy,
+            allowed_binaries: config.allowed_binaries.clone(),
+            allowed_commands: config.allowed_commands.clone(),
+            profiles_approved,
+            max_per_minute: config.max_commands_per_minute,
+            rate: rate.clone(),
+        }
+    }
+
+    /// Policy for ad-hoc menus (`ShowAdHocMenu`) and the Settings ring
+    /// (`CheckCommand`)
+    ///
+    /// The allowlist and rate limit still apply; `confirm` does not, since
+    /// there is no profiles.json content to approve.
+    pub fn for_adhoc(config: &ExecutionConfig, rate: &CommandRateLimit) -> Self {
+        Self {
+            profiles_approved: true,
+            ..Self::for_paths(config, rate, Path::new(""), Path::new(""))
+        }
+    }
+
+    /// Check a shell command against the policy and count it as started
+    ///
+    /// Returns how the command must run: in allowlist mode, the argv that
+    /// was checked, without a shell.
+    pub fn check_command(&self, cmd: &str) -> Result<CommandLine, ActionError> {
+        let line = match self.policy {
+            CommandPolicy::Unrestricted => CommandLine::Shell,
+            CommandPolicy::Confirm => {
+                self.check_approved()?;
+                CommandLine::Shell
+            }
+            CommandPolicy::Allowlist => {
+                if cmd.contains(SHELL_METACHARACTERS) {
+                    return Err(ActionError::CommandNotAllowed(format!(
+                        "shell metacharacters are not allowed in allowlist mode: {}",
+                        cmd
+                    )));
+                }
+                let argv = shlex::split(cmd)
+                    .ok_or_else(|| ActionError::CommandNotAllowed(format!("unbalanced quotes: {}", cmd)))?;
+                self.check_allowlisted(&argv)?;
+                CommandLine::Argv(argv)
+            }
+        };
+        self.rate.try_start(self.max_per_minute)?;
+        Ok(line)
+    }
+
+    /// Check a command the overlay is about to spawn as `argv` (no shell)
+    /// and count it as started
+    pub fn check_argv(&self, argv: &[String]) -> Result<(), ActionError> {
+        match self.policy {
+            CommandPolicy::Unrestricted => {}
+            CommandPolicy::Confirm => self.check_approved()?,
+            CommandPolicy::Allowlist => self.check_allowlisted(argv)?,
+        }
+        self.rate.try_start(self.max_per_minute)
+    }
+
+    /// Whether the whole of `argv` is allowlisted: its program by path, or
+    /// the argv as listed
+    fn check_allowlisted(&self, argv: &[String]) -> Result<(), ActionError> {
+        let Some(binary) = argv.first() else {
+            return Err(ActionError::CommandNotAllowed("empty command".to_string()));
+        };
+        let path = Path::new(binary);
+        let by_binary = path.is_absolute() && self.allowed_binaries.iter().any(|b| b == path);
+        if by_binary || self.allowed_commands.iter().any(|allowed| allowed.as_slice() == argv) {
+            Ok(())
+        } else {
+            Err(ActionError::CommandNotAllowed(format!(
+                "'{}' is not an allowlisted absolute path or command",
+                argv.join(" ")
+            )))
+        }
+    }
+
+    /// Check a KWin shortcut invocation against the policy
+    ///
+    /// Allowlist mode only covers binaries, so KWin shortcuts are left alone.
+    pub fn check_kwin(&self) -> Result<(), ActionError> {
+        match self.policy {
+            CommandPolicy::Confirm => self.check_approved(),
+            CommandPolicy::Unrestricted | CommandPolicy::Allowlist => Ok(()),
+        }
+    }
+
+    fn check_approved(&self) -> Result<(), ActionError> {
+        if self.profiles_approved {
+            Ok(())
+        } else {
+            Err(ActionError::ProfileNotApproved)
+        }
+    }
+}
+
+/// Path of the approval file (~/.config/juhradial/approved_profiles)
+pub fn get_approval_path() -> PathBuf {
+    crate::profiles::get_config_dir().join(APPROVAL_FILENAME)
+}
+
+/// Record the current profiles.json content as approved
+///
+/// Returns the approved content hash.
+pub fn approve_profiles(profiles_path: &Path, approval_path: &Path) -> std::io::Result<u64> {
+    let hash = content_hash(&fs::read(profiles_path)?);
+    fs_util::write_private(approval_path, format!("{:016x}\n", hash))?;
+    tracing::info!(
+        path = %profiles_path.display(),
+        hash = %format!("{:016x}", hash),
+        "Profiles approved for command execution"
+    );
+    Ok(hash)
+}
+
+/// True when profiles.json is unchanged since it was last approved
+pub fn profiles_approved(profiles_path: &Path, approval_path: &Path) -> bool {
+    let Ok(contents) = fs::read(profiles_path) else {
+        return false;
+    };
+    let Ok(approved) = fs::read_to_string(approval_path) else {
+        return false;
+    };
+    u64::from_str_radix(approved.trim(), 16)
+        .map(|hash| hash == content_hash(&contents))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(policy: CommandPolicy, allowed: &[&str]) -> ExecutionConfig {
+        ExecutionConfig {
+            policy,
+            allowed_binaries: allowed.iter().map(PathBuf::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn argv(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_unrestricted_allows_everything() {
+        let policy = ExecutionPolicy::unrestricted();
+        assert_eq!(policy.check_command("rm -rf ~/tmp; echo done").unwrap(), CommandLine::Shell);
+        assert!(policy.check_kwin().is_ok());
+    }
+
+    #[test]
+    fn test_allowlist_mode() {
+        let dir = TempDir::new().unwrap();
+        let policy = ExecutionPolicy::for_paths(
+            &config(CommandPolicy::Allowlist, &["/usr/bin/konsole"]),
+            &CommandRateLimit::default(),
+            &dir.path().join("profiles.json"),
+            &dir.path().join(APPROVAL_FILENAME),
+        );
+
+        assert_eq!(
+            policy.check_command("/usr/bin/konsole --workdir '/tmp/my dir'").unwrap(),
+            CommandLine::Argv(argv(&["/usr/bin/konsole", "--workdir", "/tmp/my dir"]))
+        );
+        assert!(matches!(
+            policy.check_command("/usr/bin/konsole 'unbalanced"),
+            Err(ActionError::CommandNotAllowed(_))
+        ));
+        assert!(matches!(
+            policy.check_command("konsole"),
+            Err(ActionError::CommandNotAllowed(_))
+        ));
+        assert!(matches!(
+            policy.check_command("/usr/bin/dolphin"),
+            Err(ActionError::CommandNotAllowed(_))
+        ));
+        assert!(matches!(
+            policy.check_command("/usr/bin/konsole; rm -rf ~"),
+            Err(ActionError::CommandNotAllowed(_))
+        ));
+        assert!(matches!(
+            policy.check_command("/usr/bin/konsole $(whoami)"),
+            Err(ActionError::CommandNotAllowed(_))
+        ));
+        assert!(policy.check_kwin().is_ok());
+    }
+
+    #[test]
+    fn test_allowlist_matches_whole_argv() {
+        let cfg = ExecutionConfig {
+            policy: CommandPolicy::Allowlist,
+            allowed_commands: vec![argv(&["/usr/bin/systemctl", "suspend"])],
+            ..Default::default()
+        };
+        let policy = ExecutionPolicy::for_adhoc(&cfg, &CommandRateLimit::default());
+        assert_eq!(
+            policy.check_command("/usr/bin/systemctl  suspend").unwrap(),
+            CommandLine::Argv(argv(&["/usr/bin/systemctl", "suspend"]))
+        );
+        assert!(policy.check_argv(&argv(&["/usr/bin/systemctl", "suspend"])).is_ok());
+        // The binary alone is not allowlisted, so other arguments are refused
+        for refused in [&["/usr/bin/systemctl", "poweroff"][..], &["/usr/bin/systemctl"], &[]] {
+            assert!(matches!(policy.check_argv(&argv(refused)), Err(ActionError::CommandNotAllowed(_))));
+        }
+        assert!(matches!(
+            policy.check_command("/usr/bin/systemctl suspend; rm -rf ~"),
+            Err(ActionError::CommandNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_allowlist_keeps_invocation_variables() {
+        let cfg = ExecutionConfig {
+            policy: CommandPolicy::Allowlist,
+            allowed_commands: vec![argv(&["printf", "%s", "$JUH_PROFILE"])],
+            ..Default::default()
+        };
+        let policy = ExecutionPolicy::for_adhoc(&cfg, &CommandRateLimit::default());
+        // Checked as written; the variable is substituted when it runs
+        assert_eq!(
+            policy.check_command("printf %s $JUH_PROFILE").unwrap(),
+            CommandLine::Argv(argv(&["printf", "%s", "$JUH_PROFILE"]))
+        );
+        assert!(matches!(
+            policy.check_command("printf %s $(id)"),
+            Err(ActionError::CommandNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_rate_limit_window() {
+        let start = Instant::now();
+        let mut rate = CommandRate::default();
+        assert!(rate.try_start(0, start).is_ok());
+        assert!(rate.try_start(2, start).is_ok());
+        assert!(rate.try_start(2, start + Duration::from_secs(10)).is_ok());
+        assert_eq!(rate.try_start(2, start + Duration::from_secs(20)), Err(Duration::from_secs(40)));
+        // The first start leaves the window after a minute
+        assert!(rate.try_start(2, start + Duration::from_secs(60)).is_ok());
+        assert_eq!(rate.try_start(2, start + Duration::from_secs(61)), Err(Duration::from_secs(9)));
+    }
+
+    #[test]
+    fn test_rate_limit_is_shared_between_policies() {
+        let cfg = ExecutionConfig {
+            max_commands_per_minute: 1,
+            ..Default::default()
+        };
+        let rate = CommandRateLimit::default();
+        assert!(ExecutionPolicy::from_config(&cfg, &rate).check_command("true").is_ok());
+        assert!(matches!(
+            ExecutionPolicy::for_adhoc(&cfg, &rate).check_argv(&argv(&["true"])),
+            Err(ActionError::RateLimited(_))
+        ));
+        // Built-in presets are never limited
+        assert!(ExecutionPolicy::unrestricted().check_command("true").is_ok());
+    }
+
+    #[test]
+    fn test_confirm_mode_requires_approval() {
+        let dir = TempDir::new().unwrap();
+        let profiles = dir.path().join("profiles.json");
+        let approval = dir.path().join(APPROVAL_FILENAME);
+        fs::write(&profiles, r#"{"profiles": []}"#).unwrap();
+        let cfg = config(CommandPolicy::Confirm, &[]);
+
+        let policy = ExecutionPolicy::for_paths(&cfg, &CommandRateLimit::default(), &profiles, &approval);
+        assert!(matches!(policy.check_command("konsole"), Err(ActionError::ProfileNotApproved)));
+        assert!(matches!(policy.check_kwin(), Err(ActionError::ProfileNotApproved)));
+
+        approve_profiles(&profiles, &approval).unwrap();
+        let policy = ExecutionPolicy::for_paths(&cfg, &CommandRateLimit::default(), &profiles, &approval);
+        assert!(policy.check_command("konsole").is_ok());
+        assert!(policy.check_kwin().is_ok());
+
+        // Any edit to profiles.json revokes the approval
+        fs::write(&profiles, r#"{"profiles": [], "hardware": {}}"#).unwrap();
+        let policy = ExecutionPolicy::for_paths(&cfg, &CommandRateLimit::default(), &profiles, &approval);
+        assert!(matches!(policy.check_command("konsole"), Err(ActionError::ProfileNotApproved)));
+    }
+
+    #[test]
+    fn test_approval_without_files() {
+        let dir = TempDir::new().unwrap();
+        let profiles = dir.path().join("profiles.json");
+        let approval = dir.path().join(APPROVAL_FILENAME);
+        assert!(!profiles_approved(&profiles, &approval));
+        assert!(approve_profiles(&profiles, &approval).is_err());
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(content_hash(b"ab"), content_hash(b"ba"));
+    }
+}
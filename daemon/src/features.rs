@@ -0,0 +1,76 @@
+//! Cargo features the daemon was built with
+//!
+//! Every feature except `metrics` is on by default. A build without one keeps the D-Bus
+//! interface and config keys as they are and turns the feature's work into a
+//! no-op, so a minimal daemon (`--no-default-features --features profiles`)
+//! starts normally and logs one line per missing feature instead of failing.
+//! `profiles` (profiles, actions and the menu) is always built.
+
+/// An optional part of the daemon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+    /// Cargo feature name
+    pub name: &'static str,
+    pub enabled: bool,
+    /// What a build without it does instead
+    pub without: &'static str,
+}
+
+/// The optional features, in `Cargo.toml` order
+pub const FEATURES: &[Feature] = &[
+    Feature {
+        name: "haptics",
+        enabled: cfg!(feature = "haptics"),
+        without: "no haptic pulses; HID++ still diverts buttons and reads DPI",
+    },
+    Feature {
+        name: "battery",
+        enabled: cfg!(feature = "battery"),
+        without: "battery status unavailable",
+    },
+    Feature {
+        name: "themes",
+        enabled: cfg!(feature = "themes"),
+        without: "bundled themes only, no theme file watching",
+    },
+    Feature {
+        name: "window-tracking",
+        enabled: cfg!(feature = "window-tracking"),
+        without: "active window not tracked; per-app hardware profiles inactive; X11 cursor and monitors via libX11 and xrandr",
+    },
+    Feature {
+        name: "metrics",
+        enabled: cfg!(feature = "metrics"),
+        without: "no Prometheus endpoint; telemetry.metrics_port is ignored",
+    },
+];
+
+/// Names of the features this build has
+pub fn enabled() -> Vec<&'static str> {
+    FEATURES.iter().filter(|f| f.enabled).map(|f| f.name).collect()
+}
+
+/// Log each feature this build lacks
+pub fn log_disabled() {
+    for feature in FEATURES.iter().filter(|f| !f.enabled) {
+        tracing::info!(
+            feature = feature.name,
+            "Feature {} disabled at build time: {}",
+            feature.name,
+            feature.without
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_follows_the_build() {
+        assert_eq!(enabled().contains(&"battery"), cfg!(feature = "battery"));
+        assert_eq!(enabled().contains(&"window-tracking"), cfg!(feature = "window-tracking"));
+        let names: Vec<&str> = FEATURES.iter().map(|f| f.name).collect();
+        assert_eq!(names, ["haptics", "battery", "themes", "window-tracking", "metrics"]);
+    }
+}
@@ -0,0 +1,281 @@
+//! Flick gestures (`menu.flick`)
+//!
+//! With flick mode on, a press of a menu trigger holds the menu back for
+//! `menu.flick_window_ms`. A release inside that window after the pointer
+//! travelled at least `menu.flick_distance_px` is a flick: the slice in the
+//! direction of travel runs at once and the overlay is never drawn. Any
+//! other press falls through to the normal menu. A release that comes too
+//! soon or moved too little opens it right then, so a tap still enters
+//! toggle mode, and holding past the window opens it when the window ends.
+//!
+//! The menu a press would open reaches the daemon as `ShowMenu` (or the KWin
+//! helper's `ShowMenuAtCursor`), which [`Flick::menu_requested`] defers while
+//! a press is pending. Pointer travel comes from the evdev loops' relative
+//! motion, including the MX loop while the HID++-diverted gesture button is
+//! held, so without readable evdev nodes nothing ever counts as a flick.
+//!
+//! `Flick` is a [`Deadline`] state machine; the input loops, the D-Bus
+//! service and the gesture loop feed it through the [`FlickHandle`] on
+//! `DaemonHandles`, and a daemon task runs its timer.
+
+use std::time::{Duration, Instant};
+
+use crate::deadline::{Deadline, DeadlineHandle};
+use crate::geometry::{angle_of, slice_for_angle_in, slot_for_position_in};
+
+/// Default pointer travel that makes a flick, in pixels
+pub const FLICK_DISTANCE_PX: u32 = 80;
+
+/// Default time a flick must be released within, in milliseconds
+pub const FLICK_WINDOW_MS: u64 = 200;
+
+/// Flick detection settings from `menu`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlickSettings {
+    /// Least pointer travel from the press point
+    pub distance: f64,
+    /// The release must come this soon after the press
+    pub window: Duration,
+}
+
+/// What a trigger release means to flick detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Release {
+    /// Nothing was held back; handle the release as usual
+    Normal,
+    /// Run the slice this way from the press point without a menu; the
+    /// menu would have opened at `menu_at`
+    Flick { dx: i32, dy: i32, menu_at: (i32, i32) },
+    /// Too short or too slow: open the held-back menu at this point, then
+    /// handle the release as usual
+    Open { x: i32, y: i32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// No press, flick mode off, or the press already fell through
+    Idle,
+    /// A press inside the flick window
+    Pending {
+        pressed_at: Instant,
+        /// Pointer travel since the press
+        offset: (i32, i32),
+        /// Where the menu would have opened; `None` until `ShowMenu` arrives
+        menu_at: Option<(i32, i32)>,
+    },
+}
+
+/// Flick detection for the held menu trigger
+#[derive(Debug)]
+pub struct Flick {
+    settings: Option<FlickSettings>,
+    state: State,
+}
+
+impl Flick {
+    pub fn new() -> Self {
+        Self {
+            settings: None,
+            state: State::Idle,
+        }
+    }
+
+    /// Flick mode settings; `None` turns it off and drops a pending press
+    pub fn set_settings(&mut self, settings: Option<FlickSettings>) {
+        self.settings = settings;
+        if settings.is_none() {
+            self.state = State::Idle;
+        }
+    }
+
+    /// A menu trigger went down
+    pub fn pressed(&mut self, now: Instant) {
+        self.state = match self.settings {
+            Some(_) => State::Pending { pressed_at: now, offset: (0, 0), menu_at: None },
+            None => State::Idle,
+        };
+    }
+
+    /// Relative pointer motion; counts only while a press is pending
+    pub fn pointer_delta(&mut self, dx: i32, dy: i32) {
+        if let State::Pending { ref mut offset, .. } = self.state {
+            offset.0 = offset.0.saturating_add(dx);
+            offset.1 = offset.1.saturating_add(dy);
+        }
+    }
+
+    /// The press asks for the menu at `(x, y)`; `true` when it is held back
+    ///
+    /// `defer` is `false` when the press does not open a fresh menu (a
+    /// toggle-mode menu is up, or it confirms a pending slice); that press
+    /// can no longer flick.
+    pub fn menu_requested(&mut self, x: i32, y: i32, defer: bool) -> bool {
+        match self.state {
+            State::Pending { ref mut menu_at, .. } if defer => {
+                *menu_at = Some((x, y));
+                true
+            }
+            _ => {
+                self.state = State::Idle;
+                false
+            }
+        }
+    }
+
+    /// Whether a press is inside its flick window
+    pub fn is_pending(&self) -> bool {
+        matches!(self.state, State::Pending { .. })
+    }
+
+    /// Timer woke: the held-back menu to open once the window has passed
+    ///
+    /// Returns `None` when there is nothing to open (yet); a `ShowMenu` that
+    /// arrives after the window opens at once.
+    pub fn fire(&mut self, now: Instant) -> Option<(i32, i32)> {
+        match self.state {
+            State::Pending { menu_at, .. } if self.wake_at().is_some_and(|due| now >= due) => {
+                self.state = State::Idle;
+                menu_at
+            }
+            _ => None,
+        }
+    }
+
+    /// The trigger came up at `at`
+    pub fn released(&mut self, at: Instant) -> Release {
+        let State::Pending { pressed_at, offset, menu_at } = std::mem::replace(&mut self.state, State::Idle) else {
+            return Release::Normal;
+        };
+        let Some((x, y)) = menu_at else {
+            // No menu would have opened (gaming mode, disarmed, or the cursor
+            // query has not answered yet)
+            return Release::Normal;
+        };
+        let Some(settings) = self.settings else {
+            return Release::Open { x, y };
+        };
+        let in_time = at.saturating_duration_since(pressed_at) <= settings.window;
+        let travel = f64::from(offset.0).hypot(f64::from(offset.1));
+        if in_time && travel >= settings.distance {
+            Release::Flick { dx: offset.0, dy: offset.1, menu_at: (x, y) }
+        } else {
+            Release::Open { x, y }
+        }
+    }
+}
+
+impl Default for Flick {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deadline for Flick {
+    /// When the timer should next wake
+    fn wake_at(&self) -> Option<Instant> {
+        match (self.state, self.settings) {
+            (State::Pending { pressed_at, .. }, Some(settings)) => Some(pressed_at + settings.window),
+            _ => None,
+        }
+    }
+}
+
+/// Profile slot a flick along `(dx, dy)` selects on a `count`-slice ring
+pub fn slot(dx: i32, dy: i32, count: u8, left_handed: bool) -> u8 {
+    let position = slice_for_angle_in(angle_of(f64::from(dx), f64::from(dy)), count);
+    slot_for_position_in(position, left_handed, count)
+}
+
+/// Shared handle between the input loops, the D-Bus service, the gesture
+/// loop and the timer task
+pub type FlickHandle = DeadlineHandle<Flick>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: FlickSettings = FlickSettings {
+        distance: 80.0,
+        window: Duration::from_millis(200),
+    };
+
+    fn pressed() -> (Flick, Instant) {
+        let mut flick = Flick::new();
+        flick.set_settings(Some(SETTINGS));
+        let t0 = Instant::now();
+        flick.pressed(t0);
+        assert!(flick.menu_requested(500, 400, true));
+        (flick, t0)
+    }
+
+    #[test]
+    fn test_fast_long_flick_runs_without_a_menu() {
+        let (mut flick, t0) = pressed();
+        // Mostly right, a little up
+        flick.pointer_delta(60, -10);
+        flick.pointer_delta(40, -5);
+        assert_eq!(flick.fire(t0 + Duration::from_millis(150)), None);
+        assert_eq!(flick.released(t0 + Duration::from_millis(180)), Release::Flick { dx: 100, dy: -15, menu_at: (500, 400) });
+        assert_eq!(slot(100, -15, 8, false), 2);
+        // Nothing left for the timer
+        assert_eq!(flick.wake_at(), None);
+        assert_eq!(flick.released(t0 + Duration::from_millis(190)), Release::Normal);
+    }
+
+    #[test]
+    fn test_slow_drag_falls_through_to_the_menu() {
+        let (mut flick, t0) = pressed();
+        flick.pointer_delta(30, 0);
+        assert_eq!(flick.wake_at(), Some(t0 + SETTINGS.window));
+        // The window ends with the button still down: the menu opens
+        assert_eq!(flick.fire(t0 + SETTINGS.window), Some((500, 400)));
+        flick.pointer_delta(200, 0);
+        assert!(!flick.menu_requested(500, 400, true));
+        assert_eq!(flick.released(t0 + Duration::from_millis(600)), Release::Normal);
+
+        // Far enough but released late (timer not run yet): open, no flick
+        let (mut flick, t0) = pressed();
+        flick.pointer_delta(0, 120);
+        assert_eq!(flick.released(t0 + Duration::from_millis(250)), Release::Open { x: 500, y: 400 });
+    }
+
+    #[test]
+    fn test_tap_and_small_moves_open_the_menu() {
+        let (mut flick, t0) = pressed();
+        flick.pointer_delta(20, 20);
+        assert_eq!(flick.released(t0 + Duration::from_millis(90)), Release::Open { x: 500, y: 400 });
+    }
+
+    #[test]
+    fn test_hold_without_menu_and_off_switch() {
+        // ShowMenu never came (gaming mode): no flick and nothing to open
+        let mut flick = Flick::new();
+        flick.set_settings(Some(SETTINGS));
+        let t0 = Instant::now();
+        flick.pressed(t0);
+        flick.pointer_delta(0, -200);
+        assert_eq!(flick.fire(t0 + SETTINGS.window), None);
+        assert_eq!(flick.released(t0 + Duration::from_millis(100)), Release::Normal);
+
+        // A press that confirms a slice or closes a toggle menu never flicks
+        let (mut flick, t0) = pressed();
+        assert!(!flick.menu_requested(500, 400, false));
+        flick.pointer_delta(0, -200);
+        assert_eq!(flick.released(t0 + Duration::from_millis(100)), Release::Normal);
+
+        let mut flick = Flick::new();
+        flick.pressed(t0);
+        assert!(!flick.menu_requested(1, 2, true));
+        assert_eq!(flick.wake_at(), None);
+    }
+
+    #[test]
+    fn test_direction_maps_to_the_ring_slot() {
+        assert_eq!(slot(0, -100, 8, false), 0);
+        assert_eq!(slot(0, 100, 8, false), 4);
+        assert_eq!(slot(-100, 0, 8, false), 6);
+        assert_eq!(slot(-100, 0, 8, true), 2);
+        assert_eq!(slot(100, 20, 4, false), 1);
+        assert_eq!(slot(-100, 0, 4, false), 3);
+    }
+}
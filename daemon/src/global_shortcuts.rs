@@ -0,0 +1,272 @@
+//! Keyboard shortcut menu triggers (KDE KGlobalAccel)
+//!
+//! Each `{"source": "shortcut", "keys": "Meta+Space"}` entry in `triggers` is
+//! registered as an action of the `juhradialmx` KGlobalAccel component. The
+//! component's `globalShortcutPressed`/`globalShortcutReleased` signals are
+//! turned into the same `GestureEvent::Pressed`/`Released` pair a mouse
+//! trigger produces, gated by the shared `TriggerArbiter`, so holding the
+//! shortcut opens the menu and letting go confirms the slice under the cursor.
+//!
+//! Only available where kglobalaccel runs (Plasma); elsewhere the listener
+//! logs once and exits. The configured keys are applied with `NoAutoloading`,
+//! so config.json wins over an edit in System Settings.
+
+use std::time::Instant;
+
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+
+use crate::evdev::{EvdevHandler, GestureEvent};
+use crate::menu_trigger::{SharedTriggerArbiter, TriggerId};
+
+const KGLOBALACCEL_BUS_NAME: &str = "org.kde.kglobalaccel";
+const KGLOBALACCEL_PATH: &str = "/kglobalaccel";
+const KGLOBALACCEL_INTERFACE: &str = "org.kde.KGlobalAccel";
+const COMPONENT_INTERFACE: &str = "org.kde.kglobalaccel.Component";
+
+/// KGlobalAccel component name for our actions
+pub const COMPONENT_NAME: &str = "juhradialmx";
+const COMPONENT_FRIENDLY_NAME: &str = "JuhRadial MX";
+
+/// `KGlobalAccel::SetShortcutFlag`: SetPresent | NoAutoloading
+const SET_SHORTCUT_FLAGS: u32 = 0x2 | 0x4;
+
+const QT_SHIFT: i32 = 0x0200_0000;
+const QT_CTRL: i32 = 0x0400_0000;
+const QT_ALT: i32 = 0x0800_0000;
+const QT_META: i32 = 0x1000_0000;
+
+/// Parse a key sequence like `Meta+Space` or `Ctrl+Alt+F5` into a Qt key code
+///
+/// Accepts any number of modifiers (Meta/Super, Ctrl/Control, Alt, Shift,
+/// case-insensitive) and exactly one key: a letter, a digit, F1-F35 or a named
+/// key (Space, Tab, Return, Escape, arrows, ...). `None` when unparseable.
+pub fn parse_keys(keys: &str) -> Option<i32> {
+    let mut modifiers = 0;
+    let mut key = None;
+    for part in keys.split('+').map(str::trim) {
+        let lower = part.to_ascii_lowercase();
+        let modifier = match lower.as_str() {
+            "meta" | "super" => QT_META,
+            "ctrl" | "control" => QT_CTRL,
+            "alt" => QT_ALT,
+            "shift" => QT_SHIFT,
+            _ => 0,
+        };
+        if modifier != 0 {
+            modifiers |= modifier;
+        } else if key.replace(qt_key(&lower)?).is_some() {
+            return None;
+        }
+    }
+    key.map(|k| modifiers | k)
+}
+
+/// Qt::Key for one lowercase key name
+fn qt_key(name: &str) -> Option<i32> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphanumeric() {
+            return Some(c.to_ascii_uppercase() as i32);
+        }
+    }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<i32>().ok()) {
+        return (1..=35).contains(&n).then_some(0x0100_0030 + n - 1);
+    }
+    Some(match name {
+        "space" => 0x20,
+        "escape" | "esc" => 0x0100_0000,
+        "tab" => 0x0100_0001,
+        "backspace" => 0x0100_0003,
+        "return" | "enter" => 0x0100_0004,
+        "insert" | "ins" => 0x0100_0006,
+        "delete" | "del" => 0x0100_0007,
+        "pause" => 0x0100_0008,
+        "print" => 0x0100_0009,
+        "home" => 0x0100_0010,
+        "end" => 0x0100_0011,
+        "left" => 0x0100_0012,
+        "up" => 0x0100_0013,
+        "right" => 0x0100_0014,
+        "down" => 0x0100_0015,
+        "pgup" | "pageup" => 0x0100_0016,
+        "pgdown" | "pagedown" => 0x0100_0017,
+        _ => return None,
+    })
+}
+
+/// KGlobalAccel action name for the shortcut at `index`
+fn action_name(index: u8) -> String {
+    format!("show-menu-{}", index)
+}
+
+/// `[component, action, component friendly, action friendly]`
+fn action_id(index: u8, keys: &str) -> Vec<String> {
+    vec![
+        COMPONENT_NAME.to_string(),
+        action_name(index),
+        COMPONENT_FRIENDLY_NAME.to_string(),
+        format!("Show radial menu ({})", keys),
+    ]
+}
+
+/// Register `shortcuts` with KGlobalAccel and feed their presses into the
+/// gesture channel until the connection closes
+pub async fn run_shortcut_listener(
+    connection: zbus::Connection,
+    shortcuts: Vec<String>,
+    event_tx: mpsc::Sender<GestureEvent>,
+    arbiter: SharedTriggerArbiter,
+    kwin: crate::compositor::KWinAvailability,
+) {
+    let accel = match zbus::Proxy::new(
+        &connection,
+        KGLOBALACCEL_BUS_NAME,
+        KGLOBALACCEL_PATH,
+        KGLOBALACCEL_INTERFACE,
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::warn!(error = %e, "Shortcut triggers: KGlobalAccel unavailable");
+            return;
+        }
+    };
+
+    let mut registered = 0;
+    for (index, keys) in shortcuts.iter().enumerate().take(u8::MAX as usize) {
+        let index = index as u8;
+        let Some(code) = parse_keys(keys) else {
+            tracing::warn!(keys = %keys, "Shortcut trigger: cannot parse key sequence; skipped");
+            continue;
+        };
+        let id = action_id(index, keys);
+        let result = async {
+            accel.call::<_, _, ()>("doRegister", &(&id,)).await?;
+            accel
+                .call::<_, _, Vec<i32>>("setShortcut", &(&id, vec![code], SET_SHORTCUT_FLAGS))
+                .await
+        }
+        .await;
+        match result {
+            Ok(active) if active.contains(&code) => {
+                registered += 1;
+                tracing::info!(keys = %keys, "Registered shortcut trigger");
+            }
+            Ok(_) => tracing::warn!(keys = %keys, "Shortcut trigger already taken by another action"),
+            Err(e) => tracing::warn!(keys = %keys, error = %e, "Failed to register shortcut trigger"),
+        }
+    }
+    if registered == 0 {
+        return;
+    }
+
+    let component_path: zbus::zvariant::OwnedObjectPath =
+        match accel.call("getComponent", &(COMPONENT_NAME,)).await {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::warn!(error = %e, "Shortcut triggers: KGlobalAccel component not found");
+                return;
+            }
+        };
+    let component = match zbus::Proxy::new(
+        &connection,
+        KGLOBALACCEL_BUS_NAME,
+        component_path,
+        COMPONENT_INTERFACE,
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::warn!(error = %e, "Shortcut triggers: could not open component");
+            return;
+        }
+    };
+    let mut signals = match component.receive_all_signals().await {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!(error = %e, "Shortcut triggers: could not watch component signals");
+            return;
+        }
+    };
+
+    let mut press_times: Vec<Option<Instant>> = vec![None; shortcuts.len()];
+    while let Some(msg) = signals.next().await {
+        let header = msg.header();
+        let pressed = match header.member().map(|m| m.as_str()) {
+            Some("globalShortcutPressed") => true,
+            Some("globalShortcutReleased") => false,
+            _ => continue,
+        };
+        let Ok((_component, action, _timestamp)) = msg.body().deserialize::<(String, String, i64)>() else {
+            continue;
+        };
+        let Some(index) = (0..press_times.len() as u8).find(|&i| action_name(i) == action) else {
+            continue;
+        };
+        let trigger = TriggerId::Shortcut(index);
+        let held = &mut press_times[index as usize];
+
+        if pressed {
+            // Key repeat re-sends the press; only the first one opens the menu
+            if held.is_some() {
+                continue;
+            }
+            if !crate::menu_trigger::claim(&arbiter, trigger) {
+                tracing::debug!(%trigger, "Menu held by another trigger; ignoring shortcut");
+                continue;
+            }
+            *held = Some(Instant::now());
+            open_menu(&event_tx, &kwin).await;
+        } else if let Some(since) = held.take() {
+            if !crate::menu_trigger::release(&arbiter, trigger) {
+                continue;
+            }
+            let duration_ms = since.elapsed().as_millis() as u64;
+            tracing::info!(duration_ms, %trigger, "Shortcut trigger released");
+            let _ = event_tx
+                .send(GestureEvent::Released { duration_ms, released_at: Instant::now() })
+                .await;
+        }
+    }
+}
+
+/// Open the menu at the cursor the same way a mouse trigger does
+async fn open_menu(event_tx: &mpsc::Sender<GestureEvent>, kwin: &crate::compositor::KWinAvailability) {
+    let kwin_owned = kwin.is_owned();
+    if crate::compositor::cursor_backend(kwin_owned, kwin.helper().cursor().is_some()) == crate::compositor::CursorBackend::KWin {
+        tracing::info!("Shortcut trigger pressed - triggering KWin cursor query");
+        // On success the script calls ShowMenuAtCursor itself
+        if EvdevHandler::trigger_kwin_cursor_script(kwin.helper()) {
+            return;
+        }
+    }
+    let pos = crate::cursor::get_cursor_position(kwin.helper());
+    tracing::info!(x = pos.x, y = pos.y, kwin_owned, "Shortcut trigger pressed - cursor query");
+    let _ = event_tx.send(GestureEvent::Pressed { x: pos.x, y: pos.y }).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(parse_keys("Meta+Space"), Some(QT_META | 0x20));
+        assert_eq!(parse_keys("ctrl + alt + r"), Some(QT_CTRL | QT_ALT | 0x52));
+        assert_eq!(parse_keys("Super+F5"), Some(QT_META | 0x0100_0034));
+        assert_eq!(parse_keys("Shift+1"), Some(QT_SHIFT | 0x31));
+        assert_eq!(parse_keys("F12"), Some(0x0100_003B));
+    }
+
+    #[test]
+    fn test_parse_keys_rejects_invalid() {
+        assert_eq!(parse_keys("Meta"), None);
+        assert_eq!(parse_keys("Meta+A+B"), None);
+        assert_eq!(parse_keys("Meta+Hyper"), None);
+        assert_eq!(parse_keys("F36"), None);
+        assert_eq!(parse_keys(""), None);
+    }
+}
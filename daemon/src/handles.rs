@@ -0,0 +1,144 @@
+//! Daemon state shared by the D-Bus service and the daemon's tasks
+//!
+//! `main` builds one [`DaemonHandles`] and clones it into the service and
+//! into every task that needs it. Each field is a handle around an `Arc`, so
+//! a clone shares the same state. State machines without a timer are kept
+//! in a [`StateHandle`]; those with one in a `deadline::DeadlineHandle`.
+
+use std::sync::{Arc, Mutex};
+
+use crate::action_latency::LatencyHandle;
+use crate::calibration::CalibrationHandle;
+use crate::compositor::{BlurAvailability, KWinAvailability};
+use crate::conflicts::ConflictsHandle;
+use crate::device_watcher::DeviceWatcher;
+use crate::dwell_confirm::DwellConfirmHandle;
+use crate::execution_policy::CommandRateLimit;
+use crate::flick::FlickHandle;
+use crate::hidpp::trace::HidppTrace;
+use crate::idle_effect::IdleEffectHandle;
+use crate::instance::QuitHandle;
+use crate::invocation::InvocationHandle;
+use crate::late_focus::LateFocusHandle;
+use crate::logging::RecentWarnings;
+use crate::media_control::MediaQueue;
+use crate::menu_anchor::MenuAnchorHandle;
+use crate::menu_log::MenuLogHandle;
+use crate::metrics::MetricsHandle;
+use crate::monitors::MonitorsHandle;
+use crate::panic_switch::PanicSwitch;
+use crate::persistence::PersistQueue;
+use crate::prewarm::PrewarmHandle;
+use crate::quiet_hours::QuietHoursHandle;
+use crate::sandbox::ResolvedBinaries;
+use crate::slice_confirmation::SliceConfirmationHandle;
+use crate::slice_cooldown::SliceCooldownHandle;
+use crate::system_accent::SystemAccentHandle;
+use crate::theme_transition::ThemeTransitionHandle;
+#[cfg(feature = "themes")]
+use crate::theme_watcher::ThemeWatcherStatsHandle;
+use crate::wheel_axis::WheelAxisHandle;
+use crate::window_tracker::settle::FocusSettleHandle;
+
+/// Handles to the shared daemon state
+#[derive(Debug, Clone, Default)]
+pub struct DaemonHandles {
+    /// Command starts counted against `execution.max_commands_per_minute`
+    pub command_rate: CommandRateLimit,
+    /// Action, menu-open and haptic latency histograms (`Status()`, metrics)
+    pub latency: LatencyHandle,
+    /// Highlight dwell and the action prepared for the release
+    pub prewarm: PrewarmHandle,
+    /// Where programs are spawned, and the PATH lookups reused by every execution
+    pub binaries: ResolvedBinaries,
+    /// The last menu invocation, handed to actions as `JUH_*` variables
+    pub invocation: InvocationHandle,
+    /// Where the open menu was drawn relative to the press
+    pub anchor: MenuAnchorHandle,
+    /// Whether KWin's blur effect is loaded, kept current by the KWin watcher
+    pub blur: BlurAvailability,
+    /// Whether KWin owns its bus name, and its resident helper script
+    pub kwin: KWinAvailability,
+    /// Armed/disarmed input interception, shared by every input source
+    pub panic_switch: PanicSwitch,
+    /// The held `wheel_axis` slice of the open menu
+    pub wheel_axis: WheelAxisHandle,
+    /// The HID++ trace switch (`debug.hidpp_trace`, `SIGUSR2`)
+    pub hidpp_trace: HidppTrace,
+    /// The theme crossfade in flight, so quick switches coalesce
+    pub theme_transition: ThemeTransitionHandle,
+    /// The `require_confirmation` slice waiting for its second tap
+    pub slice_confirmation: SliceConfirmationHandle,
+    /// Queue to the `media`/`volume` worker
+    pub media: MediaQueue,
+    /// Focus reports racing the menu open (`menu.late_focus_ms`)
+    pub late_focus: LateFocusHandle,
+    /// Hover-to-select countdown (`accessibility.dwell_confirm_ms`)
+    pub dwell_confirm: DwellConfirmHandle,
+    /// HID++ connection counters and the metrics endpoint address
+    pub metrics: MetricsHandle,
+    /// Monitor layout for `menu.size`, and the open menu's monitor
+    pub monitors: MonitorsHandle,
+    /// Scheduled and `QuietNow` quiet, checked before event pulses
+    pub quiet_hours: QuietHoursHandle,
+    /// Quick press-and-move on the gesture button (`menu.flick`)
+    pub flick: FlickHandle,
+    /// Programs last found competing for the device (logid, Solaar, ratbagd)
+    pub conflicts: ConflictsHandle,
+    /// Write-behind queue for state, stats and config writes
+    pub persistence: Arc<PersistQueue>,
+    /// `Quit` over D-Bus, waited on by the main loop
+    pub quit: QuitHandle,
+    /// First-run calibration session (`StartCalibration`)
+    pub calibration: CalibrationHandle,
+    /// Desktop accent color and `colors.accent_follow_system`
+    pub system_accent: SystemAccentHandle,
+    /// `idle_effects` caps and the power source
+    pub idle_effect: IdleEffectHandle,
+    /// udev hotplug, debounced into device inventory changes
+    pub device_watcher: DeviceWatcher,
+    /// The last warnings and errors logged, for `DumpState()`
+    pub recent_warnings: RecentWarnings,
+    /// Settle timer for focus changes (`menu.focus_settle_ms`)
+    pub focus_settle: FocusSettleHandle,
+    /// Slices still in their `cooldown_ms`
+    pub slice_cooldown: SliceCooldownHandle,
+    /// Counters of the theme file watcher (`Diagnostics()`)
+    #[cfg(feature = "themes")]
+    pub theme_watcher: ThemeWatcherStatsHandle,
+    /// Recent menu sessions (`GetLastSessions`)
+    pub menu_log: MenuLogHandle,
+}
+
+/// A state machine shared between the service and the input loops
+#[derive(Debug)]
+pub struct StateHandle<S> {
+    state: Arc<Mutex<S>>,
+}
+
+impl<S> Clone for StateHandle<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl<S: Default> Default for StateHandle<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S> StateHandle<S> {
+    pub fn new(state: S) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Run `f` on the state; `None` if the lock is poisoned
+    pub fn update<R>(&self, f: impl FnOnce(&mut S) -> R) -> Option<R> {
+        self.state.lock().ok().map(|mut s| f(&mut s))
+    }
+}
@@ -6,17 +6,30 @@
 
 use std::collections::HashSet;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use std::io::Write;
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use super::constants::{blocklisted_features, features, report_type};
+use super::constants::{blocklisted_features, features, read_only_functions, report_type};
+use crate::battery::{parse_battery_response, BatteryReading};
 use super::error::HapticError;
+use super::feature_cache::{self, DeviceIdentity, FeatureSource};
 use super::messages::ConnectionType;
 use super::patterns::Mx4HapticPattern;
+use super::request::{self, ErrorCode, Request, RequestError, SwIdCounter, READ_RETRIES, REQUEST_TIMEOUT};
+use super::trace::HidppTrace;
+use crate::persistence::PersistQueue;
 
-/// Software ID for HID++ message tracking
-const SOFTWARE_ID: u8 = 0x01;
+/// Whether this build plays haptics (`haptics` feature)
+const HAPTICS_BUILT: bool = cfg!(feature = "haptics");
+
+/// Software ID of the fire-and-forget receiver wake ping
+const WAKE_SW_ID: u8 = 0x01;
+
+/// Discovery ping timeout: responsive devices reply within ~20ms
+const PING_TIMEOUT: Duration = Duration::from_millis(200);
 
 /// HID++ device wrapper for communication with MX Master 4
 ///
@@ -29,6 +42,10 @@ pub struct HidppDevice {
     device_index: u8,
     /// Connection type
     connection_type: ConnectionType,
+    /// Records the reports while the HID++ trace is on
+    trace: HidppTrace,
+    /// Takes the feature cache writes
+    persistence: Arc<PersistQueue>,
     /// Cached feature table (feature_id -> feature_index)
     feature_table: std::collections::HashMap<u16, u8>,
     /// Whether haptic feature is available (legacy force feedback 0x8123)
@@ -63,6 +80,14 @@ pub struct HidppDevice {
     thumbwheel_feature_index: Option<u8>,
     /// Path to the hidraw device we connected to
     device_path: PathBuf,
+    /// Rotating software ID for request/reply correlation
+    sw_ids: SwIdCounter,
+    /// Last reply from the device, or haptic command sent to it
+    last_contact: Option<Instant>,
+    /// Idle time after which a haptic command is preceded by a wake ping
+    wake_ping_idle: Option<Duration>,
+    /// Whether the feature table came from the cache
+    feature_source: FeatureSource,
 }
 
 trait ButtonDivertIo {
@@ -234,6 +259,38 @@ impl HidppDevice {
         candidates.into_iter().map(|(path, _, conn_type)| (path, conn_type)).collect()
     }
 
+    /// A device at `device_index` on `device`, with no features known yet
+    fn new(device: File, device_path: PathBuf, device_index: u8, connection_type: ConnectionType, trace: HidppTrace, persistence: Arc<PersistQueue>) -> Self {
+        Self {
+            device,
+            device_index,
+            connection_type,
+            trace,
+            persistence,
+            feature_table: std::collections::HashMap::new(),
+            haptic_supported: false,
+            haptic_feature_index: None,
+            mx4_haptic_supported: false,
+            mx4_haptic_feature_index: None,
+            dpi_supported: false,
+            dpi_feature_index: None,
+            smartshift_supported: false,
+            smartshift_feature_index: None,
+            battery_supported: false,
+            battery_feature_index: None,
+            is_unified_battery: false,
+            reprog_controls_supported: false,
+            reprog_controls_feature_index: None,
+            thumbwheel_supported: false,
+            thumbwheel_feature_index: None,
+            device_path,
+            sw_ids: SwIdCounter::new(),
+            last_contact: None,
+            wake_ping_idle: None,
+            feature_source: FeatureSource::Enumerated,
+        }
+    }
+
     /// Attempt to open and initialize an MX Master 4 device
     ///
     /// Returns None if no compatible device is found.
@@ -245,7 +302,7 @@ impl HidppDevice {
     /// Tries ALL candidate devices until one validates HID++ 2.0.
     /// This handles setups with multiple Logitech receivers (e.g., MX Master 4
     /// on one Bolt receiver, Keys S on another).
-    pub fn open() -> Option<Self> {
+    pub fn open(trace: &HidppTrace, persistence: &Arc<PersistQueue>) -> Option<Self> {
         let candidates = Self::find_all_devices();
 
         if candidates.is_empty() {
@@ -310,28 +367,7 @@ impl HidppDevice {
                     Err(_) => continue,
                 };
 
-                let mut hidpp = Self {
-                    device: device_clone,
-                    device_index: *device_index,
-                    connection_type,
-                    feature_table: std::collections::HashMap::new(),
-                    haptic_supported: false,
-                    haptic_feature_index: None,
-                    mx4_haptic_supported: false,
-                    mx4_haptic_feature_index: None,
-                    dpi_supported: false,
-                    dpi_feature_index: None,
-                    smartshift_supported: false,
-                    smartshift_feature_index: None,
-                    battery_supported: false,
-                    battery_feature_index: None,
-                    is_unified_battery: false,
-                    reprog_controls_supported: false,
-                    reprog_controls_feature_index: None,
-                    thumbwheel_supported: false,
-                    thumbwheel_feature_index: None,
-                    device_path: device_path.clone(),
-                };
+                let mut hidpp = Self::new(device_clone, device_path.clone(), *device_index, connection_type, trace.clone(), persistence.clone());
 
                 // Try HID++ validation — uses fast 200ms timeout per slot.
                 // Responsive devices reply within ~20ms; empty slots never reply.
@@ -349,8 +385,8 @@ impl HidppDevice {
                     continue; // Try next device index
                 }
 
-                // Enumerate features and check for haptic support
-                hidpp.enumerate_features();
+                // Load the feature table and check for haptic support
+                hidpp.load_features();
 
                 // Skip devices that aren't a mouse
                 // Use DPI support (0x2201) as the filter - only mice have DPI,
@@ -395,9 +431,11 @@ impl HidppDevice {
                     wake[0] = report_type::SHORT;
                     wake[1] = 0xFF;
                     wake[2] = 0x00; // IRoot
-                    wake[3] = (0x01 << 4) | SOFTWARE_ID; // ping
+                    wake[3] = (0x01 << 4) | WAKE_SW_ID; // ping
                     wake[6] = 0xAA;
-                    let _ = wake_fd.write_all(&wake);
+                    if wake_fd.write_all(&wake).is_ok() {
+                        trace.outgoing(&wake);
+                    }
                 }
                 std::thread::sleep(std::time::Duration::from_millis(250));
                 continue 'pass_loop;
@@ -413,189 +451,39 @@ impl HidppDevice {
         None
     }
 
-    /// Drain any pending data from the device buffer
+    /// Send a HID++ request and wait for the reply correlated to it
     ///
-    /// This prevents reading stale responses from previous requests.
-    fn drain_buffer(&mut self) {
-        let mut drain_buf = [0u8; 64];
-        loop {
-            match self.device.read(&mut drain_buf) {
-                Ok(_) => continue, // Discard stale data
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
-                Err(_) => break,
-            }
-        }
-    }
-
-    /// Send a HID++ request and wait for matching response
-    ///
-    /// Uses polling with timeout (same approach as battery module).
+    /// Not retried: use `hidpp_read` for idempotent getters.
     fn hidpp_request(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Option<Vec<u8>> {
-        self.hidpp_request_with_timeout(feature_index, function, params, 100)
+        self.hidpp_request_with_timeout(feature_index, function, params, REQUEST_TIMEOUT)
     }
 
-    /// Send a HID++ request with a custom max attempt count.
-    ///
-    /// Each attempt polls at 10ms intervals. Use a lower max_attempts for
-    /// fast-fail scenarios (e.g., device discovery pings) to avoid
-    /// hammering receivers with long blocking waits on empty slots.
-    fn hidpp_request_with_timeout(&mut self, feature_index: u8, function: u8, params: &[u8], max_attempts: u32) -> Option<Vec<u8>> {
-        // Bluetooth-connected devices do not expose the short (0x10) HID++
-        // report — their HID descriptor only contains the long (0x11) report.
-        // A short write there is dropped and never answered, so route every
-        // request through the long path. Makes HID++ validation, feature
-        // enumeration and haptics work over Bluetooth.
-        if self.connection_type == ConnectionType::Bluetooth {
-            return self.hidpp_long_request(feature_index, function, params);
-        }
-
-        // Drain any pending data first
-        self.drain_buffer();
-
-        // Build HID++ short report (7 bytes)
-        let mut request = [0u8; 7];
-        request[0] = report_type::SHORT;
-        request[1] = self.device_index;
-        request[2] = feature_index;
-        request[3] = (function << 4) | SOFTWARE_ID;
-
-        // Copy params (up to 3 bytes for short report)
-        let param_len = params.len().min(3);
-        request[4..4 + param_len].copy_from_slice(&params[..param_len]);
-
-        tracing::debug!(
-            feature_index,
-            function,
-            "Sending HID++ request: {:02X?}",
-            &request
-        );
-
-        // Send request
-        if let Err(e) = self.device.write_all(&request) {
-            tracing::debug!(error = %e, "Failed to write HID++ message");
-            return None;
-        }
-
-        // Read response with timeout (non-blocking, so we poll)
-        let mut response = [0u8; 20];
-        let mut attempts = 0u32;
-
-        loop {
-            match self.device.read(&mut response) {
-                Ok(len) if len >= 7 => {
-                    let resp_function = (response[3] >> 4) & 0x0F;
-                    let resp_sw_id = response[3] & 0x0F;
-
-                    tracing::debug!(
-                        "HID++ response: {:02X?} (feat={}, fn={}, sw={})",
-                        &response[..len],
-                        response[2],
-                        resp_function,
-                        resp_sw_id
-                    );
-
-                    // Check if this is a response to our request
-                    if response[0] == report_type::SHORT || response[0] == report_type::LONG {
-                        // Must match: device index, feature index, function, AND software ID
-                        if response[1] == self.device_index
-                            && response[2] == feature_index
-                            && resp_function == function
-                            && resp_sw_id == SOFTWARE_ID
-                        {
-                            tracing::debug!("HID++ request matched! Returning response");
-                            return Some(response[..len].to_vec());
-                        }
-                        // Check for error response (0xFF feature_index indicates error)
-                        // Format: [report_type, device_idx, 0xFF, orig_feature_idx, orig_fn_sw, error_code, ...]
-                        if response[2] == 0xFF {
-                            let error_code = response[5];
-                            let error_msg = match error_code {
-                                0x00 => "No error",
-                                0x01 => "Unknown function",
-                                0x02 => "Function not available",
-                                0x03 => "Invalid argument",
-                                0x04 => "Not supported",
-                                0x05 => "Invalid argument/Out of range",
-                                0x06 => "Device busy",
-                                0x07 => "Connection failed",
-                                0x08 => "Invalid address",
-                                _ => "Unknown error",
-                            };
-                            tracing::warn!(
-                                error_code,
-                                error_msg,
-                                feature_index = response[3],
-                                "HID++ error response: {:02X?}",
-                                &response[..len]
-                            );
-                            return None;
-                        }
-                        // Legacy error check (0x8F)
-                        if response[2] == 0x8F {
-                            tracing::debug!("HID++ legacy error response: {:02X?}", &response[..len]);
-                            return None;
-                        }
-                        // Log non-matching responses for debugging
-                        tracing::debug!(
-                            expected_dev = self.device_index,
-                            expected_feat = feature_index,
-                            expected_fn = function,
-                            expected_sw = SOFTWARE_ID,
-                            got_dev = response[1],
-                            got_feat = response[2],
-                            got_fn = resp_function,
-                            got_sw = resp_sw_id,
-                            "HID++ response didn't match expected values"
-                        );
-                    }
-                }
-                Ok(_) => {
-                    // Short read, continue
-                }
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                    // No data yet
-                }
-                Err(e) => {
-                    tracing::debug!(error = %e, "Error reading HID++ response");
-                    return None;
-                }
-            }
-
-            attempts += 1;
-            if attempts > max_attempts {
-                tracing::debug!(feature_index, function, max_attempts, "HID++ request timeout");
-                return None;
-            }
-
-            std::thread::sleep(std::time::Duration::from_millis(10));
-        }
+    fn hidpp_request_with_timeout(&mut self, feature_index: u8, function: u8, params: &[u8], timeout: Duration) -> Option<Vec<u8>> {
+        let sw_id = self.sw_ids.next_id();
+        let request = self.request(false, feature_index, function, params);
+        let result = request::exchange(&mut self.device, &self.trace, &request, sw_id, timeout);
+        self.settle(&request, result)
     }
 
-    /// Send a long HID++ message (20 bytes) - fire and forget
-    #[allow(dead_code)]
-    fn hidpp_send_long(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Result<(), std::io::Error> {
-        // Drain any pending data first
-        self.drain_buffer();
-
-        // Build HID++ long report (20 bytes)
-        let mut request = [0u8; 20];
-        request[0] = report_type::LONG;
-        request[1] = self.device_index;
-        request[2] = feature_index;
-        request[3] = (function << 4) | SOFTWARE_ID;
-
-        // Copy params (up to 16 bytes for long report)
-        let param_len = params.len().min(16);
-        request[4..4 + param_len].copy_from_slice(&params[..param_len]);
-
-        tracing::trace!(
-            feature_index,
-            function,
-            "Sending HID++ long message: {:02X?}",
-            &request
-        );
+    /// `hidpp_request` for idempotent reads: timeouts are retried with a
+    /// fresh software ID. Never use it for haptics or setters.
+    fn hidpp_read(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Option<Vec<u8>> {
+        let request = self.request(false, feature_index, function, params);
+        let result = request::exchange_read(&mut self.device, &self.trace, &request, &mut self.sw_ids, REQUEST_TIMEOUT);
+        self.settle(&request, result)
+    }
 
-        self.device.write_all(&request)
+    /// Send a HID++ request without waiting for, or reading, a reply
+    ///
+    /// For haptics: a reply that does come is skipped as unrelated (or
+    /// drained) by the next request that waits for one.
+    fn hidpp_send(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Result<(), std::io::Error> {
+        let sw_id = self.sw_ids.next_id();
+        let request = self.request(false, feature_index, function, params).encode(sw_id);
+        tracing::trace!(feature_index, function, "Sending HID++ request, no reply awaited: {:02X?}", &request);
+        self.device.write_all(&request)?;
+        self.trace.outgoing(&request);
+        Ok(())
     }
 
     /// Send a long HID++ request (20 bytes) and wait for response
@@ -603,88 +491,101 @@ impl HidppDevice {
     /// Used for commands that need more than 3 parameter bytes
     /// (e.g. setCidReporting which needs 5 bytes).
     fn hidpp_long_request(&mut self, feature_index: u8, function: u8, params: &[u8]) -> Option<Vec<u8>> {
-        // Drain any pending data first
-        self.drain_buffer();
-
-        // Build HID++ long report (20 bytes)
-        let mut request = [0u8; 20];
-        request[0] = report_type::LONG;
-        request[1] = self.device_index;
-        request[2] = feature_index;
-        request[3] = (function << 4) | SOFTWARE_ID;
-
-        // Copy params (up to 16 bytes for long report)
-        let param_len = params.len().min(16);
-        request[4..4 + param_len].copy_from_slice(&params[..param_len]);
+        let sw_id = self.sw_ids.next_id();
+        let request = self.request(true, feature_index, function, params);
+        let result = request::exchange(&mut self.device, &self.trace, &request, sw_id, REQUEST_TIMEOUT);
+        self.settle(&request, result)
+    }
 
-        tracing::debug!(
+    /// Build a request for this device
+    ///
+    /// Bluetooth-connected devices do not expose the short (0x10) HID++
+    /// report — their HID descriptor only contains the long (0x11) report.
+    /// A short write there is dropped and never answered, so every request
+    /// goes out long. Makes HID++ validation, feature enumeration and
+    /// haptics work over Bluetooth.
+    fn request<'a>(&self, long: bool, feature_index: u8, function: u8, params: &'a [u8]) -> Request<'a> {
+        Request {
+            long: long || self.connection_type == ConnectionType::Bluetooth,
+            device_index: self.device_index,
             feature_index,
             function,
-            "Sending HID++ long request: {:02X?}",
-            &request
-        );
+            params,
+        }
+    }
 
-        // Send request
-        if let Err(e) = self.device.write_all(&request) {
-            tracing::debug!(error = %e, "Failed to write HID++ long message");
-            return None;
+    /// Log the outcome of `request` and pass the reply on
+    ///
+    /// A feature the device answers Unsupported is dropped for the rest of
+    /// the connection, so it is not asked again.
+    fn settle(&mut self, request: &Request, result: Result<Vec<u8>, RequestError>) -> Option<Vec<u8>> {
+        if result.as_ref().err().and_then(RequestError::code) == Some(ErrorCode::Unsupported) {
+            self.drop_feature(request.feature_index);
         }
+        self.replied(Self::log_result(request, result))
+    }
 
-        // Read response with timeout (same as hidpp_request)
-        let mut response = [0u8; 20];
-        let mut attempts = 0;
-
-        loop {
-            match self.device.read(&mut response) {
-                Ok(len) if len >= 7 => {
-                    let resp_function = (response[3] >> 4) & 0x0F;
-                    let resp_sw_id = response[3] & 0x0F;
-
-                    // Check for matching response
-                    if (response[0] == report_type::SHORT || response[0] == report_type::LONG)
-                        && response[1] == self.device_index
-                        && response[2] == feature_index
-                        && resp_function == function
-                        && resp_sw_id == SOFTWARE_ID
-                    {
-                        tracing::debug!("HID++ long request matched: {:02X?}", &response[..len]);
-                        return Some(response[..len].to_vec());
-                    }
+    /// Note that the device answered, then pass `reply` on
+    fn replied(&mut self, reply: Option<Vec<u8>>) -> Option<Vec<u8>> {
+        if reply.is_some() {
+            self.note_contact();
+        }
+        reply
+    }
 
-                    // Check for error response. Gate on the report type and
-                    // device index first: on Bluetooth the same hidraw fd also
-                    // carries 0x02 mouse-motion reports where byte 2 is
-                    // coordinate data — an ungated 0xFF check misparses pointer
-                    // motion as a HID++ error (feature enumeration then fails
-                    // whenever the mouse is moving).
-                    if (response[0] == report_type::SHORT || response[0] == report_type::LONG)
-                        && response[1] == self.device_index
-                        && response[2] == 0xFF
-                    {
-                        let error_code = response[5];
-                        tracing::warn!(
-                            error_code,
-                            "HID++ error response to long request: {:02X?}",
-                            &response[..len]
-                        );
-                        return None;
-                    }
-                }
-                Ok(_) => {}
-                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
-                Err(e) => {
-                    tracing::debug!(error = %e, "Error reading HID++ long response");
-                    return None;
-                }
-            }
+    /// The device answered (or took a haptic command) just now
+    pub fn note_contact(&mut self) {
+        self.last_contact = Some(Instant::now());
+    }
 
-            attempts += 1;
-            if attempts > 100 {
-                tracing::debug!(feature_index, function, "HID++ long request timeout");
-                return None;
-            }
+    /// Idle time after which the next haptic command is preceded by a wake
+    /// ping; `None` never pings
+    pub fn set_wake_ping_idle(&mut self, idle: Option<Duration>) {
+        self.wake_ping_idle = idle;
+    }
 
-            std::thread::sleep(std::time::Duration::from_millis(10));
+    /// Write a ping (reply not awaited) if the device has been quiet long
+    /// enough to be in power-save, so its radio is up for the command after it
+    fn wake_if_idle(&mut self) {
+        if !needs_wake(self.last_contact, self.wake_ping_idle, Instant::now()) {
+            return;
+        }
+        let ping = self
+            .request(false, 0x00, 0x01, &[0x00, 0x00, 0xAA])
+            .encode(WAKE_SW_ID);
+        tracing::debug!(idle = ?self.last_contact.map(|t| t.elapsed()), "Device idle; sending wake ping before haptic");
+        if self.device.write_all(&ping).is_ok() {
+            self.trace.outgoing(&ping);
+        }
+    }
+
+    fn log_result(request: &Request, result: Result<Vec<u8>, RequestError>) -> Option<Vec<u8>> {
+        match result {
+            Ok(response) => {
+                tracing::debug!("HID++ request matched: {:02X?}", &response);
+                Some(response)
+            }
+            Err(RequestError::ErrorReply(error)) if !error.legacy => {
+                tracing::warn!(
+                    error_code = error.raw_code,
+                    error_msg = error.code().name(),
+                    feature_index = request.feature_index,
+                    function = request.function,
+                    "HID++ error response"
+                );
+                None
+            }
+            // Timeouts, I/O errors, and the HID++ 1.0 errors empty receiver
+            // slots answer during discovery
+            Err(e) => {
+                tracing::debug!(
+                    feature_index = request.feature_index,
+                    function = request.function,
+                    error = %e,
+                    "HID++ request failed"
+                );
+                None
+            }
         }
     }
 
@@ -693,12 +594,13 @@ impl HidppDevice {
     /// Uses a short timeout (200ms) since responsive devices reply within
     /// ~20ms. Empty receiver slots and non-HID++ devices won't respond at all,
     /// so waiting longer just hammers the receiver firmware for no benefit.
+    /// Not retried for the same reason (see the slot scan in `open`).
     fn validate_hidpp20(&mut self) -> bool {
         // Send IRoot ping (feature 0x00, function 0x01)
         // Ping echoes back the data byte and returns protocol version
         let params = [0x00, 0x00, 0xAA]; // 0xAA is ping data to echo
 
-        if let Some(response) = self.hidpp_request_with_timeout(0x00, 0x01, &params, 20) {
+        if let Some(response) = self.hidpp_request_with_timeout(0x00, 0x01, &params, PING_TIMEOUT) {
             // Check if ping data was echoed (byte 6 should be 0xAA)
             if response.len() >= 7 && response[6] == 0xAA {
                 tracing::debug!("HID++ 2.0 validated, ping echoed successfully");
@@ -709,7 +611,71 @@ impl HidppDevice {
         false
     }
 
-    /// Enumerate device features and build feature table
+    /// Fill the feature table from the cache or by enumeration
+    ///
+    /// See [`feature_cache`]: a cached table is used only when the firmware
+    /// matches and the spot-check passes, and a fresh enumeration replaces
+    /// the device's entry.
+    fn load_features(&mut self) {
+        let identity = self.identity();
+        let path = feature_cache::cache_path();
+        if let (Some(identity), Some(path)) = (&identity, &path) {
+            if let Some(mut table) = feature_cache::load(path, identity) {
+                table.retain(|&id, _| !blocklisted_features::is_blocklisted(id));
+                self.feature_table = table;
+                if self.spot_check() {
+                    tracing::debug!(unit_id = %identity.unit_id, "Using cached feature table");
+                    self.feature_source = FeatureSource::Cached;
+                    self.apply_feature_table();
+                    return;
+                }
+                tracing::info!(unit_id = %identity.unit_id, "Cached feature table is stale; re-enumerating");
+                self.feature_table.clear();
+            }
+        }
+
+        self.enumerate_features();
+        self.feature_source = FeatureSource::Enumerated;
+        self.apply_feature_table();
+        if let (Some(identity), Some(path)) = (&identity, &path) {
+            if !self.feature_table.is_empty() {
+                feature_cache::store(&self.persistence, path, identity, &self.feature_table);
+            }
+        }
+    }
+
+    /// Unit ID and main firmware via DEVICE_INFORMATION (0x0003); `None`
+    /// when the device does not report them, which disables the cache
+    fn identity(&mut self) -> Option<DeviceIdentity> {
+        let index = self.get_feature_index(features::DEVICE_INFORMATION)?;
+        let info = self.hidpp_read(index, 0x00, &[])?;
+        let unit_id = DeviceIdentity::unit_id_from(&info)?;
+        let entities = info[4];
+        let firmware = (0..entities).find_map(|entity| {
+            let reply = self.hidpp_read(index, 0x01, &[entity, 0, 0])?;
+            DeviceIdentity::firmware_from(&reply)
+        })?;
+        Some(DeviceIdentity { unit_id, firmware })
+    }
+
+    /// Whether IRoot still puts the haptic feature (DPI on a device without
+    /// one) at the index the feature table has for it
+    fn spot_check(&mut self) -> bool {
+        let probe = [
+            features::MX_MASTER_4_HAPTIC,
+            features::MX4_HAPTIC_ALT,
+            features::FORCE_FEEDBACK,
+            features::ADJUSTABLE_DPI,
+        ]
+        .into_iter()
+        .find_map(|id| self.feature_table.get(&id).map(|&index| (id, index)));
+        match probe {
+            Some((id, cached)) => self.get_feature_index(id) == Some(cached),
+            None => false,
+        }
+    }
+
+    /// Enumerate device features into the feature table
     ///
     /// # SAFETY
     ///
@@ -727,7 +693,7 @@ impl HidppDevice {
         };
 
         // Get feature count (function 0x00 of IFeatureSet)
-        let feature_count = match self.hidpp_request(feature_set_index, 0x00, &[]) {
+        let feature_count = match self.hidpp_read(feature_set_index, 0x00, &[]) {
             Some(resp) if resp.len() >= 5 => resp[4],
             _ => return,
         };
@@ -736,7 +702,7 @@ impl HidppDevice {
 
         // Enumerate each feature (function 0x01 of IFeatureSet)
         for i in 0..feature_count {
-            if let Some(resp) = self.hidpp_request(feature_set_index, 0x01, &[i, 0, 0]) {
+            if let Some(resp) = self.hidpp_read(feature_set_index, 0x01, &[i, 0, 0]) {
                 if resp.len() < 6 {
                     continue;
                 }
@@ -758,118 +724,128 @@ impl HidppDevice {
                 }
 
                 self.feature_table.insert(feature_id, feature_index);
+            }
+        }
+    }
 
-                // Log all features for debugging
-                tracing::debug!(
-                    feature_id = format!("0x{:04X}", feature_id),
-                    feature_index = feature_index,
-                    "Found feature"
+    /// Set the per-feature support flags and indices from the feature table
+    ///
+    /// Entries are applied in index order, so the result matches applying
+    /// them as enumeration finds them.
+    fn apply_feature_table(&mut self) {
+        let mut entries: Vec<(u16, u8)> = self.feature_table.iter().map(|(&id, &index)| (id, index)).collect();
+        entries.sort_by_key(|&(_, index)| index);
+        for (feature_id, feature_index) in entries {
+            // Log all features for debugging
+            tracing::debug!(
+                feature_id = format!("0x{:04X}", feature_id),
+                feature_index = feature_index,
+                "Found feature"
+            );
+
+            // Check for legacy force feedback feature (0x8123 - for racing wheels)
+            if feature_id == features::FORCE_FEEDBACK {
+                self.haptic_supported = true;
+                self.haptic_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "Legacy haptic/force feedback feature found (0x8123)"
                 );
+            }
 
-                // Check for legacy force feedback feature (0x8123 - for racing wheels)
-                if feature_id == features::FORCE_FEEDBACK {
-                    self.haptic_supported = true;
-                    self.haptic_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "Legacy haptic/force feedback feature found (0x8123)"
-                    );
-                }
+            // Check for MX Master 4 haptic feature (0x19B0)
+            if feature_id == features::MX_MASTER_4_HAPTIC {
+                self.mx4_haptic_supported = true;
+                self.mx4_haptic_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "MX Master 4 haptic feature found (0x19B0)"
+                );
+            }
 
-                // Check for MX Master 4 haptic feature (0x19B0)
-                if feature_id == features::MX_MASTER_4_HAPTIC {
-                    self.mx4_haptic_supported = true;
-                    self.mx4_haptic_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "MX Master 4 haptic feature found (0x19B0)"
-                    );
-                }
+            // Check for alternative haptic feature (0x0B4E from mx4notifications)
+            if feature_id == features::MX4_HAPTIC_ALT {
+                self.mx4_haptic_supported = true;
+                self.mx4_haptic_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "MX Master 4 haptic feature found (0x0B4E - mx4notifications)"
+                );
+            }
 
-                // Check for alternative haptic feature (0x0B4E from mx4notifications)
-                if feature_id == features::MX4_HAPTIC_ALT {
-                    self.mx4_haptic_supported = true;
-                    self.mx4_haptic_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "MX Master 4 haptic feature found (0x0B4E - mx4notifications)"
-                    );
-                }
+            // Check for adjustable DPI feature (0x2201)
+            if feature_id == features::ADJUSTABLE_DPI {
+                self.dpi_supported = true;
+                self.dpi_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "Adjustable DPI feature found (0x2201)"
+                );
+            }
 
-                // Check for adjustable DPI feature (0x2201)
-                if feature_id == features::ADJUSTABLE_DPI {
-                    self.dpi_supported = true;
-                    self.dpi_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "Adjustable DPI feature found (0x2201)"
-                    );
-                }
+            // Check for HiResScroll feature (0x2111) - MX Master 3/4 SmartShift control
+            if feature_id == features::HIRES_SCROLL {
+                self.smartshift_supported = true;
+                self.smartshift_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "HiResScroll feature found (0x2111) - SmartShift control available"
+                );
+            }
 
-                // Check for HiResScroll feature (0x2111) - MX Master 3/4 SmartShift control
-                if feature_id == features::HIRES_SCROLL {
+            // Also check for legacy SmartShift feature (0x2110) for older mice
+            if feature_id == features::SMARTSHIFT_LEGACY {
+                // Only set if not already detected via HiResScroll
+                if !self.smartshift_supported {
                     self.smartshift_supported = true;
                     self.smartshift_feature_index = Some(feature_index);
                     tracing::info!(
                         index = feature_index,
-                        "HiResScroll feature found (0x2111) - SmartShift control available"
+                        "Legacy SmartShift feature found (0x2110)"
                     );
                 }
+            }
 
-                // Also check for legacy SmartShift feature (0x2110) for older mice
-                if feature_id == features::SMARTSHIFT_LEGACY {
-                    // Only set if not already detected via HiResScroll
-                    if !self.smartshift_supported {
-                        self.smartshift_supported = true;
-                        self.smartshift_feature_index = Some(feature_index);
-                        tracing::info!(
-                            index = feature_index,
-                            "Legacy SmartShift feature found (0x2110)"
-                        );
-                    }
-                }
-
-                // Check for UNIFIED_BATTERY feature (0x1004) - preferred for MX Master 4
-                if feature_id == features::UNIFIED_BATTERY {
-                    self.battery_supported = true;
-                    self.battery_feature_index = Some(feature_index);
-                    self.is_unified_battery = true;
-                    tracing::info!(
-                        index = feature_index,
-                        "Unified Battery feature found (0x1004)"
-                    );
-                }
+            // Check for UNIFIED_BATTERY feature (0x1004) - preferred for MX Master 4
+            if feature_id == features::UNIFIED_BATTERY {
+                self.battery_supported = true;
+                self.battery_feature_index = Some(feature_index);
+                self.is_unified_battery = true;
+                tracing::info!(
+                    index = feature_index,
+                    "Unified Battery feature found (0x1004)"
+                );
+            }
 
-                // Check for BATTERY_STATUS feature (0x1000) - fallback for older devices
-                if feature_id == features::BATTERY_STATUS && !self.battery_supported {
-                    self.battery_supported = true;
-                    self.battery_feature_index = Some(feature_index);
-                    self.is_unified_battery = false;
-                    tracing::info!(
-                        index = feature_index,
-                        "Battery Status feature found (0x1000)"
-                    );
-                }
+            // Check for BATTERY_STATUS feature (0x1000) - fallback for older devices
+            if feature_id == features::BATTERY_STATUS && !self.battery_supported {
+                self.battery_supported = true;
+                self.battery_feature_index = Some(feature_index);
+                self.is_unified_battery = false;
+                tracing::info!(
+                    index = feature_index,
+                    "Battery Status feature found (0x1000)"
+                );
+            }
 
-                // Check for REPROG_CONTROLS_V4 feature (0x1B04) - button divert
-                if feature_id == features::REPROG_CONTROLS_V4 {
-                    self.reprog_controls_supported = true;
-                    self.reprog_controls_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "REPROG_CONTROLS_V4 feature found (0x1B04) - button divert available"
-                    );
-                }
+            // Check for REPROG_CONTROLS_V4 feature (0x1B04) - button divert
+            if feature_id == features::REPROG_CONTROLS_V4 {
+                self.reprog_controls_supported = true;
+                self.reprog_controls_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "REPROG_CONTROLS_V4 feature found (0x1B04) - button divert available"
+                );
+            }
 
-                // Check for ThumbWheel feature (0x2150) - thumb-wheel divert
-                if feature_id == features::THUMB_WHEEL {
-                    self.thumbwheel_supported = true;
-                    self.thumbwheel_feature_index = Some(feature_index);
-                    tracing::info!(
-                        index = feature_index,
-                        "ThumbWheel feature found (0x2150) - thumb-wheel divert available"
-                    );
-                }
+            // Check for ThumbWheel feature (0x2150) - thumb-wheel divert
+            if feature_id == features::THUMB_WHEEL {
+                self.thumbwheel_supported = true;
+                self.thumbwheel_feature_index = Some(feature_index);
+                tracing::info!(
+                    index = feature_index,
+                    "ThumbWheel feature found (0x2150) - thumb-wheel divert available"
+                );
             }
         }
 
@@ -881,16 +857,46 @@ impl HidppDevice {
             smartshift = self.smartshift_supported,
             battery = self.battery_supported,
             reprog_controls = self.reprog_controls_supported,
-            "Feature enumeration complete (blocklisted features excluded)"
+            "Feature table ready (blocklisted features excluded)"
         );
     }
 
+    /// Stop using the feature at `feature_index` until the device is reopened
+    fn drop_feature(&mut self, feature_index: u8) {
+        // IRoot answers for every device
+        if feature_index == 0 {
+            return;
+        }
+        let before = self.feature_table.len();
+        self.feature_table.retain(|_, index| *index != feature_index);
+        if self.feature_table.len() == before {
+            return;
+        }
+        tracing::warn!(feature_index, "Device reports the feature unsupported; disabling it");
+        self.haptic_supported = false;
+        self.haptic_feature_index = None;
+        self.mx4_haptic_supported = false;
+        self.mx4_haptic_feature_index = None;
+        self.dpi_supported = false;
+        self.dpi_feature_index = None;
+        self.smartshift_supported = false;
+        self.smartshift_feature_index = None;
+        self.battery_supported = false;
+        self.battery_feature_index = None;
+        self.is_unified_battery = false;
+        self.reprog_controls_supported = false;
+        self.reprog_controls_feature_index = None;
+        self.thumbwheel_supported = false;
+        self.thumbwheel_feature_index = None;
+        self.apply_feature_table();
+    }
+
     /// Get the feature index for a given feature ID using IRoot
     fn get_feature_index(&mut self, feature_id: u16) -> Option<u8> {
         // IRoot function 0x00: getFeatureIndex
         let params = [(feature_id >> 8) as u8, (feature_id & 0xFF) as u8, 0];
 
-        self.hidpp_request(0x00, 0x00, &params).and_then(|resp| {
+        self.hidpp_read(0x00, 0x00, &params).and_then(|resp| {
             if resp.len() >= 5 {
                 let index = resp[4];
                 if index == 0 {
@@ -1224,17 +1230,25 @@ impl HidppDevice {
 
     /// Check if any haptic feedback is supported (MX4 or legacy)
     pub fn haptic_supported(&self) -> bool {
-        self.mx4_haptic_supported || self.haptic_supported
+        self.mx4_haptic_supported() || self.legacy_haptic_supported()
     }
 
     /// Check if MX Master 4 specific haptic is supported (feature 0x19B0)
+    ///
+    /// Always false without the `haptics` feature, which keeps every pulse
+    /// path off the device while DPI, diverts and battery still work.
     pub fn mx4_haptic_supported(&self) -> bool {
-        self.mx4_haptic_supported
+        HAPTICS_BUILT && self.mx4_haptic_supported
     }
 
     /// Check if legacy force feedback haptic is supported (feature 0x8123)
     pub fn legacy_haptic_supported(&self) -> bool {
-        self.haptic_supported
+        HAPTICS_BUILT && self.haptic_supported
+    }
+
+    /// Whether the feature table came from the cache or an enumeration
+    pub fn feature_source(&self) -> FeatureSource {
+        self.feature_source
     }
 
     /// Get connection type
@@ -1305,7 +1319,7 @@ impl HidppDevice {
     ///
     /// * `pattern` - The MX4 haptic pattern to play (0-14)
     pub fn send_haptic_pattern(&mut self, pattern: Mx4HapticPattern) -> Result<(), HapticError> {
-        if !self.mx4_haptic_supported {
+        if !self.mx4_haptic_supported() {
             tracing::trace!("MX4 haptic not supported, skipping pattern");
             return Ok(());
         }
@@ -1328,7 +1342,9 @@ impl HidppDevice {
         const MX4_HAPTIC_FUNCTION: u8 = 0x04;       // Function ID for haptic play
         const MX4_HAPTIC_SW_ID: u8 = 0x0E;          // Software ID used by mx4notifications
 
-        self.drain_buffer();
+        // Write only: nothing is read here, so a device in power-save cannot
+        // stall the caller waiting for an answer
+        self.wake_if_idle();
 
         // Bluetooth devices only expose the long (0x11) report, so send the
         // haptic command as a 20-byte long report there. The short-report
@@ -1352,6 +1368,8 @@ impl HidppDevice {
             self.device
                 .write_all(&request)
                 .map_err(HapticError::IoError)?;
+            self.trace.outgoing(&request);
+            self.note_contact();
 
             return Ok(());
         }
@@ -1370,6 +1388,8 @@ impl HidppDevice {
         );
 
         self.device.write_all(&request).map_err(HapticError::IoError)?;
+        self.trace.outgoing(&request);
+        self.note_contact();
 
         Ok(())
     }
@@ -1381,7 +1401,7 @@ impl HidppDevice {
     /// This method ONLY sends volatile/runtime commands.
     /// It does NOT write to onboard memory.
     pub fn send_haptic_pulse(&mut self, intensity: u8, duration_ms: u16) -> Result<(), HapticError> {
-        let feature_index = match self.haptic_feature_index {
+        let feature_index = match self.haptic_feature_index.filter(|_| HAPTICS_BUILT) {
             Some(idx) => idx,
             None => {
                 // Legacy haptics not supported, succeed silently
@@ -1397,10 +1417,11 @@ impl HidppDevice {
             (duration_ms & 0xFF) as u8,
         ];
 
-        // Use hidpp_request for short messages (will drain buffer and send)
-        if self.hidpp_request(feature_index, 0x00, &params).is_none() {
-            tracing::debug!("Legacy haptic pulse - no response (may be expected)");
-        }
+        // Fire and forget, like the MX4 path: waiting for the reply would
+        // hold the caller for the full request timeout when the device dozes
+        self.wake_if_idle();
+        self.hidpp_send(feature_index, 0x00, &params)?;
+        self.note_contact();
 
         Ok(())
     }
@@ -1742,62 +1763,32 @@ impl HidppDevice {
     // =========================================================================
 
     /// Query battery status from the device
-    pub fn query_battery(&mut self) -> Result<(u8, bool), HapticError> {
-        let feature_index = match self.battery_feature_index {
-            Some(idx) => idx,
-            None => {
-                tracing::debug!("Battery feature not supported on this device");
-                return Err(HapticError::NotSupported);
-            }
-        };
-
-        // Query battery status
-        let function = if self.is_unified_battery { 0x01 } else { 0x00 };
-
-        match self.hidpp_request(feature_index, function, &[]) {
-            Some(resp) => {
-                tracing::debug!(
-                    response_len = resp.len(),
-                    is_unified = self.is_unified_battery,
-                    "Battery response: {:02X?}",
-                    &resp[..resp.len().min(12)]
-                );
-
-                if self.is_unified_battery && resp.len() >= 8 {
-                    let percentage = resp[4];
-                    let charging_status = resp[7];
-                    let charging = (1..=3).contains(&charging_status);
-
-                    tracing::debug!(
-                        percentage,
-                        charging_status,
-                        charging,
-                        "Battery query result (UNIFIED_BATTERY)"
-                    );
-
-                    Ok((percentage, charging))
-                } else if resp.len() >= 7 {
-                    let percentage = resp[4];
-                    let charging_status = resp[6];
-                    let charging = (1..=4).contains(&charging_status);
-
-                    tracing::debug!(
-                        percentage,
-                        charging_status,
-                        charging,
-                        "Battery query result (BATTERY_STATUS)"
-                    );
-
-                    Ok((percentage, charging))
-                } else {
-                    Err(HapticError::ProtocolError("Invalid battery response".into()))
-                }
-            }
-            None => {
-                tracing::warn!("No response from battery query");
-                Err(HapticError::CommunicationError)
-            }
+    pub fn query_battery(&mut self) -> Result<BatteryReading, HapticError> {
+        let reading = self.battery_probe()?.query();
+        if reading.is_ok() {
+            self.note_contact();
         }
+        reading
+    }
+
+    /// The battery request on a handle of its own, to run without `&mut self`
+    ///
+    /// Takes the software IDs the probe may use (one per attempt), so replies
+    /// to this device's own requests and to the probe cannot be confused.
+    pub fn battery_probe(&mut self) -> Result<BatteryProbe, HapticError> {
+        let Some(feature_index) = self.battery_feature_index else {
+            tracing::debug!("Battery feature not supported on this device");
+            return Err(HapticError::NotSupported);
+        };
+        Ok(BatteryProbe {
+            device: self.device.try_clone()?,
+            long: self.connection_type == ConnectionType::Bluetooth,
+            device_index: self.device_index,
+            feature_index,
+            is_unified: self.is_unified_battery,
+            sw_ids: self.sw_ids.reserve(1 + READ_RETRIES),
+            trace: self.trace.clone(),
+        })
     }
 
     /// Check if battery feature is supported
@@ -1805,17 +1796,41 @@ impl HidppDevice {
         self.battery_supported
     }
 
+    /// A battery probe was answered Unsupported: stop asking
+    pub fn battery_unsupported(&mut self) {
+        if let Some(feature_index) = self.battery_feature_index {
+            self.drop_feature(feature_index);
+        }
+    }
+
     // =========================================================================
     // Easy-Switch Methods
     // =========================================================================
 
+    /// Send a request to an audited getter of a feature that also has setters
+    ///
+    /// Refuses any function not in `read_only_functions`, so host-state
+    /// reads cannot drift into the pairing-table writers of 0x1815.
+    fn read_only_request(&mut self, feature_id: u16, feature_index: u8, function: u8, params: &[u8]) -> Option<Vec<u8>> {
+        if !read_only_functions::is_read_only(feature_id, function) {
+            tracing::error!(
+                feature_id = format!("0x{:04X}", feature_id),
+                function,
+                "Refusing non-audited HID++ function on a host feature"
+            );
+            return None;
+        }
+        self.hidpp_request(feature_index, function, params)
+    }
+
     /// Get host names for Easy-Switch slots using HID++ 0x1815 (HOSTS_INFO)
     ///
     /// This is a READ-ONLY operation that retrieves the friendly names of
     /// paired hosts. It does NOT write to device memory.
     pub fn get_host_names(&mut self) -> Vec<String> {
         // Query HOSTS_INFO feature (0x1815) directly using IRoot
-        // This bypasses the blocklist check since we only READ, never WRITE
+        // This bypasses the blocklist check; read_only_request admits only
+        // the audited getters (see read_only_functions)
         let hosts_info_index = match self.get_feature_index(features::HOSTS_INFO) {
             Some(idx) => idx,
             None => {
@@ -1827,7 +1842,7 @@ impl HidppDevice {
         tracing::debug!(index = hosts_info_index, "Found HOSTS_INFO feature");
 
         // Function 0x00: getHostInfo - get number of hosts and capabilities
-        let resp = match self.hidpp_request(hosts_info_index, 0x00, &[]) {
+        let resp = match self.read_only_request(features::HOSTS_INFO, hosts_info_index, 0x00, &[]) {
             Some(r) => r,
             None => {
                 tracing::debug!("Failed to get host info");
@@ -1851,7 +1866,7 @@ impl HidppDevice {
         // Break on first failed slot to avoid noisy HID++ error log spam.
         for host_idx in 0..num_hosts {
             // Function 0x01: getHostDescriptor - get status and name length
-            let resp = match self.hidpp_request(hosts_info_index, 0x01, &[host_idx, 0, 0]) {
+            let resp = match self.read_only_request(features::HOSTS_INFO, hosts_info_index, 0x01, &[host_idx, 0, 0]) {
                 Some(r) => r,
                 None => {
                     // Non-existent slot - no more valid hosts
@@ -1876,7 +1891,7 @@ impl HidppDevice {
             let mut offset = 0u8;
 
             while (offset as usize) < name_len {
-                let resp = match self.hidpp_request(hosts_info_index, 0x03, &[host_idx, offset, 0]) {
+                let resp = match self.read_only_request(features::HOSTS_INFO, hosts_info_index, 0x03, &[host_idx, offset, 0]) {
                     Some(r) => r,
                     None => break,
                 };
@@ -1913,7 +1928,7 @@ impl HidppDevice {
         let change_host_index = self.get_feature_index(features::CHANGE_HOST)?;
 
         // Function 0: getHostInfo
-        let resp = self.hidpp_request(change_host_index, 0x00, &[])?;
+        let resp = self.read_only_request(features::CHANGE_HOST, change_host_index, 0x00, &[])?;
 
         if resp.len() < 6 {
             return None;
@@ -1957,6 +1972,75 @@ impl HidppDevice {
     }
 }
 
+/// Whether a device last heard from at `last_contact` should get a wake ping
+/// before a haptic command at `now`
+pub(crate) fn needs_wake(last_contact: Option<Instant>, idle: Option<Duration>, now: Instant) -> bool {
+    match (idle, last_contact) {
+        (Some(idle), Some(last)) => now.saturating_duration_since(last) >= idle,
+        _ => false,
+    }
+}
+
+/// One battery query, detached from the [`HidppDevice`] it came from
+///
+/// Holds a duplicate of the hidraw fd so the (up to three second) wait for the
+/// reply can run while the device itself stays free for haptics.
+pub struct BatteryProbe {
+    device: File,
+    long: bool,
+    device_index: u8,
+    feature_index: u8,
+    is_unified: bool,
+    sw_ids: SwIdCounter,
+    trace: HidppTrace,
+}
+
+impl BatteryProbe {
+    /// Send the battery request and wait for its reply
+    pub fn query(&mut self) -> Result<BatteryReading, HapticError> {
+        let request = Request {
+            long: self.long,
+            device_index: self.device_index,
+            feature_index: self.feature_index,
+            function: if self.is_unified { 0x01 } else { 0x00 },
+            params: &[],
+        };
+        let result = request::exchange_read(&mut self.device, &self.trace, &request, &mut self.sw_ids, REQUEST_TIMEOUT);
+        // An error report is an answer: the device is there, no reconnect
+        let refused = match result.as_ref().err() {
+            Some(e) if e.code() == Some(ErrorCode::Unsupported) => Some(HapticError::NotSupported),
+            Some(e @ RequestError::ErrorReply(_)) => Some(HapticError::ProtocolError(e.to_string())),
+            _ => None,
+        };
+        let reply = HidppDevice::log_result(&request, result);
+        if let Some(e) = refused {
+            return Err(e);
+        }
+        let Some(resp) = reply else {
+            tracing::warn!("No response from battery query");
+            return Err(HapticError::CommunicationError);
+        };
+        tracing::trace!(
+            response_len = resp.len(),
+            is_unified = self.is_unified,
+            "Battery response: {:02X?}",
+            &resp[..resp.len().min(12)]
+        );
+        match parse_battery_response(&resp, self.is_unified) {
+            Some(reading) => {
+                tracing::trace!(
+                    percentage = reading.percentage,
+                    charging = reading.charging,
+                    is_unified = self.is_unified,
+                    "Battery query result"
+                );
+                Ok(reading)
+            }
+            None => Err(HapticError::ProtocolError("Invalid battery response".into())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod button_divert_tests {
     use std::collections::VecDeque;
@@ -2091,3 +2175,55 @@ mod button_divert_tests {
         assert!(io.long_requests.is_empty());
     }
 }
+
+#[cfg(test)]
+mod unsupported_tests {
+    use super::*;
+    use crate::hidpp::request::HidppError;
+
+    fn device() -> HidppDevice {
+        let file = tempfile::tempfile().unwrap();
+        let mut device = HidppDevice::new(file, PathBuf::from("/dev/hidraw-test"), 0x02, ConnectionType::Bolt, HidppTrace::default(), Arc::default());
+        device.feature_table.insert(features::ADJUSTABLE_DPI, 0x04);
+        device.feature_table.insert(features::UNIFIED_BATTERY, 0x06);
+        device.apply_feature_table();
+        device
+    }
+
+    fn refused(feature_index: u8, raw_code: u8) -> Result<Vec<u8>, RequestError> {
+        Err(RequestError::ErrorReply(HidppError {
+            legacy: false,
+            device_index: 0x02,
+            feature_index,
+            function: 0x01,
+            sw_id: 0x01,
+            raw_code,
+        }))
+    }
+
+    #[test]
+    fn test_unsupported_reply_drops_only_that_feature() {
+        let mut device = device();
+        assert!(device.battery_supported() && device.dpi_supported());
+
+        // Busy and other errors leave the feature in place
+        let request = device.request(false, 0x06, 0x01, &[]);
+        assert_eq!(device.settle(&request, refused(0x06, 0x08)), None);
+        assert!(device.battery_supported());
+
+        assert_eq!(device.settle(&request, refused(0x06, 0x09)), None);
+        assert!(!device.battery_supported());
+        assert_eq!(device.feature_index(features::UNIFIED_BATTERY), None);
+        assert!(matches!(device.battery_probe(), Err(HapticError::NotSupported)));
+        assert!(device.dpi_supported());
+        assert_eq!(device.feature_index(features::ADJUSTABLE_DPI), Some(0x04));
+    }
+
+    #[test]
+    fn test_unsupported_battery_probe_drops_the_battery() {
+        let mut device = device();
+        device.battery_unsupported();
+        assert!(!device.battery_supported());
+        assert!(device.dpi_supported());
+    }
+}
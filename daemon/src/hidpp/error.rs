@@ -1,74 +1,39 @@
 //! HID++ error types
 
-use std::fmt;
-
 /// Haptic error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum HapticError {
     /// No compatible device found
+    #[error("Logitech device not connected")]
     DeviceNotFound,
     /// Permission denied accessing device
+    #[error("Permission denied accessing HID device")]
     PermissionDenied,
     /// Device does not support haptics
+    #[error("Device does not support haptic feedback")]
     UnsupportedDevice,
     /// Feature not supported on this device
+    #[error("Feature not supported on this device")]
     NotSupported,
     /// Communication error with device
+    #[error("Communication error with device")]
     CommunicationError,
     /// I/O error during communication
-    IoError(std::io::Error),
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
     /// HID++ protocol error
+    #[error("HID++ protocol error: {0}")]
     ProtocolError(String),
+    /// The device is active on another Easy-Switch host
+    #[error("Device switched to another Easy-Switch host")]
+    HostSwitchedAway,
     /// CRITICAL: Attempted to use blocklisted feature that writes to memory
     ///
     /// This error indicates a programming bug - we should NEVER
     /// attempt to use persistent/memory-writing HID++ features.
+    #[error("SAFETY VIOLATION: Blocked feature 0x{feature_id:04X} - {reason}")]
     SafetyViolation {
         feature_id: u16,
         reason: &'static str,
     },
 }
-
-impl fmt::Display for HapticError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            HapticError::DeviceNotFound => write!(f, "Logitech device not connected"),
-            HapticError::PermissionDenied => {
-                write!(f, "Permission denied accessing HID device")
-            }
-            HapticError::UnsupportedDevice => {
-                write!(f, "Device does not support haptic feedback")
-            }
-            HapticError::NotSupported => {
-                write!(f, "Feature not supported on this device")
-            }
-            HapticError::CommunicationError => {
-                write!(f, "Communication error with device")
-            }
-            HapticError::IoError(e) => write!(f, "I/O error: {}", e),
-            HapticError::ProtocolError(msg) => write!(f, "HID++ protocol error: {}", msg),
-            HapticError::SafetyViolation { feature_id, reason } => {
-                write!(
-                    f,
-                    "SAFETY VIOLATION: Blocked feature 0x{:04X} - {}",
-                    feature_id, reason
-                )
-            }
-        }
-    }
-}
-
-impl std::error::Error for HapticError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            HapticError::IoError(e) => Some(e),
-            _ => None,
-        }
-    }
-}
-
-impl From<std::io::Error> for HapticError {
-    fn from(err: std::io::Error) -> Self {
-        HapticError::IoError(err)
-    }
-}
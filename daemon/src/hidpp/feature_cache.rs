@@ -0,0 +1,274 @@
+//! On-disk cache of enumerated HID++ feature tables
+//!
+//! Enumerating features costs one IFeatureSet round trip per feature, 30 or
+//! more on an MX Master 4, so every reconnect after sleep used to hold
+//! haptics back by a few hundred milliseconds. The table is now kept in
+//! `~/.cache/juhradial/hidpp-features.json`, keyed by the device's unit ID
+//! (DEVICE_INFORMATION 0x0003 `[0] getDeviceInfo`) and tagged with its main
+//! application firmware (`[1] getFwInfo`).
+//!
+//! On connect the device reads its identity (three round trips), takes the
+//! cached table when the firmware matches, and spot-checks it: IRoot must
+//! still put the haptic feature (the DPI feature on a device without one) at
+//! the cached index. A firmware change or a failed spot-check falls back to
+//! full enumeration, which then replaces the entry. Any cache error (no
+//! file, unreadable, corrupt, written by another version) is a miss, never a
+//! failed connection.
+//!
+//! The time from the start of a connect to a ready device, and whether the
+//! table came from the cache, is kept for `Status()` and `Diagnostics()`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::persistence::{PersistJob, PersistQueue};
+
+/// Cache file name under `~/.cache/juhradial`
+pub const CACHE_FILE_NAME: &str = "hidpp-features.json";
+
+/// Bumped when the file layout changes; other versions are a miss
+const CACHE_VERSION: u32 = 1;
+
+/// DEVICE_INFORMATION firmware entity type of the main application
+const FW_TYPE_MAIN_APPLICATION: u8 = 0;
+
+/// Who a cached table belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceIdentity {
+    /// Unit ID as 8 hex digits, unique per device
+    pub unit_id: String,
+    /// Main application firmware, e.g. `RBM14.00.B0011`
+    pub firmware: String,
+}
+
+impl DeviceIdentity {
+    /// Unit ID from a `getDeviceInfo` reply; `None` for a short reply or an
+    /// all-zero ID (devices that do not report one)
+    pub fn unit_id_from(reply: &[u8]) -> Option<String> {
+        let unit_id = reply.get(5..9)?;
+        if unit_id.iter().all(|&b| b == 0) {
+            return None;
+        }
+        Some(unit_id.iter().map(|b| format!("{b:02X}")).collect())
+    }
+
+    /// Firmware name from a `getFwInfo` reply; `None` unless it describes
+    /// the main application
+    pub fn firmware_from(reply: &[u8]) -> Option<String> {
+        let info = reply.get(4..12)?;
+        if info[0] != FW_TYPE_MAIN_APPLICATION {
+            return None;
+        }
+        let prefix = String::from_utf8_lossy(&info[1..4]).trim_end_matches('\0').trim().to_string();
+        Some(format!("{prefix}{:02X}.{:02X}.B{:02X}{:02X}", info[4], info[5], info[6], info[7]))
+    }
+}
+
+/// Where a connected device's feature table came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeatureSource {
+    /// The cache, confirmed by the spot-check
+    Cached,
+    /// A full IFeatureSet enumeration
+    Enumerated,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    /// Unit ID -> entry
+    devices: BTreeMap<String, CachedDevice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedDevice {
+    firmware: String,
+    /// Feature ID -> feature index
+    features: BTreeMap<u16, u8>,
+}
+
+/// `~/.cache/juhradial/hidpp-features.json`
+pub fn cache_path() -> Option<PathBuf> {
+    Some(crate::paths::paths().cache_dir.join(CACHE_FILE_NAME))
+}
+
+/// The cached table for `identity`; `None` on any miss or error
+pub fn load(path: &Path, identity: &DeviceIdentity) -> Option<HashMap<u16, u8>> {
+    let cache = read(path)?;
+    let Some(entry) = cache.devices.get(&identity.unit_id) else {
+        tracing::debug!(unit_id = %identity.unit_id, "No cached feature table for this device");
+        return None;
+    };
+    if entry.firmware != identity.firmware {
+        tracing::info!(
+            unit_id = %identity.unit_id,
+            cached = %entry.firmware,
+            firmware = %identity.firmware,
+            "Firmware changed; re-enumerating features"
+        );
+        return None;
+    }
+    Some(entry.features.iter().map(|(&id, &index)| (id, index)).collect())
+}
+
+/// Record `table` for `identity`, keeping other devices' entries
+///
+/// Goes through the write-behind `queue`; a failed write only costs the
+/// next connect an enumeration.
+pub fn store(queue: &PersistQueue, path: &Path, identity: &DeviceIdentity, table: &HashMap<u16, u8>) {
+    let mut cache = read(path).unwrap_or_default();
+    cache.version = CACHE_VERSION;
+    cache.devices.insert(
+        identity.unit_id.clone(),
+        CachedDevice {
+            firmware: identity.firmware.clone(),
+            features: table.iter().map(|(&id, &index)| (id, index)).collect(),
+        },
+    );
+    match serde_json::to_vec_pretty(&cache) {
+        Ok(contents) => queue.submit(PersistJob::WriteState { path: path.to_path_buf(), contents }),
+        Err(e) => tracing::debug!(error = %e, "Failed to serialize the feature cache"),
+    }
+}
+
+/// Parse the cache file; errors are logged and treated as an empty cache
+fn read(path: &Path) -> Option<CacheFile> {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            tracing::debug!(path = %path.display(), error = %e, "Feature cache unreadable; ignoring it");
+            return None;
+        }
+    };
+    match serde_json::from_slice::<CacheFile>(&contents) {
+        Ok(cache) if cache.version == CACHE_VERSION => Some(cache),
+        Ok(cache) => {
+            tracing::debug!(version = cache.version, "Feature cache from another version; ignoring it");
+            None
+        }
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "Feature cache corrupt; ignoring it");
+            None
+        }
+    }
+}
+
+/// One connect, from the start of device discovery to a ready device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectTiming {
+    pub source: FeatureSource,
+    pub elapsed: Duration,
+}
+
+/// The connects seen by one `HapticManager`
+#[derive(Debug, Default)]
+pub struct ConnectTimings {
+    last: Option<ConnectTiming>,
+    last_cached: Option<Duration>,
+    last_enumerated: Option<Duration>,
+}
+
+impl ConnectTimings {
+    /// Note how long a connect took to make haptics ready
+    pub fn record(&mut self, timing: ConnectTiming) {
+        tracing::info!(
+            source = ?timing.source,
+            elapsed_ms = timing.elapsed.as_millis() as u64,
+            "HID++ device ready"
+        );
+        self.last = Some(timing);
+        match timing.source {
+            FeatureSource::Cached => self.last_cached = Some(timing.elapsed),
+            FeatureSource::Enumerated => self.last_enumerated = Some(timing.elapsed),
+        }
+    }
+
+    /// Connect-to-ready times for `Status()` and `Diagnostics()`: the last
+    /// connect, and the last one of each kind for comparison
+    pub fn to_json(&self) -> serde_json::Value {
+        let ms = |d: Option<Duration>| d.map(|d| d.as_millis() as u64);
+        serde_json::json!({
+            "last_ms": ms(self.last.map(|t| t.elapsed)),
+            "last_source": self.last.map(|t| t.source),
+            "cached_ms": ms(self.last_cached),
+            "enumerated_ms": ms(self.last_enumerated),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn identity(firmware: &str) -> DeviceIdentity {
+        DeviceIdentity { unit_id: "1A2B3C4D".into(), firmware: firmware.into() }
+    }
+
+    fn table() -> HashMap<u16, u8> {
+        HashMap::from([(0x0001, 1), (0x19B0, 0x0E), (0x2201, 0x0A)])
+    }
+
+    #[test]
+    fn test_round_trip_and_firmware_change() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CACHE_FILE_NAME);
+        assert_eq!(load(&path, &identity("RBM14.00.B0011")), None);
+
+        // No write-behind task runs here, so the write is immediate
+        let queue = PersistQueue::new();
+        store(&queue, &path, &identity("RBM14.00.B0011"), &table());
+        assert_eq!(load(&path, &identity("RBM14.00.B0011")), Some(table()));
+        assert_eq!(load(&path, &identity("RBM14.01.B0002")), None);
+        let other = DeviceIdentity { unit_id: "00000001".into(), firmware: "RBM14.00.B0011".into() };
+        assert_eq!(load(&path, &other), None);
+
+        // A second device keeps the first one's entry
+        store(&queue, &path, &other, &HashMap::from([(0x2201, 4)]));
+        assert_eq!(load(&path, &identity("RBM14.00.B0011")), Some(table()));
+    }
+
+    #[test]
+    fn test_corrupt_cache_is_a_miss() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CACHE_FILE_NAME);
+        for contents in ["", "{not json", "{\"version\": 99, \"devices\": {}}", "[1, 2]"] {
+            std::fs::write(&path, contents).unwrap();
+            assert_eq!(load(&path, &identity("RBM14.00.B0011")), None, "{contents:?}");
+        }
+        // And is replaced by the next store
+        store(&PersistQueue::new(), &path, &identity("RBM14.00.B0011"), &table());
+        assert_eq!(load(&path, &identity("RBM14.00.B0011")), Some(table()));
+    }
+
+    #[test]
+    fn test_identity_parsing() {
+        let info = [0x11, 0x02, 0x05, 0x01, 0x03, 0x1A, 0x2B, 0x3C, 0x4D, 0x00, 0x07];
+        assert_eq!(DeviceIdentity::unit_id_from(&info).as_deref(), Some("1A2B3C4D"));
+        assert_eq!(DeviceIdentity::unit_id_from(&[0x11, 0x02, 0x05, 0x01, 0x03, 0, 0, 0, 0]), None);
+        assert_eq!(DeviceIdentity::unit_id_from(&info[..7]), None);
+
+        let fw = [0x11, 0x02, 0x05, 0x11, 0x00, b'R', b'B', b'M', 0x14, 0x00, 0x00, 0x11];
+        assert_eq!(DeviceIdentity::firmware_from(&fw).as_deref(), Some("RBM14.00.B0011"));
+        let mut bootloader = fw;
+        bootloader[4] = 0x01;
+        assert_eq!(DeviceIdentity::firmware_from(&bootloader), None);
+    }
+
+    #[test]
+    fn test_connect_timing_keeps_both_kinds() {
+        let mut timings = ConnectTimings::default();
+        timings.record(ConnectTiming { source: FeatureSource::Enumerated, elapsed: Duration::from_millis(420) });
+        timings.record(ConnectTiming { source: FeatureSource::Cached, elapsed: Duration::from_millis(60) });
+        let json = timings.to_json();
+        assert_eq!(json["last_ms"], 60);
+        assert_eq!(json["last_source"], "cached");
+        assert_eq!(json["cached_ms"], 60);
+        assert_eq!(json["enumerated_ms"], 420);
+    }
+}
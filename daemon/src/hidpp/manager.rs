@@ -4,10 +4,20 @@
 //! debouncing, reconnection, and delegation to HidppDevice.
 
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use super::device::HidppDevice;
+use super::device::{BatteryProbe, HidppDevice};
 use super::error::HapticError;
+use super::feature_cache::{ConnectTiming, ConnectTimings};
+use super::messages::ConnectionType;
+use super::trace::HidppTrace;
+use crate::action_latency::LatencyHandle;
+use crate::battery::BatteryReading;
+use crate::conflicts::ConflictsHandle;
+use crate::metrics::MetricsHandle;
+use crate::persistence::PersistQueue;
+use crate::quiet_hours::QuietHoursHandle;
 use super::patterns::*;
 
 /// Connection state for graceful fallback handling
@@ -22,9 +32,33 @@ pub enum ConnectionState {
     Disconnected,
     /// Waiting for cooldown before attempting reconnection
     Cooldown,
+    /// The device was switched to another Easy-Switch host
+    SwitchedAway,
 }
 
-/// Reconnection cooldown in milliseconds (5 seconds)
+impl ConnectionState {
+    pub const ALL: [ConnectionState; 5] = [
+        ConnectionState::NotConnected,
+        ConnectionState::Connected,
+        ConnectionState::Disconnected,
+        ConnectionState::Cooldown,
+        ConnectionState::SwitchedAway,
+    ];
+
+    /// Stable name for `Status()`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionState::NotConnected => "not_connected",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Disconnected => "disconnected",
+            ConnectionState::Cooldown => "cooldown",
+            ConnectionState::SwitchedAway => "switched_away",
+        }
+    }
+}
+
+/// Reconnection cooldown in milliseconds (5 seconds); stretched by
+/// [`ConflictsHandle::backoff`] while another program holds the device
 const RECONNECT_COOLDOWN_MS: u64 = 5000;
 
 
@@ -34,6 +68,13 @@ const DEFAULT_SLICE_DEBOUNCE_MS: u64 = 20;
 /// Default re-entry debounce time (milliseconds)
 const DEFAULT_REENTRY_DEBOUNCE_MS: u64 = 50;
 
+/// Consecutive battery query timeouts after which a multi-host device is
+/// treated as switched away (host switches do not always announce themselves)
+const SWITCHED_AWAY_TIMEOUTS: u32 = 2;
+
+/// Legacy pulse intensity that custom step `intensity_scale` multiplies
+const LEGACY_PATTERN_INTENSITY: f32 = 50.0;
+
 /// HID++ haptic manager
 pub struct HapticManager {
     /// Optional HID++ device connection
@@ -42,6 +83,12 @@ pub struct HapticManager {
     default_pattern: Mx4HapticPattern,
     /// Per-event pattern configuration
     pub(crate) per_event: PerEventPattern,
+    /// Per-event on/off switches
+    event_toggles: EventToggles,
+    /// Custom step patterns for events configured to use one
+    patterns: PatternRegistry,
+    /// Bumped whenever a step pattern starts, so a newer one cancels an older
+    pattern_generation: u64,
     /// Whether haptics are enabled
     enabled: bool,
     /// Last pulse timestamp for debouncing (milliseconds)
@@ -64,6 +111,51 @@ pub struct HapticManager {
     pub(crate) _short_msg_buffer: [u8; 7],
     /// Timestamp of last successful host switch (suppresses reconnection)
     last_host_switch_ms: u64,
+    /// Easy-Switch slot the device used while talking to us
+    pub(crate) home_host: Option<u8>,
+    /// Slot the device switched to, when known
+    away_host: Option<u8>,
+    /// Battery queries in a row that got no answer
+    consecutive_timeouts: u32,
+    /// `haptics.wake_ping_idle_ms`, handed to each device on connect
+    wake_ping_idle: Option<Duration>,
+    /// Where send times go (the daemon's shared latency stats)
+    pub(crate) latency: LatencyHandle,
+    /// HID++ trace handed to each device on open
+    pub(crate) trace: HidppTrace,
+    /// Write-behind queue handed to each device on open (feature cache)
+    pub(crate) persistence: Arc<PersistQueue>,
+    /// Where connection changes and send failures are counted
+    metrics: MetricsHandle,
+    /// Event pulses are held back while quiet
+    quiet_hours: QuietHoursHandle,
+    /// Programs competing for the device; the reconnect cooldown backs off for them
+    conflicts: ConflictsHandle,
+    /// Connect-to-ready times for `Status()` and `Diagnostics()`
+    connect_timings: ConnectTimings,
+}
+
+/// A device opened without the manager, ready for [`HapticManager::attach`]
+///
+/// Opening pings the receiver slots and enumerates features, which can take
+/// a second or more; `query_battery_shared` does it without holding the
+/// manager lock, so haptics are not held up meanwhile.
+pub struct OpenedDevice {
+    device: HidppDevice,
+    /// Easy-Switch slot the device reported while answering us
+    current_host: Option<u8>,
+    /// How long opening took
+    timing: ConnectTiming,
+}
+
+impl OpenedDevice {
+    pub fn open(trace: &HidppTrace, persistence: &Arc<PersistQueue>) -> Option<Self> {
+        let started = Instant::now();
+        let mut device = HidppDevice::open(trace, persistence)?;
+        let current_host = device.get_easy_switch_info().map(|(_, host)| host);
+        let timing = ConnectTiming { source: device.feature_source(), elapsed: started.elapsed() };
+        Some(Self { device, current_host, timing })
+    }
 }
 
 impl HapticManager {
@@ -73,6 +165,9 @@ impl HapticManager {
             device: None,
             default_pattern: Mx4HapticPattern::SubtleCollision,
             per_event: PerEventPattern::default(),
+            event_toggles: EventToggles::default(),
+            patterns: PatternRegistry::default(),
+            pattern_generation: 0,
             enabled,
             last_pulse_ms: 0,
             connection_state: ConnectionState::NotConnected,
@@ -84,6 +179,17 @@ impl HapticManager {
             last_slice_index: None,
             _short_msg_buffer: [0u8; 7],
             last_host_switch_ms: 0,
+            home_host: None,
+            away_host: None,
+            consecutive_timeouts: 0,
+            wake_ping_idle: None,
+            latency: LatencyHandle::default(),
+            trace: HidppTrace::default(),
+            persistence: Arc::default(),
+            metrics: MetricsHandle::default(),
+            quiet_hours: QuietHoursHandle::default(),
+            conflicts: ConflictsHandle::default(),
+            connect_timings: ConnectTimings::default(),
         }
     }
 
@@ -91,15 +197,14 @@ impl HapticManager {
     ///
     /// This is the preferred way to initialize HapticManager with user settings.
     pub fn from_config(config: &crate::config::HapticConfig) -> Self {
+        let (patterns, per_event) = PatternRegistry::from_config(config);
         Self {
             device: None,
             default_pattern: Mx4HapticPattern::from_name(&config.default_pattern),
-            per_event: PerEventPattern {
-                menu_appear: Mx4HapticPattern::from_name(&config.per_event.menu_appear),
-                slice_change: Mx4HapticPattern::from_name(&config.per_event.slice_change),
-                confirm: Mx4HapticPattern::from_name(&config.per_event.confirm),
-                invalid: Mx4HapticPattern::from_name(&config.per_event.invalid),
-            },
+            per_event,
+            event_toggles: EventToggles::from_config(&config.per_event),
+            patterns,
+            pattern_generation: 0,
             enabled: config.enabled,
             last_pulse_ms: 0,
             connection_state: ConnectionState::NotConnected,
@@ -111,22 +216,35 @@ impl HapticManager {
             last_slice_index: None,
             _short_msg_buffer: [0u8; 7],
             last_host_switch_ms: 0,
+            home_host: None,
+            away_host: None,
+            consecutive_timeouts: 0,
+            wake_ping_idle: config.wake_ping_idle(),
+            latency: LatencyHandle::default(),
+            trace: HidppTrace::default(),
+            persistence: Arc::default(),
+            metrics: MetricsHandle::default(),
+            quiet_hours: QuietHoursHandle::default(),
+            conflicts: ConflictsHandle::default(),
+            connect_timings: ConnectTimings::default(),
         }
     }
 
     /// Update settings from configuration (for hot-reload)
     pub fn update_from_config(&mut self, config: &crate::config::HapticConfig) {
         self.default_pattern = Mx4HapticPattern::from_name(&config.default_pattern);
-        self.per_event = PerEventPattern {
-            menu_appear: Mx4HapticPattern::from_name(&config.per_event.menu_appear),
-            slice_change: Mx4HapticPattern::from_name(&config.per_event.slice_change),
-            confirm: Mx4HapticPattern::from_name(&config.per_event.confirm),
-            invalid: Mx4HapticPattern::from_name(&config.per_event.invalid),
-        };
+        let (patterns, per_event) = PatternRegistry::from_config(config);
+        self.per_event = per_event;
+        self.event_toggles = EventToggles::from_config(&config.per_event);
+        self.patterns = patterns;
         self.enabled = config.enabled;
         self.debounce_ms = config.debounce_ms;
         self.slice_debounce_ms = config.slice_debounce_ms;
         self.reentry_debounce_ms = config.reentry_debounce_ms;
+        self.wake_ping_idle = config.wake_ping_idle();
+        if let Some(device) = self.device.as_mut() {
+            device.set_wake_ping_idle(self.wake_ping_idle);
+        }
 
         tracing::debug!(
             default_pattern = %self.default_pattern,
@@ -134,6 +252,7 @@ impl HapticManager {
             debounce_ms = self.debounce_ms,
             slice_debounce_ms = self.slice_debounce_ms,
             reentry_debounce_ms = self.reentry_debounce_ms,
+            toggles = ?self.event_toggles,
             "Haptic settings updated from config"
         );
     }
@@ -143,12 +262,29 @@ impl HapticManager {
     /// Returns Ok(true) if connected, Ok(false) if no device found.
     /// This is NOT an error - haptics are optional.
     pub fn connect(&mut self) -> Result<bool, HapticError> {
-        match HidppDevice::open() {
-            Some(device) => {
+        Ok(self.attach(OpenedDevice::open(&self.trace, &self.persistence)))
+    }
+
+    /// Take over a device from [`OpenedDevice::open`]; `false` when none was found
+    pub fn attach(&mut self, opened: Option<OpenedDevice>) -> bool {
+        match opened {
+            Some(OpenedDevice { mut device, current_host, timing }) => {
+                self.connect_timings.record(timing);
                 let haptic_supported = device.haptic_supported();
                 let connection = device.connection_type();
+                // The device answered us, so its active slot is ours
+                if current_host.is_some() {
+                    self.home_host = current_host;
+                }
+                device.set_wake_ping_idle(self.wake_ping_idle);
+                device.note_contact();
+                if self.connection_state == ConnectionState::SwitchedAway {
+                    tracing::info!(host = ?self.home_host, "Device is back from another Easy-Switch host");
+                }
                 self.device = Some(device);
-                self.connection_state = ConnectionState::Connected;
+                self.set_connection_state(ConnectionState::Connected);
+                self.away_host = None;
+                self.consecutive_timeouts = 0;
 
                 if haptic_supported {
                     tracing::info!(
@@ -162,12 +298,12 @@ impl HapticManager {
                     );
                 }
 
-                Ok(true)
+                true
             }
             None => {
                 tracing::debug!("No MX Master 4 found, haptics disabled");
-                self.connection_state = ConnectionState::NotConnected;
-                Ok(false)
+                self.set_connection_state(ConnectionState::NotConnected);
+                false
             }
         }
     }
@@ -211,8 +347,37 @@ impl HapticManager {
         }
     }
 
+    /// Hand the device back on daemon shutdown
+    ///
+    /// Clears the volatile diverts on `cids` and the thumb-wheel divert so the
+    /// buttons work natively without the daemon, then closes the hidraw fd.
+    pub fn release(&mut self, cids: &[u16]) {
+        let Some(mut device) = self.device.take() else {
+            return;
+        };
+        match device.set_button_diverts(cids, false) {
+            Ok(released) => tracing::info!(count = released.len(), "Button diverts cleared"),
+            Err(e) => tracing::warn!(error = %e, "Failed to clear button diverts"),
+        }
+        if device.thumbwheel_supported() {
+            if let Err(e) = device.set_thumbwheel_reporting(false, false) {
+                tracing::warn!(error = %e, "Failed to clear thumb-wheel divert");
+            }
+        }
+        self.set_connection_state(ConnectionState::NotConnected);
+        tracing::info!("HID++ device released");
+    }
+
+    /// Move to `state`, counting the change for the metrics endpoint
+    fn set_connection_state(&mut self, state: ConnectionState) {
+        if state != self.connection_state {
+            self.metrics.record_connection_change(self.connection_state, state);
+            self.connection_state = state;
+        }
+    }
+
     /// Handle device disconnection gracefully
-    fn handle_disconnect(&mut self) {
+    pub(crate) fn handle_disconnect(&mut self) {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -224,10 +389,50 @@ impl HapticManager {
         }
 
         self.device = None;
-        self.connection_state = ConnectionState::Disconnected;
+        self.set_connection_state(ConnectionState::Disconnected);
         self.last_disconnect_ms = now;
     }
 
+    /// Drop the connection because the device now serves another host
+    ///
+    /// Haptics and battery queries stop immediately instead of timing out;
+    /// `connect()` (input hotplug, reconnect poll) or a host change back to
+    /// the home slot resumes.
+    fn mark_switched_away(&mut self, host: Option<u8>) {
+        if self.connection_state != ConnectionState::SwitchedAway {
+            tracing::info!(host = ?host, home_host = ?self.home_host, "Device switched to another Easy-Switch host");
+        }
+        self.device = None;
+        self.set_connection_state(ConnectionState::SwitchedAway);
+        self.away_host = host;
+        self.consecutive_timeouts = 0;
+    }
+
+    /// Apply a CHANGE_HOST notification (the device's newly active slot)
+    pub fn note_active_host(&mut self, host: u8) {
+        match self.home_host {
+            Some(home) if home != host => self.mark_switched_away(Some(host)),
+            Some(_) if self.connection_state == ConnectionState::SwitchedAway => {
+                tracing::info!(host, "Device reports the home Easy-Switch host again");
+                // Reconnect on the next attempt without waiting out a cooldown
+                self.set_connection_state(ConnectionState::Disconnected);
+                self.last_disconnect_ms = 0;
+                self.away_host = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Connection state and Easy-Switch slots for `Status()`
+    pub fn connection_status(&self) -> serde_json::Value {
+        serde_json::json!({
+            "state": self.connection_state.as_str(),
+            "home_host": self.home_host,
+            "away_host": self.away_host,
+            "connect_timing": self.connect_timings.to_json(),
+        })
+    }
+
     /// Attempt to reconnect if device was disconnected and cooldown has passed
     pub fn reconnect_if_needed(&mut self) -> bool {
         // Only reconnect if we were previously connected but lost connection
@@ -243,8 +448,9 @@ impl HapticManager {
             .as_millis() as u64;
 
         // Check if cooldown has passed
-        if now.saturating_sub(self.last_disconnect_ms) < RECONNECT_COOLDOWN_MS {
-            self.connection_state = ConnectionState::Cooldown;
+        let cooldown = self.conflicts.backoff(Duration::from_millis(RECONNECT_COOLDOWN_MS));
+        if u128::from(now.saturating_sub(self.last_disconnect_ms)) < cooldown.as_millis() {
+            self.set_connection_state(ConnectionState::Cooldown);
             return false;
         }
 
@@ -254,6 +460,7 @@ impl HapticManager {
         match self.connect() {
             Ok(true) => {
                 tracing::info!("Haptic device reconnected successfully");
+                self.conflicts.refresh_system();
                 // Re-divert buttons after reconnect (divert is volatile)
                 match self.divert_buttons() {
                     Ok(n) if n > 0 => tracing::info!(count = n, "Re-diverted buttons after reconnect"),
@@ -264,13 +471,13 @@ impl HapticManager {
             }
             Ok(false) => {
                 // No device found, go back to cooldown
-                self.connection_state = ConnectionState::Cooldown;
+                self.set_connection_state(ConnectionState::Cooldown);
                 self.last_disconnect_ms = now;
                 false
             }
             Err(e) => {
                 tracing::debug!(error = %e, "Reconnection failed");
-                self.connection_state = ConnectionState::Cooldown;
+                self.set_connection_state(ConnectionState::Cooldown);
                 self.last_disconnect_ms = now;
                 false
             }
@@ -290,6 +497,11 @@ impl HapticManager {
             .unwrap_or(false)
     }
 
+    /// How the connected device is attached (USB, Bolt, Bluetooth, Unifying)
+    pub fn connection_type(&self) -> Option<ConnectionType> {
+        self.device.as_ref().map(|d| d.connection_type())
+    }
+
     /// Get the hidraw device path the MX Master 4 is connected to
     pub fn device_path(&self) -> Option<PathBuf> {
         self.device.as_ref().map(|d| d.device_path().to_path_buf())
@@ -339,11 +551,13 @@ impl HapticManager {
                 Ok(())
             }
             Err(HapticError::IoError(_)) => {
+                self.metrics.record_haptic_failure();
                 self.handle_disconnect();
                 Ok(()) // Return Ok - haptics are optional
             }
             Err(e) => {
                 tracing::debug!(error = %e, "Haptic pulse failed");
+                self.metrics.record_haptic_failure();
                 Ok(()) // Still return Ok - haptics are optional
             }
         }
@@ -383,11 +597,13 @@ impl HapticManager {
                 Ok(())
             }
             Err(HapticError::IoError(_)) => {
+                self.metrics.record_haptic_failure();
                 self.handle_disconnect();
                 Ok(())
             }
             Err(e) => {
                 tracing::debug!(error = %e, "MX4 test pattern failed");
+                self.metrics.record_haptic_failure();
                 Ok(())
             }
         }
@@ -396,6 +612,30 @@ impl HapticManager {
     pub fn emit(&mut self, event: HapticEvent) -> Result<(), HapticError> {
         tracing::debug!(event = %event, enabled = self.enabled, has_device = self.device.is_some(), "HapticManager.emit() called");
 
+        if !self.event_toggles.is_enabled(&event) {
+            tracing::debug!(event = %event, "Haptic event disabled - returning early");
+            return Ok(());
+        }
+        if self.quiet_hours.is_quiet() {
+            tracing::debug!(event = %event, "Quiet hours - returning early");
+            return Ok(());
+        }
+
+        // Custom step pattern: played inline, bounded by MAX_PATTERN_TOTAL_MS.
+        // `emit_shared` plays multi-step patterns off the caller's thread.
+        if let Some(steps) = self.patterns.get(&event) {
+            if self.begin_pattern().is_none() {
+                return Ok(());
+            }
+            for (i, step) in steps.iter().enumerate() {
+                if i > 0 {
+                    std::thread::sleep(steps[i - 1].delay_to_next());
+                }
+                self.play_step(event, step)?;
+            }
+            return Ok(());
+        }
+
         // Check if haptics are enabled
         if !self.enabled {
             tracing::debug!("Haptic disabled - returning early");
@@ -442,11 +682,13 @@ impl HapticManager {
                     return Ok(());
                 }
                 Err(HapticError::IoError(_)) => {
+                    self.metrics.record_haptic_failure();
                     self.handle_disconnect();
                     return Ok(());
                 }
                 Err(e) => {
                     tracing::debug!(error = %e, "MX4 haptic pattern failed");
+                    self.metrics.record_haptic_failure();
                     return Ok(());
                 }
             }
@@ -512,9 +754,96 @@ impl HapticManager {
         let _ = self.emit(event);
     }
 
+    /// Whether a specific event is switched on (`per_event.*_enabled`)
+    pub fn event_enabled(&self, event: HapticEvent) -> bool {
+        self.event_toggles.is_enabled(&event)
+    }
+
+    /// Custom step pattern configured for an event
+    pub fn event_steps(&self, event: HapticEvent) -> Option<std::sync::Arc<[HapticStep]>> {
+        self.patterns.get(&event)
+    }
+
+    /// Start a step pattern if haptics can play now
+    ///
+    /// Applies the enabled, quiet hours, device and debounce checks once for
+    /// the whole pattern. Returns the pattern's generation, which later steps
+    /// compare against `pattern_generation()` to notice they were superseded.
+    pub fn begin_pattern(&mut self) -> Option<u64> {
+        if !self.enabled || self.quiet_hours.is_quiet() {
+            return None;
+        }
+        match &self.device {
+            Some(d) if d.haptic_supported() || d.mx4_haptic_supported() => {}
+            _ => return None,
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        if now.saturating_sub(self.last_pulse_ms) < self.debounce_ms {
+            return None;
+        }
+        self.pattern_generation = self.pattern_generation.wrapping_add(1);
+        Some(self.pattern_generation)
+    }
+
+    /// Generation of the most recently started step pattern
+    pub fn pattern_generation(&self) -> u64 {
+        self.pattern_generation
+    }
+
+    /// Play one step of a custom pattern (no debounce between steps)
+    ///
+    /// MX4 devices play the step's waveform, or the event's default one;
+    /// legacy devices pulse at the scaled intensity for `duration_ms`.
+    pub fn play_step(&mut self, event: HapticEvent, step: &HapticStep) -> Result<(), HapticError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let waveform = step.waveform.unwrap_or_else(|| self.per_event.get(&event));
+        let device = match &mut self.device {
+            Some(d) => d,
+            None => return Ok(()),
+        };
+        let result = if device.mx4_haptic_supported() {
+            device.send_haptic_pattern(waveform)
+        } else if device.haptic_supported() {
+            let intensity = (LEGACY_PATTERN_INTENSITY * step.intensity_scale).round().clamp(0.0, 100.0) as u8;
+            device.send_haptic_pulse(intensity, step.duration_ms)
+        } else {
+            return Ok(());
+        };
+        match result {
+            Ok(()) => {
+                self.last_pulse_ms = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64;
+                Ok(())
+            }
+            Err(HapticError::IoError(_)) => {
+                self.metrics.record_haptic_failure();
+                self.handle_disconnect();
+                Ok(())
+            }
+            Err(e) => {
+                tracing::debug!(error = %e, event = %event, "Haptic pattern step failed");
+                self.metrics.record_haptic_failure();
+                Ok(())
+            }
+        }
+    }
+
     /// Emit a slice change haptic with smart debouncing
+    ///
+    /// `slice_index` is the profile slot, not the drawn position, so the
+    /// debounce behaves the same with `input.left_handed`.
     pub fn emit_slice_change(&mut self, slice_index: u8) -> bool {
-        if !self.enabled {
+        if !self.enabled
+            || !self.event_toggles.is_enabled(&HapticEvent::SliceChange)
+            || self.quiet_hours.is_quiet()
+        {
             return false;
         }
 
@@ -582,6 +911,36 @@ impl HapticManager {
         self.reentry_debounce_ms
     }
 
+    /// Record haptic send times in `latency` (the daemon's shared stats)
+    pub fn set_latency(&mut self, latency: LatencyHandle) {
+        self.latency = latency;
+    }
+
+    /// Count connection changes and send failures in `metrics` (the daemon's endpoint)
+    pub fn set_metrics(&mut self, metrics: MetricsHandle) {
+        self.metrics = metrics;
+    }
+
+    /// Check reconnects against `conflicts` (the daemon's shared report)
+    pub fn set_conflicts(&mut self, conflicts: ConflictsHandle) {
+        self.conflicts = conflicts;
+    }
+
+    /// Hold back event pulses while `quiet_hours` is quiet (the daemon's shared state)
+    pub fn set_quiet_hours(&mut self, quiet_hours: QuietHoursHandle) {
+        self.quiet_hours = quiet_hours;
+    }
+
+    /// Queue feature cache writes on `persistence` (the daemon's queue)
+    pub fn set_persistence(&mut self, persistence: Arc<PersistQueue>) {
+        self.persistence = persistence;
+    }
+
+    /// Trace device traffic through `trace` (the daemon's shared switch)
+    pub fn set_trace(&mut self, trace: HidppTrace) {
+        self.trace = trace;
+    }
+
     /// Set slice debounce time in milliseconds
     pub fn set_slice_debounce_ms(&mut self, ms: u64) {
         self.slice_debounce_ms = ms;
@@ -796,15 +1155,21 @@ impl HapticManager {
     /// Query battery status from the device
     ///
     /// On IO error (stale fd), forces reconnect and retries once.
-    pub fn query_battery(&mut self) -> Result<(u8, bool), HapticError> {
+    pub fn query_battery(&mut self) -> Result<BatteryReading, HapticError> {
+        if self.connection_state == ConnectionState::SwitchedAway {
+            return Err(HapticError::HostSwitchedAway);
+        }
         if self.device.is_none() {
             let _ = self.connect();
         }
         match self.device.as_mut() {
             Some(device) => {
-                match device.query_battery() {
+                let mut timed_out = false;
+                let result = match device.query_battery() {
                     Ok(v) => Ok(v),
-                    Err(HapticError::IoError(_)) | Err(HapticError::CommunicationError) => {
+                    Err(e @ (HapticError::IoError(_) | HapticError::CommunicationError)) => {
+                        // No answer while the hidraw node still works
+                        timed_out = matches!(e, HapticError::CommunicationError);
                         self.handle_disconnect();
                         if let Ok(true) = self.connect() {
                             match self.device.as_mut() {
@@ -816,7 +1181,8 @@ impl HapticManager {
                         }
                     }
                     Err(e) => Err(e),
-                }
+                };
+                self.track_query_timeouts(result, timed_out)
             }
             None => {
                 tracing::debug!("Cannot query battery: device not connected");
@@ -825,11 +1191,54 @@ impl HapticManager {
         }
     }
 
+    /// The connected device's battery request, to run without the manager lock
+    pub fn battery_probe(&mut self) -> Result<BatteryProbe, HapticError> {
+        if self.connection_state == ConnectionState::SwitchedAway {
+            return Err(HapticError::HostSwitchedAway);
+        }
+        match self.device.as_mut() {
+            Some(device) => device.battery_probe(),
+            None => Err(HapticError::DeviceNotFound),
+        }
+    }
+
     /// Check if battery feature is supported
     pub fn battery_supported(&self) -> bool {
         self.device.as_ref().map(|d| d.battery_supported()).unwrap_or(false)
     }
 
+    /// Treat repeated unanswered queries on a multi-host device as a switch
+    ///
+    /// A host switch from the mouse's own button leaves the receiver in
+    /// place but silences the device, without a notification we can rely on.
+    /// A battery feature the device answered Unsupported is dropped.
+    pub(crate) fn track_query_timeouts(
+        &mut self,
+        result: Result<BatteryReading, HapticError>,
+        timed_out: bool,
+    ) -> Result<BatteryReading, HapticError> {
+        if matches!(result, Err(HapticError::NotSupported)) {
+            if let Some(device) = self.device.as_mut() {
+                device.battery_unsupported();
+            }
+        }
+        if result.is_ok() || !timed_out || self.home_host.is_none() {
+            if result.is_ok() {
+                self.consecutive_timeouts = 0;
+                if let Some(device) = self.device.as_mut() {
+                    device.note_contact();
+                }
+            }
+            return result;
+        }
+        self.consecutive_timeouts += 1;
+        if self.consecutive_timeouts >= SWITCHED_AWAY_TIMEOUTS {
+            self.mark_switched_away(None);
+            return Err(HapticError::HostSwitchedAway);
+        }
+        result
+    }
+
     // =========================================================================
     // Easy-Switch Methods (delegated to HidppDevice)
     // =========================================================================
@@ -875,6 +1284,9 @@ impl HapticManager {
                             .unwrap()
                             .as_millis() as u64;
                         self.last_host_switch_ms = now;
+                        if self.home_host.is_some_and(|home| home != host_index) {
+                            self.mark_switched_away(Some(host_index));
+                        }
                         Ok(())
                     }
                     Err(e) => {
@@ -897,6 +1309,9 @@ impl HapticManager {
                                             .unwrap()
                                             .as_millis() as u64;
                                         self.last_host_switch_ms = now;
+                                        if self.home_host.is_some_and(|home| home != host_index) {
+                                            self.mark_switched_away(Some(host_index));
+                                        }
                                     }
                                     result
                                 }
@@ -14,33 +14,47 @@
 //! Uses direct hidraw device access (same approach as battery module).
 //! This is more reliable than hidapi library for Logitech devices.
 
-pub mod constants;
 pub mod device;
 pub mod error;
+pub mod feature_cache;
 pub mod manager;
-pub mod messages;
 pub mod notifications;
 pub mod patterns;
+pub mod request;
 pub mod safety;
+pub mod trace;
+
+// Wire-format types shared with other tools live in juhradial-core
+pub use juhradial_core::hidpp::{constants, messages};
 
 #[cfg(test)]
 mod tests;
 
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::battery::BatteryReading;
 
 // Re-export all public types at the module level for backwards compatibility
 pub use constants::{
-    allowed_features, blocklisted_features, features, product_ids, report_type,
+    allowed_features, blocklisted_features, features, product_ids, read_only_functions, report_type,
     LOGITECH_VENDOR_ID,
 };
 pub use error::HapticError;
-pub use manager::{ConnectionState, HapticManager};
+pub use manager::{ConnectionState, HapticManager, OpenedDevice};
 pub use messages::{ConnectionType, HidppLongMessage, HidppShortMessage};
 pub use patterns::{
-    haptic_profiles, HapticEvent, HapticPattern, HapticPulse, Mx4HapticPattern, PerEventPattern,
+    haptic_profiles, validate_pattern, EventToggles, HapticEvent, HapticPattern, HapticPulse, HapticStep,
+    Mx4HapticPattern, PatternRegistry, PerEventPattern, MAX_PATTERN_STEPS, MAX_PATTERN_TOTAL_MS,
 };
 pub use safety::verify_feature_safety;
 
+/// The HID backend, reported in `Status()`
+///
+/// Haptics, battery, diverts and notifications all go through `/dev/hidraw`
+/// directly; there is no hidapi build, so nothing to select between.
+pub const HID_BACKEND: &str = "hidraw";
+
 /// Shared haptic manager for thread-safe access from D-Bus handlers
 pub type SharedHapticManager = Arc<Mutex<HapticManager>>;
 
@@ -48,3 +62,100 @@ pub type SharedHapticManager = Arc<Mutex<HapticManager>>;
 pub fn new_shared_haptic_manager(config: &crate::config::HapticConfig) -> SharedHapticManager {
     Arc::new(Mutex::new(HapticManager::from_config(config)))
 }
+
+/// Emit a haptic event without waiting out a multi-step pattern
+///
+/// Built-in and single-step patterns play inline. A multi-step custom pattern
+/// plays its first step inline and the rest on a short-lived thread that takes
+/// the manager lock per step, so neither the caller nor other haptic users
+/// block for the gaps. Starting another pattern cancels the remaining steps.
+pub fn emit_shared(manager: &SharedHapticManager, event: HapticEvent) {
+    let started = Instant::now();
+    let (steps, generation) = {
+        let Ok(mut m) = manager.lock() else {
+            tracing::error!("Failed to lock haptic manager");
+            return;
+        };
+        if !m.event_enabled(event) {
+            return;
+        }
+        match m.event_steps(event) {
+            Some(steps) if steps.len() > 1 => {
+                let Some(generation) = m.begin_pattern() else {
+                    return;
+                };
+                if let Err(e) = m.play_step(event, &steps[0]) {
+                    tracing::warn!(error = %e, "Haptic emit failed");
+                }
+                m.latency.record_haptic_send(started.elapsed());
+                (steps, generation)
+            }
+            _ => {
+                if let Err(e) = m.emit(event) {
+                    tracing::warn!(error = %e, "Haptic emit failed");
+                }
+                m.latency.record_haptic_send(started.elapsed());
+                return;
+            }
+        }
+    };
+
+    let manager = manager.clone();
+    std::thread::spawn(move || {
+        for pair in steps.windows(2) {
+            std::thread::sleep(pair[0].delay_to_next());
+            let Ok(mut m) = manager.lock() else {
+                return;
+            };
+            if m.pattern_generation() != generation {
+                tracing::debug!(event = %event, "Haptic pattern superseded");
+                return;
+            }
+            let _ = m.play_step(event, &pair[1]);
+        }
+    });
+}
+
+/// Query the battery without holding the manager lock while the device answers
+///
+/// The lock is only taken to fetch the request and to record the outcome.
+/// Opening the device (slot pings, feature lookup) and the wait for the reply
+/// run unlocked, so a haptic fired meanwhile goes out at once instead of
+/// queueing behind a device that is slow to wake. Otherwise as
+/// `HapticManager::query_battery`: a stale fd or a missing reply reopens the
+/// device and asks once more.
+pub fn query_battery_shared(manager: &SharedHapticManager) -> Result<BatteryReading, HapticError> {
+    let lock = || manager.lock().map_err(|_| HapticError::CommunicationError);
+
+    let (trace, persistence) = {
+        let m = lock()?;
+        (m.trace.clone(), m.persistence.clone())
+    };
+    let mut probe = lock()?.battery_probe();
+    if matches!(probe, Err(HapticError::DeviceNotFound)) {
+        let opened = OpenedDevice::open(&trace, &persistence);
+        let mut m = lock()?;
+        m.attach(opened);
+        probe = m.battery_probe();
+    }
+
+    let mut timed_out = false;
+    let result = match probe.and_then(|mut p| p.query()) {
+        Err(e @ (HapticError::IoError(_) | HapticError::CommunicationError)) => {
+            // No answer while the hidraw node still works
+            timed_out = matches!(e, HapticError::CommunicationError);
+            lock()?.handle_disconnect();
+            let opened = OpenedDevice::open(&trace, &persistence);
+            let mut m = lock()?;
+            if m.attach(opened) {
+                let probe = m.battery_probe();
+                drop(m);
+                probe.and_then(|mut p| p.query())
+            } else {
+                Err(HapticError::DeviceNotFound)
+            }
+        }
+        result => result,
+    };
+    lock()?.track_query_timeouts(result, timed_out)
+}
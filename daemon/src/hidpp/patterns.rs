@@ -1,9 +1,13 @@
 //! Haptic patterns and event types
 //!
 //! MX Master 4 waveform patterns, legacy haptic profiles,
-//! and UX haptic event definitions.
+//! UX haptic event definitions, and user-defined step patterns.
 
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
+
+use crate::config::HapticStepConfig;
 
 /// HID++ haptic intensity levels
 #[derive(Debug, Clone, Copy)]
@@ -170,27 +174,32 @@ impl Mx4HapticPattern {
     /// Create from config name string (snake_case)
     /// Returns SubtleCollision as default if name is not recognized
     pub fn from_name(name: &str) -> Self {
+        Self::try_from_name(name).unwrap_or_else(|| {
+            tracing::warn!(name, "Unknown haptic pattern name, using default");
+            Self::SubtleCollision
+        })
+    }
+
+    /// Create from config name string, `None` if not recognized
+    pub fn try_from_name(name: &str) -> Option<Self> {
         match name {
-            "sharp_state_change" => Self::SharpStateChange,
-            "damp_state_change" => Self::DampStateChange,
-            "sharp_collision" => Self::SharpCollision,
-            "damp_collision" => Self::DampCollision,
-            "subtle_collision" => Self::SubtleCollision,
-            "whisper_collision" => Self::WhisperCollision,
-            "happy_alert" => Self::HappyAlert,
-            "angry_alert" => Self::AngryAlert,
-            "completed" => Self::Completed,
-            "square" => Self::Square,
-            "wave" => Self::Wave,
-            "firework" => Self::Firework,
-            "mad" => Self::Mad,
-            "knock" => Self::Knock,
-            "jingle" => Self::Jingle,
-            "ringing" => Self::Ringing,
-            _ => {
-                tracing::warn!(name, "Unknown haptic pattern name, using default");
-                Self::SubtleCollision
-            }
+            "sharp_state_change" => Some(Self::SharpStateChange),
+            "damp_state_change" => Some(Self::DampStateChange),
+            "sharp_collision" => Some(Self::SharpCollision),
+            "damp_collision" => Some(Self::DampCollision),
+            "subtle_collision" => Some(Self::SubtleCollision),
+            "whisper_collision" => Some(Self::WhisperCollision),
+            "happy_alert" => Some(Self::HappyAlert),
+            "angry_alert" => Some(Self::AngryAlert),
+            "completed" => Some(Self::Completed),
+            "square" => Some(Self::Square),
+            "wave" => Some(Self::Wave),
+            "firework" => Some(Self::Firework),
+            "mad" => Some(Self::Mad),
+            "knock" => Some(Self::Knock),
+            "jingle" => Some(Self::Jingle),
+            "ringing" => Some(Self::Ringing),
+            _ => None,
         }
     }
 }
@@ -202,7 +211,7 @@ impl fmt::Display for Mx4HapticPattern {
 }
 
 /// UX haptic events triggered during menu interaction
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HapticEvent {
     /// Radial menu appears on screen
     MenuAppear,
@@ -264,6 +273,16 @@ impl HapticEvent {
     }
 }
 
+impl HapticEvent {
+    /// All events, in config order
+    pub const ALL: [HapticEvent; 4] = [
+        HapticEvent::MenuAppear,
+        HapticEvent::SliceChange,
+        HapticEvent::SelectionConfirm,
+        HapticEvent::InvalidAction,
+    ];
+}
+
 impl fmt::Display for HapticEvent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -309,5 +328,197 @@ impl PerEventPattern {
             HapticEvent::InvalidAction => self.invalid,
         }
     }
+
+    /// Set pattern for a specific event
+    pub fn set(&mut self, event: &HapticEvent, pattern: Mx4HapticPattern) {
+        match event {
+            HapticEvent::MenuAppear => self.menu_appear = pattern,
+            HapticEvent::SliceChange => self.slice_change = pattern,
+            HapticEvent::SelectionConfirm => self.confirm = pattern,
+            HapticEvent::InvalidAction => self.invalid = pattern,
+        }
+    }
+}
+
+/// Per-event on/off switches (`haptics.per_event.*_enabled`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventToggles {
+    pub menu_appear: bool,
+    pub slice_change: bool,
+    pub confirm: bool,
+    pub invalid: bool,
+}
+
+impl Default for EventToggles {
+    fn default() -> Self {
+        Self {
+            menu_appear: true,
+            slice_change: true,
+            confirm: true,
+            invalid: true,
+        }
+    }
+}
+
+impl EventToggles {
+    /// Read the switches from the per-event config
+    pub fn from_config(config: &crate::config::HapticEventConfig) -> Self {
+        Self {
+            menu_appear: config.menu_appear_enabled,
+            slice_change: config.slice_change_enabled,
+            confirm: config.confirm_enabled,
+            invalid: config.invalid_enabled,
+        }
+    }
+
+    /// Whether a specific event should play
+    pub fn is_enabled(&self, event: &HapticEvent) -> bool {
+        match event {
+            HapticEvent::MenuAppear => self.menu_appear,
+            HapticEvent::SliceChange => self.slice_change,
+            HapticEvent::SelectionConfirm => self.confirm,
+            HapticEvent::InvalidAction => self.invalid,
+        }
+    }
+}
+
+
+// ============================================================================
+// Custom step patterns
+// ============================================================================
+
+/// Most steps a custom pattern may have
+pub const MAX_PATTERN_STEPS: usize = 6;
+
+/// Longest a custom pattern may run, pulses plus gaps (milliseconds)
+pub const MAX_PATTERN_TOTAL_MS: u32 = 500;
+
+/// Largest accepted `intensity_scale`
+const MAX_INTENSITY_SCALE: f32 = 2.0;
+
+/// One validated step of a custom pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HapticStep {
+    /// Multiplier on the legacy pulse intensity
+    pub intensity_scale: f32,
+    /// Pulse length in milliseconds
+    pub duration_ms: u16,
+    /// Pause before the next step in milliseconds
+    pub gap_after_ms: u16,
+    /// MX4 waveform override (`None` = the event's built-in waveform)
+    pub waveform: Option<Mx4HapticPattern>,
 }
 
+impl HapticStep {
+    /// Delay from the start of this step to the start of the next
+    pub fn delay_to_next(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(u64::from(self.duration_ms) + u64::from(self.gap_after_ms))
+    }
+}
+
+/// Validate a custom pattern definition against the step and duration caps
+pub fn validate_pattern(steps: &[HapticStepConfig]) -> Result<Vec<HapticStep>, String> {
+    if steps.is_empty() {
+        return Err("pattern has no steps".to_string());
+    }
+    if steps.len() > MAX_PATTERN_STEPS {
+        return Err(format!("{} steps exceeds the maximum of {}", steps.len(), MAX_PATTERN_STEPS));
+    }
+
+    let mut validated = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        if !(0.0..=MAX_INTENSITY_SCALE).contains(&step.intensity_scale) {
+            return Err(format!(
+                "step {}: intensity_scale {} outside 0.0-{}",
+                i, step.intensity_scale, MAX_INTENSITY_SCALE
+            ));
+        }
+        if step.duration_ms == 0 {
+            return Err(format!("step {}: duration_ms must be positive", i));
+        }
+        let waveform = match step.waveform {
+            Some(ref name) => Some(
+                Mx4HapticPattern::try_from_name(name)
+                    .ok_or_else(|| format!("step {}: unknown waveform '{}'", i, name))?,
+            ),
+            None => None,
+        };
+        validated.push(HapticStep {
+            intensity_scale: step.intensity_scale,
+            duration_ms: step.duration_ms,
+            gap_after_ms: step.gap_after_ms,
+            waveform,
+        });
+    }
+
+    // The last step's gap is never waited out, so it does not count
+    let total: u32 = validated
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let gap = if i + 1 < validated.len() { s.gap_after_ms } else { 0 };
+            u32::from(s.duration_ms) + u32::from(gap)
+        })
+        .sum();
+    if total > MAX_PATTERN_TOTAL_MS {
+        return Err(format!("total {}ms exceeds the maximum of {}ms", total, MAX_PATTERN_TOTAL_MS));
+    }
+    Ok(validated)
+}
+
+/// Step patterns selected per event
+///
+/// Built from `haptics.patterns` and the `haptics.per_event` names that refer
+/// to them. Events without an entry keep their waveform from `PerEventPattern`.
+#[derive(Debug, Clone, Default)]
+pub struct PatternRegistry {
+    events: HashMap<HapticEvent, Arc<[HapticStep]>>,
+}
+
+impl PatternRegistry {
+    /// Resolve configured patterns and mappings
+    ///
+    /// Returns the registry and the waveform mapping for events that use a
+    /// built-in waveform. An invalid pattern is dropped with a warning and the
+    /// events naming it fall back to their default waveform.
+    pub fn from_config(config: &crate::config::HapticConfig) -> (Self, PerEventPattern) {
+        let mut patterns: HashMap<&str, Option<Arc<[HapticStep]>>> = HashMap::new();
+        for (name, steps) in &config.patterns {
+            let resolved = match validate_pattern(steps) {
+                Ok(steps) => Some(Arc::from(steps)),
+                Err(reason) => {
+                    tracing::warn!(pattern = %name, %reason, "Invalid haptic pattern, using defaults");
+                    None
+                }
+            };
+            patterns.insert(name.as_str(), resolved);
+        }
+
+        let defaults = PerEventPattern::default();
+        let mut per_event = defaults;
+        let mut events = HashMap::new();
+        for event in HapticEvent::ALL {
+            let name = match event {
+                HapticEvent::MenuAppear => &config.per_event.menu_appear,
+                HapticEvent::SliceChange => &config.per_event.slice_change,
+                HapticEvent::SelectionConfirm => &config.per_event.confirm,
+                HapticEvent::InvalidAction => &config.per_event.invalid,
+            };
+            let waveform = match patterns.get(name.as_str()) {
+                Some(Some(steps)) => {
+                    events.insert(event, steps.clone());
+                    defaults.get(&event)
+                }
+                Some(None) => defaults.get(&event),
+                None => Mx4HapticPattern::from_name(name),
+            };
+            per_event.set(&event, waveform);
+        }
+        (Self { events }, per_event)
+    }
+
+    /// Custom steps for an event, if it uses one
+    pub fn get(&self, event: &HapticEvent) -> Option<Arc<[HapticStep]>> {
+        self.events.get(event).cloned()
+    }
+}
@@ -0,0 +1,754 @@
+//! HID++ request/response correlation
+//!
+//! Every request carries a software ID in the low nibble of byte 3, and the
+//! device echoes it back. `SwIdCounter` rotates it through 0x01-0x0F (0 marks
+//! device notifications), so a late reply to a request that already timed out
+//! cannot be taken for the answer to the next one.
+//!
+//! `exchange` accepts only a reply or an error report whose device index,
+//! feature index, function and software ID all match the request. Anything
+//! else read from the fd (notifications, mouse reports, stale replies) is
+//! discarded, and the overall timeout still applies. An error report fails the
+//! request at once as a [`HidppError`] with its decoded [`ErrorCode`].
+//! `exchange_read` retries timeouts and Busy errors with a fresh software ID;
+//! it is for idempotent reads (ping, feature lookup, battery) only, never for
+//! haptics or setters.
+//!
+//! Both pass every report they write or read to `trace`, which records them
+//! while the HID++ trace is on.
+
+use std::io::{ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+
+use super::constants::report_type;
+use super::trace::HidppTrace;
+
+/// Default time to wait for a reply
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sleep between reads while nothing is pending
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Retries after a timeout or a Busy error in `exchange_read`
+pub const READ_RETRIES: u32 = 2;
+
+/// Wait before asking a device that answered Busy again
+const BUSY_BACKOFF: Duration = Duration::from_millis(50);
+
+/// HID++ 2.0 error report marker (byte 2)
+const ERROR_2_0: u8 = 0xFF;
+
+/// HID++ 1.0 error report marker (byte 2)
+const ERROR_1_0: u8 = 0x8F;
+
+/// Rotating software ID, 0x01-0x0F
+#[derive(Debug, Default)]
+pub struct SwIdCounter(u8);
+
+impl SwIdCounter {
+    pub const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Software ID for the next request
+    pub fn next_id(&mut self) -> u8 {
+        self.0 = self.0 % 0x0F + 1;
+        self.0
+    }
+
+    /// A counter that hands out the next `n` IDs, which this one then skips
+    ///
+    /// For a request sent on another handle to the same device.
+    pub fn reserve(&mut self, n: u32) -> SwIdCounter {
+        let reserved = SwIdCounter(self.0);
+        for _ in 0..n {
+            self.next_id();
+        }
+        reserved
+    }
+}
+
+/// One HID++ request
+#[derive(Debug, Clone, Copy)]
+pub struct Request<'a> {
+    /// Long (20-byte) report instead of short (7-byte)
+    pub long: bool,
+    pub device_index: u8,
+    pub feature_index: u8,
+    pub function: u8,
+    /// Truncated to 3 bytes (short) or 16 bytes (long)
+    pub params: &'a [u8],
+}
+
+impl Request<'_> {
+    /// Wire bytes with `sw_id`
+    pub fn encode(&self, sw_id: u8) -> Vec<u8> {
+        let (report, len, max_params) = if self.long {
+            (report_type::LONG, 20, 16)
+        } else {
+            (report_type::SHORT, 7, 3)
+        };
+        let mut bytes = vec![0u8; len];
+        bytes[0] = report;
+        bytes[1] = self.device_index;
+        bytes[2] = self.feature_index;
+        bytes[3] = self.fn_sw(sw_id);
+        let param_len = self.params.len().min(max_params);
+        bytes[4..4 + param_len].copy_from_slice(&self.params[..param_len]);
+        bytes
+    }
+
+    fn fn_sw(&self, sw_id: u8) -> u8 {
+        (self.function << 4) | (sw_id & 0x0F)
+    }
+}
+
+/// How a packet read from the device relates to a pending request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    /// The reply to this request
+    Matched,
+    /// An error report for this request
+    Error(HidppError),
+    /// Anything else: notification, input report, reply to another request
+    Unrelated,
+}
+
+/// Correlate a packet with the request sent with `sw_id`
+pub fn classify(packet: &[u8], request: &Request, sw_id: u8) -> Reply {
+    if packet.len() < 7
+        || !(packet[0] == report_type::SHORT || packet[0] == report_type::LONG)
+        || packet[1] != request.device_index
+    {
+        return Reply::Unrelated;
+    }
+    if packet[2] == request.feature_index && packet[3] == request.fn_sw(sw_id) {
+        return Reply::Matched;
+    }
+    match HidppError::parse(packet) {
+        Some(error)
+            if error.feature_index == request.feature_index
+                && error.function == request.function
+                && error.sw_id == sw_id & 0x0F =>
+        {
+            Reply::Error(error)
+        }
+        _ => Reply::Unrelated,
+    }
+}
+
+/// What an error report says went wrong
+///
+/// HID++ 2.0 codes as the spec numbers them; the HID++ 1.0 codes receivers
+/// answer with map onto the same names where they mean the same thing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    Unknown,
+    InvalidArgument,
+    OutOfRange,
+    HardwareError,
+    LogitechInternal,
+    InvalidFeatureIndex,
+    InvalidFunctionId,
+    /// Try again later
+    Busy,
+    /// The device does not implement the function
+    Unsupported,
+    // HID++ 1.0 only
+    InvalidSubId,
+    InvalidAddress,
+    ConnectFail,
+    TooManyDevices,
+    AlreadyExists,
+    UnknownDevice,
+    ResourceError,
+    RequestUnavailable,
+    WrongPinCode,
+    /// A code the spec does not assign (0 is "no error")
+    Other(u8),
+}
+
+impl ErrorCode {
+    /// A code from a HID++ 2.0 error report
+    pub fn from_hidpp20(code: u8) -> Self {
+        match code {
+            0x01 => Self::Unknown,
+            0x02 => Self::InvalidArgument,
+            0x03 => Self::OutOfRange,
+            0x04 => Self::HardwareError,
+            0x05 => Self::LogitechInternal,
+            0x06 => Self::InvalidFeatureIndex,
+            0x07 => Self::InvalidFunctionId,
+            0x08 => Self::Busy,
+            0x09 => Self::Unsupported,
+            other => Self::Other(other),
+        }
+    }
+
+    /// A code from a HID++ 1.0 error report
+    pub fn from_hidpp10(code: u8) -> Self {
+        match code {
+            0x01 => Self::InvalidSubId,
+            0x02 => Self::InvalidAddress,
+            0x03 | 0x0B => Self::InvalidArgument,
+            0x04 => Self::ConnectFail,
+            0x05 => Self::TooManyDevices,
+            0x06 => Self::AlreadyExists,
+            0x07 => Self::Busy,
+            0x08 => Self::UnknownDevice,
+            0x09 => Self::ResourceError,
+            0x0A => Self::RequestUnavailable,
+            0x0C => Self::WrongPinCode,
+            other => Self::Other(other),
+        }
+    }
+
+    /// Decode a HID++ 1.0 (`legacy`) or 2.0 error code
+    pub fn decode(legacy: bool, code: u8) -> Self {
+        if legacy {
+            Self::from_hidpp10(code)
+        } else {
+            Self::from_hidpp20(code)
+        }
+    }
+
+    /// Readable name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown",
+            Self::InvalidArgument => "Invalid argument",
+            Self::OutOfRange => "Out of range",
+            Self::HardwareError => "Hardware error",
+            Self::LogitechInternal => "Logitech internal",
+            Self::InvalidFeatureIndex => "Invalid feature index",
+            Self::InvalidFunctionId => "Invalid function",
+            Self::Busy => "Busy",
+            Self::Unsupported => "Unsupported",
+            Self::InvalidSubId => "Invalid sub ID",
+            Self::InvalidAddress => "Invalid address",
+            Self::ConnectFail => "Connection failed",
+            Self::TooManyDevices => "Too many devices",
+            Self::AlreadyExists => "Already exists",
+            Self::UnknownDevice => "Unknown device",
+            Self::ResourceError => "Resource error",
+            Self::RequestUnavailable => "Request unavailable",
+            Self::WrongPinCode => "Wrong PIN code",
+            Self::Other(_) => "Unassigned error",
+        }
+    }
+}
+
+/// A decoded error report
+///
+/// Short and long reports share the layout `[type, device, marker, feature,
+/// fn_sw, code]`: the marker is 0xFF for HID++ 2.0 and 0x8F for HID++ 1.0,
+/// and the two bytes after it echo the failed request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "HID++ {} error 0x{raw_code:02X} ({}) for feature index 0x{feature_index:02X} function {function}",
+    if *.legacy { "1.0" } else { "2.0" },
+    ErrorCode::decode(*.legacy, *.raw_code).name()
+)]
+pub struct HidppError {
+    /// HID++ 1.0 report (receivers, empty slots)
+    pub legacy: bool,
+    pub device_index: u8,
+    /// Feature index (HID++ 1.0: sub ID) of the failed request
+    pub feature_index: u8,
+    pub function: u8,
+    pub sw_id: u8,
+    /// Code as sent, for codes the enum folds together
+    pub raw_code: u8,
+}
+
+impl HidppError {
+    /// Decode `packet` if it is an error report, short or long
+    pub fn parse(packet: &[u8]) -> Option<Self> {
+        if packet.len() < 7 || !(packet[0] == report_type::SHORT || packet[0] == report_type::LONG) {
+            return None;
+        }
+        let legacy = match packet[2] {
+            ERROR_2_0 => false,
+            ERROR_1_0 => true,
+            _ => return None,
+        };
+        Some(Self {
+            legacy,
+            device_index: packet[1],
+            feature_index: packet[3],
+            function: packet[4] >> 4,
+            sw_id: packet[4] & 0x0F,
+            raw_code: packet[5],
+        })
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::decode(self.legacy, self.raw_code)
+    }
+}
+
+/// Why a request got no reply
+#[derive(Debug, thiserror::Error)]
+pub enum RequestError {
+    /// Nothing matching arrived in time
+    #[error("HID++ request timed out")]
+    Timeout,
+    /// The device answered with an error report
+    #[error(transparent)]
+    ErrorReply(HidppError),
+    /// Reading or writing the device failed
+    #[error("I/O error: {0}")]
+    Io(#[source] std::io::Error),
+}
+
+impl RequestError {
+    /// The error report's code, if the device sent one
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::ErrorReply(error) => Some(error.code()),
+            _ => None,
+        }
+    }
+}
+
+/// A HID++ channel: a non-blocking hidraw fd, or a scripted one in tests
+pub trait Transport: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Transport for T {}
+
+/// Most packets `drain` discards, so a chatty fd cannot hold it forever
+const MAX_DRAIN_PACKETS: usize = 64;
+
+/// Discard whatever is already queued on a non-blocking fd
+pub fn drain<T: Read>(io: &mut T, trace: &HidppTrace) {
+    let mut buf = [0u8; 64];
+    for _ in 0..MAX_DRAIN_PACKETS {
+        match io.read(&mut buf) {
+            Ok(len) if len > 0 => trace.incoming(&buf[..len]),
+            _ => break,
+        }
+    }
+}
+
+/// Send `request` with `sw_id` and wait up to `timeout` for its reply
+///
+/// `io` must be non-blocking. Unrelated packets are skipped without sleeping,
+/// so a burst of notifications cannot stretch the wait.
+pub fn exchange<T: Read + Write>(
+    io: &mut T,
+    trace: &HidppTrace,
+    request: &Request,
+    sw_id: u8,
+    timeout: Duration,
+) -> Result<Vec<u8>, RequestError> {
+    drain(io, trace);
+    let bytes = request.encode(sw_id);
+    tracing::debug!(
+        feature_index = request.feature_index,
+        function = request.function,
+        sw_id,
+        "Sending HID++ request: {:02X?}",
+        &bytes
+    );
+    io.write_all(&bytes).map_err(RequestError::Io)?;
+    trace.outgoing(&bytes);
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0u8; 64];
+    loop {
+        match io.read(&mut buf) {
+            Ok(0) => std::thread::sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))),
+            Ok(len) => {
+                trace.incoming(&buf[..len]);
+                match classify(&buf[..len], request, sw_id) {
+                    Reply::Matched => return Ok(buf[..len].to_vec()),
+                    Reply::Error(error) => return Err(RequestError::ErrorReply(error)),
+                    Reply::Unrelated => {
+                        tracing::trace!(sw_id, "Skipping unrelated HID++ packet: {:02X?}", &buf[..len.min(20)]);
+                    }
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now())));
+            }
+            Err(e) => return Err(RequestError::Io(e)),
+        }
+        if Instant::now() >= deadline {
+            return Err(RequestError::Timeout);
+        }
+    }
+}
+
+/// `exchange` for idempotent reads: a timeout or a Busy error is retried up
+/// to `READ_RETRIES` times, each with a fresh software ID
+pub fn exchange_read<T: Read + Write>(
+    io: &mut T,
+    trace: &HidppTrace,
+    request: &Request,
+    sw_ids: &mut SwIdCounter,
+    timeout: Duration,
+) -> Result<Vec<u8>, RequestError> {
+    let mut retries = 0;
+    loop {
+        match exchange(io, trace, request, sw_ids.next_id(), timeout) {
+            Err(RequestError::Timeout) if retries < READ_RETRIES => {
+                retries += 1;
+                tracing::debug!(
+                    feature_index = request.feature_index,
+                    function = request.function,
+                    retry = retries,
+                    "HID++ read timed out; retrying"
+                );
+            }
+            Err(e) if e.code() == Some(ErrorCode::Busy) && retries < READ_RETRIES => {
+                retries += 1;
+                tracing::debug!(
+                    feature_index = request.feature_index,
+                    function = request.function,
+                    retry = retries,
+                    "HID++ device busy; retrying"
+                );
+                std::thread::sleep(BUSY_BACKOFF);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// In-memory transport for tests here and in the battery module
+#[cfg(test)]
+pub(crate) mod mock {
+    use std::collections::VecDeque;
+    use std::io::{ErrorKind, Read, Write};
+
+    use crate::hidpp::trace::Direction;
+
+    /// Non-blocking transport: each write queues the next batch of packets
+    #[derive(Default)]
+    pub(crate) struct MockTransport {
+        pub(crate) batches: VecDeque<Vec<Vec<u8>>>,
+        pub(crate) inbox: VecDeque<Vec<u8>>,
+        /// Packet repeated forever once the inbox is empty
+        pub(crate) flood: Option<Vec<u8>>,
+        pub(crate) writes: Vec<Vec<u8>>,
+        /// Recorded writes a replayed trace expects, in order
+        pub(crate) expected: VecDeque<Vec<u8>>,
+    }
+
+    impl MockTransport {
+        /// Script the exchanges of a HID++ trace (`hidpp::trace` format)
+        ///
+        /// Each `>` line is one expected write; the `<` lines after it are the
+        /// batch that write releases, and any before the first write start in
+        /// the inbox. A write must match its recorded report apart from the
+        /// software id (else it fails with `InvalidData`), and replies to it are given the id actually sent, so
+        /// a trace from a long-running daemon replays against a fresh counter.
+        pub(crate) fn replay(trace: &str) -> Self {
+            let mut io = Self::default();
+            for (number, line) in trace.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let mut fields = line.split_whitespace().skip(1);
+                let direction = fields.next().and_then(Direction::from_name);
+                let bytes: Result<Vec<u8>, _> = fields.map(|b| u8::from_str_radix(b, 16)).collect();
+                match (direction, bytes) {
+                    (Some(Direction::Out), Ok(bytes)) => {
+                        io.expected.push_back(bytes);
+                        io.batches.push_back(Vec::new());
+                    }
+                    (Some(Direction::In), Ok(bytes)) => match io.batches.back_mut() {
+                        Some(batch) => batch.push(bytes),
+                        None => io.inbox.push_back(bytes),
+                    },
+                    _ => panic!("trace line {}: cannot parse {:?}", number + 1, line),
+                }
+            }
+            io
+        }
+
+        /// Whether every recorded write of a replayed trace was made
+        pub(crate) fn replayed(&self) -> bool {
+            self.expected.is_empty()
+        }
+    }
+
+    /// Byte 3 with the software id (low nibble) masked off
+    fn same_request(recorded: &[u8], written: &[u8]) -> bool {
+        recorded.len() == written.len()
+            && recorded.iter().zip(written).enumerate().all(|(i, (r, w))| if i == 3 { r & 0xF0 == w & 0xF0 } else { r == w })
+    }
+
+    /// Move a recorded reply (or error report) onto the software id sent
+    fn resync_sw_id(packet: &mut [u8], recorded: &[u8], written: &[u8]) {
+        if packet.len() < 7 || recorded.len() < 4 || packet[1] != recorded[1] {
+            return;
+        }
+        if packet[2] == recorded[2] && packet[3] == recorded[3] {
+            packet[3] = written[3];
+        } else if packet[3] == recorded[2] && packet[4] == recorded[3] {
+            packet[4] = written[3];
+        }
+    }
+
+    impl Read for MockTransport {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let packet = match self.inbox.pop_front().or_else(|| self.flood.clone()) {
+                Some(packet) => packet,
+                None => return Err(ErrorKind::WouldBlock.into()),
+            };
+            buf[..packet.len()].copy_from_slice(&packet);
+            Ok(packet.len())
+        }
+    }
+
+    impl Write for MockTransport {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes.push(buf.to_vec());
+            let mut batch = self.batches.pop_front().unwrap_or_default();
+            if let Some(recorded) = self.expected.pop_front() {
+                if !same_request(&recorded, buf) {
+                    let message = format!("trace expected {:02X?}, got {:02X?}", recorded, buf);
+                    return Err(std::io::Error::new(ErrorKind::InvalidData, message));
+                }
+                for packet in &mut batch {
+                    resync_sw_id(packet, &recorded, buf);
+                }
+            }
+            self.inbox.extend(batch);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::mock::MockTransport;
+
+    const PING: Request = Request { long: false, device_index: 0x02, feature_index: 0x00, function: 0x01, params: &[0, 0, 0xAA] };
+
+    fn packet(feature: u8, fn_sw: u8, payload: [u8; 3]) -> Vec<u8> {
+        vec![report_type::SHORT, 0x02, feature, fn_sw, payload[0], payload[1], payload[2]]
+    }
+
+    #[test]
+    fn test_sw_id_rotates_and_skips_zero() {
+        let mut ids = SwIdCounter::new();
+        let seen: Vec<u8> = (0..16).map(|_| ids.next_id()).collect();
+        assert_eq!(seen[0], 0x01);
+        assert_eq!(seen[14], 0x0F);
+        assert_eq!(seen[15], 0x01);
+        assert!(!seen.contains(&0));
+    }
+
+    #[test]
+    fn test_reserved_ids_are_skipped() {
+        let mut ids = SwIdCounter::new();
+        ids.next_id();
+        let mut reserved = ids.reserve(3);
+        let taken: Vec<u8> = (0..3).map(|_| reserved.next_id()).collect();
+        assert_eq!(taken, [0x02, 0x03, 0x04]);
+        assert_eq!(ids.next_id(), 0x05);
+    }
+
+    #[test]
+    fn test_stale_reply_then_correct_reply() {
+        let mut io = MockTransport::default();
+        io.batches.push_back(vec![
+            // Late reply to an earlier ping (sw 1) carrying battery-like bytes
+            packet(0x00, 0x11, [0x04, 0x50, 0x00]),
+            // Spontaneous notification (sw 0)
+            packet(0x00, 0x10, [0x00, 0x00, 0xAA]),
+            // Same request on another device index
+            vec![report_type::SHORT, 0x03, 0x00, 0x12, 0x04, 0x05, 0xAA],
+            packet(0x00, 0x12, [0x04, 0x05, 0xAA]),
+        ]);
+        let reply = exchange(&mut io, &HidppTrace::default(), &PING, 0x02, Duration::from_millis(100)).unwrap();
+        assert_eq!(reply, packet(0x00, 0x12, [0x04, 0x05, 0xAA]));
+        assert_eq!(io.writes, [PING.encode(0x02)]);
+    }
+
+    #[test]
+    fn test_only_matching_error_report_fails_the_request() {
+        let mut io = MockTransport::default();
+        io.batches.push_back(vec![
+            // Error for another request's sw_id: skipped
+            vec![report_type::SHORT, 0x02, 0xFF, 0x00, 0x11, 0x02, 0x00],
+            // Mouse report with 0xFF in byte 2 on another report type
+            vec![0x02, 0x02, 0xFF, 0x00, 0x13, 0x05, 0x00],
+            vec![report_type::SHORT, 0x02, 0xFF, 0x00, 0x13, 0x05, 0x00],
+        ]);
+        let result = exchange(&mut io, &HidppTrace::default(), &PING, 0x03, Duration::from_millis(100));
+        let Err(RequestError::ErrorReply(error)) = result else {
+            panic!("expected an error reply, got {result:?}");
+        };
+        assert_eq!(error.code(), ErrorCode::LogitechInternal);
+        assert_eq!((error.feature_index, error.function, error.sw_id), (0x00, 0x01, 0x03));
+    }
+
+    #[test]
+    fn test_unrelated_flood_still_times_out() {
+        let mut io = MockTransport { flood: Some(packet(0x04, 0x00, [1, 2, 3])), ..Default::default() };
+        let started = Instant::now();
+        let result = exchange(&mut io, &HidppTrace::default(), &PING, 0x01, Duration::from_millis(30));
+        assert!(matches!(result, Err(RequestError::Timeout)));
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_read_retries_timeouts_with_fresh_sw_ids() {
+        let mut io = MockTransport::default();
+        io.batches.push_back(vec![]);
+        // The first attempt's reply arrives late, during the retry
+        io.batches.push_back(vec![packet(0x00, 0x11, [0x04, 0x05, 0xAA]), packet(0x00, 0x12, [0x04, 0x05, 0xAA])]);
+        let mut ids = SwIdCounter::new();
+        let reply = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut ids, Duration::from_millis(20)).unwrap();
+        assert_eq!(reply[3], 0x12);
+        assert_eq!(io.writes, [PING.encode(0x01), PING.encode(0x02)]);
+
+        let mut io = MockTransport::default();
+        let result = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut ids, Duration::from_millis(10));
+        assert!(matches!(result, Err(RequestError::Timeout)));
+        assert_eq!(io.writes.len(), 1 + READ_RETRIES as usize);
+    }
+
+    #[test]
+    fn test_replayed_trace_follows_the_sent_sw_ids() {
+        let trace = "\
+# recorded with sw ids 0x0B and 0x0C
+1000 < 10 02 00 00 00 00 00
+1001 > 10 02 00 1b 00 00 aa
+1009 < 10 02 00 1b 04 05 aa
+1010 > 10 02 00 1c 00 00 aa
+1015 < 10 02 ff 00 1c 05 00
+";
+        let mut io = MockTransport::replay(trace);
+        assert_eq!(io.inbox.len(), 1);
+        let mut ids = SwIdCounter::new();
+        let reply = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut ids, Duration::from_millis(20)).unwrap();
+        assert_eq!(reply, packet(0x00, 0x11, [0x04, 0x05, 0xAA]));
+        let result = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut ids, Duration::from_millis(20));
+        assert!(matches!(result, Err(RequestError::ErrorReply(e)) if e.raw_code == 0x05));
+        assert!(io.replayed());
+    }
+
+    #[test]
+    fn test_replay_rejects_a_different_request() {
+        let mut io = MockTransport::replay("1 > 10 02 00 00 10 04 00\n");
+        let result = exchange(&mut io, &HidppTrace::default(), &PING, 0x01, Duration::from_millis(10));
+        assert!(matches!(result, Err(RequestError::Io(e)) if e.to_string().starts_with("trace expected")));
+    }
+
+    #[test]
+    fn test_error_reply_is_not_retried() {
+        let mut io = MockTransport::default();
+        io.batches.push_back(vec![vec![report_type::SHORT, 0x02, 0x8F, 0x00, 0x11, 0x09, 0x00]]);
+        let result = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut SwIdCounter::new(), Duration::from_millis(20));
+        assert!(matches!(result, Err(RequestError::ErrorReply(e)) if e.legacy && e.code() == ErrorCode::ResourceError));
+        assert_eq!(io.writes.len(), 1);
+    }
+
+    /// The error report a device sends for PING with `sw_id`
+    fn error_report(marker: u8, sw_id: u8, code: u8) -> Vec<u8> {
+        vec![report_type::SHORT, 0x02, marker, 0x00, 0x10 | sw_id, code, 0x00]
+    }
+
+    #[test]
+    fn test_each_hidpp20_code_is_decoded() {
+        let codes = [
+            (0x01, ErrorCode::Unknown),
+            (0x02, ErrorCode::InvalidArgument),
+            (0x03, ErrorCode::OutOfRange),
+            (0x04, ErrorCode::HardwareError),
+            (0x05, ErrorCode::LogitechInternal),
+            (0x06, ErrorCode::InvalidFeatureIndex),
+            (0x07, ErrorCode::InvalidFunctionId),
+            (0x08, ErrorCode::Busy),
+            (0x09, ErrorCode::Unsupported),
+            (0x0A, ErrorCode::Other(0x0A)),
+        ];
+        for (raw, code) in codes {
+            let mut io = MockTransport::default();
+            io.batches.push_back(vec![error_report(ERROR_2_0, 0x04, raw)]);
+            let result = exchange(&mut io, &HidppTrace::default(), &PING, 0x04, Duration::from_millis(20));
+            let Err(RequestError::ErrorReply(error)) = result else {
+                panic!("code 0x{raw:02X}: expected an error reply, got {result:?}");
+            };
+            assert!(!error.legacy);
+            assert_eq!((error.code(), error.raw_code), (code, raw));
+            assert!(error.to_string().contains(code.name()));
+        }
+    }
+
+    #[test]
+    fn test_each_hidpp10_code_is_decoded() {
+        let codes = [
+            (0x01, ErrorCode::InvalidSubId),
+            (0x02, ErrorCode::InvalidAddress),
+            (0x03, ErrorCode::InvalidArgument),
+            (0x04, ErrorCode::ConnectFail),
+            (0x05, ErrorCode::TooManyDevices),
+            (0x06, ErrorCode::AlreadyExists),
+            (0x07, ErrorCode::Busy),
+            (0x08, ErrorCode::UnknownDevice),
+            (0x09, ErrorCode::ResourceError),
+            (0x0A, ErrorCode::RequestUnavailable),
+            (0x0B, ErrorCode::InvalidArgument),
+            (0x0C, ErrorCode::WrongPinCode),
+        ];
+        for (raw, code) in codes {
+            let mut io = MockTransport::default();
+            io.batches.push_back(vec![error_report(ERROR_1_0, 0x04, raw)]);
+            let result = exchange(&mut io, &HidppTrace::default(), &PING, 0x04, Duration::from_millis(20));
+            assert!(
+                matches!(result, Err(RequestError::ErrorReply(e)) if e.legacy && e.code() == code),
+                "code 0x{raw:02X}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_error_report_is_decoded() {
+        let mut long = vec![0u8; 20];
+        long[..6].copy_from_slice(&[report_type::LONG, 0x02, ERROR_2_0, 0x00, 0x15, 0x09]);
+        let error = HidppError::parse(&long).unwrap();
+        assert_eq!((error.feature_index, error.function, error.sw_id), (0x00, 0x01, 0x05));
+        assert_eq!(error.code(), ErrorCode::Unsupported);
+        // Neither marker: a reply, not an error
+        assert_eq!(HidppError::parse(&packet(0x00, 0x15, [0x09, 0, 0])), None);
+    }
+
+    #[test]
+    fn test_busy_is_retried_with_fresh_sw_ids() {
+        let mut io = MockTransport::default();
+        io.batches.push_back(vec![error_report(ERROR_2_0, 0x01, 0x08)]);
+        io.batches.push_back(vec![packet(0x00, 0x12, [0x04, 0x05, 0xAA])]);
+        let mut ids = SwIdCounter::new();
+        let reply = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut ids, Duration::from_millis(20)).unwrap();
+        assert_eq!(reply[3], 0x12);
+
+        // A device that stays busy fails after the same bounded retries
+        let mut io = MockTransport::default();
+        for sw_id in 0x03..0x03 + 1 + READ_RETRIES as u8 {
+            io.batches.push_back(vec![error_report(ERROR_1_0, sw_id, 0x07)]);
+        }
+        let result = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut ids, Duration::from_millis(20));
+        assert!(matches!(result, Err(RequestError::ErrorReply(e)) if e.code() == ErrorCode::Busy));
+        assert_eq!(io.writes.len(), 1 + READ_RETRIES as usize);
+    }
+
+    #[test]
+    fn test_unsupported_is_not_retried() {
+        let mut io = MockTransport::default();
+        io.batches.push_back(vec![error_report(ERROR_2_0, 0x01, 0x09)]);
+        let result = exchange_read(&mut io, &HidppTrace::default(), &PING, &mut SwIdCounter::new(), Duration::from_millis(20));
+        assert_eq!(result.unwrap_err().code(), Some(ErrorCode::Unsupported));
+        assert_eq!(io.writes.len(), 1);
+    }
+}
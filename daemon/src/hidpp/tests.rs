@@ -32,54 +32,6 @@ fn test_enabled_haptics_no_device() {
     assert!(manager.pulse(haptic_profiles::CONFIRM).is_ok());
 }
 
-#[test]
-fn test_short_message_construction() {
-    let msg = HidppShortMessage::new(0xFF, 0x00, 0x01, 0x05).with_params([0xAA, 0xBB, 0xCC]);
-
-    let bytes = msg.to_bytes();
-    assert_eq!(bytes[0], 0x10); // Short report type
-    assert_eq!(bytes[1], 0xFF); // Device index
-    assert_eq!(bytes[2], 0x00); // Feature index
-    assert_eq!(bytes[3], 0x15); // Function 1, SW ID 5
-    assert_eq!(bytes[4], 0xAA);
-    assert_eq!(bytes[5], 0xBB);
-    assert_eq!(bytes[6], 0xCC);
-}
-
-#[test]
-fn test_short_message_parsing() {
-    let bytes = [0x10, 0xFF, 0x00, 0x15, 0xAA, 0xBB, 0xCC];
-    let msg = HidppShortMessage::from_bytes(&bytes).unwrap();
-
-    assert_eq!(msg.device_index, 0xFF);
-    assert_eq!(msg.feature_index, 0x00);
-    assert_eq!(msg.function_id(), 0x01);
-    assert_eq!(msg.sw_id(), 0x05);
-    assert_eq!(msg.params, [0xAA, 0xBB, 0xCC]);
-}
-
-#[test]
-fn test_long_message_construction() {
-    let msg = HidppLongMessage::new(0x01, 0x05, 0x02, 0x0A).with_params(&[1, 2, 3, 4, 5]);
-
-    let bytes = msg.to_bytes();
-    assert_eq!(bytes[0], 0x11); // Long report type
-    assert_eq!(bytes[1], 0x01); // Device index
-    assert_eq!(bytes[2], 0x05); // Feature index
-    assert_eq!(bytes[3], 0x2A); // Function 2, SW ID 10
-    assert_eq!(bytes[4], 1);
-    assert_eq!(bytes[5], 2);
-    assert_eq!(bytes[6], 3);
-}
-
-#[test]
-fn test_connection_type_display() {
-    assert_eq!(format!("{}", ConnectionType::Usb), "USB");
-    assert_eq!(format!("{}", ConnectionType::Bolt), "Bolt");
-    assert_eq!(format!("{}", ConnectionType::Bluetooth), "Bluetooth");
-    assert_eq!(format!("{}", ConnectionType::Unifying), "Unifying");
-}
-
 #[test]
 fn test_haptic_error_display() {
     assert!(
@@ -134,6 +86,8 @@ fn test_from_config() {
         debounce_ms: 30,
         slice_debounce_ms: 20,
         reentry_debounce_ms: 50,
+        patterns: Default::default(),
+        wake_ping_idle_ms: 0,
     };
 
     let manager = HapticManager::from_config(&config);
@@ -152,6 +106,8 @@ fn test_from_config_disabled() {
         debounce_ms: 20,
         slice_debounce_ms: 20,
         reentry_debounce_ms: 50,
+        patterns: Default::default(),
+        wake_ping_idle_ms: 0,
     };
 
     let manager = HapticManager::from_config(&config);
@@ -172,6 +128,8 @@ fn test_update_from_config() {
         debounce_ms: 25,
         slice_debounce_ms: 20,
         reentry_debounce_ms: 50,
+        patterns: Default::default(),
+        wake_ping_idle_ms: 0,
     };
 
     manager.update_from_config(&new_config);
@@ -317,10 +275,13 @@ fn test_from_config_with_per_event() {
             slice_change: "sharp_state_change".to_string(),
             confirm: "angry_alert".to_string(),
             invalid: "subtle_collision".to_string(),
+            ..Default::default()
         },
         debounce_ms: 25,
         slice_debounce_ms: 20,
         reentry_debounce_ms: 50,
+        patterns: Default::default(),
+        wake_ping_idle_ms: 0,
     };
 
     let manager = HapticManager::from_config(&config);
@@ -351,10 +312,13 @@ fn test_update_from_config_with_per_event() {
             slice_change: "angry_alert".to_string(),
             confirm: "damp_state_change".to_string(),
             invalid: "subtle_collision".to_string(),
+            ..Default::default()
         },
         debounce_ms: 30,
         slice_debounce_ms: 20,
         reentry_debounce_ms: 50,
+        patterns: Default::default(),
+        wake_ping_idle_ms: 0,
     };
 
     manager.update_from_config(&new_config);
@@ -372,59 +336,6 @@ fn test_update_from_config_with_per_event() {
 // Story 5.4: Safety Verification Tests
 // ========================================================================
 
-#[test]
-fn test_blocklisted_features_detection() {
-    assert!(blocklisted_features::is_blocklisted(
-        blocklisted_features::REPORT_RATE
-    ));
-    assert!(blocklisted_features::is_blocklisted(
-        blocklisted_features::ONBOARD_PROFILES
-    ));
-    assert!(blocklisted_features::is_blocklisted(
-        blocklisted_features::MODE_STATUS
-    ));
-    assert!(blocklisted_features::is_blocklisted(
-        blocklisted_features::MOUSE_BUTTON_SPY
-    ));
-    assert!(blocklisted_features::is_blocklisted(
-        blocklisted_features::PERSISTENT_REMAPPABLE_ACTION
-    ));
-    assert!(blocklisted_features::is_blocklisted(
-        blocklisted_features::HOST_INFO
-    ));
-
-    // REPROG_CONTROLS_V4 (0x1B04) was moved to allowed
-    assert!(!blocklisted_features::is_blocklisted(
-        features::REPROG_CONTROLS_V4
-    ));
-}
-
-#[test]
-fn test_allowed_features_not_blocklisted() {
-    assert!(!blocklisted_features::is_blocklisted(features::I_ROOT));
-    assert!(!blocklisted_features::is_blocklisted(
-        features::I_FEATURE_SET
-    ));
-    assert!(!blocklisted_features::is_blocklisted(features::DEVICE_NAME));
-    assert!(!blocklisted_features::is_blocklisted(
-        features::BATTERY_STATUS
-    ));
-    assert!(!blocklisted_features::is_blocklisted(features::LED_CONTROL));
-    assert!(!blocklisted_features::is_blocklisted(
-        features::FORCE_FEEDBACK
-    ));
-}
-
-#[test]
-fn test_allowed_features_in_safelist() {
-    assert!(allowed_features::is_allowed(features::I_ROOT));
-    assert!(allowed_features::is_allowed(features::I_FEATURE_SET));
-    assert!(allowed_features::is_allowed(features::DEVICE_NAME));
-    assert!(allowed_features::is_allowed(features::BATTERY_STATUS));
-    assert!(allowed_features::is_allowed(features::LED_CONTROL));
-    assert!(allowed_features::is_allowed(features::FORCE_FEEDBACK));
-}
-
 #[test]
 fn test_verify_feature_safety_allowed() {
     assert!(verify_feature_safety(features::I_ROOT).is_ok());
@@ -537,6 +448,32 @@ fn test_reconnect_not_needed_when_not_connected() {
     assert_eq!(manager.connection_state(), ConnectionState::NotConnected);
 }
 
+#[test]
+fn test_shared_battery_query_skips_a_switched_away_device() {
+    let manager = new_shared_haptic_manager(&crate::config::HapticConfig::default());
+    {
+        let mut m = manager.lock().unwrap();
+        m.home_host = Some(0);
+        m.note_active_host(2);
+    }
+    assert!(matches!(query_battery_shared(&manager), Err(HapticError::HostSwitchedAway)));
+}
+
+#[test]
+fn test_wake_ping_only_after_idle() {
+    use crate::hidpp::device::needs_wake;
+    use std::time::{Duration, Instant};
+
+    let idle = Some(Duration::from_secs(30));
+    let t0 = Instant::now();
+    assert!(!needs_wake(Some(t0), idle, t0 + Duration::from_secs(29)));
+    assert!(needs_wake(Some(t0), idle, t0 + Duration::from_secs(30)));
+    // Off, or never heard from (a fresh device was just opened)
+    assert!(!needs_wake(Some(t0), None, t0 + Duration::from_secs(600)));
+    assert!(!needs_wake(None, idle, t0));
+    assert_eq!(crate::config::HapticConfig::default().wake_ping_idle(), Some(Duration::from_secs(30)));
+}
+
 #[test]
 fn test_connection_state_enum_variants() {
     assert_ne!(ConnectionState::NotConnected, ConnectionState::Connected);
@@ -544,6 +481,49 @@ fn test_connection_state_enum_variants() {
     assert_ne!(ConnectionState::Disconnected, ConnectionState::Cooldown);
 }
 
+#[test]
+fn test_host_change_marks_switched_away_and_back() {
+    let mut manager = HapticManager::new(true);
+    manager.home_host = Some(0);
+
+    // Reporting the home slot changes nothing unless switched away
+    manager.note_active_host(0);
+    assert_eq!(manager.connection_state(), ConnectionState::NotConnected);
+
+    manager.note_active_host(2);
+    assert_eq!(manager.connection_state(), ConnectionState::SwitchedAway);
+    assert!(matches!(manager.query_battery(), Err(HapticError::HostSwitchedAway)));
+    assert!(manager.emit(HapticEvent::SelectionConfirm).is_ok());
+    let status = manager.connection_status();
+    assert_eq!(status["state"], "switched_away");
+    assert_eq!(status["away_host"], 2);
+
+    manager.note_active_host(0);
+    assert_eq!(manager.connection_state(), ConnectionState::Disconnected);
+    assert!(manager.connection_status()["away_host"].is_null());
+}
+
+#[test]
+fn test_repeated_timeouts_mark_switched_away() {
+    let mut manager = HapticManager::new(true);
+
+    // Single-host devices never switch away
+    for _ in 0..3 {
+        let _ = manager.track_query_timeouts(Err(HapticError::CommunicationError), true);
+    }
+    assert_ne!(manager.connection_state(), ConnectionState::SwitchedAway);
+
+    manager.home_host = Some(1);
+    let result = manager.track_query_timeouts(Err(HapticError::CommunicationError), true);
+    assert!(matches!(result, Err(HapticError::CommunicationError)));
+    // A vanished hidraw node is an unplug, not a host switch
+    let _ = manager.track_query_timeouts(Err(HapticError::DeviceNotFound), false);
+    let result = manager.track_query_timeouts(Err(HapticError::CommunicationError), true);
+    assert!(matches!(result, Err(HapticError::HostSwitchedAway)));
+    assert_eq!(manager.connection_state(), ConnectionState::SwitchedAway);
+    assert!(manager.connection_status()["away_host"].is_null());
+}
+
 #[test]
 fn test_connection_state_default_trait() {
     let state: ConnectionState = Default::default();
@@ -603,6 +583,25 @@ fn test_emit_slice_change_no_device() {
     assert!(manager.emit_slice_change(0));
 }
 
+#[test]
+fn test_emit_slice_change_event_disabled() {
+    use crate::config::HapticConfig;
+
+    let mut config = HapticConfig::default();
+    config.per_event.slice_change_enabled = false;
+    let mut manager = HapticManager::from_config(&config);
+    assert!(!manager.event_enabled(HapticEvent::SliceChange));
+    assert!(manager.event_enabled(HapticEvent::SelectionConfirm));
+    assert!(!manager.emit_slice_change(2));
+    // Skipped before the debounce state is touched
+    assert_eq!(manager.last_slice_index, None);
+    assert!(manager.emit(HapticEvent::SliceChange).is_ok());
+
+    config.per_event.slice_change_enabled = true;
+    manager.update_from_config(&config);
+    assert!(manager.emit_slice_change(2));
+}
+
 #[test]
 fn test_reset_slice_tracking() {
     let mut manager = HapticManager::new(true);
@@ -640,6 +639,8 @@ fn test_from_config_with_slice_debounce() {
         debounce_ms: 20,
         slice_debounce_ms: 25,
         reentry_debounce_ms: 60,
+        patterns: Default::default(),
+        wake_ping_idle_ms: 0,
     };
 
     let manager = HapticManager::from_config(&config);
@@ -662,6 +663,8 @@ fn test_update_from_config_with_slice_debounce() {
         debounce_ms: 20,
         slice_debounce_ms: 35,
         reentry_debounce_ms: 75,
+        patterns: Default::default(),
+        wake_ping_idle_ms: 0,
     };
 
     manager.update_from_config(&new_config);
@@ -682,3 +685,135 @@ fn test_pulse_command_construction_fast() {
     let bytes = msg.to_bytes();
     assert_eq!(bytes.len(), 7);
 }
+
+// ========================================================================
+// Custom Step Pattern Tests
+// ========================================================================
+
+fn step(duration_ms: u16, gap_after_ms: u16) -> crate::config::HapticStepConfig {
+    crate::config::HapticStepConfig {
+        intensity_scale: 1.0,
+        duration_ms,
+        gap_after_ms,
+        waveform: None,
+    }
+}
+
+#[test]
+fn test_validate_pattern_caps() {
+    let heartbeat = vec![step(40, 60), step(15, 0)];
+    let steps = validate_pattern(&heartbeat).unwrap();
+    assert_eq!(steps.len(), 2);
+    assert_eq!(steps[0].delay_to_next(), std::time::Duration::from_millis(100));
+
+    assert!(validate_pattern(&[]).is_err());
+    assert!(validate_pattern(&vec![step(10, 10); MAX_PATTERN_STEPS + 1]).is_err());
+    assert!(validate_pattern(&[step(300, 150), step(100, 0)]).is_err());
+    // The trailing gap is never waited out, so it does not count
+    assert!(validate_pattern(&[step(250, 0), step(250, 1000)]).is_ok());
+    assert!(validate_pattern(&[step(0, 10)]).is_err());
+
+    let mut loud = step(20, 0);
+    loud.intensity_scale = f32::NAN;
+    assert!(validate_pattern(&[loud]).is_err());
+    let mut bogus = step(20, 0);
+    bogus.waveform = Some("thunder".to_string());
+    assert!(validate_pattern(&[bogus]).is_err());
+}
+
+#[test]
+fn test_pattern_registry_mapping_and_fallback() {
+    use crate::config::HapticConfig;
+
+    let mut config: HapticConfig = serde_json::from_str(
+        r#"{
+            "per_event": {"confirm": "heartbeat", "invalid": "broken", "slice_change": "knock"},
+            "patterns": {
+                "heartbeat": [
+                    {"duration_ms": 40, "gap_after_ms": 80, "waveform": "sharp_collision"},
+                    {"intensity_scale": 0.5, "duration_ms": 15}
+                ],
+                "broken": [{"duration_ms": 400, "gap_after_ms": 200}, {"duration_ms": 10}]
+            }
+        }"#,
+    )
+    .unwrap();
+    let (registry, per_event) = PatternRegistry::from_config(&config);
+
+    let heartbeat = registry.get(&HapticEvent::SelectionConfirm).unwrap();
+    assert_eq!(heartbeat.len(), 2);
+    assert_eq!(heartbeat[0].waveform, Some(Mx4HapticPattern::SharpCollision));
+    assert_eq!(heartbeat[1].intensity_scale, 0.5);
+
+    // Invalid definitions fall back to the event's default waveform
+    assert!(registry.get(&HapticEvent::InvalidAction).is_none());
+    assert_eq!(per_event.invalid, Mx4HapticPattern::AngryAlert);
+    assert_eq!(per_event.slice_change, Mx4HapticPattern::Knock);
+    assert_eq!(per_event.menu_appear, Mx4HapticPattern::DampStateChange);
+
+    // Without a device, playing a custom pattern is a silent no-op
+    config.enabled = true;
+    let mut manager = HapticManager::from_config(&config);
+    assert!(manager.event_steps(HapticEvent::SelectionConfirm).is_some());
+    assert!(manager.begin_pattern().is_none());
+    assert!(manager.emit(HapticEvent::SelectionConfirm).is_ok());
+}
+
+// ============================================================================
+// Property tests: truncated or garbage hidraw reads must never panic
+// ============================================================================
+
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn prop_short_message_from_bytes(bytes in prop::collection::vec(any::<u8>(), 0..32)) {
+        if let Some(msg) = HidppShortMessage::from_bytes(&bytes) {
+            prop_assert!(bytes.len() >= 7);
+            prop_assert_eq!(&msg.to_bytes()[..], &bytes[..7]);
+        }
+    }
+
+    #[test]
+    fn prop_long_message_from_bytes(bytes in prop::collection::vec(any::<u8>(), 0..40)) {
+        if let Some(msg) = HidppLongMessage::from_bytes(&bytes) {
+            prop_assert!(bytes.len() >= 20);
+            prop_assert_eq!(&msg.to_bytes()[..], &bytes[..20]);
+        }
+    }
+
+    #[test]
+    fn prop_notification_route_never_panics(
+        indices in any::<[u8; 4]>(),
+        feature_index in any::<u8>(),
+        data in prop::collection::vec(any::<u8>(), 0..24),
+    ) {
+        let routing = notifications::NotificationIndices {
+            battery: Some(indices[0]),
+            change_host: Some(indices[1]),
+            dpi: Some(indices[2]),
+            hires_wheel: Some(indices[3]),
+        };
+        if let Some(notifications::HardwareNotification::DpiChanged { dpi }) =
+            routing.route(feature_index, &data)
+        {
+            prop_assert!(dpi > 0);
+        }
+    }
+
+    #[test]
+    fn prop_battery_response_never_panics(
+        response in prop::collection::vec(any::<u8>(), 0..24),
+        is_unified in any::<bool>(),
+    ) {
+        let needed = if is_unified { 8 } else { 7 };
+        match crate::battery::parse_battery_response(&response, is_unified) {
+            Some(reading) => {
+                prop_assert!(response.len() >= needed);
+                prop_assert_eq!(reading.percentage, response[4]);
+                prop_assert!(!reading.charge_complete || reading.charging);
+            }
+            None => prop_assert!(response.len() < needed),
+        }
+    }
+}
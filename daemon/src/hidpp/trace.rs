@@ -0,0 +1,275 @@
+//! HID++ traffic trace
+//!
+//! With `debug.hidpp_trace` set (or after `SIGUSR2`), every report the daemon
+//! writes to or reads from the HID++ hidraw fds is appended to
+//! `~/.local/share/juhradial/hidpp-trace.log` as one timestamped hex line:
+//!
+//! ```text
+//! 1760601600123 > 10 02 00 12 10 04 00
+//! 1760601600131 < 11 02 00 12 08 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00
+//! ```
+//!
+//! `>` is a report written to the device, `<` one read from it. The file
+//! starts with a header describing the byte layout (see [`HEADER`]) and
+//! rotates to `hidpp-trace.log.1` at [`MAX_TRACE_FILE_BYTES`]. Nothing is
+//! redacted; the file stays on the machine unless the user attaches it to a
+//! bug report, where the test mock can replay it
+//! (`request::mock::MockTransport::replay`).
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::fs_util;
+
+const TRACE_FILENAME: &str = "hidpp-trace.log";
+
+/// Rotate when the live file passes this size; live + rotated stay under ~8MB
+pub const MAX_TRACE_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// First lines of every trace file
+pub const HEADER: &str = "\
+# JuhRadial MX HID++ trace
+# <unix ms> <direction> <report bytes, hex>
+#   >  written to the device    <  read from the device
+# Byte layout of a HID++ report:
+#   0   report id: 10 short (7 bytes), 11 long (20 bytes); other ids are input reports
+#   1   device index: ff direct (USB cable, Bluetooth), 01-06 receiver slot
+#   2   feature index (00 = IRoot); ff = HID++ 2.0 error, 8f = HID++ 1.0 error
+#   3   function << 4 | software id (software id 0 = notification from the device)
+#   4+  parameters; error reports carry feature index, function/software id, error code
+# Nothing is redacted: reports include feature data such as battery level,
+# DPI, Easy-Switch host names and receiver pairing info.
+";
+
+/// Which way a report went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Written to the device
+    Out,
+    /// Read from the device
+    In,
+}
+
+impl Direction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Out => ">",
+            Self::In => "<",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            ">" => Some(Self::Out),
+            "<" => Some(Self::In),
+            _ => None,
+        }
+    }
+}
+
+/// One trace line, without the newline
+pub fn format_line(direction: Direction, bytes: &[u8], unix_ms: u64) -> String {
+    let mut line = format!("{unix_ms} {}", direction.as_str());
+    for byte in bytes {
+        line.push_str(&format!(" {byte:02x}"));
+    }
+    line
+}
+
+/// Appends trace lines to a file, rotating it at a size limit
+#[derive(Debug)]
+pub struct TraceLog {
+    path: PathBuf,
+    max_bytes: u64,
+    file: Option<File>,
+    len: u64,
+}
+
+impl TraceLog {
+    /// Log writing to the default trace file
+    pub fn new() -> Self {
+        Self::with_path(trace_path(), MAX_TRACE_FILE_BYTES)
+    }
+
+    /// Log writing to an explicit file (tests)
+    pub fn with_path(path: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            path,
+            max_bytes,
+            file: None,
+            len: 0,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one report, rotating first if the file is over the size limit
+    pub fn record(&mut self, direction: Direction, bytes: &[u8], unix_ms: u64) -> io::Result<()> {
+        let mut file = match self.file.take() {
+            Some(file) if self.len < self.max_bytes => file,
+            _ => self.open()?,
+        };
+        let line = format_line(direction, bytes, unix_ms);
+        writeln!(file, "{line}")?;
+        self.len += line.len() as u64 + 1;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Drop the open handle; the next record reopens the file
+    pub fn close(&mut self) {
+        self.file = None;
+    }
+
+    fn open(&mut self) -> io::Result<File> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut len = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if len >= self.max_bytes {
+            fs::rename(&self.path, rotated_path(&self.path))?;
+            len = 0;
+        }
+        let mut file = fs_util::open_private_append(&self.path)?;
+        if len == 0 {
+            file.write_all(HEADER.as_bytes())?;
+            len = HEADER.len() as u64;
+        }
+        self.len = len;
+        Ok(file)
+    }
+}
+
+impl Default for TraceLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Trace file path (~/.local/share/juhradial/hidpp-trace.log)
+///
+/// Respects XDG_DATA_HOME if set, see [`crate::paths`].
+pub fn trace_path() -> PathBuf {
+    crate::paths::paths().user_data_dir.join(TRACE_FILENAME)
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    path.with_file_name(name)
+}
+
+// ============================================================================
+// Daemon-wide switch
+// ============================================================================
+
+/// The trace switch and log, shared by every HID++ reader and writer
+///
+/// Clones share both; the daemon keeps one on `DaemonHandles`.
+#[derive(Debug, Clone, Default)]
+pub struct HidppTrace {
+    enabled: Arc<AtomicBool>,
+    log: Arc<Mutex<TraceLog>>,
+}
+
+impl HidppTrace {
+    /// Whether reports are being traced
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Turn tracing on or off; logs the change
+    pub fn set_enabled(&self, enabled: bool) {
+        if self.enabled.swap(enabled, Ordering::Relaxed) == enabled {
+            return;
+        }
+        let Ok(mut log) = self.log.lock() else {
+            return;
+        };
+        if enabled {
+            tracing::info!(path = %log.path().display(), "HID++ trace started");
+        } else {
+            log.close();
+            tracing::info!(path = %log.path().display(), "HID++ trace stopped");
+        }
+    }
+
+    /// Flip tracing (SIGUSR2); returns the new state
+    pub fn toggle(&self) -> bool {
+        let enabled = !self.is_enabled();
+        self.set_enabled(enabled);
+        enabled
+    }
+
+    /// A report written to the device
+    pub fn outgoing(&self, bytes: &[u8]) {
+        if self.is_enabled() {
+            self.record(Direction::Out, bytes);
+        }
+    }
+
+    /// A report read from the device
+    pub fn incoming(&self, bytes: &[u8]) {
+        if self.is_enabled() {
+            self.record(Direction::In, bytes);
+        }
+    }
+
+    fn record(&self, direction: Direction, bytes: &[u8]) {
+        let unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let Ok(mut log) = self.log.lock() else {
+            return;
+        };
+        if let Err(e) = log.record(direction, bytes, unix_ms) {
+            // One warning, not one per report
+            self.enabled.store(false, Ordering::Relaxed);
+            log.close();
+            tracing::warn!(path = %log.path().display(), error = %e, "Cannot write HID++ trace; tracing stopped");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_follow_the_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("juhradial").join(TRACE_FILENAME);
+        let mut log = TraceLog::with_path(path.clone(), MAX_TRACE_FILE_BYTES);
+        log.record(Direction::Out, &[0x10, 0x02, 0x00, 0x12, 0x10, 0x04, 0x00], 1_000).unwrap();
+        log.record(Direction::In, &[0x10, 0x02, 0x00, 0x12, 0x08, 0x00, 0x00], 1_007).unwrap();
+
+        let text = fs::read_to_string(&path).unwrap();
+        let body: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        assert!(text.starts_with(HEADER));
+        assert_eq!(body, ["1000 > 10 02 00 12 10 04 00", "1007 < 10 02 00 12 08 00 00"]);
+    }
+
+    #[test]
+    fn test_rotates_at_the_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TRACE_FILENAME);
+        let max = HEADER.len() as u64 + 32;
+        let mut log = TraceLog::with_path(path.clone(), max);
+        for ms in 0..4 {
+            log.record(Direction::In, &[0x11, 0xFF, 0x05, 0x00], ms).unwrap();
+        }
+
+        let rotated = fs::read_to_string(rotated_path(&path)).unwrap();
+        let live = fs::read_to_string(&path).unwrap();
+        assert!(rotated.starts_with(HEADER) && live.starts_with(HEADER));
+        assert_eq!(rotated.lines().filter(|l| !l.starts_with('#')).count(), 2);
+        assert_eq!(live.lines().last(), Some("3 < 11 ff 05 00"));
+    }
+}
@@ -14,6 +14,7 @@ use std::time::Instant;
 use tokio::sync::mpsc;
 
 use crate::evdev::GestureEvent;
+use crate::handles::DaemonHandles;
 
 /// Logitech vendor ID
 pub const LOGITECH_VENDOR_ID: u16 = 0x046D;
@@ -79,7 +80,14 @@ pub struct HidrawHandler {
     notification_indices: crate::hidpp::notifications::NotificationIndices,
     /// Live KWin availability (D-Bus name ownership), used to pick the cursor
     /// backend on KDE instead of the XDG_CURRENT_DESKTOP env var (issue #32).
-    kwin_available: Option<crate::compositor::KWinAvailability>,
+    kwin_available: crate::compositor::KWinAvailability,
+    /// Decides which configured trigger owns the menu
+    arbiter: crate::menu_trigger::SharedTriggerArbiter,
+    /// First pressed CID in the previous button report (0 when none)
+    last_cid: u16,
+    /// State shared with the service and the other input loops (panic
+    /// switch, HID++ trace, flick)
+    handles: DaemonHandles,
 }
 
 /// Map HID++ CID to evdev key code for macro trigger forwarding
@@ -118,14 +126,32 @@ impl HidrawHandler {
             active_button_action: None,
             thumbwheel_feature_index: None,
             notification_indices: Default::default(),
-            kwin_available: None,
+            kwin_available: crate::compositor::KWinAvailability::new(),
+            arbiter: crate::menu_trigger::new_shared_arbiter(),
+            last_cid: 0,
+            handles: DaemonHandles::default(),
         }
     }
 
     /// Share the live KWin availability flag so the gesture handler can pick the
     /// cursor backend by D-Bus capability rather than an environment string.
     pub fn set_kwin_availability(&mut self, kwin: crate::compositor::KWinAvailability) {
-        self.kwin_available = Some(kwin);
+        self.kwin_available = kwin;
+    }
+
+    /// Share the trigger arbiter with the other menu trigger sources
+    pub fn set_trigger_arbiter(&mut self, arbiter: crate::menu_trigger::SharedTriggerArbiter) {
+        self.arbiter = arbiter;
+    }
+
+    /// Share the daemon's state with the service and the other input loops
+    pub fn set_handles(&mut self, handles: DaemonHandles) {
+        self.handles = handles;
+    }
+
+    /// Use another panic switch than the one in the shared handles
+    pub fn set_panic_switch(&mut self, panic_switch: crate::panic_switch::PanicSwitch) {
+        self.handles.panic_switch = panic_switch;
     }
 
     /// Register CIDs that are diverted for macro triggers (not gesture buttons)
@@ -313,6 +339,11 @@ impl HidrawHandler {
         tracing::info!("Listening for HID++ diverted button events...");
 
         loop {
+            // Disarmed: the caller closes the handle and releases the diverts
+            if !self.handles.panic_switch.is_armed() {
+                return Ok(());
+            }
+
             // Get device reference for read
             let read_result = {
                 let device = self.device.as_mut().ok_or(HidrawError::DeviceNotFound)?;
@@ -322,6 +353,7 @@ impl HidrawHandler {
             // Process result outside of borrow
             match read_result {
                 Ok(len) if len >= 7 => {
+                    self.handles.hidpp_trace.incoming(&buf[..len]);
                     self.process_hidpp_report(&buf[..len]).await;
                 }
                 Ok(_) => {
@@ -481,6 +513,20 @@ impl HidrawHandler {
         // A CID of 0 means all buttons released
         let pressed = cid != 0;
 
+        // Panic sequence first, before config lookups or menu state. The
+        // report repeats while other buttons change, so count transitions.
+        let new_press = cid != self.last_cid;
+        self.last_cid = cid;
+        if new_press
+            && (cid == button_cid::GESTURE_BUTTON || cid == button_cid::HAPTIC)
+            && self.handles.panic_switch.record_press(Instant::now())
+        {
+            return;
+        }
+        if !self.handles.panic_switch.is_armed() {
+            return;
+        }
+
         // Whether this CID maps to a configured action (gesture/haptic, or a
         // reassigned back/forward/middle/shift-wheel) or is the release marker.
         let is_known = self.is_action_button(cid) || cid == 0;
@@ -586,11 +632,30 @@ impl HidrawHandler {
         }
     }
 
+    /// Whether `triggers` includes the MX gesture button (default when unset)
+    fn mx_gesture_enabled(&self) -> bool {
+        self.shared_config
+            .as_ref()
+            .and_then(|c| c.read().ok().map(|c| c.mx_gesture_trigger()))
+            .unwrap_or(true)
+    }
+
     /// Handle gesture button press/release
     async fn handle_gesture_button(&mut self, pressed: bool) {
+        use crate::menu_trigger::TriggerId;
+
         if pressed {
-            // Button pressed
+            // Leaving press_time unset makes the matching release a no-op
+            if !self.mx_gesture_enabled() {
+                tracing::debug!("mx-gesture-button is not a configured trigger; ignoring press");
+                return;
+            }
+            if !crate::menu_trigger::claim(&self.arbiter, TriggerId::MxGesture) {
+                tracing::debug!("Menu held by another trigger; ignoring gesture press");
+                return;
+            }
             self.press_time = Some(Instant::now());
+            self.handles.flick.update(|f, now| f.pressed(now));
 
             // Desktop-aware cursor query:
             // - KDE: KWin script for accurate multi-monitor Wayland cursor
@@ -598,23 +663,19 @@ impl HidrawHandler {
             // Pick the cursor backend by whether KWin owns its D-Bus name, not
             // by XDG_CURRENT_DESKTOP, which is empty when systemd starts the
             // daemon at cold boot and made KDE look non-KDE (issue #32).
-            let kwin_owned = self
-                .kwin_available
-                .as_ref()
-                .map(|k| k.is_owned())
-                .unwrap_or(false);
-            match crate::compositor::cursor_backend(kwin_owned) {
+            let kwin_owned = self.kwin_available.is_owned();
+            match crate::compositor::cursor_backend(kwin_owned, self.kwin_available.helper().cursor().is_some()) {
                 crate::compositor::CursorBackend::KWin => {
                     tracing::info!(kwin_owned, "Gesture button PRESSED - triggering KWin cursor query");
-                    if !Self::trigger_kwin_cursor_script() {
-                        let (x, y) = Self::get_cursor_position();
+                    if !crate::evdev::EvdevHandler::trigger_kwin_cursor_script(self.kwin_available.helper()) {
+                        let (x, y) = self.get_cursor_position();
                         tracing::warn!(x, y, "KWin script failed, using fallback cursor position");
                         let _ = self.event_tx.send(GestureEvent::Pressed { x, y }).await;
                     }
                     // If KWin script succeeded, it calls ShowMenuAtCursor via D-Bus
                 }
                 crate::compositor::CursorBackend::Fallback => {
-                    let (x, y) = Self::get_cursor_position();
+                    let (x, y) = self.get_cursor_position();
                     tracing::info!(x, y, kwin_owned, "Gesture button PRESSED - cursor query");
                     let _ = self.event_tx.send(GestureEvent::Pressed { x, y }).await;
                 }
@@ -628,109 +689,25 @@ impl HidrawHandler {
 
             self.press_time = None;
 
+            if !crate::menu_trigger::release(&self.arbiter, TriggerId::MxGesture) {
+                tracing::debug!("Menu taken over by another trigger; ignoring gesture release");
+                return;
+            }
             tracing::info!(duration_ms, "Gesture button RELEASED");
 
             let _ = self
                 .event_tx
-                .send(GestureEvent::Released { duration_ms })
+                .send(GestureEvent::Released { duration_ms, released_at: Instant::now() })
                 .await;
         }
     }
 
     /// Get current cursor position (fallback method)
-    fn get_cursor_position() -> (i32, i32) {
-        let pos = crate::cursor::get_cursor_position();
+    fn get_cursor_position(&self) -> (i32, i32) {
+        let pos = crate::cursor::get_cursor_position(self.kwin_available.helper());
         (pos.x, pos.y)
     }
 
-    /// Trigger KWin script to get cursor position and call ShowMenuAtCursor
-    ///
-    /// This method works correctly on Plasma 6 Wayland with multiple monitors,
-    /// unlike xdotool/XWayland which clamps cursor to a single screen.
-    fn trigger_kwin_cursor_script() -> bool {
-        use std::io::Write;
-        use std::process::Command;
-        use tempfile::Builder;
-
-        let script = crate::cursor::KWIN_CURSOR_SCRIPT;
-
-        // Create a temporary file with .js suffix securely
-        let mut temp_file = match Builder::new().suffix(".js").tempfile() {
-            Ok(file) => file,
-            Err(e) => {
-                tracing::warn!("Failed to create temp file for KWin script: {}", e);
-                return false;
-            }
-        };
-
-        // Write script to temp file
-        if let Err(e) = write!(temp_file, "{}", script) {
-            tracing::warn!("Failed to write KWin script: {}", e);
-            return false;
-        }
-
-        // Get the path as a string
-        let script_path = temp_file.path().to_string_lossy();
-
-        // Load script via D-Bus
-        let load_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                "/Scripting",
-                "org.kde.kwin.Scripting.loadScript",
-                &format!("string:{}", script_path),
-            ])
-            .output();
-
-        let load_output = match load_result {
-            Ok(output) if output.status.success() => output,
-            _ => {
-                tracing::warn!("Failed to load KWin script");
-                return false;
-            }
-        };
-
-        // Parse script ID from output (looks like "int32 5")
-        let stdout = String::from_utf8_lossy(&load_output.stdout);
-        let script_id: Option<i32> = stdout
-            .lines()
-            .find(|line| line.contains("int32"))
-            .and_then(|line| line.split_whitespace().last())
-            .and_then(|s| s.parse().ok());
-
-        let script_id = match script_id {
-            Some(id) => id,
-            None => {
-                tracing::warn!("Failed to parse KWin script ID");
-                return false;
-            }
-        };
-
-        // Run the script
-        let run_result = Command::new("dbus-send")
-            .args([
-                "--session",
-                "--print-reply",
-                "--dest=org.kde.KWin",
-                &format!("/Scripting/Script{}", script_id),
-                "org.kde.kwin.Script.run",
-            ])
-            .output();
-
-        match run_result {
-            Ok(output) if output.status.success() => {
-                tracing::debug!(script_id, "KWin cursor script triggered successfully");
-                true
-            }
-            _ => {
-                tracing::warn!("Failed to run KWin script");
-                false
-            }
-        }
-    }
-
     /// Check if handler is connected
     pub fn is_connected(&self) -> bool {
         self.device.is_some()
@@ -745,37 +722,32 @@ impl HidrawHandler {
     pub fn close(&mut self) {
         self.device = None;
         self.device_path = None;
-        self.press_time = None;
+        self.last_cid = 0;
+        // A held gesture's release died with the handle
+        if self.press_time.take().is_some()
+            && self.active_button_action == Some(crate::config::ButtonAction::RadialMenu)
+        {
+            crate::menu_trigger::release(&self.arbiter, crate::menu_trigger::TriggerId::MxGesture);
+        }
         self.active_macro_cid = None;
         self.active_button_action = None;
     }
 }
 
 /// Hidraw error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum HidrawError {
     /// Device not found
+    #[error("Logitech hidraw device not found")]
     DeviceNotFound,
     /// Permission denied
+    #[error("Permission denied. Ensure udev rules are installed.")]
     PermissionDenied,
     /// I/O error
+    #[error("I/O error: {0}")]
     IoError(std::io::Error),
 }
 
-impl std::fmt::Display for HidrawError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            HidrawError::DeviceNotFound => write!(f, "Logitech hidraw device not found"),
-            HidrawError::PermissionDenied => {
-                write!(f, "Permission denied. Ensure udev rules are installed.")
-            }
-            HidrawError::IoError(e) => write!(f, "I/O error: {}", e),
-        }
-    }
-}
-
-impl std::error::Error for HidrawError {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -821,4 +793,30 @@ mod tests {
         assert!(!handler.is_connected());
         assert_eq!(handler.device_path(), None);
     }
+
+    #[tokio::test]
+    async fn test_panic_sequence_disarms_stuck_menu() {
+        use crate::menu_trigger::TriggerId;
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let mut handler = HidrawHandler::new(tx);
+        let panic_switch = crate::panic_switch::PanicSwitch::new();
+        handler.set_panic_switch(panic_switch.clone());
+        // Wedged: a shortcut "holds" the menu, so every gesture press is ignored
+        let arbiter = crate::menu_trigger::new_shared_arbiter();
+        assert!(crate::menu_trigger::claim(&arbiter, TriggerId::Shortcut(0)));
+        handler.set_trigger_arbiter(arbiter);
+
+        let report = |cid: u16| [HIDPP_LONG, 0x02, 0x08, DIVERTED_BUTTONS_EVENT, (cid >> 8) as u8, cid as u8, 0];
+        for _ in 0..crate::panic_switch::PANIC_PRESSES {
+            handler.handle_button_event(&report(button_cid::HAPTIC)).await;
+            handler.handle_button_event(&report(0)).await;
+        }
+        assert!(!panic_switch.is_armed());
+        assert!(rx.try_recv().is_err());
+
+        // Disarmed: a reassigned button press is no longer dispatched
+        handler.handle_button_event(&report(button_cid::GESTURE_BUTTON)).await;
+        assert!(rx.try_recv().is_err());
+    }
 }
@@ -0,0 +1,161 @@
+//! Long-hover help for menu slices
+//!
+//! A slice may carry a `description` in profiles.json. When the highlight
+//! rests on one slice for `menu.hover_help_ms`, the daemon emits
+//! `HighlightStable(slot)` and the overlay shows that slice's description in
+//! the hub. A highlight change or a close before then cancels it, so sweeping
+//! across the ring never flashes help text. Menus without descriptions arm
+//! nothing.
+//!
+//! `HoverHelp` is a [`Deadline`] state machine (times are passed in); the
+//! D-Bus service feeds it open/close/highlight changes through
+//! `HoverHelpHandle`, and a daemon task runs its timer.
+
+use std::time::{Duration, Instant};
+
+use crate::deadline::{Deadline, DeadlineHandle};
+
+/// Default `menu.hover_help_ms`
+pub const HOVER_HELP_MS: u64 = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// No menu, no highlight, or help is off for this menu
+    Idle,
+    /// Highlight on `slot` since `due - delay`
+    Dwelling { slot: u8, due: Instant },
+    /// `HighlightStable` was sent for `slot`
+    Shown { slot: u8 },
+}
+
+/// Dwell tracking for the highlighted slice of the open menu
+#[derive(Debug)]
+pub struct HoverHelp {
+    /// Hover delay for the open menu; `None` while closed or without help
+    delay: Option<Duration>,
+    state: State,
+}
+
+impl HoverHelp {
+    pub fn new() -> Self {
+        Self { delay: None, state: State::Idle }
+    }
+
+    /// Menu shown; `delay` is `None` when it has no descriptions or help is off
+    pub fn opened(&mut self, delay: Option<Duration>) {
+        self.delay = delay;
+        self.state = State::Idle;
+    }
+
+    /// Highlight moved to `slot` (`None` = hub or outside the ring)
+    pub fn highlighted(&mut self, slot: Option<u8>, now: Instant) {
+        self.state = match (slot, self.delay) {
+            (Some(slot), _) if self.current() == Some(slot) => return,
+            (Some(slot), Some(delay)) => State::Dwelling { slot, due: now + delay },
+            _ => State::Idle,
+        };
+    }
+
+    /// Menu closed (selection, cancel, dismissal or shutdown)
+    pub fn closed(&mut self) {
+        self.delay = None;
+        self.state = State::Idle;
+    }
+
+    /// Timer woke: the slot to announce once its highlight has rested long enough
+    ///
+    /// A timer outrun by a highlight change or close finds a later (or no)
+    /// deadline and announces nothing.
+    pub fn fire(&mut self, now: Instant) -> Option<u8> {
+        match self.state {
+            State::Dwelling { slot, due } if now >= due => {
+                self.state = State::Shown { slot };
+                Some(slot)
+            }
+            _ => None,
+        }
+    }
+
+    /// Slot the highlight currently rests on, if tracked
+    fn current(&self) -> Option<u8> {
+        match self.state {
+            State::Dwelling { slot, .. } | State::Shown { slot } => Some(slot),
+            State::Idle => None,
+        }
+    }
+}
+
+impl Default for HoverHelp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deadline for HoverHelp {
+    /// When the timer should next wake
+    fn wake_at(&self) -> Option<Instant> {
+        match self.state {
+            State::Dwelling { due, .. } => Some(due),
+            _ => None,
+        }
+    }
+}
+
+/// Shared handle between the D-Bus service and the hover-help timer task
+pub type HoverHelpHandle = DeadlineHandle<HoverHelp>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(HOVER_HELP_MS);
+
+    #[test]
+    fn test_rested_highlight_fires_once() {
+        let t0 = Instant::now();
+        let mut help = HoverHelp::new();
+        help.opened(Some(DELAY));
+        help.highlighted(Some(3), t0);
+        assert_eq!(help.wake_at(), Some(t0 + DELAY));
+        assert_eq!(help.fire(t0 + DELAY / 2), None);
+        assert_eq!(help.fire(t0 + DELAY), Some(3));
+        assert_eq!(help.wake_at(), None);
+        // Re-reporting the same slot neither re-arms nor repeats
+        help.highlighted(Some(3), t0 + DELAY * 2);
+        assert_eq!(help.fire(t0 + DELAY * 4), None);
+    }
+
+    #[test]
+    fn test_highlight_change_and_close_suppress() {
+        let t0 = Instant::now();
+        let mut help = HoverHelp::new();
+        help.opened(Some(DELAY));
+        help.highlighted(Some(1), t0);
+        let t1 = t0 + DELAY / 2;
+        help.highlighted(Some(2), t1);
+        // The timer armed for slot 1 wakes and finds slot 2's later deadline
+        assert_eq!(help.fire(t0 + DELAY), None);
+        assert_eq!(help.fire(t1 + DELAY), Some(2));
+
+        help.highlighted(Some(4), t1 + DELAY);
+        help.highlighted(None, t1 + DELAY);
+        assert_eq!(help.wake_at(), None);
+
+        help.highlighted(Some(5), t0);
+        help.closed();
+        assert_eq!(help.fire(t0 + DELAY * 10), None);
+        // After the close nothing arms until the next open
+        help.highlighted(Some(5), t0);
+        assert_eq!(help.wake_at(), None);
+    }
+
+    #[test]
+    fn test_menu_without_help_never_arms() {
+        let t0 = Instant::now();
+        let mut help = HoverHelp::new();
+        help.opened(None);
+        help.highlighted(Some(0), t0);
+        assert_eq!(help.wake_at(), None);
+        assert_eq!(help.fire(t0 + Duration::from_secs(60)), None);
+    }
+}
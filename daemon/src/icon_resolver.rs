@@ -0,0 +1,438 @@
+//! File icons for slices (`"icon": "~/icons/term.svg"`)
+//!
+//! A slice icon ending in `.svg`, `.png` or `.ico` names a file: absolute,
+//! `~/`-relative, or relative to the config directory. When profiles load,
+//! [`IconResolver`] reads each such file once and records its format, its
+//! intrinsic size (an SVG's `width`/`height` or `viewBox`, a PNG's `IHDR`,
+//! the largest image of an ICO) and a content hash the overlay caches its
+//! raster under. Icon names and emoji are left to the overlay.
+//!
+//! At menu open [`IconInfo::hint`] adds the raster size for the ring that is
+//! about to be drawn, so the overlay renders an SVG once at the size it is
+//! shown at instead of scaling a fixed-size raster. A file that is missing,
+//! too large or malformed is logged with its path and the slot keeps the
+//! overlay's fallback icon.
+
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+
+use crate::content_hash::{content_hash, ContentHash};
+use crate::geometry::{Monitor, MENU_DIAMETER};
+
+/// Icon box on a default-size (300px), eight-slice ring, in pixels
+pub const ICON_BOX_PX: f64 = 30.0;
+
+/// Distance of the icon centers from the ring center on a default-size ring
+/// (the overlay's `ICON_ZONE_RADIUS`)
+const ICON_RING_RADIUS: f64 = 100.0;
+
+/// Most of a slice's arc at the icon radius an icon may take on crowded rings
+const ICON_ARC_SHARE: f64 = 0.55;
+
+/// Icon files larger than this are not read
+pub const MAX_ICON_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Icon file format, from the extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconFormat {
+    Svg,
+    Png,
+    Ico,
+}
+
+impl IconFormat {
+    /// Format of an icon reference that names a file, `None` for icon names
+    /// and emoji
+    pub fn of(icon: &str) -> Option<Self> {
+        let lower = icon.to_ascii_lowercase();
+        if lower.ends_with(".svg") {
+            Some(Self::Svg)
+        } else if lower.ends_with(".png") {
+            Some(Self::Png)
+        } else if lower.ends_with(".ico") {
+            Some(Self::Ico)
+        } else {
+            None
+        }
+    }
+}
+
+/// Why an icon file cannot be used
+#[derive(Debug, thiserror::Error)]
+pub enum IconError {
+    #[error("cannot read: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("larger than {} bytes", MAX_ICON_BYTES)]
+    TooLarge,
+    #[error("path is not valid UTF-8")]
+    NonUtf8Path,
+    #[error("malformed SVG: {0}")]
+    Svg(String),
+    #[error("not a PNG image")]
+    Png,
+    #[error("not an ICO image")]
+    Ico,
+}
+
+/// A readable icon file
+#[derive(Debug, Clone, PartialEq)]
+pub struct IconInfo {
+    pub path: String,
+    pub format: IconFormat,
+    /// Intrinsic size in pixels; `None` for an SVG without `width`/`height`
+    /// or `viewBox`
+    pub size: Option<(u32, u32)>,
+    /// Hash of the file's bytes
+    pub hash: ContentHash,
+}
+
+impl IconInfo {
+    /// Read and inspect the icon file at `path`
+    pub fn inspect(path: &Path, format: IconFormat) -> Result<Self, IconError> {
+        let display = path.to_str().ok_or(IconError::NonUtf8Path)?.to_string();
+        if std::fs::metadata(path)?.len() > MAX_ICON_BYTES {
+            return Err(IconError::TooLarge);
+        }
+        let bytes = std::fs::read(path)?;
+        let size = match format {
+            IconFormat::Svg => svg_size(&bytes)?,
+            IconFormat::Png => Some(png_size(&bytes)?),
+            IconFormat::Ico => Some(ico_size(&bytes)?),
+        };
+        Ok(Self {
+            path: display,
+            format,
+            size,
+            hash: ContentHash(content_hash(&bytes)),
+        })
+    }
+
+    /// Payload entry for an icon drawn in a `box_px` device-pixel box
+    pub fn hint(&self, box_px: u32) -> IconHint<'_> {
+        IconHint {
+            path: &self.path,
+            format: self.format,
+            width: self.size.map(|(w, _)| w),
+            height: self.size.map(|(_, h)| h),
+            raster: (self.format == IconFormat::Svg).then(|| fit(self.size, box_px)),
+            hash: self.hash,
+        }
+    }
+}
+
+/// One slot of the payload's `icons` list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IconHint<'a> {
+    pub path: &'a str,
+    pub format: IconFormat,
+    /// Intrinsic size; raster formats always have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// SVG only: the size to rasterize at, the box fitted to the aspect ratio
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raster: Option<[u32; 2]>,
+    pub hash: ContentHash,
+}
+
+/// Device-pixel box an icon gets on a ring of `slice_count` slices
+///
+/// `diameter` is the resolved `menu_diameter` in logical pixels (`None`:
+/// the default size); on a compositor that scales logical pixels the
+/// monitor's scale turns that into device pixels.
+pub fn raster_box(diameter: Option<u32>, slice_count: u8, monitor: Option<&Monitor>) -> u32 {
+    let arc = TAU * ICON_RING_RADIUS / f64::from(slice_count.max(1));
+    let base = ICON_BOX_PX.min(arc * ICON_ARC_SHARE);
+    let ring = f64::from(diameter.unwrap_or(MENU_DIAMETER)) / f64::from(MENU_DIAMETER);
+    let device = monitor
+        .filter(|m| m.compositor_scaled && m.scale.is_finite() && m.scale > 0.0)
+        .map_or(1.0, |m| m.scale);
+    (base * ring * device).round().max(1.0) as u32
+}
+
+/// `box_px` square fitted to the aspect ratio of `size`
+fn fit(size: Option<(u32, u32)>, box_px: u32) -> [u32; 2] {
+    let Some((w, h)) = size.filter(|&(w, h)| w > 0 && h > 0) else {
+        return [box_px, box_px];
+    };
+    let scaled = |long: u32, short: u32| ((f64::from(box_px) * f64::from(short) / f64::from(long)).round() as u32).max(1);
+    if w >= h {
+        [box_px, scaled(w, h)]
+    } else {
+        [scaled(h, w), box_px]
+    }
+}
+
+/// Intrinsic size of an SVG document, checking the whole file is well formed
+fn svg_size(bytes: &[u8]) -> Result<Option<(u32, u32)>, IconError> {
+    let mut reader = Reader::from_reader(bytes);
+    let mut size = None;
+    let mut root_seen = false;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| IconError::Svg(format!("{} at byte {}", e, reader.error_position())))?;
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) if !root_seen => {
+                if e.local_name().as_ref() != b"svg" {
+                    return Err(IconError::Svg("root element is not <svg>".to_string()));
+                }
+                root_seen = true;
+                size = root_size(e)?;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if !root_seen {
+        return Err(IconError::Svg("no <svg> element".to_string()));
+    }
+    Ok(size)
+}
+
+/// Size from the root element's `width`, `height` and `viewBox`
+fn root_size(root: &BytesStart<'_>) -> Result<Option<(u32, u32)>, IconError> {
+    let (mut width, mut height, mut view_box) = (None, None, None);
+    for attr in root.attributes() {
+        let attr = attr.map_err(|e| IconError::Svg(e.to_string()))?;
+        let value = std::str::from_utf8(&attr.value).map_err(|e| IconError::Svg(e.to_string()))?;
+        match attr.key.local_name().as_ref() {
+            b"width" => width = svg_length(value)?,
+            b"height" => height = svg_length(value)?,
+            b"viewBox" => view_box = Some(view_box_size(value)?),
+            _ => {}
+        }
+    }
+    let size = match (width, height, view_box) {
+        (Some(w), Some(h), _) => Some((w, h)),
+        (Some(w), None, Some((vw, vh))) => Some((w, w * vh / vw)),
+        (None, Some(h), Some((vw, vh))) => Some((h * vw / vh, h)),
+        (_, _, view_box) => view_box,
+    };
+    Ok(size.map(|(w, h)| (pixels(w), pixels(h))))
+}
+
+/// An absolute SVG length in pixels; `None` for relative units (`%`, `em`)
+fn svg_length(value: &str) -> Result<Option<f64>, IconError> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let per_unit = match unit.trim() {
+        "" | "px" => 1.0,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "%" | "em" | "ex" => return Ok(None),
+        other => return Err(IconError::Svg(format!("unknown unit '{}' in '{}'", other, value))),
+    };
+    match number.parse::<f64>() {
+        Ok(n) if n.is_finite() && n > 0.0 => Ok(Some(n * per_unit)),
+        _ => Err(IconError::Svg(format!("invalid length '{}'", value))),
+    }
+}
+
+/// Width and height of a `viewBox="min-x min-y width height"`
+fn view_box_size(value: &str) -> Result<(f64, f64), IconError> {
+    let numbers: Vec<f64> = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|_| IconError::Svg(format!("invalid viewBox '{}'", value)))?;
+    match numbers[..] {
+        [_, _, w, h] if w.is_finite() && h.is_finite() && w > 0.0 && h > 0.0 => Ok((w, h)),
+        _ => Err(IconError::Svg(format!("invalid viewBox '{}'", value))),
+    }
+}
+
+fn pixels(length: f64) -> u32 {
+    (length.round() as u32).max(1)
+}
+
+/// Width and height from a PNG's `IHDR` chunk
+fn png_size(bytes: &[u8]) -> Result<(u32, u32), IconError> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if bytes.len() < 24 || !bytes.starts_with(SIGNATURE) || &bytes[12..16] != b"IHDR" {
+        return Err(IconError::Png);
+    }
+    let width = u32::from_be_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]);
+    let height = u32::from_be_bytes([bytes[20], bytes[21], bytes[22], bytes[23]]);
+    if width == 0 || height == 0 {
+        return Err(IconError::Png);
+    }
+    Ok((width, height))
+}
+
+/// Size of the largest image in an ICO directory (a 0 byte means 256)
+fn ico_size(bytes: &[u8]) -> Result<(u32, u32), IconError> {
+    if bytes.len() < 6 || bytes[0..4] != [0, 0, 1, 0] {
+        return Err(IconError::Ico);
+    }
+    let count = usize::from(u16::from_le_bytes([bytes[4], bytes[5]]));
+    let entries = bytes.get(6..6 + count * 16).ok_or(IconError::Ico)?;
+    let side = |b: u8| if b == 0 { 256 } else { u32::from(b) };
+    entries
+        .chunks_exact(16)
+        .map(|entry| (side(entry[0]), side(entry[1])))
+        .max_by_key(|&(w, h)| w * h)
+        .ok_or(IconError::Ico)
+}
+
+/// File modification time and length, to notice an edited icon
+type Stamp = (Option<SystemTime>, u64);
+
+/// Resolves slice icon references to inspected files, caching each file
+/// until it changes so a profile reload does not re-read or re-warn
+#[derive(Debug)]
+pub struct IconResolver {
+    /// Directory relative icon paths are resolved against
+    base_dir: PathBuf,
+    cache: HashMap<PathBuf, (Stamp, Option<IconInfo>)>,
+}
+
+impl IconResolver {
+    pub fn new(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// File a reference names, `None` for icon names and emoji
+    pub fn path_of(&self, icon: &str) -> Option<(PathBuf, IconFormat)> {
+        let format = IconFormat::of(icon)?;
+        let path = match icon.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir()?.join(rest),
+            None if Path::new(icon).is_absolute() => PathBuf::from(icon),
+            None => self.base_dir.join(icon),
+        };
+        Some((path, format))
+    }
+
+    /// The inspected file `icon` names; `None` for icon names and emoji, and
+    /// for files that cannot be used (logged once per change of the file)
+    pub fn resolve(&mut self, icon: &str) -> Option<IconInfo> {
+        let (path, format) = self.path_of(icon)?;
+        let metadata = std::fs::metadata(&path).ok();
+        let stamp = (metadata.as_ref().and_then(|m| m.modified().ok()), metadata.as_ref().map_or(0, |m| m.len()));
+        if let Some((cached, info)) = self.cache.get(&path) {
+            if *cached == stamp {
+                return info.clone();
+            }
+        }
+        let info = match IconInfo::inspect(&path, format) {
+            Ok(info) => Some(info),
+            Err(e) => {
+                tracing::warn!(icon = %path.display(), "Slice icon {}; the default icon is used", e);
+                None
+            }
+        };
+        self.cache.insert(path, (stamp, info.clone()));
+        info
+    }
+
+    /// Forget files no longer referenced (after a profile reload)
+    pub fn retain(&mut self, keep: impl Fn(&Path) -> bool) {
+        self.cache.retain(|path, _| keep(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORRUPT_SVG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/icon-corrupt.svg");
+
+    fn write(dir: &tempfile::TempDir, name: &str, bytes: &[u8]) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_svg_size_from_attributes_and_view_box() {
+        assert_eq!(svg_size(br#"<svg xmlns="http://www.w3.org/2000/svg" width="48" height="24"/>"#).unwrap(), Some((48, 24)));
+        assert_eq!(svg_size(br#"<?xml version="1.0"?><svg viewBox="0 0 16 32"><path d="M0 0"/></svg>"#).unwrap(), Some((16, 32)));
+        // Relative width falls back to the viewBox; one side follows its aspect
+        assert_eq!(svg_size(br#"<svg width="100%" viewBox="0,0,24,24"></svg>"#).unwrap(), Some((24, 24)));
+        assert_eq!(svg_size(br#"<svg width="12pt" viewBox="0 0 2 1"></svg>"#).unwrap(), Some((16, 8)));
+        assert_eq!(svg_size(br#"<svg:svg xmlns:svg="http://www.w3.org/2000/svg"></svg:svg>"#).unwrap(), None);
+    }
+
+    #[test]
+    fn test_malformed_svg_is_rejected() {
+        assert!(matches!(svg_size(std::fs::read(CORRUPT_SVG).unwrap().as_slice()), Err(IconError::Svg(_))));
+        assert!(matches!(svg_size(b"<html></html>"), Err(IconError::Svg(_))));
+        assert!(matches!(svg_size(b""), Err(IconError::Svg(_))));
+        assert!(matches!(svg_size(br#"<svg viewBox="0 0 0 24"/>"#), Err(IconError::Svg(_))));
+        assert!(matches!(svg_size(br#"<svg width="wide" height="24"/>"#), Err(IconError::Svg(_))));
+    }
+
+    #[test]
+    fn test_raster_sizes() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&[0, 0, 0, 64, 0, 0, 0, 32, 8, 6, 0, 0, 0]);
+        assert_eq!(png_size(&png).unwrap(), (64, 32));
+        assert!(png_size(b"GIF89a").is_err());
+
+        // Two images, 16x16 and 256x256 (stored as 0)
+        let mut ico = vec![0, 0, 1, 0, 2, 0];
+        ico.extend_from_slice(&[16, 16, 0, 0, 1, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        ico.extend_from_slice(&[0, 0, 0, 0, 1, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(ico_size(&ico).unwrap(), (256, 256));
+        assert!(ico_size(&ico[..20]).is_err());
+    }
+
+    #[test]
+    fn test_raster_box_follows_ring_and_scale() {
+        assert_eq!(raster_box(None, 8, None), 30);
+        assert_eq!(raster_box(Some(600), 8, None), 60);
+        // Twelve slices leave less arc per icon
+        assert_eq!(raster_box(None, 12, None), 29);
+        let hidpi = Monitor { x: 0, y: 0, width: 1920, height: 1080, scale: 2.0, compositor_scaled: true };
+        assert_eq!(raster_box(Some(450), 8, Some(&hidpi)), 90);
+        let x11 = Monitor { compositor_scaled: false, ..hidpi };
+        assert_eq!(raster_box(Some(450), 8, Some(&x11)), 45);
+
+        assert_eq!(fit(Some((48, 24)), 60), [60, 30]);
+        assert_eq!(fit(Some((16, 32)), 60), [30, 60]);
+        assert_eq!(fit(None, 60), [60, 60]);
+    }
+
+    #[test]
+    fn test_resolver_falls_back_on_bad_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir, "ok.svg", br#"<svg width="24" height="24"><circle r="4"/></svg>"#);
+        let mut resolver = IconResolver::new(dir.path().to_path_buf());
+
+        let info = resolver.resolve("ok.svg").unwrap();
+        assert_eq!(info.format, IconFormat::Svg);
+        assert_eq!(info.size, Some((24, 24)));
+        let hint = serde_json::to_value(info.hint(40)).unwrap();
+        assert_eq!(hint["raster"], serde_json::json!([40, 40]));
+        assert_eq!(hint["hash"], serde_json::json!(info.hash.to_string()));
+
+        assert_eq!(resolver.resolve(CORRUPT_SVG), None);
+        assert_eq!(resolver.resolve("missing.png"), None);
+        assert_eq!(resolver.resolve("dialog-information"), None);
+        assert_eq!(resolver.resolve("📋"), None);
+
+        // An edited file is read again
+        write(&dir, "ok.svg", br#"<svg width="48" height="24"/>"#);
+        let edited = resolver.resolve("ok.svg").unwrap();
+        assert_eq!(edited.size, Some((48, 24)));
+        assert_ne!(edited.hash, info.hash);
+    }
+}
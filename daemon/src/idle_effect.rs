@@ -0,0 +1,169 @@
+//! Idle effect limits and the power source
+//!
+//! A theme may animate the idle menu (`animation.idleEffect`: matrix rain or
+//! particles) at the density, speed and frame rate it names. The user's
+//! `idle_effects` section caps those, and with `disable_on_battery` (the
+//! default) turns the effect off while UPower reports the machine on battery.
+//! Reduced motion turns it off regardless.
+//!
+//! [`run_power_watcher`] reads UPower's `OnBattery` at startup and follows
+//! its changes. A change that turns the effect on or off is pushed to the
+//! overlay at once: `AnimationTimingsChanged` when `idle_effects_enabled`
+//! flips, and a fresh `MenuPayload` carrying the new `idle_effect`. Without
+//! UPower (a desktop machine, a container) the machine counts as on mains.
+
+
+use tokio_stream::StreamExt;
+
+use crate::accessibility::{current_animation_timings, emit_animation_timings_changed, SharedAccessibility};
+use crate::config::IdleEffectsConfig;
+use crate::dbus::DBUS_PATH;
+use crate::handles::StateHandle;
+use crate::theme::{IdleEffectLimits, SharedThemeManager};
+
+/// UPower bus name
+const UPOWER_BUS_NAME: &str = "org.freedesktop.UPower";
+
+/// UPower daemon object
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+
+/// UPower daemon interface
+const UPOWER_INTERFACE: &str = "org.freedesktop.UPower";
+
+/// Whether the machine draws from a battery (bool)
+const ON_BATTERY_PROPERTY: &str = "OnBattery";
+
+/// `idle_effects` and the power source
+#[derive(Debug, Default)]
+pub struct IdleEffectState {
+    config: IdleEffectsConfig,
+    /// Last `OnBattery` from UPower; `None` until it answers
+    on_battery: Option<bool>,
+}
+
+impl IdleEffectState {
+    /// Apply the `idle_effects` section
+    pub fn set_config(&mut self, config: IdleEffectsConfig) {
+        self.config = config;
+    }
+
+    /// Record UPower's `OnBattery`
+    pub fn set_on_battery(&mut self, on_battery: bool) {
+        self.on_battery = Some(on_battery);
+    }
+
+    /// The caps in effect now
+    pub fn limits(&self) -> IdleEffectLimits {
+        self.config.limits(self.on_battery.unwrap_or(false))
+    }
+}
+
+/// The daemon's idle effect state
+pub type IdleEffectHandle = StateHandle<IdleEffectState>;
+
+impl IdleEffectHandle {
+    /// The caps in effect now; no caps if the state is poisoned
+    pub fn limits(&self) -> IdleEffectLimits {
+        self.update(|s| s.limits()).unwrap_or_default()
+    }
+
+    /// The `idle_effect` block of `Status()`
+    pub fn status(&self) -> serde_json::Value {
+        self.update(|s| {
+            let limits = s.limits();
+            serde_json::json!({
+                "on_battery": s.on_battery,
+                "disable_on_battery": s.config.disable_on_battery,
+                "enabled": limits.enabled,
+                "max_fps": limits.max_fps,
+                "max_density": limits.max_density,
+            })
+        })
+        .unwrap_or(serde_json::Value::Null)
+    }
+}
+
+/// Follow UPower's `OnBattery` and push idle effect changes to the overlay
+///
+/// Best-effort: without UPower the machine counts as on mains and the
+/// watcher returns.
+pub async fn run_power_watcher(
+    connection: zbus::Connection,
+    themes: SharedThemeManager,
+    accessibility: SharedAccessibility,
+    idle_effect: IdleEffectHandle,
+) {
+    let proxy = match zbus::Proxy::new(&connection, UPOWER_BUS_NAME, UPOWER_PATH, UPOWER_INTERFACE).await {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::debug!(error = %e, "Power watcher: UPower unavailable");
+            return;
+        }
+    };
+
+    // Subscribe before the first read so a change in between is not lost
+    let mut changes = proxy.receive_property_changed::<bool>(ON_BATTERY_PROPERTY).await;
+    match proxy.get_property::<bool>(ON_BATTERY_PROPERTY).await {
+        Ok(on_battery) => {
+            idle_effect.update(|s| s.set_on_battery(on_battery));
+            tracing::info!(on_battery, "Power source read from UPower");
+        }
+        Err(e) => {
+            tracing::info!(error = %e, "UPower does not report OnBattery; idle effects are not limited by power source");
+            return;
+        }
+    }
+
+    while let Some(change) = changes.next().await {
+        let Ok(on_battery) = change.get().await else {
+            continue;
+        };
+        let timings_before = current_animation_timings(&themes, &accessibility, &idle_effect);
+        let changed = idle_effect.update(|s| {
+            let before = s.limits();
+            s.set_on_battery(on_battery);
+            s.limits() != before
+        })
+        .unwrap_or(false);
+        tracing::info!(on_battery, "Power source changed");
+        if !changed {
+            continue;
+        }
+        if current_animation_timings(&themes, &accessibility, &idle_effect) != timings_before {
+            if let Err(e) = emit_animation_timings_changed(&connection, &themes, &accessibility, &idle_effect).await {
+                tracing::warn!(error = %e, "Failed to emit AnimationTimingsChanged");
+            }
+        }
+        if let Err(e) = refresh_menu_payload(&connection).await {
+            tracing::warn!(error = %e, "Failed to emit refreshed MenuPayload");
+        }
+    }
+}
+
+/// Emit a fresh `MenuPayload` so an idle overlay starts or stops the effect
+async fn refresh_menu_payload(connection: &zbus::Connection) -> zbus::Result<()> {
+    let iface = connection
+        .object_server()
+        .interface::<_, crate::dbus::JuhRadialService>(DBUS_PATH)
+        .await?;
+    let service = iface.get().await;
+    service.emit_menu_payload(iface.signal_emitter()).await.map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_battery_turns_effects_off_unless_configured_otherwise() {
+        let mut state = IdleEffectState::default();
+        assert!(state.limits().enabled, "no UPower answer counts as mains");
+        state.set_on_battery(true);
+        assert!(!state.limits().enabled);
+        state.set_config(IdleEffectsConfig { disable_on_battery: false, ..Default::default() });
+        assert!(state.limits().enabled);
+        state.set_config(IdleEffectsConfig { enabled: false, ..Default::default() });
+        state.set_on_battery(false);
+        assert!(!state.limits().enabled);
+    }
+}
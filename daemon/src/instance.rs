@@ -0,0 +1,259 @@
+//! Single-instance guard
+//!
+//! Two daemons (autostart plus a manual launch) fight over evdev grabs and
+//! HID++ diverts, which users see as "my keyboard stopped working". The first
+//! guard is the bus name: [`claim_name`] takes `org.kde.juhradialmx` with
+//! do-not-queue semantics, so a second daemon on the same session bus is
+//! refused. The second is a file lock, `$XDG_RUNTIME_DIR/juhradial/juhradiald.lock`,
+//! which also catches a daemon the name cannot: one on another session bus
+//! (a terminal with its own `dbus-launch`, a session without a shared bus).
+//! The lock is `flock(2)`: the kernel drops it when its holder
+//! exits, crashes included, so a left-over file never blocks startup. The
+//! file records the holder's pid for the messages.
+//!
+//! `--replace` asks the running daemon to leave instead: `Quit` over D-Bus
+//! when it owns the name, otherwise SIGTERM to the pid in the lock file. Both
+//! take the daemon's normal shutdown path (menu dismissed, stats flushed,
+//! diverts released), and the new daemon waits up to [`REPLACE_TIMEOUT`] for
+//! the name and the lock to come free.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::dbus::{claim_name, DBUS_INTERFACE, DBUS_NAME, DBUS_PATH};
+use crate::fs_util;
+
+/// Lock file name in the private runtime directory
+pub const LOCK_FILE_NAME: &str = "juhradiald.lock";
+
+/// How long `--replace` waits for the running daemon to exit; its own
+/// shutdown is bounded at 2s
+pub const REPLACE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Poll interval while waiting for a replaced daemon to go
+const REPLACE_POLL: Duration = Duration::from_millis(100);
+
+/// Why this process may not run as the daemon
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum InstanceError {
+    /// The lock file could not be created or locked
+    #[error("Cannot lock {}: {source}", path.display())]
+    Lock {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// `--replace` gave the running daemon its time and it is still there
+    #[error("the running juhradiald did not exit within {}s of being asked to", REPLACE_TIMEOUT.as_secs())]
+    ReplaceTimedOut,
+    /// The name claim or the `Quit` call failed
+    #[error("D-Bus error: {0}")]
+    DBus(#[from] zbus::Error),
+}
+
+/// Held [`LOCK_FILE_NAME`]; released when dropped or when the process exits
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+    path: PathBuf,
+}
+
+/// Result of [`InstanceLock::acquire`]
+#[derive(Debug)]
+pub enum LockAttempt {
+    Acquired(InstanceLock),
+    /// Another process holds it; its pid when the file names one
+    Held { pid: Option<u32> },
+}
+
+impl InstanceLock {
+    /// Lock `path`, creating it `0600`, and record our pid in it
+    pub fn acquire(path: &Path) -> Result<LockAttempt, InstanceError> {
+        let failed = |source| InstanceError::Lock { path: path.to_path_buf(), source };
+        if let Some(dir) = path.parent() {
+            fs_util::private_dir(dir).map_err(failed)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .mode(0o600)
+            .custom_flags(libc::O_NOFOLLOW | libc::O_CLOEXEC)
+            .open(path)
+            .map_err(failed)?;
+        // SAFETY: flock on a descriptor we own; no memory is involved
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() == Some(libc::EWOULDBLOCK) {
+                let mut contents = String::new();
+                let _ = file.read_to_string(&mut contents);
+                return Ok(LockAttempt::Held { pid: contents.trim().parse().ok() });
+            }
+            return Err(failed(error));
+        }
+        file.set_len(0).map_err(failed)?;
+        file.rewind().map_err(failed)?;
+        writeln!(file, "{}", std::process::id()).map_err(failed)?;
+        Ok(LockAttempt::Acquired(Self { _file: file, path: path.to_path_buf() }))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/juhradial/juhradiald.lock`; `None` without a runtime
+/// directory
+pub fn lock_path() -> Option<PathBuf> {
+    fs_util::runtime_dir().map(|d| d.join(LOCK_FILE_NAME))
+}
+
+/// Outcome of [`claim`]
+#[derive(Debug)]
+pub enum Claim {
+    /// This process is the daemon; the lock is `None` when it could not be
+    /// taken for a reason other than another daemon (no runtime directory)
+    Owned(Option<InstanceLock>),
+    /// Another daemon runs; `pid` when the lock file names it
+    Running { pid: Option<u32> },
+}
+
+/// Become the only daemon: the bus name and the lock, or, with `replace`,
+/// ask the running daemon to quit and wait for both
+pub async fn claim(connection: &zbus::Connection, replace: bool) -> Result<Claim, InstanceError> {
+    let path = lock_path();
+    let mut lock = try_lock(path.as_deref());
+    let mut named = claim_name(connection, DBUS_NAME).await?;
+    let held_by = match &lock {
+        Some(LockAttempt::Held { pid }) => Some(*pid),
+        _ => None,
+    };
+    if named && held_by.is_none() {
+        return Ok(Claim::Owned(acquired(lock)));
+    }
+    if !replace {
+        return Ok(Claim::Running { pid: held_by.flatten() });
+    }
+
+    if !named {
+        tracing::info!("Asking the running juhradiald to quit (--replace)");
+        connection.call_method(Some(DBUS_NAME), DBUS_PATH, Some(DBUS_INTERFACE), "Quit", &()).await?;
+    } else if let Some(Some(pid)) = held_by {
+        // Not on our bus, so it cannot hear Quit; SIGTERM takes the same path
+        tracing::info!(pid, "The running juhradiald is on another bus; sending SIGTERM (--replace)");
+        // SAFETY: plain kill(2); the pid holds our lock, so it is a live daemon
+        unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+    }
+    let deadline = Instant::now() + REPLACE_TIMEOUT;
+    loop {
+        if !named {
+            named = claim_name(connection, DBUS_NAME).await?;
+        }
+        if matches!(lock, Some(LockAttempt::Held { .. })) {
+            lock = try_lock(path.as_deref());
+        }
+        if named && !matches!(lock, Some(LockAttempt::Held { .. })) {
+            tracing::info!("Took over from the previous juhradiald");
+            return Ok(Claim::Owned(acquired(lock)));
+        }
+        if Instant::now() >= deadline {
+            return Err(InstanceError::ReplaceTimedOut);
+        }
+        tokio::time::sleep(REPLACE_POLL).await;
+    }
+}
+
+/// The lock at `path`; `None` when it cannot be taken at all (no runtime
+/// directory, an unwritable one), which leaves the guard to the bus name
+fn try_lock(path: Option<&Path>) -> Option<LockAttempt> {
+    let Some(path) = path else {
+        tracing::warn!("No XDG_RUNTIME_DIR; the single-instance guard relies on the bus name alone");
+        return None;
+    };
+    InstanceLock::acquire(path)
+        .inspect_err(|e| {
+            tracing::warn!(error = %e, "Instance lock unavailable; the single-instance guard relies on the bus name alone")
+        })
+        .ok()
+}
+
+fn acquired(lock: Option<LockAttempt>) -> Option<InstanceLock> {
+    match lock {
+        Some(LockAttempt::Acquired(lock)) => Some(lock),
+        _ => None,
+    }
+}
+
+/// A request to shut down as on SIGTERM (the `Quit` D-Bus method)
+///
+/// Clones share the request; the daemon keeps one on `DaemonHandles`.
+#[derive(Debug, Clone, Default)]
+pub struct QuitHandle {
+    notify: Arc<Notify>,
+}
+
+impl QuitHandle {
+    /// Ask the daemon to shut down
+    pub fn request(&self) {
+        // notify_one keeps the request if the main loop is not waiting yet
+        self.notify.notify_one();
+    }
+
+    /// Resolves once [`Self::request`] has been called
+    pub async fn requested(&self) {
+        self.notify.notified().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_second_lock_is_refused_until_the_first_goes() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("juhradial").join(LOCK_FILE_NAME);
+        let LockAttempt::Acquired(first) = InstanceLock::acquire(&path).unwrap() else {
+            panic!("first lock refused");
+        };
+        assert_eq!(std::fs::read_to_string(first.path()).unwrap().trim(), std::process::id().to_string());
+        // flock locks belong to the open file, so a second open in the same
+        // process conflicts like another daemon would
+        let LockAttempt::Held { pid } = InstanceLock::acquire(&path).unwrap() else {
+            panic!("second lock granted");
+        };
+        assert_eq!(pid, Some(std::process::id()));
+        drop(first);
+        assert!(matches!(InstanceLock::acquire(&path).unwrap(), LockAttempt::Acquired(_)));
+    }
+
+    #[test]
+    fn test_stale_lock_file_does_not_block() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOCK_FILE_NAME);
+        // Left behind by a crashed daemon: the file stays, its lock does not
+        std::fs::write(&path, "999999\n").unwrap();
+        let LockAttempt::Acquired(lock) = InstanceLock::acquire(&path).unwrap() else {
+            panic!("stale lock file blocked startup");
+        };
+        assert_eq!(std::fs::read_to_string(lock.path()).unwrap(), format!("{}\n", std::process::id()));
+    }
+
+    #[tokio::test]
+    async fn test_quit_request_is_kept_until_awaited() {
+        let quit = QuitHandle::default();
+        quit.clone().request();
+        tokio::time::timeout(Duration::from_secs(1), quit.requested()).await.unwrap();
+    }
+}
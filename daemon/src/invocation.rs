@@ -0,0 +1,250 @@
+//! What was around when an action was picked
+//!
+//! `ShowMenu` records an [`InvocationContext`]: the press point, the focused
+//! window's class, the profile in effect and the time. `ReportMenuPlacement`
+//! adds the centre the overlay drew the ring at (the press point until then),
+//! and the slice is filled in when one is picked. `ShowAdHocMenu` records one
+//! without a profile. Thumb-wheel bindings, which fire without a menu, carry
+//! only the window class and profile.
+//!
+//! The executor hands the context to actions as the `JUH_*` variables in
+//! [`VARIABLES`]. Values are never spliced into a command: the variables are
+//! set in the spawned shell's environment and the shell expands
+//! `$JUH_CURSOR_X` itself, so a window class such as `"; rm -rf ~` is never
+//! read back as shell syntax. D-Bus call arguments get `$JUH_NAME` and
+//! `${JUH_NAME}` replaced in their strings, and each reaches `dbus-send` as
+//! its own argument. Unknown values are empty.
+//!
+//! The last invocation lives in an [`InvocationHandle`] on `DaemonHandles`.
+//! The overlay spawns the Settings ring's commands itself, so it reads the
+//! same variables with `GetInvocation` and sets them in their environment.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Press point, compositor-logical pixels
+pub const CURSOR_X: &str = "JUH_CURSOR_X";
+pub const CURSOR_Y: &str = "JUH_CURSOR_Y";
+/// Centre the ring was drawn at, clamped away from screen edges
+pub const CENTER_X: &str = "JUH_CENTER_X";
+pub const CENTER_Y: &str = "JUH_CENTER_Y";
+/// Focused window's resource class, lowercased
+pub const WINDOW_CLASS: &str = "JUH_WINDOW_CLASS";
+/// Profile the menu was opened with
+pub const PROFILE: &str = "JUH_PROFILE";
+/// Picked slot, from 0
+pub const SLICE: &str = "JUH_SLICE";
+/// Milliseconds since the Unix epoch at the press
+pub const TIMESTAMP: &str = "JUH_TIMESTAMP";
+
+/// Every variable an action sees
+pub const VARIABLES: [&str; 8] = [CURSOR_X, CURSOR_Y, CENTER_X, CENTER_Y, WINDOW_CLASS, PROFILE, SLICE, TIMESTAMP];
+
+/// The menu invocation an action runs for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InvocationContext {
+    /// Press point
+    pub cursor: Option<(i32, i32)>,
+    /// Rendered ring centre; the press point until the overlay reports it
+    pub center: Option<(i32, i32)>,
+    pub window_class: Option<String>,
+    pub profile: Option<String>,
+    pub slice: Option<u8>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+impl InvocationContext {
+    /// A context stamped now, with nothing else known
+    pub fn now() -> Self {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Self { timestamp_ms, ..Self::default() }
+    }
+
+    /// The focused window and the profile in effect
+    pub fn in_window(mut self, window_class: &str, profile: Option<&str>) -> Self {
+        self.window_class = Some(window_class.to_string()).filter(|c| !c.is_empty());
+        self.profile = profile.map(str::to_string);
+        self
+    }
+
+    /// A menu pressed at `cursor`
+    pub fn at(mut self, cursor: (i32, i32)) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// The picked slot
+    pub fn with_slice(mut self, slice: u8) -> Self {
+        self.slice = Some(slice);
+        self
+    }
+
+    /// Value of one of [`VARIABLES`]; empty when unknown
+    pub fn value(&self, name: &str) -> Option<String> {
+        let center = self.center.or(self.cursor);
+        let coordinate = |c: Option<i32>| c.map(|c| c.to_string()).unwrap_or_default();
+        Some(match name {
+            CURSOR_X => coordinate(self.cursor.map(|c| c.0)),
+            CURSOR_Y => coordinate(self.cursor.map(|c| c.1)),
+            CENTER_X => coordinate(center.map(|c| c.0)),
+            CENTER_Y => coordinate(center.map(|c| c.1)),
+            WINDOW_CLASS => self.window_class.clone().unwrap_or_default(),
+            PROFILE => self.profile.clone().unwrap_or_default(),
+            SLICE => self.slice.map(|s| s.to_string()).unwrap_or_default(),
+            TIMESTAMP => self.timestamp_ms.to_string(),
+            _ => return None,
+        })
+    }
+
+    /// All variables with their values, for a child's environment
+    pub fn variables(&self) -> impl Iterator<Item = (&'static str, String)> + '_ {
+        VARIABLES.into_iter().map(|name| (name, self.value(name).unwrap_or_default()))
+    }
+
+    /// Replace `$JUH_NAME` and `${JUH_NAME}` in `text`
+    ///
+    /// A single pass: substituted values are not scanned again. Names that
+    /// are not in [`VARIABLES`] are left as written.
+    pub fn substitute(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(at) = rest.find('$') {
+            out.push_str(&rest[..at]);
+            let after = &rest[at + 1..];
+            let (name, consumed) = match after.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], end + 2),
+                    None => ("", 0),
+                },
+                None => {
+                    let end = after
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(after.len());
+                    (&after[..end], end)
+                }
+            };
+            match self.value(name) {
+                Some(value) => {
+                    out.push_str(&value);
+                    rest = &after[consumed..];
+                }
+                None => {
+                    out.push('$');
+                    rest = after;
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// The last menu invocation, shared by the D-Bus service and the input loop
+#[derive(Debug, Clone, Default)]
+pub struct InvocationHandle {
+    current: Arc<Mutex<Option<InvocationContext>>>,
+}
+
+impl InvocationHandle {
+    /// A menu opened; replaces the last invocation
+    pub fn opened(&self, context: InvocationContext) {
+        if let Ok(mut current) = self.current.lock() {
+            *current = Some(context);
+        }
+    }
+
+    /// The overlay drew the open menu centred on `center`
+    pub fn placed(&self, center: (i32, i32)) {
+        if let Ok(mut current) = self.current.lock() {
+            if let Some(context) = current.as_mut() {
+                context.center = Some(center);
+            }
+        }
+    }
+
+    /// The last menu invocation, kept after the menu closes so its action
+    /// can still read it
+    pub fn current(&self) -> Option<InvocationContext> {
+        self.current.lock().ok().and_then(|c| c.clone())
+    }
+
+    /// [`VARIABLES`] of the last invocation with `slice` picked, for a
+    /// command the overlay spawns; a fresh context when there was none
+    pub fn variables(&self, slice: Option<u8>) -> HashMap<String, String> {
+        let context = self.current().unwrap_or_else(InvocationContext::now);
+        let context = match slice {
+            Some(slice) => context.with_slice(slice),
+            None => context,
+        };
+        context.variables().map(|(name, value)| (name.to_string(), value)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> InvocationContext {
+        InvocationContext {
+            cursor: Some((10, 1070)),
+            center: Some((160, 920)),
+            window_class: Some("org.kde.dolphin".into()),
+            profile: Some("files".into()),
+            slice: Some(3),
+            timestamp_ms: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_substitute_known_variables() {
+        let context = context();
+        assert_eq!(
+            context.substitute("grab --x $JUH_CURSOR_X --y ${JUH_CURSOR_Y} at $JUH_CENTER_X,$JUH_CENTER_Y"),
+            "grab --x 10 --y 1070 at 160,920"
+        );
+        assert_eq!(context.substitute("${JUH_PROFILE}/$JUH_SLICE@$JUH_TIMESTAMP"), "files/3@1700000000000");
+        // Unknown names, lone and unclosed dollars stay as written
+        assert_eq!(context.substitute("$HOME $JUH_NOPE $ ${JUH_SLICE"), "$HOME $JUH_NOPE $ ${JUH_SLICE");
+        assert_eq!(context.substitute("cost: 5$"), "cost: 5$");
+    }
+
+    #[test]
+    fn test_unknown_values_are_empty_and_center_defaults_to_press() {
+        let context = InvocationContext::now().in_window("", None).at((5, 6));
+        assert_eq!(context.value(CENTER_X).as_deref(), Some("5"));
+        assert_eq!(context.value(WINDOW_CLASS).as_deref(), Some(""));
+        assert_eq!(context.value(SLICE).as_deref(), Some(""));
+        assert!(context.timestamp_ms > 0);
+        assert_eq!(context.variables().count(), VARIABLES.len());
+    }
+
+    #[test]
+    fn test_substituted_values_are_not_expanded_again() {
+        let context = InvocationContext {
+            window_class: Some("$JUH_PROFILE ${JUH_SLICE} $(id)".into()),
+            profile: Some("secret".into()),
+            ..context()
+        };
+        assert_eq!(context.substitute("class=$JUH_WINDOW_CLASS"), "class=$JUH_PROFILE ${JUH_SLICE} $(id)");
+    }
+
+    #[test]
+    fn test_placement_fills_in_the_open_menu() {
+        let handle = InvocationHandle::default();
+        handle.opened(InvocationContext::now().at((0, 0)));
+        handle.clone().placed((160, 160));
+        let current = handle.current().unwrap();
+        assert_eq!(current.value(CENTER_Y).as_deref(), Some("160"));
+        assert_eq!(current.value(CURSOR_Y).as_deref(), Some("0"));
+
+        let variables = handle.variables(Some(2));
+        assert_eq!(variables[SLICE], "2");
+        assert_eq!(variables[CENTER_X], "160");
+        assert_eq!(variables.len(), VARIABLES.len());
+    }
+}
@@ -0,0 +1,374 @@
+//! KWin scripts
+//!
+//! On KDE the daemon talks to KWin through its scripting API
+//! (`org.kde.kwin.Scripting`). Every script is loaded under a stable plugin
+//! name, so loading it again replaces the previous copy instead of leaving one
+//! resident per call. Its file gets a unique name in the user's private
+//! runtime directory (`$XDG_RUNTIME_DIR/juhradial/kwin`, `0700`) and is
+//! checked to be the user's own just before KWin is asked to load it; it is
+//! removed when the next copy of the same script replaces it.
+//!
+//! The resident helper (`kwin/juhradial-helper.js`, compiled in with
+//! `include_str!`) reports the cursor position, the active window and the
+//! screen layout to the daemon (`ReportCursorPosition` /
+//! `ReportActiveWindow` / `ReportScreens`, the last for `monitors`). The KWin watcher
+//! (`compositor::run_kwin_watcher`) calls [`KWinHelper::reattach`] whenever
+//! `org.kde.KWin` gains or loses an owner, so a KWin restart gets a fresh
+//! copy, and [`KWinHelper::unload_all`] removes it on shutdown. While it runs,
+//! `cursor.rs` answers from its last report and the window tracker skips its
+//! own script; otherwise both keep their one-shot paths
+//! ([`KWinHelper::run_script_blocking`]).
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tempfile::TempPath;
+
+use crate::compositor::KWIN_BUS_NAME;
+use crate::cursor::CursorPosition;
+use crate::fs_util;
+
+/// The resident helper script
+pub const HELPER_SCRIPT: &str = include_str!("../kwin/juhradial-helper.js");
+
+/// Plugin name of the resident helper
+pub const HELPER_PLUGIN: &str = "juhradialmx-helper";
+
+/// Plugin name of the one-shot cursor script (`cursor::KWIN_CURSOR_SCRIPT`)
+pub const CURSOR_PLUGIN: &str = "juhradialmx-cursor";
+
+/// Plugin name of the standalone active-window script, used without the helper
+pub const ACTIVE_WINDOW_PLUGIN: &str = "juhradialmx-active-window";
+
+const SCRIPTING_PATH: &str = "/Scripting";
+const SCRIPTING_INTERFACE: &str = "org.kde.kwin.Scripting";
+const SCRIPT_INTERFACE: &str = "org.kde.kwin.Script";
+
+/// A restarted KWin can own its name before `/Scripting` is exported
+const INSTALL_ATTEMPTS: u32 = 3;
+const INSTALL_RETRY: Duration = Duration::from_secs(1);
+
+/// No helper loaded
+const NO_SCRIPT: i32 = -1;
+
+/// KWin script errors
+#[derive(Debug, thiserror::Error)]
+pub enum KWinScriptError {
+    #[error("Cannot write KWin script: {0}")]
+    Io(#[from] io::Error),
+    #[error("KWin D-Bus error: {0}")]
+    DBus(#[from] zbus::Error),
+    #[error("KWin did not load {0}")]
+    Rejected(&'static str),
+}
+
+/// The resident helper as the daemon knows it, and the script files written
+///
+/// Kept by the KWin watcher, read by the input loops and the D-Bus service.
+/// Clones share the state.
+#[derive(Debug, Clone)]
+pub struct KWinHelper {
+    /// KWin's id for the loaded helper, or [`NO_SCRIPT`]
+    id: Arc<AtomicI32>,
+    /// The helper's last cursor report
+    cursor: Arc<Mutex<Option<CursorPosition>>>,
+    /// The current script file of each plugin; dropping one deletes it
+    files: Arc<Mutex<HashMap<&'static str, TempPath>>>,
+}
+
+impl Default for KWinHelper {
+    fn default() -> Self {
+        Self {
+            id: Arc::new(AtomicI32::new(NO_SCRIPT)),
+            cursor: Arc::default(),
+            files: Arc::default(),
+        }
+    }
+}
+
+impl KWinHelper {
+    /// Whether the resident helper is loaded
+    pub fn is_active(&self) -> bool {
+        self.id.load(Ordering::Acquire) != NO_SCRIPT
+    }
+
+    /// The cursor position the helper last reported, while it is loaded
+    pub fn cursor(&self) -> Option<CursorPosition> {
+        if !self.is_active() {
+            return None;
+        }
+        self.cursor.lock().ok().and_then(|c| *c)
+    }
+
+    /// Record a cursor report from the helper
+    pub fn report_cursor(&self, x: i32, y: i32) {
+        if let Ok(mut cursor) = self.cursor.lock() {
+            *cursor = Some(CursorPosition::new(x, y));
+        }
+    }
+
+    fn set_id(&self, id: i32) {
+        self.id.store(id, Ordering::Release);
+        if id == NO_SCRIPT {
+            // A position from before a KWin restart is no answer
+            if let Ok(mut cursor) = self.cursor.lock() {
+                *cursor = None;
+            }
+        }
+    }
+
+    /// Install the helper for a (new) KWin, or forget it when KWin went away
+    pub async fn reattach(&self, connection: &zbus::Connection, owned: bool) {
+        // Any previous id belonged to the old KWin
+        self.set_id(NO_SCRIPT);
+        if !owned {
+            return;
+        }
+        for attempt in 1..=INSTALL_ATTEMPTS {
+            match self.install_helper(connection).await {
+                Ok(id) => {
+                    self.set_id(id);
+                    tracing::info!(script_id = id, "KWin helper script loaded");
+                    return;
+                }
+                Err(e) if attempt < INSTALL_ATTEMPTS => {
+                    tracing::debug!(error = %e, attempt, "KWin helper install failed, retrying");
+                    tokio::time::sleep(INSTALL_RETRY).await;
+                }
+                Err(e) => tracing::warn!(error = %e, "KWin helper install failed; using one-shot KWin scripts"),
+            }
+        }
+    }
+
+    /// Unload every script the daemon may have left in KWin
+    ///
+    /// Called on shutdown, after the KWin watcher has stopped. Best-effort: a
+    /// missing KWin has nothing loaded.
+    pub async fn unload_all(&self, connection: &zbus::Connection) {
+        let had_helper = self.is_active();
+        self.set_id(NO_SCRIPT);
+        for plugin in [HELPER_PLUGIN, CURSOR_PLUGIN, ACTIVE_WINDOW_PLUGIN] {
+            match unload(connection, plugin).await {
+                Ok(true) => tracing::debug!(plugin, "KWin script unloaded"),
+                Ok(false) => {}
+                Err(e) if had_helper => tracing::warn!(plugin, error = %e, "Failed to unload KWin script"),
+                Err(e) => tracing::trace!(plugin, error = %e, "KWin script unload skipped"),
+            }
+        }
+        self.remove_script_files();
+    }
+
+    /// Write `script` to a new `<plugin>-<random>.js` in the script directory
+    ///
+    /// The file replaces (and deletes) the previous one written for `plugin`.
+    pub fn write_script(&self, plugin: &'static str, script: &str) -> io::Result<PathBuf> {
+        let dir = script_dir()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no runtime or cache directory"))?;
+        let file = write_script_in(&dir, plugin, script)?;
+        let path = file.to_path_buf();
+        if let Ok(mut files) = self.files.lock() {
+            files.insert(plugin, file);
+        }
+        Ok(path)
+    }
+
+    /// Delete every script file written so far
+    fn remove_script_files(&self) {
+        if let Ok(mut files) = self.files.lock() {
+            files.clear();
+        }
+    }
+
+    async fn install_helper(&self, connection: &zbus::Connection) -> Result<i32, KWinScriptError> {
+        let id = self.install_script(connection, HELPER_SCRIPT, HELPER_PLUGIN).await?;
+        // The helper reports the active window itself
+        if let Err(e) = unload(connection, ACTIVE_WINDOW_PLUGIN).await {
+            tracing::debug!(error = %e, "Could not unload the standalone active-window script");
+        }
+        Ok(id)
+    }
+
+    /// Write `script` and load it as `plugin`, replacing a loaded copy
+    pub async fn install_script(
+        &self,
+        connection: &zbus::Connection,
+        script: &str,
+        plugin: &'static str,
+    ) -> Result<i32, KWinScriptError> {
+        let path = self.write_script(plugin, script)?;
+        load(connection, &path, plugin).await
+    }
+
+    /// Load and run `script` as `plugin` through `dbus-send`
+    ///
+    /// Blocking, for the input threads. The previous copy under the same plugin
+    /// name is unloaded first, so at most one stays resident.
+    pub fn run_script_blocking(&self, script: &str, plugin: &'static str) -> Result<i32, KWinScriptError> {
+        let path = self.write_script(plugin, script)?;
+        // Nothing loaded under that name is fine
+        let _ = dbus_send(SCRIPTING_PATH, "org.kde.kwin.Scripting.unloadScript", &[&format!("string:{plugin}")]);
+        fs_util::check_owned(&path)?;
+
+        let load_output = dbus_send(
+            SCRIPTING_PATH,
+            "org.kde.kwin.Scripting.loadScript",
+            &[&format!("string:{}", path.to_string_lossy()), &format!("string:{plugin}")],
+        )?;
+        let id = parse_int32_reply(&String::from_utf8_lossy(&load_output))
+            .filter(|id| *id >= 0)
+            .ok_or(KWinScriptError::Rejected(plugin))?;
+
+        dbus_send(&format!("{SCRIPTING_PATH}/Script{id}"), "org.kde.kwin.Script.run", &[])?;
+        Ok(id)
+    }
+}
+
+/// Script files go in the private runtime directory, or the cache without one
+fn script_dir() -> Option<PathBuf> {
+    let paths = crate::paths::paths();
+    Some(paths.runtime_dir.as_ref().unwrap_or(&paths.cache_dir).join("kwin"))
+}
+
+/// [`KWinHelper::write_script`] into `dir`, creating it; both are private to the user
+fn write_script_in(dir: &Path, plugin: &str, script: &str) -> io::Result<TempPath> {
+    fs_util::private_dir(dir)?;
+    fs_util::create_private_temp(dir, &format!("{plugin}-"), ".js", script.as_bytes())
+}
+
+// ============================================================================
+// Loading over D-Bus
+// ============================================================================
+
+/// Load `path` as `plugin` (replacing a loaded copy) and run it
+async fn load(connection: &zbus::Connection, path: &Path, plugin: &'static str) -> Result<i32, KWinScriptError> {
+    // loadScript refuses a plugin name that is already loaded
+    unload(connection, plugin).await?;
+    fs_util::check_owned(path)?;
+    let scripting = zbus::Proxy::new(connection, KWIN_BUS_NAME, SCRIPTING_PATH, SCRIPTING_INTERFACE).await?;
+    let id: i32 = scripting
+        .call("loadScript", &(path.to_string_lossy().as_ref(), plugin))
+        .await?;
+    if id < 0 {
+        return Err(KWinScriptError::Rejected(plugin));
+    }
+    let script_path = format!("{SCRIPTING_PATH}/Script{id}");
+    let script = zbus::Proxy::new(connection, KWIN_BUS_NAME, script_path, SCRIPT_INTERFACE).await?;
+    script.call_method("run", &()).await?;
+    Ok(id)
+}
+
+/// `org.kde.kwin.Scripting.unloadScript`; whether a script was loaded
+async fn unload(connection: &zbus::Connection, plugin: &str) -> zbus::Result<bool> {
+    let scripting = zbus::Proxy::new(connection, KWIN_BUS_NAME, SCRIPTING_PATH, SCRIPTING_INTERFACE).await?;
+    scripting.call("unloadScript", &(plugin,)).await
+}
+
+// ============================================================================
+// One-shot scripts (blocking, for the input threads)
+// ============================================================================
+
+/// `dbus-send --print-reply` to KWin; stdout on success
+fn dbus_send(path: &str, method: &str, args: &[&str]) -> io::Result<Vec<u8>> {
+    let dest = format!("--dest={KWIN_BUS_NAME}");
+    let output = Command::new("dbus-send")
+        .args(["--session", "--print-reply", &dest, path, method])
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{} failed: {}",
+            method,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// The value of a `dbus-send --print-reply` int32 reply (`   int32 5`)
+fn parse_int32_reply(stdout: &str) -> Option<i32> {
+    stdout
+        .lines()
+        .find(|line| line.contains("int32"))
+        .and_then(|line| line.split_whitespace().last())
+        .and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_parse_int32_reply() {
+        let reply = "method return time=1.0 sender=:1.20 -> destination=:1.99 serial=7 reply_serial=2\n   int32 5\n";
+        assert_eq!(parse_int32_reply(reply), Some(5));
+        assert_eq!(parse_int32_reply("   int32 -1\n"), Some(-1));
+        assert_eq!(parse_int32_reply("   boolean true\n"), None);
+    }
+
+    #[test]
+    fn test_scripts_are_private_and_uniquely_named() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("juhradial").join("kwin");
+        let first = write_script_in(&dir, CURSOR_PLUGIN, "first").unwrap();
+        let second = write_script_in(&dir, CURSOR_PLUGIN, "second").unwrap();
+        assert_ne!(first.to_path_buf(), second.to_path_buf());
+        let name = second.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with("juhradialmx-cursor-") && name.ends_with(".js"), "{name}");
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "second");
+        fs_util::check_owned(&second).unwrap();
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&second), 0o600);
+        assert_eq!(mode(&dir), 0o700);
+
+        // Replacing a script deletes the old file
+        let old = first.to_path_buf();
+        drop(first);
+        assert!(!old.exists());
+    }
+
+    #[test]
+    fn test_script_dir_behind_a_symlink_is_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let elsewhere = tmp.path().join("elsewhere");
+        std::fs::create_dir(&elsewhere).unwrap();
+        let dir = tmp.path().join("kwin");
+        std::os::unix::fs::symlink(&elsewhere, &dir).unwrap();
+        assert!(write_script_in(&dir, CURSOR_PLUGIN, "x").is_err());
+        assert_eq!(std::fs::read_dir(&elsewhere).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_helper_cursor_only_while_loaded() {
+        let helper = KWinHelper::default();
+        helper.report_cursor(640, 360);
+        helper.set_id(NO_SCRIPT);
+        assert_eq!(helper.cursor().map(|c| (c.x, c.y)), None);
+
+        helper.set_id(7);
+        assert_eq!(helper.cursor().map(|c| (c.x, c.y)), None);
+        helper.report_cursor(640, 360);
+        let shared = helper.clone();
+        assert_eq!(shared.cursor().map(|c| (c.x, c.y)), Some((640, 360)));
+        helper.set_id(NO_SCRIPT);
+        assert!(!shared.is_active());
+        assert_eq!(shared.cursor().map(|c| (c.x, c.y)), None);
+    }
+
+    #[test]
+    fn test_helper_script_reports_to_the_daemon() {
+        assert!(HELPER_SCRIPT.contains("\"ReportCursorPosition\""));
+        assert!(HELPER_SCRIPT.contains("\"ReportActiveWindow\""));
+        assert!(HELPER_SCRIPT.contains("\"ReportScreens\""));
+        assert!(HELPER_SCRIPT.contains("workspace.screensChanged.connect"));
+        assert!(HELPER_SCRIPT.contains("org.kde.juhradialmx.Daemon"));
+        // Plasma 6 and Plasma 5 activation signals
+        assert!(HELPER_SCRIPT.contains("workspace.windowActivated.connect"));
+        assert!(HELPER_SCRIPT.contains("workspace.clientActivated.connect"));
+    }
+}
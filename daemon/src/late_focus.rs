@@ -0,0 +1,164 @@
+//! Focus reports that arrive just after the menu opened
+//!
+//! The menu never waits for a window lookup: the window tracker pushes each
+//! focus change as it happens, and `ShowMenu` sends the payload for the
+//! profile of the last reported class (or `default`). A report can still land
+//! a few milliseconds after the press, for example when a click focused a
+//! window and the gesture button followed at once. If one arrives within
+//! `menu.late_focus_ms` of the open and resolves to a different profile, the
+//! daemon sends a fresh `MenuPayload` for it so the overlay swaps the slice
+//! set before the pointer has moved.
+//!
+//! `LateFocus` is the pure state machine (times are passed in). `Status()`
+//! reports how many opens saw a late report and how many of those swapped the
+//! profile under `late_focus`; if swaps stay rare the window can be shortened.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::content_hash::ContentHash;
+use crate::dbus::DBUS_PATH;
+use crate::handles::StateHandle;
+
+/// Default `menu.late_focus_ms`
+pub const LATE_FOCUS_MS: u64 = 50;
+
+/// Counters for `Status()`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LateFocusStats {
+    /// Menus opened from a profile (`ShowMenu`)
+    pub opens: u64,
+    /// Opens that saw a focus report within the window
+    pub late_reports: u64,
+    /// Late reports that changed the profile and resent the payload
+    pub swaps: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct OpenMenu {
+    at: Instant,
+    /// Profile the payload was sent for
+    profile: Option<ContentHash>,
+}
+
+/// Late focus state for the open menu
+#[derive(Debug, Default)]
+pub struct LateFocus {
+    window: Duration,
+    open: Option<OpenMenu>,
+    stats: LateFocusStats,
+}
+
+impl LateFocus {
+    pub fn new() -> Self {
+        Self {
+            window: Duration::from_millis(LATE_FOCUS_MS),
+            ..Self::default()
+        }
+    }
+
+    /// Menu opened at `at` with the payload for `profile`
+    ///
+    /// A zero `window` turns swapping off for this open.
+    pub fn opened(&mut self, at: Instant, profile: Option<ContentHash>, window: Duration) {
+        self.window = window;
+        self.open = Some(OpenMenu { at, profile });
+        self.stats.opens += 1;
+    }
+
+    /// The menu closed; later reports no longer concern it
+    pub fn closed(&mut self) {
+        self.open = None;
+    }
+
+    /// Whether a focus report at `at` would still reach the open menu
+    pub fn awaiting(&self, at: Instant) -> bool {
+        self.open
+            .is_some_and(|open| !self.window.is_zero() && at.saturating_duration_since(open.at) <= self.window)
+    }
+
+    /// The focused window changed at `at` and now resolves to `profile`
+    ///
+    /// Returns whether the open menu should be sent the new profile. Only the
+    /// first report within the window counts; the open is then settled.
+    pub fn focus_changed(&mut self, at: Instant, profile: Option<ContentHash>) -> bool {
+        let Some(open) = self.open.take() else {
+            return false;
+        };
+        if self.window.is_zero() || at.saturating_duration_since(open.at) > self.window {
+            return false;
+        }
+        self.stats.late_reports += 1;
+        if profile == open.profile {
+            return false;
+        }
+        self.stats.swaps += 1;
+        true
+    }
+
+    pub fn stats(&self) -> LateFocusStats {
+        self.stats
+    }
+}
+
+/// The open menu's late focus window, shared by the service and the focus task
+pub type LateFocusHandle = StateHandle<LateFocus>;
+
+impl LateFocusHandle {
+    /// Counters for `Status()`
+    pub fn stats(&self) -> LateFocusStats {
+        self.update(|l| l.stats()).unwrap_or_default()
+    }
+}
+
+/// Send the open menu the payload of the profile now in effect
+pub async fn resend(connection: &zbus::Connection) -> zbus::Result<()> {
+    let iface = connection
+        .object_server()
+        .interface::<_, crate::dbus::JuhRadialService>(DBUS_PATH)
+        .await?;
+    let service = iface.get().await;
+    service.open_profile(iface.signal_emitter()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(n: u64) -> Option<ContentHash> {
+        Some(ContentHash::of(&n))
+    }
+
+    #[test]
+    fn test_late_report_for_another_profile_swaps_once() {
+        let t0 = Instant::now();
+        let mut late = LateFocus::new();
+        late.opened(t0, hash(1), Duration::from_millis(50));
+        assert!(late.focus_changed(t0 + Duration::from_millis(20), hash(2)));
+        // Settled: a second report does not swap again
+        assert!(!late.focus_changed(t0 + Duration::from_millis(30), hash(3)));
+        assert_eq!(late.stats(), LateFocusStats { opens: 1, late_reports: 1, swaps: 1 });
+    }
+
+    #[test]
+    fn test_same_profile_late_or_closed_does_not_swap() {
+        let t0 = Instant::now();
+        let mut late = LateFocus::new();
+        late.opened(t0, hash(1), Duration::from_millis(50));
+        // Another window with the same profile
+        assert!(!late.focus_changed(t0 + Duration::from_millis(10), hash(1)));
+
+        late.opened(t0, hash(1), Duration::from_millis(50));
+        assert!(!late.focus_changed(t0 + Duration::from_millis(51), hash(2)));
+
+        late.opened(t0, hash(1), Duration::from_millis(50));
+        late.closed();
+        assert!(!late.focus_changed(t0 + Duration::from_millis(5), hash(2)));
+
+        // Disabled
+        late.opened(t0, hash(1), Duration::ZERO);
+        assert!(!late.focus_changed(t0 + Duration::from_millis(1), hash(2)));
+        assert_eq!(late.stats(), LateFocusStats { opens: 4, late_reports: 1, swaps: 0 });
+    }
+}
@@ -3,38 +3,95 @@
 //! Public API for testing and integration.
 
 pub mod accessibility;
+pub mod adhoc_menu;
+pub mod action_latency;
 pub mod actions;
 pub mod battery;
-pub mod bundled_themes;
+pub mod calibration;
+pub mod clipboard;
 pub mod compositor;
 pub mod config;
+pub mod config_schema;
+pub mod conflicts;
 pub mod cursor;
 pub mod dbus;
+pub mod deadline;
 pub mod device_descriptor;
+pub mod device_watcher;
+pub mod doctor;
+pub mod dwell_confirm;
+pub mod error;
 pub mod evdev;
+pub mod execution_policy;
+pub mod features;
+pub mod flick;
 pub mod gaming;
+pub mod global_shortcuts;
+pub mod handles;
 pub mod hidpp;
 pub mod hidraw;
+pub mod hover_help;
+pub mod icon_resolver;
+pub mod idle_effect;
+pub mod instance;
+pub mod invocation;
+pub mod kwin_script;
+pub mod late_focus;
+pub mod logging;
 pub mod macros;
+pub mod media_control;
+pub mod menu_anchor;
+pub mod menu_dismissal;
+pub mod menu_log;
+pub mod menu_payload;
+pub mod menu_simulation;
+pub mod menu_trigger;
+pub mod metrics;
+pub mod monitors;
+pub mod overlay;
+pub mod panic_switch;
 pub mod performance_monitor;
+pub mod persistence;
 pub mod presets;
+pub mod prewarm;
+pub mod profile_menu;
+pub mod profile_selection;
 pub mod profiles;
-pub mod theme;
+pub mod quiet_hours;
+pub mod reload;
+pub mod sandbox;
+pub mod sd_notify;
+pub mod self_test;
+pub mod session;
+pub mod slice_confirmation;
+pub mod slice_cooldown;
+pub mod state_dump;
+pub mod stats;
+pub mod system_accent;
+pub mod theme_transition;
+#[cfg(feature = "themes")]
 pub mod theme_watcher;
+pub mod thumbwheel_mapping;
+pub mod validate;
+pub mod wheel_axis;
 pub mod window_tracker;
 
+pub use juhradial_core::{bundled_themes, color, content_hash, fs_util, geometry, label, locale, migration, paths, shortcut_lint, slice_tint, theme, validation};
+
 /// Re-export commonly used types
-pub use accessibility::{AccessibilitySettings, EffectiveAnimationTimings};
+pub use accessibility::{AccessibilitySettings, EffectiveAnimationTimings, SharedAccessibility};
 pub use actions::{Action, ActionType};
 pub use battery::{BatteryState, SharedBatteryState, new_shared_state as new_battery_state, start_battery_updater_shared};
 pub use bundled_themes::{get_bundled_theme, get_default_theme, list_bundled_themes, DEFAULT_THEME_NAME};
 pub use config::{Config, SharedConfig, new_shared_config, load_shared_config};
+pub use error::JuhError;
 pub use cursor::{get_cursor_position, get_screen_bounds, CursorPosition, ScreenBounds, EDGE_MARGIN, MENU_DIAMETER, MENU_RADIUS};
 pub use dbus::{claim_name, init_dbus_service, init_dbus_service_with_device, JuhRadialService, DBUS_INTERFACE, DBUS_NAME, DBUS_PATH};
 pub use evdev::{DeviceInfo, EvdevError, EvdevHandler, GestureEvent, LOGITECH_VENDOR_ID, GENERIC_TRIGGER_BUTTON};
 pub use performance_monitor::{BlurMode, PerformanceMonitor};
 pub use profiles::{Profile, ProfileManager};
-pub use theme::{Theme, ThemeManager};
+pub use theme::{SharedThemeManager, Theme, ThemeManager, ThemeOrigin, ThemeSource};
+#[cfg(feature = "themes")]
 pub use theme_watcher::{ThemeEvent, ThemeHotReloader, ThemeWatcher};
 pub use window_tracker::WindowTracker;
 pub use gaming::{GamingMode, SharedGamingMode, new_shared_gaming_mode};
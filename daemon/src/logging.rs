@@ -0,0 +1,342 @@
+//! Log output of the daemon binary
+//!
+//! Logs go to stderr, with ANSI colors only when stderr is a terminal and
+//! `NO_COLOR` is not set, so journald and redirected output get plain text.
+//! `--log-file <path>` also writes them, always uncolored, to a size-capped
+//! file that rotates to `<path>.1` .. `<path>.3`.
+//!
+//! The level comes from `--verbosity` when given, then `RUST_LOG`, then
+//! `--verbose` (debug) or info. Both take a level (`debug`) or per-target
+//! directives (`info,juhradiald::hidpp=trace`).
+//!
+//! The last [`RECENT_WARNINGS`] warnings and errors are also kept in memory
+//! for `DumpState()`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::fs_util;
+
+/// Rotate the log file when it passes this size
+pub const MAX_LOG_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rotated log files kept next to the live one
+pub const KEEP_ROTATED: usize = 3;
+
+/// Warnings and errors kept for `DumpState()`
+pub const RECENT_WARNINGS: usize = 50;
+
+/// Logging choices from the command line
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+    /// `--verbose`
+    pub verbose: bool,
+    /// `--verbosity`, overriding `RUST_LOG`
+    pub verbosity: Option<String>,
+    /// `--log-file`
+    pub log_file: Option<PathBuf>,
+}
+
+/// Whether to color output: a terminal, and `NO_COLOR` unset or empty
+pub fn use_ansi(no_color: Option<&str>, is_terminal: bool) -> bool {
+    is_terminal && no_color.is_none_or(str::is_empty)
+}
+
+/// The filter for `options`, with `rust_log` as the `RUST_LOG` value
+///
+/// A directive string that does not parse falls back to the default level;
+/// the error is returned so it can be logged once logging is up.
+pub fn filter(options: &LogOptions, rust_log: Option<&str>) -> (Targets, Option<String>) {
+    let default = if options.verbose { LevelFilter::DEBUG } else { LevelFilter::INFO };
+    let directives = options
+        .verbosity
+        .as_deref()
+        .or(rust_log)
+        .map(str::trim)
+        .filter(|d| !d.is_empty());
+    match directives.map(|d| (d, Targets::from_str(d))) {
+        None => (Targets::new().with_default(default), None),
+        Some((_, Ok(targets))) => (targets, None),
+        Some((d, Err(e))) => (
+            Targets::new().with_default(default),
+            Some(format!("ignoring log filter '{d}': {e}")),
+        ),
+    }
+}
+
+/// Install the global subscriber; returns the kept warnings for `DumpState()`
+pub fn init(options: &LogOptions) -> Result<RecentWarnings, crate::error::JuhError> {
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let no_color = std::env::var("NO_COLOR").ok();
+    let (targets, filter_error) = filter(options, rust_log.as_deref());
+    let ansi = use_ansi(no_color.as_deref(), io::stderr().is_terminal());
+
+    let file = match options.log_file {
+        Some(ref path) => Some(RotatingFile::open(path.clone(), MAX_LOG_FILE_BYTES, KEEP_ROTATED)?),
+        None => None,
+    };
+    let file_layer = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(std::sync::Mutex::new(file))
+    });
+    let recent = RecentWarnings::default();
+    tracing_subscriber::registry()
+        .with(targets)
+        .with(tracing_subscriber::fmt::layer().with_ansi(ansi).with_writer(io::stderr))
+        .with(file_layer)
+        .with(recent.clone())
+        .try_init()?;
+
+    if let Some(e) = filter_error {
+        tracing::warn!("{}", e);
+    }
+    if let Some(ref path) = options.log_file {
+        tracing::info!(path = %path.display(), "Logging to file");
+    }
+    Ok(recent)
+}
+
+/// A warning or error as logged
+#[derive(Debug, Clone, Serialize)]
+pub struct LoggedWarning {
+    /// Unix seconds
+    pub at: u64,
+    pub level: &'static str,
+    pub target: String,
+    pub message: String,
+    /// The event's other fields, as logged
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Layer keeping the last [`RECENT_WARNINGS`] warnings and errors
+///
+/// Clones share the kept warnings; the daemon keeps one on `DaemonHandles`.
+#[derive(Debug, Clone, Default)]
+pub struct RecentWarnings {
+    recent: Arc<Mutex<VecDeque<LoggedWarning>>>,
+}
+
+impl RecentWarnings {
+    /// The kept warnings and errors, oldest first
+    pub fn warnings(&self) -> Vec<LoggedWarning> {
+        self.recent.lock().map(|r| r.iter().cloned().collect()).unwrap_or_default()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for RecentWarnings {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+        let mut visitor = WarningVisitor::default();
+        event.record(&mut visitor);
+        let warning = LoggedWarning {
+            at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            level: if *metadata.level() == Level::ERROR { "error" } else { "warn" },
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+        let Ok(mut recent) = self.recent.lock() else {
+            return;
+        };
+        if recent.len() == RECENT_WARNINGS {
+            recent.pop_front();
+        }
+        recent.push_back(warning);
+    }
+}
+
+#[derive(Default)]
+struct WarningVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for WarningVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.fields.insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+}
+
+/// A log file that moves itself to `<path>.1` once it reaches `max_bytes`
+///
+/// Older files shift up to `<path>.<keep>`; the one past that is deleted.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    len: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs_util::open_private_append(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self { path, max_bytes, keep, file, len })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let _ = fs::remove_file(rotated(&self.path, self.keep));
+        for n in (1..self.keep).rev() {
+            let from = rotated(&self.path, n);
+            if from.exists() {
+                fs::rename(&from, rotated(&self.path, n + 1))?;
+            }
+        }
+        if self.keep > 0 {
+            fs::rename(&self.path, rotated(&self.path, 1))?;
+        } else {
+            fs::remove_file(&self.path)?;
+        }
+        self.file = fs_util::open_private_append(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// `<path>.<n>`
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{n}"));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_three_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("juhradiald.log");
+        let mut log = RotatingFile::open(path.clone(), 10, KEEP_ROTATED).unwrap();
+        for line in ["aaaaaaaa\n", "bbbbbbbb\n", "cccccccc\n", "dddddddd\n", "eeeeeeee\n"] {
+            log.write_all(line.as_bytes()).unwrap();
+        }
+        let read = |p: PathBuf| fs::read_to_string(p).unwrap();
+        assert_eq!(read(path.clone()), "eeeeeeee\n");
+        assert_eq!(read(rotated(&path, 1)), "dddddddd\n");
+        assert_eq!(read(rotated(&path, 2)), "cccccccc\n");
+        assert_eq!(read(rotated(&path, 3)), "bbbbbbbb\n");
+        assert!(!rotated(&path, 4).exists());
+    }
+
+    #[test]
+    fn test_reopen_appends_and_counts_the_existing_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("juhradiald.log");
+        RotatingFile::open(path.clone(), 16, KEEP_ROTATED).unwrap().write_all(b"12345678\n").unwrap();
+        let mut log = RotatingFile::open(path.clone(), 16, KEEP_ROTATED).unwrap();
+        log.write_all(b"abcdefgh\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "abcdefgh\n");
+        assert_eq!(fs::read_to_string(rotated(&path, 1)).unwrap(), "12345678\n");
+    }
+
+    #[test]
+    fn test_oversized_write_goes_to_a_fresh_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("juhradiald.log");
+        let mut log = RotatingFile::open(path.clone(), 4, 1).unwrap();
+        log.write_all(b"much longer than four\n").unwrap();
+        assert!(!rotated(&path, 1).exists());
+        log.write_all(b"next\n").unwrap();
+        assert_eq!(fs::read_to_string(rotated(&path, 1)).unwrap(), "much longer than four\n");
+    }
+
+    #[test]
+    fn test_ansi_only_on_a_terminal_without_no_color() {
+        assert!(use_ansi(None, true));
+        assert!(use_ansi(Some(""), true));
+        assert!(!use_ansi(Some("1"), true));
+        assert!(!use_ansi(None, false));
+    }
+
+    #[test]
+    fn test_verbosity_overrides_rust_log() {
+        let debug = Some(LevelFilter::DEBUG);
+        let options = LogOptions { verbosity: Some("debug".into()), ..LogOptions::default() };
+        let (targets, error) = filter(&options, Some("warn"));
+        assert_eq!(targets.default_level(), debug);
+        assert!(error.is_none());
+
+        let (targets, _) = filter(&LogOptions::default(), Some("warn,juhradiald::hidpp=trace"));
+        assert_eq!(targets.default_level(), Some(LevelFilter::WARN));
+        assert!(targets.would_enable("juhradiald::hidpp::device", &tracing::Level::TRACE));
+
+        let verbose = LogOptions { verbose: true, ..LogOptions::default() };
+        assert_eq!(filter(&verbose, None).0.default_level(), debug);
+        let (targets, error) = filter(&verbose, Some("=nonsense="));
+        assert_eq!(targets.default_level(), debug);
+        assert!(error.unwrap().contains("=nonsense="));
+    }
+
+    #[test]
+    fn test_recent_warnings_keep_the_last_warnings_and_errors() {
+        let layer = RecentWarnings::default();
+        let subscriber = tracing_subscriber::registry().with(layer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("not kept");
+            for n in 0..RECENT_WARNINGS {
+                tracing::warn!(n, "filler");
+            }
+            tracing::error!(path = "/dev/hidraw3", "Device vanished");
+        });
+        let recent = layer.warnings();
+        assert_eq!(recent.len(), RECENT_WARNINGS);
+        assert!(recent.iter().all(|w| w.message != "not kept"));
+        assert_eq!(recent[0].fields["n"], "1", "the oldest warning made room");
+        let last = recent.last().unwrap();
+        assert_eq!(last.level, "error");
+        assert_eq!(last.message, "Device vanished");
+        assert_eq!(last.fields["path"], "/dev/hidraw3");
+        assert!(last.target.ends_with("logging::tests"));
+    }
+}
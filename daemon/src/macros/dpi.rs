@@ -176,28 +176,19 @@ pub fn get_dpi(haptic_manager: &SharedHapticManager) -> Option<u16> {
 // ============================================================================
 
 /// DPI error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum DpiError {
     /// No profile available
+    #[error("No DPI profile available")]
     NoProfile,
     /// Device communication error
+    #[error("DPI device error: {0}")]
     DeviceError(String),
     /// Failed to acquire lock
+    #[error("Failed to lock haptic manager")]
     LockError,
 }
 
-impl std::fmt::Display for DpiError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            DpiError::NoProfile => write!(f, "No DPI profile available"),
-            DpiError::DeviceError(msg) => write!(f, "DPI device error: {}", msg),
-            DpiError::LockError => write!(f, "Failed to lock haptic manager"),
-        }
-    }
-}
-
-impl std::error::Error for DpiError {}
-
 // ============================================================================
 // Tests
 // ============================================================================
@@ -348,18 +348,14 @@ fn release_stuck_keys(actions: &[MacroAction]) {
     // Scan the action list to find all KeyDown/MouseDown that could be held
     for action in actions {
         match action {
-            MacroAction::KeyDown(key) => {
-                if !held_keys.contains(key) {
-                    held_keys.push(key.clone());
-                }
+            MacroAction::KeyDown(key) if !held_keys.contains(key) => {
+                held_keys.push(key.clone());
             }
             MacroAction::KeyUp(key) => {
                 held_keys.retain(|k| k != key);
             }
-            MacroAction::MouseDown(btn) => {
-                if !held_mouse.contains(btn) {
-                    held_mouse.push(btn.clone());
-                }
+            MacroAction::MouseDown(btn) if !held_mouse.contains(btn) => {
+                held_mouse.push(btn.clone());
             }
             MacroAction::MouseUp(btn) => {
                 held_mouse.retain(|b| b != btn);
@@ -327,28 +327,19 @@ fn record_events(
 // ============================================================================
 
 /// Recorder error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum RecorderError {
     /// Already recording
+    #[error("Already recording")]
     AlreadyRecording,
     /// No keyboard device found
+    #[error("No keyboard device found")]
     NoKeyboard,
     /// Device access error
+    #[error("Device error: {0}")]
     DeviceError(String),
 }
 
-impl std::fmt::Display for RecorderError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            RecorderError::AlreadyRecording => write!(f, "Already recording"),
-            RecorderError::NoKeyboard => write!(f, "No keyboard device found"),
-            RecorderError::DeviceError(msg) => write!(f, "Device error: {}", msg),
-        }
-    }
-}
-
-impl std::error::Error for RecorderError {}
-
 // ============================================================================
 // Tests
 // ============================================================================
@@ -9,6 +9,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use super::types::MacroConfig;
+use crate::fs_util;
 
 // ============================================================================
 // Constants
@@ -26,7 +27,7 @@ const MACRO_EXT: &str = "json";
 
 /// Get the macros directory path: ~/.config/juhradial/macros/
 pub fn macros_dir() -> Option<PathBuf> {
-    dirs::config_dir().map(|p| p.join("juhradial").join(MACROS_DIR))
+    Some(crate::paths::paths().config_dir.join(MACROS_DIR))
 }
 
 /// Create the macros directory if it does not exist
@@ -125,8 +126,9 @@ fn load_macro_file(path: &Path) -> Result<MacroConfig, StorageError> {
 
 /// Save a macro to disk using atomic write pattern
 ///
-/// Writes to a .tmp file first, then renames to the final path.
-/// This prevents corruption if the process is interrupted.
+/// Writes to a uniquely named temp file first, then renames it over the final
+/// path (`fs_util::write_private`). This prevents corruption if the process
+/// is interrupted.
 pub fn save_macro(config: &MacroConfig) -> Result<(), StorageError> {
     let dir = ensure_macros_dir()?;
     save_macro_to(&dir, config)
@@ -136,16 +138,11 @@ pub fn save_macro(config: &MacroConfig) -> Result<(), StorageError> {
 pub fn save_macro_to(dir: &Path, config: &MacroConfig) -> Result<(), StorageError> {
     validate_id(&config.id)?;
     let final_path = dir.join(format!("{}.{}", config.id, MACRO_EXT));
-    let tmp_path = dir.join(format!("{}.{}.tmp", config.id, MACRO_EXT));
 
     // Serialize to pretty JSON
     let contents = serde_json::to_string_pretty(config).map_err(StorageError::ParseError)?;
 
-    // Write to temp file
-    fs::write(&tmp_path, &contents).map_err(StorageError::IoError)?;
-
-    // Atomic rename
-    fs::rename(&tmp_path, &final_path).map_err(StorageError::IoError)?;
+    fs_util::write_private(&final_path, contents).map_err(StorageError::IoError)?;
 
     tracing::info!(id = %config.id, path = %final_path.display(), "Macro saved");
     Ok(())
@@ -181,34 +178,25 @@ pub fn delete_macro_from(dir: &Path, id: &str) -> Result<(), StorageError> {
 // ============================================================================
 
 /// Storage error type
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum StorageError {
     /// Could not determine config directory
+    #[error("Could not determine config directory")]
     NoConfigDir,
     /// Macro not found
+    #[error("Macro not found: {0}")]
     NotFound(String),
     /// Invalid macro ID (path traversal attempt or illegal characters)
+    #[error("Invalid macro ID: {0}")]
     InvalidId(String),
     /// I/O error
+    #[error("I/O error: {0}")]
     IoError(std::io::Error),
     /// JSON parse error
+    #[error("Parse error: {0}")]
     ParseError(serde_json::Error),
 }
 
-impl std::fmt::Display for StorageError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            StorageError::NoConfigDir => write!(f, "Could not determine config directory"),
-            StorageError::NotFound(id) => write!(f, "Macro not found: {}", id),
-            StorageError::InvalidId(id) => write!(f, "Invalid macro ID: {}", id),
-            StorageError::IoError(e) => write!(f, "I/O error: {}", e),
-            StorageError::ParseError(e) => write!(f, "Parse error: {}", e),
-        }
-    }
-}
-
-impl std::error::Error for StorageError {}
-
 // ============================================================================
 // Tests
 // ============================================================================
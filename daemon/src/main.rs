@@ -3,27 +3,55 @@
 //! A daemon for Linux that provides radial menu functionality for the
 //! Logitech MX Master 4 mouse via evdev input and KWin overlay.
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::collections::HashSet;
 use std::path::PathBuf;
+use std::process::ExitCode;
 use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio::time::{Duration, sleep};
-use tracing::{Level, debug, error, info, warn};
-use tracing_subscriber::FmtSubscriber;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{debug, error, info, warn};
 
 use juhradiald::{
+    accessibility::{new_shared_accessibility, run_accessibility_watcher},
+    actions::{ActionError, ActionExecutor, ActionType, PreparedAction},
     battery::{new_shared_state, start_battery_updater_shared, SharedBatteryState},
-    config::load_shared_config,
-    dbus::{DBUS_NAME, DBUS_PATH, claim_name, init_dbus_service_with_device},
+    config::{load_shared_config, Config, GenericMouseMode, SharedConfig},
+    conflicts::ConflictsHandle,
+    dbus::{DBUS_INTERFACE, DBUS_NAME, DBUS_PATH, JuhRadialService, init_dbus_service_with_device},
+    deadline,
+    device_watcher::{DeviceKind, DeviceSubscription},
+    flick::{self, FlickHandle},
     evdev::{EvdevError, EvdevHandler, GestureEvent},
+    execution_policy::ExecutionPolicy,
     gaming::new_shared_gaming_mode,
-    hidpp::SharedHapticManager,
+    handles::DaemonHandles,
+    error::JuhError,
+    hidpp::{HapticEvent, SharedHapticManager},
     hidraw::{HidrawError, HidrawHandler},
+    hover_help::HoverHelpHandle,
+    instance::{self, Claim},
+    invocation::InvocationContext,
+    logging::{self, LogOptions},
     macros::{MacroEngine, MacroRecorder, TriggerMap},
+    menu_dismissal::{MenuDismissal, MenuDismissalHandle, ReleaseOutcome},
     new_shared_haptic_manager,
+    overlay::{OverlayHandle, run_overlay_supervisor},
+    prewarm::PrewarmHandle,
+    profile_menu::ProfileMenu,
+    profile_selection::{apply_switch, focus_window, load_shared_selection, SharedProfileSelection},
     profiles::{ProfileManager, SharedHardwareProfiles},
-    window_tracker::WindowTracker,
+    sandbox::ResolvedBinaries,
+    sd_notify,
+    self_test::{new_shared_report, run_self_test},
+    slice_confirmation::ConfirmOutcome,
+    slice_cooldown::SliceCooldownHandle,
+    state_dump,
+    theme::{load_shared_theme_manager, load_theme_manager},
+    thumbwheel_mapping::{SharedThumbwheelMapper, ThumbwheelMapper},
+    validate,
+    window_tracker::{DesktopTracker, WindowTracker},
 };
 
 use std::collections::HashMap;
@@ -31,8 +59,8 @@ use std::sync::{Arc, Mutex, RwLock};
 
 /// Fallback poll interval when no device is found (60 seconds).
 ///
-/// The inotify hotplug watcher on `/dev/input/` wakes the loops the instant a
-/// new event* device appears, so the timer is purely a safety net for hotplug
+/// The device watcher (`device_watcher`) wakes the loops the instant a new
+/// event* device appears, so the timer is purely a safety net for hotplug
 /// failure modes. The previous 2-second cadence opened every evdev node on
 /// every tick (including the MX mouse currently streaming events through
 /// another task), causing visible cursor stutter every 2 seconds. 60 seconds
@@ -47,6 +75,57 @@ const DEVICE_POLL_INTERVAL_SECS: u64 = 60;
 /// scanning stutter that `DEVICE_POLL_INTERVAL_SECS` avoids.
 const HIDRAW_RECONNECT_POLL_INTERVAL_SECS: u64 = 5;
 
+/// Generic-mouse scan cadence while none is found: `require` polls at the
+/// minimum, `auto` doubles from it up to the maximum. Hotplug re-scans at once
+/// either way.
+const GENERIC_POLL_MIN_SECS: u64 = 2;
+const GENERIC_POLL_MAX_SECS: u64 = 30;
+
+/// Delay before the next generic-mouse scan after `misses` scans in a row
+/// found nothing
+fn generic_poll_delay(mode: GenericMouseMode, misses: u32) -> Duration {
+    let secs = match mode {
+        GenericMouseMode::Require | GenericMouseMode::Off => GENERIC_POLL_MIN_SECS,
+        GenericMouseMode::Auto => GENERIC_POLL_MIN_SECS
+            .saturating_mul(1u64 << misses.saturating_sub(1).min(16))
+            .min(GENERIC_POLL_MAX_SECS),
+    };
+    Duration::from_secs(secs)
+}
+
+/// Upper bound on the shutdown sequence. systemd's stop job and a user's
+/// Ctrl+C should never wait on a wedged HID++ request or D-Bus call.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Long-running tasks that stop together on shutdown
+///
+/// Every task is wrapped in the shared cancellation token, and the tracker lets
+/// shutdown wait for all of them to finish unwinding.
+#[derive(Clone, Default)]
+struct BackgroundTasks {
+    cancel: CancellationToken,
+    tracker: TaskTracker,
+}
+
+impl BackgroundTasks {
+    /// Spawn `task`; it is dropped at its next await point once cancelled
+    fn spawn<F>(&self, task: F) -> tokio::task::JoinHandle<Option<F::Output>>
+    where
+        F: std::future::Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.tracker
+            .spawn(self.cancel.clone().run_until_cancelled_owned(task))
+    }
+
+    /// Cancel every task and wait for them to finish
+    async fn stop(&self) {
+        self.cancel.cancel();
+        self.tracker.close();
+        self.tracker.wait().await;
+    }
+}
+
 /// Emit monotonic checkpoints so cold-start latency can be attributed to a
 /// concrete phase instead of inferring it from process activation.
 fn log_startup_phase(started_at: &Instant, phase: &'static str) {
@@ -57,101 +136,6 @@ fn log_startup_phase(started_at: &Instant, phase: &'static str) {
     );
 }
 
-/// Spawn a background thread that watches /dev/input/ for device hotplug events
-/// using inotify. Returns a Notify that fires when event* devices appear or disappear.
-/// This allows evdev loops to re-scan immediately instead of waiting for the 2s poll.
-///
-/// Only reacts to Create/Remove events (actual device plug/unplug). Access events
-/// (e.g. Close(Write)) are filtered out to prevent a feedback loop where our own
-/// device scanning triggers inotify events that cause more scanning. A 500ms
-/// debounce window coalesces rapid events from USB hubs into a single notification.
-fn spawn_device_hotplug_watcher() -> Arc<tokio::sync::Notify> {
-    let hotplug = Arc::new(tokio::sync::Notify::new());
-    let hotplug_tx = hotplug.clone();
-
-    std::thread::spawn(move || {
-        use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-        use std::sync::mpsc::channel;
-        use std::time::Instant;
-
-        let (tx, rx) = channel();
-        let config = Config::default().with_poll_interval(Duration::from_millis(200));
-
-        let mut watcher = match RecommendedWatcher::new(tx, config) {
-            Ok(w) => w,
-            Err(e) => {
-                warn!(
-                    "Device hotplug watcher init failed: {} - falling back to polling",
-                    e
-                );
-                return;
-            }
-        };
-
-        let input_dir = std::path::PathBuf::from("/dev/input");
-        if let Err(e) = watcher.watch(&input_dir, RecursiveMode::NonRecursive) {
-            warn!(
-                "Failed to watch /dev/input/: {} - falling back to polling",
-                e
-            );
-            return;
-        }
-
-        info!("Device hotplug watcher active on /dev/input/");
-
-        let mut last_notify = Instant::now() - Duration::from_secs(1);
-        let debounce = Duration::from_millis(500);
-
-        loop {
-            match rx.recv() {
-                Ok(Ok(event)) => {
-                    // Only react to actual device creation/removal - NOT access events.
-                    // Our own scanning opens /dev/input/event* files, which generates
-                    // Access(Close(Write)) inotify events. If we react to those, we
-                    // enter an infinite scan loop (~50ms cycle) that saturates the
-                    // input subsystem and can block other devices (see issue #15).
-                    let is_hotplug =
-                        matches!(event.kind, EventKind::Create(_) | EventKind::Remove(_));
-                    if !is_hotplug {
-                        continue;
-                    }
-
-                    let is_event_device = event.paths.iter().any(|p| {
-                        p.file_name()
-                            .and_then(|n| n.to_str())
-                            .map(|n| n.starts_with("event"))
-                            .unwrap_or(false)
-                    });
-                    if !is_event_device {
-                        continue;
-                    }
-
-                    // Debounce: coalesce rapid events (e.g. USB hub enumerating
-                    // multiple devices) into a single scan notification.
-                    let now = Instant::now();
-                    if now.duration_since(last_notify) < debounce {
-                        debug!("Device hotplug debounced: {:?}", event.kind);
-                        continue;
-                    }
-                    last_notify = now;
-
-                    info!("Device hotplug detected: {:?}", event.kind);
-                    hotplug_tx.notify_waiters();
-                }
-                Ok(Err(e)) => {
-                    warn!("Device watcher error: {}", e);
-                }
-                Err(_) => {
-                    // Channel closed
-                    break;
-                }
-            }
-        }
-    });
-
-    hotplug
-}
-
 /// JuhRadial MX Daemon - Radial menu for Logitech MX Master 4
 #[derive(Parser, Debug)]
 #[command(name = "juhradiald")]
@@ -165,23 +149,185 @@ struct Args {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Log level or per-target directives (`debug`, `info,juhradiald::hidpp=trace`); overrides RUST_LOG
+    #[arg(long, value_name = "FILTER")]
+    verbosity: Option<String>,
+
+    /// Also write logs to this file (uncolored, rotated at 10 MiB, 3 kept)
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
     /// List all Logitech devices and exit
     #[arg(long)]
     list_devices: bool,
+
+    /// Ask a running daemon to quit and take over from it
+    #[arg(long)]
+    replace: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Tools that run instead of the daemon
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check a theme.json for invalid colors and out-of-range values
+    ValidateTheme {
+        path: PathBuf,
+        /// Write clamped values back to the file
+        #[arg(long)]
+        fix: bool,
+        /// Machine-readable output
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check a profiles.json for slice counts, shortcuts, icons and action types
+    ValidateProfiles {
+        path: PathBuf,
+        /// Pad or truncate slice lists to 8 entries
+        #[arg(long)]
+        fix: bool,
+        /// Machine-readable output
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print config, profiles, themes and the running daemon's state as JSON
+    /// for a bug report (actions reduced to their type)
+    DumpState {
+        /// Keep commands, shortcuts and other action values
+        #[arg(long)]
+        include_sensitive: bool,
+    },
+    /// Check access to the mouse, /dev/uinput and the input group, printing
+    /// the udev rule or command that fixes each failure
+    Doctor {
+        /// Install the generated rules (needs root)
+        #[arg(long)]
+        write_rules: bool,
+        /// Machine-readable output
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Run a validation subcommand; exits non-zero when the file has errors
+fn run_validate_command(command: Command) -> ! {
+    // The profile lint honours the `profile_lint` override in config.json
+    let config = juhradiald::config::Config::load_default().unwrap_or_default();
+    let rules = config.shortcut_lint_rules();
+    let (report, json) = match command {
+        Command::ValidateTheme { path, fix, json } => (validate::validate_theme(&path, fix), json),
+        Command::ValidateProfiles { path, fix, json } => (validate::validate_profiles(&path, fix, &rules), json),
+        Command::DumpState { .. } | Command::Doctor { .. } => unreachable!("not a validation"),
+    };
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report.to_human());
+    }
+    std::process::exit(if report.has_errors() { 1 } else { 0 });
+}
+
+/// Run the device access checks; exits non-zero when one fails or the
+/// rules could not be written
+fn run_doctor(write_rules: bool, json: bool) -> ExitCode {
+    let report = juhradiald::doctor::run_doctor();
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report.to_human());
+    }
+    if write_rules {
+        let path = std::path::Path::new(juhradiald::doctor::RULES_PATH);
+        if report.rules.is_empty() {
+            eprintln!("juhradiald: no rules needed; nothing written");
+        } else if let Err(e) = juhradiald::doctor::write_rules(&report, path) {
+            eprintln!("juhradiald: {e}");
+            return ExitCode::FAILURE;
+        } else {
+            eprintln!(
+                "Wrote {}; apply it with: sudo udevadm control --reload && sudo udevadm trigger",
+                path.display()
+            );
+        }
+    }
+    if report.passed { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
+
+/// Print the running daemon's `DumpState()`, or what can be read from disk
+/// when no daemon answers
+async fn run_dump_state(include_sensitive: bool) -> ExitCode {
+    let dump = match ask_dump_state(include_sensitive).await {
+        Ok(json) => serde_json::from_str(&json).unwrap_or(serde_json::Value::String(json)),
+        Err(e) => {
+            let config = Config::load_default().unwrap_or_default();
+            let themes = load_theme_manager(&config.theme);
+            let daemon = serde_json::json!({ "running": false, "error": e.to_string() });
+            let profiles = juhradiald::profiles::get_profiles_path();
+            let strategy = juhradiald::sandbox::ExecutionStrategy::detect(config.execution.host_spawn);
+            state_dump::document(&config, &profiles, &themes, &strategy, daemon, include_sensitive)
+        }
+    };
+    match serde_json::to_string_pretty(&dump) {
+        Ok(json) => {
+            println!("{json}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("juhradiald: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `DumpState()` from the daemon on the session bus
+async fn ask_dump_state(include_sensitive: bool) -> Result<String, JuhError> {
+    let connection = zbus::Connection::session().await.map_err(JuhError::SessionBus)?;
+    let reply = connection
+        .call_method(Some(DBUS_NAME), DBUS_PATH, Some(DBUS_INTERFACE), "DumpState", &(include_sensitive,))
+        .await?;
+    Ok(reply.body().deserialize()?)
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
     let args = Args::parse();
 
+    // Subcommands never read from devices (doctor only tries opening them);
+    // only dump-state uses the session bus
+    match args.command {
+        Some(Command::DumpState { include_sensitive }) => return run_dump_state(include_sensitive).await,
+        Some(Command::Doctor { write_rules, json }) => return run_doctor(write_rules, json),
+        Some(command) => run_validate_command(command),
+        None => {}
+    }
+
+    // Startup failures end here with one message rather than a panic or a
+    // Debug dump of the error
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!(error = %e, "juhradiald failed");
+            eprintln!("juhradiald: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Read the shared config, as an error rather than a panic when poisoned
+fn read_config(config: &SharedConfig) -> Result<std::sync::RwLockReadGuard<'_, Config>, JuhError> {
+    config.read().map_err(|_| JuhError::LockPoisoned("config"))
+}
+
+/// The daemon proper; returns only on early exit or a startup failure
+async fn run(args: Args) -> Result<(), JuhError> {
     // Initialize logging
-    let level = if args.verbose {
-        Level::DEBUG
-    } else {
-        Level::INFO
-    };
-    let subscriber = FmtSubscriber::builder().with_max_level(level).finish();
-    tracing::subscriber::set_global_default(subscriber)?;
+    let recent_warnings = logging::init(&LogOptions {
+        verbose: args.verbose,
+        verbosity: args.verbosity.clone(),
+        log_file: args.log_file.clone(),
+    })?;
 
     info!("JuhRadial MX Daemon starting...");
     let startup_started_at = Instant::now();
@@ -196,23 +342,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // the systemd user service and the autostart launcher race to start a
     // daemon (issue #60): the launcher's NameHasOwner check is check-then-act,
     // so a second copy can always slip through. Claiming the well-known name
-    // atomically here makes the first daemon win and every later copy exit
-    // cleanly (exit 0, so Restart=on-abnormal never sees a refused claim as a
-    // crash) before it has diverted buttons or opened any device. Claiming
-    // this early also shrinks the launcher's race window: the name becomes
-    // visible immediately instead of after the ~1.5s HID++ probe.
-    let dbus_connection = zbus::Connection::session().await.map_err(|e| {
-        error!("Failed to connect to session D-Bus: {}", e);
-        e
-    })?;
-    if !claim_name(&dbus_connection, DBUS_NAME).await? {
-        info!(
-            "another juhradiald already owns {}; exiting (single-instance guard)",
-            DBUS_NAME
-        );
-        return Ok(());
-    }
+    // (and the instance lock, for a daemon on another bus) atomically here
+    // makes the first daemon win and every later copy exit cleanly (exit 0,
+    // so Restart=on-abnormal never sees a refused claim as a crash) before it
+    // has diverted buttons or opened any device. Claiming this early also
+    // shrinks the launcher's race window: the name becomes visible
+    // immediately instead of after the ~1.5s HID++ probe.
+    let dbus_connection = zbus::Connection::session().await.map_err(JuhError::SessionBus)?;
+    // Held until the process exits
+    let _instance_lock = match instance::claim(&dbus_connection, args.replace).await? {
+        Claim::Owned(lock) => lock,
+        Claim::Running { pid } => {
+            let pid = pid.map(|p| format!(" (pid {p})")).unwrap_or_default();
+            info!("another juhradiald{pid} is already running; exiting (single-instance guard)");
+            eprintln!("juhradiald: already running{pid}; use --replace to take over from it");
+            // A Type=notify unit whose process exits before READY=1 is marked
+            // failed; the refused claim is a clean exit, so report it as one.
+            let _ = sd_notify::notify(sd_notify::READY);
+            return Ok(());
+        }
+    };
     log_startup_phase(&startup_started_at, "bus-name claim");
+    juhradiald::features::log_disabled();
 
     info!("Configuration: {}", args.config);
 
@@ -232,9 +383,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     log_startup_phase(&startup_started_at, "config");
 
+    // Before the first device probe, so its exchanges are in the trace
+    let hidpp_trace = juhradiald::hidpp::trace::HidppTrace::default();
+    hidpp_trace.set_enabled(read_config(&shared_config)?.debug.hidpp_trace);
+
+    // Where commands and key/clipboard helpers run (host when sandboxed)
+    let strategy = juhradiald::sandbox::ExecutionStrategy::detect(read_config(&shared_config)?.execution.host_spawn);
+    match strategy.denied() {
+        Some(reason) => warn!(
+            strategy = strategy.as_str(),
+            reason,
+            permission = juhradiald::sandbox::HOST_PERMISSION,
+            "Running in a Flatpak sandbox but flatpak-spawn --host is unavailable; commands will fail"
+        ),
+        None => info!(strategy = strategy.as_str(), "Action execution strategy"),
+    }
+
+    // Resolve the install data directory before anything loads themes or assets
+    let data_dir = juhradiald::paths::init_data_dir(read_config(&shared_config)?.paths.data_dir.as_deref());
+    info!(
+        data_dir = %data_dir.path.display(),
+        source = ?data_dir.source,
+        exists = data_dir.path.is_dir(),
+        themes_dir = %data_dir.themes_dir().display(),
+        "Data directory resolved"
+    );
+
     // Initialize haptic manager for MX4 haptic feedback
-    let haptic_config = shared_config.read().unwrap().haptics.clone();
+    let haptic_config = read_config(&shared_config)?.haptics.clone();
     let haptic_manager = new_shared_haptic_manager(&haptic_config);
+    // State the D-Bus service shares with the input loops and tasks below
+    let handles = DaemonHandles {
+        binaries: ResolvedBinaries::new(strategy),
+        hidpp_trace,
+        recent_warnings,
+        ..DaemonHandles::default()
+    };
+    if let Ok(mut m) = haptic_manager.lock() {
+        m.set_latency(handles.latency.clone());
+        m.set_trace(handles.hidpp_trace.clone());
+        m.set_persistence(handles.persistence.clone());
+        m.set_metrics(handles.metrics.clone());
+        m.set_quiet_hours(handles.quiet_hours.clone());
+        m.set_conflicts(handles.conflicts.clone());
+    }
 
     // Try to connect to MX Master 4 for haptic feedback and divert gesture buttons.
     // HID++ probing does blocking hidraw I/O with std::thread::sleep — running it
@@ -288,6 +480,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     log_startup_phase(&startup_started_at, "hidpp_bootstrap");
 
+    // Device access self-test: tells missing nodes, permission problems and
+    // competing programs (logid, Solaar, ratbagd) apart instead of a generic "not found" (reads sysfs/proc only).
+    let self_test_conflicts = handles.conflicts.clone();
+    let startup_report = tokio::task::spawn_blocking(move || run_self_test(&self_test_conflicts))
+        .await
+        .unwrap_or_default();
+    startup_report.log();
+    let startup_report = new_shared_report(startup_report);
+
     // Clone haptic_manager for battery updater before passing to D-Bus
     let haptic_manager_for_battery = haptic_manager.clone();
 
@@ -299,7 +500,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config_device_mode = read_device_mode_from_config();
     info!("Config device_mode: {}", config_device_mode);
 
-    let (device_mode, device_name) = if config_device_mode == "generic" {
+    let generic_mouse = shared_config.read().map(|c| c.input.generic_mouse).unwrap_or_default();
+    if generic_mouse == GenericMouseMode::Off && config_device_mode == "generic" {
+        warn!("device_mode is \"generic\" but input.generic_mouse is \"off\" - generic mice are not scanned");
+    }
+
+    let (device_mode, device_name) = if config_device_mode == "generic" && generic_mouse != GenericMouseMode::Off {
         // User forced generic mode via settings toggle
         let name = match EvdevHandler::find_any_mouse() {
             Ok(info) => {
@@ -324,6 +530,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 info!("Device mode: logitech (evdev MX detected: {})", info.name);
                 ("logitech".to_string(), info.name)
             }
+            Err(_) if generic_mouse == GenericMouseMode::Off => {
+                warn!("No MX mouse detected at startup - will poll for connection");
+                ("logitech".to_string(), "Unknown".to_string())
+            }
             Err(_) => {
                 // Try generic mouse fallback
                 match EvdevHandler::find_any_mouse() {
@@ -433,6 +643,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     log_startup_phase(&startup_started_at, "macro_diverts");
 
+    // Everything diverted above or by the hidraw loop, handed back on shutdown
+    let release_cids: Vec<u16> = [
+        juhradiald::hidraw::button_cid::GESTURE_BUTTON,
+        juhradiald::hidraw::button_cid::HAPTIC,
+    ]
+    .into_iter()
+    .chain(juhradiald::config::Config::managed_button_cids())
+    .chain(macro_cids.iter().copied())
+    .collect();
+
     // Clone trigger_map and macro_engine for event processing (macro trigger detection)
     // Must clone before D-Bus init which moves them
     let trigger_map_for_events = trigger_map.clone();
@@ -446,6 +666,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Clone the haptic manager for the profile consumer before it is moved into
     // the D-Bus service below.
     let haptic_manager_for_profiles = haptic_manager_for_battery.clone();
+    let haptic_manager_for_shutdown = haptic_manager_for_battery.clone();
 
     // Shared per-app hardware profile map. Created empty here, populated once
     // profiles.json is loaded below, and refreshed by `ReloadConfig` whenever
@@ -453,6 +674,59 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // consumer hold a clone, so a UI save reaches the consumer without restart.
     let hardware_profiles: SharedHardwareProfiles = Arc::new(RwLock::new(HashMap::new()));
 
+    // Overlay supervisor handle, shared with the D-Bus service for Status()
+    // and RestartOverlay(). The supervisor task starts once profiles load.
+    let overlay_handle = OverlayHandle::new();
+
+    // Themes and accessibility state behind the menu payload. The portal
+    // watcher below keeps reduced motion, high contrast and the accent color
+    // in sync with the desktop settings.
+    let (theme_manager, accessibility) = {
+        let config = read_config(&shared_config)?;
+        handles.system_accent.update(|a| a.set_follow(config.colors.accent_follow_system));
+        handles.idle_effect.update(|s| s.set_config(config.idle_effects.clone()));
+        (
+            load_shared_theme_manager(&config.theme),
+            new_shared_accessibility(&config.accessibility),
+        )
+    };
+
+    // Per-profile thumb-wheel bindings. Profiles are filled in once loaded;
+    // the MX evdev loop intercepts, Status() reports, ReloadConfig refreshes.
+    let thumbwheel_mapper: SharedThumbwheelMapper = {
+        let config = read_config(&shared_config)?;
+        let mut mapper = ThumbwheelMapper::new(&config.thumbwheel);
+        mapper.set_label_budget(config.menu.label_budget());
+        Arc::new(RwLock::new(mapper))
+    };
+
+    // Manual profile selection from the last run; checked against the
+    // profiles once they load, dropped on the next focus change.
+    let profile_selection = load_shared_selection();
+
+    // Menu auto-dismiss: the D-Bus service drives the state, the gesture
+    // loop owns the timer.
+    let menu_dismissal = {
+        let config = read_config(&shared_config)?;
+        MenuDismissalHandle::new(MenuDismissal::new(config.menu.auto_dismiss()))
+    };
+    // Flick mode: the input loops and the D-Bus service feed the state, a
+    // task below owns the flick window timer.
+    let flick_settings = read_config(&shared_config)?.menu.flick();
+    handles.flick.update(|f, _| f.set_settings(flick_settings));
+    let focus_settle = read_config(&shared_config)?.menu.focus_settle();
+    handles.focus_settle.update(|s| s.set_settle(focus_settle));
+    // Slice descriptions: the D-Bus service tracks the highlight, a task
+    // below owns the dwell timer.
+    let hover_help = HoverHelpHandle::default();
+
+    // Probe the active-window backend up front so Status() can report it.
+    let window_tracker = WindowTracker::new();
+    // What works in this session (X11/Wayland, KDE or not), for the log;
+    // Status() builds the same matrix per call
+    let session = juhradiald::session::Environment::detect(window_tracker.backend_name());
+    session.log();
+
     // Export the D-Bus service on the connection that already holds the
     // single-instance name claim from startup.
     match init_dbus_service_with_device(
@@ -468,6 +742,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         trigger_map,
         active_window_tx.clone(),
         hardware_profiles.clone(),
+        overlay_handle.clone(),
+        theme_manager.clone(),
+        accessibility.clone(),
+        window_tracker.backend_name(),
+        thumbwheel_mapper.clone(),
+        startup_report,
+        menu_dismissal.clone(),
+        hover_help.clone(),
+        profile_selection.clone(),
+        handles.clone(),
     )
     .await
     {
@@ -484,14 +768,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     log_startup_phase(&startup_started_at, "dbus");
 
+    let background = BackgroundTasks::default();
+
     // Detect KWin by D-Bus name ownership (not XDG_CURRENT_DESKTOP, which is
     // empty when systemd starts the daemon at cold boot, issue #32). The watcher
-    // seeds the flag and follows KWin restarts on the same session connection.
-    let kwin_availability = juhradiald::compositor::KWinAvailability::new();
+    // seeds the flag and follows KWin restarts on the same session connection,
+    // installing the resident helper (unloaded in shutdown()) and, for the
+    // KWin window backend, the active-window script on each KWin it sees.
+    let kwin_availability = handles.kwin.clone();
+    let kwin_session = juhradiald::compositor::KWinSession::new(
+        kwin_availability.clone(),
+        handles.blur.clone(),
+        window_tracker.backend_name() == Some("kwin"),
+    );
+    {
+        let conn = dbus_connection.clone();
+        let session = kwin_session.clone();
+        background.spawn(async move { juhradiald::compositor::run_kwin_watcher(conn, session).await });
+    }
+    // Monitor layout for `menu.size`, queried once up front so the first menu
+    // finds it; KWin's helper reports its own when it loads
+    let monitors = handles.monitors.clone();
+    let monitors_helper = handles.kwin.helper().clone();
+    background.spawn(async move {
+        let _ = tokio::task::spawn_blocking(move || monitors.refresh(&monitors_helper)).await;
+    });
+
+    // Edited theme files: reload once valid, report rejected ones
+    #[cfg(feature = "themes")]
+    {
+        let conn = dbus_connection.clone();
+        let themes = theme_manager.clone();
+        let stats = handles.theme_watcher.clone();
+        background.spawn(async move { juhradiald::theme_watcher::run_hot_reload(conn, themes, stats).await });
+    }
+
+    // Idle effects go off on battery (`idle_effects.disable_on_battery`)
     {
         let conn = dbus_connection.clone();
-        let kwin = kwin_availability.clone();
-        tokio::spawn(async move { juhradiald::compositor::run_kwin_watcher(conn, kwin).await });
+        let themes = theme_manager.clone();
+        let accessibility = accessibility.clone();
+        let idle_effect = handles.idle_effect.clone();
+        background.spawn(async move { juhradiald::idle_effect::run_power_watcher(conn, themes, accessibility, idle_effect).await });
+    }
+
+    {
+        let conn = dbus_connection.clone();
+        let system_accent = handles.system_accent.clone();
+        let idle_effect = handles.idle_effect.clone();
+        background.spawn(async move { run_accessibility_watcher(conn, accessibility, theme_manager, system_accent, idle_effect).await });
     }
 
     let haptic_manager_for_hidraw = haptic_manager_for_battery.clone();
@@ -500,9 +825,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // writes, so GetBatteryStatus reflects them even when the active query fails.
     let battery_state_for_events = battery_state.clone();
 
+    // Prometheus endpoint on localhost (`telemetry.metrics_port`, read once)
+    let metrics_port = read_config(&shared_config)?.telemetry.metrics_port;
+    if metrics_port != 0 {
+        let battery_state = battery_state.clone();
+        let handles = handles.clone();
+        background.spawn(async move { juhradiald::metrics::run(metrics_port, battery_state, handles).await });
+    }
+
+    // Write-behind queue for state, stats and config writes from the input path
+    let persistence = handles.persistence.clone();
+    background.spawn(async move { persistence.run().await });
+
+    // Quiet hours: the schedule is re-read from the live config every tick
+    let quiet_config = shared_config.clone();
+    let quiet_hours = handles.quiet_hours.clone();
+    background.spawn(async move { juhradiald::quiet_hours::run(quiet_config, quiet_hours).await });
+
+    // udev hotplug events, debounced into device inventory changes. The evdev
+    // loops, the HID++ loop and the battery updater subscribe to the kinds of
+    // device they use and re-scan only when those changed.
+    let device_watcher = handles.device_watcher.clone();
+    {
+        let watcher = device_watcher.clone();
+        background.spawn(async move { watcher.run().await });
+    }
+
     // Spawn battery status updater (shares HidppDevice with haptic via SharedHapticManager)
-    let battery_handle = tokio::spawn(async move {
-        start_battery_updater_shared(battery_state, haptic_manager_for_battery).await
+    let (battery_summary_interval, battery_unavailable_after) = {
+        let config = read_config(&shared_config)?;
+        (config.battery.summary_interval(), config.battery.unavailable_after())
+    };
+    let battery_devices = device_watcher.subscribe(&[DeviceKind::Hidraw]);
+    let battery_conflicts = handles.conflicts.clone();
+    let battery_handle = background.spawn(async move {
+        start_battery_updater_shared(
+            battery_state,
+            haptic_manager_for_battery,
+            battery_summary_interval,
+            battery_unavailable_after,
+            battery_devices,
+            battery_conflicts,
+        )
+        .await
     });
 
     // Load profiles (Story 3.1: Task 5)
@@ -513,6 +878,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 profile_count = manager.profile_count(),
                 "Profile manager initialized"
             );
+            manager.lint_shortcuts(&read_config(&shared_config)?.shortcut_lint_rules());
             manager
         }
         Err(e) => {
@@ -532,16 +898,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(mut map) => *map = profile_manager.hardware_profiles(),
         Err(e) => error!(error = %e, "Failed to seed shared hardware profiles"),
     }
+    let selected = match profile_selection.write() {
+        Ok(mut selection) => selection.retain_loaded(&profile_manager).map(str::to_string),
+        Err(e) => {
+            error!(error = %e, "Failed to read profile selection");
+            None
+        }
+    };
+    if let Some(ref name) = selected {
+        info!(profile = %name, "Manually selected profile restored");
+    }
+    match thumbwheel_mapper.write() {
+        Ok(mut mapper) => {
+            mapper.set_profiles(&profile_manager);
+            mapper.set_selected(selected.as_deref());
+        }
+        Err(e) => error!(error = %e, "Failed to seed thumb-wheel bindings"),
+    }
     log_startup_phase(&startup_started_at, "profiles");
 
-    // Initialize window tracker for per-app HARDWARE profiles (Story 3.2/3.3).
+    // Supervise the overlay so a crash does not leave gesture presses dead
+    let overlay_config = shared_config
+        .read()
+        .map(|c| c.overlay.clone())
+        .unwrap_or_default();
+    if overlay_config.supervise {
+        let conn = dbus_connection.clone();
+        let config = shared_config.clone();
+        let thumbwheel = thumbwheel_mapper.clone();
+        let handle = overlay_handle.clone();
+        background.spawn(async move {
+            run_overlay_supervisor(handle, overlay_config.path, conn, config, thumbwheel).await
+        });
+    } else {
+        info!("Overlay supervision disabled in config");
+    }
+
+    // Start the window tracker for per-app HARDWARE profiles (Story 3.2/3.3).
     // The tracker pushes focused-window resource classes; the consumer below
     // applies any matching HardwareProfile via volatile HID++ setters.
-    let window_tracker = WindowTracker::new();
     if window_tracker.is_available() {
-        info!(desktop = window_tracker.desktop(), "Window tracking enabled for per-app hardware profiles");
+        info!(
+            desktop = window_tracker.desktop(),
+            backend = window_tracker.backend_name().unwrap_or(""),
+            "Window tracking enabled for per-app hardware profiles"
+        );
         let watch_tx = active_window_tx.clone();
-        tokio::spawn(async move { window_tracker.watch(watch_tx).await });
+        background.spawn(async move { window_tracker.watch(watch_tx).await });
     } else {
         warn!("Window tracking unavailable - per-app hardware profiles inactive");
     }
@@ -555,16 +958,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Read the live shared map (refreshed by ReloadConfig) instead of a
         // one-time snapshot, so UI saves take effect without a daemon restart.
         let hw_profiles = hardware_profiles.clone();
+        let thumbwheel = thumbwheel_mapper.clone();
+        let selection = profile_selection.clone();
+        let late_focus_connection = dbus_connection.clone();
+        let late_focus = handles.late_focus.clone();
+        let persistence = handles.persistence.clone();
+        let focus_settle = handles.focus_settle.clone();
         if !hw_profiles.read().map(|m| m.is_empty()).unwrap_or(true) {
             info!("Per-app hardware profiles configured; focus-change application active");
         }
-        tokio::spawn(async move {
+        background.spawn(async move {
             let mut current_class = String::new();
-            while let Some(class) = active_window_rx.recv().await {
+            loop {
+                // Each report restarts the settle timer; only a class that
+                // stays focused switches profiles (`window_tracker::settle`)
+                let deadline = focus_settle.update(|s| s.deadline()).flatten();
+                tokio::select! {
+                    received = active_window_rx.recv() => {
+                        let Some(class) = received else { break };
+                        let now = std::time::Instant::now();
+                        // A report racing the press applies at once
+                        let racing = late_focus.update(|l| l.awaiting(now)).unwrap_or(false);
+                        focus_settle.update(|s| {
+                            s.report(&class, now);
+                            if racing {
+                                s.flush(now);
+                            }
+                        });
+                    }
+                    _ = tokio::time::sleep_until(deadline.unwrap_or_else(std::time::Instant::now).into()), if deadline.is_some() => {}
+                }
+                let Some(class) = focus_settle.update(|s| s.settled(std::time::Instant::now())).flatten() else {
+                    continue;
+                };
                 if class == current_class {
                     continue;
                 }
                 current_class = class.clone();
+                // `ShowMenu` may have applied it already for its press
+                if let Some(profile) = focus_window(&selection, &thumbwheel, &class, &persistence) {
+                    // A report racing the press swaps the just-opened menu
+                    let swap = late_focus.update(|l| l.focus_changed(std::time::Instant::now(), profile));
+                    if swap == Some(true) {
+                        debug!(class = %class, "Focus changed as the menu opened - sending its profile");
+                        if let Err(e) = juhradiald::late_focus::resend(&late_focus_connection).await {
+                            warn!(error = %e, "Failed to resend the menu payload for a late focus report");
+                        }
+                    }
+                }
                 // Lookup is case-insensitive: keys are lowercased at load, so
                 // lowercase the incoming class (window-tracker sources vary).
                 let hw = {
@@ -595,31 +1036,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         });
     }
 
-    // Start inotify watcher on /dev/input/ for instant device hotplug detection.
-    // Shared across both evdev loops so they re-scan immediately on device changes.
-    let hotplug_notify = spawn_device_hotplug_watcher();
+    // Per-desktop profiles: KWin reports the current virtual desktop, and the
+    // profile selection and thumb-wheel bindings match against it.
+    {
+        let (desktop_tx, mut desktop_rx) = tokio::sync::mpsc::unbounded_channel::<u32>();
+        let conn = dbus_connection.clone();
+        let restarts = kwin_session.subscribe();
+        background.spawn(async move { DesktopTracker::new().watch_kwin(conn, desktop_tx, restarts).await });
+        let thumbwheel = thumbwheel_mapper.clone();
+        let selection = profile_selection.clone();
+        background.spawn(async move {
+            while let Some(desktop) = desktop_rx.recv().await {
+                debug!(desktop, "Virtual desktop changed");
+                match selection.write() {
+                    Ok(mut selection) => selection.set_active_desktop(Some(desktop)),
+                    Err(e) => error!(error = %e, "Failed to update profile selection desktop"),
+                }
+                match thumbwheel.write() {
+                    Ok(mut mapper) => mapper.set_active_desktop(Some(desktop)),
+                    Err(e) => error!(error = %e, "Failed to update thumb-wheel desktop"),
+                }
+            }
+        });
+    }
 
     // Create channel for gesture events
     let (event_tx, mut event_rx) = mpsc::channel::<GestureEvent>(32);
 
+    // Every menu trigger source (MX gesture, evdev buttons, shortcuts) shares
+    // one arbiter so only the trigger that opened the menu can close it
+    let trigger_arbiter = juhradiald::menu_trigger::new_shared_arbiter();
+
     // Spawn the HID++ hidraw handler (reads button events directly from mouse).
     // Button divert is volatile and is reset by Easy-Switch host changes, so
     // this loop owns re-applying diverts whenever the mouse hotplugs/reconnects.
     let hidraw_tx = event_tx.clone();
     let hidraw_config = shared_config.clone();
-    let hidraw_hotplug = hotplug_notify.clone();
+    let hidraw_hotplug = device_watcher.subscribe(&[DeviceKind::Input, DeviceKind::Hidraw]);
     let hidraw_kwin = kwin_availability.clone();
-    let hidraw_handle = tokio::spawn(async move {
+    let hidraw_arbiter = trigger_arbiter.clone();
+    let hidraw_release_cids = release_cids.clone();
+    let hidraw_handles = handles.clone();
+    let hidraw_handle = background.spawn(async move {
         run_hidraw_loop(
             hidraw_tx,
             HidrawStartup {
                 preferred_path: mx4_hidraw_path,
+                release_cids: hidraw_release_cids,
             },
             macro_cids,
             hidraw_config,
             hidraw_hotplug,
             haptic_manager_for_hidraw,
             hidraw_kwin,
+            hidraw_arbiter,
+            hidraw_handles,
         )
         .await
     });
@@ -636,37 +1107,117 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     for &code in juhradiald::evdev::GESTURE_BUTTON_CODES {
         suppressed_for_mx.insert(code);
     }
-    let hotplug_for_mx = hotplug_notify.clone();
+    let hotplug_for_mx = device_watcher.subscribe(&[DeviceKind::Input]);
     let evdev_config = shared_config.clone();
     let evdev_kwin = kwin_availability.clone();
-    let evdev_handle = tokio::spawn(async move {
-        run_evdev_loop(evdev_tx, suppressed_for_mx, hotplug_for_mx, evdev_config, evdev_kwin).await
+    let evdev_thumbwheel = thumbwheel_mapper.clone();
+    let evdev_arbiter = trigger_arbiter.clone();
+    let evdev_handles = handles.clone();
+    let evdev_handle = background.spawn(async move {
+        run_evdev_loop(
+            evdev_tx,
+            suppressed_for_mx,
+            hotplug_for_mx,
+            evdev_config,
+            evdev_kwin,
+            evdev_thumbwheel,
+            evdev_arbiter,
+            evdev_handles,
+        )
+        .await
     });
 
     let generic_evdev_tx = event_tx.clone();
     let suppressed_for_generic = macro_evdev_codes.clone();
-    let hotplug_for_generic = hotplug_notify.clone();
+    let hotplug_for_generic = device_watcher.subscribe(&[DeviceKind::Input]);
     let generic_evdev_config = shared_config.clone();
     let generic_evdev_kwin = kwin_availability.clone();
-    let generic_evdev_handle = tokio::spawn(async move {
+    let generic_evdev_arbiter = trigger_arbiter.clone();
+    let generic_evdev_handles = handles.clone();
+    let generic_evdev_handle = background.spawn(async move {
         run_generic_evdev_loop(
             generic_evdev_tx,
             suppressed_for_generic,
             hotplug_for_generic,
             generic_evdev_config,
             generic_evdev_kwin,
+            generic_evdev_arbiter,
+            generic_evdev_handles,
+            generic_mouse,
         )
         .await
     });
 
+    // Keyboard shortcut triggers (KGlobalAccel); read once at startup
+    let shortcuts = read_config(&shared_config)?.shortcut_triggers();
+    if !shortcuts.is_empty() {
+        let conn = dbus_connection.clone();
+        let shortcut_tx = event_tx.clone();
+        let shortcut_kwin = kwin_availability.clone();
+        background.spawn(async move {
+            juhradiald::global_shortcuts::run_shortcut_listener(
+                conn,
+                shortcuts,
+                shortcut_tx,
+                trigger_arbiter,
+                shortcut_kwin,
+            )
+            .await
+        });
+    }
+
+    // Panic switch: SIGUSR1 disarms input interception; any disarm dismisses
+    // the menu. The input loops release their own grabs and diverts.
+    let sigusr1 =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1())?;
+    let panic_connection = dbus_connection.clone();
+    let panic_switch = handles.panic_switch.clone();
+    background.spawn(async move { watch_panic_switch(&panic_connection, panic_switch, sigusr1).await });
+
+    // SIGHUP (`systemctl reload`) reloads config, themes and profiles as
+    // one transaction
+    let sighup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+    background.spawn(juhradiald::reload::run_on_sighup(dbus_connection.clone(), sighup));
+
+    // SIGUSR2 toggles the HID++ trace
+    let mut sigusr2 =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined2())?;
+    let sigusr2_trace = handles.hidpp_trace.clone();
+    background.spawn(async move {
+        while sigusr2.recv().await.is_some() {
+            sigusr2_trace.toggle();
+        }
+    });
+
+    let hover_connection = dbus_connection.clone();
+    background.spawn(async move { run_hover_help(&hover_connection, hover_help).await });
+    background.spawn(run_prewarm(handles.prewarm.clone(), handles.binaries.clone()));
+    let dwell_connection = dbus_connection.clone();
+    let dwell_handles = handles.clone();
+    background.spawn(async move { run_dwell_confirm(&dwell_connection, &dwell_handles).await });
+    let flick_connection = dbus_connection.clone();
+    let flick = handles.flick.clone();
+    background.spawn(async move { run_flick(&flick_connection, flick).await });
+    background.spawn(juhradiald::media_control::run(dbus_connection.clone(), handles.media.clone()));
+
     // Spawn event processing task with D-Bus connection
-    let event_handle = tokio::spawn(async move {
+    let shutdown_connection = dbus_connection.clone();
+    let haptic_manager_for_events = haptic_manager_for_shutdown.clone();
+    let config_for_events = shared_config.clone();
+    let handles_for_events = handles.clone();
+    let event_handle = background.spawn(async move {
         process_gesture_events(
             &mut event_rx,
             &dbus_connection,
             trigger_map_for_events,
             macro_engine_for_events,
             battery_state_for_events,
+            config_for_events,
+            thumbwheel_mapper,
+            profile_selection,
+            menu_dismissal,
+            haptic_manager_for_events,
+            handles_for_events,
         )
         .await
     });
@@ -675,13 +1226,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 4. Initialize HID++ haptic subsystem
 
     log_startup_phase(&startup_started_at, "ready");
+    // Install the SIGTERM handler before announcing readiness: a stop request
+    // that arrives right after READY=1 must still shut down gracefully.
+    let mut sigterm =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
     info!("JuhRadial MX Daemon ready");
+    if let Err(e) = sd_notify::notify(sd_notify::READY) {
+        warn!(error = %e, "Failed to send readiness notification to systemd");
+    }
 
-    // Wait for shutdown signal
+    // Wait for shutdown signal (SIGTERM from systemd, SIGINT from a terminal)
     tokio::select! {
         _ = tokio::signal::ctrl_c() => {
             info!("Shutdown signal received, exiting...");
         }
+        _ = sigterm.recv() => {
+            info!("SIGTERM received, exiting...");
+        }
+        _ = handles.quit.requested() => {
+            info!("Quit requested over D-Bus, exiting...");
+        }
         result = hidraw_handle => {
             if let Err(e) = result {
                 error!("hidraw task panicked: {:?}", e);
@@ -709,7 +1273,69 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    Ok(())
+    shutdown(
+        &shutdown_connection,
+        &background,
+        haptic_manager_for_shutdown,
+        release_cids,
+        &handles,
+    )
+    .await;
+    // Exit directly: dropping the runtime would block on any spawn_blocking
+    // HID++ call still in flight, defeating the shutdown bound.
+    std::process::exit(0)
+}
+
+/// Stop the daemon within `SHUTDOWN_TIMEOUT`
+///
+/// Dismisses an open menu (the overlay process itself keeps running so the
+/// next daemon adopts it), records a still-open menu in local stats, cancels
+/// the background tasks, unloads the daemon's KWin scripts, then hands diverted buttons and the thumb wheel back
+/// to the mouse so it works normally while the daemon is down.
+async fn shutdown(
+    connection: &zbus::Connection,
+    background: &BackgroundTasks,
+    haptic_manager: SharedHapticManager,
+    release_cids: Vec<u16>,
+    handles: &DaemonHandles,
+) {
+    if let Err(e) = sd_notify::notify(sd_notify::STOPPING) {
+        warn!(error = %e, "Failed to send stopping notification to systemd");
+    }
+    let sequence = async {
+        if let Err(e) = emit_dismiss_menu(connection).await {
+            warn!(error = %e, "Failed to dismiss the menu on shutdown");
+        }
+        match connection
+            .object_server()
+            .interface::<_, JuhRadialService>(DBUS_PATH)
+            .await
+        {
+            Ok(service) => service.get().await.shutdown(),
+            Err(e) => warn!(error = %e, "D-Bus service unavailable during shutdown"),
+        }
+        // Before the task is cancelled, and after the stats event above
+        handles.persistence.flush().await;
+        background.stop().await;
+        // After the helper task: nothing re-installs it behind our back
+        handles.kwin.helper().unload_all(connection).await;
+        // Last: the tasks above no longer touch the device
+        let released = tokio::task::spawn_blocking(move || match haptic_manager.lock() {
+            Ok(mut manager) => manager.release(&release_cids),
+            Err(e) => error!(error = %e, "Failed to lock haptic manager for release"),
+        })
+        .await;
+        if let Err(e) = released {
+            error!("HID++ release task panicked: {:?}", e);
+        }
+    };
+    match tokio::time::timeout(SHUTDOWN_TIMEOUT, sequence).await {
+        Ok(()) => info!("Shutdown complete"),
+        Err(_) => warn!(
+            timeout_ms = SHUTDOWN_TIMEOUT.as_millis() as u64,
+            "Shutdown timed out; exiting anyway"
+        ),
+    }
 }
 
 /// List all detected Logitech devices and generic mouse fallback
@@ -764,6 +1390,8 @@ fn list_logitech_devices() {
 
 struct HidrawStartup {
     preferred_path: Option<PathBuf>,
+    /// Diverted CIDs handed back to the mouse while disarmed
+    release_cids: Vec<u16>,
 }
 
 /// Reconnect HID++ and re-apply volatile button diverts.
@@ -774,6 +1402,7 @@ async fn refresh_hidpp_button_diverts(
     haptic_manager: SharedHapticManager,
     macro_cids: Vec<u16>,
     remapped_cids: Vec<u16>,
+    conflicts: ConflictsHandle,
 ) -> Option<PathBuf> {
     match tokio::task::spawn_blocking(move || {
         let mut manager = haptic_manager.lock().unwrap();
@@ -788,6 +1417,8 @@ async fn refresh_hidpp_button_diverts(
             debug!("No MX Master HID++ device available for button divert");
             return None;
         }
+        // The device is back: see whether something else grabbed it meanwhile
+        conflicts.refresh_system();
 
         match manager.divert_buttons() {
             Ok(n) if n > 0 => info!(count = n, "HID++ gesture buttons diverted"),
@@ -848,6 +1479,18 @@ async fn refresh_hidpp_button_diverts(
     }
 }
 
+/// Clear HID++ button and thumb-wheel diverts after the panic switch fired
+async fn release_hidpp_diverts(haptic_manager: SharedHapticManager, cids: Vec<u16>) {
+    let released = tokio::task::spawn_blocking(move || match haptic_manager.lock() {
+        Ok(mut manager) => manager.release(&cids),
+        Err(e) => error!(error = %e, "Failed to lock haptic manager to release diverts"),
+    })
+    .await;
+    if let Err(e) = released {
+        error!("HID++ divert release task panicked: {:?}", e);
+    }
+}
+
 /// Apply thumb-wheel divert from config and return the ThumbWheel feature index.
 ///
 /// Like button divert, thumb-wheel reporting (HID++ 0x2150) is volatile and is
@@ -901,16 +1544,20 @@ async fn fetch_notification_indices(
     .unwrap_or_default()
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_hidraw_loop(
     event_tx: mpsc::Sender<GestureEvent>,
     startup: HidrawStartup,
     macro_cids: Vec<u16>,
     shared_config: juhradiald::config::SharedConfig,
-    hotplug: Arc<tokio::sync::Notify>,
+    mut hotplug: DeviceSubscription,
     haptic_manager: SharedHapticManager,
     kwin_availability: juhradiald::compositor::KWinAvailability,
+    trigger_arbiter: juhradiald::menu_trigger::SharedTriggerArbiter,
+    handles: DaemonHandles,
 ) {
-    let HidrawStartup { mut preferred_path } = startup;
+    let panic_switch = handles.panic_switch.clone();
+    let HidrawStartup { mut preferred_path, release_cids } = startup;
     let mut handler = HidrawHandler::new(event_tx);
     let macro_cids_for_divert = macro_cids.clone();
     let config_for_thumbwheel = shared_config.clone();
@@ -918,8 +1565,20 @@ async fn run_hidraw_loop(
     handler.set_macro_cids(macro_cids);
     handler.set_shared_config(shared_config);
     handler.set_kwin_availability(kwin_availability);
+    handler.set_trigger_arbiter(trigger_arbiter);
+    handler.set_handles(handles.clone());
 
     loop {
+        // Disarmed by the panic switch: hand the buttons and thumb wheel back
+        // to the mouse, and divert again only once re-armed
+        if !panic_switch.is_armed() {
+            release_hidpp_diverts(haptic_manager.clone(), release_cids.clone()).await;
+            panic_switch.armed().await;
+        }
+
+        // This (re)connect sees every device change up to here
+        hotplug.clear();
+
         // Re-read the reassigned buttons each cycle so a config change is
         // picked up on the next reconnect (live changes go through ReloadConfig).
         let remapped_cids = config_for_divert
@@ -930,6 +1589,7 @@ async fn run_hidraw_loop(
             haptic_manager.clone(),
             macro_cids_for_divert.clone(),
             remapped_cids,
+            handles.conflicts.clone(),
         )
         .await
         {
@@ -979,9 +1639,13 @@ async fn run_hidraw_loop(
                 // us the mouse may have returned from another Easy-Switch host.
                 let start_result = tokio::select! {
                     result = handler.start() => Some(result),
-                    _ = hotplug.notified() => None,
+                    _ = hotplug.changed() => None,
+                    _ = panic_switch.disarmed() => Some(Ok(())),
                 };
                 handler.close();
+                if !panic_switch.is_armed() {
+                    continue;
+                }
 
                 match start_result {
                     Some(Ok(())) => {
@@ -1024,12 +1688,14 @@ async fn run_hidraw_loop(
             continue;
         }
 
-        // Wait for either the shorter HID++ reconnect poll or device hotplug.
+        // Wait for either the shorter HID++ reconnect poll or device hotplug;
+        // slower while another program holds the device.
         tokio::select! {
-            _ = sleep(Duration::from_secs(HIDRAW_RECONNECT_POLL_INTERVAL_SECS)) => {}
-            _ = hotplug.notified() => {
+            _ = sleep(handles.conflicts.backoff(Duration::from_secs(HIDRAW_RECONNECT_POLL_INTERVAL_SECS))) => {}
+            _ = hotplug.changed() => {
                 debug!("Device hotplug detected, re-scanning HID++ devices");
             }
+            _ = panic_switch.disarmed() => {}
         }
     }
 }
@@ -1040,22 +1706,33 @@ async fn run_hidraw_loop(
 /// - Initial device detection
 /// - Polling for device when not found (2-second intervals)
 /// - Reconnection after device disconnect
-/// - Instant re-scan on device hotplug (via inotify)
+/// - Instant re-scan on device hotplug (via the device watcher)
+#[allow(clippy::too_many_arguments)]
 async fn run_evdev_loop(
     event_tx: mpsc::Sender<GestureEvent>,
     suppressed_keys: HashSet<u16>,
-    hotplug: Arc<tokio::sync::Notify>,
+    mut hotplug: DeviceSubscription,
     shared_config: juhradiald::config::SharedConfig,
     kwin_availability: juhradiald::compositor::KWinAvailability,
+    thumbwheel: SharedThumbwheelMapper,
+    trigger_arbiter: juhradiald::menu_trigger::SharedTriggerArbiter,
+    handles: DaemonHandles,
 ) {
     let mut handler = EvdevHandler::new(event_tx.clone());
     handler.set_suppressed_keys(suppressed_keys);
     handler.set_shared_config(shared_config);
     handler.set_kwin_availability(kwin_availability);
+    handler.set_thumbwheel_mapper(thumbwheel);
+    handler.set_trigger_arbiter(trigger_arbiter);
+    handler.set_handles(handles);
 
     let mut logged_waiting = false;
 
     loop {
+        // Forget changes this scan will see; a node created while we scan or
+        // run the event loop still wakes the wait below
+        hotplug.clear();
+
         // Try to find and connect to the device
         match EvdevHandler::find_device() {
             Ok(device_info) => {
@@ -1074,6 +1751,16 @@ async fn run_evdev_loop(
                         warn!("Device disconnected, will poll for reconnection...");
                         logged_waiting = false;
                     }
+                    Err(EvdevError::Disconnected) => {
+                        // Node renumbered or gone (suspend/resume): re-scan now
+                        // instead of waiting for the next hotplug or poll
+                        logged_waiting = false;
+                        continue;
+                    }
+                    Err(EvdevError::Disarmed) => {
+                        // Grab dropped with the node; start() waits for re-arm
+                        continue;
+                    }
                     Err(EvdevError::PermissionDenied) => {
                         error!("Permission denied. Ensure udev rules are installed.");
                         error!("Run: sudo usermod -aG input $USER && logout");
@@ -1097,12 +1784,13 @@ async fn run_evdev_loop(
             Err(EvdevError::IoError(e)) => {
                 error!("I/O error during device scan: {}", e);
             }
+            Err(EvdevError::Disconnected | EvdevError::Disarmed) => {}
         }
 
         // Wait for either poll interval OR instant hotplug notification
         tokio::select! {
             _ = sleep(Duration::from_secs(DEVICE_POLL_INTERVAL_SECS)) => {}
-            _ = hotplug.notified() => {
+            _ = hotplug.changed() => {
                 debug!("Device hotplug detected, re-scanning MX devices");
                 logged_waiting = false;
             }
@@ -1112,9 +1800,7 @@ async fn run_evdev_loop(
 
 /// Read generic_trigger_button from ~/.config/juhradial/config.json
 fn read_trigger_button_from_config() -> Option<u16> {
-    let home = std::env::var("HOME").ok()?;
-    let path = std::path::PathBuf::from(home).join(".config/juhradial/config.json");
-    let data = std::fs::read_to_string(&path).ok()?;
+    let data = std::fs::read_to_string(juhradiald::paths::paths().config_path()).ok()?;
     let json: serde_json::Value = serde_json::from_str(&data).ok()?;
     json.get("generic_trigger_button")?
         .as_u64()
@@ -1126,12 +1812,7 @@ fn read_trigger_button_from_config() -> Option<u16> {
 /// Returns "generic", "logitech", or "auto" (default).
 /// When the user toggles "Generic" in settings, this is set to "generic".
 fn read_device_mode_from_config() -> String {
-    let home = match std::env::var("HOME") {
-        Ok(h) => h,
-        Err(_) => return "auto".to_string(),
-    };
-    let path = std::path::PathBuf::from(home).join(".config/juhradial/config.json");
-    let data = match std::fs::read_to_string(&path) {
+    let data = match std::fs::read_to_string(juhradiald::paths::paths().config_path()) {
         Ok(d) => d,
         Err(_) => return "auto".to_string(),
     };
@@ -1147,15 +1828,27 @@ fn read_device_mode_from_config() -> String {
 
 /// Run the generic mouse evdev detection and event loop
 ///
-/// Same as run_evdev_loop but uses find_any_mouse() and configurable trigger button.
-/// This is the fallback when no Logitech MX device is found.
+/// Same as run_evdev_loop but scans for any mouse, preferring one that has a
+/// configured trigger button (`evdev-button` triggers, else
+/// `generic_trigger_button`). This is the fallback when no Logitech MX device
+/// is found. `input.generic_mouse` picks the scan cadence, or turns the loop
+/// off entirely (it then idles until shutdown).
+#[allow(clippy::too_many_arguments)]
 async fn run_generic_evdev_loop(
     event_tx: mpsc::Sender<GestureEvent>,
     suppressed_keys: HashSet<u16>,
-    hotplug: Arc<tokio::sync::Notify>,
+    mut hotplug: DeviceSubscription,
     shared_config: juhradiald::config::SharedConfig,
     kwin_availability: juhradiald::compositor::KWinAvailability,
+    trigger_arbiter: juhradiald::menu_trigger::SharedTriggerArbiter,
+    handles: DaemonHandles,
+    mode: GenericMouseMode,
 ) {
+    if mode == GenericMouseMode::Off {
+        info!("Generic mouse scanning disabled (input.generic_mouse = off)");
+        // Returning would end the daemon's main select
+        return std::future::pending().await;
+    }
     let trigger = read_trigger_button_from_config();
     if let Some(code) = trigger {
         info!("Generic trigger button from config: {:#x}", code);
@@ -1164,10 +1857,17 @@ async fn run_generic_evdev_loop(
     handler.set_suppressed_keys(suppressed_keys);
     handler.set_shared_config(shared_config);
     handler.set_kwin_availability(kwin_availability);
+    handler.set_trigger_arbiter(trigger_arbiter);
+    handler.set_handles(handles);
 
     let mut logged_waiting = false;
+    // Consecutive scans that found nothing (drives the `auto` backoff)
+    let mut misses: u32 = 0;
 
     loop {
+        // Forget changes this scan will see (see run_evdev_loop)
+        hotplug.clear();
+
         // Re-read trigger button from config on each reconnect cycle
         // so rebinds in settings take effect without daemon restart
         if let Some(code) = read_trigger_button_from_config() {
@@ -1175,9 +1875,10 @@ async fn run_generic_evdev_loop(
         }
 
         // Try to find any generic mouse
-        match EvdevHandler::find_any_mouse() {
+        match EvdevHandler::find_any_mouse_with_buttons(None, &handler.scan_trigger_codes()) {
             Ok(device_info) => {
                 logged_waiting = false;
+                misses = 0;
                 info!(
                     "Detected generic mouse at {:?} ({})",
                     device_info.path, device_info.name
@@ -1192,6 +1893,16 @@ async fn run_generic_evdev_loop(
                         warn!("Generic mouse disconnected, will poll for reconnection...");
                         logged_waiting = false;
                     }
+                    Err(EvdevError::Disconnected) => {
+                        // Node renumbered or gone (suspend/resume): re-scan now
+                        // instead of waiting for the next hotplug or poll
+                        logged_waiting = false;
+                        continue;
+                    }
+                    Err(EvdevError::Disarmed) => {
+                        // Grab dropped with the node; start() waits for re-arm
+                        continue;
+                    }
                     Err(EvdevError::PermissionDenied) => {
                         error!("Permission denied. Ensure udev rules are installed.");
                         error!("Run: sudo usermod -aG input $USER && logout");
@@ -1202,27 +1913,36 @@ async fn run_generic_evdev_loop(
                 }
             }
             Err(EvdevError::DeviceNotFound) => {
-                // Only log once to avoid spamming every 2s when no generic mouse exists
+                misses = misses.saturating_add(1);
+                // Logged once per state change, not on every scan
                 if !logged_waiting {
-                    info!("No generic mouse found - polling in background");
+                    info!(
+                        mode = ?mode,
+                        max_interval_secs = generic_poll_delay(mode, u32::MAX).as_secs(),
+                        "No generic mouse found - polling in background"
+                    );
                     logged_waiting = true;
                 }
             }
             Err(EvdevError::PermissionDenied) => {
+                misses = misses.saturating_add(1);
                 error!("Permission denied accessing input devices.");
                 error!("Ensure udev rules are installed and user is in 'input' group.");
             }
             Err(EvdevError::IoError(e)) => {
+                misses = misses.saturating_add(1);
                 error!("I/O error during device scan: {}", e);
             }
+            Err(EvdevError::Disconnected | EvdevError::Disarmed) => {}
         }
 
         // Wait for either poll interval OR instant hotplug notification
         tokio::select! {
-            _ = sleep(Duration::from_secs(DEVICE_POLL_INTERVAL_SECS)) => {}
-            _ = hotplug.notified() => {
+            _ = sleep(generic_poll_delay(mode, misses.max(1))) => {}
+            _ = hotplug.changed() => {
                 debug!("Device hotplug detected, re-scanning generic mice immediately");
-                logged_waiting = false; // Re-log status after hotplug
+                // A new device restarts the backoff
+                misses = 0;
             }
         }
     }
@@ -1233,36 +1953,200 @@ async fn run_generic_evdev_loop(
 /// Press triggers ydotool injection -> cursor_grabber catches -> emits ShowMenu
 /// Release emits HideMenu directly
 /// MacroTriggered events are checked against the TriggerMap for macro execution
+/// Also owns the menu auto-dismiss timer, emitting DismissMenu when it fires
+#[allow(clippy::too_many_arguments)]
 async fn process_gesture_events(
     event_rx: &mut mpsc::Receiver<GestureEvent>,
     dbus_connection: &zbus::Connection,
     trigger_map: Arc<std::sync::RwLock<juhradiald::macros::TriggerMap>>,
     macro_engine: Arc<Mutex<juhradiald::macros::MacroEngine>>,
     battery_state: SharedBatteryState,
+    shared_config: juhradiald::config::SharedConfig,
+    thumbwheel: SharedThumbwheelMapper,
+    profile_selection: SharedProfileSelection,
+    menu_dismissal: MenuDismissalHandle,
+    haptic_manager: SharedHapticManager,
+    handles: DaemonHandles,
 ) {
-    while let Some(event) = event_rx.recv().await {
+    // Last rebased pointer offset, where a pending confirmation resumes from
+    let mut pointer = (0, 0);
+    loop {
+        // Re-armed every iteration: a reset, close or reload wakes `changed()`
+        // and the old sleep is dropped in favour of the new deadline.
+        let wake_at = menu_dismissal.wake_at();
+        let confirm_by = handles.slice_confirmation.update(|c| c.deadline()).flatten();
+        let event = tokio::select! {
+            biased;
+            event = event_rx.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = menu_dismissal.changed() => continue,
+            _ = deadline::sleep_until(wake_at) => {
+                if menu_dismissal.update(|m, now| m.fire(now)).unwrap_or(false) {
+                    info!("Menu idle past auto-dismiss timeout - dismissing");
+                    if let Err(e) = emit_dismiss_menu(dbus_connection).await {
+                        error!("Failed to emit DismissMenu signal: {}", e);
+                    }
+                }
+                continue;
+            }
+            _ = deadline::sleep_until(confirm_by) => {
+                if handles.slice_confirmation.update(|c| c.expired(Instant::now())).unwrap_or(false) {
+                    info!("Slice not confirmed in time - dismissing");
+                    if let Err(e) = emit_dismiss_menu(dbus_connection).await {
+                        error!("Failed to emit DismissMenu signal: {}", e);
+                    }
+                }
+                continue;
+            }
+        };
         match event {
             GestureEvent::Pressed { x, y } => {
                 // HID++ hidraw handler provides cursor coordinates directly
                 info!(x, y, "Gesture button pressed - showing radial menu");
+                handles.dwell_confirm.update(|d, _| d.pressed());
+                handles.calibration.update(|c| c.pressed());
 
                 // Emit ShowMenu via D-Bus
                 if let Err(e) = emit_menu_requested(dbus_connection, x, y).await {
                     error!("Failed to emit ShowMenu signal: {}", e);
                 }
             }
-            GestureEvent::Released { duration_ms } => {
+            GestureEvent::Released { duration_ms, released_at } => {
                 info!(duration_ms, "Gesture button released");
+                // The wheel scrolls again even before the overlay reports the close
+                handles.wheel_axis.update(|w| w.released());
+                let flick = handles
+                    .flick
+                    .update(|f, _| f.released(released_at))
+                    .unwrap_or(flick::Release::Normal);
+                if handles.calibration.is_capturing() {
+                    // Capture-only: the release is a sample, never an action
+                    handles.dwell_confirm.update(|d, _| d.released());
+                    let events = handles.calibration.update(|c| c.released(duration_ms)).unwrap_or_default();
+                    for event in &events {
+                        if let Err(e) = emit_calibration_event(dbus_connection, event).await {
+                            error!("Failed to emit CalibrationEvent signal: {}", e);
+                        }
+                    }
+                    if let Err(e) = emit_dismiss_menu(dbus_connection).await {
+                        error!("Failed to emit DismissMenu signal: {}", e);
+                    }
+                    continue;
+                }
+                let open_at = match flick {
+                    flick::Release::Normal => None,
+                    flick::Release::Open { x, y } => Some((x, y)),
+                    flick::Release::Flick { dx, dy, menu_at } => match flick_target(&thumbwheel, &shared_config, dx, dy) {
+                        Some((slot, count)) => {
+                            if let Some(left) = flick_cooling(&thumbwheel, &handles.slice_cooldown, slot, released_at) {
+                                tracing::debug!(slot, left_ms = left.as_millis() as u64, "Flick on a slice still cooling - not running it");
+                                juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::InvalidAction);
+                                continue;
+                            }
+                            info!(slot, dx, dy, "Flick - running the slice without the menu");
+                            juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::SelectionConfirm);
+                            let profile_menu = thumbwheel
+                                .read()
+                                .ok()
+                                .and_then(|m| m.menu_actions().map(|actions| ProfileMenu::new(m.menu_profile(), actions)));
+                            match profile_menu {
+                                Some(menu) => {
+                                    run_flicked_slice(&menu, slot, &thumbwheel, &profile_selection, &haptic_manager, &shared_config, &handles).await;
+                                }
+                                // The Settings ring runs in the overlay
+                                None => {
+                                    if let Err(e) = emit_flick_selected(dbus_connection, slot, count).await {
+                                        error!("Failed to emit FlickSelected signal: {}", e);
+                                    }
+                                }
+                            }
+                            continue;
+                        }
+                        None => Some(menu_at),
+                    },
+                };
+                if let Some((x, y)) = open_at {
+                    // Too short or too small for a flick: the held-back menu
+                    // opens now and the release below is a tap
+                    if let Err(e) = emit_menu_requested(dbus_connection, x, y).await {
+                        error!("Failed to emit ShowMenu signal: {}", e);
+                    }
+                }
+                if handles.dwell_confirm.update(|d, _| d.released()).unwrap_or(false) {
+                    handles.menu_log.update(|l| l.released("after_dwell", released_at));
+                    tracing::debug!("Release after dwell confirm ignored");
+                    continue;
+                }
 
-                // Emit HideMenu signal via D-Bus
-                // Overlay tracks duration internally for tap-to-toggle detection
-                if let Err(e) = emit_hide_menu(dbus_connection).await {
-                    error!("Failed to emit HideMenu signal: {}", e);
+                // A release stamped before the auto-dismiss deadline confirms
+                // even if the timer is already due
+                let outcome = menu_dismissal
+                    .update(|m, _| m.released(released_at))
+                    .unwrap_or(ReleaseOutcome::Confirm);
+                // A slice still in its cooldown closes the menu unrun, before
+                // any confirmation prompt
+                let cooling = match outcome {
+                    ReleaseOutcome::Confirm => handles.slice_cooldown.update(|c| c.blocked(released_at)).flatten(),
+                    _ => None,
+                };
+                if let Some((slot, left)) = cooling {
+                    handles.menu_log.update(|l| l.released("cooling", released_at));
+                    tracing::debug!(slot, left_ms = left.as_millis() as u64, "Released on a slice still cooling - not running it");
+                    juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::InvalidAction);
+                    handles.slice_confirmation.update(|c| c.closed());
+                    if let Err(e) = emit_dismiss_menu(dbus_connection).await {
+                        error!("Failed to emit DismissMenu signal: {}", e);
+                    }
+                    continue;
+                }
+                let confirmation = match outcome {
+                    ReleaseOutcome::Confirm => handles.slice_confirmation.update(|c| c.released(released_at, pointer))
+                        .unwrap_or(ConfirmOutcome::Execute),
+                    _ => ConfirmOutcome::Execute,
+                };
+                let logged = match (outcome, confirmation) {
+                    (ReleaseOutcome::Confirm, ConfirmOutcome::Await { .. }) => "await_confirmation",
+                    (ReleaseOutcome::Confirm, ConfirmOutcome::Cancel) => "cancel",
+                    (ReleaseOutcome::Confirm, ConfirmOutcome::Execute) => "select",
+                    (ReleaseOutcome::DismissNow, _) => "dismiss",
+                    (ReleaseOutcome::AlreadyDismissed, _) => "after_dismiss",
+                };
+                handles.menu_log.update(|l| l.released(logged, released_at));
+                let result = match (outcome, confirmation) {
+                    (ReleaseOutcome::Confirm, ConfirmOutcome::Await { slice, window }) => {
+                        info!(slice, window_ms = window.as_millis() as u64, "Slice needs confirming - awaiting a second press");
+                        juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::InvalidAction);
+                        handles.dwell_confirm.update(|d, _| d.suspend());
+                        emit_awaiting_confirmation(dbus_connection, slice, window).await
+                    }
+                    (ReleaseOutcome::Confirm, ConfirmOutcome::Cancel) => {
+                        info!("Second release off the pending slice - dismissing");
+                        emit_dismiss_menu(dbus_connection).await
+                    }
+                    // Emit HideMenu signal via D-Bus
+                    // Overlay tracks duration internally for tap-to-toggle detection
+                    (ReleaseOutcome::Confirm, ConfirmOutcome::Execute) => emit_hide_menu(dbus_connection).await,
+                    (ReleaseOutcome::DismissNow, _) => emit_dismiss_menu(dbus_connection).await,
+                    (ReleaseOutcome::AlreadyDismissed, _) => {
+                        tracing::debug!("Release after auto-dismiss ignored");
+                        Ok(())
+                    }
+                };
+                if let Err(e) = result {
+                    error!(?outcome, "Failed to emit menu close signal: {}", e);
                 }
             }
             GestureEvent::CursorMoved { x, y } => {
+                menu_dismissal.update(|m, now| m.activity(now));
                 // Emit CursorMoved signal for overlay hover detection
-                // x, y are relative to button press point (menu center)
+                // x, y are relative to the button press point; rebase them onto
+                // the rendered centre in case the menu was clamped off an edge
+                handles.calibration.update(|c| c.moved(x, y));
+                let (x, y) = handles.anchor.current().from_center(x, y);
+                pointer = (x, y);
+                handles.menu_log.update(|l| l.moved(x, y));
                 if let Err(e) = emit_cursor_moved(dbus_connection, x, y).await {
                     // Don't log errors for every cursor move - too noisy
                     tracing::trace!("Failed to emit CursorMoved: {}", e);
@@ -1318,7 +2202,7 @@ async fn process_gesture_events(
             GestureEvent::ButtonActionEvent { action, pressed } => {
                 if pressed {
                     info!(%action, "Button action triggered");
-                    match juhradiald::actions::execute_button_action(action).await {
+                    match juhradiald::actions::execute_button_action(action, &handles).await {
                         Ok(true) => {
                             // Action was handled directly
                         }
@@ -1336,12 +2220,83 @@ async fn process_gesture_events(
                 }
             }
             GestureEvent::ThumbwheelScroll { clicks } => {
+                menu_dismissal.update(|m, now| m.activity(now));
                 tracing::debug!(clicks, "Thumb-wheel horizontal scroll");
-                if let Err(e) = juhradiald::actions::execute_horizontal_scroll(clicks).await {
+                if let Err(e) = juhradiald::actions::execute_horizontal_scroll(clicks, &handles).await {
                     error!(clicks, error = %e, "Failed to inject horizontal scroll");
                 }
             }
+            GestureEvent::ThumbwheelBound { direction } => {
+                let action = match thumbwheel.read() {
+                    Ok(mapper) => mapper.action(direction),
+                    Err(e) => {
+                        error!(error = %e, "Failed to read thumb-wheel bindings");
+                        None
+                    }
+                };
+                let Some(action) = action else {
+                    continue;
+                };
+                if let ActionType::ProfileSwitch(ref target) = action.action_type {
+                    match apply_switch(&profile_selection, &thumbwheel, target, &handles.persistence) {
+                        Ok(_) => juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::MenuAppear),
+                        Err(e) => {
+                            warn!(?direction, target = %target, error = %e, "Thumb-wheel profile switch failed");
+                            juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::InvalidAction);
+                        }
+                    }
+                    continue;
+                }
+                let policy = shared_config
+                    .read()
+                    .map(|c| ExecutionPolicy::from_config(&c.execution, &handles.command_rate))
+                    .unwrap_or_else(|_| ExecutionPolicy::unrestricted());
+                let context = thumbwheel
+                    .read()
+                    .map(|m| InvocationContext::now().in_window(m.active_class(), Some(m.menu_profile())))
+                    .unwrap_or_else(|_| InvocationContext::now());
+                tracing::debug!(?direction, "Thumb-wheel binding fired");
+                match ActionExecutor::execute_with_policy(&action, &policy, &context, &handles).await {
+                    Ok(()) => {}
+                    Err(ActionError::InvalidAction) => {
+                        warn!(?direction, "Thumb-wheel binding had nothing to act on");
+                        juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::InvalidAction);
+                    }
+                    Err(e) => warn!(?direction, error = %e, "Thumb-wheel binding failed"),
+                }
+            }
+            GestureEvent::WheelAxisStep { direction } => {
+                let Some(action) = handles.wheel_axis.update(|w| w.step(direction)).flatten() else {
+                    continue;
+                };
+                let policy = shared_config
+                    .read()
+                    .map(|c| ExecutionPolicy::from_config(&c.execution, &handles.command_rate))
+                    .unwrap_or_else(|_| ExecutionPolicy::unrestricted());
+                // The menu is open on the held slice
+                let slot = handles.wheel_axis.update(|w| w.armed()).flatten();
+                let context = handles.invocation.current().unwrap_or_else(InvocationContext::now);
+                let context = match slot {
+                    Some(slot) => context.with_slice(slot),
+                    None => context,
+                };
+                tracing::debug!(?direction, "Wheel axis step");
+                match ActionExecutor::execute_with_policy(&action, &policy, &context, &handles).await {
+                    // A light tick per step, like moving between slices
+                    Ok(()) => juhradiald::hidpp::emit_shared(&haptic_manager, HapticEvent::SliceChange),
+                    Err(e) => warn!(?direction, error = %e, "Wheel axis step failed"),
+                }
+            }
             GestureEvent::Hardware(note) => {
+                if let juhradiald::hidpp::notifications::HardwareNotification::HostChanged { host } = note {
+                    let manager = haptic_manager.clone();
+                    let _ = tokio::task::spawn_blocking(move || {
+                        if let Ok(mut m) = manager.lock() {
+                            m.note_active_host(host);
+                        }
+                    })
+                    .await;
+                }
                 if let Err(e) = emit_hardware_notification(dbus_connection, &battery_state, note).await {
                     tracing::warn!(?note, error = %e, "Failed to emit hardware notification signal");
                 }
@@ -1350,6 +2305,39 @@ async fn process_gesture_events(
     }
 }
 
+/// Disarm on SIGUSR1 and dismiss the menu whenever the panic switch fires
+async fn watch_panic_switch(
+    connection: &zbus::Connection,
+    panic_switch: juhradiald::panic_switch::PanicSwitch,
+    mut sigusr1: tokio::signal::unix::Signal,
+) {
+    use juhradiald::panic_switch::DisarmReason;
+
+    let mut armed = panic_switch.subscribe();
+    loop {
+        tokio::select! {
+            received = sigusr1.recv() => {
+                if received.is_none() {
+                    break;
+                }
+                if !panic_switch.disarm(DisarmReason::Signal) {
+                    info!("SIGUSR1 received; input interception already disarmed");
+                }
+            }
+            changed = armed.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                if !*armed.borrow_and_update() {
+                    if let Err(e) = emit_dismiss_menu(connection).await {
+                        error!("Failed to dismiss the menu after disarming: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Emit the D-Bus change signal for a decoded live hardware notification.
 ///
 /// Broadcast directly on the connection (empty destination) so any subscribed
@@ -1358,21 +2346,19 @@ async fn emit_hardware_notification(
     connection: &zbus::Connection,
     battery_state: &SharedBatteryState,
     note: juhradiald::hidpp::notifications::HardwareNotification,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(), JuhError> {
     use juhradiald::hidpp::notifications::HardwareNotification as HN;
     let iface = "org.kde.juhradialmx.Daemon";
     match note {
-        HN::BatteryChanged { percent, status } => {
+        HN::BatteryChanged { percent, status } if cfg!(feature = "battery") => {
             info!(percent, status, "Battery changed (notification)");
             // Cache so GetBatteryStatus reports the live value even while the
             // active poll is failing (e.g. shared hidraw handle churning).
-            {
-                let mut s = battery_state.write().await;
-                s.percentage = percent;
-                s.charging = matches!(status, "charging" | "full");
-                s.available = true;
-                s.error = None;
-            }
+            battery_state.write().await.apply(juhradiald::battery::BatteryReading {
+                percentage: percent,
+                charging: matches!(status, "charging" | "full"),
+                charge_complete: status == "full",
+            });
             connection
                 .emit_signal(None::<&str>, DBUS_PATH, iface, "BatteryChanged", &(percent, status))
                 .await?;
@@ -1395,6 +2381,7 @@ async fn emit_hardware_notification(
                 .emit_signal(None::<&str>, DBUS_PATH, iface, "DpiChanged", &(dpi,))
                 .await?;
         }
+        HN::BatteryChanged { .. } => tracing::trace!("Battery notification ignored (battery feature disabled)"),
     }
     Ok(())
 }
@@ -1410,7 +2397,7 @@ async fn emit_menu_requested(
     connection: &zbus::Connection,
     x: i32,
     y: i32,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(), JuhError> {
     use zbus::proxy::Proxy;
 
     let proxy = Proxy::new(
@@ -1432,7 +2419,7 @@ async fn emit_menu_requested(
 /// Overlay tracks time internally for tap-to-toggle detection.
 async fn emit_hide_menu(
     connection: &zbus::Connection,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(), JuhError> {
     // Emit signal directly (no parameters)
     connection
         .emit_signal(
@@ -1448,15 +2435,224 @@ async fn emit_hide_menu(
     Ok(())
 }
 
+/// Emit DismissMenu signal via D-Bus
+///
+/// Tells the overlay to close without executing anything (auto-dismiss).
+async fn emit_dismiss_menu(
+    connection: &zbus::Connection,
+) -> Result<(), JuhError> {
+    connection
+        .emit_signal(
+            None::<&str>,
+            DBUS_PATH,
+            "org.kde.juhradialmx.Daemon",
+            "DismissMenu",
+            &(),
+        )
+        .await?;
+
+    info!("DismissMenu signal emitted");
+    Ok(())
+}
+
+/// Emit HighlightStable once the menu highlight has rested on a slice
+async fn run_hover_help(connection: &zbus::Connection, hover_help: HoverHelpHandle) {
+    deadline::run_timer(hover_help, |h, now| h.fire(now), |slot| async move {
+        tracing::debug!(slot, "Highlight stable - showing slice description");
+        if let Err(e) = connection
+            .emit_signal(None::<&str>, DBUS_PATH, "org.kde.juhradialmx.Daemon", "HighlightStable", &(slot,))
+            .await
+        {
+            warn!(error = %e, "Failed to emit HighlightStable signal");
+        }
+    })
+    .await
+}
+
+/// Resolve the highlighted slice's action once its highlight has rested
+///
+/// Preparing only resolves paths (through `binaries`, the executor's cache)
+/// and arguments, so it runs inline.
+async fn run_prewarm(prewarm: PrewarmHandle, binaries: ResolvedBinaries) {
+    deadline::run_timer(prewarm, |p, now| p.fire(now, |a| PreparedAction::prepare(a, &binaries)), |slot| async move {
+        tracing::debug!(slot, "Highlight stable - action pre-warmed");
+    })
+    .await
+}
+
+/// Confirm the highlighted slice once its highlight has rested for
+/// `accessibility.dwell_confirm_ms`
+///
+/// Sends the same `HideMenu` as a release, so the overlay runs the slice
+/// with its usual flash and haptic.
+async fn run_dwell_confirm(connection: &zbus::Connection, handles: &DaemonHandles) {
+    deadline::run_timer(handles.dwell_confirm.clone(), |d, now| d.fire(now), |slot| async move {
+        if handles.calibration.is_capturing() {
+            return;
+        }
+        if handles.slice_cooldown.update(|c| c.is_cooling(slot, Instant::now())).unwrap_or(false) {
+            tracing::debug!(slot, "Highlight dwelled on a slice still cooling - not confirming it");
+            return;
+        }
+        info!(slot, "Highlight dwelled - confirming slice");
+        if let Err(e) = emit_hide_menu(connection).await {
+            error!("Failed to emit HideMenu signal: {}", e);
+        }
+    })
+    .await
+}
+
+/// Open the menu a flick-mode press held back once its flick window passes
+async fn run_flick(connection: &zbus::Connection, flick: FlickHandle) {
+    deadline::run_timer(flick, |f, now| f.fire(now), |(x, y)| async move {
+        tracing::debug!(x, y, "No flick within the window - opening the menu");
+        if let Err(e) = emit_menu_requested(connection, x, y).await {
+            error!("Failed to emit ShowMenu signal: {}", e);
+        }
+    })
+    .await
+}
+
+/// Slot and ring size a flick along `(dx, dy)` runs, or `None` when that
+/// slice does not run on a plain release (`wheel_axis`,
+/// `require_confirmation`) and the menu should open instead
+fn flick_target(
+    thumbwheel: &SharedThumbwheelMapper,
+    config: &SharedConfig,
+    dx: i32,
+    dy: i32,
+) -> Option<(u8, u8)> {
+    let left_handed = config.read().map(|c| c.input.left_handed).unwrap_or(false);
+    let mapper = thumbwheel.read().ok()?;
+    let count = mapper.slice_count();
+    let slot = flick::slot(dx, dy, count, left_handed);
+    let axis = mapper
+        .slice_axes()
+        .is_some_and(|axes| juhradiald::wheel_axis::slots(axes).contains(&slot));
+    let confirm = mapper.confirm_slots().is_some_and(|slots| slots.contains(&slot));
+    (!axis && !confirm).then_some((slot, count))
+}
+
+/// Time left on the cooldown of the flicked `slot`; records the run when
+/// there is none
+fn flick_cooling(
+    thumbwheel: &SharedThumbwheelMapper,
+    cooldowns: &SliceCooldownHandle,
+    slot: u8,
+    at: Instant,
+) -> Option<std::time::Duration> {
+    let mapper = thumbwheel.read().ok()?;
+    let cooldown = mapper
+        .cooldown_slots()
+        .iter()
+        .find(|(s, _)| *s == slot)
+        .map_or(std::time::Duration::ZERO, |(_, cooldown)| *cooldown);
+    cooldowns.update(|c| c.try_run(mapper.menu_profile(), slot, cooldown, at)).flatten()
+}
+
+/// Run `slot` of a flicked profile menu, as a release on it would
+async fn run_flicked_slice(
+    menu: &ProfileMenu,
+    slot: u8,
+    thumbwheel: &SharedThumbwheelMapper,
+    profile_selection: &SharedProfileSelection,
+    haptic_manager: &SharedHapticManager,
+    config: &SharedConfig,
+    handles: &DaemonHandles,
+) {
+    let Some(action) = menu.action(i32::from(slot)) else {
+        tracing::debug!(slot, "Flick on an empty slot - nothing to run");
+        return;
+    };
+    if let ActionType::ProfileSwitch(ref target) = action.action_type {
+        match apply_switch(profile_selection, thumbwheel, target, &handles.persistence) {
+            Ok(_) => juhradiald::hidpp::emit_shared(haptic_manager, HapticEvent::MenuAppear),
+            Err(e) => {
+                warn!(slot, target = %target, error = %e, "Flicked profile switch failed");
+                juhradiald::hidpp::emit_shared(haptic_manager, HapticEvent::InvalidAction);
+            }
+        }
+        return;
+    }
+    let policy = config
+        .read()
+        .map(|c| ExecutionPolicy::from_config(&c.execution, &handles.command_rate))
+        .unwrap_or_else(|_| ExecutionPolicy::unrestricted());
+    let context = thumbwheel
+        .read()
+        .map(|m| InvocationContext::now().in_window(m.active_class(), Some(&menu.profile)))
+        .unwrap_or_else(|_| InvocationContext::now())
+        .with_slice(slot);
+    if let Err(e) = ActionExecutor::execute_with_policy(action, &policy, &context, handles).await {
+        warn!(slot, error = %e, "Flicked slice failed");
+    }
+}
+
+/// Emit FlickSelected: the overlay runs `slot` of a `count`-slice Settings
+/// ring without showing the menu
+async fn emit_flick_selected(connection: &zbus::Connection, slot: u8, count: u8) -> Result<(), JuhError> {
+    connection
+        .emit_signal(
+            None::<&str>,
+            DBUS_PATH,
+            "org.kde.juhradialmx.Daemon",
+            "FlickSelected",
+            &(slot, count),
+        )
+        .await?;
+
+    info!(slot, count, "FlickSelected signal emitted");
+    Ok(())
+}
+
+/// Emit CalibrationEvent with `event` as JSON
+async fn emit_calibration_event(
+    connection: &zbus::Connection,
+    event: &juhradiald::calibration::CalibrationEvent,
+) -> Result<(), JuhError> {
+    let json = serde_json::to_string(event).unwrap_or_default();
+    connection
+        .emit_signal(
+            None::<&str>,
+            DBUS_PATH,
+            "org.kde.juhradialmx.Daemon",
+            "CalibrationEvent",
+            &(json,),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Emit AwaitingConfirmation: the menu stays open for a second press on `slot`
+async fn emit_awaiting_confirmation(
+    connection: &zbus::Connection,
+    slot: u8,
+    window: Duration,
+) -> Result<(), JuhError> {
+    let timeout_ms = u32::try_from(window.as_millis()).unwrap_or(u32::MAX);
+    connection
+        .emit_signal(
+            None::<&str>,
+            DBUS_PATH,
+            "org.kde.juhradialmx.Daemon",
+            "AwaitingConfirmation",
+            &(slot, timeout_ms),
+        )
+        .await?;
+
+    info!(slot, timeout_ms, "AwaitingConfirmation signal emitted");
+    Ok(())
+}
+
 /// Emit CursorMoved signal via D-Bus
 ///
 /// Broadcasts cursor position updates for overlay hover detection.
-/// x, y are relative offsets from the menu center (button press point).
+/// x, y are relative offsets from the rendered menu center.
 async fn emit_cursor_moved(
     connection: &zbus::Connection,
     x: i32,
     y: i32,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<(), JuhError> {
     // Emit signal directly without going through a method
     connection
         .emit_signal(
@@ -1474,7 +2670,7 @@ async fn emit_cursor_moved(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use juhradiald::cursor::{CursorPosition, EDGE_MARGIN, MENU_RADIUS, ScreenBounds};
+    use juhradiald::cursor::{CursorPosition, EDGE_MARGIN, MENU_DIAMETER, MENU_RADIUS, ScreenBounds};
 
     #[test]
     fn test_device_poll_interval() {
@@ -1484,6 +2680,18 @@ mod tests {
         assert_eq!(HIDRAW_RECONNECT_POLL_INTERVAL_SECS, 5);
     }
 
+    #[test]
+    fn test_generic_poll_backoff() {
+        let auto: Vec<u64> = (1..=7)
+            .map(|misses| generic_poll_delay(GenericMouseMode::Auto, misses).as_secs())
+            .collect();
+        assert_eq!(auto, [2, 4, 8, 16, 30, 30, 30]);
+        assert_eq!(generic_poll_delay(GenericMouseMode::Auto, u32::MAX).as_secs(), GENERIC_POLL_MAX_SECS);
+        for misses in [1, 5, 100] {
+            assert_eq!(generic_poll_delay(GenericMouseMode::Require, misses).as_secs(), GENERIC_POLL_MIN_SECS);
+        }
+    }
+
     #[test]
     fn test_args_default_config() {
         // Verify default config path
@@ -1499,12 +2707,26 @@ mod tests {
         assert!(args.verbose);
     }
 
+    #[test]
+    fn test_args_log_output() {
+        let args = Args::parse_from(["juhradiald", "--verbosity", "juhradiald=trace", "--log-file", "/tmp/jr.log"]);
+        assert_eq!(args.verbosity.as_deref(), Some("juhradiald=trace"));
+        assert_eq!(args.log_file, Some(PathBuf::from("/tmp/jr.log")));
+        assert!(Args::parse_from(["juhradiald"]).log_file.is_none());
+    }
+
     #[test]
     fn test_args_list_devices() {
         let args = Args::parse_from(["juhradiald", "--list-devices"]);
         assert!(args.list_devices);
     }
 
+    #[test]
+    fn test_args_replace() {
+        assert!(!Args::parse_from(["juhradiald"]).replace);
+        assert!(Args::parse_from(["juhradiald", "--replace"]).replace);
+    }
+
     #[tokio::test]
     async fn test_gesture_event_channel() {
         let (tx, mut rx) = mpsc::channel::<GestureEvent>(8);
@@ -1519,12 +2741,12 @@ mod tests {
         assert!(matches!(event, GestureEvent::Pressed { x: 100, y: 200 }));
 
         // Send release event
-        tx.send(GestureEvent::Released { duration_ms: 500 })
+        tx.send(GestureEvent::Released { duration_ms: 500, released_at: Instant::now() })
             .await
             .unwrap();
 
         let event = rx.recv().await.unwrap();
-        assert!(matches!(event, GestureEvent::Released { duration_ms: 500 }));
+        assert!(matches!(event, GestureEvent::Released { duration_ms: 500, .. }));
     }
 
     #[tokio::test]
@@ -1542,6 +2764,7 @@ mod tests {
             .unwrap();
             tx.send(GestureEvent::Released {
                 duration_ms: 50 + (i as u64 * 10),
+                released_at: Instant::now(),
             })
             .await
             .unwrap();
@@ -1554,7 +2777,7 @@ mod tests {
 
             let release = rx.recv().await.unwrap();
             assert!(
-                matches!(release, GestureEvent::Released { duration_ms } if duration_ms == 50 + (i as u64 * 10))
+                matches!(release, GestureEvent::Released { duration_ms, .. } if duration_ms == 50 + (i as u64 * 10))
             );
         }
 
@@ -1572,17 +2795,17 @@ mod tests {
 
         // Test near left edge
         let pos = CursorPosition::new(50, 540);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
         assert_eq!(clamped.x, EDGE_MARGIN + MENU_RADIUS); // 170
 
         // Test near top edge
         let pos = CursorPosition::new(960, 30);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
         assert_eq!(clamped.y, EDGE_MARGIN + MENU_RADIUS); // 170
 
         // Test bottom-right corner
         let pos = CursorPosition::new(1900, 1060);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
         assert_eq!(clamped.x, 1920 - EDGE_MARGIN - MENU_RADIUS); // 1750
         assert_eq!(clamped.y, 1080 - EDGE_MARGIN - MENU_RADIUS); // 910
     }
@@ -1595,7 +2818,7 @@ mod tests {
             height: 1080,
         };
         let pos = CursorPosition::new(500, 500);
-        let clamped = pos.clamp_to_screen(&bounds);
+        let clamped = pos.clamp_to_screen(&bounds, MENU_DIAMETER as u32);
         assert_eq!(clamped.x, 500);
         assert_eq!(clamped.y, 500);
     }
@@ -0,0 +1,380 @@
+//! Built-in `media` and `volume` actions
+//!
+//! Neither spawns a program. `media` calls the MPRIS player interface of the
+//! most recently active player on the session bus; `volume` changes the
+//! default sink through PulseAudio's D-Bus protocol (`module-dbus-protocol`)
+//! and, where that is not loaded (PipeWire), through Plasma's volume
+//! shortcuts over kglobalaccel, which step by Plasma's own amount.
+//!
+//! Executing an action only queues it for the worker task (`run`) through the
+//! [`MediaQueue`] on `DaemonHandles`, so the dispatch stays well under the
+//! 10ms action budget; the bus calls finish in the background and failures
+//! are logged.
+//!
+//! The daemon does not follow players between actions: `PlayerTracker` sees
+//! each player's `PlaybackStatus` when an action runs and ranks them from
+//! those observations.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::actions::ActionError;
+
+pub use juhradial_core::action::{MediaControl, VolumeControl};
+
+/// Bus name prefix of MPRIS players
+pub const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const MPRIS_PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+const PULSE_LOOKUP_NAME: &str = "org.PulseAudio1";
+const PULSE_LOOKUP_PATH: &str = "/org/pulseaudio/server_lookup1";
+const PULSE_LOOKUP_INTERFACE: &str = "org.PulseAudio.ServerLookup1";
+const PULSE_CORE_NAME: &str = "org.PulseAudio.Core1";
+const PULSE_CORE_PATH: &str = "/org/pulseaudio/core1";
+const PULSE_DEVICE_INTERFACE: &str = "org.PulseAudio.Core1.Device";
+
+/// PulseAudio's 100%
+pub const PA_VOLUME_NORM: u32 = 0x10000;
+
+/// How long one queued action may take on the bus
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A queued `media` or `volume` action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaRequest {
+    Media(MediaControl),
+    Volume(VolumeControl),
+}
+
+/// Why a media or volume action failed
+#[derive(Debug, thiserror::Error)]
+pub enum MediaError {
+    #[error("media control is not running")]
+    Unavailable,
+    #[error("no MPRIS media player on the session bus")]
+    NoPlayer,
+    #[error("no volume backend: PulseAudio D-Bus protocol not loaded and Plasma volume shortcuts unavailable")]
+    NoVolumeBackend,
+    #[error("timed out")]
+    Timeout,
+    #[error("D-Bus: {0}")]
+    Bus(#[from] zbus::Error),
+}
+
+impl From<MediaError> for ActionError {
+    fn from(e: MediaError) -> Self {
+        match e {
+            MediaError::Timeout => ActionError::Timeout,
+            other => ActionError::ExecutionFailed(other.to_string()),
+        }
+    }
+}
+
+// ============================================================================
+// Player selection
+// ============================================================================
+
+/// MPRIS `PlaybackStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl PlaybackStatus {
+    pub fn parse(status: &str) -> Self {
+        match status {
+            "Playing" => Self::Playing,
+            "Paused" => Self::Paused,
+            _ => Self::Stopped,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SeenPlayer {
+    status: PlaybackStatus,
+    /// Last observed start or stop of playback
+    active: Option<Instant>,
+}
+
+/// Players seen on the bus, ranked by how recently they were active
+#[derive(Debug, Default)]
+pub struct PlayerTracker {
+    players: HashMap<String, SeenPlayer>,
+}
+
+impl PlayerTracker {
+    /// Record the players on the bus at `now`; players that left are forgotten
+    pub fn observe(&mut self, players: &[(String, PlaybackStatus)], now: Instant) {
+        self.players.retain(|name, _| players.iter().any(|(present, _)| present == name));
+        for (name, status) in players {
+            let seen = self.players.entry(name.clone()).or_insert(SeenPlayer {
+                status: *status,
+                active: (*status == PlaybackStatus::Playing).then_some(now),
+            });
+            let started_or_stopped = (seen.status == PlaybackStatus::Playing) != (*status == PlaybackStatus::Playing);
+            if started_or_stopped {
+                seen.active = Some(now);
+            }
+            seen.status = *status;
+        }
+    }
+
+    /// The player to control
+    ///
+    /// A playing player wins, the latest to start first; then the one that
+    /// stopped playing last; then paused before stopped, by bus name.
+    pub fn select(&self) -> Option<&str> {
+        let rank = |p: &SeenPlayer| (p.status == PlaybackStatus::Playing, p.active, p.status == PlaybackStatus::Paused);
+        self.players
+            .iter()
+            .max_by(|(a_name, a), (b_name, b)| rank(a).cmp(&rank(b)).then_with(|| b_name.cmp(a_name)))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+/// Channel volumes after a step of `percent` (negative lowers)
+///
+/// Raising stops at 100%, or at the current volume if it is already above.
+pub fn stepped_volume(channels: &[u32], percent: i32) -> Vec<u32> {
+    let step = i64::from(PA_VOLUME_NORM) * i64::from(percent) / 100;
+    channels
+        .iter()
+        .map(|&volume| {
+            let ceiling = i64::from(volume.max(PA_VOLUME_NORM));
+            (i64::from(volume) + step).clamp(0, ceiling) as u32
+        })
+        .collect()
+}
+
+// ============================================================================
+// Worker
+// ============================================================================
+
+/// The worker's queue, shared through `DaemonHandles`
+///
+/// Empty until [`run`] starts; clones share the queue.
+#[derive(Debug, Clone, Default)]
+pub struct MediaQueue {
+    requests: Arc<OnceLock<mpsc::UnboundedSender<MediaRequest>>>,
+}
+
+impl MediaQueue {
+    /// Queue an action for the worker; returns at once
+    pub fn dispatch(&self, request: MediaRequest) -> Result<(), MediaError> {
+        let requests = self.requests.get().ok_or(MediaError::Unavailable)?;
+        requests.send(request).map_err(|_| MediaError::Unavailable)
+    }
+}
+
+/// Run the actions queued on `queue` on `connection` (the session bus)
+pub async fn run(connection: zbus::Connection, queue: MediaQueue) {
+    let (sender, mut requests) = mpsc::unbounded_channel();
+    if queue.requests.set(sender).is_err() {
+        tracing::warn!("Media control worker already running");
+        return;
+    }
+    let mut worker = Worker { session: connection, players: PlayerTracker::default(), pulse: None };
+    while let Some(request) = requests.recv().await {
+        let result = match tokio::time::timeout(REQUEST_TIMEOUT, worker.handle(request)).await {
+            Ok(result) => result,
+            Err(_) => Err(MediaError::Timeout),
+        };
+        if let Err(e) = result {
+            // A PulseAudio restart leaves the peer connection dead
+            worker.pulse = None;
+            tracing::warn!(request = ?request, error = %ActionError::from(e), "Media action failed");
+        }
+    }
+}
+
+struct Worker {
+    session: zbus::Connection,
+    players: PlayerTracker,
+    /// Peer connection to the PulseAudio D-Bus server, once found
+    pulse: Option<zbus::Connection>,
+}
+
+impl Worker {
+    async fn handle(&mut self, request: MediaRequest) -> Result<(), MediaError> {
+        match request {
+            MediaRequest::Media(control) => self.media(control).await,
+            MediaRequest::Volume(control) => self.volume(control).await,
+        }
+    }
+
+    async fn media(&mut self, control: MediaControl) -> Result<(), MediaError> {
+        let players = list_players(&self.session).await?;
+        self.players.observe(&players, Instant::now());
+        let player = self.players.select().ok_or(MediaError::NoPlayer)?.to_string();
+        tracing::debug!(player = %player, method = control.mpris_method(), "MPRIS call");
+        let proxy = uncached_proxy(&self.session, &player, MPRIS_PATH, MPRIS_PLAYER_INTERFACE).await?;
+        proxy.call_method(control.mpris_method(), &()).await?;
+        Ok(())
+    }
+
+    async fn volume(&mut self, control: VolumeControl) -> Result<(), MediaError> {
+        match self.pulse_volume(control).await {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.pulse = None;
+                tracing::debug!(error = %e, "PulseAudio D-Bus unavailable - using Plasma volume shortcuts");
+                plasma_volume(&self.session, control).await
+            }
+        }
+    }
+
+    async fn pulse_volume(&mut self, control: VolumeControl) -> Result<(), MediaError> {
+        let pulse = match self.pulse {
+            Some(ref pulse) => pulse.clone(),
+            None => {
+                let lookup = uncached_proxy(&self.session, PULSE_LOOKUP_NAME, PULSE_LOOKUP_PATH, PULSE_LOOKUP_INTERFACE).await?;
+                let address: String = lookup.get_property("Address").await?;
+                let pulse = zbus::connection::Builder::address(address.as_str())?.p2p().build().await?;
+                self.pulse.insert(pulse).clone()
+            }
+        };
+        let core = uncached_proxy(&pulse, PULSE_CORE_NAME, PULSE_CORE_PATH, "org.PulseAudio.Core1").await?;
+        let sink: OwnedObjectPath = core.get_property("FallbackSink").await?;
+        let device = uncached_proxy(&pulse, PULSE_CORE_NAME, sink.as_str(), PULSE_DEVICE_INTERFACE).await?;
+        let percent = match control {
+            VolumeControl::Up(step) => i32::from(step),
+            VolumeControl::Down(step) => -i32::from(step),
+            VolumeControl::Mute => {
+                let muted: bool = device.get_property("Mute").await?;
+                device.set_property("Mute", !muted).await.map_err(zbus::Error::from)?;
+                return Ok(());
+            }
+        };
+        let volume: Vec<u32> = device.get_property("Volume").await?;
+        device
+            .set_property("Volume", stepped_volume(&volume, percent))
+            .await
+            .map_err(zbus::Error::from)?;
+        Ok(())
+    }
+}
+
+/// MPRIS players on the bus with their playback status
+async fn list_players(session: &zbus::Connection) -> Result<Vec<(String, PlaybackStatus)>, MediaError> {
+    let bus = zbus::fdo::DBusProxy::new(session).await?;
+    let names = bus.list_names().await.map_err(zbus::Error::from)?;
+    let mut players = Vec::new();
+    for name in names.iter().map(|n| n.as_str()).filter(|n| n.starts_with(MPRIS_PREFIX)) {
+        let proxy = uncached_proxy(session, name, MPRIS_PATH, MPRIS_PLAYER_INTERFACE).await?;
+        match proxy.get_property::<String>("PlaybackStatus").await {
+            Ok(status) => players.push((name.to_string(), PlaybackStatus::parse(&status))),
+            Err(e) => tracing::debug!(player = name, error = %e, "Skipping MPRIS player without a status"),
+        }
+    }
+    Ok(players)
+}
+
+/// Plasma's volume shortcuts (plasma-pa registers them as the `kmix` component)
+async fn plasma_volume(session: &zbus::Connection, control: VolumeControl) -> Result<(), MediaError> {
+    let shortcut = match control {
+        VolumeControl::Up(_) => "increase_volume",
+        VolumeControl::Down(_) => "decrease_volume",
+        VolumeControl::Mute => "mute",
+    };
+    let component = uncached_proxy(session, "org.kde.kglobalaccel", "/component/kmix", "org.kde.kglobalaccel.Component").await?;
+    component.call_method("invokeShortcut", &(shortcut,)).await.map_err(|e| {
+        tracing::debug!(error = %e, shortcut, "Plasma volume shortcut failed");
+        MediaError::NoVolumeBackend
+    })?;
+    Ok(())
+}
+
+async fn uncached_proxy<'p>(
+    connection: &zbus::Connection,
+    destination: &'p str,
+    path: &'p str,
+    interface: &'p str,
+) -> zbus::Result<zbus::Proxy<'p>> {
+    zbus::proxy::Builder::new(connection)
+        .destination(destination)?
+        .path(path)?
+        .interface(interface)?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seen(players: &[(&str, PlaybackStatus)]) -> Vec<(String, PlaybackStatus)> {
+        players.iter().map(|(name, status)| (format!("{MPRIS_PREFIX}{name}"), *status)).collect()
+    }
+
+    #[test]
+    fn test_latest_player_to_start_wins() {
+        use PlaybackStatus::*;
+        let t0 = Instant::now();
+        let mut tracker = PlayerTracker::default();
+        tracker.observe(&seen(&[("spotify", Playing), ("firefox", Paused)]), t0);
+        assert_eq!(tracker.select(), Some("org.mpris.MediaPlayer2.spotify"));
+
+        // Both playing: firefox started later
+        tracker.observe(&seen(&[("spotify", Playing), ("firefox", Playing)]), t0 + Duration::from_secs(5));
+        assert_eq!(tracker.select(), Some("org.mpris.MediaPlayer2.firefox"));
+        // Still playing later does not count as starting again
+        tracker.observe(&seen(&[("spotify", Playing), ("firefox", Playing)]), t0 + Duration::from_secs(9));
+        assert_eq!(tracker.select(), Some("org.mpris.MediaPlayer2.firefox"));
+
+        // A player that leaves the bus is forgotten
+        tracker.observe(&seen(&[("spotify", Playing)]), t0 + Duration::from_secs(10));
+        assert_eq!(tracker.select(), Some("org.mpris.MediaPlayer2.spotify"));
+        tracker.observe(&[], t0 + Duration::from_secs(11));
+        assert_eq!(tracker.select(), None);
+    }
+
+    #[test]
+    fn test_none_playing_picks_the_last_active() {
+        use PlaybackStatus::*;
+        let t0 = Instant::now();
+        let mut tracker = PlayerTracker::default();
+        tracker.observe(&seen(&[("mpv", Playing), ("vlc", Playing)]), t0);
+        tracker.observe(&seen(&[("mpv", Paused), ("vlc", Playing)]), t0 + Duration::from_secs(1));
+        tracker.observe(&seen(&[("mpv", Paused), ("vlc", Stopped)]), t0 + Duration::from_secs(2));
+        // vlc stopped after mpv paused
+        assert_eq!(tracker.select(), Some("org.mpris.MediaPlayer2.vlc"));
+
+        // Never seen playing: paused before stopped, then by name
+        let mut tracker = PlayerTracker::default();
+        tracker.observe(&seen(&[("b", Stopped), ("c", Paused), ("a", Stopped)]), t0);
+        assert_eq!(tracker.select(), Some("org.mpris.MediaPlayer2.c"));
+        tracker.observe(&seen(&[("b", Stopped), ("a", Stopped)]), t0);
+        assert_eq!(tracker.select(), Some("org.mpris.MediaPlayer2.a"));
+        assert_eq!(PlaybackStatus::parse("Buffering"), Stopped);
+    }
+
+    #[test]
+    fn test_volume_steps_stop_at_full() {
+        let half = PA_VOLUME_NORM / 2;
+        assert_eq!(stepped_volume(&[half, half], 10), [half + 6553, half + 6553]);
+        assert_eq!(stepped_volume(&[PA_VOLUME_NORM - 100], 5), [PA_VOLUME_NORM]);
+        assert_eq!(stepped_volume(&[1000], -5), [0]);
+        // Boosted above 100% by another mixer: raising keeps it, lowering steps down
+        assert_eq!(stepped_volume(&[PA_VOLUME_NORM + 500], 5), [PA_VOLUME_NORM + 500]);
+        assert_eq!(stepped_volume(&[PA_VOLUME_NORM + 500], -5), [PA_VOLUME_NORM + 500 - 3276]);
+    }
+
+    #[test]
+    fn test_dispatch_without_the_worker_maps_to_an_action_error() {
+        let result = MediaQueue::default().dispatch(MediaRequest::Media(MediaControl::Next));
+        assert!(matches!(result, Err(MediaError::Unavailable)));
+        let e = ActionError::from(result.unwrap_err());
+        assert_eq!(e.to_string(), "Execution failed: media control is not running");
+        assert!(matches!(ActionError::from(MediaError::Timeout), ActionError::Timeout));
+    }
+}
@@ -0,0 +1,123 @@
+//! Where the menu was actually drawn
+//!
+//! Near a screen edge the overlay clamps the ring inward, so its centre is no
+//! longer the press point. The gesture loop tracks pointer motion as offsets
+//! from the press; hit-testing those against the ring would skew every slice
+//! towards the edge. The overlay reports the press and the rendered centre
+//! (`ReportMenuPlacement`, compositor-logical pixels) and `CursorMoved` is
+//! rebased onto the centre before it goes out.
+//!
+//! Until the report arrives (or when the overlay never sends one) the press
+//! point is taken as the centre, which is exact for an unclamped menu. The
+//! open menu's anchor lives in a [`MenuAnchorHandle`] on `DaemonHandles`.
+
+use std::sync::{Arc, Mutex};
+
+/// Offset of the press point from the rendered menu centre
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MenuAnchor {
+    dx: i32,
+    dy: i32,
+}
+
+impl MenuAnchor {
+    /// Anchor for a menu pressed at `press` and drawn centred on `center`
+    pub fn placed(press: (i32, i32), center: (i32, i32)) -> Self {
+        Self {
+            dx: press.0 - center.0,
+            dy: press.1 - center.1,
+        }
+    }
+
+    /// Anchor for a press that continues an open menu whose pointer sat at
+    /// `pointer` (rebased) when the previous press was released
+    pub fn resumed(pointer: (i32, i32)) -> Self {
+        Self {
+            dx: pointer.0,
+            dy: pointer.1,
+        }
+    }
+
+    /// Whether the menu was drawn away from the press point
+    pub fn is_clamped(&self) -> bool {
+        self.dx != 0 || self.dy != 0
+    }
+
+    /// Rebase a press-relative offset onto the rendered centre
+    pub fn from_center(&self, x: i32, y: i32) -> (i32, i32) {
+        (x.saturating_add(self.dx), y.saturating_add(self.dy))
+    }
+}
+
+/// The open menu's anchor, shared by the D-Bus service and the gesture loop
+///
+/// Clones share the anchor.
+#[derive(Debug, Clone, Default)]
+pub struct MenuAnchorHandle {
+    current: Arc<Mutex<MenuAnchor>>,
+}
+
+impl MenuAnchorHandle {
+    /// A menu opened; its centre is the press point until the overlay says otherwise
+    pub fn reset(&self) {
+        self.set(MenuAnchor::default());
+    }
+
+    /// Record the overlay's placement for the open menu
+    pub fn set(&self, anchor: MenuAnchor) {
+        if let Ok(mut current) = self.current.lock() {
+            *current = anchor;
+        }
+    }
+
+    /// The open menu's anchor
+    pub fn current(&self) -> MenuAnchor {
+        self.current.lock().map(|a| *a).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::slice_at;
+
+    #[test]
+    fn test_corner_press_highlights_from_rendered_center() {
+        // Pressed in the top-left corner; the ring was clamped to (160, 160).
+        let anchor = MenuAnchor::placed((0, 0), (160, 160));
+        assert!(anchor.is_clamped());
+
+        // The hand moves to just north-east of the visible centre
+        let (x, y) = anchor.from_center(230, 90);
+        assert_eq!((x, y), (70, -70));
+        assert_eq!(slice_at(x.into(), y.into()), Some(1));
+        // Measured from the press point it would miss the ring entirely
+        assert_eq!(slice_at(230.0, 90.0), None);
+
+        // Straight up from the centre is north, wherever the press was
+        let (x, y) = anchor.from_center(160, 60);
+        assert_eq!(slice_at(x.into(), y.into()), Some(0));
+    }
+
+    #[test]
+    fn test_unclamped_menu_keeps_press_offsets() {
+        let anchor = MenuAnchor::placed((960, 540), (960, 540));
+        assert!(!anchor.is_clamped());
+        assert_eq!(anchor.from_center(70, -70), (70, -70));
+        assert_eq!(MenuAnchor::default(), anchor);
+
+        // Bottom-right corner: clamped up and left
+        let anchor = MenuAnchor::placed((1919, 1079), (1760, 920));
+        let (x, y) = anchor.from_center(-159, -229);
+        assert_eq!(slice_at(x.into(), y.into()), Some(0));
+    }
+
+    #[test]
+    fn test_resumed_press_continues_from_the_last_pointer() {
+        // Released over the south-west slice, then pressed again
+        let anchor = MenuAnchor::resumed((-60, 70));
+        assert_eq!(anchor.from_center(0, 0), (-60, 70));
+        assert_eq!(slice_at(-60.0, 70.0), Some(5));
+        assert_eq!(anchor.from_center(5, -3), (-55, 67));
+    }
+}
@@ -0,0 +1,228 @@
+//! Auto-dismiss for an open radial menu
+//!
+//! With `menu.auto_dismiss_ms` set, a menu left open (typically tap-to-toggle
+//! followed by a distraction) cancels itself after that long without activity,
+//! so it cannot swallow the next unrelated click. Dismissal executes nothing
+//! and plays no haptic. Slice highlight changes, cursor motion and wheel or
+//! keyboard input reset the countdown.
+//!
+//! `MenuDismissal` is the pure state machine (times are passed in). The D-Bus
+//! service feeds it open/close/activity through a
+//! [`DeadlineHandle`](crate::deadline::DeadlineHandle), and the gesture loop
+//! owns the tokio timer: it sleeps until `wake_at()` and re-reads it whenever
+//! the handle signals a change, which cancels the old sleep.
+//!
+//! A gesture release races the timer by timestamp: a release stamped before the
+//! deadline confirms even if it is processed after the deadline passed. The
+//! timer only commits the dismissal `RELEASE_GRACE` after the deadline so such
+//! in-flight releases are seen first.
+
+use std::time::{Duration, Instant};
+
+use crate::deadline::{Deadline, DeadlineHandle};
+
+/// Time after the deadline during which an earlier-stamped release still wins
+pub const RELEASE_GRACE: Duration = Duration::from_millis(30);
+
+/// Shortest accepted auto-dismiss timeout
+pub const MIN_AUTO_DISMISS: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed,
+    /// Menu shown; `deadline` is `None` when auto-dismiss is off
+    Open { deadline: Option<Instant> },
+    /// The timer cancelled the menu at `at` (its deadline)
+    Dismissed { at: Instant },
+}
+
+/// What to do with a gesture release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseOutcome {
+    /// Released before the deadline: hide the menu and run the selection
+    Confirm,
+    /// Released after the deadline but before the timer committed: dismiss now
+    DismissNow,
+    /// The menu was already dismissed; ignore the release
+    AlreadyDismissed,
+}
+
+/// Auto-dismiss state machine for one menu at a time
+#[derive(Debug)]
+pub struct MenuDismissal {
+    timeout: Option<Duration>,
+    state: State,
+}
+
+impl MenuDismissal {
+    /// `timeout` of `None` disables auto-dismiss
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout: timeout.map(|t| t.max(MIN_AUTO_DISMISS)),
+            state: State::Closed,
+        }
+    }
+
+    /// Apply a (re)loaded timeout; an open menu restarts its countdown
+    pub fn set_timeout(&mut self, timeout: Option<Duration>, now: Instant) {
+        self.timeout = timeout.map(|t| t.max(MIN_AUTO_DISMISS));
+        if let State::Open { .. } = self.state {
+            self.state = State::Open { deadline: self.timeout.map(|t| now + t) };
+        }
+    }
+
+    /// Menu shown
+    pub fn opened(&mut self, now: Instant) {
+        self.state = State::Open { deadline: self.timeout.map(|t| now + t) };
+    }
+
+    /// Highlight change or navigation input; returns whether the countdown reset
+    pub fn activity(&mut self, now: Instant) -> bool {
+        match (self.state, self.timeout) {
+            (State::Open { deadline: Some(_) }, Some(timeout)) => {
+                self.state = State::Open { deadline: Some(now + timeout) };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Menu closed by the overlay (selection, cancel, or after a dismissal)
+    pub fn closed(&mut self) {
+        self.state = State::Closed;
+    }
+
+    /// Timer woke: returns true when the menu should be dismissed now
+    ///
+    /// A timer that was outrun by a reset or close finds a later (or no)
+    /// deadline and does nothing.
+    pub fn fire(&mut self, now: Instant) -> bool {
+        match self.state {
+            State::Open { deadline: Some(deadline) } if now >= deadline + RELEASE_GRACE => {
+                self.state = State::Dismissed { at: deadline };
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Gesture button released at `at`
+    pub fn released(&mut self, at: Instant) -> ReleaseOutcome {
+        match self.state {
+            State::Open { deadline: Some(deadline) } if at >= deadline => {
+                self.state = State::Dismissed { at: deadline };
+                ReleaseOutcome::DismissNow
+            }
+            State::Dismissed { .. } => ReleaseOutcome::AlreadyDismissed,
+            // Hold-release closes the menu; a tap leaves it open (toggle
+            // mode), so keep counting until the overlay reports the close.
+            _ => ReleaseOutcome::Confirm,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(self.state, State::Open { .. })
+    }
+}
+
+impl Default for MenuDismissal {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Deadline for MenuDismissal {
+    /// When the gesture loop's timer should next wake
+    fn wake_at(&self) -> Option<Instant> {
+        match self.state {
+            State::Open { deadline } => deadline.map(|d| d + RELEASE_GRACE),
+            _ => None,
+        }
+    }
+}
+
+/// Shared handle between the D-Bus service and the gesture loop's timer
+pub type MenuDismissalHandle = DeadlineHandle<MenuDismissal>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(5000);
+
+    fn open_menu() -> (MenuDismissal, Instant) {
+        let t0 = Instant::now();
+        let mut menu = MenuDismissal::new(Some(TIMEOUT));
+        menu.opened(t0);
+        (menu, t0)
+    }
+
+    #[test]
+    fn test_disabled_never_dismisses() {
+        let t0 = Instant::now();
+        let mut menu = MenuDismissal::new(None);
+        menu.opened(t0);
+        assert_eq!(menu.wake_at(), None);
+        assert!(!menu.fire(t0 + Duration::from_secs(3600)));
+        assert_eq!(menu.released(t0 + Duration::from_secs(3600)), ReleaseOutcome::Confirm);
+    }
+
+    #[test]
+    fn test_timeout_fires_after_grace() {
+        let (mut menu, t0) = open_menu();
+        assert_eq!(menu.wake_at(), Some(t0 + TIMEOUT + RELEASE_GRACE));
+        assert!(!menu.fire(t0 + TIMEOUT));
+        assert!(menu.fire(t0 + TIMEOUT + RELEASE_GRACE));
+        assert!(!menu.is_open());
+        // Only once
+        assert!(!menu.fire(t0 + TIMEOUT * 2));
+        assert_eq!(menu.released(t0 + TIMEOUT * 2), ReleaseOutcome::AlreadyDismissed);
+    }
+
+    #[test]
+    fn test_activity_resets_countdown() {
+        let (mut menu, t0) = open_menu();
+        let t1 = t0 + Duration::from_millis(4000);
+        assert!(menu.activity(t1));
+        // The timer armed for the old deadline wakes and finds a later one
+        assert!(!menu.fire(t0 + TIMEOUT + RELEASE_GRACE));
+        assert_eq!(menu.wake_at(), Some(t1 + TIMEOUT + RELEASE_GRACE));
+        assert!(menu.fire(t1 + TIMEOUT + RELEASE_GRACE));
+    }
+
+    #[test]
+    fn test_close_cancels_timer() {
+        let (mut menu, t0) = open_menu();
+        menu.closed();
+        assert_eq!(menu.wake_at(), None);
+        assert!(!menu.fire(t0 + TIMEOUT * 2));
+        assert!(!menu.activity(t0));
+    }
+
+    #[test]
+    fn test_release_before_deadline_wins_even_if_processed_late() {
+        let (mut menu, t0) = open_menu();
+        // Released 1ms before the deadline, seen inside the grace window
+        let released_at = t0 + TIMEOUT - Duration::from_millis(1);
+        assert_eq!(menu.released(released_at), ReleaseOutcome::Confirm);
+    }
+
+    #[test]
+    fn test_release_after_deadline_loses_to_timer() {
+        let (mut menu, t0) = open_menu();
+        // Released at the deadline, before the timer committed
+        assert_eq!(menu.released(t0 + TIMEOUT), ReleaseOutcome::DismissNow);
+        // The timer then finds nothing left to do
+        assert!(!menu.fire(t0 + TIMEOUT + RELEASE_GRACE));
+    }
+
+    #[test]
+    fn test_reload_applies_floor_and_restarts() {
+        let t0 = Instant::now();
+        let mut menu = MenuDismissal::new(None);
+        menu.opened(t0);
+        let t1 = t0 + Duration::from_millis(200);
+        menu.set_timeout(Some(Duration::from_millis(10)), t1);
+        assert_eq!(menu.wake_at(), Some(t1 + MIN_AUTO_DISMISS + RELEASE_GRACE));
+    }
+}
@@ -0,0 +1,433 @@
+//! Recent menu sessions, for reconstructing "I released on Copy but it pasted"
+//!
+//! Each menu open starts a record: the press point and time, every highlight
+//! change with the pointer's angle and distance from the ring centre at that
+//! moment, wheel and keyboard navigation, the release with its position and
+//! what the daemon did with it, the slot the overlay reports as picked, and
+//! whether that slot's action started. The last [`SESSION_CAPACITY`] records
+//! stay in memory; `GetLastSessions(n)` returns them as JSON, and a failed
+//! action logs its session at debug level.
+//!
+//! Recording runs on every pointer move and highlight, so it never
+//! allocates: the records and their event arrays are allocated once, a new
+//! session reuses the oldest record, and events past [`EVENT_CAPACITY`] are
+//! counted rather than stored.
+
+use std::time::Instant;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::geometry::angle_of;
+use crate::handles::StateHandle;
+
+/// Sessions kept
+pub const SESSION_CAPACITY: usize = 20;
+
+/// Highlight and navigation events kept per session
+pub const EVENT_CAPACITY: usize = 64;
+
+/// Bytes of an action error kept; longer messages are cut at a character
+const ERROR_CAPACITY: usize = 120;
+
+/// Pointer offset from the ring centre, with the angle and distance the
+/// hit test works from
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct PointerSample {
+    pub dx: i32,
+    pub dy: i32,
+    /// Clockwise from straight up, degrees
+    pub angle: f32,
+    /// Pixels from the centre
+    pub distance: f32,
+}
+
+impl PointerSample {
+    pub fn new(dx: i32, dy: i32) -> Self {
+        let (x, y) = (f64::from(dx), f64::from(dy));
+        Self {
+            dx,
+            dy,
+            angle: angle_of(x, y) as f32,
+            distance: x.hypot(y) as f32,
+        }
+    }
+}
+
+/// One step of a session's timeline
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// The overlay highlighted `slot`; `None` when the pointer left the slices
+    Highlight { at_ms: u32, slot: Option<u8>, pointer: PointerSample },
+    /// Wheel or keyboard navigation
+    Navigation { at_ms: u32, input: &'static str },
+}
+
+/// The gesture button release
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Release {
+    pub at_ms: u32,
+    pub pointer: PointerSample,
+    /// What the daemon did: `select`, `dismiss`, `cancel`, `cooling`,
+    /// `await_confirmation`, `after_dismiss` or `after_dwell`
+    pub outcome: &'static str,
+}
+
+/// How the overlay reported the menu closed (`ReportMenuClosed`)
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Selection {
+    pub at_ms: u32,
+    /// `None` when cancelled
+    pub slot: Option<u8>,
+    pub input: &'static str,
+}
+
+/// Whether the picked action started
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct ActionResult {
+    pub at_ms: u32,
+    pub slot: Option<u8>,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "ErrorText::is_empty")]
+    pub error: ErrorText,
+}
+
+/// An error message stored inline, cut to [`ERROR_CAPACITY`] bytes
+#[derive(Clone, Copy, PartialEq)]
+pub struct ErrorText {
+    len: u8,
+    bytes: [u8; ERROR_CAPACITY],
+}
+
+impl ErrorText {
+    pub fn new(text: &str) -> Self {
+        let mut end = text.len().min(ERROR_CAPACITY);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut bytes = [0; ERROR_CAPACITY];
+        bytes[..end].copy_from_slice(&text.as_bytes()[..end]);
+        Self { len: end as u8, bytes }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Cut at a character boundary in `new`
+        std::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl std::fmt::Debug for ErrorText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl Serialize for ErrorText {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// One menu, from press to action
+#[derive(Debug, Clone, Copy)]
+pub struct MenuSession {
+    /// Increasing per open, so a reader can tell sessions apart
+    pub id: u64,
+    /// Unix time of the press in milliseconds
+    pub opened_at_ms: u64,
+    opened: Option<Instant>,
+    /// Press point, compositor-logical pixels
+    pub press: (i32, i32),
+    /// `ShowAdHocMenu` rather than the gesture button
+    pub adhoc: bool,
+    events: [SessionEvent; EVENT_CAPACITY],
+    len: usize,
+    /// Events past [`EVENT_CAPACITY`], not stored
+    pub dropped_events: u32,
+    pub release: Option<Release>,
+    pub selection: Option<Selection>,
+    pub result: Option<ActionResult>,
+}
+
+impl MenuSession {
+    const EMPTY: Self = Self {
+        id: 0,
+        opened_at_ms: 0,
+        opened: None,
+        press: (0, 0),
+        adhoc: false,
+        events: [SessionEvent::Navigation { at_ms: 0, input: "" }; EVENT_CAPACITY],
+        len: 0,
+        dropped_events: 0,
+        release: None,
+        selection: None,
+        result: None,
+    };
+
+    /// The highlight and navigation timeline, oldest first
+    pub fn events(&self) -> &[SessionEvent] {
+        &self.events[..self.len]
+    }
+
+    /// Milliseconds from the press to `now`
+    fn at_ms(&self, now: Instant) -> u32 {
+        self.opened
+            .map_or(0, |opened| now.saturating_duration_since(opened).as_millis().min(u128::from(u32::MAX)) as u32)
+    }
+
+    fn push(&mut self, event: SessionEvent) {
+        if self.len < EVENT_CAPACITY {
+            self.events[self.len] = event;
+            self.len += 1;
+        } else {
+            self.dropped_events = self.dropped_events.saturating_add(1);
+        }
+    }
+}
+
+impl Serialize for MenuSession {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("MenuSession", 9)?;
+        s.serialize_field("id", &self.id)?;
+        s.serialize_field("opened_at_ms", &self.opened_at_ms)?;
+        s.serialize_field("press", &self.press)?;
+        s.serialize_field("adhoc", &self.adhoc)?;
+        s.serialize_field("events", self.events())?;
+        s.serialize_field("dropped_events", &self.dropped_events)?;
+        s.serialize_field("release", &self.release)?;
+        s.serialize_field("selection", &self.selection)?;
+        s.serialize_field("result", &self.result)?;
+        s.end()
+    }
+}
+
+/// Input names as `'static` strings, so recording them allocates nothing
+fn input_name(input: &str) -> &'static str {
+    match input {
+        "pointer" => "pointer",
+        "wheel" => "wheel",
+        "keyboard" => "keyboard",
+        "timeout" => "timeout",
+        "shutdown" => "shutdown",
+        _ => "other",
+    }
+}
+
+/// The last [`SESSION_CAPACITY`] menu sessions
+#[derive(Debug)]
+pub struct MenuSessionLog {
+    sessions: Box<[MenuSession]>,
+    /// Sessions recorded, capped at the capacity
+    count: usize,
+    /// Index of the newest session
+    newest: usize,
+    next_id: u64,
+    /// The newest session still takes highlights and navigation
+    open: bool,
+    /// Last pointer offset from `CursorMoved`
+    pointer: (i32, i32),
+}
+
+impl Default for MenuSessionLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MenuSessionLog {
+    pub fn new() -> Self {
+        Self {
+            sessions: vec![MenuSession::EMPTY; SESSION_CAPACITY].into_boxed_slice(),
+            count: 0,
+            newest: SESSION_CAPACITY - 1,
+            next_id: 1,
+            open: false,
+            pointer: (0, 0),
+        }
+    }
+
+    /// A menu opened at `press`; `opened_at_ms` is the Unix time of `now`
+    pub fn opened(&mut self, press: (i32, i32), opened_at_ms: u64, adhoc: bool, now: Instant) {
+        self.newest = (self.newest + 1) % SESSION_CAPACITY;
+        self.count = (self.count + 1).min(SESSION_CAPACITY);
+        let session = &mut self.sessions[self.newest];
+        *session = MenuSession::EMPTY;
+        session.id = self.next_id;
+        session.opened_at_ms = opened_at_ms;
+        session.opened = Some(now);
+        session.press = press;
+        session.adhoc = adhoc;
+        self.next_id += 1;
+        self.open = true;
+        self.pointer = (0, 0);
+    }
+
+    /// The pointer moved to `(dx, dy)` from the ring centre
+    pub fn moved(&mut self, dx: i32, dy: i32) {
+        self.pointer = (dx, dy);
+    }
+
+    /// The overlay highlighted `slot` (`None`: no slice)
+    pub fn highlighted(&mut self, slot: Option<u8>, now: Instant) {
+        let pointer = PointerSample::new(self.pointer.0, self.pointer.1);
+        if let Some(session) = self.current() {
+            let at_ms = session.at_ms(now);
+            session.push(SessionEvent::Highlight { at_ms, slot, pointer });
+        }
+    }
+
+    /// Wheel or keyboard navigation in the overlay
+    pub fn navigated(&mut self, input: &str, now: Instant) {
+        if let Some(session) = self.current() {
+            let at_ms = session.at_ms(now);
+            session.push(SessionEvent::Navigation { at_ms, input: input_name(input) });
+        }
+    }
+
+    /// The gesture button came up; `outcome` as in [`Release::outcome`]
+    pub fn released(&mut self, outcome: &'static str, now: Instant) {
+        let pointer = PointerSample::new(self.pointer.0, self.pointer.1);
+        if let Some(session) = self.current() {
+            let at_ms = session.at_ms(now);
+            session.release = Some(Release { at_ms, pointer, outcome });
+        }
+    }
+
+    /// The overlay closed the menu on `slot` (`None`: cancelled)
+    pub fn closed(&mut self, slot: Option<u8>, input: &str, now: Instant) {
+        if let Some(session) = self.current() {
+            let at_ms = session.at_ms(now);
+            session.selection = Some(Selection { at_ms, slot, input: input_name(input) });
+        }
+        self.open = false;
+    }
+
+    /// The picked action started, or failed with `error`; returns the
+    /// session it belongs to (the newest)
+    pub fn action_result(&mut self, slot: Option<u8>, error: Option<&str>, now: Instant) -> Option<&MenuSession> {
+        if self.count == 0 {
+            return None;
+        }
+        let session = &mut self.sessions[self.newest];
+        let at_ms = session.at_ms(now);
+        session.result = Some(ActionResult {
+            at_ms,
+            slot,
+            ok: error.is_none(),
+            error: ErrorText::new(error.unwrap_or_default()),
+        });
+        Some(&self.sessions[self.newest])
+    }
+
+    /// Up to `n` sessions, newest first
+    pub fn last(&self, n: usize) -> impl Iterator<Item = &MenuSession> {
+        (0..self.count.min(n)).map(move |i| &self.sessions[(self.newest + SESSION_CAPACITY - i) % SESSION_CAPACITY])
+    }
+
+    /// [`last`](Self::last) as a JSON array
+    pub fn to_json(&self, n: usize) -> serde_json::Value {
+        serde_json::to_value(self.last(n).collect::<Vec<_>>()).unwrap_or_default()
+    }
+
+    fn current(&mut self) -> Option<&mut MenuSession> {
+        self.open.then(|| &mut self.sessions[self.newest])
+    }
+}
+
+/// The daemon's menu session log
+pub type MenuLogHandle = StateHandle<MenuSessionLog>;
+
+impl MenuLogHandle {
+    /// Record the picked action's outcome; a failure logs its session at
+    /// debug level
+    pub fn action_result(&self, slot: Option<u8>, error: Option<&str>) {
+        let session = self
+            .update(|log| log.action_result(slot, error, Instant::now()).map(|s| serde_json::to_string(s).unwrap_or_default()))
+            .flatten();
+        if let (Some(error), Some(session)) = (error, session) {
+            tracing::debug!(?slot, error, session, "Menu action failed to start; its session");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_session_record_matches_inputs() {
+        let mut log = MenuSessionLog::new();
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+
+        log.opened((800, 450), 1_700_000_000_000, false, t0);
+        // Up and right into the NE slice, then a keyboard step, then east
+        log.moved(30, -30);
+        log.highlighted(Some(1), ms(40));
+        log.navigated("keyboard", ms(90));
+        log.moved(60, 5);
+        log.highlighted(Some(2), ms(130));
+        log.released("select", ms(200));
+        log.closed(Some(2), "pointer", ms(205));
+        // Events after the close do not belong to the session
+        log.highlighted(Some(5), ms(300));
+        let failed = log.action_result(Some(2), Some("xdg-open: not found"), ms(225)).unwrap();
+        assert_eq!(failed.id, 1);
+
+        let session = log.last(1).next().unwrap();
+        assert_eq!((session.press, session.opened_at_ms, session.adhoc), ((800, 450), 1_700_000_000_000, false));
+        assert_eq!(session.events().len(), 3);
+        let SessionEvent::Highlight { at_ms, slot, pointer } = session.events()[0] else {
+            panic!("expected a highlight: {:?}", session.events()[0]);
+        };
+        assert_eq!((at_ms, slot, pointer.dx, pointer.dy), (40, Some(1), 30, -30));
+        assert!((pointer.angle - 45.0).abs() < 1e-4);
+        assert!((pointer.distance - 42.426).abs() < 1e-3);
+        assert_eq!(session.events()[1], SessionEvent::Navigation { at_ms: 90, input: "keyboard" });
+
+        let release = session.release.unwrap();
+        assert_eq!((release.at_ms, release.outcome, release.pointer.dx, release.pointer.dy), (200, "select", 60, 5));
+        assert!((release.pointer.angle - 94.764).abs() < 1e-3);
+        assert_eq!(session.selection, Some(Selection { at_ms: 205, slot: Some(2), input: "pointer" }));
+        let result = session.result.unwrap();
+        assert_eq!((result.at_ms, result.ok, result.error.as_str()), (225, false, "xdg-open: not found"));
+
+        let json = log.to_json(5);
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        assert_eq!(json[0]["events"][0]["kind"], "highlight");
+        assert_eq!(json[0]["events"][1]["input"], "keyboard");
+        assert_eq!(json[0]["release"]["outcome"], "select");
+        assert_eq!(json[0]["result"]["error"], "xdg-open: not found");
+    }
+
+    #[test]
+    fn test_ring_keeps_the_newest_sessions() {
+        let mut log = MenuSessionLog::new();
+        let t0 = Instant::now();
+        for i in 0..SESSION_CAPACITY as i32 + 5 {
+            log.opened((i, i), 0, i % 2 == 0, t0);
+            log.closed(None, "timeout", t0);
+        }
+        let ids: Vec<u64> = log.last(usize::MAX).map(|s| s.id).collect();
+        assert_eq!(ids.len(), SESSION_CAPACITY);
+        assert_eq!((ids[0], ids[SESSION_CAPACITY - 1]), (25, 6));
+        assert_eq!(log.last(3).map(|s| s.press.0).collect::<Vec<_>>(), [24, 23, 22]);
+
+        // A reused record starts clean, and overflowing events are counted
+        log.opened((0, 0), 0, false, t0);
+        for _ in 0..EVENT_CAPACITY + 3 {
+            log.highlighted(Some(0), t0);
+        }
+        let session = log.last(1).next().unwrap();
+        assert_eq!((session.events().len(), session.dropped_events), (EVENT_CAPACITY, 3));
+        assert!(session.selection.is_none() && session.result.is_none());
+        let long = "é".repeat(100);
+        let result = log.action_result(None, Some(&long), t0).unwrap().result.unwrap();
+        assert_eq!(result.error.as_str(), "é".repeat(60));
+    }
+}
@@ -0,0 +1,1478 @@
+//! Menu payload sent to the overlay at menu-open time
+//!
+//! `MenuRequested(x, y)` stays a bare position so older overlays keep working.
+//! Everything else the overlay needs to render the menu travels as JSON in the
+//! `MenuPayload` signal, emitted immediately before `MenuRequested`.
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::accessibility::{AccessibilitySettings, EffectiveAnimationTimings};
+use crate::battery::BatteryState;
+use crate::color::Rgba;
+use crate::content_hash::ContentHash;
+use crate::geometry::SLICE_COUNT;
+use crate::hidpp::ConnectionType;
+use crate::icon_resolver::IconHint;
+use crate::slice_cooldown::CoolingSlot;
+use crate::slice_tint::{self, SliceTinting};
+use crate::theme::{IdleEffectLimits, IdleEffectSettings, PackedThemeColors, Theme, ThemeTexture};
+use crate::theme_transition::{effective_ms, TRANSITION_MS as THEME_TRANSITION_MS};
+
+/// Payload schema version; bump on incompatible changes
+pub const MENU_PAYLOAD_VERSION: u32 = 1;
+
+/// Optional features this daemon sends. An overlay only relies on a feature
+/// it finds listed here; older overlays ignore the list and the fields.
+///
+/// - `theme_transition`: `ThemeChanged` signals and `theme_transition_ms`
+/// - `slice_confirmation`: the `confirm` block and `AwaitingConfirmation`
+///   signals
+/// - `content_hash`: `theme_hash` and `profile_hash`
+/// - `slice_count`: rings of 4, 6, 8 or 12 slices (`slice_count`)
+/// - `dwell_confirm`: `dwell_confirm_ms`, after which a highlighted slice
+///   gets `HideMenu` without a release
+/// - `short_labels`: `short_labels` and ad-hoc `short_label`, labels cut to
+///   `menu.label_max_cells` at a grapheme boundary
+/// - `menu_size`: `menu_diameter`, the ring size resolved from `menu.size`
+///   or the profile's `menu_size` for the monitor under the press
+/// - `icons`: `icons`, the profile's file icons with their intrinsic size,
+///   content hash and (SVG) the size to rasterize at
+/// - `localized_labels`: `labels`, the profile's slice labels picked for the
+///   daemon's locale when the profile has per-language labels
+/// - `slice_colors`: `slice_colors`, one fill color per slice resolved from
+///   `accessibility.slice_tinting`, sent in every mode
+/// - `system_accent`: `accent`, the desktop accent the menu is drawn in
+///   instead of the theme's (`colors.accent_follow_system`), and
+///   `accent_override` in `ThemeChanged`
+/// - `idle_effect`: `idle_effect`, the theme's idle animation with the
+///   user's `idle_effects` caps applied; `type` is `"none"` on battery and
+///   under reduced motion
+/// - `cooldown`: `cooldown`, the profile slots still in their `cooldown_ms`
+///   with the time left; a release on one gets `DismissMenu`
+/// - `packed_colors`: `packed_colors`, the effective palette as premultiplied
+///   `0xRRGGBBAA` numbers and the glassmorphism values in one flat array
+///   ([`PackedThemeColors`]), sent alongside everything the overlay reads
+///   from theme.json
+/// - `background_texture`: `background_texture`, the theme's checked slice
+///   texture as an absolute path, format and content hash
+/// - `profile_slices`: `slices`, what the overlay draws for a profile other
+///   than `default`; the daemon runs the slot reported by `ReportMenuClosed`
+pub const PAYLOAD_CAPABILITIES: &[&str] = &[
+    "theme_transition",
+    "slice_confirmation",
+    "content_hash",
+    "slice_count",
+    "dwell_confirm",
+    "short_labels",
+    "menu_size",
+    "icons",
+    "localized_labels",
+    "slice_colors",
+    "system_accent",
+    "idle_effect",
+    "cooldown",
+    "packed_colors",
+    "background_texture",
+    "profile_slices",
+];
+
+/// At or below this the battery badge uses the theme's error color
+pub const BATTERY_CRITICAL_PERCENT: u8 = 15;
+
+/// At or below this the battery badge uses the theme's warning color
+pub const BATTERY_LOW_PERCENT: u8 = 30;
+
+/// How long the overlay stays up after release to show an action's outcome
+pub const FEEDBACK_HOLD_MS: u32 = 150;
+
+/// Default `menu.no_blur_min_opacity`
+pub const NO_BLUR_MIN_OPACITY: f32 = 0.9;
+
+/// Payloads larger than this are logged at warn level
+pub const PAYLOAD_WARN_BYTES: usize = 16 * 1024;
+
+/// Payloads larger than this are not sent, so a pathological payload cannot
+/// stall the menu; the overlay keeps its previous render state
+pub const PAYLOAD_MAX_BYTES: usize = 64 * 1024;
+
+/// Battery and connection badges for the menu hub
+///
+/// Borrows from the theme so building it on the open path allocates nothing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeviceStatus<'a> {
+    pub battery_percent: u8,
+    pub charging: bool,
+    pub charge_complete: bool,
+    /// "USB", "Bolt", "Bluetooth" or "Unifying"; omitted when unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<&'static str>,
+    /// Theme color (hex) for the battery badge, chosen from the thresholds
+    pub battery_color: &'a str,
+}
+
+impl<'a> DeviceStatus<'a> {
+    /// Build from cached state; `None` when battery info is unavailable
+    pub fn build(
+        battery: &BatteryState,
+        connection: Option<ConnectionType>,
+        theme: &'a Theme,
+    ) -> Option<Self> {
+        if !battery.available {
+            return None;
+        }
+        let colors = &theme.colors;
+        let battery_color = if battery.charging || battery.charge_complete {
+            &colors.success
+        } else if battery.percentage <= BATTERY_CRITICAL_PERCENT {
+            &colors.error
+        } else if battery.percentage <= BATTERY_LOW_PERCENT {
+            &colors.warning
+        } else {
+            &colors.success
+        };
+        Some(Self {
+            battery_percent: battery.percentage,
+            charging: battery.charging,
+            charge_complete: battery.charge_complete,
+            connection: connection.map(|c| c.as_str()),
+            battery_color,
+        })
+    }
+}
+
+/// Outcome flash for the executed slice (`menu.action_feedback`)
+///
+/// The overlay flashes `error_color` when an action fails to start within
+/// its 20ms check, and `success_color` (if set) when it starts.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionFeedback {
+    pub error_color: String,
+    /// Omitted unless `menu.success_flash` is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success_color: Option<String>,
+    /// Delay between release and hiding the menu
+    pub hold_ms: u32,
+}
+
+impl ActionFeedback {
+    /// Colors from the active theme
+    pub fn build(theme: &Theme, success_flash: bool) -> Self {
+        Self {
+            error_color: theme.colors.error.clone(),
+            success_color: success_flash.then(|| theme.colors.success.clone()),
+            hold_ms: FEEDBACK_HOLD_MS,
+        }
+    }
+}
+
+/// High contrast overrides for the overlay (Story 4.5)
+///
+/// Present only while high contrast is in effect; the overlay paints labels
+/// in `text_color` and outlines the selected slice with the selection border.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HighContrastStyle {
+    pub text_color: String,
+    pub text_secondary_color: String,
+    pub border_opacity: f32,
+    pub selection_border_color: String,
+    pub selection_border_width: u8,
+}
+
+impl HighContrastStyle {
+    /// Effective high contrast colors of the theme
+    pub fn build(theme: &Theme) -> Self {
+        let colors = theme.get_effective_colors(true);
+        let glass = theme.get_effective_glassmorphism(true);
+        let settings = Theme::get_high_contrast_settings();
+        Self {
+            text_color: colors.text,
+            text_secondary_color: colors.text_secondary,
+            border_opacity: glass.border_opacity,
+            selection_border_color: settings.selection_border_color,
+            selection_border_width: settings.selection_border_width,
+        }
+    }
+}
+
+/// Slice descriptions for long-hover help (`menu.hover_help_ms`)
+///
+/// The overlay shows `descriptions[slot]` in the hub once the daemon reports
+/// the highlight stable (`HighlightStable`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SliceHelp<'a> {
+    /// Hover delay before a description shows
+    pub delay_ms: u64,
+    /// One entry per profile slot; `null` for slots without one
+    pub descriptions: &'a [Option<String>],
+}
+
+impl<'a> SliceHelp<'a> {
+    /// `None` with help off or when no slot has a description, so menus
+    /// without descriptions carry no block
+    pub fn build(delay: Option<Duration>, descriptions: Option<&'a [Option<String>]>) -> Option<Self> {
+        let delay = delay?;
+        let descriptions = descriptions.filter(|d| d.iter().any(Option::is_some))?;
+        Some(Self {
+            delay_ms: u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            descriptions,
+        })
+    }
+
+    /// The hover delay
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms)
+    }
+}
+
+/// Profile slots that need a second press (`require_confirmation`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SliceConfirm<'a> {
+    /// How long the second press may take (`menu.confirm_window_ms`)
+    pub window_ms: u64,
+    pub slots: &'a [u8],
+}
+
+impl<'a> SliceConfirm<'a> {
+    /// `None` when no slot needs confirming, so most menus carry no block
+    pub fn build(window: Duration, slots: Option<&'a [u8]>) -> Option<Self> {
+        let slots = slots.filter(|s| !s.is_empty())?;
+        Some(Self {
+            window_ms: u64::try_from(window.as_millis()).unwrap_or(u64::MAX),
+            slots,
+        })
+    }
+}
+
+/// One slot as sent to the overlay (the action stays in the daemon)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OverlaySlice {
+    pub label: String,
+    /// `label` cut to `menu.label_max_cells`; omitted when it fits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+/// Per-slot markers of the open menu's profile
+#[derive(Debug, Clone, Default)]
+pub struct SlotHints<'a> {
+    /// Slices of a daemon-run profile menu, one per slot
+    pub slices: Option<&'a [Option<OverlaySlice>]>,
+    /// Slots bound to `wheel_axis`
+    pub wheel_axis: Option<&'a [u8]>,
+    pub confirm: Option<SliceConfirm<'a>>,
+    /// Slots still cooling
+    pub cooldown: Option<&'a [CoolingSlot]>,
+    /// Content hash of the profile itself
+    pub profile_hash: Option<ContentHash>,
+    /// Slices in the profile's ring
+    pub slice_count: Option<u8>,
+    /// Labels in the daemon's locale, one per slot
+    pub labels: Option<&'a [Option<String>]>,
+    /// Shortened labels, one per slot
+    pub short_labels: Option<&'a [Option<String>]>,
+    /// Ring diameter in logical pixels; `None` leaves it to the overlay
+    pub menu_diameter: Option<u32>,
+    /// File icons, one per slot
+    pub icons: Option<&'a [Option<IconHint<'a>>]>,
+}
+
+/// Render state for one menu invocation
+#[derive(Debug, Clone, Serialize)]
+pub struct MenuPayload<'a> {
+    /// Schema version ([`MENU_PAYLOAD_VERSION`])
+    pub version: u32,
+    /// Optional features ([`PAYLOAD_CAPABILITIES`])
+    pub capabilities: &'static [&'static str],
+    /// Active theme name
+    pub theme: String,
+    /// Content hash of the active theme; unchanged means nothing to restyle
+    pub theme_hash: ContentHash,
+    /// Whether reduced motion is in effect (system or config override)
+    pub reduced_motion: bool,
+    /// Animation timings with reduced motion applied (Story 4.6)
+    pub animation: EffectiveAnimationTimings,
+    /// Idle animation behind the slices, within the `idle_effects` limits;
+    /// `idle_effects_enabled` in `animation` agrees with it
+    pub idle_effect: IdleEffectSettings,
+    /// Crossfade for a theme switch while the menu is open
+    /// (`menu.theme_transition_ms`, 0 under reduced motion)
+    pub theme_transition_ms: u32,
+    /// Highlight dwell after which the slice runs without a release
+    /// (`accessibility.dwell_confirm_ms`, 0 = off); the overlay fills a
+    /// progress ring on the highlighted slice over this time
+    pub dwell_confirm_ms: u32,
+    /// Ring mirrored for left-handed use: the overlay maps positions to
+    /// profile slots with `geometry::slot_for_position`
+    pub left_handed: bool,
+    /// Action outcome flash; omitted when `menu.action_feedback` is off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<ActionFeedback>,
+    /// Whether the compositor blurs behind the menu (KWin blur effect loaded)
+    pub blur_available: bool,
+    /// Menu background opacity to paint with: the theme's (or high
+    /// contrast's), raised to `menu.no_blur_min_opacity` without blur
+    pub background_opacity: f32,
+    /// Effective colors and glassmorphism as numbers (high contrast applied,
+    /// `background_opacity` as above), for an overlay that would otherwise
+    /// parse hex strings per frame
+    pub packed_colors: PackedThemeColors,
+    /// Texture to draw behind the slices (`glassmorphism.backgroundTexture`);
+    /// omitted when the theme has none, it was rejected, or in high contrast
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_texture: Option<ThemeTexture>,
+    /// High contrast overrides; omitted unless high contrast is in effect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high_contrast: Option<HighContrastStyle>,
+    /// Desktop accent to draw instead of the theme's; omitted when the
+    /// theme's own applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    /// Slices of an ad-hoc menu (`ShowAdHocMenu`), one per slot; the overlay
+    /// draws these instead of the profile and leaves execution to the daemon
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adhoc: Option<Vec<Option<OverlaySlice>>>,
+    /// Slices of the menu's profile, one per slot, when it is not `default`:
+    /// the overlay draws these instead of config.json's `radial_menu` ring
+    /// and leaves execution to the daemon. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slices: Option<&'a [Option<OverlaySlice>]>,
+    /// Long-hover slice descriptions; omitted when the menu has none.
+    /// Appended by [`PayloadEncoder`] like `status`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<SliceHelp<'a>>,
+    /// Profile slots bound to `wheel_axis`, drawn with an up/down hint;
+    /// omitted when the menu has none. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wheel_axis: Option<&'a [u8]>,
+    /// Slots that run only after a second press; omitted when the menu has
+    /// none. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<SliceConfirm<'a>>,
+    /// Slots that ran within their `cooldown_ms`, with the milliseconds
+    /// left; omitted when none is cooling. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooldown: Option<&'a [CoolingSlot]>,
+    /// Content hash of the menu's profile; omitted for ad-hoc menus.
+    /// Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile_hash: Option<ContentHash>,
+    /// Slices in the menu's ring (`slice_count`, 4, 6, 8 or 12); omitted
+    /// for ad-hoc menus, which have 8. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slice_count: Option<u8>,
+    /// Slice labels in the daemon's locale, one per slot (`null` = the
+    /// slice has none); omitted unless the profile has per-language labels,
+    /// whose map the overlay cannot resolve itself. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<&'a [Option<String>]>,
+    /// Labels wider than `menu.label_max_cells`, shortened with `…`, one per
+    /// slot (`null` = draw the label as it is); omitted when every label
+    /// fits. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_labels: Option<&'a [Option<String>]>,
+    /// Ring diameter in compositor-logical pixels for the monitor the menu
+    /// opens on (`menu.size` or the profile's `menu_size`); omitted for
+    /// `auto`, where the overlay sizes the ring itself. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub menu_diameter: Option<u32>,
+    /// File icons of the menu's profile, one per slot (`null` = the
+    /// overlay's own icon for the slice), sized for this ring; omitted when
+    /// the profile has none. Appended like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icons: Option<&'a [Option<IconHint<'a>>]>,
+    /// Fill color per slice (`accessibility.slice_tinting`), for the ring's
+    /// slice count or 8; the surface color for every slice with tinting off.
+    /// Always sent; `None` only in [`PayloadEncoder`]'s cached prefix, which
+    /// appends it like `help`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slice_colors: Option<Vec<String>>,
+    /// Battery/connection badges; omitted when battery info is unavailable.
+    /// Last, so [`PayloadEncoder`] can append it to the cached prefix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<DeviceStatus<'a>>,
+}
+
+impl<'a> MenuPayload<'a> {
+    /// Build the payload from the active theme and accessibility state
+    pub fn build(theme: &Theme, accessibility: &AccessibilitySettings) -> Self {
+        let reduced_motion = accessibility.should_reduce_motion();
+        let high_contrast = accessibility.should_use_high_contrast();
+        Self {
+            version: MENU_PAYLOAD_VERSION,
+            capabilities: PAYLOAD_CAPABILITIES,
+            theme: theme.name.clone(),
+            theme_hash: ContentHash::of(theme),
+            reduced_motion,
+            animation: theme.get_effective_animation_timings(reduced_motion),
+            idle_effect: theme.get_effective_idle_effect(reduced_motion),
+            theme_transition_ms: effective_ms(THEME_TRANSITION_MS, reduced_motion),
+            dwell_confirm_ms: 0,
+            status: None,
+            left_handed: false,
+            feedback: None,
+            blur_available: true,
+            background_opacity: theme.effective_background_opacity(high_contrast),
+            packed_colors: theme.get_packed_colors(high_contrast),
+            background_texture: theme.effective_background_texture(high_contrast).cloned(),
+            high_contrast: high_contrast.then(|| HighContrastStyle::build(theme)),
+            accent: None,
+            adhoc: None,
+            slices: None,
+            help: None,
+            wheel_axis: None,
+            confirm: None,
+            cooldown: None,
+            profile_hash: None,
+            slice_count: None,
+            labels: None,
+            short_labels: None,
+            menu_diameter: None,
+            icons: None,
+            slice_colors: Some(slice_tint::slice_colors(theme, SliceTinting::default(), SLICE_COUNT.into())),
+        }
+    }
+
+    /// Attach the device status block
+    pub fn with_status(mut self, status: Option<DeviceStatus<'a>>) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Set the handedness (`input.left_handed`)
+    pub fn with_left_handed(mut self, left_handed: bool) -> Self {
+        self.left_handed = left_handed;
+        self
+    }
+
+    /// Attach ad-hoc menu slices
+    pub fn with_adhoc(mut self, adhoc: Option<Vec<Option<OverlaySlice>>>) -> Self {
+        self.adhoc = adhoc;
+        self
+    }
+
+    /// Attach the slices of a daemon-run profile menu
+    pub fn with_slices(mut self, slices: Option<&'a [Option<OverlaySlice>]>) -> Self {
+        self.slices = slices;
+        self
+    }
+
+    /// Attach the slice descriptions block
+    pub fn with_help(mut self, help: Option<SliceHelp<'a>>) -> Self {
+        self.help = help;
+        self
+    }
+
+    /// Attach the `wheel_axis` slots
+    pub fn with_wheel_axis(mut self, slots: Option<&'a [u8]>) -> Self {
+        self.wheel_axis = slots;
+        self
+    }
+
+    /// Attach the slots that need confirming
+    pub fn with_confirm(mut self, confirm: Option<SliceConfirm<'a>>) -> Self {
+        self.confirm = confirm;
+        self
+    }
+
+    /// Attach the slots still cooling
+    pub fn with_cooldown(mut self, slots: Option<&'a [CoolingSlot]>) -> Self {
+        self.cooldown = slots;
+        self
+    }
+
+    /// Attach the profile's content hash
+    pub fn with_profile_hash(mut self, hash: Option<ContentHash>) -> Self {
+        self.profile_hash = hash;
+        self
+    }
+
+    /// Set the ring's slice count
+    pub fn with_slice_count(mut self, count: Option<u8>) -> Self {
+        self.slice_count = count;
+        self
+    }
+
+    /// Attach the labels resolved for the locale
+    pub fn with_labels(mut self, labels: Option<&'a [Option<String>]>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Attach the shortened labels
+    pub fn with_short_labels(mut self, labels: Option<&'a [Option<String>]>) -> Self {
+        self.short_labels = labels;
+        self
+    }
+
+    /// Set the resolved ring diameter
+    pub fn with_menu_diameter(mut self, diameter: Option<u32>) -> Self {
+        self.menu_diameter = diameter;
+        self
+    }
+
+    /// Attach the file icons
+    pub fn with_icons(mut self, icons: Option<&'a [Option<IconHint<'a>>]>) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Resolve the slice colors for `mode` and the ring's slice count (set
+    /// that first)
+    pub fn with_slice_tinting(mut self, theme: &Theme, mode: SliceTinting) -> Self {
+        let count = self.slice_count.unwrap_or(SLICE_COUNT);
+        self.slice_colors = Some(slice_tint::slice_colors(theme, mode, count.into()));
+        self
+    }
+
+    /// Set the desktop accent the theme is drawn with
+    pub fn with_accent(mut self, accent: Option<Rgba>) -> Self {
+        self.accent = accent.map(Rgba::to_hex);
+        self
+    }
+
+    /// Bound the idle effect by the user's limits (`idle_effects`, off on
+    /// battery)
+    pub fn with_idle_limits(mut self, limits: &IdleEffectLimits) -> Self {
+        self.idle_effect = self.idle_effect.limited(limits);
+        self.animation = self.animation.with_idle_effect(&self.idle_effect);
+        self
+    }
+
+    /// Set the theme crossfade (`menu.theme_transition_ms`)
+    pub fn with_theme_transition(mut self, transition_ms: u32) -> Self {
+        self.theme_transition_ms = effective_ms(transition_ms, self.reduced_motion);
+        self
+    }
+
+    /// Set the dwell confirmation time (0 = off)
+    pub fn with_dwell_confirm(mut self, dwell_ms: u32) -> Self {
+        self.dwell_confirm_ms = dwell_ms;
+        self
+    }
+
+    /// Attach the action feedback block
+    pub fn with_feedback(mut self, feedback: Option<ActionFeedback>) -> Self {
+        self.feedback = feedback;
+        self
+    }
+
+    /// Record whether blur is available, raising the background opacity to
+    /// `min_opacity` when it is not so the menu stays readable
+    pub fn with_blur(mut self, blur_available: bool, min_opacity: f32) -> Self {
+        self.blur_available = blur_available;
+        self.background_opacity = opacity_without_blur(self.background_opacity, blur_available, min_opacity);
+        self.packed_colors.glassmorphism[1] = self.background_opacity;
+        self
+    }
+
+    /// Serialize for the `MenuPayload` signal
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// [`to_json`](Self::to_json) with the size logged and capped; `None`
+    /// when over [`PAYLOAD_MAX_BYTES`]
+    pub fn to_signal_json(&self) -> Option<String> {
+        let json = self.to_json();
+        size_ok(json.len()).then_some(json)
+    }
+}
+
+fn opacity_without_blur(opacity: f32, blur_available: bool, min_opacity: f32) -> f32 {
+    if blur_available {
+        opacity
+    } else {
+        opacity.max(min_opacity.clamp(0.0, 1.0))
+    }
+}
+
+/// Per-invocation inputs that come from config and the compositor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PayloadOptions {
+    /// `input.left_handed`
+    pub left_handed: bool,
+    /// `menu.success_flash`, or `None` with `menu.action_feedback` off
+    pub success_flash: Option<bool>,
+    /// KWin blur effect loaded (`DaemonHandles::blur`)
+    pub blur_available: bool,
+    /// `menu.no_blur_min_opacity`
+    pub no_blur_min_opacity: f32,
+    /// `menu.theme_transition_ms`
+    pub theme_transition_ms: u32,
+    /// `accessibility.dwell_confirm_ms` after the floor, 0 when off
+    pub dwell_confirm_ms: u32,
+    /// `accessibility.slice_tinting`
+    pub slice_tinting: SliceTinting,
+    /// Desktop accent in place of the theme's (`colors.accent_follow_system`)
+    pub accent: Option<Rgba>,
+    /// `idle_effects` caps, idle effects off while on battery
+    pub idle_limits: IdleEffectLimits,
+}
+
+impl Default for PayloadOptions {
+    fn default() -> Self {
+        Self {
+            left_handed: false,
+            success_flash: None,
+            blur_available: true,
+            no_blur_min_opacity: NO_BLUR_MIN_OPACITY,
+            theme_transition_ms: THEME_TRANSITION_MS,
+            dwell_confirm_ms: 0,
+            slice_tinting: SliceTinting::default(),
+            accent: None,
+            idle_limits: IdleEffectLimits::default(),
+        }
+    }
+}
+
+/// Log the payload size; false above the hard cap
+fn size_ok(bytes: usize) -> bool {
+    if bytes > PAYLOAD_MAX_BYTES {
+        tracing::warn!(
+            bytes,
+            max = PAYLOAD_MAX_BYTES,
+            "Menu payload over the size cap - not sent; the overlay renders with its previous state"
+        );
+        return false;
+    }
+    if bytes > PAYLOAD_WARN_BYTES {
+        tracing::warn!(bytes, "Large menu payload may delay menu appearance");
+    } else {
+        tracing::trace!(bytes, "Menu payload size");
+    }
+    true
+}
+
+/// Payload serializer for the menu-open path
+///
+/// Everything except the slice descriptions, the slot hints and the device
+/// status only changes with the theme, the accessibility state or the
+/// config, so it is serialized once and reused while those values are
+/// unchanged (compared in place, without allocating; the theme by its
+/// content hash, so an edited theme of the same name rebuilds it). Each open copies that
+/// prefix into a buffer kept across opens and appends the help, slot hint
+/// and status blocks. The slice colors are appended from their own cache,
+/// keyed by theme, tinting mode and slice count.
+#[derive(Debug, Default)]
+pub struct PayloadEncoder {
+    /// Static part the prefix was built from (`help`, the slot hints and
+    /// `status` always `None`)
+    cached: Option<(MenuPayload<'static>, PayloadOptions)>,
+    /// `cached` serialized without its closing brace
+    prefix: Vec<u8>,
+    /// Serialized `slice_colors` and the theme hash, mode and slice count
+    /// they were resolved for
+    slice_colors: Option<((ContentHash, SliceTinting, u8), Vec<u8>)>,
+    buffer: Vec<u8>,
+}
+
+impl PayloadEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serialize one invocation's payload; `None` when over the size cap
+    #[allow(clippy::too_many_arguments)]
+    pub fn encode(
+        &mut self,
+        theme: &Theme,
+        theme_hash: ContentHash,
+        accessibility: &AccessibilitySettings,
+        options: &PayloadOptions,
+        help: Option<&SliceHelp<'_>>,
+        hints: &SlotHints<'_>,
+        status: Option<&DeviceStatus<'_>>,
+    ) -> Option<&str> {
+        if !self.is_current(theme, theme_hash, accessibility, options) {
+            self.rebuild(theme, accessibility, options);
+        }
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&self.prefix);
+        if let Some(slices) = hints.slices {
+            self.buffer.extend_from_slice(b",\"slices\":");
+            serde_json::to_writer(&mut self.buffer, slices).ok()?;
+        }
+        if let Some(help) = help {
+            self.buffer.extend_from_slice(b",\"help\":");
+            serde_json::to_writer(&mut self.buffer, help).ok()?;
+        }
+        if let Some(slots) = hints.wheel_axis {
+            self.buffer.extend_from_slice(b",\"wheel_axis\":");
+            serde_json::to_writer(&mut self.buffer, slots).ok()?;
+        }
+        if let Some(confirm) = &hints.confirm {
+            self.buffer.extend_from_slice(b",\"confirm\":");
+            serde_json::to_writer(&mut self.buffer, confirm).ok()?;
+        }
+        if let Some(slots) = hints.cooldown {
+            self.buffer.extend_from_slice(b",\"cooldown\":");
+            serde_json::to_writer(&mut self.buffer, slots).ok()?;
+        }
+        if let Some(hash) = hints.profile_hash {
+            self.buffer.extend_from_slice(b",\"profile_hash\":");
+            serde_json::to_writer(&mut self.buffer, &hash).ok()?;
+        }
+        if let Some(count) = hints.slice_count {
+            self.buffer.extend_from_slice(b",\"slice_count\":");
+            serde_json::to_writer(&mut self.buffer, &count).ok()?;
+        }
+        if let Some(labels) = hints.labels {
+            self.buffer.extend_from_slice(b",\"labels\":");
+            serde_json::to_writer(&mut self.buffer, labels).ok()?;
+        }
+        if let Some(labels) = hints.short_labels {
+            self.buffer.extend_from_slice(b",\"short_labels\":");
+            serde_json::to_writer(&mut self.buffer, labels).ok()?;
+        }
+        if let Some(diameter) = hints.menu_diameter {
+            self.buffer.extend_from_slice(b",\"menu_diameter\":");
+            serde_json::to_writer(&mut self.buffer, &diameter).ok()?;
+        }
+        if let Some(icons) = hints.icons {
+            self.buffer.extend_from_slice(b",\"icons\":");
+            serde_json::to_writer(&mut self.buffer, icons).ok()?;
+        }
+        let key = (theme_hash, options.slice_tinting, hints.slice_count.unwrap_or(SLICE_COUNT));
+        if self.slice_colors.as_ref().is_none_or(|(cached, _)| *cached != key) {
+            let colors = slice_tint::slice_colors(theme, key.1, key.2.into());
+            self.slice_colors = Some((key, serde_json::to_vec(&colors).ok()?));
+        }
+        self.buffer.extend_from_slice(b",\"slice_colors\":");
+        self.buffer.extend_from_slice(&self.slice_colors.as_ref()?.1);
+        if let Some(status) = status {
+            self.buffer.extend_from_slice(b",\"status\":");
+            serde_json::to_writer(&mut self.buffer, status).ok()?;
+        }
+        self.buffer.push(b'}');
+        if !size_ok(self.buffer.len()) {
+            return None;
+        }
+        std::str::from_utf8(&self.buffer).ok()
+    }
+
+    /// Whether the cached prefix still matches the inputs
+    fn is_current(
+        &self,
+        theme: &Theme,
+        theme_hash: ContentHash,
+        accessibility: &AccessibilitySettings,
+        options: &PayloadOptions,
+    ) -> bool {
+        let Some((cached, cached_options)) = &self.cached else {
+            return false;
+        };
+        let reduced_motion = accessibility.should_reduce_motion();
+        let high_contrast = accessibility.should_use_high_contrast();
+        let opacity = opacity_without_blur(
+            theme.effective_background_opacity(high_contrast),
+            options.blur_available,
+            options.no_blur_min_opacity,
+        );
+        let feedback_matches = match (&cached.feedback, options.success_flash) {
+            (None, None) => true,
+            (Some(f), Some(flash)) => {
+                f.error_color == theme.colors.error
+                    && f.success_color.as_deref() == flash.then_some(theme.colors.success.as_str())
+            }
+            _ => false,
+        };
+        cached_options == options
+            && cached.theme == theme.name
+            && cached.theme_hash == theme_hash
+            && cached.reduced_motion == reduced_motion
+            && cached.idle_effect == theme.get_effective_idle_effect(reduced_motion).limited(&options.idle_limits)
+            && cached.animation
+                == theme
+                    .get_effective_animation_timings(reduced_motion)
+                    .with_idle_effect(&cached.idle_effect)
+            && cached.background_opacity == opacity
+            && cached.high_contrast.is_some() == high_contrast
+            && feedback_matches
+    }
+
+    /// Re-serialize the static part
+    fn rebuild(&mut self, theme: &Theme, accessibility: &AccessibilitySettings, options: &PayloadOptions) {
+        let payload = MenuPayload::build(theme, accessibility)
+            .with_left_handed(options.left_handed)
+            .with_feedback(options.success_flash.map(|flash| ActionFeedback::build(theme, flash)))
+            .with_blur(options.blur_available, options.no_blur_min_opacity)
+            .with_theme_transition(options.theme_transition_ms)
+            .with_dwell_confirm(options.dwell_confirm_ms)
+            .with_accent(options.accent)
+            .with_idle_limits(&options.idle_limits);
+        let payload = MenuPayload { slice_colors: None, ..payload };
+        self.prefix.clear();
+        // Serializing a struct of plain fields into a Vec cannot fail
+        let _ = serde_json::to_writer(&mut self.prefix, &payload);
+        self.prefix.pop();
+        tracing::debug!(theme = %payload.theme, bytes = self.prefix.len(), "Menu payload prefix rebuilt");
+        self.cached = Some((payload, *options));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_carries_theme_timings() {
+        let theme = Theme::catppuccin_mocha();
+        let mut accessibility = AccessibilitySettings::default();
+        accessibility.set_system_reduced_motion(false);
+
+        let payload = MenuPayload::build(&theme, &accessibility);
+        assert_eq!(payload.version, MENU_PAYLOAD_VERSION);
+        assert_eq!(payload.theme, "catppuccin-mocha");
+        assert!(!payload.reduced_motion);
+        assert_eq!(payload.animation, theme.get_effective_animation_timings(false));
+    }
+
+    #[test]
+    fn test_payload_honors_reduced_motion_override() {
+        let theme = Theme::catppuccin_mocha();
+        let mut accessibility = AccessibilitySettings::default();
+        accessibility.set_reduced_motion(Some(true));
+
+        let payload = MenuPayload::build(&theme, &accessibility);
+        assert!(payload.reduced_motion);
+        assert_eq!(payload.animation, EffectiveAnimationTimings::reduced_motion());
+    }
+
+    #[test]
+    fn test_payload_json_shape() {
+        let payload = MenuPayload::build(&Theme::catppuccin_mocha(), &AccessibilitySettings::default());
+        let json: serde_json::Value = serde_json::from_str(&payload.to_json()).unwrap();
+        assert_eq!(json["version"], 1);
+        assert!(json["animation"]["appear_ms"].is_number());
+        assert!(json["animation"]["idle_effects_enabled"].is_boolean());
+        assert!(json.get("status").is_none());
+        assert_eq!(json["left_handed"], false);
+        assert!(json.get("feedback").is_none());
+        assert!(json.get("adhoc").is_none());
+        assert!(json.get("slices").is_none());
+        assert!(json.get("help").is_none());
+        assert!(json.get("accent").is_none());
+        assert_eq!(json["idle_effect"], serde_json::json!({"type": "none", "density": 0.0, "speed": 0.0, "max_fps": 0}));
+        assert_eq!(
+            json["capabilities"],
+            serde_json::json!([
+                "theme_transition",
+                "slice_confirmation",
+                "content_hash",
+                "slice_count",
+                "dwell_confirm",
+                "short_labels",
+                "menu_size",
+                "icons",
+                "localized_labels",
+                "slice_colors",
+                "system_accent",
+                "idle_effect",
+                "cooldown",
+                "packed_colors",
+                "background_texture",
+                "profile_slices"
+            ])
+        );
+        assert!(json.get("background_texture").is_none());
+        assert_eq!(json["theme_transition_ms"], 150);
+        assert_eq!(json["dwell_confirm_ms"], 0);
+        assert_eq!(json["packed_colors"].as_array().unwrap().len(), crate::theme::PACKED_THEME_COLORS_LEN);
+        assert_eq!(json["packed_colors"][0], 0x1e1e2eff_u32);
+    }
+
+    #[test]
+    fn test_theme_transition_follows_config_and_reduced_motion() {
+        let theme = Theme::catppuccin_mocha();
+        let mut accessibility = AccessibilitySettings::default();
+        accessibility.set_reduced_motion(Some(false));
+        let payload = MenuPayload::build(&theme, &accessibility).with_theme_transition(400);
+        assert_eq!(payload.theme_transition_ms, 400);
+
+        accessibility.set_reduced_motion(Some(true));
+        let payload = MenuPayload::build(&theme, &accessibility).with_theme_transition(400);
+        assert_eq!(payload.theme_transition_ms, 0);
+        assert!(payload.capabilities.contains(&"theme_transition"));
+
+        // The cached prefix is rebuilt when the config value changes
+        let mut encoder = PayloadEncoder::new();
+        accessibility.set_reduced_motion(Some(false));
+        let options = PayloadOptions { theme_transition_ms: 0, ..Default::default() };
+        let json: serde_json::Value =
+            serde_json::from_str(encoder.encode(&theme, ContentHash::of(&theme), &accessibility, &options, None, &SlotHints::default(), None).unwrap()).unwrap();
+        assert_eq!(json["theme_transition_ms"], 0);
+        let json: serde_json::Value = serde_json::from_str(
+            encoder.encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &SlotHints::default(), None).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["theme_transition_ms"], 150);
+    }
+
+    #[test]
+    fn test_adhoc_slices_reach_payload() {
+        let menu = crate::adhoc_menu::AdHocMenu::parse(
+            r#"{"slices": [null, {"type": "command", "value": "true", "label": "Run"}]}"#,
+        )
+        .unwrap();
+        let payload = MenuPayload::build(&Theme::catppuccin_mocha(), &AccessibilitySettings::default())
+            .with_adhoc(Some(menu.overlay_slices(None)));
+        let json: serde_json::Value = serde_json::from_str(&payload.to_signal_json().unwrap()).unwrap();
+        let adhoc = json["adhoc"].as_array().unwrap();
+        assert_eq!(adhoc.len(), 8);
+        assert!(adhoc[0].is_null());
+        assert_eq!(adhoc[1]["label"], "Run");
+        // The command itself never reaches the overlay
+        assert!(adhoc[1].get("value").is_none());
+    }
+
+    #[test]
+    fn test_profile_slices_reach_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let slices = [
+            Some(OverlaySlice { label: "Edit".to_string(), short_label: None, icon: Some("document-edit".to_string()) }),
+            None,
+        ];
+        let hints = SlotHints { slices: Some(&slices), slice_count: Some(8), ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility)
+            .with_slices(Some(&slices))
+            .with_slice_count(Some(8))
+            .to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["slices"], serde_json::json!([{"label": "Edit", "icon": "document-edit"}, null]));
+        // The Settings ring (default profile) sends none
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &SlotHints::default(), None)
+            .unwrap();
+        assert!(!json.contains("\"slices\":"));
+    }
+
+    #[test]
+    fn test_payload_size_guard() {
+        let payload = MenuPayload::build(&Theme::catppuccin_mocha(), &AccessibilitySettings::default());
+        let json = payload.to_signal_json().unwrap();
+        assert!(json.len() < PAYLOAD_WARN_BYTES);
+
+        assert!(size_ok(PAYLOAD_MAX_BYTES));
+        assert!(!size_ok(PAYLOAD_MAX_BYTES + 1));
+    }
+
+    #[test]
+    fn test_encoder_matches_full_serialization() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let status = DeviceStatus::build(&battery(42, false, true), Some(ConnectionType::Bolt), &theme);
+        let expected = MenuPayload::build(&theme, &accessibility)
+            .with_status(status.clone())
+            .with_left_handed(true)
+            .with_feedback(Some(ActionFeedback::build(&theme, false)))
+            .with_blur(false, 0.9)
+            .with_dwell_confirm(900)
+            .with_accent(Rgba::from_hex("#3daee9"))
+            .with_idle_limits(&IdleEffectLimits { enabled: true, max_fps: 20, max_density: 0.4 })
+            .to_json();
+
+        let options = PayloadOptions {
+            left_handed: true,
+            success_flash: Some(false),
+            blur_available: false,
+            no_blur_min_opacity: 0.9,
+            theme_transition_ms: THEME_TRANSITION_MS,
+            dwell_confirm_ms: 900,
+            slice_tinting: SliceTinting::Theme,
+            accent: Rgba::from_hex("#3daee9"),
+            idle_limits: IdleEffectLimits { enabled: true, max_fps: 20, max_density: 0.4 },
+        };
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder.encode(&theme, ContentHash::of(&theme), &accessibility, &options, None, &SlotHints::default(), status.as_ref()).unwrap();
+        assert_eq!(json, expected);
+
+        // No status: still a complete object
+        let json = encoder.encode(&theme, ContentHash::of(&theme), &accessibility, &options, None, &SlotHints::default(), None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert!(parsed.get("status").is_none());
+    }
+
+    #[test]
+    fn test_slice_help_only_with_descriptions() {
+        let delay = Some(Duration::from_millis(600));
+        let none: Vec<Option<String>> = vec![None; 8];
+        assert_eq!(SliceHelp::build(delay, Some(&none)), None);
+        assert_eq!(SliceHelp::build(delay, None), None);
+        let mut descriptions = none.clone();
+        descriptions[2] = Some("Undo the last edit".to_string());
+        assert_eq!(SliceHelp::build(None, Some(&descriptions)), None);
+
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let help = SliceHelp::build(delay, Some(&descriptions));
+        let expected = MenuPayload::build(&theme, &accessibility).with_help(help.clone()).to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), help.as_ref(), &SlotHints::default(), None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["help"]["delay_ms"], 600);
+        assert_eq!(parsed["help"]["descriptions"][2], "Undo the last edit");
+        assert!(parsed["help"]["descriptions"][0].is_null());
+    }
+
+    #[test]
+    fn test_wheel_axis_slots_reach_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let status = DeviceStatus::build(&battery(80, false, true), None, &theme);
+        let slots = [1u8, 4];
+        let expected = MenuPayload::build(&theme, &accessibility)
+            .with_wheel_axis(Some(&slots))
+            .with_status(status.clone())
+            .to_json();
+        let hints = SlotHints { wheel_axis: Some(&slots), ..Default::default() };
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, status.as_ref())
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["wheel_axis"], serde_json::json!([1, 4]));
+    }
+
+    #[test]
+    fn test_profile_hash_reaches_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let hash = ContentHash(0x1234);
+        let expected = MenuPayload::build(&theme, &accessibility).with_profile_hash(Some(hash)).to_json();
+        let hints = SlotHints { profile_hash: Some(hash), ..Default::default() };
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["profile_hash"], "0000000000001234");
+        assert!(parsed["capabilities"].as_array().unwrap().contains(&"content_hash".into()));
+    }
+
+    #[test]
+    fn test_slot_blocks_follow_the_slice_count() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let mut encoder = PayloadEncoder::new();
+        for count in crate::geometry::SUPPORTED_SLICE_COUNTS {
+            let last = count - 1;
+            let mut descriptions = vec![None; count.into()];
+            descriptions[usize::from(last)] = Some("Last slot".to_string());
+            let help = SliceHelp::build(Some(Duration::from_millis(400)), Some(&descriptions));
+            let slots = [last];
+            let hints = SlotHints {
+                slices: None,
+                wheel_axis: Some(&slots),
+                confirm: SliceConfirm::build(Duration::from_millis(1200), Some(&slots)),
+                cooldown: None,
+                profile_hash: Some(ContentHash(1)),
+                slice_count: Some(count),
+                labels: None,
+                short_labels: None,
+                menu_diameter: None,
+                icons: None,
+            };
+            let expected = MenuPayload::build(&theme, &accessibility)
+                .with_help(help.clone())
+                .with_wheel_axis(hints.wheel_axis)
+                .with_confirm(hints.confirm.clone())
+                .with_profile_hash(hints.profile_hash)
+                .with_slice_count(hints.slice_count)
+                .with_slice_tinting(&theme, SliceTinting::default())
+                .to_json();
+            let json = encoder
+                .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), help.as_ref(), &hints, None)
+                .unwrap();
+            assert_eq!(json, expected);
+            let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+            assert_eq!(parsed["slice_count"], count);
+            assert_eq!(parsed["help"]["descriptions"].as_array().unwrap().len(), usize::from(count));
+            assert_eq!(parsed["confirm"]["slots"][0], last);
+            assert_eq!(parsed["slice_colors"].as_array().unwrap().len(), usize::from(count));
+        }
+        // Ad-hoc menus leave it out; the overlay draws its eight slots
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &SlotHints::default(), None)
+            .unwrap();
+        assert!(!json.contains("slice_count\":"));
+    }
+
+    #[test]
+    fn test_short_labels_reach_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let labels = [None, Some("Toggle Always…".to_string())];
+        let hints = SlotHints { short_labels: Some(&labels), slice_count: Some(8), ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility)
+            .with_slice_count(Some(8))
+            .with_short_labels(Some(&labels))
+            .to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["short_labels"], serde_json::json!([null, "Toggle Always…"]));
+        assert!(parsed["capabilities"].as_array().unwrap().contains(&"short_labels".into()));
+    }
+
+    #[test]
+    fn test_localized_labels_reach_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let labels = [Some("Kopieren".to_string()), None, Some("Einfügen".to_string())];
+        let short = [Some("Kopi…".to_string()), None, None];
+        let hints = SlotHints { labels: Some(&labels), short_labels: Some(&short), ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility)
+            .with_labels(Some(&labels))
+            .with_short_labels(Some(&short))
+            .to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["labels"], serde_json::json!(["Kopieren", null, "Einfügen"]));
+        assert!(parsed["capabilities"].as_array().unwrap().contains(&"localized_labels".into()));
+    }
+
+    #[test]
+    fn test_menu_diameter_reaches_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let monitor = crate::geometry::Monitor { x: 0, y: 0, width: 1366, height: 768, scale: 1.0, compositor_scaled: true };
+        let diameter = crate::geometry::MenuSize::Percent(30.0).diameter(Some(&monitor));
+        let hints = SlotHints { menu_diameter: diameter, ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility).with_menu_diameter(diameter).to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["menu_diameter"], 230);
+        // `auto` leaves the ring to the overlay
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &SlotHints::default(), None)
+            .unwrap();
+        assert!(!json.contains("menu_diameter"));
+    }
+
+    #[test]
+    fn test_icons_reach_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("term.svg"), r#"<svg viewBox="0 0 32 16"/>"#).unwrap();
+        let mut resolver = crate::icon_resolver::IconResolver::new(dir.path().to_path_buf());
+        let info = resolver.resolve("term.svg").unwrap();
+        let box_px = crate::icon_resolver::raster_box(Some(600), 8, None);
+        let icons = [None, Some(info.hint(box_px))];
+        let hints = SlotHints { icons: Some(&icons), ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility).with_icons(hints.icons).to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["icons"][0], serde_json::Value::Null);
+        assert_eq!(parsed["icons"][1]["format"], "svg");
+        assert_eq!(parsed["icons"][1]["width"], 32);
+        assert_eq!(parsed["icons"][1]["raster"], serde_json::json!([60, 30]));
+        assert_eq!(parsed["icons"][1]["hash"], info.hash.to_string());
+    }
+
+    #[test]
+    fn test_slice_colors_follow_tinting() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let mut encoder = PayloadEncoder::new();
+        let auto = PayloadOptions { slice_tinting: SliceTinting::Auto, ..Default::default() };
+        let hints = SlotHints { slice_count: Some(12), ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility)
+            .with_slice_count(Some(12))
+            .with_slice_tinting(&theme, SliceTinting::Auto)
+            .to_json();
+        let json = encoder.encode(&theme, ContentHash::of(&theme), &accessibility, &auto, None, &hints, None).unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        let colors = parsed["slice_colors"].as_array().unwrap();
+        assert_eq!(colors.len(), 12);
+        assert_ne!(colors[0], colors[1]);
+        assert!(parsed["capabilities"].as_array().unwrap().contains(&"slice_colors".into()));
+
+        // Off still sends one (surface) color per slice
+        let off = PayloadOptions { slice_tinting: SliceTinting::Off, ..Default::default() };
+        let json = encoder.encode(&theme, ContentHash::of(&theme), &accessibility, &off, None, &SlotHints::default(), None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["slice_colors"], serde_json::json!(vec![theme.colors.surface.clone(); 8]));
+
+        // A new accent regenerates the cached tints
+        let mut recolored = theme.clone();
+        recolored.colors.accent = "#f38ba8".to_string();
+        let json = encoder.encode(&recolored, ContentHash::of(&recolored), &accessibility, &auto, None, &hints, None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_ne!(parsed["slice_colors"][0], colors[0]);
+    }
+
+    #[test]
+    fn test_confirm_slots_reach_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let window = Duration::from_millis(1200);
+        assert_eq!(SliceConfirm::build(window, Some(&[])), None);
+        let slots = [6u8];
+        let hints = SlotHints { confirm: SliceConfirm::build(window, Some(&slots)), ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility).with_confirm(hints.confirm.clone()).to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["confirm"], serde_json::json!({"window_ms": 1200, "slots": [6]}));
+        assert!(parsed["capabilities"].as_array().unwrap().contains(&"slice_confirmation".into()));
+    }
+
+    #[test]
+    fn test_cooling_slots_reach_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let cooling = [CoolingSlot { slot: 3, remaining_ms: 420 }];
+        let hints = SlotHints { cooldown: Some(&cooling), ..Default::default() };
+        let expected = MenuPayload::build(&theme, &accessibility).with_cooldown(hints.cooldown).to_json();
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder
+            .encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &hints, None)
+            .unwrap();
+        assert_eq!(json, expected);
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["cooldown"], serde_json::json!([{"slot": 3, "remaining_ms": 420}]));
+        assert!(parsed["capabilities"].as_array().unwrap().contains(&"cooldown".into()));
+    }
+
+    #[test]
+    fn test_encoder_rebuilds_when_inputs_change() {
+        let mocha = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let plain = PayloadOptions::default();
+        let mut encoder = PayloadEncoder::new();
+        encoder.encode(&mocha, ContentHash::of(&mocha), &accessibility, &plain, None, &SlotHints::default(), None);
+        assert!(encoder.is_current(&mocha, ContentHash::of(&mocha), &accessibility, &plain));
+        for changed in [
+            PayloadOptions { left_handed: true, ..plain },
+            PayloadOptions { success_flash: Some(true), ..plain },
+            PayloadOptions { blur_available: false, ..plain },
+        ] {
+            assert!(!encoder.is_current(&mocha, ContentHash::of(&mocha), &accessibility, &changed));
+        }
+
+        let flash = PayloadOptions { success_flash: Some(false), ..plain };
+        let mut edited = mocha.clone();
+        edited.colors.error = "#ff0000".to_string();
+        encoder.encode(&mocha, ContentHash::of(&mocha), &accessibility, &flash, None, &SlotHints::default(), None);
+        assert!(!encoder.is_current(&edited, ContentHash::of(&edited), &accessibility, &flash));
+
+        let json = encoder.encode(&edited, ContentHash::of(&edited), &accessibility, &flash, None, &SlotHints::default(), None).unwrap();
+        assert!(json.contains("#ff0000"));
+
+        // A color the payload does not carry still changes its theme hash
+        let mut recolored = edited.clone();
+        recolored.colors.accent = "#00ff00".to_string();
+        assert!(!encoder.is_current(&recolored, ContentHash::of(&recolored), &accessibility, &flash));
+        let json = encoder.encode(&recolored, ContentHash::of(&recolored), &accessibility, &flash, None, &SlotHints::default(), None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["theme_hash"], ContentHash::of(&recolored).to_string());
+
+        let mut high_contrast = accessibility.clone();
+        high_contrast.set_high_contrast(Some(true));
+        assert!(!encoder.is_current(&edited, ContentHash::of(&edited), &high_contrast, &flash));
+    }
+
+    #[test]
+    fn test_payload_carries_the_system_accent() {
+        let mocha = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let mut encoder = PayloadEncoder::new();
+        let plain = PayloadOptions { slice_tinting: SliceTinting::Auto, ..Default::default() };
+        let json = encoder.encode(&mocha, ContentHash::of(&mocha), &accessibility, &plain, None, &SlotHints::default(), None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert!(parsed.get("accent").is_none());
+        let own_tints = parsed["slice_colors"].clone();
+
+        // What the theme manager hands out with an accent override
+        let accented = mocha.with_accent("#3daee9");
+        let options = PayloadOptions { accent: Rgba::from_hex("#3daee9"), ..plain };
+        assert!(!encoder.is_current(&accented, ContentHash::of(&accented), &accessibility, &options));
+        let json = encoder.encode(&accented, ContentHash::of(&accented), &accessibility, &options, None, &SlotHints::default(), None).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed["accent"], "#3daee9");
+        assert_eq!(parsed["theme_hash"], ContentHash::of(&accented).to_string());
+        assert_ne!(parsed["slice_colors"], own_tints, "auto tints follow the accent");
+        assert!(PAYLOAD_CAPABILITIES.contains(&"system_accent"));
+    }
+
+    #[test]
+    fn test_idle_effect_follows_limits_and_reduced_motion() {
+        let mut theme = Theme::catppuccin_mocha();
+        theme.animation.idle_effect = "matrix-rain".to_string();
+        theme.animation.idle_density = 0.9;
+        let mut accessibility = AccessibilitySettings::default();
+        accessibility.set_reduced_motion(Some(false));
+        let mut encoder = PayloadEncoder::new();
+        let mut encode = |accessibility: &AccessibilitySettings, limits: IdleEffectLimits| -> serde_json::Value {
+            let options = PayloadOptions { idle_limits: limits, ..Default::default() };
+            let json = encoder.encode(&theme, ContentHash::of(&theme), accessibility, &options, None, &SlotHints::default(), None).unwrap();
+            serde_json::from_str(json).unwrap()
+        };
+
+        let capped = IdleEffectLimits { enabled: true, max_fps: 15, max_density: 0.5 };
+        let json = encode(&accessibility, capped);
+        assert_eq!(json["idle_effect"]["type"], "matrix-rain");
+        assert_eq!(json["idle_effect"]["max_fps"], 15);
+        assert_eq!(json["idle_effect"]["density"], 0.5);
+        assert_eq!(json["animation"]["idle_effects_enabled"], true);
+
+        // On battery: the prefix is rebuilt and the effect is off throughout
+        let json = encode(&accessibility, IdleEffectLimits { enabled: false, ..capped });
+        assert_eq!(json["idle_effect"]["type"], "none");
+        assert_eq!(json["animation"]["idle_effects_enabled"], false);
+
+        accessibility.set_reduced_motion(Some(true));
+        let json = encode(&accessibility, capped);
+        assert_eq!(json["idle_effect"]["type"], "none");
+        assert_eq!(json["animation"]["idle_effects_enabled"], false);
+    }
+
+    #[test]
+    fn test_background_opacity_floor_without_blur() {
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let payload = MenuPayload::build(&theme, &accessibility);
+        assert!(payload.blur_available);
+        assert_eq!(payload.background_opacity, 0.75);
+
+        let payload = payload.with_blur(false, NO_BLUR_MIN_OPACITY);
+        assert!(!payload.blur_available);
+        assert_eq!(payload.background_opacity, NO_BLUR_MIN_OPACITY);
+        assert_eq!(payload.packed_colors.glassmorphism[1], NO_BLUR_MIN_OPACITY);
+
+        // Already above the floor (high contrast): unchanged
+        let mut high_contrast = accessibility.clone();
+        high_contrast.set_high_contrast(Some(true));
+        let payload = MenuPayload::build(&theme, &high_contrast).with_blur(false, NO_BLUR_MIN_OPACITY);
+        assert_eq!(payload.background_opacity, 0.95);
+        assert_eq!(payload.packed_colors, theme.get_packed_colors(true));
+    }
+
+    #[test]
+    fn test_background_texture_reaches_payload() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("grain.svg"), "<svg xmlns=\"http://www.w3.org/2000/svg\"/>").unwrap();
+        let mut theme = Theme::catppuccin_mocha();
+        theme.glassmorphism.background_texture = Some("grain.svg".to_string());
+        assert!(theme.resolve_background_texture(dir.path()).is_none());
+
+        let mut accessibility = AccessibilitySettings::default();
+        let json: serde_json::Value = serde_json::from_str(&MenuPayload::build(&theme, &accessibility).to_json()).unwrap();
+        let texture = &json["background_texture"];
+        assert_eq!(texture["path"], dir.path().canonicalize().unwrap().join("grain.svg").to_str().unwrap());
+        assert_eq!(texture["format"], "svg");
+        assert_eq!(texture["hash"].as_str().unwrap().len(), 16);
+        assert_ne!(ContentHash::of(&theme), ContentHash::of(&Theme::catppuccin_mocha()));
+
+        // High contrast paints solid slices
+        accessibility.set_high_contrast(Some(true));
+        assert!(MenuPayload::build(&theme, &accessibility).background_texture.is_none());
+    }
+
+    #[test]
+    fn test_high_contrast_style_reaches_payload() {
+        let theme = Theme::catppuccin_mocha();
+        let mut accessibility = AccessibilitySettings::default();
+        let json: serde_json::Value = serde_json::from_str(&MenuPayload::build(&theme, &accessibility).to_json()).unwrap();
+        assert!(json.get("high_contrast").is_none());
+
+        accessibility.set_system_high_contrast(true);
+        let mut encoder = PayloadEncoder::new();
+        let json = encoder.encode(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default(), None, &SlotHints::default(), None).unwrap();
+        let json: serde_json::Value = serde_json::from_str(json).unwrap();
+        let style = &json["high_contrast"];
+        assert_eq!(style["text_color"], "#ffffff");
+        assert_eq!(style["selection_border_color"], "#ffffff");
+        assert_eq!(style["selection_border_width"], 3);
+        assert_eq!(json["background_opacity"], 0.95);
+
+        // The config override wins over the system preference
+        accessibility.set_high_contrast(Some(false));
+        assert!(!encoder.is_current(&theme, ContentHash::of(&theme), &accessibility, &PayloadOptions::default()));
+    }
+
+    #[test]
+    fn test_feedback_uses_theme_colors() {
+        let theme = Theme::catppuccin_mocha();
+        let payload = MenuPayload::build(&theme, &AccessibilitySettings::default())
+            .with_feedback(Some(ActionFeedback::build(&theme, false)));
+        let json: serde_json::Value = serde_json::from_str(&payload.to_json()).unwrap();
+        assert_eq!(json["feedback"]["error_color"], theme.colors.error.as_str());
+        assert_eq!(json["feedback"]["hold_ms"], FEEDBACK_HOLD_MS);
+        assert!(json["feedback"].get("success_color").is_none());
+
+        let feedback = ActionFeedback::build(&theme, true);
+        assert_eq!(feedback.success_color.as_deref(), Some(theme.colors.success.as_str()));
+    }
+
+    fn battery(percentage: u8, charging: bool, available: bool) -> BatteryState {
+        BatteryState {
+            percentage,
+            charging,
+            available,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_status_omitted_without_battery() {
+        let theme = Theme::catppuccin_mocha();
+        assert!(DeviceStatus::build(&battery(80, false, false), Some(ConnectionType::Bolt), &theme).is_none());
+    }
+
+    #[test]
+    fn test_status_colors_follow_thresholds() {
+        let theme = Theme::catppuccin_mocha();
+        let color = |b: BatteryState| DeviceStatus::build(&b, None, &theme).unwrap().battery_color;
+        assert_eq!(color(battery(80, false, true)), theme.colors.success);
+        assert_eq!(color(battery(BATTERY_LOW_PERCENT, false, true)), theme.colors.warning);
+        assert_eq!(color(battery(BATTERY_CRITICAL_PERCENT, false, true)), theme.colors.error);
+        assert_eq!(color(battery(5, true, true)), theme.colors.success);
+    }
+
+    #[test]
+    fn test_status_json_shape() {
+        let theme = Theme::catppuccin_mocha();
+        let status = DeviceStatus::build(&battery(55, true, true), Some(ConnectionType::Bluetooth), &theme);
+        let payload = MenuPayload::build(&theme, &AccessibilitySettings::default()).with_status(status);
+        let json: serde_json::Value = serde_json::from_str(&payload.to_json()).unwrap();
+        assert_eq!(json["status"]["battery_percent"], 55);
+        assert_eq!(json["status"]["charging"], true);
+        assert_eq!(json["status"]["connection"], "Bluetooth");
+    }
+}
@@ -0,0 +1,245 @@
+//! Dry run of the radial menu pipeline (`SimulateMenu`)
+//!
+//! Resolves the profile for a window class, builds the payload the overlay
+//! would receive and optionally runs one slice's action, all without a mouse,
+//! so profiles can be exercised over SSH or in CI.
+
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::accessibility::AccessibilitySettings;
+use crate::actions::{Action, ActionExecutor};
+use crate::execution_policy::ExecutionPolicy;
+use crate::geometry::slot_for_position_in;
+use crate::handles::DaemonHandles;
+use crate::invocation::InvocationContext;
+use crate::menu_payload::MenuPayload;
+use crate::profiles::{validate_icon_reference, ProfileManager};
+use crate::slice_tint::SliceTinting;
+use crate::theme::Theme;
+
+/// Slice direction labels, indexed like `Profile::slices`
+pub const SLICE_DIRECTIONS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// Labels of the four- and six-slice rings
+const FOUR_DIRECTIONS: [&str; 4] = ["N", "E", "S", "W"];
+const SIX_DIRECTIONS: [&str; 6] = ["N", "NE", "SE", "S", "SW", "NW"];
+
+/// Labels of the 12-slice clock layout
+const CLOCK_DIRECTIONS: [&str; 12] = [
+    "12 o'clock", "1 o'clock", "2 o'clock", "3 o'clock", "4 o'clock", "5 o'clock",
+    "6 o'clock", "7 o'clock", "8 o'clock", "9 o'clock", "10 o'clock", "11 o'clock",
+];
+
+/// Label of ring position `position` on a `count`-slice ring
+pub fn direction(position: u8, count: u8) -> &'static str {
+    let labels: &[&'static str] = match count {
+        4 => &FOUR_DIRECTIONS,
+        6 => &SIX_DIRECTIONS,
+        12 => &CLOCK_DIRECTIONS,
+        _ => &SLICE_DIRECTIONS,
+    };
+    labels[usize::from(position) % labels.len()]
+}
+
+/// One slice as the overlay would show it
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulatedSlice {
+    /// Profile slot
+    pub index: u8,
+    /// Where the slot is drawn (mirrored with `input.left_handed`)
+    pub direction: &'static str,
+    /// `None` for an empty slice
+    pub action: Option<Action>,
+    /// Whether the action's icon reference resolves; omitted without an icon
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_valid: Option<bool>,
+}
+
+/// Outcome of running one slice's action
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionReport {
+    pub slice: u8,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Time until the executor returned (spawned commands are not awaited)
+    pub elapsed_us: u64,
+}
+
+/// Everything one simulated invocation produced
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    pub window_class: String,
+    /// Name of the resolved profile (`default` when no mapping matched)
+    pub profile: String,
+    pub payload: MenuPayload<'static>,
+    pub slices: Vec<SimulatedSlice>,
+    /// Present when a slice was selected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution: Option<ExecutionReport>,
+}
+
+impl SimulationReport {
+    /// Serialize for the D-Bus reply
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Resolve the profile and build the payload; no action runs
+///
+/// `slice` must be -1 (inspect only) or a slot of the resolved profile
+/// (0-7 on the default ring).
+pub fn plan_menu(
+    profiles: &ProfileManager,
+    window_class: &str,
+    slice: i32,
+    theme: &Theme,
+    accessibility: &AccessibilitySettings,
+    left_handed: bool,
+) -> Result<SimulationReport, SimulationError> {
+    let profile = profiles.get_profile_for_window(window_class);
+    let count = profile.slice_count;
+    if !(-1..i32::from(count)).contains(&slice) {
+        return Err(SimulationError::InvalidSlice { slice, count });
+    }
+    let slices = profile
+        .slices
+        .iter()
+        .enumerate()
+        .map(|(i, action)| SimulatedSlice {
+            index: i as u8,
+            direction: direction(slot_for_position_in(i as u8, left_handed, count), count),
+            icon_valid: action
+                .as_ref()
+                .and_then(|a| a.icon.as_deref())
+                .map(validate_icon_reference),
+            action: action.clone(),
+        })
+        .collect();
+    Ok(SimulationReport {
+        window_class: window_class.to_string(),
+        profile: profile.name.clone(),
+        payload: MenuPayload::build(theme, accessibility)
+            .with_left_handed(left_handed)
+            .with_slice_count(Some(count))
+            .with_slice_tinting(theme, SliceTinting::default()),
+        slices,
+        execution: None,
+    })
+}
+
+/// [`plan_menu`], then execute the selected slice's action under `policy`,
+/// recording it in the latency histograms of `handles`
+#[allow(clippy::too_many_arguments)]
+pub async fn simulate_menu(
+    profiles: &ProfileManager,
+    window_class: &str,
+    slice: i32,
+    theme: &Theme,
+    accessibility: &AccessibilitySettings,
+    left_handed: bool,
+    policy: &ExecutionPolicy,
+    handles: &DaemonHandles,
+) -> Result<SimulationReport, SimulationError> {
+    let mut report = plan_menu(profiles, window_class, slice, theme, accessibility, left_handed)?;
+    let Ok(index) = u8::try_from(slice) else {
+        return Ok(report);
+    };
+    let context = InvocationContext::now()
+        .in_window(window_class, Some(&report.profile))
+        .with_slice(index);
+    let start = Instant::now();
+    let result = match &report.slices[index as usize].action {
+        Some(action) => ActionExecutor::execute_with_policy(action, policy, &context, handles)
+            .await
+            .map_err(|e| e.to_string()),
+        None => Err("slice has no action".to_string()),
+    };
+    tracing::info!(
+        window_class,
+        profile = %report.profile,
+        slice = index,
+        ok = result.is_ok(),
+        "Simulated menu selection"
+    );
+    report.execution = Some(ExecutionReport {
+        slice: index,
+        ok: result.is_ok(),
+        error: result.err(),
+        elapsed_us: start.elapsed().as_micros() as u64,
+    });
+    Ok(report)
+}
+
+/// Simulation error type
+#[derive(Debug, thiserror::Error)]
+pub enum SimulationError {
+    /// Slice index outside -1 and the profile's slots
+    #[error("Invalid slice {slice} (expected -1 or 0-{})", .count - 1)]
+    InvalidSlice { slice: i32, count: u8 },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_lists_default_profile() {
+        let profiles = ProfileManager::new();
+        let report = plan_menu(
+            &profiles,
+            "org.kde.dolphin",
+            -1,
+            &Theme::catppuccin_mocha(),
+            &AccessibilitySettings::default(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(report.profile, "default");
+        assert_eq!(report.slices.len(), 8);
+        assert_eq!(report.slices[2].direction, "E");
+        assert!(report.execution.is_none());
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["payload"]["theme"], "catppuccin-mocha");
+        assert!(json.get("execution").is_none());
+    }
+
+    #[test]
+    fn test_plan_mirrors_directions_for_left_hand() {
+        let profiles = ProfileManager::new();
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        let report = plan_menu(&profiles, "x", -1, &theme, &accessibility, true).unwrap();
+        // Slot 1 (the profile's NE action) is drawn at NW
+        assert_eq!(report.slices[1].index, 1);
+        assert_eq!(report.slices[1].direction, "NW");
+        assert_eq!(report.slices[0].direction, "N");
+        assert!(report.payload.left_handed);
+    }
+
+    #[test]
+    fn test_slice_out_of_range_rejected() {
+        let profiles = ProfileManager::new();
+        let theme = Theme::catppuccin_mocha();
+        let accessibility = AccessibilitySettings::default();
+        for slice in [-2, 8] {
+            let err = plan_menu(&profiles, "x", slice, &theme, &accessibility, false).unwrap_err();
+            assert!(err.to_string().contains(&slice.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_directions_follow_the_slice_count() {
+        assert_eq!(direction(2, 8), "E");
+        assert_eq!(direction(1, 4), "E");
+        assert_eq!(direction(3, 6), "S");
+        assert_eq!(direction(3, 12), "3 o'clock");
+        assert_eq!(direction(0, 12), "12 o'clock");
+        // Mirrored clock: slot 1 is drawn at 11 o'clock
+        assert_eq!(direction(slot_for_position_in(1, true, 12), 12), "11 o'clock");
+    }
+}
@@ -0,0 +1,141 @@
+//! Arbitration between menu triggers
+//!
+//! The radial menu can be opened by several sources (`triggers` in config):
+//! the MX gesture button, a button code on any pointer device, and KDE global
+//! shortcuts. Each source tracks its own press/release, but they all feed the
+//! one menu state machine through `GestureEvent::Pressed`/`Released`.
+//!
+//! `TriggerArbiter` decides which trigger owns the open menu. The first press
+//! wins; presses of other triggers while it is held are ignored, and so are
+//! their releases, so holding two triggers at once can neither reopen the
+//! menu nor confirm it early. Only the owner's release closes the menu. A hold
+//! whose release was lost (device unplugged mid-press) stops blocking other
+//! triggers after `STALE_HOLD`.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A held trigger older than this no longer blocks other triggers
+pub const STALE_HOLD: Duration = Duration::from_secs(30);
+
+/// Identity of one configured trigger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriggerId {
+    /// MX gesture button (HID++ divert or evdev fallback)
+    MxGesture,
+    /// Button code on a pointer device
+    EvdevButton(u16),
+    /// Global shortcut, by position among the configured shortcuts
+    Shortcut(u8),
+}
+
+impl std::fmt::Display for TriggerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerId::MxGesture => write!(f, "mx-gesture-button"),
+            TriggerId::EvdevButton(code) => write!(f, "evdev-button {:#x}", code),
+            TriggerId::Shortcut(index) => write!(f, "shortcut #{}", index),
+        }
+    }
+}
+
+/// Which trigger currently holds the menu open
+#[derive(Debug, Default)]
+pub struct TriggerArbiter {
+    owner: Option<(TriggerId, Instant)>,
+}
+
+impl TriggerArbiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A trigger went down; `true` when it now owns the menu and should open it
+    pub fn press(&mut self, id: TriggerId, now: Instant) -> bool {
+        match self.owner {
+            Some((owner, since)) if owner != id && now.duration_since(since) < STALE_HOLD => false,
+            _ => {
+                self.owner = Some((id, now));
+                true
+            }
+        }
+    }
+
+    /// A trigger went up; `true` when it owned the menu and should close it
+    pub fn release(&mut self, id: TriggerId) -> bool {
+        if self.owner.is_some_and(|(owner, _)| owner == id) {
+            self.owner = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Trigger currently holding the menu
+    pub fn owner(&self) -> Option<TriggerId> {
+        self.owner.map(|(id, _)| id)
+    }
+}
+
+/// Arbiter shared by every trigger source
+pub type SharedTriggerArbiter = Arc<Mutex<TriggerArbiter>>;
+
+/// Create a new shared arbiter
+pub fn new_shared_arbiter() -> SharedTriggerArbiter {
+    Arc::new(Mutex::new(TriggerArbiter::new()))
+}
+
+/// Press `id` on a shared arbiter (a poisoned lock never blocks the menu)
+pub fn claim(arbiter: &SharedTriggerArbiter, id: TriggerId) -> bool {
+    arbiter
+        .lock()
+        .map(|mut a| a.press(id, Instant::now()))
+        .unwrap_or(true)
+}
+
+/// Release `id` on a shared arbiter
+pub fn release(arbiter: &SharedTriggerArbiter, id: TriggerId) -> bool {
+    arbiter.lock().map(|mut a| a.release(id)).unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_trigger_owns_menu() {
+        let mut arbiter = TriggerArbiter::new();
+        let now = Instant::now();
+        let button = TriggerId::EvdevButton(0x113);
+        assert!(arbiter.press(TriggerId::MxGesture, now));
+        assert!(!arbiter.press(button, now));
+        // The ignored trigger's release must not confirm the menu
+        assert!(!arbiter.release(button));
+        assert_eq!(arbiter.owner(), Some(TriggerId::MxGesture));
+        assert!(arbiter.release(TriggerId::MxGesture));
+        assert_eq!(arbiter.owner(), None);
+        // Released owner frees the menu for the other trigger
+        assert!(arbiter.press(button, now));
+    }
+
+    #[test]
+    fn test_same_trigger_repress_keeps_ownership() {
+        let mut arbiter = TriggerArbiter::new();
+        let now = Instant::now();
+        assert!(arbiter.press(TriggerId::Shortcut(0), now));
+        assert!(arbiter.press(TriggerId::Shortcut(0), now));
+        assert!(!arbiter.press(TriggerId::Shortcut(1), now));
+        assert!(arbiter.release(TriggerId::Shortcut(0)));
+    }
+
+    #[test]
+    fn test_stale_hold_can_be_taken_over() {
+        let mut arbiter = TriggerArbiter::new();
+        let start = Instant::now();
+        assert!(arbiter.press(TriggerId::MxGesture, start));
+        assert!(arbiter.press(TriggerId::Shortcut(0), start + STALE_HOLD));
+        // The lost hold's late release is ignored
+        assert!(!arbiter.release(TriggerId::MxGesture));
+        assert_eq!(arbiter.owner(), Some(TriggerId::Shortcut(0)));
+    }
+}
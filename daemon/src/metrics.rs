@@ -0,0 +1,518 @@
+//! Prometheus metrics endpoint (`telemetry.metrics_port`)
+//!
+//! Built with the `metrics` Cargo feature, which is off by default. With a
+//! port set, the daemon answers `GET /metrics` on 127.0.0.1 only, without
+//! auth, in the Prometheus text exposition format: menu invocations, action,
+//! menu-open and haptic latency histograms, haptic send failures, battery
+//! level, and HID++ connection state changes. The responder is a few lines
+//! over a tokio `TcpListener` rather than an HTTP stack: it reads one request
+//! head, answers, and closes the connection.
+//!
+//! The device counters are always built, so the haptic manager records into
+//! them unconditionally; they are plain atomics that nothing reads until a
+//! scrape. Histograms come from `action_latency` (so `ResetActionLatency()`
+//! resets them like a restart would) and menu invocations from the
+//! `late_focus` open count.
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use crate::action_latency::{ActionKind, LatencyHistogram, LatencyStats, ShortcutPhase};
+use crate::battery::SharedBatteryState;
+use crate::handles::DaemonHandles;
+use crate::hidpp::ConnectionState;
+use crate::late_focus::LateFocusStats;
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// ============================================================================
+// Device counters
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct DeviceCounters {
+    /// Times each state was entered, in `ConnectionState::ALL` order
+    entered: [AtomicU64; ConnectionState::ALL.len()],
+    reconnects: AtomicU64,
+    haptic_failures: AtomicU64,
+    /// Index of the current state in `ConnectionState::ALL`
+    state: AtomicUsize,
+}
+
+fn state_index(state: ConnectionState) -> usize {
+    ConnectionState::ALL.iter().position(|&s| s == state).unwrap_or(0)
+}
+
+/// The device counters and the endpoint address, shared through `DaemonHandles`
+///
+/// The haptic manager records into a clone; scrapes and `Status()` read it.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsHandle {
+    device: Arc<DeviceCounters>,
+    /// Where the endpoint listens, while it runs
+    listening: Arc<Mutex<Option<SocketAddr>>>,
+}
+
+impl MetricsHandle {
+    /// The HID++ connection moved from `from` to `to`
+    pub fn record_connection_change(&self, from: ConnectionState, to: ConnectionState) {
+        self.device.entered[state_index(to)].fetch_add(1, Ordering::Relaxed);
+        self.device.state.store(state_index(to), Ordering::Relaxed);
+        let lost = matches!(
+            from,
+            ConnectionState::Disconnected | ConnectionState::Cooldown | ConnectionState::SwitchedAway
+        );
+        if to == ConnectionState::Connected && lost {
+            self.device.reconnects.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// A haptic pulse or pattern could not be written to the device
+    pub fn record_haptic_failure(&self) {
+        self.device.haptic_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Copy of the device counters
+    pub fn device_counts(&self) -> DeviceCounts {
+        DeviceCounts {
+            entered: std::array::from_fn(|i| self.device.entered[i].load(Ordering::Relaxed)),
+            reconnects: self.device.reconnects.load(Ordering::Relaxed),
+            haptic_failures: self.device.haptic_failures.load(Ordering::Relaxed),
+            state: ConnectionState::ALL[self.device.state.load(Ordering::Relaxed)],
+        }
+    }
+
+    /// Endpoint state for `Status()`
+    pub fn status(&self) -> Value {
+        let address = self.listening.lock().ok().and_then(|a| *a);
+        json!({
+            "built": cfg!(feature = "metrics"),
+            "running": address.is_some(),
+            "address": address.map(|a| a.to_string()),
+        })
+    }
+}
+
+/// Copy of the device counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceCounts {
+    pub entered: [u64; ConnectionState::ALL.len()],
+    pub reconnects: u64,
+    pub haptic_failures: u64,
+    pub state: ConnectionState,
+}
+
+// ============================================================================
+// Exposition
+// ============================================================================
+
+/// Battery reading at scrape time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryGauge {
+    pub percentage: u8,
+    pub charging: bool,
+}
+
+/// Everything one scrape reports
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub latency: LatencyStats,
+    pub menus: LateFocusStats,
+    pub device: DeviceCounts,
+    /// `None` while battery info is unavailable
+    pub battery: Option<BatteryGauge>,
+}
+
+impl Snapshot {
+    pub async fn collect(battery: &SharedBatteryState, handles: &DaemonHandles) -> Self {
+        let battery = {
+            let state = battery.read().await;
+            state.available.then_some(BatteryGauge { percentage: state.percentage, charging: state.charging })
+        };
+        Self {
+            latency: handles.latency.snapshot(),
+            menus: handles.late_focus.stats(),
+            device: handles.metrics.device_counts(),
+            battery,
+        }
+    }
+}
+
+/// `snapshot` in the Prometheus text exposition format
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    family(&mut out, "juhradial_build_info", "gauge", "Daemon version");
+    let _ = writeln!(out, "juhradial_build_info{{version=\"{}\"}} 1", env!("CARGO_PKG_VERSION"));
+
+    family(&mut out, "juhradial_menu_invocations_total", "counter", "Profile menus opened with ShowMenu");
+    let _ = writeln!(out, "juhradial_menu_invocations_total {}", snapshot.menus.opens);
+
+    let latency = &snapshot.latency;
+    let actions: Vec<_> = ActionKind::ALL
+        .iter()
+        .map(|&k| (Some(("type", k.as_str())), latency.histogram(k)))
+        .collect();
+    histogram(&mut out, "juhradial_action_latency_seconds", "Action start latency by action type", &actions);
+    let phases: Vec<_> = ShortcutPhase::ALL
+        .iter()
+        .map(|&p| (Some(("phase", p.as_str())), latency.phase(p)))
+        .collect();
+    histogram(&mut out, "juhradial_shortcut_phase_latency_seconds", "Shortcut execution time by phase", &phases);
+    histogram(&mut out, "juhradial_menu_open_latency_seconds", "ShowMenu until the menu payload is sent", &[(None, latency.menu_open())]);
+    histogram(&mut out, "juhradial_haptic_send_latency_seconds", "Haptic event until its pulse is written", &[(None, latency.haptic_send())]);
+
+    let device = &snapshot.device;
+    family(&mut out, "juhradial_haptic_failures_total", "counter", "Haptic pulses the device did not take");
+    let _ = writeln!(out, "juhradial_haptic_failures_total {}", device.haptic_failures);
+    family(&mut out, "juhradial_device_connection_state", "gauge", "HID++ connection state (1 = current)");
+    for state in ConnectionState::ALL {
+        let _ = writeln!(
+            out,
+            "juhradial_device_connection_state{{state=\"{}\"}} {}",
+            state.as_str(),
+            u8::from(state == device.state)
+        );
+    }
+    family(&mut out, "juhradial_device_connection_changes_total", "counter", "HID++ connection state changes by new state");
+    for (state, count) in ConnectionState::ALL.iter().zip(device.entered) {
+        let _ = writeln!(out, "juhradial_device_connection_changes_total{{state=\"{}\"}} {}", state.as_str(), count);
+    }
+    family(&mut out, "juhradial_device_reconnects_total", "counter", "HID++ connections regained after a loss");
+    let _ = writeln!(out, "juhradial_device_reconnects_total {}", device.reconnects);
+
+    family(&mut out, "juhradial_battery_available", "gauge", "Whether battery info is available");
+    let _ = writeln!(out, "juhradial_battery_available {}", u8::from(snapshot.battery.is_some()));
+    if let Some(battery) = snapshot.battery {
+        family(&mut out, "juhradial_battery_percent", "gauge", "Battery level");
+        let _ = writeln!(out, "juhradial_battery_percent {}", battery.percentage);
+        family(&mut out, "juhradial_battery_charging", "gauge", "Whether the device is charging");
+        let _ = writeln!(out, "juhradial_battery_charging {}", u8::from(battery.charging));
+    }
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// One histogram family; each series has at most one label
+fn histogram(out: &mut String, name: &str, help: &str, series: &[(Option<(&str, &str)>, &LatencyHistogram)]) {
+    family(out, name, "histogram", help);
+    for (label, h) in series {
+        let label = label.map(|(k, v)| format!("{}=\"{}\"", k, v));
+        let with = |extra: Option<String>| {
+            let labels: Vec<String> = label.iter().cloned().chain(extra).collect();
+            if labels.is_empty() {
+                String::new()
+            } else {
+                format!("{{{}}}", labels.join(","))
+            }
+        };
+        for (bound_us, count) in h.cumulative_buckets() {
+            let le = bound_us.map_or_else(|| "+Inf".to_string(), |us| (us as f64 / 1e6).to_string());
+            let _ = writeln!(out, "{}_bucket{} {}", name, with(Some(format!("le=\"{}\"", le))), count);
+        }
+        let _ = writeln!(out, "{}_sum{} {}", name, with(None), h.sum_us() as f64 / 1e6);
+        let _ = writeln!(out, "{}_count{} {}", name, with(None), h.count());
+    }
+}
+
+// ============================================================================
+// HTTP responder
+// ============================================================================
+
+/// What a request head asks for
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Metrics,
+    NotFound,
+    MethodNotAllowed,
+    BadRequest,
+}
+
+#[cfg(feature = "metrics")]
+fn route(head: &str) -> Route {
+    let mut parts = head.lines().next().unwrap_or("").split(' ');
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Route::BadRequest;
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Route::BadRequest;
+    }
+    if method != "GET" {
+        return Route::MethodNotAllowed;
+    }
+    match target.split('?').next() {
+        Some("/metrics") => Route::Metrics,
+        _ => Route::NotFound,
+    }
+}
+
+/// Serve `/metrics` on 127.0.0.1:`port` until the task is cancelled
+#[cfg(feature = "metrics")]
+pub async fn run(port: u16, battery: SharedBatteryState, handles: DaemonHandles) {
+    match tokio::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, port)).await {
+        Ok(listener) => serve(listener, battery, handles).await,
+        Err(e) => tracing::warn!(port, error = %e, "Cannot bind the metrics endpoint; it stays off"),
+    }
+}
+
+/// Without the `metrics` feature there is nothing to serve
+#[cfg(not(feature = "metrics"))]
+pub async fn run(port: u16, _battery: SharedBatteryState, _handles: DaemonHandles) {
+    tracing::warn!(port, "telemetry.metrics_port is set but this build has no metrics feature; the endpoint stays off");
+}
+
+#[cfg(feature = "metrics")]
+pub use responder::serve;
+
+#[cfg(feature = "metrics")]
+mod responder {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::{render, route, MetricsHandle, Route, Snapshot, CONTENT_TYPE};
+    use crate::battery::SharedBatteryState;
+    use crate::handles::DaemonHandles;
+
+    /// A client gets this long to send its request head
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Request heads above this are refused
+    const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+    /// Clears the `Status()` address when the endpoint stops
+    struct Listening(MetricsHandle);
+
+    impl Drop for Listening {
+        fn drop(&mut self) {
+            if let Ok(mut address) = self.0.listening.lock() {
+                *address = None;
+            }
+        }
+    }
+
+    /// Answer connections on `listener` until the task is cancelled
+    pub async fn serve(listener: TcpListener, battery: SharedBatteryState, handles: DaemonHandles) {
+        let address = listener.local_addr().ok();
+        if let Ok(mut listening) = handles.metrics.listening.lock() {
+            *listening = address;
+        }
+        let _listening = Listening(handles.metrics.clone());
+        tracing::info!(address = ?address, "Metrics endpoint listening");
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(respond(stream, battery.clone(), handles.clone()));
+                }
+                Err(e) => {
+                    tracing::debug!(error = %e, "Metrics accept failed");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    }
+
+    async fn respond(mut stream: TcpStream, battery: SharedBatteryState, handles: DaemonHandles) {
+        let head = match tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await {
+            Ok(Some(head)) => head,
+            _ => return,
+        };
+        let (status, body) = match route(&head) {
+            Route::Metrics => ("200 OK", render(&Snapshot::collect(&battery, &handles).await)),
+            Route::NotFound => ("404 Not Found", "Only /metrics is served\n".to_string()),
+            Route::MethodNotAllowed => ("405 Method Not Allowed", "Only GET is supported\n".to_string()),
+            Route::BadRequest => ("400 Bad Request", String::new()),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            CONTENT_TYPE,
+            body.len(),
+            body
+        );
+        if stream.write_all(response.as_bytes()).await.is_ok() {
+            let _ = stream.shutdown().await;
+        }
+    }
+
+    /// The request line and headers, `None` when the client closes first or
+    /// sends too much
+    async fn read_head(stream: &mut TcpStream) -> Option<String> {
+        let mut head = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut chunk).await.ok()?;
+            if n == 0 || head.len() + n > MAX_HEAD_BYTES {
+                return None;
+            }
+            head.extend_from_slice(&chunk[..n]);
+        }
+        String::from_utf8(head).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    /// Check `text` against the exposition format; returns the samples by
+    /// series (name plus labels)
+    fn parse_exposition(text: &str) -> HashMap<String, f64> {
+        let mut types: HashMap<String, String> = HashMap::new();
+        let mut samples = HashMap::new();
+        let valid_name = |name: &str| {
+            name.chars().enumerate().all(|(i, c)| c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit()))
+                && !name.is_empty()
+        };
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let (kind, name, rest) = (parts.next().unwrap(), parts.next().unwrap(), parts.next().unwrap_or(""));
+                assert!(valid_name(name), "{line}");
+                if kind == "TYPE" {
+                    assert!(["counter", "gauge", "histogram"].contains(&rest), "{line}");
+                    assert!(types.insert(name.to_string(), rest.to_string()).is_none(), "duplicate TYPE: {line}");
+                }
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').unwrap_or_else(|| panic!("no value: {line}"));
+            let value: f64 = match value {
+                "+Inf" => f64::INFINITY,
+                v => v.parse().unwrap_or_else(|_| panic!("bad value: {line}")),
+            };
+            let name = series.split('{').next().unwrap();
+            assert!(valid_name(name), "{line}");
+            if let Some(labels) = series.strip_prefix(name).filter(|l| !l.is_empty()) {
+                let inner = labels.strip_prefix('{').and_then(|l| l.strip_suffix('}')).unwrap();
+                for pair in inner.split(',') {
+                    let (key, quoted) = pair.split_once('=').unwrap();
+                    assert!(valid_name(key) && quoted.starts_with('"') && quoted.ends_with('"'), "{line}");
+                }
+            }
+            let family = ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix).filter(|base| types.get(*base).is_some_and(|t| t == "histogram")))
+                .unwrap_or(name);
+            let kind = types.get(family).unwrap_or_else(|| panic!("sample before TYPE: {line}"));
+            if kind == "counter" {
+                assert!(family.ends_with("_total"), "{line}");
+            }
+            assert!(samples.insert(series.to_string(), value).is_none(), "duplicate series: {line}");
+        }
+        samples
+    }
+
+    fn snapshot() -> Snapshot {
+        let mut latency = LatencyStats::new();
+        latency.record(ActionKind::Shortcut, Duration::from_micros(300));
+        latency.record(ActionKind::Shortcut, Duration::from_millis(3));
+        latency.record_menu_open(Duration::from_secs(2));
+        let mut entered = [0; ConnectionState::ALL.len()];
+        entered[state_index(ConnectionState::Connected)] = 2;
+        entered[state_index(ConnectionState::Disconnected)] = 1;
+        Snapshot {
+            latency,
+            menus: LateFocusStats { opens: 7, late_reports: 0, swaps: 0 },
+            device: DeviceCounts { entered, reconnects: 1, haptic_failures: 3, state: ConnectionState::Connected },
+            battery: Some(BatteryGauge { percentage: 80, charging: true }),
+        }
+    }
+
+    #[test]
+    fn test_render_parses_as_exposition_format() {
+        let samples = parse_exposition(&render(&snapshot()));
+        assert_eq!(samples["juhradial_menu_invocations_total"], 7.0);
+        assert_eq!(samples["juhradial_action_latency_seconds_bucket{type=\"shortcut\",le=\"0.0005\"}"], 1.0);
+        assert_eq!(samples["juhradial_action_latency_seconds_bucket{type=\"shortcut\",le=\"+Inf\"}"], 2.0);
+        assert_eq!(samples["juhradial_action_latency_seconds_sum{type=\"shortcut\"}"], 0.0033);
+        assert_eq!(samples["juhradial_action_latency_seconds_count{type=\"command\"}"], 0.0);
+        // Beyond the largest bound only +Inf counts it
+        assert_eq!(samples["juhradial_menu_open_latency_seconds_bucket{le=\"1\"}"], 0.0);
+        assert_eq!(samples["juhradial_menu_open_latency_seconds_bucket{le=\"+Inf\"}"], 1.0);
+        assert_eq!(samples["juhradial_device_connection_state{state=\"connected\"}"], 1.0);
+        assert_eq!(samples["juhradial_device_connection_state{state=\"cooldown\"}"], 0.0);
+        assert_eq!(samples["juhradial_device_connection_changes_total{state=\"connected\"}"], 2.0);
+        assert_eq!(samples["juhradial_device_reconnects_total"], 1.0);
+        assert_eq!(samples["juhradial_haptic_failures_total"], 3.0);
+        assert_eq!(samples["juhradial_battery_percent"], 80.0);
+        assert_eq!(samples["juhradial_battery_charging"], 1.0);
+    }
+
+    #[test]
+    fn test_battery_gauges_need_a_reading() {
+        let samples = parse_exposition(&render(&Snapshot { battery: None, ..snapshot() }));
+        assert_eq!(samples["juhradial_battery_available"], 0.0);
+        assert!(!samples.contains_key("juhradial_battery_percent"));
+    }
+
+    #[test]
+    fn test_reconnect_counts_only_a_regained_connection() {
+        let metrics = MetricsHandle::default();
+        metrics.record_connection_change(ConnectionState::NotConnected, ConnectionState::Connected);
+        metrics.record_connection_change(ConnectionState::Connected, ConnectionState::Disconnected);
+        metrics.record_connection_change(ConnectionState::Disconnected, ConnectionState::Cooldown);
+        metrics.record_connection_change(ConnectionState::Cooldown, ConnectionState::Connected);
+        let counts = metrics.device_counts();
+        assert_eq!(counts.reconnects, 1);
+        assert_eq!(counts.entered[state_index(ConnectionState::Connected)], 2);
+        assert_eq!(counts.state, ConnectionState::Connected);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_route() {
+        assert_eq!(route("GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n"), Route::Metrics);
+        assert_eq!(route("GET /metrics?name[]=x HTTP/1.0\r\n\r\n"), Route::Metrics);
+        assert_eq!(route("GET / HTTP/1.1\r\n\r\n"), Route::NotFound);
+        assert_eq!(route("POST /metrics HTTP/1.1\r\n\r\n"), Route::MethodNotAllowed);
+        assert_eq!(route("garbage\r\n\r\n"), Route::BadRequest);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_endpoint_serves_metrics_on_localhost() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        async fn get(address: SocketAddr, request: &str) -> String {
+            let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        }
+
+        let listener = tokio::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let handles = DaemonHandles::default();
+        let server = tokio::spawn(serve(listener, crate::battery::new_shared_state(), handles.clone()));
+
+        let response = get(address, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"), "{head}");
+        assert!(head.contains(CONTENT_TYPE));
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        let samples = parse_exposition(body);
+        assert!(samples.contains_key("juhradial_menu_invocations_total"));
+        assert_eq!(samples["juhradial_battery_available"], 0.0);
+        assert_eq!(handles.metrics.status()["address"], address.to_string());
+
+        assert!(get(address, "GET /other HTTP/1.1\r\n\r\n").await.starts_with("HTTP/1.1 404"));
+        assert!(get(address, "DELETE /metrics HTTP/1.1\r\n\r\n").await.starts_with("HTTP/1.1 405"));
+
+        server.abort();
+        let _ = server.await;
+        assert_eq!(handles.metrics.status()["running"], false);
+    }
+}
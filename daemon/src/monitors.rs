@@ -0,0 +1,222 @@
+//! Monitor layout for sizing the menu
+//!
+//! `menu.size` and a profile's `menu_size` resolve against the monitor the
+//! menu opens on (`geometry::MenuSize::diameter`). Under KWin the resident
+//! helper reports the layout, with each screen's `devicePixelRatio`, when it
+//! loads and whenever screens change (`ReportScreens`). Elsewhere
+//! `cursor::get_monitors` queries it, which may spawn a process, so the
+//! menu-open path only reads the cached layout and starts a background
+//! refresh once it is older than [`REFRESH_AFTER`]. Until a layout is known
+//! a percentage size is left to the overlay. The cache lives in the
+//! [`MonitorsHandle`] on `DaemonHandles`.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::geometry::{monitor_at, Monitor};
+use crate::kwin_script::KWinHelper;
+
+/// Age after which a queried layout is refreshed in the background
+pub const REFRESH_AFTER: Duration = Duration::from_secs(30);
+
+/// One screen as the KWin helper reports it (logical geometry)
+#[derive(Debug, Deserialize)]
+struct KWinScreen {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    #[serde(default = "default_scale")]
+    scale: f64,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// Where the layout came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    /// Nothing known yet
+    None,
+    /// The KWin helper; current until it reports again or unloads
+    KWin,
+    /// `cursor::get_monitors` at this time
+    Queried(Instant),
+}
+
+/// Cached monitor layout
+#[derive(Debug)]
+pub struct Layout {
+    monitors: Vec<Monitor>,
+    source: Source,
+    refreshing: bool,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self {
+            monitors: Vec::new(),
+            source: Source::None,
+            refreshing: false,
+        }
+    }
+
+    /// Take a `ReportScreens` JSON array; returns how many screens it held
+    pub fn report_kwin(&mut self, json: &str) -> Result<usize, serde_json::Error> {
+        let screens: Vec<KWinScreen> = serde_json::from_str(json)?;
+        self.monitors = screens
+            .iter()
+            .map(|s| Monitor {
+                x: s.x.round() as i32,
+                y: s.y.round() as i32,
+                width: s.width.round() as i32,
+                height: s.height.round() as i32,
+                scale: s.scale,
+                compositor_scaled: true,
+            })
+            .collect();
+        self.source = Source::KWin;
+        Ok(self.monitors.len())
+    }
+
+    /// Take a queried layout, unless the KWin helper is reporting
+    pub fn queried(&mut self, monitors: Vec<Monitor>, now: Instant, kwin_reporting: bool) {
+        self.refreshing = false;
+        if self.source == Source::KWin && kwin_reporting {
+            return;
+        }
+        self.monitors = monitors;
+        self.source = Source::Queried(now);
+    }
+
+    /// Whether a background query is due; marks one as running when it is
+    pub fn claim_refresh(&mut self, now: Instant, kwin_reporting: bool) -> bool {
+        let due = match self.source {
+            Source::KWin => !kwin_reporting,
+            Source::None => true,
+            Source::Queried(at) => now.duration_since(at) >= REFRESH_AFTER,
+        };
+        if !due || self.refreshing {
+            return false;
+        }
+        self.refreshing = true;
+        true
+    }
+
+    /// The monitor at `(x, y)`; a KWin layout only while the helper is loaded
+    pub fn at(&self, x: i32, y: i32, kwin_reporting: bool) -> Option<Monitor> {
+        if self.source == Source::KWin && !kwin_reporting {
+            return None;
+        }
+        monitor_at(&self.monitors, x, y).copied()
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The cached layout and the open menu's monitor, shared through `DaemonHandles`
+#[derive(Debug, Clone, Default)]
+pub struct MonitorsHandle {
+    layout: Arc<Mutex<Layout>>,
+    /// The monitor of the open menu
+    pressed: Arc<Mutex<Option<Monitor>>>,
+}
+
+impl MonitorsHandle {
+    /// Record a `ReportScreens` call from the KWin helper
+    pub fn report_kwin(&self, json: &str) {
+        match self.layout.lock().map(|mut l| l.report_kwin(json)) {
+            Ok(Ok(count)) => tracing::debug!(screens = count, "KWin reported the screen layout"),
+            Ok(Err(e)) => tracing::warn!(error = %e, "Ignoring an unreadable screen layout from the KWin helper"),
+            Err(_) => {}
+        }
+    }
+
+    /// Query the layout now (blocking); for startup and the background refresh
+    pub fn refresh(&self, helper: &KWinHelper) {
+        let monitors = crate::cursor::get_monitors();
+        tracing::debug!(monitors = monitors.len(), "Queried the monitor layout");
+        if let Ok(mut layout) = self.layout.lock() {
+            layout.queried(monitors, Instant::now(), helper.is_active());
+        }
+    }
+
+    /// A menu opened at `(x, y)`: remember its monitor for the payload
+    ///
+    /// Never blocks on a query; a stale layout is refreshed on a helper thread
+    /// for the next menu.
+    pub fn pressed(&self, x: i32, y: i32, helper: &KWinHelper) {
+        let kwin = helper.is_active();
+        let (monitor, refresh_due) = match self.layout.lock() {
+            Ok(mut layout) => (layout.at(x, y, kwin), layout.claim_refresh(Instant::now(), kwin)),
+            Err(_) => (None, false),
+        };
+        if refresh_due {
+            let monitors = self.clone();
+            let helper = helper.clone();
+            std::thread::spawn(move || monitors.refresh(&helper));
+        }
+        if let Ok(mut pressed) = self.pressed.lock() {
+            *pressed = monitor;
+        }
+    }
+
+    /// The monitor of the open menu, if the layout was known at the press
+    pub fn current(&self) -> Option<Monitor> {
+        self.pressed.lock().ok().and_then(|m| *m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREENS: &str = r#"[
+        {"name": "DP-1", "x": 0, "y": 0, "width": 2560, "height": 1440, "scale": 1.5},
+        {"name": "eDP-1", "x": 2560, "y": 180, "width": 1280, "height": 800}
+    ]"#;
+
+    #[test]
+    fn test_kwin_report_is_used_while_the_helper_is_loaded() {
+        let mut layout = Layout::new();
+        assert_eq!(layout.report_kwin(SCREENS).unwrap(), 2);
+        let laptop = layout.at(3000, 500, true).unwrap();
+        assert_eq!((laptop.width, laptop.height, laptop.scale), (1280, 800, 1.0));
+        assert!(laptop.compositor_scaled);
+        assert_eq!(layout.at(10, 10, true).unwrap().scale, 1.5);
+        // No query while KWin reports
+        assert!(!layout.claim_refresh(Instant::now(), true));
+
+        // The helper went away with KWin: the layout is no answer
+        assert_eq!(layout.at(10, 10, false), None);
+        assert!(layout.claim_refresh(Instant::now(), false));
+        assert!(layout.report_kwin("{}").is_err());
+    }
+
+    #[test]
+    fn test_queried_layout_refreshes_once_stale() {
+        let t0 = Instant::now();
+        let mut layout = Layout::new();
+        assert_eq!(layout.at(0, 0, false), None);
+        assert!(layout.claim_refresh(t0, false));
+        // One refresh at a time
+        assert!(!layout.claim_refresh(t0, false));
+        let monitor = Monitor { x: 0, y: 0, width: 1920, height: 1080, scale: 1.0, compositor_scaled: false };
+        layout.queried(vec![monitor], t0, false);
+        assert_eq!(layout.at(5, 5, false), Some(monitor));
+        assert!(!layout.claim_refresh(t0 + REFRESH_AFTER / 2, false));
+        assert!(layout.claim_refresh(t0 + REFRESH_AFTER, false));
+
+        // A query that finishes after the helper reported does not replace it
+        layout.report_kwin(SCREENS).unwrap();
+        layout.queried(vec![monitor], t0, true);
+        assert_eq!(layout.at(5, 5, true).unwrap().width, 2560);
+    }
+}
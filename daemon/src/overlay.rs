@@ -0,0 +1,527 @@
+//! Overlay process supervision
+//!
+//! The radial menu itself is drawn by the Python overlay. If it crashes (GPU
+//! driver hiccup, Qt abort) the daemon keeps emitting `MenuRequested` into the
+//! void, so gesture presses silently do nothing. The supervisor spawns the
+//! overlay (or adopts one the launcher already started), waits on it, and
+//! restarts it with exponential backoff after an abnormal exit. When the
+//! overlay keeps dying it gives up and reports `failed` through `Status()`
+//! until `RestartOverlay()` is called.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+use crate::config::SharedConfig;
+use crate::dbus::{DBUS_INTERFACE, DBUS_PATH};
+use crate::thumbwheel_mapping::SharedThumbwheelMapper;
+
+/// Overlay entry point file name
+pub const OVERLAY_SCRIPT_NAME: &str = "juhradial-overlay.py";
+
+/// Maximum restarts allowed inside [`RESTART_WINDOW`] before giving up
+const MAX_RESTARTS_PER_WINDOW: usize = 3;
+
+/// Sliding window for counting restarts
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+/// First restart delay; doubles for each restart still inside the window
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Liveness poll interval for an adopted overlay (not our child, cannot wait)
+const ADOPTED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time for a freshly spawned overlay to subscribe to D-Bus signals before
+/// the state resync is sent
+const OVERLAY_SETTLE: Duration = Duration::from_secs(2);
+
+/// Install locations searched for the overlay after the resolved data
+/// directory, mirroring the launcher script
+const OVERLAY_SEARCH_DIRS: &[&str] = &["/usr/share/juhradial", "/opt/juhradial-mx"];
+
+/// Supervision state reported through `Status()`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum OverlayStatus {
+    /// Supervision disabled, or no graphical session to start an overlay in
+    Unmanaged,
+    /// Overlay process alive
+    Running {
+        /// Overlay process id
+        pid: u32,
+        /// True when the overlay was started by someone else (the launcher)
+        adopted: bool,
+    },
+    /// Waiting out the backoff before the next restart
+    Restarting {
+        /// Restart attempt inside the current window (1-based)
+        attempt: u32,
+        /// Backoff delay before the restart
+        delay_ms: u64,
+    },
+    /// Overlay exited cleanly (e.g. Quit from the tray); not restarted
+    Stopped,
+    /// Overlay crashed too often or could not be started; retries stopped
+    Failed {
+        /// Human-readable reason
+        reason: String,
+    },
+}
+
+/// Overlay supervision snapshot
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlayState {
+    /// Current supervision state
+    pub status: OverlayStatus,
+    /// Total restarts since the daemon started
+    pub restart_count: u32,
+    /// Description of the last overlay exit, if any
+    pub last_exit: Option<String>,
+}
+
+impl Default for OverlayState {
+    fn default() -> Self {
+        Self {
+            status: OverlayStatus::Unmanaged,
+            restart_count: 0,
+            last_exit: None,
+        }
+    }
+}
+
+/// Shared handle between the supervisor task and the D-Bus service
+#[derive(Clone, Default)]
+pub struct OverlayHandle {
+    state: Arc<RwLock<OverlayState>>,
+    restart: Arc<Notify>,
+}
+
+impl OverlayHandle {
+    /// Create a handle in the `unmanaged` state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current supervision state
+    pub fn snapshot(&self) -> OverlayState {
+        self.state.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Ask the supervisor to (re)start the overlay now
+    ///
+    /// Clears a `failed` state and resets the restart budget.
+    pub fn request_restart(&self) {
+        self.restart.notify_one();
+    }
+
+    fn set_status(&self, status: OverlayStatus) {
+        if let Ok(mut state) = self.state.write() {
+            state.status = status;
+        }
+    }
+
+    fn record_exit(&self, exit: String) {
+        if let Ok(mut state) = self.state.write() {
+            state.last_exit = Some(exit);
+        }
+    }
+
+    fn record_restart(&self) {
+        if let Ok(mut state) = self.state.write() {
+            state.restart_count += 1;
+        }
+    }
+}
+
+/// Restart budget with exponential backoff
+#[derive(Debug)]
+pub struct RestartPolicy {
+    recent: VecDeque<Instant>,
+    max_restarts: usize,
+    window: Duration,
+    initial_backoff: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            recent: VecDeque::new(),
+            max_restarts: MAX_RESTARTS_PER_WINDOW,
+            window: RESTART_WINDOW,
+            initial_backoff: INITIAL_BACKOFF,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before the next restart, or `None` when the budget is spent
+    pub fn next_delay(&mut self, now: Instant) -> Option<Duration> {
+        while let Some(&oldest) = self.recent.front() {
+            if now.duration_since(oldest) >= self.window {
+                self.recent.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if self.recent.len() >= self.max_restarts {
+            return None;
+        }
+
+        let delay = self.initial_backoff * 2u32.pow(self.recent.len() as u32);
+        self.recent.push_back(now);
+        Some(delay)
+    }
+
+    /// Restarts counted in the current window
+    pub fn attempts(&self) -> u32 {
+        self.recent.len() as u32
+    }
+
+    /// Forget previous restarts (manual restart)
+    pub fn reset(&mut self) {
+        self.recent.clear();
+    }
+}
+
+/// Resolve the overlay entry point
+///
+/// Uses the configured path when set, otherwise the data directory, the
+/// install locations and the dev checkout layout
+/// (`<repo>/daemon/target/<profile>/juhradiald`).
+pub fn resolve_overlay_path(configured: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = configured {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    let data_dir = crate::paths::data_dir().path.clone();
+    let mut roots: Vec<PathBuf> = OVERLAY_SEARCH_DIRS
+        .iter()
+        .map(PathBuf::from)
+        .filter(|d| *d != data_dir)
+        .collect();
+    roots.insert(0, data_dir);
+    if let Ok(exe) = std::env::current_exe() {
+        if let Some(repo) = exe.ancestors().nth(4) {
+            roots.insert(0, repo.to_path_buf());
+        }
+    }
+
+    roots.into_iter().find_map(|root| {
+        [
+            root.join("overlay").join(OVERLAY_SCRIPT_NAME),
+            root.join(OVERLAY_SCRIPT_NAME),
+        ]
+        .into_iter()
+        .find(|p| p.is_file())
+    })
+}
+
+/// Find an overlay process that is already running (started by the launcher)
+pub fn find_running_overlay() -> Option<u32> {
+    let own_pid = std::process::id();
+    let entries = std::fs::read_dir("/proc").ok()?;
+
+    entries.filter_map(|e| e.ok()).find_map(|entry| {
+        let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+        if pid == own_pid {
+            return None;
+        }
+        let cmdline = std::fs::read(entry.path().join("cmdline")).ok()?;
+        cmdline_runs_overlay(&cmdline).then_some(pid)
+    })
+}
+
+/// Check a NUL-separated /proc cmdline for the overlay script argument
+fn cmdline_runs_overlay(cmdline: &[u8]) -> bool {
+    cmdline.split(|b| *b == 0).any(|arg| {
+        Path::new(std::str::from_utf8(arg).unwrap_or("")).file_name()
+            == Some(std::ffi::OsStr::new(OVERLAY_SCRIPT_NAME))
+    })
+}
+
+/// True when a graphical session is available to show the overlay in
+fn has_graphical_session() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some() || std::env::var_os("DISPLAY").is_some()
+}
+
+fn process_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// How a supervised overlay run ended
+enum RunOutcome {
+    /// Exited with status 0
+    Clean,
+    /// Crashed, was killed, or vanished
+    Abnormal(String),
+    /// `RestartOverlay()` was requested while it was running
+    RestartRequested,
+}
+
+/// Run the overlay supervisor until the daemon exits
+///
+/// After every restart the current theme and profile are re-sent through the
+/// `OverlayRestarted` signal so the new overlay process starts from the same
+/// state as the one that died. Both are read when the signal is sent, so a
+/// profile or theme switched since startup is the one re-applied.
+pub async fn run_overlay_supervisor(
+    handle: OverlayHandle,
+    configured_path: Option<PathBuf>,
+    connection: zbus::Connection,
+    config: SharedConfig,
+    thumbwheel: SharedThumbwheelMapper,
+) {
+    if !has_graphical_session() {
+        tracing::info!("No graphical session, overlay supervision inactive");
+        return;
+    }
+
+    let mut policy = RestartPolicy::default();
+    let mut adopted = find_running_overlay();
+    let mut restarted = false;
+
+    loop {
+        let outcome = match adopted.take() {
+            Some(pid) => {
+                tracing::info!(pid, "Adopted running overlay");
+                handle.set_status(OverlayStatus::Running { pid, adopted: true });
+                watch_adopted(pid, &handle).await
+            }
+            None => {
+                let Some(path) = resolve_overlay_path(configured_path.as_deref()) else {
+                    tracing::warn!("Overlay entry point not found, supervision stopped");
+                    handle.set_status(OverlayStatus::Failed {
+                        reason: "overlay entry point not found".to_string(),
+                    });
+                    handle.restart.notified().await;
+                    policy.reset();
+                    continue;
+                };
+                match tokio::process::Command::new("python3").arg(&path).spawn() {
+                    Ok(child) => {
+                        let pid = child.id().unwrap_or_default();
+                        tracing::info!(pid, path = %path.display(), "Overlay started");
+                        handle.set_status(OverlayStatus::Running {
+                            pid,
+                            adopted: false,
+                        });
+                        if restarted {
+                            handle.record_restart();
+                            resync_overlay(&connection, &config, &thumbwheel, pid).await;
+                        }
+                        watch_child(child, &handle).await
+                    }
+                    Err(e) => RunOutcome::Abnormal(format!("spawn failed: {}", e)),
+                }
+            }
+        };
+        restarted = true;
+
+        match outcome {
+            RunOutcome::Clean => {
+                tracing::info!("Overlay exited cleanly, not restarting");
+                handle.record_exit("exited with status 0".to_string());
+                handle.set_status(OverlayStatus::Stopped);
+                handle.restart.notified().await;
+                policy.reset();
+            }
+            RunOutcome::RestartRequested => {
+                tracing::info!("Overlay restart requested");
+                policy.reset();
+            }
+            RunOutcome::Abnormal(reason) => {
+                handle.record_exit(reason.clone());
+                match policy.next_delay(Instant::now()) {
+                    Some(delay) => {
+                        tracing::warn!(
+                            reason = %reason,
+                            attempt = policy.attempts(),
+                            delay_ms = delay.as_millis() as u64,
+                            "Overlay exited abnormally, restarting"
+                        );
+                        handle.set_status(OverlayStatus::Restarting {
+                            attempt: policy.attempts(),
+                            delay_ms: delay.as_millis() as u64,
+                        });
+                        tokio::select! {
+                            _ = tokio::time::sleep(delay) => {}
+                            _ = handle.restart.notified() => policy.reset(),
+                        }
+                    }
+                    None => {
+                        tracing::error!(
+                            reason = %reason,
+                            max_restarts = MAX_RESTARTS_PER_WINDOW,
+                            "Overlay keeps crashing, giving up until RestartOverlay is called"
+                        );
+                        handle.set_status(OverlayStatus::Failed {
+                            reason: format!(
+                                "crashed {} times within {}s: {}",
+                                MAX_RESTARTS_PER_WINDOW + 1,
+                                RESTART_WINDOW.as_secs(),
+                                reason
+                            ),
+                        });
+                        handle.restart.notified().await;
+                        policy.reset();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Wait for a spawned overlay to exit or for a restart request
+async fn watch_child(mut child: tokio::process::Child, handle: &OverlayHandle) -> RunOutcome {
+    tokio::select! {
+        status = child.wait() => match status {
+            Ok(status) if status.success() => RunOutcome::Clean,
+            Ok(status) => RunOutcome::Abnormal(status.to_string()),
+            Err(e) => RunOutcome::Abnormal(format!("wait failed: {}", e)),
+        },
+        _ = handle.restart.notified() => {
+            if let Err(e) = child.kill().await {
+                tracing::warn!(error = %e, "Failed to stop overlay for restart");
+            }
+            RunOutcome::RestartRequested
+        }
+    }
+}
+
+/// Poll an adopted overlay until it disappears or a restart is requested
+async fn watch_adopted(pid: u32, handle: &OverlayHandle) -> RunOutcome {
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(ADOPTED_POLL_INTERVAL) => {
+                if !process_alive(pid) {
+                    // Not our child, so the exit status is unknown
+                    return RunOutcome::Abnormal(format!("adopted overlay {} exited", pid));
+                }
+            }
+            _ = handle.restart.notified() => {
+                // SAFETY: plain kill(2) on a pid we found running the overlay
+                unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+                return RunOutcome::RestartRequested;
+            }
+        }
+    }
+}
+
+/// Re-send theme and profile state to a restarted overlay
+async fn resync_overlay(
+    connection: &zbus::Connection,
+    config: &SharedConfig,
+    thumbwheel: &SharedThumbwheelMapper,
+    pid: u32,
+) {
+    tokio::time::sleep(OVERLAY_SETTLE).await;
+
+    let theme = config.read().map(|c| c.theme.clone()).unwrap_or_default();
+    // The profile in effect now: selected, window-matched or reloaded
+    let profile = thumbwheel
+        .read()
+        .map(|m| m.menu_profile().to_string())
+        .unwrap_or_else(|_| "default".to_string());
+
+    if let Err(e) = connection
+        .emit_signal(
+            None::<&str>,
+            DBUS_PATH,
+            DBUS_INTERFACE,
+            "OverlayRestarted",
+            &(pid, theme.as_str(), profile.as_str()),
+        )
+        .await
+    {
+        tracing::warn!(error = %e, "Failed to emit OverlayRestarted");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_within_window() {
+        let mut policy = RestartPolicy::default();
+        let start = Instant::now();
+
+        assert_eq!(policy.next_delay(start), Some(Duration::from_secs(1)));
+        assert_eq!(policy.next_delay(start), Some(Duration::from_secs(2)));
+        assert_eq!(policy.next_delay(start), Some(Duration::from_secs(4)));
+        assert_eq!(policy.next_delay(start), None);
+    }
+
+    #[test]
+    fn test_budget_recovers_after_window() {
+        let mut policy = RestartPolicy::default();
+        let start = Instant::now();
+        for _ in 0..MAX_RESTARTS_PER_WINDOW {
+            assert!(policy.next_delay(start).is_some());
+        }
+        assert!(policy.next_delay(start).is_none());
+
+        let later = start + RESTART_WINDOW;
+        assert_eq!(policy.next_delay(later), Some(INITIAL_BACKOFF));
+    }
+
+    #[test]
+    fn test_reset_clears_budget() {
+        let mut policy = RestartPolicy::default();
+        let now = Instant::now();
+        for _ in 0..MAX_RESTARTS_PER_WINDOW {
+            policy.next_delay(now);
+        }
+        policy.reset();
+        assert_eq!(policy.attempts(), 0);
+        assert_eq!(policy.next_delay(now), Some(INITIAL_BACKOFF));
+    }
+
+    #[test]
+    fn test_cmdline_matching() {
+        assert!(cmdline_runs_overlay(
+            b"python3\0/usr/share/juhradial/overlay/juhradial-overlay.py\0"
+        ));
+        assert!(!cmdline_runs_overlay(
+            b"python3\0/tmp/juhradial-overlay.py.bak\0"
+        ));
+        assert!(!cmdline_runs_overlay(b"grep\0juhradial\0"));
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        let json = serde_json::to_value(OverlayStatus::Running {
+            pid: 42,
+            adopted: true,
+        })
+        .unwrap();
+        assert_eq!(json["state"], "running");
+        assert_eq!(json["pid"], 42);
+
+        let json = serde_json::to_value(OverlayStatus::Failed { reason: "x".into() }).unwrap();
+        assert_eq!(json["state"], "failed");
+    }
+
+    #[test]
+    fn test_handle_defaults_to_unmanaged() {
+        let handle = OverlayHandle::new();
+        let state = handle.snapshot();
+        assert_eq!(state.status, OverlayStatus::Unmanaged);
+        assert_eq!(state.restart_count, 0);
+        assert!(state.last_exit.is_none());
+    }
+
+    #[test]
+    fn test_configured_path_must_exist() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join(OVERLAY_SCRIPT_NAME);
+        assert!(resolve_overlay_path(Some(&script)).is_none());
+        std::fs::write(&script, "").unwrap();
+        assert_eq!(resolve_overlay_path(Some(&script)), Some(script));
+    }
+}
@@ -0,0 +1,196 @@
+//! Emergency kill switch for input interception
+//!
+//! If a bug leaves the daemon grabbing the mouse at the wrong time, the user
+//! may be unable to click their way to a fix. Pressing the gesture button
+//! `PANIC_PRESSES` times within `PANIC_WINDOW`, or sending the daemon
+//! `SIGUSR1`, disarms it: the evdev loops drop their grabs, the hidraw loop
+//! hands the HID++ diverts back to the mouse, the menu is dismissed and no
+//! trigger opens it again. The daemon keeps running disarmed until `Rearm`
+//! is called over D-Bus or it is restarted.
+//!
+//! Presses are counted where the input loops read raw events, ahead of the
+//! menu state, the trigger arbiter and config lookups, so the sequence still
+//! works when those are wedged.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+/// Gesture button presses that disarm the daemon...
+pub const PANIC_PRESSES: usize = 5;
+
+/// ...when they all land within this window
+pub const PANIC_WINDOW: Duration = Duration::from_secs(2);
+
+/// What threw the switch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisarmReason {
+    /// The gesture button press sequence
+    PressSequence,
+    /// SIGUSR1
+    Signal,
+}
+
+impl std::fmt::Display for DisarmReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisarmReason::PressSequence => {
+                write!(f, "gesture button pressed {} times within {:?}", PANIC_PRESSES, PANIC_WINDOW)
+            }
+            DisarmReason::Signal => write!(f, "SIGUSR1"),
+        }
+    }
+}
+
+/// Recent gesture button presses
+#[derive(Debug, Default)]
+pub struct PressSequence {
+    presses: VecDeque<Instant>,
+}
+
+impl PressSequence {
+    /// Record a press; `true` when it completes the sequence
+    pub fn press(&mut self, now: Instant) -> bool {
+        while self
+            .presses
+            .front()
+            .is_some_and(|&at| now.duration_since(at) > PANIC_WINDOW)
+        {
+            self.presses.pop_front();
+        }
+        self.presses.push_back(now);
+        if self.presses.len() >= PANIC_PRESSES {
+            self.presses.clear();
+            return true;
+        }
+        false
+    }
+}
+
+/// Armed/disarmed state shared by every input source
+///
+/// Cheap to clone; clones share the state. The daemon's switch lives on
+/// `DaemonHandles`, tests give handlers their own.
+#[derive(Debug, Clone)]
+pub struct PanicSwitch {
+    armed: Arc<watch::Sender<bool>>,
+    presses: Arc<Mutex<PressSequence>>,
+}
+
+impl Default for PanicSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PanicSwitch {
+    /// A new, armed switch
+    pub fn new() -> Self {
+        Self {
+            armed: Arc::new(watch::Sender::new(true)),
+            presses: Arc::new(Mutex::new(PressSequence::default())),
+        }
+    }
+
+    /// Whether input interception is enabled
+    pub fn is_armed(&self) -> bool {
+        *self.armed.borrow()
+    }
+
+    /// Count a gesture button press; disarms when it completes the sequence
+    ///
+    /// Returns `true` when this press disarmed the switch.
+    pub fn record_press(&self, now: Instant) -> bool {
+        if !self.is_armed() {
+            return false;
+        }
+        // A poisoned lock must not cost the user their escape hatch
+        let tripped = match self.presses.lock() {
+            Ok(mut presses) => presses.press(now),
+            Err(poisoned) => poisoned.into_inner().press(now),
+        };
+        tripped && self.disarm(DisarmReason::PressSequence)
+    }
+
+    /// Disable input interception; `true` when it was armed
+    pub fn disarm(&self, reason: DisarmReason) -> bool {
+        let changed = self.armed.send_if_modified(|armed| std::mem::replace(armed, false));
+        if changed {
+            tracing::error!(
+                %reason,
+                "PANIC SWITCH: input interception DISARMED - grabs and diverts released, \
+                 menu disabled. Re-arm with the D-Bus Rearm method or restart the daemon."
+            );
+        }
+        changed
+    }
+
+    /// Re-enable input interception; `true` when it was disarmed
+    pub fn rearm(&self) -> bool {
+        let changed = self.armed.send_if_modified(|armed| !std::mem::replace(armed, true));
+        if changed {
+            tracing::info!("Panic switch re-armed; input interception resumes");
+        }
+        changed
+    }
+
+    /// Resolves once the switch is disarmed (at once if it already is)
+    pub async fn disarmed(&self) {
+        let _ = self.armed.subscribe().wait_for(|armed| !armed).await;
+    }
+
+    /// Resolves once the switch is armed (at once if it already is)
+    pub async fn armed(&self) {
+        let _ = self.armed.subscribe().wait_for(|armed| *armed).await;
+    }
+
+    /// Watch armed/disarmed changes
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.armed.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequence_needs_presses_within_window() {
+        let start = Instant::now();
+        let mut sequence = PressSequence::default();
+        // Slow presses slide out of the window and never trip it
+        for i in 0..10 {
+            assert!(!sequence.press(start + Duration::from_millis(600) * i));
+        }
+
+        let start = start + Duration::from_secs(30);
+        for i in 0..PANIC_PRESSES as u32 - 1 {
+            assert!(!sequence.press(start + Duration::from_millis(300) * i));
+        }
+        assert!(sequence.press(start + Duration::from_millis(1500)));
+        // Counting starts over afterwards
+        assert!(!sequence.press(start + Duration::from_millis(1600)));
+    }
+
+    #[tokio::test]
+    async fn test_switch_disarms_and_rearms() {
+        let switch = PanicSwitch::new();
+        let now = Instant::now();
+        let tripped: Vec<bool> = (0..PANIC_PRESSES as u32)
+            .map(|i| switch.record_press(now + Duration::from_millis(100) * i))
+            .collect();
+        assert_eq!(tripped, [false, false, false, false, true]);
+        assert!(!switch.is_armed());
+        switch.disarmed().await;
+        // Already disarmed: more presses and signals change nothing
+        assert!(!switch.record_press(now));
+        assert!(!switch.disarm(DisarmReason::Signal));
+
+        assert!(switch.rearm());
+        assert!(!switch.rearm());
+        switch.armed().await;
+        assert!(switch.disarm(DisarmReason::Signal));
+    }
+}
@@ -0,0 +1,419 @@
+//! Write-behind persistence for config, state and stats files
+//!
+//! The focus consumer, `SetProfile` and `ReportMenuClosed` all run on the
+//! input path, and a slow disk (or a full one) used to stall them on the
+//! write. They now hand a [`PersistJob`] to the daemon's queue (on
+//! `DaemonHandles`) and return. One background task ([`PersistQueue::run`])
+//! collects the jobs per target file: state and config writes replace each
+//! other, latest wins, and stats lines are appended in the order they came
+//! in. A target is written
+//! [`DEBOUNCE`] after its first pending job, so ten profile switches in a
+//! burst cost one atomic write.
+//!
+//! Failed writes are logged and counted for `Status()` (`persistence`).
+//! Shutdown calls [`PersistQueue::flush`] before cancelling the background
+//! tasks, so nothing queued is lost. Until the task runs, and after it is
+//! gone, jobs are written on the spot; the startup bootstrap (default config,
+//! migrations) writes directly as before.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::fs_util;
+
+/// How long a target collects jobs before it is written
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// A write for the background task
+#[derive(Debug, Clone, PartialEq)]
+pub enum PersistJob {
+    /// Replace a state file (`profile_state.json`); latest wins
+    WriteState { path: PathBuf, contents: Vec<u8> },
+    /// Append one line to a stats file, rotating it past `max_bytes`
+    WriteStats { path: PathBuf, max_bytes: u64, line: String },
+    /// Replace the config file; latest wins
+    WriteConfig { path: PathBuf, contents: Vec<u8> },
+}
+
+impl PersistJob {
+    /// The file this job writes
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::WriteState { path, .. } | Self::WriteStats { path, .. } | Self::WriteConfig { path, .. } => path,
+        }
+    }
+
+    fn into_write(self) -> (PathBuf, Write) {
+        match self {
+            Self::WriteState { path, contents } | Self::WriteConfig { path, contents } => {
+                (path, Write::Replace(contents))
+            }
+            Self::WriteStats { path, max_bytes, line } => (path, Write::Append { max_bytes, lines: vec![line] }),
+        }
+    }
+}
+
+/// What is pending for one target
+#[derive(Debug)]
+enum Write {
+    Replace(Vec<u8>),
+    Append { max_bytes: u64, lines: Vec<String> },
+}
+
+impl Write {
+    /// Fold a later job for the same target into this one
+    fn merge(&mut self, later: Write) {
+        match (self, later) {
+            (Write::Append { lines, max_bytes }, Write::Append { lines: more, max_bytes: limit }) => {
+                lines.extend(more);
+                *max_bytes = limit;
+            }
+            (current, later) => *current = later,
+        }
+    }
+
+    fn perform(&self, path: &Path) -> io::Result<()> {
+        match self {
+            Write::Replace(contents) => fs_util::write_private(path, contents),
+            Write::Append { max_bytes, lines } => crate::stats::append_lines(path, *max_bytes, lines),
+        }
+    }
+}
+
+/// Pending writes by target, each due [`DEBOUNCE`] after its first job
+#[derive(Debug)]
+struct Pending {
+    debounce: Duration,
+    targets: HashMap<PathBuf, (Instant, Write)>,
+}
+
+impl Pending {
+    fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            targets: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, job: PersistJob, now: Instant) {
+        let (path, write) = job.into_write();
+        match self.targets.get_mut(&path) {
+            Some((_, pending)) => pending.merge(write),
+            None => {
+                self.targets.insert(path, (now + self.debounce, write));
+            }
+        }
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.targets.values().map(|(due, _)| *due).min()
+    }
+
+    /// Targets due by `now` (all of them with `None`), oldest first
+    fn take(&mut self, now: Option<Instant>) -> Vec<(PathBuf, Write)> {
+        let due: Vec<PathBuf> = self
+            .targets
+            .iter()
+            .filter(|(_, (due, _))| now.is_none_or(|now| *due <= now))
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut batch: Vec<(Instant, PathBuf, Write)> = due
+            .into_iter()
+            .filter_map(|path| self.targets.remove(&path).map(|(due, write)| (due, path, write)))
+            .collect();
+        batch.sort_by_key(|(due, _, _)| *due);
+        batch.into_iter().map(|(_, path, write)| (path, write)).collect()
+    }
+}
+
+enum Message {
+    Job(PersistJob),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Write counts for `Status()`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PersistStatus {
+    /// Whether the background task is running
+    pub running: bool,
+    pub writes: u64,
+    pub failures: u64,
+    /// The last failed target and error
+    pub last_failure: Option<String>,
+}
+
+/// The job channel and its counters
+///
+/// The daemon shares one through `DaemonHandles`.
+#[derive(Debug)]
+pub struct PersistQueue {
+    tx: mpsc::UnboundedSender<Message>,
+    rx: Mutex<Option<mpsc::UnboundedReceiver<Message>>>,
+    debounce: Duration,
+    running: AtomicBool,
+    writes: AtomicU64,
+    failures: AtomicU64,
+    last_failure: Mutex<Option<String>>,
+}
+
+impl PersistQueue {
+    pub fn new() -> Self {
+        Self::with_debounce(DEBOUNCE)
+    }
+
+    /// Queue with another debounce window (tests)
+    pub fn with_debounce(debounce: Duration) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx: Mutex::new(Some(rx)),
+            debounce,
+            running: AtomicBool::new(false),
+            writes: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            last_failure: Mutex::new(None),
+        }
+    }
+
+    /// Hand `job` to the background task, or write it now when the task is
+    /// not running
+    pub fn submit(&self, job: PersistJob) {
+        if !self.running.load(Ordering::Acquire) {
+            self.write_now(job);
+            return;
+        }
+        if let Err(mpsc::error::SendError(Message::Job(job))) = self.tx.send(Message::Job(job)) {
+            self.write_now(job);
+        }
+    }
+
+    /// Write everything pending and wait until it is on disk
+    pub async fn flush(&self) {
+        if !self.running.load(Ordering::Acquire) {
+            return;
+        }
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.tx.send(Message::Flush(done_tx)).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+
+    /// The background task; returns at once if it already ran
+    pub async fn run(&self) {
+        let Some(mut rx) = self.rx.lock().ok().and_then(|mut rx| rx.take()) else {
+            tracing::warn!("Persistence task already started");
+            return;
+        };
+        let _running = RunningGuard::new(&self.running);
+        let mut pending = Pending::new(self.debounce);
+        loop {
+            let message = match pending.next_due() {
+                Some(due) => tokio::select! {
+                    message = rx.recv() => message,
+                    _ = tokio::time::sleep_until(due.into()) => {
+                        self.write(pending.take(Some(Instant::now()))).await;
+                        continue;
+                    }
+                },
+                None => rx.recv().await,
+            };
+            match message {
+                Some(Message::Job(job)) => pending.push(job, Instant::now()),
+                Some(Message::Flush(done)) => {
+                    self.write(pending.take(None)).await;
+                    let _ = done.send(());
+                }
+                None => {
+                    self.write(pending.take(None)).await;
+                    return;
+                }
+            }
+        }
+    }
+
+    pub fn status(&self) -> PersistStatus {
+        PersistStatus {
+            running: self.running.load(Ordering::Acquire),
+            writes: self.writes.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            last_failure: self.last_failure.lock().ok().and_then(|f| f.clone()),
+        }
+    }
+
+    async fn write(&self, batch: Vec<(PathBuf, Write)>) {
+        if batch.is_empty() {
+            return;
+        }
+        let results = tokio::task::spawn_blocking(move || {
+            batch
+                .into_iter()
+                .map(|(path, write)| {
+                    let result = write.perform(&path);
+                    (path, result)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await;
+        match results {
+            Ok(results) => results.into_iter().for_each(|(path, result)| self.record(&path, result)),
+            Err(e) => tracing::error!("Persistence write task panicked: {:?}", e),
+        }
+    }
+
+    fn write_now(&self, job: PersistJob) {
+        let (path, write) = job.into_write();
+        let result = write.perform(&path);
+        self.record(&path, result);
+    }
+
+    fn record(&self, path: &Path, result: io::Result<()>) {
+        match result {
+            Ok(()) => {
+                self.writes.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Deferred write failed");
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                if let Ok(mut last) = self.last_failure.lock() {
+                    *last = Some(format!("{}: {}", path.display(), e));
+                }
+            }
+        }
+    }
+}
+
+impl Default for PersistQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sets the running flag for the life of [`PersistQueue::run`], so jobs
+/// submitted after the task is cancelled are written directly
+struct RunningGuard<'a>(&'a AtomicBool);
+
+impl<'a> RunningGuard<'a> {
+    fn new(flag: &'a AtomicBool) -> Self {
+        flag.store(true, Ordering::Release);
+        Self(flag)
+    }
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn state(path: &Path, contents: &str) -> PersistJob {
+        PersistJob::WriteState {
+            path: path.to_path_buf(),
+            contents: contents.as_bytes().to_vec(),
+        }
+    }
+
+    fn stats(path: &Path, line: &str) -> PersistJob {
+        PersistJob::WriteStats {
+            path: path.to_path_buf(),
+            max_bytes: 1 << 20,
+            line: line.to_string(),
+        }
+    }
+
+    /// A queue with its task spawned, once the task has started
+    async fn started(debounce: Duration) -> (Arc<PersistQueue>, tokio::task::JoinHandle<()>) {
+        let queue = Arc::new(PersistQueue::with_debounce(debounce));
+        let task = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.run().await })
+        };
+        while !queue.status().running {
+            tokio::task::yield_now().await;
+        }
+        (queue, task)
+    }
+
+    #[tokio::test]
+    async fn test_burst_is_one_write() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("profile_state.json");
+        let (queue, _task) = started(Duration::from_millis(50)).await;
+
+        for i in 0..10 {
+            queue.submit(state(&path, &format!("{{\"n\": {i}}}")));
+        }
+        assert!(!path.exists(), "written before the debounce window");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"n\": 9}");
+        assert_eq!(queue.status().writes, 1);
+        assert_eq!(queue.status().failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_order_is_kept_per_target() {
+        let dir = TempDir::new().unwrap();
+        let log = dir.path().join("stats.ndjson");
+        let first = dir.path().join("a.json");
+        let second = dir.path().join("b.json");
+        let (queue, _task) = started(Duration::from_secs(3600)).await;
+
+        for i in 0..5 {
+            queue.submit(stats(&log, &format!("line {i}")));
+            queue.submit(state(&first, &format!("a{i}")));
+        }
+        queue.submit(state(&second, "b"));
+        queue.flush().await;
+        queue.submit(stats(&log, "line 5"));
+        queue.submit(state(&first, "a5"));
+        queue.flush().await;
+
+        let lines: Vec<String> = fs::read_to_string(&log).unwrap().lines().map(String::from).collect();
+        assert_eq!(lines, (0..6).map(|i| format!("line {i}")).collect::<Vec<_>>());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "a5");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "b");
+        // Two flushes of two targets, plus the second state file once
+        assert_eq!(queue.status().writes, 5);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_then_writes_directly() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("profile_state.json");
+        let (queue, task) = started(Duration::from_secs(3600)).await;
+
+        // The daemon's shutdown: flush, then cancel the background tasks
+        queue.submit(state(&path, "queued"));
+        queue.flush().await;
+        assert_eq!(fs::read_to_string(&path).unwrap(), "queued");
+        task.abort();
+        let _ = task.await;
+        assert!(!queue.status().running);
+
+        // Late writes (the menu still open at shutdown) go straight to disk
+        queue.submit(state(&path, "late"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "late");
+        queue.flush().await;
+
+        // Failures are counted, not raised
+        let blocked = dir.path().join("file");
+        fs::write(&blocked, "").unwrap();
+        queue.submit(state(&blocked.join("state.json"), "x"));
+        let status = queue.status();
+        assert_eq!(status.failures, 1);
+        assert!(status.last_failure.unwrap().contains("state.json"));
+    }
+}
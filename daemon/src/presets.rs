@@ -13,6 +13,7 @@
 
 use crate::actions::{detect_desktop, Action, ActionError, ActionExecutor, ActionType};
 use crate::config::ButtonAction;
+use crate::handles::DaemonHandles;
 
 /// A desktop-portable semantic action.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -70,15 +71,15 @@ impl Preset {
 }
 
 fn shortcut(keys: &str) -> Action {
-    Action { action_type: ActionType::Shortcut(keys.to_string()), label: None, icon: None }
+    Action { action_type: ActionType::Shortcut(keys.to_string()), label: None, icon: None, description: None, require_confirmation: false, cooldown_ms: 0 }
 }
 
 fn command(cmd: &str) -> Action {
-    Action { action_type: ActionType::Command(cmd.to_string()), label: None, icon: None }
+    Action { action_type: ActionType::Command(cmd.to_string()), label: None, icon: None, description: None, require_confirmation: false, cooldown_ms: 0 }
 }
 
 fn kwin(name: &str) -> Action {
-    Action { action_type: ActionType::KWin(name.to_string()), label: None, icon: None }
+    Action { action_type: ActionType::KWin(name.to_string()), label: None, icon: None, description: None, require_confirmation: false, cooldown_ms: 0 }
 }
 
 /// Resolve a preset to a concrete [`Action`] for a desktop environment.
@@ -123,11 +124,11 @@ pub fn resolve(preset: Preset, de: &str) -> Action {
 /// Reuses [`ActionExecutor::execute`], so kglobalaccel/dbus/key-synthesis are
 /// all handled by the existing primitives. Callers on the zbus executor must
 /// drive this off-thread (the KWin/dbus arms block on `dbus-send`).
-pub async fn execute_preset(preset: Preset) -> Result<(), ActionError> {
+pub async fn execute_preset(preset: Preset, handles: &DaemonHandles) -> Result<(), ActionError> {
     let de = detect_desktop();
     let action = resolve(preset, de);
     tracing::info!(preset = preset.as_str(), de, "Executing preset");
-    ActionExecutor::execute(&action).await
+    ActionExecutor::execute(&action, handles).await
 }
 
 #[cfg(test)]
@@ -0,0 +1,225 @@
+//! Pre-warming the highlighted slice's action
+//!
+//! Most of a shortcut's 10ms budget goes to resolving it and forking the
+//! backend. Once the highlight has rested on a slice for `menu.prewarm_ms`,
+//! that slice's action is resolved ahead of the release
+//! ([`PreparedAction`]) and stashed; executing the same action then only
+//! spawns. Commands are resolved too but never started early. The stash is
+//! dropped the moment the highlight moves and replaced on the next open.
+//!
+//! Only menus whose picks the daemon executes arm it: the menus of app
+//! profiles and ad-hoc menus. The `default` profile's Settings ring is run
+//! by the overlay, so nothing is prepared for it. `menu.prewarm_ms = 0`
+//! turns it off.
+//!
+//! `Prewarm` is a [`Deadline`] state machine; the D-Bus service feeds it
+//! through the [`PrewarmHandle`] on `DaemonHandles`, the executor takes the
+//! prepared action from it, and a daemon task runs its timer and does the
+//! preparing.
+
+use std::time::{Duration, Instant};
+
+use crate::deadline::{Deadline, DeadlineHandle};
+
+use crate::actions::{Action, ActionType, PreparedAction};
+
+/// Default `menu.prewarm_ms`
+pub const PREWARM_MS: u64 = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// No menu, no highlight, or nothing to prepare
+    Idle,
+    /// Highlight on `slot` since `due - delay`
+    Dwelling { slot: u8, due: Instant },
+    /// `slot` was prepared (or found to need nothing)
+    Warm { slot: u8 },
+}
+
+/// Dwell tracking and the prepared action for the open menu
+#[derive(Debug)]
+pub struct Prewarm {
+    /// Pre-warm delay for the open menu; `None` while closed or disabled
+    delay: Option<Duration>,
+    /// The open menu's actions by slot
+    actions: Vec<Option<Action>>,
+    state: State,
+    prepared: Option<PreparedAction>,
+}
+
+impl Prewarm {
+    pub fn new() -> Self {
+        Self {
+            delay: None,
+            actions: Vec::new(),
+            state: State::Idle,
+            prepared: None,
+        }
+    }
+
+    /// Menu shown with `actions` by slot; `delay` is `None` when disabled
+    pub fn opened(&mut self, delay: Option<Duration>, actions: Vec<Option<Action>>) {
+        self.delay = delay;
+        self.actions = actions;
+        self.state = State::Idle;
+        self.prepared = None;
+    }
+
+    /// Highlight moved to `slot` (`None` = hub or outside the ring)
+    ///
+    /// Anything prepared for another slot is dropped at once.
+    pub fn highlighted(&mut self, slot: Option<u8>, now: Instant) {
+        if slot.is_some() && slot == self.current() {
+            return;
+        }
+        self.prepared = None;
+        self.state = match (slot, self.delay) {
+            (Some(slot), Some(delay)) if self.action(slot).is_some() => State::Dwelling { slot, due: now + delay },
+            _ => State::Idle,
+        };
+    }
+
+    /// Menu closed; the prepared action stays for the release to take
+    pub fn closed(&mut self) {
+        self.delay = None;
+        self.actions.clear();
+        self.state = State::Idle;
+    }
+
+    /// Timer woke: prepare the slot whose highlight has rested long enough
+    ///
+    /// Returns the prepared slot. A timer outrun by a highlight change or
+    /// close finds a later (or no) deadline and prepares nothing.
+    pub fn fire(&mut self, now: Instant, prepare: impl FnOnce(&ActionType) -> Option<PreparedAction>) -> Option<u8> {
+        let State::Dwelling { slot, due } = self.state else {
+            return None;
+        };
+        if now < due {
+            return None;
+        }
+        self.state = State::Warm { slot };
+        self.prepared = self.action(slot).and_then(|a| prepare(&a.action_type));
+        self.prepared.as_ref().map(|_| slot)
+    }
+
+    /// The prepared action if it was prepared from `action`; empties the stash
+    pub fn take(&mut self, action: &ActionType) -> Option<PreparedAction> {
+        self.prepared.take().filter(|p| p.matches(action))
+    }
+
+    fn action(&self, slot: u8) -> Option<&Action> {
+        self.actions.get(usize::from(slot)).and_then(Option::as_ref)
+    }
+
+    /// Slot the highlight currently rests on, if tracked
+    fn current(&self) -> Option<u8> {
+        match self.state {
+            State::Dwelling { slot, .. } | State::Warm { slot } => Some(slot),
+            State::Idle => None,
+        }
+    }
+}
+
+impl Default for Prewarm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deadline for Prewarm {
+    /// When the timer should next wake
+    fn wake_at(&self) -> Option<Instant> {
+        match self.state {
+            State::Dwelling { due, .. } => Some(due),
+            _ => None,
+        }
+    }
+}
+
+/// Shared handle between the D-Bus service, the executor and the timer task
+pub type PrewarmHandle = DeadlineHandle<Prewarm>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sandbox::ResolvedBinaries;
+
+    const DELAY: Duration = Duration::from_millis(PREWARM_MS);
+
+    fn prepare(action: &ActionType) -> Option<PreparedAction> {
+        PreparedAction::prepare(action, &ResolvedBinaries::default())
+    }
+
+    fn menu() -> Vec<Option<Action>> {
+        let action = |action_type| {
+            Some(Action {
+                action_type,
+                label: None,
+                icon: None,
+                description: None,
+                require_confirmation: false,
+                cooldown_ms: 0,
+            })
+        };
+        vec![
+            action(ActionType::Shortcut("ctrl+c".to_string())),
+            action(ActionType::Command("true".to_string())),
+            action(ActionType::None),
+            None,
+        ]
+    }
+
+    #[test]
+    fn test_rested_highlight_prepares_for_release() {
+        let t0 = Instant::now();
+        let mut prewarm = Prewarm::new();
+        prewarm.opened(Some(DELAY), menu());
+        prewarm.highlighted(Some(0), t0);
+        assert_eq!(prewarm.fire(t0 + DELAY / 2, prepare), None);
+        assert_eq!(prewarm.fire(t0 + DELAY, prepare), Some(0));
+        assert_eq!(prewarm.wake_at(), None);
+
+        // The release outlives the close and takes only the matching action
+        prewarm.closed();
+        assert!(prewarm.take(&ActionType::Shortcut("ctrl+v".to_string())).is_none());
+        prewarm.opened(Some(DELAY), menu());
+        prewarm.highlighted(Some(1), t0);
+        prewarm.fire(t0 + DELAY, prepare);
+        prewarm.closed();
+        let prepared = prewarm.take(&ActionType::Command("true".to_string()));
+        assert!(prepared.is_some());
+        assert!(prewarm.take(&ActionType::Command("true".to_string())).is_none());
+    }
+
+    #[test]
+    fn test_highlight_change_drops_prepared() {
+        let t0 = Instant::now();
+        let mut prewarm = Prewarm::new();
+        prewarm.opened(Some(DELAY), menu());
+        prewarm.highlighted(Some(0), t0);
+        prewarm.fire(t0 + DELAY, prepare);
+        // Re-reporting the warm slot keeps it
+        prewarm.highlighted(Some(0), t0 + DELAY * 2);
+        prewarm.highlighted(Some(1), t0 + DELAY * 2);
+        assert!(prewarm.take(&ActionType::Shortcut("ctrl+c".to_string())).is_none());
+
+        // A timer armed for slot 1 finds the hub highlighted
+        prewarm.highlighted(None, t0 + DELAY * 2);
+        let unreachable = |_: &ActionType| -> Option<PreparedAction> { panic!("nothing to prepare") };
+        assert_eq!(prewarm.fire(t0 + DELAY * 4, unreachable), None);
+        // Slices without a preparable action never arm
+        prewarm.highlighted(Some(3), t0);
+        assert_eq!(prewarm.wake_at(), None);
+        prewarm.highlighted(Some(2), t0);
+        assert_eq!(prewarm.fire(t0 + DELAY, prepare), None);
+    }
+
+    #[test]
+    fn test_disabled_never_arms() {
+        let t0 = Instant::now();
+        let mut prewarm = Prewarm::new();
+        prewarm.opened(None, menu());
+        prewarm.highlighted(Some(0), t0);
+        assert_eq!(prewarm.wake_at(), None);
+    }
+}
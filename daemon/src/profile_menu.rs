@@ -0,0 +1,112 @@
+//! Profile menus the daemon runs
+//!
+//! The `default` profile's menu is the Settings ring from config.json
+//! (`radial_menu.slices`), which the overlay draws and runs. Every other
+//! profile's menu comes from profiles.json: the payload sends its labels and
+//! icons (`slices`), the overlay reports the picked slot with
+//! `ReportMenuClosed`, and the daemon runs that slot's action here, under the
+//! execution policy. A flick on such a menu runs the slot straight from the
+//! gesture loop. Ad-hoc menus (`ShowAdHocMenu`) run theirs the same way.
+
+use crate::actions::{Action, ActionExecutor, ActionType};
+use crate::execution_policy::ExecutionPolicy;
+use crate::handles::DaemonHandles;
+use crate::invocation::InvocationContext;
+
+/// The profile menu on screen: the profile and the action behind each slot
+#[derive(Debug, Clone)]
+pub struct ProfileMenu {
+    pub profile: String,
+    actions: Vec<Option<Action>>,
+}
+
+impl ProfileMenu {
+    pub fn new(profile: &str, actions: &[Option<Action>]) -> Self {
+        Self {
+            profile: profile.to_string(),
+            actions: actions.to_vec(),
+        }
+    }
+
+    /// The action a release runs, by slot (for pre-warming)
+    pub fn actions(&self) -> Vec<Option<Action>> {
+        (0..self.actions.len())
+            .map(|slot| i32::try_from(slot).ok().and_then(|slot| self.action(slot)).cloned())
+            .collect()
+    }
+
+    /// Action a release on `slot` runs; `None` for the hub (-1), empty
+    /// slots and `wheel_axis` slices, whose steps ran while it was held
+    pub fn action(&self, slot: i32) -> Option<&Action> {
+        let action = self.actions.get(usize::try_from(slot).ok()?)?.as_ref()?;
+        (!matches!(action.action_type, ActionType::None | ActionType::WheelAxis { .. })).then_some(action)
+    }
+}
+
+/// Run the action picked from `slot` of a daemon-run menu
+///
+/// Command and D-Bus executors may block, so this runs on its own thread
+/// with a current-thread runtime, like `ExecutePreset`, and records the
+/// outcome in the menu session log. `profile_switch` is not an executor
+/// action; the caller applies it.
+pub fn spawn_slice_action(
+    action: Action,
+    slot: u8,
+    policy: ExecutionPolicy,
+    context: InvocationContext,
+    handles: DaemonHandles,
+) {
+    std::thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(rt) => rt,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to build runtime for menu action");
+                return;
+            }
+        };
+        match rt.block_on(ActionExecutor::execute_with_policy(&action, &policy, &context, &handles)) {
+            Ok(()) => handles.menu_log.action_result(Some(slot), None),
+            Err(e) => {
+                tracing::warn!(error = %e, slot, "Menu action failed");
+                handles.menu_log.action_result(Some(slot), Some(&e.to_string()));
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(action_type: ActionType) -> Option<Action> {
+        Some(Action {
+            action_type,
+            label: None,
+            icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        })
+    }
+
+    #[test]
+    fn test_only_runnable_slots_have_an_action() {
+        let axis: Action = serde_json::from_value(serde_json::json!({
+            "type": "wheel_axis",
+            "value": {"up": {"type": "shortcut", "value": "ctrl+plus"}, "down": {"type": "shortcut", "value": "ctrl+minus"}}
+        }))
+        .unwrap();
+        let menu = ProfileMenu::new(
+            "blender",
+            &[action(ActionType::Shortcut("Tab".to_string())), Some(axis), action(ActionType::None), None],
+        );
+        assert!(menu.action(0).is_some());
+        assert!(menu.action(1).is_none());
+        assert!(menu.action(2).is_none());
+        assert!(menu.action(3).is_none());
+        assert!(menu.action(-1).is_none());
+        assert!(menu.action(12).is_none());
+        let runnable: Vec<bool> = menu.actions().iter().map(Option::is_some).collect();
+        assert_eq!(runnable, [true, false, false, false]);
+    }
+}
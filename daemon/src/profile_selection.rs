@@ -0,0 +1,310 @@
+//! Manual profile selection (`profile_switch` actions and `SetProfile`)
+//!
+//! Window-class (and virtual desktop) matching picks the profile for the
+//! focused app. A profile
+//! selected by hand takes precedence over that match until the focus moves to
+//! a window with a different class; from then on matching resumes. The
+//! selection and the class it was made in are saved to
+//! `~/.config/juhradial/profile_state.json`, so a restart keeps it for as long
+//! as the same window class stays focused.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::content_hash::ContentHash;
+use crate::fs_util;
+use crate::persistence::{PersistJob, PersistQueue};
+use crate::profiles::{ProfileError, ProfileManager};
+use crate::thumbwheel_mapping::SharedThumbwheelMapper;
+
+/// State file name in the config directory
+const STATE_FILENAME: &str = "profile_state.json";
+
+/// Manually selected profile and the window class it applies to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSelection {
+    /// Selected profile name, `None` while window matching applies
+    #[serde(default)]
+    profile: Option<String>,
+    /// Lowercased window class focused when the selection was made
+    #[serde(default)]
+    window_class: String,
+    /// Lowercased window class focused now
+    #[serde(skip)]
+    active_class: String,
+    /// Current virtual desktop, when the session reports one
+    #[serde(skip)]
+    active_desktop: Option<u32>,
+}
+
+/// Shared selection: D-Bus `SetProfile`, the thumb-wheel and the focus
+/// consumer all update it
+pub type SharedProfileSelection = Arc<RwLock<ProfileSelection>>;
+
+impl ProfileSelection {
+    /// Load the saved selection; a missing or unreadable file starts empty
+    pub fn load(path: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        match serde_json::from_str(&content) {
+            Ok(selection) => selection,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Ignoring unreadable profile state");
+                Self::default()
+            }
+        }
+    }
+
+    /// Save the selection (the focused class is not persisted)
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs_util::write_private(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Queue the selection on `queue` instead of writing it on the caller's
+    /// path
+    pub fn save_deferred(&self, path: &Path, queue: &PersistQueue) {
+        match serde_json::to_string_pretty(self) {
+            Ok(contents) => queue.submit(PersistJob::WriteState {
+                path: path.to_path_buf(),
+                contents: contents.into_bytes(),
+            }),
+            Err(e) => tracing::warn!(error = %e, "Failed to serialize profile selection"),
+        }
+    }
+
+    /// Manually selected profile, if any
+    pub fn selected(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Select `name` for the focused window class
+    pub fn select(&mut self, name: &str) {
+        self.profile = Some(name.to_string());
+        self.window_class = self.active_class.clone();
+    }
+
+    /// Drop the manual selection so window matching applies again
+    pub fn clear(&mut self) {
+        self.profile = None;
+        self.window_class.clear();
+    }
+
+    /// Drop a selection whose profile is no longer loaded
+    ///
+    /// Returns the selected name still in effect, if any.
+    pub fn retain_loaded(&mut self, manager: &ProfileManager) -> Option<&str> {
+        if let Some(ref name) = self.profile {
+            if !manager.profiles().any(|p| &p.name == name) {
+                tracing::warn!(profile = %name, "Selected profile no longer exists; resuming window matching");
+                self.clear();
+            }
+        }
+        self.selected()
+    }
+
+    /// Focus moved to a window with this class
+    ///
+    /// Returns `true` when this dropped the manual selection.
+    pub fn set_active_window(&mut self, class: &str) -> bool {
+        let class = class.to_lowercase();
+        if class == self.active_class {
+            return false;
+        }
+        self.active_class = class;
+        if self.profile.is_none() || self.active_class == self.window_class {
+            return false;
+        }
+        self.clear();
+        true
+    }
+
+    /// The current virtual desktop changed; a manual selection is kept
+    pub fn set_active_desktop(&mut self, desktop: Option<u32>) {
+        self.active_desktop = desktop;
+    }
+
+    /// Name of the profile in effect: the selection while its profile still
+    /// exists, otherwise the focused window's match on the current desktop
+    pub fn effective<'a>(&'a self, manager: &'a ProfileManager) -> &'a str {
+        match self.profile {
+            Some(ref name) if manager.profiles().any(|p| &p.name == name) => name,
+            _ => &manager.get_profile_for_context(&self.active_class, self.active_desktop).name,
+        }
+    }
+
+    /// Resolve a `profile_switch` target from the profile in effect and
+    /// select it; returns the selected name
+    pub fn switch(&mut self, manager: &ProfileManager, target: &str) -> Result<String, ProfileError> {
+        let name = manager.switch_target(self.effective(manager), target)?.name.clone();
+        self.select(&name);
+        Ok(name)
+    }
+}
+
+/// Path of the state file (~/.config/juhradial/profile_state.json)
+pub fn get_state_path() -> PathBuf {
+    crate::profiles::get_config_dir().join(STATE_FILENAME)
+}
+
+/// Load the saved selection for sharing
+pub fn load_shared_selection() -> SharedProfileSelection {
+    Arc::new(RwLock::new(ProfileSelection::load(&get_state_path())))
+}
+
+/// Apply a `profile_switch` target (a profile name, `next` or `previous`)
+///
+/// Re-reads profiles.json, selects the resolved profile, saves the state file
+/// and points the thumb-wheel bindings at it. Returns the selected name.
+pub fn apply_switch(
+    selection: &SharedProfileSelection,
+    thumbwheel: &SharedThumbwheelMapper,
+    target: &str,
+    queue: &PersistQueue,
+) -> Result<String, ProfileError> {
+    let manager = ProfileManager::load_or_create()?;
+    let (name, snapshot) = {
+        let mut selection = selection
+            .write()
+            .map_err(|e| ProfileError::ValidationError(format!("profile selection lock: {}", e)))?;
+        let name = selection.switch(&manager, target)?;
+        (name, selection.clone())
+    };
+    snapshot.save_deferred(&get_state_path(), queue);
+    match thumbwheel.write() {
+        Ok(mut mapper) => {
+            mapper.set_profiles(&manager);
+            mapper.set_selected(Some(&name));
+        }
+        Err(e) => tracing::error!(error = %e, "Failed to update thumb-wheel profile"),
+    }
+    tracing::info!(target, profile = %name, "Profile selected");
+    Ok(name)
+}
+
+/// Make `class` the focused window for menus and thumb-wheel bindings
+///
+/// Drops a manual selection made in another window class (and saves that).
+/// Returns the hash of the profile now in effect, or `None` when `class` was
+/// already in effect.
+pub fn focus_window(
+    selection: &SharedProfileSelection,
+    thumbwheel: &SharedThumbwheelMapper,
+    class: &str,
+    queue: &PersistQueue,
+) -> Option<Option<ContentHash>> {
+    // A manual selection lasts until the window class changes
+    let dropped = match selection.write() {
+        Ok(mut selection) => selection.set_active_window(class),
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to update profile selection focus");
+            false
+        }
+    };
+    if dropped {
+        tracing::info!(class = %class, "Focus changed - manual profile selection cleared");
+        if let Ok(selection) = selection.read() {
+            selection.save_deferred(&get_state_path(), queue);
+        }
+    }
+    match thumbwheel.write() {
+        Ok(mut mapper) => {
+            if mapper.active_class() == class.to_lowercase() && !dropped {
+                return None;
+            }
+            mapper.set_active_window(class);
+            if dropped {
+                mapper.set_selected(None);
+            }
+            Some(mapper.profile_hash())
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to update thumb-wheel focus");
+            Some(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn manager(dir: &TempDir) -> ProfileManager {
+        let path = dir.path().join("profiles.json");
+        let empty = serde_json::json!([null, null, null, null, null, null, null, null]);
+        let doc = serde_json::json!({
+            "profiles": [
+                {"name": "default", "slices": empty},
+                {"name": "blender", "window_class": "blender", "slices": empty},
+                {"name": "writing", "slices": empty},
+                {"name": "code", "virtual_desktop": 2, "slices": empty}
+            ]
+        });
+        fs::write(&path, doc.to_string()).unwrap();
+        ProfileManager::load_from_path(&path).unwrap()
+    }
+
+    #[test]
+    fn test_selection_holds_until_class_changes() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(&dir);
+        let mut selection = ProfileSelection::default();
+        selection.set_active_window("blender");
+        assert_eq!(selection.effective(&manager), "blender");
+
+        assert_eq!(selection.switch(&manager, "next").unwrap(), "code");
+        assert_eq!(selection.switch(&manager, "next").unwrap(), "default");
+        assert_eq!(selection.switch(&manager, "next").unwrap(), "writing");
+        assert_eq!(selection.effective(&manager), "writing");
+        // Same class again keeps it; another class resumes matching
+        assert!(!selection.set_active_window("Blender"));
+        assert!(selection.set_active_window("konsole"));
+        assert_eq!(selection.selected(), None);
+        assert_eq!(selection.effective(&manager), "default");
+        assert!(selection.switch(&manager, "gimp").is_err());
+
+        selection.select("removed");
+        assert_eq!(selection.effective(&manager), "default");
+        assert_eq!(selection.retain_loaded(&manager), None);
+    }
+
+    #[test]
+    fn test_matching_follows_the_virtual_desktop() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(&dir);
+        let mut selection = ProfileSelection::default();
+        selection.set_active_window("konsole");
+        selection.set_active_desktop(Some(2));
+        assert_eq!(selection.effective(&manager), "code");
+        // The window class still wins over the desktop
+        selection.set_active_window("blender");
+        assert_eq!(selection.effective(&manager), "blender");
+
+        // A desktop switch keeps a manual selection
+        selection.select("writing");
+        selection.set_active_desktop(Some(1));
+        assert_eq!(selection.effective(&manager), "writing");
+    }
+
+    #[test]
+    fn test_selection_round_trips_through_state_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(STATE_FILENAME);
+        let mut selection = ProfileSelection::default();
+        selection.set_active_window("firefox");
+        selection.select("writing");
+        selection.save(&path).unwrap();
+
+        let mut restored = ProfileSelection::load(&path);
+        assert_eq!(restored.selected(), Some("writing"));
+        // After a restart the first report of the same class keeps it
+        assert!(!restored.set_active_window("firefox"));
+        assert_eq!(restored.selected(), Some("writing"));
+        assert_eq!(ProfileSelection::load(&dir.path().join("missing")), ProfileSelection::default());
+    }
+}
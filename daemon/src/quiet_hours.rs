@@ -0,0 +1,413 @@
+//! Quiet hours: scheduled and on-demand suppression of haptics and
+//! notifications
+//!
+//! `quiet_hours.schedule` lists windows of local time (`{days, start, end}`).
+//! A window whose end is not after its start runs past midnight and belongs
+//! to the day it starts on, so `fri 22:00-07:00` covers Friday night into
+//! Saturday morning. [`run`] re-evaluates the schedule against the wall clock
+//! every [`TICK`] instead of computing absolute instants up front, so a
+//! timezone change or a DST switch in the middle of a window is picked up on
+//! the next tick. `QuietNow(minutes)` forces quiet for a while on top of the
+//! schedule; its deadline is monotonic and ignores clock changes.
+//!
+//! While quiet, `HapticManager` plays no event pulses and the settings app
+//! holds back its low-battery notification (it reads `quiet_hours` from
+//! `Status()`). Auditioning a waveform on the haptics page still plays.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SharedConfig;
+use crate::handles::StateHandle;
+
+/// How often the schedule is checked against the clock
+pub const TICK: Duration = Duration::from_secs(30);
+
+/// Longest `QuietNow` override, in minutes (one day)
+pub const MAX_OVERRIDE_MINUTES: u32 = 24 * 60;
+
+/// Day of the week in a schedule window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    #[serde(alias = "monday")]
+    Mon,
+    #[serde(alias = "tuesday")]
+    Tue,
+    #[serde(alias = "wednesday")]
+    Wed,
+    #[serde(alias = "thursday")]
+    Thu,
+    #[serde(alias = "friday")]
+    Fri,
+    #[serde(alias = "saturday")]
+    Sat,
+    #[serde(alias = "sunday")]
+    Sun,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Sun,
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+    ];
+
+    /// From `tm_wday` (0 = Sunday)
+    pub fn from_tm(wday: i32) -> Self {
+        Self::ALL[wday.rem_euclid(7) as usize]
+    }
+
+    /// The day before
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|&d| d == self).unwrap_or(0);
+        Self::ALL[(index + 6) % 7]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Weekday::Mon => "mon",
+            Weekday::Tue => "tue",
+            Weekday::Wed => "wed",
+            Weekday::Thu => "thu",
+            Weekday::Fri => "fri",
+            Weekday::Sat => "sat",
+            Weekday::Sun => "sun",
+        }
+    }
+}
+
+/// A bad `"HH:MM"` time in a schedule window
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid time '{0}' (expected HH:MM, 00:00 to 23:59)")]
+pub struct ClockTimeError(String);
+
+/// Time of day as minutes after midnight, written `"HH:MM"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ClockTime(u16);
+
+impl ClockTime {
+    pub fn new(hour: u16, minute: u16) -> Option<Self> {
+        (hour < 24 && minute < 60).then_some(Self(hour * 60 + minute))
+    }
+
+    pub fn minutes(self) -> u16 {
+        self.0
+    }
+}
+
+impl FromStr for ClockTime {
+    type Err = ClockTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ClockTimeError(s.to_string());
+        let (hour, minute) = s.trim().split_once(':').ok_or_else(err)?;
+        if minute.len() != 2 {
+            return Err(err());
+        }
+        let hour = hour.parse().map_err(|_| err())?;
+        let minute = minute.parse().map_err(|_| err())?;
+        Self::new(hour, minute).ok_or_else(err)
+    }
+}
+
+impl TryFrom<String> for ClockTime {
+    type Error = ClockTimeError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ClockTime> for String {
+    fn from(time: ClockTime) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for ClockTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / 60, self.0 % 60)
+    }
+}
+
+/// One `quiet_hours.schedule` entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietWindow {
+    /// Days the window starts on; empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    pub start: ClockTime,
+    /// Not after `start`: the window ends the next day
+    pub end: ClockTime,
+}
+
+impl QuietWindow {
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// Whether the window covers `now`
+    ///
+    /// Equal start and end make a full 24 hours from the start.
+    pub fn contains(&self, now: LocalTime) -> bool {
+        let minute = now.minute;
+        if self.start < self.end {
+            return self.starts_on(now.weekday) && (self.start.0..self.end.0).contains(&minute);
+        }
+        (self.starts_on(now.weekday) && minute >= self.start.0)
+            || (self.starts_on(now.weekday.previous()) && minute < self.end.0)
+    }
+}
+
+impl fmt::Display for QuietWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.days.is_empty() {
+            write!(f, "daily")?;
+        } else {
+            let days: Vec<&str> = self.days.iter().map(|d| d.name()).collect();
+            write!(f, "{}", days.join(","))?;
+        }
+        write!(f, " {}-{}", self.start, self.end)
+    }
+}
+
+/// Local wall-clock time, as far as the schedule cares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub weekday: Weekday,
+    /// Minutes after local midnight
+    pub minute: u16,
+}
+
+extern "C" {
+    // Not bound by `libc` on Linux; re-reads TZ and /etc/localtime
+    fn tzset();
+}
+
+impl LocalTime {
+    /// The current local time, with the timezone re-read so a change to
+    /// TZ data or the system zone applies
+    pub fn now() -> Option<Self> {
+        // SAFETY: tzset and localtime_r only write the libc timezone globals
+        // and the `tm` we own
+        unsafe {
+            tzset();
+            let now = libc::time(std::ptr::null_mut());
+            let mut tm: libc::tm = std::mem::zeroed();
+            if libc::localtime_r(&now, &mut tm).is_null() {
+                return None;
+            }
+            Some(Self {
+                weekday: Weekday::from_tm(tm.tm_wday),
+                minute: (tm.tm_hour * 60 + tm.tm_min) as u16,
+            })
+        }
+    }
+}
+
+/// Whether quiet hours are on, and why
+#[derive(Debug, Default)]
+pub struct QuietHours {
+    /// The schedule window covering the last evaluation
+    scheduled: Option<QuietWindow>,
+    /// End of a `QuietNow` override
+    override_until: Option<Instant>,
+}
+
+impl QuietHours {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check the schedule at `now`; returns `true` when the scheduled state
+    /// changed
+    pub fn evaluate(&mut self, schedule: &[QuietWindow], now: LocalTime) -> bool {
+        let window = schedule.iter().find(|w| w.contains(now)).cloned();
+        if window == self.scheduled {
+            return false;
+        }
+        match &window {
+            Some(w) => tracing::info!(window = %w, "Quiet hours started"),
+            None => tracing::info!("Quiet hours ended"),
+        }
+        self.scheduled = window;
+        true
+    }
+
+    /// Force quiet for `minutes` from `now` (capped at
+    /// [`MAX_OVERRIDE_MINUTES`]); 0 clears the override
+    pub fn set_override(&mut self, minutes: u32, now: Instant) {
+        self.override_until = (minutes > 0).then(|| {
+            now + Duration::from_secs(u64::from(minutes.min(MAX_OVERRIDE_MINUTES)) * 60)
+        });
+    }
+
+    /// Drop an override that ran out; `true` when one did
+    pub fn expire_override(&mut self, now: Instant) -> bool {
+        if self.override_until.is_some_and(|until| now >= until) {
+            self.override_until = None;
+            return true;
+        }
+        false
+    }
+
+    fn override_left(&self, now: Instant) -> Option<Duration> {
+        self.override_until
+            .map(|until| until.saturating_duration_since(now))
+            .filter(|left| !left.is_zero())
+    }
+
+    pub fn is_quiet(&self, now: Instant) -> bool {
+        self.scheduled.is_some() || self.override_left(now).is_some()
+    }
+
+    /// `quiet_hours` in `Status()`
+    pub fn to_json(&self, now: Instant) -> serde_json::Value {
+        let override_left = self.override_left(now);
+        let reason = if override_left.is_some() {
+            Some("override")
+        } else if self.scheduled.is_some() {
+            Some("schedule")
+        } else {
+            None
+        };
+        serde_json::json!({
+            "active": self.is_quiet(now),
+            "reason": reason,
+            "window": self.scheduled.as_ref().map(|w| w.to_string()),
+            "override_seconds_left": override_left.map(|d| d.as_secs()),
+        })
+    }
+}
+
+/// Quiet state shared by the service, the haptic manager and [`run`]
+pub type QuietHoursHandle = StateHandle<QuietHours>;
+
+impl QuietHoursHandle {
+    /// Whether haptics and notifications are suppressed right now
+    pub fn is_quiet(&self) -> bool {
+        self.update(|q| q.is_quiet(Instant::now())).unwrap_or(false)
+    }
+
+    /// Check `schedule` against the clock now (startup tick, `ReloadConfig`)
+    pub fn evaluate(&self, schedule: &[QuietWindow]) {
+        let Some(now) = LocalTime::now() else {
+            tracing::warn!("Could not read the local time; quiet hours unchanged");
+            return;
+        };
+        self.update(|q| q.evaluate(schedule, now));
+    }
+
+    /// `QuietNow(minutes)`
+    pub fn quiet_now(&self, minutes: u32) {
+        self.update(|q| q.set_override(minutes, Instant::now()));
+    }
+
+    /// `quiet_hours` in `Status()`
+    pub fn status(&self) -> serde_json::Value {
+        self.update(|q| q.to_json(Instant::now())).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+/// Re-evaluate the live schedule every [`TICK`]
+pub async fn run(config: SharedConfig, quiet: QuietHoursHandle) {
+    let mut tick = tokio::time::interval(TICK);
+    loop {
+        tick.tick().await;
+        let schedule = match config.read() {
+            Ok(c) => c.quiet_hours.schedule.clone(),
+            Err(_) => continue,
+        };
+        quiet.evaluate(&schedule);
+        if quiet.update(|q| q.expire_override(Instant::now())).unwrap_or(false) {
+            tracing::info!("QuietNow override ended");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(weekday: Weekday, time: &str) -> LocalTime {
+        LocalTime { weekday, minute: time.parse::<ClockTime>().unwrap().minutes() }
+    }
+
+    fn window(json: &str) -> QuietWindow {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_clock_time_parsing() {
+        assert_eq!("07:05".parse::<ClockTime>().unwrap().minutes(), 425);
+        assert_eq!("7:05".parse::<ClockTime>().unwrap().to_string(), "07:05");
+        for bad in ["24:00", "12:60", "12", "12:5", "ab:cd", ""] {
+            assert!(bad.parse::<ClockTime>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_window_within_a_day() {
+        let w = window(r#"{"days": ["mon", "tuesday"], "start": "12:00", "end": "13:30"}"#);
+        assert!(w.contains(at(Weekday::Mon, "12:00")));
+        assert!(w.contains(at(Weekday::Tue, "13:29")));
+        assert!(!w.contains(at(Weekday::Tue, "13:30")));
+        assert!(!w.contains(at(Weekday::Wed, "12:30")));
+        assert_eq!(w.to_string(), "mon,tue 12:00-13:30");
+    }
+
+    #[test]
+    fn test_window_across_midnight_belongs_to_its_start_day() {
+        let w = window(r#"{"days": ["fri"], "start": "22:00", "end": "07:00"}"#);
+        assert!(w.contains(at(Weekday::Fri, "23:59")));
+        assert!(w.contains(at(Weekday::Sat, "06:59")));
+        assert!(!w.contains(at(Weekday::Sat, "07:00")));
+        assert!(!w.contains(at(Weekday::Sat, "22:30")));
+        // Thursday night's tail is not Friday's window
+        assert!(!w.contains(at(Weekday::Fri, "03:00")));
+        // Sunday night runs into Monday
+        let w = window(r#"{"days": ["sun"], "start": "23:00", "end": "01:00"}"#);
+        assert!(w.contains(at(Weekday::Mon, "00:30")));
+
+        let daily = window(r#"{"start": "21:00", "end": "21:00"}"#);
+        assert!(daily.contains(at(Weekday::Wed, "12:00")));
+        assert_eq!(daily.to_string(), "daily 21:00-21:00");
+    }
+
+    #[test]
+    fn test_schedule_and_override() {
+        let schedule = vec![window(r#"{"start": "22:00", "end": "07:00"}"#)];
+        let t0 = Instant::now();
+        let mut quiet = QuietHours::new();
+        assert!(!quiet.evaluate(&schedule, at(Weekday::Mon, "21:59")));
+        assert!(!quiet.is_quiet(t0));
+        assert!(quiet.evaluate(&schedule, at(Weekday::Mon, "22:00")));
+        assert!(!quiet.evaluate(&schedule, at(Weekday::Tue, "03:00")));
+        let status = quiet.to_json(t0);
+        assert_eq!(status["reason"], "schedule");
+        assert_eq!(status["window"], "daily 22:00-07:00");
+        assert!(quiet.evaluate(&schedule, at(Weekday::Tue, "07:00")));
+
+        quiet.set_override(10, t0);
+        assert!(quiet.is_quiet(t0 + Duration::from_secs(599)));
+        assert_eq!(quiet.to_json(t0)["reason"], "override");
+        assert_eq!(quiet.to_json(t0)["override_seconds_left"], 600);
+        assert!(!quiet.is_quiet(t0 + Duration::from_secs(600)));
+        assert!(quiet.expire_override(t0 + Duration::from_secs(600)));
+        assert!(!quiet.expire_override(t0 + Duration::from_secs(600)));
+
+        quiet.set_override(u32::MAX, t0);
+        assert_eq!(quiet.to_json(t0)["override_seconds_left"], u64::from(MAX_OVERRIDE_MINUTES) * 60);
+        quiet.set_override(0, t0);
+        assert_eq!(quiet.to_json(t0)["active"], false);
+    }
+}
@@ -0,0 +1,264 @@
+//! Combined reload on SIGHUP (`systemctl reload`)
+//!
+//! `ReloadConfig` and the theme watcher each reload one source, so a change
+//! spanning them (a new theme and a config that selects it) can land in the
+//! wrong order. SIGHUP re-reads everything as one transaction: config.json,
+//! the theme directories and profiles.json are parsed into a [`Staged`] set,
+//! the references between them are checked (`theme` names a loaded theme,
+//! every `profile_switch` target names a profile), and only a set that passes
+//! is swapped into the live daemon, through the same path as `ReloadConfig`.
+//! A failure at any stage keeps everything as it was. Either way the outcome
+//! is one log line and a `ReloadCompleted` or `ReloadFailed` signal.
+//!
+//! Profiles carry no theme of their own, so the theme reference checked is
+//! the config's.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::content_hash::ContentHash;
+use crate::paths::Paths;
+use crate::profiles::{Profile, ProfileManager};
+use crate::theme::{ThemeManager, ThemeSource};
+use crate::validation::{IssueCode, ValidationIssue};
+
+/// Where a reload reads from
+#[derive(Debug, Clone)]
+pub struct ReloadSources {
+    pub config: PathBuf,
+    pub profiles: PathBuf,
+    /// Theme directories in load order, later overriding earlier
+    pub theme_dirs: Vec<(PathBuf, ThemeSource)>,
+}
+
+impl ReloadSources {
+    /// The daemon's own files
+    pub fn default_paths() -> Self {
+        Self::from_paths(crate::paths::paths())
+    }
+
+    /// The files under `paths`
+    pub fn from_paths(paths: &Paths) -> Self {
+        let theme_dirs = if cfg!(feature = "themes") {
+            let [system_dir, user_dir] = paths.themes_dirs();
+            vec![(system_dir, ThemeSource::System), (user_dir, ThemeSource::User)]
+        } else {
+            Vec::new()
+        };
+        Self {
+            config: paths.config_path(),
+            profiles: paths.profiles_path(),
+            theme_dirs,
+        }
+    }
+}
+
+/// Stage a reload failed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadStage {
+    Config,
+    Profiles,
+    References,
+}
+
+/// Why a reload was rolled back, sent as `ReloadFailed`
+#[derive(Debug, Clone, PartialEq, Serialize, thiserror::Error)]
+#[error("{stage:?} stage failed: {}", .errors.join("; "))]
+pub struct ReloadFailure {
+    pub stage: ReloadStage,
+    pub errors: Vec<String>,
+    /// The same findings, structured
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ReloadFailure {
+    fn new(stage: ReloadStage, issues: Vec<ValidationIssue>) -> Self {
+        Self {
+            stage,
+            errors: issues.iter().map(|i| format!("{}: {}", i.field, i.message)).collect(),
+            issues,
+        }
+    }
+}
+
+/// What a reload applied, sent as `ReloadCompleted`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReloadSummary {
+    pub theme: String,
+    pub themes: usize,
+    pub profiles: usize,
+    pub profiles_hash: ContentHash,
+}
+
+/// Config, themes and profiles read and checked, not yet applied
+pub struct Staged {
+    pub config: Config,
+    /// With `config.theme` selected
+    pub themes: ThemeManager,
+    pub profiles: ProfileManager,
+}
+
+impl Staged {
+    pub fn summary(&self) -> ReloadSummary {
+        ReloadSummary {
+            theme: self.themes.current().name.clone(),
+            themes: self.themes.theme_names().len(),
+            profiles: self.profiles.profile_count(),
+            profiles_hash: self.profiles.content_hash(),
+        }
+    }
+}
+
+/// Read all three sources and check their references
+///
+/// Nothing live is touched, so an `Err` needs no undo.
+pub fn stage(sources: &ReloadSources) -> Result<Staged, ReloadFailure> {
+    let config = Config::load(&sources.config).map_err(|e| {
+        ReloadFailure::new(ReloadStage::Config, vec![ValidationIssue::error("", IssueCode::ParseError, e.to_string())])
+    })?;
+    let profiles = ProfileManager::load_from_path(&sources.profiles).map_err(|e| {
+        ReloadFailure::new(ReloadStage::Profiles, vec![ValidationIssue::error("", IssueCode::ParseError, e.to_string())])
+    })?;
+    profiles.lint_shortcuts(&config.shortcut_lint_rules());
+    let mut themes = ThemeManager::new();
+    for (dir, source) in &sources.theme_dirs {
+        themes.load_from_dir(dir, *source);
+    }
+
+    let issues = cross_check(&config, &themes, &profiles);
+    if !issues.is_empty() {
+        return Err(ReloadFailure::new(ReloadStage::References, issues));
+    }
+    // Checked above, so this selects it
+    let _ = themes.set_current(&config.theme);
+    Ok(Staged { config, themes, profiles })
+}
+
+/// References between the staged sources that do not resolve
+pub fn cross_check(config: &Config, themes: &ThemeManager, profiles: &ProfileManager) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    if !themes.has_theme(&config.theme) {
+        issues.push(
+            ValidationIssue::error("theme", IssueCode::Missing, format!("theme '{}' is not among the loaded themes", config.theme))
+                .got(config.theme.as_str()),
+        );
+    }
+    let mut loaded: Vec<Profile> = profiles.profiles().cloned().collect();
+    loaded.sort_by(|a, b| a.name.cmp(&b.name));
+    for unknown in crate::profiles::unknown_switch_targets(&loaded) {
+        let mut field = format!("profiles.{}.{}", loaded[unknown.profile].name, unknown.field);
+        if let Some(slice) = unknown.slice {
+            field.push_str(&format!("[{slice}]"));
+        }
+        issues.push(ValidationIssue::error(field, IssueCode::UnknownProfile, unknown.to_string()).got(unknown.target.as_str()));
+    }
+    issues
+}
+
+/// Stage from `sources`, logging a rollback in one line
+pub fn stage_logged(sources: &ReloadSources) -> Result<Staged, ReloadFailure> {
+    let staged = stage(sources);
+    if let Err(ref failure) = staged {
+        tracing::warn!(
+            stage = ?failure.stage,
+            config = %sources.config.display(),
+            profiles = %sources.profiles.display(),
+            "Reload rolled back, keeping the running configuration: {}",
+            failure.errors.join("; ")
+        );
+    }
+    staged
+}
+
+/// Reload everything on each SIGHUP for the life of the daemon
+pub async fn run_on_sighup(connection: zbus::Connection, mut sighup: tokio::signal::unix::Signal) {
+    while sighup.recv().await.is_some() {
+        tracing::info!("SIGHUP received, reloading config, themes and profiles");
+        let result = async {
+            let iface = connection
+                .object_server()
+                .interface::<_, crate::dbus::JuhRadialService>(crate::DBUS_PATH)
+                .await?;
+            let service = iface.get().await;
+            service.reload_all(iface.signal_emitter()).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Failed to announce the SIGHUP reload");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const PROFILES: &str = r#"{"version": 2, "profiles": [
+        {"name": "default", "slices": [{"type": "profile_switch", "value": "writing"}]},
+        {"name": "writing", "slices": []}
+    ]}"#;
+
+    fn sources(dir: &TempDir, theme: &str, profiles: &str) -> ReloadSources {
+        let config = dir.path().join("config.json");
+        fs::write(&config, format!(r#"{{"theme": "{theme}"}}"#)).unwrap();
+        let profiles_path = dir.path().join("profiles.json");
+        fs::write(&profiles_path, profiles).unwrap();
+        let themes = dir.path().join("themes");
+        fs::create_dir_all(&themes).unwrap();
+        ReloadSources { config, profiles: profiles_path, theme_dirs: vec![(themes, ThemeSource::User)] }
+    }
+
+    fn write_theme(sources: &ReloadSources, name: &str, accent: &str) {
+        let json = format!(
+            r##"{{"name": "{name}", "colors": {{"base": "#1e1e2e", "surface": "#313244", "text": "#cdd6f4",
+                "accent": "{accent}", "border": "#585b70"}}, "glassmorphism": {{}}, "animation": {{}}}}"##
+        );
+        fs::write(sources.theme_dirs[0].0.join(format!("{name}.json")), json).unwrap();
+    }
+
+    #[test]
+    fn test_new_theme_and_config_stage_together() {
+        let dir = TempDir::new().unwrap();
+        let sources = sources(&dir, "midnight", PROFILES);
+        write_theme(&sources, "midnight", "#b4befe");
+        let staged = stage(&sources).unwrap();
+        assert_eq!(staged.themes.current().name, "midnight");
+        assert_eq!(staged.config.theme, "midnight");
+        let summary = staged.summary();
+        assert_eq!((summary.theme.as_str(), summary.profiles), ("midnight", 2));
+    }
+
+    #[test]
+    fn test_unresolved_references_roll_back() {
+        let dir = TempDir::new().unwrap();
+        // The config names a theme whose file is broken, and a slice
+        // switches to a profile that was renamed away
+        let profiles = PROFILES.replace(r#""name": "writing""#, r#""name": "prose""#);
+        let sources = sources(&dir, "midnight", &profiles);
+        fs::write(sources.theme_dirs[0].0.join("midnight.json"), r#"{"name": "midnight", "colors": {"#).unwrap();
+
+        let failure = stage_logged(&sources).err().unwrap();
+        assert_eq!(failure.stage, ReloadStage::References);
+        let fields: Vec<&str> = failure.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["theme", "profiles.default.slices[0]"]);
+        assert_eq!(failure.issues[1].code, IssueCode::UnknownProfile);
+        let json = serde_json::to_value(&failure).unwrap();
+        assert_eq!(json["stage"], "references");
+        assert_eq!(json["issues"][0]["got"], "midnight");
+        assert!(failure.errors[0].starts_with("theme: theme 'midnight'"));
+    }
+
+    #[test]
+    fn test_unreadable_source_fails_its_stage() {
+        let dir = TempDir::new().unwrap();
+        let sources = sources(&dir, "catppuccin-mocha", "{not json");
+        assert_eq!(stage(&sources).err().unwrap().stage, ReloadStage::Profiles);
+        fs::write(&sources.config, "{\"theme\": ").unwrap();
+        assert_eq!(stage(&sources).err().unwrap().stage, ReloadStage::Config);
+    }
+}
@@ -0,0 +1,292 @@
+//! Running helper programs from inside a Flatpak sandbox
+//!
+//! When juhradiald is itself packaged as a Flatpak, the programs it spawns
+//! (shell commands, xdotool/ydotool, the clipboard helpers) would run inside
+//! the sandbox, where most of them do not exist. Inside a sandbox
+//! (`/.flatpak-info` present) they are started through
+//! `flatpak-spawn --host` instead, unless `execution.host_spawn` is off.
+//!
+//! The strategy is picked once at startup and handed to the executor inside
+//! [`ResolvedBinaries`]. `flatpak-spawn` needs permission to talk to
+//! `org.freedesktop.Flatpak`, so [`ExecutionStrategy::detect`] probes it with
+//! `flatpak-spawn --host true`; when that fails, actions that spawn programs
+//! fail with `ActionError::HostSpawnDenied` instead of a generic error.
+//!
+//! Host programs keep the direct-spawn semantics: they start in the daemon's
+//! working directory (`--directory`), see the host session's environment
+//! rather than the sandbox's remapped one, and outlive the daemon like a
+//! direct child would (no `--watch-bus`).
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Present inside every Flatpak sandbox
+pub const FLATPAK_INFO: &str = "/.flatpak-info";
+
+/// How long the startup probe may take before host spawning counts as denied
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Permission `flatpak-spawn --host` needs
+pub const HOST_PERMISSION: &str = "--talk-name=org.freedesktop.Flatpak";
+
+/// Where spawned programs run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ExecutionStrategy {
+    /// Not sandboxed: spawn directly
+    #[default]
+    Direct,
+    /// Sandboxed with `execution.host_spawn` off: spawn inside the sandbox
+    Sandboxed,
+    /// Sandboxed: spawn on the host through `flatpak-spawn --host`
+    HostSpawn,
+    /// Sandboxed, but the `flatpak-spawn --host` probe failed
+    HostSpawnDenied(String),
+}
+
+impl ExecutionStrategy {
+    /// The strategy for this process (`execution.host_spawn`), probing
+    /// `flatpak-spawn` when it would be used
+    pub fn detect(host_spawn: bool) -> Self {
+        Self::select(is_confined(), host_spawn, probe_host_spawn)
+    }
+
+    /// Pick the strategy; `probe` runs only when host spawning is wanted
+    pub fn select(confined: bool, host_spawn: bool, probe: impl FnOnce() -> Result<(), String>) -> Self {
+        match (confined, host_spawn) {
+            (false, _) => Self::Direct,
+            (true, false) => Self::Sandboxed,
+            (true, true) => match probe() {
+                Ok(()) => Self::HostSpawn,
+                Err(reason) => Self::HostSpawnDenied(reason),
+            },
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Direct => "direct",
+            Self::Sandboxed => "sandbox",
+            Self::HostSpawn => "flatpak-spawn",
+            Self::HostSpawnDenied(_) => "flatpak-spawn-denied",
+        }
+    }
+
+    /// Whether programs are started through `flatpak-spawn --host`
+    pub fn spawns_on_host(&self) -> bool {
+        matches!(self, Self::HostSpawn | Self::HostSpawnDenied(_))
+    }
+
+    /// Why host spawning is unavailable, if it was denied
+    pub fn denied(&self) -> Option<&str> {
+        match self {
+            Self::HostSpawnDenied(reason) => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// `Command` for `program` under this strategy; add arguments as usual
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        if !self.spawns_on_host() {
+            return Command::new(program);
+        }
+        let mut command = Command::new("flatpak-spawn");
+        command.arg("--host");
+        if let Ok(cwd) = std::env::current_dir() {
+            command.arg(format!("--directory={}", cwd.display()));
+        }
+        command.arg(program);
+        command
+    }
+
+    /// [`Self::command`] with `vars` in the program's environment
+    ///
+    /// `flatpak-spawn` does not pass its own environment on to the host, so
+    /// there each variable becomes an `--env=NAME=VALUE` argument.
+    pub fn command_with_env<K, V>(&self, program: impl AsRef<OsStr>, vars: impl IntoIterator<Item = (K, V)>) -> Command
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        if !self.spawns_on_host() {
+            let mut command = Command::new(program);
+            command.envs(vars);
+            return command;
+        }
+        let mut command = Command::new("flatpak-spawn");
+        command.arg("--host");
+        if let Ok(cwd) = std::env::current_dir() {
+            command.arg(format!("--directory={}", cwd.display()));
+        }
+        for (name, value) in vars {
+            let mut arg = std::ffi::OsString::from("--env=");
+            arg.push(name);
+            arg.push("=");
+            arg.push(value);
+            command.arg(arg);
+        }
+        command.arg(program);
+        command
+    }
+
+    /// Full path of `binary` on the PATH, if it is there
+    ///
+    /// `None` for host spawns, whose PATH is not the daemon's.
+    pub fn find(&self, binary: &str) -> Option<PathBuf> {
+        if self.spawns_on_host() {
+            return None;
+        }
+        std::env::var_os("PATH").and_then(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join(binary))
+                .find(|path| path.is_file())
+        })
+    }
+
+    /// Whether `binary` is on the PATH where programs are spawned
+    pub fn on_path(&self, binary: &str) -> bool {
+        if self.spawns_on_host() {
+            return self
+                .command("sh")
+                .args(["-c", "command -v \"$1\"", "sh", binary])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|s| s.success());
+        }
+        self.find(binary).is_some()
+    }
+
+    /// For `Status()`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "strategy": self.as_str(),
+            "error": self.denied(),
+        })
+    }
+}
+
+/// Whether the daemon runs inside a Flatpak sandbox
+pub fn is_confined() -> bool {
+    std::path::Path::new(FLATPAK_INFO).exists()
+}
+
+/// The execution strategy and the binaries found under it, shared by the
+/// executor and the pre-warm task
+///
+/// Lets a spawn skip the PATH walk once a binary has been found. Clones share
+/// one cache.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedBinaries {
+    strategy: Arc<ExecutionStrategy>,
+    paths: Arc<Mutex<HashMap<String, PathBuf>>>,
+}
+
+impl ResolvedBinaries {
+    /// An empty cache for programs spawned under `strategy`
+    pub fn new(strategy: ExecutionStrategy) -> Self {
+        Self {
+            strategy: Arc::new(strategy),
+            paths: Arc::default(),
+        }
+    }
+
+    /// Where programs are spawned
+    pub fn strategy(&self) -> &Arc<ExecutionStrategy> {
+        &self.strategy
+    }
+
+    /// Full path of `binary`, cached after the first hit
+    ///
+    /// Host spawns and binaries that are not found get the bare name back,
+    /// so spawning them behaves as before.
+    pub fn resolve(&self, binary: &str) -> PathBuf {
+        let mut paths = self.paths.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(path) = paths.get(binary) {
+            return path.clone();
+        }
+        match self.strategy.find(binary) {
+            Some(path) => {
+                paths.insert(binary.to_string(), path.clone());
+                path
+            }
+            None => PathBuf::from(binary),
+        }
+    }
+}
+
+/// Run `flatpak-spawn --host true`, giving up after [`PROBE_TIMEOUT`]
+fn probe_host_spawn() -> Result<(), String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let result = Command::new("flatpak-spawn")
+            .args(["--host", "true"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .output();
+        let _ = tx.send(result);
+    });
+    match rx.recv_timeout(PROBE_TIMEOUT) {
+        Ok(Ok(output)) if output.status.success() => Ok(()),
+        Ok(Ok(output)) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(match stderr.trim() {
+                "" => format!("flatpak-spawn exited with {}", output.status),
+                message => message.to_string(),
+            })
+        }
+        Ok(Err(e)) => Err(format!("flatpak-spawn unavailable: {}", e)),
+        Err(_) => Err(format!("flatpak-spawn did not answer within {:?}", PROBE_TIMEOUT)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategy_selection() {
+        let unreachable = || -> Result<(), String> { panic!("probe must not run") };
+        assert_eq!(ExecutionStrategy::select(false, true, unreachable), ExecutionStrategy::Direct);
+        assert_eq!(ExecutionStrategy::select(true, false, unreachable), ExecutionStrategy::Sandboxed);
+        assert_eq!(ExecutionStrategy::select(true, true, || Ok(())), ExecutionStrategy::HostSpawn);
+        let denied = ExecutionStrategy::select(true, true, || Err("AccessDenied".to_string()));
+        assert_eq!(denied.denied(), Some("AccessDenied"));
+        assert!(denied.spawns_on_host());
+        assert_eq!(denied.to_json()["strategy"], "flatpak-spawn-denied");
+        assert!(ExecutionStrategy::Direct.to_json()["error"].is_null());
+    }
+
+    #[test]
+    fn test_host_command_wraps_program() {
+        let mut direct = ExecutionStrategy::Direct.command("xdotool");
+        direct.args(["key", "ctrl+c"]);
+        assert_eq!(direct.get_program(), "xdotool");
+        assert_eq!(direct.get_args().collect::<Vec<_>>(), ["key", "ctrl+c"]);
+
+        let mut host = ExecutionStrategy::HostSpawn.command("sh");
+        host.args(["-c", "dolphin ~"]);
+        assert_eq!(host.get_program(), "flatpak-spawn");
+        let args: Vec<_> = host.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(args[0], "--host");
+        assert!(args[1].starts_with("--directory="));
+        assert_eq!(args[2..], ["sh", "-c", "dolphin ~"]);
+    }
+
+    #[test]
+    fn test_host_command_passes_environment_as_arguments() {
+        let direct = ExecutionStrategy::Direct.command_with_env("sh", [("JUH_SLICE", "2")]);
+        assert_eq!(direct.get_args().count(), 0);
+        let envs: Vec<_> = direct.get_envs().collect();
+        assert_eq!(envs, [(OsStr::new("JUH_SLICE"), Some(OsStr::new("2")))]);
+
+        let host = ExecutionStrategy::HostSpawn.command_with_env("sh", [("JUH_WINDOW_CLASS", "a b; c")]);
+        let args: Vec<_> = host.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(args[2..], ["--env=JUH_WINDOW_CLASS=a b; c", "sh"]);
+        assert_eq!(host.get_envs().count(), 0);
+    }
+}
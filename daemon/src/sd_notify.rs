@@ -0,0 +1,65 @@
+//! Minimal systemd readiness notification (sd_notify)
+//!
+//! Sends `READY=1` / `STOPPING=1` datagrams to `$NOTIFY_SOCKET` so a
+//! `Type=notify` unit reports the daemon's real state. Without the variable
+//! (started outside systemd, or `Type=simple`) every call is a no-op.
+
+use std::io;
+use std::os::unix::net::UnixDatagram;
+
+/// Startup finished: D-Bus service exported and input loops running
+pub const READY: &str = "READY=1";
+
+/// Graceful shutdown started
+pub const STOPPING: &str = "STOPPING=1";
+
+/// Send `state` to the socket named by `$NOTIFY_SOCKET`
+///
+/// Returns `Ok(false)` when no socket is configured.
+pub fn notify(state: &str) -> io::Result<bool> {
+    match std::env::var_os("NOTIFY_SOCKET") {
+        Some(socket) => notify_socket(&socket.to_string_lossy(), state).map(|()| true),
+        None => Ok(false),
+    }
+}
+
+/// Send `state` to a notify socket path (`@name` for the abstract namespace)
+pub fn notify_socket(socket: &str, state: &str) -> io::Result<()> {
+    let sock = UnixDatagram::unbound()?;
+    match socket.strip_prefix('@') {
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+            sock.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        None => {
+            sock.send_to(state.as_bytes(), socket)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_notify_socket_delivers_state() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notify.sock");
+        let server = UnixDatagram::bind(&path).unwrap();
+
+        notify_socket(path.to_str().unwrap(), READY).unwrap();
+        let mut buf = [0u8; 64];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+    }
+
+    #[test]
+    fn test_missing_socket_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("absent.sock");
+        assert!(notify_socket(path.to_str().unwrap(), STOPPING).is_err());
+    }
+}
@@ -0,0 +1,530 @@
+//! Startup self-test for device access
+//!
+//! Most "No MX Master 4 found" reports come down to udev rules or group
+//! membership. The self-test tells the cases apart: device nodes present but
+//! not openable (with the node's group and whether this process is in it),
+//! no Logitech nodes at all, and logid, Solaar, ratbagd or another program
+//! competing for the device (see [`crate::conflicts`]). Findings are
+//! logged once as a startup report and exposed through `Status()` and
+//! `Diagnostics()`, each with a remediation string the settings UI can show
+//! verbatim.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+
+use crate::conflicts::{ConflictReport, ConflictsHandle};
+
+/// Group the udev rules grant device access to
+pub(crate) const INPUT_GROUP: &str = "input";
+
+/// Outcome of one check, ordered by severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    #[default]
+    Ok,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Ok => write!(f, "ok"),
+            CheckStatus::Warning => write!(f, "warning"),
+            CheckStatus::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// One self-test finding
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    /// Check id: `device_present`, `hidraw_access`, `evdev_access`, `logid`,
+    /// `solaar`, `ratbagd`, `device_holder`, `hid_backend`
+    pub check: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Device node the finding is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<PathBuf>,
+    /// What the user should do, suitable for showing verbatim
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl Finding {
+    fn ok(check: &'static str, message: String) -> Self {
+        Self {
+            check,
+            status: CheckStatus::Ok,
+            message,
+            node: None,
+            remediation: None,
+        }
+    }
+}
+
+/// All findings from one self-test run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StartupReport {
+    /// Worst status among the findings
+    pub status: CheckStatus,
+    pub findings: Vec<Finding>,
+}
+
+/// Report shared with the D-Bus service (`Diagnostics()` refreshes it)
+pub type SharedStartupReport = Arc<RwLock<StartupReport>>;
+
+impl StartupReport {
+    fn from_findings(findings: Vec<Finding>) -> Self {
+        let status = findings.iter().map(|f| f.status).max().unwrap_or_default();
+        Self { status, findings }
+    }
+
+    /// Log the report as one block
+    pub fn log(&self) {
+        tracing::info!(status = %self.status, findings = self.findings.len(), "Startup report");
+        for f in &self.findings {
+            let node = f.node.as_ref().map(|p| p.display().to_string());
+            let remediation = f.remediation.as_deref().unwrap_or("");
+            match f.status {
+                CheckStatus::Ok => tracing::info!(check = f.check, node, "  {}", f.message),
+                CheckStatus::Warning => {
+                    tracing::warn!(check = f.check, node, remediation, "  {}", f.message)
+                }
+                CheckStatus::Error => {
+                    tracing::error!(check = f.check, node, remediation, "  {}", f.message)
+                }
+            }
+        }
+    }
+
+    /// Report as JSON for `Status()` / `Diagnostics()`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// Run the self-test against the live system
+///
+/// Also refreshes the shared conflict report in `conflicts`.
+pub fn run_self_test(conflicts: &ConflictsHandle) -> StartupReport {
+    let test = SelfTest::system();
+    let conflicts = conflicts.refresh(&test.logitech_hidraw_nodes());
+    test.run(&conflicts)
+}
+
+/// The Logitech /dev/hidrawN nodes present now
+pub fn logitech_hidraw_nodes() -> Vec<PathBuf> {
+    SelfTest::system().logitech_hidraw_nodes()
+}
+
+/// Create a shared report holder
+pub fn new_shared_report(report: StartupReport) -> SharedStartupReport {
+    Arc::new(RwLock::new(report))
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Kind of device node being checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Hidraw,
+    Evdev,
+}
+
+impl NodeKind {
+    fn check(self) -> &'static str {
+        match self {
+            NodeKind::Hidraw => "hidraw_access",
+            NodeKind::Evdev => "evdev_access",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            NodeKind::Hidraw => "hidraw",
+            NodeKind::Evdev => "input",
+        }
+    }
+}
+
+/// Group membership of this process and of the user it runs as
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Groups {
+    /// gid -> (name, listed members) from /etc/group
+    pub(crate) table: HashMap<u32, (String, Vec<String>)>,
+    /// Groups this process actually holds
+    pub(crate) process_gids: Vec<u32>,
+    /// Primary gid of the user in /etc/passwd
+    pub(crate) primary_gid: Option<u32>,
+    pub(crate) user: String,
+}
+
+impl Groups {
+    pub(crate) fn name(&self, gid: u32) -> String {
+        self.table
+            .get(&gid)
+            .map(|(name, _)| name.clone())
+            .unwrap_or_else(|| gid.to_string())
+    }
+
+    pub(crate) fn process_has(&self, gid: u32) -> bool {
+        self.process_gids.contains(&gid)
+    }
+
+    /// User is a member per /etc/group, whether or not this session has it yet
+    pub(crate) fn user_listed_in(&self, gid: u32) -> bool {
+        self.primary_gid == Some(gid)
+            || self
+                .table
+                .get(&gid)
+                .is_some_and(|(_, members)| members.contains(&self.user))
+    }
+}
+
+/// Filesystem roots, overridable for tests
+pub(crate) struct SelfTest {
+    pub(crate) sys: PathBuf,
+    pub(crate) dev: PathBuf,
+    pub(crate) proc: PathBuf,
+    pub(crate) etc: PathBuf,
+}
+
+impl SelfTest {
+    pub(crate) fn system() -> Self {
+        Self {
+            sys: PathBuf::from("/sys"),
+            dev: PathBuf::from("/dev"),
+            proc: PathBuf::from("/proc"),
+            etc: PathBuf::from("/etc"),
+        }
+    }
+
+    fn run(&self, conflicts: &ConflictReport) -> StartupReport {
+        let groups = self.groups();
+        let hidraw = self.logitech_hidraw_nodes();
+        let evdev = self.logitech_event_nodes();
+        let mut findings = Vec::new();
+
+        if hidraw.is_empty() && evdev.is_empty() {
+            findings.push(Finding {
+                check: "device_present",
+                status: CheckStatus::Warning,
+                message: "No Logitech hidraw or input devices found (other mice use generic mode)"
+                    .to_string(),
+                node: None,
+                remediation: Some(
+                    "Plug in the receiver or cable, or wake the mouse and switch it to this computer \
+                     with the Easy-Switch button. The daemon picks it up automatically."
+                        .to_string(),
+                ),
+            });
+        } else {
+            findings.extend(self.check_nodes(NodeKind::Hidraw, &hidraw, &groups));
+            findings.extend(self.check_nodes(NodeKind::Evdev, &evdev, &groups));
+        }
+
+        findings.extend(conflicts.conflicts.iter().map(|conflict| Finding {
+            check: conflict.tool.check(),
+            status: CheckStatus::Warning,
+            message: conflict.message(),
+            node: conflict.nodes.first().cloned(),
+            remediation: Some(conflict.guidance.to_string()),
+        }));
+
+        findings.push(Finding::ok(
+            "hid_backend",
+            "HID++ uses direct hidraw access; no hidapi build feature is required".to_string(),
+        ));
+        StartupReport::from_findings(findings)
+    }
+
+    /// Try to open each node; one finding per failure, or one summary when all open
+    fn check_nodes(&self, kind: NodeKind, nodes: &[PathBuf], groups: &Groups) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for node in nodes {
+            let result = OpenOptions::new()
+                .read(true)
+                .write(kind == NodeKind::Hidraw)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(node);
+            if let Err(e) = result {
+                let meta = fs::metadata(node).ok().map(|m| (m.gid(), m.mode()));
+                findings.push(open_failure(kind, node, &e, meta, groups));
+            }
+        }
+        if findings.is_empty() && !nodes.is_empty() {
+            findings.push(Finding::ok(
+                kind.check(),
+                format!("{} Logitech {} node(s) accessible", nodes.len(), kind.label()),
+            ));
+        }
+        findings
+    }
+
+    /// /dev/hidrawN nodes whose HID device is Logitech
+    pub(crate) fn logitech_hidraw_nodes(&self) -> Vec<PathBuf> {
+        self.sysfs_nodes("class/hidraw", "hidraw", |dir| {
+            fs::read_to_string(dir.join("device/uevent"))
+                .is_ok_and(|u| u.to_uppercase().contains("046D"))
+        })
+        .into_iter()
+        .map(|name| self.dev.join(name))
+        .collect()
+    }
+
+    /// /dev/input/eventN nodes whose device is Logitech
+    fn logitech_event_nodes(&self) -> Vec<PathBuf> {
+        self.sysfs_nodes("class/input", "event", |dir| {
+            fs::read_to_string(dir.join("device/id/vendor"))
+                .is_ok_and(|v| v.trim().eq_ignore_ascii_case("046d"))
+        })
+        .into_iter()
+        .map(|name| self.dev.join("input").join(name))
+        .collect()
+    }
+
+    fn sysfs_nodes(&self, class: &str, prefix: &str, matches: impl Fn(&Path) -> bool) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(self.sys.join(class)) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .flatten()
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|name| name.starts_with(prefix))
+            .filter(|name| matches(&self.sys.join(class).join(name)))
+            .collect();
+        names.sort_by_key(|n| n.trim_start_matches(prefix).parse::<u32>().unwrap_or(u32::MAX));
+        names
+    }
+
+    pub(crate) fn groups(&self) -> Groups {
+        let status = fs::read_to_string(self.proc.join("self/status")).unwrap_or_default();
+        let field = |key: &str| -> Vec<u32> {
+            status
+                .lines()
+                .find_map(|l| l.strip_prefix(key))
+                .map(|rest| rest.split_whitespace().filter_map(|g| g.parse().ok()).collect())
+                .unwrap_or_default()
+        };
+        let uid = field("Uid:").first().copied();
+        let mut process_gids = field("Groups:");
+        process_gids.extend(field("Gid:").first());
+
+        let passwd = fs::read_to_string(self.etc.join("passwd")).unwrap_or_default();
+        let account = passwd.lines().find_map(|line| {
+            let parts: Vec<&str> = line.split(':').collect();
+            (parts.len() > 3 && parts[2].parse::<u32>().ok() == uid)
+                .then(|| (parts[0].to_string(), parts[3].parse::<u32>().ok()))
+        });
+        let (user, primary_gid) = account
+            .unwrap_or_else(|| (std::env::var("USER").unwrap_or_default(), None));
+
+        let table = parse_group_file(&fs::read_to_string(self.etc.join("group")).unwrap_or_default());
+        Groups {
+            table,
+            process_gids,
+            primary_gid,
+            user,
+        }
+    }
+}
+
+impl SelfTest {
+    /// [`groups`](Self::groups) for `user` instead of this process, which
+    /// holds none of them (a check run under sudo on the user's behalf)
+    pub(crate) fn groups_of(&self, user: &str) -> Groups {
+        let passwd = fs::read_to_string(self.etc.join("passwd")).unwrap_or_default();
+        let primary_gid = passwd.lines().find_map(|line| {
+            let parts: Vec<&str> = line.split(':').collect();
+            (parts.len() > 3 && parts[0] == user).then(|| parts[3].parse::<u32>().ok()).flatten()
+        });
+        Groups {
+            process_gids: Vec::new(),
+            primary_gid,
+            user: user.to_string(),
+            ..self.groups()
+        }
+    }
+}
+
+/// Parse /etc/group into gid -> (name, members)
+fn parse_group_file(contents: &str) -> HashMap<u32, (String, Vec<String>)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split(':').collect();
+            let gid = parts.get(2)?.parse::<u32>().ok()?;
+            let members = parts
+                .get(3)
+                .map(|m| m.split(',').filter(|s| !s.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default();
+            Some((gid, (parts[0].to_string(), members)))
+        })
+        .collect()
+}
+
+/// Finding for a node that failed to open, with the matching remediation
+fn open_failure(
+    kind: NodeKind,
+    node: &Path,
+    error: &io::Error,
+    meta: Option<(u32, u32)>,
+    groups: &Groups,
+) -> Finding {
+    let finding = |status, message: String, remediation: &str| Finding {
+        check: kind.check(),
+        status,
+        message,
+        node: Some(node.to_path_buf()),
+        remediation: Some(remediation.to_string()),
+    };
+
+    match error.kind() {
+        io::ErrorKind::NotFound => finding(
+            CheckStatus::Error,
+            format!("{} is listed in sysfs but missing from /dev", node.display()),
+            "udev did not create the device node. Check that systemd-udevd is running \
+             (systemctl status systemd-udevd), then replug the receiver.",
+        ),
+        io::ErrorKind::PermissionDenied => {
+            let Some((gid, mode)) = meta else {
+                return finding(
+                    CheckStatus::Error,
+                    format!("Permission denied opening {}", node.display()),
+                    "Install the udev rules and reboot: \
+                     sudo cp packaging/udev/99-juhradialmx.rules /etc/udev/rules.d/",
+                );
+            };
+            let group = groups.name(gid);
+            let in_group = groups.process_has(gid);
+            let message = format!(
+                "Permission denied opening {} (group '{}', mode {:04o}); this process is {}in '{}'",
+                node.display(),
+                group,
+                mode & 0o7777,
+                if in_group { "" } else { "not " },
+                group
+            );
+            let group_rw = mode & 0o060 == 0o060;
+            let remediation = if group != INPUT_GROUP || !group_rw {
+                "The udev rules are not applied to this node. Install them and reload: \
+                 sudo cp packaging/udev/99-juhradialmx.rules /etc/udev/rules.d/ && \
+                 sudo udevadm control --reload && sudo udevadm trigger"
+            } else if groups.user_listed_in(gid) {
+                "You are in the 'input' group, but this session started before you were added. \
+                 Reboot (or fully log out and back in) so the daemon picks up the group."
+            } else {
+                "Add yourself to the 'input' group, then reboot (or fully log out and back in): \
+                 sudo usermod -aG input $USER"
+            };
+            finding(CheckStatus::Error, message, remediation)
+        }
+        _ if error.raw_os_error() == Some(libc::EBUSY) => finding(
+            CheckStatus::Error,
+            format!("{} is busy (held exclusively by another program)", node.display()),
+            "Another program holds the mouse. Stop logid, Solaar or other mouse tools and restart the daemon.",
+        ),
+        _ => finding(
+            CheckStatus::Warning,
+            format!("Could not open {}: {}", node.display(), error),
+            "Replug the receiver or restart the daemon; if this persists, include this message in a bug report.",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conflicts::ConflictDetector;
+    use tempfile::TempDir;
+
+    fn groups(process_gids: Vec<u32>, input_members: &[&str]) -> Groups {
+        let file = format!("root:x:0:\ninput:x:104:{}\nalice:x:1000:\n", input_members.join(","));
+        Groups {
+            table: parse_group_file(&file),
+            process_gids,
+            primary_gid: Some(1000),
+            user: "alice".to_string(),
+        }
+    }
+
+    fn denied(meta: (u32, u32), groups: &Groups) -> Finding {
+        let error = io::Error::from(io::ErrorKind::PermissionDenied);
+        open_failure(NodeKind::Hidraw, Path::new("/dev/hidraw3"), &error, Some(meta), groups)
+    }
+
+    #[test]
+    fn test_permission_denied_cases() {
+        // Not in the group at all
+        let f = denied((104, 0o20660), &groups(vec![1000], &[]));
+        assert_eq!(f.status, CheckStatus::Error);
+        assert!(f.message.contains("group 'input', mode 0660"));
+        assert!(f.message.contains("not in 'input'"));
+        assert!(f.remediation.unwrap().contains("usermod -aG input"));
+
+        // Added to the group, but the session predates it
+        let f = denied((104, 0o20660), &groups(vec![1000], &["alice"]));
+        assert!(f.remediation.unwrap().contains("session started before"));
+
+        // Node still root-owned: udev rules not applied
+        let f = denied((0, 0o20600), &groups(vec![1000, 104], &["alice"]));
+        assert!(f.message.contains("group 'root'"));
+        assert!(f.remediation.unwrap().contains("udevadm trigger"));
+    }
+
+    #[test]
+    fn test_report_status_and_json() {
+        let dir = TempDir::new().unwrap();
+        let test = SelfTest {
+            sys: dir.path().join("sys"),
+            dev: dir.path().join("dev"),
+            proc: dir.path().join("proc"),
+            etc: dir.path().join("etc"),
+        };
+
+        let run = |test: &SelfTest| {
+            let conflicts = ConflictDetector::new(test.proc.clone(), 1).scan(&test.logitech_hidraw_nodes());
+            test.run(&conflicts)
+        };
+
+        // Nothing present: receiver unplugged
+        let report = run(&test);
+        assert_eq!(report.status, CheckStatus::Warning);
+        assert_eq!(report.findings[0].check, "device_present");
+
+        // A Logitech hidraw node in sysfs without a /dev node, and logid running
+        let hidraw = test.sys.join("class/hidraw/hidraw2/device");
+        fs::create_dir_all(&hidraw).unwrap();
+        fs::write(hidraw.join("uevent"), "HID_ID=0003:0000046D:0000C548\n").unwrap();
+        let logid = test.proc.join("812");
+        fs::create_dir_all(&logid).unwrap();
+        fs::write(logid.join("comm"), "logid\n").unwrap();
+
+        let report = run(&test);
+        let checks: Vec<_> = report.findings.iter().map(|f| (f.check, f.status)).collect();
+        assert_eq!(
+            checks,
+            vec![
+                ("hidraw_access", CheckStatus::Error),
+                ("logid", CheckStatus::Warning),
+                ("hid_backend", CheckStatus::Ok),
+            ]
+        );
+        let json = report.to_json();
+        assert_eq!(json["status"], "error");
+        assert!(json["findings"][0]["node"].as_str().unwrap().ends_with("dev/hidraw2"));
+        assert!(json["findings"][2].get("remediation").is_none());
+    }
+}
@@ -0,0 +1,243 @@
+//! Session environment and the backend each subsystem uses in it
+//!
+//! The daemon sorts the session into one of four kinds at startup: KDE or
+//! another desktop, on X11 or Wayland. The kind picks the backend for the
+//! cursor query, the screen bounds, active-window tracking and key
+//! synthesis, and [`Environment::log`] writes one line per subsystem saying
+//! what will and won't work, so a report from an unusual session starts from
+//! the right place. `Status()` carries the same matrix under `session`.
+//!
+//! X11 sessions outside KDE get no help from a compositor: the cursor comes
+//! from `XQueryPointer`, the monitor layout from RandR and the focused window
+//! from `_NET_ACTIVE_WINDOW`, all over x11rb (the `window-tracking` feature;
+//! without it the cursor falls back to libX11 and the layout to `xrandr`).
+//! Under Wayland outside KDE and Hyprland the X11 queries only see XWayland,
+//! which is why those rows are marked partial.
+
+use serde::Serialize;
+
+use crate::actions::{detect_desktop, is_wayland_session};
+
+/// Which kind of session the daemon runs in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SessionKind {
+    X11Kde,
+    WaylandKde,
+    X11Other,
+    WaylandOther,
+}
+
+impl SessionKind {
+    /// The kind for `desktop` (as [`detect_desktop`] names it)
+    pub fn new(desktop: &str, wayland: bool) -> Self {
+        match (desktop == "kde", wayland) {
+            (true, false) => Self::X11Kde,
+            (true, true) => Self::WaylandKde,
+            (false, false) => Self::X11Other,
+            (false, true) => Self::WaylandOther,
+        }
+    }
+
+    /// The kind of this session, from `XDG_CURRENT_DESKTOP`,
+    /// `WAYLAND_DISPLAY` and `XDG_SESSION_TYPE`
+    pub fn detect() -> Self {
+        Self::new(detect_desktop(), is_wayland_session())
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::X11Kde => "x11-kde",
+            Self::WaylandKde => "wayland-kde",
+            Self::X11Other => "x11-other",
+            Self::WaylandOther => "wayland-other",
+        }
+    }
+
+    pub fn is_wayland(&self) -> bool {
+        matches!(self, Self::WaylandKde | Self::WaylandOther)
+    }
+}
+
+/// How well a subsystem works in the session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Support {
+    Full,
+    /// Works with a caveat (see the note)
+    Partial,
+    Unavailable,
+}
+
+/// One row of the capability matrix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Capability {
+    pub subsystem: &'static str,
+    /// Backend chosen for it; empty when there is none
+    pub backend: &'static str,
+    pub support: Support,
+    /// What the user should know; empty when it simply works
+    pub note: &'static str,
+}
+
+impl Capability {
+    fn new(subsystem: &'static str, backend: &'static str, support: Support, note: &'static str) -> Self {
+        Self { subsystem, backend, support, note }
+    }
+}
+
+/// The session and what works in it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Environment {
+    pub kind: SessionKind,
+    pub desktop: &'static str,
+    pub capabilities: Vec<Capability>,
+}
+
+impl Environment {
+    /// Build the matrix for a session
+    ///
+    /// `window_backend` is the active-window source the tracker probed
+    /// (`None` when it found none); `x11rb` is whether this build has the
+    /// x11rb queries.
+    pub fn new(kind: SessionKind, desktop: &'static str, window_backend: Option<&'static str>, x11rb: bool) -> Self {
+        let x11_cursor = if x11rb { "x11rb" } else { "xlib" };
+        let x11_bounds = if x11rb { "randr" } else { "xrandr" };
+        let cursor = match kind {
+            SessionKind::WaylandKde => Capability::new("cursor", "kwin", Support::Full, ""),
+            SessionKind::X11Kde | SessionKind::X11Other => Capability::new("cursor", x11_cursor, Support::Full, ""),
+            SessionKind::WaylandOther => match desktop {
+                "hyprland" => Capability::new("cursor", "hyprland", Support::Full, ""),
+                "gnome" => Capability::new(
+                    "cursor",
+                    "gnome-shell",
+                    Support::Partial,
+                    "needs the GNOME Shell extension; otherwise only XWayland windows report the pointer",
+                ),
+                _ => Capability::new(
+                    "cursor",
+                    "xwayland",
+                    Support::Partial,
+                    "the pointer is only seen over XWayland windows",
+                ),
+            },
+        };
+        let screen_bounds = match kind {
+            SessionKind::WaylandKde => Capability::new("screen_bounds", "kwin", Support::Full, ""),
+            SessionKind::X11Kde | SessionKind::X11Other => Capability::new("screen_bounds", x11_bounds, Support::Full, ""),
+            SessionKind::WaylandOther if desktop == "hyprland" => {
+                Capability::new("screen_bounds", "hyprland", Support::Full, "")
+            }
+            SessionKind::WaylandOther => Capability::new(
+                "screen_bounds",
+                "xrandr",
+                Support::Partial,
+                "XWayland reports the layout without per-monitor scaling",
+            ),
+        };
+        let window_tracking = match window_backend {
+            None => Capability::new(
+                "window_tracking",
+                "",
+                Support::Unavailable,
+                "no active-window source; per-app profiles inactive",
+            ),
+            Some("x11") if kind.is_wayland() => Capability::new(
+                "window_tracking",
+                "x11",
+                Support::Partial,
+                "only XWayland windows are seen",
+            ),
+            Some(backend) => Capability::new("window_tracking", backend, Support::Full, ""),
+        };
+        let key_synthesis = if kind.is_wayland() {
+            Capability::new("key_synthesis", "ydotool", Support::Partial, "needs ydotoold running and uinput access")
+        } else {
+            Capability::new("key_synthesis", "xdotool", Support::Full, "")
+        };
+        Self {
+            kind,
+            desktop,
+            capabilities: vec![cursor, screen_bounds, window_tracking, key_synthesis],
+        }
+    }
+
+    /// Detect this session
+    pub fn detect(window_backend: Option<&'static str>) -> Self {
+        Self::new(SessionKind::detect(), detect_desktop(), window_backend, cfg!(feature = "window-tracking"))
+    }
+
+    /// The backend chosen for `subsystem`
+    pub fn backend(&self, subsystem: &str) -> Option<&'static str> {
+        self.capabilities
+            .iter()
+            .find(|c| c.subsystem == subsystem && c.support != Support::Unavailable)
+            .map(|c| c.backend)
+    }
+
+    /// One line per subsystem; anything short of full support is a warning
+    pub fn log(&self) {
+        tracing::info!(session = self.kind.as_str(), desktop = self.desktop, "Session detected");
+        for c in &self.capabilities {
+            match c.support {
+                Support::Full => tracing::info!(subsystem = c.subsystem, backend = c.backend, "Session capability"),
+                Support::Partial | Support::Unavailable => tracing::warn!(
+                    subsystem = c.subsystem,
+                    backend = c.backend,
+                    support = ?c.support,
+                    "Session capability limited: {}",
+                    c.note
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_from_desktop_and_display_server() {
+        assert_eq!(SessionKind::new("kde", false), SessionKind::X11Kde);
+        assert_eq!(SessionKind::new("kde", true), SessionKind::WaylandKde);
+        assert_eq!(SessionKind::new("unknown", false), SessionKind::X11Other);
+        assert_eq!(SessionKind::new("sway", true), SessionKind::WaylandOther);
+        assert_eq!(serde_json::to_value(SessionKind::X11Other).unwrap(), SessionKind::X11Other.as_str());
+    }
+
+    #[test]
+    fn test_x11_other_uses_x11_backends_throughout() {
+        let env = Environment::new(SessionKind::X11Other, "unknown", Some("x11"), true);
+        assert_eq!(env.backend("cursor"), Some("x11rb"));
+        assert_eq!(env.backend("screen_bounds"), Some("randr"));
+        assert_eq!(env.backend("window_tracking"), Some("x11"));
+        assert_eq!(env.backend("key_synthesis"), Some("xdotool"));
+        assert!(env.capabilities.iter().all(|c| c.support == Support::Full));
+
+        // Without x11rb the cursor and layout fall back to libX11 and xrandr
+        let minimal = Environment::new(SessionKind::X11Other, "unknown", None, false);
+        assert_eq!(minimal.backend("cursor"), Some("xlib"));
+        assert_eq!(minimal.backend("screen_bounds"), Some("xrandr"));
+        assert_eq!(minimal.backend("window_tracking"), None);
+    }
+
+    #[test]
+    fn test_wayland_gaps_are_reported() {
+        let kde = Environment::new(SessionKind::WaylandKde, "kde", Some("kwin"), true);
+        assert_eq!(kde.backend("cursor"), Some("kwin"));
+        assert_eq!(kde.capabilities[3].support, Support::Partial, "ydotool needs its daemon");
+
+        let other = Environment::new(SessionKind::WaylandOther, "cosmic", Some("x11"), true);
+        let partial: Vec<&str> = other
+            .capabilities
+            .iter()
+            .filter(|c| c.support == Support::Partial)
+            .map(|c| c.subsystem)
+            .collect();
+        assert_eq!(partial, ["cursor", "screen_bounds", "window_tracking", "key_synthesis"]);
+        let json = serde_json::to_value(&other).unwrap();
+        assert_eq!(json["kind"], "wayland-other");
+        assert_eq!(json["capabilities"][0]["support"], "partial");
+    }
+}
@@ -0,0 +1,234 @@
+//! Confirm-by-double-tap slices (`require_confirmation`)
+//!
+//! A profile action may set `require_confirmation`. Releasing the gesture
+//! button on such a slice does not run it: the menu stays open, the mouse
+//! plays the invalid-action haptic and the overlay is told (with
+//! `AwaitingConfirmation`) to draw a prompt on the slice. A second press and
+//! release on the same slice within `menu.confirm_window_ms` runs it as a
+//! normal release would; a release anywhere else, or the window running out,
+//! closes the menu without running anything.
+//!
+//! The second press continues the open menu instead of opening a new one, so
+//! pointer offsets keep counting from where the first release left them.
+//! `SliceConfirmation` is the pure state machine (times are passed in); the
+//! D-Bus service feeds it open/highlight/close and the gesture loop owns the
+//! release and the timer.
+
+use std::time::{Duration, Instant};
+
+use crate::actions::Action;
+use crate::handles::StateHandle;
+use crate::profiles::Profile;
+
+/// Default `menu.confirm_window_ms`
+pub const CONFIRM_WINDOW_MS: u64 = 1200;
+
+/// Slots of a profile whose actions need confirming; `None` when it has none
+pub fn profile_slots(profile: &Profile) -> Option<Vec<u8>> {
+    let slots: Vec<u8> = (0u8..)
+        .zip(&profile.slices)
+        .filter(|(_, slice)| slice.as_ref().is_some_and(|a: &Action| a.require_confirmation))
+        .map(|(slot, _)| slot)
+        .collect();
+    (!slots.is_empty()).then_some(slots)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed,
+    Open,
+    /// Released once on `slice`; a second release before `deadline` runs it
+    AwaitingConfirmation {
+        slice: u8,
+        deadline: Instant,
+        /// Rebased pointer offset at the first release
+        pointer: (i32, i32),
+    },
+}
+
+/// What to do with a gesture release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmOutcome {
+    /// Hide the menu and run the highlighted slice as usual
+    Execute,
+    /// Keep the menu open and prompt for a second press on `slice`
+    Await { slice: u8, window: Duration },
+    /// Close the menu without running anything
+    Cancel,
+}
+
+/// Confirmation state for the open menu
+#[derive(Debug)]
+pub struct SliceConfirmation {
+    /// Slots of the open menu that need confirming
+    slots: Vec<u8>,
+    window: Duration,
+    highlighted: Option<u8>,
+    state: State,
+}
+
+impl SliceConfirmation {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            window: Duration::from_millis(CONFIRM_WINDOW_MS),
+            highlighted: None,
+            state: State::Closed,
+        }
+    }
+
+    /// Menu shown, with the slots that need confirming
+    pub fn opened(&mut self, slots: Vec<u8>, window: Duration) {
+        self.slots = slots;
+        self.window = window;
+        self.highlighted = None;
+        self.state = State::Open;
+    }
+
+    /// Highlight moved to `slot` (`None` = hub or outside the ring)
+    pub fn highlighted(&mut self, slot: Option<u8>) {
+        self.highlighted = slot;
+    }
+
+    /// A press while the menu is shown
+    ///
+    /// Returns the pointer offset to resume from when the press continues a
+    /// pending confirmation rather than opening a new menu.
+    pub fn pressed(&self) -> Option<(i32, i32)> {
+        match self.state {
+            State::AwaitingConfirmation { pointer, .. } => Some(pointer),
+            _ => None,
+        }
+    }
+
+    /// The gesture button came up at `at`, the pointer at `pointer`
+    pub fn released(&mut self, at: Instant, pointer: (i32, i32)) -> ConfirmOutcome {
+        match self.state {
+            State::Closed => ConfirmOutcome::Execute,
+            State::Open => match self.highlighted.filter(|slot| self.slots.contains(slot)) {
+                Some(slice) => {
+                    self.state = State::AwaitingConfirmation {
+                        slice,
+                        deadline: at + self.window,
+                        pointer,
+                    };
+                    ConfirmOutcome::Await { slice, window: self.window }
+                }
+                None => ConfirmOutcome::Execute,
+            },
+            State::AwaitingConfirmation { slice, deadline, .. } => {
+                self.state = State::Closed;
+                if at < deadline && self.highlighted == Some(slice) {
+                    ConfirmOutcome::Execute
+                } else {
+                    ConfirmOutcome::Cancel
+                }
+            }
+        }
+    }
+
+    /// When the pending confirmation runs out
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.state {
+            State::AwaitingConfirmation { deadline, .. } => Some(deadline),
+            _ => None,
+        }
+    }
+
+    /// Timer check; returns whether a pending confirmation just ran out
+    pub fn expired(&mut self, now: Instant) -> bool {
+        if self.deadline().is_some_and(|deadline| now >= deadline) {
+            self.state = State::Closed;
+            return true;
+        }
+        false
+    }
+
+    /// Menu closed (selection, cancel, dismissal or shutdown)
+    pub fn closed(&mut self) {
+        self.slots.clear();
+        self.highlighted = None;
+        self.state = State::Closed;
+    }
+}
+
+impl Default for SliceConfirmation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The pending double-tap confirmation, shared by the service and the gesture loop
+pub type SliceConfirmationHandle = StateHandle<SliceConfirmation>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(CONFIRM_WINDOW_MS);
+
+    fn menu() -> SliceConfirmation {
+        let mut confirmation = SliceConfirmation::new();
+        confirmation.opened(vec![5], WINDOW);
+        confirmation
+    }
+
+    #[test]
+    fn test_second_release_on_the_slice_executes() {
+        let t0 = Instant::now();
+        let mut confirmation = menu();
+        confirmation.highlighted(Some(5));
+        assert_eq!(confirmation.pressed(), None);
+        let outcome = confirmation.released(t0, (-60, 70));
+        assert_eq!(outcome, ConfirmOutcome::Await { slice: 5, window: WINDOW });
+        assert_eq!(confirmation.deadline(), Some(t0 + WINDOW));
+
+        // The second press resumes from the first release's pointer
+        assert_eq!(confirmation.pressed(), Some((-60, 70)));
+        assert!(!confirmation.expired(t0 + Duration::from_millis(1199)));
+        let outcome = confirmation.released(t0 + Duration::from_millis(1199), (-60, 70));
+        assert_eq!(outcome, ConfirmOutcome::Execute);
+        assert_eq!(confirmation.deadline(), None);
+    }
+
+    #[test]
+    fn test_other_slices_and_timeouts_cancel() {
+        let t0 = Instant::now();
+        // Slices without the flag run on the first release
+        let mut confirmation = menu();
+        confirmation.highlighted(Some(2));
+        assert_eq!(confirmation.released(t0, (70, 0)), ConfirmOutcome::Execute);
+
+        // Second release on another slice
+        let mut confirmation = menu();
+        confirmation.highlighted(Some(5));
+        confirmation.released(t0, (0, 0));
+        confirmation.highlighted(Some(4));
+        assert_eq!(confirmation.released(t0 + Duration::from_millis(300), (0, 0)), ConfirmOutcome::Cancel);
+        assert_eq!(confirmation.pressed(), None);
+
+        // Window runs out before the second press
+        let mut confirmation = menu();
+        confirmation.highlighted(Some(5));
+        confirmation.released(t0, (0, 0));
+        assert!(confirmation.expired(t0 + WINDOW));
+        assert!(!confirmation.expired(t0 + WINDOW));
+        assert_eq!(confirmation.pressed(), None);
+
+        // A release stamped after the deadline loses even before the timer fires
+        let mut confirmation = menu();
+        confirmation.highlighted(Some(5));
+        confirmation.released(t0, (0, 0));
+        assert_eq!(confirmation.released(t0 + WINDOW, (0, 0)), ConfirmOutcome::Cancel);
+    }
+
+    #[test]
+    fn test_profile_slots_follow_the_flag() {
+        let mut profile = Profile::default();
+        assert!(profile_slots(&profile).is_none());
+        let mut close = crate::actions::get_default_actions()[0].clone();
+        close.require_confirmation = true;
+        profile.slices[3] = Some(close);
+        assert_eq!(profile_slots(&profile), Some(vec![3]));
+    }
+}
@@ -0,0 +1,272 @@
+//! Slice cooldowns (`cooldown_ms`)
+//!
+//! A profile action may set `cooldown_ms`. Once the slice has run, the daemon
+//! refuses to run the same profile slot again until the cooldown is over, so
+//! a bouncing release or a flick followed by the menu cannot toggle mute or
+//! play/pause twice. A refused release closes the menu without running
+//! anything and plays the invalid-action haptic; a refused flick only plays
+//! the haptic. The payload's `cooldown` block lists the slots still cooling
+//! with their remaining time, and the overlay greys them out and refuses a
+//! click on one.
+//!
+//! Runs are recorded where the daemon runs the slot: when the overlay reports
+//! a profile menu closed on it and when a flick runs one. The `default`
+//! profile shows the Settings ring, which has no cooldowns. The registry is kept in memory, pruned of finished
+//! cooldowns as it is used, and capped at [`MAX_ENTRIES`]; it outlives each
+//! menu but not the daemon. A `require_confirmation` slice starts its
+//! cooldown on the confirmed run, and a release on one that is still cooling
+//! is refused before any prompt. `SliceCooldowns` is the pure state machine
+//! (times are passed in) behind a [`SliceCooldownHandle`] on `DaemonHandles`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::actions::Action;
+use crate::handles::StateHandle;
+use crate::profiles::Profile;
+
+/// Most cooldowns tracked at once; the soonest to finish go first
+pub const MAX_ENTRIES: usize = 64;
+
+/// Slots of a profile with a cooldown; `None` when it has none
+pub fn profile_cooldowns(profile: &Profile) -> Option<Vec<(u8, Duration)>> {
+    let slots: Vec<(u8, Duration)> = (0u8..)
+        .zip(&profile.slices)
+        .filter_map(|(slot, slice)| {
+            let ms = slice.as_ref().map_or(0, |a: &Action| a.cooldown_ms);
+            (ms > 0).then(|| (slot, Duration::from_millis(ms)))
+        })
+        .collect();
+    (!slots.is_empty()).then_some(slots)
+}
+
+/// A slot still cooling, for the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CoolingSlot {
+    pub slot: u8,
+    pub remaining_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+struct OpenMenu {
+    profile: String,
+    /// Slots with a cooldown
+    slots: Vec<(u8, Duration)>,
+}
+
+/// Cooldowns of recently run slices and the open menu
+#[derive(Debug, Default)]
+pub struct SliceCooldowns {
+    /// When each profile slot may run again
+    ready_at: HashMap<(String, u8), Instant>,
+    open: Option<OpenMenu>,
+    highlighted: Option<u8>,
+}
+
+impl SliceCooldowns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Menu shown for `profile`, with its slots that have a cooldown
+    pub fn opened(&mut self, profile: &str, slots: Vec<(u8, Duration)>) {
+        self.open = Some(OpenMenu {
+            profile: profile.to_string(),
+            slots,
+        });
+        self.highlighted = None;
+    }
+
+    /// Menu closed, or an ad-hoc menu opened
+    pub fn closed(&mut self) {
+        self.open = None;
+        self.highlighted = None;
+    }
+
+    /// Highlight moved to `slot` (`None` = hub or outside the ring)
+    pub fn highlighted(&mut self, slot: Option<u8>) {
+        self.highlighted = slot;
+    }
+
+    /// Time left before `slot` of `profile` may run again
+    pub fn remaining(&mut self, profile: &str, slot: u8, at: Instant) -> Option<Duration> {
+        self.prune(at);
+        self.ready_at
+            .get(&(profile.to_string(), slot))
+            .map(|ready| ready.saturating_duration_since(at))
+    }
+
+    /// `slot` of `profile` ran at `at`
+    pub fn ran(&mut self, profile: &str, slot: u8, cooldown: Duration, at: Instant) {
+        if cooldown.is_zero() {
+            return;
+        }
+        self.prune(at);
+        if self.ready_at.len() >= MAX_ENTRIES {
+            if let Some(soonest) = self.ready_at.iter().min_by_key(|(_, ready)| **ready).map(|(key, _)| key.clone()) {
+                self.ready_at.remove(&soonest);
+            }
+        }
+        self.ready_at.insert((profile.to_string(), slot), at + cooldown);
+    }
+
+    /// Record a run of `slot` unless it is cooling; returns the time left
+    /// when refused
+    pub fn try_run(&mut self, profile: &str, slot: u8, cooldown: Duration, at: Instant) -> Option<Duration> {
+        if let Some(left) = self.remaining(profile, slot, at) {
+            return Some(left);
+        }
+        self.ran(profile, slot, cooldown, at);
+        None
+    }
+
+    /// The highlighted slot of the open menu and its time left, when a
+    /// release at `at` must not run it
+    pub fn blocked(&mut self, at: Instant) -> Option<(u8, Duration)> {
+        let slot = self.highlighted?;
+        let profile = self.open.as_ref()?.profile.clone();
+        self.remaining(&profile, slot, at).map(|left| (slot, left))
+    }
+
+    /// Whether `slot` of the open menu is cooling at `at`
+    pub fn is_cooling(&mut self, slot: u8, at: Instant) -> bool {
+        let Some(profile) = self.open.as_ref().map(|o| o.profile.clone()) else {
+            return false;
+        };
+        self.remaining(&profile, slot, at).is_some()
+    }
+
+    /// The open menu ran `slot` at `at`
+    pub fn selected(&mut self, slot: u8, at: Instant) {
+        let Some(open) = self.open.take() else {
+            return;
+        };
+        if let Some(&(_, cooldown)) = open.slots.iter().find(|(s, _)| *s == slot) {
+            self.ran(&open.profile, slot, cooldown, at);
+        }
+        self.open = Some(open);
+    }
+
+    /// Slots of `profile` still cooling at `at`, for the payload
+    pub fn cooling(&mut self, profile: &str, at: Instant) -> Vec<CoolingSlot> {
+        self.prune(at);
+        let mut slots: Vec<CoolingSlot> = self
+            .ready_at
+            .iter()
+            .filter(|((p, _), _)| p == profile)
+            .map(|((_, slot), ready)| CoolingSlot {
+                slot: *slot,
+                remaining_ms: u64::try_from(ready.saturating_duration_since(at).as_millis()).unwrap_or(u64::MAX),
+            })
+            .collect();
+        slots.sort_by_key(|s| s.slot);
+        slots
+    }
+
+    /// Cooldowns tracked now
+    pub fn len(&self) -> usize {
+        self.ready_at.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ready_at.is_empty()
+    }
+
+    /// Drop cooldowns that are over; a slot may run again at its ready time
+    fn prune(&mut self, at: Instant) {
+        self.ready_at.retain(|_, ready| *ready > at);
+    }
+}
+
+/// The daemon's cooldown registry
+pub type SliceCooldownHandle = StateHandle<SliceCooldowns>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slice_confirmation::{ConfirmOutcome, SliceConfirmation};
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_cooldown_ends_exactly_at_its_time() {
+        let t0 = Instant::now();
+        let mut cooldowns = SliceCooldowns::new();
+        assert_eq!(cooldowns.try_run("default", 2, ms(500), t0), None);
+        assert_eq!(cooldowns.try_run("default", 2, ms(500), t0 + ms(1)), Some(ms(499)));
+        assert_eq!(cooldowns.remaining("default", 2, t0 + ms(499)), Some(ms(1)));
+        // Other slots and profiles are unaffected
+        assert_eq!(cooldowns.remaining("default", 3, t0 + ms(10)), None);
+        assert_eq!(cooldowns.remaining("media", 2, t0 + ms(10)), None);
+        assert_eq!(cooldowns.try_run("default", 2, ms(500), t0 + ms(500)), None);
+        // A zero cooldown records nothing
+        cooldowns.ran("default", 4, Duration::ZERO, t0);
+        assert_eq!(cooldowns.remaining("default", 4, t0), None);
+    }
+
+    #[test]
+    fn test_open_menu_blocks_the_cooling_highlight_across_invocations() {
+        let t0 = Instant::now();
+        let mut cooldowns = SliceCooldowns::new();
+        cooldowns.opened("default", vec![(1, ms(800))]);
+        cooldowns.highlighted(Some(1));
+        assert_eq!(cooldowns.blocked(t0), None);
+        cooldowns.selected(1, t0);
+        cooldowns.closed();
+
+        cooldowns.opened("default", vec![(1, ms(800))]);
+        assert_eq!(cooldowns.blocked(t0 + ms(100)), None, "nothing highlighted");
+        cooldowns.highlighted(Some(1));
+        assert_eq!(cooldowns.blocked(t0 + ms(100)), Some((1, ms(700))));
+        assert!(cooldowns.is_cooling(1, t0 + ms(100)));
+        assert_eq!(cooldowns.cooling("default", t0 + ms(300)), [CoolingSlot { slot: 1, remaining_ms: 500 }]);
+        cooldowns.highlighted(Some(0));
+        assert_eq!(cooldowns.blocked(t0 + ms(100)), None);
+        // A slot without a cooldown records nothing
+        cooldowns.selected(0, t0 + ms(100));
+        assert_eq!(cooldowns.len(), 1);
+        assert_eq!(cooldowns.blocked(t0 + ms(800)), None);
+        assert!(cooldowns.is_empty(), "finished cooldowns are pruned");
+    }
+
+    #[test]
+    fn test_confirmation_starts_the_cooldown_on_the_confirmed_run() {
+        let t0 = Instant::now();
+        let mut cooldowns = SliceCooldowns::new();
+        let mut confirmation = SliceConfirmation::new();
+        cooldowns.opened("default", vec![(5, ms(1000))]);
+        confirmation.opened(vec![5], ms(1200));
+        cooldowns.highlighted(Some(5));
+        confirmation.highlighted(Some(5));
+
+        // The prompt does not run the slice, so nothing cools yet
+        assert_eq!(cooldowns.blocked(t0), None);
+        assert!(matches!(confirmation.released(t0, (0, 0)), ConfirmOutcome::Await { slice: 5, .. }));
+        assert_eq!(cooldowns.blocked(t0 + ms(300)), None);
+        assert_eq!(confirmation.released(t0 + ms(300), (0, 0)), ConfirmOutcome::Execute);
+        cooldowns.selected(5, t0 + ms(300));
+
+        // The next menu refuses the release before asking for confirmation
+        cooldowns.opened("default", vec![(5, ms(1000))]);
+        cooldowns.highlighted(Some(5));
+        assert_eq!(cooldowns.blocked(t0 + ms(600)), Some((5, ms(700))));
+        assert_eq!(cooldowns.blocked(t0 + ms(1300)), None);
+    }
+
+    #[test]
+    fn test_registry_stays_small() {
+        let t0 = Instant::now();
+        let mut cooldowns = SliceCooldowns::new();
+        for i in 0..(MAX_ENTRIES as u64 + 10) {
+            cooldowns.ran(&format!("p{i}"), 0, ms(1000 + i), t0);
+        }
+        assert_eq!(cooldowns.len(), MAX_ENTRIES);
+        // The soonest to finish were dropped
+        assert_eq!(cooldowns.remaining("p0", 0, t0), None);
+        assert!(cooldowns.remaining(&format!("p{}", MAX_ENTRIES + 9), 0, t0).is_some());
+    }
+}
@@ -0,0 +1,273 @@
+//! Runtime state for bug reports
+//!
+//! `DumpState()` and `juhradiald dump-state` put what a bug report usually
+//! lacks into one JSON document: the config with every default filled in,
+//! the loaded profiles and the windows and desktops they match, the themes
+//! with their source and content hash, the key synthesis and HID backends,
+//! the build, the findings validation reports for config.json and
+//! profiles.json, and from the running daemon its device, connection and
+//! battery state, accessibility flags and last warnings.
+//!
+//! Redacted by default: every action (slices, center, thumb-wheel, wheel
+//! axis directions) is reduced to its type, so commands, shortcuts and D-Bus
+//! calls stay out of a pasted report, profile findings keep only their field
+//! and code, and warnings keep their message but not their fields.
+//! `--include-sensitive` (`DumpState(true)`) keeps all of it.
+//! When no daemon answers, the command prints what it can read from disk.
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::actions::Action;
+use crate::config::Config;
+use crate::logging::RecentWarnings;
+use crate::profiles::{Profile, ProfileManager};
+use crate::sandbox::ExecutionStrategy;
+use crate::shortcut_lint::ShortcutLintRules;
+use crate::theme::ThemeManager;
+use crate::validation::ValidationIssue;
+
+/// Bumped when a section is renamed or changes shape
+pub const DUMP_VERSION: u32 = 1;
+
+/// The whole document
+///
+/// `daemon` is the running daemon's own state, or why there is none.
+pub fn document(
+    config: &Config,
+    profiles_path: &Path,
+    themes: &ThemeManager,
+    strategy: &ExecutionStrategy,
+    daemon: Value,
+    include_sensitive: bool,
+) -> Value {
+    json!({
+        "dump_version": DUMP_VERSION,
+        "redacted": !include_sensitive,
+        "build": build(),
+        "config": {
+            "path": config.config_path,
+            "resolved": config,
+            "issues": config.config_path.as_deref().map(crate::config_schema::check_file).unwrap_or_default(),
+        },
+        "profiles": profiles(profiles_path, &config.shortcut_lint_rules(), include_sensitive),
+        "themes": themes_section(themes),
+        "backends": {
+            "key_synthesis": crate::actions::key_synthesis_backend(strategy),
+            "hid": crate::hidpp::HID_BACKEND,
+            "execution": strategy.to_json(),
+        },
+        "daemon": daemon,
+    })
+}
+
+/// Version, features and target of this binary
+pub fn build() -> Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": crate::features::enabled(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "debug_assertions": cfg!(debug_assertions),
+    })
+}
+
+/// An action as written in profiles.json, or only its type when redacted
+pub fn action(action: &Action, include_sensitive: bool) -> Value {
+    if include_sensitive {
+        serde_json::to_value(action).unwrap_or(Value::Null)
+    } else {
+        json!({ "type": action.action_type.as_str() })
+    }
+}
+
+/// A validation finding, without its message and value when redacted (a
+/// message may quote a command)
+pub fn issue(issue: &ValidationIssue, include_sensitive: bool) -> Value {
+    if include_sensitive {
+        serde_json::to_value(issue).unwrap_or(Value::Null)
+    } else {
+        json!({ "severity": issue.severity, "field": issue.field, "code": issue.code })
+    }
+}
+
+/// The profiles in `path`, sorted by name, with the issues `validate-profiles`
+/// finds under the lint `rules`; an unreadable file is reported as an error
+/// in place of the list
+pub fn profiles(path: &Path, rules: &ShortcutLintRules, include_sensitive: bool) -> Value {
+    let manager = match ProfileManager::load_from_path(path) {
+        Ok(manager) => manager,
+        Err(e) => return json!({ "path": path, "error": e.to_string() }),
+    };
+    let mut loaded: Vec<&Profile> = manager.profiles().collect();
+    loaded.sort_by(|a, b| a.name.cmp(&b.name));
+    let optional = |a: &Option<Action>| a.as_ref().map(|a| action(a, include_sensitive));
+    let list: Vec<Value> = loaded
+        .into_iter()
+        .map(|p| {
+            json!({
+                "name": p.name,
+                "hash": manager.profile_hash(&p.name),
+                "extends": p.extends,
+                "window_class": p.window_class,
+                "virtual_desktop": p.virtual_desktop,
+                "slice_count": p.slice_count,
+                "menu_size": p.menu_size,
+                "slices": p.slices.iter().map(optional).collect::<Vec<_>>(),
+                "center": optional(&p.center),
+                "thumbwheel_left": optional(&p.thumbwheel_left),
+                "thumbwheel_right": optional(&p.thumbwheel_right),
+            })
+        })
+        .collect();
+    let report = crate::validate::validate_profiles(path, false, rules);
+    let issues: Vec<Value> = report.diagnostics.iter().map(|d| issue(&d.issue, include_sensitive)).collect();
+    json!({
+        "path": path,
+        "hash": manager.content_hash(),
+        "issues": issues,
+        "profiles": list,
+        "hardware": manager.hardware_profiles(),
+        "collisions": manager.collisions(),
+    })
+}
+
+/// Loaded themes with their source and content hash, and the current one
+pub fn themes_section(themes: &ThemeManager) -> Value {
+    let list: Vec<Value> = themes
+        .themes_with_sources()
+        .into_iter()
+        .map(|(theme, origin)| {
+            json!({
+                "name": theme.name,
+                "source": origin.source,
+                "path": origin.path,
+                "hash": themes.hash(&theme.name),
+            })
+        })
+        .collect();
+    let current = themes.current();
+    json!({
+        "current": current.name,
+        "current_hash": themes.current_hash(),
+        "accent_override": themes.accent_override(),
+        "themes": list,
+    })
+}
+
+/// The daemon's last warnings and errors, oldest first
+pub fn recent_warnings(recent: &RecentWarnings, include_sensitive: bool) -> Value {
+    let warnings: Vec<Value> = recent
+        .warnings()
+        .into_iter()
+        .map(|w| {
+            let mut json = json!({
+                "at": w.at,
+                "level": w.level,
+                "target": w.target,
+                "message": w.message,
+            });
+            if include_sensitive {
+                json["fields"] = json!(w.fields);
+            }
+            json
+        })
+        .collect();
+    Value::Array(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PROFILES: &str = r#"{
+        "version": 2,
+        "profiles": [{
+            "name": "default",
+            "slices": [
+                {"type": "command", "value": "curl -H 'Authorization: secret' example.org", "label": "Sync"},
+                {"type": "shortcut", "value": "ctrl+c", "label": "Copy"},
+                {"type": "wheel_axis", "value": {
+                    "up": {"type": "command", "value": "pactl set-sink-volume @DEFAULT_SINK@ +5%"},
+                    "down": {"type": "command", "value": "pactl set-sink-volume @DEFAULT_SINK@ -5%"}
+                }}
+            ],
+            "center": {"type": "command", "value": "rm -rf ~/scratch"}
+        }, {
+            "name": "editor",
+            "window_class": "org.example.editor",
+            "slices": []
+        }]
+    }"#;
+
+    fn dump(include_sensitive: bool) -> Value {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("profiles.json");
+        std::fs::write(&path, PROFILES).unwrap();
+        document(
+            &Config::default(),
+            &path,
+            &ThemeManager::new(),
+            &ExecutionStrategy::Direct,
+            Value::Null,
+            include_sensitive,
+        )
+    }
+
+    #[test]
+    fn test_redaction_strips_commands() {
+        let dump = dump(false);
+        let text = dump.to_string();
+        for secret in ["curl", "secret", "pactl", "rm -rf", "ctrl+c"] {
+            assert!(!text.contains(secret), "{secret} leaked into the redacted dump");
+        }
+        assert_eq!(dump["redacted"], true);
+        let profiles = dump["profiles"]["profiles"].as_array().unwrap();
+        assert_eq!(profiles[0]["name"], "default");
+        let slices = profiles[0]["slices"].as_array().unwrap();
+        assert_eq!(slices[0], json!({"type": "command"}));
+        assert_eq!(slices[1], json!({"type": "shortcut"}));
+        assert_eq!(slices[2], json!({"type": "wheel_axis"}));
+        assert_eq!(profiles[0]["center"], json!({"type": "command"}));
+        // Findings, without the messages that quote commands
+        let shell = dump["profiles"]["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|i| i["field"] == "profiles[0].slices[0]")
+            .unwrap();
+        assert_eq!(shell, &json!({"severity": "warning", "field": "profiles[0].slices[0]", "code": "shell_command"}));
+        // Window mappings are kept
+        assert_eq!(profiles[1]["window_class"], "org.example.editor");
+    }
+
+    #[test]
+    fn test_include_sensitive_keeps_commands() {
+        let dump = dump(true);
+        assert_eq!(dump["redacted"], false);
+        let slices = &dump["profiles"]["profiles"][0]["slices"];
+        assert_eq!(slices[0]["value"], "curl -H 'Authorization: secret' example.org");
+        assert_eq!(slices[0]["label"], "Sync");
+        assert_eq!(slices[2]["value"]["up"]["value"], "pactl set-sink-volume @DEFAULT_SINK@ +5%");
+        let issues = dump["profiles"]["issues"].as_array().unwrap();
+        assert!(issues.iter().any(|i| i["message"].as_str().is_some_and(|m| m.contains("curl"))));
+    }
+
+    #[test]
+    fn test_dump_sections() {
+        let dump = dump(false);
+        assert_eq!(dump["dump_version"], DUMP_VERSION);
+        assert_eq!(dump["build"]["version"], env!("CARGO_PKG_VERSION"));
+        // Defaults are filled in
+        assert_eq!(dump["config"]["resolved"]["theme"], Config::default().theme);
+        assert!(dump["config"]["resolved"]["menu"].is_object());
+        assert_eq!(dump["themes"]["current"], "catppuccin-mocha");
+        assert!(dump["themes"]["themes"].as_array().unwrap().iter().all(|t| t["hash"].is_string()));
+        assert_eq!(dump["backends"]["hid"], crate::hidpp::HID_BACKEND);
+
+        let missing = profiles(Path::new("/nonexistent/profiles.json"), &ShortcutLintRules::default(), false);
+        assert!(missing["error"].is_string());
+    }
+}
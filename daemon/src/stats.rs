@@ -0,0 +1,288 @@
+//! Opt-in local usage statistics
+//!
+//! When `telemetry.local_stats` is enabled, every menu invocation is appended
+//! as one JSON line to `~/.local/share/juhradial/stats.ndjson`: profile, slice
+//! (or cancel), open duration, and input method. Nothing else is recorded and
+//! nothing leaves the machine. The file rotates to `stats.ndjson.1` so at most
+//! ~5MB is kept. `GetStatsSummary()` aggregates both files for the settings app.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::fs_util;
+use crate::geometry::MAX_SLICE_COUNT;
+use crate::persistence::{PersistJob, PersistQueue};
+
+const STATS_FILENAME: &str = "stats.ndjson";
+
+/// Rotate when the live file passes this size; live + rotated stay under ~5MB
+pub const MAX_STATS_FILE_BYTES: u64 = 5 * 1024 * 1024 / 2;
+
+/// How the menu selection (or cancel) was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputMethod {
+    Pointer,
+    Wheel,
+    Keyboard,
+    /// Cancelled by `menu.auto_dismiss_ms`
+    Timeout,
+    /// Still open when the daemon stopped
+    Shutdown,
+}
+
+impl InputMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pointer" => Some(Self::Pointer),
+            "wheel" => Some(Self::Wheel),
+            "keyboard" => Some(Self::Keyboard),
+            "timeout" => Some(Self::Timeout),
+            "shutdown" => Some(Self::Shutdown),
+            _ => None,
+        }
+    }
+}
+
+/// One menu invocation, serialized as a single NDJSON line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsEvent {
+    /// Unix time in milliseconds when the menu closed
+    pub timestamp_ms: u64,
+    pub profile: String,
+    /// Selected slice (0-7), or `None` when the menu was cancelled
+    pub slice: Option<u8>,
+    pub open_ms: u64,
+    pub input: InputMethod,
+}
+
+/// Aggregate counts returned by `GetStatsSummary()`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StatsSummary {
+    pub invocations: u64,
+    pub cancels: u64,
+    /// Selections per profile slot, up to the 12-slice ring
+    pub slices: [u64; MAX_SLICE_COUNT as usize],
+    pub profiles: BTreeMap<String, u64>,
+    pub inputs: BTreeMap<InputMethod, u64>,
+    pub avg_open_ms: u64,
+}
+
+impl StatsSummary {
+    fn add(&mut self, event: &StatsEvent, total_open_ms: &mut u64) {
+        self.invocations += 1;
+        match event.slice {
+            Some(slice) if (slice as usize) < self.slices.len() => self.slices[slice as usize] += 1,
+            Some(_) => {}
+            None => self.cancels += 1,
+        }
+        *self.profiles.entry(event.profile.clone()).or_default() += 1;
+        *self.inputs.entry(event.input).or_default() += 1;
+        *total_open_ms += event.open_ms;
+    }
+}
+
+/// Records menu invocations to the stats file
+#[derive(Debug)]
+pub struct StatsCollector {
+    path: PathBuf,
+    max_bytes: u64,
+    opened_at: Option<Instant>,
+}
+
+impl StatsCollector {
+    /// Collector writing to the default stats file
+    pub fn new() -> Self {
+        Self::with_path(get_stats_path(), MAX_STATS_FILE_BYTES)
+    }
+
+    /// Collector writing to an explicit file (tests)
+    pub fn with_path(path: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            path,
+            max_bytes,
+            opened_at: None,
+        }
+    }
+
+    /// Mark the start of a menu invocation (ShowMenu)
+    pub fn menu_opened(&mut self) {
+        self.opened_at = Some(Instant::now());
+    }
+
+    /// Finish the pending invocation and queue it for the stats file
+    ///
+    /// Returns the recorded event, or `None` when no menu was open.
+    pub fn menu_closed(
+        &mut self,
+        profile: &str,
+        slice: Option<u8>,
+        input: InputMethod,
+        queue: &PersistQueue,
+    ) -> Option<StatsEvent> {
+        let opened_at = self.opened_at.take()?;
+        let event = StatsEvent {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            profile: profile.to_string(),
+            slice,
+            open_ms: opened_at.elapsed().as_millis() as u64,
+            input,
+        };
+        match serde_json::to_string(&event) {
+            Ok(line) => queue.submit(PersistJob::WriteStats {
+                path: self.path.clone(),
+                max_bytes: self.max_bytes,
+                line,
+            }),
+            Err(e) => tracing::warn!(error = %e, "Failed to serialize stats event"),
+        }
+        Some(event)
+    }
+
+    /// Append one event now, rotating first if the file is over the size
+    /// limit; [`Self::menu_closed`] queues it instead
+    pub fn append(&self, event: &StatsEvent) -> std::io::Result<()> {
+        let line = serde_json::to_string(event).map_err(std::io::Error::other)?;
+        append_lines(&self.path, self.max_bytes, &[line])
+    }
+
+    /// Aggregate the rotated and live files, skipping malformed lines
+    pub fn summary(&self) -> StatsSummary {
+        let mut summary = StatsSummary::default();
+        let mut total_open_ms = 0u64;
+        for path in [rotated_path(&self.path), self.path.clone()] {
+            let Ok(file) = fs::File::open(&path) else {
+                continue;
+            };
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if let Ok(event) = serde_json::from_str::<StatsEvent>(&line) {
+                    summary.add(&event, &mut total_open_ms);
+                }
+            }
+        }
+        summary.avg_open_ms = total_open_ms.checked_div(summary.invocations).unwrap_or(0);
+        summary
+    }
+}
+
+impl Default for StatsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stats file path (~/.local/share/juhradial/stats.ndjson)
+///
+/// Respects XDG_DATA_HOME if set, see [`crate::paths`].
+pub fn get_stats_path() -> PathBuf {
+    crate::paths::paths().user_data_dir.join(STATS_FILENAME)
+}
+
+/// Append serialized events to `path`, rotating it first once it has reached
+/// `max_bytes`
+pub fn append_lines(path: &Path, max_bytes: u64, lines: &[String]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(path).map(|m| m.len() >= max_bytes).unwrap_or(false) {
+        fs::rename(path, rotated_path(path))?;
+    }
+    let mut file = fs_util::open_private_append(path)?;
+    let mut batch = String::new();
+    for line in lines {
+        batch.push_str(line);
+        batch.push('\n');
+    }
+    file.write_all(batch.as_bytes())
+}
+
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".1");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn event(profile: &str, slice: Option<u8>, open_ms: u64, input: InputMethod) -> StatsEvent {
+        StatsEvent {
+            timestamp_ms: 0,
+            profile: profile.to_string(),
+            slice,
+            open_ms,
+            input,
+        }
+    }
+
+    #[test]
+    fn test_menu_close_without_open_is_ignored() {
+        let dir = TempDir::new().unwrap();
+        let mut stats = StatsCollector::with_path(dir.path().join(STATS_FILENAME), MAX_STATS_FILE_BYTES);
+        let queue = PersistQueue::new();
+        assert!(stats.menu_closed("default", Some(1), InputMethod::Pointer, &queue).is_none());
+
+        stats.menu_opened();
+        let recorded = stats.menu_closed("default", Some(1), InputMethod::Pointer, &queue).unwrap();
+        assert_eq!(recorded.slice, Some(1));
+        assert!(stats.menu_closed("default", Some(1), InputMethod::Pointer, &queue).is_none());
+        assert_eq!(stats.summary().invocations, 1);
+    }
+
+    #[test]
+    fn test_summary_aggregates() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(STATS_FILENAME);
+        let stats = StatsCollector::with_path(path.clone(), MAX_STATS_FILE_BYTES);
+        stats.append(&event("default", Some(0), 100, InputMethod::Pointer)).unwrap();
+        stats.append(&event("default", Some(0), 200, InputMethod::Pointer)).unwrap();
+        stats.append(&event("firefox", None, 300, InputMethod::Keyboard)).unwrap();
+
+        // Hand-edited garbage is skipped, not fatal
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let summary = stats.summary();
+        assert_eq!(summary.invocations, 3);
+        assert_eq!(summary.cancels, 1);
+        assert_eq!(summary.slices[0], 2);
+        assert_eq!(summary.profiles["default"], 2);
+        assert_eq!(summary.profiles["firefox"], 1);
+        assert_eq!(summary.inputs[&InputMethod::Keyboard], 1);
+        assert_eq!(summary.avg_open_ms, 200);
+    }
+
+    #[test]
+    fn test_rotation_keeps_one_previous_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(STATS_FILENAME);
+        let stats = StatsCollector::with_path(path.clone(), 150);
+        for _ in 0..6 {
+            stats.append(&event("default", Some(2), 10, InputMethod::Wheel)).unwrap();
+        }
+
+        assert!(rotated_path(&path).exists());
+        assert!(fs::metadata(&path).unwrap().len() < 300);
+        // Older rotations are dropped, so the summary covers at most two files
+        let summary = stats.summary();
+        assert!(summary.invocations > 0 && summary.invocations < 6);
+    }
+
+    #[test]
+    fn test_input_method_names() {
+        assert_eq!(InputMethod::from_name("wheel"), Some(InputMethod::Wheel));
+        assert_eq!(InputMethod::from_name("touch"), None);
+        let json = serde_json::to_string(&event("default", None, 5, InputMethod::Keyboard)).unwrap();
+        assert!(json.contains(r#""input":"keyboard""#));
+        assert!(json.contains(r#""slice":null"#));
+    }
+}
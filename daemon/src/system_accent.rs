@@ -0,0 +1,208 @@
+//! Desktop accent color
+//!
+//! With `colors.accent_follow_system` (the default) the menu is drawn in the
+//! desktop's accent color instead of the theme's own, and theme colors
+//! derived from the accent (`{"derive": "accent", ...}`) follow it. Plasma
+//! publishes its accent, including the one it picks from the wallpaper,
+//! through the settings portal twice: `AccentColor` in
+//! `org.kde.kdeglobals.General` (`"r,g,b"`) and the cross-desktop
+//! `accent-color` in `org.freedesktop.appearance` (three doubles in
+//! `0.0..=1.0`; out of range means none is set).
+//!
+//! The accessibility portal watcher reads both at startup and applies every
+//! `SettingChanged` for them, so a new accent restyles an open or idle
+//! overlay at once (`ThemeChanged` plus a fresh `MenuPayload`). Without a
+//! portal, or when the desktop sets no accent, the theme's accent is used.
+
+
+use zbus::zvariant::Value;
+
+use crate::accessibility::{APPEARANCE_NAMESPACE, KDE_GENERAL_NAMESPACE};
+use crate::handles::StateHandle;
+use crate::theme::ThemeManager;
+
+/// Cross-desktop accent color ((ddd), 0.0-1.0 per channel)
+pub const APPEARANCE_ACCENT_KEY: &str = "accent-color";
+
+/// Plasma accent color (string, "r,g,b"; empty when the scheme's is used)
+pub const KDE_ACCENT_KEY: &str = "AccentColor";
+
+/// Portal settings that carry the desktop accent color
+pub const ACCENT_SETTINGS: &[(&str, &str)] = &[
+    (KDE_GENERAL_NAMESPACE, KDE_ACCENT_KEY),
+    (APPEARANCE_NAMESPACE, APPEARANCE_ACCENT_KEY),
+];
+
+/// The desktop accent and whether the menu follows it
+#[derive(Debug)]
+pub struct SystemAccent {
+    /// Last accent the desktop reported, as `#rrggbb`
+    system: Option<String>,
+    /// `colors.accent_follow_system`
+    follow: bool,
+}
+
+impl Default for SystemAccent {
+    fn default() -> Self {
+        Self { system: None, follow: true }
+    }
+}
+
+impl SystemAccent {
+    /// Record the desktop accent; `None` when it sets none
+    pub fn set_system(&mut self, accent: Option<String>) {
+        self.system = accent;
+    }
+
+    /// Apply `colors.accent_follow_system`
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+    }
+
+    /// Last accent the desktop reported
+    pub fn system(&self) -> Option<&str> {
+        self.system.as_deref()
+    }
+
+    /// Accent to draw instead of the theme's; `None` keeps the theme's
+    pub fn effective(&self) -> Option<&str> {
+        self.system.as_deref().filter(|_| self.follow)
+    }
+}
+
+/// The daemon's accent state
+pub type SystemAccentHandle = StateHandle<SystemAccent>;
+
+impl SystemAccentHandle {
+    /// Draw `themes` with the effective accent; true when that changed what
+    /// is drawn
+    ///
+    /// Called whenever the desktop accent or the config changes, and after
+    /// the theme manager is replaced on `ReloadConfig`.
+    pub fn apply(&self, themes: &mut ThemeManager) -> bool {
+        let accent = self.update(|s| s.effective().map(str::to_string)).flatten();
+        themes.set_accent_override(accent.as_deref())
+    }
+}
+
+/// Interpret a portal setting as the desktop accent color
+///
+/// Returns `None` for settings that are not an accent color, `Some(None)`
+/// when the desktop sets no accent (or one that cannot be read), and
+/// `Some(Some("#rrggbb"))` otherwise.
+pub fn accent_from_setting(namespace: &str, key: &str, value: &Value<'_>) -> Option<Option<String>> {
+    if let Value::Value(inner) = value {
+        return accent_from_setting(namespace, key, inner);
+    }
+
+    match (namespace, key, value) {
+        (KDE_GENERAL_NAMESPACE, KDE_ACCENT_KEY, Value::Str(color)) => Some(parse_kde_color(color)),
+        (APPEARANCE_NAMESPACE, APPEARANCE_ACCENT_KEY, Value::Structure(rgb)) => {
+            let channels: Vec<f64> = rgb
+                .fields()
+                .iter()
+                .filter_map(|field| match field {
+                    Value::F64(channel) => Some(*channel),
+                    _ => None,
+                })
+                .collect();
+            let [r, g, b] = channels[..] else {
+                return Some(None);
+            };
+            if ![r, g, b].iter().all(|c| (0.0..=1.0).contains(c)) {
+                return Some(None);
+            }
+            let byte = |c: f64| (c * 255.0).round() as u8;
+            Some(Some(hex(byte(r), byte(g), byte(b))))
+        }
+        _ => None,
+    }
+}
+
+/// A kdeglobals color, `"r,g,b"` (an alpha after it is ignored) or `#rrggbb`
+fn parse_kde_color(color: &str) -> Option<String> {
+    let color = color.trim();
+    if let Some(digits) = color.strip_prefix('#') {
+        return (digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| color.to_ascii_lowercase());
+    }
+    let channels: Vec<u8> = color
+        .split(',')
+        .map(|c| c.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    match channels[..] {
+        [r, g, b] | [r, g, b, _] => Some(hex(r, g, b)),
+        _ => None,
+    }
+}
+
+fn hex(r: u8, g: u8, b: u8) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::zvariant::StructureBuilder;
+
+    fn rgb(r: f64, g: f64, b: f64) -> Value<'static> {
+        Value::Structure(StructureBuilder::new().add_field(r).add_field(g).add_field(b).build().unwrap())
+    }
+
+    #[test]
+    fn test_accent_from_kde_setting() {
+        let read = |s: &str| accent_from_setting(KDE_GENERAL_NAMESPACE, KDE_ACCENT_KEY, &Value::from(s));
+        assert_eq!(read("61,174,233"), Some(Some("#3daee9".into())));
+        assert_eq!(read(" 61, 174, 233, 255"), Some(Some("#3daee9".into())));
+        assert_eq!(read("#3DAEE9"), Some(Some("#3daee9".into())));
+        // Empty: Plasma uses the color scheme's accent, which it does not publish
+        assert_eq!(read(""), Some(None));
+        assert_eq!(read("61,174"), Some(None));
+        assert_eq!(read("300,0,0"), Some(None));
+        let wrapped = Value::Value(Box::new(Value::from("255,0,0")));
+        assert_eq!(accent_from_setting(KDE_GENERAL_NAMESPACE, KDE_ACCENT_KEY, &wrapped), Some(Some("#ff0000".into())));
+    }
+
+    #[test]
+    fn test_accent_from_appearance_setting() {
+        let read = |value: &Value<'_>| accent_from_setting(APPEARANCE_NAMESPACE, APPEARANCE_ACCENT_KEY, value);
+        assert_eq!(read(&rgb(0.24, 0.68, 0.91)), Some(Some("#3dade8".into())));
+        assert_eq!(read(&Value::Value(Box::new(rgb(1.0, 0.0, 0.0)))), Some(Some("#ff0000".into())));
+        // Out of range is the portal's "no accent"
+        assert_eq!(read(&rgb(-1.0, -1.0, -1.0)), Some(None));
+        // Not accent settings
+        assert_eq!(accent_from_setting(APPEARANCE_NAMESPACE, "contrast", &Value::U32(1)), None);
+        assert_eq!(accent_from_setting(KDE_GENERAL_NAMESPACE, "ColorScheme", &Value::from("BreezeDark")), None);
+        assert_eq!(read(&Value::from("61,174,233")), None);
+    }
+
+    #[test]
+    fn test_effective_accent_follows_config() {
+        let mut accent = SystemAccent::default();
+        assert_eq!(accent.effective(), None);
+        accent.set_system(Some("#3daee9".into()));
+        assert_eq!(accent.effective(), Some("#3daee9"));
+        accent.set_follow(false);
+        assert_eq!(accent.effective(), None);
+        assert_eq!(accent.system(), Some("#3daee9"));
+    }
+
+    #[test]
+    fn test_apply_substitutes_and_restores_the_accent() {
+        let mut themes = ThemeManager::new();
+        let own = themes.current().colors.accent.clone();
+        let accent = SystemAccentHandle::default();
+        accent.update(|s| {
+            s.set_follow(true);
+            s.set_system(Some("#3daee9".into()));
+        });
+        assert!(accent.apply(&mut themes));
+        assert_eq!(themes.current().colors.accent, "#3daee9");
+        assert!(!accent.apply(&mut themes), "unchanged accent is not a change");
+
+        // A desktop that stops publishing an accent falls back to the theme's
+        accent.update(|s| s.set_system(None));
+        assert!(accent.apply(&mut themes));
+        assert_eq!(themes.current().colors.accent, own);
+    }
+}
@@ -0,0 +1,205 @@
+//! Theme crossfades
+//!
+//! When the effective theme changes (a new `theme` on `ReloadConfig`, or high
+//! contrast turning on or off) the daemon emits `ThemeChanged` with the
+//! resolved theme before and after and how long the overlay should crossfade
+//! between them (`menu.theme_transition_ms`, 0 under reduced motion), so an
+//! open menu fades instead of snapping.
+//!
+//! The from-state is held until the crossfade would have finished. A switch
+//! arriving before then coalesces: it fades from that same from-state to the
+//! newest target, so quick successive switches never chain fades through
+//! themes the user only passed by. `ThemeTransitions` is the pure state
+//! machine (times are passed in); the D-Bus service feeds it through the
+//! [`ThemeTransitionHandle`] on `DaemonHandles`.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::accessibility::SharedAccessibility;
+use crate::handles::StateHandle;
+use crate::theme::{EffectiveColors, SharedThemeManager, Theme};
+
+/// Default `menu.theme_transition_ms`
+pub const TRANSITION_MS: u32 = 150;
+
+/// The theme as the overlay draws it, accessibility overrides applied
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedTheme {
+    pub name: String,
+    pub high_contrast: bool,
+    pub background_opacity: f32,
+    pub colors: EffectiveColors,
+    /// Desktop accent the theme is drawn with, as in the payload's `accent`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_override: Option<String>,
+}
+
+impl ResolvedTheme {
+    pub fn resolve(theme: &Theme, high_contrast: bool) -> Self {
+        Self {
+            name: theme.name.clone(),
+            high_contrast,
+            background_opacity: theme.effective_background_opacity(high_contrast),
+            colors: theme.get_effective_colors(high_contrast),
+            accent_override: None,
+        }
+    }
+
+    /// The current theme and accessibility state; `None` on a poisoned lock
+    pub fn current(themes: &SharedThemeManager, accessibility: &SharedAccessibility) -> Option<Self> {
+        let high_contrast = accessibility.read().ok()?.should_use_high_contrast();
+        let themes = themes.read().ok()?;
+        Some(Self {
+            accent_override: themes.accent_override().map(str::to_string),
+            ..Self::resolve(themes.current(), high_contrast)
+        })
+    }
+}
+
+/// Body of the `ThemeChanged` signal
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThemeChange {
+    pub from: ResolvedTheme,
+    pub to: ResolvedTheme,
+    /// Crossfade duration; 0 means switch at once
+    pub transition_ms: u32,
+}
+
+impl ThemeChange {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// Crossfade transition ms for the config value and reduced motion
+pub fn effective_ms(transition_ms: u32, reduced_motion: bool) -> u32 {
+    if reduced_motion {
+        0
+    } else {
+        transition_ms
+    }
+}
+
+/// The crossfade in flight, if any
+#[derive(Debug, Default)]
+pub struct ThemeTransitions {
+    /// From-state of the running crossfade and when it ends
+    in_flight: Option<(ResolvedTheme, Instant)>,
+}
+
+impl ThemeTransitions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The effective theme went from `before` to `after`
+    ///
+    /// Returns the change to announce, or `None` when nothing the overlay
+    /// draws changed. During a running crossfade the change starts from that
+    /// crossfade's from-state rather than `before`.
+    pub fn switched(
+        &mut self,
+        before: ResolvedTheme,
+        after: ResolvedTheme,
+        transition_ms: u32,
+        now: Instant,
+    ) -> Option<ThemeChange> {
+        if before == after {
+            return None;
+        }
+        let from = match self.in_flight.take() {
+            Some((from, ends)) if now < ends => from,
+            _ => before,
+        };
+        let duration = Duration::from_millis(transition_ms.into());
+        self.in_flight = (transition_ms > 0).then(|| (from.clone(), now + duration));
+        Some(ThemeChange {
+            from,
+            to: after,
+            transition_ms,
+        })
+    }
+
+    /// Whether a crossfade is still running at `now`
+    pub fn in_flight(&self, now: Instant) -> bool {
+        self.in_flight.as_ref().is_some_and(|(_, ends)| now < *ends)
+    }
+}
+
+/// The running crossfade, shared through `DaemonHandles`
+pub type ThemeTransitionHandle = StateHandle<ThemeTransitions>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundled_themes::get_bundled_theme;
+
+    fn resolved(name: &str) -> ResolvedTheme {
+        ResolvedTheme::resolve(&get_bundled_theme(name).unwrap(), false)
+    }
+
+    #[test]
+    fn test_change_carries_both_themes() {
+        let t0 = Instant::now();
+        let mut transitions = ThemeTransitions::new();
+        let mocha = resolved("catppuccin-mocha");
+        let vaporwave = resolved("vaporwave");
+        assert!(transitions.switched(mocha.clone(), mocha.clone(), TRANSITION_MS, t0).is_none());
+
+        let change = transitions.switched(mocha.clone(), vaporwave.clone(), TRANSITION_MS, t0).unwrap();
+        assert_eq!((change.from.name.as_str(), change.to.name.as_str()), ("catppuccin-mocha", "vaporwave"));
+        assert_eq!(change.transition_ms, 150);
+        let json: serde_json::Value = serde_json::from_str(&change.to_json()).unwrap();
+        assert_eq!(json["to"]["colors"]["accent"], vaporwave.colors.accent.as_str());
+        assert_eq!(json["from"]["high_contrast"], false);
+        assert!(transitions.in_flight(t0 + Duration::from_millis(149)));
+        assert!(!transitions.in_flight(t0 + Duration::from_millis(150)));
+
+        // High contrast is a change of the resolved theme too
+        let contrast = ResolvedTheme::resolve(&get_bundled_theme("vaporwave").unwrap(), true);
+        let later = t0 + Duration::from_secs(1);
+        let change = transitions.switched(vaporwave.clone(), contrast, TRANSITION_MS, later).unwrap();
+        assert_eq!(change.from, vaporwave);
+        assert!(change.to.high_contrast);
+    }
+
+    #[test]
+    fn test_rapid_switches_coalesce_onto_the_first_from_state() {
+        let t0 = Instant::now();
+        let mut transitions = ThemeTransitions::new();
+        let mocha = resolved("catppuccin-mocha");
+        let vaporwave = resolved("vaporwave");
+        let matrix = resolved("matrix-rain");
+
+        transitions.switched(mocha.clone(), vaporwave.clone(), TRANSITION_MS, t0).unwrap();
+        // Mid-fade: only the newest target matters, still fading from mocha
+        let change = transitions.switched(vaporwave, matrix.clone(), TRANSITION_MS, t0 + Duration::from_millis(60)).unwrap();
+        assert_eq!((change.from, change.to), (mocha.clone(), matrix.clone()));
+        // The from-state is held for a full crossfade from the last switch
+        assert!(transitions.in_flight(t0 + Duration::from_millis(200)));
+
+        // Once settled the next switch starts from where it settled
+        let later = t0 + Duration::from_secs(1);
+        let change = transitions.switched(matrix.clone(), mocha.clone(), TRANSITION_MS, later).unwrap();
+        assert_eq!(change.from, matrix);
+    }
+
+    #[test]
+    fn test_reduced_motion_switches_at_once() {
+        assert_eq!(effective_ms(TRANSITION_MS, true), 0);
+        assert_eq!(effective_ms(TRANSITION_MS, false), 150);
+
+        let t0 = Instant::now();
+        let mut transitions = ThemeTransitions::new();
+        let mocha = resolved("catppuccin-mocha");
+        let vaporwave = resolved("vaporwave");
+        let change = transitions.switched(mocha.clone(), vaporwave.clone(), 0, t0).unwrap();
+        assert_eq!(change.transition_ms, 0);
+        assert!(!transitions.in_flight(t0));
+        // Nothing held: the next switch fades from the settled theme
+        let change = transitions.switched(vaporwave.clone(), mocha, TRANSITION_MS, t0).unwrap();
+        assert_eq!(change.from, vaporwave);
+    }
+}
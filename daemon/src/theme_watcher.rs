@@ -2,19 +2,54 @@
 //!
 //! Watches theme directories for changes using inotify and triggers hot-reload.
 //! Changes are detected within 100ms and debounced to avoid rapid reloads.
+//! A save that leaves the theme's content hash unchanged is not reloaded.
+//! Both layouts hot-reload: `{name}/theme.json` and a flat `{name}.json` in a
+//! themes directory. A file that fails to parse or validate leaves the loaded
+//! version in place and is reported as `ThemeReloadFailed`.
+//! Any other file changing under a themes directory may be a background
+//! texture and reloads the themes that could use it; those whose texture did
+//! not change hash the same and are skipped.
+//!
+//! Memory stays bounded when something floods a themes directory: notify's
+//! events go through a queue of [`QUEUE_CAPACITY`] that drops the oldest,
+//! at most [`MAX_PENDING`] paths wait for the debounce, and past
+//! [`STORM_THRESHOLD`] events a second the watcher stops tracking files and
+//! rescans the directories once the storm is over. `Diagnostics()` reports
+//! the drops and storms under `theme_watcher`.
 
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashSet;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{channel, Receiver};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
 
-use crate::theme::{get_system_themes_dir, get_user_themes_dir, Theme, ThemeManager};
+use serde::Serialize;
+
+use crate::content_hash::ContentHash;
+use crate::handles::StateHandle;
+use crate::paths::Paths;
+use crate::theme::{
+    get_user_themes_dir, is_flat_theme_file, scan_themes_directory, SharedThemeManager,
+    Theme, ThemeError, ThemeOrigin, ThemeSource,
+};
+use crate::validation::{IssueCode, ValidationIssue};
 
 /// Debounce window to avoid multiple reloads on rapid saves
 const DEBOUNCE_MS: u64 = 50;
 
+/// Raw notify events held between polls; past it the oldest are dropped
+pub const QUEUE_CAPACITY: usize = 1024;
+
+/// Changed paths held for debouncing; past it new paths are dropped and
+/// picked up by a rescan
+pub const MAX_PENDING: usize = 256;
+
+/// Raw events within [`STORM_WINDOW`] that switch to rescan mode
+pub const STORM_THRESHOLD: usize = 500;
+
+/// Window the event rate is measured over
+const STORM_WINDOW: Duration = Duration::from_secs(1);
+
 /// Theme change event
 #[derive(Debug, Clone)]
 pub enum ThemeEvent {
@@ -28,16 +63,257 @@ pub enum ThemeEvent {
     Error(String),
 }
 
+type NotifyResult = Result<Event, notify::Error>;
+
+/// Counters for `Diagnostics()`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ThemeWatcherStats {
+    /// Raw events dropped because the queue was full
+    pub dropped_events: u64,
+    /// Changed paths dropped because the pending set was full
+    pub dropped_changes: u64,
+    /// Times an event storm switched the watcher to rescan mode
+    pub storm_activations: u64,
+    /// Full rescans of the theme directories
+    pub rescans: u64,
+    /// Whether a storm is going on now
+    pub in_storm: bool,
+}
+
+/// Bounded queue between the notify thread and the poll loop
+///
+/// Keeps the newest events: a push onto a full queue drops the oldest one
+/// and counts it, so a burst never blocks notify's thread and the last
+/// write to a file is never the one lost. The poll loop rescans after any
+/// drop.
+pub struct EventQueue {
+    inner: Mutex<QueueState>,
+    ready: Condvar,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<NotifyResult>,
+    dropped: u64,
+}
+
+impl EventQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(QueueState::default()),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Add an event, dropping the oldest when full
+    pub fn push(&self, event: NotifyResult) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if state.events.len() >= self.capacity {
+            state.events.pop_front();
+            state.dropped += 1;
+        }
+        state.events.push_back(event);
+        self.ready.notify_one();
+    }
+
+    /// Everything queued, and how many events were dropped since the last
+    /// drain
+    pub fn drain(&self) -> (Vec<NotifyResult>, u64) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let dropped = std::mem::take(&mut state.dropped);
+        (state.events.drain(..).collect(), dropped)
+    }
+
+    /// The oldest event, waiting up to `timeout` for one
+    pub fn pop(&self, timeout: Duration) -> Option<NotifyResult> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut state, _) = self
+            .ready
+            .wait_timeout_while(state, timeout, |s| s.events.is_empty())
+            .unwrap_or_else(|e| e.into_inner());
+        state.events.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner()).events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Turns raw events into debounced theme events
+///
+/// Normally each theme file's latest change is held until no event arrived
+/// for [`DEBOUNCE_MS`]. Once [`STORM_THRESHOLD`] raw events land within
+/// [`STORM_WINDOW`] (an editor or build writing into a themes directory),
+/// per-file tracking stops: the held changes are dropped, events only mark
+/// the directories dirty, and after the first window under the threshold a
+/// full rescan reports every theme file as modified (unchanged ones are
+/// skipped by their content hash). Overflowing the queue or the pending set
+/// also leads to a rescan. Times are passed in.
+pub struct ChangeCollector {
+    /// Themes directories, for telling flat theme files from other JSON
+    roots: Vec<PathBuf>,
+    /// The latest change per recently modified path
+    pending: HashMap<PathBuf, ThemeEvent>,
+    /// Last event time for debouncing
+    last_event: Option<Instant>,
+    /// Start of the current rate window and the raw events counted in it
+    window_start: Option<Instant>,
+    window_events: usize,
+    /// Rescan once the burst is over
+    dirty: bool,
+    stats: ThemeWatcherStats,
+}
+
+impl ChangeCollector {
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        Self {
+            roots,
+            pending: HashMap::new(),
+            last_event: None,
+            window_start: None,
+            window_events: 0,
+            dirty: false,
+            stats: ThemeWatcherStats::default(),
+        }
+    }
+
+    /// Take a drained batch (with the count dropped before it) and return
+    /// the events ready at `now`
+    pub fn collect(&mut self, batch: Vec<NotifyResult>, dropped: u64, now: Instant) -> Vec<ThemeEvent> {
+        let mut events = Vec::new();
+        if dropped > 0 {
+            self.stats.dropped_events += dropped;
+            self.dirty = true;
+        }
+        self.measure(batch.len() as u64 + dropped, now);
+
+        for result in batch {
+            match result {
+                Ok(event) => {
+                    self.last_event = Some(now);
+                    if self.stats.in_storm {
+                        continue;
+                    }
+                    for theme_event in self.process(event) {
+                        self.hold(theme_event);
+                    }
+                }
+                Err(e) => events.push(ThemeEvent::Error(e.to_string())),
+            }
+        }
+
+        // Within the debounce window the held changes wait for the next poll
+        let quiet = self
+            .last_event
+            .is_none_or(|last| now.saturating_duration_since(last) >= Duration::from_millis(DEBOUNCE_MS));
+        if quiet && !self.stats.in_storm {
+            events.extend(self.pending.drain().map(|(_, event)| event));
+            if std::mem::take(&mut self.dirty) {
+                self.stats.rescans += 1;
+                let files: Vec<PathBuf> = self.roots.iter().flat_map(|root| scan_themes_directory(root)).collect();
+                tracing::info!(files = files.len(), "Rescanning theme directories after dropped events");
+                events.extend(files.into_iter().map(ThemeEvent::Modified));
+            }
+        }
+        events
+    }
+
+    /// Count `count` raw events at `now` against the storm threshold
+    fn measure(&mut self, count: u64, now: Instant) {
+        let start = *self.window_start.get_or_insert(now);
+        if now.saturating_duration_since(start) >= STORM_WINDOW {
+            if self.stats.in_storm && self.window_events < STORM_THRESHOLD {
+                self.stats.in_storm = false;
+                tracing::info!("Theme file event storm over; rescanning");
+            }
+            self.window_start = Some(now);
+            self.window_events = 0;
+        }
+        self.window_events = self.window_events.saturating_add(usize::try_from(count).unwrap_or(usize::MAX));
+        if !self.stats.in_storm && self.window_events >= STORM_THRESHOLD {
+            self.stats.in_storm = true;
+            self.stats.storm_activations += 1;
+            self.dirty = true;
+            self.pending.clear();
+            tracing::warn!(
+                events = self.window_events,
+                "Theme file event storm; rescanning once it subsides instead of reloading per file"
+            );
+        }
+    }
+
+    /// Process a raw notify event into theme events.
+    ///
+    /// A change to any other file is taken for a background texture and
+    /// reloads the theme files that may use it.
+    fn process(&self, event: Event) -> Vec<ThemeEvent> {
+        let kind: fn(PathBuf) -> ThemeEvent = match event.kind {
+            EventKind::Create(_) => ThemeEvent::Created,
+            EventKind::Modify(_) => ThemeEvent::Modified,
+            EventKind::Remove(_) => ThemeEvent::Deleted,
+            _ => return Vec::new(),
+        };
+        if let Some(path) = event.paths.iter().find(|p| is_watched_theme_file(p, &self.roots)) {
+            return vec![kind(path.clone())];
+        }
+        event
+            .paths
+            .iter()
+            .flat_map(|p| texture_owners(p, &self.roots))
+            .map(ThemeEvent::Modified)
+            .collect()
+    }
+
+    /// Keep `event` until the burst it belongs to is over
+    fn hold(&mut self, event: ThemeEvent) {
+        let path = match &event {
+            ThemeEvent::Modified(path) | ThemeEvent::Created(path) | ThemeEvent::Deleted(path) => path.clone(),
+            ThemeEvent::Error(_) => return,
+        };
+        if self.pending.len() >= MAX_PENDING && !self.pending.contains_key(&path) {
+            self.stats.dropped_changes += 1;
+            self.dirty = true;
+            return;
+        }
+        self.pending.insert(path, event);
+    }
+
+    /// Changed paths held now
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn stats(&self) -> ThemeWatcherStats {
+        self.stats
+    }
+}
+
+/// Counters of the daemon's running watcher, for `Diagnostics()`
+pub type ThemeWatcherStatsHandle = StateHandle<ThemeWatcherStats>;
+
+impl ThemeWatcherStatsHandle {
+    /// The last published counters
+    pub fn stats(&self) -> ThemeWatcherStats {
+        self.update(|s| *s).unwrap_or_default()
+    }
+}
+
 /// Theme file watcher using inotify
 pub struct ThemeWatcher {
     /// The underlying notify watcher
     _watcher: RecommendedWatcher,
-    /// Channel receiver for events
-    event_rx: Receiver<Result<Event, notify::Error>>,
-    /// Debounce state: paths that were recently modified
-    pending_changes: Arc<Mutex<HashSet<PathBuf>>>,
-    /// Last event time for debouncing
-    last_event_time: Arc<Mutex<Instant>>,
+    /// Events from notify's thread
+    queue: Arc<EventQueue>,
+    /// Debounce and storm state
+    collector: Mutex<ChangeCollector>,
+    /// Where the collector's counters are published
+    stats: ThemeWatcherStatsHandle,
 }
 
 impl ThemeWatcher {
@@ -47,16 +323,25 @@ impl ThemeWatcher {
     /// * `Ok(ThemeWatcher)` - Watcher is running
     /// * `Err` - Failed to initialize watcher
     pub fn new() -> Result<Self, ThemeWatcherError> {
-        let (tx, rx) = channel();
+        Self::watching(crate::paths::paths())
+    }
+
+    /// Watch the theme directories of `paths`
+    pub fn watching(paths: &Paths) -> Result<Self, ThemeWatcherError> {
+        let [system_dir, user_dir] = paths.themes_dirs();
+        let queue = Arc::new(EventQueue::new(QUEUE_CAPACITY));
+        let handler = {
+            let queue = queue.clone();
+            move |event: NotifyResult| queue.push(event)
+        };
 
         // Configure watcher with recommended settings
         let config = Config::default().with_poll_interval(Duration::from_millis(100));
 
-        let mut watcher = RecommendedWatcher::new(tx, config)
+        let mut watcher = RecommendedWatcher::new(handler, config)
             .map_err(|e| ThemeWatcherError::InitError(e.to_string()))?;
 
         // Watch system themes directory
-        let system_dir = get_system_themes_dir();
         if system_dir.exists() {
             watcher
                 .watch(&system_dir, RecursiveMode::Recursive)
@@ -65,7 +350,6 @@ impl ThemeWatcher {
         }
 
         // Watch user themes directory
-        let user_dir = get_user_themes_dir();
         if user_dir.exists() {
             watcher
                 .watch(&user_dir, RecursiveMode::Recursive)
@@ -77,144 +361,161 @@ impl ThemeWatcher {
 
         Ok(Self {
             _watcher: watcher,
-            event_rx: rx,
-            pending_changes: Arc::new(Mutex::new(HashSet::new())),
-            last_event_time: Arc::new(Mutex::new(Instant::now())),
+            queue,
+            collector: Mutex::new(ChangeCollector::new(vec![system_dir, user_dir])),
+            stats: ThemeWatcherStatsHandle::default(),
         })
     }
 
     /// Check for pending theme events (non-blocking).
     ///
-    /// Returns events that have been debounced and are ready to process.
+    /// Returns events that have been debounced and are ready to process:
+    /// changes are held until no new one arrived for [`DEBOUNCE_MS`], then
+    /// returned once per path, latest kind wins. During an event storm they
+    /// come as one rescan once it is over (see [`ChangeCollector`]).
     pub fn poll_events(&self) -> Vec<ThemeEvent> {
-        let mut events = Vec::new();
-
-        // Collect all pending notify events
-        while let Ok(result) = self.event_rx.try_recv() {
-            match result {
-                Ok(event) => {
-                    if let Some(theme_event) = self.process_notify_event(event) {
-                        events.push(theme_event);
-                    }
-                }
-                Err(e) => {
-                    events.push(ThemeEvent::Error(e.to_string()));
-                }
-            }
-        }
-
-        // Apply debouncing
-        self.debounce_events(&mut events);
-
+        let (batch, dropped) = self.queue.drain();
+        let mut collector = self.collector.lock().unwrap_or_else(|e| e.into_inner());
+        let events = collector.collect(batch, dropped, Instant::now());
+        self.stats.update(|s| *s = collector.stats());
         events
     }
 
-    /// Process a raw notify event into a theme event.
-    fn process_notify_event(&self, event: Event) -> Option<ThemeEvent> {
-        // Only process events for theme.json files
-        let theme_json_paths: Vec<PathBuf> = event
-            .paths
-            .into_iter()
-            .filter(|p| p.file_name().map(|n| n == "theme.json").unwrap_or(false))
-            .collect();
-
-        if theme_json_paths.is_empty() {
-            return None;
-        }
-
-        let path = theme_json_paths.into_iter().next()?;
-
-        // Update debounce tracking
-        {
-            let mut pending = self.pending_changes.lock().unwrap();
-            pending.insert(path.clone());
-            *self.last_event_time.lock().unwrap() = Instant::now();
-        }
-
-        match event.kind {
-            EventKind::Create(_) => Some(ThemeEvent::Created(path)),
-            EventKind::Modify(_) => Some(ThemeEvent::Modified(path)),
-            EventKind::Remove(_) => Some(ThemeEvent::Deleted(path)),
-            _ => None,
+    /// Blocking wait for the next theme event.
+    ///
+    /// Waits up to the specified timeout for an event.
+    pub fn wait_for_event(&self, timeout: Duration) -> Option<ThemeEvent> {
+        match self.queue.pop(timeout)? {
+            Ok(event) => self.collector.lock().unwrap_or_else(|e| e.into_inner()).process(event).into_iter().next(),
+            Err(e) => Some(ThemeEvent::Error(e.to_string())),
         }
     }
+}
 
-    /// Apply debouncing to events.
-    fn debounce_events(&self, events: &mut Vec<ThemeEvent>) {
-        let last_event = *self.last_event_time.lock().unwrap();
-        let elapsed = last_event.elapsed();
-
-        // If we're within the debounce window, clear events
-        if elapsed < Duration::from_millis(DEBOUNCE_MS) {
-            events.clear();
-        } else {
-            // Clear the pending set since we're processing
-            let mut pending = self.pending_changes.lock().unwrap();
-            pending.clear();
-        }
+/// Whether a changed path is a theme file under one of the themes `roots`
+///
+/// `theme.json` counts inside a theme subdirectory; `{name}.json` counts
+/// directly in a root. A root-level `theme.json` is ambiguous and ignored, as
+/// in `scan_themes_directory`.
+fn is_watched_theme_file(path: &Path, roots: &[PathBuf]) -> bool {
+    let Some(parent) = path.parent() else {
+        return false;
+    };
+    let in_root = roots.iter().any(|root| root == parent);
+    if is_flat_theme_file(path) {
+        in_root
+    } else {
+        !in_root && path.file_name().is_some_and(|n| n == "theme.json")
     }
+}
 
-    /// Blocking wait for the next theme event.
-    ///
-    /// Waits up to the specified timeout for an event.
-    pub fn wait_for_event(&self, timeout: Duration) -> Option<ThemeEvent> {
-        match self.event_rx.recv_timeout(timeout) {
-            Ok(Ok(event)) => self.process_notify_event(event),
-            Ok(Err(e)) => Some(ThemeEvent::Error(e.to_string())),
-            Err(_) => None, // Timeout
+/// Theme files whose background texture `path` could be
+///
+/// Textures resolve against the directory holding the theme file: inside a
+/// theme subdirectory that is its `theme.json`, directly in a root it is
+/// every flat theme there. Hidden files (editor swap files) are nobody's.
+fn texture_owners(path: &Path, roots: &[PathBuf]) -> Vec<PathBuf> {
+    let hidden = path.file_name().and_then(|n| n.to_str()).is_none_or(|n| n.starts_with('.'));
+    let Some(root) = roots.iter().find(|root| path.starts_with(root)) else {
+        return Vec::new();
+    };
+    if hidden || path.file_name().is_some_and(|n| n == "theme.json") {
+        return Vec::new();
+    }
+    match path.strip_prefix(root).ok().and_then(|rest| rest.components().next()) {
+        Some(_) if path.parent() == Some(root.as_path()) => scan_themes_directory(root)
+            .into_iter()
+            .filter(|file| is_flat_theme_file(file))
+            .collect(),
+        Some(dir) => {
+            let theme_file = root.join(dir).join("theme.json");
+            if theme_file.is_file() {
+                vec![theme_file]
+            } else {
+                Vec::new()
+            }
         }
+        None => Vec::new(),
     }
 }
 
 /// Error types for theme watcher
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum ThemeWatcherError {
     /// Failed to initialize the watcher
+    #[error("Failed to initialize theme watcher: {0}")]
     InitError(String),
     /// Failed to watch a specific path
+    #[error("Failed to watch {path}: {1}", path = .0.display())]
     WatchError(PathBuf, String),
 }
 
-impl std::fmt::Display for ThemeWatcherError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::InitError(msg) => write!(f, "Failed to initialize theme watcher: {}", msg),
-            Self::WatchError(path, msg) => {
-                write!(f, "Failed to watch {}: {}", path.display(), msg)
-            }
+/// Why a changed theme file was not loaded, sent as `ThemeReloadFailed`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThemeReloadFailure {
+    /// The theme's name, from the file when it parsed, else from the loaded
+    /// theme at that path or the file name
+    pub theme: String,
+    pub path: PathBuf,
+    /// Parse error, or every validation error as `field: message`
+    pub errors: Vec<String>,
+    /// The same findings, structured
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ThemeReloadFailure {
+    fn new(theme: String, path: &Path, errors: Vec<String>, issues: Vec<ValidationIssue>) -> Self {
+        Self {
+            theme,
+            path: path.to_path_buf(),
+            errors,
+            issues,
         }
     }
+
+    /// One line for a desktop notification
+    pub fn summary(&self) -> String {
+        format!("{}: {}", self.theme, self.errors.join("; "))
+    }
 }
 
-impl std::error::Error for ThemeWatcherError {}
+/// What a changed theme file led to
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThemeReload {
+    /// The theme passed validation and replaced the loaded one
+    Reloaded(String),
+    /// The file was rejected; the manager still has the previous version
+    Failed(ThemeReloadFailure),
+}
 
 /// Hot-reload handler for theme manager
 pub struct ThemeHotReloader {
     /// Theme manager to reload into
-    manager: Arc<Mutex<ThemeManager>>,
+    manager: SharedThemeManager,
     /// Theme watcher
     watcher: ThemeWatcher,
 }
 
 impl ThemeHotReloader {
-    /// Create a new hot-reloader for the given theme manager.
-    pub fn new(manager: Arc<Mutex<ThemeManager>>) -> Result<Self, ThemeWatcherError> {
-        let watcher = ThemeWatcher::new()?;
+    /// Create a new hot-reloader for the given theme manager, publishing
+    /// the watcher's counters to `stats`
+    pub fn new(manager: SharedThemeManager, stats: ThemeWatcherStatsHandle) -> Result<Self, ThemeWatcherError> {
+        let mut watcher = ThemeWatcher::new()?;
+        watcher.stats = stats;
         Ok(Self { manager, watcher })
     }
 
     /// Process pending theme events and apply changes.
     ///
-    /// Returns the list of themes that were reloaded.
-    pub fn process_events(&self) -> Vec<String> {
-        let mut reloaded = Vec::new();
+    /// Returns the themes that were reloaded or rejected; unchanged saves and
+    /// shadowed files are left out.
+    pub fn process_events(&self) -> Vec<ThemeReload> {
+        let mut reloads = Vec::new();
 
         for event in self.watcher.poll_events() {
             match event {
                 ThemeEvent::Modified(path) | ThemeEvent::Created(path) => {
-                    if let Some(theme_name) = self.reload_theme(&path) {
-                        reloaded.push(theme_name);
-                    }
+                    reloads.extend(reload_theme(&self.manager, &path));
                 }
                 ThemeEvent::Deleted(path) => {
                     tracing::info!(path = %path.display(), "Theme file deleted");
@@ -227,65 +528,225 @@ impl ThemeHotReloader {
             }
         }
 
-        reloaded
+        reloads
     }
+}
 
-    /// Reload a single theme from file.
-    ///
-    /// Returns the theme name if successful.
-    fn reload_theme(&self, path: &Path) -> Option<String> {
-        tracing::debug!(path = %path.display(), "Attempting to reload theme");
-
-        match Theme::load_from_path(path) {
-            Ok(mut theme) => {
-                // Validate the new theme
-                let validation = theme.validate_and_clamp();
-
-                if validation.has_errors() {
-                    for error in &validation.errors {
-                        tracing::warn!(
-                            path = %path.display(),
-                            error = %error,
-                            "Invalid theme, keeping previous version"
-                        );
-                    }
-                    return None;
-                }
-
-                for warning in &validation.warnings {
-                    tracing::warn!(
-                        theme = %theme.name,
-                        warning = %warning,
-                        "Theme validation warning"
-                    );
-                }
+/// Reload a single theme from file into `manager`
+///
+/// The manager is only touched once the file has parsed and passed
+/// validation; a rejected file leaves the previous version in place. `None`
+/// when there was nothing to do (unchanged content, shadowed flat file).
+pub fn reload_theme(manager: &SharedThemeManager, path: &Path) -> Option<ThemeReload> {
+    tracing::debug!(path = %path.display(), "Attempting to reload theme");
+
+    let mut theme = match Theme::load_from_path(path) {
+        Ok(theme) => theme,
+        Err(e) => {
+            tracing::warn!(
+                path = %path.display(),
+                error = %e,
+                "Failed to reload theme, keeping previous version"
+            );
+            let name = loaded_name(manager, path);
+            let issues = match e {
+                ThemeError::Invalid(ref result) => result.errors.clone(),
+                ThemeError::ParseError(_) => vec![ValidationIssue::error("", IssueCode::ParseError, e.to_string())],
+                _ => vec![ValidationIssue::error("", IssueCode::Invalid, e.to_string())],
+            };
+            return Some(ThemeReload::Failed(ThemeReloadFailure::new(name, path, vec![e.to_string()], issues)));
+        }
+    };
+
+    // Validate the new theme
+    let validation = theme.validate_and_clamp();
+
+    if validation.has_errors() {
+        for error in &validation.errors {
+            tracing::warn!(
+                path = %path.display(),
+                field = %error.field,
+                code = %error.code,
+                error = %error,
+                "Invalid theme, keeping previous version"
+            );
+        }
+        let errors = validation
+            .errors
+            .iter()
+            .map(|issue| format!("{}: {}", issue.field, issue.message))
+            .collect();
+        return Some(ThemeReload::Failed(ThemeReloadFailure::new(theme.name, path, errors, validation.errors)));
+    }
 
-                let theme_name = theme.name.clone();
+    for warning in &validation.warnings {
+        tracing::warn!(
+            theme = %theme.name,
+            field = %warning.field,
+            code = %warning.code,
+            warning = %warning,
+            "Theme validation warning"
+        );
+    }
 
-                // Update the manager
-                let mut manager = self.manager.lock().unwrap();
-                manager.add_or_update_theme(theme);
+    let theme_name = theme.name.clone();
+    let source = if path.starts_with(get_user_themes_dir()) {
+        ThemeSource::User
+    } else {
+        ThemeSource::System
+    };
+
+    // Update the manager
+    let mut manager = match manager.write() {
+        Ok(manager) => manager,
+        Err(e) => {
+            tracing::error!(error = %e, "Theme manager lock poisoned, skipping reload");
+            return None;
+        }
+    };
+    if manager.shadowed_by_subdirectory(&theme_name, path) {
+        tracing::warn!(
+            theme = %theme_name,
+            path = %path.display(),
+            "Flat theme file has the same name as a theme subdirectory, ignoring"
+        );
+        return None;
+    }
+    if manager.is_unchanged(&theme, path) {
+        tracing::debug!(
+            theme = %theme_name,
+            hash = %ContentHash::of(&theme),
+            "Theme file saved without changes, skipping reload"
+        );
+        return None;
+    }
+    manager.add_or_update_theme_from(
+        theme,
+        ThemeOrigin {
+            source,
+            path: Some(path.to_path_buf()),
+        },
+    );
+
+    tracing::info!(
+        theme = %theme_name,
+        path = %path.display(),
+        "Theme hot-reloaded successfully"
+    );
+
+    Some(ThemeReload::Reloaded(theme_name))
+}
 
-                tracing::info!(
-                    theme = %theme_name,
-                    path = %path.display(),
-                    "Theme hot-reloaded successfully"
-                );
+/// Name of the theme loaded from `path`, or the name its file implies
+fn loaded_name(manager: &SharedThemeManager, path: &Path) -> String {
+    let loaded = manager.read().ok().and_then(|manager| {
+        manager
+            .themes_with_sources()
+            .into_iter()
+            .find(|(_, origin)| origin.path.as_deref() == Some(path))
+            .map(|(theme, _)| theme.name.clone())
+    });
+    loaded.unwrap_or_else(|| {
+        let named = if is_flat_theme_file(path) { Some(path) } else { path.parent() };
+        named
+            .and_then(Path::file_stem)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    })
+}
 
-                Some(theme_name)
-            }
+/// Poll the theme directories and apply changes for the life of the daemon
+///
+/// A reload of the current theme re-emits `MenuPayload` so an open menu
+/// restyles; a rejected file emits nothing but `ThemeReloadFailed`, and when
+/// the settings app is not running, a `notify-send` notification. The
+/// watcher lives on its own thread; results come back over a channel.
+pub async fn run_hot_reload(connection: zbus::Connection, themes: SharedThemeManager, stats: ThemeWatcherStatsHandle) {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let manager = themes.clone();
+    std::thread::spawn(move || {
+        let reloader = match ThemeHotReloader::new(manager, stats) {
+            Ok(reloader) => reloader,
             Err(e) => {
-                tracing::warn!(
-                    path = %path.display(),
-                    error = %e,
-                    "Failed to reload theme, keeping previous version"
-                );
-                None
+                tracing::warn!(error = %e, "Theme hot-reload unavailable");
+                return;
             }
+        };
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            for reload in reloader.process_events() {
+                if tx.send(reload).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    while let Some(reload) = rx.recv().await {
+        let result = match reload {
+            ThemeReload::Reloaded(name) => {
+                let current = themes.read().is_ok_and(|t| t.current().name == name);
+                if current {
+                    refresh_menu_payload(&connection).await
+                } else {
+                    Ok(())
+                }
+            }
+            ThemeReload::Failed(failure) => announce_failure(&connection, &failure).await,
+        };
+        if let Err(e) = result {
+            tracing::warn!(error = %e, "Failed to announce theme reload");
         }
     }
 }
 
+/// How often the watcher thread collects debounced events
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether the settings app owns its bus name
+async fn settings_app_running(connection: &zbus::Connection) -> bool {
+    let Ok(proxy) = zbus::fdo::DBusProxy::new(connection).await else {
+        return false;
+    };
+    let Ok(name) = zbus::names::BusName::try_from(SETTINGS_APP_NAME) else {
+        return false;
+    };
+    proxy.name_has_owner(name).await.unwrap_or(false)
+}
+
+/// Bus name of the settings app, which shows `ThemeReloadFailed` itself
+const SETTINGS_APP_NAME: &str = "org.kde.juhradialmx.settings";
+
+async fn refresh_menu_payload(connection: &zbus::Connection) -> zbus::Result<()> {
+    let iface = connection
+        .object_server()
+        .interface::<_, crate::dbus::JuhRadialService>(crate::DBUS_PATH)
+        .await?;
+    let service = iface.get().await;
+    service.emit_menu_payload(iface.signal_emitter()).await.map(drop)
+}
+
+async fn announce_failure(connection: &zbus::Connection, failure: &ThemeReloadFailure) -> zbus::Result<()> {
+    let json = serde_json::to_string(failure).unwrap_or_default();
+    connection
+        .emit_signal(None::<&str>, crate::DBUS_PATH, crate::DBUS_INTERFACE, "ThemeReloadFailed", &(json,))
+        .await?;
+    if !settings_app_running(connection).await {
+        let spawned = tokio::process::Command::new("notify-send")
+            .args(["--app-name=JuhRadial MX", "--icon=dialog-warning", "Theme not reloaded"])
+            .arg(failure.summary())
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .await;
+        if let Err(e) = spawned {
+            tracing::debug!(error = %e, "notify-send unavailable for the theme reload failure");
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,11 +778,190 @@ mod tests {
         assert!(msg.contains("permission denied"));
     }
 
+    #[test]
+    fn test_watched_theme_files_both_layouts() {
+        let roots = vec![PathBuf::from("/usr/share/juhradial/themes"), PathBuf::from("/home/u/.config/juhradial/themes")];
+        let user = &roots[1];
+        assert!(is_watched_theme_file(&user.join("nord").join("theme.json"), &roots));
+        assert!(is_watched_theme_file(&user.join("my-theme.json"), &roots));
+        assert!(is_watched_theme_file(&roots[0].join("dark.json"), &roots));
+        // Ambiguous top-level theme.json, stray JSON inside a theme dir, swap files
+        assert!(!is_watched_theme_file(&user.join("theme.json"), &roots));
+        assert!(!is_watched_theme_file(&user.join("nord").join("extra.json"), &roots));
+        assert!(!is_watched_theme_file(&user.join(".my-theme.json"), &roots));
+        assert!(!is_watched_theme_file(&user.join("my-theme.json.swp"), &roots));
+    }
+
+    #[test]
+    fn test_texture_change_reloads_the_theme_using_it() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let path = root.join("grainy").join("theme.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let json = theme_json("#b4befe").replace("\"glassmorphism\": {}", "\"glassmorphism\": {\"backgroundTexture\": \"grain.svg\"}");
+        fs::write(&path, json).unwrap();
+        fs::write(root.join("flat.json"), theme_json("#b4befe")).unwrap();
+        let texture = root.join("grainy").join("grain.svg");
+        fs::write(&texture, "<svg/>").unwrap();
+        let manager: SharedThemeManager = Arc::new(std::sync::RwLock::new(crate::theme::ThemeManager::new()));
+        assert!(reload_theme(&manager, &path).is_some());
+
+        let roots = vec![root.to_path_buf()];
+        assert_eq!(texture_owners(&texture, &roots), vec![path.clone()]);
+        assert_eq!(texture_owners(&root.join("grain.png"), &roots), vec![root.join("flat.json")]);
+        assert!(texture_owners(&root.join("grainy").join(".grain.svg.swp"), &roots).is_empty());
+        assert!(texture_owners(&root.join("no-theme").join("grain.svg"), &roots).is_empty());
+
+        let mut collector = ChangeCollector::new(roots);
+        let t0 = Instant::now();
+        fs::write(&texture, "<svg width=\"4\"/>").unwrap();
+        collector.collect(vec![modified(&texture)], 0, t0);
+        let events = collector.collect(Vec::new(), 0, t0 + Duration::from_millis(DEBOUNCE_MS));
+        let [ThemeEvent::Modified(changed)] = events.as_slice() else {
+            panic!("one reload of the theme file, got {events:?}");
+        };
+        assert_eq!(changed, &path);
+        assert!(matches!(reload_theme(&manager, changed), Some(ThemeReload::Reloaded(_))));
+        // Touched without a change: same hash, no reload
+        assert!(reload_theme(&manager, changed).is_none());
+    }
+
     #[test]
     fn test_debounce_constant() {
         assert_eq!(DEBOUNCE_MS, 50);
     }
 
+    fn modified(path: &Path) -> NotifyResult {
+        Ok(Event::new(EventKind::Modify(notify::event::ModifyKind::Any)).add_path(path.to_path_buf()))
+    }
+
+    #[test]
+    fn test_storm_stays_bounded_and_rescan_lands_the_final_theme() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("edited").join("theme.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, theme_json("#b4befe")).unwrap();
+        let manager: SharedThemeManager = Arc::new(std::sync::RwLock::new(crate::theme::ThemeManager::new()));
+        assert!(reload_theme(&manager, &path).is_some());
+
+        // A build writing thousands of files: the queue keeps only the newest
+        let queue = EventQueue::new(QUEUE_CAPACITY);
+        for i in 0..5000 {
+            queue.push(modified(&dir.path().join(format!("gen{i}.json"))));
+        }
+        queue.push(modified(&path));
+        assert_eq!(queue.len(), QUEUE_CAPACITY);
+
+        let t0 = Instant::now();
+        let mut collector = ChangeCollector::new(vec![dir.path().to_path_buf()]);
+        let (batch, dropped) = queue.drain();
+        assert_eq!(dropped, 5001 - QUEUE_CAPACITY as u64);
+        assert!(collector.collect(batch, dropped, t0).is_empty());
+        assert!(collector.stats().in_storm);
+        assert_eq!(collector.pending_len(), 0, "nothing is tracked per file during a storm");
+
+        // The storm goes on; the theme gets its final content
+        for i in 0..600 {
+            queue.push(modified(&dir.path().join(format!("gen{i}.json"))));
+        }
+        fs::write(&path, theme_json("#f38ba8")).unwrap();
+        let (batch, dropped) = queue.drain();
+        assert!(collector.collect(batch, dropped, t0 + Duration::from_millis(1000)).is_empty());
+        assert!(collector.stats().in_storm, "still over the threshold");
+
+        assert!(collector.collect(Vec::new(), 0, t0 + Duration::from_millis(2100)).is_empty());
+        assert!(collector.stats().in_storm, "the last window was still over the threshold");
+
+        // A quiet window ends it with one rescan
+        let events = collector.collect(Vec::new(), 0, t0 + Duration::from_millis(3200));
+        let stats = collector.stats();
+        assert_eq!((stats.in_storm, stats.storm_activations, stats.rescans), (false, 1, 1));
+        assert!(stats.dropped_events > 0);
+        for event in events {
+            let ThemeEvent::Modified(changed) = event else {
+                panic!("rescan reports modifications");
+            };
+            reload_theme(&manager, &changed);
+        }
+        let manager = manager.read().unwrap();
+        let theme = manager.themes_with_sources().into_iter().find(|(t, _)| t.name == "edited").unwrap().0;
+        assert_eq!(theme.colors.accent, "#f38ba8");
+    }
+
+    #[test]
+    fn test_pending_set_is_capped_and_overflow_rescans() {
+        let dir = TempDir::new().unwrap();
+        let t0 = Instant::now();
+        let mut collector = ChangeCollector::new(vec![dir.path().to_path_buf()]);
+        let batch: Vec<NotifyResult> =
+            (0..MAX_PENDING + 44).map(|i| modified(&dir.path().join(format!("t{i}.json")))).collect();
+        assert!(collector.collect(batch, 0, t0).is_empty(), "held for the debounce");
+        assert_eq!(collector.pending_len(), MAX_PENDING);
+        assert_eq!(collector.stats().dropped_changes, 44);
+        assert!(!collector.stats().in_storm);
+
+        // The held changes, then a rescan for the dropped ones (none exist on disk)
+        let events = collector.collect(Vec::new(), 0, t0 + Duration::from_millis(DEBOUNCE_MS));
+        assert_eq!(events.len(), MAX_PENDING);
+        assert_eq!(collector.stats().rescans, 1);
+        assert!(collector.collect(Vec::new(), 0, t0 + Duration::from_millis(200)).is_empty());
+    }
+
+    fn theme_json(accent: &str) -> String {
+        format!(
+            r##"{{"name": "edited", "colors": {{"base": "#1e1e2e", "surface": "#313244", "text": "#cdd6f4",
+                "accent": "{accent}", "border": "#585b70"}}, "glassmorphism": {{}}, "animation": {{}}}}"##
+        )
+    }
+
+    #[test]
+    fn test_rejected_edit_keeps_previous_theme() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("edited").join("theme.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let manager: SharedThemeManager = Arc::new(std::sync::RwLock::new(crate::theme::ThemeManager::new()));
+
+        fs::write(&path, theme_json("#b4befe")).unwrap();
+        assert_eq!(reload_theme(&manager, &path), Some(ThemeReload::Reloaded("edited".to_string())));
+        manager.write().unwrap().set_current("edited").unwrap();
+        let hash = manager.read().unwrap().current_hash();
+
+        // A validation error: nothing of the edit reaches the manager
+        fs::write(&path, theme_json("#nothex")).unwrap();
+        let Some(ThemeReload::Failed(failure)) = reload_theme(&manager, &path) else {
+            panic!("invalid theme was not rejected");
+        };
+        assert_eq!(failure.theme, "edited");
+        assert_eq!(failure.path, path);
+        assert_eq!(failure.errors.len(), 1);
+        assert!(failure.errors[0].starts_with("colors.accent: Invalid hex color"), "{:?}", failure.errors);
+        let json = serde_json::to_value(&failure).unwrap();
+        assert_eq!(json["errors"][0], failure.errors[0]);
+        assert_eq!(json["issues"][0]["field"], "colors.accent");
+        assert_eq!(json["issues"][0]["code"], "invalid_color");
+        assert_eq!(json["issues"][0]["got"], "#nothex");
+        assert!(failure.summary().starts_with("edited: colors.accent"));
+        {
+            let manager = manager.read().unwrap();
+            assert_eq!(manager.current().colors.accent, "#b4befe");
+            assert_eq!(manager.current_hash(), hash);
+        }
+
+        // A half-written file does not parse; the name comes from the loaded theme
+        fs::write(&path, r#"{"name": "edited", "colors": {"#).unwrap();
+        let Some(ThemeReload::Failed(failure)) = reload_theme(&manager, &path) else {
+            panic!("unparsable theme was not rejected");
+        };
+        assert_eq!(failure.theme, "edited");
+        assert_eq!(manager.read().unwrap().current_hash(), hash);
+
+        // Fixing it reloads again
+        fs::write(&path, theme_json("#f38ba8")).unwrap();
+        assert_eq!(reload_theme(&manager, &path), Some(ThemeReload::Reloaded("edited".to_string())));
+        assert_eq!(manager.read().unwrap().current().colors.accent, "#f38ba8");
+        assert_eq!(reload_theme(&manager, &path), None, "an unchanged save is not a reload");
+    }
+
     // Integration test for file watching (requires actual filesystem)
     #[test]
     #[ignore] // This test requires actual inotify which may not work in all environments
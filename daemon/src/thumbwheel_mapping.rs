@@ -0,0 +1,774 @@
+//! Per-profile thumb-wheel action bindings
+//!
+//! A profile may bind `thumbwheel_left` / `thumbwheel_right` actions. While the
+//! focused app's profile (matched by window class and virtual desktop) binds a
+//! direction, the MX evdev loop swallows that
+//! direction's REL_HWHEEL events and fires the action once every
+//! `thumbwheel.detents_per_action` detents. Unbound directions pass through
+//! untouched, as does everything while the radial menu is open (the menu takes
+//! precedence) or when `thumbwheel.profile_mappings` is off. A profile picked
+//! with a `profile_switch` action replaces the focused app's profile until the
+//! focus moves to another window class.
+//!
+//! The mapper also keeps each profile's menu slices, slice descriptions,
+//! `wheel_axis` slices, `require_confirmation` slots and content hash, since
+//! it already tracks which profile is in effect; the menu payload takes the
+//! slices, hover help, wheel and confirmation hints and the profile hash for
+//! the open menu from here. Labels wider than `menu.label_max_cells` get
+//! their shortened form here too, once per profile load rather than per menu.
+//!
+//! The `default` profile's menu is the Settings ring (config.json
+//! `radial_menu.slices`), which the overlay draws and runs itself, so only
+//! the other profiles have slices and per-slot hints here.
+//!
+//! Only the un-diverted wheel reaches evdev: with `thumbwheel.mode` set to
+//! volume or zoom the rotation goes to HID++ instead and bindings never fire.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::actions::Action;
+use crate::config::ThumbwheelConfig;
+use crate::content_hash::ContentHash;
+use crate::geometry::{MenuSize, SLICE_COUNT};
+use crate::icon_resolver::{IconInfo, IconResolver};
+use crate::locale::LocalizedText;
+use crate::menu_payload::OverlaySlice;
+use crate::profiles::{Profile, ProfileCollision, ProfileManager, ProfileMatcher};
+use crate::wheel_axis::AxisSlice;
+
+/// Treat the menu as closed after this long without a ReportMenuClosed, so a
+/// crashed overlay cannot leave bindings suspended forever
+const MENU_OPEN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Horizontal wheel direction (REL_HWHEEL is positive to the right)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WheelDirection {
+    Left,
+    Right,
+}
+
+impl WheelDirection {
+    /// Direction of a REL_HWHEEL / REL_HWHEEL_HI_RES value (`None` for 0)
+    pub fn from_value(value: i32) -> Option<Self> {
+        match value.signum() {
+            1 => Some(Self::Right),
+            -1 => Some(Self::Left),
+            _ => None,
+        }
+    }
+}
+
+/// Thumb-wheel actions bound by one profile
+#[derive(Debug, Clone, Default)]
+pub struct ThumbwheelBinding {
+    pub profile: String,
+    pub left: Option<Action>,
+    pub right: Option<Action>,
+}
+
+impl ThumbwheelBinding {
+    fn from_profile(profile: &Profile) -> Self {
+        Self {
+            profile: profile.name.clone(),
+            left: profile.thumbwheel_left.clone(),
+            right: profile.thumbwheel_right.clone(),
+        }
+    }
+
+    fn action(&self, direction: WheelDirection) -> Option<&Action> {
+        match direction {
+            WheelDirection::Left => self.left.as_ref(),
+            WheelDirection::Right => self.right.as_ref(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
+}
+
+/// Resolves thumb-wheel bindings for the focused window
+#[derive(Debug)]
+pub struct ThumbwheelMapper {
+    enabled: bool,
+    detents_per_action: u8,
+    /// Wheel diverted to HID++ (volume/zoom), so evdev never sees it
+    hid_diverted: bool,
+    /// Profile names by lowercased window class and virtual desktop
+    matcher: ProfileMatcher,
+    /// Bindings of the default profile, used when no app profile matches
+    default_binding: ThumbwheelBinding,
+    /// Bindings keyed by profile name, for a manual selection
+    named: HashMap<String, ThumbwheelBinding>,
+    /// Menu slices keyed by profile name; `default` is absent
+    actions: HashMap<String, Vec<Option<Action>>>,
+    /// Slice descriptions keyed by profile name; profiles without any are absent
+    descriptions: HashMap<String, Vec<Option<String>>>,
+    /// `wheel_axis` slices keyed by profile name; profiles without any are absent
+    axes: HashMap<String, Vec<Option<AxisSlice>>>,
+    /// `require_confirmation` slots keyed by profile name; profiles without
+    /// any are absent
+    confirmations: HashMap<String, Vec<u8>>,
+    /// Slots with a `cooldown_ms` keyed by profile name; profiles without
+    /// any are absent
+    cooldowns: HashMap<String, Vec<(u8, Duration)>>,
+    /// Ring size keyed by profile name; eight-slice profiles are absent
+    slice_counts: HashMap<String, u8>,
+    /// `menu_size` keyed by profile name; profiles following `menu.size`
+    /// are absent
+    menu_sizes: HashMap<String, MenuSize>,
+    /// Content hash of each profile, keyed by profile name
+    hashes: HashMap<String, ContentHash>,
+    /// Slice labels in the process locale keyed by profile name, kept to
+    /// re-shorten on a budget change
+    labels: HashMap<String, Vec<Option<String>>>,
+    /// Profiles with at least one per-language label
+    localized: HashSet<String>,
+    /// Shortened labels keyed by profile name; profiles whose labels all fit
+    /// are absent
+    short_labels: HashMap<String, Vec<Option<String>>>,
+    /// `menu.label_max_cells`; `None` when shortening is off
+    label_budget: Option<usize>,
+    /// File icons keyed by profile name; profiles without any are absent
+    icons: HashMap<String, Vec<Option<IconInfo>>>,
+    icon_resolver: IconResolver,
+    /// Content hash of the profile set the bindings were built from
+    profiles_hash: Option<ContentHash>,
+    /// Duplicate names / window classes found when that set was loaded
+    collisions: Vec<ProfileCollision>,
+    /// Manually selected profile, overriding the window-class match
+    selected: Option<String>,
+    active_class: String,
+    active_desktop: Option<u32>,
+    menu_opened_at: Option<Instant>,
+    /// Signed detents not yet turned into an action
+    accumulated: i32,
+}
+
+/// Shared mapper: the evdev loop intercepts, the focus consumer and D-Bus
+/// service update it
+pub type SharedThumbwheelMapper = Arc<RwLock<ThumbwheelMapper>>;
+
+impl ThumbwheelMapper {
+    pub fn new(config: &ThumbwheelConfig) -> Self {
+        Self {
+            enabled: config.profile_mappings,
+            detents_per_action: config.detents(),
+            hid_diverted: config.is_diverted(),
+            matcher: ProfileMatcher::new(),
+            default_binding: ThumbwheelBinding::default(),
+            named: HashMap::new(),
+            actions: HashMap::new(),
+            descriptions: HashMap::new(),
+            axes: HashMap::new(),
+            confirmations: HashMap::new(),
+            cooldowns: HashMap::new(),
+            slice_counts: HashMap::new(),
+            menu_sizes: HashMap::new(),
+            hashes: HashMap::new(),
+            labels: HashMap::new(),
+            localized: HashSet::new(),
+            short_labels: HashMap::new(),
+            label_budget: Some(crate::label::LABEL_BUDGET_CELLS),
+            icons: HashMap::new(),
+            icon_resolver: IconResolver::new(crate::profiles::get_config_dir()),
+            profiles_hash: None,
+            collisions: Vec::new(),
+            selected: None,
+            active_class: String::new(),
+            active_desktop: None,
+            menu_opened_at: None,
+            accumulated: 0,
+        }
+    }
+
+    /// Apply a (re)loaded `thumbwheel` config section
+    pub fn set_config(&mut self, config: &ThumbwheelConfig) {
+        self.enabled = config.profile_mappings;
+        self.detents_per_action = config.detents();
+        self.hid_diverted = config.is_diverted();
+        self.accumulated = 0;
+    }
+
+    /// Apply `menu.label_max_cells`, re-shortening labels when it changed
+    pub fn set_label_budget(&mut self, budget: Option<usize>) {
+        if budget != self.label_budget {
+            self.label_budget = budget;
+            self.shorten_labels();
+        }
+    }
+
+    /// Rebuild bindings from loaded profiles
+    pub fn set_profiles(&mut self, manager: &ProfileManager) {
+        self.matcher = ProfileMatcher::new();
+        self.named.clear();
+        self.actions.clear();
+        self.descriptions.clear();
+        self.axes.clear();
+        self.confirmations.clear();
+        self.cooldowns.clear();
+        self.slice_counts.clear();
+        self.menu_sizes.clear();
+        self.labels.clear();
+        self.localized.clear();
+        self.icons.clear();
+        self.hashes.clone_from(manager.profile_hashes());
+        self.profiles_hash = Some(manager.content_hash());
+        self.collisions = manager.collisions().to_vec();
+        self.default_binding = ThumbwheelBinding::default();
+        for profile in manager.profiles() {
+            let binding = ThumbwheelBinding::from_profile(profile);
+            self.named.insert(profile.name.clone(), binding.clone());
+            if profile.slice_count != SLICE_COUNT {
+                self.slice_counts.insert(profile.name.clone(), profile.slice_count);
+            }
+            if let Some(size) = profile.menu_size {
+                self.menu_sizes.insert(profile.name.clone(), size);
+            }
+            if profile.name == "default" {
+                // Its menu is the Settings ring, not these slices
+                self.default_binding = binding;
+                continue;
+            }
+            let class = profile.window_class.as_ref().map(|c| c.to_lowercase());
+            self.matcher.insert(class.as_deref(), profile.virtual_desktop, &profile.name);
+            self.actions.insert(profile.name.clone(), profile.slices.clone());
+            let descriptions: Vec<Option<String>> = profile
+                .slices
+                .iter()
+                .map(|slice| slice.as_ref().and_then(|a| a.description.clone()))
+                .collect();
+            if descriptions.iter().any(Option::is_some) {
+                self.descriptions.insert(profile.name.clone(), descriptions);
+            }
+            if let Some(axes) = crate::wheel_axis::profile_axes(profile) {
+                self.axes.insert(profile.name.clone(), axes);
+            }
+            if let Some(slots) = crate::slice_confirmation::profile_slots(profile) {
+                self.confirmations.insert(profile.name.clone(), slots);
+            }
+            if let Some(slots) = crate::slice_cooldown::profile_cooldowns(profile) {
+                self.cooldowns.insert(profile.name.clone(), slots);
+            }
+            let icons: Vec<Option<IconInfo>> = profile
+                .slices
+                .iter()
+                .map(|slice| slice.as_ref().and_then(|a| a.icon.as_deref()).and_then(|icon| self.icon_resolver.resolve(icon)))
+                .collect();
+            if icons.iter().any(Option::is_some) {
+                self.icons.insert(profile.name.clone(), icons);
+            }
+            let labels = profile.slices.iter().map(|slice| slice.as_ref().and_then(|a| a.label.as_ref()));
+            if labels.clone().flatten().any(LocalizedText::is_localized) {
+                self.localized.insert(profile.name.clone());
+            }
+            let labels = labels.map(|label| label.map(|l| l.current().to_string())).collect();
+            self.labels.insert(profile.name.clone(), labels);
+        }
+        let icons = &self.icons;
+        self.icon_resolver
+            .retain(|path| icons.values().flatten().flatten().any(|icon| Path::new(&icon.path) == path));
+        self.shorten_labels();
+        self.accumulated = 0;
+    }
+
+    /// Rebuild `short_labels` from `labels` and the budget, warning once per
+    /// label that does not fit
+    fn shorten_labels(&mut self) {
+        self.short_labels.clear();
+        let Some(budget) = self.label_budget else {
+            return;
+        };
+        for (name, labels) in &self.labels {
+            let short: Vec<Option<String>> = labels
+                .iter()
+                .map(|label| label.as_deref().and_then(|l| crate::label::ellipsize(l, budget)))
+                .collect();
+            for (slot, (label, short)) in labels.iter().zip(&short).enumerate() {
+                if let (Some(label), Some(short)) = (label, short) {
+                    tracing::warn!(
+                        profile = %name,
+                        slot,
+                        label = %label,
+                        cells = crate::label::display_width(label),
+                        budget,
+                        "Slice label is wider than menu.label_max_cells; the menu shows '{}'",
+                        short
+                    );
+                }
+            }
+            if short.iter().any(Option::is_some) {
+                self.short_labels.insert(name.clone(), short);
+            }
+        }
+    }
+
+    /// Focus moved to a window with this resource class
+    pub fn set_active_window(&mut self, class: &str) {
+        let class = class.to_lowercase();
+        if class != self.active_class {
+            self.active_class = class;
+            self.accumulated = 0;
+        }
+    }
+
+    /// The current virtual desktop changed
+    pub fn set_active_desktop(&mut self, desktop: Option<u32>) {
+        if desktop != self.active_desktop {
+            self.active_desktop = desktop;
+            self.accumulated = 0;
+        }
+    }
+
+    /// Manually selected profile (`None` resumes window-class matching)
+    pub fn set_selected(&mut self, profile: Option<&str>) {
+        self.selected = profile.map(str::to_string);
+        self.accumulated = 0;
+    }
+
+    /// Radial menu shown (`true`) or closed (`false`)
+    pub fn set_menu_open(&mut self, open: bool) {
+        self.menu_opened_at = open.then(Instant::now);
+        self.accumulated = 0;
+    }
+
+    fn menu_open(&self) -> bool {
+        self.menu_opened_at
+            .is_some_and(|at| at.elapsed() < MENU_OPEN_TIMEOUT)
+    }
+
+    /// Bindings of the selected or focused app's profile, if one matches
+    fn matched_binding(&self) -> Option<&ThumbwheelBinding> {
+        let name = match self.selected {
+            Some(ref name) => name.as_str(),
+            None => self.matcher.find(&self.active_class, self.active_desktop)?,
+        };
+        self.named.get(name)
+    }
+
+    /// Bindings of the selected or focused app's profile (default profile
+    /// as fallback)
+    fn active_binding(&self) -> &ThumbwheelBinding {
+        match self.matched_binding() {
+            Some(binding) if !binding.is_empty() => binding,
+            _ => &self.default_binding,
+        }
+    }
+
+    /// Resource class of the focused window, lowercased
+    pub fn active_class(&self) -> &str {
+        &self.active_class
+    }
+
+    /// Name of the selected or focused app's profile, whatever it binds
+    pub fn menu_profile(&self) -> &str {
+        self.matched_binding().map_or("default", |binding| &binding.profile)
+    }
+
+    /// Actions of the profile menu in effect, one per slot; `None` for the
+    /// Settings ring (`default`), which the overlay runs
+    pub fn menu_actions(&self) -> Option<&[Option<Action>]> {
+        self.actions.get(self.menu_profile()).map(Vec::as_slice)
+    }
+
+    /// What the overlay draws for the profile menu in effect, one entry per
+    /// slot; `None` for the Settings ring
+    pub fn menu_slices(&self) -> Option<Vec<Option<OverlaySlice>>> {
+        let name = self.menu_profile();
+        let actions = self.actions.get(name)?;
+        let labels = self.labels.get(name);
+        let short_labels = self.short_labels.get(name);
+        let slices = actions
+            .iter()
+            .enumerate()
+            .map(|(slot, action)| {
+                action.as_ref().map(|a| OverlaySlice {
+                    label: labels.and_then(|l| l.get(slot).cloned().flatten()).unwrap_or_default(),
+                    short_label: short_labels.and_then(|l| l.get(slot).cloned().flatten()),
+                    icon: a.icon.clone(),
+                })
+            })
+            .collect();
+        Some(slices)
+    }
+
+    /// Slice descriptions of the profile in effect, one per slot; `None`
+    /// when it has none
+    pub fn slice_descriptions(&self) -> Option<&[Option<String>]> {
+        self.descriptions.get(self.menu_profile()).map(Vec::as_slice)
+    }
+
+    /// `wheel_axis` slices of the profile in effect, one per slot; `None`
+    /// when it has none
+    pub fn slice_axes(&self) -> Option<&[Option<AxisSlice>]> {
+        self.axes.get(self.menu_profile()).map(Vec::as_slice)
+    }
+
+    /// `require_confirmation` slots of the profile in effect; `None` when it
+    /// has none
+    pub fn confirm_slots(&self) -> Option<&[u8]> {
+        self.confirmations.get(self.menu_profile()).map(Vec::as_slice)
+    }
+
+    /// Slots with a cooldown in the profile in effect; empty when it has none
+    pub fn cooldown_slots(&self) -> &[(u8, Duration)] {
+        self.cooldowns.get(self.menu_profile()).map_or(&[], Vec::as_slice)
+    }
+
+    /// Shortened labels of the profile in effect, one per slot; `None` when
+    /// every label fits
+    pub fn short_labels(&self) -> Option<&[Option<String>]> {
+        self.short_labels.get(self.menu_profile()).map(Vec::as_slice)
+    }
+
+    /// Labels of the profile in effect in the process locale, one per slot;
+    /// `None` when none of them has translations
+    pub fn localized_labels(&self) -> Option<&[Option<String>]> {
+        let name = self.menu_profile();
+        self.localized.contains(name).then(|| self.labels.get(name)).flatten().map(Vec::as_slice)
+    }
+
+    /// File icons of the profile in effect, one per slot; `None` when it
+    /// has none
+    pub fn slice_icons(&self) -> Option<&[Option<IconInfo>]> {
+        self.icons.get(self.menu_profile()).map(Vec::as_slice)
+    }
+
+    /// Slices in the ring of the profile in effect
+    pub fn slice_count(&self) -> u8 {
+        self.slice_counts.get(self.menu_profile()).copied().unwrap_or(SLICE_COUNT)
+    }
+
+    /// Ring size of the profile in effect; `None` follows `menu.size`
+    pub fn menu_size(&self) -> Option<MenuSize> {
+        self.menu_sizes.get(self.menu_profile()).copied()
+    }
+
+    /// Content hash of the profile in effect
+    pub fn profile_hash(&self) -> Option<ContentHash> {
+        self.hashes.get(self.menu_profile()).copied()
+    }
+
+    /// Content hash of each loaded profile, keyed by name
+    pub fn profile_hashes(&self) -> &HashMap<String, ContentHash> {
+        &self.hashes
+    }
+
+    /// Content hash of the profiles the bindings were built from; `None`
+    /// before any were loaded
+    pub fn profiles_hash(&self) -> Option<ContentHash> {
+        self.profiles_hash
+    }
+
+    /// Collisions found when the bound profile set was loaded (for `Status()`)
+    pub fn profile_collisions(&self) -> &[ProfileCollision] {
+        &self.collisions
+    }
+
+    /// Whether a wheel event with this value should be kept from the OS
+    pub fn intercepts(&self, value: i32) -> bool {
+        let Some(direction) = WheelDirection::from_value(value) else {
+            return false;
+        };
+        self.enabled && !self.menu_open() && self.active_binding().action(direction).is_some()
+    }
+
+    /// Accumulate intercepted REL_HWHEEL detents
+    ///
+    /// Returns the direction to fire once enough detents in one direction
+    /// have built up; reversing direction starts over.
+    pub fn feed(&mut self, detents: i32) -> Option<WheelDirection> {
+        if !self.intercepts(detents) {
+            return None;
+        }
+        if self.accumulated.signum() == -detents.signum() {
+            self.accumulated = 0;
+        }
+        self.accumulated += detents;
+        let needed = i32::from(self.detents_per_action);
+        if self.accumulated.abs() < needed {
+            return None;
+        }
+        self.accumulated -= needed * self.accumulated.signum();
+        WheelDirection::from_value(detents)
+    }
+
+    /// Action bound to a direction for the focused app
+    pub fn action(&self, direction: WheelDirection) -> Option<Action> {
+        self.active_binding().action(direction).cloned()
+    }
+
+    /// Mapping state for `Status()`
+    pub fn status(&self) -> serde_json::Value {
+        let binding = self.active_binding();
+        json!({
+            "enabled": self.enabled,
+            "hid_diverted": self.hid_diverted,
+            "detents_per_action": self.detents_per_action,
+            "active_class": self.active_class,
+            "active_desktop": self.active_desktop,
+            "selected": self.selected,
+            "profile": binding.profile,
+            "left": binding.left,
+            "right": binding.right,
+            "menu_open": self.menu_open(),
+        })
+    }
+}
+
+/// Create a shared mapper from config and loaded profiles
+pub fn new_shared_mapper(config: &ThumbwheelConfig, profiles: &ProfileManager) -> SharedThumbwheelMapper {
+    let mut mapper = ThumbwheelMapper::new(config);
+    mapper.set_profiles(profiles);
+    Arc::new(RwLock::new(mapper))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::ActionType;
+    use tempfile::TempDir;
+
+    fn manager() -> ProfileManager {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("profiles.json");
+        let empty = serde_json::json!([null, null, null, null, null, null, null, null]);
+        let described = serde_json::json!([
+            {"type": "shortcut", "value": "Tab", "label": "Edit", "description": "Toggle edit mode"},
+            {"type": "wheel_axis", "value": {
+                "up": {"type": "shortcut", "value": "ctrl+plus"},
+                "down": {"type": "shortcut", "value": "ctrl+minus"}
+            }, "label": "Zoom"},
+            null, null, null, null, null,
+            {"type": "shortcut", "value": "ctrl+w", "label": "Close", "require_confirmation": true}
+        ]);
+        let doc = serde_json::json!({
+            "profiles": [
+                {"name": "default", "slices": empty},
+                {
+                    "name": "blender",
+                    "window_class": "Blender",
+                    "slices": described,
+                    "thumbwheel_right": {"type": "shortcut", "value": "Ctrl+Tab"}
+                },
+                {
+                    "name": "code",
+                    "virtual_desktop": 2,
+                    "slice_count": 12,
+                    "menu_size": "25%",
+                    "slices": empty,
+                    "thumbwheel_left": {"type": "shortcut", "value": "Ctrl+PgUp"}
+                }
+            ]
+        });
+        std::fs::write(&path, doc.to_string()).unwrap();
+        ProfileManager::load_from_path(&path).unwrap()
+    }
+
+    fn shortcut(mapper: &ThumbwheelMapper, direction: Option<WheelDirection>) -> Option<String> {
+        match mapper.action(direction?)?.action_type {
+            ActionType::Shortcut(keys) => Some(keys),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_unmapped_app_passes_through() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("firefox");
+        assert!(!mapper.intercepts(1));
+        assert!(mapper.feed(1).is_none());
+    }
+
+    #[test]
+    fn test_mapped_direction_only() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("blender");
+        assert!(mapper.intercepts(1));
+        assert!(!mapper.intercepts(-1));
+        assert!(!mapper.intercepts(0));
+        let direction = mapper.feed(1);
+        assert_eq!(direction, Some(WheelDirection::Right));
+        assert_eq!(shortcut(&mapper, direction).as_deref(), Some("Ctrl+Tab"));
+        assert_eq!(mapper.status()["profile"], "blender");
+    }
+
+    #[test]
+    fn test_detent_accumulation() {
+        let config = ThumbwheelConfig { detents_per_action: 3, ..Default::default() };
+        let mut mapper = ThumbwheelMapper::new(&config);
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("Blender");
+        assert!(mapper.feed(1).is_none());
+        assert!(mapper.feed(1).is_none());
+        assert!(mapper.feed(1).is_some());
+        assert!(mapper.feed(2).is_none());
+        // Focus change starts the count over
+        mapper.set_active_window("konsole");
+        mapper.set_active_window("blender");
+        assert!(mapper.feed(2).is_none());
+        assert!(mapper.feed(1).is_some());
+    }
+
+    #[test]
+    fn test_menu_and_kill_switch_take_precedence() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("blender");
+
+        mapper.set_menu_open(true);
+        assert!(!mapper.intercepts(1));
+        assert!(mapper.feed(1).is_none());
+        mapper.set_menu_open(false);
+        assert!(mapper.intercepts(1));
+
+        mapper.set_config(&ThumbwheelConfig { profile_mappings: false, ..Default::default() });
+        assert!(!mapper.intercepts(1));
+        assert_eq!(mapper.status()["enabled"], false);
+    }
+
+    #[test]
+    fn test_virtual_desktop_profile_applies_below_the_window_match() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("firefox");
+        assert!(!mapper.intercepts(-1));
+        mapper.set_active_desktop(Some(2));
+        let direction = mapper.feed(-1);
+        assert_eq!(shortcut(&mapper, direction).as_deref(), Some("Ctrl+PgUp"));
+        assert_eq!(mapper.status()["active_desktop"], 2);
+        // Blender's own profile wins on desktop 2
+        mapper.set_active_window("blender");
+        assert!(!mapper.intercepts(-1));
+        assert!(mapper.intercepts(1));
+        mapper.set_active_desktop(None);
+        mapper.set_active_window("firefox");
+        assert!(!mapper.intercepts(-1));
+    }
+
+    #[test]
+    fn test_selected_profile_overrides_window_match() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("firefox");
+        mapper.set_selected(Some("blender"));
+        assert!(mapper.intercepts(1));
+        assert_eq!(mapper.status()["selected"], "blender");
+        mapper.set_selected(None);
+        assert!(!mapper.intercepts(1));
+    }
+
+    #[test]
+    fn test_slice_descriptions_follow_profile() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("firefox");
+        assert!(mapper.slice_descriptions().is_none());
+        mapper.set_active_window("blender");
+        let descriptions = mapper.slice_descriptions().unwrap();
+        assert_eq!(descriptions.len(), 8);
+        assert_eq!(descriptions[0].as_deref(), Some("Toggle edit mode"));
+        assert!(descriptions[1].is_none());
+        let axes = mapper.slice_axes().unwrap();
+        assert!(axes[0].is_none() && axes[1].is_some());
+        assert_eq!(mapper.confirm_slots(), Some(&[7][..]));
+        mapper.set_selected(Some("default"));
+        assert!(mapper.slice_descriptions().is_none());
+        assert!(mapper.slice_axes().is_none());
+        assert!(mapper.confirm_slots().is_none());
+    }
+
+    #[test]
+    fn test_profile_hash_follows_profile() {
+        let manager = manager();
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        assert_eq!(mapper.profiles_hash(), None);
+        mapper.set_profiles(&manager);
+        assert_eq!(mapper.profiles_hash(), Some(manager.content_hash()));
+        mapper.set_active_window("blender");
+        assert_eq!(mapper.profile_hash(), manager.profile_hash("blender"));
+        mapper.set_active_window("firefox");
+        assert_eq!(mapper.profile_hash(), manager.profile_hash("default"));
+    }
+
+    #[test]
+    fn test_collisions_follow_the_bound_profiles() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("profiles.json");
+        let doc = serde_json::json!({
+            "profiles": [
+                {"name": "default", "slices": []},
+                {"name": "editor", "window_class": "kate", "slices": []},
+                {"name": "notes", "window_class": "kate", "slices": []}
+            ]
+        });
+        std::fs::write(&path, doc.to_string()).unwrap();
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&ProfileManager::load_from_path(&path).unwrap());
+        assert_eq!(mapper.profile_collisions().len(), 1);
+        assert_eq!(mapper.profile_collisions()[0].ignored_name, "notes");
+        mapper.set_profiles(&manager());
+        assert!(mapper.profile_collisions().is_empty());
+    }
+
+    #[test]
+    fn test_slice_count_follows_profile() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        assert_eq!(mapper.slice_count(), 8);
+        mapper.set_active_desktop(Some(2));
+        assert_eq!(mapper.slice_count(), 12);
+        mapper.set_active_window("blender");
+        assert_eq!(mapper.slice_count(), 8);
+    }
+
+    #[test]
+    fn test_menu_size_follows_profile() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        assert_eq!(mapper.menu_size(), None);
+        mapper.set_active_desktop(Some(2));
+        assert_eq!(mapper.menu_size(), Some(MenuSize::Percent(25.0)));
+        mapper.set_active_window("blender");
+        assert_eq!(mapper.menu_size(), None);
+    }
+
+    #[test]
+    fn test_long_labels_are_shortened_per_profile() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("blender");
+        // "Edit", "Zoom" and "Close" all fit the default budget
+        assert!(mapper.short_labels().is_none());
+        mapper.set_label_budget(Some(4));
+        let short = mapper.short_labels().unwrap();
+        assert_eq!(short[0], None);
+        assert_eq!(short[7].as_deref(), Some("Clo…"));
+        mapper.set_label_budget(None);
+        assert!(mapper.short_labels().is_none());
+    }
+
+    #[test]
+    fn test_only_app_profiles_have_menu_slices() {
+        let mut mapper = ThumbwheelMapper::new(&ThumbwheelConfig::default());
+        mapper.set_profiles(&manager());
+        mapper.set_active_window("firefox");
+        // The Settings ring runs in the overlay
+        assert!(mapper.menu_actions().is_none());
+        assert!(mapper.menu_slices().is_none());
+        mapper.set_active_window("blender");
+        assert_eq!(mapper.menu_actions().unwrap().len(), 8);
+        let slices = mapper.menu_slices().unwrap();
+        assert_eq!(slices[0].as_ref().unwrap().label, "Edit");
+        assert!(slices[2].is_none());
+        mapper.set_label_budget(Some(4));
+        assert_eq!(mapper.menu_slices().unwrap()[7].as_ref().unwrap().short_label.as_deref(), Some("Clo…"));
+    }
+}
@@ -0,0 +1,1083 @@
+//! Offline validation for theme and profile authors
+//!
+//! Backs `juhradiald validate-theme <path>` and `juhradiald validate-profiles
+//! <path>`. Both reuse the daemon's own loaders and validators and report each
+//! finding with its JSON field path and source line, either human-readable or
+//! as JSON for editor integrations. `--fix` writes back what the daemon would
+//! otherwise correct silently at load time (clamped theme values, padded or
+//! truncated slice lists), leaving the rest of the file as it was.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::actions::{Action, ActionExecutor, ActionType};
+use crate::fs_util;
+use crate::geometry::{is_supported_slice_count, SLICE_COUNT};
+use crate::locale::LocalizedText;
+use crate::migration::{migrate_document, MigrationOutcome, MIN_SCHEMA_VERSION};
+use crate::profiles::{validate_icon_reference, CollisionKind, HardwareProfile, ProfilesConfig, SCHEMA_VERSION};
+use crate::shortcut_lint::ShortcutLintRules;
+use crate::theme::{Theme, ThemeError};
+pub use crate::validation::{IssueCode, Severity, ValidationIssue};
+
+/// One finding, located in the source file where possible
+///
+/// The issue's field is a JSON path such as `profiles[1].slices[3]` (empty
+/// for the whole file).
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    #[serde(flatten)]
+    pub issue: ValidationIssue,
+    /// 1-based line and column of the field's value
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Outcome of validating one file
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+    /// `--fix` rewrote the file
+    pub fixed: bool,
+}
+
+impl Report {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            diagnostics: Vec::new(),
+            fixed: false,
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.issue.is_error())
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics.iter().filter(|d| d.issue.severity == severity).count()
+    }
+
+    /// Compiler-style output: `path:line:col: severity: field: message`
+    pub fn to_human(&self) -> String {
+        let mut out = String::new();
+        let path = self.path.display();
+        for d in &self.diagnostics {
+            match (d.line, d.column) {
+                (Some(line), Some(column)) => out.push_str(&format!("{}:{}:{}: ", path, line, column)),
+                _ => out.push_str(&format!("{}: ", path)),
+            }
+            let issue = &d.issue;
+            if issue.field.is_empty() {
+                out.push_str(&format!("{}: {}\n", issue.severity, issue.message));
+            } else {
+                out.push_str(&format!("{}: {}: {}\n", issue.severity, issue.field, issue.message));
+            }
+        }
+        let (errors, warnings) = (self.count(Severity::Error), self.count(Severity::Warning));
+        out.push_str(&format!(
+            "{}: {} error{}, {} warning{}{}\n",
+            path,
+            errors,
+            if errors == 1 { "" } else { "s" },
+            warnings,
+            if warnings == 1 { "" } else { "s" },
+            if self.fixed { " (fixes written)" } else { "" }
+        ));
+        out
+    }
+
+    /// Machine-readable output for editor integrations
+    pub fn to_json(&self) -> String {
+        let json = serde_json::json!({
+            "path": self.path,
+            "valid": !self.has_errors(),
+            "fixed": self.fixed,
+            "diagnostics": self.diagnostics,
+        });
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    }
+}
+
+/// Diagnostics collector that resolves field paths to source positions
+struct Collector<'a> {
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Collector<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Record `issue` at `path`, which replaces its field
+    fn push(&mut self, path: &[Seg], mut issue: ValidationIssue) {
+        let position = locate(self.source, path).map(|offset| line_column(self.source, offset));
+        issue.field = field_name(path);
+        self.diagnostics.push(Diagnostic {
+            issue,
+            line: position.map(|p| p.0),
+            column: position.map(|p| p.1),
+        });
+    }
+
+    fn error(&mut self, path: &[Seg], code: IssueCode, message: String) {
+        self.push(path, ValidationIssue::error("", code, message));
+    }
+
+    fn warning(&mut self, path: &[Seg], code: IssueCode, message: String) {
+        self.push(path, ValidationIssue::warning("", code, message));
+    }
+
+    /// Error for a serde parse failure, at the position serde reports
+    fn parse_error(&mut self, e: &serde_json::Error) {
+        self.diagnostics.push(Diagnostic {
+            issue: ValidationIssue::error("", IssueCode::ParseError, format!("JSON parse error: {}", e)),
+            line: (e.line() > 0).then_some(e.line()),
+            column: (e.line() > 0).then_some(e.column()),
+        });
+    }
+}
+
+// ============================================================================
+// Theme validation
+// ============================================================================
+
+/// Validate a theme.json; with `fix`, write clamped values back
+pub fn validate_theme(path: &Path, fix: bool) -> Report {
+    let mut report = Report::new(path);
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            report.diagnostics.push(io_error(e));
+            return report;
+        }
+    };
+    let mut collect = Collector::new(&source);
+
+    // The file may spell `glassmorphism` with its `effects` alias
+    let doc: Value = serde_json::from_str(&source).unwrap_or(Value::Null);
+    let theme_path = |field: &str| -> Vec<Seg> {
+        let mut segs: Vec<Seg> = field
+            .split('.')
+            .filter(|k| !k.is_empty())
+            .map(|k| Seg::Key(k.to_string()))
+            .collect();
+        if field.starts_with("glassmorphism.") && doc.get("glassmorphism").is_none() && doc.get("effects").is_some() {
+            segs[0] = Seg::Key("effects".to_string());
+        }
+        segs
+    };
+
+    let mut theme = match Theme::load_from_path(path) {
+        Ok(theme) => theme,
+        Err(ThemeError::ParseError(e)) => {
+            collect.parse_error(&e);
+            report.diagnostics = collect.diagnostics;
+            return report;
+        }
+        Err(ThemeError::Invalid(result)) => {
+            for issue in &result.errors {
+                collect.push(&theme_path(&issue.field), issue.clone());
+            }
+            report.diagnostics = collect.diagnostics;
+            return report;
+        }
+        Err(e) => {
+            report.diagnostics.push(Diagnostic {
+                issue: ValidationIssue::error("", IssueCode::Invalid, e.to_string()),
+                line: None,
+                column: None,
+            });
+            return report;
+        }
+    };
+
+    let result = theme.validate_and_clamp();
+    for issue in result.issues() {
+        collect.push(&theme_path(&issue.field), issue.clone());
+    }
+
+    // A rejected texture is a file to fix, not a value to write back
+    let fixable: Vec<&ValidationIssue> =
+        result.warnings.iter().filter(|issue| issue.code != IssueCode::InvalidTexture).collect();
+    if fix && !fixable.is_empty() {
+        let clamped = serde_json::to_value(&theme).unwrap_or(Value::Null);
+        let mut doc = doc.clone();
+        for issue in fixable {
+            let pointer = format!("/{}", issue.field.replace('.', "/"));
+            if let Some(value) = clamped.pointer(&pointer) {
+                set_at(&mut doc, &theme_path(&issue.field), value.clone());
+            }
+        }
+        match write_json(path, &doc) {
+            Ok(()) => report.fixed = true,
+            Err(e) => collect.error(&[], IssueCode::Io, format!("Failed to write fixes: {}", e)),
+        }
+    }
+
+    report.diagnostics = collect.diagnostics;
+    report
+}
+
+// ============================================================================
+// Profiles validation
+// ============================================================================
+
+/// Validate a profiles.json; with `fix`, pad/truncate slice lists to 8
+///
+/// Risky shortcuts are flagged under `rules` (the `profile_lint` override).
+pub fn validate_profiles(path: &Path, fix: bool, rules: &ShortcutLintRules) -> Report {
+    let mut report = Report::new(path);
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            report.diagnostics.push(io_error(e));
+            return report;
+        }
+    };
+    let mut collect = Collector::new(&source);
+
+    let mut doc: Value = match serde_json::from_str(&source) {
+        Ok(doc) => doc,
+        Err(e) => {
+            collect.parse_error(&e);
+            report.diagnostics = collect.diagnostics;
+            return report;
+        }
+    };
+    if !doc.is_object() {
+        collect.error(&[], IssueCode::WrongType, "top level must be a JSON object".to_string());
+        report.diagnostics = collect.diagnostics;
+        return report;
+    }
+
+    let mut migrated = doc.clone();
+    match migrate_document(&mut migrated) {
+        MigrationOutcome::Current => {}
+        MigrationOutcome::Migrated { from } => collect.warning(
+            &[Seg::key("version")],
+            IssueCode::SchemaVersion,
+            format!(
+                "schema v{} is migrated to v{} on the next load (the original is kept as {}.bak-{})",
+                from,
+                SCHEMA_VERSION,
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                from
+            ),
+        ),
+        MigrationOutcome::TooNew { found } | MigrationOutcome::TooOld { found } => {
+            collect.error(
+                &[Seg::key("version")],
+                IssueCode::SchemaVersion,
+                format!("schema version {} is not supported (this build reads {} to {})", found, MIN_SCHEMA_VERSION, SCHEMA_VERSION),
+            );
+            report.diagnostics = collect.diagnostics;
+            return report;
+        }
+    }
+
+    let mut fixable = false;
+    if let Some(profiles) = doc.get("profiles") {
+        match profiles.as_array() {
+            Some(profiles) => {
+                for (i, profile) in profiles.iter().enumerate() {
+                    let base = vec![Seg::key("profiles"), Seg::Index(i)];
+                    fixable |= check_profile(&mut collect, &base, profile);
+                }
+            }
+            None => collect.error(&[Seg::key("profiles")], IssueCode::WrongType, "must be an array of profiles".to_string()),
+        }
+    }
+    if let Some(hardware) = doc.get("hardware").and_then(Value::as_object) {
+        for (class, entry) in hardware {
+            if let Err(e) = serde_json::from_value::<HardwareProfile>(entry.clone()) {
+                collect.error(&[Seg::key("hardware"), Seg::key(class)], IssueCode::Invalid, e.to_string());
+            }
+        }
+    }
+
+    // Anything the per-field checks missed still fails the real loader
+    let structural_errors = collect.diagnostics.iter().any(|d| d.issue.is_error());
+    if !structural_errors && !fixable {
+        match serde_json::from_str::<ProfilesConfig>(&source) {
+            Ok(config) => {
+                check_profile_collisions(&mut collect, &config);
+                check_shortcut_risks(&mut collect, &config, rules);
+                check_switch_targets(&mut collect, &config);
+                check_inheritance(&mut collect, &config);
+            }
+            Err(e) => collect.parse_error(&e),
+        }
+    }
+
+    if fix && fixable {
+        if let Some(profiles) = doc.get_mut("profiles").and_then(Value::as_array_mut) {
+            for profile in profiles.iter_mut().filter(|p| p.get("extends").is_none_or(|e| !e.is_string())) {
+                let expected = slice_count(profile);
+                if let Some(slices) = profile.get_mut("slices").and_then(Value::as_array_mut) {
+                    slices.resize(expected, Value::Null);
+                }
+            }
+        }
+        match write_json(path, &doc) {
+            Ok(()) => report.fixed = true,
+            Err(e) => collect.error(&[], IssueCode::Io, format!("Failed to write fixes: {}", e)),
+        }
+    }
+
+    report.diagnostics = collect.diagnostics;
+    report
+}
+
+/// Check one profile entry; returns true when its slice count needs fixing
+fn check_profile(collect: &mut Collector, base: &[Seg], profile: &Value) -> bool {
+    let at = |key: &str| -> Vec<Seg> {
+        let mut path = base.to_vec();
+        path.push(Seg::key(key));
+        path
+    };
+
+    if !profile.get("name").is_some_and(Value::is_string) {
+        collect.error(&at("name"), IssueCode::Missing, "profile needs a string name".to_string());
+    }
+    if let Some(icon) = profile.get("icon").and_then(Value::as_str) {
+        if !validate_icon_reference(icon) {
+            collect.warning(&at("icon"), IssueCode::InvalidIcon, format!("icon '{}' may not resolve; the default icon is used", icon));
+        }
+    }
+
+    if let Some(count) = profile.get("slice_count") {
+        if !count.as_u64().and_then(|c| u8::try_from(c).ok()).is_some_and(is_supported_slice_count) {
+            let message = format!("slice_count {} is not one of 4, 6, 8 or 12; 8 is used", count);
+            collect.push(&at("slice_count"), ValidationIssue::error("", IssueCode::SliceCount, message).got(count.clone()).expected("4, 6, 8 or 12"));
+        }
+    }
+    let expected = slice_count(profile);
+    // An extending profile may list only the slots it overrides
+    let extends = match profile.get("extends") {
+        None | Some(Value::Null) => false,
+        Some(Value::String(_)) => true,
+        Some(_) => {
+            collect.error(&at("extends"), IssueCode::WrongType, "extends must be the name of another profile".to_string());
+            false
+        }
+    };
+
+    let mut fixable = false;
+    match profile.get("slices").and_then(Value::as_array) {
+        Some(slices) => {
+            if slices.len() != expected && !(extends && slices.len() < expected) {
+                let message = format!("expected {} slices, found {} (fixable with --fix)", expected, slices.len());
+                let issue = ValidationIssue::error("", IssueCode::SliceCount, message).got(slices.len()).expected(expected.to_string());
+                collect.push(&at("slices"), issue);
+                fixable = true;
+            }
+            for (j, slice) in slices.iter().enumerate().take(expected) {
+                if !slice.is_null() {
+                    let mut path = at("slices");
+                    path.push(Seg::Index(j));
+                    check_action(collect, &path, slice);
+                }
+            }
+        }
+        None if extends && profile.get("slices").is_none() => {}
+        None => collect.error(&at("slices"), IssueCode::WrongType, format!("expected an array of {} slices", expected)),
+    }
+
+    for key in ["center", "thumbwheel_left", "thumbwheel_right"] {
+        if let Some(action) = profile.get(key).filter(|v| !v.is_null()) {
+            if action.get("type").and_then(Value::as_str) == Some("wheel_axis") {
+                collect.error(&at(key), IssueCode::InvalidAction, "wheel_axis only works on a slice held with the gesture button".to_string());
+                continue;
+            }
+            check_action(collect, &at(key), action);
+        }
+    }
+    fixable
+}
+
+/// Slots a profile entry's `slices` should hold (its `slice_count`, or 8)
+fn slice_count(profile: &Value) -> usize {
+    profile
+        .get("slice_count")
+        .and_then(Value::as_u64)
+        .and_then(|c| u8::try_from(c).ok())
+        .filter(|&c| is_supported_slice_count(c))
+        .unwrap_or(SLICE_COUNT)
+        .into()
+}
+
+/// Duplicate names and matches (the loader keeps the first declared)
+fn check_profile_collisions(collect: &mut Collector, config: &ProfilesConfig) {
+    for collision in crate::profiles::find_collisions(&config.profiles) {
+        let field = match collision.kind {
+            CollisionKind::Name => "name",
+            CollisionKind::WindowClass => "window_class",
+            CollisionKind::VirtualDesktop => "virtual_desktop",
+        };
+        let path = [Seg::key("profiles"), Seg::Index(collision.ignored), Seg::key(field)];
+        collect.warning(&path, IssueCode::Collision, collision.to_string());
+    }
+}
+
+/// Advisory warnings for destructive shortcuts in prime slices
+fn check_shortcut_risks(collect: &mut Collector, config: &ProfilesConfig, rules: &ShortcutLintRules) {
+    for (i, profile) in config.profiles.iter().enumerate() {
+        for risk in rules.check_profile(profile) {
+            let path = [Seg::key("profiles"), Seg::Index(i), Seg::key("slices"), Seg::Index(risk.slice as usize)];
+            collect.warning(
+                &path,
+                IssueCode::RiskyShortcut,
+                format!(
+                    "shortcut '{}' {} and sits in an easy-to-hit slot; make sure that is intended",
+                    risk.keys, risk.reason
+                ),
+            );
+        }
+        for risk in rules.check_crowding(profile) {
+            let path = [Seg::key("profiles"), Seg::Index(i), Seg::key("slices"), Seg::Index(risk.slice as usize)];
+            collect.warning(
+                &path,
+                IssueCode::RiskyShortcut,
+                format!(
+                    "shortcut '{}' {} and has actions beside it on a {}-slice ring, where a near miss is easy; consider require_confirmation or an empty neighbour",
+                    risk.keys, risk.reason, profile.slice_count
+                ),
+            );
+        }
+    }
+}
+
+/// `profile_switch` actions naming a profile that is not in the file
+fn check_switch_targets(collect: &mut Collector, config: &ProfilesConfig) {
+    for unknown in crate::profiles::unknown_switch_targets(&config.profiles) {
+        let mut path = vec![Seg::key("profiles"), Seg::Index(unknown.profile), Seg::key(unknown.field)];
+        path.extend(unknown.slice.map(Seg::Index));
+        collect.warning(&path, IssueCode::UnknownProfile, unknown.to_string());
+    }
+}
+
+/// `extends` naming no profile, or looping back on itself
+fn check_inheritance(collect: &mut Collector, config: &ProfilesConfig) {
+    for issue in crate::profiles::resolve_inheritance(&config.profiles).1 {
+        collect.warning(&[Seg::key("profiles"), Seg::Index(issue.profile()), Seg::key("extends")], IssueCode::UnknownProfile, issue.to_string());
+    }
+}
+
+/// Check one action: known type, parseable shortcut, resolvable icon
+fn check_action(collect: &mut Collector, path: &[Seg], value: &Value) {
+    let action: Action = match serde_json::from_value(value.clone()) {
+        Ok(action) => action,
+        Err(e) => {
+            let kind = value.get("type").and_then(Value::as_str).unwrap_or("");
+            let message = if kind.is_empty() || e.to_string().contains("unknown variant") {
+                format!("unknown action type '{}' (expected shortcut, command, dbus, kwin, paste_plain, profile_switch, wheel_axis, media, volume or none)", kind)
+            } else {
+                e.to_string()
+            };
+            collect.error(path, IssueCode::InvalidAction, message);
+            return;
+        }
+    };
+
+    match action.action_type {
+        ActionType::Shortcut(ref keys) => {
+            if keys.trim().is_empty() || keys.split('+').any(|k| k.trim().is_empty()) {
+                collect.error(path, IssueCode::InvalidShortcut, format!("shortcut '{}' has an empty key", keys));
+            } else if ActionExecutor::shortcut_to_evdev_codes(keys).is_none() {
+                collect.warning(
+                    path,
+                    IssueCode::InvalidShortcut,
+                    format!("shortcut '{}' has no uinput mapping; on Wayland it relies on xdotool", keys),
+                );
+            }
+        }
+        ActionType::Command(ref cmd) => {
+            collect.warning(path, IssueCode::ShellCommand, format!("runs shell command '{}'", cmd));
+        }
+        ActionType::WheelAxis { ref up, ref down } => {
+            for (direction, step) in [("up", up), ("down", down)] {
+                let mut step_path = path.to_vec();
+                step_path.extend([Seg::key("value"), Seg::key(direction)]);
+                if step.action_type.is_wheel_axis_step() {
+                    check_action(collect, &step_path, &value["value"][direction]);
+                } else {
+                    collect.error(
+                        &step_path,
+                        IssueCode::InvalidAction,
+                        format!(
+                            "wheel_axis {} must be a shortcut, command or volume step, not '{}'",
+                            direction,
+                            step.action_type.as_str()
+                        ),
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+    // Each translation is checked on its own, at `label.<tag>`
+    let labels: Vec<(Option<&str>, &str)> = match action.label {
+        Some(LocalizedText::Plain(ref label)) => vec![(None, label.as_str())],
+        Some(LocalizedText::Localized(ref map)) => map.iter().map(|(tag, label)| (Some(tag.as_str()), label.as_str())).collect(),
+        None => Vec::new(),
+    };
+    for (tag, label) in labels {
+        let mut label_path = path.to_vec();
+        label_path.push(Seg::key("label"));
+        label_path.extend(tag.map(Seg::key));
+        if let Some(issue) = crate::label::text_issue(label) {
+            collect.warning(&label_path, IssueCode::Label, format!("label '{}' {}", label, issue));
+        } else if let Some(short) = crate::label::ellipsize(label, crate::label::LABEL_BUDGET_CELLS) {
+            collect.warning(
+                &label_path,
+                IssueCode::Label,
+                format!(
+                    "label '{}' is {} cells wide; the menu shows '{}' unless menu.label_max_cells is raised",
+                    label,
+                    crate::label::display_width(label),
+                    short
+                ),
+            );
+        }
+    }
+    if let Some(ref icon) = action.icon {
+        let mut icon_path = path.to_vec();
+        icon_path.push(Seg::key("icon"));
+        if let Some(issue) = crate::label::icon_issue(icon) {
+            collect.warning(&icon_path, IssueCode::InvalidIcon, format!("icon '{}' {}", icon, issue));
+        } else if !validate_icon_reference(icon) {
+            collect.warning(&icon_path, IssueCode::InvalidIcon, format!("icon '{}' may not resolve; the default icon is used", icon));
+        }
+    }
+}
+
+// ============================================================================
+// JSON source positions
+// ============================================================================
+
+/// One step of a JSON path
+#[derive(Debug, Clone, PartialEq)]
+enum Seg {
+    Key(String),
+    Index(usize),
+}
+
+impl Seg {
+    fn key(key: &str) -> Self {
+        Seg::Key(key.to_string())
+    }
+}
+
+fn field_name(path: &[Seg]) -> String {
+    let mut out = String::new();
+    for seg in path {
+        match seg {
+            Seg::Key(key) if out.is_empty() => out.push_str(key),
+            Seg::Key(key) => {
+                out.push('.');
+                out.push_str(key);
+            }
+            Seg::Index(i) => out.push_str(&format!("[{}]", i)),
+        }
+    }
+    out
+}
+
+fn set_at(doc: &mut Value, path: &[Seg], value: Value) {
+    let mut node = doc;
+    for seg in path {
+        node = match seg {
+            Seg::Key(key) => &mut node[key.as_str()],
+            Seg::Index(i) => &mut node[*i],
+        };
+    }
+    *node = value;
+}
+
+/// Byte offset of the value at `path` in JSON `source`
+///
+/// Falls back to the deepest enclosing value that exists, so a missing field
+/// still points at its parent object.
+fn locate(source: &str, path: &[Seg]) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let mut pos = skip_ws(bytes, 0);
+    for seg in path {
+        match (seg, bytes.get(pos)) {
+            (Seg::Key(key), Some(b'{')) => match find_member(bytes, pos, key) {
+                Some(value) => pos = value,
+                None => return Some(pos),
+            },
+            (Seg::Index(index), Some(b'[')) => match find_element(bytes, pos, *index) {
+                Some(value) => pos = value,
+                None => return Some(pos),
+            },
+            _ => return Some(pos),
+        }
+    }
+    Some(pos)
+}
+
+/// Offset of the value of `key` in the object starting at `pos`
+fn find_member(bytes: &[u8], pos: usize, key: &str) -> Option<usize> {
+    let mut pos = skip_ws(bytes, pos + 1);
+    while bytes.get(pos) == Some(&b'"') {
+        let key_end = skip_value(bytes, pos)?;
+        let name = std::str::from_utf8(&bytes[pos + 1..key_end - 1]).ok()?;
+        pos = skip_ws(bytes, key_end);
+        if bytes.get(pos) != Some(&b':') {
+            return None;
+        }
+        let value = skip_ws(bytes, pos + 1);
+        if name == key {
+            return Some(value);
+        }
+        pos = skip_ws(bytes, skip_value(bytes, value)?);
+        if bytes.get(pos) == Some(&b',') {
+            pos = skip_ws(bytes, pos + 1);
+        }
+    }
+    None
+}
+
+/// Offset of element `index` in the array starting at `pos`
+fn find_element(bytes: &[u8], pos: usize, index: usize) -> Option<usize> {
+    let mut pos = skip_ws(bytes, pos + 1);
+    for i in 0.. {
+        if matches!(bytes.get(pos), None | Some(b']')) {
+            return None;
+        }
+        if i == index {
+            return Some(pos);
+        }
+        pos = skip_ws(bytes, skip_value(bytes, pos)?);
+        if bytes.get(pos) == Some(&b',') {
+            pos = skip_ws(bytes, pos + 1);
+        }
+    }
+    None
+}
+
+/// Offset just past the JSON value starting at `pos`
+fn skip_value(bytes: &[u8], pos: usize) -> Option<usize> {
+    match bytes.get(pos)? {
+        b'"' => {
+            let mut i = pos + 1;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b'"' => return Some(i + 1),
+                    _ => i += 1,
+                }
+            }
+            None
+        }
+        b'{' | b'[' => {
+            let mut depth = 0usize;
+            let mut i = pos;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'"' => {
+                        i = skip_value(bytes, i)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(i + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            None
+        }
+        _ => {
+            let len = bytes[pos..]
+                .iter()
+                .position(|b| matches!(b, b',' | b'}' | b']') || b.is_ascii_whitespace())
+                .unwrap_or(bytes.len() - pos);
+            Some(pos + len)
+        }
+    }
+}
+
+fn skip_ws(bytes: &[u8], mut pos: usize) -> usize {
+    while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+        pos += 1;
+    }
+    pos
+}
+
+/// 1-based line and column (in characters) of a byte offset
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+fn io_error(e: std::io::Error) -> Diagnostic {
+    Diagnostic {
+        issue: ValidationIssue::error("", IssueCode::Io, format!("I/O error: {}", e)),
+        line: None,
+        column: None,
+    }
+}
+
+fn write_json(path: &Path, doc: &Value) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(doc).map_err(std::io::Error::other)?;
+    fs_util::write_private(path, json + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn theme_json(blur: i64, accent: &str) -> String {
+        let mut doc = serde_json::to_value(Theme::catppuccin_mocha()).unwrap();
+        doc["glassmorphism"]["blurRadius"] = blur.into();
+        doc["colors"]["accent"] = accent.into();
+        serde_json::to_string_pretty(&doc).unwrap()
+    }
+
+    #[test]
+    fn test_locate_reports_line_and_column() {
+        let source = "{\n  \"a\": {\"b\": [1, {\"c\": true}]},\n  \"d\": \"x\\\"]\"\n}";
+        let path = [Seg::key("a"), Seg::key("b"), Seg::Index(1), Seg::key("c")];
+        assert_eq!(line_column(source, locate(source, &path).unwrap()), (2, 24));
+        assert_eq!(line_column(source, locate(source, &[Seg::key("d")]).unwrap()), (3, 8));
+        // Missing members point at the enclosing object
+        let missing = [Seg::key("a"), Seg::key("zz")];
+        assert_eq!(line_column(source, locate(source, &missing).unwrap()), (2, 8));
+        assert_eq!(field_name(&path), "a.b[1].c");
+    }
+
+    #[test]
+    fn test_validate_theme_reports_and_fixes() {
+        let dir = TempDir::new().unwrap();
+        let path = write(&dir, "theme.json", &theme_json(100, "#cba6f7"));
+
+        let report = validate_theme(&path, false);
+        assert!(!report.has_errors());
+        assert_eq!(report.diagnostics.len(), 1);
+        let d = &report.diagnostics[0];
+        assert_eq!(d.issue.severity, Severity::Warning);
+        assert_eq!(d.issue.field, "glassmorphism.blurRadius");
+        assert!(d.line.is_some());
+        assert!(report.to_human().contains("0 errors, 1 warning"));
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        let entry = &json["diagnostics"][0];
+        assert_eq!(entry["code"], "out_of_range");
+        assert_eq!((entry["got"].as_u64(), entry["expected"].as_str()), (Some(100), Some("8-48")));
+        assert_eq!(entry["line"], d.line.unwrap());
+
+        let report = validate_theme(&path, true);
+        assert!(report.fixed);
+        assert!(validate_theme(&path, false).diagnostics.is_empty());
+        let doc: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(doc["glassmorphism"]["blurRadius"], 48);
+    }
+
+    #[test]
+    fn test_validate_theme_errors() {
+        let dir = TempDir::new().unwrap();
+        let path = write(&dir, "theme.json", &theme_json(24, "purple"));
+        let report = validate_theme(&path, false);
+        assert!(report.has_errors());
+        assert_eq!(report.diagnostics[0].issue.field, "colors.accent");
+
+        let path = write(&dir, "broken.json", "{\n  \"colors\": ,\n}");
+        let report = validate_theme(&path, false);
+        assert!(report.has_errors());
+        assert_eq!(report.diagnostics[0].line, Some(2));
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["valid"], false);
+
+        // Valid JSON, wrong shape: every problem at its own field
+        let path = write(
+            &dir,
+            "shape.json",
+            "{\n  \"colors\": {\"base\": \"#000000\", \"surface\": 1, \"text\": \"#ffffff\",\n    \"accent\": \"#b4befe\"},\n  \"effects\": {\"blurRadius\": \"24\"}\n}",
+        );
+        let report = validate_theme(&path, false);
+        let fields: Vec<&str> = report.diagnostics.iter().map(|d| d.issue.field.as_str()).collect();
+        assert_eq!(fields, ["effects.blurRadius", "animation", "colors.surface", "colors.border"]);
+        assert_eq!(report.diagnostics[0].line, Some(4));
+        assert!(report.diagnostics[2].issue.message.contains("found number"));
+    }
+
+    #[test]
+    fn test_validate_profiles_findings() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{
+  "profiles": [
+    {
+      "name": "default",
+      "slices": [
+        {"type": "shortcut", "value": "ctrl+c"},
+        {"type": "launch", "value": "x"},
+        {"type": "shortcut", "value": "ctrl++"},
+        {"type": "shortcut", "value": "ctrl+F13"},
+        null, null, null
+      ]
+    }
+  ]
+}"#,
+        );
+
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        let find = |field: &str| report.diagnostics.iter().find(|d| d.issue.field == field).unwrap();
+        assert_eq!(find("profiles[0].slices").issue.severity, Severity::Error);
+        let unknown = find("profiles[0].slices[1]");
+        assert!(unknown.issue.message.contains("unknown action type 'launch'"));
+        assert_eq!(unknown.line, Some(7));
+        assert_eq!(find("profiles[0].slices[2]").issue.severity, Severity::Error);
+        assert_eq!(find("profiles[0].slices[3]").issue.severity, Severity::Warning);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_validate_profiles_wheel_axis_steps() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [{"name": "default", "slices": [
+                {"type": "wheel_axis", "value": {
+                    "up": {"type": "shortcut", "value": "ctrl+plus"},
+                    "down": {"type": "paste_plain"}
+                }},
+                {"type": "wheel_axis", "value": {
+                    "up": {"type": "shortcut", "value": "ctrl++"},
+                    "down": {"type": "command", "value": "zoom-out"}
+                }},
+                null, null, null, null, null, null
+            ], "center": {"type": "wheel_axis", "value": {
+                "up": {"type": "shortcut", "value": "a"},
+                "down": {"type": "shortcut", "value": "b"}
+            }}}]}"#,
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        let find = |field: &str| report.diagnostics.iter().find(|d| d.issue.field == field).unwrap();
+        let down = find("profiles[0].slices[0].value.down");
+        assert_eq!(down.issue.severity, Severity::Error);
+        assert!(down.issue.message.contains("not 'paste_plain'"));
+        // Allowed steps get the usual shortcut and command checks
+        assert_eq!(find("profiles[0].slices[1].value.up").issue.severity, Severity::Error);
+        assert_eq!(find("profiles[0].slices[1].value.down").issue.severity, Severity::Warning);
+        assert!(find("profiles[0].center").issue.message.contains("only works on a slice"));
+    }
+
+    #[test]
+    fn test_validate_profiles_schema_version() {
+        let dir = TempDir::new().unwrap();
+        let path = write(&dir, "profiles.json", r#"{"version": 1, "profiles": [{"name": "default", "slices": []}]}"#);
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        let version = report.diagnostics.iter().find(|d| d.issue.field == "version").unwrap();
+        assert_eq!(version.issue.severity, Severity::Warning);
+        assert!(version.issue.message.contains("profiles.json.bak-1"));
+
+        let path = write(&dir, "newer.json", r#"{"version": 99, "profiles": []}"#);
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        assert!(report.has_errors());
+        assert_eq!(report.diagnostics[0].issue.field, "version");
+    }
+
+    #[test]
+    fn test_validate_profiles_fix_pads_slices() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [{"name": "default", "slices": [{"type": "none"}]}]}"#,
+        );
+        let report = validate_profiles(&path, true, &ShortcutLintRules::default());
+        assert!(report.fixed);
+
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        assert!(!report.has_errors(), "{}", report.to_human());
+        assert!(crate::profiles::ProfileManager::load_from_path(&path).is_ok());
+    }
+
+    #[test]
+    fn test_validate_profiles_follows_slice_count() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [
+                {"name": "default", "slice_count": 12, "slices": [
+                    {"type": "shortcut", "value": "ctrl+c"}, null, null, null, null, null, null, null
+                ]},
+                {"name": "odd", "window_class": "odd", "slice_count": 5, "slices": [null, null, null, null, null, null, null, null]}
+            ]}"#,
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        let find = |field: &str| report.diagnostics.iter().find(|d| d.issue.field == field).unwrap();
+        assert!(find("profiles[0].slices").issue.message.contains("expected 12 slices, found 8"));
+        assert!(find("profiles[1].slice_count").issue.message.contains("not one of 4, 6, 8 or 12"));
+        assert_eq!(report.diagnostics.len(), 2, "{}", report.to_human());
+
+        // --fix pads the clock layout with empty slots
+        assert!(validate_profiles(&path, true, &ShortcutLintRules::default()).fixed);
+        let doc: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(doc["profiles"][0]["slices"].as_array().unwrap().len(), 12);
+        assert_eq!(doc["profiles"][0]["slices"][0]["value"], "ctrl+c");
+        assert_eq!(doc["profiles"][1]["slices"].as_array().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_validate_profiles_warns_on_crowded_risky_shortcut() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [{"name": "default", "slice_count": 12, "slices": [
+                null, null, null, null, null, null, null, null, null,
+                {"type": "shortcut", "value": "ctrl+s"}, {"type": "shortcut", "value": "ctrl+w"}, null
+            ]}]}"#,
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        assert!(!report.has_errors(), "{}", report.to_human());
+        assert_eq!(report.diagnostics.len(), 1, "{}", report.to_human());
+        assert_eq!(report.diagnostics[0].issue.field, "profiles[0].slices[10]");
+        assert!(report.diagnostics[0].issue.message.contains("12-slice ring"));
+    }
+
+    #[test]
+    fn test_validate_profiles_warns_on_collisions() {
+        let dir = TempDir::new().unwrap();
+        let slices = "[null, null, null, null, null, null, null, null]";
+        let path = write(
+            &dir,
+            "profiles.json",
+            &format!(
+                r#"{{"profiles": [
+                    {{"name": "default", "slices": {s}}},
+                    {{"name": "firefox", "window_class": "firefox", "slices": {s}}},
+                    {{"name": "firefox", "slices": {s}}},
+                    {{"name": "browser", "window_class": "firefox", "slices": {s}}}
+                ]}}"#,
+                s = slices
+            ),
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        assert!(!report.has_errors(), "{}", report.to_human());
+        let fields: Vec<&str> = report.diagnostics.iter().map(|d| d.issue.field.as_str()).collect();
+        assert_eq!(fields, ["profiles[2].name", "profiles[3].window_class"]);
+        assert!(report.diagnostics[1].issue.message.contains("'firefox' is used"));
+    }
+
+    #[test]
+    fn test_validate_profiles_warns_on_unknown_switch_target() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [{"name": "default", "slices": [
+                {"type": "profile_switch", "value": "next"}, null, null, null,
+                null, null, {"type": "profile_switch", "value": "gimp"}, null
+            ], "thumbwheel_left": {"type": "profile_switch", "value": "default"}}]}"#,
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        assert!(!report.has_errors(), "{}", report.to_human());
+        assert_eq!(report.diagnostics.len(), 1);
+        assert_eq!(report.diagnostics[0].issue.field, "profiles[0].slices[6]");
+        assert!(report.diagnostics[0].issue.message.contains("'gimp'"));
+    }
+
+    #[test]
+    fn test_validate_profiles_checks_extends() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [
+                {"name": "default", "slices": [null, null, null, null, null, null, null, null]},
+                {"name": "code", "window_class": "code", "extends": "default", "slices": [null, {"type": "none"}]},
+                {"name": "term", "window_class": "term", "extends": "default"},
+                {"name": "a", "window_class": "a", "extends": "b", "slices": []},
+                {"name": "b", "window_class": "b", "extends": "a", "slices": []},
+                {"name": "gimp", "window_class": "gimp", "extends": "photoshop", "slices": []}
+            ]}"#,
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        // Short or missing slice lists are fine with extends
+        assert!(!report.has_errors(), "{}", report.to_human());
+        let fields: Vec<&str> = report.diagnostics.iter().map(|d| d.issue.field.as_str()).collect();
+        assert_eq!(fields, ["profiles[3].extends", "profiles[4].extends", "profiles[5].extends"]);
+        assert!(report.diagnostics[0].issue.message.contains("a -> b -> a"));
+        assert!(report.diagnostics[2].issue.message.contains("'photoshop'"));
+    }
+
+    #[test]
+    fn test_validate_profiles_warns_on_risky_prime_shortcut() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [{"name": "browser", "slices": [
+                null, {"type": "shortcut", "value": "Ctrl+W"}, null, null,
+                {"type": "shortcut", "value": "ctrl+q"}, null, null, null
+            ]}]}"#,
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        assert!(!report.has_errors());
+        assert_eq!(report.diagnostics.len(), 1);
+        assert_eq!(report.diagnostics[0].issue.field, "profiles[0].slices[1]");
+        assert_eq!(report.diagnostics[0].issue.severity, Severity::Warning);
+        assert!(report.diagnostics[0].issue.message.contains("closes the tab"));
+        // Advisory only: the profile still loads
+        assert!(crate::profiles::ProfileManager::load_from_path(&path).is_ok());
+    }
+
+    #[test]
+    fn test_validate_profiles_warns_on_wide_or_broken_labels() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "profiles.json",
+            r#"{"profiles": [{"name": "default", "slices": [
+                {"type": "none", "label": "Toggle Always On Top"},
+                {"type": "none", "label": "ðŸ“‹ Copy"},
+                {"type": "none", "label": "Copy", "icon": "📋📄"},
+                {"type": "none", "label": "Kopieren", "icon": "📋"},
+                {"type": "none", "label": {"en": "Paste", "de": "Immer im Vordergrund"}},
+                null, null, null
+            ]}]}"#,
+        );
+        let report = validate_profiles(&path, false, &ShortcutLintRules::default());
+        assert!(!report.has_errors(), "{}", report.to_human());
+        let fields: Vec<&str> = report.diagnostics.iter().map(|d| d.issue.field.as_str()).collect();
+        assert_eq!(fields, [
+            "profiles[0].slices[0].label",
+            "profiles[0].slices[1].label",
+            "profiles[0].slices[2].icon",
+            "profiles[0].slices[4].label.de",
+        ]);
+        assert!(report.diagnostics[0].issue.message.contains("'Toggle Always…'"));
+        assert!(report.diagnostics[1].issue.message.contains("probably meant '📋 Copy'"));
+        assert!(report.diagnostics[2].issue.message.contains("not a single emoji"));
+    }
+}
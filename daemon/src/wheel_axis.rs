@@ -0,0 +1,264 @@
+//! Held-slice wheel control (`wheel_axis` slices)
+//!
+//! A `wheel_axis` slice binds an `up` and a `down` step (a shortcut or a
+//! command), e.g. volume or zoom. While the gesture button is held and the
+//! highlight rests on such a slice, the MX evdev loop swallows vertical wheel
+//! events and each REL_WHEEL detent runs the matching step, at most
+//! `menu.wheel_axis_per_second` times a second (detents beyond that are
+//! dropped, not queued). Releasing the button closes the menu without
+//! running anything.
+//!
+//! Like bound thumb-wheel directions, swallowing needs the grabbed device, so
+//! it only applies to the MX evdev loop and generic mice with macro-bound
+//! buttons. `WheelAxis` is the pure state machine (times are passed in); the
+//! D-Bus service feeds it open/highlight/close, the gesture loop the release,
+//! through the [`WheelAxisHandle`] on `DaemonHandles`.
+
+use std::time::{Duration, Instant};
+
+use crate::actions::{Action, ActionType};
+use crate::handles::StateHandle;
+use crate::profiles::Profile;
+
+/// Default `menu.wheel_axis_per_second`
+pub const MAX_PER_SECOND: u32 = 10;
+
+/// Vertical wheel direction (REL_WHEEL is positive away from the user)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AxisDirection {
+    Up,
+    Down,
+}
+
+impl AxisDirection {
+    /// Direction of a REL_WHEEL / REL_WHEEL_HI_RES value (`None` for 0)
+    pub fn from_value(value: i32) -> Option<Self> {
+        match value.signum() {
+            1 => Some(Self::Up),
+            -1 => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
+/// The two steps of a usable `wheel_axis` slice
+#[derive(Debug, Clone)]
+pub struct AxisSlice {
+    pub up: Action,
+    pub down: Action,
+}
+
+impl AxisSlice {
+    /// Steps of a `wheel_axis` action; `None` for other actions and for
+    /// steps that are not shortcuts, commands or volume steps
+    pub fn of(action: &Action) -> Option<Self> {
+        let ActionType::WheelAxis { ref up, ref down } = action.action_type else {
+            return None;
+        };
+        (up.action_type.is_wheel_axis_step() && down.action_type.is_wheel_axis_step()).then(|| Self {
+            up: (**up).clone(),
+            down: (**down).clone(),
+        })
+    }
+
+    fn step(&self, direction: AxisDirection) -> &Action {
+        match direction {
+            AxisDirection::Up => &self.up,
+            AxisDirection::Down => &self.down,
+        }
+    }
+}
+
+/// A profile's `wheel_axis` slices by slot; `None` when it has none
+pub fn profile_axes(profile: &Profile) -> Option<Vec<Option<AxisSlice>>> {
+    let axes: Vec<Option<AxisSlice>> = profile
+        .slices
+        .iter()
+        .map(|slice| slice.as_ref().and_then(AxisSlice::of))
+        .collect();
+    axes.iter().any(Option::is_some).then_some(axes)
+}
+
+/// Slots holding a `wheel_axis` slice, for the menu payload's hints
+pub fn slots(axes: &[Option<AxisSlice>]) -> Vec<u8> {
+    (0u8..).zip(axes).filter(|(_, a)| a.is_some()).map(|(slot, _)| slot).collect()
+}
+
+/// Wheel routing for the open menu
+#[derive(Debug)]
+pub struct WheelAxis {
+    /// The open menu's `wheel_axis` slices by slot
+    slices: Vec<Option<AxisSlice>>,
+    /// Shortest gap between two steps
+    min_interval: Duration,
+    /// Gesture button still down for the open menu
+    held: bool,
+    /// Highlighted slot, kept only when it is a `wheel_axis` slice
+    slot: Option<u8>,
+    last_step: Option<Instant>,
+}
+
+impl WheelAxis {
+    pub fn new() -> Self {
+        Self {
+            slices: Vec::new(),
+            min_interval: Duration::ZERO,
+            held: false,
+            slot: None,
+            last_step: None,
+        }
+    }
+
+    /// Menu shown by a press, with its `wheel_axis` slices by slot
+    pub fn opened(&mut self, slices: Vec<Option<AxisSlice>>, per_second: u32) {
+        self.slices = slices;
+        self.min_interval = Duration::from_secs(1) / per_second.max(1);
+        self.held = true;
+        self.slot = None;
+        self.last_step = None;
+    }
+
+    /// Highlight moved to `slot` (`None` = hub or outside the ring)
+    pub fn highlighted(&mut self, slot: Option<u8>) {
+        self.slot = slot.filter(|&s| self.slice(s).is_some());
+    }
+
+    /// Gesture button released: the wheel scrolls again
+    pub fn released(&mut self) {
+        self.held = false;
+        self.slot = None;
+    }
+
+    /// Menu closed (selection, cancel, dismissal or shutdown)
+    pub fn closed(&mut self) {
+        self.slices.clear();
+        self.released();
+    }
+
+    /// Slot whose steps the wheel runs, if any
+    pub fn armed(&self) -> Option<u8> {
+        self.slot.filter(|_| self.held)
+    }
+
+    /// A REL_WHEEL detent arrived while armed
+    ///
+    /// Returns the direction to step, or `None` when disarmed or when the
+    /// previous step is too recent.
+    pub fn detent(&mut self, value: i32, now: Instant) -> Option<AxisDirection> {
+        self.armed()?;
+        let direction = AxisDirection::from_value(value)?;
+        if self.last_step.is_some_and(|at| now.duration_since(at) < self.min_interval) {
+            return None;
+        }
+        self.last_step = Some(now);
+        Some(direction)
+    }
+
+    /// The armed slice's step for `direction`
+    pub fn step(&self, direction: AxisDirection) -> Option<Action> {
+        self.slice(self.armed()?).map(|s| s.step(direction).clone())
+    }
+
+    fn slice(&self, slot: u8) -> Option<&AxisSlice> {
+        self.slices.get(usize::from(slot)).and_then(Option::as_ref)
+    }
+}
+
+impl Default for WheelAxis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The open menu's wheel axis, shared by the service and the input loops
+pub type WheelAxisHandle = StateHandle<WheelAxis>;
+
+impl WheelAxisHandle {
+    /// Whether vertical wheel events belong to a held `wheel_axis` slice
+    pub fn is_armed(&self) -> bool {
+        self.update(|w| w.armed().is_some()).unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(action_type: ActionType) -> Action {
+        Action {
+            action_type,
+            label: None,
+            icon: None,
+            description: None,
+            require_confirmation: false,
+            cooldown_ms: 0,
+        }
+    }
+
+    fn volume() -> Action {
+        action(ActionType::WheelAxis {
+            up: Box::new(action(ActionType::Shortcut("XF86AudioRaiseVolume".to_string()))),
+            down: Box::new(action(ActionType::Command("pactl set-sink-volume @DEFAULT_SINK@ -5%".to_string()))),
+        })
+    }
+
+    fn menu() -> Vec<Option<AxisSlice>> {
+        vec![None, AxisSlice::of(&volume()), None]
+    }
+
+    #[test]
+    fn test_detents_step_only_while_held_on_the_slice() {
+        let t0 = Instant::now();
+        let mut wheel = WheelAxis::new();
+        wheel.opened(menu(), MAX_PER_SECOND);
+        assert_eq!(wheel.detent(1, t0), None);
+        wheel.highlighted(Some(0));
+        assert_eq!(wheel.armed(), None);
+
+        wheel.highlighted(Some(1));
+        assert_eq!(wheel.armed(), Some(1));
+        assert_eq!(wheel.detent(1, t0), Some(AxisDirection::Up));
+        let down = wheel.detent(-1, t0 + Duration::from_millis(100));
+        assert_eq!(down, Some(AxisDirection::Down));
+        assert!(matches!(wheel.step(down.unwrap()).unwrap().action_type, ActionType::Command(_)));
+
+        // The release hands the wheel back at once
+        wheel.released();
+        assert_eq!(wheel.detent(1, t0 + Duration::from_secs(1)), None);
+        assert!(wheel.step(AxisDirection::Up).is_none());
+    }
+
+    #[test]
+    fn test_detents_are_rate_limited() {
+        let t0 = Instant::now();
+        let mut wheel = WheelAxis::new();
+        wheel.opened(menu(), 4);
+        wheel.highlighted(Some(1));
+        assert!(wheel.detent(1, t0).is_some());
+        assert!(wheel.detent(1, t0 + Duration::from_millis(200)).is_none());
+        assert!(wheel.detent(1, t0 + Duration::from_millis(250)).is_some());
+        // A rate of 0 is read as 1 per second
+        wheel.opened(menu(), 0);
+        wheel.highlighted(Some(1));
+        assert!(wheel.detent(-1, t0).is_some());
+        assert!(wheel.detent(-1, t0 + Duration::from_millis(999)).is_none());
+    }
+
+    #[test]
+    fn test_only_shortcut_and_command_steps_arm() {
+        let paste = action(ActionType::WheelAxis {
+            up: Box::new(action(ActionType::Shortcut("ctrl+plus".to_string()))),
+            down: Box::new(action(ActionType::PastePlain)),
+        });
+        assert!(AxisSlice::of(&paste).is_none());
+        assert!(AxisSlice::of(&action(ActionType::Shortcut("ctrl+c".to_string()))).is_none());
+
+        let mut profile = Profile::default();
+        assert!(profile_axes(&profile).is_none());
+        profile.slices[3] = Some(volume());
+        let axes = profile_axes(&profile).unwrap();
+        assert_eq!(axes.len(), 8);
+        assert!(axes[3].is_some());
+        assert_eq!(slots(&axes), [3]);
+    }
+}
@@ -0,0 +1,469 @@
+//! Active-window backends (`window-tracking` feature)
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::sync::mpsc::UnboundedSender;
+use wayland_client::backend::ObjectId;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::{Connection as WaylandConnection, Dispatch, Proxy, QueueHandle};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1::{
+    self, State as ToplevelHandleState, ZwlrForeignToplevelHandleV1,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_manager_v1::{
+    self, ZwlrForeignToplevelManagerV1,
+};
+use x11rb::connection::Connection as _;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, EventMask, GetPropertyReply, Window,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+use super::{WindowBackend, WindowInfo};
+
+pub(super) fn probe_backend(de: &str) -> Option<Box<dyn WindowBackend>> {
+    match de {
+        "kde" => return Some(Box::new(KWinBackend)),
+        "hyprland" => {
+            if let Some(path) = hyprland_socket2_path() {
+                return Some(Box::new(HyprlandBackend { path }));
+            }
+            tracing::warn!("Hyprland socket signature not found; trying other window sources");
+        }
+        _ => {}
+    }
+
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        match WlrToplevelBackend::probe() {
+            Some(backend) => return Some(Box::new(backend)),
+            None => tracing::debug!("Compositor does not offer wlr-foreign-toplevel-management"),
+        }
+    }
+
+    if std::env::var_os("DISPLAY").is_some() {
+        match X11Backend::probe() {
+            Some(backend) => return Some(Box::new(backend)),
+            None => tracing::debug!("X11 server unavailable or lacks _NET_ACTIVE_WINDOW"),
+        }
+    }
+
+    None
+}
+
+/// Push a window's class into `tx`. Returns false once the receiver is gone.
+fn report(tx: &UnboundedSender<String>, info: WindowInfo) -> bool {
+    tracing::debug!(
+        class = %info.resource_class,
+        caption = info.caption.as_deref().unwrap_or(""),
+        "Active window changed"
+    );
+    tx.send(info.resource_class).is_ok()
+}
+
+// ============================================================================
+// KDE (KWin script)
+// ============================================================================
+
+struct KWinBackend;
+
+impl WindowBackend for KWinBackend {
+    fn name(&self) -> &'static str {
+        "kwin"
+    }
+
+    /// The KWin watcher installs the reporting script (`attach_kwin`), at
+    /// startup and again after each KWin restart
+    fn run(self: Box<Self>, _tx: UnboundedSender<String>) {}
+}
+
+// ============================================================================
+// Hyprland (.socket2 event stream)
+// ============================================================================
+
+/// Path to the Hyprland `.socket2` event socket for this session.
+fn hyprland_socket2_path() -> Option<PathBuf> {
+    let sig = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+    let runtime = crate::paths::paths().session_runtime_dir()?;
+    Some(runtime.join("hypr").join(sig).join(".socket2.sock"))
+}
+
+/// Parse an `activewindow>>CLASS,TITLE` event line.
+fn parse_hyprland_activewindow(line: &str) -> Option<WindowInfo> {
+    let rest = line.strip_prefix("activewindow>>")?;
+    let (class, title) = rest.split_once(',').unwrap_or((rest, ""));
+    WindowInfo::new(class, Some(title.to_string()))
+}
+
+struct HyprlandBackend {
+    path: PathBuf,
+}
+
+impl WindowBackend for HyprlandBackend {
+    fn name(&self) -> &'static str {
+        "hyprland"
+    }
+
+    /// Blocking event loop; reconnects with backoff until `tx` closes.
+    fn run(self: Box<Self>, tx: UnboundedSender<String>) {
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+            match UnixStream::connect(&self.path) {
+                Ok(stream) => {
+                    tracing::info!("Connected to Hyprland event socket (per-app hardware profiles)");
+                    let reader = BufReader::new(stream);
+                    for line in reader.lines() {
+                        let line = match line {
+                            Ok(l) => l,
+                            Err(_) => break,
+                        };
+                        if let Some(info) = parse_hyprland_activewindow(&line) {
+                            if !report(&tx, info) {
+                                return;
+                            }
+                        }
+                    }
+                }
+                Err(e) => tracing::debug!(error = %e, "Hyprland socket connect failed; retrying"),
+            }
+            std::thread::sleep(Duration::from_secs(2));
+        }
+    }
+}
+
+// ============================================================================
+// X11 (_NET_ACTIVE_WINDOW + WM_CLASS)
+// ============================================================================
+
+struct X11Backend {
+    conn: RustConnection,
+    root: Window,
+    net_active_window: Atom,
+    net_wm_name: Atom,
+    utf8_string: Atom,
+}
+
+impl X11Backend {
+    /// Connect to $DISPLAY and check the window manager publishes
+    /// `_NET_ACTIVE_WINDOW` (EWMH).
+    fn probe() -> Option<Self> {
+        let (conn, screen) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen)?.root;
+        let intern = |name: &[u8]| -> Option<Atom> {
+            Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom)
+        };
+        let backend = Self {
+            net_active_window: intern(b"_NET_ACTIVE_WINDOW")?,
+            net_wm_name: intern(b"_NET_WM_NAME")?,
+            utf8_string: intern(b"UTF8_STRING")?,
+            conn,
+            root,
+        };
+        // Set by EWMH window managers even while nothing has focus (as 0)
+        let supported = backend.active_window_property()?.format == 32;
+        supported.then_some(backend)
+    }
+
+    fn active_window_property(&self) -> Option<GetPropertyReply> {
+        self.conn
+            .get_property(false, self.root, self.net_active_window, AtomEnum::WINDOW, 0, 1)
+            .ok()?
+            .reply()
+            .ok()
+    }
+
+    fn active_window(&self) -> Option<Window> {
+        let reply = self.active_window_property()?;
+        let window = reply.value32()?.next();
+        window.filter(|w| *w != x11rb::NONE)
+    }
+
+    fn window_info(&self, window: Window) -> Option<WindowInfo> {
+        let class = self
+            .conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+            .ok()?
+            .reply()
+            .ok()?;
+        let caption = self
+            .conn
+            .get_property(false, window, self.net_wm_name, self.utf8_string, 0, 256)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map(|reply| String::from_utf8_lossy(&reply.value).into_owned());
+        parse_wm_class(&class.value).and_then(|c| WindowInfo::new(&c, caption))
+    }
+}
+
+impl WindowBackend for X11Backend {
+    fn name(&self) -> &'static str {
+        "x11"
+    }
+
+    /// Blocking event loop: waits for `_NET_ACTIVE_WINDOW` changes on the root
+    /// window and pushes the new window's class when it changes.
+    fn run(self: Box<Self>, tx: UnboundedSender<String>) {
+        let subscribed = self
+            .conn
+            .change_window_attributes(
+                self.root,
+                &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+            )
+            .ok()
+            .and_then(|cookie| cookie.check().ok());
+        if subscribed.is_none() {
+            tracing::warn!("Failed to watch X11 root window; window tracking disabled");
+            return;
+        }
+        tracing::info!("Watching X11 _NET_ACTIVE_WINDOW (per-app hardware profiles)");
+
+        let mut last = String::new();
+        let mut check = true;
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+            if check {
+                if let Some(info) = self.active_window().and_then(|w| self.window_info(w)) {
+                    if info.resource_class != last {
+                        last = info.resource_class.clone();
+                        if !report(&tx, info) {
+                            return;
+                        }
+                    }
+                }
+            }
+            match self.conn.wait_for_event() {
+                Ok(Event::PropertyNotify(e)) => check = e.atom == self.net_active_window,
+                Ok(_) => check = false,
+                Err(e) => {
+                    tracing::warn!(error = %e, "X11 connection lost; window tracking stopped");
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Extract the class (second NUL-separated field) from a WM_CLASS property.
+fn parse_wm_class(value: &[u8]) -> Option<String> {
+    // "instance\0Class\0"
+    let fields: Vec<&[u8]> = value.split(|b| *b == 0).filter(|f| !f.is_empty()).collect();
+    let class = fields.get(1).or_else(|| fields.first())?;
+    let class = String::from_utf8_lossy(class).trim().to_lowercase();
+    if class.is_empty() {
+        None
+    } else {
+        Some(class)
+    }
+}
+
+// ============================================================================
+// wlroots compositors (wlr-foreign-toplevel-management)
+// ============================================================================
+
+struct WlrToplevelBackend {
+    conn: WaylandConnection,
+}
+
+impl WlrToplevelBackend {
+    /// Connect to $WAYLAND_DISPLAY and check the compositor advertises
+    /// `zwlr_foreign_toplevel_manager_v1`.
+    fn probe() -> Option<Self> {
+        let conn = WaylandConnection::connect_to_env().ok()?;
+        let (globals, _queue) = registry_queue_init::<ToplevelState>(&conn).ok()?;
+        let offered = globals.contents().with_list(|list| {
+            list.iter()
+                .any(|g| g.interface == ZwlrForeignToplevelManagerV1::interface().name)
+        });
+        offered.then_some(Self { conn })
+    }
+}
+
+impl WindowBackend for WlrToplevelBackend {
+    fn name(&self) -> &'static str {
+        "wlr-foreign-toplevel"
+    }
+
+    /// Blocking dispatch loop: reports a toplevel when its `done` event marks
+    /// it activated.
+    fn run(self: Box<Self>, tx: UnboundedSender<String>) {
+        let (globals, mut queue) = match registry_queue_init::<ToplevelState>(&self.conn) {
+            Ok(v) => v,
+            Err(e) => {
+                tracing::warn!(error = %e, "Wayland registry init failed; window tracking disabled");
+                return;
+            }
+        };
+        let qh = queue.handle();
+        if let Err(e) = globals.bind::<ZwlrForeignToplevelManagerV1, _, _>(&qh, 1..=3, ()) {
+            tracing::warn!(error = %e, "Failed to bind foreign-toplevel manager; window tracking disabled");
+            return;
+        }
+        tracing::info!("Watching wlr foreign toplevels (per-app hardware profiles)");
+
+        let mut state = ToplevelState {
+            tx,
+            toplevels: HashMap::new(),
+            last: String::new(),
+            closed: false,
+        };
+        while !state.closed {
+            if let Err(e) = queue.blocking_dispatch(&mut state) {
+                tracing::warn!(error = %e, "Wayland connection lost; window tracking stopped");
+                return;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct PendingToplevel {
+    app_id: String,
+    title: Option<String>,
+    activated: bool,
+}
+
+struct ToplevelState {
+    tx: UnboundedSender<String>,
+    toplevels: HashMap<ObjectId, PendingToplevel>,
+    last: String,
+    /// Set when the receiver or the manager goes away
+    closed: bool,
+}
+
+/// Whether a foreign-toplevel `state` array (native-endian u32s) contains
+/// `activated`.
+fn toplevel_state_activated(state: &[u8]) -> bool {
+    state
+        .chunks_exact(4)
+        .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+        .any(|s| s == ToplevelHandleState::Activated as u32)
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for ToplevelState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &WaylandConnection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &WaylandConnection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                state.toplevels.insert(toplevel.id(), PendingToplevel::default());
+            }
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => state.closed = true,
+            _ => {}
+        }
+    }
+
+    wayland_client::event_created_child!(ToplevelState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for ToplevelState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &WaylandConnection,
+        _: &QueueHandle<Self>,
+    ) {
+        let id = handle.id();
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                state.toplevels.entry(id).or_default().app_id = app_id;
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => {
+                state.toplevels.entry(id).or_default().title = Some(title);
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: flags } => {
+                state.toplevels.entry(id).or_default().activated = toplevel_state_activated(&flags);
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                let Some(toplevel) = state.toplevels.get(&id) else {
+                    return;
+                };
+                if !toplevel.activated {
+                    return;
+                }
+                if let Some(info) = WindowInfo::new(&toplevel.app_id, toplevel.title.clone()) {
+                    if info.resource_class != state.last {
+                        state.last = info.resource_class.clone();
+                        if !report(&state.tx, info) {
+                            state.closed = true;
+                        }
+                    }
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels.remove(&id);
+                handle.destroy();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_wm_class_extracts_class_field() {
+        assert_eq!(parse_wm_class(b"navigator\0Firefox\0"), Some("firefox".to_string()));
+    }
+
+    #[test]
+    fn parse_wm_class_single_field() {
+        assert_eq!(parse_wm_class(b"konsole\0"), Some("konsole".to_string()));
+    }
+
+    #[test]
+    fn parse_wm_class_empty_is_none() {
+        assert_eq!(parse_wm_class(b""), None);
+        assert_eq!(parse_wm_class(b"\0\0"), None);
+    }
+
+    #[test]
+    fn hyprland_activewindow_line_parses() {
+        let info = parse_hyprland_activewindow("activewindow>>firefox,Mozilla Firefox").unwrap();
+        assert_eq!(info.resource_class, "firefox");
+        assert_eq!(info.caption.as_deref(), Some("Mozilla Firefox"));
+        assert!(parse_hyprland_activewindow("activewindow>>,").is_none());
+        assert!(parse_hyprland_activewindow("workspace>>2").is_none());
+    }
+
+    #[test]
+    fn toplevel_state_array_detects_activated() {
+        let state: Vec<u8> = [0u32, 2].iter().flat_map(|s| s.to_ne_bytes()).collect();
+        assert!(toplevel_state_activated(&state));
+        let state: Vec<u8> = [0u32, 1].iter().flat_map(|s| s.to_ne_bytes()).collect();
+        assert!(!toplevel_state_activated(&state));
+        assert!(!toplevel_state_activated(&[2, 0]));
+    }
+}
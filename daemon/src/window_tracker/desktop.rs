@@ -0,0 +1,260 @@
+//! Virtual desktop tracking for per-desktop profiles
+//!
+//! Profiles may name a `virtual_desktop`. On KDE the current desktop comes
+//! from KWin: `currentDesktop` on `org.kde.KWin` (numbered from 1) is read at
+//! startup and again on every `currentChanged` signal of KWin's virtual
+//! desktop manager. A restarted KWin gets fresh proxies and a fresh
+//! subscription, and the desktop is read again. Elsewhere no desktop is
+//! reported, so only window-class matching applies.
+//!
+//! The proxies are created once per KWin instance and reused for every read.
+//! A read that KWin does not answer within [`KWIN_CALL_TIMEOUT`] is dropped
+//! and KWin is left alone for [`KWIN_RETRY_AFTER`]: signals in that window
+//! keep the last desktop instead of queueing more calls on a wedged
+//! compositor. Reads slower than [`KWIN_SLOW_CALL`] are logged. The menu
+//! never waits on these reads; it uses the last desktop pushed.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tokio_stream::StreamExt;
+
+use crate::compositor::KWIN_BUS_NAME;
+
+const KWIN_PATH: &str = "/KWin";
+const KWIN_INTERFACE: &str = "org.kde.KWin";
+const DESKTOP_MANAGER_PATH: &str = "/VirtualDesktopManager";
+const DESKTOP_MANAGER_INTERFACE: &str = "org.kde.KWin.VirtualDesktopManager";
+
+/// How long KWin may take to answer `currentDesktop`
+pub const KWIN_CALL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long KWin is left alone after a read timed out
+pub const KWIN_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Reads slower than this are logged
+pub const KWIN_SLOW_CALL: Duration = Duration::from_millis(20);
+
+/// The current virtual desktop, with change tracking
+#[derive(Debug, Default)]
+pub struct DesktopTracker {
+    current: Option<u32>,
+    /// Set when a read timed out; no reads until then
+    retry_at: Option<Instant>,
+}
+
+impl DesktopTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current desktop, numbered from 1; `None` until one is reported
+    pub fn current(&self) -> Option<u32> {
+        self.current
+    }
+
+    /// Whether KWin is asked on the next read, false while a timed-out read
+    /// is being waited out
+    pub fn kwin_available(&self) -> bool {
+        self.retry_at.is_none_or(|at| Instant::now() >= at)
+    }
+
+    /// Record a reading; returns whether the desktop changed
+    pub fn observe(&mut self, desktop: Option<u32>) -> bool {
+        if desktop.is_none() || desktop == self.current {
+            return false;
+        }
+        self.current = desktop;
+        true
+    }
+
+    /// Follow KWin's current desktop until `tx` closes, pushing each change
+    ///
+    /// Starts over with new proxies whenever `restarts` changes (a KWin
+    /// restart, see `compositor::KWinSession`). Without KWin the first read
+    /// fails quietly and no signal arrives until one starts.
+    pub async fn watch_kwin(mut self, connection: zbus::Connection, tx: UnboundedSender<u32>, mut restarts: watch::Receiver<u64>) {
+        while self.attach(&connection, &tx, &mut restarts).await {
+            tracing::debug!("Desktop tracker: KWin restarted, re-subscribing");
+        }
+    }
+
+    /// Follow one KWin instance; `true` when a restart calls for a new attach,
+    /// `false` once `tx` closes or no restart can come
+    async fn attach(&mut self, connection: &zbus::Connection, tx: &UnboundedSender<u32>, restarts: &mut watch::Receiver<u64>) -> bool {
+        // A new KWin gets asked at once
+        self.retry_at = None;
+        let proxies = tokio::try_join!(
+            zbus::Proxy::new(connection, KWIN_BUS_NAME, KWIN_PATH, KWIN_INTERFACE),
+            zbus::Proxy::new(connection, KWIN_BUS_NAME, DESKTOP_MANAGER_PATH, DESKTOP_MANAGER_INTERFACE),
+        );
+        let (kwin, manager) = match proxies {
+            Ok(proxies) => proxies,
+            Err(e) => {
+                tracing::warn!(error = %e, "Desktop tracker: could not create KWin proxies; per-desktop profiles inactive until KWin restarts");
+                return restarts.changed().await.is_ok();
+            }
+        };
+
+        // Subscribe before the first read so a switch in between is seen
+        let stream = manager.receive_signal("currentChanged").await;
+        if !self.refresh(&kwin, tx).await {
+            return false;
+        }
+        let mut stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!(error = %e, "Desktop tracker: could not watch currentChanged; per-desktop profiles use the last desktop until KWin restarts");
+                return restarts.changed().await.is_ok();
+            }
+        };
+        let mut restarts_open = true;
+        loop {
+            tokio::select! {
+                signal = stream.next() => match signal {
+                    Some(_) if self.refresh(&kwin, tx).await => {}
+                    Some(_) => return false,
+                    None => return restarts.changed().await.is_ok(),
+                },
+                changed = restarts.changed(), if restarts_open => match changed {
+                    Ok(()) => return true,
+                    // No watcher; keep the current subscription
+                    Err(_) => restarts_open = false,
+                },
+            }
+        }
+    }
+
+    /// Read the current desktop and push it if it changed; `false` once `tx`
+    /// is closed
+    async fn refresh(&mut self, kwin: &zbus::Proxy<'_>, tx: &UnboundedSender<u32>) -> bool {
+        let raw = self.read(kwin.call::<_, _, i32>("currentDesktop", &())).await;
+        if self.observe(raw.and_then(desktop_number)) {
+            if let Some(desktop) = self.current {
+                return tx.send(desktop).is_ok();
+            }
+        }
+        !tx.is_closed()
+    }
+
+    /// Run one `currentDesktop` call within [`KWIN_CALL_TIMEOUT`]
+    ///
+    /// A timeout drops the call and skips reads for [`KWIN_RETRY_AFTER`];
+    /// `None` for a skipped, failed or timed-out read.
+    async fn read(&mut self, call: impl Future<Output = zbus::Result<i32>>) -> Option<i32> {
+        if !self.kwin_available() {
+            tracing::debug!("KWin currentDesktop skipped; KWin timed out recently");
+            return None;
+        }
+        let started = Instant::now();
+        match tokio::time::timeout(KWIN_CALL_TIMEOUT, call).await {
+            Ok(Ok(raw)) => {
+                self.retry_at = None;
+                let elapsed = started.elapsed();
+                if elapsed > KWIN_SLOW_CALL {
+                    tracing::warn!(elapsed_ms = elapsed.as_millis() as u64, "KWin currentDesktop answered slowly");
+                }
+                Some(raw)
+            }
+            Ok(Err(e)) => {
+                tracing::debug!(error = %e, "KWin currentDesktop unavailable");
+                None
+            }
+            Err(_) => {
+                self.retry_at = Some(Instant::now() + KWIN_RETRY_AFTER);
+                tracing::warn!(
+                    timeout_ms = KWIN_CALL_TIMEOUT.as_millis() as u64,
+                    retry_after_s = KWIN_RETRY_AFTER.as_secs(),
+                    "KWin did not answer currentDesktop in time; keeping the last desktop"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Desktop number from KWin's `currentDesktop` (`None` below 1)
+fn desktop_number(raw: i32) -> Option<u32> {
+    u32::try_from(raw).ok().filter(|&n| n > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe_reports_changes_only() {
+        let mut tracker = DesktopTracker::new();
+        assert_eq!(tracker.current(), None);
+        assert!(tracker.observe(desktop_number(2)));
+        assert!(!tracker.observe(desktop_number(2)));
+        assert!(tracker.observe(desktop_number(1)));
+        // A bad reading keeps the last desktop
+        assert!(!tracker.observe(desktop_number(0)));
+        assert!(!tracker.observe(desktop_number(-1)));
+        assert_eq!(tracker.current(), Some(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timed_out_read_skips_kwin_until_retry() {
+        let mut tracker = DesktopTracker::new();
+        assert_eq!(tracker.read(async { Ok(2) }).await, Some(2));
+
+        let started = Instant::now();
+        assert_eq!(tracker.read(std::future::pending()).await, None);
+        assert_eq!(started.elapsed(), KWIN_CALL_TIMEOUT);
+        assert!(!tracker.kwin_available());
+        // Not asked while waiting out the retry, even if KWin would answer
+        assert_eq!(tracker.read(async { Ok(3) }).await, None);
+
+        tokio::time::advance(KWIN_RETRY_AFTER).await;
+        assert!(tracker.kwin_available());
+        assert_eq!(tracker.read(async { Ok(3) }).await, Some(3));
+    }
+
+    /// KWin stand-in whose `currentDesktop` never answers
+    struct HungKWin;
+
+    #[zbus::interface(name = "org.kde.KWin")]
+    impl HungKWin {
+        #[zbus(name = "currentDesktop")]
+        async fn current_desktop(&self) -> i32 {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hung_kwin_does_not_block_refresh() {
+        let (server, client) = std::os::unix::net::UnixStream::pair().unwrap();
+        let guid = zbus::Guid::generate();
+        let (server, client) = tokio::try_join!(
+            zbus::connection::Builder::unix_stream(server)
+                .server(guid)
+                .unwrap()
+                .p2p()
+                .serve_at(KWIN_PATH, HungKWin)
+                .unwrap()
+                .build(),
+            zbus::connection::Builder::unix_stream(client)
+                .p2p()
+                .build(),
+        )
+        .unwrap();
+        let kwin = zbus::Proxy::new(&client, KWIN_BUS_NAME, KWIN_PATH, KWIN_INTERFACE).await.unwrap();
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut tracker = DesktopTracker::new();
+        tracker.observe(Some(2));
+        let started = std::time::Instant::now();
+        assert!(tracker.refresh(&kwin, &tx).await);
+        assert!(started.elapsed() < KWIN_CALL_TIMEOUT * 5);
+        assert!(!tracker.kwin_available());
+        assert_eq!(tracker.current(), Some(2), "last desktop is kept");
+        drop(server);
+    }
+}
@@ -0,0 +1,209 @@
+//! Active-window tracking for per-application hardware profiles
+//!
+//! Reports the focused window's resource class so the daemon can apply a
+//! per-app [`HardwareProfile`](crate::profiles::HardwareProfile). Each desktop
+//! environment has its own proven source:
+//!
+//! - **KDE**: a persistent KWin script connects to the activation signal and
+//!   calls the daemon's `ReportActiveWindow` D-Bus method. The resident helper
+//!   (`kwin_script`) does this when it is loaded; otherwise [`attach_kwin`]
+//!   loads [`KWIN_ACTIVE_WINDOW_SCRIPT`] on its own. The KWin watcher
+//!   (`compositor`) calls it at startup and after every KWin restart, since a
+//!   restarted KWin has no scripts. Handles Plasma 6
+//!   (`windowActivated`/`activeWindow`) and Plasma 5
+//!   (`clientActivated`/`activeClient`).
+//! - **Hyprland**: reads the `activewindow` event from the `.socket2` event
+//!   stream.
+//! - **wlroots compositors** (Sway, river, labwc, ...): the
+//!   wlr-foreign-toplevel-management protocol, reporting the `activated`
+//!   toplevel.
+//! - **X11**: watches `_NET_ACTIVE_WINDOW` on the root window and reads
+//!   `WM_CLASS` via x11rb.
+//!
+//! Each source is a [`WindowBackend`], probed once at startup. Non-KDE sources
+//! push classes straight into the channel; KDE pushes via the D-Bus method
+//! (which forwards into the same channel).
+//!
+//! The backends live in `backends` and need the `window-tracking` feature
+//! (which brings in x11rb and the Wayland client crates). Without it no
+//! backend is probed and the tracker reports itself unavailable.
+//!
+//! [`DesktopTracker`] follows the current virtual desktop (KWin only) for
+//! profiles that match on `virtual_desktop`.
+//!
+//! [`settle`] holds a focused class back until focus has stayed on it for
+//! `menu.focus_settle_ms`, so an alt-tab sequence does not switch profiles
+//! for every window it passes.
+//!
+//! SPDX-License-Identifier: GPL-3.0
+
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::actions::detect_desktop;
+
+#[cfg(feature = "window-tracking")]
+mod backends;
+mod desktop;
+pub mod settle;
+
+pub use desktop::DesktopTracker;
+
+#[cfg(feature = "window-tracking")]
+use backends::probe_backend;
+
+/// Persistent KWin script that reports the active window's resource class on
+/// every activation change. Stays resident after `run()` because it connects to
+/// a workspace signal (unlike the one-shot cursor script).
+pub const KWIN_ACTIVE_WINDOW_SCRIPT: &str = r#"
+function reportActive(w) {
+    if (w && w.resourceClass) {
+        callDBus("org.kde.juhradialmx", "/org/kde/juhradialmx/Daemon",
+                 "org.kde.juhradialmx.Daemon", "ReportActiveWindow",
+                 String(w.resourceClass));
+    }
+}
+if (typeof workspace.windowActivated !== "undefined") {
+    // Plasma 6
+    workspace.windowActivated.connect(reportActive);
+    reportActive(workspace.activeWindow);
+} else if (typeof workspace.clientActivated !== "undefined") {
+    // Plasma 5
+    workspace.clientActivated.connect(reportActive);
+    reportActive(workspace.activeClient);
+}
+"#;
+
+/// The focused window as reported by a backend.
+///
+/// Every backend fills this the same way (lowercased resource class, caption
+/// when the source has one) so profile matching doesn't depend on the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowInfo {
+    pub resource_class: String,
+    pub caption: Option<String>,
+}
+
+impl WindowInfo {
+    /// Build from a raw class; `None` when the class is empty.
+    pub fn new(resource_class: &str, caption: Option<String>) -> Option<Self> {
+        let resource_class = resource_class.trim().to_lowercase();
+        if resource_class.is_empty() {
+            return None;
+        }
+        Some(Self {
+            resource_class,
+            caption: caption.filter(|c| !c.is_empty()),
+        })
+    }
+}
+
+/// An active-window source. `run` blocks (on the blocking pool) until `tx`
+/// closes or the source goes away.
+pub trait WindowBackend: Send {
+    /// Short name shown in logs and `Status()` ("kwin", "x11", ...).
+    fn name(&self) -> &'static str;
+
+    /// Push each newly focused window's resource class into `tx`.
+    fn run(self: Box<Self>, tx: UnboundedSender<String>);
+}
+
+/// Tracks the active window via the backend probed for this session.
+pub struct WindowTracker {
+    de: &'static str,
+    backend: Option<Box<dyn WindowBackend>>,
+}
+
+impl WindowTracker {
+    /// Create a tracker, probing for a working backend.
+    ///
+    /// KDE and Hyprland use their native sources. Other Wayland sessions use
+    /// wlr-foreign-toplevel-management when the compositor offers it (Sway,
+    /// river, labwc, ...), before X11, which under XWayland only sees X clients.
+    pub fn new() -> Self {
+        let de = detect_desktop();
+        Self {
+            de,
+            backend: probe_backend(de),
+        }
+    }
+
+    /// The detected desktop environment ("kde", "hyprland", ...).
+    pub fn desktop(&self) -> &'static str {
+        self.de
+    }
+
+    /// Name of the selected backend, if any.
+    pub fn backend_name(&self) -> Option<&'static str> {
+        self.backend.as_ref().map(|b| b.name())
+    }
+
+    /// Whether a working active-window source exists for this session.
+    pub fn is_available(&self) -> bool {
+        self.backend.is_some()
+    }
+
+    /// Run the tracker until `tx` is closed. Pushes each newly focused window's
+    /// lowercased resource class into `tx`.
+    ///
+    /// The KWin backend returns at once: its script (installed by
+    /// [`attach_kwin`]) feeds `ReportActiveWindow` → the same `tx`. The others
+    /// run their own loops on the blocking pool.
+    pub async fn watch(self, tx: UnboundedSender<String>) {
+        if let Some(backend) = self.backend {
+            let _ = tokio::task::spawn_blocking(move || backend.run(tx)).await;
+        }
+    }
+}
+
+impl Default for WindowTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Give a (re)started KWin the standalone active-window script, unless the
+/// resident helper already reports the active window
+///
+/// The script reports the current window as it loads, so the focused profile
+/// is refreshed too.
+pub async fn attach_kwin(connection: &zbus::Connection, helper: &crate::kwin_script::KWinHelper) {
+    if helper.is_active() {
+        tracing::info!("KWin helper reports the active window (per-app hardware profiles)");
+        return;
+    }
+    match helper.install_script(connection, KWIN_ACTIVE_WINDOW_SCRIPT, crate::kwin_script::ACTIVE_WINDOW_PLUGIN).await {
+        Ok(script_id) => tracing::info!(script_id, "KWin active-window script installed (per-app hardware profiles)"),
+        Err(e) => tracing::warn!(
+            error = %e,
+            "Failed to install KWin active-window script; per-app hardware profiles inactive on KDE until KWin restarts"
+        ),
+    }
+}
+
+/// No backends in this build
+#[cfg(not(feature = "window-tracking"))]
+fn probe_backend(_de: &str) -> Option<Box<dyn WindowBackend>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kwin_script_handles_both_plasma_versions() {
+        assert!(KWIN_ACTIVE_WINDOW_SCRIPT.contains("windowActivated"));
+        assert!(KWIN_ACTIVE_WINDOW_SCRIPT.contains("activeWindow"));
+        assert!(KWIN_ACTIVE_WINDOW_SCRIPT.contains("clientActivated"));
+        assert!(KWIN_ACTIVE_WINDOW_SCRIPT.contains("activeClient"));
+        assert!(KWIN_ACTIVE_WINDOW_SCRIPT.contains("ReportActiveWindow"));
+    }
+
+    #[test]
+    fn window_info_is_normalized() {
+        let info = WindowInfo::new(" Org.Kde.Dolphin ", Some(String::new())).unwrap();
+        assert_eq!(info.resource_class, "org.kde.dolphin");
+        assert_eq!(info.caption, None);
+        assert!(WindowInfo::new("  ", None).is_none());
+    }
+}
@@ -0,0 +1,200 @@
+//! Focus changes settle before they switch profiles
+//!
+//! An alt-tab sequence focuses every window it passes for a few tens of
+//! milliseconds. Each report would otherwise re-match the menu profile, clear
+//! a manual selection and apply hardware settings for a window the user never
+//! stopped on. A focused class only takes effect once no other report has
+//! arrived for `menu.focus_settle_ms` (150 by default); each new class
+//! restarts the timer, and classes replaced before it ran out are counted as
+//! suppressed.
+//!
+//! A menu press never waits: `ShowMenu` resolves the class focused at that
+//! instant with [`FocusSettle::flush`], and so does a report racing an open
+//! menu (`late_focus`). `FocusSettle` is the pure state machine (times are
+//! passed in) behind a [`FocusSettleHandle`] on `DaemonHandles`; the focus
+//! consumer owns the timer.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::handles::StateHandle;
+
+/// Default `menu.focus_settle_ms`
+pub const FOCUS_SETTLE_MS: u64 = 150;
+
+/// Counters for `Status()`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FocusSettleStats {
+    /// Classes that took effect
+    pub switches: u64,
+    /// Classes focused only briefly and never applied
+    pub suppressed: u64,
+    /// Menu presses that applied a class before it settled
+    pub menu_flushes: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Pending {
+    class: String,
+    /// When the class takes effect unless focus moves again
+    due: Instant,
+}
+
+/// Settle timer for the focused window class
+#[derive(Debug)]
+pub struct FocusSettle {
+    settle: Duration,
+    /// Class in effect for menus and profiles
+    applied: String,
+    pending: Option<Pending>,
+    stats: FocusSettleStats,
+}
+
+impl Default for FocusSettle {
+    fn default() -> Self {
+        Self {
+            settle: Duration::from_millis(FOCUS_SETTLE_MS),
+            applied: String::new(),
+            pending: None,
+            stats: FocusSettleStats::default(),
+        }
+    }
+}
+
+impl FocusSettle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long a class must stay focused; zero applies every report at once
+    pub fn set_settle(&mut self, settle: Duration) {
+        self.settle = settle;
+    }
+
+    /// `class` was focused at `at`
+    ///
+    /// Restarts the timer for a new class. A repeat of the pending class
+    /// keeps its time, and a return to the class in effect drops the pending
+    /// one.
+    pub fn report(&mut self, class: &str, at: Instant) {
+        if self.pending.as_ref().is_some_and(|p| p.class == class) {
+            return;
+        }
+        if self.pending.take().is_some() {
+            self.stats.suppressed += 1;
+        }
+        if class != self.applied {
+            self.pending = Some(Pending { class: class.to_string(), due: at + self.settle });
+        }
+    }
+
+    /// When the pending class takes effect
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|p| p.due)
+    }
+
+    /// The pending class, once it has stayed focused long enough
+    pub fn settled(&mut self, now: Instant) -> Option<String> {
+        if self.pending.as_ref()?.due > now {
+            return None;
+        }
+        let class = self.pending.take()?.class;
+        self.applied = class.clone();
+        self.stats.switches += 1;
+        Some(class)
+    }
+
+    /// Make the pending class due at once and return it
+    ///
+    /// For a menu press, which must open with the window focused now.
+    pub fn flush(&mut self, now: Instant) -> Option<String> {
+        let pending = self.pending.as_mut()?;
+        pending.due = pending.due.min(now);
+        self.stats.menu_flushes += 1;
+        Some(pending.class.clone())
+    }
+
+    /// The class focused now, settled or not
+    pub fn current(&self) -> &str {
+        self.pending.as_ref().map_or(&self.applied, |p| &p.class)
+    }
+
+    pub fn stats(&self) -> FocusSettleStats {
+        self.stats
+    }
+}
+
+/// The daemon's settle state, fed by the focus consumer and `ShowMenu`
+pub type FocusSettleHandle = StateHandle<FocusSettle>;
+
+impl FocusSettleHandle {
+    /// Counters for `Status()`
+    pub fn stats(&self) -> FocusSettleStats {
+        self.update(|s| s.stats()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn test_alt_tab_burst_applies_only_the_last_window() {
+        let t0 = Instant::now();
+        let mut settle = FocusSettle::new();
+        for (i, class) in ["konsole", "firefox", "dolphin", "kate"].into_iter().enumerate() {
+            settle.report(class, t0 + ms(50 * i as u64));
+            assert_eq!(settle.settled(t0 + ms(50 * i as u64 + 49)), None);
+        }
+        assert_eq!(settle.current(), "kate");
+        assert_eq!(settle.deadline(), Some(t0 + ms(300)));
+        assert_eq!(settle.settled(t0 + ms(299)), None);
+        assert_eq!(settle.settled(t0 + ms(300)).as_deref(), Some("kate"));
+        assert_eq!(settle.deadline(), None);
+        assert_eq!(settle.stats(), FocusSettleStats { switches: 1, suppressed: 3, menu_flushes: 0 });
+    }
+
+    #[test]
+    fn test_repeats_keep_the_timer_and_returning_cancels() {
+        let t0 = Instant::now();
+        let mut settle = FocusSettle::new();
+        settle.report("konsole", t0);
+        settle.report("konsole", t0 + ms(100));
+        assert_eq!(settle.settled(t0 + ms(150)).as_deref(), Some("konsole"));
+        // Away and straight back: nothing to apply
+        settle.report("firefox", t0 + ms(200));
+        settle.report("konsole", t0 + ms(240));
+        assert_eq!(settle.deadline(), None);
+        assert_eq!(settle.settled(t0 + ms(1000)), None);
+        assert_eq!(settle.current(), "konsole");
+        assert_eq!(settle.stats().suppressed, 1);
+    }
+
+    #[test]
+    fn test_menu_press_resolves_the_window_focused_now() {
+        let t0 = Instant::now();
+        let mut settle = FocusSettle::new();
+        assert_eq!(settle.flush(t0), None, "nothing pending, the applied class is current");
+        settle.report("firefox", t0);
+        assert_eq!(settle.flush(t0 + ms(20)).as_deref(), Some("firefox"));
+        assert_eq!(settle.settled(t0 + ms(20)).as_deref(), Some("firefox"));
+        assert_eq!(settle.stats().menu_flushes, 1);
+    }
+
+    #[test]
+    fn test_zero_settle_applies_every_change() {
+        let t0 = Instant::now();
+        let mut settle = FocusSettle::new();
+        settle.set_settle(Duration::ZERO);
+        settle.report("konsole", t0);
+        assert_eq!(settle.settled(t0).as_deref(), Some("konsole"));
+        settle.report("firefox", t0);
+        assert_eq!(settle.settled(t0).as_deref(), Some("firefox"));
+        assert_eq!(settle.stats().suppressed, 0);
+    }
+}
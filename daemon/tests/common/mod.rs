@@ -0,0 +1,123 @@
+//! Helpers for tests that run the real daemon binary
+//!
+//! Each test gets a private session bus (skipped when `dbus-daemon` is not
+//! installed) and a temporary home with no display and a `NOTIFY_SOCKET`.
+
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixDatagram;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use tempfile::TempDir;
+
+/// Startup includes a HID++ probe that can take ~1.5s with a mouse attached
+pub const READY_DEADLINE: Duration = Duration::from_secs(20);
+
+/// Private `dbus-daemon --session`, killed on drop
+pub struct PrivateBus {
+    child: Child,
+    pub address: String,
+}
+
+impl PrivateBus {
+    pub fn start() -> Option<Self> {
+        let mut child = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(child.stdout.take()?).read_line(&mut address).ok()?;
+        Some(Self {
+            child,
+            address: address.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Wait for a notify datagram with the given payload
+pub fn expect_notification(socket: &UnixDatagram, expected: &str, deadline: Duration) {
+    let started = Instant::now();
+    let mut buf = [0u8; 256];
+    while started.elapsed() < deadline {
+        if let Ok(n) = socket.recv(&mut buf) {
+            if String::from_utf8_lossy(&buf[..n]).lines().any(|l| l == expected) {
+                return;
+            }
+        }
+    }
+    panic!("no {} notification within {:?}", expected, deadline);
+}
+
+/// Temporary home with a config that keeps the overlay out of the way
+pub fn headless_home() -> TempDir {
+    home_with_config(&serde_json::json!({}))
+}
+
+/// Temporary home with `config` as config.json; `overlay.supervise` is
+/// forced off, as there is no overlay process in a headless test
+pub fn home_with_config(config: &serde_json::Value) -> TempDir {
+    let home = TempDir::new().unwrap();
+    let mut config = config.clone();
+    config["overlay"]["supervise"] = false.into();
+    write_config(&home, "config.json", &config);
+    home
+}
+
+/// Write `value` to `name` in the juhradial config directory of `home`
+pub fn write_config(home: &TempDir, name: &str, value: &serde_json::Value) {
+    let config_dir = home.path().join("config/juhradial");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join(name), value.to_string()).unwrap();
+}
+
+/// The daemon binary on `bus`, reporting to a notify socket in `home`;
+/// its log (stderr) goes to `stderr`
+pub fn spawn_daemon(bus: &PrivateBus, home: &TempDir, stderr: Stdio) -> (Child, UnixDatagram) {
+    spawn_daemon_with(bus, home, stderr, &[])
+}
+
+/// [`spawn_daemon`] with extra command line arguments
+pub fn spawn_daemon_with(bus: &PrivateBus, home: &TempDir, stderr: Stdio, args: &[&str]) -> (Child, UnixDatagram) {
+    let notify_path = home.path().join("notify.sock");
+    let _ = std::fs::remove_file(&notify_path);
+    let notify = UnixDatagram::bind(&notify_path).unwrap();
+    notify
+        .set_read_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+
+    let daemon = daemon_command(bus, home)
+        .args(args)
+        .env("NOTIFY_SOCKET", &notify_path)
+        .stdout(Stdio::null())
+        .stderr(stderr)
+        .spawn()
+        .unwrap();
+    (daemon, notify)
+}
+
+/// The daemon binary set up for `bus` and `home`, not yet spawned; its
+/// runtime directory (and so the instance lock) is inside `home`
+pub fn daemon_command(bus: &PrivateBus, home: &TempDir) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_juhradiald"));
+    command
+        .env("DBUS_SESSION_BUS_ADDRESS", &bus.address)
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .env("XDG_DATA_HOME", home.path().join("data"))
+        .env("XDG_RUNTIME_DIR", home.path().join("runtime"))
+        .env_remove("NOTIFY_SOCKET")
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY");
+    command
+}
@@ -0,0 +1,710 @@
+//! The daemon against a fake overlay over the real D-Bus interface
+//!
+//! `FakeOverlay` talks to a spawned daemon the way the Python overlay does:
+//! it checks the payload version and capabilities before relying on them,
+//! records `MenuPayload`, `MenuRequested`, `ThemeChanged` and
+//! `ThemeReloadFailed`, and answers a menu request from a script (report the clamped ring placement, highlight
+//! a slot and close the menu on it). No mouse, display or overlay process is
+//! needed, only `dbus-daemon` (the tests skip without it).
+//!
+//! Opt in with `cargo test --features integration`; each test starts its own
+//! bus and daemon, which takes a few seconds.
+
+#![cfg(feature = "integration")]
+
+mod common;
+
+use std::collections::{HashMap, VecDeque};
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use juhradiald::menu_payload::MENU_PAYLOAD_VERSION;
+use juhradiald::{get_bundled_theme, CursorPosition, ScreenBounds, EDGE_MARGIN, MENU_DIAMETER, MENU_RADIUS};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+
+use common::{
+    daemon_command, expect_notification, home_with_config, spawn_daemon, write_config, PrivateBus, READY_DEADLINE,
+};
+
+/// Signals follow the method call that caused them within milliseconds
+const SIGNAL_DEADLINE: Duration = Duration::from_secs(5);
+
+/// Spawned commands are not awaited by the executor
+const COMMAND_DEADLINE: Duration = Duration::from_secs(5);
+
+/// Capabilities the fake overlay relies on
+const REQUIRED_CAPABILITIES: &[&str] = &["theme_transition", "content_hash", "slice_count"];
+
+#[zbus::proxy(
+    interface = "org.kde.juhradialmx.Daemon",
+    default_service = "org.kde.juhradialmx",
+    default_path = "/org/kde/juhradialmx/Daemon"
+)]
+trait Daemon {
+    fn show_menu(&self, x: i32, y: i32) -> zbus::Result<()>;
+    fn report_active_window(&self, class: &str) -> zbus::Result<()>;
+    fn report_menu_placement(&self, press_x: i32, press_y: i32, center_x: i32, center_y: i32) -> zbus::Result<()>;
+    fn notify_slice_hover(&self, index: u8) -> zbus::Result<()>;
+    fn report_menu_closed(&self, slice: i32, input: &str) -> zbus::Result<()>;
+    fn report_action_result(&self, slice: i32, error: &str, kind: &str, elapsed_us: u64) -> zbus::Result<()>;
+    fn simulate_menu(&self, window_class: &str, slice: i32) -> zbus::Result<String>;
+    fn check_command(&self, argv: &[&str]) -> zbus::Result<()>;
+    fn get_invocation(&self, slice: i32) -> zbus::Result<HashMap<String, String>>;
+    fn reload_config(&self) -> zbus::Result<()>;
+    fn status(&self) -> zbus::Result<String>;
+    fn dump_state(&self, include_sensitive: bool) -> zbus::Result<String>;
+
+    #[zbus(signal)]
+    fn menu_requested(&self, x: i32, y: i32) -> zbus::Result<()>;
+    #[zbus(signal)]
+    fn menu_payload(&self, payload: String) -> zbus::Result<()>;
+    #[zbus(signal)]
+    fn theme_changed(&self, change: String) -> zbus::Result<()>;
+    #[zbus(signal)]
+    fn theme_reload_failed(&self, failure: String) -> zbus::Result<()>;
+}
+
+/// How the fake overlay answers `MenuRequested`
+#[derive(Debug, Clone, Copy, Default)]
+struct Script {
+    /// Report the ring placement, clamped to the screen like the overlay does
+    ack: bool,
+    /// Highlight this slot, then close the menu on it
+    select: Option<u8>,
+}
+
+/// Something the fake overlay received
+#[derive(Debug)]
+enum Event {
+    Payload(Value),
+    /// `MenuRequested` at `press`; the ring was drawn at `center`
+    Requested { press: (i32, i32), center: (i32, i32) },
+    ThemeChanged(Value),
+    ThemeReloadFailed(Value),
+}
+
+/// An overlay stand-in on the daemon's bus
+struct FakeOverlay {
+    proxy: DaemonProxy<'static>,
+    script: Arc<Mutex<Script>>,
+    events: mpsc::UnboundedReceiver<Event>,
+    /// Received while waiting for another kind; each signal has its own
+    /// stream, so a `ThemeChanged` and the `MenuPayload` sent right after it
+    /// can arrive in either order
+    skipped: VecDeque<Event>,
+}
+
+impl FakeOverlay {
+    /// Connect to the daemon on `address`, drawing on a `screen` sized display
+    async fn connect(address: &str, screen: ScreenBounds) -> Self {
+        let connection = zbus::connection::Builder::address(address)
+            .unwrap()
+            .build()
+            .await
+            .unwrap();
+        let proxy = DaemonProxy::new(&connection).await.unwrap();
+        let mut payloads = proxy.receive_menu_payload().await.unwrap();
+        let mut requests = proxy.receive_menu_requested().await.unwrap();
+        let mut themes = proxy.receive_theme_changed().await.unwrap();
+        let mut rejected = proxy.receive_theme_reload_failed().await.unwrap();
+
+        let script = Arc::new(Mutex::new(Script::default()));
+        let (tx, events) = mpsc::unbounded_channel();
+        let overlay = proxy.clone();
+        let replies = script.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    Some(signal) = payloads.next() => {
+                        let payload = signal.args().unwrap().payload;
+                        Event::Payload(serde_json::from_str(&payload).unwrap())
+                    }
+                    Some(signal) = themes.next() => {
+                        let change = signal.args().unwrap().change;
+                        Event::ThemeChanged(serde_json::from_str(&change).unwrap())
+                    }
+                    Some(signal) = rejected.next() => {
+                        let failure = signal.args().unwrap().failure;
+                        Event::ThemeReloadFailed(serde_json::from_str(&failure).unwrap())
+                    }
+                    Some(signal) = requests.next() => {
+                        let args = signal.args().unwrap();
+                        let press = (args.x, args.y);
+                        let clamped = CursorPosition::new(args.x, args.y).clamp_to_screen(&screen, MENU_DIAMETER as u32);
+                        let center = (clamped.x, clamped.y);
+                        let script = *replies.lock().unwrap();
+                        if script.ack {
+                            let _ = overlay.report_menu_placement(press.0, press.1, center.0, center.1).await;
+                        }
+                        if let Some(slot) = script.select {
+                            let _ = overlay.notify_slice_hover(slot).await;
+                            let _ = overlay.report_menu_closed(i32::from(slot), "gesture").await;
+                        }
+                        Event::Requested { press, center }
+                    }
+                    else => break,
+                };
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let overlay = Self { proxy, script, events, skipped: VecDeque::new() };
+        // The name is claimed before READY=1, but give the bus a moment anyway
+        let started = Instant::now();
+        while overlay.proxy.status().await.is_err() {
+            assert!(started.elapsed() < SIGNAL_DEADLINE, "daemon not reachable on the bus");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        overlay
+    }
+
+    /// Answer the next menu requests with `script`
+    fn script(&self, script: Script) {
+        *self.script.lock().unwrap() = script;
+    }
+
+    /// The next event `pick` takes, keeping the others for later
+    async fn next<T>(&mut self, mut pick: impl FnMut(Event) -> Result<T, Event>) -> T {
+        let mut skipped = VecDeque::new();
+        while let Some(event) = self.skipped.pop_front() {
+            match pick(event) {
+                Ok(picked) => {
+                    skipped.append(&mut self.skipped);
+                    self.skipped = skipped;
+                    return picked;
+                }
+                Err(other) => skipped.push_back(other),
+            }
+        }
+        self.skipped = skipped;
+        loop {
+            let event = tokio::time::timeout(SIGNAL_DEADLINE, self.events.recv())
+                .await
+                .expect("no signal from the daemon")
+                .expect("signal listener stopped");
+            match pick(event) {
+                Ok(picked) => return picked,
+                Err(other) => self.skipped.push_back(other),
+            }
+        }
+    }
+
+    /// The next payload, after the version handshake an overlay does on each
+    async fn next_payload(&mut self) -> Value {
+        let payload = self
+            .next(|event| match event {
+                Event::Payload(payload) => Ok(payload),
+                other => Err(other),
+            })
+            .await;
+        assert_eq!(payload["version"], MENU_PAYLOAD_VERSION, "payload version");
+        let capabilities = payload["capabilities"].as_array().unwrap();
+        for required in REQUIRED_CAPABILITIES {
+            assert!(capabilities.iter().any(|c| c == required), "daemon lacks {required}");
+        }
+        payload
+    }
+
+    async fn next_request(&mut self) -> ((i32, i32), (i32, i32)) {
+        self.next(|event| match event {
+            Event::Requested { press, center } => Ok((press, center)),
+            other => Err(other),
+        })
+        .await
+    }
+
+    async fn next_theme_change(&mut self) -> Value {
+        self.next(|event| match event {
+            Event::ThemeChanged(change) => Ok(change),
+            other => Err(other),
+        })
+        .await
+    }
+
+    /// The next `ThemeReloadFailed`, or `None` when none came in `deadline`
+    ///
+    /// Anything else received meanwhile is returned as an error.
+    async fn next_reload_failure(&mut self, deadline: Duration) -> Result<Option<Value>, Event> {
+        if let Some(other) = self.skipped.pop_front() {
+            return Err(other);
+        }
+        match tokio::time::timeout(deadline, self.events.recv()).await {
+            Ok(Some(Event::ThemeReloadFailed(failure))) => Ok(Some(failure)),
+            Ok(Some(other)) => Err(other),
+            Ok(None) => panic!("signal listener stopped"),
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn status(&self) -> Value {
+        serde_json::from_str(&self.proxy.status().await.unwrap()).unwrap()
+    }
+
+    /// Report `class` as focused and wait until the daemon has applied it
+    async fn focus(&self, class: &str) {
+        self.proxy.report_active_window(class).await.unwrap();
+        let started = Instant::now();
+        while self.status().await["thumbwheel"]["active_class"] != class {
+            assert!(started.elapsed() < SIGNAL_DEADLINE, "focus on {class} not applied");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+}
+
+/// The daemon binary on its own bus, stopped on drop
+struct Daemon {
+    child: Child,
+    home: TempDir,
+    // Dropped after the daemon
+    bus: PrivateBus,
+}
+
+impl Daemon {
+    /// Start with `config` as config.json and `profiles` as profiles.json
+    fn start(config: Value, profiles: Value) -> Option<Self> {
+        Self::start_in(home_with_config(&config), profiles)
+    }
+
+    /// Start in a prepared `home`, with `profiles` as profiles.json
+    fn start_in(home: TempDir, profiles: Value) -> Option<Self> {
+        Some(Self::start_on(PrivateBus::start()?, home, profiles))
+    }
+
+    /// [`start_in`](Self::start_in) on a bus that may already carry
+    /// services the daemon looks for at startup
+    fn start_on(bus: PrivateBus, home: TempDir, profiles: Value) -> Self {
+        write_config(&home, "profiles.json", &profiles);
+        let (child, notify) = spawn_daemon(&bus, &home, Stdio::null());
+        expect_notification(&notify, "READY=1", READY_DEADLINE);
+        Self { child, home, bus }
+    }
+
+    async fn overlay(&self) -> FakeOverlay {
+        FakeOverlay::connect(&self.bus.address, ScreenBounds { width: 1920, height: 1080 }).await
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Plasma's accent color as the settings portal publishes it
+const KDE_GENERAL: &str = "org.kde.kdeglobals.General";
+const ACCENT_COLOR: &str = "AccentColor";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const PORTAL_SETTINGS: &str = "org.freedesktop.portal.Settings";
+
+/// Stands in for the settings portal; it knows Plasma's accent color only
+struct FakePortal {
+    accent: String,
+}
+
+#[zbus::interface(name = "org.freedesktop.portal.Settings")]
+impl FakePortal {
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::fdo::Result<zbus::zvariant::OwnedValue> {
+        if (namespace, key) != (KDE_GENERAL, ACCENT_COLOR) {
+            return Err(zbus::fdo::Error::Failed(format!("{namespace} {key} is not set")));
+        }
+        zbus::zvariant::Value::from(self.accent.as_str())
+            .try_to_owned()
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
+    }
+}
+
+impl FakePortal {
+    /// Serve the portal on `address` with `accent` ("r,g,b") set
+    async fn serve(address: &str, accent: &str) -> zbus::Connection {
+        zbus::connection::Builder::address(address)
+            .unwrap()
+            .serve_at(PORTAL_PATH, FakePortal { accent: accent.to_string() })
+            .unwrap()
+            .name("org.freedesktop.portal.Desktop")
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+    }
+
+    /// What Plasma does when the accent changes: `SettingChanged`
+    async fn change_accent(portal: &zbus::Connection, accent: &str) {
+        let value = zbus::zvariant::Value::from(accent);
+        portal
+            .emit_signal(None::<&str>, PORTAL_PATH, PORTAL_SETTINGS, "SettingChanged", &(KDE_GENERAL, ACCENT_COLOR, value))
+            .await
+            .unwrap();
+    }
+}
+
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+
+/// UPower's power source, switched by the test
+struct FakeUPower {
+    on_battery: bool,
+}
+
+#[zbus::interface(name = "org.freedesktop.UPower")]
+impl FakeUPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> bool {
+        self.on_battery
+    }
+}
+
+impl FakeUPower {
+    /// Serve UPower on `address`
+    async fn serve(address: &str, on_battery: bool) -> zbus::Connection {
+        zbus::connection::Builder::address(address)
+            .unwrap()
+            .serve_at(UPOWER_PATH, FakeUPower { on_battery })
+            .unwrap()
+            .name("org.freedesktop.UPower")
+            .unwrap()
+            .build()
+            .await
+            .unwrap()
+    }
+
+    /// Unplug or plug in: set `OnBattery` and announce it
+    async fn set_on_battery(upower: &zbus::Connection, on_battery: bool) {
+        let iface = upower.object_server().interface::<_, FakeUPower>(UPOWER_PATH).await.unwrap();
+        iface.get_mut().await.on_battery = on_battery;
+        iface.get().await.on_battery_changed(iface.signal_emitter()).await.unwrap();
+    }
+}
+
+/// An 8-slice `default` and a 4-slice `editor` for `org.example.editor`
+fn profiles(marker: Option<&std::path::Path>) -> Value {
+    let command = marker.map(|m| json!({"type": "command", "value": format!("touch '{}'", m.display()), "label": "Mark"}));
+    json!({
+        "version": 2,
+        "profiles": [
+            {"name": "default", "slices": [null, null, null, null, null, null, null, null]},
+            {
+                "name": "editor",
+                "window_class": "org.example.editor",
+                "slice_count": 4,
+                "slices": [null, command, null, null]
+            }
+        ]
+    })
+}
+
+#[tokio::test]
+async fn test_menu_payload_follows_the_focused_window() {
+    let Some(daemon) = Daemon::start(json!({}), profiles(None)) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let mut overlay = daemon.overlay().await;
+    let hashes = overlay.status().await["content_hashes"]["profiles"]["by_name"].clone();
+
+    overlay.focus("org.example.editor").await;
+    overlay.proxy.show_menu(800, 500).await.unwrap();
+    let payload = overlay.next_payload().await;
+    assert_eq!(payload["slice_count"], 4);
+    assert_eq!(payload["profile_hash"], hashes["editor"]);
+    overlay.next_request().await;
+
+    overlay.focus("org.example.browser").await;
+    overlay.proxy.show_menu(800, 500).await.unwrap();
+    let payload = overlay.next_payload().await;
+    assert_eq!(payload["slice_count"], 8);
+    assert_eq!(payload["profile_hash"], hashes["default"]);
+}
+
+#[tokio::test]
+async fn test_theme_switch_reaches_the_overlay() {
+    let Some(daemon) = Daemon::start(json!({"theme": "catppuccin-mocha"}), profiles(None)) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let mut overlay = daemon.overlay().await;
+
+    // What the settings UI does: save config.json, then ReloadConfig
+    let config = json!({"theme": "vaporwave", "overlay": {"supervise": false}});
+    write_config(&daemon.home, "config.json", &config);
+    overlay.proxy.reload_config().await.unwrap();
+
+    let change = overlay.next_theme_change().await;
+    let expected = get_bundled_theme("vaporwave").unwrap().get_effective_colors(false);
+    assert_eq!(change["from"]["name"], "catppuccin-mocha");
+    assert_eq!(change["to"]["name"], "vaporwave");
+    assert_eq!(change["to"]["colors"]["accent"], expected.accent);
+    assert_eq!(change["to"]["colors"]["base"], expected.base);
+    // The restyled payload follows the change
+    assert_eq!(overlay.next_payload().await["theme"], "vaporwave");
+}
+
+#[tokio::test]
+async fn test_menu_near_an_edge_is_clamped_by_the_overlay_and_closes_on_selection() {
+    let Some(daemon) = Daemon::start(json!({}), profiles(None)) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let mut overlay = daemon.overlay().await;
+    overlay.script(Script { ack: true, select: Some(2) });
+
+    overlay.proxy.show_menu(5, 1075).await.unwrap();
+    // The daemon passes the press through; placing the ring is the overlay's
+    let (press, center) = overlay.next_request().await;
+    assert_eq!(press, (5, 1075));
+    assert_eq!(center, (EDGE_MARGIN + MENU_RADIUS, 1080 - EDGE_MARGIN - MENU_RADIUS));
+
+    // The scripted selection closed the menu again
+    let started = Instant::now();
+    while overlay.status().await["thumbwheel"]["menu_open"] != false {
+        assert!(started.elapsed() < SIGNAL_DEADLINE, "menu still open after the selection");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_overlay_reads_the_invocation_of_its_menu() {
+    let Some(daemon) = Daemon::start(json!({}), profiles(None)) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let mut overlay = daemon.overlay().await;
+    overlay.script(Script { ack: true, select: None });
+    overlay.focus("org.example.editor").await;
+
+    overlay.proxy.show_menu(5, 1075).await.unwrap();
+    overlay.next_request().await;
+    let variables = overlay.proxy.get_invocation(4).await.unwrap();
+    assert_eq!(variables["JUH_CURSOR_X"], "5");
+    assert_eq!(variables["JUH_CURSOR_Y"], "1075");
+    assert_eq!(variables["JUH_WINDOW_CLASS"], "org.example.editor");
+    assert_eq!(variables["JUH_PROFILE"], "editor");
+    assert_eq!(variables["JUH_SLICE"], "4");
+    assert_eq!(overlay.proxy.get_invocation(-1).await.unwrap()["JUH_SLICE"], "");
+}
+
+#[tokio::test]
+async fn test_selected_command_runs() {
+    let marker_dir = TempDir::new().unwrap();
+    let marker = marker_dir.path().join("fired");
+    let Some(daemon) = Daemon::start(json!({}), profiles(Some(&marker))) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let overlay = daemon.overlay().await;
+
+    let report: Value = serde_json::from_str(&overlay.proxy.simulate_menu("org.example.editor", 1).await.unwrap()).unwrap();
+    assert_eq!(report["profile"], "editor");
+    assert_eq!(report["execution"]["ok"], true);
+
+    let started = Instant::now();
+    while !marker.exists() {
+        assert!(started.elapsed() < COMMAND_DEADLINE, "command did not create {:?}", marker);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_settings_ring_commands_follow_the_execution_policy() {
+    let execution = json!({
+        "policy": "allowlist",
+        "allowed_commands": [["/usr/bin/true", "--ok"]],
+        "max_commands_per_minute": 1
+    });
+    let Some(daemon) = Daemon::start(json!({"execution": execution}), profiles(None)) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let overlay = daemon.overlay().await;
+
+    overlay.proxy.check_command(&["/usr/bin/true", "--ok"]).await.unwrap();
+    // Not the allowlisted argv, and a refusal does not count as a start
+    let refused = overlay.proxy.check_command(&["/usr/bin/true", "--ok", "; rm -rf ~"]).await.unwrap_err();
+    assert!(refused.to_string().contains("not allowed"), "{refused}");
+    let limited = overlay.proxy.check_command(&["/usr/bin/true", "--ok"]).await.unwrap_err();
+    assert!(limited.to_string().contains("rate limit"), "{limited}");
+}
+
+#[tokio::test]
+async fn test_overlay_reported_actions_land_in_latency_stats() {
+    let Some(daemon) = Daemon::start(json!({}), profiles(None)) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let overlay = daemon.overlay().await;
+
+    overlay.proxy.report_action_result(2, "", "command", 3_000).await.unwrap();
+    // No kind (nothing spawned) and an unknown one are not recorded
+    overlay.proxy.report_action_result(3, "", "", 0).await.unwrap();
+    overlay.proxy.report_action_result(3, "", "teleport", 10).await.unwrap();
+    let commands = &overlay.status().await["action_latency"]["actions"]["command"];
+    assert_eq!(commands["count"], 1, "{commands}");
+    assert_eq!(commands["max_ms"], 3.0, "{commands}");
+}
+
+#[tokio::test]
+async fn test_dump_state_redacts_commands() {
+    let marker_dir = TempDir::new().unwrap();
+    let marker = marker_dir.path().join("fired");
+    let Some(daemon) = Daemon::start(json!({}), profiles(Some(&marker))) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let overlay = daemon.overlay().await;
+    let command = format!("touch '{}'", marker.display());
+
+    let redacted = overlay.proxy.dump_state(false).await.unwrap();
+    assert!(!redacted.contains(&command), "command leaked into the redacted dump");
+    let dump: Value = serde_json::from_str(&redacted).unwrap();
+    assert_eq!(dump["redacted"], true);
+    assert_eq!(dump["daemon"]["running"], true);
+    let editor = &dump["profiles"]["profiles"][1];
+    assert_eq!(editor["window_class"], "org.example.editor");
+    assert_eq!(editor["slices"][1], json!({"type": "command"}));
+
+    let full: Value = serde_json::from_str(&overlay.proxy.dump_state(true).await.unwrap()).unwrap();
+    assert_eq!(full["profiles"]["profiles"][1]["slices"][1]["value"], command);
+
+    // The command line asks the running daemon
+    let output = daemon_command(&daemon.bus, &daemon.home).arg("dump-state").output().unwrap();
+    assert!(output.status.success());
+    let printed: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(printed["daemon"]["running"], true);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains(&command));
+}
+
+/// A theme named `edited` with the given accent
+fn edited_theme(accent: &str) -> String {
+    json!({
+        "name": "edited",
+        "colors": {"base": "#1e1e2e", "surface": "#313244", "text": "#cdd6f4", "accent": accent, "border": "#585b70"},
+        "glassmorphism": {},
+        "animation": {}
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_broken_theme_edit_is_reported_and_not_applied() {
+    let home = home_with_config(&json!({"theme": "edited"}));
+    let theme = home.path().join("config/juhradial/themes/edited/theme.json");
+    std::fs::create_dir_all(theme.parent().unwrap()).unwrap();
+    std::fs::write(&theme, edited_theme("#b4befe")).unwrap();
+    let Some(daemon) = Daemon::start_in(home, profiles(None)) else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let mut overlay = daemon.overlay().await;
+    let loaded = overlay.status().await["content_hashes"]["theme"].clone();
+    assert_eq!(loaded["name"], "edited");
+
+    // The watcher starts with the daemon's background tasks, so save again
+    // until it has seen the edit; each save must be answered within a second
+    let mut failure = None;
+    for _ in 0..5 {
+        std::fs::write(&theme, edited_theme("#nothex")).unwrap();
+        match overlay.next_reload_failure(Duration::from_secs(1)).await {
+            Ok(Some(reported)) => {
+                failure = Some(reported);
+                break;
+            }
+            Ok(None) => {}
+            Err(event) => panic!("rejected theme sent {event:?}"),
+        }
+    }
+    let failure = failure.expect("no ThemeReloadFailed for the broken theme");
+    assert_eq!(failure["theme"], "edited");
+    assert_eq!(failure["path"], theme.display().to_string());
+    assert!(failure["errors"][0].as_str().unwrap().starts_with("colors.accent"), "{failure}");
+    // The previous version is still the one served
+    assert_eq!(overlay.status().await["content_hashes"]["theme"], loaded);
+
+    // A valid save goes through and restyles the overlay
+    std::fs::write(&theme, edited_theme("#f38ba8")).unwrap();
+    assert_eq!(overlay.next_payload().await["theme"], "edited");
+    assert_ne!(overlay.status().await["content_hashes"]["theme"]["hash"], loaded["hash"]);
+}
+
+#[tokio::test]
+async fn test_desktop_accent_change_restyles_the_overlay() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let portal = FakePortal::serve(&bus.address, "61,174,233").await;
+    let daemon = Daemon::start_on(bus, home_with_config(&json!({"theme": "catppuccin-mocha"})), profiles(None));
+    let mut overlay = daemon.overlay().await;
+
+    // The accent read at startup replaces the theme's
+    let started = Instant::now();
+    while overlay.status().await["content_hashes"]["theme"]["accent_override"] != "#3daee9" {
+        assert!(started.elapsed() < SIGNAL_DEADLINE, "startup accent not applied");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // The watcher subscribes after its startup reads, so change the accent
+    // until it has seen it
+    let mut change = None;
+    for _ in 0..10 {
+        FakePortal::change_accent(&portal, "255,0,0").await;
+        if let Ok(received) = tokio::time::timeout(Duration::from_millis(500), overlay.next_theme_change()).await {
+            change = Some(received);
+            break;
+        }
+    }
+    let change = change.expect("no ThemeChanged for the new accent");
+    assert_eq!(change["from"]["accent_override"], "#3daee9");
+    assert_eq!(change["from"]["colors"]["accent"], "#3daee9");
+    assert_eq!(change["to"]["accent_override"], "#ff0000");
+    assert_eq!(change["to"]["colors"]["accent"], "#ff0000");
+    assert_eq!(change["to"]["name"], "catppuccin-mocha");
+    // The restyled payload follows, carrying the accent for the overlay
+    assert_eq!(overlay.next_payload().await["accent"], "#ff0000");
+
+    // An empty AccentColor: Plasma went back to the scheme's accent
+    FakePortal::change_accent(&portal, "").await;
+    let change = overlay.next_theme_change().await;
+    let own = get_bundled_theme("catppuccin-mocha").unwrap().get_effective_colors(false);
+    assert_eq!(change["to"]["colors"]["accent"], own.accent);
+    assert!(change["to"].get("accent_override").is_none());
+    assert!(overlay.next_payload().await.get("accent").is_none());
+}
+
+#[tokio::test]
+async fn test_idle_effect_stops_on_battery() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon unavailable; skipping overlay IPC test");
+        return;
+    };
+    let upower = FakeUPower::serve(&bus.address, false).await;
+    let config = json!({"theme": "matrix-rain", "idle_effects": {"max_fps": 20}});
+    let daemon = Daemon::start_on(bus, home_with_config(&config), profiles(None));
+    let mut overlay = daemon.overlay().await;
+
+    let started = Instant::now();
+    while overlay.status().await["idle_effect"]["on_battery"] != false {
+        assert!(started.elapsed() < SIGNAL_DEADLINE, "power source not read from UPower");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    overlay.proxy.show_menu(800, 500).await.unwrap();
+    let payload = overlay.next_payload().await;
+    assert_eq!(payload["idle_effect"]["type"], "matrix-rain");
+    assert_eq!(payload["idle_effect"]["max_fps"], 20, "capped by idle_effects.max_fps");
+    assert_eq!(payload["animation"]["idle_effects_enabled"], true);
+    overlay.next_request().await;
+
+    // Unplugged: a fresh payload turns the effect off without a menu open
+    FakeUPower::set_on_battery(&upower, true).await;
+    let payload = overlay.next_payload().await;
+    assert_eq!(payload["idle_effect"]["type"], "none");
+    assert_eq!(payload["animation"]["idle_effects_enabled"], false);
+    assert_eq!(overlay.status().await["idle_effect"]["enabled"], false);
+
+    FakeUPower::set_on_battery(&upower, false).await;
+    assert_eq!(overlay.next_payload().await["idle_effect"]["type"], "matrix-rain");
+}
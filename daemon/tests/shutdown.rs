@@ -0,0 +1,85 @@
+//! Graceful shutdown: the daemon exits cleanly and promptly on SIGTERM
+//!
+//! Runs the real binary against a private session bus (skipped when
+//! `dbus-daemon` is not installed) with a temporary home, no display and a
+//! `NOTIFY_SOCKET`, so the readiness and stopping notifications are checked too.
+
+mod common;
+
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use common::{expect_notification, headless_home, spawn_daemon, PrivateBus, READY_DEADLINE};
+
+/// Generous: the daemon itself bounds shutdown at 2s
+const EXIT_DEADLINE: Duration = Duration::from_secs(5);
+
+#[test]
+fn test_sigterm_exits_cleanly() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon unavailable; skipping shutdown test");
+        return;
+    };
+    let home = headless_home();
+    let (mut daemon, notify) = spawn_daemon(&bus, &home, Stdio::null());
+
+    expect_notification(&notify, "READY=1", READY_DEADLINE);
+    let signalled_at = Instant::now();
+    // SAFETY: plain kill(2) on our own child's pid
+    assert_eq!(unsafe { libc::kill(daemon.id() as libc::pid_t, libc::SIGTERM) }, 0);
+    expect_notification(&notify, "STOPPING=1", EXIT_DEADLINE);
+
+    let status = loop {
+        if let Some(status) = daemon.try_wait().unwrap() {
+            break status;
+        }
+        if signalled_at.elapsed() > EXIT_DEADLINE {
+            let _ = daemon.kill();
+            panic!("daemon still running {:?} after SIGTERM", EXIT_DEADLINE);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    assert!(status.success(), "daemon exited with {}", status);
+}
+
+/// A build without some features still starts, and says what it lacks
+#[cfg(not(all(feature = "haptics", feature = "battery", feature = "themes", feature = "window-tracking")))]
+#[test]
+fn test_minimal_build_logs_disabled_features() {
+    use std::io::{BufRead, BufReader};
+
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon unavailable; skipping minimal build test");
+        return;
+    };
+    let home = headless_home();
+    let (mut daemon, notify) = spawn_daemon(&bus, &home, Stdio::piped());
+    let stderr = daemon.stderr.take().unwrap();
+    let log = std::thread::spawn(move || {
+        BufReader::new(stderr).lines().map_while(Result::ok).collect::<Vec<_>>()
+    });
+
+    expect_notification(&notify, "READY=1", READY_DEADLINE);
+    // SAFETY: plain kill(2) on our own child's pid
+    assert_eq!(unsafe { libc::kill(daemon.id() as libc::pid_t, libc::SIGTERM) }, 0);
+    assert!(daemon.wait().unwrap().success());
+
+    let log = log.join().unwrap();
+    let disabled: Vec<&str> = juhradiald::features::FEATURES
+        .iter()
+        .filter(|f| !f.enabled)
+        .map(|f| f.name)
+        .collect();
+    for name in disabled {
+        let line = format!("Feature {name} disabled at build time");
+        assert!(log.iter().any(|l| l.contains(&line)), "no `{line}` in the daemon log");
+    }
+    // Missing input devices are expected on a test machine; the disabled
+    // parts must not be what fails
+    let feature_errors: Vec<&String> = log
+        .iter()
+        .filter(|l| l.contains("ERROR"))
+        .filter(|l| ["battery", "haptic", "theme", "window_tracker"].iter().any(|m| l.contains(m)))
+        .collect();
+    assert!(feature_errors.is_empty(), "errors from disabled features: {feature_errors:#?}");
+}
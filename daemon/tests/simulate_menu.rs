@@ -0,0 +1,117 @@
+//! Menu simulation end to end: profiles.json on disk → profile resolution →
+//! payload → a real Command action, with no mouse attached
+
+use std::time::{Duration, Instant};
+
+use juhradiald::accessibility::AccessibilitySettings;
+use juhradiald::execution_policy::ExecutionPolicy;
+use juhradiald::handles::DaemonHandles;
+use juhradiald::menu_simulation::simulate_menu;
+use juhradiald::profiles::ProfileManager;
+use juhradiald::theme::Theme;
+use tempfile::TempDir;
+
+/// Spawned commands are not awaited by the executor
+const COMMAND_DEADLINE: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+async fn test_simulated_selection_runs_profile_command() {
+    let dir = TempDir::new().unwrap();
+    let marker = dir.path().join("fired");
+    let profiles_path = dir.path().join("profiles.json");
+    let profiles = serde_json::json!({
+        "version": 2,
+        "profiles": [{
+            "name": "editor",
+            "window_class": "org.example.editor",
+            "slices": [
+                null, null, null,
+                {"type": "command", "value": format!("touch '{}'", marker.display()), "label": "Mark"},
+                null, null, null, null
+            ]
+        }]
+    });
+    std::fs::write(&profiles_path, profiles.to_string()).unwrap();
+    let manager = ProfileManager::load_from_path(&profiles_path).unwrap();
+    let theme = Theme::catppuccin_mocha();
+    let accessibility = AccessibilitySettings::default();
+    let policy = ExecutionPolicy::unrestricted();
+
+    // Inspect only: nothing runs
+    let report = simulate_menu(&manager, "org.example.editor", -1, &theme, &accessibility, false, &policy, &DaemonHandles::default())
+        .await
+        .unwrap();
+    assert_eq!(report.profile, "editor");
+    assert!(report.execution.is_none());
+    assert!(!marker.exists());
+
+    let report = simulate_menu(&manager, "org.example.editor", 3, &theme, &accessibility, false, &policy, &DaemonHandles::default())
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["slices"][3]["direction"], "SE");
+    assert_eq!(json["slices"][3]["action"]["label"], "Mark");
+    assert_eq!(json["execution"]["ok"], true);
+
+    let started = Instant::now();
+    while !marker.exists() {
+        assert!(started.elapsed() < COMMAND_DEADLINE, "command did not create {:?}", marker);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // Unmapped class falls back to the built-in default profile
+    let report = simulate_menu(&manager, "org.example.other", -1, &theme, &accessibility, false, &policy, &DaemonHandles::default())
+        .await
+        .unwrap();
+    assert_eq!(report.profile, "default");
+
+    // An empty slice reports instead of failing the call
+    let report = simulate_menu(&manager, "org.example.editor", 0, &theme, &accessibility, false, &policy, &DaemonHandles::default())
+        .await
+        .unwrap();
+    let execution = report.execution.unwrap();
+    assert!(!execution.ok);
+    assert_eq!(execution.error.as_deref(), Some("slice has no action"));
+}
+
+#[tokio::test]
+async fn test_simulated_command_sees_invocation_variables() {
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("context");
+    let profiles_path = dir.path().join("profiles.json");
+    let command = format!(
+        r#"printf '%s %s %s' "$JUH_WINDOW_CLASS" "$JUH_PROFILE" "$JUH_SLICE" > '{}.tmp' && mv '{0}.tmp' '{0}'"#,
+        out.display()
+    );
+    let profiles = serde_json::json!({
+        "version": 2,
+        "profiles": [{
+            "name": "editor",
+            "window_class": "org.example.editor",
+            "slices": [null, null, {"type": "command", "value": command}, null, null, null, null, null]
+        }]
+    });
+    std::fs::write(&profiles_path, profiles.to_string()).unwrap();
+    let manager = ProfileManager::load_from_path(&profiles_path).unwrap();
+    let report = simulate_menu(
+        &manager,
+        "org.example.editor",
+        2,
+        &Theme::catppuccin_mocha(),
+        &AccessibilitySettings::default(),
+        false,
+        &ExecutionPolicy::unrestricted(),
+        &DaemonHandles::default(),
+    )
+    .await
+    .unwrap();
+    let execution = report.execution.unwrap();
+    assert!(execution.ok, "{:?}", execution.error);
+
+    let started = Instant::now();
+    while !out.exists() {
+        assert!(started.elapsed() < COMMAND_DEADLINE, "command did not write {:?}", out);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "org.example.editor editor 2");
+}
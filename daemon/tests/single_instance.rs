@@ -0,0 +1,134 @@
+//! Single-instance guard: a second daemon leaves, `--replace` takes over
+//!
+//! Runs the real binary against private session buses (skipped when
+//! `dbus-daemon` is not installed). Daemons started with the same temporary
+//! home share its runtime directory and so the instance lock.
+
+mod common;
+
+use std::process::{Child, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use common::{daemon_command, expect_notification, headless_home, spawn_daemon, spawn_daemon_with, PrivateBus, READY_DEADLINE};
+use juhradiald::dbus::{DBUS_NAME, DBUS_PATH};
+
+/// The refused daemon exits before any device work
+const EXIT_DEADLINE: Duration = Duration::from_secs(10);
+
+/// Run a second daemon to completion; its exit status and stderr
+fn run_second(bus: &PrivateBus, home: &tempfile::TempDir) -> (std::process::ExitStatus, String) {
+    let mut second = daemon_command(bus, home)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let started = Instant::now();
+    while second.try_wait().unwrap().is_none() {
+        if started.elapsed() > EXIT_DEADLINE {
+            let _ = second.kill();
+            panic!("second daemon still running after {:?}", EXIT_DEADLINE);
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let output = second.wait_with_output().unwrap();
+    (output.status, String::from_utf8_lossy(&output.stderr).into_owned())
+}
+
+fn stop(mut daemon: Child) {
+    // SAFETY: plain kill(2) on our own child's pid
+    unsafe { libc::kill(daemon.id() as libc::pid_t, libc::SIGTERM) };
+    assert!(daemon.wait().unwrap().success());
+}
+
+#[test]
+fn test_second_instance_exits_cleanly() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon unavailable; skipping single-instance test");
+        return;
+    };
+    let home = headless_home();
+    let (mut first, notify) = spawn_daemon(&bus, &home, Stdio::null());
+    expect_notification(&notify, "READY=1", READY_DEADLINE);
+
+    let (status, stderr) = run_second(&bus, &home);
+    assert!(status.success(), "second daemon exited with {status}");
+    assert!(stderr.contains(&format!("already running (pid {})", first.id())), "stderr: {stderr}");
+    assert!(stderr.contains("--replace"));
+    assert!(first.try_wait().unwrap().is_none(), "the first daemon must keep running");
+    stop(first);
+}
+
+/// The bus name cannot see a daemon on another bus; the lock can
+#[test]
+fn test_lock_holds_off_a_daemon_on_another_bus() {
+    let (Some(bus), Some(other_bus)) = (PrivateBus::start(), PrivateBus::start()) else {
+        eprintln!("dbus-daemon unavailable; skipping single-instance test");
+        return;
+    };
+    let home = headless_home();
+    let (first, notify) = spawn_daemon(&bus, &home, Stdio::null());
+    expect_notification(&notify, "READY=1", READY_DEADLINE);
+
+    let (status, stderr) = run_second(&other_bus, &home);
+    assert!(status.success(), "second daemon exited with {status}");
+    assert!(stderr.contains(&format!("already running (pid {})", first.id())), "stderr: {stderr}");
+    stop(first);
+}
+
+/// Stands in for a running daemon: owns the name and records `Quit`
+struct StubDaemon {
+    quit: mpsc::Sender<()>,
+}
+
+#[zbus::interface(name = "org.kde.juhradialmx.Daemon")]
+impl StubDaemon {
+    fn quit(&self) {
+        let _ = self.quit.send(());
+    }
+}
+
+#[test]
+fn test_replace_asks_the_running_daemon_to_quit() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon unavailable; skipping single-instance test");
+        return;
+    };
+    let (quit_tx, quit_rx) = mpsc::channel();
+    let stub = zbus::blocking::connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .serve_at(DBUS_PATH, StubDaemon { quit: quit_tx })
+        .unwrap()
+        .build()
+        .unwrap();
+    stub.request_name(DBUS_NAME).unwrap();
+
+    let home = headless_home();
+    let (daemon, notify) = spawn_daemon_with(&bus, &home, Stdio::null(), &["--replace"]);
+    quit_rx.recv_timeout(READY_DEADLINE).expect("--replace never called Quit");
+    // The stub "shuts down": the replacing daemon is waiting for the name
+    stub.release_name(DBUS_NAME).unwrap();
+    expect_notification(&notify, "READY=1", READY_DEADLINE);
+
+    let dbus = zbus::blocking::fdo::DBusProxy::new(&stub).unwrap();
+    let owner = dbus.get_name_owner(DBUS_NAME.try_into().unwrap()).unwrap();
+    assert_ne!(&owner, stub.unique_name().unwrap(), "the new daemon must own the name");
+    stop(daemon);
+}
+
+#[test]
+fn test_replace_takes_over_from_a_real_daemon() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("dbus-daemon unavailable; skipping single-instance test");
+        return;
+    };
+    let home = headless_home();
+    let (mut first, notify) = spawn_daemon(&bus, &home, Stdio::null());
+    expect_notification(&notify, "READY=1", READY_DEADLINE);
+
+    let (second, notify) = spawn_daemon_with(&bus, &home, Stdio::null(), &["--replace"]);
+    expect_notification(&notify, "READY=1", READY_DEADLINE);
+    // Quit takes the clean shutdown path, so the first daemon exits 0
+    assert!(first.wait().unwrap().success());
+    stop(second);
+}
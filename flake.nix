@@ -96,6 +96,8 @@
               # Assets - radial wheel images, device illustrations, AI icons
               mkdir -p $out/share/juhradial/assets/radial-wheels
               cp assets/radial-wheels/*.png $out/share/juhradial/assets/radial-wheels/
+              mkdir -p $out/share/juhradial/assets/themes
+              cp -r assets/themes/* $out/share/juhradial/assets/themes/
               if [ -d assets/devices ]; then
                 mkdir -p $out/share/juhradial/assets/devices
                 cp assets/devices/*.png assets/devices/*.svg $out/share/juhradial/assets/devices/ 2>/dev/null || true
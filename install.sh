@@ -524,6 +524,10 @@ install_files() {
     # Install 3D radial wheel images
     sudo mkdir -p /usr/share/juhradial/assets/radial-wheels
     sudo cp -r assets/radial-wheels/*.png /usr/share/juhradial/assets/radial-wheels/
+
+    # Install bundled theme textures
+    sudo mkdir -p /usr/share/juhradial/assets/themes
+    sudo cp -r assets/themes/* /usr/share/juhradial/assets/themes/
     log_success "Theme assets"
 
     # Install device images (mouse illustrations for settings)
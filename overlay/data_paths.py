@@ -0,0 +1,74 @@
+"""
+JuhRadial MX - Install Data Directory
+
+Resolves the data directory (themes, assets) the same way the daemon does,
+so snap/flatpak, /usr/local and prefix installs find their files:
+
+1. $JUHRADIAL_DATA_DIR
+2. "paths": {"data_dir": ...} in config.json
+3. the first existing <dir>/juhradial in $XDG_DATA_DIRS
+4. /usr/share/juhradial
+
+SPDX-License-Identifier: GPL-3.0
+"""
+
+import json
+import os
+from pathlib import Path
+
+DATA_DIR_ENV = "JUHRADIAL_DATA_DIR"
+DEFAULT_DATA_DIR = "/usr/share/juhradial"
+_DEFAULT_XDG_DATA_DIRS = "/usr/local/share:/usr/share"
+
+_SCRIPT_DIR = os.path.dirname(os.path.abspath(__file__))
+_cached = None
+
+
+def _configured_data_dir():
+    config_home = os.environ.get("XDG_CONFIG_HOME") or os.path.join(
+        str(Path.home()), ".config"
+    )
+    try:
+        with open(os.path.join(config_home, "juhradial", "config.json")) as f:
+            value = json.load(f).get("paths", {}).get("data_dir")
+    except (OSError, ValueError, AttributeError):
+        return None
+    return value if isinstance(value, str) and value else None
+
+
+def data_dir():
+    """Resolved data directory (cached for the process)."""
+    global _cached
+    if _cached is None:
+        _cached = os.environ.get(DATA_DIR_ENV) or _configured_data_dir()
+        if not _cached:
+            xdg = os.environ.get("XDG_DATA_DIRS") or _DEFAULT_XDG_DATA_DIRS
+            candidates = [
+                os.path.join(base, "juhradial")
+                for base in xdg.split(":")
+                if os.path.isabs(base)
+            ]
+            _cached = next(
+                (d for d in candidates if os.path.isdir(d)), DEFAULT_DATA_DIR
+            )
+    return _cached
+
+
+def asset_dirs(*subdirs):
+    """Asset directories to search, most specific first.
+
+    The dev checkout (overlay/../assets) and an assets/ beside the installed
+    scripts come before the resolved data directory and the compiled-in default.
+    """
+    roots = [
+        os.path.join(_SCRIPT_DIR, "..", "assets"),
+        os.path.join(_SCRIPT_DIR, "assets"),
+        os.path.join(data_dir(), "assets"),
+        os.path.join(DEFAULT_DATA_DIR, "assets"),
+    ]
+    dirs = []
+    for root in roots:
+        path = os.path.join(root, *subdirs)
+        if path not in dirs:
+            dirs.append(path)
+    return dirs
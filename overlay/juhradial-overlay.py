@@ -66,6 +66,7 @@ _wait_for_x11()
 # native wayland platform - positioning degrades but nothing crashes.
 os.environ["QT_QPA_PLATFORM"] = "xcb;wayland"
 
+import json
 import math
 import shlex
 import subprocess
@@ -78,6 +79,7 @@ from PyQt6.QtCore import (
     QEasingCurve,
     QTimer,
     QRectF,
+    QMetaType,
 )
 from PyQt6.QtGui import (
     QPainter,
@@ -90,13 +92,19 @@ from PyQt6.QtGui import (
     QFont,
     QRadialGradient,
 )
-from PyQt6.QtDBus import QDBusConnection, QDBusInterface
+from PyQt6.QtDBus import QDBusArgument, QDBusConnection, QDBusInterface
 
 from overlay_constants import (
     MENU_RADIUS,
     CENTER_ZONE_RADIUS,
     WINDOW_SIZE,
     compute_ring_scale,
+    ring_scale_for_diameter,
+    mirror_slice,
+    slice_span,
+    SLICE_COUNT,
+    SUPPORTED_SLICE_COUNTS,
+    ACTION_CHECK_SECONDS,
     map_and_clamp_menu,
     IS_HYPRLAND,
     IS_GNOME,
@@ -121,6 +129,7 @@ from overlay_cursor import (
     find_monitor_at,
 )
 import overlay_actions
+from data_paths import asset_dirs
 from overlay_painting import RadialMenuPaintingMixin
 from i18n import _
 
@@ -176,10 +185,7 @@ class SplashScreen(QWidget):
         wheel_candidates.append("radialwheel3.png")  # neon fallback
 
         for wname in wheel_candidates:
-            for base in [
-                os.path.join(os.path.dirname(__file__), "..", "assets", "radial-wheels"),
-                "/usr/share/juhradial/assets/radial-wheels",
-            ]:
+            for base in asset_dirs("radial-wheels"):
                 wpath = os.path.join(base, wname)
                 if os.path.exists(wpath):
                     pm = QPixmap(wpath)
@@ -451,13 +457,28 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         self.submenu_slice = -1  # Which main slice has active submenu
         self.highlighted_subitem = -1  # Which sub-item is highlighted (-1 = none)
 
+        # Slices in the ring (MenuPayload slice_count; 8 for older daemons)
+        self.slice_count = SLICE_COUNT
+        # Ring diameter from menu.size (MenuPayload menu_diameter, logical
+        # pixels); None scales with the monitor height
+        self.menu_diameter = None
         # Animation state - per-slice highlight progress (0.0 = off, 1.0 = full)
-        self.slice_highlights = [0.0] * 8
+        self.slice_highlights = [0.0] * self.slice_count
         # Submenu pop-out animation progress (0.0 = hidden, 1.0 = fully shown)
         self.submenu_progress = 0.0
         # Selection flash (slice index to flash, -1 = none)
         self.flash_slice = -1
         self.flash_progress = 0.0  # 1.0 = bright, fades to 0.0
+        # Flash tint (hex) for an action outcome; None = the plain white flash
+        self.flash_color = None
+        # Animation timings (MenuPayload `animation`, AnimationTimingsChanged);
+        # all 0 under reduced motion
+        self.appear_ms = 180
+        self.dismiss_ms = 0
+        self.highlight_in_ms = 112
+        self.highlight_out_ms = 80
+        # True while the dismiss fade runs; the menu counts as closed
+        self._dismissing = False
         # Menu open bloom scale (0.0 = start, 1.0 = settled)
         self.bloom_progress = 0.0
         # Center zone pulse (0.0 = start, 1.0 = settled)
@@ -470,6 +491,71 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         self._menu_closed_at = 0.0
         # Track when menu was shown (for tap detection)
         self.show_time = None
+        # Battery/connection badges from the daemon's MenuPayload (None = hide)
+        self.device_status = None
+        # Content hashes from the last MenuPayload, and the theme hash
+        # overlay_actions.COLORS was loaded under
+        self.theme_hash = None
+        self.profile_hash = None
+        self._loaded_theme_hash = None
+        # Desktop accent the daemon draws the theme with (None = the theme's)
+        self.system_accent = None
+        # Idle animation (type, density, speed, max_fps) the daemon allows;
+        # type "none" on battery and under reduced motion
+        self.idle_effect = None
+        # Mirrored ring (input.left_handed), set by each MenuPayload
+        self.left_handed = False
+        # Outcome flash colors and hold time (menu.action_feedback; None = off)
+        self.action_feedback = None
+        # When the last Settings ring action began, and how many µs later its
+        # process was spawned (0 = it spawned none); reported to the daemon's
+        # latency stats
+        self.action_started = 0.0
+        self.action_spawn_us = 0
+        # High contrast text and selection border overrides (None = off)
+        self.high_contrast = None
+        # Slices of a pending ad-hoc menu (ShowAdHocMenu), consumed by on_show
+        self.adhoc_slices = None
+        # Slices of the menu's profile when it is not default (None = the
+        # Settings ring from config.json); the daemon runs these
+        self.profile_slices = None
+        # Slice descriptions and hover delay (None = menu has none), and the
+        # slot whose description the hub shows (-1 = none)
+        self.hover_help = None
+        self.hover_help_slot = -1
+        # Slots bound to wheel_axis: drawn with an up/down hint, and a release
+        # on one runs nothing
+        self.wheel_axis_slots = []
+        # Slots that run only on a second press, and the pending one as
+        # (slot, monotonic deadline) after the first; None = nothing pending
+        self.confirm_slots = []
+        self.confirm_window_ms = 1200
+        self.confirm_pending = None
+        # Slots still in their cooldown_ms, as slot -> monotonic end; drawn
+        # dimmed, and a click on one runs nothing
+        self.cooldown_until = {}
+        # Hover to select (accessibility.dwell_confirm_ms, 0 = off) and when
+        # the highlight landed on a slice that can dwell (monotonic seconds)
+        self.dwell_confirm_ms = 0
+        self.dwell_started = None
+        # Labels the daemon cut to menu.label_max_cells, one per slot
+        # (None = draw the label as is); empty from older daemons
+        self.short_labels = []
+        # Profile labels the daemon picked for its locale, one per slot;
+        # empty unless the profile has per-language labels
+        self.localized_labels = []
+        # Fill color per slice (accessibility.slice_tinting); empty from
+        # older daemons, which leaves the surface color
+        self.slice_colors = []
+        # Theme texture behind the slices (glassmorphism.backgroundTexture),
+        # checked by the daemon; None draws them flat
+        self.background_texture = None
+        # File icons resolved by the daemon, one per slot (None = built-in
+        # icon); empty from older daemons
+        self.slice_icons = []
+        # Running theme crossfade (ThemeChanged): from colors, to colors,
+        # start (monotonic seconds) and duration (seconds); None = none
+        self._theme_fade = None
 
         # D-Bus setup. Subscribe with an EMPTY service name: QtDBus resolves
         # a named service to its unique bus name when the match rule is
@@ -502,15 +588,79 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             "ii",
             self.on_cursor_moved,
         )
+        # Auto-dismiss (menu.auto_dismiss_ms): close without executing
+        ok_dismiss = bus.connect(
+            "",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "DismissMenu",
+            "",
+            self.on_dismiss,
+        )
         print(
             f"[DBUS] signal subscriptions: MenuRequested={ok_show} "
-            f"HideMenu={ok_hide} CursorMoved={ok_cursor}",
+            f"HideMenu={ok_hide} CursorMoved={ok_cursor} DismissMenu={ok_dismiss}",
             flush=True,
         )
         # Prime the KScreen layout cache off the latency-critical open path.
         if IS_KDE and not IS_X11 and _HAS_XWAYLAND:
             _log(f"KScreen layout primed: {get_kde_monitors_logical()}")
 
+        # Render state pushed by the daemon: MenuPayload precedes each
+        # MenuRequested, AnimationTimingsChanged follows a reduce-motion flip.
+        bus.connect(
+            "",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "MenuPayload",
+            "s",
+            self._on_menu_payload,
+        )
+        bus.connect(
+            "",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "AnimationTimingsChanged",
+            "s",
+            self._on_animation_timings,
+        )
+        # Theme switched (config or high contrast): crossfade an open menu
+        bus.connect(
+            "",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "ThemeChanged",
+            "s",
+            self._on_theme_changed,
+        )
+        # Highlight rested on a slice for menu.hover_help_ms: show its description
+        bus.connect(
+            "",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "HighlightStable",
+            "y",
+            self._on_highlight_stable,
+        )
+        # Released on a require_confirmation slice: prompt for a second press
+        bus.connect(
+            "",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "AwaitingConfirmation",
+            "yu",
+            self._on_awaiting_confirmation,
+        )
+        # Flick mode: a fast flick picked a slice, run it without the menu
+        bus.connect(
+            "",
+            "/org/kde/juhradialmx/Daemon",
+            "org.kde.juhradialmx.Daemon",
+            "FlickSelected",
+            "yy",
+            self._on_flick_selected,
+        )
+
         # Listen for language changes from settings process
         bus.connect(
             "",  # any sender
@@ -532,10 +682,15 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             f"[DBUS] D-Bus interface created - isValid: {self.daemon_iface.isValid()}"
         )
 
-        # Fade animation
+        # Fade animations; durations follow the daemon's animation timings
         self.anim = QPropertyAnimation(self, b"windowOpacity")
-        self.anim.setDuration(180)
+        self.anim.setDuration(self.appear_ms)
         self.anim.setEasingCurve(QEasingCurve.Type.OutCubic)
+        self.dismiss_anim = QPropertyAnimation(self, b"windowOpacity")
+        self.dismiss_anim.setDuration(self.dismiss_ms)
+        self.dismiss_anim.setEndValue(0.0)
+        self.dismiss_anim.setEasingCurve(QEasingCurve.Type.InCubic)
+        self.dismiss_anim.finished.connect(self._hide_window)
 
         # Cursor polling timer for toggle mode (tracks cursor position when menu stays open)
         self.cursor_timer = QTimer(self)
@@ -571,6 +726,249 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             print(f"    {directions[i]:12} -> {action[0]}", flush=True)
         print("\n" + "=" * 60 + "\n", flush=True)
 
+    @pyqtSlot(str)
+    def _on_menu_payload(self, payload):
+        """Apply the daemon's per-invocation render state."""
+        try:
+            data = json.loads(payload)
+        except ValueError:
+            return
+        self._apply_animation_timings(data.get("animation") or {})
+        self.device_status = data.get("status")
+        self.left_handed = bool(data.get("left_handed"))
+        self.action_feedback = data.get("feedback")
+        self.high_contrast = data.get("high_contrast")
+        self.adhoc_slices = data.get("adhoc")
+        self.profile_slices = data.get("slices")
+        self.hover_help = data.get("help")
+        # wheel_axis slots index the profile slices drawn above, never the
+        # Settings ring the overlay runs
+        self.wheel_axis_slots = (data.get("wheel_axis") or []) if self.profile_slices else []
+        confirm = data.get("confirm") or {}
+        self.confirm_slots = confirm.get("slots") or []
+        self.confirm_window_ms = int(confirm.get("window_ms", 1200))
+        import time
+        now = time.monotonic()
+        self.cooldown_until = {
+            entry["slot"]: now + entry.get("remaining_ms", 0) / 1000.0
+            for entry in data.get("cooldown") or []
+            if isinstance(entry, dict) and isinstance(entry.get("slot"), int)
+        } if self.profile_slices else {}
+        self.dwell_confirm_ms = int(data.get("dwell_confirm_ms") or 0)
+        self.short_labels = data.get("short_labels") or []
+        self.localized_labels = data.get("labels") or []
+        self.slice_colors = data.get("slice_colors") or []
+        self.background_texture = data.get("background_texture")
+        self.slice_icons = data.get("icons") or []
+        self.system_accent = data.get("accent")
+        idle = data.get("idle_effect") or {}
+        self.idle_effect = idle if idle.get("type", "none") != "none" else None
+        # Content hashes; None from daemons without the content_hash capability
+        self.theme_hash = data.get("theme_hash")
+        previous_profile = self.profile_hash
+        self.profile_hash = data.get("profile_hash")
+        # The ring keeps its size while open and a resend only restyles it,
+        # unless a late focus report swapped the profile before the pointer
+        # moved off the hub
+        count = data.get("slice_count")
+        swapped = (
+            self.isVisible()
+            and self.profile_hash != previous_profile
+            and self.highlighted_slice < 0
+        )
+        if not self.isVisible() or swapped:
+            self.slice_count = count if count in SUPPORTED_SLICE_COUNTS else SLICE_COUNT
+        if not self.isVisible():
+            diameter = data.get("menu_diameter")
+            self.menu_diameter = diameter if isinstance(diameter, int) and diameter > 0 else None
+        if swapped:
+            overlay_actions.ACTIONS = overlay_actions.fit_actions(
+                self._menu_actions(), self.slice_count
+            )
+        # Also sent when the desktop contrast setting flips mid-menu
+        if self.isVisible():
+            self.update()
+
+    def _menu_actions(self):
+        """ACTIONS for the menu's profile: its slices from the payload, run by
+        the daemon, or the Settings ring from config.json for default."""
+        if self.profile_slices:
+            return overlay_actions.actions_from_daemon(
+                self.profile_slices, "profile", len(self.profile_slices)
+            )
+        return overlay_actions.load_actions_from_config()
+
+    @pyqtSlot(int)
+    def _on_highlight_stable(self, slot):
+        """The highlight rested on a slice: show its description in the hub."""
+        if not self.isVisible() or slot != self.highlighted_slice:
+            return
+        if self._hover_description(slot):
+            self.hover_help_slot = slot
+            self.update()
+
+    @pyqtSlot(int, int)
+    def _on_flick_selected(self, slot, count):
+        """A flick picked a slice: run its action without drawing the menu.
+        The daemon already played the confirm haptic."""
+        if self.isVisible():
+            return
+        count = count if count in SUPPORTED_SLICE_COUNTS else SLICE_COUNT
+        actions = overlay_actions.fit_actions(
+            overlay_actions.load_actions_from_config(), count
+        )
+        if not 0 <= slot < len(actions):
+            return
+        action = actions[slot]
+        # Empty slots have nothing to run, submenus need the menu
+        if action[1] in ("none", "submenu"):
+            print(f"OVERLAY: Flick on slot {slot} ({action[1]}) - nothing to run")
+            return
+        print(f"OVERLAY: Flick on slot {slot}")
+        error = self._execute_action(action, slot)
+        if error:
+            print(f"Action failed to start: {error}")
+
+    @pyqtSlot(int, int)
+    def _on_awaiting_confirmation(self, slot, timeout_ms):
+        """The daemon held the menu open after a release on a slice that
+        needs confirming: mark it until the second press or the timeout."""
+        if not self.isVisible():
+            return
+        self._set_confirm_pending(slot, timeout_ms)
+
+    def _set_confirm_pending(self, slot, timeout_ms):
+        import time
+        self.confirm_pending = (slot, time.monotonic() + timeout_ms / 1000.0)
+        # The daemon stops dwelling once a confirmation is pending
+        self.dwell_started = None
+        self.update()
+
+    def is_cooling(self, slot):
+        """Whether a profile slot is still in its cooldown."""
+        import time
+        until = self.cooldown_until.get(slot)
+        return until is not None and time.monotonic() < until
+
+    def _can_dwell(self, slot):
+        """Whether the daemon dwell-confirms a highlight on this slot: not the
+        hub, not a slice that never runs on a plain release or is cooling
+        down, and not while a confirmation is pending or on an ad-hoc menu."""
+        return (
+            self.dwell_confirm_ms > 0
+            and slot >= 0
+            and not self.adhoc_slices
+            and self.confirm_pending is None
+            and slot not in self.confirm_slots
+            and slot not in self.wheel_axis_slots
+            and not self.is_cooling(slot)
+        )
+
+    def dwell_progress(self):
+        """Fill of the dwell ring on the highlighted slice (0..1), or None."""
+        import time
+        if self.dwell_started is None:
+            return None
+        elapsed_ms = (time.monotonic() - self.dwell_started) * 1000
+        return min(1.0, elapsed_ms / self.dwell_confirm_ms)
+
+    def _slice_label(self, slot):
+        """Label for a profile slot, shortened by the daemon when too wide."""
+        if 0 <= slot < len(self.short_labels) and self.short_labels[slot]:
+            return self.short_labels[slot]
+        if 0 <= slot < len(self.localized_labels) and self.localized_labels[slot]:
+            return self.localized_labels[slot]
+        return overlay_actions.ACTIONS[slot][0]
+
+    def _slice_icon(self, slot):
+        """Payload `icons` entry for a profile slot, or None."""
+        if 0 <= slot < len(self.slice_icons) and isinstance(self.slice_icons[slot], dict):
+            return self.slice_icons[slot]
+        return None
+
+    def _hover_description(self, slot):
+        """Description for a profile slot from the payload, or None."""
+        descriptions = (self.hover_help or {}).get("descriptions") or []
+        if 0 <= slot < len(descriptions):
+            return descriptions[slot]
+        return None
+
+    def _slot_for_angle(self, angle):
+        """Profile slot under a compass angle (degrees, N = 0, clockwise)."""
+        count = self.slice_count
+        span = slice_span(count)
+        position = int((angle + span / 2) / span) % count
+        return mirror_slice(position, count) if self.left_handed else position
+
+    @pyqtSlot(str)
+    def _on_animation_timings(self, timings):
+        """Reduce-motion changed while the menu was closed."""
+        try:
+            self._apply_animation_timings(json.loads(timings))
+        except ValueError:
+            pass
+
+    @pyqtSlot(str)
+    def _on_theme_changed(self, change):
+        """The daemon switched themes: fade an open menu to the new colors."""
+        import time
+
+        try:
+            data = json.loads(change)
+        except ValueError:
+            return
+        # What is on screen now, mid-fade included, is where a new fade starts
+        shown = dict(overlay_actions.COLORS)
+        self.system_accent = (data.get("to") or {}).get("accent_override")
+        overlay_actions.COLORS = overlay_actions.load_theme(self.system_accent)
+        duration = (data.get("transition_ms") or 0) / 1000.0
+        if not self.isVisible() or duration <= 0:
+            self._theme_fade = None
+            self.update()
+            return
+        self._theme_fade = (shown, dict(overlay_actions.COLORS), time.monotonic(), duration)
+        overlay_actions.COLORS = dict(shown)
+        self._anim_timer.start()
+
+    def _step_theme_fade(self):
+        """Advance the theme crossfade; True while it is still running."""
+        import time
+
+        start_colors, end_colors, started, duration = self._theme_fade
+        t = min(1.0, (time.monotonic() - started) / duration)
+        colors = dict(end_colors)
+        for key, end in end_colors.items():
+            start = start_colors.get(key)
+            if start is not None:
+                colors[key] = QColor.fromRgbF(
+                    start.redF() + (end.redF() - start.redF()) * t,
+                    start.greenF() + (end.greenF() - start.greenF()) * t,
+                    start.blueF() + (end.blueF() - start.blueF()) * t,
+                    start.alphaF() + (end.alphaF() - start.alphaF()) * t,
+                )
+        overlay_actions.COLORS = colors
+        if t >= 1.0:
+            self._theme_fade = None
+            return False
+        return True
+
+    def _apply_animation_timings(self, timings):
+        """Use the daemon's durations; missing keys keep the current ones."""
+        def duration(key, current):
+            value = timings.get(key, current)
+            return value if isinstance(value, int) and value >= 0 else current
+
+        self.appear_ms = duration("appear_ms", self.appear_ms)
+        self.dismiss_ms = duration("dismiss_ms", self.dismiss_ms)
+        self.highlight_in_ms = duration("highlight_in_ms", self.highlight_in_ms)
+        self.highlight_out_ms = duration("highlight_out_ms", self.highlight_out_ms)
+        self.anim.setDuration(self.appear_ms)
+        self.dismiss_anim.setDuration(self.dismiss_ms)
+
+    def _anim_step(self, duration_ms):
+        """Progress per 16ms animation tick for a duration (0 = at once)."""
+        return 1.0 if duration_ms <= 0 else min(1.0, 16.0 / duration_ms)
+
     @pyqtSlot(str)
     def _on_language_changed(self, lang):
         """Reload translations immediately when settings changes language."""
@@ -606,6 +1004,10 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         ):
             print("OVERLAY: duplicate MenuRequested while fresh - ignored")
             return
+        # A new menu replaces one still fading out
+        if self._dismissing:
+            self.dismiss_anim.stop()
+            self._hide_window()
 
         # Reload translations for language changes
         from i18n import setup_i18n
@@ -616,11 +1018,26 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
 
         # Reload actions, theme, and translations from config each time menu is shown
         # This ensures changes from settings are picked up immediately
-        overlay_actions.ACTIONS = overlay_actions.load_actions_from_config()
-        overlay_actions.COLORS = overlay_actions.load_theme()
+        overlay_actions.ACTIONS = self._menu_actions()
+        # The theme files are only re-read when the daemon's theme hash moved
+        if self.theme_hash is None or self.theme_hash != self._loaded_theme_hash:
+            overlay_actions.COLORS = overlay_actions.load_theme(self.system_accent)
+            self._loaded_theme_hash = self.theme_hash
         overlay_actions.load_radial_image()
         overlay_actions.MINIMAL_MODE = overlay_actions.load_minimal_mode()
 
+        # An ad-hoc menu from another tool replaces the profile slices for
+        # this open only; no button is held, so it opens click-to-select
+        adhoc = self.adhoc_slices
+        self.adhoc_slices = None
+        if adhoc:
+            overlay_actions.ACTIONS = overlay_actions.actions_from_daemon(adhoc, "adhoc")
+            self.slice_count = SLICE_COUNT
+        # One entry per slot of the ring; slots past the configured ones stay empty
+        overlay_actions.ACTIONS = overlay_actions.fit_actions(
+            overlay_actions.ACTIONS, self.slice_count
+        )
+
         # If already in toggle mode and menu is visible, this is a second tap to close
         if self.toggle_mode and self.isVisible():
             print("OVERLAY: Second tap detected - closing menu")
@@ -776,7 +1193,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         _log(f"MenuRequested final pos: ({x}, {y})")
 
         # Size the ring for the monitor it opens on
-        self._apply_ring_scale(mon)
+        self._apply_ring_scale(mon, kde_mon)
 
         # Decide where the window goes. On Hyprland, hyprctl cursorpos is in
         # compositor-logical pixels while QWidget.move() is in Qt's own space;
@@ -786,7 +1203,10 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         # menu_center_x/y stay logical because the hover poll compares them
         # against get_cursor_pos() (also logical). Other compositors: logical
         # space already equals move() space, so clamp directly there.
+        # The logical centre goes back to the daemon (_report_placement) so
+        # hold-mode CursorMoved offsets are measured from the visible ring.
         half = self.win_px // 2
+        press_x, press_y = x, y
         if IS_HYPRLAND and mon:
             from PyQt6.QtWidgets import QApplication
             app = QApplication.instance()
@@ -803,6 +1223,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                 x, y, mon, get_all_monitors_logical(), qt_screens, self.win_px
             )
             self.menu_center_x, self.menu_center_y = placement["logical_center"]
+            logical_center = placement["logical_center"]
             move_x, move_y = placement["qt_origin"]
             _log(
                 f"Hyprland placement: logical ({x},{y}) -> Qt {placement['qt_center']} "
@@ -833,6 +1254,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                 x, y, kde_mon, kde_mons, qt_screens, self.win_px
             )
             self.menu_center_x, self.menu_center_y = placement["qt_center"]
+            logical_center = placement["logical_center"]
             move_x, move_y = placement["qt_origin"]
             _log(
                 f"KDE placement: logical ({x},{y}) -> Qt {placement['qt_center']} "
@@ -846,16 +1268,20 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                 y = max(mon["y"] + half, min(y, mon["y"] + mon["height"] - half))
             self.menu_center_x = x
             self.menu_center_y = y
+            logical_center = (x, y)
             move_x, move_y = x - half, y - half
+        self._report_placement(press_x, press_y, *logical_center)
 
-        self.toggle_mode = False  # Reset toggle mode on new show
+        self.toggle_mode = bool(adhoc)  # Reset toggle mode on new show
+        if adhoc:
+            self.cursor_timer.start()
         self.show_time = time.time()  # Track when menu was shown
 
         # Reset submenu and animation state
         self.submenu_active = False
         self.submenu_slice = -1
         self.highlighted_subitem = -1
-        self.slice_highlights = [0.0] * 8
+        self.slice_highlights = [0.0] * self.slice_count
         self.flash_slice = -1
         self.flash_progress = 0.0
         self.bloom_progress = 0.0
@@ -876,6 +1302,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         # move_x/move_y were computed above (Qt space on Hyprland, logical space
         # otherwise).
         self.highlighted_slice = -1
+        self.hover_help_slot = -1
         self.setWindowOpacity(0.0)
         self.move(move_x, move_y)
 
@@ -955,13 +1382,69 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                 f"[HAPTIC] ERROR: daemon_iface is INVALID - cannot send haptic signal"
             )
 
-    def _apply_ring_scale(self, mon):
+    def _report_menu_closed(self, slice_index, input_method):
+        """Tell the daemon how the menu closed (local stats; -1 = cancelled)."""
+        if self.daemon_iface.isValid():
+            self.daemon_iface.call("ReportMenuClosed", slice_index, input_method)
+
+    def _report_action_result(self, slot, error):
+        """Tell the daemon whether the picked action started (its menu
+        session log; a failure is logged with the session) and, for a
+        spawned command, how long it took to start (its latency stats)."""
+        if self.daemon_iface.isValid():
+            kind = "command" if self.action_spawn_us else ""
+            elapsed = QDBusArgument(self.action_spawn_us, QMetaType.Type.ULongLong.value)
+            self.daemon_iface.call("ReportActionResult", int(slot), error or "", kind, elapsed)
+
+    def _notify_highlight(self, slot):
+        """Report a highlight change: restarts the daemon's hover-help and
+        dwell countdowns and resets its auto-dismiss countdown."""
+        import time
+        self.hover_help_slot = -1
+        self.dwell_started = time.monotonic() if self._can_dwell(slot) else None
+        if self.dwell_started is not None and not self._anim_timer.isActive():
+            self._anim_timer.start()
+        if not self.daemon_iface.isValid():
+            return
+        if slot >= 0:
+            # NotifySliceHover takes a byte; a bare int would go out as int32
+            index = QDBusArgument(slot, QMetaType.Type.UChar.value)
+            self.daemon_iface.call("NotifySliceHover", index)
+        else:
+            self.daemon_iface.call("NotifyMenuActivity", "pointer")
+
+    def _report_placement(self, press_x, press_y, center_x, center_y):
+        """Tell the daemon where the ring was drawn for this press (logical
+        pixels), so CursorMoved offsets arrive relative to the visible centre
+        when the menu was clamped off a screen edge."""
+        if self.daemon_iface.isValid():
+            self.daemon_iface.call(
+                "ReportMenuPlacement",
+                int(press_x), int(press_y), int(center_x), int(center_y),
+            )
+
+    def _notify_menu_activity(self, input_method):
+        """Reset the daemon's auto-dismiss countdown (highlight change or navigation)."""
+        if self.daemon_iface.isValid():
+            self.daemon_iface.call("NotifyMenuActivity", input_method)
+
+    def _apply_ring_scale(self, mon, logical_mon=None):
         """Scale the ring window to the monitor it is shown on.
 
         Drawing stays in logical base coordinates (paintEvent applies
         QPainter.scale); hit-testing divides physical offsets by the factor.
+        A diameter from the daemon (menu.size) wins over the height-based
+        scale; logical_mon is the same monitor in compositor-logical pixels
+        when that space differs from Qt's (KDE Wayland).
         """
-        scale = compute_ring_scale(mon.get("height") if mon else None)
+        if self.menu_diameter:
+            scale = ring_scale_for_diameter(
+                self.menu_diameter,
+                mon.get("height") if mon and logical_mon else None,
+                logical_mon.get("height") if logical_mon else None,
+            )
+        else:
+            scale = compute_ring_scale(mon.get("height") if mon else None)
         if abs(scale - self.ring_scale) < 0.01:
             return
         self.ring_scale = scale
@@ -1003,14 +1486,16 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
     def _tick_animations(self):
         """Update animation state for smooth hover transitions."""
         dirty = False
-        for i in range(8):
+        for i in range(len(self.slice_highlights)):
             target = 1.0 if i == self.highlighted_slice else 0.0
             current = self.slice_highlights[i]
             if current < target:
-                self.slice_highlights[i] = min(1.0, current + 0.15)  # ~112ms in
+                step = self._anim_step(self.highlight_in_ms)
+                self.slice_highlights[i] = min(1.0, current + step)
                 dirty = True
             elif current > target:
-                self.slice_highlights[i] = max(0.0, current - 0.20)  # ~80ms out
+                step = self._anim_step(self.highlight_out_ms)
+                self.slice_highlights[i] = max(0.0, current - step)
                 dirty = True
 
         # Submenu pop-out animation
@@ -1025,14 +1510,25 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             if self.flash_progress <= 0:
                 self.flash_slice = -1
 
-        # Menu open bloom (0 -> 1 over ~220ms)
+        # Menu open bloom (0 -> 1 over ~220ms) and center zone pulse (~350ms);
+        # skipped when the menu appears without animation
+        still = self.appear_ms <= 0
         if self.bloom_progress < 1.0:
-            self.bloom_progress = min(1.0, self.bloom_progress + 0.075)
+            self.bloom_progress = 1.0 if still else min(1.0, self.bloom_progress + 0.075)
             dirty = True
 
-        # Center zone pulse (0 -> 1 over ~350ms)
         if self.center_pulse < 1.0:
-            self.center_pulse = min(1.0, self.center_pulse + 0.05)
+            self.center_pulse = 1.0 if still else min(1.0, self.center_pulse + 0.05)
+            dirty = True
+
+        # Dwell ring fills until the daemon confirms the slice
+        progress = self.dwell_progress()
+        if progress is not None and progress < 1.0:
+            dirty = True
+
+        # Theme crossfade (ThemeChanged); the last step still repaints
+        if self._theme_fade is not None:
+            self._step_theme_fade()
             dirty = True
 
         if dirty:
@@ -1051,7 +1547,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         # Guard: if menu was already closed (e.g., by toggle-close in on_show),
         # don't process another HideMenu - it would use stale show_time and
         # might execute an action on the already-hidden menu.
-        if not self.isVisible():
+        if not self.isVisible() or self._dismissing:
             print("OVERLAY: HideMenu received but menu already hidden - ignoring")
             return
 
@@ -1070,15 +1566,27 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             # Start cursor polling for hover detection in toggle mode
             self.cursor_timer.start()
             # Menu stays open - user will click to select or tap again to close
+        elif self.highlighted_slice in self.wheel_axis_slots:
+            # Held wheel_axis slice: the wheel already ran its steps
+            print("OVERLAY: Released on a wheel slice - closing without action")
+            self._close_menu(execute=False)
         else:
             # Normal hold-and-release - close and execute
             self._close_menu(execute=True)
 
+    @pyqtSlot()
+    def on_dismiss(self):
+        """Handle DismissMenu signal - menu sat idle past the auto-dismiss timeout."""
+        if not self.isVisible():
+            return
+        print("OVERLAY: DismissMenu received - closing without action")
+        self._close_menu(execute=False, input_method="timeout")
+
     @pyqtSlot(int, int)
     def on_cursor_moved(self, dx, dy):
         """Handle cursor movement from daemon (relative to menu center)."""
-        # dx, dy are relative offsets from menu center (button press point),
-        # in physical pixels — convert to the ring's logical space first.
+        # dx, dy are offsets from the rendered menu center (the daemon rebases
+        # them off the press point when the ring was clamped), in physical pixels — convert to the ring's logical space first.
         dx /= self.ring_scale
         dy /= self.ring_scale
         distance = math.hypot(dx, dy)
@@ -1091,7 +1599,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             angle = math.degrees(math.atan2(dx, -dy))
             if angle < 0:
                 angle += 360
-            new_slice = int((angle + 22.5) / 45) % 8
+            new_slice = self._slot_for_angle(angle)
 
         if new_slice != self.highlighted_slice:
             print(
@@ -1100,6 +1608,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             # Trigger haptic for slice change (only when entering a valid slice)
             if new_slice >= 0:
                 self._trigger_haptic("slice_change")
+            self._notify_highlight(new_slice)
             self.highlighted_slice = new_slice
             # Start animation timer for smooth highlight transition
             if not self._anim_timer.isActive():
@@ -1115,9 +1624,10 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             self.menu_center_x = x
             self.menu_center_y = y
             self.move(x - half, y - half)
+            self._report_placement(x, y, x, y)
             print(f"OVERLAY: COSMIC reposition to ({x}, {y})")
 
-    def _close_menu(self, execute=True):
+    def _close_menu(self, execute=True, input_method="pointer"):
         import time
         # Record the close time so on_show can debounce the daemon's duplicate
         # MenuRequested on every close path, not just the toggle-close branch.
@@ -1129,6 +1639,14 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             f"_close_menu: execute={execute}, submenu_active={self.submenu_active}, subitem={self.highlighted_subitem}, slice={self.highlighted_slice}"
         )
 
+        if not execute:
+            selected = -1
+        elif self.submenu_active and self.highlighted_subitem >= 0:
+            selected = self.submenu_slice
+        else:
+            selected = self.highlighted_slice
+        self._report_menu_closed(selected, input_method)
+
         if execute:
             if self.submenu_active and self.highlighted_subitem >= 0:
                 # Execute submenu item
@@ -1151,59 +1669,99 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                     self.flash_slice = self.highlighted_slice
                     self.flash_progress = 1.0
                     self._anim_timer.start()
-                    self.update()
                     self._trigger_haptic("confirm")  # Haptic for selection confirm
+                    feedback = self.action_feedback
+                    if feedback:
+                        # Run now so a failure to start tints the flash
+                        # before the menu hides
+                        error = self._execute_action(action, self.highlighted_slice)
+                        if action[1] not in overlay_actions.DAEMON_RUN_TYPES:
+                            self._report_action_result(self.highlighted_slice, error)
+                        if error:
+                            print(f"Action failed to start: {error}")
+                            self.flash_color = feedback.get("error_color")
+                        else:
+                            self.flash_color = feedback.get("success_color")
+                        self.update()
+                        QTimer.singleShot(int(feedback.get("hold_ms", 150)), self._finish_hide)
+                        return  # Don't hide yet
+                    self.update()
                     # Delay hide briefly so flash + confirm ripple are visible
-                    QTimer.singleShot(110, lambda: self._finish_close(action))
+                    slot = self.highlighted_slice
+                    QTimer.singleShot(110, lambda: self._finish_close(action, slot))
                     return  # Don't hide yet
 
         # Reset submenu state and hide immediately (no flash)
         self._finish_hide()
 
-    def _finish_close(self, action):
+    def _finish_close(self, action, slot=-1):
         """Complete the close after selection flash - execute action and hide."""
-        self._execute_action(action)
+        error = self._execute_action(action, slot)
+        if action[1] not in overlay_actions.DAEMON_RUN_TYPES:
+            self._report_action_result(slot, error)
         self._finish_hide()
 
     def _finish_hide(self):
-        """Reset state and hide the menu."""
-        # On KDE, make window invisible BEFORE stopping the animation timer.
-        # Otherwise KWin gets one frame to show the cached wallpaper rectangle.
-        if IS_KDE:
-            self.setWindowOpacity(0.0)
-        self._anim_timer.stop()
+        """Reset state and hide the menu, fading out over dismiss_ms."""
         self.submenu_active = False
         self.submenu_slice = -1
         self.highlighted_subitem = -1
         self.flash_slice = -1
         self.flash_progress = 0.0
+        self.flash_color = None
+        self.hover_help_slot = -1
+        self.confirm_pending = None
+        self.dwell_started = None
         self.show_time = None  # Prevent stale duration in on_hide
+        if self._theme_fade is not None:
+            overlay_actions.COLORS = self._theme_fade[1]
+            self._theme_fade = None
+        if self.dismiss_ms > 0 and self.isVisible():
+            self._dismissing = True
+            self.anim.stop()
+            self.dismiss_anim.setStartValue(self.windowOpacity())
+            self.dismiss_anim.start()
+            return
+        self._hide_window()
+
+    def _hide_window(self):
+        """Hide the window at once (also ends a running dismiss fade)."""
+        self._dismissing = False
+        # On KDE, make window invisible BEFORE stopping the animation timer.
+        # Otherwise KWin gets one frame to show the cached wallpaper rectangle.
+        if IS_KDE:
+            self.setWindowOpacity(0.0)
+        self._anim_timer.stop()
         self.hide()
         if IS_KDE:
             self.clearMask()
 
-    def _execute_action(self, action):
+    def _execute_action(self, action, slot=-1):
+        """Run a slice action picked from `slot`; return an error string if
+        it failed to start."""
+        import time
         label, cmd_type, cmd = action[0], action[1], action[2]
         print(f"Executing: {label}")
+        self.action_started = time.monotonic()
+        self.action_spawn_us = 0
 
         try:
             if cmd_type == "exec":
                 try:
                     cmd_args = shlex.split(cmd)
-                    subprocess.Popen(
-                        cmd_args, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL
-                    )
                 except ValueError as e:
                     print(f"Invalid command syntax: {cmd} - {e}")
+                    return f"invalid command syntax: {e}"
+                refused = self._check_command(cmd_args)
+                if refused:
+                    print(f"Command refused: {refused}")
+                    return refused
+                return self._spawn_checked(cmd_args, self._invocation_env(slot))
             elif cmd_type == "url":
                 if cmd.startswith("-"):
                     print(f"Invalid URL (starts with -): {cmd}")
-                else:
-                    subprocess.Popen(
-                        ["xdg-open", cmd],
-                        stdout=subprocess.DEVNULL,
-                        stderr=subprocess.DEVNULL,
-                    )
+                    return "invalid URL"
+                return self._spawn_checked(["xdg-open", cmd])
             elif cmd_type == "emoji":
                 import shutil
                 if shutil.which("plasma-emojier"):
@@ -1214,13 +1772,29 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                     emoji_cmd = ["ibus", "emoji"]
                 else:
                     emoji_cmd = ["xdg-open", "https://emojipedia.org"]
-                subprocess.Popen(
-                    emoji_cmd,
-                    stdout=subprocess.DEVNULL,
-                    stderr=subprocess.DEVNULL,
-                )
+                return self._spawn_checked(emoji_cmd)
             elif cmd_type == "settings":
                 overlay_actions.open_settings()
+            elif cmd_type == "paste_plain":
+                # The daemon reads the clipboard and injects the keys; it
+                # replies at once and pastes after this window has hidden.
+                if not self.daemon_iface.isValid():
+                    print("PastePlain: daemon unavailable")
+                    return "daemon unavailable"
+                reply = self.daemon_iface.call("PastePlain")
+                if reply.type() == reply.MessageType.ErrorMessage:
+                    print(f"PastePlain failed: {reply.errorMessage()}")
+                    return reply.errorMessage()
+            elif cmd_type == "profile_switch":
+                # The daemon resolves next/previous, persists the choice and
+                # confirms with a haptic pulse.
+                if not self.daemon_iface.isValid():
+                    print("SetProfile: daemon unavailable")
+                    return "daemon unavailable"
+                reply = self.daemon_iface.call("SetProfile", cmd or "next")
+                if reply.type() == reply.MessageType.ErrorMessage:
+                    print(f"SetProfile failed: {reply.errorMessage()}")
+                    return reply.errorMessage()
             elif cmd_type == "submenu":
                 self.submenu_active = True
                 self.submenu_slice = self.highlighted_slice
@@ -1230,9 +1804,61 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                 if not self._anim_timer.isActive():
                     self._anim_timer.start()
                 self.update()
-                return  # Don't close menu
+                return None  # Don't close menu
         except Exception as e:
             print(f"Error executing action: {e}")
+            return str(e)
+        return None
+
+    def _check_command(self, args):
+        """Ask the daemon whether its execution policy lets `args` run;
+        return the refusal, or None when it may start."""
+        if not self.daemon_iface.isValid():
+            return "daemon unavailable"
+        argv = QDBusArgument(list(args), QMetaType.Type.QStringList.value)
+        reply = self.daemon_iface.call("CheckCommand", argv)
+        if reply.type() == reply.MessageType.ErrorMessage:
+            return reply.errorMessage()
+        return None
+
+    def _invocation_env(self, slot):
+        """Environment for a command picked from `slot`: ours plus the
+        daemon's JUH_* variables for this menu (GetInvocation), which
+        the commands the daemon runs see too."""
+        env = dict(os.environ)
+        if not self.daemon_iface.isValid():
+            return env
+        reply = self.daemon_iface.call("GetInvocation", int(slot))
+        if reply.type() == reply.MessageType.ErrorMessage:
+            print(f"GetInvocation failed: {reply.errorMessage()}")
+            return env
+        arguments = reply.arguments()
+        variables = arguments[0] if arguments else None
+        if isinstance(variables, dict):
+            env.update({str(k): str(v) for k, v in variables.items()})
+        return env
+
+    def _spawn_checked(self, args, env=None):
+        """Start a process; return an error string if it failed to start.
+
+        A missing binary fails in Popen. A process that exits non-zero within
+        ACTION_CHECK_SECONDS (bad arguments, missing backend) also counts as
+        failed; one still running by then counts as started. The time to
+        the spawn (not the check wait) is kept for `_report_action_result`.
+        """
+        import time
+        try:
+            proc = subprocess.Popen(
+                args, env=env, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL
+            )
+        except OSError as e:
+            return str(e)
+        self.action_spawn_us = max(1, int((time.monotonic() - self.action_started) * 1_000_000))
+        try:
+            code = proc.wait(timeout=ACTION_CHECK_SECONDS)
+        except subprocess.TimeoutExpired:
+            return None
+        return f"{args[0]} exited with status {code}" if code else None
 
     def _execute_subaction(self, subitem):
         """Execute a submenu item action."""
@@ -1244,7 +1870,10 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                 try:
                     cmd_args = shlex.split(cmd)
                     subprocess.Popen(
-                        cmd_args, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL
+                        cmd_args,
+                        env=self._invocation_env(self.submenu_slice),
+                        stdout=subprocess.DEVNULL,
+                        stderr=subprocess.DEVNULL,
                     )
                 except ValueError as e:
                     print(f"Invalid command syntax: {cmd} - {e}")
@@ -1326,7 +1955,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             angle = math.degrees(math.atan2(dx, -dy))
             if angle < 0:
                 angle += 360
-            new_slice = int((angle + 22.5) / 45) % 8
+            new_slice = self._slot_for_angle(angle)
 
         if self.submenu_active:
             subitem = self._get_subitem_at_position(dx, dy)
@@ -1362,6 +1991,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             )
             if new_slice >= 0:
                 self._trigger_haptic("slice_change")
+            self._notify_highlight(new_slice)
             self.highlighted_slice = new_slice
             if not self._anim_timer.isActive():
                 self._anim_timer.start()
@@ -1378,7 +2008,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
         if not submenu:
             return -1
 
-        parent_angle = self.submenu_slice * 45 - 90
+        parent_angle = self._slot_angle(self.submenu_slice) - 90
         SUBMENU_RADIUS = MENU_RADIUS + 45
         SUBITEM_SIZE = 32
 
@@ -1418,7 +2048,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             angle = math.degrees(math.atan2(dx, -dy))
             if angle < 0:
                 angle += 360
-            new_slice = int((angle + 22.5) / 45) % 8
+            new_slice = self._slot_for_angle(angle)
 
         if self.submenu_active:
             subitem = self._get_subitem_at_position(dx, dy)
@@ -1454,6 +2084,7 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             )
             if new_slice >= 0:
                 self._trigger_haptic("slice_change")
+            self._notify_highlight(new_slice)
             self.highlighted_slice = new_slice
             if not self._anim_timer.isActive():
                 self._anim_timer.start()
@@ -1468,6 +2099,13 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
                 print(
                     f"OVERLAY: Left click in toggle mode - slice={self.highlighted_slice}, submenu_active={self.submenu_active}, subitem={self.highlighted_subitem}"
                 )
+                if not self.submenu_active and self.is_cooling(self.highlighted_slice):
+                    print(f"OVERLAY: Slice {self.highlighted_slice} is cooling down - closing without action")
+                    self._trigger_haptic("invalid")
+                    self._close_menu(execute=False)
+                    return
+                if not self.submenu_active and self._confirm_click():
+                    return
                 self._close_menu(execute=True)
             elif event.button() == Qt.MouseButton.RightButton:
                 print("OVERLAY: Right click in toggle mode - closing")
@@ -1476,24 +2114,61 @@ class RadialMenu(RadialMenuPaintingMixin, QWidget):
             # Prevents race: Qt mouse event arrives before D-Bus ShowMenu signal,
             # which would close the menu then immediately reopen it.
 
+    def _confirm_click(self, now=None):
+        """Toggle-mode click on a require_confirmation slice.
+
+        The first click only marks the slice; a second click on it within
+        the window falls through to run it, anywhere else closes the menu.
+        Returns True when the click was consumed.
+        """
+        import time
+        now = time.monotonic() if now is None else now
+        pending = self.confirm_pending
+        if pending:
+            self.confirm_pending = None
+            if pending[0] == self.highlighted_slice and now < pending[1]:
+                return False
+            print("OVERLAY: Click off the pending slice - closing without action")
+            self._close_menu(execute=False)
+            return True
+        if self.highlighted_slice in self.confirm_slots:
+            print(f"OVERLAY: Slice {self.highlighted_slice} needs confirming - click again")
+            self._trigger_haptic("invalid")
+            self._set_confirm_pending(self.highlighted_slice, self.confirm_window_ms)
+            QTimer.singleShot(self.confirm_window_ms, self._confirm_timed_out)
+            return True
+        return False
+
+    def _confirm_timed_out(self):
+        """Toggle-mode confirmation window ran out: close without running."""
+        import time
+        pending = self.confirm_pending
+        if pending and self.isVisible() and time.monotonic() >= pending[1]:
+            print("OVERLAY: Slice not confirmed in time - closing without action")
+            self._close_menu(execute=False, input_method="timeout")
+
     def mouseReleaseEvent(self, event):
         """Handle mouse release - only used in non-toggle mode."""
         pass
 
     def keyPressEvent(self, event):
         if event.key() == Qt.Key.Key_Escape:
-            self._close_menu(execute=False)
+            self._close_menu(execute=False, input_method="keyboard")
+        else:
+            self._notify_menu_activity("keyboard")
+
+    def wheelEvent(self, event):
+        self._notify_menu_activity("wheel")
 
 
 def create_tray_icon(app, radial_menu):
     """Create system tray icon with menu"""
     icon = QIcon.fromTheme("juhradial-mx")
 
-    icon_paths = [
-        os.path.join(os.path.dirname(__file__), "..", "assets", "juhradial-mx.svg"),
-        os.path.join("/usr/share/juhradial/assets", "juhradial-mx.svg"),
-        os.path.join("/usr/share/icons/hicolor/scalable/apps", "juhradial-mx.svg"),
-    ]
+    icon_paths = [os.path.join(d, "juhradial-mx.svg") for d in asset_dirs()]
+    icon_paths.append(
+        os.path.join("/usr/share/icons/hicolor/scalable/apps", "juhradial-mx.svg")
+    )
 
     if icon.isNull():
         for icon_path in icon_paths:
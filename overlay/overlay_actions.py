@@ -10,11 +10,12 @@ SPDX-License-Identifier: GPL-3.0
 import os
 import subprocess
 
-from PyQt6.QtGui import QColor, QPixmap
+from PyQt6.QtGui import QBrush, QColor, QPixmap
 from PyQt6.QtCore import Qt
 from PyQt6.QtSvg import QSvgRenderer
 
 from overlay_constants import MENU_RADIUS
+from data_paths import asset_dirs
 from themes import (
     get_colors,
     load_theme_name,
@@ -39,8 +40,12 @@ def hex_to_qcolor(hex_color: str) -> QColor:
     return QColor(r, g, b)
 
 
-def load_theme() -> dict:
-    """Load theme from config and convert to QColor objects"""
+def load_theme(accent=None) -> dict:
+    """Load theme from config and convert to QColor objects
+
+    accent: the desktop accent the daemon draws the menu in (MenuPayload
+    "accent"), replacing the theme's own.
+    """
     theme_name = load_theme_name()
     hex_colors = get_colors(theme_name)
 
@@ -53,6 +58,10 @@ def load_theme() -> dict:
             # Skip rgba strings, just use the accent color
             continue
 
+    if accent:
+        qcolors["accent"] = hex_to_qcolor(accent)
+        qcolors["lavender"] = qcolors["accent"]
+
     # Ensure 'lavender' exists (used for accent in ACTIONS)
     if "lavender" not in qcolors and "accent" in qcolors:
         qcolors["lavender"] = qcolors["accent"]
@@ -72,10 +81,7 @@ def load_radial_image():
 
     # Search paths: development (../assets/radial-wheels/) and installed
     search_paths = [
-        os.path.join(
-            os.path.dirname(__file__), "..", "assets", "radial-wheels", image_name
-        ),
-        os.path.join("/usr/share/juhradial/assets/radial-wheels", image_name),
+        os.path.join(d, image_name) for d in asset_dirs("radial-wheels")
     ]
 
     for path in search_paths:
@@ -285,6 +291,44 @@ def load_actions_from_config():
     return DEFAULT_ACTIONS
 
 
+def fit_actions(actions, count):
+    """Pad `actions` with empty slots (or cut it) to a `count`-slice ring."""
+    actions = list(actions[:count])
+    while len(actions) < count:
+        actions.append(("", "none", "", "surface2", None, None))
+    return actions
+
+
+# Slice types the daemon runs and reports itself; the overlay only draws them
+DAEMON_RUN_TYPES = ("adhoc", "profile")
+
+
+def actions_from_daemon(slices, kind, count=8):
+    """Build ACTIONS from slices the daemon runs itself: an ad-hoc menu
+    (ShowAdHocMenu, kind "adhoc") or a profile other than default (the
+    payload's "slices", kind "profile").
+
+    The daemon keeps the actions and runs the picked one, so every entry is
+    display-only: type `kind` for filled slots, "none" for empty ones.
+    """
+    actions = []
+    for i in range(count):
+        entry = slices[i] if i < len(slices) else None
+        if not entry:
+            actions.append(("", "none", "", "surface2", None, None))
+            continue
+        icon = entry.get("icon") or ""
+        actions.append((
+            entry.get("short_label") or entry.get("label", ""),
+            kind,
+            "",
+            DEFAULT_ACTIONS[i % len(DEFAULT_ACTIONS)][3],
+            ICON_NAME_MAP.get(icon, icon),
+            None,
+        ))
+    return actions
+
+
 # =============================================================================
 # AI SUBMENU ICONS (SVG)
 # =============================================================================
@@ -295,12 +339,7 @@ OS_ICONS = {}
 
 def _get_assets_dir():
     """Get the assets directory, searching dev and installed paths."""
-    script_dir = os.path.dirname(os.path.abspath(__file__))
-    search_dirs = [
-        os.path.join(script_dir, "..", "assets"),  # dev: overlay/../assets
-        os.path.join(script_dir, "assets"),  # installed: /usr/share/juhradial/assets
-        "/usr/share/juhradial/assets",  # absolute fallback
-    ]
+    search_dirs = asset_dirs()
     return next((d for d in search_dirs if os.path.isdir(d)), search_dirs[0])
 
 
@@ -371,6 +410,79 @@ def load_os_icons():
             print(f"OS icon not found: {path}")
 
 
+# Slice file icons from the MenuPayload `icons` list, keyed by
+# (content hash, raster size); a failed load is cached as None
+SLICE_ICONS = {}
+
+
+def slice_icon_pixmap(hint):
+    """Pixmap for a payload `icons` entry, or None to keep the built-in icon.
+
+    SVGs are rendered once at the daemon's `raster` size for this ring, so
+    they stay sharp; PNG and ICO files load as they are.
+    """
+    raster = hint.get("raster")
+    key = (hint.get("hash"), tuple(raster) if raster else None)
+    if key in SLICE_ICONS:
+        return SLICE_ICONS[key]
+    path = hint.get("path", "")
+    pixmap = None
+    if hint.get("format") == "svg":
+        from PyQt6.QtGui import QPainter, QImage
+        renderer = QSvgRenderer(path)
+        if renderer.isValid() and raster:
+            img = QImage(raster[0], raster[1], QImage.Format.Format_ARGB32_Premultiplied)
+            img.fill(0)
+            p = QPainter(img)
+            renderer.render(p)
+            p.end()
+            pixmap = QPixmap.fromImage(img)
+    else:
+        loaded = QPixmap(path)
+        pixmap = None if loaded.isNull() else loaded
+    if pixmap is None:
+        print(f"Failed to load slice icon: {path}")
+    SLICE_ICONS[key] = pixmap
+    return pixmap
+
+
+# Theme slice textures from the MenuPayload `background_texture`, keyed by
+# content hash; a failed load is cached as None
+TEXTURES = {}
+
+
+def texture_brush(texture):
+    """Tiled brush for a payload `background_texture`, or None for flat slices.
+
+    The daemon has already checked the file (inside the theme directory,
+    at most 1MB, PNG or SVG by content); SVGs render at their own size.
+    """
+    key = texture.get("hash")
+    if key in TEXTURES:
+        return TEXTURES[key]
+    path = texture.get("path", "")
+    pixmap = None
+    if texture.get("format") == "svg":
+        from PyQt6.QtGui import QPainter, QImage
+        renderer = QSvgRenderer(path)
+        size = renderer.defaultSize()
+        if renderer.isValid() and not size.isEmpty():
+            img = QImage(size, QImage.Format.Format_ARGB32_Premultiplied)
+            img.fill(0)
+            p = QPainter(img)
+            renderer.render(p)
+            p.end()
+            pixmap = QPixmap.fromImage(img)
+    else:
+        loaded = QPixmap(path)
+        pixmap = None if loaded.isNull() else loaded
+    if pixmap is None:
+        print(f"Failed to load theme texture: {path}")
+    brush = QBrush(pixmap) if pixmap is not None else None
+    TEXTURES[key] = brush
+    return brush
+
+
 # =============================================================================
 # SETTINGS LAUNCHER
 # =============================================================================
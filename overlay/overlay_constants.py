@@ -12,7 +12,9 @@ import time as _time_mod
 
 __all__ = [
     "MENU_RADIUS", "SHADOW_OFFSET", "CENTER_ZONE_RADIUS", "ICON_ZONE_RADIUS",
-    "SUBMENU_EXTEND", "WINDOW_SIZE", "compute_ring_scale", "map_logical_to_screen",
+    "SUBMENU_EXTEND", "WINDOW_SIZE", "SLICE_COUNT", "mirror_slice",
+    "ACTION_CHECK_SECONDS",
+    "compute_ring_scale", "ring_scale_for_diameter", "map_logical_to_screen",
     "hyprland_menu_center",
     "IS_HYPRLAND", "IS_GNOME", "IS_COSMIC", "IS_KDE", "IS_SWAY", "IS_NIRI", "IS_X11",
     "_HAS_XWAYLAND",
@@ -28,6 +30,29 @@ CENTER_ZONE_RADIUS = 45
 ICON_ZONE_RADIUS = 100
 SUBMENU_EXTEND = 80  # Extra space for submenu items beyond main menu
 WINDOW_SIZE = (MENU_RADIUS + SHADOW_OFFSET + SUBMENU_EXTEND) * 2
+SLICE_COUNT = 8
+
+# How long a just-started action is watched for an immediate failure
+# (non-zero exit) before it counts as started; bounds the release delay.
+ACTION_CHECK_SECONDS = 0.02
+
+
+# Ring sizes a profile may use (MenuPayload slice_count); 12 is experimental
+SUPPORTED_SLICE_COUNTS = (4, 6, 8, 12)
+
+
+def mirror_slice(index, count=SLICE_COUNT):
+    """Reflect a ring position across the vertical axis (N and S stay put).
+
+    Matches juhradial_core::geometry::mirror_slice_in; with input.left_handed
+    profile slot i is drawn and hit-tested at position mirror_slice(i).
+    """
+    return (count - index % count) % count
+
+
+def slice_span(count=SLICE_COUNT):
+    """Angular width of one slice of a `count`-slice ring, in degrees."""
+    return 360.0 / count
 
 # Ring scaling: the geometry above is the LOGICAL base (tuned at 1440p).
 # The window is scaled per-monitor so the ring keeps the same apparent
@@ -46,6 +71,20 @@ def compute_ring_scale(monitor_height):
     return max(RING_SCALE_MIN, min(RING_SCALE_MAX, scale))
 
 
+def ring_scale_for_diameter(diameter, qt_height=None, logical_height=None):
+    """Ring scale factor for a daemon-resolved diameter (MenuPayload
+    menu_diameter, compositor-logical pixels).
+
+    Where Qt's screen space differs from the compositor's logical space
+    (XWayland under mixed or fractional scaling, issue #45), the diameter is
+    converted by the ratio of the monitor's heights in the two spaces.
+    """
+    to_qt = 1.0
+    if qt_height and logical_height:
+        to_qt = qt_height / logical_height
+    return diameter * to_qt / (MENU_RADIUS * 2)
+
+
 def map_logical_to_screen(lx, ly, mon, screen_geo):
     """Map a compositor-logical cursor coordinate into the matching Qt screen's
     coordinate space, by fractional position within the monitor.
@@ -224,7 +263,7 @@ _HAS_XWAYLAND = os.environ.get("DISPLAY") is not None
 # =============================================================================
 # LOGGING
 # =============================================================================
-_LOG_DIR = os.environ.get("XDG_RUNTIME_DIR") or os.environ.get("XDG_CACHE_HOME") or "/tmp"
+_LOG_DIR = os.environ.get("XDG_RUNTIME_DIR") or os.environ.get("XDG_CACHE_HOME") or os.path.expanduser("~/.cache")
 _LOG_PATH = os.path.join(_LOG_DIR, "juhradial-overlay.log")
 
 
@@ -22,6 +22,7 @@ from PyQt6.QtGui import (
     QFont,
     QFontMetrics,
     QPainterPath,
+    QTransform,
 )
 
 import overlay_actions
@@ -30,6 +31,9 @@ from overlay_constants import (
     CENTER_ZONE_RADIUS,
     ICON_ZONE_RADIUS,
     WINDOW_SIZE,
+    SLICE_COUNT,
+    mirror_slice,
+    slice_span,
 )
 from i18n import _
 
@@ -82,7 +86,8 @@ class RadialMenuPaintingMixin:
 
         minimal = overlay_actions.MINIMAL_MODE
 
-        if overlay_actions.RADIAL_IMAGE is not None:
+        # The pre-rendered 3D wheel has eight slices; other rings draw vectors
+        if overlay_actions.RADIAL_IMAGE is not None and self.slice_count == SLICE_COUNT:
             # === 3D Image Mode ===
             if not minimal:
                 # Draw the pre-rendered 3D radial wheel image centered
@@ -91,13 +96,13 @@ class RadialMenuPaintingMixin:
                 p.drawPixmap(int(img_x), int(img_y), overlay_actions.RADIAL_IMAGE)
 
                 # Draw highlight on slices with active animation
-                highlights = getattr(self, 'slice_highlights', [0.0] * 8)
-                for i in range(8):
+                highlights = getattr(self, 'slice_highlights', [0.0] * self.slice_count)
+                for i in range(self.slice_count):
                     if highlights[i] > 0:
                         self._draw_3d_slice_highlight(p, cx, cy, i, highlights[i])
 
             # Draw icons floating on the 3D image - clockwise entrance sweep
-            for i in range(8):
+            for i in range(self.slice_count):
                 ent = self._entrance_t(i)
                 if ent <= 0:
                     continue
@@ -128,7 +133,7 @@ class RadialMenuPaintingMixin:
                 p.drawEllipse(QPointF(cx, cy), MENU_RADIUS, MENU_RADIUS)
 
                 # Draw slices - clockwise entrance sweep during the bloom
-                for i in range(8):
+                for i in range(self.slice_count):
                     ent = self._entrance_t(i)
                     if ent <= 0:
                         continue
@@ -138,7 +143,7 @@ class RadialMenuPaintingMixin:
                     p.restore()
             else:
                 # Minimal mode - draw only floating icons (no slices)
-                for i in range(8):
+                for i in range(self.slice_count):
                     ent = self._entrance_t(i)
                     if ent <= 0:
                         continue
@@ -161,6 +166,13 @@ class RadialMenuPaintingMixin:
                 p.setPen(QPen(ping, 2))
                 p.drawEllipse(QPointF(cx, cy), ping_r, ping_r)
 
+        # Up/down hint on slices the wheel steps while held
+        self._draw_wheel_hints(p, cx, cy)
+        # Accent arc on a slice waiting for its confirming second press
+        self._draw_confirm_prompt(p, cx, cy)
+        # Arc filling along the highlighted slice while it dwells
+        self._draw_dwell_progress(p, cx, cy)
+
         # Draw submenu if active (same for both modes)
         if self.submenu_active and self.submenu_slice >= 0:
             self._draw_submenu(p, cx, cy)
@@ -194,7 +206,7 @@ class RadialMenuPaintingMixin:
         fill_rgba = (*fill_base[:3], int(fill_base[3] * progress))
         border_rgba = (*border_base[:3], int(border_base[3] * progress))
 
-        start_angle = index * 45 - 22.5 - 90
+        start_angle = self._slot_angle(index) - self._slice_span() / 2 - 90
 
         path = QPainterPath()
         inner_start_x = cx + inner_r * math.cos(math.radians(start_angle))
@@ -206,28 +218,40 @@ class RadialMenuPaintingMixin:
         path.lineTo(outer_start_x, outer_start_y)
 
         outer_rect = QRectF(cx - outer_r, cy - outer_r, outer_r * 2, outer_r * 2)
-        path.arcTo(outer_rect, -start_angle, -45)
+        path.arcTo(outer_rect, -start_angle, -self._slice_span())
 
-        end_angle = start_angle + 45
+        end_angle = start_angle + self._slice_span()
         inner_end_x = cx + inner_r * math.cos(math.radians(end_angle))
         inner_end_y = cy + inner_r * math.sin(math.radians(end_angle))
         path.lineTo(inner_end_x, inner_end_y)
 
         inner_rect = QRectF(cx - inner_r, cy - inner_r, inner_r * 2, inner_r * 2)
-        path.arcTo(inner_rect, -end_angle, 45)
+        path.arcTo(inner_rect, -end_angle, self._slice_span())
         path.closeSubpath()
 
         p.setBrush(QBrush(QColor(*fill_rgba)))
         p.setPen(QPen(QColor(*border_rgba), 1.5))
         p.drawPath(path)
+        self._draw_selection_border(p, path, progress)
+
+    def _draw_selection_border(self, p, path, progress):
+        """Outline the selected slice with the high contrast selection border."""
+        hc = getattr(self, "high_contrast", None)
+        if not hc or progress <= 0:
+            return
+        border = QColor(hc.get("selection_border_color", "#ffffff"))
+        border.setAlpha(int(255 * min(progress, 1.0)))
+        p.setBrush(Qt.BrushStyle.NoBrush)
+        p.setPen(QPen(border, float(hc.get("selection_border_width", 3))))
+        p.drawPath(path)
 
     def _draw_flash(self, p, cx, cy, index, progress):
-        """Draw a white flash overlay on a slice for selection feedback."""
+        """Draw a flash overlay on a slice for selection feedback (white or outcome tint)."""
         params = overlay_actions.RADIAL_PARAMS or {}
         outer_r = params.get("ring_outer", MENU_RADIUS - 6)
         inner_r = params.get("ring_inner", CENTER_ZONE_RADIUS + 6)
 
-        start_angle = index * 45 - 22.5 - 90
+        start_angle = self._slot_angle(index) - self._slice_span() / 2 - 90
 
         path = QPainterPath()
         inner_start_x = cx + inner_r * math.cos(math.radians(start_angle))
@@ -239,18 +263,18 @@ class RadialMenuPaintingMixin:
         path.lineTo(outer_start_x, outer_start_y)
 
         outer_rect = QRectF(cx - outer_r, cy - outer_r, outer_r * 2, outer_r * 2)
-        path.arcTo(outer_rect, -start_angle, -45)
+        path.arcTo(outer_rect, -start_angle, -self._slice_span())
 
-        end_angle = start_angle + 45
+        end_angle = start_angle + self._slice_span()
         inner_end_x = cx + inner_r * math.cos(math.radians(end_angle))
         inner_end_y = cy + inner_r * math.sin(math.radians(end_angle))
         path.lineTo(inner_end_x, inner_end_y)
 
         inner_rect = QRectF(cx - inner_r, cy - inner_r, inner_r * 2, inner_r * 2)
-        path.arcTo(inner_rect, -end_angle, 45)
+        path.arcTo(inner_rect, -end_angle, self._slice_span())
         path.closeSubpath()
 
-        p.setBrush(QBrush(QColor(255, 255, 255, int(120 * progress))))
+        p.setBrush(QBrush(self._flash_fill(progress)))
         p.setPen(Qt.PenStyle.NoPen)
         p.drawPath(path)
 
@@ -260,23 +284,35 @@ class RadialMenuPaintingMixin:
         ring_r = outer_r + 18.0 * ripple_t
         ring_alpha = int(150 * progress)
         if ring_alpha > 0:
-            ripple = QColor(overlay_actions.COLORS.get("accent", "#00d4ff"))
+            ripple = QColor(
+                getattr(self, 'flash_color', None)
+                or overlay_actions.COLORS.get("accent", "#00d4ff")
+            )
             ripple.setAlpha(ring_alpha)
             ring_rect = QRectF(cx - ring_r, cy - ring_r, ring_r * 2, ring_r * 2)
             arc = QPainterPath()
             arc.arcMoveTo(ring_rect, -start_angle)
-            arc.arcTo(ring_rect, -start_angle, -45)
+            arc.arcTo(ring_rect, -start_angle, -self._slice_span())
             p.setBrush(Qt.BrushStyle.NoBrush)
             p.setPen(QPen(ripple, 3))
             p.drawPath(arc)
 
+    def _flash_fill(self, progress):
+        """Flash fill: white, or the action outcome tint when one is set."""
+        tint = getattr(self, 'flash_color', None)
+        if tint:
+            color = QColor(tint)
+            color.setAlpha(int(170 * progress))
+            return color
+        return QColor(255, 255, 255, int(120 * progress))
+
     def _draw_minimal_flash(self, p, cx, cy, index, progress):
-        """Draw a circular white flash on an icon for minimal mode selection feedback."""
-        icon_angle = math.radians(index * 45 - 90)
+        """Draw a circular flash on an icon for minimal mode selection feedback."""
+        icon_angle = math.radians(self._slot_angle(index) - 90)
         icon_x = cx + ICON_ZONE_RADIUS * math.cos(icon_angle)
         icon_y = cy + ICON_ZONE_RADIUS * math.sin(icon_angle)
         flash_radius = 30
-        p.setBrush(QBrush(QColor(255, 255, 255, int(120 * progress))))
+        p.setBrush(QBrush(self._flash_fill(progress)))
         p.setPen(Qt.PenStyle.NoPen)
         p.drawEllipse(QPointF(icon_x, icon_y), flash_radius, flash_radius)
 
@@ -284,7 +320,10 @@ class RadialMenuPaintingMixin:
         ripple_t = self._ease_out_cubic(1.0 - progress)
         ring_alpha = int(150 * progress)
         if ring_alpha > 0:
-            ripple = QColor(overlay_actions.COLORS.get("accent", "#00d4ff"))
+            ripple = QColor(
+                getattr(self, 'flash_color', None)
+                or overlay_actions.COLORS.get("accent", "#00d4ff")
+            )
             ripple.setAlpha(ring_alpha)
             p.setBrush(Qt.BrushStyle.NoBrush)
             p.setPen(QPen(ripple, 3))
@@ -361,7 +400,7 @@ class RadialMenuPaintingMixin:
         # Hovered icon pops: scales up slightly and lifts outward along
         # its radial axis - tactile, like the key travel of a good keyboard.
         scale = scale * (1.0 + 0.08 * h)
-        angle_deg = index * 45 - 90
+        angle_deg = self._slot_angle(index) - 90
         icon_angle = math.radians(angle_deg)
         lift_radius = icon_radius + 3.0 * h
         icon_x = cx + lift_radius * math.cos(icon_angle)
@@ -426,7 +465,7 @@ class RadialMenuPaintingMixin:
         p.save()
         p.translate(icon_x, icon_y)
         p.scale(hover_bold, hover_bold)
-        self._draw_icon(p, 0, 0, action[4], icon_size, icon_color)
+        self._draw_slice_icon(p, 0, 0, index, action[4], icon_size, icon_color)
         p.restore()
 
     def _draw_slice(self, p, cx, cy, index):
@@ -434,7 +473,7 @@ class RadialMenuPaintingMixin:
         h = self._ease_out_cubic(h)
         action = overlay_actions.ACTIONS[index]
 
-        start_angle = index * 45 - 22.5 - 90
+        start_angle = self._slot_angle(index) - self._slice_span() / 2 - 90
         outer_r = MENU_RADIUS - 6
         inner_r = CENTER_ZONE_RADIUS + 6
 
@@ -452,22 +491,26 @@ class RadialMenuPaintingMixin:
 
         # Outer arc
         outer_rect = QRectF(cx - outer_r, cy - outer_r, outer_r * 2, outer_r * 2)
-        path.arcTo(outer_rect, -start_angle, -45)
+        path.arcTo(outer_rect, -start_angle, -self._slice_span())
 
         # Line to inner arc end
-        end_angle = start_angle + 45
+        end_angle = start_angle + self._slice_span()
         inner_end_x = cx + inner_r * math.cos(math.radians(end_angle))
         inner_end_y = cy + inner_r * math.sin(math.radians(end_angle))
         path.lineTo(inner_end_x, inner_end_y)
 
         # Inner arc back
         inner_rect = QRectF(cx - inner_r, cy - inner_r, inner_r * 2, inner_r * 2)
-        path.arcTo(inner_rect, -end_angle, 45)
+        path.arcTo(inner_rect, -end_angle, self._slice_span())
 
         path.closeSubpath()
 
-        # Fill slice - base fill always drawn
-        fill = QColor(overlay_actions.COLORS["surface0"])
+        # Fill slice - base fill always drawn, in the daemon's per-slice
+        # color (accessibility.slice_tinting) when it sent one
+        tints = getattr(self, 'slice_colors', None) or []
+        fill = QColor(tints[index][:7]) if index < len(tints) and tints[index] else QColor()
+        if not fill.isValid():
+            fill = QColor(overlay_actions.COLORS["surface0"])
         fill.setAlpha(80)
         p.setBrush(QBrush(fill))
 
@@ -482,15 +525,35 @@ class RadialMenuPaintingMixin:
         p.setPen(QPen(stroke, 1.0 + 0.5 * h))
         p.drawPath(path)
 
+        # Theme texture (payload `background_texture`), anchored to the ring
+        # center so it tiles seamlessly across slices
+        texture = getattr(self, "background_texture", None)
+        brush = overlay_actions.texture_brush(texture) if texture else None
+        if brush is not None:
+            brush.setTransform(QTransform.fromTranslate(cx, cy))
+            p.setBrush(brush)
+            p.setPen(Qt.PenStyle.NoPen)
+            p.drawPath(path)
+
+        # Dim a slice still in its cooldown (payload `cooldown`)
+        cooling = getattr(self, "is_cooling", None)
+        if cooling and cooling(index):
+            dim = QColor(overlay_actions.COLORS["surface0"])
+            dim.setAlpha(170)
+            p.setBrush(QBrush(dim))
+            p.setPen(Qt.PenStyle.NoPen)
+            p.drawPath(path)
+
         # Hover white overlay - fades in with highlight progress
         if h > 0:
             hover = QColor(255, 255, 255, int(45 * h))
             p.setBrush(QBrush(hover))
             p.setPen(Qt.PenStyle.NoPen)
             p.drawPath(path)
+            self._draw_selection_border(p, path, h)
 
         # Icon position (center of slice) - hovered icon lifts outward
-        icon_angle = math.radians(index * 45 - 90)
+        icon_angle = math.radians(self._slot_angle(index) - 90)
         icon_x = cx + (ICON_ZONE_RADIUS + 3.0 * h) * math.cos(icon_angle)
         icon_y = cy + (ICON_ZONE_RADIUS + 3.0 * h) * math.sin(icon_angle)
 
@@ -523,7 +586,60 @@ class RadialMenuPaintingMixin:
             int(ct1.green() + (ct2.green() - ct1.green()) * h),
             int(ct1.blue() + (ct2.blue() - ct1.blue()) * h),
         )
-        self._draw_icon(p, icon_x, icon_y, action[4], icon_radius * 0.65, icon_color)
+        self._draw_slice_icon(p, icon_x, icon_y, index, action[4], icon_radius * 0.65, icon_color)
+
+    def _draw_wheel_hints(self, p, cx, cy):
+        """Mark wheel_axis slots (payload) with a small up/down arrow outside
+        the icon, brighter on the highlighted slice."""
+        slots = getattr(self, "wheel_axis_slots", None) or []
+        if not slots:
+            return
+        p.setFont(QFont("Sans", 9))
+        for slot in slots:
+            if not 0 <= slot < 8:
+                continue
+            angle = math.radians(self._slot_angle(slot) - 90)
+            x = cx + (ICON_ZONE_RADIUS + 36) * math.cos(angle)
+            y = cy + (ICON_ZONE_RADIUS + 36) * math.sin(angle)
+            key = "text" if slot == self.highlighted_slice else "subtext1"
+            p.setPen(QPen(overlay_actions.COLORS[key]))
+            p.drawText(QRectF(x - 8, y - 8, 16, 16), Qt.AlignmentFlag.AlignCenter, "\u2195")
+
+    def _draw_confirm_prompt(self, p, cx, cy):
+        """Outline the require_confirmation slice waiting for a second press
+        with an accent arc just outside the ring."""
+        pending = getattr(self, "confirm_pending", None)
+        if not pending:
+            return
+        params = overlay_actions.RADIAL_PARAMS or {}
+        ring_r = params.get("ring_outer", MENU_RADIUS - 6) + 6
+        start_angle = self._slot_angle(pending[0]) - self._slice_span() / 2 - 90
+        ring_rect = QRectF(cx - ring_r, cy - ring_r, ring_r * 2, ring_r * 2)
+        arc = QPainterPath()
+        arc.arcMoveTo(ring_rect, -start_angle)
+        arc.arcTo(ring_rect, -start_angle, -self._slice_span())
+        p.setBrush(Qt.BrushStyle.NoBrush)
+        p.setPen(QPen(QColor(overlay_actions.COLORS.get("accent", "#00d4ff")), 3))
+        p.drawPath(arc)
+
+    def _draw_dwell_progress(self, p, cx, cy):
+        """Fill an accent arc along the highlighted slice, just outside the
+        ring, as its dwell runs towards hover-to-select."""
+        progress = self.dwell_progress() if hasattr(self, "dwell_progress") else None
+        if progress is None or progress <= 0 or self.highlighted_slice < 0:
+            return
+        params = overlay_actions.RADIAL_PARAMS or {}
+        ring_r = params.get("ring_outer", MENU_RADIUS - 6) + 6
+        start_angle = self._slot_angle(self.highlighted_slice) - self._slice_span() / 2 - 90
+        ring_rect = QRectF(cx - ring_r, cy - ring_r, ring_r * 2, ring_r * 2)
+        arc = QPainterPath()
+        arc.arcMoveTo(ring_rect, -start_angle)
+        arc.arcTo(ring_rect, -start_angle, -self._slice_span() * progress)
+        color = QColor(overlay_actions.COLORS.get("accent", "#00d4ff"))
+        color.setAlpha(200)
+        p.setBrush(Qt.BrushStyle.NoBrush)
+        p.setPen(QPen(color, 3))
+        p.drawPath(arc)
 
     def _draw_minimal_icon(self, p, cx, cy, index):
         """Draw a floating icon without slice background (vector minimal mode)."""
@@ -532,7 +648,7 @@ class RadialMenuPaintingMixin:
         action = overlay_actions.ACTIONS[index]
 
         # Icon position - hovered icon lifts outward and pops slightly
-        icon_angle = math.radians(index * 45 - 90)
+        icon_angle = math.radians(self._slot_angle(index) - 90)
         icon_x = cx + (ICON_ZONE_RADIUS + 3.0 * h) * math.cos(icon_angle)
         icon_y = cy + (ICON_ZONE_RADIUS + 3.0 * h) * math.sin(icon_angle)
 
@@ -566,7 +682,28 @@ class RadialMenuPaintingMixin:
             int(ct1.green() + (ct2.green() - ct1.green()) * h),
             int(ct1.blue() + (ct2.blue() - ct1.blue()) * h),
         )
-        self._draw_icon(p, icon_x, icon_y, action[4], icon_radius * 0.65, icon_color)
+        self._draw_slice_icon(p, icon_x, icon_y, index, action[4], icon_radius * 0.65, icon_color)
+
+    def _draw_slice_icon(self, p, cx, cy, index, icon_type, size, color):
+        """Draw a slot's file icon from the payload, else its built-in icon.
+
+        The icon fills a box of 1.8 * size. An SVG was rasterized for that
+        box; a PNG or ICO smaller than it is centered at its own size
+        instead of being blown up.
+        """
+        hint = self._slice_icon(index)
+        pixmap = overlay_actions.slice_icon_pixmap(hint) if hint else None
+        if pixmap is None:
+            self._draw_icon(p, cx, cy, icon_type, size, color)
+            return
+        box = size * 1.8
+        w, h = pixmap.width(), pixmap.height()
+        fit = box / max(w, h)
+        if not hint.get("raster"):
+            device = abs(p.worldTransform().m11()) * p.device().devicePixelRatioF()
+            fit = min(fit, 1.0 / device) if device > 0 else fit
+        dw, dh = w * fit, h * fit
+        p.drawPixmap(QRectF(cx - dw / 2, cy - dh / 2, dw, dh), pixmap, QRectF(0, 0, w, h))
 
     def _draw_icon(self, p, cx, cy, icon_type, size, color):
         # Thicker strokes for better visibility
@@ -1053,6 +1190,20 @@ class RadialMenuPaintingMixin:
             return 1.0
         return 1.0 - 2.0 ** (-10.0 * t)
 
+    def _slot_angle(self, index):
+        """Compass angle (degrees, N = 0) where profile slot `index` is drawn.
+
+        With input.left_handed the ring is mirrored across the vertical axis.
+        """
+        count = self.slice_count
+        if getattr(self, 'left_handed', False):
+            index = mirror_slice(index, count)
+        return index * slice_span(count)
+
+    def _slice_span(self):
+        """Angular width of one slice of the open ring, in degrees."""
+        return slice_span(self.slice_count)
+
     def _entrance_t(self, index):
         """Per-slice entrance progress during the open bloom.
 
@@ -1074,7 +1225,7 @@ class RadialMenuPaintingMixin:
         progress = getattr(self, 'submenu_progress', 1.0)
 
         # Calculate parent slice angle
-        parent_angle = self.submenu_slice * 45 - 90
+        parent_angle = self._slot_angle(self.submenu_slice) - 90
 
         # Submenu items positioned in an arc beyond the main menu
         SUBMENU_RADIUS = MENU_RADIUS + 45
@@ -1204,20 +1355,31 @@ class RadialMenuPaintingMixin:
             p.setPen(QPen(border, 2))
             p.drawEllipse(QPointF(cx, cy), center_radius, center_radius)
             text_color = QColor(overlay_actions.COLORS["subtext1"])
+        hc = getattr(self, "high_contrast", None)
+        if hc:
+            text_color = QColor(hc.get("text_color", "#ffffff"))
 
         # Label text - show submenu item name if hovering one
         if self.submenu_active and self.highlighted_subitem >= 0:
             submenu = overlay_actions.ACTIONS[self.submenu_slice][5]
             text = submenu[self.highlighted_subitem][0] if submenu else "AI"
         elif self.highlighted_slice >= 0:
-            text = overlay_actions.ACTIONS[self.highlighted_slice][0]
+            text = self._slice_label(self.highlighted_slice)
         else:
             text = _("Drag")
+        if getattr(self, "confirm_pending", None):
+            text = _("Press again to confirm")
+        # Long hover: the slice's description replaces its label, shrunk
+        # and elided to fit the hub like any long label
+        help_text = None
+        if not self.submenu_active and self.highlighted_slice >= 0:
+            if getattr(self, "hover_help_slot", -1) == self.highlighted_slice:
+                help_text = self._hover_description(self.highlighted_slice)
         base_font_size = int(params.get("center_font_size", 11))
         min_font_size = int(params.get("center_min_font_size", 7))
         font_bold = bool(params.get("center_font_bold", False))
 
-        text = self._wrap_center_text(text)
+        text = help_text or self._wrap_center_text(text)
 
         text_width = center_radius * 1.7
         text_height = center_radius * 1.2
@@ -1256,10 +1418,31 @@ class RadialMenuPaintingMixin:
         else:
             p.drawText(text_rect, text_flags, text)
 
+        self._draw_status_badge(p, cx, cy, center_radius)
+
         # Restore center pulse transform
         if pulse < 1.0:
             p.restore()
 
+    def _draw_status_badge(self, p, cx, cy, center_radius):
+        """Battery percentage under the hub label, colored by the daemon."""
+        status = getattr(self, "device_status", None)
+        if not status:
+            return
+        text = f"{status.get('battery_percent', 0)}%"
+        if status.get("charging") and not status.get("charge_complete"):
+            text = "\u26a1" + text
+        font = QFont("Sans", 7)
+        p.setFont(font)
+        p.setPen(QPen(QColor(status.get("battery_color", "#a6e3a1"))))
+        badge_rect = QRectF(
+            cx - center_radius * 0.6,
+            cy + center_radius * 0.45,
+            center_radius * 1.2,
+            center_radius * 0.35,
+        )
+        p.drawText(badge_rect, Qt.AlignmentFlag.AlignCenter, text)
+
     def _wrap_center_text(self, text):
         if not text or "\n" in text:
             return text
@@ -40,6 +40,10 @@ class ConfigManager:
                 "slice_change": "subtle_collision",
                 "confirm": "sharp_state_change",
                 "invalid": "angry_alert",
+                "menu_appear_enabled": True,
+                "slice_change_enabled": True,
+                "confirm_enabled": True,
+                "invalid_enabled": True,
             },
             "debounce_ms": 20,
             "slice_debounce_ms": 20,
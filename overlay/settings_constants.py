@@ -160,6 +160,14 @@ _BASE_RADIAL_ACTIONS = [
     ("ai", "AI Assistant", "dialog-information-symbolic", "submenu", "", "teal"),
     ("copy", "Copy", "edit-copy-symbolic", "shortcut", "ctrl+c", "blue"),
     ("paste", "Paste", "edit-paste-symbolic", "shortcut", "ctrl+v", "blue"),
+    (
+        "paste_plain",
+        "Paste as Plain Text",
+        "edit-paste-symbolic",
+        "paste_plain",
+        "",
+        "blue",
+    ),
     ("undo", "Undo", "edit-undo-symbolic", "shortcut", "ctrl+z", "blue"),
     ("redo", "Redo", "edit-redo-symbolic", "shortcut", "ctrl+shift+z", "blue"),
     ("cut", "Cut", "edit-cut-symbolic", "shortcut", "ctrl+x", "blue"),
@@ -12,6 +12,7 @@ import ctypes
 import ctypes.util
 import gi
 import logging
+import json
 import os
 import sys
 import signal
@@ -302,6 +303,11 @@ class SettingsWindow(SidebarMixin, Adw.ApplicationWindow):
             GLib.source_remove(self._heart_timer)
             self._heart_timer = None
 
+        # Unsubscribe the theme reload signal
+        if getattr(self, "_theme_signal_id", None):
+            self._session_bus.signal_unsubscribe(self._theme_signal_id)
+            self._theme_signal_id = None
+
         # Unsubscribe UPower signals
         if hasattr(self, "_system_bus") and self._system_bus:
             for sub_id in getattr(self, "_upower_signal_ids", []):
@@ -332,10 +338,49 @@ class SettingsWindow(SidebarMixin, Adw.ApplicationWindow):
                 "org.kde.juhradialmx.Daemon",
                 None,
             )
+            # Rejected theme edits: the daemon keeps the old version and
+            # tells us why; we show it (it falls back to notify-send when
+            # this app is not running)
+            self._session_bus = bus
+            self._theme_signal_id = bus.signal_subscribe(
+                "org.kde.juhradialmx",
+                "org.kde.juhradialmx.Daemon",
+                "ThemeReloadFailed",
+                "/org/kde/juhradialmx/Daemon",
+                None,
+                Gio.DBusSignalFlags.NONE,
+                self._on_theme_reload_failed,
+                None,
+            )
         except Exception as e:
             logger.error("Failed to connect to D-Bus: %s", e)
             self.dbus_proxy = None
 
+    def _on_theme_reload_failed(
+        self, connection, sender, path, interface, signal, params, user_data
+    ):
+        """Notify the theme author that their last save was rejected"""
+        try:
+            failure = json.loads(params.unpack()[0])
+        except (ValueError, IndexError, TypeError) as e:
+            logger.debug("unreadable ThemeReloadFailed: %s", e)
+            return
+        errors = failure.get("errors") or []
+        body = "%s\n%s" % (
+            _("Theme '%s' was not reloaded; the previous version stays active.")
+            % failure.get("theme", ""),
+            "\n".join(errors[:5]),
+        )
+        try:
+            app = self.get_application() or Gio.Application.get_default()
+            if app is not None:
+                notif = Gio.Notification.new(_("JuhRadial MX"))
+                notif.set_body(body)
+                notif.set_icon(Gio.ThemedIcon.new("dialog-warning-symbolic"))
+                app.send_notification("juhradial-theme-reload", notif)
+        except Exception as e:
+            logger.debug("theme reload notify failed: %s", e)
+
     def _setup_upower_signals(self):
         """Setup UPower D-Bus signals for instant battery charging updates"""
         self._system_bus = None
@@ -419,12 +464,15 @@ class SettingsWindow(SidebarMixin, Adw.ApplicationWindow):
         """Fire one desktop notification when the mouse battery gets low.
 
         Resets once charging or comfortably above the threshold, so a single
-        low episode notifies exactly once (no spam from the 2s poll)."""
+        low episode notifies exactly once (no spam from the 2s poll). Held
+        back while the daemon reports quiet hours, then sent once they end."""
         LOW = 15
         if is_charging or percentage > LOW + 5:
             self._low_batt_notified = False
             return
         if percentage <= LOW and not self._low_batt_notified:
+            if self._daemon_quiet():
+                return
             self._low_batt_notified = True
             try:
                 app = self.get_application() or Gio.Application.get_default()
@@ -437,6 +485,20 @@ class SettingsWindow(SidebarMixin, Adw.ApplicationWindow):
             except Exception as e:
                 logger.debug("low-battery notify failed: %s", e)
 
+    def _daemon_quiet(self):
+        """Whether the daemon's quiet hours are on (Status() JSON)."""
+        if self.dbus_proxy is None:
+            return False
+        try:
+            result = self.dbus_proxy.call_sync(
+                "Status", None, Gio.DBusCallFlags.NONE, 1000, None
+            )
+            status = json.loads(result.unpack()[0])
+            return bool((status.get("quiet_hours") or {}).get("active"))
+        except Exception as e:
+            logger.debug("quiet hours check failed: %s", e)
+            return False
+
     def _update_battery(self):
         """Fetch battery status from daemon via D-Bus"""
         if not self._battery_available:
@@ -190,6 +190,8 @@ class SliceConfigDialog(Adw.Window):
             ("url", _("Open URL"), _("Open a web address")),
             ("settings", _("Open Settings"), _("Open JuhRadial settings")),
             ("emoji", _("Emoji Picker"), _("Show emoji picker")),
+            ("paste_plain", _("Paste as Plain Text"), _("Paste the clipboard without formatting")),
+            ("profile_switch", _("Switch Profile"), _("Select a profile by name, or next / previous")),
             ("submenu", _("Submenu"), _("Show a submenu with more options")),
         ]
         # Resolve DE-appropriate commands for preset buttons
@@ -487,13 +489,16 @@ class SliceConfigDialog(Adw.Window):
             else "exec"
         )
 
-        # Command is needed for exec and url types
-        needs_command = type_id in ("exec", "url")
+        # Command is needed for exec and url types, the target for profile_switch
+        needs_command = type_id in ("exec", "url", "profile_switch")
         self.cmd_box.set_visible(needs_command)
 
         if type_id == "url":
             self.cmd_title.set_text(_("URL"))
             self.command_entry.set_placeholder_text(_("e.g., https://claude.ai"))
+        elif type_id == "profile_switch":
+            self.cmd_title.set_text(_("Profile"))
+            self.command_entry.set_placeholder_text(_("next, previous or a profile name"))
         else:
             self.cmd_title.set_text(_("Command"))
             self.command_entry.set_placeholder_text(_("e.g., playerctl play-pause"))
@@ -28,6 +28,30 @@ from settings_widgets import (
 )
 
 
+def battery_unavailable_text(proxy):
+    """Why the battery reads as unavailable, from the daemon's GetBatteryError.
+
+    The daemon classifies the last failed query, so a permission problem and a
+    sleeping mouse get different advice. Falls back to plain "Unavailable"
+    when the daemon is older or reports nothing.
+    """
+    hints = {
+        "permission_denied": _("Unavailable - no access to the device (install the udev rule)"),
+        "device_not_found": _("Unavailable - device not connected"),
+        "timeout": _("Unavailable - mouse is asleep, move it to wake it"),
+        "conflict": _("Unavailable - another program is using the device (see Diagnostics)"),
+        # Daemons before the conflict detector
+        "logid_active": _("Unavailable - logid is using the device"),
+        "protocol": _("Unavailable - device did not answer correctly"),
+    }
+    try:
+        res = proxy.call_sync("GetBatteryError", None, Gio.DBusCallFlags.NONE, 500, None)
+        kind, _message = res.unpack()
+    except GLib.Error:
+        return _("Unavailable")
+    return hints.get(kind, _("Unavailable"))
+
+
 class DevicesPage(Gtk.ScrolledWindow):
     """Device information and management page"""
 
@@ -294,7 +318,7 @@ class DevicesPage(Gtk.ScrolledWindow):
                             status = _("Charging") if charging else _("Discharging")
                             battery_info = f"{percentage}% ({status})"
                         else:
-                            battery_info = _("Unavailable")
+                            battery_info = battery_unavailable_text(proxy)
                     else:
                         battery_info = _("Unavailable")
                 except GLib.Error as e:
@@ -245,6 +245,14 @@ class HapticsPage(Gtk.ScrolledWindow):
                 current, lambda pattern, k=key: config.set("haptics", "per_event", k, pattern)
             )
             self.event_dropdowns[key] = dropdown
+            enabled = config.get("haptics", "per_event", f"{key}_enabled", default=True)
+            dropdown.set_sensitive(enabled)
+            event_switch = Gtk.Switch()
+            event_switch.set_valign(Gtk.Align.CENTER)
+            event_switch.set_margin_end(12)
+            event_switch.set_active(enabled)
+            event_switch.connect("state-set", self._on_event_toggled, key)
+            row.set_control(event_switch)
             row.set_control(dropdown)
             card.append(row)
 
@@ -414,6 +422,13 @@ class HapticsPage(Gtk.ScrolledWindow):
         if hasattr(self, "_anim_id"):
             self._start_anim() if enabled else self._stop_anim()
 
+    def _on_event_toggled(self, switch, state, key):
+        config.set("haptics", "per_event", f"{key}_enabled", state)
+        config.save(show_toast=False)
+        self.event_dropdowns[key].set_sensitive(state)
+        self._reload_daemon_config()
+        return False
+
     def _on_haptics_toggled(self, switch, state):
         config.set("haptics", "enabled", state)
         config.save(show_toast=False)
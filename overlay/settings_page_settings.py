@@ -21,7 +21,7 @@ gi.require_version("Adw", "1")
 from gi.repository import Gtk, Gdk, GdkPixbuf, GLib, Gio, Adw
 
 from i18n import _, SUPPORTED_LANGUAGES
-from settings_config import ConfigManager, config
+from settings_config import ConfigManager, config, _get_daemon_proxy
 from settings_constants import (
     SUPPORTED_DES,
     DE_COMMAND_MAP,
@@ -378,6 +378,15 @@ class SettingsPage(Gtk.ScrolledWindow):
         tray_row.set_control(tray_switch)
         app_card.append(tray_row)
 
+        debug_row = SettingRow(
+            _("Debug Info"),
+            _("Copy config, profiles and device state for a bug report (commands left out)"),
+        )
+        debug_btn = Gtk.Button(label=_("Copy"))
+        debug_btn.connect("clicked", self._on_copy_debug_info)
+        debug_row.set_control(debug_btn)
+        app_card.append(debug_row)
+
         content.append(app_card)
 
         # Device Information used to live here — moved to the Devices tab
@@ -448,6 +457,32 @@ class SettingsPage(Gtk.ScrolledWindow):
         dialog.add_response("ok", _("OK"))
         dialog.present(self.get_root())
 
+    def _on_copy_debug_info(self, button):
+        """Copy the daemon's DumpState() document, redacted, to the clipboard."""
+        proxy = _get_daemon_proxy()
+        heading = _("Debug Info Copied")
+        body = _("Paste it into your bug report. Commands and shortcuts are not included.")
+        try:
+            if proxy is None:
+                raise GLib.Error("session bus unavailable")
+            result = proxy.call_sync(
+                "DumpState",
+                GLib.Variant("(b)", (False,)),
+                Gio.DBusCallFlags.NONE,
+                5000,
+                None,
+            )
+            text = json.dumps(json.loads(result.get_child_value(0).get_string()), indent=2)
+            self.get_clipboard().set(text)
+        except (GLib.Error, ValueError) as e:
+            logger.warning("DumpState failed: %s", e)
+            heading = _("Daemon Not Running")
+            body = _("Run 'juhradiald dump-state' in a terminal to get the same information.")
+
+        dialog = Adw.AlertDialog(heading=heading, body=body)
+        dialog.add_response("ok", _("OK"))
+        dialog.present(self.get_root())
+
     def _on_theme_changed(self, dropdown, _):
         """Handle theme selection change - applies to both overlay and settings"""
         selected = dropdown.get_selected()
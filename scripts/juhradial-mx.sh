@@ -8,12 +8,13 @@
 
 # Locate the project tree (the one holding the overlay) from the launcher dir.
 # Works for a dev checkout (<repo>/scripts/..), an installed flat layout, and
-# the system install locations.
+# the system install locations ($JUHRADIAL_DATA_DIR first, as the daemon does).
 resolve_project_root() {
     local script_dir="$1" candidate
     for candidate in \
         "$script_dir/.." \
         "$script_dir" \
+        ${JUHRADIAL_DATA_DIR:+"$JUHRADIAL_DATA_DIR"} \
         /usr/share/juhradial \
         /opt/juhradial-mx; do
         candidate="$(cd "$candidate" 2>/dev/null && pwd || true)"
@@ -24,6 +24,7 @@ from overlay.overlay_constants import (
     map_logical_to_screen,
     hyprland_menu_center,
     map_and_clamp_menu,
+    ring_scale_for_diameter,
 )
 
 
@@ -253,6 +254,16 @@ def test_clamp_global_affine_when_names_differ():
     assert ox >= 0 and oy >= 0
     assert ox + 400 <= 2600 and oy + 400 <= 900  # qt desktop bbox is 2600x900
 
+def test_ring_scale_for_daemon_diameter():
+    # menu.size resolved by the daemon in logical pixels: 300 is the base ring
+    assert ring_scale_for_diameter(300) == 1.0
+    assert ring_scale_for_diameter(450) == 1.5
+    # Qt space 1.6x smaller than logical (issue #45 layout): the ring shrinks
+    # by the same ratio so it covers the same part of the screen
+    assert abs(ring_scale_for_diameter(480, 900, 1440) - 1.0) < 1e-9
+    # Missing either height: no conversion
+    assert ring_scale_for_diameter(480, 900, None) == 1.6
+
 
 if __name__ == "__main__":
     import traceback
@@ -268,3 +279,4 @@ if __name__ == "__main__":
                 print(f"FAIL {name}")
                 traceback.print_exc()
     sys.exit(1 if failures else 0)
+